@@ -1,5 +1,5 @@
 use actix_http::Method;
-use database_entity::dto::{AFAccessLevel, AFRole};
+use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 use redis::{ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRedisArgs, Value};
 use std::cmp::Ordering;
 
@@ -15,6 +15,7 @@ pub enum ActionVariant<'a> {
   FromRole(&'a AFRole),
   FromAccessLevel(&'a AFAccessLevel),
   FromAction(&'a Action),
+  FromCapability(&'a WorkspaceCapability),
 }
 
 impl<'a> ActionVariant<'a> {
@@ -23,6 +24,7 @@ impl<'a> ActionVariant<'a> {
       ActionVariant::FromRole(role) => role.policy_acts(),
       ActionVariant::FromAccessLevel(level) => level.policy_acts(),
       ActionVariant::FromAction(action) => action.policy_acts(),
+      ActionVariant::FromCapability(capability) => capability.policy_acts(),
     }
   }
 
@@ -31,6 +33,7 @@ impl<'a> ActionVariant<'a> {
       ActionVariant::FromRole(role) => role.to_enforce_act(),
       ActionVariant::FromAccessLevel(level) => level.to_enforce_act(),
       ActionVariant::FromAction(action) => action.to_enforce_act(),
+      ActionVariant::FromCapability(capability) => capability.to_enforce_act(),
     }
   }
 }
@@ -111,6 +114,30 @@ impl Acts for AFRole {
   }
 }
 
+impl Acts for WorkspaceCapability {
+  fn policy_acts(&self) -> Vec<&'static str> {
+    vec![self.to_enforce_act()]
+  }
+
+  fn to_enforce_act(&self) -> &'static str {
+    match self {
+      WorkspaceCapability::Invite => "c:invite",
+      WorkspaceCapability::Publish => "c:publish",
+      WorkspaceCapability::DeleteCollab => "c:delete_collab",
+      WorkspaceCapability::ManageMembers => "c:manage_members",
+    }
+  }
+
+  fn from_enforce_act(act: &str) -> Self {
+    match act {
+      "c:publish" => WorkspaceCapability::Publish,
+      "c:delete_collab" => WorkspaceCapability::DeleteCollab,
+      "c:manage_members" => WorkspaceCapability::ManageMembers,
+      _ => WorkspaceCapability::Invite,
+    }
+  }
+}
+
 /// Represents the actions that can be performed on objects.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Action {
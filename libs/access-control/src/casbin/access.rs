@@ -1,5 +1,8 @@
 use super::adapter::PgAdapter;
+use super::archive::ArchivedWorkspaces;
 use super::enforcer::{AFEnforcer, NoEnforceGroup};
+use super::expiry::PolicyExpirations;
+use super::view_permission::ViewPermissions;
 use crate::act::{Action, ActionVariant, Acts};
 use crate::entity::ObjectType;
 use crate::metrics::{tick_metric, AccessControlMetrics};
@@ -9,13 +12,15 @@ use app_error::AppError;
 use casbin::function_map::OperatorFunction;
 use casbin::rhai::{Dynamic, ImmutableString};
 use casbin::{CoreApi, DefaultModel, Enforcer, MgmtApi};
-use database_entity::dto::{AFAccessLevel, AFRole};
+use chrono::{DateTime, Utc};
+use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 
 use sqlx::PgPool;
 
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::trace;
+use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub enum AccessControlChange {
@@ -41,6 +46,8 @@ pub struct AccessControl {
   #[allow(dead_code)]
   access_control_metrics: Arc<AccessControlMetrics>,
   change_tx: broadcast::Sender<AccessControlChange>,
+  archived_workspaces: ArchivedWorkspaces,
+  view_permissions: ViewPermissions,
 }
 
 impl AccessControl {
@@ -49,13 +56,20 @@ impl AccessControl {
     access_control_metrics: Arc<AccessControlMetrics>,
   ) -> Result<Self, AppError> {
     let model = casbin_model().await?;
-    let adapter = PgAdapter::new(pg_pool.clone(), access_control_metrics.clone());
+    let archived_workspaces = ArchivedWorkspaces::new(pg_pool.clone()).await?;
+    let view_permissions = ViewPermissions::new(pg_pool.clone()).await?;
+    let expirations = PolicyExpirations::default();
+    let adapter = PgAdapter::new(
+      pg_pool.clone(),
+      access_control_metrics.clone(),
+      expirations.clone(),
+    );
     let mut enforcer = casbin::Enforcer::new(model, adapter).await.map_err(|e| {
       AppError::Internal(anyhow!("Failed to create access control enforcer: {}", e))
     })?;
     enforcer.add_function("cmpRoleOrLevel", OperatorFunction::Arg2(cmp_role_or_level));
 
-    let enforcer = Arc::new(AFEnforcer::new(enforcer, NoEnforceGroup).await?);
+    let enforcer = Arc::new(AFEnforcer::new(enforcer, NoEnforceGroup, expirations).await?);
     tick_metric(
       enforcer.metrics_state.clone(),
       access_control_metrics.clone(),
@@ -65,6 +79,8 @@ impl AccessControl {
       enforcer,
       access_control_metrics,
       change_tx,
+      archived_workspaces,
+      view_permissions,
     })
   }
 
@@ -85,6 +101,18 @@ impl AccessControl {
     Ok(())
   }
 
+  pub async fn update_policy_expiry(
+    &self,
+    uid: &i64,
+    obj: ObjectType<'_>,
+    expires_at: Option<DateTime<Utc>>,
+  ) {
+    self
+      .enforcer
+      .update_policy_expiry(uid, &obj, expires_at)
+      .await;
+  }
+
   pub async fn remove_policy(&self, uid: &i64, obj: &ObjectType<'_>) -> Result<(), AppError> {
     let access_control_change = self.enforcer.remove_policy(uid, obj).await?;
     if let Some(change) = access_control_change {
@@ -93,6 +121,43 @@ impl AccessControl {
     Ok(())
   }
 
+  /// Replaces the capabilities of the user on the workspace.
+  pub async fn update_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError> {
+    let access_control_change = self.enforcer.remove_capabilities(uid, workspace_id).await?;
+    if let Some(change) = access_control_change {
+      let _ = self.change_tx.send(change);
+    }
+    for capability in capabilities {
+      self
+        .update_policy(
+          uid,
+          ObjectType::Workspace(workspace_id),
+          ActionVariant::FromCapability(capability),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
+  pub async fn update_workspace_archived(&self, workspace_id: &Uuid, archived: bool) {
+    self.archived_workspaces.set(*workspace_id, archived).await;
+  }
+
+  /// Returns true if the workspace is archived, in which case its collabs can't be written.
+  pub async fn is_workspace_archived(&self, workspace_id: &str) -> bool {
+    self.archived_workspaces.contains(workspace_id).await
+  }
+
+  /// Reloads the permissions of the restricted views, after they or the member groups changed.
+  pub async fn reload_view_permissions(&self) -> Result<(), AppError> {
+    self.view_permissions.reload().await
+  }
+
   pub async fn enforce(
     &self,
     workspace_id: &str,
@@ -100,13 +165,65 @@ impl AccessControl {
     obj: ObjectType<'_>,
     act: ActionVariant<'_>,
   ) -> Result<(), AppError> {
+    let view_id = match obj {
+      ObjectType::Collab(oid) => Some(oid),
+      ObjectType::Workspace(_) => None,
+    };
+    let required_access_level = required_access_level(&act);
     self
       .enforcer
       .enforce_policy(workspace_id, uid, obj, act)
+      .await?;
+    match view_id {
+      Some(view_id) => {
+        self
+          .enforce_view_permission(workspace_id, uid, view_id, required_access_level)
+          .await
+      },
+      None => Ok(()),
+    }
+  }
+
+  /// The permissions of a restricted view apply on top of the policies: the owners of the
+  /// workspace keep their access, the other users need a permission granting the access level.
+  async fn enforce_view_permission(
+    &self,
+    workspace_id: &str,
+    uid: &i64,
+    view_id: &str,
+    required_access_level: AFAccessLevel,
+  ) -> Result<(), AppError> {
+    let granted_access_level = match self.view_permissions.access_level(view_id, *uid).await {
+      None => return Ok(()),
+      Some(granted_access_level) => granted_access_level,
+    };
+    if matches!(granted_access_level, Some(level) if level >= required_access_level) {
+      return Ok(());
+    }
+    self
+      .enforcer
+      .enforce_policy(
+        workspace_id,
+        uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Owner),
+      )
       .await
   }
 }
 
+fn required_access_level(act: &ActionVariant<'_>) -> AFAccessLevel {
+  match act {
+    ActionVariant::FromRole(role) => AFAccessLevel::from(*role),
+    ActionVariant::FromAccessLevel(level) => **level,
+    ActionVariant::FromAction(Action::Read) => AFAccessLevel::ReadOnly,
+    ActionVariant::FromAction(Action::Write) => AFAccessLevel::ReadAndWrite,
+    ActionVariant::FromAction(Action::Delete) => AFAccessLevel::FullAccess,
+    // The capabilities are only enforced on the workspaces
+    ActionVariant::FromCapability(_) => AFAccessLevel::FullAccess,
+  }
+}
+
 ///
 /// ## Policy Definitions:
 /// - p1 = sub=uid, obj=object_id, act=role_id
@@ -118,6 +235,9 @@ impl AccessControl {
 /// - p3 = sub=guid, obj=object_id, act=access_level
 ///   - Defines the access level (`access_level`) a group (`guid`) has for an object (`object_id`).
 ///
+/// - p4 = sub=uid, obj=workspace_id, act=capability
+///   - Grants a capability (`capability`) of a custom role to a user (`uid`) in a workspace.
+///
 /// ## Role Definitions in Database:
 /// Roles and access levels are defined with the following mappings:
 /// - **Role "1" (Owner):** Can `delete`, `write`, and `read`.
@@ -190,7 +310,7 @@ pub async fn casbin_model() -> Result<DefaultModel, AppError> {
 /// it is designed to compare roles or access levels specified in the request and policy.
 /// It supports two prefixes: "r:" for roles and "l:" for access levels. When the prefixes match,
 /// it compares the values to determine if the policy's role or level is greater than or equal to
-/// the request's role or level.
+/// the request's role or level. The capabilities, prefixed with "c:", only match themselves.
 ///
 /// # Arguments
 /// * `r_act` - The role or access level from the request, prefixed with "r:" for roles or "l:" for levels.
@@ -211,6 +331,10 @@ pub fn cmp_role_or_level(r_act: ImmutableString, p_act: ImmutableString) -> Dyna
     return Dynamic::from_bool(p >= r);
   }
 
+  if r_act.starts_with("c:") && p_act.starts_with("c:") {
+    return Dynamic::from_bool(r_act == p_act);
+  }
+
   if r_act.starts_with("l:") && p_act.starts_with("r:") {
     let r = AFAccessLevel::from_enforce_act(r_act.as_str());
     let role = AFRole::from_enforce_act(p_act.as_str());
@@ -218,6 +342,12 @@ pub fn cmp_role_or_level(r_act: ImmutableString, p_act: ImmutableString) -> Dyna
     return Dynamic::from_bool(p >= r);
   }
 
+  // Deleting a collab requires the full access level, which the capability grants on the collabs
+  // of the workspace.
+  if r_act.starts_with("l:") && p_act == WorkspaceCapability::DeleteCollab.to_enforce_act() {
+    return Dynamic::from_bool(true);
+  }
+
   Dynamic::from_bool(false)
 }
 
@@ -277,6 +407,19 @@ pub(crate) async fn load_group_policies(enforcer: &mut Enforcer) -> Result<(), A
     }
   }
 
+  // The owners have every capability. The capabilities that match an action grant it on the
+  // objects of the workspace, e.g. deleting its collabs.
+  for capability in WorkspaceCapability::ALL {
+    grouping_policies.push([AFRole::Owner.to_enforce_act(), capability.to_enforce_act()].to_vec());
+  }
+  grouping_policies.push(
+    [
+      WorkspaceCapability::DeleteCollab.to_enforce_act(),
+      Action::Delete.to_enforce_act(),
+    ]
+    .to_vec(),
+  );
+
   let grouping_policies = grouping_policies
     .into_iter()
     .map(|actions| actions.into_iter().map(|a| a.to_string()).collect())
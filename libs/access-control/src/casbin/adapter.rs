@@ -9,10 +9,13 @@ use casbin::Result;
 
 use database::collab::select_collab_member_access_level;
 use database::pg_row::AFCollabMemberAccessLevelRow;
+use database::pg_row::AFWorkspaceMemberCapabilityRow;
 use database::pg_row::AFWorkspaceMemberPermRow;
 use database::workspace::select_workspace_member_perm_stream;
+use database::workspace_role::select_workspace_member_capability_stream;
 
 use crate::act::Acts;
+use crate::casbin::expiry::PolicyExpirations;
 use futures_util::stream::BoxStream;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -24,25 +27,41 @@ use tokio_stream::StreamExt;
 pub struct PgAdapter {
   pg_pool: PgPool,
   access_control_metrics: Arc<AccessControlMetrics>,
+  expirations: PolicyExpirations,
 }
 
 impl PgAdapter {
-  pub fn new(pg_pool: PgPool, access_control_metrics: Arc<AccessControlMetrics>) -> Self {
+  pub fn new(
+    pg_pool: PgPool,
+    access_control_metrics: Arc<AccessControlMetrics>,
+    expirations: PolicyExpirations,
+  ) -> Self {
     Self {
       pg_pool,
       access_control_metrics,
+      expirations,
     }
   }
 }
 
 async fn load_collab_policies(
   mut stream: BoxStream<'_, sqlx::Result<AFCollabMemberAccessLevelRow>>,
+  expirations: &PolicyExpirations,
 ) -> Result<Vec<Vec<String>>> {
   let mut policies: Vec<Vec<String>> = Vec::new();
 
   while let Some(Ok(member_access_lv)) = stream.next().await {
     let uid = member_access_lv.uid;
     let object_type = ObjectType::Collab(&member_access_lv.oid);
+    if member_access_lv.expires_at.is_some() {
+      expirations
+        .set(
+          uid,
+          object_type.policy_object(),
+          member_access_lv.expires_at,
+        )
+        .await;
+    }
     for act in member_access_lv.access_level.policy_acts() {
       let policy = [
         uid.to_string(),
@@ -98,6 +117,7 @@ async fn load_collab_policies(
 ///   member roles (`Owner`, `Member`, `Guest`) using the `to_action` method.
 async fn load_workspace_policies(
   mut stream: BoxStream<'_, sqlx::Result<AFWorkspaceMemberPermRow>>,
+  expirations: &PolicyExpirations,
 ) -> Result<Vec<Vec<String>>> {
   let mut policies: Vec<Vec<String>> = Vec::new();
 
@@ -105,6 +125,15 @@ async fn load_workspace_policies(
     let uid = member_permission.uid;
     let workspace_id = member_permission.workspace_id.to_string();
     let object_type = ObjectType::Workspace(&workspace_id);
+    if member_permission.expires_at.is_some() {
+      expirations
+        .set(
+          uid,
+          object_type.policy_object(),
+          member_permission.expires_at,
+        )
+        .await;
+    }
     for act in member_permission.role.policy_acts() {
       let policy = vec![
         uid.to_string(),
@@ -118,22 +147,53 @@ async fn load_workspace_policies(
   Ok(policies)
 }
 
+/// Loads the capabilities the custom roles grant to the members of the workspaces.
+async fn load_capability_policies(
+  mut stream: BoxStream<'_, sqlx::Result<AFWorkspaceMemberCapabilityRow>>,
+) -> Result<Vec<Vec<String>>> {
+  let mut policies: Vec<Vec<String>> = Vec::new();
+
+  while let Some(Ok(member_capability)) = stream.next().await {
+    let workspace_id = member_capability.workspace_id.to_string();
+    let object_type = ObjectType::Workspace(&workspace_id);
+    for capability in member_capability.capabilities() {
+      for act in capability.policy_acts() {
+        policies.push(vec![
+          member_capability.uid.to_string(),
+          object_type.policy_object(),
+          act.to_string(),
+        ]);
+      }
+    }
+  }
+
+  Ok(policies)
+}
+
 #[async_trait]
 impl Adapter for PgAdapter {
   async fn load_policy(&mut self, model: &mut dyn Model) -> Result<()> {
     let start = Instant::now();
     let workspace_member_perm_stream = select_workspace_member_perm_stream(&self.pg_pool);
-    let workspace_policies = load_workspace_policies(workspace_member_perm_stream).await?;
+    let workspace_policies =
+      load_workspace_policies(workspace_member_perm_stream, &self.expirations).await?;
 
     // Policy definition `p` of type `p`. See `model.conf`
     model.add_policies("p", "p", workspace_policies);
 
     let collab_member_access_lv_stream = select_collab_member_access_level(&self.pg_pool);
-    let collab_policies = load_collab_policies(collab_member_access_lv_stream).await?;
+    let collab_policies =
+      load_collab_policies(collab_member_access_lv_stream, &self.expirations).await?;
 
     // Policy definition `p` of type `p`. See `model.conf`
     model.add_policies("p", "p", collab_policies);
 
+    let member_capability_stream = select_workspace_member_capability_stream(&self.pg_pool);
+    let capability_policies = load_capability_policies(member_capability_stream).await?;
+
+    // Policy definition `p` of type `p`. See `model.conf`
+    model.add_policies("p", "p", capability_policies);
+
     self
       .access_control_metrics
       .record_load_all_policies_in_ms(start.elapsed().as_millis() as u64);
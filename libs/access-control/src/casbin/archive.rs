@@ -0,0 +1,65 @@
+use app_error::AppError;
+use database::workspace::select_archived_workspace_ids;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::error;
+use uuid::Uuid;
+
+/// How often the archived workspaces are reloaded from the database, to pick up the workspaces
+/// archived through the other instances of the server.
+const ARCHIVED_WORKSPACES_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tracks the archived workspaces, whose collabs can be read but not written.
+#[derive(Clone)]
+pub struct ArchivedWorkspaces {
+  pg_pool: PgPool,
+  inner: Arc<RwLock<HashSet<Uuid>>>,
+}
+
+impl ArchivedWorkspaces {
+  pub async fn new(pg_pool: PgPool) -> Result<Self, AppError> {
+    let archived_workspaces = Self {
+      pg_pool,
+      inner: Default::default(),
+    };
+    archived_workspaces.reload().await?;
+
+    let cloned_archived_workspaces = archived_workspaces.clone();
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(ARCHIVED_WORKSPACES_RELOAD_INTERVAL);
+      interval.tick().await;
+      loop {
+        interval.tick().await;
+        if let Err(err) = cloned_archived_workspaces.reload().await {
+          error!("Failed to reload archived workspaces: {:?}", err);
+        }
+      }
+    });
+    Ok(archived_workspaces)
+  }
+
+  async fn reload(&self) -> Result<(), AppError> {
+    let workspace_ids = select_archived_workspace_ids(&self.pg_pool).await?;
+    *self.inner.write().await = workspace_ids.into_iter().collect();
+    Ok(())
+  }
+
+  pub async fn set(&self, workspace_id: Uuid, archived: bool) {
+    let mut inner = self.inner.write().await;
+    if archived {
+      inner.insert(workspace_id);
+    } else {
+      inner.remove(&workspace_id);
+    }
+  }
+
+  pub async fn contains(&self, workspace_id: &str) -> bool {
+    match Uuid::parse_str(workspace_id) {
+      Ok(workspace_id) => self.inner.read().await.contains(&workspace_id),
+      Err(_) => false,
+    }
+  }
+}
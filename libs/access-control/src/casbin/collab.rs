@@ -1,7 +1,9 @@
 use app_error::AppError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use database_entity::dto::AFAccessLevel;
 use tracing::instrument;
+use uuid::Uuid;
 
 use crate::{
   act::{Action, ActionVariant},
@@ -79,6 +81,20 @@ impl CollabAccessControl for CollabAccessControlImpl {
     Ok(())
   }
 
+  #[instrument(level = "info", skip_all)]
+  async fn update_access_level_expiry(
+    &self,
+    uid: &i64,
+    oid: &str,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_policy_expiry(uid, ObjectType::Collab(oid), expires_at)
+      .await;
+    Ok(())
+  }
+
   #[instrument(level = "info", skip_all)]
   async fn remove_access_level(&self, uid: &i64, oid: &str) -> Result<(), AppError> {
     self
@@ -87,6 +103,10 @@ impl CollabAccessControl for CollabAccessControlImpl {
       .await?;
     Ok(())
   }
+
+  async fn reload_view_permissions(&self) -> Result<(), AppError> {
+    self.access_control.reload_view_permissions().await
+  }
 }
 
 #[derive(Clone)]
@@ -142,6 +162,13 @@ impl RealtimeAccessControl for RealtimeCollabAccessControlImpl {
     uid: &i64,
     oid: &str,
   ) -> Result<bool, AppError> {
+    if self
+      .access_control
+      .is_workspace_archived(workspace_id)
+      .await
+    {
+      return Ok(false);
+    }
     self
       .can_perform_action(workspace_id, uid, oid, Action::Write)
       .await
@@ -157,4 +184,16 @@ impl RealtimeAccessControl for RealtimeCollabAccessControlImpl {
       .can_perform_action(workspace_id, uid, oid, Action::Read)
       .await
   }
+
+  async fn update_workspace_archived(
+    &self,
+    workspace_id: &Uuid,
+    archived: bool,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_workspace_archived(workspace_id, archived)
+      .await;
+    Ok(())
+  }
 }
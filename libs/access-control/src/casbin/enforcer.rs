@@ -1,6 +1,8 @@
 use super::access::{
-  load_group_policies, AccessControlChange, POLICY_FIELD_INDEX_OBJECT, POLICY_FIELD_INDEX_SUBJECT,
+  load_group_policies, AccessControlChange, POLICY_FIELD_INDEX_ACTION, POLICY_FIELD_INDEX_OBJECT,
+  POLICY_FIELD_INDEX_SUBJECT,
 };
+use super::expiry::PolicyExpirations;
 use crate::act::ActionVariant;
 use crate::entity::ObjectType;
 use crate::metrics::MetricsCalState;
@@ -9,6 +11,7 @@ use anyhow::anyhow;
 use app_error::AppError;
 use async_trait::async_trait;
 use casbin::{CoreApi, Enforcer, MgmtApi};
+use chrono::{DateTime, Utc};
 use std::sync::atomic::Ordering;
 use tokio::sync::RwLock;
 use tracing::{event, instrument, trace};
@@ -24,18 +27,24 @@ pub struct AFEnforcer<T> {
   enforcer: RwLock<Enforcer>,
   pub(crate) metrics_state: MetricsCalState,
   enforce_group: T,
+  expirations: PolicyExpirations,
 }
 
 impl<T> AFEnforcer<T>
 where
   T: EnforcerGroup,
 {
-  pub async fn new(mut enforcer: Enforcer, enforce_group: T) -> Result<Self, AppError> {
+  pub async fn new(
+    mut enforcer: Enforcer,
+    enforce_group: T,
+    expirations: PolicyExpirations,
+  ) -> Result<Self, AppError> {
     load_group_policies(&mut enforcer).await?;
     Ok(Self {
       enforcer: RwLock::new(enforcer),
       metrics_state: MetricsCalState::new(),
       enforce_group,
+      expirations,
     })
   }
 
@@ -79,18 +88,93 @@ where
     }
   }
 
+  /// Update the expiry time of the user's policies on the given object.
+  /// Passing `None` makes the policies permanent.
+  pub async fn update_policy_expiry(
+    &self,
+    uid: &i64,
+    obj: &ObjectType<'_>,
+    expires_at: Option<DateTime<Utc>>,
+  ) {
+    self
+      .expirations
+      .set(*uid, obj.policy_object(), expires_at)
+      .await;
+  }
+
   /// Returns policies that match the filter.
   pub async fn remove_policy(
     &self,
     uid: &i64,
     object_type: &ObjectType<'_>,
   ) -> Result<Option<AccessControlChange>, AppError> {
+    self
+      .expirations
+      .remove(*uid, object_type.policy_object())
+      .await;
     let mut enforcer = self.enforcer.write().await;
     self
       .remove_with_enforcer(uid, object_type, &mut enforcer)
       .await
   }
 
+  /// Removes the capabilities of the user on the workspace, keeping the policy of their role.
+  pub async fn remove_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+  ) -> Result<Option<AccessControlChange>, AppError> {
+    let object_type = ObjectType::Workspace(workspace_id);
+    let mut enforcer = self.enforcer.write().await;
+    let capability_policies = policies_for_subject_with_given_object(uid, &object_type, &enforcer)
+      .await
+      .into_iter()
+      .filter(|p| p[POLICY_FIELD_INDEX_ACTION].starts_with("c:"))
+      .collect::<Vec<_>>();
+    if capability_policies.is_empty() {
+      return Ok(None);
+    }
+
+    trace!(
+      "[access control]: remove capabilities:{:?}",
+      capability_policies
+    );
+    enforcer
+      .remove_policies(capability_policies)
+      .await
+      .map_err(|e| AppError::Internal(anyhow!("fail to remove capabilities: {e:?}")))?;
+    Ok(Some(AccessControlChange::RemovePolicy {
+      uid: *uid,
+      oid: workspace_id.to_string(),
+    }))
+  }
+
+  /// Removes the user's time-boxed policies on the given objects if they have expired.
+  async fn remove_expired_policies(
+    &self,
+    uid: &i64,
+    object_types: &[&ObjectType<'_>],
+  ) -> Result<(), AppError> {
+    for object_type in object_types {
+      if self
+        .expirations
+        .take_expired(*uid, object_type.policy_object())
+        .await
+      {
+        trace!(
+          "[access control]: policy expired:user={}, object={}",
+          uid,
+          object_type.policy_object()
+        );
+        let mut enforcer = self.enforcer.write().await;
+        self
+          .remove_with_enforcer(uid, object_type, &mut enforcer)
+          .await?;
+      }
+    }
+    Ok(())
+  }
+
   /// 1. **Workspace Policy**: Initially, it checks if the user has permission at the workspace level. If the user
   ///    has permission to perform the action on the workspace, the function returns `true` without further checks.
   ///
@@ -124,15 +208,35 @@ where
       .total_read_enforce_result
       .fetch_add(1, Ordering::Relaxed);
 
+    // 0. Time-boxed policies are revoked lazily, once they are checked after their expiry time.
+    let workspace_object = ObjectType::Workspace(workspace_id);
+    self
+      .remove_expired_policies(uid, &[&workspace_object, &obj])
+      .await?;
+
     // 1. First, check workspace-level permissions.
+    //    Guests are skipped when accessing a collab, they rely on the object-specific policy.
     let workspace_policy_request = WorkspacePolicyRequest::new(workspace_id, uid, &obj, &act);
-    let policy = workspace_policy_request.to_policy();
-    let mut result = self
-      .enforcer
-      .read()
-      .await
-      .enforce(policy)
-      .map_err(|e| AppError::Internal(anyhow!("enforce: {e:?}")))?;
+    let inherits_workspace_role = match workspace_policy_request.to_member_policy() {
+      Some(member_policy) => self
+        .enforcer
+        .read()
+        .await
+        .enforce(member_policy)
+        .map_err(|e| AppError::Internal(anyhow!("enforce: {e:?}")))?,
+      None => true,
+    };
+    let mut result = if inherits_workspace_role {
+      let policy = workspace_policy_request.to_policy();
+      self
+        .enforcer
+        .read()
+        .await
+        .enforce(policy)
+        .map_err(|e| AppError::Internal(anyhow!("enforce: {e:?}")))?
+    } else {
+      false
+    };
 
     // 2. Fallback to group policy if workspace-level check fails.
     if !result {
@@ -204,6 +308,7 @@ where
 fn validate_obj_action(obj: &ObjectType<'_>, act: &ActionVariant) -> Result<(), AppError> {
   match (obj, act) {
     (ObjectType::Workspace(_), ActionVariant::FromRole(_))
+    | (ObjectType::Workspace(_), ActionVariant::FromCapability(_))
     | (ObjectType::Collab(_), ActionVariant::FromAccessLevel(_)) => Ok(()),
     _ => Err(AppError::Internal(anyhow!(
       "invalid object type and action type combination: object={:?}, action={:?}",
@@ -250,9 +355,9 @@ mod tests {
   use app_error::ErrorCode;
   use async_trait::async_trait;
   use casbin::{function_map::OperatorFunction, prelude::*};
-  use database_entity::dto::{AFAccessLevel, AFRole};
+  use database_entity::dto::{AFAccessLevel, AFRole, WorkspaceCapability};
 
-  use super::{AFEnforcer, EnforcerGroup};
+  use super::{AFEnforcer, EnforcerGroup, PolicyExpirations};
 
   pub struct TestEnforceGroup {
     guid: String,
@@ -274,7 +379,9 @@ mod tests {
       .unwrap();
 
     enforcer.add_function("cmpRoleOrLevel", OperatorFunction::Arg2(cmp_role_or_level));
-    AFEnforcer::new(enforcer, enforce_group).await.unwrap()
+    AFEnforcer::new(enforcer, enforce_group, PolicyExpirations::default())
+      .await
+      .unwrap()
   }
   #[tokio::test]
   async fn collab_group_test() {
@@ -725,31 +832,110 @@ mod tests {
       .await
       .unwrap();
 
+    // the guest role is only granted on the workspace itself
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Guest),
+      )
+      .await
+      .is_ok());
+
+    // guests never inherit access to the collabs of the workspace
     for role in [AFRole::Owner, AFRole::Member, AFRole::Guest] {
-      if role == AFRole::Owner || role == AFRole::Member {
-        let result = enforcer
-          .enforce_policy(
-            workspace_id,
-            &uid,
-            ObjectType::Collab(object_1),
-            ActionVariant::FromRole(&role),
-          )
-          .await;
-        assert!(result.is_err());
-        let error_code = result.unwrap_err().code();
-        assert_eq!(error_code, ErrorCode::NotEnoughPermissions);
-      } else {
-        assert!(enforcer
-          .enforce_policy(
-            workspace_id,
-            &uid,
-            ObjectType::Collab(object_1),
-            ActionVariant::FromRole(&role),
-          )
-          .await
-          .is_ok());
-      }
+      let result = enforcer
+        .enforce_policy(
+          workspace_id,
+          &uid,
+          ObjectType::Collab(object_1),
+          ActionVariant::FromRole(&role),
+        )
+        .await;
+      assert!(result.is_err());
+      let error_code = result.unwrap_err().code();
+      assert_eq!(error_code, ErrorCode::NotEnoughPermissions);
     }
+
+    // explicitly grant read access to the collab
+    enforcer
+      .update_policy(
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAccessLevel(&AFAccessLevel::ReadOnly),
+      )
+      .await
+      .unwrap();
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&Action::Read),
+      )
+      .await
+      .is_ok());
+    let result = enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&Action::Write),
+      )
+      .await;
+    assert!(result.is_err());
+  }
+
+  #[tokio::test]
+  async fn expired_policy_test() {
+    let enforcer = test_enforcer(NoEnforceGroup).await;
+    let uid = 1;
+    let workspace_id = "w1";
+    let object_1 = "o1";
+
+    enforcer
+      .update_policy(
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAccessLevel(&AFAccessLevel::ReadAndWrite),
+      )
+      .await
+      .unwrap();
+    enforcer
+      .update_policy_expiry(
+        &uid,
+        &ObjectType::Collab(object_1),
+        Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+      )
+      .await;
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&Action::Read),
+      )
+      .await
+      .is_ok());
+
+    // the policy is revoked once it is checked after its expiry time
+    enforcer
+      .update_policy_expiry(
+        &uid,
+        &ObjectType::Collab(object_1),
+        Some(chrono::Utc::now() - chrono::Duration::seconds(1)),
+      )
+      .await;
+    let result = enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&Action::Read),
+      )
+      .await;
+    assert_eq!(result.unwrap_err().code(), ErrorCode::NotEnoughPermissions);
   }
 
   #[tokio::test]
@@ -842,4 +1028,131 @@ mod tests {
       }
     }
   }
+
+  #[tokio::test]
+  async fn workspace_member_capability_test() {
+    let enforcer = test_enforcer(NoEnforceGroup).await;
+    let uid = 1;
+    let workspace_id = "w1";
+    let object_1 = "o1";
+
+    enforcer
+      .update_policy(
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Member),
+      )
+      .await
+      .unwrap();
+    for capability in [
+      WorkspaceCapability::Invite,
+      WorkspaceCapability::DeleteCollab,
+    ] {
+      enforcer
+        .update_policy(
+          &uid,
+          ObjectType::Workspace(workspace_id),
+          ActionVariant::FromCapability(&capability),
+        )
+        .await
+        .unwrap();
+    }
+
+    // the member only has the capabilities of their custom role
+    for capability in WorkspaceCapability::ALL {
+      let result = enforcer
+        .enforce_policy(
+          workspace_id,
+          &uid,
+          ObjectType::Workspace(workspace_id),
+          ActionVariant::FromCapability(&capability),
+        )
+        .await;
+      let expected = matches!(
+        capability,
+        WorkspaceCapability::Invite | WorkspaceCapability::DeleteCollab
+      );
+      assert_eq!(result.is_ok(), expected, "capability={:?}", capability);
+    }
+
+    // the capabilities don't make the member an owner, but let them delete the collabs
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Owner),
+      )
+      .await
+      .is_err());
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAction(&Action::Delete),
+      )
+      .await
+      .is_ok());
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Collab(object_1),
+        ActionVariant::FromAccessLevel(&AFAccessLevel::FullAccess),
+      )
+      .await
+      .is_ok());
+
+    // removing the capabilities keeps the role of the member
+    enforcer
+      .remove_capabilities(&uid, workspace_id)
+      .await
+      .unwrap();
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromCapability(&WorkspaceCapability::Invite),
+      )
+      .await
+      .is_err());
+    assert!(enforcer
+      .enforce_policy(
+        workspace_id,
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Member),
+      )
+      .await
+      .is_ok());
+  }
+
+  #[tokio::test]
+  async fn workspace_owner_has_every_capability_test() {
+    let enforcer = test_enforcer(NoEnforceGroup).await;
+    let uid = 1;
+    let workspace_id = "w1";
+
+    enforcer
+      .update_policy(
+        &uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromRole(&AFRole::Owner),
+      )
+      .await
+      .unwrap();
+    for capability in WorkspaceCapability::ALL {
+      let result = enforcer
+        .enforce_policy(
+          workspace_id,
+          &uid,
+          ObjectType::Workspace(workspace_id),
+          ActionVariant::FromCapability(&capability),
+        )
+        .await;
+      assert!(result.is_ok(), "capability={:?}", capability);
+    }
+  }
 }
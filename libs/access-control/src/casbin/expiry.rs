@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Tracks the policies that are only granted for a limited period of time.
+///
+/// Entries are keyed by the policy subject (`uid`) and the policy object (e.g. `workspace::<id>`
+/// or `collab::<id>`). Policies without an entry never expire. Every server holds its own
+/// expirations, the server making a change to a grant sends it to the others.
+#[derive(Clone, Default)]
+pub struct PolicyExpirations {
+  inner: Arc<RwLock<HashMap<(i64, String), DateTime<Utc>>>>,
+}
+
+impl PolicyExpirations {
+  /// Set the expiry time of the policy. Passing `None` makes the policy permanent.
+  pub async fn set(&self, uid: i64, policy_object: String, expires_at: Option<DateTime<Utc>>) {
+    let mut inner = self.inner.write().await;
+    match expires_at {
+      Some(expires_at) => {
+        inner.insert((uid, policy_object), expires_at);
+      },
+      None => {
+        inner.remove(&(uid, policy_object));
+      },
+    }
+  }
+
+  pub async fn remove(&self, uid: i64, policy_object: String) {
+    self.inner.write().await.remove(&(uid, policy_object));
+  }
+
+  /// Returns true if the policy has expired. The entry is removed once it has expired, the caller
+  /// is responsible for removing the policy itself.
+  pub async fn take_expired(&self, uid: i64, policy_object: String) -> bool {
+    let key = (uid, policy_object);
+    let is_expired = match self.inner.read().await.get(&key) {
+      Some(expires_at) => *expires_at <= Utc::now(),
+      None => return false,
+    };
+    if is_expired {
+      self.inner.write().await.remove(&key);
+    }
+    is_expired
+  }
+}
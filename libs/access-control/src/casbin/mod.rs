@@ -1,6 +1,9 @@
 pub mod access;
 mod adapter;
+mod archive;
 pub mod collab;
 mod enforcer;
+mod expiry;
 pub mod notification;
+mod view_permission;
 pub mod workspace;
@@ -0,0 +1,93 @@
+use app_error::AppError;
+use database::member_group::select_all_member_group_members;
+use database::view_permission::select_all_view_permissions;
+use database_entity::dto::{AFAccessLevel, ViewPermissionGrantee};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::error;
+use uuid::Uuid;
+
+/// How often the permissions of the views are reloaded from the database, to pick up the changes
+/// made through the other instances of the server.
+const VIEW_PERMISSIONS_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tracks the restricted views, which are only accessible to the grantees of their permissions.
+#[derive(Clone)]
+pub struct ViewPermissions {
+  pg_pool: PgPool,
+  inner: Arc<RwLock<ViewPermissionsInner>>,
+}
+
+#[derive(Default)]
+struct ViewPermissionsInner {
+  permissions_by_view: HashMap<String, Vec<(ViewPermissionGrantee, AFAccessLevel)>>,
+  members_by_group: HashMap<Uuid, HashSet<i64>>,
+}
+
+impl ViewPermissions {
+  pub async fn new(pg_pool: PgPool) -> Result<Self, AppError> {
+    let view_permissions = Self {
+      pg_pool,
+      inner: Default::default(),
+    };
+    view_permissions.reload().await?;
+
+    let cloned_view_permissions = view_permissions.clone();
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(VIEW_PERMISSIONS_RELOAD_INTERVAL);
+      interval.tick().await;
+      loop {
+        interval.tick().await;
+        if let Err(err) = cloned_view_permissions.reload().await {
+          error!("Failed to reload view permissions: {:?}", err);
+        }
+      }
+    });
+    Ok(view_permissions)
+  }
+
+  pub async fn reload(&self) -> Result<(), AppError> {
+    let mut inner = ViewPermissionsInner::default();
+    for row in select_all_view_permissions(&self.pg_pool).await? {
+      if let Some(grantee) = row.grantee() {
+        inner
+          .permissions_by_view
+          .entry(row.view_id)
+          .or_default()
+          .push((grantee, AFAccessLevel::from(row.access_level)));
+      }
+    }
+    for (group_id, uid) in select_all_member_group_members(&self.pg_pool).await? {
+      inner
+        .members_by_group
+        .entry(group_id)
+        .or_default()
+        .insert(uid);
+    }
+    *self.inner.write().await = inner;
+    Ok(())
+  }
+
+  /// Returns `None` if the view isn't restricted. Otherwise returns the highest access level
+  /// granted to the user, directly or through a group, `Some(None)` if the user isn't granted any.
+  pub async fn access_level(&self, view_id: &str, uid: i64) -> Option<Option<AFAccessLevel>> {
+    let inner = self.inner.read().await;
+    let permissions = inner.permissions_by_view.get(view_id)?;
+    let access_level = permissions
+      .iter()
+      .filter(|(grantee, _)| match grantee {
+        ViewPermissionGrantee::Member { uid: grantee_uid } => *grantee_uid == uid,
+        ViewPermissionGrantee::Group { group_id } => inner
+          .members_by_group
+          .get(group_id)
+          .map(|members| members.contains(&uid))
+          .unwrap_or(false),
+      })
+      .map(|(_, access_level)| *access_level)
+      .max();
+    Some(access_level)
+  }
+}
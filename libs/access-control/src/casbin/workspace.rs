@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -7,7 +8,7 @@ use crate::act::{Action, ActionVariant};
 use crate::entity::ObjectType;
 use crate::workspace::WorkspaceAccessControl;
 use app_error::AppError;
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, WorkspaceCapability};
 
 #[derive(Clone)]
 pub struct WorkspaceAccessControlImpl {
@@ -56,6 +57,36 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
       .await
   }
 
+  async fn enforce_capability(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capability: WorkspaceCapability,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .enforce(
+        workspace_id,
+        uid,
+        ObjectType::Workspace(workspace_id),
+        ActionVariant::FromCapability(&capability),
+      )
+      .await
+  }
+
+  #[instrument(level = "info", skip_all)]
+  async fn update_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_capabilities(uid, &workspace_id.to_string(), capabilities)
+      .await
+  }
+
   #[instrument(level = "info", skip_all)]
   async fn insert_role(
     &self,
@@ -74,6 +105,24 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     Ok(())
   }
 
+  #[instrument(level = "info", skip_all)]
+  async fn update_role_expiry(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError> {
+    self
+      .access_control
+      .update_policy_expiry(
+        uid,
+        ObjectType::Workspace(&workspace_id.to_string()),
+        expires_at,
+      )
+      .await;
+    Ok(())
+  }
+
   #[instrument(level = "info", skip_all)]
   async fn remove_user_from_workspace(
     &self,
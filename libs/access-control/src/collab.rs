@@ -1,7 +1,9 @@
 use crate::act::Action;
 use app_error::AppError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use database_entity::dto::AFAccessLevel;
+use uuid::Uuid;
 
 #[async_trait]
 pub trait CollabAccessControl: Sync + Send + 'static {
@@ -33,7 +35,20 @@ pub trait CollabAccessControl: Sync + Send + 'static {
     level: AFAccessLevel,
   ) -> Result<(), AppError>;
 
+  /// Set the time at which the user's access level in the collab is revoked.
+  /// Passing `None` makes the access level permanent.
+  async fn update_access_level_expiry(
+    &self,
+    uid: &i64,
+    oid: &str,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError>;
+
   async fn remove_access_level(&self, uid: &i64, oid: &str) -> Result<(), AppError>;
+
+  /// Reloads the permissions of the restricted views, after they or the member groups of a
+  /// workspace changed.
+  async fn reload_view_permissions(&self) -> Result<(), AppError>;
 }
 
 #[async_trait]
@@ -62,4 +77,12 @@ pub trait RealtimeAccessControl: Sync + Send + 'static {
     uid: &i64,
     oid: &str,
   ) -> Result<bool, AppError>;
+
+  /// Mark the workspace as archived or not. The collabs of an archived workspace can be observed
+  /// but not edited.
+  async fn update_workspace_archived(
+    &self,
+    workspace_id: &Uuid,
+    archived: bool,
+  ) -> Result<(), AppError>;
 }
@@ -1,6 +1,8 @@
 use app_error::AppError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use database_entity::dto::AFAccessLevel;
+use uuid::Uuid;
 
 use crate::{
   act::Action,
@@ -53,9 +55,22 @@ impl CollabAccessControl for CollabAccessControlImpl {
     Ok(())
   }
 
+  async fn update_access_level_expiry(
+    &self,
+    _uid: &i64,
+    _oid: &str,
+    _expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError> {
+    Ok(())
+  }
+
   async fn remove_access_level(&self, _uid: &i64, _oid: &str) -> Result<(), AppError> {
     Ok(())
   }
+
+  async fn reload_view_permissions(&self) -> Result<(), AppError> {
+    Ok(())
+  }
 }
 
 #[derive(Clone)]
@@ -92,4 +107,12 @@ impl RealtimeAccessControl for RealtimeCollabAccessControlImpl {
   ) -> Result<bool, AppError> {
     Ok(true)
   }
+
+  async fn update_workspace_archived(
+    &self,
+    _workspace_id: &Uuid,
+    _archived: bool,
+  ) -> Result<(), AppError> {
+    Ok(())
+  }
 }
@@ -1,10 +1,11 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::act::Action;
 use crate::workspace::WorkspaceAccessControl;
 use app_error::AppError;
-use database_entity::dto::AFRole;
+use database_entity::dto::{AFRole, WorkspaceCapability};
 
 #[derive(Clone)]
 pub struct WorkspaceAccessControlImpl;
@@ -41,6 +42,24 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     Ok(())
   }
 
+  async fn enforce_capability(
+    &self,
+    _uid: &i64,
+    _workspace_id: &str,
+    _capability: WorkspaceCapability,
+  ) -> Result<(), AppError> {
+    Ok(())
+  }
+
+  async fn update_capabilities(
+    &self,
+    _uid: &i64,
+    _workspace_id: &Uuid,
+    _capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError> {
+    Ok(())
+  }
+
   async fn insert_role(
     &self,
     _uid: &i64,
@@ -50,6 +69,15 @@ impl WorkspaceAccessControl for WorkspaceAccessControlImpl {
     Ok(())
   }
 
+  async fn update_role_expiry(
+    &self,
+    _uid: &i64,
+    _workspace_id: &Uuid,
+    _expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError> {
+    Ok(())
+  }
+
   async fn remove_user_from_workspace(
     &self,
     _uid: &i64,
@@ -1,5 +1,6 @@
-use crate::act::ActionVariant;
+use crate::act::{ActionVariant, Acts};
 use crate::entity::ObjectType;
+use database_entity::dto::AFRole;
 
 pub struct GroupPolicyRequest<'a> {
   pub guid: &'a str,
@@ -71,6 +72,23 @@ impl<'a> WorkspacePolicyRequest<'a> {
       },
     }
   }
+
+  /// Returns the policy that the user must satisfy before the workspace role can be used to
+  /// access a collab. Guests never inherit access to the collabs of a workspace, they only
+  /// get access to the collabs that are explicitly granted to them.
+  pub fn to_member_policy(&self) -> Option<Vec<String>> {
+    match self.object_type {
+      ObjectType::Workspace(_) => None,
+      ObjectType::Collab(_) => {
+        let object_type = ObjectType::Workspace(self.workspace_id);
+        Some(vec![
+          self.uid.to_string(),
+          object_type.policy_object(),
+          AFRole::Member.to_enforce_act().to_string(),
+        ])
+      },
+    }
+  }
 }
 
 pub struct PolicyRequest<'a> {
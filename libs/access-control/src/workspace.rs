@@ -1,7 +1,8 @@
 use crate::act::Action;
 use app_error::AppError;
 use async_trait::async_trait;
-use database_entity::dto::AFRole;
+use chrono::{DateTime, Utc};
+use database_entity::dto::{AFRole, WorkspaceCapability};
 use sqlx::types::Uuid;
 
 #[async_trait]
@@ -20,9 +21,36 @@ pub trait WorkspaceAccessControl: Send + Sync + 'static {
     action: Action,
   ) -> Result<(), AppError>;
 
+  /// Check if the user has the capability in the workspace, from their custom role or because they
+  /// are an owner.
+  /// Returns AppError::NotEnoughPermission if the user does not have the capability.
+  async fn enforce_capability(
+    &self,
+    uid: &i64,
+    workspace_id: &str,
+    capability: WorkspaceCapability,
+  ) -> Result<(), AppError>;
+
+  /// Replaces the capabilities granted to the user by their custom role.
+  async fn update_capabilities(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    capabilities: &[WorkspaceCapability],
+  ) -> Result<(), AppError>;
+
   async fn insert_role(&self, uid: &i64, workspace_id: &Uuid, role: AFRole)
     -> Result<(), AppError>;
 
+  /// Set the time at which the user's role in the workspace is revoked.
+  /// Passing `None` makes the role permanent.
+  async fn update_role_expiry(
+    &self,
+    uid: &i64,
+    workspace_id: &Uuid,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<(), AppError>;
+
   async fn remove_user_from_workspace(
     &self,
     uid: &i64,
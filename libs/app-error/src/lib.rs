@@ -3,13 +3,14 @@ pub mod gotrue;
 
 #[cfg(feature = "gotrue_error")]
 use crate::gotrue::GoTrueError;
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::string::FromUtf8Error;
 
 #[cfg(feature = "appflowy_ai_error")]
 use appflowy_ai_client::error::AIError;
 use reqwest::StatusCode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -173,9 +174,86 @@ pub enum AppError {
 
   #[error("There is an invalid character in the publish namespace: {character}")]
   CustomNamespaceInvalidCharacter { character: char },
+
+  #[error("{0}")]
+  ClientVersionBlocked(String),
+
+  #[error("{0}")]
+  SecretDetected(String),
+
+  #[error("{0}")]
+  InvitationExpired(String),
+
+  #[error("{0}")]
+  PublishedViewExpired(String),
+
+  /// The collab is being moved to another storage shard, the write can be retried shortly.
+  #[error("{0}")]
+  CollabMigrating(String),
+
+  /// The stored payload of the collab doesn't match its checksum.
+  #[error("{0}")]
+  CollabCorrupted(String),
+
+  /// The client sent more than its share of requests, it can retry after the given delay.
+  #[error("Too many requests, retry after {retry_after_secs} seconds")]
+  TooManyRequests { retry_after_secs: u64 },
+
+  /// The address of the client is outside the IP allowlist of the workspace.
+  #[error("{0}")]
+  IpNotAllowed(String),
+
+  /// The workspace requires a second factor, which the session of the user lacks. The client can
+  /// prompt the user to enroll one.
+  #[error("{0}")]
+  SecondFactorRequired(String),
+
+  /// An error along with the structured details sent to the client. See [AppError::with_details].
+  #[error("{error}")]
+  WithDetails {
+    error: Box<AppError>,
+    details: Box<ErrorDetails>,
+  },
 }
 
 impl AppError {
+  /// Attaches structured details to the error, so that the clients can act on it without
+  /// parsing its message. The code of the error is unchanged.
+  pub fn with_details(self, details: ErrorDetails) -> Self {
+    AppError::WithDetails {
+      error: Box::new(self),
+      details: Box::new(details),
+    }
+  }
+
+  /// Returns the structured details of the error, if any.
+  pub fn details(&self) -> Option<ErrorDetails> {
+    match self {
+      AppError::WithDetails { details, .. } => Some(details.as_ref().clone()),
+      #[cfg(feature = "validation_error")]
+      AppError::ValidatorError(errors) => Some(ErrorDetails::from(errors)),
+      AppError::PublishNameInvalidCharacter { character } => Some(
+        ErrorDetails::field("publish_name", "invalid_character")
+          .with_param("character", character.to_string()),
+      ),
+      AppError::PublishNameTooLong {
+        given_length,
+        max_length,
+      } => Some(ErrorDetails::limit(
+        *max_length as i64,
+        Some(*given_length as i64),
+      )),
+      AppError::CustomNamespaceInvalidCharacter { character } => Some(
+        ErrorDetails::field("namespace", "invalid_character")
+          .with_param("character", character.to_string()),
+      ),
+      AppError::TooManyRequests { retry_after_secs } => Some(ErrorDetails::RetryAfter {
+        retry_after_secs: *retry_after_secs,
+      }),
+      _ => None,
+    }
+  }
+
   pub fn is_not_enough_permissions(&self) -> bool {
     matches!(self, AppError::NotEnoughPermissions { .. })
   }
@@ -249,6 +327,16 @@ impl AppError {
       AppError::CustomNamespaceInvalidCharacter { .. } => {
         ErrorCode::CustomNamespaceInvalidCharacter
       },
+      AppError::ClientVersionBlocked(_) => ErrorCode::ClientVersionBlocked,
+      AppError::SecretDetected(_) => ErrorCode::SecretDetected,
+      AppError::InvitationExpired(_) => ErrorCode::InvitationExpired,
+      AppError::PublishedViewExpired(_) => ErrorCode::PublishedViewExpired,
+      AppError::CollabMigrating(_) => ErrorCode::CollabMigrating,
+      AppError::CollabCorrupted(_) => ErrorCode::CollabCorrupted,
+      AppError::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+      AppError::IpNotAllowed(_) => ErrorCode::IpNotAllowed,
+      AppError::SecondFactorRequired(_) => ErrorCode::SecondFactorRequired,
+      AppError::WithDetails { error, .. } => error.code(),
     }
   }
 }
@@ -388,6 +476,15 @@ pub enum ErrorCode {
   PublishNameInvalidCharacter = 1051,
   PublishNameTooLong = 1052,
   CustomNamespaceInvalidCharacter = 1053,
+  ClientVersionBlocked = 1054,
+  SecretDetected = 1055,
+  InvitationExpired = 1056,
+  PublishedViewExpired = 1057,
+  CollabMigrating = 1058,
+  CollabCorrupted = 1059,
+  TooManyRequests = 1060,
+  IpNotAllowed = 1061,
+  SecondFactorRequired = 1062,
 }
 
 impl ErrorCode {
@@ -396,10 +493,92 @@ impl ErrorCode {
   }
 }
 
+/// Structured information about an error, sent along with its code and message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ErrorDetails {
+  /// The fields of the request that failed the validation.
+  Validation { field_errors: Vec<FieldError> },
+  /// The limit, or the quota, exceeded by the request.
+  Limit {
+    limit: i64,
+    /// The value that exceeded the limit, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    actual: Option<i64>,
+  },
+  /// How long the client should wait before retrying the request.
+  RetryAfter { retry_after_secs: u64 },
+}
+
+impl ErrorDetails {
+  /// The details of a single invalid field.
+  pub fn field(field: impl Into<String>, code: impl Into<String>) -> Self {
+    ErrorDetails::Validation {
+      field_errors: vec![FieldError {
+        field: field.into(),
+        code: code.into(),
+        params: HashMap::new(),
+      }],
+    }
+  }
+
+  pub fn limit(limit: i64, actual: Option<i64>) -> Self {
+    ErrorDetails::Limit { limit, actual }
+  }
+
+  /// Adds a parameter to the last field error. Does nothing for the other details.
+  pub fn with_param(mut self, name: &str, value: impl Into<serde_json::Value>) -> Self {
+    if let ErrorDetails::Validation { field_errors } = &mut self {
+      if let Some(field_error) = field_errors.last_mut() {
+        field_error.params.insert(name.to_string(), value.into());
+      }
+    }
+    self
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+  /// The name of the field in the request, e.g. `email`.
+  pub field: String,
+  /// A stable identifier of the failed check, e.g. `too_long` or `invalid_character`.
+  pub code: String,
+  /// The values the check was made with, e.g. the maximum length.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub params: HashMap<String, serde_json::Value>,
+}
+
+#[cfg(feature = "validation_error")]
+impl From<&validator::ValidationErrors> for ErrorDetails {
+  fn from(errors: &validator::ValidationErrors) -> Self {
+    let mut field_errors = errors
+      .field_errors()
+      .into_iter()
+      .flat_map(|(field, errors)| {
+        errors.iter().map(move |error| FieldError {
+          field: field.to_string(),
+          code: error.code.to_string(),
+          params: error
+            .params
+            .iter()
+            // The rejected value is not sent back, it may be a secret.
+            .filter(|(name, _)| *name != "value")
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect(),
+        })
+      })
+      .collect::<Vec<_>>();
+    field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+    ErrorDetails::Validation { field_errors }
+  }
+}
+
 #[derive(Serialize)]
 struct AppErrorSerde {
   code: ErrorCode,
   message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  details: Option<ErrorDetails>,
 }
 
 impl From<&AppError> for AppErrorSerde {
@@ -407,6 +586,7 @@ impl From<&AppError> for AppErrorSerde {
     Self {
       code: value.code(),
       message: value.to_string(),
+      details: value.details(),
     }
   }
 }
@@ -0,0 +1,107 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use actix_web::http::Method;
+use actix_web::{web::Data, HttpMessage, HttpRequest};
+use async_trait::async_trait;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::types::Uuid;
+
+/// The prefix of the personal access tokens, which tells them apart from the gotrue JWTs.
+pub const ACCESS_TOKEN_PREFIX: &str = "afpat_";
+
+const ACCESS_TOKEN_RANDOM_LENGTH: usize = 40;
+
+pub fn is_access_token(token: &str) -> bool {
+  token.starts_with(ACCESS_TOKEN_PREFIX)
+}
+
+pub fn generate_access_token() -> String {
+  let random: String = rand::thread_rng()
+    .sample_iter(&Alphanumeric)
+    .take(ACCESS_TOKEN_RANDOM_LENGTH)
+    .map(char::from)
+    .collect();
+  format!("{}{}", ACCESS_TOKEN_PREFIX, random)
+}
+
+/// Only the hash of the access tokens is stored, the tokens are shown once when issued.
+pub fn hash_access_token(token: &str) -> String {
+  hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// What a personal access token grants: acting as its user, within the scopes of the token.
+#[derive(Debug, Clone)]
+pub struct AccessTokenGrant {
+  pub token_id: Uuid,
+  pub user_uuid: Uuid,
+  /// Only the requests that don't change anything are allowed.
+  pub read_only: bool,
+  /// The workspaces the token is limited to, all the workspaces of the user if `None`.
+  pub workspace_ids: Option<Vec<Uuid>>,
+}
+
+impl AccessTokenGrant {
+  /// Checks that the request is within the scopes of the token. The access control checks of the
+  /// handlers still apply, as for any request of the user of the token, so a token never grants
+  /// more than its user has.
+  pub fn check_scopes(&self, req: &HttpRequest) -> Result<(), actix_web::Error> {
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD);
+    if self.read_only && !is_read {
+      return Err(actix_web::error::ErrorForbidden(
+        "The access token is read-only",
+      ));
+    }
+    if let Some(workspace_ids) = &self.workspace_ids {
+      match req.match_info().get("workspace_id") {
+        Some(workspace_id) => {
+          let is_allowed = Uuid::from_str(workspace_id)
+            .map(|workspace_id| workspace_ids.contains(&workspace_id))
+            .unwrap_or(false);
+          if !is_allowed {
+            return Err(actix_web::error::ErrorForbidden(
+              "The access token is not allowed on this workspace",
+            ));
+          }
+        },
+        // The routes that aren't about a workspace may still change one, e.g. from the body of
+        // the request, so the tokens limited to some workspaces can only read them.
+        None if !is_read => {
+          return Err(actix_web::error::ErrorForbidden(
+            "The access token is limited to some workspaces",
+          ));
+        },
+        None => {},
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Looks up the personal access tokens. The server registers it as
+/// `Data<Arc<dyn AccessTokenVerifier>>`.
+#[async_trait]
+pub trait AccessTokenVerifier: Send + Sync {
+  /// Returns what the token grants, or an error if the token is unknown, revoked or expired.
+  async fn verify(&self, token: &str) -> Result<AccessTokenGrant, actix_web::Error>;
+}
+
+/// Verifies the access token of the request and checks the request is within its scopes. The
+/// grant is kept in the extensions of the request.
+pub(crate) async fn verify_access_token(
+  req: &HttpRequest,
+  token: &str,
+) -> Result<AccessTokenGrant, actix_web::Error> {
+  let verifier = req
+    .app_data::<Data<Arc<dyn AccessTokenVerifier>>>()
+    .ok_or(actix_web::error::ErrorUnauthorized(
+      "The access tokens are not accepted by this server",
+    ))?
+    .clone();
+  let grant = verifier.verify(token).await?;
+  grant.check_scopes(req)?;
+  req.extensions_mut().insert(grant.clone());
+  Ok(grant)
+}
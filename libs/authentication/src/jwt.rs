@@ -1,5 +1,6 @@
 use actix_http::Payload;
 use actix_web::{web::Data, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
 
 use gotrue_entity::gotrue_jwt::GoTrueJWTClaims;
 use secrecy::{ExposeSecret, Secret};
@@ -10,6 +11,9 @@ use std::ops::Deref;
 use std::str::FromStr;
 use tracing::instrument;
 
+use crate::access_token::{is_access_token, verify_access_token};
+use crate::session_policy::{enforce_workspace_session_policy, SessionAssurance};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserUuid(uuid::Uuid);
 
@@ -41,41 +45,72 @@ impl Display for UserToken {
   }
 }
 
+/// Accepts the gotrue JWTs and the personal access tokens. The requests made with an access token
+/// must be within its scopes.
 impl FromRequest for UserUuid {
   type Error = actix_web::Error;
 
-  type Future = std::future::Ready<Result<Self, Self::Error>>;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-    let auth = get_auth_from_request(req);
-    match auth {
-      Ok(auth) => match UserUuid::from_auth(auth) {
-        Ok(uuid) => std::future::ready(Ok(uuid)),
-        Err(e) => std::future::ready(Err(e)),
-      },
-      Err(e) => std::future::ready(Err(e)),
-    }
+    let req = req.clone();
+    Box::pin(async move { user_uuid_from_request(&req).await })
+  }
+}
+
+async fn user_uuid_from_request(req: &HttpRequest) -> Result<UserUuid, actix_web::Error> {
+  let (user_uuid, session) = authenticate_request(req).await?;
+  enforce_workspace_session_policy(req, session).await?;
+  Ok(user_uuid)
+}
+
+/// Returns the user of the request, and how strongly its session was authenticated.
+async fn authenticate_request(
+  req: &HttpRequest,
+) -> Result<(UserUuid, SessionAssurance), actix_web::Error> {
+  let token = bearer_token(req)?;
+  if is_access_token(token) {
+    let grant = verify_access_token(req, token).await?;
+    // The access tokens are issued without a second factor, they aren't sessions of the user.
+    let session = SessionAssurance {
+      has_second_factor: false,
+    };
+    return Ok((UserUuid(grant.user_uuid), session));
+  }
+  let jwt_secret = jwt_secret_from_request(req)?;
+  let auth = authorization_from_token(token, jwt_secret)?;
+  let session = session_assurance(&auth);
+  Ok((UserUuid::from_auth(auth)?, session))
+}
+
+fn session_assurance(auth: &Authorization) -> SessionAssurance {
+  SessionAssurance {
+    has_second_factor: auth.claims.has_second_factor(),
   }
 }
 
 // For cases where the handler itself will handle the request differently
-// based on whether the user is authenticated or not
+// based on whether the user is authenticated or not. The requests without a valid authorization
+// are anonymous, but the sessions rejected by the policy of the workspace are still rejected.
 pub struct OptionalUserUuid(Option<UserUuid>);
 
 impl FromRequest for OptionalUserUuid {
   type Error = actix_web::Error;
 
-  type Future = std::future::Ready<Result<Self, Self::Error>>;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-    let auth = get_auth_from_request(req);
-    match auth {
-      Ok(auth) => match UserUuid::from_auth(auth) {
-        Ok(uuid) => std::future::ready(Ok(OptionalUserUuid(Some(uuid)))),
-        Err(_) => std::future::ready(Ok(OptionalUserUuid(None))),
-      },
-      Err(_) => std::future::ready(Ok(OptionalUserUuid(None))),
-    }
+    let req = req.clone();
+    Box::pin(async move {
+      let (user_uuid, session) = match authenticate_request(&req).await {
+        Ok(authenticated) => authenticated,
+        Err(_) => return Ok(OptionalUserUuid(None)),
+      };
+      // A signed in user whose session doesn't meet the requirements of the workspace is
+      // rejected, rather than handled as an anonymous user.
+      enforce_workspace_session_policy(&req, session).await?;
+      Ok(OptionalUserUuid(Some(user_uuid)))
+    })
   }
 }
 
@@ -113,24 +148,33 @@ impl Authorization {
 impl FromRequest for Authorization {
   type Error = actix_web::Error;
 
-  type Future = std::future::Ready<Result<Self, Self::Error>>;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-    let auth = get_auth_from_request(req);
-    match auth {
-      Ok(auth) => std::future::ready(Ok(auth)),
-      Err(e) => std::future::ready(Err(e)),
-    }
+    let req = req.clone();
+    Box::pin(async move {
+      let auth = get_auth_from_request(&req)?;
+      enforce_workspace_session_policy(&req, session_assurance(&auth)).await?;
+      Ok(auth)
+    })
   }
 }
 
+/// Only accepts the gotrue JWTs, for the handlers that need a gotrue session.
 fn get_auth_from_request(req: &HttpRequest) -> Result<Authorization, actix_web::Error> {
-  let jwt_secret_data =
-    req
-      .app_data::<Data<Secret<String>>>()
-      .ok_or(actix_web::error::ErrorInternalServerError(
-        "jwt secret not found",
-      ))?;
+  let jwt_secret_data = jwt_secret_from_request(req)?;
+  authorization_from_token(bearer_token(req)?, jwt_secret_data)
+}
+
+fn jwt_secret_from_request(req: &HttpRequest) -> Result<&Data<Secret<String>>, actix_web::Error> {
+  req
+    .app_data::<Data<Secret<String>>>()
+    .ok_or(actix_web::error::ErrorInternalServerError(
+      "jwt secret not found",
+    ))
+}
+
+fn bearer_token(req: &HttpRequest) -> Result<&str, actix_web::Error> {
   let bearer = req
     .headers()
     .get("Authorization")
@@ -147,8 +191,7 @@ fn get_auth_from_request(req: &HttpRequest) -> Result<Authorization, actix_web::
     .ok_or(actix_web::error::ErrorUnauthorized(
       "Invalid Authorization header, missing Bearer",
     ))?;
-
-  authorization_from_token(token, jwt_secret_data)
+  Ok(token)
 }
 
 #[instrument(level = "trace", skip_all, err)]
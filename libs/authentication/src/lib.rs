@@ -1,4 +1,6 @@
+pub mod access_token;
 pub mod error;
 pub mod jwt;
 pub mod password;
+pub mod session_policy;
 pub mod user;
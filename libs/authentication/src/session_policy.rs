@@ -0,0 +1,47 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use actix_web::{web::Data, HttpMessage, HttpRequest};
+use async_trait::async_trait;
+use sqlx::types::Uuid;
+
+/// How strongly the session of a request was authenticated. It is kept in the extensions of the
+/// request once the session is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionAssurance {
+  /// Whether the user signed in with a second factor, as told by the amr claims of the gotrue JWT.
+  pub has_second_factor: bool,
+}
+
+/// Checks the sessions against the requirements of a workspace. The server registers it as
+/// `Data<Arc<dyn WorkspaceSessionPolicy>>`.
+#[async_trait]
+pub trait WorkspaceSessionPolicy: Send + Sync {
+  /// Returns an error if the session doesn't meet the requirements of the workspace.
+  async fn check_session(
+    &self,
+    workspace_id: &Uuid,
+    session: &SessionAssurance,
+  ) -> Result<(), actix_web::Error>;
+}
+
+/// Checks the session of the request against the policy of the workspace of the route, if any.
+pub(crate) async fn enforce_workspace_session_policy(
+  req: &HttpRequest,
+  session: SessionAssurance,
+) -> Result<(), actix_web::Error> {
+  req.extensions_mut().insert(session);
+  let workspace_id = match req
+    .match_info()
+    .get("workspace_id")
+    .and_then(|workspace_id| Uuid::from_str(workspace_id).ok())
+  {
+    Some(workspace_id) => workspace_id,
+    None => return Ok(()),
+  };
+  let policy = match req.app_data::<Data<Arc<dyn WorkspaceSessionPolicy>>>() {
+    Some(policy) => policy.clone(),
+    None => return Ok(()),
+  };
+  policy.check_session(&workspace_id, &session).await
+}
@@ -453,6 +453,7 @@ impl TestClient {
         workspace_id: workspace_id.to_string(),
         object_id: object_id.to_string(),
         access_level,
+        expires_at: None,
       })
       .await
       .unwrap();
@@ -473,6 +474,7 @@ impl TestClient {
         workspace_id: workspace_id.to_string(),
         object_id: object_id.to_string(),
         access_level,
+        expires_at: None,
       })
       .await
       .unwrap();
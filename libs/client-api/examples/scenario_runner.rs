@@ -0,0 +1,283 @@
+//! Runs the flows of a typical user against an AppFlowy Cloud server: sign in, create a
+//! workspace, import a Markdown page, publish it, comment on it and export it back. Each step
+//! prints its outcome and the process exits with a non-zero status as soon as one fails, so that
+//! self-hosters can check their deployment end to end.
+//!
+//! The user must already exist and be able to sign in with a password:
+//!
+//! ```sh
+//! cargo run -p client-api --example scenario_runner -- \
+//!   --base-url https://appflowy.example.com \
+//!   --gotrue-url https://appflowy.example.com/gotrue \
+//!   --email admin@example.com --password secret
+//! ```
+//!
+//! The arguments can also be set with the `APPFLOWY_BASE_URL`, `APPFLOWY_WS_URL`,
+//! `APPFLOWY_GOTRUE_URL`, `APPFLOWY_EMAIL` and `APPFLOWY_PASSWORD` env vars. The workspace
+//! created by the run is deleted at the end, unless `--keep` is passed.
+
+use std::future::Future;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use anyhow::{anyhow, bail, Context};
+use client_api::entity::workspace_dto::{CollabExportFormat, CreateWorkspaceParam};
+use client_api::entity::{CollabType, PublishCollabItem, PublishCollabMetadata, QueryCollabParams};
+use client_api::{Client, ClientConfiguration};
+use uuid::Uuid;
+
+const MARKDOWN: &str = "# Scenario runner\n\nThis page was imported by the scenario runner.\n";
+const COMMENT: &str = "Commented by the scenario runner";
+
+struct Args {
+  base_url: String,
+  ws_url: String,
+  gotrue_url: String,
+  email: String,
+  password: String,
+  keep: bool,
+}
+
+impl Args {
+  fn parse() -> anyhow::Result<Self> {
+    let mut base_url = std::env::var("APPFLOWY_BASE_URL").ok();
+    let mut ws_url = std::env::var("APPFLOWY_WS_URL").ok();
+    let mut gotrue_url = std::env::var("APPFLOWY_GOTRUE_URL").ok();
+    let mut email = std::env::var("APPFLOWY_EMAIL").ok();
+    let mut password = std::env::var("APPFLOWY_PASSWORD").ok();
+    let mut keep = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+      let mut value = || {
+        args
+          .next()
+          .ok_or_else(|| anyhow!("Missing the value of {}", arg))
+      };
+      match arg.as_str() {
+        "--base-url" => base_url = Some(value()?),
+        "--ws-url" => ws_url = Some(value()?),
+        "--gotrue-url" => gotrue_url = Some(value()?),
+        "--email" => email = Some(value()?),
+        "--password" => password = Some(value()?),
+        "--keep" => keep = true,
+        _ => bail!("Unknown argument: {}", arg),
+      }
+    }
+
+    let base_url = base_url
+      .unwrap_or_else(|| "http://localhost:8000".to_string())
+      .trim_end_matches('/')
+      .to_string();
+    let ws_url = ws_url.unwrap_or_else(|| {
+      format!(
+        "{}/ws/v1",
+        base_url.replacen("http", "ws", 1).trim_end_matches('/')
+      )
+    });
+    let gotrue_url = gotrue_url.unwrap_or_else(|| format!("{}/gotrue", base_url));
+    Ok(Self {
+      base_url,
+      ws_url,
+      gotrue_url,
+      email: email.context("The email is required, pass --email")?,
+      password: password.context("The password is required, pass --password")?,
+      keep,
+    })
+  }
+}
+
+/// The state shared by the steps, each step relying on the ones before it.
+struct Scenario {
+  client: Client,
+  workspace_id: Option<Uuid>,
+  view_id: Option<Uuid>,
+}
+
+impl Scenario {
+  fn workspace_id(&self) -> anyhow::Result<Uuid> {
+    self.workspace_id.context("No workspace was created")
+  }
+
+  fn view_id(&self) -> anyhow::Result<Uuid> {
+    self.view_id.context("No page was imported")
+  }
+
+  async fn sign_in(&self, email: &str, password: &str) -> anyhow::Result<()> {
+    self.client.sign_in_password(email, password).await?;
+    let profile = self.client.get_profile().await?;
+    println!("    signed in as {}", profile.uuid);
+    Ok(())
+  }
+
+  async fn create_workspace(&mut self) -> anyhow::Result<()> {
+    let workspace = self
+      .client
+      .create_workspace(CreateWorkspaceParam {
+        workspace_name: Some(format!("Scenario runner {}", Uuid::new_v4())),
+        residency: None,
+      })
+      .await?;
+    println!("    created workspace {}", workspace.workspace_id);
+    self.workspace_id = Some(workspace.workspace_id);
+    Ok(())
+  }
+
+  async fn import_markdown(&mut self) -> anyhow::Result<()> {
+    let workspace_id = self.workspace_id()?;
+    let folder = self
+      .client
+      .get_workspace_folder(&workspace_id.to_string(), Some(1), None)
+      .await?;
+    let space = folder
+      .children
+      .first()
+      .context("The workspace has no space to import the page in")?;
+    let page = self
+      .client
+      .import_page_from_markdown(workspace_id, &space.view_id, None, MARKDOWN)
+      .await?;
+    println!("    imported page {}", page.view_id);
+    self.view_id = Some(page.view_id.parse()?);
+    Ok(())
+  }
+
+  async fn publish(&self) -> anyhow::Result<()> {
+    let workspace_id = self.workspace_id()?.to_string();
+    let view_id = self.view_id()?;
+    let collab = self
+      .client
+      .get_collab(QueryCollabParams::new(
+        view_id,
+        CollabType::Document,
+        &workspace_id,
+      ))
+      .await?;
+    self
+      .client
+      .publish_collabs(
+        &workspace_id,
+        vec![PublishCollabItem {
+          meta: PublishCollabMetadata {
+            view_id,
+            publish_name: format!("scenario-runner-{}", view_id),
+            metadata: serde_json::json!({ "title": "Scenario runner" }),
+          },
+          data: collab.encode_collab.doc_state.to_vec(),
+        }],
+      )
+      .await?;
+    let info = self.client.get_published_collab_info(&view_id).await?;
+    println!("    published as {}/{}", info.namespace, info.publish_name);
+    Ok(())
+  }
+
+  async fn comment(&self) -> anyhow::Result<()> {
+    let view_id = self.view_id()?;
+    self
+      .client
+      .create_comment_on_published_view(&view_id, COMMENT, &None)
+      .await?;
+    let comments = self.client.get_published_view_comments(&view_id).await?;
+    if !comments
+      .comments
+      .iter()
+      .any(|comment| comment.content == COMMENT)
+    {
+      bail!("The comment is missing from the comments of the published page");
+    }
+    println!("    read back {} comments", comments.comments.len());
+    Ok(())
+  }
+
+  async fn export(&self) -> anyhow::Result<()> {
+    let markdown = self
+      .client
+      .export_document(
+        &self.workspace_id()?.to_string(),
+        &self.view_id()?.to_string(),
+        CollabExportFormat::Markdown,
+      )
+      .await?;
+    if !markdown.contains("Scenario runner") {
+      bail!(
+        "The exported page lacks the imported heading:\n{}",
+        markdown
+      );
+    }
+    println!("    exported {} bytes of Markdown", markdown.len());
+    Ok(())
+  }
+
+  async fn clean_up(&self) -> anyhow::Result<()> {
+    let workspace_id = self.workspace_id()?.to_string();
+    if let Some(view_id) = self.view_id {
+      self
+        .client
+        .unpublish_collabs(&workspace_id, &[view_id])
+        .await?;
+    }
+    self.client.delete_workspace(&workspace_id).await?;
+    println!("    deleted workspace {}", workspace_id);
+    Ok(())
+  }
+}
+
+/// Prints the outcome of the step, returns false if it failed.
+async fn run_step(name: &str, step: impl Future<Output = anyhow::Result<()>>) -> bool {
+  println!("==> {}", name);
+  let start = Instant::now();
+  match step.await {
+    Ok(()) => {
+      println!("    ok ({} ms)", start.elapsed().as_millis());
+      true
+    },
+    Err(err) => {
+      println!("    FAILED ({} ms): {:#}", start.elapsed().as_millis(), err);
+      false
+    },
+  }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+  let args = match Args::parse() {
+    Ok(args) => args,
+    Err(err) => {
+      eprintln!("{:#}", err);
+      return ExitCode::from(2);
+    },
+  };
+  println!("Running the scenarios against {}", args.base_url);
+  let mut scenario = Scenario {
+    client: Client::new(
+      &args.base_url,
+      &args.ws_url,
+      &args.gotrue_url,
+      &Uuid::new_v4().to_string(),
+      ClientConfiguration::default(),
+      "0.7.0",
+    ),
+    workspace_id: None,
+    view_id: None,
+  };
+
+  let passed = run_step("sign in", scenario.sign_in(&args.email, &args.password)).await
+    && run_step("create a workspace", scenario.create_workspace()).await
+    && run_step("import a Markdown page", scenario.import_markdown()).await
+    && run_step("publish the page", scenario.publish()).await
+    && run_step("comment on the published page", scenario.comment()).await
+    && run_step("export the page as Markdown", scenario.export()).await;
+
+  let cleaned_up = if args.keep || scenario.workspace_id.is_none() {
+    true
+  } else {
+    run_step("clean up", scenario.clean_up()).await
+  };
+  if passed && cleaned_up {
+    println!("All the scenarios passed");
+    ExitCode::SUCCESS
+  } else {
+    ExitCode::FAILURE
+  }
+}
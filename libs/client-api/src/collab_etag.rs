@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use shared_entity::dto::workspace_dto::CollabResponse;
+
+/// The maximum number of collabs kept in the cache, an arbitrary one is evicted beyond that.
+const MAX_CACHED_COLLABS: usize = 200;
+
+/// Caches the collabs fetched by the client along with their ETag, so that fetching a collab
+/// that hasn't changed since only costs a `304 Not Modified` response.
+#[derive(Clone, Default)]
+pub(crate) struct CollabETagCache {
+  collabs: Arc<RwLock<HashMap<String, CachedCollab>>>,
+}
+
+struct CachedCollab {
+  etag: String,
+  collab: CollabResponse,
+}
+
+impl CollabETagCache {
+  pub fn etag(&self, workspace_id: &str, object_id: &str) -> Option<String> {
+    self
+      .collabs
+      .read()
+      .get(&cache_key(workspace_id, object_id))
+      .map(|cached| cached.etag.clone())
+  }
+
+  /// Returns the cached collab if it still has the given ETag.
+  pub fn get(&self, workspace_id: &str, object_id: &str, etag: &str) -> Option<CollabResponse> {
+    self
+      .collabs
+      .read()
+      .get(&cache_key(workspace_id, object_id))
+      .filter(|cached| cached.etag == etag)
+      .map(|cached| cached.collab.clone())
+  }
+
+  pub fn insert(&self, workspace_id: &str, object_id: &str, etag: String, collab: CollabResponse) {
+    let key = cache_key(workspace_id, object_id);
+    let mut collabs = self.collabs.write();
+    if collabs.len() >= MAX_CACHED_COLLABS && !collabs.contains_key(&key) {
+      if let Some(evicted) = collabs.keys().next().cloned() {
+        collabs.remove(&evicted);
+      }
+    }
+    collabs.insert(key, CachedCollab { etag, collab });
+  }
+
+  pub fn clear(&self) {
+    self.collabs.write().clear();
+  }
+}
+
+fn cache_key(workspace_id: &str, object_id: &str) -> String {
+  format!("{}/{}", workspace_id, object_id)
+}
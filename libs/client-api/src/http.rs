@@ -1,12 +1,25 @@
+use crate::collab_etag::CollabETagCache;
 use crate::notify::{ClientToken, TokenStateReceiver};
 use app_error::AppError;
 use app_error::ErrorCode;
 use client_api_entity::auth_dto::DeleteUserQuery;
-use client_api_entity::server_info_dto::ServerInfoResponseItem;
+use client_api_entity::server_info_dto::{
+  ClientVersionPolicy, CollabCorruption, CollabMigration, CollabShard, CollabShardRebalance,
+  DeleteClientVersionPolicyParams, MigrateCollabParams, ProvisionAccountParams, ProvisionedAccount,
+  RebalanceCollabShardsParams, Schedules, ServerInfoResponseItem, UpdateWorkspaceLifecycleParams,
+  UpsertClientVersionPolicyParams, WorkspaceLifecycle,
+};
 use client_api_entity::workspace_dto::FavoriteSectionItems;
+use client_api_entity::workspace_dto::GuestSectionItems;
 use client_api_entity::workspace_dto::RecentSectionItems;
 use client_api_entity::workspace_dto::TrashSectionItems;
-use client_api_entity::workspace_dto::{FolderView, QueryWorkspaceFolder, QueryWorkspaceParam};
+use client_api_entity::workspace_dto::{
+  AnalyticsFormat, AnalyticsReport, AnalyticsReportData, FolderView, FolderViewFilter,
+  FolderViewMetadata, InsightsRange, PermissionAuditEvent, PublishAccessLog, PublishAnalytics,
+  QueryAnalyticsReport, QueryPermissionAuditLog, QueryPublishAccessLog, QueryPublishAnalytics,
+  QueryWorkspaceApiUsage, QueryWorkspaceAuditLog, QueryWorkspaceFolder, QueryWorkspaceInsights,
+  QueryWorkspaceParam, WorkspaceApiUsage, WorkspaceAuditEvent, WorkspaceInsights,
+};
 use client_api_entity::AuthProvider;
 use client_api_entity::CollabType;
 use gotrue::grant::PasswordGrant;
@@ -14,7 +27,10 @@ use gotrue::grant::{Grant, RefreshTokenGrant};
 use gotrue::params::MagicLinkParams;
 use gotrue::params::{AdminUserParams, GenerateLinkParams};
 use reqwest::StatusCode;
-use shared_entity::dto::workspace_dto::{CreateWorkspaceParam, PatchWorkspaceParam};
+use shared_entity::dto::workspace_dto::{
+  CollabResponse, CollabTypeParam, CreateWorkspaceParam, DuplicateWorkspaceParams, OEmbed,
+  OEmbedParams, PatchWorkspaceParam,
+};
 use std::fmt::{Display, Formatter};
 #[cfg(feature = "enable_brotli")]
 use std::io::Read;
@@ -24,12 +40,17 @@ use reqwest::Method;
 use reqwest::RequestBuilder;
 
 use client_api_entity::{
-  AFSnapshotMeta, AFSnapshotMetas, AFUserProfile, AFUserWorkspaceInfo, AFWorkspace,
-  QuerySnapshotParams, SnapshotData,
+  AFCreatedAccessToken, AFSnapshotMeta, AFSnapshotMetas, AFUserAccessTokens, AFUserDevices,
+  AFUserProfile, AFUserWorkspaceInfo, AFWorkspace, BlockUserParams, BlockedUsers, CollabAwareness,
+  CollabPresence, CreateAccessTokenParams, DatabasePresence, QuerySnapshotParams, SnapshotData,
+  WorkspaceRealtimeStats, WorkspaceUsage,
 };
 use semver::Version;
 use shared_entity::dto::auth_dto::SignInTokenResponse;
 use shared_entity::dto::auth_dto::UpdateUserParams;
+use shared_entity::dto::auth_dto::{
+  ChangeEmailParams, EmailChangeStatus, MergeAccountParams, MergeAccountSummary,
+};
 use shared_entity::dto::workspace_dto::WorkspaceSpaceUsage;
 use shared_entity::response::{AppResponse, AppResponseError};
 use std::sync::atomic::AtomicBool;
@@ -38,6 +59,7 @@ use std::time::Duration;
 use tracing::{error, event, info, instrument, trace, warn};
 use url::Url;
 
+use crate::retry_policy::{RetryPolicy, RetryableRequest};
 use crate::ws::ConnectInfo;
 use client_api_entity::SignUpResponse::{Authenticated, NotAuthenticated};
 use client_api_entity::{GotrueTokenResponse, UpdateGotrueUserParams, User};
@@ -56,6 +78,8 @@ pub struct ClientConfiguration {
   /// A larger buffer size means more data is compressed in a single operation, which can lead to better compression ratios
   /// since Brotli has more data to analyze for patterns and repetitions.
   pub(crate) compression_buffer_size: usize,
+  /// How the HTTP requests are retried, see [RetryPolicy].
+  pub(crate) retry_policy: RetryPolicy,
 }
 
 impl ClientConfiguration {
@@ -73,6 +97,11 @@ impl ClientConfiguration {
     };
     self
   }
+
+  pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+    self.retry_policy = retry_policy;
+    self
+  }
 }
 
 impl Default for ClientConfiguration {
@@ -80,6 +109,7 @@ impl Default for ClientConfiguration {
     Self {
       compression_quality: 8,
       compression_buffer_size: 10240,
+      retry_policy: RetryPolicy::none(),
     }
   }
 }
@@ -109,6 +139,7 @@ pub struct Client {
   pub(crate) refresh_ret_txs: Arc<RwLock<Vec<RefreshTokenSender>>>,
   pub(crate) config: ClientConfiguration,
   pub(crate) ai_model: Arc<RwLock<AIModel>>,
+  pub(crate) collab_etag_cache: CollabETagCache,
 }
 
 pub(crate) type RefreshTokenSender = tokio::sync::oneshot::Sender<Result<(), AppResponseError>>;
@@ -186,6 +217,7 @@ impl Client {
       device_id: device_id.to_string(),
       client_version,
       ai_model,
+      collab_etag_cache: CollabETagCache::default(),
     }
   }
 
@@ -417,7 +449,10 @@ impl Client {
   /// Used to extract the sign in url from the action link
   /// Only expose this method for testing
   pub async fn extract_sign_in_url(&self, action_link: &str) -> Result<String, AppResponseError> {
-    let resp = reqwest::Client::new().get(action_link).send().await?;
+    let resp = reqwest::Client::new()
+      .get(action_link)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     let html = resp.text().await.unwrap();
 
     trace!("action_link:{}, html: {}", action_link, html);
@@ -447,7 +482,11 @@ impl Client {
   #[inline]
   async fn verify_token_cloud(&self, access_token: &str) -> Result<bool, AppResponseError> {
     let url = format!("{}/api/user/verify/{}", self.base_url, access_token);
-    let resp = self.cloud_client.get(&url).send().await?;
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     let sign_in_resp: SignInTokenResponse = AppResponse::from_response(resp).await?.into_data()?;
     Ok(sign_in_resp.is_new)
   }
@@ -584,7 +623,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFUserProfile>::from_response(resp)
@@ -592,13 +631,132 @@ impl Client {
       .into_data()
   }
 
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_blocked_users(&self) -> Result<BlockedUsers, AppResponseError> {
+    let url = format!("{}/api/user/blocks", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<BlockedUsers>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Hides the comments and reactions of the user from this user, and prevents the user from
+  /// commenting on, replying to or reacting to the content of this user.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn block_user(&self, user_uuid: &uuid::Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/blocks", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&BlockUserParams {
+        user_uuid: *user_uuid,
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn unblock_user(&self, user_uuid: &uuid::Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/blocks/{}", self.base_url, user_uuid);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Lists the devices the user connected from to the realtime server, except the revoked ones.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_user_devices(&self) -> Result<AFUserDevices, AppResponseError> {
+    let url = format!("{}/api/user/devices", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFUserDevices>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Revokes the device and closes its realtime connection. The device can't connect again until
+  /// the user signs in again on it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_user_device(&self, device_id: &str) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/devices/{}", self.base_url, device_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Issues a personal access token, to send as a bearer token from the scripts and the
+  /// integrations. The token is only returned once.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_access_token(
+    &self,
+    params: &CreateAccessTokenParams,
+  ) -> Result<AFCreatedAccessToken, AppResponseError> {
+    let url = format!("{}/api/user/access-token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFCreatedAccessToken>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Lists the personal access tokens of the user, except the revoked and the expired ones.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_access_tokens(&self) -> Result<AFUserAccessTokens, AppResponseError> {
+    let url = format!("{}/api/user/access-token", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFUserAccessTokens>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_access_token(&self, token_id: &uuid::Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/user/access-token/{}", self.base_url, token_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn get_user_workspace_info(&self) -> Result<AFUserWorkspaceInfo, AppResponseError> {
     let url = format!("{}/api/user/workspace", self.base_url);
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFUserWorkspaceInfo>::from_response(resp)
@@ -612,7 +770,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
@@ -629,7 +787,28 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Duplicates the workspace, with all its collabs and blobs, into a new workspace owned by the
+  /// user. Only the owner of the workspace can duplicate it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn duplicate_workspace(
+    &self,
+    workspace_id: &str,
+    params: DuplicateWorkspaceParams,
+  ) -> Result<AFWorkspace, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/duplicate", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFWorkspace>::from_response(resp)
@@ -637,6 +816,27 @@ impl Client {
       .into_data()
   }
 
+  /// Resolves the embed of the URL with the oEmbed provider of its host. Only the providers
+  /// allowed by the server can be resolved.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn resolve_oembed(
+    &self,
+    workspace_id: &str,
+    params: &OEmbedParams,
+  ) -> Result<OEmbed, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/oembed", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<OEmbed>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn patch_workspace(&self, params: PatchWorkspaceParam) -> Result<(), AppResponseError> {
     let url = format!("{}/api/workspace", self.base_url);
@@ -644,7 +844,7 @@ impl Client {
       .http_client_with_auth(Method::PATCH, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -666,7 +866,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .query(&param)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<Vec<AFWorkspace>>::from_response(resp)
@@ -700,8 +900,9 @@ impl Client {
       .query(&QueryWorkspaceFolder {
         depth,
         root_view_id,
+        filter: None,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<FolderView>::from_response(resp)
@@ -709,144 +910,661 @@ impl Client {
       .into_data()
   }
 
+  /// Returns the views of the folder matching the filter, along with their ancestors. The filter
+  /// is applied by the server, within the `depth` below the root view.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn open_workspace(&self, workspace_id: &str) -> Result<AFWorkspace, AppResponseError> {
-    let url = format!("{}/api/workspace/{}/open", self.base_url, workspace_id);
+  pub async fn get_workspace_folder_with_filter(
+    &self,
+    workspace_id: &str,
+    depth: Option<u32>,
+    root_view_id: Option<String>,
+    filter: &FolderViewFilter,
+  ) -> Result<FolderView, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/folder", self.base_url, workspace_id);
+    let filter = serde_json::to_string(filter)?;
     let resp = self
-      .http_client_with_auth(Method::PUT, &url)
+      .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .query(&QueryWorkspaceFolder {
+        depth,
+        root_view_id,
+        filter: Some(filter),
+      })
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
-    AppResponse::<AFWorkspace>::from_response(resp)
+    AppResponse::<FolderView>::from_response(resp)
       .await?
       .into_data()
   }
 
+  /// Returns the metadata of a single view, i.e. its name, icon and the ids of its children,
+  /// without fetching the folder view tree.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn get_workspace_favorite(
+  pub async fn get_workspace_folder_view(
     &self,
     workspace_id: &str,
-  ) -> Result<FavoriteSectionItems, AppResponseError> {
-    let url = format!("{}/api/workspace/{}/favorite", self.base_url, workspace_id);
+    view_id: &str,
+  ) -> Result<FolderViewMetadata, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/folder/views/{}",
+      self.base_url, workspace_id, view_id
+    );
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
-    AppResponse::<FavoriteSectionItems>::from_response(resp)
+    AppResponse::<FolderViewMetadata>::from_response(resp)
       .await?
       .into_data()
   }
 
+  /// Returns the folder of a public workspace, without the private spaces and the trash. The
+  /// request doesn't require the client to be signed in.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn get_workspace_recent(
+  pub async fn get_public_workspace_folder(
     &self,
     workspace_id: &str,
-  ) -> Result<RecentSectionItems, AppResponseError> {
-    let url = format!("{}/api/workspace/{}/recent", self.base_url, workspace_id);
+    depth: Option<u32>,
+    root_view_id: Option<String>,
+  ) -> Result<FolderView, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/public/{}/folder",
+      self.base_url, workspace_id
+    );
     let resp = self
-      .http_client_with_auth(Method::GET, &url)
+      .cloud_client
+      .get(&url)
+      .query(&QueryWorkspaceFolder {
+        depth,
+        root_view_id,
+        filter: None,
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FolderView>::from_response(resp)
       .await?
-      .send()
+      .into_data()
+  }
+
+  /// Returns the content of a view of a public workspace. The request doesn't require the client
+  /// to be signed in.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_public_workspace_collab(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    collab_type: CollabType,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/public/{}/collab/{}",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .query(&CollabTypeParam { collab_type })
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
-    AppResponse::<RecentSectionItems>::from_response(resp)
+    AppResponse::<CollabResponse>::from_response(resp)
       .await?
       .into_data()
   }
 
+  /// Only the owner of the workspace can get the insights. Defaults to the last 7 days.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn get_workspace_trash(
+  pub async fn get_workspace_insights(
     &self,
     workspace_id: &str,
-  ) -> Result<TrashSectionItems, AppResponseError> {
-    let url = format!("{}/api/workspace/{}/trash", self.base_url, workspace_id);
+    range: Option<InsightsRange>,
+  ) -> Result<WorkspaceInsights, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/insights", self.base_url, workspace_id);
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .query(&QueryWorkspaceInsights { range })
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
-    AppResponse::<TrashSectionItems>::from_response(resp)
+    AppResponse::<WorkspaceInsights>::from_response(resp)
       .await?
       .into_data()
   }
 
-  #[instrument(skip_all, err)]
-  pub async fn sign_in_password(
+  /// Only the owner of the workspace can run the analytics reports. Defaults to the last 30 days.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_analytics_report(
     &self,
-    email: &str,
-    password: &str,
-  ) -> Result<bool, AppResponseError> {
-    let access_token_resp = self
-      .gotrue_client
-      .token(&Grant::Password(PasswordGrant {
-        email: email.to_owned(),
-        password: password.to_owned(),
-      }))
-      .await?;
-    let is_new = self
-      .verify_token_cloud(&access_token_resp.access_token)
+    workspace_id: &str,
+    report: AnalyticsReport,
+    query: &QueryAnalyticsReport,
+  ) -> Result<AnalyticsReportData, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/analytics/{}",
+      self.base_url,
+      workspace_id,
+      report.as_str()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
       .await?;
-    self.token.write().set(access_token_resp);
-    Ok(is_new)
+    log_request_id(&resp);
+    AppResponse::<AnalyticsReportData>::from_response(resp)
+      .await?
+      .into_data()
   }
 
+  /// Same as [Client::get_workspace_analytics_report], as a CSV file with a header row.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn sign_up(&self, email: &str, password: &str) -> Result<(), AppResponseError> {
-    match self.gotrue_client.sign_up(email, password, None).await? {
-      Authenticated(access_token_resp) => {
-        self.token.write().set(access_token_resp);
-        Ok(())
-      },
-      NotAuthenticated(user) => {
-        tracing::info!("sign_up but not authenticated: {}", user.email);
-        Ok(())
-      },
+  pub async fn get_workspace_analytics_report_csv(
+    &self,
+    workspace_id: &str,
+    report: AnalyticsReport,
+    query: &QueryAnalyticsReport,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/analytics/{}",
+      self.base_url,
+      workspace_id,
+      report.as_str()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryAnalyticsReport {
+        format: Some(AnalyticsFormat::Csv),
+        ..*query
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let is_csv = resp
+      .headers()
+      .get(reqwest::header::CONTENT_TYPE)
+      .and_then(|v| v.to_str().ok())
+      .map_or(false, |v| v.starts_with("text/csv"));
+    if !is_csv {
+      AppResponse::<()>::from_response(resp).await?.into_error()?;
+      return Err(AppResponseError::from(AppError::Unhandled(
+        "Unexpected response for the analytics report".to_string(),
+      )));
     }
+    Ok(resp.text().await?)
   }
 
-  #[instrument(level = "info", skip_all)]
-  pub async fn sign_out(&self) -> Result<(), AppResponseError> {
-    self.gotrue_client.logout(&self.access_token()?).await?;
-    self.token.write().unset();
-    Ok(())
-  }
-
+  /// Only the owner of the workspace can get the storage used by the workspace, broken down by
+  /// collab type and by member.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn update_user(&self, params: UpdateUserParams) -> Result<(), AppResponseError> {
-    let gotrue_params = UpdateGotrueUserParams::new()
-      .with_opt_email(params.email.clone())
-      .with_opt_password(params.password.clone());
-
-    let updated_user = self
-      .gotrue_client
-      .update_user(&self.access_token()?, &gotrue_params)
-      .await?;
-
-    if let Some(token) = self.token.write().as_mut() {
-      token.user = updated_user;
-    }
-
-    let url = format!("{}/api/user/update", self.base_url);
+  pub async fn get_workspace_usage_breakdown(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceUsage, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/usage", self.base_url, workspace_id);
     let resp = self
-      .http_client_with_auth(Method::POST, &url)
+      .http_client_with_auth(Method::GET, &url)
       .await?
-      .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
-    AppResponse::<()>::from_response(resp).await?.into_error()
+    AppResponse::<WorkspaceUsage>::from_response(resp)
+      .await?
+      .into_data()
   }
 
+  /// Only the owner of the workspace can get its realtime activity. The stats only cover the
+  /// server node that answered the request.
   #[instrument(level = "info", skip_all, err)]
-  pub async fn delete_user(&self) -> Result<(), AppResponseError> {
-    let (provider_access_token, provider_refresh_token) = {
-      let token = &self.token;
-      let token_read = token.read();
-      let token_resp = token_read
+  pub async fn get_workspace_realtime_stats(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceRealtimeStats, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/realtime/stats",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceRealtimeStats>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Only the owner of the workspace can get the access log of the published views. Defaults to
+  /// the whole retention of the access log.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_publish_access_log(
+    &self,
+    workspace_id: &str,
+    days: Option<i64>,
+  ) -> Result<PublishAccessLog, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-access-log",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryPublishAccessLog { days })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishAccessLog>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Only the owner of the workspace can get the analytics of the published views. Defaults to
+  /// the whole retention of the analytics.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_publish_analytics(
+    &self,
+    workspace_id: &str,
+    days: Option<i64>,
+  ) -> Result<PublishAnalytics, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/analytics",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryPublishAnalytics { days })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishAnalytics>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the API calls made on the workspace and their error rates, by route family and by
+  /// day. Only the owner of the workspace can get it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_api_usage(
+    &self,
+    workspace_id: &str,
+    days: Option<i64>,
+  ) -> Result<WorkspaceApiUsage, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/api-usage", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryWorkspaceApiUsage { days })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceApiUsage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the events of the audit log of the workspace, most recent first. Only the owner of
+  /// the workspace can read it.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_workspace_audit_log(
+    &self,
+    workspace_id: &str,
+    query: &QueryWorkspaceAuditLog,
+  ) -> Result<Vec<WorkspaceAuditEvent>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/audit-log", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<WorkspaceAuditEvent>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the changes of the roles of the members of the workspace and of its collabs, most
+  /// recent first. Only the owner of the workspace can read them.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_permission_audit_log(
+    &self,
+    workspace_id: &str,
+    query: &QueryPermissionAuditLog,
+  ) -> Result<Vec<PermissionAuditEvent>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/audit/permissions",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<PermissionAuditEvent>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the rows of the database that are being edited by the connected users.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_database_presence(
+    &self,
+    workspace_id: &str,
+    database_id: &str,
+  ) -> Result<DatabasePresence, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/database/{}/presence",
+      self.base_url, workspace_id, database_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DatabasePresence>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the last-known presence of the users in the collab, without waiting for the
+  /// connected clients to broadcast their awareness state.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_collab_awareness(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<CollabAwareness, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/awareness",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabAwareness>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the users viewing the collab, along with their device and cursor.
+  #[instrument(level = "debug", skip_all, err)]
+  pub async fn get_collab_presence(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<CollabPresence, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/presence",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabPresence>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn open_workspace(&self, workspace_id: &str) -> Result<AFWorkspace, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/open", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Only the owner of the workspace can archive it. An archived workspace is read-only and
+  /// hidden from [Client::get_workspaces].
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn archive_workspace(&self, workspace_id: &str) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/archive", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn unarchive_workspace(&self, workspace_id: &str) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/unarchive", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_favorite(
+    &self,
+    workspace_id: &str,
+  ) -> Result<FavoriteSectionItems, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/favorite", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<FavoriteSectionItems>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_recent(
+    &self,
+    workspace_id: &str,
+  ) -> Result<RecentSectionItems, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/recent", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<RecentSectionItems>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_trash(
+    &self,
+    workspace_id: &str,
+  ) -> Result<TrashSectionItems, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/trash", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<TrashSectionItems>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// List the views that are explicitly granted to a guest of the workspace.
+  /// Only the owner of the workspace is allowed to call this.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_guest_views(
+    &self,
+    workspace_id: &str,
+    guest_uid: i64,
+  ) -> Result<GuestSectionItems, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/guest/{}/view",
+      self.base_url, workspace_id, guest_uid
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<GuestSectionItems>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(skip_all, err)]
+  pub async fn sign_in_password(
+    &self,
+    email: &str,
+    password: &str,
+  ) -> Result<bool, AppResponseError> {
+    let access_token_resp = self
+      .gotrue_client
+      .token(&Grant::Password(PasswordGrant {
+        email: email.to_owned(),
+        password: password.to_owned(),
+      }))
+      .await?;
+    let is_new = self
+      .verify_token_cloud(&access_token_resp.access_token)
+      .await?;
+    self.token.write().set(access_token_resp);
+    Ok(is_new)
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn sign_up(&self, email: &str, password: &str) -> Result<(), AppResponseError> {
+    match self.gotrue_client.sign_up(email, password, None).await? {
+      Authenticated(access_token_resp) => {
+        self.token.write().set(access_token_resp);
+        Ok(())
+      },
+      NotAuthenticated(user) => {
+        tracing::info!("sign_up but not authenticated: {}", user.email);
+        Ok(())
+      },
+    }
+  }
+
+  #[instrument(level = "info", skip_all)]
+  pub async fn sign_out(&self) -> Result<(), AppResponseError> {
+    self.gotrue_client.logout(&self.access_token()?).await?;
+    self.token.write().unset();
+    self.collab_etag_cache.clear();
+    Ok(())
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_user(&self, params: UpdateUserParams) -> Result<(), AppResponseError> {
+    let gotrue_params = UpdateGotrueUserParams::new()
+      .with_opt_email(params.email.clone())
+      .with_opt_password(params.password.clone());
+
+    let updated_user = self
+      .gotrue_client
+      .update_user(&self.access_token()?, &gotrue_params)
+      .await?;
+
+    if let Some(token) = self.token.write().as_mut() {
+      token.user = updated_user;
+    }
+
+    let url = format!("{}/api/user/update", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Changes the email of the user. The email is only changed once the user confirmed the new
+  /// email, with the link GoTrue sent to it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn change_email(&self, email: &str) -> Result<EmailChangeStatus, AppResponseError> {
+    let url = format!("{}/api/user/email", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&ChangeEmailParams {
+        email: email.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<EmailChangeStatus>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Applies the new email of the user once it's confirmed.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn sync_email(&self) -> Result<EmailChangeStatus, AppResponseError> {
+    let url = format!("{}/api/user/email/sync", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<EmailChangeStatus>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Merges the account signed in with the given access token into the account of the current
+  /// user. The other account is deleted afterwards.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn merge_account(
+    &self,
+    source_access_token: &str,
+  ) -> Result<MergeAccountSummary, AppResponseError> {
+    let url = format!("{}/api/user/merge", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&MergeAccountParams {
+        source_access_token: source_access_token.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<MergeAccountSummary>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_user(&self) -> Result<(), AppResponseError> {
+    let (provider_access_token, provider_refresh_token) = {
+      let token = &self.token;
+      let token_read = token.read();
+      let token_resp = token_read
         .as_ref()
         .ok_or(AppResponseError::from(AppError::NotLoggedIn(
           "token is empty".to_string(),
@@ -865,7 +1583,7 @@ impl Client {
         provider_access_token,
         provider_refresh_token,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -884,7 +1602,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFSnapshotMetas>::from_response(resp)
@@ -906,7 +1624,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<SnapshotData>::from_response(resp)
@@ -928,7 +1646,32 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&collab_type)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFSnapshotMeta>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Rolls the collab back to the content of the snapshot. Returns the snapshot of the state
+  /// before the rollback, which can be restored to undo it.
+  pub async fn restore_snapshot(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    snapshot_id: i64,
+    collab_type: CollabType,
+  ) -> Result<AFSnapshotMeta, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/{}/snapshot/{}/restore",
+      self.base_url, workspace_id, object_id, snapshot_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&collab_type)
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFSnapshotMeta>::from_response(resp)
@@ -962,7 +1705,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<WorkspaceSpaceUsage>::from_response(resp)
@@ -976,7 +1719,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     if resp.status() == StatusCode::NOT_FOUND {
       Err(AppResponseError::new(
@@ -990,6 +1733,214 @@ impl Client {
     }
   }
 
+  /// Only the administrator of the server can list the client version policies.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_client_version_policies(
+    &self,
+  ) -> Result<Vec<ClientVersionPolicy>, AppResponseError> {
+    let url = format!("{}/api/server/client-version-policy", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<ClientVersionPolicy>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Marks the client versions matching `params.version_req` as deprecated or blocked. Only the
+  /// administrator of the server can change the client version policies.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn upsert_client_version_policy(
+    &self,
+    params: &UpsertClientVersionPolicyParams,
+  ) -> Result<ClientVersionPolicy, AppResponseError> {
+    let url = format!("{}/api/server/client-version-policy", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<ClientVersionPolicy>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_client_version_policy(
+    &self,
+    version_req: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/server/client-version-policy", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .json(&DeleteClientVersionPolicyParams {
+        version_req: version_req.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the state of the inactivity lifecycle of the workspace. Only the administrator of
+  /// the server can read it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_lifecycle(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspaceLifecycle, AppResponseError> {
+    let url = format!(
+      "{}/api/server/workspace-lifecycle/{}",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceLifecycle>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Exempts the workspace from the inactivity lifecycle policies, or subjects it to them again.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_lifecycle(
+    &self,
+    workspace_id: &str,
+    params: &UpdateWorkspaceLifecycleParams,
+  ) -> Result<WorkspaceLifecycle, AppResponseError> {
+    let url = format!(
+      "{}/api/server/workspace-lifecycle/{}",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceLifecycle>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates an account and its workspace for a user who hasn't signed up yet. Retrying with the
+  /// same `params.idempotency_key` returns the account created the first time. Only the
+  /// administrator of the server can provision accounts.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn provision_account(
+    &self,
+    params: &ProvisionAccountParams,
+  ) -> Result<ProvisionedAccount, AppResponseError> {
+    let url = format!("{}/api/server/provision", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<ProvisionedAccount>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Lists the storage shards of the collabs, with the number of collabs placed in each shard.
+  /// Only the server admins can list the shards.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_collab_shards(&self) -> Result<Vec<CollabShard>, AppResponseError> {
+    let url = format!("{}/api/server/collab-shards", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CollabShard>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Moves a batch of collabs to the shard they belong to. Call again with the returned
+  /// `next_cursor` as `params.after` until it's `None` to rebalance all the collabs.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn rebalance_collab_shards(
+    &self,
+    params: &RebalanceCollabShardsParams,
+  ) -> Result<CollabShardRebalance, AppResponseError> {
+    let url = format!("{}/api/server/collab-shards/rebalance", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabShardRebalance>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Moves the collab, along with its snapshots, to another shard.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn migrate_collab_to_shard(
+    &self,
+    params: &MigrateCollabParams,
+  ) -> Result<CollabMigration, AppResponseError> {
+    let url = format!("{}/api/server/collab-shards/migrate", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabMigration>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Lists the jobs run by the scheduler of the server, with their upcoming and last runs. Only
+  /// the server admins can list the jobs.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_schedules(&self) -> Result<Schedules, AppResponseError> {
+    let url = format!("{}/api/admin/schedules", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Schedules>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Lists the stored collabs and snapshots whose blob doesn't match its checksum, along with the
+  /// last good snapshot of the collab. Only the server admins can list the corruptions.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_collab_corruptions(&self) -> Result<Vec<CollabCorruption>, AppResponseError> {
+    let url = format!("{}/api/admin/collab-corruptions", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CollabCorruption>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   // Refresh token if given timestamp is close to the token expiration time
   pub async fn refresh_if_expired(&self, ts: i64, reason: &str) -> Result<(), AppResponseError> {
     let expires_at = self.token_expires_at()?;
@@ -1028,6 +1979,7 @@ impl Client {
       ("client-version", self.client_version.to_string()),
       ("client-timestamp", ts_now.to_string()),
       ("device_id", self.device_id.clone()),
+      ("client-platform", std::env::consts::OS.to_string()),
       ("ai-model", self.ai_model.read().to_str().to_string()),
     ];
     trace!(
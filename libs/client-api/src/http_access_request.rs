@@ -6,6 +6,7 @@ use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use uuid::Uuid;
 
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 impl Client {
@@ -17,7 +18,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<AccessRequest>::from_response(resp)
       .await?
@@ -33,7 +34,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&data)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<AccessRequestMinimal>::from_response(resp)
       .await?
@@ -52,7 +53,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&ApproveAccessRequestParams { is_approved: true })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -69,7 +70,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&ApproveAccessRequestParams { is_approved: false })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
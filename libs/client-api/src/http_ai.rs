@@ -1,4 +1,5 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 use reqwest::Method;
 use shared_entity::dto::ai_dto::{
@@ -24,7 +25,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -48,7 +49,7 @@ impl Client {
       .await?
       .json(&params)
       .timeout(Duration::from_secs(30))
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -69,7 +70,7 @@ impl Client {
       .await?
       .json(&params)
       .timeout(Duration::from_secs(30))
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<CompleteTextResponse>::from_response(resp)
@@ -91,7 +92,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<LocalAIConfig>::from_response(resp)
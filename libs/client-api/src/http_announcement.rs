@@ -0,0 +1,107 @@
+use client_api_entity::server_info_dto::{Announcement, AnnouncementParams};
+use reqwest::Method;
+use shared_entity::response::{AppResponse, AppResponseError};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::retry_policy::RetryableRequest;
+use crate::{log_request_id, Client};
+
+impl Client {
+  /// Returns the announcements to show to the user, the most severe first. The announcements
+  /// dismissed by the user are left out.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_announcements(&self) -> Result<Vec<Announcement>, AppResponseError> {
+    let url = format!("{}/api/announcements", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<Announcement>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn dismiss_announcement(&self, announcement_id: &Uuid) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/announcements/{}/dismiss",
+      self.base_url, announcement_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns all the announcements, including the ones that ended or haven't started yet. Only the
+  /// administrator of the server can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_all_announcements(&self) -> Result<Vec<Announcement>, AppResponseError> {
+    let url = format!("{}/api/announcements/all", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<Announcement>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Only the administrator of the server can create, update and delete the announcements.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_announcement(
+    &self,
+    params: &AnnouncementParams,
+  ) -> Result<Announcement, AppResponseError> {
+    let url = format!("{}/api/announcements", self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Announcement>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_announcement(
+    &self,
+    announcement_id: &Uuid,
+    params: &AnnouncementParams,
+  ) -> Result<Announcement, AppResponseError> {
+    let url = format!("{}/api/announcements/{}", self.base_url, announcement_id);
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Announcement>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_announcement(&self, announcement_id: &Uuid) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/announcements/{}", self.base_url, announcement_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+}
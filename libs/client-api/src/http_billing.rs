@@ -1,3 +1,4 @@
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 use client_api_entity::billing_dto::{
   SetSubscriptionRecurringInterval, SubscriptionCancelRequest, SubscriptionLinkRequest,
@@ -29,7 +30,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<String>::from_response(resp)
@@ -67,7 +68,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .query(sub_link_req)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<String>::from_response(resp)
@@ -87,7 +88,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(req)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -102,7 +103,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Vec<WorkspaceSubscriptionStatus>>::from_response(resp)
@@ -118,7 +119,7 @@ impl Client {
     let portal_url = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?
       .error_for_status()?
       .json::<AppResponse<String>>()
@@ -138,7 +139,7 @@ impl Client {
     self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?
       .error_for_status()?
       .json::<AppResponse<WorkspaceUsageAndLimit>>()
@@ -159,7 +160,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Vec<WorkspaceSubscriptionStatus>>::from_response(resp)
@@ -180,7 +181,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Vec<SubscriptionPlan>>::from_response(resp)
@@ -201,7 +202,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(set_sub_recur)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -215,7 +216,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Vec<SubscriptionPlanDetail>>::from_response(resp)
@@ -238,7 +239,7 @@ impl Client {
       .cloud_client
       .post(&url)
       .query(&SubscriptionTrialRequest { plan })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
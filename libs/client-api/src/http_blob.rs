@@ -1,8 +1,10 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 use app_error::AppError;
 use bytes::Bytes;
+use client_api_entity::CustomEmoji;
 use futures_util::TryStreamExt;
 use mime::Mime;
 use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
@@ -34,7 +36,7 @@ impl Client {
       .await?
       .header(header::CONTENT_TYPE, mime.to_string())
       .body(data)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     if resp.status() == StatusCode::PAYLOAD_TOO_LARGE {
@@ -61,7 +63,7 @@ impl Client {
       .header(header::CONTENT_TYPE, mime.to_string())
       .header(header::CONTENT_LENGTH, content_length)
       .body(data.into())
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<crate::entity::AFBlobRecord>::from_response(resp)
@@ -126,7 +128,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -147,7 +149,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -163,7 +165,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
 
@@ -207,7 +209,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -221,7 +223,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -235,11 +237,71 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<RepeatedBlobMetaData>::from_response(resp)
       .await?
       .into_data()
   }
+
+  /// Uploads the image of a custom emoji, referenced in comments and reactions as `:shortcode:`.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn put_custom_emoji<T: Into<Bytes>>(
+    &self,
+    workspace_id: &str,
+    shortcode: &str,
+    data: T,
+    mime: &Mime,
+  ) -> Result<CustomEmoji, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/emoji/{}",
+      self.base_url, workspace_id, shortcode
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .header(header::CONTENT_TYPE, mime.to_string())
+      .body(data.into())
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CustomEmoji>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn list_custom_emojis(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<CustomEmoji>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/emoji", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<CustomEmoji>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn delete_custom_emoji(
+    &self,
+    workspace_id: &str,
+    shortcode: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/emoji/{}",
+      self.base_url, workspace_id, shortcode
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
 }
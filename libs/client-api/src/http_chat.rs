@@ -1,4 +1,5 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 use client_api_entity::{
@@ -28,7 +29,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -44,7 +45,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -65,7 +66,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<ChatMessage>::from_response(resp)
@@ -88,7 +89,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<ChatMessage>::from_response(resp)
@@ -110,7 +111,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .timeout(Duration::from_secs(30))
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     let stream = AppResponse::<serde_json::Value>::json_response_stream(resp).await?;
@@ -130,7 +131,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<ChatMessage>::from_response(resp)
@@ -154,7 +155,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -174,7 +175,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<RepeatedRelatedQuestion>::from_response(resp)
@@ -209,7 +210,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<RepeatedChatMessage>::from_response(resp)
       .await?
@@ -1,15 +1,97 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::{blocking_brotli_compress, Client};
 use app_error::AppError;
+use bytes::Bytes;
+use client_api_entity::workspace_dto::{
+  CollabExportFormat, CollabRedaction, DocumentStats, ExportCollabParams, ExportDecryption,
+  QueryCollabExport, RedactCollabParams,
+};
 use client_api_entity::{
-  BatchQueryCollabParams, BatchQueryCollabResult, CreateCollabParams, DeleteCollabParams,
-  QueryCollab, UpdateCollabWebParams,
+  BatchQueryCollabParams, BatchQueryCollabResult, CollabSyncState, CollabType,
+  CollabUpdateValidation, CollabUpdatesSince, CollabUpdatesSinceParams, CreateCollabParams,
+  DeleteCollabParams, QueryCollab, QueryCollabParams, UpdateCollabWebParams,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use encrypt::envelope::OpeningKey;
+use reqwest::header::{AUTHORIZATION, CONTENT_DISPOSITION, ETAG, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode};
+use shared_entity::dto::workspace_dto::{
+  CollabResponse, CollabShareToken, CollabTypeParam, CreateCollabShareTokenParams,
 };
-use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use tracing::instrument;
+use uuid::Uuid;
 
 impl Client {
+  /// Fetches the latest state of the collab. The collab is only downloaded if it changed since it
+  /// was last fetched by this client, otherwise the cached copy is returned.
+  pub(crate) async fn fetch_collab(
+    &self,
+    params: &QueryCollabParams,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{}/collab/{}",
+      self.base_url, &params.workspace_id, &params.object_id
+    );
+    let mut builder =
+      self
+        .http_client_with_auth(Method::GET, &url)
+        .await?
+        .query(&CollabTypeParam {
+          collab_type: params.collab_type.clone(),
+        });
+    let cached_etag = self
+      .collab_etag_cache
+      .etag(&params.workspace_id, &params.object_id);
+    if let Some(etag) = &cached_etag {
+      builder = builder.header(IF_NONE_MATCH, etag);
+    }
+    let resp = builder.send_with_retry(&self.config.retry_policy).await?;
+    log_request_id(&resp);
+
+    if resp.status() == StatusCode::NOT_MODIFIED {
+      if let Some(collab) = cached_etag.and_then(|etag| {
+        self
+          .collab_etag_cache
+          .get(&params.workspace_id, &params.object_id, &etag)
+      }) {
+        return Ok(collab);
+      }
+      // The cached copy was evicted meanwhile, download the collab again
+      let resp = self
+        .http_client_with_auth(Method::GET, &url)
+        .await?
+        .query(&CollabTypeParam {
+          collab_type: params.collab_type.clone(),
+        })
+        .send_with_retry(&self.config.retry_policy)
+        .await?;
+      log_request_id(&resp);
+      return AppResponse::<CollabResponse>::from_response(resp)
+        .await?
+        .into_data();
+    }
+
+    let etag = resp
+      .headers()
+      .get(ETAG)
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.to_string());
+    let collab = AppResponse::<CollabResponse>::from_response(resp)
+      .await?
+      .into_data()?;
+    if let Some(etag) = etag {
+      self.collab_etag_cache.insert(
+        &params.workspace_id,
+        &params.object_id,
+        etag,
+        collab.clone(),
+      );
+    }
+    Ok(collab)
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn create_collab(&self, params: CreateCollabParams) -> Result<(), AppResponseError> {
     let url = format!(
@@ -37,7 +119,10 @@ impl Client {
       builder = builder.timeout(std::time::Duration::from_secs(60));
     }
 
-    let resp = builder.body(compress_bytes).send().await?;
+    let resp = builder
+      .body(compress_bytes)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -52,7 +137,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -72,12 +157,183 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  /// Applies the update to a scratch copy of the collab and reports whether it decodes and
+  /// applies cleanly, and what it would change. The collab is left untouched.
+  pub async fn validate_collab_update(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &UpdateCollabWebParams,
+  ) -> Result<CollabUpdateValidation, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/validate-update",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabUpdateValidation>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the changes of the collab that are missing from the state vector, to catch up after
+  /// a short offline period without fetching the whole collab.
+  pub async fn get_collab_updates_since(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &CollabUpdatesSinceParams,
+  ) -> Result<CollabUpdatesSince, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/updates/since",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabUpdatesSince>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the state vector and the content hash of the collab on the server. Compare them with
+  /// the local copy, with [CollabSyncState::content_matches], to tell whether a full resync is
+  /// needed.
+  pub async fn get_collab_sync_state(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    collab_type: CollabType,
+  ) -> Result<CollabSyncState, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/sync-state",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&CollabTypeParam { collab_type })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabSyncState>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Issues a token granting read access to the collab to anyone who has it, until it expires.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_collab_share_token(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &CreateCollabShareTokenParams,
+  ) -> Result<CollabShareToken, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-token",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabShareToken>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Revokes the share token, which stops working before it expires.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn revoke_collab_share_token(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    token_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/share-token/{}",
+      self.base_url, workspace_id, object_id, token_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Fetches the collab with a share token, which doesn't require to be signed in.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_with_share_token(
+    &self,
+    params: &QueryCollabParams,
+    share_token: &str,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{}/collab/{}",
+      self.base_url, &params.workspace_id, &params.object_id
+    );
+    let resp = self
+      .http_client_without_auth(Method::GET, &url)
+      .await?
+      .header(AUTHORIZATION, format!("ShareToken {}", share_token))
+      .query(&CollabTypeParam {
+        collab_type: params.collab_type.clone(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Fetches the document of a page with the token of a link sharing the page, which doesn't
+  /// require to be signed in.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_collab_with_share_link(
+    &self,
+    params: &QueryCollabParams,
+    share_link_token: &Uuid,
+  ) -> Result<CollabResponse, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/{}/collab/{}",
+      self.base_url, &params.workspace_id, &params.object_id
+    );
+    let resp = self
+      .http_client_without_auth(Method::GET, &url)
+      .await?
+      .header(AUTHORIZATION, format!("ShareLink {}", share_link_token))
+      .query(&CollabTypeParam {
+        collab_type: params.collab_type.clone(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabResponse>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   // The browser will call this API to get the collab list, because the URL length limit and browser can't send the body in GET request
   #[instrument(level = "info", skip_all, err)]
   pub async fn batch_post_collab(
@@ -116,7 +372,7 @@ impl Client {
       .http_client_with_auth(method, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<BatchQueryCollabResult>::from_response(resp)
@@ -133,9 +389,171 @@ impl Client {
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
+
+  /// Restores a deleted collab from the trash of the workspace.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn restore_collab(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/restore",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Permanently deletes a collab from the trash of the workspace, it can't be restored anymore.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn purge_collab(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/purge",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Redacts the given text spans and blocks from the current state, the snapshots and the
+  /// history of the collab. Only the administrator of the server can redact a collab.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn redact_collab(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &RedactCollabParams,
+  ) -> Result<CollabRedaction, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/redact",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CollabRedaction>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the word count, the block counts and the other content statistics of the document.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_document_stats(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<DocumentStats, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/stats",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<DocumentStats>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Exports the document as Markdown, plain text or JSON. Returns the content of the exported file.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn export_document(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    format: CollabExportFormat,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/export",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryCollabExport { format })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    // The errors are sent as a JSON response too, only the exported file is an attachment.
+    if !resp.headers().contains_key(CONTENT_DISPOSITION) {
+      AppResponse::<()>::from_response(resp).await?.into_error()?;
+      return Err(AppResponseError::from(AppError::Unhandled(
+        "Unexpected response for the document export".to_string(),
+      )));
+    }
+    Ok(resp.text().await?)
+  }
+
+  /// Exports the latest state of the document, encrypted with the passphrase or the public key of
+  /// the params when it is set. The encrypted export can be opened with [Client::decrypt_export].
+  pub async fn export_document_with_params(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    params: &ExportCollabParams,
+  ) -> Result<Bytes, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab/{}/export",
+      self.base_url, workspace_id, object_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    if !resp.headers().contains_key(CONTENT_DISPOSITION) {
+      AppResponse::<()>::from_response(resp).await?.into_error()?;
+      return Err(AppResponseError::from(AppError::Unhandled(
+        "Unexpected response for the document export".to_string(),
+      )));
+    }
+    Ok(resp.bytes().await?)
+  }
+
+  /// Decrypts an export encrypted by [Client::export_document_with_params].
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn decrypt_export(
+    data: &[u8],
+    decryption: &ExportDecryption,
+  ) -> Result<Vec<u8>, AppResponseError> {
+    let key = match decryption {
+      ExportDecryption::Passphrase { passphrase } => OpeningKey::Passphrase(passphrase),
+      ExportDecryption::SecretKey { secret_key } => OpeningKey::SecretKey(secret_key),
+    };
+    encrypt::envelope::open(data, key).map_err(|err| {
+      AppResponseError::from(AppError::InvalidRequest(format!(
+        "Failed to decrypt the export: {}",
+        err
+      )))
+    })
+  }
 }
@@ -1,4 +1,5 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 use client_api_entity::CollabType;
 use reqwest::Method;
@@ -20,7 +21,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<RepeatedSnapshotMeta>::from_response(resp)
@@ -42,7 +43,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<SnapshotInfo>::from_response(resp)
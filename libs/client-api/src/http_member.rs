@@ -1,16 +1,24 @@
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
+use app_error::AppError;
+use bytes::Bytes;
 use client_api_entity::{
-  AFCollabMember, AFCollabMembers, AFWorkspaceInvitation, AFWorkspaceInvitationStatus,
-  AFWorkspaceMember, CollabMemberIdentify, InsertCollabMemberParams, QueryCollabMembers,
-  QueryWorkspaceMember, UpdateCollabMemberParams,
+  AFCollabMember, AFCollabMembers, AFMemberGroup, AFMemberGroups, AFWorkspaceInvitation,
+  AFWorkspaceInvitationPreview, AFWorkspaceInvitationStatus, AFWorkspaceMember, AFWorkspaceRole,
+  AFWorkspaceRoles, CollabMemberIdentify, CreateMemberGroupParams, CreateWorkspaceRoleParams,
+  InsertCollabMemberParams, QueryCollabMembers, QueryWorkspaceMember, UpdateCollabMemberParams,
+  UpdateMemberGroupParams, UpdateWorkspaceRoleMembersParams, UpdateWorkspaceRoleParams,
 };
-use reqwest::Method;
+use reqwest::{header, Method};
 use shared_entity::dto::workspace_dto::{
-  CreateWorkspaceMembers, WorkspaceMemberChangeset, WorkspaceMemberInvitation, WorkspaceMembers,
+  BatchInviteWorkspaceMembers, BulkInviteTask, CreateWorkspaceMembers, InvitationResult,
+  InviteWorkspaceGuest, QueryWorkspaceMembers, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
+  WorkspaceMembers, WorkspaceMembersPage,
 };
 use shared_entity::response::{AppResponse, AppResponseError};
 use tracing::instrument;
+use uuid::Uuid;
 
 impl Client {
   #[instrument(level = "info", skip_all, err)]
@@ -20,7 +28,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&())
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -39,7 +47,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<Vec<AFWorkspaceMember>>::from_response(resp)
@@ -47,6 +55,31 @@ impl Client {
       .into_data()
   }
 
+  /// Returns a page of the workspace members, optionally filtered by the prefix of their name or
+  /// email, along with the number of matching members.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_members_paginated<W: AsRef<str>>(
+    &self,
+    workspace_id: W,
+    query: &QueryWorkspaceMembers,
+  ) -> Result<WorkspaceMembersPage, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member/paginated",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspaceMembersPage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "info", skip_all, err)]
   pub async fn invite_workspace_members(
     &self,
@@ -58,13 +91,133 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&invitations)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
     Ok(())
   }
 
+  /// Invites a guest who can only read the given views. Only the owner of the workspace is allowed
+  /// to call this.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn invite_workspace_guest(
+    &self,
+    workspace_id: &str,
+    params: &InviteWorkspaceGuest,
+  ) -> Result<(), AppResponseError> {
+    let url = format!("{}/api/workspace/{}/guest", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()?;
+    Ok(())
+  }
+
+  /// Invites each member independently and returns the result of each invitation, so that an
+  /// invalid email doesn't fail the other invitations.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn batch_invite_workspace_members(
+    &self,
+    workspace_id: &str,
+    batch: &BatchInviteWorkspaceMembers,
+  ) -> Result<Vec<InvitationResult>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite/batch",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(batch)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<InvitationResult>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Invites the members listed in a CSV file with the columns `email`, `role` and an optional
+  /// `message`. The invitations are sent in the background, use [Client::get_bulk_invite_task] to
+  /// follow the progress.
+  pub async fn bulk_invite_workspace_members<T: Into<Bytes>>(
+    &self,
+    workspace_id: &str,
+    csv: T,
+  ) -> Result<BulkInviteTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite/bulk",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .header(header::CONTENT_TYPE, "text/csv")
+      .body(csv.into())
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<BulkInviteTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_bulk_invite_task(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<BulkInviteTask, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite/bulk/{}",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<BulkInviteTask>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the rows that couldn't be invited as a CSV file with the columns `row`, `email` and
+  /// `error`.
+  pub async fn get_bulk_invite_error_report(
+    &self,
+    workspace_id: &str,
+    task_id: &uuid::Uuid,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/invite/bulk/{}/errors",
+      self.base_url, workspace_id, task_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let is_csv = resp
+      .headers()
+      .get(header::CONTENT_TYPE)
+      .and_then(|v| v.to_str().ok())
+      .map_or(false, |v| v.starts_with("text/csv"));
+    if !is_csv {
+      AppResponse::<()>::from_response(resp).await?.into_error()?;
+      return Err(AppResponseError::from(AppError::Unhandled(
+        "Unexpected response for the bulk invite error report".to_string(),
+      )));
+    }
+    Ok(resp.text().await?)
+  }
+
   pub async fn list_workspace_invitations(
     &self,
     status: Option<AFWorkspaceInvitationStatus>,
@@ -74,7 +227,7 @@ impl Client {
     if let Some(status) = status {
       builder = builder.query(&[("status", status)])
     }
-    let resp = builder.send().await?;
+    let resp = builder.send_with_retry(&self.config.retry_policy).await?;
     log_request_id(&resp);
     let res = AppResponse::<Vec<AFWorkspaceInvitation>>::from_response(resp).await?;
     res.into_data()
@@ -88,13 +241,32 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     let res: AppResponse<AFWorkspaceInvitation> = AppResponse::from_response(resp).await?;
     res.into_data()
   }
 
+  /// Returns the public information of an invitation. Doesn't require the user to be signed in.
+  pub async fn get_workspace_invitation_preview(
+    &self,
+    invite_uuid: &str,
+  ) -> Result<AFWorkspaceInvitationPreview, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/invite/{}/preview",
+      self.base_url, invite_uuid
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let res: AppResponse<AFWorkspaceInvitationPreview> = AppResponse::from_response(resp).await?;
+    res.into_data()
+  }
+
   pub async fn accept_workspace_invitation(
     &self,
     invitation_id: &str,
@@ -107,7 +279,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&())
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
@@ -131,7 +303,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&members)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
@@ -153,7 +325,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&changeset)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
@@ -176,7 +348,7 @@ impl Client {
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&payload)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()?;
@@ -196,7 +368,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -215,7 +387,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFCollabMember>::from_response(resp)
@@ -236,7 +408,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -255,7 +427,7 @@ impl Client {
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -274,7 +446,7 @@ impl Client {
       .http_client_with_auth(Method::GET, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFCollabMembers>::from_response(resp)
@@ -294,11 +466,200 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<AFWorkspaceMember>::from_response(resp)
       .await?
       .into_data()
   }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_member_groups(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<AFMemberGroups, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-group",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFMemberGroups>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates a group of members of the workspace, which the permissions of the views can be
+  /// granted to.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_member_group(
+    &self,
+    workspace_id: &Uuid,
+    params: &CreateMemberGroupParams,
+  ) -> Result<AFMemberGroup, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-group",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFMemberGroup>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_member_group(
+    &self,
+    workspace_id: &Uuid,
+    group_id: &Uuid,
+    params: &UpdateMemberGroupParams,
+  ) -> Result<AFMemberGroup, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-group/{}",
+      self.base_url, workspace_id, group_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFMemberGroup>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_member_group(
+    &self,
+    workspace_id: &Uuid,
+    group_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/member-group/{}",
+      self.base_url, workspace_id, group_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_roles(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<AFWorkspaceRoles, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/roles", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspaceRoles>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates a custom role, granting its capabilities to the members it's assigned to.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn create_workspace_role(
+    &self,
+    workspace_id: &Uuid,
+    params: &CreateWorkspaceRoleParams,
+  ) -> Result<AFWorkspaceRole, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/roles", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspaceRole>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_role(
+    &self,
+    workspace_id: &Uuid,
+    role_id: &Uuid,
+    params: &UpdateWorkspaceRoleParams,
+  ) -> Result<AFWorkspaceRole, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/roles/{}",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspaceRole>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Replaces the members of the custom role.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_role_members(
+    &self,
+    workspace_id: &Uuid,
+    role_id: &Uuid,
+    params: &UpdateWorkspaceRoleMembersParams,
+  ) -> Result<AFWorkspaceRole, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/roles/{}/members",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<AFWorkspaceRole>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_role(
+    &self,
+    workspace_id: &Uuid,
+    role_id: &Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/roles/{}",
+      self.base_url, workspace_id, role_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
 }
@@ -1,15 +1,32 @@
 use bytes::Bytes;
-use client_api_entity::workspace_dto::PublishInfoView;
+use client_api_entity::workspace_dto::{
+  PublishInfoView, PublishedOutlineChanges, PublishedView, QueryPublishedOutline,
+};
 use client_api_entity::{workspace_dto::PublishedDuplicate, PublishInfo, UpdatePublishNamespace};
 use client_api_entity::{
+  BatchComments, BatchCommentsParams, CommentAttachment, CommentBlocklist, CommentBlocklistParams,
+  CommentModeration, CommentSubscriberCount, CommentSubscriptionTokenParams,
   CreateGlobalCommentParams, CreateReactionParams, DeleteGlobalCommentParams, DeleteReactionParams,
-  GetReactionQueryParams, GlobalComments, PatchPublishedCollab, PublishInfoMeta, Reactions,
-  UpdateDefaultPublishView,
+  GetReactionQueryParams, GlobalComments, ModerateCommentsParams, PatchPublishedCollab,
+  PublishInfoMeta, PublishedViewVersion, QueryGlobalComments, Reactions, UpdateDefaultPublishView,
+  UpdateGlobalCommentParams,
+};
+use client_api_entity::{
+  CreatePublishSubNamespace, PublishSubNamespace, UpdatePublishSubNamespace,
+  UpdatePublishSubNamespaceViews,
+};
+use client_api_entity::{PatchPublishedViewSeo, PublishedViewSeo};
+use client_api_entity::{PublishEmbedAllowlist, UpdatePublishEmbedAllowlist};
+use client_api_entity::{WorkspacePublisherParams, WorkspacePublishers};
+use mime::Mime;
+use reqwest::{header, Method, StatusCode};
+use shared_entity::dto::publish_dto::{
+  PublishedDatabaseRows, QueryPublishedDatabaseRows, UpdateAutoPublish,
 };
-use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use tracing::instrument;
 
+use crate::retry_policy::RetryableRequest;
 use crate::{log_request_id, Client};
 
 // Publisher API
@@ -27,7 +44,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<Vec<PublishInfoView>>::from_response(resp)
@@ -56,7 +73,7 @@ impl Client {
         old_namespace,
         new_namespace,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -73,7 +90,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<String>::from_response(resp)
@@ -81,6 +98,242 @@ impl Client {
       .into_data()
   }
 
+  /// Lists the members granted the publish permission, besides the owners.
+  pub async fn get_workspace_publishers(
+    &self,
+    workspace_id: &str,
+  ) -> Result<WorkspacePublishers, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publishers",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<WorkspacePublishers>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Grants the publish permission to a member of the workspace. Only the owners can grant it.
+  pub async fn grant_workspace_publisher(
+    &self,
+    workspace_id: &str,
+    email: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publishers",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&WorkspacePublisherParams {
+        email: email.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn revoke_workspace_publisher(
+    &self,
+    workspace_id: &str,
+    email: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publishers",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .json(&WorkspacePublisherParams {
+        email: email.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn get_publish_embed_allowlist(
+    &self,
+    workspace_id: &str,
+  ) -> Result<PublishEmbedAllowlist, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/embed-allowlist",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishEmbedAllowlist>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Replaces the hosts of the embeds kept when the views of the workspace are published, in
+  /// addition to the hosts trusted by the server.
+  pub async fn set_publish_embed_allowlist(
+    &self,
+    workspace_id: &str,
+    hosts: Vec<String>,
+  ) -> Result<PublishEmbedAllowlist, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/embed-allowlist",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdatePublishEmbedAllowlist { hosts })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishEmbedAllowlist>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn list_publish_sub_namespaces(
+    &self,
+    workspace_id: &str,
+  ) -> Result<Vec<PublishSubNamespace>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/sub",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<PublishSubNamespace>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates a sub-namespace under the publish namespace of the workspace, e.g. `handbook` is
+  /// served at `{namespace}/handbook`.
+  pub async fn create_publish_sub_namespace(
+    &self,
+    workspace_id: &str,
+    params: &CreatePublishSubNamespace,
+  ) -> Result<PublishSubNamespace, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/sub",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishSubNamespace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn update_publish_sub_namespace(
+    &self,
+    workspace_id: &str,
+    sub_namespace: &str,
+    params: &UpdatePublishSubNamespace,
+  ) -> Result<PublishSubNamespace, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/sub/{}",
+      self.base_url, workspace_id, sub_namespace
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishSubNamespace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// The views published in the sub-namespace are moved back to the publish namespace of the
+  /// workspace.
+  pub async fn delete_publish_sub_namespace(
+    &self,
+    workspace_id: &str,
+    sub_namespace: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/sub/{}",
+      self.base_url, workspace_id, sub_namespace
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Moves the published views to the sub-namespace.
+  pub async fn add_publish_sub_namespace_views(
+    &self,
+    workspace_id: &str,
+    sub_namespace: &str,
+    view_ids: Vec<uuid::Uuid>,
+  ) -> Result<PublishSubNamespace, AppResponseError> {
+    self
+      .update_publish_sub_namespace_views(Method::POST, workspace_id, sub_namespace, view_ids)
+      .await
+  }
+
+  /// Moves the views of the sub-namespace back to the publish namespace of the workspace.
+  pub async fn remove_publish_sub_namespace_views(
+    &self,
+    workspace_id: &str,
+    sub_namespace: &str,
+    view_ids: Vec<uuid::Uuid>,
+  ) -> Result<PublishSubNamespace, AppResponseError> {
+    self
+      .update_publish_sub_namespace_views(Method::DELETE, workspace_id, sub_namespace, view_ids)
+      .await
+  }
+
+  async fn update_publish_sub_namespace_views(
+    &self,
+    method: Method,
+    workspace_id: &str,
+    sub_namespace: &str,
+    view_ids: Vec<uuid::Uuid>,
+  ) -> Result<PublishSubNamespace, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish-namespace/sub/{}/view",
+      self.base_url, workspace_id, sub_namespace
+    );
+    let resp = self
+      .http_client_with_auth(method, &url)
+      .await?
+      .json(&UpdatePublishSubNamespaceViews { view_ids })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishSubNamespace>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   pub async fn patch_published_collabs(
     &self,
     workspace_id: &str,
@@ -91,7 +344,7 @@ impl Client {
       .http_client_with_auth(Method::PATCH, &url)
       .await?
       .json(patches)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -107,7 +360,7 @@ impl Client {
       .http_client_with_auth(Method::DELETE, &url)
       .await?
       .json(view_ids)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -118,6 +371,25 @@ impl Client {
     view_id: &uuid::Uuid,
     comment_content: &str,
     reply_comment_id: &Option<uuid::Uuid>,
+  ) -> Result<(), AppResponseError> {
+    self
+      .create_comment_with_attachments_on_published_view(
+        view_id,
+        comment_content,
+        reply_comment_id,
+        &[],
+      )
+      .await
+  }
+
+  /// The attachments must be uploaded with [Client::upload_comment_attachment_on_published_view]
+  /// before the comment is created.
+  pub async fn create_comment_with_attachments_on_published_view(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_content: &str,
+    reply_comment_id: &Option<uuid::Uuid>,
+    attachment_ids: &[uuid::Uuid],
   ) -> Result<(), AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/comment",
@@ -129,8 +401,56 @@ impl Client {
       .json(&CreateGlobalCommentParams {
         content: comment_content.to_string(),
         reply_comment_id: *reply_comment_id,
+        attachments: attachment_ids.to_vec(),
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn upload_comment_attachment_on_published_view<T: Into<Bytes>>(
+    &self,
+    view_id: &uuid::Uuid,
+    data: T,
+    mime: &Mime,
+  ) -> Result<CommentAttachment, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/attachment",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .header(header::CONTENT_TYPE, mime.to_string())
+      .body(data.into())
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentAttachment>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Only the author can edit a comment, shortly after creating it.
+  pub async fn update_comment_on_published_view(
+    &self,
+    view_id: &uuid::Uuid,
+    comment_id: &uuid::Uuid,
+    comment_content: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdateGlobalCommentParams {
+        comment_id: *comment_id,
+        content: comment_content.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -151,12 +471,206 @@ impl Client {
       .json(&DeleteGlobalCommentParams {
         comment_id: *comment_id,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Hides, pins or deletes comments of the published view. Only the owners of the workspace the
+  /// view is published in can moderate its comments.
+  pub async fn moderate_comments_on_published_view(
+    &self,
+    view_id: &uuid::Uuid,
+    params: &ModerateCommentsParams,
+  ) -> Result<CommentModeration, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/moderation",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentModeration>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_comment_blocklist(
+    &self,
+    view_id: &uuid::Uuid,
+  ) -> Result<CommentBlocklist, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/moderation/blocklist",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentBlocklist>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Blocks the user from commenting and reacting on the views published by the workspace of the
+  /// view.
+  pub async fn block_commenter(
+    &self,
+    view_id: &uuid::Uuid,
+    user_uuid: &uuid::Uuid,
+  ) -> Result<CommentBlocklist, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/moderation/blocklist",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&CommentBlocklistParams {
+        user_uuid: *user_uuid,
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentBlocklist>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn unblock_commenter(
+    &self,
+    view_id: &uuid::Uuid,
+    user_uuid: &uuid::Uuid,
+  ) -> Result<CommentBlocklist, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/moderation/blocklist",
+      self.base_url, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .json(&CommentBlocklistParams {
+        user_uuid: *user_uuid,
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentBlocklist>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Subscribes the email to the new comments on the published view. A confirmation email is
+  /// sent to the email, the subscription is only active once confirmed.
+  pub async fn subscribe_to_published_view_comments(
+    &self,
+    view_id: &uuid::Uuid,
+    email: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/subscription",
+      self.base_url, view_id
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(&SubscribeCommentParams {
+        email: email.to_string(),
+      })
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
 
+  pub async fn confirm_published_view_comment_subscription(
+    &self,
+    view_id: &uuid::Uuid,
+    token: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/subscription/confirm",
+      self.base_url, view_id
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(&CommentSubscriptionTokenParams { token: *token })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn unsubscribe_from_published_view_comments(
+    &self,
+    view_id: &uuid::Uuid,
+    token: &uuid::Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-info/{}/comment/subscription/unsubscribe",
+      self.base_url, view_id
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(&CommentSubscriptionTokenParams { token: *token })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn get_published_view_comment_subscriber_count(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<CommentSubscriberCount, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/published-info/{}/comment/subscriber-count",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<CommentSubscriberCount>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the comment count and the latest comments of each of the views, e.g. to show the
+  /// comment badges of the folder.
+  pub async fn batch_get_view_comments(
+    &self,
+    workspace_id: &str,
+    params: &BatchCommentsParams,
+  ) -> Result<BatchComments, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/comments/batch",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<BatchComments>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   pub async fn create_reaction_on_comment(
     &self,
     reaction_type: &str,
@@ -174,7 +688,7 @@ impl Client {
         reaction_type: reaction_type.to_string(),
         comment_id: *comment_id,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -197,7 +711,7 @@ impl Client {
         reaction_type: reaction_type.to_string(),
         comment_id: *comment_id,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -216,7 +730,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&UpdateDefaultPublishView { view_id })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -233,7 +747,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -250,13 +764,81 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<PublishInfo>::from_response(resp)
       .await?
       .into_data()
   }
+
+  /// Enables or disables the automatic republish of a published document when its content
+  /// changes. Returns the publish info of the document.
+  pub async fn set_auto_publish(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    enabled: bool,
+  ) -> Result<PublishInfo, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/auto-publish",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdateAutoPublish { enabled })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishInfo>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the versions of the published view, the most recent first.
+  pub async fn list_published_view_versions(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+  ) -> Result<Vec<PublishedViewVersion>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/versions",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<PublishedViewVersion>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Serves a prior version of the published view again, as a new version. Returns the versions
+  /// of the published view.
+  pub async fn rollback_published_view(
+    &self,
+    workspace_id: &str,
+    view_id: &uuid::Uuid,
+    version: i32,
+  ) -> Result<Vec<PublishedViewVersion>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/publish/{}/rollback/{}",
+      self.base_url, workspace_id, view_id, version
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<PublishedViewVersion>>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
 
 // Optional login
@@ -264,6 +846,17 @@ impl Client {
   pub async fn get_published_view_comments(
     &self,
     view_id: &uuid::Uuid,
+  ) -> Result<GlobalComments, AppResponseError> {
+    self
+      .query_published_view_comments(view_id, &QueryGlobalComments::default())
+      .await
+  }
+
+  /// Returns a page of the comments, optionally as threads, see [QueryGlobalComments].
+  pub async fn query_published_view_comments(
+    &self,
+    view_id: &uuid::Uuid,
+    query: &QueryGlobalComments,
   ) -> Result<GlobalComments, AppResponseError> {
     let url = format!(
       "{}/api/workspace/published-info/{}/comment",
@@ -275,7 +868,10 @@ impl Client {
       self.http_client_without_auth(Method::GET, &url).await?
     };
 
-    let resp = client.send().await?;
+    let resp = client
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     log_request_id(&resp);
     AppResponse::<GlobalComments>::from_response(resp)
       .await?
@@ -292,12 +888,59 @@ impl Client {
   ) -> Result<PublishInfo, AppResponseError> {
     let url = format!("{}/api/workspace/published-info/{}", self.base_url, view_id);
 
-    let resp = self.cloud_client.get(&url).send().await?;
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     AppResponse::<PublishInfo>::from_response(resp)
       .await?
       .into_data()
   }
 
+  /// Returns the views published in the namespace, along with the views they are nested in.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_outline(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<PublishedView, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-outline/{}",
+      self.base_url, publish_namespace
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PublishedView>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the changes of the published outline since the cursor of the previous changes, in
+  /// milliseconds since the epoch.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_outline_changes(
+    &self,
+    publish_namespace: &str,
+    since: i64,
+  ) -> Result<PublishedOutlineChanges, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published-outline/{}",
+      self.base_url, publish_namespace
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .query(&QueryPublishedOutline { since: Some(since) })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PublishedOutlineChanges>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   #[instrument(level = "debug", skip_all)]
   pub async fn get_default_published_collab<T>(
     &self,
@@ -308,13 +951,14 @@ impl Client {
   {
     let url = format!(
       "{}/api/workspace/published/{}",
-      self.base_url, publish_namespace,
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
     );
 
     let resp = self
       .cloud_client
       .get(&url)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?
       .error_for_status()?;
 
@@ -340,16 +984,22 @@ impl Client {
     );
     let url = format!(
       "{}/api/workspace/v1/published/{}/{}",
-      self.base_url, publish_namespace, publish_name
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+      publish_name
     );
 
     let resp = self
       .cloud_client
       .get(&url)
-      .send()
-      .await?
-      .error_for_status()?;
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     log_request_id(&resp);
+    // The expired published views are answered with `410 Gone` and an error response.
+    let resp = match resp.status() {
+      StatusCode::GONE => resp,
+      _ => resp.error_for_status()?,
+    };
 
     AppResponse::<T>::from_response(resp).await?.into_data()
   }
@@ -367,11 +1017,20 @@ impl Client {
     );
     let url = format!(
       "{}/api/workspace/published/{}/{}/blob",
-      self.base_url, publish_namespace, publish_name
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+      publish_name
     );
-    let resp = self.cloud_client.get(&url).send().await?;
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
     log_request_id(&resp);
-    let bytes = resp.error_for_status()?.bytes().await?;
+    let bytes = match resp.status() {
+      StatusCode::GONE => resp.bytes().await?,
+      _ => resp.error_for_status()?.bytes().await?,
+    };
 
     if let Ok(app_err) = serde_json::from_slice::<AppResponseError>(&bytes) {
       return Err(app_err);
@@ -380,6 +1039,104 @@ impl Client {
     Ok(bytes)
   }
 
+  /// Returns the sitemap of the views published in the namespace, as XML.
+  /// The request doesn't require the client to be signed in.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_published_sitemap(
+    &self,
+    publish_namespace: &str,
+  ) -> Result<String, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/sitemap.xml",
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let text = resp.error_for_status()?.text().await?;
+    if let Ok(app_err) = serde_json::from_str::<AppResponseError>(&text) {
+      return Err(app_err);
+    }
+    Ok(text)
+  }
+
+  /// Returns the metadata used to render the SEO and OpenGraph tags of a published view. The
+  /// request doesn't require the client to be signed in.
+  pub async fn get_published_view_seo(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+  ) -> Result<PublishedViewSeo, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/published/{}/{}/meta/seo",
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+      publish_name
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishedViewSeo>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn patch_published_view_seo(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    patch: &PatchPublishedViewSeo,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/v1/published/{}/{}/meta/seo",
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+      publish_name
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(patch)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns a page of the rows of a published database view, filtered and sorted by the server.
+  /// The request doesn't require the client to be signed in.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn query_published_database_rows(
+    &self,
+    publish_namespace: &str,
+    publish_name: &str,
+    query: &QueryPublishedDatabaseRows,
+  ) -> Result<PublishedDatabaseRows, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/published/{}/{}/database-rows",
+      self.base_url,
+      encode_publish_namespace(publish_namespace),
+      publish_name
+    );
+    let resp = self
+      .cloud_client
+      .post(&url)
+      .json(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<PublishedDatabaseRows>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
   pub async fn duplicate_published_to_workspace(
     &self,
     workspace_id: &str,
@@ -393,7 +1150,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(publish_duplicate)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -414,7 +1171,7 @@ impl Client {
       .query(&GetReactionQueryParams {
         comment_id: *comment_id,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<Reactions>::from_response(resp)
@@ -422,3 +1179,9 @@ impl Client {
       .into_data()
   }
 }
+
+/// The sub-namespace is separated by a slash, which is encoded to keep the publish namespace in a
+/// single segment of the path, e.g. `acme/handbook` as `acme%2Fhandbook`.
+fn encode_publish_namespace(publish_namespace: &str) -> String {
+  publish_namespace.replace('/', "%2F")
+}
@@ -1,9 +1,13 @@
 use app_error::ErrorCode;
 use reqwest::Method;
-use shared_entity::dto::search_dto::SearchDocumentResponseItem;
+use shared_entity::dto::search_dto::{
+  FullTextSearchResultItem, SearchDocumentResponseItem, SemanticSearchRequest,
+  SemanticSearchResultItem,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 impl Client {
@@ -24,11 +28,59 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<Vec<SearchDocumentResponseItem>>::from_response(resp)
       .await?
       .into_data()
   }
+
+  pub async fn full_text_search(
+    &self,
+    workspace_id: &str,
+    query: &str,
+    limit: u32,
+  ) -> Result<Vec<FullTextSearchResultItem>, AppResponseError> {
+    let query = serde_urlencoded::to_string([("q", query), ("limit", &limit.to_string())])
+      .map_err(|err| AppResponseError::new(ErrorCode::InvalidRequest, err.to_string()))?;
+    let url = format!(
+      "{}/api/workspace/{workspace_id}/search?{query}",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<FullTextSearchResultItem>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn semantic_search(
+    &self,
+    workspace_id: &str,
+    query: &str,
+    limit: u32,
+  ) -> Result<Vec<SemanticSearchResultItem>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{workspace_id}/search/semantic",
+      self.base_url
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(&SemanticSearchRequest {
+        query: query.to_string(),
+        limit: Some(limit),
+      })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<Vec<SemanticSearchResultItem>>::from_response(resp)
+      .await?
+      .into_data()
+  }
 }
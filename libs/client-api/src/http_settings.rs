@@ -2,10 +2,15 @@ use reqwest::Method;
 use tracing::{instrument, trace};
 
 use client_api_entity::AFWorkspaceSettings;
+use shared_entity::dto::workspace_dto::{
+  AddWorkspaceDomainParams, CollabSecretFinding, UpdateWorkspacePublicAccess,
+  UpdateWorkspaceSamlConfigParams, WorkspaceDomain, WorkspacePublicAccess, WorkspaceSamlConfig,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::entity::AFWorkspaceSettingsChange;
 use crate::http::log_request_id;
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 impl Client {
@@ -22,7 +27,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     let resp = AppResponse::<AFWorkspaceSettings>::from_response(resp).await?;
@@ -45,10 +50,229 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&changes)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     let resp = AppResponse::<AFWorkspaceSettings>::from_response(resp).await?;
     resp.into_data()
   }
+
+  /// Returns the documents flagged by the secret scanning of the workspace. Only the owner of the
+  /// workspace can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_secret_findings<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+  ) -> Result<Vec<CollabSecretFinding>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/secret-finding",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<Vec<CollabSecretFinding>>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_public_access<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+  ) -> Result<WorkspacePublicAccess, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/public-access",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspacePublicAccess>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  /// Makes the whole workspace readable by anyone, without signing in, or private again. Only the
+  /// owner of the workspace can change it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn set_workspace_public_access<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    enabled: bool,
+  ) -> Result<WorkspacePublicAccess, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/public-access",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(&UpdateWorkspacePublicAccess { enabled })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspacePublicAccess>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  /// Returns the SAML identity provider of the workspace, along with the URLs it has to be set up
+  /// with. Only the owner of the workspace can get it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn get_workspace_saml_config<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+  ) -> Result<WorkspaceSamlConfig, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/saml",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspaceSamlConfig>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  /// Lets the members of the workspace sign in with a SAML identity provider. Only the owner of
+  /// the workspace can set it.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn update_workspace_saml_config<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    params: &UpdateWorkspaceSamlConfigParams,
+  ) -> Result<WorkspaceSamlConfig, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/saml",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspaceSamlConfig>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_saml_config<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/saml",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Returns the email domains of the workspace. Only the owner of the workspace can list them.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn list_workspace_domains<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+  ) -> Result<Vec<WorkspaceDomain>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/domain",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<Vec<WorkspaceDomain>>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  /// Adds an unverified email domain to the workspace. It's verified by
+  /// [Self::verify_workspace_domain] once its DNS has the returned TXT record.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn add_workspace_domain<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    params: &AddWorkspaceDomainParams,
+  ) -> Result<WorkspaceDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/domain",
+      self.base_url,
+      workspace_id.as_ref()
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspaceDomain>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn verify_workspace_domain<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    domain: &str,
+  ) -> Result<WorkspaceDomain, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/domain/{}/verify",
+      self.base_url,
+      workspace_id.as_ref(),
+      domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let resp = AppResponse::<WorkspaceDomain>::from_response(resp).await?;
+    resp.into_data()
+  }
+
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn delete_workspace_domain<T: AsRef<str>>(
+    &self,
+    workspace_id: T,
+    domain: &str,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/domain/{}",
+      self.base_url,
+      workspace_id.as_ref(),
+      domain
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
 }
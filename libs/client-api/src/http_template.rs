@@ -1,6 +1,8 @@
 use client_api_entity::{
   AccountLink, CreateTemplateCategoryParams, CreateTemplateCreatorParams, CreateTemplateParams,
-  GetTemplateCategoriesQueryParams, GetTemplateCreatorsQueryParams, GetTemplatesQueryParams,
+  GetMarketplaceTemplatesQueryParams, GetTemplateCategoriesQueryParams,
+  GetTemplateCreatorsQueryParams, GetTemplatesQueryParams, InstallMarketplaceTemplateParams,
+  MarketplaceTemplateWithPublishInfo, MarketplaceTemplates, PublishMarketplaceTemplateParams,
   Template, TemplateCategories, TemplateCategory, TemplateCategoryType, TemplateCreator,
   TemplateCreators, TemplateWithPublishInfo, Templates, UpdateTemplateCategoryParams,
   UpdateTemplateCreatorParams, UpdateTemplateParams,
@@ -9,6 +11,7 @@ use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use uuid::Uuid;
 
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 fn template_api_prefix(base_url: &str) -> String {
@@ -43,6 +46,14 @@ fn template_resource_url(base_url: &str, view_id: Uuid) -> String {
   format!("{}/{}", template_resources_url(base_url), view_id)
 }
 
+fn marketplace_resources_url(base_url: &str) -> String {
+  format!("{}/api/templates/marketplace", base_url)
+}
+
+fn marketplace_resource_url(base_url: &str, view_id: Uuid) -> String {
+  format!("{}/{}", marketplace_resources_url(base_url), view_id)
+}
+
 impl Client {
   pub async fn create_template_category(
     &self,
@@ -53,7 +64,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<TemplateCategory>::from_response(resp)
@@ -74,7 +85,7 @@ impl Client {
         name_contains: name_contains.map(|s| s.to_string()),
         category_type,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<TemplateCategories>::from_response(resp)
       .await?
@@ -89,7 +100,7 @@ impl Client {
     let resp = self
       .http_client_without_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<TemplateCategory>::from_response(resp)
       .await?
@@ -101,7 +112,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -116,7 +127,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<TemplateCategory>::from_response(resp)
@@ -139,7 +150,7 @@ impl Client {
         avatar_url: avatar_url.to_string(),
         account_links,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<TemplateCreator>::from_response(resp)
@@ -158,7 +169,7 @@ impl Client {
       .query(&GetTemplateCreatorsQueryParams {
         name_contains: name_contains.map(|s| s.to_string()),
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<TemplateCreators>::from_response(resp)
       .await?
@@ -173,7 +184,7 @@ impl Client {
     let resp = self
       .http_client_without_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<TemplateCreator>::from_response(resp)
       .await?
@@ -185,7 +196,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -206,7 +217,7 @@ impl Client {
         avatar_url: avatar_url.to_string(),
         account_links,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<TemplateCreator>::from_response(resp)
@@ -223,7 +234,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Template>::from_response(resp)
@@ -239,7 +250,7 @@ impl Client {
     let resp = self
       .http_client_without_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<TemplateWithPublishInfo>::from_response(resp)
@@ -264,7 +275,7 @@ impl Client {
         is_new_template,
         name_contains,
       })
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Templates>::from_response(resp)
@@ -282,7 +293,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<Template>::from_response(resp)
@@ -295,7 +306,93 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::DELETE, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Lists the published view in the template marketplace, or updates its listing.
+  pub async fn publish_marketplace_template(
+    &self,
+    params: &PublishMarketplaceTemplateParams,
+  ) -> Result<MarketplaceTemplateWithPublishInfo, AppResponseError> {
+    let url = marketplace_resources_url(&self.base_url);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+
+    AppResponse::<MarketplaceTemplateWithPublishInfo>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_marketplace_templates(
+    &self,
+    query: &GetMarketplaceTemplatesQueryParams,
+  ) -> Result<MarketplaceTemplates, AppResponseError> {
+    let url = marketplace_resources_url(&self.base_url);
+    let resp = self
+      .http_client_without_auth(Method::GET, &url)
+      .await?
+      .query(query)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+
+    AppResponse::<MarketplaceTemplates>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_marketplace_template(
+    &self,
+    view_id: Uuid,
+  ) -> Result<MarketplaceTemplateWithPublishInfo, AppResponseError> {
+    let url = marketplace_resource_url(&self.base_url, view_id);
+    let resp = self
+      .http_client_without_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+
+    AppResponse::<MarketplaceTemplateWithPublishInfo>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Removes the view from the template marketplace, the view stays published.
+  pub async fn unpublish_marketplace_template(
+    &self,
+    view_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = marketplace_resource_url(&self.base_url, view_id);
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Instantiates the template of the marketplace into the workspace, under the view.
+  pub async fn install_marketplace_template(
+    &self,
+    view_id: Uuid,
+    params: &InstallMarketplaceTemplateParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/install",
+      marketplace_resource_url(&self.base_url, view_id)
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -1,8 +1,17 @@
-use client_api_entity::workspace_dto::{CreatePageParams, Page, PageCollab};
+use client_api_entity::workspace_dto::{
+  AppendBlockToPageParams, BatchCreatePageParams, BatchCreatePageResult, CollabExportFormat,
+  CreatePageParams, CreatePageShareLinkParams, CreatePageTemplateParams, ImportPageFormat,
+  ImportPageParams, Page, PageCollab, PageOperationsParams, PageOperationsResult, PageShareLink,
+  PageTemplate, PageViewContent, PatchPageParams, QueryCollabExport, ReorderPageParams,
+  RevokeShareLinksQuery, RevokedShareLinks, SharedPage, UpdatePageTemplateParams,
+  WorkspaceShareLinks,
+};
+use client_api_entity::{AFViewPermissions, UpdateViewPermissionsParams};
 use reqwest::Method;
 use shared_entity::response::{AppResponse, AppResponseError};
 use uuid::Uuid;
 
+use crate::retry_policy::RetryableRequest;
 use crate::Client;
 
 impl Client {
@@ -16,7 +25,92 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<Page>::from_response(resp).await?.into_data()
+  }
+
+  /// Applies the operations atomically: either all of them are saved, or none is.
+  pub async fn apply_workspace_page_operations(
+    &self,
+    workspace_id: Uuid,
+    params: &PageOperationsParams,
+  ) -> Result<PageOperationsResult, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/operations",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageOperationsResult>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates the tree of pages in a single transaction. Returns the id of the view created for each
+  /// page, by its client id.
+  pub async fn batch_create_workspace_page_views(
+    &self,
+    workspace_id: Uuid,
+    params: &BatchCreatePageParams,
+  ) -> Result<BatchCreatePageResult, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/batch",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<BatchCreatePageResult>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates a document page from the Markdown content. The name of the page defaults to the
+  /// first heading of the content.
+  pub async fn import_page_from_markdown(
+    &self,
+    workspace_id: Uuid,
+    parent_view_id: &str,
+    name: Option<&str>,
+    markdown: &str,
+  ) -> Result<Page, AppResponseError> {
+    self
+      .import_page(
+        workspace_id,
+        &ImportPageParams {
+          parent_view_id: parent_view_id.to_string(),
+          name: name.map(|name| name.to_string()),
+          format: ImportPageFormat::Markdown,
+          content: markdown.to_string(),
+          decryption: None,
+        },
+      )
+      .await
+  }
+
+  /// Creates a document page from Markdown or HTML content.
+  pub async fn import_page(
+    &self,
+    workspace_id: Uuid,
+    params: &ImportPageParams,
+  ) -> Result<Page, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/import/page",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<Page>::from_response(resp).await?.into_data()
   }
@@ -33,10 +127,378 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<PageCollab>::from_response(resp)
       .await?
       .into_data()
   }
+
+  /// Returns the document of the page converted to Markdown or plain text.
+  pub async fn get_workspace_page_view_content(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    format: CollabExportFormat,
+  ) -> Result<PageViewContent, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/content",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .query(&QueryCollabExport { format })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageViewContent>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Creates a link sharing the document page with anyone who has it.
+  pub async fn create_workspace_page_share_link(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    params: &CreatePageShareLinkParams,
+  ) -> Result<PageShareLink, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/share-link",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageShareLink>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn list_workspace_page_share_links(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+  ) -> Result<Vec<PageShareLink>, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/share-link",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<Vec<PageShareLink>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn revoke_workspace_page_share_link(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    link_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/share-link/{}",
+      self.base_url, workspace_id, view_id, link_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Lists the share links of the pages and the share tokens of the collabs of the workspace.
+  pub async fn list_workspace_share_links(
+    &self,
+    workspace_id: Uuid,
+  ) -> Result<WorkspaceShareLinks, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/share-link",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<WorkspaceShareLinks>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Revokes the share links and share tokens of the workspace, only the ones created by the
+  /// user if given.
+  pub async fn revoke_workspace_share_links(
+    &self,
+    workspace_id: Uuid,
+    created_by: Option<i64>,
+  ) -> Result<RevokedShareLinks, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/share-link",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .query(&RevokeShareLinksQuery { created_by })
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<RevokedShareLinks>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_workspace_page_view_permissions(
+    &self,
+    workspace_id: Uuid,
+    view_id: &str,
+  ) -> Result<AFViewPermissions, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/permission",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<AFViewPermissions>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Restricts the page to the given members and groups, or lifts the restriction if no
+  /// permission is given.
+  pub async fn update_workspace_page_view_permissions(
+    &self,
+    workspace_id: Uuid,
+    view_id: &str,
+    params: &UpdateViewPermissionsParams,
+  ) -> Result<AFViewPermissions, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/permission",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PUT, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<AFViewPermissions>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Returns the page shared by the link of the token. The request doesn't require the client to
+  /// be signed in.
+  pub async fn get_shared_page(&self, token: Uuid) -> Result<SharedPage, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/public/share-link/{}",
+      self.base_url, token
+    );
+    let resp = self
+      .cloud_client
+      .get(&url)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<SharedPage>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Renames, changes the icon of, or moves the view of the page in the folder.
+  pub async fn update_workspace_page_view(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    params: &PatchPageParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Moves the view of the page, along with its descendants, to the trash.
+  pub async fn move_workspace_page_view_to_trash(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Moves the view of the page right after or right before one of its siblings.
+  pub async fn reorder_workspace_page_view(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    params: &ReorderPageParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/reorder",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Appends the blocks to the end of the document of the page.
+  pub async fn append_block_to_workspace_page_view(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+    params: &AppendBlockToPageParams,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/append-block",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  pub async fn list_workspace_page_templates(
+    &self,
+    workspace_id: Uuid,
+  ) -> Result<Vec<PageTemplate>, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/template", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<Vec<PageTemplate>>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  /// Saves a copy of the document as a template, new pages can be created from it with the
+  /// `template_id` of [CreatePageParams].
+  pub async fn create_workspace_page_template(
+    &self,
+    workspace_id: Uuid,
+    params: &CreatePageTemplateParams,
+  ) -> Result<PageTemplate, AppResponseError> {
+    let url = format!("{}/api/workspace/{}/template", self.base_url, workspace_id);
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageTemplate>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn get_workspace_page_template(
+    &self,
+    workspace_id: Uuid,
+    template_id: Uuid,
+  ) -> Result<PageTemplate, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/template/{}",
+      self.base_url, workspace_id, template_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::GET, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageTemplate>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn update_workspace_page_template(
+    &self,
+    workspace_id: Uuid,
+    template_id: Uuid,
+    params: &UpdatePageTemplateParams,
+  ) -> Result<PageTemplate, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/template/{}",
+      self.base_url, workspace_id, template_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::PATCH, &url)
+      .await?
+      .json(params)
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<PageTemplate>::from_response(resp)
+      .await?
+      .into_data()
+  }
+
+  pub async fn delete_workspace_page_template(
+    &self,
+    workspace_id: Uuid,
+    template_id: Uuid,
+  ) -> Result<(), AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/template/{}",
+      self.base_url, workspace_id, template_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::DELETE, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<()>::from_response(resp).await?.into_error()
+  }
+
+  /// Duplicates the page, along with its descendants, and returns the view of the copy.
+  pub async fn duplicate_workspace_page_view(
+    &self,
+    workspace_id: Uuid,
+    view_id: Uuid,
+  ) -> Result<Page, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/page-view/{}/duplicate",
+      self.base_url, workspace_id, view_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    AppResponse::<Page>::from_response(resp).await?.into_data()
+  }
 }
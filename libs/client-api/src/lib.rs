@@ -1,8 +1,10 @@
+mod collab_etag;
 mod http;
 mod http_ai;
 mod http_billing;
 
 mod http_access_request;
+mod http_announcement;
 mod http_blob;
 mod http_collab;
 mod http_history;
@@ -10,7 +12,9 @@ mod http_member;
 mod http_publish;
 mod http_template;
 mod http_view;
+mod retry_policy;
 pub use http::*;
+pub use retry_policy::{RetryOn, RetryPolicy};
 
 #[cfg(feature = "collab-sync")]
 pub mod collab_sync;
@@ -1,5 +1,6 @@
 use crate::http::log_request_id;
 use crate::native::GetCollabAction;
+use crate::retry_policy::RetryableRequest;
 use crate::ws::{ConnectInfo, WSClientConnectURLProvider, WSClientHttpSender, WSError};
 use crate::{blocking_brotli_compress, brotli_compress, Client};
 use crate::{RefreshTokenAction, RefreshTokenRetryCondition};
@@ -9,15 +10,16 @@ use async_trait::async_trait;
 use rayon::iter::ParallelIterator;
 use std::fs::metadata;
 
-use bytes::Bytes;
+use bytes::{Buf, Bytes, BytesMut};
 use client_api_entity::{
-  CollabParams, CreateImportTask, CreateImportTaskResponse, PublishCollabItem, QueryCollabParams,
+  BatchQueryCollabItem, BatchQueryCollabPage, BatchQueryCollabParams, CollabParams,
+  CreateImportTask, CreateImportTaskResponse, PublishCollabItem, QueryCollab, QueryCollabParams,
 };
 use client_api_entity::{
   CompleteUploadRequest, CreateUploadRequest, CreateUploadResponse, UploadPartResponse,
 };
-use collab_rt_entity::HttpRealtimeMessage;
-use futures::Stream;
+use collab_rt_entity::{HttpRealtimeMessage, REALTIME_PROTOCOL_VERSION};
+use futures::{ready, Stream};
 use futures_util::stream;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use prost::Message;
@@ -57,7 +59,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::answer_response_stream(resp).await
@@ -77,7 +79,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .json(&req)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<CreateUploadResponse>::from_response(resp)
@@ -114,7 +116,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .body(body)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<UploadPartResponse>::from_response(resp)
@@ -135,7 +137,7 @@ impl Client {
       .http_client_with_auth(Method::PUT, &url)
       .await?
       .json(&req)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -163,14 +165,19 @@ impl Client {
     let payload =
       blocking_brotli_compress(msg.into_data(), 6, self.config.compression_buffer_size).await?;
 
-    let msg = HttpRealtimeMessage { device_id, payload }.encode_to_vec();
+    let msg = HttpRealtimeMessage {
+      device_id,
+      payload,
+      protocol_version: REALTIME_PROTOCOL_VERSION,
+    }
+    .encode_to_vec();
     let body = Body::wrap_stream(stream::iter(vec![Ok::<_, reqwest::Error>(msg)]));
     let url = format!("{}/api/realtime/post/stream", self.base_url);
     let resp = self
       .http_client_with_auth_compress(Method::POST, &url)
       .await?
       .body(body)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     crate::http::log_request_id(&resp);
     AppResponse::<()>::from_response(resp).await?.into_error()
@@ -221,7 +228,7 @@ impl Client {
       .await?
       .timeout(Duration::from_secs(60))
       .body(body)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -284,6 +291,31 @@ impl Client {
     }
   }
 
+  /// Streams the collabs of the batch query instead of buffering them, the items are yielded as
+  /// soon as they are received. Pass a page to only run a part of the queries.
+  #[instrument(level = "info", skip_all, err)]
+  pub async fn batch_get_collab_stream(
+    &self,
+    workspace_id: &str,
+    params: Vec<QueryCollab>,
+    page: Option<BatchQueryCollabPage>,
+  ) -> Result<BatchQueryCollabStream, AppResponseError> {
+    let url = format!(
+      "{}/api/workspace/{}/collab_list/stream",
+      self.base_url, workspace_id
+    );
+    let resp = self
+      .http_client_with_auth(Method::POST, &url)
+      .await?
+      .query(&page.unwrap_or_default())
+      .json(&BatchQueryCollabParams(params))
+      .send_with_retry(&self.config.retry_policy)
+      .await?;
+    log_request_id(&resp);
+    let stream = AppResponse::<()>::answer_response_stream(resp).await?;
+    Ok(BatchQueryCollabStream::new(stream))
+  }
+
   pub async fn publish_collabs<Metadata, Data>(
     &self,
     workspace_id: &str,
@@ -299,7 +331,7 @@ impl Client {
       .http_client_with_auth(Method::POST, &url)
       .await?
       .body(Body::wrap_stream(publish_collab_stream))
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -361,7 +393,7 @@ impl Client {
       .header("X-Host", self.base_url.clone())
       .header("X-Content-MD5", md5_base64)
       .header("X-Content-Length", metadata.len());
-    let resp = builder.send().await?;
+    let resp = builder.send_with_retry(&self.config.retry_policy).await?;
 
     AppResponse::<()>::from_response(resp).await?.into_error()
   }
@@ -395,7 +427,7 @@ impl Client {
       .await?
       .header("X-Host", self.base_url.clone())
       .json(&params)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     log_request_id(&resp);
@@ -430,7 +462,7 @@ impl Client {
       .header("Content-Length", file_size)
       .header("Content-Type", "application/zip")
       .body(stream_body)
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     if !upload_resp.status().is_success() {
@@ -446,7 +478,7 @@ impl Client {
     let resp = self
       .http_client_with_auth(Method::GET, &url)
       .await?
-      .send()
+      .send_with_retry(&self.config.retry_policy)
       .await?;
 
     AppResponse::<UserImportTask>::from_response(resp)
@@ -538,6 +570,63 @@ where
   }
 }
 
+/// Decodes the length-prefixed frames of a streamed batch query into its items.
+pub struct BatchQueryCollabStream {
+  stream: Pin<Box<dyn Stream<Item = Result<Bytes, AppResponseError>> + Send>>,
+  buffer: BytesMut,
+}
+
+impl BatchQueryCollabStream {
+  pub fn new<S>(stream: S) -> Self
+  where
+    S: Stream<Item = Result<Bytes, AppResponseError>> + Send + 'static,
+  {
+    BatchQueryCollabStream {
+      stream: Box::pin(stream),
+      buffer: BytesMut::new(),
+    }
+  }
+
+  fn next_frame(&mut self) -> Option<BytesMut> {
+    if self.buffer.len() < 4 {
+      return None;
+    }
+    let mut len = [0; 4];
+    len.copy_from_slice(&self.buffer[..4]);
+    let len = u32::from_le_bytes(len) as usize;
+    if self.buffer.len() < 4 + len {
+      return None;
+    }
+    self.buffer.advance(4);
+    Some(self.buffer.split_to(len))
+  }
+}
+
+impl Stream for BatchQueryCollabStream {
+  type Item = Result<BatchQueryCollabItem, AppResponseError>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    loop {
+      if let Some(frame) = this.next_frame() {
+        let item = BatchQueryCollabItem::from_bytes(&frame).map_err(AppError::from);
+        return Poll::Ready(Some(item.map_err(AppResponseError::from)));
+      }
+      match ready!(this.stream.as_mut().poll_next(cx)) {
+        Some(Ok(bytes)) => this.buffer.extend_from_slice(&bytes),
+        Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+        None if this.buffer.is_empty() => return Poll::Ready(None),
+        None => {
+          this.buffer.clear();
+          return Poll::Ready(Some(Err(AppResponseError::from(AppError::Internal(
+            anyhow!("The batch query stream ended in the middle of a frame"),
+          )))));
+        },
+      }
+    }
+  }
+}
+
 fn serialize_metadata_data<Metadata>(m: Metadata, d: &[u8]) -> Result<Bytes, std::io::Error>
 where
   Metadata: Serialize,
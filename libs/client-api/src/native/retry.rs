@@ -1,4 +1,3 @@
-use crate::http::log_request_id;
 use crate::notify::ClientToken;
 use crate::ws::{
   ConnectState, ConnectStateNotify, StateNotify, WSClientConnectURLProvider, WSError,
@@ -10,9 +9,8 @@ use client_websocket::{connect_async, WebSocketStream};
 use gotrue::grant::{Grant, RefreshTokenGrant};
 use parking_lot::RwLock;
 use reqwest::header::HeaderMap;
-use reqwest::Method;
-use shared_entity::dto::workspace_dto::{CollabResponse, CollabTypeParam};
-use shared_entity::response::{AppResponse, AppResponseError};
+use shared_entity::dto::workspace_dto::CollabResponse;
+use shared_entity::response::AppResponseError;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc, Weak};
@@ -163,22 +161,11 @@ impl Action for GetCollabAction {
   fn run(&mut self) -> Self::Future {
     let client = self.client.clone();
     let params = self.params.clone();
-    let collab_type = self.params.collab_type.clone();
 
-    Box::pin(async move {
-      let url = format!(
-        "{}/api/workspace/v1/{}/collab/{}",
-        client.base_url, &params.workspace_id, &params.object_id
-      );
-      let resp = client
-        .http_client_with_auth(Method::GET, &url)
-        .await?
-        .query(&CollabTypeParam { collab_type })
-        .send()
-        .await?;
-      log_request_id(&resp);
-      let resp = AppResponse::<CollabResponse>::from_response(resp).await?;
-      resp.into_data()
-    })
+    Box::pin(async move { client.fetch_collab(&params).await })
   }
 }
+
+pub(crate) async fn retry_sleep(duration: Duration) {
+  tokio::time::sleep(duration).await;
+}
@@ -0,0 +1,223 @@
+use std::error::Error;
+use std::io;
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use tracing::debug;
+
+use crate::retry_sleep;
+
+/// The failures a request can be retried on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryOn {
+  /// `429 Too Many Requests`. The `Retry-After` of the response is waited for when it is longer
+  /// than the backoff, and the request isn't retried when it is longer than the maximum backoff.
+  TooManyRequests,
+  /// `502 Bad Gateway`, `503 Service Unavailable` and `504 Gateway Timeout`, usually returned by
+  /// a proxy while the server restarts.
+  ServiceUnavailable,
+  /// The connection couldn't be established, or was reset. The requests that aren't idempotent
+  /// are only retried when the connection couldn't be established, as the server may have handled
+  /// them otherwise.
+  ConnectionError,
+  /// The request timed out. Only the idempotent requests are retried.
+  Timeout,
+}
+
+/// How the requests of the [crate::Client] are retried. The backoff grows exponentially from the
+/// initial backoff up to the maximum backoff, with a random jitter so that the clients failing at
+/// the same time don't retry at the same time.
+///
+/// The requests whose body is streamed can't be sent again, so they are never retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  /// The number of times a request is sent at most, including the first time.
+  pub(crate) max_attempts: u32,
+  pub(crate) initial_backoff: Duration,
+  pub(crate) max_backoff: Duration,
+  pub(crate) retry_on: Vec<RetryOn>,
+}
+
+impl RetryPolicy {
+  /// Sends the requests once, which is what the [crate::ClientConfiguration] does by default.
+  pub fn none() -> Self {
+    Self {
+      max_attempts: 1,
+      ..Default::default()
+    }
+  }
+
+  pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+    self.max_attempts = max_attempts.max(1);
+    self
+  }
+
+  pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+    self.initial_backoff = initial_backoff;
+    self.max_backoff = max_backoff.max(initial_backoff);
+    self
+  }
+
+  pub fn with_retry_on(mut self, retry_on: Vec<RetryOn>) -> Self {
+    self.retry_on = retry_on;
+    self
+  }
+
+  fn retries_on(&self, retry_on: RetryOn) -> bool {
+    self.retry_on.contains(&retry_on)
+  }
+
+  /// The backoff before the given retry, starting at 1, with an equal jitter: between half of
+  /// the exponential backoff and the whole of it.
+  fn backoff(&self, retry: u32) -> Duration {
+    let backoff = self
+      .initial_backoff
+      .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+      .min(self.max_backoff);
+    let half = backoff / 2;
+    half + half.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+  }
+
+  /// The backoff before the given retry on the response, `None` if the response isn't retried.
+  fn response_backoff(&self, resp: &Response, retry: u32) -> Option<Duration> {
+    match resp.status() {
+      StatusCode::TOO_MANY_REQUESTS if self.retries_on(RetryOn::TooManyRequests) => {
+        let backoff = self.backoff(retry);
+        match retry_after(resp) {
+          Some(retry_after) if retry_after > self.max_backoff => None,
+          Some(retry_after) => Some(backoff.max(retry_after)),
+          None => Some(backoff),
+        }
+      },
+      StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        if self.retries_on(RetryOn::ServiceUnavailable) =>
+      {
+        Some(self.backoff(retry))
+      },
+      _ => None,
+    }
+  }
+
+  fn is_retryable_error(&self, err: &reqwest::Error, is_idempotent: bool) -> bool {
+    if err.is_timeout() {
+      return is_idempotent && self.retries_on(RetryOn::Timeout);
+    }
+    if !self.retries_on(RetryOn::ConnectionError) {
+      return false;
+    }
+    is_connect_error(err) || (is_idempotent && is_connection_reset(err))
+  }
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(200),
+      max_backoff: Duration::from_secs(5),
+      retry_on: vec![
+        RetryOn::TooManyRequests,
+        RetryOn::ServiceUnavailable,
+        RetryOn::ConnectionError,
+        RetryOn::Timeout,
+      ],
+    }
+  }
+}
+
+pub(crate) trait RetryableRequest {
+  /// Sends the request, and sends it again on the failures the policy retries on.
+  async fn send_with_retry(self, policy: &RetryPolicy) -> reqwest::Result<Response>;
+}
+
+impl RetryableRequest for RequestBuilder {
+  async fn send_with_retry(self, policy: &RetryPolicy) -> reqwest::Result<Response> {
+    if policy.max_attempts <= 1 {
+      return self.send().await;
+    }
+    let is_idempotent = self
+      .try_clone()
+      .and_then(|request| request.build().ok())
+      .map(|request| is_idempotent(request.method()))
+      .unwrap_or(false);
+
+    let mut request = self;
+    let mut retry = 1;
+    loop {
+      let next_request = match request.try_clone() {
+        Some(next_request) if retry < policy.max_attempts => next_request,
+        _ => return request.send().await,
+      };
+      let backoff = match request.send().await {
+        Ok(resp) => match policy.response_backoff(&resp, retry) {
+          Some(backoff) => {
+            debug!(
+              "retry request after {:?}, status: {}",
+              backoff,
+              resp.status()
+            );
+            backoff
+          },
+          None => return Ok(resp),
+        },
+        Err(err) if policy.is_retryable_error(&err, is_idempotent) => {
+          let backoff = policy.backoff(retry);
+          debug!("retry request after {:?}, error: {}", backoff, err);
+          backoff
+        },
+        Err(err) => return Err(err),
+      };
+      retry_sleep(backoff).await;
+      request = next_request;
+      retry += 1;
+    }
+  }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+  matches!(
+    *method,
+    Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+  )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect_error(err: &reqwest::Error) -> bool {
+  err.is_connect()
+}
+
+/// The fetch API doesn't tell why a request failed, so a request without a response is deemed to
+/// have failed to connect.
+#[cfg(target_arch = "wasm32")]
+fn is_connect_error(err: &reqwest::Error) -> bool {
+  err.is_request()
+}
+
+fn is_connection_reset(err: &reqwest::Error) -> bool {
+  let mut source = err.source();
+  while let Some(err) = source {
+    if let Some(io_err) = err.downcast_ref::<io::Error>() {
+      return matches!(
+        io_err.kind(),
+        io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+      );
+    }
+    source = err.source();
+  }
+  false
+}
+
+/// The `Retry-After` of the response, only its delay in seconds form is supported.
+fn retry_after(resp: &Response) -> Option<Duration> {
+  resp
+    .headers()
+    .get(RETRY_AFTER)?
+    .to_str()
+    .ok()?
+    .trim()
+    .parse::<u64>()
+    .ok()
+    .map(Duration::from_secs)
+}
@@ -1,4 +1,3 @@
-use crate::http::log_request_id;
 use crate::ws::{ConnectInfo, WSClientConnectURLProvider, WSClientHttpSender, WSError};
 use crate::Client;
 use app_error::gotrue::GoTrueError;
@@ -6,9 +5,8 @@ use app_error::ErrorCode;
 use async_trait::async_trait;
 use client_api_entity::{CollabParams, QueryCollabParams};
 use gotrue::grant::{Grant, RefreshTokenGrant};
-use reqwest::Method;
-use shared_entity::dto::workspace_dto::{CollabResponse, CollabTypeParam};
-use shared_entity::response::{AppResponse, AppResponseError};
+use shared_entity::dto::workspace_dto::CollabResponse;
+use shared_entity::response::AppResponseError;
 use std::future::Future;
 use std::sync::atomic::Ordering;
 use tracing::{info, instrument};
@@ -31,20 +29,7 @@ impl Client {
     &self,
     params: QueryCollabParams,
   ) -> Result<CollabResponse, AppResponseError> {
-    let url = format!(
-      "{}/api/workspace/v1/{}/collab/{}",
-      self.base_url, &params.workspace_id, &params.object_id
-    );
-    let collab_type = params.collab_type.clone();
-    let resp = self
-      .http_client_with_auth(Method::GET, &url)
-      .await?
-      .query(&CollabTypeParam { collab_type })
-      .send()
-      .await?;
-    log_request_id(&resp);
-    let resp = AppResponse::<CollabResponse>::from_response(resp).await?;
-    resp.into_data()
+    self.fetch_collab(&params).await
   }
 
   #[instrument(level = "debug", skip_all, err)]
@@ -22,3 +22,7 @@ pub async fn retry_connect(
   let stream = connect_async(url, headers).await?;
   Ok(stream)
 }
+
+pub(crate) async fn retry_sleep(duration: std::time::Duration) {
+  let _ = wasm_timer::Delay::new(duration).await;
+}
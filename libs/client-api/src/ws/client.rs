@@ -18,7 +18,10 @@ use client_websocket::{CloseCode, CloseFrame, Message, WebSocketStream};
 use collab_rt_entity::user::UserMessage;
 use collab_rt_entity::ClientCollabMessage;
 use collab_rt_entity::ServerCollabMessage;
-use collab_rt_entity::{RealtimeMessage, SystemMessage};
+use collab_rt_entity::{
+  RealtimeMessage, RowEditIntent, SystemMessage, REALTIME_PROTOCOL_VERSION,
+  REALTIME_PROTOCOL_VERSION_HEADER,
+};
 
 use crate::ws::msg_queue::{AggregateMessageQueue, AggregateMessagesReceiver};
 use crate::ws::{ConnectState, ConnectStateNotify, WSError, WebSocketChannel};
@@ -303,7 +306,9 @@ impl WSClient {
                 RealtimeMessage::ServerCollabV1(collab_messages) => {
                   handle_collab_message(&weak_collab_channels, collab_messages);
                 },
-                RealtimeMessage::ClientCollabV1(_) | RealtimeMessage::ClientCollabV2(_) => {
+                RealtimeMessage::ClientCollabV1(_)
+                | RealtimeMessage::ClientCollabV2(_)
+                | RealtimeMessage::RowEditIntent(_) => {
                   // The message from server should not be collab message.
                   error!(
                     "received unexpected collab message from websocket: {:?}",
@@ -405,6 +410,13 @@ impl WSClient {
     Ok(())
   }
 
+  /// Tells the server that the user starts or stops editing a row of a database. The intent must
+  /// be sent again before it expires if the user keeps editing the row.
+  pub fn send_row_edit_intent(&self, intent: RowEditIntent) -> Result<(), WSError> {
+    let data = RealtimeMessage::RowEditIntent(intent).encode()?;
+    self.send(Message::Binary(data))
+  }
+
   pub fn get_state(&self) -> ConnectState {
     self.state_notify.lock().state.clone()
   }
@@ -505,6 +517,10 @@ impl From<ConnectInfo> for HeaderMap {
       HeaderValue::from_str(&info.client_version.to_string())
         .unwrap_or(HeaderValue::from_static("unknown_client")),
     );
+    headers.insert(
+      "client-platform",
+      HeaderValue::from_static(std::env::consts::OS),
+    );
     headers.insert(
       AUTHORIZATION,
       HeaderValue::from_str(&info.access_token).unwrap_or(HeaderValue::from_static("")),
@@ -513,6 +529,10 @@ impl From<ConnectInfo> for HeaderMap {
       "connect-at",
       HeaderValue::from(chrono::Utc::now().timestamp()),
     );
+    headers.insert(
+      REALTIME_PROTOCOL_VERSION_HEADER,
+      HeaderValue::from(REALTIME_PROTOCOL_VERSION),
+    );
     headers
   }
 }
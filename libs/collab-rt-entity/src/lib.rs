@@ -2,6 +2,7 @@ mod message;
 pub mod user;
 
 mod client_message;
+mod protocol;
 // If the realtime_proto not exist, the following code will be generated:
 // ```shell
 //  cd libs/collab-rt-entity
@@ -13,5 +14,6 @@ mod server_message;
 
 pub use client_message::*;
 pub use message::*;
+pub use protocol::*;
 pub use realtime_proto::*;
 pub use server_message::*;
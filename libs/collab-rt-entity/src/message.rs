@@ -42,6 +42,7 @@ pub enum RealtimeMessage {
   ClientCollabV1(Vec<ClientCollabMessage>),
   ClientCollabV2(MessageByObjectId),
   ServerCollabV1(Vec<ServerCollabMessage>),
+  RowEditIntent(RowEditIntent),
 }
 
 impl RealtimeMessage {
@@ -56,6 +57,7 @@ impl RealtimeMessage {
         .map(|(_, value)| value.iter().map(|v| v.size()).sum::<usize>())
         .sum(),
       RealtimeMessage::ServerCollabV1(msgs) => msgs.iter().map(|msg| msg.size()).sum(),
+      RealtimeMessage::RowEditIntent(_) => 1,
     }
   }
 
@@ -152,10 +154,30 @@ impl Display for RealtimeMessage {
       RealtimeMessage::ClientCollabV1(_) => f.write_fmt(format_args!("ClientCollabV1")),
       RealtimeMessage::ClientCollabV2(_) => f.write_fmt(format_args!("ClientCollabV2")),
       RealtimeMessage::ServerCollabV1(_) => f.write_fmt(format_args!("ServerCollabV1")),
+      RealtimeMessage::RowEditIntent(intent) => f.write_fmt(format_args!(
+        "RowEditIntent: {}/{}",
+        intent.database_id, intent.row_id
+      )),
     }
   }
 }
 
+/// Sent by the client when the user starts or stops editing a row of a database, so that the
+/// other collaborators can be warned before their edits conflict.
+///
+/// The intent expires if it's not refreshed by the client, see `ROW_EDIT_INTENT_TTL_SECS`.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RowEditIntent {
+  pub workspace_id: String,
+  pub database_id: String,
+  pub row_id: String,
+  /// `false` when the user stops editing the row.
+  pub is_editing: bool,
+}
+
+/// How long a row edit intent is kept by the server without being refreshed by the client.
+pub const ROW_EDIT_INTENT_TTL_SECS: i64 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
 pub enum SystemMessage {
   RateLimit(u32),
@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter};
+
+use crate::message::RealtimeMessage;
+use crate::server_message::ServerCollabMessage;
+use crate::user::UserMessage;
+use crate::{CollabMessage, HttpRealtimeMessage};
+
+/// The version of the realtime message format spoken by this build.
+///
+/// - Version 1: the client sends [RealtimeMessage::Collab] or [RealtimeMessage::ClientCollabV1]
+///   and receives one [RealtimeMessage::Collab] per collab message.
+/// - Version 2: the client sends [RealtimeMessage::ClientCollabV2], the messages grouped by
+///   object id, and receives the collab messages in batches with
+///   [RealtimeMessage::ServerCollabV1].
+/// - Version 3: the client also receives the folder changes made through the REST API, with
+///   [UserMessage::FolderChange].
+///
+/// The server supports the versions down to [MIN_REALTIME_PROTOCOL_VERSION], translating the
+/// messages of the older peers, so that the clients and the server don't have to be deployed in
+/// lockstep.
+pub const REALTIME_PROTOCOL_VERSION: u32 = 3;
+
+/// The oldest version of the realtime message format the server can translate.
+pub const MIN_REALTIME_PROTOCOL_VERSION: u32 = 1;
+
+/// The first version receiving the collab messages in batches.
+const BATCHED_COLLAB_PROTOCOL_VERSION: u32 = 2;
+
+/// The first version receiving the folder changes.
+const FOLDER_CHANGE_PROTOCOL_VERSION: u32 = 3;
+
+/// Sent by the client in the websocket handshake, and echoed by the server with the negotiated
+/// version. Clients that don't send it are assumed to speak version 1.
+pub const REALTIME_PROTOCOL_VERSION_HEADER: &str = "realtime-protocol-version";
+
+/// The version assumed for the peers that don't send their protocol version.
+pub const LEGACY_REALTIME_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct UnsupportedProtocolVersion(pub u32);
+
+impl Display for UnsupportedProtocolVersion {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "Unsupported realtime protocol version: {}, supported versions: {}..={}",
+      self.0, MIN_REALTIME_PROTOCOL_VERSION, REALTIME_PROTOCOL_VERSION
+    )
+  }
+}
+
+impl std::error::Error for UnsupportedProtocolVersion {}
+
+/// Returns the version both peers speak: the version of the peer, capped by the version of this
+/// build. Fails if the peer is older than the oldest version this build can translate.
+pub fn negotiate_realtime_protocol_version(
+  peer_version: u32,
+) -> Result<u32, UnsupportedProtocolVersion> {
+  if peer_version < MIN_REALTIME_PROTOCOL_VERSION {
+    return Err(UnsupportedProtocolVersion(peer_version));
+  }
+  Ok(peer_version.min(REALTIME_PROTOCOL_VERSION))
+}
+
+impl HttpRealtimeMessage {
+  /// Returns the version of the realtime message format of the payload, negotiated with the
+  /// version of this build. The messages without a version were sent by the legacy clients.
+  pub fn negotiate_protocol_version(&self) -> Result<u32, UnsupportedProtocolVersion> {
+    let peer_version = if self.protocol_version == 0 {
+      LEGACY_REALTIME_PROTOCOL_VERSION
+    } else {
+      self.protocol_version
+    };
+    negotiate_realtime_protocol_version(peer_version)
+  }
+}
+
+impl RealtimeMessage {
+  /// Translates a message received from a peer speaking the given version into the current
+  /// format.
+  pub fn upgrade_from(self, version: u32) -> Result<Self, anyhow::Error> {
+    if version >= BATCHED_COLLAB_PROTOCOL_VERSION {
+      return Ok(self);
+    }
+
+    match self {
+      RealtimeMessage::Collab(_) | RealtimeMessage::ClientCollabV1(_) => {
+        Ok(RealtimeMessage::ClientCollabV2(self.transform()?))
+      },
+      message => Ok(message),
+    }
+  }
+
+  /// Translates a message in the current format into the messages understood by a peer speaking
+  /// the given version.
+  pub fn downgrade_to(self, version: u32) -> Vec<Self> {
+    match self {
+      // The older peers fail to decode the messages they don't know.
+      RealtimeMessage::User(UserMessage::FolderChange(_))
+        if version < FOLDER_CHANGE_PROTOCOL_VERSION =>
+      {
+        vec![]
+      },
+      RealtimeMessage::ServerCollabV1(messages) if version < BATCHED_COLLAB_PROTOCOL_VERSION => {
+        messages
+          .into_iter()
+          .map(|message| RealtimeMessage::Collab(collab_message_from_server(message)))
+          .collect()
+      },
+      message => vec![message],
+    }
+  }
+}
+
+fn collab_message_from_server(message: ServerCollabMessage) -> CollabMessage {
+  match message {
+    ServerCollabMessage::ClientAck(msg) => CollabMessage::ClientAck(msg),
+    ServerCollabMessage::ServerInitSync(msg) => CollabMessage::ServerInitSync(msg),
+    ServerCollabMessage::AwarenessSync(msg) => CollabMessage::AwarenessSync(msg),
+    ServerCollabMessage::ServerBroadcast(msg) => CollabMessage::ServerBroadcast(msg),
+  }
+}
@@ -7,6 +7,8 @@ use std::hash::Hash;
 pub enum UserMessage {
   ProfileChange(AFUserChange),
   WorkspaceMemberChange(AFWorkspaceMemberChange),
+  /// Only sent to the peers speaking version 3 or later of the realtime protocol.
+  FolderChange(AFFolderChange),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
@@ -24,6 +26,27 @@ pub struct AFWorkspaceMemberChange {
   removed: Vec<AFWorkspaceMember>,
 }
 
+/// A change of the folder of a workspace made through the REST API, sent to the members of the
+/// workspace so that they can refresh their folder without polling it.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub struct AFFolderChange {
+  pub workspace_id: String,
+  /// The user who made the change.
+  pub uid: i64,
+  pub kind: AFFolderChangeKind,
+  pub view_ids: Vec<String>,
+  /// The parent of the views after the change, when they all share the same known one.
+  pub parent_view_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub enum AFFolderChangeKind {
+  Created,
+  Updated,
+  Moved,
+  Trashed,
+}
+
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct UserDevice {
   device_id: String,
@@ -1 +1,2 @@
+mod protocol_test;
 mod serde_test;
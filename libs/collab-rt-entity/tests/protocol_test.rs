@@ -0,0 +1,125 @@
+use collab::core::origin::CollabOrigin;
+use collab_rt_entity::user::{AFFolderChange, AFFolderChangeKind, UserMessage};
+use collab_rt_entity::{
+  negotiate_realtime_protocol_version, BroadcastSync, CollabAck, CollabMessage,
+  HttpRealtimeMessage, RealtimeMessage, ServerCollabMessage, SystemMessage, UpdateSync,
+  LEGACY_REALTIME_PROTOCOL_VERSION, MIN_REALTIME_PROTOCOL_VERSION, REALTIME_PROTOCOL_VERSION,
+};
+
+#[test]
+fn negotiate_protocol_version_test() {
+  assert_eq!(
+    negotiate_realtime_protocol_version(REALTIME_PROTOCOL_VERSION).unwrap(),
+    REALTIME_PROTOCOL_VERSION
+  );
+  assert_eq!(
+    negotiate_realtime_protocol_version(REALTIME_PROTOCOL_VERSION - 1).unwrap(),
+    REALTIME_PROTOCOL_VERSION - 1
+  );
+  // A newer peer is capped to the version of this build.
+  assert_eq!(
+    negotiate_realtime_protocol_version(REALTIME_PROTOCOL_VERSION + 1).unwrap(),
+    REALTIME_PROTOCOL_VERSION
+  );
+  assert_eq!(
+    negotiate_realtime_protocol_version(MIN_REALTIME_PROTOCOL_VERSION).unwrap(),
+    MIN_REALTIME_PROTOCOL_VERSION
+  );
+  assert!(negotiate_realtime_protocol_version(MIN_REALTIME_PROTOCOL_VERSION - 1).is_err());
+}
+
+#[test]
+fn http_message_without_version_is_legacy_test() {
+  let message = HttpRealtimeMessage {
+    device_id: "device".to_string(),
+    payload: vec![],
+    protocol_version: 0,
+  };
+  assert_eq!(message.negotiate_protocol_version().unwrap(), 1);
+}
+
+#[test]
+fn upgrade_legacy_collab_message_test() {
+  let update = UpdateSync::new(
+    CollabOrigin::Empty,
+    "object id 1".to_string(),
+    vec![1, 2, 3],
+    1,
+  );
+  let message = RealtimeMessage::Collab(CollabMessage::ClientUpdateSync(update))
+    .upgrade_from(LEGACY_REALTIME_PROTOCOL_VERSION)
+    .unwrap();
+  match message {
+    RealtimeMessage::ClientCollabV2(messages) => {
+      assert_eq!(messages.len(), 1);
+      assert_eq!(messages["object id 1"].len(), 1);
+    },
+    _ => panic!("Expected ClientCollabV2 message"),
+  }
+
+  // The messages of the current version are left untouched.
+  let message = RealtimeMessage::System(SystemMessage::DuplicateConnection)
+    .upgrade_from(REALTIME_PROTOCOL_VERSION)
+    .unwrap();
+  assert!(matches!(
+    message,
+    RealtimeMessage::System(SystemMessage::DuplicateConnection)
+  ));
+}
+
+#[test]
+fn downgrade_server_collab_message_test() {
+  let message = RealtimeMessage::ServerCollabV1(vec![
+    ServerCollabMessage::ClientAck(CollabAck::new(
+      CollabOrigin::Server,
+      "object id 1".to_string(),
+      1,
+      1,
+    )),
+    ServerCollabMessage::ServerBroadcast(BroadcastSync::new(
+      CollabOrigin::Server,
+      "object id 1".to_string(),
+      vec![1, 2, 3],
+      2,
+    )),
+  ]);
+
+  let legacy_messages = message
+    .clone()
+    .downgrade_to(LEGACY_REALTIME_PROTOCOL_VERSION);
+  assert_eq!(legacy_messages.len(), 2);
+  assert!(matches!(
+    legacy_messages[0],
+    RealtimeMessage::Collab(CollabMessage::ClientAck(_))
+  ));
+  assert!(matches!(
+    legacy_messages[1],
+    RealtimeMessage::Collab(CollabMessage::ServerBroadcast(_))
+  ));
+
+  let messages = message.downgrade_to(REALTIME_PROTOCOL_VERSION);
+  assert_eq!(messages.len(), 1);
+  assert!(matches!(messages[0], RealtimeMessage::ServerCollabV1(_)));
+}
+
+#[test]
+fn folder_change_is_not_sent_to_older_peers_test() {
+  let message = RealtimeMessage::User(UserMessage::FolderChange(AFFolderChange {
+    workspace_id: "workspace id".to_string(),
+    uid: 1,
+    kind: AFFolderChangeKind::Created,
+    view_ids: vec!["view id 1".to_string()],
+    parent_view_id: Some("view id 0".to_string()),
+  }));
+  assert!(message
+    .clone()
+    .downgrade_to(REALTIME_PROTOCOL_VERSION - 1)
+    .is_empty());
+
+  let messages = message.downgrade_to(REALTIME_PROTOCOL_VERSION);
+  assert_eq!(messages.len(), 1);
+  assert!(matches!(
+    messages[0],
+    RealtimeMessage::User(UserMessage::FolderChange(_))
+  ));
+}
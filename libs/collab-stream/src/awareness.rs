@@ -0,0 +1,85 @@
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::error::StreamError;
+
+/// The awareness snapshots older than this are ignored. The hash of the workspace expires after
+/// the same time without any update.
+pub const AWARENESS_SNAPSHOT_TTL_SECS: i64 = 30;
+
+/// The last-known awareness state of a collab, i.e. the presence of the connected users.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwarenessSnapshot {
+  /// The awareness update of all the clients, encoded with the v1 encoding.
+  pub update: Vec<u8>,
+  /// The time the snapshot was saved, in milliseconds.
+  pub updated_at: i64,
+}
+
+impl AwarenessSnapshot {
+  pub fn is_expired(&self) -> bool {
+    Utc::now().timestamp_millis() - self.updated_at > AWARENESS_SNAPSHOT_TTL_SECS * 1000
+  }
+}
+
+/// Keeps the awareness snapshots of the collabs of a workspace in a redis hash, keyed by the
+/// object id.
+#[derive(Clone)]
+pub struct AwarenessSnapshotStore {
+  connection_manager: ConnectionManager,
+}
+
+impl AwarenessSnapshotStore {
+  pub fn new(connection_manager: ConnectionManager) -> Self {
+    Self { connection_manager }
+  }
+
+  pub async fn save(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    update: Vec<u8>,
+  ) -> Result<(), StreamError> {
+    let snapshot = AwarenessSnapshot {
+      update,
+      updated_at: Utc::now().timestamp_millis(),
+    };
+    let key = awareness_snapshot_key(workspace_id);
+    let mut conn = self.connection_manager.clone();
+    redis::pipe()
+      .atomic()
+      .hset(&key, object_id, bincode::serialize(&snapshot)?)
+      .ignore()
+      .expire(&key, AWARENESS_SNAPSHOT_TTL_SECS)
+      .ignore()
+      .query_async::<_, ()>(&mut conn)
+      .await?;
+    Ok(())
+  }
+
+  /// Returns the snapshot of the collab, unless it expired.
+  pub async fn get(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+  ) -> Result<Option<AwarenessSnapshot>, StreamError> {
+    let mut conn = self.connection_manager.clone();
+    let value: Option<Vec<u8>> = conn
+      .hget(awareness_snapshot_key(workspace_id), object_id)
+      .await?;
+    match value {
+      Some(value) => {
+        let snapshot: AwarenessSnapshot = bincode::deserialize(&value)?;
+        Ok(Some(snapshot).filter(|snapshot| !snapshot.is_expired()))
+      },
+      None => Ok(None),
+    }
+  }
+}
+
+#[inline]
+fn awareness_snapshot_key(workspace_id: &str) -> String {
+  format!("af_awareness_snapshot-{}", workspace_id)
+}
@@ -1,3 +1,4 @@
+use crate::awareness::AwarenessSnapshotStore;
 use crate::error::StreamError;
 use crate::pubsub::{CollabStreamPub, CollabStreamSub};
 use crate::stream::CollabStream;
@@ -26,6 +27,10 @@ impl CollabRedisStream {
     CollabStream::new(workspace_id, oid, self.connection_manager.clone())
   }
 
+  pub fn awareness_snapshot_store(&self) -> AwarenessSnapshotStore {
+    AwarenessSnapshotStore::new(self.connection_manager.clone())
+  }
+
   pub async fn collab_control_stream(
     &self,
     key: &str,
@@ -1,3 +1,4 @@
+pub mod awareness;
 pub mod client;
 pub mod error;
 pub mod model;
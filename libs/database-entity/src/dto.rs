@@ -7,9 +7,10 @@ use chrono::{DateTime, Utc};
 use collab_entity::proto;
 use collab_entity::CollabType;
 use prost::Message;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{json, Value};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Display;
@@ -198,6 +199,104 @@ pub struct UpdateCollabWebParams {
   pub collab_type: CollabType,
 }
 
+/// The state vector of the copy of a collab held by a client, encoded with the v1 encoding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabUpdatesSinceParams {
+  pub state_vector: Vec<u8>,
+  pub collab_type: CollabType,
+}
+
+/// The changes of a collab the client lacks, to catch up without fetching the whole collab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabUpdatesSince {
+  /// The missing changes, as a single update encoded with the v1 encoding. The update is empty
+  /// when the client is up to date.
+  pub update: Vec<u8>,
+  /// The state vector of the collab on the server, which the client has once the update applied.
+  pub state_vector: Vec<u8>,
+}
+
+/// What a client needs to tell cheaply whether its copy of a collab diverged from the one on the
+/// server, and whether to resync it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabSyncState {
+  /// The state vector of the collab on the server, encoded with the v1 encoding.
+  pub state_vector: Vec<u8>,
+  /// The hash of the content of the collab on the server, see [collab_content_hash].
+  pub content_hash: String,
+}
+
+impl CollabSyncState {
+  /// Whether the content of the copy of the collab, as JSON, is the same as on the server.
+  pub fn content_matches(&self, content: &Value) -> bool {
+    self.content_hash == collab_content_hash(content)
+  }
+}
+
+/// Returns the hex encoded SHA-256 of the content of a collab, as JSON. The keys of the objects
+/// are hashed in order, so that two copies with the same content have the same hash whatever the
+/// order their changes were applied in.
+pub fn collab_content_hash(content: &Value) -> String {
+  fn hash_value(hasher: &mut Sha256, value: &Value) {
+    match value {
+      Value::Null => hasher.update(b"n"),
+      Value::Bool(value) => hasher.update(if *value { b"t" } else { b"f" }),
+      Value::Number(number) => {
+        hasher.update(b"d");
+        hash_str(hasher, &number.to_string());
+      },
+      Value::String(string) => {
+        hasher.update(b"s");
+        hash_str(hasher, string);
+      },
+      Value::Array(values) => {
+        hasher.update(b"a");
+        hasher.update((values.len() as u64).to_be_bytes());
+        for value in values {
+          hash_value(hasher, value);
+        }
+      },
+      Value::Object(map) => {
+        hasher.update(b"o");
+        hasher.update((map.len() as u64).to_be_bytes());
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (key, value) in entries {
+          hash_str(hasher, key);
+          hash_value(hasher, value);
+        }
+      },
+    }
+  }
+
+  fn hash_str(hasher: &mut Sha256, string: &str) {
+    hasher.update((string.len() as u64).to_be_bytes());
+    hasher.update(string.as_bytes());
+  }
+
+  let mut hasher = Sha256::new();
+  hash_value(&mut hasher, content);
+  hex::encode(hasher.finalize())
+}
+
+/// The outcome of applying an update to a scratch copy of a collab, which is not persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabUpdateValidation {
+  pub decoded: bool,
+  pub applied: bool,
+  /// Why the update could not be decoded or applied, or why the updated collab is invalid.
+  pub error: Option<String>,
+  /// True if the update depends on changes the collab lacks, in which case part of it would be
+  /// kept pending instead of being applied.
+  pub has_missing_dependencies: bool,
+  /// True if the updated collab still has the data its collab type requires.
+  pub is_valid: bool,
+  /// The top level keys of the collab whose content the update would change.
+  pub changed_keys: Vec<String>,
+  pub doc_state_size_before: usize,
+  pub doc_state_size_after: usize,
+}
+
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
 pub struct DeleteCollabParams {
   #[validate(custom = "validate_not_empty_str")]
@@ -344,9 +443,75 @@ pub enum QueryCollabResult {
 #[derive(Serialize, Deserialize)]
 pub struct BatchQueryCollabResult(pub HashMap<String, QueryCollabResult>);
 
+/// Selects a page of the queries of a batch query. All the queries are run if not set.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct BatchQueryCollabPage {
+  pub offset: Option<usize>,
+  pub limit: Option<usize>,
+}
+
+impl BatchQueryCollabPage {
+  pub fn apply(&self, queries: Vec<QueryCollab>) -> Vec<QueryCollab> {
+    queries
+      .into_iter()
+      .skip(self.offset.unwrap_or(0))
+      .take(self.limit.unwrap_or(usize::MAX))
+      .collect()
+  }
+}
+
+/// The result of one query of a streamed batch query. Each item is sent as a frame: the length
+/// of the item as a little endian u32, followed by the bincode encoded item.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BatchQueryCollabItem {
+  pub object_id: String,
+  pub result: QueryCollabResult,
+}
+
+impl BatchQueryCollabItem {
+  pub fn to_frame(&self) -> Result<Vec<u8>, bincode::Error> {
+    let item = bincode::serialize(self)?;
+    let mut frame = Vec::with_capacity(4 + item.len());
+    frame.extend_from_slice(&(item.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&item);
+    Ok(frame)
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, bincode::Error> {
+    bincode::deserialize(bytes)
+  }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct WorkspaceUsage {
   pub total_document_size: i64,
+  /// The storage used by each type of collab.
+  #[serde(default)]
+  pub collab_usages: Vec<CollabTypeUsage>,
+  #[serde(default)]
+  pub blob_count: i64,
+  /// The storage used by the files uploaded to the workspace.
+  #[serde(default)]
+  pub blob_storage_bytes: i64,
+  /// The storage used by the collabs last written by each member.
+  #[serde(default)]
+  pub member_usages: Vec<WorkspaceMemberUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabTypeUsage {
+  pub collab_type: CollabType,
+  pub collab_count: i64,
+  pub total_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceMemberUsage {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub collab_count: i64,
+  pub total_bytes: i64,
 }
 
 #[derive(Debug, Clone, Validate, Serialize, Deserialize)]
@@ -357,6 +522,9 @@ pub struct InsertCollabMemberParams {
   #[validate(custom = "validate_not_empty_str")]
   pub object_id: String,
   pub access_level: AFAccessLevel,
+  /// The access is revoked once the time has passed. `None` means the access never expires.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 pub type UpdateCollabMemberParams = InsertCollabMemberParams;
@@ -376,11 +544,58 @@ pub struct UpdatePublishNamespace {
   pub new_namespace: String,
 }
 
+/// Hosts of the embeds kept when the views of the namespace are published, in addition to the
+/// hosts trusted by the server, e.g. `youtube.com` also trusts `www.youtube.com`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishEmbedAllowlist {
+  pub namespace: String,
+  pub hosts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdatePublishEmbedAllowlist {
+  pub hosts: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct UpdateDefaultPublishView {
   pub view_id: Uuid,
 }
 
+/// A site hosted under the publish namespace of the workspace, served at
+/// `{namespace}/{sub_namespace}`, e.g. `acme/handbook`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublishSubNamespace {
+  /// The full publish namespace, e.g. `acme/handbook`.
+  pub namespace: String,
+  pub sub_namespace: String,
+  pub title: String,
+  /// The view served at the root of the sub-namespace.
+  pub default_view_id: Option<Uuid>,
+  /// The views published in the sub-namespace.
+  pub view_ids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreatePublishSubNamespace {
+  pub sub_namespace: String,
+  #[serde(default)]
+  pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdatePublishSubNamespace {
+  pub title: String,
+  /// Must be a view published in the sub-namespace.
+  pub default_view_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdatePublishSubNamespaceViews {
+  pub view_ids: Vec<Uuid>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct DefaultPublishViewInfoMeta {
   pub info: PublishInfo,
@@ -403,22 +618,129 @@ pub struct QueryWorkspaceMember {
   pub uid: i64,
 }
 
+/// The realtime state of a database shared by the collaborators.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabasePresence {
+  pub database_id: String,
+  /// The rows that are being edited. Used to warn the users before their edits conflict.
+  pub row_edit_intents: Vec<RowEditIntentInfo>,
+}
+
+/// The last-known awareness state of a collab, i.e. the presence of the connected users. It's kept
+/// briefly after the last change, so it's empty when nobody opened the collab recently.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollabAwareness {
+  pub object_id: String,
+  pub updated_at: Option<DateTime<Utc>>,
+  pub states: Vec<AwarenessClientState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AwarenessClientState {
+  pub client_id: u64,
+  pub clock: u32,
+  /// The state set by the client, e.g. its user and the position of its cursor.
+  pub state: serde_json::Value,
+}
+
+/// The users viewing a collab, read from its last-known awareness state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollabPresence {
+  pub object_id: String,
+  pub updated_at: Option<DateTime<Utc>>,
+  /// One entry per connected client, a user viewing the collab on several devices is listed once
+  /// per device.
+  pub users: Vec<CollabPresenceUser>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollabPresenceUser {
+  pub client_id: u64,
+  pub uid: i64,
+  /// The name and the avatar of the user, `None` if the user doesn't exist anymore.
+  pub name: Option<String>,
+  pub avatar_url: Option<String>,
+  pub device_id: Option<String>,
+  /// The selection of the user in the collab, as set by the client.
+  pub cursor: Option<serde_json::Value>,
+}
+
+/// The realtime activity of a workspace on the server node that answered the request. The collabs
+/// of a workspace can be opened on several nodes, each node only knows about its own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceRealtimeStats {
+  /// The number of distinct users editing at least one collab of the workspace.
+  pub connected_users: usize,
+  /// The number of collabs of the workspace opened in memory.
+  pub active_collabs: usize,
+  /// The number of subscriptions to the collabs of the workspace, one per device and collab.
+  pub active_sessions: usize,
+  /// The collab messages received per second for the workspace, over the last complete minute.
+  pub messages_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RowEditIntentInfo {
+  pub row_id: String,
+  pub uid: i64,
+  pub device_id: String,
+  pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct AFCollabMember {
   pub uid: i64,
   pub oid: String,
   pub permission: AFPermission,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PublishInfo {
+  /// The full publish namespace of the view, including the sub-namespace, e.g. `acme/handbook`.
   pub namespace: String,
+  /// The sub-namespace the view is published in, `None` if it's published in the namespace of
+  /// the workspace.
+  #[serde(default)]
+  pub sub_namespace: Option<String>,
   pub publish_name: String,
   pub view_id: Uuid,
   #[serde(default)]
   pub publisher_email: String,
   #[serde(default)]
   pub publish_timestamp: DateTime<Utc>,
+  /// The last time the view was published, manually or automatically.
+  #[serde(default)]
+  pub last_published_at: Option<DateTime<Utc>>,
+  /// Whether the view is republished automatically when its content changes.
+  #[serde(default)]
+  pub auto_publish: bool,
+  /// Whether there are changes waiting to be republished automatically.
+  #[serde(default)]
+  pub auto_publish_pending: bool,
+  /// The error of the last automatic republish, if it failed.
+  #[serde(default)]
+  pub auto_publish_error: Option<String>,
+  /// The view is unpublished once this time has passed.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A version of a published view. Each publishing of the view makes a new version, and so does a
+/// rollback to a prior version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewVersion {
+  pub version: i32,
+  pub published_at: DateTime<Utc>,
+  /// The prior version this version is a copy of, if it was made by a rollback.
+  pub restored_from_version: Option<i32>,
+  /// `None` if the publisher was deleted.
+  pub publisher_email: Option<String>,
+  /// The size of the published data in bytes.
+  pub size: i64,
+  /// Whether this version is the one being served.
+  pub is_current: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -493,6 +815,50 @@ impl Ord for AFRole {
   }
 }
 
+/// What a custom role lets its members do in a workspace, on top of their role. The owners have
+/// every capability.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceCapability {
+  /// Invite members and guests to the workspace.
+  Invite,
+  /// Publish views, even if the publishing of the workspace is restricted.
+  Publish,
+  /// Delete the collabs of the workspace.
+  DeleteCollab,
+  /// Change the role of the members and remove them from the workspace, except for the owners.
+  ManageMembers,
+}
+
+impl WorkspaceCapability {
+  pub const ALL: [WorkspaceCapability; 4] = [
+    WorkspaceCapability::Invite,
+    WorkspaceCapability::Publish,
+    WorkspaceCapability::DeleteCollab,
+    WorkspaceCapability::ManageMembers,
+  ];
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      WorkspaceCapability::Invite => "invite",
+      WorkspaceCapability::Publish => "publish",
+      WorkspaceCapability::DeleteCollab => "delete_collab",
+      WorkspaceCapability::ManageMembers => "manage_members",
+    }
+  }
+}
+
+impl FromStr for WorkspaceCapability {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    WorkspaceCapability::ALL
+      .into_iter()
+      .find(|capability| capability.as_str() == s)
+      .ok_or_else(|| format!("Unknown workspace capability: {}", s))
+  }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AFPermission {
   /// The permission id
@@ -622,6 +988,9 @@ pub struct AFWorkspace {
   pub created_at: DateTime<Utc>,
   pub icon: String,
   pub member_count: Option<i64>,
+  /// An archived workspace is read-only and hidden from the workspace list by default.
+  #[serde(default)]
+  pub archived: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -631,6 +1000,39 @@ pub struct AFWorkspaceSettings {
 
   #[serde(default)]
   pub ai_model: String,
+
+  /// The region where the data of the workspace is stored, `None` for the default region. It's
+  /// chosen when the workspace is created and can't be changed afterwards.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub residency: Option<String>,
+
+  #[serde(default)]
+  pub secret_scanning: SecretScanningPolicy,
+
+  /// The number of days the invitations to the workspace can be accepted, 0 if the invitations
+  /// never expire. Only the invitations sent after a change are affected.
+  #[serde(default = "default_invitation_ttl_days")]
+  pub invitation_ttl_days: u32,
+
+  #[serde(default)]
+  pub snapshot_retention: SnapshotRetentionPolicy,
+
+  /// When set, only the owners and the members granted the publish permission can publish views.
+  /// Otherwise every member can publish.
+  #[serde(default)]
+  pub restrict_publishing: bool,
+
+  /// The CIDR ranges, e.g. `203.0.113.0/24`, the requests to the workspace must come from. Every
+  /// address is allowed when empty.
+  #[serde(default)]
+  pub ip_allowlist: Vec<String>,
+  /// Whether the sessions must be authenticated with a second factor to access the workspace.
+  #[serde(default)]
+  pub require_2fa: bool,
+}
+
+fn default_invitation_ttl_days() -> u32 {
+  7
 }
 
 impl Default for AFWorkspaceSettings {
@@ -638,16 +1040,80 @@ impl Default for AFWorkspaceSettings {
     Self {
       disable_search_indexing: false,
       ai_model: "".to_string(),
+      residency: None,
+      secret_scanning: SecretScanningPolicy::Off,
+      invitation_ttl_days: default_invitation_ttl_days(),
+      snapshot_retention: SnapshotRetentionPolicy::default(),
+      restrict_publishing: false,
+      ip_allowlist: vec![],
+      require_2fa: false,
+    }
+  }
+}
+
+/// How many snapshots of each collab of the workspace are kept, and how often the edited collabs
+/// are snapshotted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SnapshotRetentionPolicy {
+  /// The maximum number of snapshots kept for each collab, the oldest ones are deleted first.
+  #[serde(default = "default_snapshot_max_count")]
+  pub max_count: u32,
+  /// The snapshots older than this many days are deleted, 0 to keep them regardless of their
+  /// age.
+  #[serde(default)]
+  pub max_age_days: u32,
+  /// The collabs edited since their last snapshot are snapshotted every this many hours, 0 to
+  /// disable the scheduled snapshots.
+  #[serde(default)]
+  pub schedule_interval_hours: u32,
+}
+
+fn default_snapshot_max_count() -> u32 {
+  30
+}
+
+impl Default for SnapshotRetentionPolicy {
+  fn default() -> Self {
+    Self {
+      max_count: default_snapshot_max_count(),
+      max_age_days: 0,
+      schedule_interval_hours: 0,
     }
   }
 }
 
+/// What happens when a secret, such as an API key or a private key, is found in the content of a
+/// document written to the workspace.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretScanningPolicy {
+  /// The documents are not scanned.
+  #[default]
+  Off,
+  /// The document is written and flagged, and the owners of the workspace are notified.
+  Warn,
+  /// The write is rejected, and the owners of the workspace are notified.
+  Block,
+}
+
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct AFWorkspaceSettingsChange {
   #[serde(skip_serializing_if = "Option::is_none")]
   pub disable_search_indexing: Option<bool>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub ai_model: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub secret_scanning: Option<SecretScanningPolicy>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub invitation_ttl_days: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub snapshot_retention: Option<SnapshotRetentionPolicy>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub restrict_publishing: Option<bool>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ip_allowlist: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub require_2fa: Option<bool>,
 }
 
 impl AFWorkspaceSettingsChange {
@@ -655,6 +1121,12 @@ impl AFWorkspaceSettingsChange {
     Self {
       disable_search_indexing: None,
       ai_model: None,
+      secret_scanning: None,
+      invitation_ttl_days: None,
+      snapshot_retention: None,
+      restrict_publishing: None,
+      ip_allowlist: None,
+      require_2fa: None,
     }
   }
   pub fn disable_search_indexing(mut self, disable_search_indexing: bool) -> Self {
@@ -665,6 +1137,30 @@ impl AFWorkspaceSettingsChange {
     self.ai_model = Some(ai_model);
     self
   }
+  pub fn secret_scanning(mut self, secret_scanning: SecretScanningPolicy) -> Self {
+    self.secret_scanning = Some(secret_scanning);
+    self
+  }
+  pub fn invitation_ttl_days(mut self, invitation_ttl_days: u32) -> Self {
+    self.invitation_ttl_days = Some(invitation_ttl_days);
+    self
+  }
+  pub fn snapshot_retention(mut self, snapshot_retention: SnapshotRetentionPolicy) -> Self {
+    self.snapshot_retention = Some(snapshot_retention);
+    self
+  }
+  pub fn restrict_publishing(mut self, restrict_publishing: bool) -> Self {
+    self.restrict_publishing = Some(restrict_publishing);
+    self
+  }
+  pub fn ip_allowlist(mut self, ip_allowlist: Vec<String>) -> Self {
+    self.ip_allowlist = Some(ip_allowlist);
+    self
+  }
+  pub fn require_2fa(mut self, require_2fa: bool) -> Self {
+    self.require_2fa = Some(require_2fa);
+    self
+  }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -680,6 +1176,8 @@ pub struct AFWorkspaceMember {
   pub email: String,
   pub role: AFRole,
   pub avatar_url: Option<String>,
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -694,6 +1192,20 @@ pub struct AFWorkspaceInvitation {
   pub inviter_icon: Option<String>,
   pub workspace_icon: String,
   pub member_count: Option<i64>, // use unwrap_or(0) to get the value
+  /// `None` if the invitation never expires.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The public information of an invitation, shown to the invitee before they sign in.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AFWorkspaceInvitationPreview {
+  pub workspace_name: Option<String>,
+  pub workspace_icon: String,
+  pub inviter_name: Option<String>,
+  pub inviter_icon: Option<String>,
+  pub status: AFWorkspaceInvitationStatus,
+  pub member_count: Option<i64>, // use unwrap_or(0) to get the value
 }
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
@@ -702,6 +1214,7 @@ pub enum AFWorkspaceInvitationStatus {
   Pending = 0,
   Accepted = 1,
   Rejected = 2,
+  Expired = 3,
 }
 
 impl From<i16> for AFWorkspaceInvitationStatus {
@@ -710,6 +1223,7 @@ impl From<i16> for AFWorkspaceInvitationStatus {
       0 => AFWorkspaceInvitationStatus::Pending,
       1 => AFWorkspaceInvitationStatus::Accepted,
       2 => AFWorkspaceInvitationStatus::Rejected,
+      3 => AFWorkspaceInvitationStatus::Expired,
       _ => {
         error!("Invalid role id: {}", value);
         AFWorkspaceInvitationStatus::Pending
@@ -1156,11 +1670,94 @@ pub struct PublishCollabItem<Meta, Data> {
 pub struct PatchPublishedCollab {
   pub view_id: Uuid,
   pub publish_name: Option<String>,
+  /// `Some(None)`, i.e. `null`, removes the expiry of the published view, and a missing field
+  /// leaves it unchanged.
+  #[serde(
+    default,
+    deserialize_with = "deserialize_present",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub expires_at: Option<Option<DateTime<Utc>>>,
+}
+
+/// The metadata used by AppFlowy Web to render the SEO and OpenGraph tags of a published view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewSeo {
+  pub title: String,
+  pub description: Option<String>,
+  pub image_url: Option<String>,
+  pub canonical_url: String,
+}
+
+/// Overrides the SEO metadata derived from the published metadata. `Some(None)`, i.e. `null`,
+/// removes the override, and a missing field leaves it unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PatchPublishedViewSeo {
+  #[serde(
+    default,
+    deserialize_with = "deserialize_present",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub title: Option<Option<String>>,
+  #[serde(
+    default,
+    deserialize_with = "deserialize_present",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub description: Option<Option<String>>,
+  #[serde(
+    default,
+    deserialize_with = "deserialize_present",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub image_url: Option<Option<String>>,
+}
+
+/// Deserializes a field that is present, even if it's `null`, as `Some`. Along with
+/// `#[serde(default)]`, a missing field is `None`.
+pub fn deserialize_present<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  T: Deserialize<'de>,
+  D: Deserializer<'de>,
+{
+  T::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GlobalComments {
   pub comments: Vec<GlobalComment>,
+  /// Passed as the `cursor` of the next query to get the next page, `None` on the last page.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentSortOrder {
+  /// The most recent comments first.
+  #[default]
+  Newest,
+  Oldest,
+  /// The comments with the most reactions first, the most recent first among equals.
+  Top,
+}
+
+/// The pinned comments are listed first, whatever the sort order.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct QueryGlobalComments {
+  /// The `next_cursor` of the previous page, the first page is returned when `None`.
+  pub cursor: Option<String>,
+  /// All the comments are returned when `None`.
+  pub limit: Option<u32>,
+  #[serde(default)]
+  pub sort: CommentSortOrder,
+  /// Pages over the top level comments, with their replies nested in `replies` from the oldest to
+  /// the most recent.
+  #[serde(default)]
+  pub threaded: bool,
+  /// Pages over the direct replies to the comment instead, nested in the same way when
+  /// `threaded`.
+  pub reply_to: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1179,13 +1776,47 @@ pub struct GlobalComment {
   pub reply_comment_id: Option<Uuid>,
   pub comment_id: Uuid,
   pub is_deleted: bool,
+  /// The last time the author edited the content, `None` if it was never edited.
+  #[serde(default)]
+  pub edited_at: Option<DateTime<Utc>>,
+  /// Hidden by a moderator, only the owners of the workspace see the comment.
+  #[serde(default)]
+  pub is_hidden: bool,
+  /// Pinned by a moderator, the pinned comments are listed first.
+  #[serde(default)]
+  pub is_pinned: bool,
   pub can_be_deleted: bool,
+  /// Whether the current user is the author and the comment is still within the edit window.
+  #[serde(default)]
+  pub can_be_edited: bool,
+  #[serde(default)]
+  pub attachments: Vec<CommentAttachment>,
+  /// The replies to the comment, only filled in when the comments are queried as threads.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub replies: Vec<GlobalComment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommentAttachment {
+  pub attachment_id: Uuid,
+  pub url: String,
+  pub content_type: String,
+  pub file_size: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateGlobalCommentParams {
   pub content: String,
   pub reply_comment_id: Option<Uuid>,
+  /// The ids of the attachments uploaded for this comment.
+  #[serde(default)]
+  pub attachments: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateGlobalCommentParams {
+  pub comment_id: Uuid,
+  pub content: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1193,6 +1824,282 @@ pub struct DeleteGlobalCommentParams {
   pub comment_id: Uuid,
 }
 
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct SubscribeCommentParams {
+  #[validate(email)]
+  pub email: String,
+}
+
+/// The token is sent to the email of the subscription, in the confirm and unsubscribe links.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentSubscriptionTokenParams {
+  pub token: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentSubscriberCount {
+  pub count: i64,
+}
+
+/// A member of the workspace granted the publish permission.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspacePublisher {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub granted_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspacePublishers {
+  pub publishers: Vec<WorkspacePublisher>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkspacePublisherParams {
+  pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchCommentsParams {
+  pub view_ids: Vec<Uuid>,
+  /// The number of latest comments returned for each view.
+  #[serde(default = "default_batch_latest_comment_count")]
+  pub latest_count: u32,
+}
+
+fn default_batch_latest_comment_count() -> u32 {
+  3
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchComments {
+  /// The comments of each requested view, in the order of the request.
+  pub views: Vec<ViewComments>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ViewComments {
+  pub view_id: Uuid,
+  /// The number of comments that were not deleted.
+  pub comment_count: i64,
+  /// The latest comments that were not deleted, the most recent first. Their attachments are not
+  /// included.
+  pub latest_comments: Vec<GlobalComment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockUserParams {
+  pub user_uuid: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockedUser {
+  pub uuid: Uuid,
+  pub name: String,
+  pub avatar_url: Option<String>,
+  pub blocked_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockedUsers {
+  pub users: Vec<BlockedUser>,
+}
+
+/// A device the user connected from, with the realtime websocket or the realtime message stream.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFUserDevice {
+  pub device_id: String,
+  /// The platform reported by the client, e.g. `macos` or `android`.
+  pub platform: Option<String>,
+  pub client_version: Option<String>,
+  pub first_seen_at: DateTime<Utc>,
+  /// The last time the device connected, or sent a realtime message. It's updated at most once a
+  /// minute.
+  pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AFUserDevices {
+  pub devices: Vec<AFUserDevice>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateAccessTokenParams {
+  pub name: String,
+  /// A read-only token can only be used for the requests that don't change anything.
+  #[serde(default = "default_access_token_read_only")]
+  pub read_only: bool,
+  /// Limits the token to these workspaces, the token can be used on all the workspaces of the
+  /// user if missing.
+  #[serde(default)]
+  pub workspace_ids: Option<Vec<Uuid>>,
+  /// The token never expires if missing.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn default_access_token_read_only() -> bool {
+  true
+}
+
+/// A personal access token, without the token itself which is only returned when issued.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFUserAccessToken {
+  pub id: Uuid,
+  pub name: String,
+  pub read_only: bool,
+  pub workspace_ids: Option<Vec<Uuid>>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+  /// The last time the token was used. It's updated at most once a minute.
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFCreatedAccessToken {
+  /// The token to send as a bearer token. It can't be retrieved again.
+  pub token: String,
+  pub access_token: AFUserAccessToken,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AFUserAccessTokens {
+  pub access_tokens: Vec<AFUserAccessToken>,
+}
+
+/// A named group of workspace members, which the permissions of the views can be granted to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFMemberGroup {
+  pub group_id: Uuid,
+  pub name: String,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AFMemberGroups {
+  pub groups: Vec<AFMemberGroup>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateMemberGroupParams {
+  pub name: String,
+  #[serde(default)]
+  pub member_uids: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateMemberGroupParams {
+  /// Replaces the members of the group.
+  pub member_uids: Vec<i64>,
+}
+
+/// Who a permission of a view is granted to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ViewPermissionGrantee {
+  Member { uid: i64 },
+  Group { group_id: Uuid },
+}
+
+/// The access granted on a restricted view: `ReadOnly` to read it, `ReadAndComment` to also comment
+/// on it, `ReadAndWrite` or `FullAccess` to edit it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFViewPermission {
+  #[serde(flatten)]
+  pub grantee: ViewPermissionGrantee,
+  pub access_level: AFAccessLevel,
+}
+
+/// The permissions of a view. A view without any permission isn't restricted, it's accessible to
+/// every member of the workspace as granted by their role. A restricted view is only accessible to
+/// the owners of the workspace and to the grantees of its permissions, and never beyond the role
+/// of the grantees.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFViewPermissions {
+  pub view_id: String,
+  pub permissions: Vec<AFViewPermission>,
+}
+
+/// A role defined by the owners of a workspace, granting capabilities to the members it's assigned
+/// to. A member has at most one custom role, in addition to their owner, member or guest role.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AFWorkspaceRole {
+  pub role_id: Uuid,
+  pub name: String,
+  pub capabilities: Vec<WorkspaceCapability>,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AFWorkspaceRoles {
+  pub roles: Vec<AFWorkspaceRole>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateWorkspaceRoleParams {
+  pub name: String,
+  pub capabilities: Vec<WorkspaceCapability>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UpdateWorkspaceRoleParams {
+  pub name: Option<String>,
+  /// Replaces the capabilities of the role.
+  pub capabilities: Option<Vec<WorkspaceCapability>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateWorkspaceRoleMembersParams {
+  /// Replaces the members of the role. The members are moved from their previous custom role.
+  pub member_uids: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateViewPermissionsParams {
+  /// Replaces the permissions of the view, an empty list lifts the restriction.
+  pub permissions: Vec<AFViewPermission>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentModerationAction {
+  Hide,
+  Unhide,
+  Pin,
+  Unpin,
+  Delete,
+}
+
+/// Applies the moderation action to the comments of a published view. Only the owners of the
+/// workspace the view is published in can moderate the comments.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ModerateCommentsParams {
+  pub action: CommentModerationAction,
+  pub comment_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentModeration {
+  /// The comments the action was applied to, the other comments of the request were not found on
+  /// the view.
+  pub comment_ids: Vec<Uuid>,
+}
+
+/// Blocks or unblocks a user from commenting and reacting on the views published in the namespace.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentBlocklistParams {
+  pub user_uuid: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CommentBlocklist {
+  pub namespace: String,
+  pub users: Vec<BlockedUser>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Reactions {
   pub reactions: Vec<Reaction>,
@@ -1222,6 +2129,24 @@ pub struct DeleteReactionParams {
   pub comment_id: Uuid,
 }
 
+/// An emoji uploaded to a workspace. It's referenced in comments and reactions as `:shortcode:`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomEmoji {
+  pub shortcode: String,
+  /// Path of the emoji image, relative to the base url of the server.
+  pub url: String,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Returns the shortcode if the text references a custom emoji, i.e. `:shortcode:`.
+pub fn custom_emoji_shortcode(text: &str) -> Option<&str> {
+  text
+    .strip_prefix(':')
+    .and_then(|text| text.strip_suffix(':'))
+    .filter(|shortcode| !shortcode.is_empty())
+}
+
 /// Indexing status of a document.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum IndexingStatus {
@@ -1449,6 +2374,96 @@ pub struct AvatarImageSource {
   pub file_id: String,
 }
 
+/// The license other users get a template of the marketplace under.
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Copy, Clone)]
+#[repr(i32)]
+pub enum TemplateLicense {
+  /// CC0, no rights reserved.
+  PublicDomain = 0,
+  /// CC BY, the creator must be credited.
+  Attribution = 1,
+  /// CC BY-SA, the creator must be credited and the derived works shared alike.
+  AttributionShareAlike = 2,
+  /// CC BY-NC, the creator must be credited and the template can't be used commercially.
+  AttributionNonCommercial = 3,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarketplaceTemplateCreator {
+  pub uuid: Uuid,
+  pub name: String,
+  pub avatar_url: Option<String>,
+}
+
+/// A template published to the marketplace by a user, from one of the views they published.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MarketplaceTemplate {
+  pub view_id: Uuid,
+  pub created_at: DateTime<Utc>,
+  pub last_updated_at: DateTime<Utc>,
+  pub name: String,
+  pub description: String,
+  pub license: TemplateLicense,
+  pub screenshot_urls: Vec<String>,
+  pub category: Option<TemplateCategoryMinimal>,
+  pub creator: MarketplaceTemplateCreator,
+  /// The number of times the template was instantiated into a workspace.
+  pub install_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketplaceTemplateWithPublishInfo {
+  #[serde(flatten)]
+  pub template: MarketplaceTemplate,
+  pub publish_info: PublishInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketplaceTemplates {
+  pub templates: Vec<MarketplaceTemplateWithPublishInfo>,
+  /// Passed as the `offset` of the next query to get the next page, `None` on the last page.
+  pub next_offset: Option<u32>,
+}
+
+/// Publishes the published view to the marketplace, or updates its listing if it's already
+/// there.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PublishMarketplaceTemplateParams {
+  pub view_id: Uuid,
+  pub name: String,
+  pub description: String,
+  pub license: TemplateLicense,
+  #[serde(default)]
+  pub screenshot_urls: Vec<String>,
+  pub category_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketplaceTemplateSort {
+  /// The most installed first.
+  #[default]
+  Popular,
+  Newest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetMarketplaceTemplatesQueryParams {
+  pub category_id: Option<Uuid>,
+  pub name_contains: Option<String>,
+  #[serde(default)]
+  pub sort: MarketplaceTemplateSort,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+/// Instantiates the template of the marketplace into the workspace, under the view.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InstallMarketplaceTemplateParams {
+  pub workspace_id: Uuid,
+  pub dest_view_id: String,
+}
+
 #[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Copy, Clone)]
 #[repr(i32)]
 pub enum AccessRequestStatus {
@@ -1520,7 +2535,8 @@ pub struct WorkspaceNamespace {
 #[cfg(test)]
 mod test {
   use crate::dto::{
-    AFCollabEmbeddingParams, AFCollabEmbeddings, CollabParams, CollabParamsV0, EmbeddingContentType,
+    collab_content_hash, AFCollabEmbeddingParams, AFCollabEmbeddings, CollabParams, CollabParamsV0,
+    EmbeddingContentType,
   };
   use crate::error::EntityError;
   use bytes::Bytes;
@@ -1668,4 +2684,22 @@ mod test {
     assert!(result.is_err());
     assert!(matches!(result, Err(EntityError::InvalidData(_))));
   }
+
+  #[test]
+  fn collab_content_hash_ignores_key_order() {
+    let a = serde_json::json!({ "title": "a", "data": { "x": 1, "y": [1, 2] } });
+    let mut data = serde_json::Map::new();
+    data.insert("y".to_string(), serde_json::json!([1, 2]));
+    data.insert("x".to_string(), serde_json::json!(1));
+    let mut b = serde_json::Map::new();
+    b.insert("data".to_string(), serde_json::Value::Object(data));
+    b.insert("title".to_string(), serde_json::json!("a"));
+    assert_eq!(
+      collab_content_hash(&a),
+      collab_content_hash(&serde_json::Value::Object(b))
+    );
+
+    let c = serde_json::json!({ "title": "a", "data": { "x": 1, "y": [2, 1] } });
+    assert_ne!(collab_content_hash(&a), collab_content_hash(&c));
+  }
 }
@@ -77,7 +77,8 @@ pub async fn select_access_request_by_request_id<'a, E: Executor<'a, Database =
         af_workspace.deleted_at,
         af_workspace.workspace_name,
         af_workspace.icon,
-        request_id_workspace_member_count.member_count
+        request_id_workspace_member_count.member_count,
+        af_workspace.archived_at
       ) AS "workspace!: AFWorkspaceWithMemberCountRow",
       (
         af_user.uid,
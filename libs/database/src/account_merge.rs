@@ -0,0 +1,202 @@
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use database_entity::dto::{AFAccessLevel, AFRole};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// The memberships of the target user that were added or changed by the merge.
+pub struct MergedAccount {
+  pub workspace_roles: Vec<(Uuid, AFRole)>,
+  pub collab_access_levels: Vec<(String, AFAccessLevel)>,
+  pub comment_count: u64,
+}
+
+/// Moves the workspace memberships, the collab memberships, the comments and the owned content of
+/// the source user to the target user. When both users are members of the same workspace or collab,
+/// the target user keeps the higher role or access level.
+///
+/// The source user is left without any membership, so it can be deleted afterwards.
+pub async fn merge_user_accounts(
+  txn: &mut Transaction<'_, Postgres>,
+  source_uid: i64,
+  target_uid: i64,
+) -> Result<MergedAccount, AppError> {
+  let workspace_roles = sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_member (uid, role_id, workspace_id, expires_at)
+      SELECT $2, role_id, workspace_id, expires_at
+      FROM af_workspace_member
+      WHERE uid = $1
+      ON CONFLICT (uid, workspace_id) DO UPDATE
+        SET role_id = LEAST(af_workspace_member.role_id, EXCLUDED.role_id),
+            expires_at = CASE
+              WHEN af_workspace_member.expires_at IS NULL OR EXCLUDED.expires_at IS NULL THEN NULL
+              ELSE GREATEST(af_workspace_member.expires_at, EXCLUDED.expires_at)
+            END
+      RETURNING workspace_id, role_id
+    "#,
+    source_uid,
+    target_uid
+  )
+  .fetch_all(txn.deref_mut())
+  .await?
+  .into_iter()
+  .map(|row| (row.workspace_id, AFRole::from(row.role_id)))
+  .collect();
+
+  // The publish permissions are removed along with the memberships of the source user, so they are
+  // copied first.
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_publisher (workspace_id, uid, granted_by)
+      SELECT workspace_id, $2, granted_by
+      FROM af_workspace_publisher
+      WHERE uid = $1
+      ON CONFLICT DO NOTHING
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member WHERE uid = $1
+    "#,
+    source_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  let collab_access_levels = sqlx::query!(
+    r#"
+      WITH moved AS (
+        DELETE FROM af_collab_member
+        WHERE uid = $1
+        RETURNING oid, permission_id, expires_at, workspace_id
+      ),
+      merged AS (
+        INSERT INTO af_collab_member (uid, oid, permission_id, expires_at, workspace_id)
+        SELECT $2, oid, permission_id, expires_at, workspace_id FROM moved
+        ON CONFLICT (uid, oid) DO UPDATE
+          SET permission_id = CASE
+            WHEN (SELECT access_level FROM af_permissions WHERE id = EXCLUDED.permission_id)
+              > (SELECT access_level FROM af_permissions WHERE id = af_collab_member.permission_id)
+            THEN EXCLUDED.permission_id
+            ELSE af_collab_member.permission_id
+          END
+        RETURNING oid, permission_id
+      )
+      SELECT merged.oid, af_permissions.access_level
+      FROM merged
+      JOIN af_permissions ON af_permissions.id = merged.permission_id
+    "#,
+    source_uid,
+    target_uid
+  )
+  .fetch_all(txn.deref_mut())
+  .await?
+  .into_iter()
+  .map(|row| (row.oid, AFAccessLevel::from(row.access_level)))
+  .collect();
+
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace SET owner_uid = $2 WHERE owner_uid = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query!(
+    r#"
+      UPDATE af_collab SET owner_uid = $2 WHERE owner_uid = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  // The published views and their auto-publish are removed along with the user who published them.
+  sqlx::query!(
+    r#"
+      UPDATE af_published_collab SET published_by = $2 WHERE published_by = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query!(
+    r#"
+      UPDATE af_published_collab_auto_publish SET enabled_by = $2 WHERE enabled_by = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  let comment_count = sqlx::query!(
+    r#"
+      UPDATE af_published_view_comment SET created_by = $2 WHERE created_by = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?
+  .rows_affected();
+
+  sqlx::query!(
+    r#"
+      UPDATE af_published_view_comment_attachment SET created_by = $2 WHERE created_by = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  // The reactions the target user already made are kept, the duplicates are removed along with
+  // the source user.
+  sqlx::query!(
+    r#"
+      UPDATE af_published_view_reaction AS r
+      SET created_by = $2
+      WHERE r.created_by = $1
+        AND NOT EXISTS (
+          SELECT 1 FROM af_published_view_reaction
+          WHERE comment_id = r.comment_id
+            AND reaction_type = r.reaction_type
+            AND created_by = $2
+        )
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_custom_emoji SET created_by = $2 WHERE created_by = $1
+    "#,
+    source_uid,
+    target_uid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  Ok(MergedAccount {
+    workspace_roles,
+    collab_access_levels,
+    comment_count,
+  })
+}
@@ -0,0 +1,79 @@
+use app_error::AppError;
+use sqlx::PgPool;
+
+use crate::pg_row::AFAccountProvisionRow;
+
+/// Claims the idempotency key for the provisioning of the account of the email. Returns false if
+/// the key was already claimed.
+pub async fn insert_account_provision(
+  pg_pool: &PgPool,
+  idempotency_key: &str,
+  email: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_account_provision (idempotency_key, email)
+      VALUES ($1, $2)
+      ON CONFLICT DO NOTHING
+    "#,
+    idempotency_key,
+    email
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+pub async fn select_account_provision(
+  pg_pool: &PgPool,
+  idempotency_key: &str,
+) -> Result<Option<AFAccountProvisionRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFAccountProvisionRow,
+    r#"
+      SELECT email, result
+      FROM af_account_provision
+      WHERE idempotency_key = $1
+    "#,
+    idempotency_key
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_account_provision_result(
+  pg_pool: &PgPool,
+  idempotency_key: &str,
+  result: serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_account_provision
+      SET result = $2
+      WHERE idempotency_key = $1
+    "#,
+    idempotency_key,
+    result
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Releases the idempotency key of a provisioning request that failed, so it can be retried.
+pub async fn delete_account_provision(
+  pg_pool: &PgPool,
+  idempotency_key: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_account_provision
+      WHERE idempotency_key = $1 AND result IS NULL
+    "#,
+    idempotency_key
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,173 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::AFAnnouncementRow;
+
+pub async fn insert_announcement(
+  pg_pool: &PgPool,
+  title: &str,
+  message: &str,
+  severity: i16,
+  starts_at: Option<DateTime<Utc>>,
+  ends_at: Option<DateTime<Utc>>,
+  dismissible: bool,
+) -> Result<AFAnnouncementRow, AppError> {
+  let row = sqlx::query_as!(
+    AFAnnouncementRow,
+    r#"
+      INSERT INTO af_announcement (title, message, severity, starts_at, ends_at, dismissible)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING announcement_id, title, message, severity, starts_at, ends_at, dismissible,
+        created_at, updated_at
+    "#,
+    title,
+    message,
+    severity,
+    starts_at,
+    ends_at,
+    dismissible
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Returns `None` if the announcement doesn't exist.
+#[allow(clippy::too_many_arguments)]
+pub async fn update_announcement(
+  pg_pool: &PgPool,
+  announcement_id: &Uuid,
+  title: &str,
+  message: &str,
+  severity: i16,
+  starts_at: Option<DateTime<Utc>>,
+  ends_at: Option<DateTime<Utc>>,
+  dismissible: bool,
+) -> Result<Option<AFAnnouncementRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFAnnouncementRow,
+    r#"
+      UPDATE af_announcement
+      SET title = $2,
+          message = $3,
+          severity = $4,
+          starts_at = $5,
+          ends_at = $6,
+          dismissible = $7,
+          updated_at = NOW()
+      WHERE announcement_id = $1
+      RETURNING announcement_id, title, message, severity, starts_at, ends_at, dismissible,
+        created_at, updated_at
+    "#,
+    announcement_id,
+    title,
+    message,
+    severity,
+    starts_at,
+    ends_at,
+    dismissible
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if the announcement existed.
+pub async fn delete_announcement(
+  pg_pool: &PgPool,
+  announcement_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_announcement WHERE announcement_id = $1
+    "#,
+    announcement_id
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn select_announcement(
+  pg_pool: &PgPool,
+  announcement_id: &Uuid,
+) -> Result<Option<AFAnnouncementRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFAnnouncementRow,
+    r#"
+      SELECT announcement_id, title, message, severity, starts_at, ends_at, dismissible,
+        created_at, updated_at
+      FROM af_announcement
+      WHERE announcement_id = $1
+    "#,
+    announcement_id
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_all_announcements(
+  pg_pool: &PgPool,
+) -> Result<Vec<AFAnnouncementRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFAnnouncementRow,
+    r#"
+      SELECT announcement_id, title, message, severity, starts_at, ends_at, dismissible,
+        created_at, updated_at
+      FROM af_announcement
+      ORDER BY created_at DESC
+    "#
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the announcements currently shown to the user, i.e. the ones that started, haven't
+/// ended and weren't dismissed by the user. The most severe announcements come first.
+pub async fn select_active_announcements_for_user(
+  pg_pool: &PgPool,
+  uid: i64,
+) -> Result<Vec<AFAnnouncementRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFAnnouncementRow,
+    r#"
+      SELECT announcement_id, title, message, severity, starts_at, ends_at, dismissible,
+        created_at, updated_at
+      FROM af_announcement aa
+      WHERE (starts_at IS NULL OR starts_at <= NOW())
+        AND (ends_at IS NULL OR ends_at > NOW())
+        AND NOT EXISTS (
+          SELECT 1 FROM af_announcement_dismissal aad
+          WHERE aad.announcement_id = aa.announcement_id AND aad.uid = $1
+        )
+      ORDER BY severity DESC, created_at DESC
+    "#,
+    uid
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn insert_announcement_dismissal(
+  pg_pool: &PgPool,
+  announcement_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_announcement_dismissal (announcement_id, uid)
+      VALUES ($1, $2)
+      ON CONFLICT DO NOTHING
+    "#,
+    announcement_id,
+    uid
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
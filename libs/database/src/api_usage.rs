@@ -0,0 +1,117 @@
+use app_error::AppError;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::{AFApiUsageRow, AFDailyApiUsageRow};
+
+/// Adds the API calls to the usage of the workspaces on the given date. The calls made on the
+/// workspaces that don't exist are ignored.
+pub async fn upsert_workspace_api_usage(
+  pg_pool: &PgPool,
+  usage_date: NaiveDate,
+  workspace_ids: &[Uuid],
+  route_families: &[String],
+  call_counts: &[i64],
+  client_error_counts: &[i64],
+  server_error_counts: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_api_usage (
+        workspace_id, usage_date, route_family, call_count, client_error_count, server_error_count
+      )
+      SELECT usage.workspace_id, $1, usage.route_family, usage.call_count,
+        usage.client_error_count, usage.server_error_count
+      FROM UNNEST($2::UUID[], $3::TEXT[], $4::BIGINT[], $5::BIGINT[], $6::BIGINT[])
+        AS usage(workspace_id, route_family, call_count, client_error_count, server_error_count)
+      JOIN af_workspace ON af_workspace.workspace_id = usage.workspace_id
+      ON CONFLICT (workspace_id, usage_date, route_family) DO UPDATE SET
+        call_count = af_workspace_api_usage.call_count + EXCLUDED.call_count,
+        client_error_count = af_workspace_api_usage.client_error_count
+          + EXCLUDED.client_error_count,
+        server_error_count = af_workspace_api_usage.server_error_count
+          + EXCLUDED.server_error_count
+    "#,
+    usage_date,
+    workspace_ids,
+    route_families,
+    call_counts,
+    client_error_counts,
+    server_error_counts,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Returns the API calls made on the workspace since the given date, by route family, the most
+/// called first.
+pub async fn select_workspace_api_usage_by_route_family(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFApiUsageRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFApiUsageRow,
+    r#"
+      SELECT
+        route_family,
+        SUM(call_count)::BIGINT AS "call_count!",
+        SUM(client_error_count)::BIGINT AS "client_error_count!",
+        SUM(server_error_count)::BIGINT AS "server_error_count!"
+      FROM af_workspace_api_usage
+      WHERE workspace_id = $1 AND usage_date >= $2
+      GROUP BY route_family
+      ORDER BY 2 DESC, route_family
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the API calls made on the workspace on each day since the given date.
+pub async fn select_daily_workspace_api_usage(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFDailyApiUsageRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDailyApiUsageRow,
+    r#"
+      SELECT
+        usage_date,
+        SUM(call_count)::BIGINT AS "call_count!",
+        SUM(client_error_count)::BIGINT AS "client_error_count!",
+        SUM(server_error_count)::BIGINT AS "server_error_count!"
+      FROM af_workspace_api_usage
+      WHERE workspace_id = $1 AND usage_date >= $2
+      GROUP BY usage_date
+      ORDER BY usage_date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Removes the API usage recorded before the given date. Returns the number of removed rows.
+pub async fn delete_workspace_api_usage_before(
+  pg_pool: &PgPool,
+  before: NaiveDate,
+) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_api_usage WHERE usage_date < $1
+    "#,
+    before
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected())
+}
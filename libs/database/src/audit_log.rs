@@ -0,0 +1,71 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceAuditEventRow;
+
+pub async fn insert_workspace_audit_event<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  actor_uid: i64,
+  action: &str,
+  target: Option<&str>,
+  details: &serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_audit_log (workspace_id, actor_uid, action, target, details)
+      VALUES ($1, $2, $3, $4, $5)
+    "#,
+    workspace_id,
+    actor_uid,
+    action,
+    target,
+    details,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the events recorded in `[since, until)`, most recent first.
+pub async fn select_workspace_audit_events(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: Option<DateTime<Utc>>,
+  until: Option<DateTime<Utc>>,
+  actor_uid: Option<i64>,
+  limit: i64,
+) -> Result<Vec<AFWorkspaceAuditEventRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceAuditEventRow,
+    r#"
+      SELECT
+        log.event_id,
+        log.actor_uid,
+        au.name AS "actor_name?",
+        au.email AS "actor_email?",
+        log.action,
+        log.target,
+        log.details,
+        log.created_at
+      FROM af_workspace_audit_log log
+      LEFT JOIN af_user au ON au.uid = log.actor_uid
+      WHERE log.workspace_id = $1
+        AND ($2::TIMESTAMPTZ IS NULL OR log.created_at >= $2)
+        AND ($3::TIMESTAMPTZ IS NULL OR log.created_at < $3)
+        AND ($4::BIGINT IS NULL OR log.actor_uid = $4)
+      ORDER BY log.created_at DESC, log.event_id DESC
+      LIMIT $5
+    "#,
+    workspace_id,
+    since,
+    until,
+    actor_uid,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
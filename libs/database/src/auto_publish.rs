@@ -0,0 +1,124 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFAutoPublishViewRow;
+
+pub async fn upsert_auto_publish_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_collab_auto_publish (workspace_id, view_id, enabled_by)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (view_id)
+      DO UPDATE SET enabled_by = EXCLUDED.enabled_by, enabled_at = CURRENT_TIMESTAMP
+    "#,
+    workspace_id,
+    view_id,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_auto_publish_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_published_collab_auto_publish
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+    workspace_id,
+    view_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records a change of the document. Does nothing if the auto-publish is not enabled for the
+/// document.
+pub async fn update_auto_publish_view_changed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_published_collab_auto_publish
+      SET changed_at = CURRENT_TIMESTAMP,
+          pending_since = COALESCE(pending_since, CURRENT_TIMESTAMP)
+      WHERE view_id = $1
+    "#,
+    view_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the documents whose last change is older than `debounce_secs`, or whose first pending
+/// change is older than `max_delay_secs` for the documents that are continuously edited.
+pub async fn select_auto_publish_views_to_republish<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  debounce_secs: i64,
+  max_delay_secs: i64,
+  limit: i64,
+) -> Result<Vec<AFAutoPublishViewRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFAutoPublishViewRow,
+    r#"
+      SELECT
+        apa.workspace_id,
+        apa.view_id,
+        au.uuid AS publisher_uuid,
+        apa.changed_at AS "changed_at!"
+      FROM af_published_collab_auto_publish apa
+      JOIN af_user au ON apa.enabled_by = au.uid
+      WHERE apa.changed_at IS NOT NULL
+        AND (apa.changed_at <= NOW() - make_interval(secs => $1::BIGINT)
+          OR apa.pending_since <= NOW() - make_interval(secs => $2::BIGINT))
+      ORDER BY apa.pending_since
+      LIMIT $3
+    "#,
+    debounce_secs,
+    max_delay_secs,
+    limit
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Records the result of a republish. The pending changes are cleared unless the document was
+/// changed again while it was being republished.
+pub async fn update_auto_publish_view_republished<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  changed_at: &DateTime<Utc>,
+  error: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_published_collab_auto_publish
+      SET last_error = $3,
+          pending_since = CASE WHEN changed_at = $2 THEN NULL ELSE pending_since END,
+          changed_at = CASE WHEN changed_at = $2 THEN NULL ELSE changed_at END
+      WHERE view_id = $1
+    "#,
+    view_id,
+    changed_at,
+    error
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,99 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFBulkInviteRow;
+
+pub async fn insert_bulk_invite<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  workspace_id: &Uuid,
+  created_by: i64,
+  total_count: i32,
+  errors: serde_json::Value,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_bulk_invite (task_id, workspace_id, created_by, total_count, errors)
+      VALUES ($1, $2, $3, $4, $5)
+    "#,
+    task_id,
+    workspace_id,
+    created_by,
+    total_count,
+    errors
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records the result of inviting a row. The error, if any, is appended to the errors of the task.
+pub async fn update_bulk_invite_progress<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  error: Option<serde_json::Value>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_bulk_invite
+      SET
+        success_count = success_count + CASE WHEN $2::jsonb IS NULL THEN 1 ELSE 0 END,
+        errors = CASE WHEN $2::jsonb IS NULL THEN errors ELSE errors || jsonb_build_array($2::jsonb) END,
+        updated_at = CURRENT_TIMESTAMP
+      WHERE task_id = $1
+    "#,
+    task_id,
+    error
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_bulk_invite_status<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  task_id: &Uuid,
+  status: i16,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_bulk_invite
+      SET status = $2, updated_at = CURRENT_TIMESTAMP
+      WHERE task_id = $1
+    "#,
+    task_id,
+    status
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_bulk_invite<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<Option<AFBulkInviteRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFBulkInviteRow,
+    r#"
+      SELECT
+        task_id,
+        status,
+        total_count,
+        success_count,
+        jsonb_array_length(errors) AS "failed_count!",
+        errors,
+        created_at,
+        updated_at
+      FROM af_workspace_bulk_invite
+      WHERE workspace_id = $1 AND task_id = $2
+    "#,
+    workspace_id,
+    task_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
@@ -0,0 +1,66 @@
+use app_error::AppError;
+use sqlx::PgPool;
+
+use crate::pg_row::AFClientVersionPolicyRow;
+
+pub async fn upsert_client_version_policy(
+  pg_pool: &PgPool,
+  version_req: &str,
+  status: i16,
+  message: Option<&str>,
+  upgrade_url: Option<&str>,
+) -> Result<AFClientVersionPolicyRow, AppError> {
+  let row = sqlx::query_as!(
+    AFClientVersionPolicyRow,
+    r#"
+      INSERT INTO af_client_version_policy (version_req, status, message, upgrade_url)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (version_req)
+      DO UPDATE SET
+        status = EXCLUDED.status,
+        message = EXCLUDED.message,
+        upgrade_url = EXCLUDED.upgrade_url,
+        updated_at = NOW()
+      RETURNING version_req, status, message, upgrade_url, updated_at
+    "#,
+    version_req,
+    status,
+    message,
+    upgrade_url
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if the policy existed.
+pub async fn delete_client_version_policy(
+  pg_pool: &PgPool,
+  version_req: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_client_version_policy WHERE version_req = $1
+    "#,
+    version_req
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn select_client_version_policies(
+  pg_pool: &PgPool,
+) -> Result<Vec<AFClientVersionPolicyRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFClientVersionPolicyRow,
+    r#"
+      SELECT version_req, status, message, upgrade_url, updated_at
+      FROM af_client_version_policy
+      ORDER BY version_req
+    "#
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
@@ -0,0 +1,207 @@
+use std::io::Read;
+use std::ops::DerefMut;
+
+use anyhow::anyhow;
+use brotli::{CompressorReader, Decompressor};
+use collab_entity::CollabType;
+use sqlx::PgPool;
+
+use crate::collab::partition_key_from_collab_type;
+use app_error::AppError;
+
+const ARCHIVE_BUFFER_SIZE: usize = 4096;
+const ARCHIVE_COMPRESSION_QUALITY: u32 = 9;
+
+#[derive(Debug, Clone)]
+pub struct CollabArchiveCandidate {
+  pub oid: String,
+  pub partition_key: i32,
+}
+
+/// Returns the collabs that haven't been written for `inactive_secs` and are not archived yet.
+pub async fn select_collabs_to_archive(
+  pg_pool: &PgPool,
+  inactive_secs: i64,
+  limit: i64,
+) -> Result<Vec<CollabArchiveCandidate>, AppError> {
+  let candidates = sqlx::query_as!(
+    CollabArchiveCandidate,
+    r#"
+      SELECT oid, partition_key
+      FROM af_collab
+      WHERE archived_at IS NULL
+        AND deleted_at IS NULL
+        AND updated_at < NOW() - $1 * INTERVAL '1 second'
+      LIMIT $2
+    "#,
+    inactive_secs as f64,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(candidates)
+}
+
+/// Moves the payload of the collab to the archive table, compressed. The collab is left untouched
+/// if it has been written since it was selected. Returns whether the collab was archived.
+pub async fn archive_collab(
+  pg_pool: &PgPool,
+  candidate: &CollabArchiveCandidate,
+  inactive_secs: i64,
+) -> Result<bool, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let row = sqlx::query!(
+    r#"
+      SELECT workspace_id, blob
+      FROM af_collab
+      WHERE oid = $1 AND partition_key = $2
+        AND archived_at IS NULL
+        AND deleted_at IS NULL
+        AND updated_at < NOW() - $3 * INTERVAL '1 second'
+      FOR UPDATE
+    "#,
+    candidate.oid,
+    candidate.partition_key,
+    inactive_secs as f64,
+  )
+  .fetch_optional(txn.deref_mut())
+  .await?;
+  let row = match row {
+    Some(row) if !row.blob.is_empty() => row,
+    _ => return Ok(false),
+  };
+
+  let len = row.blob.len() as i32;
+  let compressed = compress_collab_blob(row.blob).await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_archive (oid, partition_key, workspace_id, blob, len)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (oid, partition_key)
+      DO UPDATE SET blob = $4, len = $5, archived_at = NOW()
+    "#,
+    candidate.oid,
+    candidate.partition_key,
+    row.workspace_id,
+    compressed,
+    len,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      UPDATE af_collab
+      SET blob = ''::BYTEA, archived_at = NOW()
+      WHERE oid = $1 AND partition_key = $2
+    "#,
+    candidate.oid,
+    candidate.partition_key,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  txn.commit().await?;
+  Ok(true)
+}
+
+/// Moves the payload of an archived collab back to `af_collab` and returns it. Returns `None` if
+/// the collab is not archived.
+pub async fn rehydrate_collab(
+  pg_pool: &PgPool,
+  object_id: &str,
+  collab_type: &CollabType,
+) -> Result<Option<Vec<u8>>, AppError> {
+  let partition_key = partition_key_from_collab_type(collab_type);
+  let mut txn = pg_pool.begin().await?;
+  let compressed = sqlx::query_scalar!(
+    r#"
+      SELECT archive.blob
+      FROM af_collab collab
+      JOIN af_collab_archive archive
+        ON collab.oid = archive.oid AND collab.partition_key = archive.partition_key
+      WHERE collab.oid = $1 AND collab.partition_key = $2 AND collab.archived_at IS NOT NULL
+      FOR UPDATE OF collab
+    "#,
+    object_id,
+    partition_key,
+  )
+  .fetch_optional(txn.deref_mut())
+  .await?;
+  let compressed = match compressed {
+    Some(compressed) => compressed,
+    None => return Ok(None),
+  };
+
+  let blob = decompress_collab_blob(compressed).await?;
+  // The rehydrated collab counts as touched, so that it isn't archived again right away.
+  sqlx::query!(
+    r#"
+      UPDATE af_collab
+      SET blob = $3, len = $4, archived_at = NULL, updated_at = NOW()
+      WHERE oid = $1 AND partition_key = $2
+    "#,
+    object_id,
+    partition_key,
+    blob,
+    blob.len() as i32,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query!(
+    "DELETE FROM af_collab_archive WHERE oid = $1 AND partition_key = $2",
+    object_id,
+    partition_key,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  txn.commit().await?;
+  Ok(Some(blob))
+}
+
+/// Deletes the archived payloads of the collabs that were written again after being archived,
+/// which replaced their payload in `af_collab`.
+pub async fn delete_stale_collab_archives(pg_pool: &PgPool) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_archive archive
+      USING af_collab collab
+      WHERE archive.oid = collab.oid
+        AND archive.partition_key = collab.partition_key
+        AND collab.archived_at IS NULL
+        AND archive.archived_at < collab.updated_at
+    "#
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+async fn compress_collab_blob(blob: Vec<u8>) -> Result<Vec<u8>, AppError> {
+  tokio::task::spawn_blocking(move || {
+    let mut compressor = CompressorReader::new(
+      blob.as_slice(),
+      ARCHIVE_BUFFER_SIZE,
+      ARCHIVE_COMPRESSION_QUALITY,
+      22,
+    );
+    let mut compressed = Vec::new();
+    compressor
+      .read_to_end(&mut compressed)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to compress collab: {}", err)))?;
+    Ok(compressed)
+  })
+  .await
+  .map_err(|err| AppError::Internal(err.into()))?
+}
+
+async fn decompress_collab_blob(compressed: Vec<u8>) -> Result<Vec<u8>, AppError> {
+  tokio::task::spawn_blocking(move || {
+    let mut decompressor = Decompressor::new(compressed.as_slice(), ARCHIVE_BUFFER_SIZE);
+    let mut blob = Vec::new();
+    decompressor
+      .read_to_end(&mut blob)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decompress collab: {}", err)))?;
+    Ok(blob)
+  })
+  .await
+  .map_err(|err| AppError::Internal(err.into()))?
+}
@@ -12,6 +12,7 @@ use tracing::{error, event, Level};
 use crate::collab::disk_cache::CollabDiskCache;
 use crate::collab::mem_cache::{cache_exp_secs_from_collab_type, CollabMemCache};
 use crate::collab::CollabMetadata;
+use crate::residency::PgPoolRouter;
 use app_error::AppError;
 use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
 
@@ -25,8 +26,17 @@ pub struct CollabCache {
 
 impl CollabCache {
   pub fn new(redis_conn_manager: redis::aio::ConnectionManager, pg_pool: PgPool) -> Self {
+    Self::with_router(redis_conn_manager, PgPoolRouter::new(pg_pool))
+  }
+
+  /// Create a cache that stores the collabs of each workspace in the database of the
+  /// workspace's region.
+  pub fn with_router(
+    redis_conn_manager: redis::aio::ConnectionManager,
+    router: PgPoolRouter,
+  ) -> Self {
     let mem_cache = CollabMemCache::new(redis_conn_manager.clone());
-    let disk_cache = CollabDiskCache::new(pg_pool.clone());
+    let disk_cache = CollabDiskCache::with_router(router);
     Self {
       disk_cache,
       mem_cache,
@@ -220,6 +230,11 @@ impl CollabCache {
     }
   }
 
+  /// See [CollabDiskCache::rehydration_state].
+  pub fn rehydration_state(&self) -> (u64, u64) {
+    self.disk_cache.rehydration_state()
+  }
+
   pub async fn delete_collab(&self, object_id: &str) -> Result<(), AppError> {
     self.mem_cache.remove_encode_collab(object_id).await?;
     self.disk_cache.delete_collab(object_id).await?;
@@ -240,6 +255,10 @@ impl CollabCache {
   pub fn pg_pool(&self) -> &sqlx::PgPool {
     &self.disk_cache.pg_pool
   }
+
+  pub fn router(&self) -> &PgPoolRouter {
+    self.disk_cache.router()
+  }
 }
 
 #[derive(Debug)]
@@ -2,10 +2,13 @@ use anyhow::{anyhow, Context};
 use collab_entity::CollabType;
 use database_entity::dto::{
   AFAccessLevel, AFCollabMember, AFPermission, AFSnapshotMeta, AFSnapshotMetas, CollabParams,
-  QueryCollab, QueryCollabResult, RawData,
+  QueryCollab, QueryCollabResult, RawData, SnapshotRetentionPolicy,
 };
 
-use crate::collab::{partition_key_from_collab_type, SNAPSHOT_PER_HOUR};
+use crate::collab::{
+  collab_checksum, is_collab_checksum_valid, partition_key_from_collab_type,
+  report_corrupted_collab, SNAPSHOT_PER_HOUR,
+};
 use crate::pg_row::AFSnapshotRow;
 use crate::pg_row::{AFCollabMemberAccessLevelRow, AFCollabRowMeta};
 use app_error::AppError;
@@ -54,6 +57,7 @@ pub async fn insert_into_af_collab(
   let encrypt = 0;
   let partition_key = crate::collab::partition_key_from_collab_type(&params.collab_type);
   let workspace_id = Uuid::from_str(workspace_id)?;
+  let checksum = collab_checksum(&params.encoded_collab_v1);
   let existing_workspace_id: Option<Uuid> = sqlx::query_scalar!(
     "SELECT workspace_id FROM af_collab WHERE oid = $1",
     &params.object_id
@@ -76,13 +80,15 @@ pub async fn insert_into_af_collab(
       if existing_workspace_id == workspace_id {
         sqlx::query!(
           "UPDATE af_collab \
-        SET blob = $3, len = $4, encrypt = $5, owner_uid = $6 WHERE oid = $1 AND partition_key = $2;",
+        SET blob = $3, len = $4, encrypt = $5, owner_uid = $6, checksum = $7, updated_at = NOW(), archived_at = NULL \
+        WHERE oid = $1 AND partition_key = $2;",
           params.object_id,
           partition_key,
           params.encoded_collab_v1.as_ref(),
           params.encoded_collab_v1.len() as i32,
           encrypt,
           uid,
+          checksum,
         )
         .execute(tx.deref_mut())
         .await.map_err(|err| {
@@ -115,15 +121,16 @@ pub async fn insert_into_af_collab(
 
       sqlx::query!(
         r#"
-        INSERT INTO af_collab_member (uid, oid, permission_id)
-        VALUES ($1, $2, $3)
+        INSERT INTO af_collab_member (uid, oid, permission_id, workspace_id)
+        VALUES ($1, $2, $3, $4)
         ON CONFLICT (uid, oid)
         DO UPDATE
           SET permission_id = excluded.permission_id;
         "#,
         uid,
         params.object_id,
-        permission_id
+        permission_id,
+        workspace_id
       )
       .execute(tx.deref_mut())
       .await
@@ -138,8 +145,8 @@ pub async fn insert_into_af_collab(
       })?;
 
       sqlx::query!(
-        "INSERT INTO af_collab (oid, blob, len, partition_key, encrypt, owner_uid, workspace_id)\
-          VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        "INSERT INTO af_collab (oid, blob, len, partition_key, encrypt, owner_uid, workspace_id, checksum)\
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
         params.object_id,
         params.encoded_collab_v1.as_ref(),
         params.encoded_collab_v1.len() as i32,
@@ -147,6 +154,7 @@ pub async fn insert_into_af_collab(
         encrypt,
         uid,
         workspace_id,
+        checksum,
       )
       .execute(tx.deref_mut())
       .await.map_err(|err| {
@@ -235,6 +243,7 @@ pub async fn insert_into_af_collab_bulk_for_user(
   let mut object_ids: Vec<Uuid> = Vec::with_capacity(len);
   let mut blobs: Vec<Vec<u8>> = Vec::with_capacity(len);
   let mut lengths: Vec<i32> = Vec::with_capacity(len);
+  let mut checksums: Vec<Vec<u8>> = Vec::with_capacity(len);
   let mut partition_keys: Vec<i32> = Vec::with_capacity(len);
   let mut permission_ids: Vec<i32> = Vec::with_capacity(len);
   let uids: Vec<i64> = vec![*uid; collab_params_list.len()];
@@ -256,6 +265,7 @@ pub async fn insert_into_af_collab_bulk_for_user(
     object_ids.push(Uuid::from_str(&params.object_id)?);
     blobs.push(params.encoded_collab_v1.to_vec());
     lengths.push(params.encoded_collab_v1.len() as i32);
+    checksums.push(collab_checksum(&params.encoded_collab_v1));
     partition_keys.push(partition_key);
     permission_ids.push(permission_id);
   }
@@ -263,14 +273,15 @@ pub async fn insert_into_af_collab_bulk_for_user(
   // Bulk insert into `af_collab_member` for the user and provided collab params
   sqlx::query!(
     r#"
-      INSERT INTO af_collab_member (uid, oid, permission_id)
-      SELECT * FROM UNNEST($1::bigint[], $2::uuid[], $3::int[])
+      INSERT INTO af_collab_member (uid, oid, permission_id, workspace_id)
+      SELECT * FROM UNNEST($1::bigint[], $2::uuid[], $3::int[], $4::uuid[])
       ON CONFLICT (uid, oid)
       DO NOTHING;
     "#,
     &uids,
     &object_ids,
-    &permission_ids
+    &permission_ids,
+    &workspace_ids
   )
   .execute(tx.deref_mut())
   .await
@@ -285,8 +296,8 @@ pub async fn insert_into_af_collab_bulk_for_user(
   // Bulk insert into `af_collab` for the provided collab params
   sqlx::query!(
       r#"
-        INSERT INTO af_collab (oid, blob, len, partition_key, encrypt, owner_uid, workspace_id)
-        SELECT * FROM UNNEST($1::uuid[], $2::bytea[], $3::int[], $4::int[], $5::int[], $6::bigint[], $7::uuid[])
+        INSERT INTO af_collab (oid, blob, len, partition_key, encrypt, owner_uid, workspace_id, checksum)
+        SELECT * FROM UNNEST($1::uuid[], $2::bytea[], $3::int[], $4::int[], $5::int[], $6::bigint[], $7::uuid[], $8::bytea[])
         ON CONFLICT (oid, partition_key)
         DO NOTHING;
       "#,
@@ -296,7 +307,8 @@ pub async fn insert_into_af_collab_bulk_for_user(
       &partition_keys,
       &vec![encrypt; collab_params_list.len()],
       &uids,
-      &workspace_ids
+      &workspace_ids,
+      &checksums
     )
       .execute(tx.deref_mut())
       .await
@@ -334,6 +346,32 @@ where
   .await
 }
 
+/// Returns the blob of the collab along with its checksum, `None` if the collab was written before
+/// the checksums were introduced.
+#[inline]
+pub async fn select_blob_and_checksum_from_af_collab<'a, E>(
+  conn: E,
+  collab_type: &CollabType,
+  object_id: &str,
+) -> Result<(Vec<u8>, Option<Vec<u8>>), sqlx::Error>
+where
+  E: Executor<'a, Database = Postgres>,
+{
+  let partition_key = partition_key_from_collab_type(collab_type);
+  let row = sqlx::query!(
+    r#"
+        SELECT blob, checksum
+        FROM af_collab
+        WHERE oid = $1 AND partition_key = $2 AND deleted_at IS NULL;
+        "#,
+    object_id,
+    partition_key,
+  )
+  .fetch_one(conn)
+  .await?;
+  Ok((row.blob, row.checksum))
+}
+
 #[inline]
 pub async fn select_collab_meta_from_af_collab<'a, E>(
   conn: E,
@@ -377,7 +415,7 @@ pub async fn batch_select_collab_blob(
     let par_results: Result<Vec<QueryCollabData>, sqlx::Error> = sqlx::query_as!(
       QueryCollabData,
       r#"
-       SELECT oid, blob
+       SELECT oid, blob, checksum
        FROM af_collab
        WHERE oid = ANY($1) AND partition_key = $2 AND deleted_at IS NULL;
     "#,
@@ -391,14 +429,24 @@ pub async fn batch_select_collab_blob(
       Ok(par_results) => {
         object_ids.retain(|oid| !par_results.iter().any(|par_result| par_result.oid == *oid));
 
-        results.extend(par_results.into_iter().map(|par_result| {
-          (
+        for par_result in par_results {
+          if !is_collab_checksum_valid(&par_result.blob, par_result.checksum.as_deref()) {
+            report_corrupted_collab(pg_pool, &par_result.oid, partition_key).await;
+            results.insert(
+              par_result.oid.clone(),
+              QueryCollabResult::Failed {
+                error: format!("The collab {} is corrupted", par_result.oid),
+              },
+            );
+            continue;
+          }
+          results.insert(
             par_result.oid,
             QueryCollabResult::Success {
               encode_collab_v1: par_result.blob,
             },
-          )
-        }));
+          );
+        }
 
         results.extend(object_ids.into_iter().map(|oid| {
           (
@@ -420,6 +468,7 @@ pub async fn batch_select_collab_blob(
 struct QueryCollabData {
   oid: String,
   blob: RawData,
+  checksum: Option<Vec<u8>>,
 }
 
 pub async fn create_snapshot(
@@ -432,14 +481,15 @@ pub async fn create_snapshot(
 
   sqlx::query!(
     r#"
-        INSERT INTO af_collab_snapshot (oid, blob, len, encrypt, workspace_id)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO af_collab_snapshot (oid, blob, len, encrypt, workspace_id, checksum)
+        VALUES ($1, $2, $3, $4, $5, $6)
         "#,
     object_id,
     encoded_collab_v1,
     encoded_collab_v1.len() as i32,
     encrypt,
     workspace_id,
+    collab_checksum(encoded_collab_v1),
   )
   .execute(pg_pool)
   .await?;
@@ -485,23 +535,23 @@ pub async fn should_create_snapshot2<'a, E: Executor<'a, Database = Postgres>>(
 /// Creates a new snapshot in the `af_collab_snapshot` table and maintains the total number of snapshots
 /// within a specified limit for a given object ID (`oid`).
 ///
-/// This asynchronous function inserts a new snapshot into the database and ensures that the total number
-/// of snapshots stored for the specified `oid` does not exceed the provided `snapshot_limit`. If the limit
-/// is exceeded, the oldest snapshots are deleted to maintain the limit.
+/// This asynchronous function inserts a new snapshot into the database and ensures that the snapshots
+/// stored for the specified `oid` stay within the provided `retention`. The oldest snapshots beyond
+/// the maximum count, and the snapshots older than the maximum age, are deleted.
 ///
 pub async fn create_snapshot_and_maintain_limit<'a>(
   mut transaction: Transaction<'a, Postgres>,
   workspace_id: &str,
   oid: &str,
   encoded_collab_v1: &[u8],
-  snapshot_limit: i64,
+  retention: &SnapshotRetentionPolicy,
 ) -> Result<AFSnapshotMeta, AppError> {
   let workspace_id = Uuid::from_str(workspace_id)?;
   let snapshot_meta = sqlx::query_as!(
     AFSnapshotMeta,
     r#"
-      INSERT INTO af_collab_snapshot (oid, blob, len, encrypt, workspace_id)
-      VALUES ($1, $2, $3, $4, $5)
+      INSERT INTO af_collab_snapshot (oid, blob, len, encrypt, workspace_id, checksum)
+      VALUES ($1, $2, $3, $4, $5, $6)
       RETURNING sid AS snapshot_id, oid AS object_id, created_at
     "#,
     oid,
@@ -509,19 +559,26 @@ pub async fn create_snapshot_and_maintain_limit<'a>(
     encoded_collab_v1.len() as i64,
     0,
     workspace_id,
+    collab_checksum(encoded_collab_v1),
   )
   .fetch_one(transaction.deref_mut())
   .await?;
 
-  // When a new snapshot is created that surpasses the preset limit, older snapshots will be deleted to maintain the limit
+  // When a new snapshot is created that surpasses the retention limits, older snapshots will be
+  // deleted to maintain the limits
   sqlx::query(
     r#"
        DELETE FROM af_collab_snapshot
-       WHERE oid = $1 AND sid NOT IN ( SELECT sid FROM af_collab_snapshot WHERE oid = $1 ORDER BY created_at DESC LIMIT $2)
+       WHERE oid = $1
+         AND (
+           sid NOT IN ( SELECT sid FROM af_collab_snapshot WHERE oid = $1 ORDER BY created_at DESC LIMIT $2)
+           OR ($3 > 0 AND created_at < NOW() - $3 * INTERVAL '1 day')
+         )
       "#,
     )
     .bind(oid)
-    .bind(snapshot_limit)
+    .bind(retention.max_count as i64)
+    .bind(retention.max_age_days as i32)
     .execute(transaction.deref_mut())
     .await?;
 
@@ -575,6 +632,7 @@ pub async fn get_all_collab_snapshot_meta(
 #[instrument(level = "trace", skip(txn), err)]
 pub async fn upsert_collab_member_with_txn<T: AsRef<str> + Debug>(
   uid: i64,
+  workspace_id: &Uuid,
   oid: T,
   access_level: &AFAccessLevel,
   txn: &mut Transaction<'_, sqlx::Postgres>,
@@ -595,15 +653,17 @@ pub async fn upsert_collab_member_with_txn<T: AsRef<str> + Debug>(
 
   sqlx::query!(
     r#"
-    INSERT INTO af_collab_member (uid, oid, permission_id)
-    VALUES ($1, $2, $3)
+    INSERT INTO af_collab_member (uid, oid, permission_id, workspace_id)
+    VALUES ($1, $2, $3, $4)
     ON CONFLICT (uid, oid)
     DO UPDATE
-      SET permission_id = excluded.permission_id;
+      SET permission_id = excluded.permission_id,
+          workspace_id = excluded.workspace_id;
     "#,
     uid,
     oid,
-    permission_id
+    permission_id,
+    workspace_id
   )
   .execute(txn.deref_mut())
   .await
@@ -619,11 +679,12 @@ pub async fn upsert_collab_member_with_txn<T: AsRef<str> + Debug>(
 #[inline]
 pub async fn insert_collab_member(
   uid: i64,
+  workspace_id: &Uuid,
   oid: &str,
   access_level: &AFAccessLevel,
   txn: &mut Transaction<'_, sqlx::Postgres>,
 ) -> Result<(), AppError> {
-  upsert_collab_member_with_txn(uid, oid, access_level, txn).await?;
+  upsert_collab_member_with_txn(uid, workspace_id, oid, access_level, txn).await?;
   Ok(())
 }
 
@@ -646,10 +707,11 @@ pub fn select_collab_member_access_level(
   sqlx::query_as!(
     AFCollabMemberAccessLevelRow,
     r#"
-      SELECT uid, oid, access_level
+      SELECT uid, oid, access_level, expires_at
       FROM af_collab_member
       INNER JOIN af_permissions
         ON af_collab_member.permission_id = af_permissions.id
+      WHERE expires_at IS NULL OR expires_at > NOW()
     "#
   )
   .fetch(pg_pool)
@@ -667,10 +729,12 @@ pub async fn select_collab_members(
         af_permissions.id,
         af_permissions.name,
         af_permissions.access_level,
-        af_permissions.description
+        af_permissions.description,
+        af_collab_member.expires_at
       FROM af_collab_member
       JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
       WHERE af_collab_member.oid = $1
+        AND (af_collab_member.expires_at IS NULL OR af_collab_member.expires_at > NOW())
       ORDER BY af_collab_member.created_at ASC
     "#,
   )
@@ -682,6 +746,36 @@ pub async fn select_collab_members(
   Ok(members)
 }
 
+/// Returns the collabs of the workspace that are explicitly granted to the user, along with the
+/// access level.
+#[inline]
+pub async fn select_collab_member_access_level_for_user<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  uid: &i64,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFCollabMemberAccessLevelRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabMemberAccessLevelRow,
+    r#"
+      SELECT uid, oid, access_level, expires_at
+      FROM af_collab_member
+      INNER JOIN af_permissions
+        ON af_collab_member.permission_id = af_permissions.id
+      WHERE uid = $1
+        AND workspace_id = $2
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    uid,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
 #[inline]
 pub async fn select_collab_member<'a, E: Executor<'a, Database = Postgres>>(
   uid: &i64,
@@ -690,10 +784,11 @@ pub async fn select_collab_member<'a, E: Executor<'a, Database = Postgres>>(
 ) -> Result<AFCollabMember, AppError> {
   let row = sqlx::query(
   r#"
-    SELECT af_collab_member.uid, af_collab_member.oid, af_permissions.id, af_permissions.name, af_permissions.access_level, af_permissions.description
+    SELECT af_collab_member.uid, af_collab_member.oid, af_permissions.id, af_permissions.name, af_permissions.access_level, af_permissions.description, af_collab_member.expires_at
     FROM af_collab_member
     JOIN af_permissions ON af_collab_member.permission_id = af_permissions.id
     WHERE af_collab_member.uid = $1 AND af_collab_member.oid = $2
+      AND (af_collab_member.expires_at IS NULL OR af_collab_member.expires_at > NOW())
   "#,
   )
   .bind(uid)
@@ -718,6 +813,7 @@ fn collab_member_try_from_row(row: PgRow) -> Result<AFCollabMember, sqlx::Error>
     uid: row.try_get(0)?,
     oid: row.try_get(1)?,
     permission,
+    expires_at: row.try_get(6)?,
   })
 }
 
@@ -729,7 +825,12 @@ pub async fn is_collab_member_exists<'a, E: Executor<'a, Database = Postgres>>(
 ) -> Result<bool, sqlx::Error> {
   let result = sqlx::query_scalar!(
     r#"
-      SELECT EXISTS (SELECT 1 FROM af_collab_member WHERE oid = $1 AND uid = $2 LIMIT 1)
+      SELECT EXISTS (
+        SELECT 1 FROM af_collab_member
+        WHERE oid = $1 AND uid = $2
+          AND (expires_at IS NULL OR expires_at > NOW())
+        LIMIT 1
+      )
     "#,
     &oid,
     &uid,
@@ -792,3 +893,26 @@ pub async fn select_workspace_database_oid<'a, E: Executor<'a, Database = Postgr
   .fetch_one(executor)
   .await
 }
+
+/// Returns the object id and the type of all the collabs of the workspace.
+pub async fn select_workspace_collab_oids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<(String, CollabType)>, AppError> {
+  let records = sqlx::query!(
+    r#"
+      SELECT oid, partition_key
+      FROM af_collab
+      WHERE workspace_id = $1 AND deleted_at IS NULL
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    records
+      .into_iter()
+      .map(|record| (record.oid, CollabType::from(record.partition_key)))
+      .collect(),
+  )
+}
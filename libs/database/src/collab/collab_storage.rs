@@ -14,7 +14,6 @@ use sqlx::Transaction;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-pub const COLLAB_SNAPSHOT_LIMIT: i64 = 30;
 pub const SNAPSHOT_PER_HOUR: i64 = 6;
 pub type AppResult<T, E = AppError> = core::result::Result<T, E>;
 
@@ -62,6 +61,10 @@ pub enum GetCollabOrigin {
 pub trait CollabStorage: Send + Sync + 'static {
   fn encode_collab_redis_query_state(&self) -> (u64, u64);
 
+  /// Returns the number of archived collabs rehydrated on access and the total rehydration time in
+  /// milliseconds.
+  fn collab_rehydration_state(&self) -> (u64, u64);
+
   /// Insert/update the collaboration object in the storage.
   /// # Arguments
   /// * `workspace_id` - The ID of the workspace.
@@ -178,6 +181,10 @@ where
     self.as_ref().encode_collab_redis_query_state()
   }
 
+  fn collab_rehydration_state(&self) -> (u64, u64) {
+    self.as_ref().collab_rehydration_state()
+  }
+
   async fn queue_insert_or_update_collab(
     &self,
     workspace_id: &str,
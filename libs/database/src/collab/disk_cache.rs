@@ -1,5 +1,7 @@
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use collab::entity::EncodedCollab;
 use collab_entity::CollabType;
@@ -8,29 +10,63 @@ use tokio::time::sleep;
 use tracing::{event, instrument, Level};
 use uuid::Uuid;
 
+use crate::auto_publish::update_auto_publish_view_changed;
 use crate::collab::util::encode_collab_from_bytes;
 use crate::collab::{
-  batch_select_collab_blob, insert_into_af_collab, is_collab_exists, select_blob_from_af_collab,
+  batch_select_collab_blob, insert_into_af_collab, is_collab_checksum_valid, is_collab_exists,
+  partition_key_from_collab_type, rehydrate_collab, report_corrupted_collab,
+  select_blob_and_checksum_from_af_collab, select_blob_from_af_collab,
   select_collab_meta_from_af_collab, AppResult,
 };
 use crate::index::upsert_collab_embeddings;
+use crate::insights::upsert_collab_edit_activity;
 use crate::pg_row::AFCollabRowMeta;
+use crate::residency::PgPoolRouter;
 use app_error::AppError;
 use database_entity::dto::{CollabParams, QueryCollab, QueryCollabResult};
 
 #[derive(Clone)]
 pub struct CollabDiskCache {
   pub pg_pool: PgPool,
+  router: PgPoolRouter,
+  rehydration_count: Arc<AtomicU64>,
+  rehydration_millis: Arc<AtomicU64>,
 }
 
 impl CollabDiskCache {
   pub fn new(pg_pool: PgPool) -> Self {
-    Self { pg_pool }
+    Self::with_router(PgPoolRouter::new(pg_pool))
+  }
+
+  pub fn with_router(router: PgPoolRouter) -> Self {
+    Self {
+      pg_pool: router.default_pool().clone(),
+      router,
+      rehydration_count: Arc::new(AtomicU64::new(0)),
+      rehydration_millis: Arc::new(AtomicU64::new(0)),
+    }
+  }
+
+  /// Returns the number of archived collabs moved back to `af_collab` on access, and the total time
+  /// spent on it in milliseconds.
+  pub fn rehydration_state(&self) -> (u64, u64) {
+    (
+      self.rehydration_count.load(Ordering::Relaxed),
+      self.rehydration_millis.load(Ordering::Relaxed),
+    )
+  }
+
+  pub fn router(&self) -> &PgPoolRouter {
+    &self.router
   }
 
   pub async fn is_exist(&self, object_id: &str) -> AppResult<bool> {
-    let is_exist = is_collab_exists(object_id, &self.pg_pool).await?;
-    Ok(is_exist)
+    for pg_pool in self.router.all_pools() {
+      if is_collab_exists(object_id, pg_pool).await? {
+        return Ok(true);
+      }
+    }
+    Ok(false)
   }
 
   pub async fn get_collab_meta(
@@ -38,24 +74,89 @@ impl CollabDiskCache {
     object_id: &str,
     collab_type: &CollabType,
   ) -> AppResult<AFCollabRowMeta> {
-    let result = select_collab_meta_from_af_collab(&self.pg_pool, object_id, collab_type).await?;
-    match result {
-      None => {
-        let msg = format!("Can't find the row for object_id: {}", object_id);
-        Err(AppError::RecordNotFound(msg))
-      },
-      Some(meta) => Ok(meta),
+    for pg_pool in self.router.all_pools() {
+      if let Some(meta) = select_collab_meta_from_af_collab(pg_pool, object_id, collab_type).await?
+      {
+        return Ok(meta);
+      }
     }
+    let msg = format!("Can't find the row for object_id: {}", object_id);
+    Err(AppError::RecordNotFound(msg))
   }
 
+  /// Writes the collab into the database of the workspace's region. The given transaction is only
+  /// used when the workspace is stored in the default database, otherwise the collab is written in
+  /// a separate transaction against the regional database.
+  ///
+  /// When the collabs are sharded, the collab is written in a separate transaction against its
+  /// shard, while its embeddings stay in the default database.
   pub async fn upsert_collab_with_transaction(
     &self,
     workspace_id: &str,
     uid: &i64,
     params: &CollabParams,
     transaction: &mut Transaction<'_, sqlx::Postgres>,
+  ) -> AppResult<()> {
+    let workspace_uuid = Uuid::parse_str(workspace_id)?;
+    match self.router.regional_pool(&workspace_uuid).await? {
+      None => match self
+        .router
+        .collab_shard_for_write(transaction, &workspace_uuid, &params.object_id)
+        .await?
+      {
+        None => Self::upsert_collab(workspace_id, uid, params, transaction).await?,
+        Some((_, shard_pool)) => {
+          let mut shard_transaction = shard_pool.begin().await?;
+          insert_into_af_collab(&mut shard_transaction, uid, workspace_id, params).await?;
+          shard_transaction.commit().await?;
+          Self::upsert_embeddings(workspace_id, params, transaction).await?;
+        },
+      },
+      Some(regional_pool) => {
+        let mut regional_transaction = regional_pool.begin().await?;
+        Self::upsert_collab(workspace_id, uid, params, &mut regional_transaction).await?;
+        regional_transaction.commit().await?;
+      },
+    }
+
+    // The edit activity is always recorded in the default database, where the workspace insights
+    // are computed.
+    if matches!(
+      params.collab_type,
+      CollabType::Document | CollabType::Database
+    ) {
+      upsert_collab_edit_activity(
+        transaction.as_mut(),
+        &workspace_uuid,
+        &params.object_id,
+        *uid,
+      )
+      .await?;
+    }
+    // The auto-published documents are republished once the changes settle down.
+    if params.collab_type == CollabType::Document {
+      if let Ok(view_id) = Uuid::parse_str(&params.object_id) {
+        update_auto_publish_view_changed(transaction.as_mut(), &view_id).await?;
+      }
+    }
+    Ok(())
+  }
+
+  async fn upsert_collab(
+    workspace_id: &str,
+    uid: &i64,
+    params: &CollabParams,
+    transaction: &mut Transaction<'_, sqlx::Postgres>,
   ) -> AppResult<()> {
     insert_into_af_collab(transaction, uid, workspace_id, params).await?;
+    Self::upsert_embeddings(workspace_id, params, transaction).await
+  }
+
+  async fn upsert_embeddings(
+    workspace_id: &str,
+    params: &CollabParams,
+    transaction: &mut Transaction<'_, sqlx::Postgres>,
+  ) -> AppResult<()> {
     if let Some(em) = &params.embeddings {
       tracing::info!(
         "saving collab {} embeddings (cost: {} tokens)",
@@ -82,15 +183,45 @@ impl CollabDiskCache {
       query.object_id
     );
 
+    for pg_pool in self.router.all_pools() {
+      match self.get_collab_encoded_from_pool(pg_pool, &query).await {
+        Err(AppError::RecordNotFound(_)) => continue,
+        result => return result,
+      }
+    }
+    let msg = format!("Can't find the row for query: {:?}", query);
+    Err(AppError::RecordNotFound(msg))
+  }
+
+  async fn get_collab_encoded_from_pool(
+    &self,
+    pg_pool: &PgPool,
+    query: &QueryCollab,
+  ) -> Result<EncodedCollab, AppError> {
     const MAX_ATTEMPTS: usize = 3;
     let mut attempts = 0;
 
     loop {
       let result =
-        select_blob_from_af_collab(&self.pg_pool, &query.collab_type, &query.object_id).await;
+        select_blob_and_checksum_from_af_collab(pg_pool, &query.collab_type, &query.object_id)
+          .await;
 
       match result {
-        Ok(data) => {
+        Ok((data, _)) if data.is_empty() => {
+          let data = self
+            .rehydrate_collab(pg_pool, &query.object_id, &query.collab_type)
+            .await?;
+          return encode_collab_from_bytes(data).await;
+        },
+        Ok((data, checksum)) => {
+          if !is_collab_checksum_valid(&data, checksum.as_deref()) {
+            let partition_key = partition_key_from_collab_type(&query.collab_type);
+            report_corrupted_collab(pg_pool, &query.object_id, partition_key).await;
+            return Err(AppError::CollabCorrupted(format!(
+              "The collab {} is corrupted",
+              query.object_id
+            )));
+          }
           return encode_collab_from_bytes(data).await;
         },
         Err(e) => {
@@ -119,21 +250,107 @@ impl CollabDiskCache {
     &self,
     queries: Vec<QueryCollab>,
   ) -> HashMap<String, QueryCollabResult> {
-    batch_select_collab_blob(&self.pg_pool, queries).await
+    let mut results = HashMap::new();
+    let mut pending_queries = queries;
+    for pg_pool in self.router.all_pools() {
+      if pending_queries.is_empty() {
+        break;
+      }
+      let mut pool_results = batch_select_collab_blob(pg_pool, pending_queries.clone()).await;
+      self
+        .rehydrate_archived_results(pg_pool, &pending_queries, &mut pool_results)
+        .await;
+      // Look up the collabs that are not found in the next database
+      pending_queries.retain(|query| {
+        !matches!(
+          pool_results.get(&query.object_id),
+          Some(QueryCollabResult::Success { .. })
+        )
+      });
+      for (object_id, result) in pool_results {
+        match result {
+          QueryCollabResult::Success { .. } => {
+            results.insert(object_id, result);
+          },
+          QueryCollabResult::Failed { .. } => {
+            results.entry(object_id).or_insert(result);
+          },
+        }
+      }
+    }
+    results
   }
 
-  pub async fn delete_collab(&self, object_id: &str) -> AppResult<()> {
-    sqlx::query!(
-      r#"
-        UPDATE af_collab
-        SET deleted_at = $2
-        WHERE oid = $1;
-        "#,
+  /// Moves the payload of an archived collab back to `af_collab`. The archived collabs are stored
+  /// with an empty blob.
+  async fn rehydrate_collab(
+    &self,
+    pg_pool: &PgPool,
+    object_id: &str,
+    collab_type: &CollabType,
+  ) -> AppResult<Vec<u8>> {
+    let start = Instant::now();
+    let data = match rehydrate_collab(pg_pool, object_id, collab_type).await? {
+      Some(data) => data,
+      // Another request rehydrated the collab in the meantime
+      None => select_blob_from_af_collab(pg_pool, collab_type, object_id).await?,
+    };
+    let elapsed = start.elapsed();
+    self.rehydration_count.fetch_add(1, Ordering::Relaxed);
+    self
+      .rehydration_millis
+      .fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+    event!(
+      Level::INFO,
+      "rehydrated archived collab {} in {:?}",
       object_id,
-      chrono::Utc::now()
-    )
-    .execute(&self.pg_pool)
-    .await?;
+      elapsed
+    );
+    Ok(data)
+  }
+
+  async fn rehydrate_archived_results(
+    &self,
+    pg_pool: &PgPool,
+    queries: &[QueryCollab],
+    results: &mut HashMap<String, QueryCollabResult>,
+  ) {
+    for query in queries {
+      let is_archived = matches!(
+        results.get(&query.object_id),
+        Some(QueryCollabResult::Success { encode_collab_v1 }) if encode_collab_v1.is_empty()
+      );
+      if !is_archived {
+        continue;
+      }
+      let result = match self
+        .rehydrate_collab(pg_pool, &query.object_id, &query.collab_type)
+        .await
+      {
+        Ok(encode_collab_v1) => QueryCollabResult::Success { encode_collab_v1 },
+        Err(err) => QueryCollabResult::Failed {
+          error: err.to_string(),
+        },
+      };
+      results.insert(query.object_id.clone(), result);
+    }
+  }
+
+  pub async fn delete_collab(&self, object_id: &str) -> AppResult<()> {
+    let deleted_at = chrono::Utc::now();
+    for pg_pool in self.router.all_pools() {
+      sqlx::query!(
+        r#"
+          UPDATE af_collab
+          SET deleted_at = $2
+          WHERE oid = $1;
+          "#,
+        object_id,
+        deleted_at
+      )
+      .execute(pg_pool)
+      .await?;
+    }
     Ok(())
   }
 }
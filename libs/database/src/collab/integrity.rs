@@ -0,0 +1,342 @@
+use app_error::AppError;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::pg_row::{AFCollabCorruptionRow, AFSnapshotRow};
+
+/// Returns the checksum stored along with the blob of a collab or of a snapshot.
+#[inline]
+pub fn collab_checksum(blob: &[u8]) -> Vec<u8> {
+  Sha256::digest(blob).to_vec()
+}
+
+/// Returns false if the blob doesn't match its checksum. The blobs written before the checksums
+/// were introduced can't be verified, nor the archived collabs, which are stored with an empty
+/// blob.
+#[inline]
+pub fn is_collab_checksum_valid(blob: &[u8], checksum: Option<&[u8]>) -> bool {
+  match checksum {
+    Some(checksum) if !blob.is_empty() => collab_checksum(blob) == checksum,
+    _ => true,
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CollabCorruptionSource {
+  /// The corruption was found when the collab was read.
+  Read,
+  /// The corruption was found by the background scrubber.
+  Scrub,
+}
+
+impl CollabCorruptionSource {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CollabCorruptionSource::Read => "read",
+      CollabCorruptionSource::Scrub => "scrub",
+    }
+  }
+}
+
+struct CollabScrubCursor {
+  collab_oid: String,
+  collab_partition_key: i32,
+  snapshot_id: i64,
+}
+
+/// Logs the corruption of the collab found when reading it, and records it for the server admins.
+pub async fn report_corrupted_collab(pg_pool: &PgPool, oid: &str, partition_key: i32) {
+  error!("The blob of collab {} doesn't match its checksum", oid);
+  if let Err(err) =
+    insert_collab_corruption(pg_pool, oid, partition_key, CollabCorruptionSource::Read).await
+  {
+    error!(
+      "Failed to record the corruption of collab {}: {:?}",
+      oid, err
+    );
+  }
+}
+
+/// Returns [AppError::CollabCorrupted] if the blob of the snapshot doesn't match its checksum, after
+/// recording the corruption for the server admins.
+pub async fn verify_snapshot_checksum(
+  pg_pool: &PgPool,
+  snapshot: &AFSnapshotRow,
+) -> Result<(), AppError> {
+  if is_collab_checksum_valid(&snapshot.blob, snapshot.checksum.as_deref()) {
+    return Ok(());
+  }
+  error!(
+    "The blob of snapshot {} of collab {} doesn't match its checksum",
+    snapshot.sid, snapshot.oid
+  );
+  if let Err(err) =
+    insert_collab_snapshot_corruption(pg_pool, snapshot.sid, CollabCorruptionSource::Read).await
+  {
+    error!(
+      "Failed to record the corruption of snapshot {}: {:?}",
+      snapshot.sid, err
+    );
+  }
+  Err(AppError::CollabCorrupted(format!(
+    "The snapshot {} of collab {} is corrupted",
+    snapshot.sid, snapshot.oid
+  )))
+}
+
+/// Records the corruption of the blob of the collab, along with its latest snapshot that still
+/// matches its checksum. Returns `None` if the corruption is already recorded.
+pub async fn insert_collab_corruption(
+  pg_pool: &PgPool,
+  oid: &str,
+  partition_key: i32,
+  source: CollabCorruptionSource,
+) -> Result<Option<AFCollabCorruptionRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabCorruptionRow,
+    r#"
+      INSERT INTO af_collab_corruption (
+        oid, workspace_id, partition_key, detected_by, last_good_snapshot_id
+      )
+      SELECT collab.oid, collab.workspace_id, collab.partition_key, $3, (
+        SELECT snapshot.sid
+        FROM af_collab_snapshot snapshot
+        WHERE snapshot.oid = collab.oid
+          AND snapshot.deleted_at IS NULL
+          AND (snapshot.checksum IS NULL OR sha256(snapshot.blob) = snapshot.checksum)
+        ORDER BY snapshot.created_at DESC
+        LIMIT 1
+      )
+      FROM af_collab collab
+      WHERE collab.oid = $1 AND collab.partition_key = $2
+      ON CONFLICT (oid, snapshot_id) WHERE resolved_at IS NULL DO NOTHING
+      RETURNING
+        oid, workspace_id, partition_key, snapshot_id, detected_by, detected_at,
+        last_good_snapshot_id
+    "#,
+    oid,
+    partition_key,
+    source.as_str(),
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Records the corruption of the blob of the snapshot, along with the latest other snapshot of the
+/// collab that still matches its checksum. Returns `None` if the corruption is already recorded.
+pub async fn insert_collab_snapshot_corruption(
+  pg_pool: &PgPool,
+  snapshot_id: i64,
+  source: CollabCorruptionSource,
+) -> Result<Option<AFCollabCorruptionRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabCorruptionRow,
+    r#"
+      INSERT INTO af_collab_corruption (
+        oid, workspace_id, snapshot_id, detected_by, last_good_snapshot_id
+      )
+      SELECT corrupted.oid, corrupted.workspace_id, corrupted.sid, $2, (
+        SELECT snapshot.sid
+        FROM af_collab_snapshot snapshot
+        WHERE snapshot.oid = corrupted.oid
+          AND snapshot.sid <> corrupted.sid
+          AND snapshot.deleted_at IS NULL
+          AND (snapshot.checksum IS NULL OR sha256(snapshot.blob) = snapshot.checksum)
+        ORDER BY snapshot.created_at DESC
+        LIMIT 1
+      )
+      FROM af_collab_snapshot corrupted
+      WHERE corrupted.sid = $1
+      ON CONFLICT (oid, snapshot_id) WHERE resolved_at IS NULL DO NOTHING
+      RETURNING
+        oid, workspace_id, partition_key, snapshot_id, detected_by, detected_at,
+        last_good_snapshot_id
+    "#,
+    snapshot_id,
+    source.as_str(),
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the corruptions that are not resolved yet, the latest first.
+pub async fn select_unresolved_collab_corruptions(
+  pg_pool: &PgPool,
+) -> Result<Vec<AFCollabCorruptionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabCorruptionRow,
+    r#"
+      SELECT
+        oid, workspace_id, partition_key, snapshot_id, detected_by, detected_at,
+        last_good_snapshot_id
+      FROM af_collab_corruption
+      WHERE resolved_at IS NULL
+      ORDER BY detected_at DESC
+    "#,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Marks the corruptions as resolved once the blob matches its checksum again, e.g. after the
+/// collab is restored from a snapshot, or once the collab or the snapshot is deleted. Returns the
+/// number of resolved corruptions.
+pub async fn resolve_repaired_collab_corruptions(pg_pool: &PgPool) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_collab_corruption corruption
+      SET resolved_at = NOW()
+      WHERE corruption.resolved_at IS NULL
+        AND (
+          (corruption.snapshot_id = 0 AND NOT EXISTS (
+            SELECT 1 FROM af_collab collab
+            WHERE collab.oid = corruption.oid
+              AND collab.partition_key = corruption.partition_key
+              AND collab.checksum IS NOT NULL
+              AND collab.blob <> ''::BYTEA
+              AND sha256(collab.blob) <> collab.checksum
+          ))
+          OR (corruption.snapshot_id <> 0 AND NOT EXISTS (
+            SELECT 1 FROM af_collab_snapshot snapshot
+            WHERE snapshot.sid = corruption.snapshot_id
+              AND snapshot.checksum IS NOT NULL
+              AND sha256(snapshot.blob) <> snapshot.checksum
+          ))
+        )
+    "#,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// Verifies the checksums of the next collabs after the cursor of the scrubber, and moves the
+/// cursor past them. The scrubber starts over once it reaches the last collab. Returns the
+/// corruptions found that were not recorded yet.
+pub async fn scrub_collabs(
+  pg_pool: &PgPool,
+  limit: i64,
+) -> Result<Vec<AFCollabCorruptionRow>, AppError> {
+  let cursor = select_collab_scrub_cursor(pg_pool).await?;
+  let rows = sqlx::query!(
+    r#"
+      SELECT
+        oid,
+        partition_key,
+        (checksum IS NOT NULL AND blob <> ''::BYTEA AND sha256(blob) <> checksum) AS "corrupted!"
+      FROM af_collab
+      WHERE (oid, partition_key) > ($1, $2)
+      ORDER BY oid, partition_key
+      LIMIT $3
+    "#,
+    cursor.collab_oid,
+    cursor.collab_partition_key,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+
+  let mut corruptions = vec![];
+  for row in rows.iter().filter(|row| row.corrupted) {
+    if let Some(corruption) = insert_collab_corruption(
+      pg_pool,
+      &row.oid,
+      row.partition_key,
+      CollabCorruptionSource::Scrub,
+    )
+    .await?
+    {
+      corruptions.push(corruption);
+    }
+  }
+
+  let (next_oid, next_partition_key) = match rows.last() {
+    Some(row) if rows.len() as i64 == limit => (row.oid.clone(), row.partition_key),
+    _ => (String::new(), -1),
+  };
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_scrub_cursor
+      SET collab_oid = $1, collab_partition_key = $2, updated_at = NOW()
+    "#,
+    next_oid,
+    next_partition_key,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(corruptions)
+}
+
+/// Verifies the checksums of the next snapshots after the cursor of the scrubber, like
+/// [scrub_collabs].
+pub async fn scrub_collab_snapshots(
+  pg_pool: &PgPool,
+  limit: i64,
+) -> Result<Vec<AFCollabCorruptionRow>, AppError> {
+  let cursor = select_collab_scrub_cursor(pg_pool).await?;
+  let rows = sqlx::query!(
+    r#"
+      SELECT
+        sid,
+        (checksum IS NOT NULL AND sha256(blob) <> checksum) AS "corrupted!"
+      FROM af_collab_snapshot
+      WHERE sid > $1
+      ORDER BY sid
+      LIMIT $2
+    "#,
+    cursor.snapshot_id,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+
+  let mut corruptions = vec![];
+  for row in rows.iter().filter(|row| row.corrupted) {
+    if let Some(corruption) =
+      insert_collab_snapshot_corruption(pg_pool, row.sid, CollabCorruptionSource::Scrub).await?
+    {
+      corruptions.push(corruption);
+    }
+  }
+
+  let next_snapshot_id = match rows.last() {
+    Some(row) if rows.len() as i64 == limit => row.sid,
+    _ => 0,
+  };
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_scrub_cursor
+      SET snapshot_id = $1, updated_at = NOW()
+    "#,
+    next_snapshot_id,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(corruptions)
+}
+
+async fn select_collab_scrub_cursor(pg_pool: &PgPool) -> Result<CollabScrubCursor, AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_scrub_cursor (id)
+      VALUES (TRUE)
+      ON CONFLICT (id) DO NOTHING
+    "#,
+  )
+  .execute(pg_pool)
+  .await?;
+  let cursor = sqlx::query_as!(
+    CollabScrubCursor,
+    r#"
+      SELECT collab_oid, collab_partition_key, snapshot_id
+      FROM af_collab_scrub_cursor
+    "#,
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(cursor)
+}
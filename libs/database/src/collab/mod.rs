@@ -1,13 +1,22 @@
+mod archive;
 pub mod cache;
 mod collab_db_ops;
 mod collab_storage;
 mod disk_cache;
+mod integrity;
 pub mod mem_cache;
+pub mod shard;
+mod snapshot_retention;
+mod trash;
 mod util;
 
+pub use archive::*;
 pub use collab_db_ops::*;
 use collab_entity::CollabType;
 pub use collab_storage::*;
+pub use integrity::*;
+pub use snapshot_retention::*;
+pub use trash::*;
 
 pub(crate) fn partition_key_from_collab_type(collab_type: &CollabType) -> i32 {
   match collab_type {
@@ -21,3 +30,15 @@ pub(crate) fn partition_key_from_collab_type(collab_type: &CollabType) -> i32 {
     CollabType::Unknown => 0,
   }
 }
+
+pub(crate) fn collab_type_from_partition_key(partition_key: i32) -> CollabType {
+  match partition_key {
+    0 => CollabType::Document,
+    1 => CollabType::Database,
+    2 => CollabType::WorkspaceDatabase,
+    3 => CollabType::Folder,
+    4 => CollabType::DatabaseRow,
+    5 => CollabType::UserAwareness,
+    _ => CollabType::Unknown,
+  }
+}
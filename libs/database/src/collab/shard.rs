@@ -0,0 +1,498 @@
+use std::collections::BTreeMap;
+use std::ops::DerefMut;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{Executor, Postgres, Transaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::pg_row::{
+  AFCollabArchiveShardRow, AFCollabPlacementRow, AFCollabShardData, AFCollabShardRow,
+};
+use crate::redaction::select_collab_snapshots;
+use crate::residency::{provision_workspace_in_region, PgPoolRouter};
+
+/// The name of the shard of the default database.
+pub const DEFAULT_COLLAB_SHARD: &str = "default";
+
+/// The number of points of each shard on the hash ring. The more points, the more evenly the
+/// collabs are spread over the shards.
+const VIRTUAL_NODES_PER_SHARD: usize = 64;
+
+/// How long the writes that resolved the shard of a collab before it was fenced are given to
+/// complete, before the collab is copied.
+const COLLAB_FENCE_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// A fence older than this is left over by an interrupted migration, and no longer blocks the
+/// writes.
+pub const COLLAB_FENCE_TIMEOUT_SECS: i64 = 60;
+
+/// Places the collabs on the storage shards by consistent hashing, so that adding a shard only
+/// moves the collabs that hash to the new shard.
+#[derive(Debug, Clone, Default)]
+pub struct CollabShardRing {
+  ring: BTreeMap<u64, String>,
+}
+
+impl CollabShardRing {
+  pub fn new<'a>(shards: impl IntoIterator<Item = &'a str>) -> Self {
+    let mut ring = BTreeMap::new();
+    for shard in shards {
+      for node in 0..VIRTUAL_NODES_PER_SHARD {
+        ring.insert(hash_key(&format!("{}#{}", shard, node)), shard.to_string());
+      }
+    }
+    Self { ring }
+  }
+
+  /// Returns the shard the collab belongs to, the default shard if the ring is empty.
+  pub fn shard_for(&self, object_id: &str) -> &str {
+    let hash = hash_key(object_id);
+    self
+      .ring
+      .range(hash..)
+      .next()
+      .or_else(|| self.ring.iter().next())
+      .map(|(_, shard)| shard.as_str())
+      .unwrap_or(DEFAULT_COLLAB_SHARD)
+  }
+}
+
+/// The hash must be stable across the releases, the placement of the collabs depends on it.
+fn hash_key(key: &str) -> u64 {
+  let digest = Sha256::digest(key.as_bytes());
+  let mut bytes = [0u8; 8];
+  bytes.copy_from_slice(&digest[..8]);
+  u64::from_be_bytes(bytes)
+}
+
+pub async fn select_collab_placement<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Option<AFCollabPlacementRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabPlacementRow,
+    r#"
+      SELECT oid, workspace_id, shard, fenced_at
+      FROM af_collab_placement
+      WHERE oid = $1
+    "#,
+    oid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the shard of the collab, which is the given shard unless another request placed the
+/// collab in the meantime.
+pub async fn insert_collab_placement<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+  workspace_id: &Uuid,
+  shard: &str,
+) -> Result<String, AppError> {
+  let shard = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_collab_placement (oid, workspace_id, shard)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (oid) DO UPDATE SET oid = af_collab_placement.oid
+      RETURNING shard
+    "#,
+    oid,
+    workspace_id,
+    shard,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(shard)
+}
+
+/// Records the collabs of the default database that haven't been placed yet, so that they can be
+/// rebalanced. Returns the number of collabs placed.
+pub async fn insert_default_collab_placements<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  limit: i64,
+) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_collab_placement (oid, workspace_id, shard)
+      SELECT ac.oid, ac.workspace_id, $1
+      FROM af_collab ac
+      JOIN af_workspace aw ON aw.workspace_id = ac.workspace_id
+      WHERE aw.residency IS NULL
+        AND NOT EXISTS (SELECT 1 FROM af_collab_placement acp WHERE acp.oid = ac.oid)
+      LIMIT $2
+      ON CONFLICT DO NOTHING
+    "#,
+    DEFAULT_COLLAB_SHARD,
+    limit,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
+
+/// Returns the placements in the order of the object ids, starting after the given object id.
+pub async fn select_collab_placements<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  after_oid: Option<&str>,
+  limit: i64,
+) -> Result<Vec<AFCollabPlacementRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabPlacementRow,
+    r#"
+      SELECT oid, workspace_id, shard, fenced_at
+      FROM af_collab_placement
+      WHERE $1::TEXT IS NULL OR oid > $1
+      ORDER BY oid
+      LIMIT $2
+    "#,
+    after_oid,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_collab_placement_counts<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<(String, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT shard, COUNT(*) AS "count!"
+      FROM af_collab_placement
+      GROUP BY shard
+    "#,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows.into_iter().map(|row| (row.shard, row.count)).collect())
+}
+
+/// Fences the collab, unless it's already fenced by another migration. Returns false if the
+/// collab is already fenced.
+async fn update_collab_placement_fence<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+  fence_expired_before: DateTime<Utc>,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_collab_placement
+      SET fenced_at = NOW()
+      WHERE oid = $1
+        AND (fenced_at IS NULL OR fenced_at < $2)
+    "#,
+    oid,
+    fence_expired_before,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Moves the placement of the collab to the shard and lifts the fence.
+async fn update_collab_placement_shard<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+  shard: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_placement
+      SET shard = $2, fenced_at = NULL, updated_at = NOW()
+      WHERE oid = $1
+    "#,
+    oid,
+    shard,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns true if the fence of the placement is still in force.
+pub fn is_collab_placement_fenced(placement: &AFCollabPlacementRow) -> bool {
+  placement.fenced_at.is_some_and(|fenced_at| {
+    Utc::now() - fenced_at < chrono::Duration::seconds(COLLAB_FENCE_TIMEOUT_SECS)
+  })
+}
+
+async fn select_collab_shard_data(
+  txn: &mut Transaction<'_, Postgres>,
+  oid: &str,
+) -> Result<AFCollabShardData, AppError> {
+  let collabs = sqlx::query_as!(
+    AFCollabShardRow,
+    r#"
+      SELECT
+        oid, blob, len, partition_key, encrypt, owner_uid, deleted_at, created_at, workspace_id,
+        updated_at, archived_at, checksum
+      FROM af_collab
+      WHERE oid = $1
+      FOR UPDATE
+    "#,
+    oid,
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  let archives = sqlx::query_as!(
+    AFCollabArchiveShardRow,
+    r#"
+      SELECT oid, partition_key, workspace_id, blob, len, archived_at
+      FROM af_collab_archive
+      WHERE oid = $1
+    "#,
+    oid,
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  let snapshots = select_collab_snapshots(txn.deref_mut(), oid).await?;
+  Ok(AFCollabShardData {
+    collabs,
+    archives,
+    snapshots,
+  })
+}
+
+/// Writes the collab into the shard. The snapshots left by an interrupted migration are replaced,
+/// so the copy can be retried. The snapshots keep their ids, so that they can still be restored by
+/// id, and the copy fails if an id is already taken by a snapshot of another collab on the shard.
+async fn insert_collab_shard_data(
+  txn: &mut Transaction<'_, Postgres>,
+  oid: &str,
+  data: &AFCollabShardData,
+) -> Result<(), AppError> {
+  for collab in &data.collabs {
+    sqlx::query!(
+      r#"
+        INSERT INTO af_collab (
+          oid, blob, len, partition_key, encrypt, owner_uid, deleted_at, created_at, workspace_id,
+          updated_at, archived_at, checksum
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (oid, partition_key) DO UPDATE
+          SET blob = EXCLUDED.blob,
+              len = EXCLUDED.len,
+              checksum = EXCLUDED.checksum,
+              encrypt = EXCLUDED.encrypt,
+              owner_uid = EXCLUDED.owner_uid,
+              deleted_at = EXCLUDED.deleted_at,
+              updated_at = EXCLUDED.updated_at,
+              archived_at = EXCLUDED.archived_at
+      "#,
+      collab.oid,
+      collab.blob,
+      collab.len,
+      collab.partition_key,
+      collab.encrypt,
+      collab.owner_uid,
+      collab.deleted_at,
+      collab.created_at,
+      collab.workspace_id,
+      collab.updated_at,
+      collab.archived_at,
+      collab.checksum,
+    )
+    .execute(txn.deref_mut())
+    .await?;
+  }
+  for archive in &data.archives {
+    sqlx::query!(
+      r#"
+        INSERT INTO af_collab_archive (oid, partition_key, workspace_id, blob, len, archived_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (oid, partition_key) DO UPDATE
+          SET blob = EXCLUDED.blob, len = EXCLUDED.len, archived_at = EXCLUDED.archived_at
+      "#,
+      archive.oid,
+      archive.partition_key,
+      archive.workspace_id,
+      archive.blob,
+      archive.len,
+      archive.archived_at,
+    )
+    .execute(txn.deref_mut())
+    .await?;
+  }
+
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_snapshot WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  for snapshot in &data.snapshots {
+    let res = sqlx::query!(
+      r#"
+        INSERT INTO af_collab_snapshot (
+          sid, oid, blob, len, encrypt, deleted_at, workspace_id, created_at, checksum
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (sid) DO NOTHING
+      "#,
+      snapshot.sid,
+      snapshot.oid,
+      snapshot.blob,
+      snapshot.len.unwrap_or(snapshot.blob.len() as i32),
+      snapshot.encrypt,
+      snapshot.deleted_at,
+      snapshot.workspace_id,
+      snapshot.created_at,
+      snapshot.checksum,
+    )
+    .execute(txn.deref_mut())
+    .await?;
+    if res.rows_affected() == 0 {
+      return Err(AppError::Internal(anyhow::anyhow!(
+        "The snapshot id {} of collab {} is already taken in the target shard",
+        snapshot.sid,
+        oid
+      )));
+    }
+  }
+  if let Some(max_sid) = data.snapshots.iter().map(|snapshot| snapshot.sid).max() {
+    // The snapshots created in the shard afterwards must not reuse the copied ids
+    sqlx::query!(
+      r#"
+        SELECT setval(pg_get_serial_sequence('af_collab_snapshot', 'sid'), $1)
+        WHERE $1 > COALESCE(
+          pg_sequence_last_value(pg_get_serial_sequence('af_collab_snapshot', 'sid')::regclass),
+          0
+        )
+      "#,
+      max_sid,
+    )
+    .execute(txn.deref_mut())
+    .await?;
+  }
+  Ok(())
+}
+
+async fn delete_collab_shard_data(
+  txn: &mut Transaction<'_, Postgres>,
+  oid: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_snapshot WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_archive WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Moves the collab, along with its archived payload and its snapshots, to the target shard while
+/// the collab is online. Returns false if the collab is already stored in the target shard.
+///
+/// The writes to the collab are fenced during the move, and rejected with
+/// [AppError::CollabMigrating]. The collab is copied to the target shard and its placement is
+/// switched to the target shard before the collab is removed from the source shard, so the
+/// placement always points to a shard storing the collab. If the move fails, the fence is kept
+/// until it times out, and the move can be retried after that.
+pub async fn migrate_collab_to_shard(
+  router: &PgPoolRouter,
+  oid: &str,
+  target_shard: &str,
+) -> Result<bool, AppError> {
+  let target_pool = router.collab_shard_pool(target_shard).ok_or_else(|| {
+    AppError::InvalidRequest(format!(
+      "The collab shard {} is not configured",
+      target_shard
+    ))
+  })?;
+  let placement = select_collab_placement(router.default_pool(), oid)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("The collab {} has not been placed in a shard", oid))
+    })?;
+  if placement.shard == target_shard {
+    return Ok(false);
+  }
+  let source_pool = router
+    .collab_shard_pool(&placement.shard)
+    .ok_or_else(|| {
+      AppError::Internal(anyhow::anyhow!(
+        "The collab shard {} of collab {} is not configured",
+        placement.shard,
+        oid
+      ))
+    })?
+    .clone();
+  let target_pool = target_pool.clone();
+
+  let fence_expired_before = Utc::now() - chrono::Duration::seconds(COLLAB_FENCE_TIMEOUT_SECS);
+  if !update_collab_placement_fence(router.default_pool(), oid, fence_expired_before).await? {
+    return Err(AppError::CollabMigrating(format!(
+      "The collab {} is already being moved",
+      oid
+    )));
+  }
+  tokio::time::sleep(COLLAB_FENCE_GRACE_PERIOD).await;
+
+  let result = async {
+    let mut source_txn = source_pool.begin().await?;
+    let data = select_collab_shard_data(&mut source_txn, oid).await?;
+    if target_shard != DEFAULT_COLLAB_SHARD {
+      let mut default_txn = router.default_pool().begin().await?;
+      provision_workspace_in_region(&mut default_txn, &target_pool, &placement.workspace_id)
+        .await?;
+      default_txn.commit().await?;
+    }
+    let mut target_txn = target_pool.begin().await?;
+    insert_collab_shard_data(&mut target_txn, oid, &data).await?;
+    target_txn.commit().await?;
+    // The target shard serves the collab from now on, and the fence is lifted
+    update_collab_placement_shard(router.default_pool(), oid, target_shard).await?;
+    Ok::<_, AppError>((source_txn, data.snapshots.len()))
+  }
+  .await;
+
+  // The fence isn't lifted on the source shard when the move fails: the switch of the placement
+  // may have been applied even if it failed, and the writes made on the target shard since would
+  // be lost. The fence times out instead.
+  let (mut source_txn, snapshot_count) = result?;
+  info!(
+    "moved collab {} with {} snapshots from shard {} to shard {}",
+    oid, snapshot_count, placement.shard, target_shard
+  );
+  let deleted = async {
+    delete_collab_shard_data(&mut source_txn, oid).await?;
+    source_txn.commit().await?;
+    Ok::<_, AppError>(())
+  }
+  .await;
+  if let Err(err) = deleted {
+    // The copy left in the source shard is no longer read, as the collab is placed elsewhere
+    warn!(
+      "failed to delete collab {} from shard {} after moving it: {}",
+      oid, placement.shard, err
+    );
+  }
+  Ok(true)
+}
@@ -0,0 +1,143 @@
+use collab_entity::CollabType;
+use database_entity::dto::SnapshotRetentionPolicy;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::collab::collab_type_from_partition_key;
+use app_error::AppError;
+
+#[derive(Debug, Clone)]
+pub struct CollabSnapshotCandidate {
+  pub oid: String,
+  pub collab_type: CollabType,
+}
+
+/// Returns the snapshot retention policy of the workspace, or the default one if the workspace
+/// has no settings.
+pub async fn select_snapshot_retention_policy(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<SnapshotRetentionPolicy, AppError> {
+  let value = sqlx::query_scalar!(
+    r#"SELECT settings->'snapshot_retention' FROM af_workspace WHERE workspace_id = $1"#,
+    workspace_id
+  )
+  .fetch_optional(pg_pool)
+  .await?
+  .flatten();
+  match value {
+    Some(value) => Ok(serde_json::from_value(value)?),
+    None => Ok(SnapshotRetentionPolicy::default()),
+  }
+}
+
+/// Returns the workspaces whose snapshot retention policy was changed from the default one.
+pub async fn select_workspaces_with_snapshot_retention(
+  pg_pool: &PgPool,
+) -> Result<Vec<(Uuid, SnapshotRetentionPolicy)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT workspace_id, settings->'snapshot_retention' AS "snapshot_retention!"
+      FROM af_workspace
+      WHERE settings->'snapshot_retention' IS NOT NULL
+    "#
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  let policies = rows
+    .into_iter()
+    .filter_map(|row| {
+      match serde_json::from_value::<SnapshotRetentionPolicy>(row.snapshot_retention) {
+        Ok(policy) if policy != SnapshotRetentionPolicy::default() => {
+          Some((row.workspace_id, policy))
+        },
+        Ok(_) => None,
+        Err(err) => {
+          warn!(
+            "Invalid snapshot retention policy of workspace {}: {}",
+            row.workspace_id, err
+          );
+          None
+        },
+      }
+    })
+    .collect();
+  Ok(policies)
+}
+
+/// Returns the collabs of the workspace that were written since their last snapshot, and whose
+/// last snapshot is older than `interval_secs`.
+pub async fn select_collabs_to_snapshot(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  interval_secs: i64,
+  limit: i64,
+) -> Result<Vec<CollabSnapshotCandidate>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT collab.oid, collab.partition_key
+      FROM af_collab collab
+      LEFT JOIN LATERAL (
+        SELECT MAX(created_at) AS created_at
+        FROM af_collab_snapshot
+        WHERE af_collab_snapshot.oid = collab.oid AND af_collab_snapshot.deleted_at IS NULL
+      ) last_snapshot ON TRUE
+      WHERE collab.workspace_id = $1
+        AND collab.deleted_at IS NULL
+        AND collab.updated_at > COALESCE(last_snapshot.created_at, 'epoch'::TIMESTAMPTZ)
+        AND COALESCE(last_snapshot.created_at, 'epoch'::TIMESTAMPTZ)
+          < NOW() - $2 * INTERVAL '1 second'
+      ORDER BY collab.updated_at DESC
+      LIMIT $3
+    "#,
+    workspace_id,
+    interval_secs as f64,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| CollabSnapshotCandidate {
+        oid: row.oid,
+        collab_type: collab_type_from_partition_key(row.partition_key),
+      })
+      .collect(),
+  )
+}
+
+/// Deletes the snapshots of the workspace beyond the limits of the retention policy. The count
+/// limit is already enforced when a snapshot is created, this also covers the collabs that are no
+/// longer edited.
+pub async fn delete_workspace_snapshots_beyond_retention(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  retention: &SnapshotRetentionPolicy,
+) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_snapshot
+      WHERE sid IN (
+        SELECT sid
+        FROM (
+          SELECT
+            sid,
+            created_at,
+            ROW_NUMBER() OVER (PARTITION BY oid ORDER BY created_at DESC) AS position
+          FROM af_collab_snapshot
+          WHERE workspace_id = $1
+        ) snapshots
+        WHERE snapshots.position > $2
+          OR ($3 > 0 AND snapshots.created_at < NOW() - $3 * INTERVAL '1 day')
+      )
+    "#,
+    workspace_id,
+    retention.max_count as i64,
+    retention.max_age_days as i32,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected())
+}
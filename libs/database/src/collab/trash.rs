@@ -0,0 +1,157 @@
+use std::ops::DerefMut;
+
+use chrono::{DateTime, Utc};
+use collab_entity::CollabType;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::collab::collab_type_from_partition_key;
+use app_error::AppError;
+
+#[derive(Debug, Clone)]
+pub struct TrashedCollab {
+  pub oid: String,
+  pub collab_type: CollabType,
+  pub deleted_at: DateTime<Utc>,
+}
+
+/// Returns the deleted collabs of the workspace that were not purged yet, the most recently
+/// deleted first.
+pub async fn select_trashed_collabs(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<TrashedCollab>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT oid, partition_key, deleted_at AS "deleted_at!"
+      FROM af_collab
+      WHERE workspace_id = $1 AND deleted_at IS NOT NULL
+      ORDER BY deleted_at DESC
+    "#,
+    workspace_id
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| TrashedCollab {
+        oid: row.oid,
+        collab_type: collab_type_from_partition_key(row.partition_key),
+        deleted_at: row.deleted_at,
+      })
+      .collect(),
+  )
+}
+
+/// Moves the collab out of the trash. Returns false if the collab is not in the trash.
+pub async fn restore_trashed_collab(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  oid: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_collab
+      SET deleted_at = NULL, updated_at = NOW()
+      WHERE workspace_id = $1 AND oid = $2 AND deleted_at IS NOT NULL
+    "#,
+    workspace_id,
+    oid
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Permanently deletes the collab from the trash, along with its snapshots and archived payload.
+/// Returns false if the collab is not in the trash.
+pub async fn purge_trashed_collab(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  oid: &str,
+) -> Result<bool, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_collab
+      WHERE workspace_id = $1 AND oid = $2 AND deleted_at IS NOT NULL
+    "#,
+    workspace_id,
+    oid
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  if result.rows_affected() == 0 {
+    return Ok(false);
+  }
+  delete_collab_leftovers(&mut txn, workspace_id, &[oid.to_string()]).await?;
+  txn.commit().await?;
+  Ok(true)
+}
+
+/// Permanently deletes the collabs that have been in the trash for longer than `retention_secs`.
+/// Returns the number of collabs deleted.
+pub async fn purge_expired_trashed_collabs(
+  pg_pool: &PgPool,
+  retention_secs: i64,
+  limit: i64,
+) -> Result<u64, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let rows = sqlx::query!(
+    r#"
+      DELETE FROM af_collab
+      WHERE (oid, partition_key) IN (
+        SELECT oid, partition_key
+        FROM af_collab
+        WHERE deleted_at < NOW() - $1 * INTERVAL '1 second'
+        LIMIT $2
+      )
+      RETURNING workspace_id, oid
+    "#,
+    retention_secs as f64,
+    limit,
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  let mut oids_by_workspace = std::collections::HashMap::<Uuid, Vec<String>>::new();
+  for row in &rows {
+    oids_by_workspace
+      .entry(row.workspace_id)
+      .or_default()
+      .push(row.oid.clone());
+  }
+  for (workspace_id, oids) in oids_by_workspace {
+    delete_collab_leftovers(&mut txn, &workspace_id, &oids).await?;
+  }
+  txn.commit().await?;
+  Ok(rows.len() as u64)
+}
+
+async fn delete_collab_leftovers(
+  txn: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+  workspace_id: &Uuid,
+  oids: &[String],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_snapshot
+      WHERE workspace_id = $1 AND oid = ANY($2)
+    "#,
+    workspace_id,
+    oids
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_archive
+      WHERE workspace_id = $1 AND oid = ANY($2)
+    "#,
+    workspace_id,
+    oids
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
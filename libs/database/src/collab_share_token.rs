@@ -0,0 +1,110 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabShareTokenRow;
+
+pub async fn insert_collab_share_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token_id: &Uuid,
+  workspace_id: &Uuid,
+  object_id: &str,
+  issued_by: i64,
+  expires_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_share_token (token_id, workspace_id, object_id, issued_by, expires_at)
+      VALUES ($1, $2, $3, $4, $5)
+    "#,
+    token_id,
+    workspace_id,
+    object_id,
+    issued_by,
+    expires_at
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the tokens of the workspace that haven't expired, the latest first.
+pub async fn select_active_collab_share_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFCollabShareTokenRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabShareTokenRow,
+    r#"
+      SELECT token_id, workspace_id, object_id, issued_by, created_at, expires_at
+      FROM af_collab_share_token
+      WHERE workspace_id = $1 AND expires_at > NOW()
+      ORDER BY created_at DESC
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the token, unless it has expired or has been revoked.
+pub async fn select_active_collab_share_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token_id: &Uuid,
+) -> Result<Option<AFCollabShareTokenRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabShareTokenRow,
+    r#"
+      SELECT token_id, workspace_id, object_id, issued_by, created_at, expires_at
+      FROM af_collab_share_token
+      WHERE token_id = $1 AND expires_at > NOW()
+    "#,
+    token_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the collab has no such token.
+pub async fn delete_collab_share_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  object_id: &str,
+  token_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_share_token
+      WHERE workspace_id = $1 AND object_id = $2 AND token_id = $3
+    "#,
+    workspace_id,
+    object_id,
+    token_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Deletes the tokens of the workspace, only the ones issued by the user if given. Returns the
+/// number of deleted tokens.
+pub async fn delete_workspace_collab_share_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  issued_by: Option<i64>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_share_token
+      WHERE workspace_id = $1 AND ($2::BIGINT IS NULL OR issued_by = $2)
+    "#,
+    workspace_id,
+    issued_by
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected())
+}
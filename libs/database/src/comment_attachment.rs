@@ -0,0 +1,119 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCommentAttachmentRow;
+
+pub async fn select_workspace_id_for_published_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Option<Uuid>, AppError> {
+  let workspace_id = sqlx::query_scalar!(
+    r#"
+      SELECT workspace_id FROM af_published_collab WHERE view_id = $1
+    "#,
+    view_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(workspace_id)
+}
+
+pub async fn insert_comment_attachment<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  attachment_id: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  content_type: &str,
+  file_size: i64,
+  created_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_view_comment_attachment
+        (attachment_id, workspace_id, view_id, content_type, file_size, created_by)
+      VALUES ($1, $2, $3, $4, $5, $6)
+    "#,
+    attachment_id,
+    workspace_id,
+    view_id,
+    content_type,
+    file_size,
+    created_by
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Attaches the uploaded attachments to the comment. Only the attachments uploaded by the author
+/// of the comment to the same view, and not attached to another comment yet, are attached.
+/// Returns the attached attachments.
+pub async fn update_comment_attachments<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+  attachment_ids: &[Uuid],
+) -> Result<Vec<AFCommentAttachmentRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCommentAttachmentRow,
+    r#"
+      UPDATE af_published_view_comment_attachment
+      SET comment_id = $1
+      WHERE attachment_id = ANY($4)
+        AND view_id = $2
+        AND created_by = (SELECT uid FROM af_user WHERE uuid = $3)
+        AND comment_id IS NULL
+      RETURNING attachment_id, workspace_id, comment_id, content_type, file_size
+    "#,
+    comment_id,
+    view_id,
+    user_uuid,
+    attachment_ids
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_comment_attachments_for_published_view<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Vec<AFCommentAttachmentRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCommentAttachmentRow,
+    r#"
+      SELECT attachment_id, workspace_id, comment_id, content_type, file_size
+      FROM af_published_view_comment_attachment
+      WHERE view_id = $1 AND comment_id IS NOT NULL
+      ORDER BY created_at
+    "#,
+    view_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the deleted attachments, so that the files of the attachments can be removed.
+pub async fn delete_comment_attachments<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+) -> Result<Vec<AFCommentAttachmentRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCommentAttachmentRow,
+    r#"
+      DELETE FROM af_published_view_comment_attachment
+      WHERE comment_id = $1
+      RETURNING attachment_id, workspace_id, comment_id, content_type, file_size
+    "#,
+    comment_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
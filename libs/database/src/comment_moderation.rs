@@ -0,0 +1,204 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFBlockedUserRow;
+
+/// Returns true if the user is an owner of the workspace the view is published in.
+pub async fn select_user_is_comment_moderator<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let is_moderator = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM af_published_collab apc
+        JOIN af_workspace_member awm ON awm.workspace_id = apc.workspace_id
+        JOIN af_roles ar ON ar.id = awm.role_id
+        JOIN af_user au ON au.uid = awm.uid
+        WHERE apc.view_id = $1
+          AND au.uuid = $2
+          AND ar.name = 'Owner'
+      ) AS "is_moderator!"
+    "#,
+    view_id,
+    user_uuid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(is_moderator)
+}
+
+/// Returns the ids of the comments on the view that were hidden or unhidden. The deleted comments
+/// are left untouched.
+pub async fn update_comments_hidden<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  comment_ids: &[Uuid],
+  is_hidden: bool,
+) -> Result<Vec<Uuid>, AppError> {
+  let comment_ids = sqlx::query_scalar!(
+    r#"
+      UPDATE af_published_view_comment
+      SET is_hidden = $3
+      WHERE view_id = $1
+        AND comment_id = ANY($2)
+        AND NOT is_deleted
+      RETURNING comment_id
+    "#,
+    view_id,
+    comment_ids,
+    is_hidden,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(comment_ids)
+}
+
+/// Returns the ids of the comments on the view that were pinned or unpinned. Pinning a comment
+/// that is already pinned keeps its position.
+pub async fn update_comments_pinned<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  comment_ids: &[Uuid],
+  is_pinned: bool,
+) -> Result<Vec<Uuid>, AppError> {
+  let comment_ids = sqlx::query_scalar!(
+    r#"
+      UPDATE af_published_view_comment
+      SET pinned_at = CASE WHEN $3 THEN COALESCE(pinned_at, NOW()) ELSE NULL END
+      WHERE view_id = $1
+        AND comment_id = ANY($2)
+        AND NOT is_deleted
+      RETURNING comment_id
+    "#,
+    view_id,
+    comment_ids,
+    is_pinned,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(comment_ids)
+}
+
+/// Marks the comments on the view as deleted, and returns their ids.
+pub async fn update_comments_deleted<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  comment_ids: &[Uuid],
+) -> Result<Vec<Uuid>, AppError> {
+  let comment_ids = sqlx::query_scalar!(
+    r#"
+      UPDATE af_published_view_comment
+      SET is_deleted = TRUE, pinned_at = NULL
+      WHERE view_id = $1
+        AND comment_id = ANY($2)
+        AND NOT is_deleted
+      RETURNING comment_id
+    "#,
+    view_id,
+    comment_ids,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(comment_ids)
+}
+
+/// Returns false if the user was already blocked in the namespace.
+pub async fn insert_comment_blocklist_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  namespace: &str,
+  blocked_uid: i64,
+  blocked_by_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_publish_namespace_comment_blocklist (namespace, blocked_uid, blocked_by)
+      VALUES ($1, $2, (SELECT uid FROM af_user WHERE uuid = $3))
+      ON CONFLICT DO NOTHING
+    "#,
+    namespace,
+    blocked_uid,
+    blocked_by_uuid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Unblocks the user in all the namespaces of the workspace. Returns false if the user was not
+/// blocked.
+pub async fn delete_comment_blocklist_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  blocked_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_publish_namespace_comment_blocklist
+      WHERE namespace IN (SELECT namespace FROM af_workspace_namespace WHERE workspace_id = $1)
+        AND blocked_uid = (SELECT uid FROM af_user WHERE uuid = $2)
+    "#,
+    workspace_id,
+    blocked_uuid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+/// Returns the users blocked in any of the namespaces of the workspace, the most recently blocked
+/// first.
+pub async fn select_comment_blocklist<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFBlockedUserRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFBlockedUserRow,
+    r#"
+      SELECT
+        au.uuid,
+        au.name,
+        au.metadata ->> 'icon_url' AS avatar_url,
+        MIN(bl.created_at) AS "blocked_at!"
+      FROM af_publish_namespace_comment_blocklist bl
+      JOIN af_workspace_namespace awn ON awn.namespace = bl.namespace
+      JOIN af_user au ON au.uid = bl.blocked_uid
+      WHERE awn.workspace_id = $1
+      GROUP BY au.uid
+      ORDER BY MIN(bl.created_at) DESC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns true if the user is blocked in any of the namespaces of the workspace the view is
+/// published in, so that a block outlives a change of the namespace.
+pub async fn select_is_blocked_from_commenting<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let is_blocked = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM af_publish_namespace_comment_blocklist bl
+        JOIN af_workspace_namespace awn ON awn.namespace = bl.namespace
+        JOIN af_published_collab apc ON apc.workspace_id = awn.workspace_id
+        JOIN af_user au ON au.uid = bl.blocked_uid
+        WHERE apc.view_id = $1 AND au.uuid = $2
+      ) AS "is_blocked!"
+    "#,
+    view_id,
+    user_uuid,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(is_blocked)
+}
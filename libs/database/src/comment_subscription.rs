@@ -0,0 +1,102 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCommentSubscriptionRow;
+
+/// Creates a subscription for the email, or returns the existing one if the email has already
+/// subscribed to the view.
+pub async fn upsert_comment_subscription<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  email: &str,
+) -> Result<AFCommentSubscriptionRow, AppError> {
+  let row = sqlx::query_as!(
+    AFCommentSubscriptionRow,
+    r#"
+      INSERT INTO af_published_view_comment_subscription (view_id, email)
+      VALUES ($1, $2)
+      ON CONFLICT (view_id, email) DO UPDATE SET email = EXCLUDED.email
+      RETURNING email, token, confirmed_at IS NOT NULL AS "confirmed!"
+    "#,
+    view_id,
+    email
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if there is no subscription with the token.
+pub async fn update_comment_subscription_confirmed<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  token: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_published_view_comment_subscription
+      SET confirmed_at = COALESCE(confirmed_at, CURRENT_TIMESTAMP)
+      WHERE view_id = $1 AND token = $2
+    "#,
+    view_id,
+    token
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns false if there is no subscription with the token.
+pub async fn delete_comment_subscription<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  token: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_published_view_comment_subscription
+      WHERE view_id = $1 AND token = $2
+    "#,
+    view_id,
+    token
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+pub async fn select_confirmed_comment_subscriptions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<Vec<AFCommentSubscriptionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCommentSubscriptionRow,
+    r#"
+      SELECT email, token, true AS "confirmed!"
+      FROM af_published_view_comment_subscription
+      WHERE view_id = $1 AND confirmed_at IS NOT NULL
+    "#,
+    view_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_comment_subscriber_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!"
+      FROM af_published_view_comment_subscription
+      WHERE view_id = $1 AND confirmed_at IS NOT NULL
+    "#,
+    view_id
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(count)
+}
@@ -0,0 +1,94 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCustomEmojiRow;
+
+/// Returns false if the workspace already has an emoji with the same shortcode.
+pub async fn insert_custom_emoji<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  shortcode: &str,
+  file_id: &str,
+  created_by: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_custom_emoji (workspace_id, shortcode, file_id, created_by)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace_id,
+    shortcode,
+    file_id,
+    created_by
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+pub async fn select_custom_emojis<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFCustomEmojiRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCustomEmojiRow,
+    r#"
+      SELECT shortcode, file_id, created_by, created_at
+      FROM af_workspace_custom_emoji
+      WHERE workspace_id = $1
+      ORDER BY shortcode
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the file id of the deleted emoji, or `None` if the emoji doesn't exist.
+pub async fn delete_custom_emoji<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  shortcode: &str,
+) -> Result<Option<String>, AppError> {
+  let file_id = sqlx::query_scalar!(
+    r#"
+      DELETE FROM af_workspace_custom_emoji
+      WHERE workspace_id = $1 AND shortcode = $2
+      RETURNING file_id
+    "#,
+    workspace_id,
+    shortcode
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(file_id)
+}
+
+/// Returns true if the workspace of the published view has an emoji with the given shortcode.
+pub async fn select_custom_emoji_exists_for_published_view<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: &Uuid,
+  shortcode: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1
+        FROM af_workspace_custom_emoji emoji
+        JOIN af_published_collab apc ON emoji.workspace_id = apc.workspace_id
+        WHERE apc.view_id = $1 AND emoji.shortcode = $2
+      )
+    "#,
+    view_id,
+    shortcode
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists.unwrap_or(false))
+}
@@ -1,6 +1,7 @@
 use crate::pg_row::AFBlobMetadataRow;
 use crate::resource_usage::{
-  delete_blob_metadata, get_blob_metadata, insert_blob_metadata, is_blob_metadata_exists,
+  copy_workspace_blob_metadata, delete_blob_metadata, get_blob_metadata, insert_blob_metadata,
+  is_blob_metadata_exists,
 };
 use app_error::AppError;
 use async_trait::async_trait;
@@ -55,6 +56,9 @@ pub trait BucketClient {
   ) -> Result<(usize, String), AppError>;
 
   async fn remove_dir(&self, dir: &str) -> Result<(), AppError>;
+
+  /// Copies all the objects under `src_dir` to `dest_dir`, keeping their relative keys.
+  async fn copy_dir(&self, src_dir: &str, dest_dir: &str) -> Result<(), AppError>;
 }
 
 pub trait BlobKey: Send + Sync {
@@ -83,6 +87,27 @@ where
     Ok(())
   }
 
+  /// Copies all the blobs of a workspace, and their metadata, to another workspace. Returns the
+  /// number of copied blobs.
+  pub async fn copy_workspace_blobs(
+    &self,
+    src_workspace_id: &Uuid,
+    dest_workspace_id: &Uuid,
+  ) -> Result<u64, AppError> {
+    info!(
+      "copying blobs of workspace {} to {}",
+      src_workspace_id, dest_workspace_id
+    );
+    self
+      .client
+      .copy_dir(
+        &format!("{}/", src_workspace_id),
+        &format!("{}/", dest_workspace_id),
+      )
+      .await?;
+    copy_workspace_blob_metadata(&self.pg_pool, src_workspace_id, dest_workspace_id).await
+  }
+
   #[instrument(skip_all, err)]
   #[inline]
   pub async fn put_blob<K: BlobKey>(
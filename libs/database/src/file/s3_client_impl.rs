@@ -434,6 +434,47 @@ impl BucketClient for AwsS3BucketClientImpl {
 
     Ok(())
   }
+
+  async fn copy_dir(&self, src_dir: &str, dest_dir: &str) -> Result<(), AppError> {
+    let mut continuation_token = None;
+    loop {
+      let list_objects = self
+        .client
+        .list_objects_v2()
+        .bucket(&self.bucket)
+        .prefix(src_dir)
+        .set_continuation_token(continuation_token.clone())
+        .send()
+        .await
+        .map_err(|err| anyhow!("Failed to list object: {}", err))?;
+
+      for key in list_objects
+        .contents
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|object| object.key)
+      {
+        let dest_key = format!("{}{}", dest_dir, &key[src_dir.len()..]);
+        trace!("Copying object {} to {}", key, dest_key);
+        self
+          .client
+          .copy_object()
+          .bucket(&self.bucket)
+          .copy_source(format!("{}/{}", self.bucket, key))
+          .key(dest_key)
+          .send()
+          .await
+          .map_err(|err| anyhow!("Failed to copy object: {}", err))?;
+      }
+
+      if !list_objects.is_truncated.unwrap_or(false) {
+        break;
+      }
+      continuation_token = list_objects.next_continuation_token;
+    }
+
+    Ok(())
+  }
 }
 
 #[derive(Debug)]
@@ -6,7 +6,7 @@ use sqlx::{Error, Executor, Postgres, Transaction};
 use uuid::Uuid;
 
 use database_entity::dto::{
-  AFCollabEmbeddingParams, IndexingStatus, QueryCollab, QueryCollabParams,
+  AFCollabEmbeddingParams, EmbeddingContentType, IndexingStatus, QueryCollab, QueryCollabParams,
 };
 
 pub async fn get_index_status<'a, E>(
@@ -92,6 +92,47 @@ pub async fn upsert_collab_embeddings(
     .execute(tx.deref_mut())
     .await?;
   }
+  upsert_collab_search_text(tx, records).await?;
+  Ok(())
+}
+
+/// Stores the plain text of the indexed collabs for the full-text search. The plain text fragments
+/// of a collab are joined together.
+async fn upsert_collab_search_text(
+  tx: &mut Transaction<'_, sqlx::Postgres>,
+  records: &[AFCollabEmbeddingParams],
+) -> Result<(), sqlx::Error> {
+  let mut contents: Vec<(&str, &CollabType, String)> = vec![];
+  for r in records {
+    if !matches!(r.content_type, EmbeddingContentType::PlainText) {
+      continue;
+    }
+    match contents
+      .iter_mut()
+      .find(|(object_id, _, _)| *object_id == r.object_id)
+    {
+      Some((_, _, content)) => {
+        content.push('\n');
+        content.push_str(&r.content);
+      },
+      None => contents.push((&r.object_id, &r.collab_type, r.content.clone())),
+    }
+  }
+
+  for (object_id, collab_type, content) in contents {
+    sqlx::query!(
+      r#"
+        INSERT INTO af_collab_search_text (oid, partition_key, content, indexed_at)
+        VALUES ($1, $2, $3, NOW())
+        ON CONFLICT (oid, partition_key) DO UPDATE SET content = $3, indexed_at = NOW()
+      "#,
+      object_id,
+      crate::collab::partition_key_from_collab_type(collab_type),
+      content,
+    )
+    .execute(tx.deref_mut())
+    .await?;
+  }
   Ok(())
 }
 
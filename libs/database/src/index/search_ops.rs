@@ -2,7 +2,7 @@ use std::ops::DerefMut;
 
 use chrono::{DateTime, Utc};
 use pgvector::Vector;
-use sqlx::Transaction;
+use sqlx::{Executor, Postgres, Transaction};
 use uuid::Uuid;
 
 pub async fn search_documents(
@@ -81,3 +81,57 @@ pub struct SearchDocumentItem {
   /// Similarity score to an original query. Lower is better.
   pub score: f64,
 }
+
+/// Searches the plain text of the collabs of the workspace with the postgres full-text search.
+/// When `object_ids` is given, only these collabs are searched.
+pub async fn search_collab_text<'a, E>(
+  executor: E,
+  workspace_id: &Uuid,
+  query: &str,
+  object_ids: Option<&[String]>,
+  limit: i64,
+) -> Result<Vec<SearchCollabTextItem>, sqlx::Error>
+where
+  E: Executor<'a, Database = Postgres>,
+{
+  let rows = sqlx::query_as!(
+    SearchCollabTextItem,
+    r#"
+    SELECT
+      st.oid AS object_id,
+      ts_headline(
+        'simple',
+        st.content,
+        query,
+        'MaxFragments=1,MaxWords=30,MinWords=10,StartSel="",StopSel=""'
+      ) AS "snippet!",
+      ts_rank(st.search_vector, query) AS "rank!"
+    FROM af_collab_search_text st
+    JOIN af_collab collab ON st.oid = collab.oid AND st.partition_key = collab.partition_key,
+      websearch_to_tsquery('simple', $2) query
+    WHERE collab.workspace_id = $1
+      AND collab.deleted_at IS NULL
+      AND st.search_vector @@ query
+      AND ($3::TEXT[] IS NULL OR st.oid = ANY($3))
+    ORDER BY "rank!" DESC
+    LIMIT $4
+    "#,
+    workspace_id,
+    query,
+    object_ids as Option<&[String]>,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchCollabTextItem {
+  /// Collab identifier.
+  pub object_id: String,
+  /// Fragment of the content around the matched words.
+  pub snippet: String,
+  /// Rank of the match, the higher the better.
+  pub rank: f32,
+}
@@ -0,0 +1,522 @@
+use app_error::AppError;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{
+  AFCommentTrendRow, AFDailyPublishedViewVisitRow, AFDailyReferrerRow, AFDailyVisitorCountRow,
+  AFMemberActivityRow, AFPageGrowthRow, AFPublishedViewVisitRow, AFViewEditActivityRow,
+};
+use crate::publish::split_publish_namespace;
+
+/// Counts an edit of the collab by the user for today.
+pub async fn upsert_collab_edit_activity<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_collab_edit_activity (workspace_id, oid, uid)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, activity_date, oid, uid)
+      DO UPDATE SET edit_count = af_collab_edit_activity.edit_count + 1
+    "#,
+    workspace_id,
+    oid,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Counts a visit of the published view for today. Does nothing if the view is not published.
+pub async fn upsert_published_view_visit<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<(), AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_view_visit (workspace_id, view_id)
+      SELECT workspace_id, view_id
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND publish_name = $3
+      ON CONFLICT (workspace_id, visit_date, view_id)
+      DO UPDATE SET visit_count = af_published_view_visit.visit_count + 1
+    "#,
+    namespace,
+    sub_namespace,
+    publish_name
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the number of members who edited at least one collab of the workspace since the given
+/// time.
+pub async fn select_active_member_count(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(DISTINCT uid) AS "count!"
+      FROM af_collab_edit_activity
+      WHERE workspace_id = $1 AND activity_date >= $2::timestamptz::date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(count)
+}
+
+/// Returns the number of members who joined the workspace since the given time.
+pub async fn select_new_member_count(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!"
+      FROM af_workspace_member
+      WHERE workspace_id = $1
+        AND created_at >= $2
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(count)
+}
+
+pub async fn select_most_edited_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFViewEditActivityRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFViewEditActivityRow,
+    r#"
+      SELECT
+        oid,
+        SUM(edit_count)::BIGINT AS "edit_count!",
+        COUNT(DISTINCT uid) AS "editor_count!"
+      FROM af_collab_edit_activity
+      WHERE workspace_id = $1 AND activity_date >= $2::timestamptz::date
+      GROUP BY oid
+      ORDER BY 2 DESC, oid
+      LIMIT $3
+    "#,
+    workspace_id,
+    since,
+    limit
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the number of comments made on the published views of the workspace since the given
+/// time.
+pub async fn select_published_view_comment_count(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!"
+      FROM af_published_view_comment avc
+      JOIN af_published_collab apc ON avc.view_id = apc.view_id
+      WHERE apc.workspace_id = $1
+        AND avc.created_at >= $2
+        AND NOT avc.is_deleted
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(count)
+}
+
+pub async fn select_published_view_visit_count(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COALESCE(SUM(visit_count), 0)::BIGINT AS "count!"
+      FROM af_published_view_visit
+      WHERE workspace_id = $1 AND visit_date >= $2::timestamptz::date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(count)
+}
+
+pub async fn select_most_visited_published_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+  limit: i64,
+) -> Result<Vec<AFPublishedViewVisitRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishedViewVisitRow,
+    r#"
+      SELECT
+        visit.view_id,
+        apc.publish_name AS "publish_name?",
+        SUM(visit.visit_count)::BIGINT AS "visit_count!"
+      FROM af_published_view_visit visit
+      LEFT JOIN af_published_collab apc
+        ON visit.workspace_id = apc.workspace_id AND visit.view_id = apc.view_id
+      WHERE visit.workspace_id = $1 AND visit.visit_date >= $2::timestamptz::date
+      GROUP BY visit.view_id, apc.publish_name
+      ORDER BY 3 DESC, visit.view_id
+      LIMIT $3
+    "#,
+    workspace_id,
+    since,
+    limit
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Records the visitor and the referrer of a visit of the published view for today. Does nothing
+/// if the view is not published.
+pub async fn upsert_published_view_access(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+  visitor_hash: &str,
+  referrer: &str,
+) -> Result<(), AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  let mut txn = pg_pool.begin().await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_view_visitor (workspace_id, visitor_hash)
+      SELECT workspace_id, $4
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND publish_name = $3
+      ON CONFLICT DO NOTHING
+    "#,
+    namespace,
+    sub_namespace,
+    publish_name,
+    visitor_hash
+  )
+  .execute(txn.as_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_published_view_referrer (workspace_id, referrer)
+      SELECT workspace_id, $4
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND publish_name = $3
+      ON CONFLICT (workspace_id, visit_date, referrer)
+      DO UPDATE SET visit_count = af_published_view_referrer.visit_count + 1
+    "#,
+    namespace,
+    sub_namespace,
+    publish_name,
+    referrer
+  )
+  .execute(txn.as_mut())
+  .await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+pub async fn select_daily_published_view_visitor_counts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFDailyVisitorCountRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDailyVisitorCountRow,
+    r#"
+      SELECT visit_date, COUNT(*) AS "visitor_count!"
+      FROM af_published_view_visitor
+      WHERE workspace_id = $1 AND visit_date >= $2
+      GROUP BY visit_date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the referrers with the most visits of each day since the given date.
+pub async fn select_daily_top_published_view_referrers(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+  limit_per_day: i64,
+) -> Result<Vec<AFDailyReferrerRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDailyReferrerRow,
+    r#"
+      SELECT visit_date AS "visit_date!", referrer AS "referrer!", visit_count AS "visit_count!"
+      FROM (
+        SELECT
+          visit_date,
+          referrer,
+          visit_count::BIGINT AS visit_count,
+          ROW_NUMBER() OVER (PARTITION BY visit_date ORDER BY visit_count DESC, referrer) AS rank
+        FROM af_published_view_referrer
+        WHERE workspace_id = $1 AND visit_date >= $2
+      ) ranked
+      WHERE rank <= $3
+      ORDER BY visit_date, visit_count DESC, referrer
+    "#,
+    workspace_id,
+    since,
+    limit_per_day
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the published views with the most visits of each day since the given date.
+pub async fn select_daily_most_visited_published_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+  limit_per_day: i64,
+) -> Result<Vec<AFDailyPublishedViewVisitRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDailyPublishedViewVisitRow,
+    r#"
+      SELECT
+        ranked.visit_date AS "visit_date!",
+        ranked.view_id AS "view_id!",
+        apc.publish_name AS "publish_name?",
+        ranked.visit_count AS "visit_count!"
+      FROM (
+        SELECT
+          workspace_id,
+          visit_date,
+          view_id,
+          visit_count::BIGINT AS visit_count,
+          ROW_NUMBER() OVER (PARTITION BY visit_date ORDER BY visit_count DESC, view_id) AS rank
+        FROM af_published_view_visit
+        WHERE workspace_id = $1 AND visit_date >= $2
+      ) ranked
+      LEFT JOIN af_published_collab apc
+        ON ranked.workspace_id = apc.workspace_id AND ranked.view_id = apc.view_id
+      WHERE ranked.rank <= $3
+      ORDER BY ranked.visit_date, ranked.visit_count DESC, ranked.view_id
+    "#,
+    workspace_id,
+    since,
+    limit_per_day
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the total number of visits of the published views of each day since the given date.
+pub async fn select_daily_published_view_visit_counts(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<(NaiveDate, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT visit_date, SUM(visit_count)::BIGINT AS "visit_count!"
+      FROM af_published_view_visit
+      WHERE workspace_id = $1 AND visit_date >= $2
+      GROUP BY visit_date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?
+  .into_iter()
+  .map(|row| (row.visit_date, row.visit_count))
+  .collect();
+  Ok(rows)
+}
+
+/// Removes the visitors and the referrers recorded before the given date. Returns the number of
+/// removed rows.
+pub async fn delete_published_view_access_before(
+  pg_pool: &PgPool,
+  before: NaiveDate,
+) -> Result<u64, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let visitors = sqlx::query!(
+    r#"
+      DELETE FROM af_published_view_visitor WHERE visit_date < $1
+    "#,
+    before
+  )
+  .execute(txn.as_mut())
+  .await?
+  .rows_affected();
+  let referrers = sqlx::query!(
+    r#"
+      DELETE FROM af_published_view_referrer WHERE visit_date < $1
+    "#,
+    before
+  )
+  .execute(txn.as_mut())
+  .await?
+  .rows_affected();
+  txn.commit().await?;
+  Ok(visitors + referrers)
+}
+
+/// Returns the edits of each member of the workspace between the given times, the most active
+/// members first.
+pub async fn select_member_activity<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  since: DateTime<Utc>,
+  until: DateTime<Utc>,
+) -> Result<Vec<AFMemberActivityRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFMemberActivityRow,
+    r#"
+      SELECT
+        au.uid AS "uid!",
+        au.name AS "name!",
+        au.email AS "email!",
+        COALESCE(SUM(acea.edit_count), 0)::BIGINT AS "edit_count!",
+        COUNT(DISTINCT acea.oid) AS "edited_page_count!",
+        MAX(acea.activity_date) AS last_active_date
+      FROM af_workspace_member afm
+      JOIN af_user au ON au.uid = afm.uid
+      LEFT JOIN af_collab_edit_activity acea
+        ON acea.workspace_id = afm.workspace_id
+        AND acea.uid = afm.uid
+        AND acea.activity_date >= $2::timestamptz::date
+        AND acea.activity_date <= $3::timestamptz::date
+      WHERE afm.workspace_id = $1
+      GROUP BY au.uid, au.name, au.email
+      ORDER BY 4 DESC, au.uid
+    "#,
+    workspace_id,
+    since,
+    until
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the number of documents created in each interval between the given times, and the
+/// number of documents of the workspace at the end of each interval. The interval is a unit
+/// accepted by `date_trunc`, e.g. `day`.
+pub async fn select_page_growth<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  interval: &str,
+  since: DateTime<Utc>,
+  until: DateTime<Utc>,
+) -> Result<Vec<AFPageGrowthRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPageGrowthRow,
+    r#"
+      WITH created AS (
+        SELECT date_trunc($2, created_at) AS period, COUNT(*) AS created_count
+        FROM af_collab
+        WHERE workspace_id = $1
+          AND partition_key = 0
+          AND deleted_at IS NULL
+          AND created_at >= $3
+          AND created_at < $4
+        GROUP BY 1
+      ),
+      existing AS (
+        SELECT COUNT(*) AS total_count
+        FROM af_collab
+        WHERE workspace_id = $1
+          AND partition_key = 0
+          AND deleted_at IS NULL
+          AND created_at < $3
+      )
+      SELECT
+        created.period AS "period!",
+        created.created_count AS "created_count!",
+        (existing.total_count + SUM(created.created_count) OVER (ORDER BY created.period))::BIGINT
+          AS "total_count!"
+      FROM created, existing
+      ORDER BY created.period
+    "#,
+    workspace_id,
+    interval,
+    since,
+    until
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the number of comments made on the published views of the workspace, and the number
+/// of their authors, in each interval between the given times. The interval is a unit accepted by
+/// `date_trunc`, e.g. `day`.
+pub async fn select_comment_trend<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  interval: &str,
+  since: DateTime<Utc>,
+  until: DateTime<Utc>,
+) -> Result<Vec<AFCommentTrendRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCommentTrendRow,
+    r#"
+      SELECT
+        date_trunc($2, avc.created_at) AS "period!",
+        COUNT(*) AS "comment_count!",
+        COUNT(DISTINCT avc.created_by) AS "commenter_count!"
+      FROM af_published_view_comment avc
+      JOIN af_published_collab apc ON avc.view_id = apc.view_id
+      WHERE apc.workspace_id = $1
+        AND avc.created_at >= $3
+        AND avc.created_at < $4
+        AND NOT avc.is_deleted
+      GROUP BY 1
+      ORDER BY 1
+    "#,
+    workspace_id,
+    interval,
+    since,
+    until
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
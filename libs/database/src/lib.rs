@@ -1,13 +1,47 @@
 pub mod access_request;
+pub mod account_merge;
+pub mod account_provision;
+pub mod announcement;
+pub mod api_usage;
+pub mod audit_log;
+pub mod auto_publish;
+pub mod bulk_invite;
 pub mod chat;
+pub mod client_version;
 pub mod collab;
+pub mod collab_share_token;
+pub mod comment_attachment;
+pub mod comment_moderation;
+pub mod comment_subscription;
+pub mod custom_emoji;
 pub mod file;
 pub mod history;
 pub mod index;
+pub mod insights;
 pub mod listener;
+pub mod member_expiry;
+pub mod member_group;
+pub mod page_share_link;
+pub mod page_template;
+pub mod permission_audit;
 pub mod pg_row;
+pub mod public_access;
 pub mod publish;
+pub mod publish_analytics;
+pub mod redaction;
+pub mod residency;
 pub mod resource_usage;
+pub mod saml;
+pub mod scheduler;
+pub mod secret_finding;
 pub mod template;
 pub mod user;
+pub mod user_access_token;
+pub mod user_block;
+pub mod user_device;
+pub mod view_permission;
 pub mod workspace;
+pub mod workspace_domain;
+pub mod workspace_lifecycle;
+pub mod workspace_publisher;
+pub mod workspace_role;
@@ -1,7 +1,8 @@
 use anyhow::Error;
+use app_error::AppError;
 use serde::de::DeserializeOwned;
 use sqlx::postgres::PgListener;
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool, Postgres};
 use tokio::sync::broadcast;
 use tracing::error;
 
@@ -39,3 +40,18 @@ where
     Ok(Self { notify })
   }
 }
+
+/// Sends the payload to the listeners of the channel, on every server listening to it. The
+/// payload must be shorter than 8000 bytes.
+pub async fn notify_listeners<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  channel: &str,
+  payload: &str,
+) -> Result<(), AppError> {
+  sqlx::query("SELECT pg_notify($1, $2)")
+    .bind(channel)
+    .bind(payload)
+    .execute(executor)
+    .await?;
+  Ok(())
+}
@@ -0,0 +1,200 @@
+use crate::pg_row::{AFCollabMemberAccessLevelRow, AFExpiringMemberRow, AFWorkspaceMemberPermRow};
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+/// Set the time at which the workspace member loses access to the workspace. The member's
+/// permission on the workspace folder collab shares the same expiry.
+pub async fn update_workspace_member_expires_at(
+  txn: &mut sqlx::Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  uid: &i64,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_workspace_member
+      SET expires_at = $3, expiry_notified_at = NULL
+      WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid,
+    expires_at,
+  )
+  .execute(txn.as_mut())
+  .await?;
+
+  if res.rows_affected() == 0 {
+    return Err(AppError::RecordNotFound(format!(
+      "user:{} is not a member of workspace:{}",
+      uid, workspace_id
+    )));
+  }
+
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_member
+      SET expires_at = $3, expiry_notified_at = NULL
+      WHERE oid = $1::TEXT AND uid = $2
+    "#,
+    workspace_id,
+    uid,
+    expires_at,
+  )
+  .execute(txn.as_mut())
+  .await?;
+  Ok(())
+}
+
+/// Set the time at which the collab member loses access to the collab.
+pub async fn update_collab_member_expires_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+  uid: &i64,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_member
+      SET expires_at = $3, expiry_notified_at = NULL
+      WHERE oid = $1 AND uid = $2
+    "#,
+    oid,
+    uid,
+    expires_at,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Delete the workspace members whose access has expired, returning the deleted members.
+pub async fn delete_expired_workspace_members(
+  pg_pool: &PgPool,
+) -> Result<Vec<AFWorkspaceMemberPermRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceMemberPermRow,
+    r#"
+      DELETE FROM af_workspace_member
+      WHERE expires_at <= NOW()
+      RETURNING uid, role_id AS role, workspace_id, expires_at
+    "#
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Delete the collab members whose access has expired, returning the deleted members.
+pub async fn delete_expired_collab_members(
+  pg_pool: &PgPool,
+) -> Result<Vec<AFCollabMemberAccessLevelRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabMemberAccessLevelRow,
+    r#"
+      WITH deleted AS (
+        DELETE FROM af_collab_member
+        WHERE expires_at <= NOW()
+        RETURNING uid, oid, permission_id, expires_at
+      )
+      SELECT
+        deleted.uid AS "uid!",
+        deleted.oid AS "oid!",
+        af_permissions.access_level,
+        deleted.expires_at
+      FROM deleted
+      JOIN af_permissions ON deleted.permission_id = af_permissions.id
+    "#
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the grants that expire before the given time and whose grantee has not been notified
+/// yet. Grants on the workspace folder collab are covered by the workspace membership.
+pub async fn select_expiring_members(
+  pg_pool: &PgPool,
+  expires_before: DateTime<Utc>,
+) -> Result<Vec<AFExpiringMemberRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFExpiringMemberRow,
+    r#"
+      WITH expiring AS (
+        SELECT uid, workspace_id, NULL::TEXT AS object_id, expires_at
+        FROM af_workspace_member
+        WHERE expires_at > NOW()
+          AND expires_at <= $1
+          AND expiry_notified_at IS NULL
+        UNION ALL
+        SELECT af_collab_member.uid, af_collab.workspace_id, af_collab_member.oid,
+          af_collab_member.expires_at
+        FROM af_collab_member
+        JOIN af_collab ON af_collab.oid = af_collab_member.oid
+        WHERE af_collab_member.expires_at > NOW()
+          AND af_collab_member.expires_at <= $1
+          AND af_collab_member.expiry_notified_at IS NULL
+          AND af_collab_member.oid <> af_collab.workspace_id::TEXT
+      )
+      SELECT
+        af_user.uid AS "uid!",
+        af_user.name AS "name!",
+        af_user.email AS "email!",
+        af_workspace.workspace_id AS "workspace_id!",
+        af_workspace.workspace_name,
+        (
+          SELECT COUNT(*)
+          FROM af_workspace_member
+          WHERE af_workspace_member.workspace_id = af_workspace.workspace_id
+        ) AS "member_count!",
+        expiring.object_id,
+        expiring.expires_at AS "expires_at!"
+      FROM expiring
+      JOIN af_user ON af_user.uid = expiring.uid
+      JOIN af_workspace ON af_workspace.workspace_id = expiring.workspace_id
+    "#,
+    expires_before,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Record that the grantee has been notified about the upcoming expiry of the grant.
+pub async fn update_member_expiry_notified<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: &i64,
+  workspace_id: &Uuid,
+  object_id: Option<&str>,
+) -> Result<(), AppError> {
+  match object_id {
+    None => {
+      sqlx::query!(
+        r#"
+          UPDATE af_workspace_member
+          SET expiry_notified_at = NOW()
+          WHERE workspace_id = $1 AND uid = $2
+        "#,
+        workspace_id,
+        uid,
+      )
+      .execute(executor)
+      .await?;
+    },
+    Some(object_id) => {
+      sqlx::query!(
+        r#"
+          UPDATE af_collab_member
+          SET expiry_notified_at = NOW()
+          WHERE oid = $1 AND uid = $2
+        "#,
+        object_id,
+        uid,
+      )
+      .execute(executor)
+      .await?;
+    },
+  }
+  Ok(())
+}
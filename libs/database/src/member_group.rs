@@ -0,0 +1,179 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::pg_row::AFMemberGroupRow;
+
+/// Creates the group, returns `None` if the workspace already has a group with the same name.
+pub async fn insert_member_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+) -> Result<Option<Uuid>, AppError> {
+  let group_id = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_workspace_member_group (workspace_id, name)
+      VALUES ($1, $2)
+      ON CONFLICT (workspace_id, name) DO NOTHING
+      RETURNING group_id
+    "#,
+    workspace_id,
+    name
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(group_id)
+}
+
+/// Replaces the members of the group.
+pub async fn replace_member_group_members(
+  txn: &mut Transaction<'_, Postgres>,
+  group_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member_group_member WHERE group_id = $1
+    "#,
+    group_id
+  )
+  .execute(txn.as_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_member_group_member (group_id, uid)
+      SELECT $1, uid FROM UNNEST($2::BIGINT[]) AS uid
+      ON CONFLICT DO NOTHING
+    "#,
+    group_id,
+    uids
+  )
+  .execute(txn.as_mut())
+  .await?;
+  Ok(())
+}
+
+/// Returns the groups of the workspace along with their members, by name.
+pub async fn select_member_groups<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFMemberGroupRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFMemberGroupRow,
+    r#"
+      SELECT
+        g.group_id,
+        g.name,
+        COALESCE(
+          ARRAY_AGG(m.uid ORDER BY m.uid) FILTER (WHERE m.uid IS NOT NULL),
+          '{}'
+        ) AS "member_uids!",
+        g.created_at
+      FROM af_workspace_member_group g
+      LEFT JOIN af_workspace_member_group_member m ON m.group_id = g.group_id
+      WHERE g.workspace_id = $1
+      GROUP BY g.group_id
+      ORDER BY g.name
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_member_group_exists<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1 FROM af_workspace_member_group WHERE workspace_id = $1 AND group_id = $2
+      ) AS "exists!"
+    "#,
+    workspace_id,
+    group_id
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists)
+}
+
+/// Deletes the group, along with the permissions granted to it. Returns false if the group is not
+/// found in the workspace.
+pub async fn delete_member_group<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member_group WHERE workspace_id = $1 AND group_id = $2
+    "#,
+    workspace_id,
+    group_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the groups of the workspace the user belongs to.
+pub async fn select_member_group_ids_for_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<Vec<Uuid>, AppError> {
+  let group_ids = sqlx::query_scalar!(
+    r#"
+      SELECT g.group_id
+      FROM af_workspace_member_group g
+      JOIN af_workspace_member_group_member m ON m.group_id = g.group_id
+      WHERE g.workspace_id = $1 AND m.uid = $2
+    "#,
+    workspace_id,
+    uid
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(group_ids)
+}
+
+/// Returns the members of every group, as `(group_id, uid)` pairs.
+pub async fn select_all_member_group_members<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT group_id, uid FROM af_workspace_member_group_member
+    "#
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.group_id, row.uid))
+      .collect(),
+  )
+}
+
+/// Returns the given users that are members of the workspace.
+pub async fn select_workspace_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uids: &[i64],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    r#"
+      SELECT uid FROM af_workspace_member WHERE workspace_id = $1 AND uid = ANY($2::BIGINT[])
+    "#,
+    workspace_id,
+    uids
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
@@ -0,0 +1,133 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFPageShareLinkRow;
+
+pub async fn insert_page_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  expires_at: Option<DateTime<Utc>>,
+  created_by: i64,
+) -> Result<AFPageShareLinkRow, AppError> {
+  let row = sqlx::query_as!(
+    AFPageShareLinkRow,
+    r#"
+      INSERT INTO af_page_share_link (workspace_id, view_id, expires_at, created_by)
+      VALUES ($1, $2, $3, $4)
+      RETURNING
+        link_id, token, workspace_id, view_id, created_by, created_at, expires_at
+    "#,
+    workspace_id,
+    view_id,
+    expires_at,
+    created_by
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the links of the page, the expired ones included, the latest first.
+pub async fn select_page_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<AFPageShareLinkRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPageShareLinkRow,
+    r#"
+      SELECT link_id, token, workspace_id, view_id, created_by, created_at, expires_at
+      FROM af_page_share_link
+      WHERE workspace_id = $1 AND view_id = $2
+      ORDER BY created_at DESC
+    "#,
+    workspace_id,
+    view_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the links of the workspace, the expired ones included, the latest first.
+pub async fn select_workspace_page_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFPageShareLinkRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPageShareLinkRow,
+    r#"
+      SELECT link_id, token, workspace_id, view_id, created_by, created_at, expires_at
+      FROM af_page_share_link
+      WHERE workspace_id = $1
+      ORDER BY created_at DESC
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the link of the token, unless it has expired.
+pub async fn select_active_page_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token: &Uuid,
+) -> Result<Option<AFPageShareLinkRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFPageShareLinkRow,
+    r#"
+      SELECT link_id, token, workspace_id, view_id, created_by, created_at, expires_at
+      FROM af_page_share_link
+      WHERE token = $1 AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    token
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the page has no such link.
+pub async fn delete_page_share_link<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  link_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_page_share_link
+      WHERE workspace_id = $1 AND view_id = $2 AND link_id = $3
+    "#,
+    workspace_id,
+    view_id,
+    link_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Deletes the links of the workspace, only the ones created by the user if given. Returns the
+/// number of deleted links.
+pub async fn delete_workspace_page_share_links<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  created_by: Option<i64>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_page_share_link
+      WHERE workspace_id = $1 AND ($2::BIGINT IS NULL OR created_by = $2)
+    "#,
+    workspace_id,
+    created_by
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected())
+}
@@ -0,0 +1,145 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFPageTemplateRow;
+
+pub async fn insert_page_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+  name: &str,
+  description: Option<&str>,
+  encoded_collab_v1: &[u8],
+  created_by: i64,
+) -> Result<AFPageTemplateRow, AppError> {
+  let row = sqlx::query_as!(
+    AFPageTemplateRow,
+    r#"
+      INSERT INTO af_workspace_page_template (
+        template_id, workspace_id, name, description, encoded_collab_v1, created_by
+      )
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING template_id, name, description, created_by, created_at, updated_at
+    "#,
+    template_id,
+    workspace_id,
+    name,
+    description,
+    encoded_collab_v1,
+    created_by
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_page_templates<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFPageTemplateRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPageTemplateRow,
+    r#"
+      SELECT template_id, name, description, created_by, created_at, updated_at
+      FROM af_workspace_page_template
+      WHERE workspace_id = $1
+      ORDER BY name, created_at
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_page_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<Option<AFPageTemplateRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFPageTemplateRow,
+    r#"
+      SELECT template_id, name, description, created_by, created_at, updated_at
+      FROM af_workspace_page_template
+      WHERE workspace_id = $1 AND template_id = $2
+    "#,
+    workspace_id,
+    template_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the name and the content of the template, or `None` if the template doesn't exist.
+pub async fn select_page_template_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<Option<(String, Vec<u8>)>, AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT name, encoded_collab_v1
+      FROM af_workspace_page_template
+      WHERE workspace_id = $1 AND template_id = $2
+    "#,
+    workspace_id,
+    template_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(|row| (row.name, row.encoded_collab_v1)))
+}
+
+/// Updates the given fields of the template, the others are kept. Returns `None` if the template
+/// doesn't exist.
+pub async fn update_page_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+  name: Option<&str>,
+  description: Option<&str>,
+  encoded_collab_v1: Option<&[u8]>,
+) -> Result<Option<AFPageTemplateRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFPageTemplateRow,
+    r#"
+      UPDATE af_workspace_page_template
+      SET name = COALESCE($3, name),
+          description = COALESCE($4, description),
+          encoded_collab_v1 = COALESCE($5, encoded_collab_v1),
+          updated_at = NOW()
+      WHERE workspace_id = $1 AND template_id = $2
+      RETURNING template_id, name, description, created_by, created_at, updated_at
+    "#,
+    workspace_id,
+    template_id,
+    name,
+    description,
+    encoded_collab_v1
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the template doesn't exist.
+pub async fn delete_page_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_page_template
+      WHERE workspace_id = $1 AND template_id = $2
+    "#,
+    workspace_id,
+    template_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
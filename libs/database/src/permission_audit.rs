@@ -0,0 +1,83 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFPermissionAuditEventRow;
+
+pub const PERMISSION_OBJECT_WORKSPACE: &str = "workspace";
+pub const PERMISSION_OBJECT_COLLAB: &str = "collab";
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_permission_audit_event<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  actor_uid: Option<i64>,
+  target_uid: i64,
+  object_type: &str,
+  object_id: &str,
+  old_role: Option<i32>,
+  new_role: Option<i32>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_permission_audit_log
+        (workspace_id, actor_uid, target_uid, object_type, object_id, old_role, new_role)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#,
+    workspace_id,
+    actor_uid,
+    target_uid,
+    object_type,
+    object_id,
+    old_role,
+    new_role,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the permission changes recorded in `[since, until)`, most recent first.
+pub async fn select_permission_audit_events(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: Option<DateTime<Utc>>,
+  until: Option<DateTime<Utc>>,
+  target_uid: Option<i64>,
+  limit: i64,
+) -> Result<Vec<AFPermissionAuditEventRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPermissionAuditEventRow,
+    r#"
+      SELECT
+        log.event_id,
+        log.actor_uid,
+        actor.email AS "actor_email?",
+        log.target_uid,
+        target.email AS "target_email?",
+        log.object_type,
+        log.object_id,
+        log.old_role,
+        log.new_role,
+        log.created_at
+      FROM af_permission_audit_log log
+      LEFT JOIN af_user actor ON actor.uid = log.actor_uid
+      LEFT JOIN af_user target ON target.uid = log.target_uid
+      WHERE log.workspace_id = $1
+        AND ($2::TIMESTAMPTZ IS NULL OR log.created_at >= $2)
+        AND ($3::TIMESTAMPTZ IS NULL OR log.created_at < $3)
+        AND ($4::BIGINT IS NULL OR log.target_uid = $4)
+      ORDER BY log.created_at DESC, log.event_id DESC
+      LIMIT $5
+    "#,
+    workspace_id,
+    since,
+    until,
+    target_uid,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
@@ -1,15 +1,19 @@
 use anyhow::anyhow;
 use app_error::AppError;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 
 use database_entity::dto::{
-  AFAccessLevel, AFRole, AFUserProfile, AFWebUser, AFWorkspace, AFWorkspaceInvitationStatus,
+  AFAccessLevel, AFMemberGroup, AFRole, AFUserAccessToken, AFUserDevice, AFUserProfile,
+  AFViewPermission, AFWebUser, AFWorkspace, AFWorkspaceInvitationStatus, AFWorkspaceRole,
   AccessRequestMinimal, AccessRequestStatus, AccessRequestWithViewId, AccessRequesterInfo,
-  AccountLink, GlobalComment, Reaction, Template, TemplateCategory, TemplateCategoryMinimal,
-  TemplateCategoryType, TemplateCreator, TemplateCreatorMinimal, TemplateGroup, TemplateMinimal,
+  AccountLink, BlockedUser, GlobalComment, MarketplaceTemplate, MarketplaceTemplateCreator,
+  PublishedViewVersion, Reaction, Template, TemplateCategory, TemplateCategoryMinimal,
+  TemplateCategoryType, TemplateCreator, TemplateCreatorMinimal, TemplateGroup, TemplateLicense,
+  TemplateMinimal, ViewPermissionGrantee, WorkspaceCapability, WorkspacePublisher,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::str::FromStr;
 use uuid::Uuid;
 
 /// Represent the row of the af_workspace table
@@ -25,6 +29,7 @@ pub struct AFWorkspaceRow {
   pub deleted_at: Option<DateTime<Utc>>,
   pub workspace_name: Option<String>,
   pub icon: Option<String>,
+  pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl TryFrom<AFWorkspaceRow> for AFWorkspace {
@@ -53,6 +58,7 @@ impl TryFrom<AFWorkspaceRow> for AFWorkspace {
       created_at,
       icon,
       member_count: None,
+      archived: value.archived_at.is_some(),
     })
   }
 }
@@ -70,6 +76,7 @@ pub struct AFWorkspaceWithMemberCountRow {
   pub workspace_name: Option<String>,
   pub icon: Option<String>,
   pub member_count: i64,
+  pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl TryFrom<AFWorkspaceWithMemberCountRow> for AFWorkspace {
@@ -98,6 +105,7 @@ impl TryFrom<AFWorkspaceWithMemberCountRow> for AFWorkspace {
       created_at,
       icon,
       member_count: Some(value.member_count),
+      archived: value.archived_at.is_some(),
     })
   }
 }
@@ -171,6 +179,7 @@ pub struct AFWorkspaceMemberPermRow {
   pub uid: i64,
   pub role: AFRole,
   pub workspace_id: Uuid,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, FromRow, Serialize, Deserialize)]
@@ -179,6 +188,7 @@ pub struct AFWorkspaceMemberRow {
   pub name: String,
   pub email: String,
   pub role: AFRole,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(FromRow)]
@@ -186,6 +196,7 @@ pub struct AFCollabMemberAccessLevelRow {
   pub uid: i64,
   pub oid: String,
   pub access_level: AFAccessLevel,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(FromRow, Clone, Debug, Serialize, Deserialize)]
@@ -227,6 +238,22 @@ pub struct AFSnapshotRow {
   pub deleted_at: Option<DateTime<Utc>>,
   pub created_at: DateTime<Utc>,
   pub workspace_id: Uuid,
+  pub checksum: Option<Vec<u8>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFCollabRedactionRow {
+  pub redaction_id: i64,
+  pub workspace_id: Uuid,
+  pub oid: String,
+  pub redacted_by: Option<i64>,
+  pub reason: String,
+  pub span_count: i32,
+  pub block_count: i32,
+  pub snapshot_count: i32,
+  pub history_count: i32,
+  pub embedding_count: i32,
+  pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, FromRow, Deserialize, Serialize)]
@@ -236,6 +263,7 @@ pub struct AFWorkspaceInvitationMinimal {
   pub invitee_uid: Option<i64>,
   pub status: AFWorkspaceInvitationStatus,
   pub role: AFRole,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(FromRow, Clone, Debug)]
@@ -291,6 +319,9 @@ pub struct AFGlobalCommentRow {
   pub reply_comment_id: Option<Uuid>,
   pub comment_id: Uuid,
   pub is_deleted: bool,
+  pub edited_at: Option<DateTime<Utc>>,
+  pub is_hidden: bool,
+  pub is_pinned: bool,
   pub can_be_deleted: bool,
 }
 
@@ -304,7 +335,87 @@ impl From<AFGlobalCommentRow> for GlobalComment {
       reply_comment_id: val.reply_comment_id,
       comment_id: val.comment_id,
       is_deleted: val.is_deleted,
+      edited_at: val.edited_at,
+      is_hidden: val.is_hidden,
+      is_pinned: val.is_pinned,
       can_be_deleted: val.can_be_deleted,
+      can_be_edited: false,
+      attachments: vec![],
+      replies: vec![],
+    }
+  }
+}
+
+/// A comment along with the published view it was made on.
+pub struct AFViewGlobalCommentRow {
+  pub view_id: Uuid,
+  pub user: Option<AFWebUserColumn>,
+  pub created_at: DateTime<Utc>,
+  pub last_updated_at: DateTime<Utc>,
+  pub content: String,
+  pub reply_comment_id: Option<Uuid>,
+  pub comment_id: Uuid,
+  pub is_deleted: bool,
+  pub edited_at: Option<DateTime<Utc>>,
+  pub is_hidden: bool,
+  pub is_pinned: bool,
+  pub can_be_deleted: bool,
+}
+
+impl From<AFViewGlobalCommentRow> for (Uuid, GlobalComment) {
+  fn from(val: AFViewGlobalCommentRow) -> Self {
+    let comment = GlobalComment {
+      user: val.user.map(|x| x.into()),
+      created_at: val.created_at,
+      last_updated_at: val.last_updated_at,
+      content: val.content,
+      reply_comment_id: val.reply_comment_id,
+      comment_id: val.comment_id,
+      is_deleted: val.is_deleted,
+      edited_at: val.edited_at,
+      is_hidden: val.is_hidden,
+      is_pinned: val.is_pinned,
+      can_be_deleted: val.can_be_deleted,
+      can_be_edited: false,
+      attachments: vec![],
+      replies: vec![],
+    };
+    (val.view_id, comment)
+  }
+}
+
+pub struct AFBlockedUserRow {
+  pub uuid: Uuid,
+  pub name: String,
+  pub avatar_url: Option<String>,
+  pub blocked_at: DateTime<Utc>,
+}
+
+impl From<AFBlockedUserRow> for BlockedUser {
+  fn from(val: AFBlockedUserRow) -> Self {
+    BlockedUser {
+      uuid: val.uuid,
+      name: val.name,
+      avatar_url: val.avatar_url,
+      blocked_at: val.blocked_at,
+    }
+  }
+}
+
+pub struct AFWorkspacePublisherRow {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub granted_at: DateTime<Utc>,
+}
+
+impl From<AFWorkspacePublisherRow> for WorkspacePublisher {
+  fn from(val: AFWorkspacePublisherRow) -> Self {
+    WorkspacePublisher {
+      uid: val.uid,
+      name: val.name,
+      email: val.email,
+      granted_at: val.granted_at,
     }
   }
 }
@@ -395,6 +506,95 @@ impl From<TemplateCategoryType> for AFTemplateCategoryTypeColumn {
   }
 }
 
+#[derive(sqlx::Type, Serialize, Debug)]
+#[repr(i32)]
+pub enum AFTemplateLicenseColumn {
+  PublicDomain = 0,
+  Attribution = 1,
+  AttributionShareAlike = 2,
+  AttributionNonCommercial = 3,
+}
+
+impl From<AFTemplateLicenseColumn> for TemplateLicense {
+  fn from(value: AFTemplateLicenseColumn) -> Self {
+    match value {
+      AFTemplateLicenseColumn::PublicDomain => TemplateLicense::PublicDomain,
+      AFTemplateLicenseColumn::Attribution => TemplateLicense::Attribution,
+      AFTemplateLicenseColumn::AttributionShareAlike => TemplateLicense::AttributionShareAlike,
+      AFTemplateLicenseColumn::AttributionNonCommercial => {
+        TemplateLicense::AttributionNonCommercial
+      },
+    }
+  }
+}
+
+impl From<TemplateLicense> for AFTemplateLicenseColumn {
+  fn from(val: TemplateLicense) -> Self {
+    match val {
+      TemplateLicense::PublicDomain => AFTemplateLicenseColumn::PublicDomain,
+      TemplateLicense::Attribution => AFTemplateLicenseColumn::Attribution,
+      TemplateLicense::AttributionShareAlike => AFTemplateLicenseColumn::AttributionShareAlike,
+      TemplateLicense::AttributionNonCommercial => {
+        AFTemplateLicenseColumn::AttributionNonCommercial
+      },
+    }
+  }
+}
+
+#[derive(Debug, FromRow)]
+pub struct AFMarketplaceTemplateRow {
+  pub view_id: Uuid,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub name: String,
+  pub description: String,
+  pub license: AFTemplateLicenseColumn,
+  pub screenshot_urls: Vec<String>,
+  pub category_id: Option<Uuid>,
+  pub category_name: Option<String>,
+  pub category_icon: Option<String>,
+  pub category_bg_color: Option<String>,
+  pub creator_uuid: Uuid,
+  pub creator_name: String,
+  pub creator_avatar_url: Option<String>,
+  pub install_count: i64,
+}
+
+impl From<AFMarketplaceTemplateRow> for MarketplaceTemplate {
+  fn from(value: AFMarketplaceTemplateRow) -> Self {
+    let category = match (
+      value.category_id,
+      value.category_name,
+      value.category_icon,
+      value.category_bg_color,
+    ) {
+      (Some(id), Some(name), Some(icon), Some(bg_color)) => Some(TemplateCategoryMinimal {
+        id,
+        name,
+        icon,
+        bg_color,
+      }),
+      _ => None,
+    };
+    Self {
+      view_id: value.view_id,
+      created_at: value.created_at,
+      last_updated_at: value.updated_at,
+      name: value.name,
+      description: value.description,
+      license: value.license.into(),
+      screenshot_urls: value.screenshot_urls,
+      category,
+      creator: MarketplaceTemplateCreator {
+        uuid: value.creator_uuid,
+        name: value.creator_name,
+        avatar_url: value.creator_avatar_url,
+      },
+      install_count: value.install_count,
+    }
+  }
+}
+
 #[derive(sqlx::Type, Serialize, Debug)]
 #[sqlx(type_name = "account_link_type")]
 pub struct AccountLinkColumn {
@@ -645,3 +845,561 @@ impl TryFrom<AFAccessRequestWithViewIdColumn> for AccessRequestWithViewId {
     })
   }
 }
+
+#[derive(FromRow, Debug)]
+pub struct AFExpiringMemberRow {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub workspace_id: Uuid,
+  pub workspace_name: Option<String>,
+  pub member_count: i64,
+  /// `None` if the expiring grant is the workspace membership itself.
+  pub object_id: Option<String>,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFViewEditActivityRow {
+  pub oid: String,
+  pub edit_count: i64,
+  pub editor_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishedViewVisitRow {
+  pub view_id: Uuid,
+  pub publish_name: Option<String>,
+  pub visit_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFApiUsageRow {
+  pub route_family: String,
+  pub call_count: i64,
+  pub client_error_count: i64,
+  pub server_error_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFDailyApiUsageRow {
+  pub usage_date: NaiveDate,
+  pub call_count: i64,
+  pub client_error_count: i64,
+  pub server_error_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFDailyPublishedViewStatsRow {
+  pub stat_date: NaiveDate,
+  pub view_count: i64,
+  pub unique_visitor_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishedViewStatsRow {
+  pub view_id: Uuid,
+  pub publish_name: Option<String>,
+  pub view_count: i64,
+  pub unique_visitor_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFDailyVisitorCountRow {
+  pub visit_date: NaiveDate,
+  pub visitor_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFDailyReferrerRow {
+  pub visit_date: NaiveDate,
+  pub referrer: String,
+  pub visit_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFDailyPublishedViewVisitRow {
+  pub visit_date: NaiveDate,
+  pub view_id: Uuid,
+  pub publish_name: Option<String>,
+  pub visit_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFMemberActivityRow {
+  pub uid: i64,
+  pub name: String,
+  pub email: String,
+  pub edit_count: i64,
+  pub edited_page_count: i64,
+  pub last_active_date: Option<NaiveDate>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPageGrowthRow {
+  pub period: DateTime<Utc>,
+  pub created_count: i64,
+  pub total_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCommentTrendRow {
+  pub period: DateTime<Utc>,
+  pub comment_count: i64,
+  pub commenter_count: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCustomEmojiRow {
+  pub shortcode: String,
+  pub file_id: String,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCommentAttachmentRow {
+  pub attachment_id: Uuid,
+  pub workspace_id: Uuid,
+  pub comment_id: Option<Uuid>,
+  pub content_type: String,
+  pub file_size: i64,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCommentSubscriptionRow {
+  pub email: String,
+  pub token: Uuid,
+  pub confirmed: bool,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFBulkInviteRow {
+  pub task_id: Uuid,
+  pub status: i16,
+  pub total_count: i32,
+  pub success_count: i32,
+  pub failed_count: i32,
+  pub errors: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFClientVersionPolicyRow {
+  pub version_req: String,
+  pub status: i16,
+  pub message: Option<String>,
+  pub upgrade_url: Option<String>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFScheduleRow {
+  pub name: String,
+  pub interval_secs: i64,
+  pub next_run_at: DateTime<Utc>,
+  pub last_started_at: Option<DateTime<Utc>>,
+  pub last_finished_at: Option<DateTime<Utc>>,
+  pub last_run_by: Option<String>,
+  pub run_count: i64,
+  pub failure_count: i64,
+  pub consecutive_failures: i32,
+  pub last_error: Option<String>,
+  pub last_failed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFSchedulerLeaderRow {
+  pub node_id: String,
+  pub lease_expires_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFAnnouncementRow {
+  pub announcement_id: Uuid,
+  pub title: String,
+  pub message: String,
+  pub severity: i16,
+  pub starts_at: Option<DateTime<Utc>>,
+  pub ends_at: Option<DateTime<Utc>>,
+  pub dismissible: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabSecretFindingRow {
+  pub oid: String,
+  pub workspace_id: Uuid,
+  pub secret_kinds: Vec<String>,
+  pub detected_by: Option<i64>,
+  pub detected_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFAutoPublishViewRow {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub publisher_uuid: Uuid,
+  pub changed_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishSubNamespaceRow {
+  pub sub_namespace: String,
+  pub title: String,
+  pub default_view_id: Option<Uuid>,
+  pub view_ids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceAuditEventRow {
+  pub event_id: i64,
+  pub actor_uid: i64,
+  pub actor_name: Option<String>,
+  pub actor_email: Option<String>,
+  pub action: String,
+  pub target: Option<String>,
+  pub details: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPermissionAuditEventRow {
+  pub event_id: i64,
+  pub actor_uid: Option<i64>,
+  pub actor_email: Option<String>,
+  pub target_uid: i64,
+  pub target_email: Option<String>,
+  pub object_type: String,
+  pub object_id: String,
+  pub old_role: Option<i32>,
+  pub new_role: Option<i32>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceSamlConfigRow {
+  pub workspace_id: Uuid,
+  pub idp_metadata_xml: String,
+  pub email_attribute: Option<String>,
+  pub enabled: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceDomainRow {
+  pub workspace_id: Uuid,
+  pub domain: String,
+  pub verification_token: String,
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceLifecycleRow {
+  pub workspace_id: Uuid,
+  pub workspace_name: Option<String>,
+  pub owner_name: String,
+  pub owner_email: String,
+  pub member_count: i64,
+  pub archived: bool,
+  pub exempt: bool,
+  /// The last edit in the workspace, or its creation or the reset of its lifecycle if later.
+  pub last_active_at: DateTime<Utc>,
+  pub warned_at: Option<DateTime<Utc>>,
+  pub archived_at: Option<DateTime<Utc>>,
+  pub delete_at: Option<DateTime<Utc>>,
+  pub deletion_notice_days: Option<i32>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFAccountProvisionRow {
+  pub email: String,
+  pub result: Option<serde_json::Value>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFExpiredPublishedCollabRow {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub publisher_uuid: Uuid,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishedSitemapEntryRow {
+  pub view_id: Uuid,
+  pub publish_name: String,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishedCollabVersionRow {
+  pub version: i32,
+  pub published_at: DateTime<Utc>,
+  pub restored_from_version: Option<i32>,
+  pub publisher_email: Option<String>,
+  pub size: i64,
+  pub is_current: bool,
+}
+
+impl From<AFPublishedCollabVersionRow> for PublishedViewVersion {
+  fn from(row: AFPublishedCollabVersionRow) -> Self {
+    PublishedViewVersion {
+      version: row.version,
+      published_at: row.published_at,
+      restored_from_version: row.restored_from_version,
+      publisher_email: row.publisher_email,
+      size: row.size,
+      is_current: row.is_current,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPublishedViewSeoRow {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub metadata: serde_json::Value,
+  pub seo_title: Option<String>,
+  pub seo_description: Option<String>,
+  pub seo_image_url: Option<String>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabPlacementRow {
+  pub oid: String,
+  pub workspace_id: Uuid,
+  pub shard: String,
+  pub fenced_at: Option<DateTime<Utc>>,
+}
+
+/// A collab read from a storage shard, along with its archived payload and its snapshots.
+pub struct AFCollabShardData {
+  pub collabs: Vec<AFCollabShardRow>,
+  pub archives: Vec<AFCollabArchiveShardRow>,
+  pub snapshots: Vec<AFSnapshotRow>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabShardRow {
+  pub oid: String,
+  pub blob: Vec<u8>,
+  pub len: Option<i32>,
+  pub partition_key: i32,
+  pub encrypt: Option<i32>,
+  pub owner_uid: i64,
+  pub deleted_at: Option<DateTime<Utc>>,
+  pub created_at: Option<DateTime<Utc>>,
+  pub workspace_id: Uuid,
+  pub updated_at: Option<DateTime<Utc>>,
+  pub archived_at: Option<DateTime<Utc>>,
+  pub checksum: Option<Vec<u8>>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabArchiveShardRow {
+  pub oid: String,
+  pub partition_key: i32,
+  pub workspace_id: Uuid,
+  pub blob: Vec<u8>,
+  pub len: i32,
+  pub archived_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFCollabCorruptionRow {
+  pub oid: String,
+  pub workspace_id: Uuid,
+  pub partition_key: Option<i32>,
+  pub snapshot_id: i64,
+  pub detected_by: String,
+  pub detected_at: DateTime<Utc>,
+  pub last_good_snapshot_id: Option<i64>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPageTemplateRow {
+  pub template_id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFPageShareLinkRow {
+  pub link_id: Uuid,
+  pub token: Uuid,
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFCollabShareTokenRow {
+  pub token_id: Uuid,
+  pub workspace_id: Uuid,
+  pub object_id: String,
+  pub issued_by: i64,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFUserDeviceRow {
+  pub device_id: String,
+  pub platform: Option<String>,
+  pub client_version: Option<String>,
+  pub first_seen_at: DateTime<Utc>,
+  pub last_seen_at: DateTime<Utc>,
+  pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<AFUserDeviceRow> for AFUserDevice {
+  fn from(val: AFUserDeviceRow) -> Self {
+    AFUserDevice {
+      device_id: val.device_id,
+      platform: val.platform,
+      client_version: val.client_version,
+      first_seen_at: val.first_seen_at,
+      last_seen_at: val.last_seen_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFUserAccessTokenRow {
+  pub id: Uuid,
+  pub name: String,
+  pub read_only: bool,
+  pub workspace_ids: Option<Vec<Uuid>>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<AFUserAccessTokenRow> for AFUserAccessToken {
+  fn from(val: AFUserAccessTokenRow) -> Self {
+    AFUserAccessToken {
+      id: val.id,
+      name: val.name,
+      read_only: val.read_only,
+      workspace_ids: val.workspace_ids,
+      created_at: val.created_at,
+      expires_at: val.expires_at,
+      last_used_at: val.last_used_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFAccessTokenGrantRow {
+  pub id: Uuid,
+  pub user_uuid: Uuid,
+  pub read_only: bool,
+  pub workspace_ids: Option<Vec<Uuid>>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFMemberGroupRow {
+  pub group_id: Uuid,
+  pub name: String,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<AFMemberGroupRow> for AFMemberGroup {
+  fn from(val: AFMemberGroupRow) -> Self {
+    AFMemberGroup {
+      group_id: val.group_id,
+      name: val.name,
+      member_uids: val.member_uids,
+      created_at: val.created_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceRoleRow {
+  pub role_id: Uuid,
+  pub name: String,
+  pub capabilities: Vec<String>,
+  pub member_uids: Vec<i64>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<AFWorkspaceRoleRow> for AFWorkspaceRole {
+  fn from(val: AFWorkspaceRoleRow) -> Self {
+    AFWorkspaceRole {
+      role_id: val.role_id,
+      name: val.name,
+      // The capabilities that are no longer known are ignored
+      capabilities: val
+        .capabilities
+        .iter()
+        .filter_map(|capability| WorkspaceCapability::from_str(capability).ok())
+        .collect(),
+      member_uids: val.member_uids,
+      created_at: val.created_at,
+    }
+  }
+}
+
+#[derive(FromRow, Debug)]
+pub struct AFWorkspaceMemberCapabilityRow {
+  pub uid: i64,
+  pub workspace_id: Uuid,
+  pub capabilities: Vec<String>,
+}
+
+impl AFWorkspaceMemberCapabilityRow {
+  pub fn capabilities(&self) -> Vec<WorkspaceCapability> {
+    self
+      .capabilities
+      .iter()
+      .filter_map(|capability| WorkspaceCapability::from_str(capability).ok())
+      .collect()
+  }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AFViewPermissionRow {
+  pub view_id: String,
+  pub uid: Option<i64>,
+  pub group_id: Option<Uuid>,
+  pub access_level: i32,
+}
+
+impl AFViewPermissionRow {
+  /// Returns who the permission is granted to, `None` for a row that is neither granted to a
+  /// member nor to a group, which the table doesn't allow.
+  pub fn grantee(&self) -> Option<ViewPermissionGrantee> {
+    match (self.uid, self.group_id) {
+      (Some(uid), None) => Some(ViewPermissionGrantee::Member { uid }),
+      (None, Some(group_id)) => Some(ViewPermissionGrantee::Group { group_id }),
+      _ => None,
+    }
+  }
+}
+
+impl TryFrom<AFViewPermissionRow> for AFViewPermission {
+  type Error = AppError;
+
+  fn try_from(value: AFViewPermissionRow) -> Result<Self, Self::Error> {
+    let grantee = value.grantee().ok_or(AppError::Internal(anyhow!(
+      "Unexpected view permission without grantee"
+    )))?;
+    Ok(AFViewPermission {
+      grantee,
+      access_level: AFAccessLevel::from(value.access_level),
+    })
+  }
+}
@@ -0,0 +1,71 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+/// Makes the workspace publicly readable. Enabling the public access of a public workspace keeps
+/// the original time and user.
+pub async fn insert_workspace_public_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  enabled_by: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_public_access (workspace_id, enabled_by)
+      VALUES ($1, $2)
+      ON CONFLICT (workspace_id) DO NOTHING
+    "#,
+    workspace_id,
+    enabled_by,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn delete_workspace_public_access<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_public_access WHERE workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the time the public access was enabled, `None` if the workspace is not public.
+pub async fn select_workspace_public_access_enabled_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+  let enabled_at = sqlx::query_scalar!(
+    r#"
+      SELECT enabled_at FROM af_workspace_public_access WHERE workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(enabled_at)
+}
+
+pub async fn select_collab_workspace_id<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Option<Uuid>, AppError> {
+  let workspace_id = sqlx::query_scalar!(
+    r#"
+      SELECT workspace_id FROM af_collab WHERE oid = $1
+    "#,
+    oid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(workspace_id)
+}
@@ -1,10 +1,25 @@
+use crate::pg_row::{
+  AFExpiredPublishedCollabRow, AFPublishSubNamespaceRow, AFPublishedCollabVersionRow,
+  AFPublishedSitemapEntryRow, AFPublishedViewSeoRow,
+};
 use app_error::AppError;
+use chrono::{DateTime, Utc};
 use database_entity::dto::{
-  PatchPublishedCollab, PublishCollabItem, PublishCollabKey, PublishInfo, WorkspaceNamespace,
+  PatchPublishedCollab, PatchPublishedViewSeo, PublishCollabItem, PublishCollabKey, PublishInfo,
+  WorkspaceNamespace,
 };
 use sqlx::{Executor, PgPool, Postgres};
 use uuid::Uuid;
 
+/// Splits a publish namespace into the namespace of the workspace and the sub-namespace, if any,
+/// e.g. `acme/handbook` into `acme` and `handbook`.
+pub fn split_publish_namespace(publish_namespace: &str) -> (&str, Option<&str>) {
+  match publish_namespace.split_once('/') {
+    Some((namespace, sub_namespace)) => (namespace, Some(sub_namespace)),
+    None => (publish_namespace, None),
+  }
+}
+
 pub async fn select_user_is_collab_publisher_for_all_views(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
@@ -61,8 +76,16 @@ pub async fn insert_non_orginal_workspace_publish_namespace(
 ) -> Result<(), AppError> {
   let res = sqlx::query!(
     r#"
-      INSERT INTO af_workspace_namespace
-      VALUES ($1, $2, FALSE)
+      INSERT INTO af_workspace_namespace (namespace, workspace_id, is_original, embed_allowlist)
+      SELECT $1, $2, FALSE, COALESCE(
+        (
+          SELECT embed_allowlist
+          FROM af_workspace_namespace
+          WHERE workspace_id = $2
+            AND is_original = TRUE
+        ),
+        '{}'
+      )
     "#,
     new_namespace,
     workspace_id,
@@ -170,6 +193,65 @@ pub async fn update_workspace_default_publish_view_set_null<
   Ok(())
 }
 
+#[inline]
+pub async fn select_workspace_publish_namespace_embed_allowlist<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  namespace: &str,
+) -> Result<Vec<String>, AppError> {
+  let embed_allowlist = sqlx::query_scalar!(
+    r#"
+      SELECT embed_allowlist
+      FROM af_workspace_namespace
+      WHERE workspace_id = $1
+        AND namespace = $2
+    "#,
+    workspace_id,
+    namespace,
+  )
+  .fetch_optional(executor)
+  .await?;
+
+  Ok(embed_allowlist.unwrap_or_default())
+}
+
+#[inline]
+pub async fn update_workspace_publish_namespace_embed_allowlist<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  namespace: &str,
+  embed_allowlist: &[String],
+) -> Result<(), AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_workspace_namespace
+      SET embed_allowlist = $3
+      WHERE workspace_id = $1
+        AND namespace = $2
+    "#,
+    workspace_id,
+    namespace,
+    embed_allowlist,
+  )
+  .execute(executor)
+  .await?;
+
+  if res.rows_affected() != 1 {
+    tracing::error!(
+      "Failed to update publish namespace embed allowlist, workspace_id: {}, namespace: {}, rows_affected: {}",
+      workspace_id, namespace, res.rows_affected()
+    );
+  }
+
+  Ok(())
+}
+
 #[inline]
 pub async fn select_workspace_publish_namespaces(
   pg_pool: &PgPool,
@@ -214,6 +296,10 @@ pub async fn select_workspace_publish_namespace(
 }
 
 #[inline]
+/// The number of prior versions kept for each published view.
+pub const MAX_ARCHIVED_PUBLISH_VERSIONS: i32 = 20;
+
+/// Publishes the views, the versions they replace are kept as prior versions.
 pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   workspace_id: &Uuid,
@@ -232,8 +318,27 @@ pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Po
     blobs.push(item.data);
   });
 
+  // The versions being replaced are archived by the same statement, along with the pruning of
+  // the oldest archived versions.
   let res = sqlx::query!(
     r#"
+      WITH archived AS (
+        INSERT INTO af_published_collab_version
+          (workspace_id, view_id, version, metadata, blob, published_by, published_at, restored_from_version)
+        SELECT workspace_id, view_id, version, metadata, blob, published_by, version_published_at, restored_from_version
+        FROM af_published_collab
+        WHERE workspace_id = $1 AND view_id = ANY($2)
+        ON CONFLICT DO NOTHING
+      ),
+      pruned AS (
+        DELETE FROM af_published_collab_version apcv
+        USING af_published_collab apc
+        WHERE apcv.workspace_id = apc.workspace_id
+          AND apcv.view_id = apc.view_id
+          AND apc.workspace_id = $1
+          AND apc.view_id = ANY($2)
+          AND apcv.version <= apc.version - $8
+      )
       INSERT INTO af_published_collab (workspace_id, view_id, publish_name, published_by, metadata, blob)
       SELECT * FROM UNNEST(
         (SELECT array_agg((SELECT $1::uuid)) FROM generate_series(1, $7))::uuid[],
@@ -247,7 +352,10 @@ pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Po
       SET metadata = EXCLUDED.metadata,
           blob = EXCLUDED.blob,
           published_by = EXCLUDED.published_by,
-          publish_name = EXCLUDED.publish_name
+          publish_name = EXCLUDED.publish_name,
+          version = af_published_collab.version + 1,
+          version_published_at = NOW(),
+          restored_from_version = NULL
     "#,
     workspace_id,
     &view_ids,
@@ -256,6 +364,7 @@ pub async fn insert_or_replace_publish_collabs<'a, E: Executor<'a, Database = Po
     &metadatas,
     &blobs,
     item_count as i32,
+    MAX_ARCHIVED_PUBLISH_VERSIONS,
   )
   .execute(executor)
   .await?;
@@ -276,14 +385,17 @@ pub async fn select_publish_collab_meta<'a, E: Executor<'a, Database = Postgres>
   publish_namespace: &str,
   publish_name: &str,
 ) -> Result<serde_json::Value, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
   let res = sqlx::query!(
     r#"
     SELECT metadata
     FROM af_published_collab
     WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
-      AND publish_name = $2
+      AND sub_namespace IS NOT DISTINCT FROM $2
+      AND publish_name = $3
     "#,
-    publish_namespace,
+    namespace,
+    sub_namespace,
     publish_name,
   )
   .fetch_one(executor)
@@ -329,33 +441,46 @@ pub async fn update_published_collabs(
   patches: &[PatchPublishedCollab],
 ) -> Result<(), AppError> {
   for patch in patches {
-    let new_publish_name = match &patch.publish_name {
-      Some(new_publish_name) => new_publish_name,
-      None => continue,
-    };
+    if let Some(new_publish_name) = &patch.publish_name {
+      let res = sqlx::query!(
+        r#"
+          UPDATE af_published_collab
+          SET publish_name = $1
+          WHERE workspace_id = $2
+              AND view_id = $3
+        "#,
+        new_publish_name,
+        workspace_id,
+        patch.view_id,
+      )
+      .execute(txn.as_mut())
+      .await?;
 
-    let res = sqlx::query!(
-      r#"
-        UPDATE af_published_collab
-        SET publish_name = $1
-        WHERE workspace_id = $2
-            AND view_id = $3
-      "#,
-      patch.publish_name,
-      workspace_id,
-      patch.view_id,
-    )
-    .execute(txn.as_mut())
-    .await?;
-
-    if res.rows_affected() != 1 {
-      tracing::error!(
-          "Failed to update published collab publish name, workspace_id: {}, view_id: {}, new_publish_name: {}, rows_affected: {}",
-          workspace_id,
-          patch.view_id,
-          new_publish_name,
-          res.rows_affected()
-        );
+      if res.rows_affected() != 1 {
+        tracing::error!(
+            "Failed to update published collab publish name, workspace_id: {}, view_id: {}, new_publish_name: {}, rows_affected: {}",
+            workspace_id,
+            patch.view_id,
+            new_publish_name,
+            res.rows_affected()
+          );
+      }
+    }
+
+    if let Some(expires_at) = patch.expires_at {
+      sqlx::query!(
+        r#"
+          UPDATE af_published_collab
+          SET expires_at = $1
+          WHERE workspace_id = $2
+              AND view_id = $3
+        "#,
+        expires_at,
+        workspace_id,
+        patch.view_id,
+      )
+      .execute(txn.as_mut())
+      .await?;
     }
   }
 
@@ -404,15 +529,18 @@ pub async fn select_published_collab_workspace_view_id<'a, E: Executor<'a, Datab
   publish_namespace: &str,
   publish_name: &str,
 ) -> Result<PublishCollabKey, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
   let key = sqlx::query_as!(
     PublishCollabKey,
     r#"
       SELECT workspace_id, view_id
       FROM af_published_collab
       WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
-      AND publish_name = $2
+      AND sub_namespace IS NOT DISTINCT FROM $2
+      AND publish_name = $3
     "#,
-    publish_namespace,
+    namespace,
+    sub_namespace,
     publish_name,
   )
   .fetch_one(executor)
@@ -426,14 +554,17 @@ pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgre
   publish_namespace: &str,
   publish_name: &str,
 ) -> Result<Vec<u8>, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
   let res = sqlx::query_scalar!(
     r#"
       SELECT blob
       FROM af_published_collab
       WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
-      AND publish_name = $2
+      AND sub_namespace IS NOT DISTINCT FROM $2
+      AND publish_name = $3
     "#,
-    publish_namespace,
+    namespace,
+    sub_namespace,
     publish_name,
   )
   .fetch_one(executor)
@@ -442,23 +573,46 @@ pub async fn select_published_collab_blob<'a, E: Executor<'a, Database = Postgre
   Ok(res)
 }
 
+/// Returns the default view of the publish namespace, or of the sub-namespace if the namespace
+/// has one, e.g. `acme/handbook`. The default view of a sub-namespace must be published in it.
 pub async fn select_default_published_view_id_for_namespace<
   'a,
   E: Executor<'a, Database = Postgres>,
 >(
   executor: E,
-  namespace: &str,
+  publish_namespace: &str,
 ) -> Result<Option<Uuid>, AppError> {
-  let res = sqlx::query_scalar!(
-    r#"
-      SELECT default_published_view_id
-      FROM af_workspace
-      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
-    "#,
-    namespace,
-  )
-  .fetch_one(executor)
-  .await?;
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  let res = match sub_namespace {
+    None => {
+      sqlx::query_scalar!(
+        r#"
+          SELECT default_published_view_id
+          FROM af_workspace
+          WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        "#,
+        namespace,
+      )
+      .fetch_one(executor)
+      .await?
+    },
+    Some(sub_namespace) => sqlx::query_scalar!(
+      r#"
+        SELECT apc.view_id
+        FROM af_publish_sub_namespace aps
+        JOIN af_published_collab apc
+          ON apc.workspace_id = aps.workspace_id
+          AND apc.view_id = aps.default_published_view_id
+          AND apc.sub_namespace = aps.sub_namespace
+        WHERE aps.workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+          AND aps.sub_namespace = $2
+      "#,
+      namespace,
+      sub_namespace,
+    )
+    .fetch_optional(executor)
+    .await?,
+  };
 
   Ok(res)
 }
@@ -511,14 +665,21 @@ pub async fn select_published_collab_info_for_view_ids(
     r#"
       SELECT
         awn.namespace,
+        apc.sub_namespace,
         apc.publish_name,
         apc.view_id,
         au.email AS publisher_email,
-        apc.created_at AS publish_timestamp
+        apc.created_at AS publish_timestamp,
+        apc.updated_at AS "last_published_at?",
+        (apa.view_id IS NOT NULL) AS "auto_publish!",
+        (apa.changed_at IS NOT NULL) AS "auto_publish_pending!",
+        apa.last_error AS "auto_publish_error?",
+        apc.expires_at
       FROM af_published_collab apc
       JOIN af_user au ON apc.published_by = au.uid
       JOIN af_workspace aw ON apc.workspace_id = aw.workspace_id
       JOIN af_workspace_namespace awn ON aw.workspace_id = awn.workspace_id AND awn.is_original = TRUE
+      LEFT JOIN af_published_collab_auto_publish apa ON apc.view_id = apa.view_id
       WHERE apc.view_id = ANY($1);
     "#,
     view_ids,
@@ -526,16 +687,7 @@ pub async fn select_published_collab_info_for_view_ids(
   .fetch_all(pg_pool)
   .await?;
 
-  if res.is_empty() {
-    return Ok(res);
-  }
-  if let Some(non_original_namespace) =
-    select_first_non_original_namespace(pg_pool, &res[0].namespace).await?
-  {
-    res.iter_mut().for_each(|info| {
-      info.namespace = non_original_namespace.clone();
-    });
-  }
+  use_non_orginal_namespace_if_possible(pg_pool, &mut res).await?;
   Ok(res)
 }
 
@@ -559,14 +711,21 @@ pub async fn select_all_published_collab_info(
     r#"
       SELECT
         awn.namespace,
+        apc.sub_namespace,
         apc.publish_name,
         apc.view_id,
         au.email AS publisher_email,
-        apc.created_at AS publish_timestamp
+        apc.created_at AS publish_timestamp,
+        apc.updated_at AS "last_published_at?",
+        (apa.view_id IS NOT NULL) AS "auto_publish!",
+        (apa.changed_at IS NOT NULL) AS "auto_publish_pending!",
+        apa.last_error AS "auto_publish_error?",
+        apc.expires_at
       FROM af_published_collab apc
       JOIN af_user au ON apc.published_by = au.uid
       JOIN af_workspace aw ON apc.workspace_id = aw.workspace_id
       JOIN af_workspace_namespace awn ON aw.workspace_id = awn.workspace_id AND awn.is_original = TRUE
+      LEFT JOIN af_published_collab_auto_publish apa ON apc.view_id = apa.view_id
       WHERE apc.workspace_id = $1;
     "#,
     workspace_id,
@@ -593,6 +752,11 @@ async fn use_non_orginal_namespace_if_possible(
       info.namespace = non_original_namespace.clone();
     });
   }
+  publish_infos.iter_mut().for_each(|info| {
+    if let Some(sub_namespace) = &info.sub_namespace {
+      info.namespace = format!("{}/{}", info.namespace, sub_namespace);
+    }
+  });
   Ok(())
 }
 
@@ -631,3 +795,470 @@ pub async fn select_published_view_ids_for_workspace<'a, E: Executor<'a, Databas
 
   Ok(res)
 }
+
+/// Returns the ids of the views published in the sub-namespace, or in the namespace of the
+/// workspace itself when `sub_namespace` is `None`.
+pub async fn select_published_view_ids_for_sub_namespace<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: Uuid,
+  sub_namespace: Option<&str>,
+) -> Result<Vec<Uuid>, AppError> {
+  let res = sqlx::query_scalar!(
+    r#"
+      SELECT view_id
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND sub_namespace IS NOT DISTINCT FROM $2
+    "#,
+    workspace_id,
+    sub_namespace,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(res)
+}
+
+/// Returns the views published in the sub-namespace along with the last time they were published.
+pub async fn select_published_view_update_times_for_sub_namespace<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: Uuid,
+  sub_namespace: Option<&str>,
+) -> Result<Vec<(Uuid, DateTime<Utc>)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT view_id, updated_at
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND sub_namespace IS NOT DISTINCT FROM $2
+    "#,
+    workspace_id,
+    sub_namespace,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.view_id, row.updated_at))
+      .collect(),
+  )
+}
+
+/// Returns the publish name and the last publish time of the views published in the sub-namespace
+/// that haven't expired.
+pub async fn select_published_sitemap_entries<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: Uuid,
+  sub_namespace: Option<&str>,
+) -> Result<Vec<AFPublishedSitemapEntryRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishedSitemapEntryRow,
+    r#"
+      SELECT view_id, publish_name, updated_at
+      FROM af_published_collab
+      WHERE workspace_id = $1
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    workspace_id,
+    sub_namespace,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns false if the sub-namespace already exists.
+pub async fn insert_publish_sub_namespace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+  title: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_publish_sub_namespace (workspace_id, sub_namespace, title)
+      VALUES ($1, $2, $3)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace_id,
+    sub_namespace,
+    title,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns false if the sub-namespace doesn't exist.
+pub async fn update_publish_sub_namespace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+  title: &str,
+  default_view_id: Option<&Uuid>,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_publish_sub_namespace
+      SET title = $3,
+          default_published_view_id = $4
+      WHERE workspace_id = $1
+        AND sub_namespace = $2
+    "#,
+    workspace_id,
+    sub_namespace,
+    title,
+    default_view_id,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected() == 1)
+}
+
+/// The views published in the sub-namespace are moved back to the namespace of the workspace.
+/// Returns false if the sub-namespace doesn't exist.
+pub async fn delete_publish_sub_namespace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_publish_sub_namespace
+      WHERE workspace_id = $1
+        AND sub_namespace = $2
+    "#,
+    workspace_id,
+    sub_namespace,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected() == 1)
+}
+
+pub async fn select_publish_sub_namespace_count<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!"
+      FROM af_publish_sub_namespace
+      WHERE workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .fetch_one(executor)
+  .await?;
+
+  Ok(count)
+}
+
+/// The default view is only returned while it's published in the sub-namespace.
+pub async fn select_publish_sub_namespaces<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFPublishSubNamespaceRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishSubNamespaceRow,
+    r#"
+      SELECT
+        aps.sub_namespace,
+        aps.title,
+        (
+          SELECT apc.view_id
+          FROM af_published_collab apc
+          WHERE apc.workspace_id = aps.workspace_id
+            AND apc.view_id = aps.default_published_view_id
+            AND apc.sub_namespace = aps.sub_namespace
+        ) AS "default_view_id?",
+        ARRAY(
+          SELECT apc.view_id
+          FROM af_published_collab apc
+          WHERE apc.workspace_id = aps.workspace_id
+            AND apc.sub_namespace = aps.sub_namespace
+          ORDER BY apc.created_at
+        ) AS "view_ids!",
+        aps.created_at
+      FROM af_publish_sub_namespace aps
+      WHERE aps.workspace_id = $1
+      ORDER BY aps.created_at
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+
+  Ok(rows)
+}
+
+/// Moves the published views to the sub-namespace. Returns the number of views moved, the views
+/// that aren't published in the workspace are ignored.
+pub async fn update_published_collabs_sub_namespace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  sub_namespace: &str,
+) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_published_collab
+      SET sub_namespace = $3
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+    "#,
+    workspace_id,
+    view_ids,
+    sub_namespace,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected())
+}
+
+/// Moves the published views of the sub-namespace back to the namespace of the workspace.
+pub async fn update_published_collabs_sub_namespace_set_null<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  sub_namespace: &str,
+) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_published_collab
+      SET sub_namespace = NULL
+      WHERE workspace_id = $1
+        AND view_id = ANY($2)
+        AND sub_namespace = $3
+    "#,
+    workspace_id,
+    view_ids,
+    sub_namespace,
+  )
+  .execute(executor)
+  .await?;
+
+  Ok(res.rows_affected())
+}
+
+/// Returns the expiry of the published view, `None` if the view never expires or isn't published.
+pub async fn select_published_collab_expires_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  let res = sqlx::query_scalar!(
+    r#"
+      SELECT expires_at
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND publish_name = $3
+    "#,
+    namespace,
+    sub_namespace,
+    publish_name,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(res.flatten())
+}
+
+/// Returns the published views whose expiry has passed, along with the users who published them.
+pub async fn select_expired_published_collabs<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  limit: i64,
+) -> Result<Vec<AFExpiredPublishedCollabRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFExpiredPublishedCollabRow,
+    r#"
+      SELECT apc.workspace_id, apc.view_id, au.uuid AS publisher_uuid
+      FROM af_published_collab apc
+      JOIN af_user au ON apc.published_by = au.uid
+      WHERE apc.expires_at <= NOW()
+      ORDER BY apc.expires_at
+      LIMIT $1
+    "#,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the published metadata of the view along with the overrides of its SEO metadata.
+pub async fn select_published_view_seo<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<AFPublishedViewSeoRow, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  let row = sqlx::query_as!(
+    AFPublishedViewSeoRow,
+    r#"
+      SELECT workspace_id, view_id, metadata, seo_title, seo_description, seo_image_url
+      FROM af_published_collab
+      WHERE workspace_id = (SELECT workspace_id FROM af_workspace_namespace WHERE namespace = $1)
+        AND sub_namespace IS NOT DISTINCT FROM $2
+        AND publish_name = $3
+    "#,
+    namespace,
+    sub_namespace,
+    publish_name,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_published_view_seo<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  patch: &PatchPublishedViewSeo,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_published_collab
+      SET seo_title = CASE WHEN $3 THEN $4 ELSE seo_title END,
+          seo_description = CASE WHEN $5 THEN $6 ELSE seo_description END,
+          seo_image_url = CASE WHEN $7 THEN $8 ELSE seo_image_url END
+      WHERE workspace_id = $1
+        AND view_id = $2
+    "#,
+    workspace_id,
+    view_id,
+    patch.title.is_some(),
+    patch.title.clone().flatten(),
+    patch.description.is_some(),
+    patch.description.clone().flatten(),
+    patch.image_url.is_some(),
+    patch.image_url.clone().flatten(),
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the current version of the published view followed by its prior versions, the most
+/// recent first.
+pub async fn select_published_collab_versions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<AFPublishedCollabVersionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishedCollabVersionRow,
+    r#"
+      SELECT
+        versions.version AS "version!",
+        versions.published_at AS "published_at!",
+        versions.restored_from_version,
+        au.email AS "publisher_email?",
+        versions.size AS "size!",
+        versions.is_current AS "is_current!"
+      FROM (
+        SELECT
+          version,
+          version_published_at AS published_at,
+          restored_from_version,
+          published_by,
+          LENGTH(blob)::BIGINT AS size,
+          TRUE AS is_current
+        FROM af_published_collab
+        WHERE workspace_id = $1 AND view_id = $2
+        UNION ALL
+        SELECT
+          version,
+          published_at,
+          restored_from_version,
+          published_by,
+          LENGTH(blob)::BIGINT AS size,
+          FALSE AS is_current
+        FROM af_published_collab_version
+        WHERE workspace_id = $1 AND view_id = $2
+      ) versions
+      LEFT JOIN af_user au ON au.uid = versions.published_by
+      ORDER BY versions.version DESC
+    "#,
+    workspace_id,
+    view_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Makes a copy of the prior version the current version of the published view, the current
+/// version becoming a prior version. Returns the new version along with its blob, `None` if there
+/// is no such prior version.
+pub async fn rollback_published_collab<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  version: i32,
+  publisher_uuid: &Uuid,
+) -> Result<Option<(i32, Vec<u8>)>, AppError> {
+  let row = sqlx::query!(
+    r#"
+      WITH target AS (
+        SELECT metadata, blob
+        FROM af_published_collab_version
+        WHERE workspace_id = $1 AND view_id = $2 AND version = $3
+      ),
+      archived AS (
+        INSERT INTO af_published_collab_version
+          (workspace_id, view_id, version, metadata, blob, published_by, published_at, restored_from_version)
+        SELECT workspace_id, view_id, version, metadata, blob, published_by, version_published_at, restored_from_version
+        FROM af_published_collab
+        WHERE workspace_id = $1 AND view_id = $2 AND EXISTS (SELECT 1 FROM target)
+        ON CONFLICT DO NOTHING
+      ),
+      pruned AS (
+        DELETE FROM af_published_collab_version apcv
+        USING af_published_collab apc
+        WHERE apcv.workspace_id = apc.workspace_id
+          AND apcv.view_id = apc.view_id
+          AND apc.workspace_id = $1
+          AND apc.view_id = $2
+          AND apcv.version <= apc.version - $5
+          AND apcv.version <> $3
+      )
+      UPDATE af_published_collab apc
+      SET metadata = target.metadata,
+          blob = target.blob,
+          published_by = (SELECT uid FROM af_user WHERE uuid = $4),
+          version = apc.version + 1,
+          version_published_at = NOW(),
+          restored_from_version = $3
+      FROM target
+      WHERE apc.workspace_id = $1 AND apc.view_id = $2
+      RETURNING apc.version, apc.blob
+    "#,
+    workspace_id,
+    view_id,
+    version,
+    publisher_uuid,
+    MAX_ARCHIVED_PUBLISH_VERSIONS,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row.map(|row| (row.version, row.blob)))
+}
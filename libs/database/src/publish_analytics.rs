@@ -0,0 +1,160 @@
+use app_error::AppError;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::pg_row::{AFDailyPublishedViewStatsRow, AFPublishedViewStatsRow};
+
+/// Adds the views and the visitors of the published views to their stats of the given date. A
+/// visitor is only counted as a unique visitor of the view the first time it's seen that day.
+///
+/// The published views are identified by the namespace of their workspace, their sub-namespace,
+/// empty if none, and their publish name. The views that are no longer published are ignored.
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_published_view_daily_stats(
+  pg_pool: &PgPool,
+  stat_date: NaiveDate,
+  namespaces: &[String],
+  sub_namespaces: &[String],
+  publish_names: &[String],
+  view_counts: &[i64],
+  visitor_namespaces: &[String],
+  visitor_sub_namespaces: &[String],
+  visitor_publish_names: &[String],
+  visitor_hashes: &[String],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      WITH new_visitor AS (
+        INSERT INTO af_published_view_daily_visitor (workspace_id, view_id, stat_date, visitor_hash)
+        SELECT apc.workspace_id, apc.view_id, $1, visitor.visitor_hash
+        FROM UNNEST($6::TEXT[], $7::TEXT[], $8::TEXT[], $9::TEXT[])
+          AS visitor(namespace, sub_namespace, publish_name, visitor_hash)
+        JOIN af_workspace_namespace awn ON awn.namespace = visitor.namespace
+        JOIN af_published_collab apc ON apc.workspace_id = awn.workspace_id
+          AND apc.sub_namespace IS NOT DISTINCT FROM NULLIF(visitor.sub_namespace, '')
+          AND apc.publish_name = visitor.publish_name
+        ON CONFLICT DO NOTHING
+        RETURNING workspace_id, view_id
+      ),
+      new_visitor_count AS (
+        SELECT workspace_id, view_id, COUNT(*) AS visitor_count
+        FROM new_visitor
+        GROUP BY workspace_id, view_id
+      )
+      INSERT INTO af_published_view_daily_stats (
+        workspace_id, view_id, stat_date, view_count, unique_visitor_count
+      )
+      SELECT apc.workspace_id, apc.view_id, $1, view.view_count,
+        COALESCE(new_visitor_count.visitor_count, 0)
+      FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[], $5::BIGINT[])
+        AS view(namespace, sub_namespace, publish_name, view_count)
+      JOIN af_workspace_namespace awn ON awn.namespace = view.namespace
+      JOIN af_published_collab apc ON apc.workspace_id = awn.workspace_id
+        AND apc.sub_namespace IS NOT DISTINCT FROM NULLIF(view.sub_namespace, '')
+        AND apc.publish_name = view.publish_name
+      LEFT JOIN new_visitor_count ON new_visitor_count.workspace_id = apc.workspace_id
+        AND new_visitor_count.view_id = apc.view_id
+      ON CONFLICT (workspace_id, stat_date, view_id) DO UPDATE SET
+        view_count = af_published_view_daily_stats.view_count + EXCLUDED.view_count,
+        unique_visitor_count = af_published_view_daily_stats.unique_visitor_count
+          + EXCLUDED.unique_visitor_count
+    "#,
+    stat_date,
+    namespaces,
+    sub_namespaces,
+    publish_names,
+    view_counts,
+    visitor_namespaces,
+    visitor_sub_namespaces,
+    visitor_publish_names,
+    visitor_hashes,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Returns the views and the unique visitors of the published views of the workspace on each day
+/// since the given date.
+pub async fn select_daily_published_view_stats(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFDailyPublishedViewStatsRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFDailyPublishedViewStatsRow,
+    r#"
+      SELECT
+        stat_date,
+        SUM(view_count)::BIGINT AS "view_count!",
+        SUM(unique_visitor_count)::BIGINT AS "unique_visitor_count!"
+      FROM af_published_view_daily_stats
+      WHERE workspace_id = $1 AND stat_date >= $2
+      GROUP BY stat_date
+      ORDER BY stat_date
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the views and the unique visitors of each published view of the workspace since the
+/// given date, the most viewed first. The views that are no longer published are included.
+pub async fn select_published_view_stats(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  since: NaiveDate,
+) -> Result<Vec<AFPublishedViewStatsRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFPublishedViewStatsRow,
+    r#"
+      SELECT
+        stats.view_id,
+        apc.publish_name AS "publish_name?",
+        SUM(stats.view_count)::BIGINT AS "view_count!",
+        SUM(stats.unique_visitor_count)::BIGINT AS "unique_visitor_count!"
+      FROM af_published_view_daily_stats stats
+      LEFT JOIN af_published_collab apc
+        ON apc.workspace_id = stats.workspace_id AND apc.view_id = stats.view_id
+      WHERE stats.workspace_id = $1 AND stats.stat_date >= $2
+      GROUP BY stats.view_id, apc.publish_name
+      ORDER BY 3 DESC, stats.view_id
+    "#,
+    workspace_id,
+    since
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Removes the stats and the visitors of the published views recorded before the given date.
+/// Returns the number of removed rows.
+pub async fn delete_published_view_daily_stats_before(
+  pg_pool: &PgPool,
+  before: NaiveDate,
+) -> Result<u64, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let stats = sqlx::query!(
+    r#"
+      DELETE FROM af_published_view_daily_stats WHERE stat_date < $1
+    "#,
+    before
+  )
+  .execute(txn.as_mut())
+  .await?;
+  let visitors = sqlx::query!(
+    r#"
+      DELETE FROM af_published_view_daily_visitor WHERE stat_date < $1
+    "#,
+    before
+  )
+  .execute(txn.as_mut())
+  .await?;
+  txn.commit().await?;
+  Ok(stats.rows_affected() + visitors.rows_affected())
+}
@@ -0,0 +1,128 @@
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::collab::collab_checksum;
+use crate::pg_row::{AFCollabRedactionRow, AFSnapshotRow};
+
+pub async fn select_collab_snapshots<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<Vec<AFSnapshotRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFSnapshotRow,
+    r#"
+      SELECT * FROM af_collab_snapshot
+      WHERE oid = $1
+    "#,
+    oid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn update_collab_snapshot_blob<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  snapshot_id: i64,
+  blob: &[u8],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_collab_snapshot
+      SET blob = $2, len = $3, checksum = $4
+      WHERE sid = $1
+    "#,
+    snapshot_id,
+    blob,
+    blob.len() as i32,
+    collab_checksum(blob),
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Deletes the update history of the collab, kept by the history service. The history is made of
+/// the document states and the snapshots pointing into them, a state can't be rewritten without
+/// invalidating its snapshots. Returns the number of deleted states.
+pub async fn delete_collab_snapshot_history(pg_pool: &PgPool, oid: &str) -> Result<u64, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  sqlx::query!(
+    r#"
+      DELETE FROM af_snapshot_meta WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_snapshot_state WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  txn.commit().await?;
+  Ok(res.rows_affected())
+}
+
+/// Deletes the search index and the embeddings of the collab. Returns the number of deleted
+/// fragments.
+pub async fn delete_collab_embeddings<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_collab_embeddings WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_collab_redaction<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+  redacted_by: i64,
+  reason: &str,
+  span_count: i32,
+  block_count: i32,
+  snapshot_count: i32,
+  history_count: i32,
+  embedding_count: i32,
+) -> Result<AFCollabRedactionRow, AppError> {
+  let row = sqlx::query_as!(
+    AFCollabRedactionRow,
+    r#"
+      INSERT INTO af_collab_redaction (
+        workspace_id, oid, redacted_by, reason, span_count, block_count,
+        snapshot_count, history_count, embedding_count
+      )
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+      RETURNING
+        redaction_id, workspace_id, oid, redacted_by, reason, span_count, block_count,
+        snapshot_count, history_count, embedding_count, created_at
+    "#,
+    workspace_id,
+    oid,
+    redacted_by,
+    reason,
+    span_count,
+    block_count,
+    snapshot_count,
+    history_count,
+    embedding_count,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
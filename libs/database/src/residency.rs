@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use app_error::AppError;
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::collab::is_collab_exists;
+use crate::collab::shard::{
+  insert_collab_placement, is_collab_placement_fenced, select_collab_placement, CollabShardRing,
+  DEFAULT_COLLAB_SHARD,
+};
+
+/// Routes the storage of a workspace to the Postgres database of the region chosen when the
+/// workspace was created.
+///
+/// Workspaces without a residency, and all workspaces when no regional database is configured,
+/// are stored in the default database. The workspace, user and member tables always live in the
+/// default database.
+///
+/// The collabs of the workspaces without a residency can be spread over several collab shards,
+/// the default database being one of them. See [PgPoolRouter::with_collab_shards].
+#[derive(Clone)]
+pub struct PgPoolRouter {
+  default_pool: PgPool,
+  regional_pools: Arc<HashMap<String, PgPool>>,
+  /// The residency of a workspace never changes after creation, so it is safe to cache it.
+  residency_cache: Arc<RwLock<HashMap<Uuid, Option<String>>>>,
+  /// The collab shards other than the default database, empty if the collabs aren't sharded.
+  collab_shards: Arc<HashMap<String, PgPool>>,
+  collab_shard_ring: Arc<CollabShardRing>,
+}
+
+impl PgPoolRouter {
+  pub fn new(default_pool: PgPool) -> Self {
+    Self::with_regional_pools(default_pool, HashMap::new())
+  }
+
+  pub fn with_regional_pools(
+    default_pool: PgPool,
+    regional_pools: HashMap<String, PgPool>,
+  ) -> Self {
+    Self {
+      default_pool,
+      regional_pools: Arc::new(regional_pools),
+      residency_cache: Default::default(),
+      collab_shards: Default::default(),
+      collab_shard_ring: Default::default(),
+    }
+  }
+
+  /// Spreads the collabs of the workspaces without a residency over the default database and the
+  /// given shards. The placement of each collab is recorded in the default database.
+  pub fn with_collab_shards(mut self, collab_shards: HashMap<String, PgPool>) -> Self {
+    let shard_names = std::iter::once(DEFAULT_COLLAB_SHARD)
+      .chain(collab_shards.keys().map(String::as_str))
+      .collect::<Vec<_>>();
+    self.collab_shard_ring = Arc::new(CollabShardRing::new(shard_names));
+    self.collab_shards = Arc::new(collab_shards);
+    self
+  }
+
+  pub fn default_pool(&self) -> &PgPool {
+    &self.default_pool
+  }
+
+  pub fn is_supported_residency(&self, residency: &str) -> bool {
+    self.regional_pools.contains_key(residency)
+  }
+
+  pub fn supported_residencies(&self) -> Vec<String> {
+    self.regional_pools.keys().cloned().collect()
+  }
+
+  pub fn pg_pool_for_residency(&self, residency: &str) -> Option<&PgPool> {
+    self.regional_pools.get(residency)
+  }
+
+  /// Returns the default database followed by the regional databases and the collab shards. Used
+  /// to look up the data that can't be attributed to a workspace.
+  pub fn all_pools(&self) -> impl Iterator<Item = &PgPool> {
+    std::iter::once(&self.default_pool)
+      .chain(self.regional_pools.values())
+      .chain(self.collab_shards.values())
+  }
+
+  /// Returns the names of the collab shards, the default shard first.
+  pub fn collab_shard_names(&self) -> Vec<String> {
+    let mut names = self.collab_shards.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    names.insert(0, DEFAULT_COLLAB_SHARD.to_string());
+    names
+  }
+
+  pub fn collab_shard_pool(&self, shard: &str) -> Option<&PgPool> {
+    if shard == DEFAULT_COLLAB_SHARD {
+      Some(&self.default_pool)
+    } else {
+      self.collab_shards.get(shard)
+    }
+  }
+
+  pub fn has_collab_shards(&self) -> bool {
+    !self.collab_shards.is_empty()
+  }
+
+  /// Returns the shard the collab belongs to according to the consistent hashing. The collab might
+  /// be stored in another shard until it's rebalanced.
+  pub fn collab_shard_for(&self, object_id: &str) -> &str {
+    self.collab_shard_ring.shard_for(object_id)
+  }
+
+  /// Records the residency of a workspace that is being created, so that the writes made before
+  /// the workspace is committed are routed to the right database.
+  pub async fn cache_residency(&self, workspace_id: Uuid, residency: Option<String>) {
+    self
+      .residency_cache
+      .write()
+      .await
+      .insert(workspace_id, residency);
+  }
+
+  /// Forgets the residency of a workspace whose creation failed.
+  pub async fn remove_cached_residency(&self, workspace_id: &Uuid) {
+    self.residency_cache.write().await.remove(workspace_id);
+  }
+
+  pub async fn residency(&self, workspace_id: &Uuid) -> Result<Option<String>, AppError> {
+    if self.regional_pools.is_empty() {
+      return Ok(None);
+    }
+
+    if let Some(residency) = self.residency_cache.read().await.get(workspace_id) {
+      return Ok(residency.clone());
+    }
+
+    let residency = select_workspace_residency(&self.default_pool, workspace_id).await?;
+    self
+      .residency_cache
+      .write()
+      .await
+      .insert(*workspace_id, residency.clone());
+    Ok(residency)
+  }
+
+  /// Returns the regional database of the workspace, or `None` if the workspace is stored in the
+  /// default database.
+  pub async fn regional_pool(&self, workspace_id: &Uuid) -> Result<Option<PgPool>, AppError> {
+    match self.residency(workspace_id).await? {
+      None => Ok(None),
+      Some(residency) => match self.regional_pools.get(&residency) {
+        Some(pool) => Ok(Some(pool.clone())),
+        None => Err(AppError::Internal(anyhow::anyhow!(
+          "Database for residency:{} of workspace:{} is not configured",
+          residency,
+          workspace_id
+        ))),
+      },
+    }
+  }
+
+  pub async fn pg_pool_for_workspace(&self, workspace_id: &Uuid) -> Result<PgPool, AppError> {
+    Ok(
+      self
+        .regional_pool(workspace_id)
+        .await?
+        .unwrap_or_else(|| self.default_pool.clone()),
+    )
+  }
+
+  /// Returns the databases the collabs of the workspace are stored in: the regional database of
+  /// the workspace, or the default database followed by the collab shards.
+  pub async fn pg_pools_for_workspace_collabs(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Vec<PgPool>, AppError> {
+    match self.regional_pool(workspace_id).await? {
+      Some(regional_pool) => Ok(vec![regional_pool]),
+      None => Ok(
+        std::iter::once(&self.default_pool)
+          .chain(self.collab_shards.values())
+          .cloned()
+          .collect(),
+      ),
+    }
+  }
+
+  /// Returns the database the collab is read from, and its snapshots are written to.
+  pub async fn pg_pool_for_collab(
+    &self,
+    workspace_id: &Uuid,
+    object_id: &str,
+  ) -> Result<PgPool, AppError> {
+    if let Some(regional_pool) = self.regional_pool(workspace_id).await? {
+      return Ok(regional_pool);
+    }
+    if !self.has_collab_shards() {
+      return Ok(self.default_pool.clone());
+    }
+    match select_collab_placement(&self.default_pool, object_id).await? {
+      None => Ok(self.default_pool.clone()),
+      Some(placement) => {
+        if is_collab_placement_fenced(&placement) {
+          return Err(AppError::CollabMigrating(format!(
+            "The collab {} is being moved to another shard",
+            object_id
+          )));
+        }
+        self.collab_shard_pool_by_name(&placement.shard)
+      },
+    }
+  }
+
+  /// Returns the name and the database of the shard the collab of a workspace without a residency
+  /// is written to, or `None` if it's written to the default database with the given transaction.
+  ///
+  /// The collabs are placed on their first write: the collabs already stored in the default
+  /// database stay there until they are rebalanced, the new ones are placed by consistent hashing.
+  /// The placement is recorded with the given transaction.
+  pub async fn collab_shard_for_write(
+    &self,
+    txn: &mut Transaction<'_, Postgres>,
+    workspace_id: &Uuid,
+    object_id: &str,
+  ) -> Result<Option<(String, PgPool)>, AppError> {
+    if !self.has_collab_shards() {
+      return Ok(None);
+    }
+    let shard = match select_collab_placement(txn.as_mut(), object_id).await? {
+      Some(placement) => {
+        if is_collab_placement_fenced(&placement) {
+          return Err(AppError::CollabMigrating(format!(
+            "The collab {} is being moved to another shard",
+            object_id
+          )));
+        }
+        placement.shard
+      },
+      None => {
+        let shard = if is_collab_exists(object_id, txn.as_mut()).await? {
+          DEFAULT_COLLAB_SHARD
+        } else {
+          self.collab_shard_for(object_id)
+        };
+        let shard = insert_collab_placement(txn.as_mut(), object_id, workspace_id, shard).await?;
+        if shard != DEFAULT_COLLAB_SHARD {
+          let shard_pool = self.collab_shard_pool_by_name(&shard)?;
+          provision_workspace_in_region(txn, &shard_pool, workspace_id).await?;
+        }
+        shard
+      },
+    };
+    if shard == DEFAULT_COLLAB_SHARD {
+      return Ok(None);
+    }
+    let shard_pool = self.collab_shard_pool_by_name(&shard)?;
+    Ok(Some((shard, shard_pool)))
+  }
+
+  fn collab_shard_pool_by_name(&self, shard: &str) -> Result<PgPool, AppError> {
+    self.collab_shard_pool(shard).cloned().ok_or_else(|| {
+      AppError::Internal(anyhow::anyhow!(
+        "Database for collab shard:{} is not configured",
+        shard
+      ))
+    })
+  }
+}
+
+pub async fn select_workspace_residency<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<String>, AppError> {
+  let residency = sqlx::query_scalar!(
+    r#"
+      SELECT residency FROM af_workspace WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_optional(executor)
+  .await?
+  .flatten();
+  Ok(residency)
+}
+
+pub async fn update_workspace_residency<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  residency: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace SET residency = $2 WHERE workspace_id = $1
+    "#,
+    workspace_id,
+    residency
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Copies the workspace and its owner into the regional database, so that the data of the
+/// workspace stored in the regional database can reference them. The workspace is read with the
+/// given transaction, which might be the one creating the workspace.
+pub async fn provision_workspace_in_region(
+  txn: &mut Transaction<'_, Postgres>,
+  regional_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  let workspace = sqlx::query!(
+    r#"
+      SELECT
+        af_workspace.workspace_id,
+        af_workspace.database_storage_id,
+        af_workspace.workspace_name,
+        af_workspace.residency,
+        af_user.uid,
+        af_user.uuid,
+        af_user.email,
+        af_user.name
+      FROM af_workspace
+      JOIN af_user ON af_workspace.owner_uid = af_user.uid
+      WHERE af_workspace.workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_one(txn.as_mut())
+  .await?;
+
+  let mut regional_txn = regional_pool.begin().await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_user (uid, uuid, email, name)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace.uid,
+    workspace.uuid,
+    workspace.email,
+    workspace.name,
+  )
+  .execute(regional_txn.as_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace (workspace_id, database_storage_id, owner_uid, workspace_name, residency)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace.workspace_id,
+    workspace.database_storage_id,
+    workspace.uid,
+    workspace.workspace_name,
+    workspace.residency,
+  )
+  .execute(regional_txn.as_mut())
+  .await?;
+  regional_txn.commit().await?;
+  Ok(())
+}
+
+/// Deletes the copy of the workspace from the regional database, along with the data of the
+/// workspace stored there. Used when the creation of the workspace failed.
+pub async fn delete_workspace_in_region(
+  regional_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_workspace WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .execute(regional_pool)
+  .await?;
+  Ok(())
+}
@@ -151,6 +151,28 @@ pub async fn get_blob_metadata(
   Ok(metadata)
 }
 
+/// Returns the metadata of the given blobs of the workspace. The blobs that don't exist are
+/// skipped.
+#[instrument(level = "trace", skip_all, err)]
+pub async fn select_blob_metadata_by_file_ids(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  file_ids: &[String],
+) -> Result<Vec<AFBlobMetadataRow>, AppError> {
+  let metadata = sqlx::query_as!(
+    AFBlobMetadataRow,
+    r#"
+        SELECT * FROM af_blob_metadata
+        WHERE workspace_id = $1 AND file_id = ANY($2)
+        "#,
+    workspace_id,
+    file_ids,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(metadata)
+}
+
 /// Return all blob metadata of a workspace
 #[instrument(level = "trace", skip_all, err)]
 #[inline]
@@ -171,6 +193,30 @@ pub async fn get_all_workspace_blob_metadata(
   Ok(all_metadata)
 }
 
+/// Copies the metadata of all the blobs of a workspace to another workspace. Returns the number of
+/// copied rows.
+#[instrument(level = "trace", skip_all, err)]
+pub async fn copy_workspace_blob_metadata<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  src_workspace_id: &Uuid,
+  dest_workspace_id: &Uuid,
+) -> Result<u64, AppError> {
+  let result = sqlx::query!(
+    r#"
+        INSERT INTO af_blob_metadata (workspace_id, file_id, file_type, file_size)
+        SELECT $2, file_id, file_type, file_size
+        FROM af_blob_metadata
+        WHERE workspace_id = $1
+        ON CONFLICT DO NOTHING
+        "#,
+    src_workspace_id,
+    dest_workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected())
+}
+
 /// Return all blob ids of a workspace
 #[instrument(level = "trace", skip_all, err)]
 #[inline]
@@ -207,3 +253,23 @@ pub async fn get_workspace_usage_size(pool: &PgPool, workspace_id: &Uuid) -> Res
     None => Ok(0),
   }
 }
+
+/// Returns the number of files uploaded to the workspace and their total size in bytes.
+pub async fn select_workspace_blob_usage(
+  pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(i64, i64), AppError> {
+  let row = sqlx::query!(
+    r#"
+      SELECT
+        COUNT(*) AS "blob_count!",
+        COALESCE(SUM(file_size), 0)::BIGINT AS "total_bytes!"
+      FROM af_blob_metadata
+      WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_one(pool)
+  .await?;
+  Ok((row.blob_count, row.total_bytes))
+}
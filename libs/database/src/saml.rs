@@ -0,0 +1,109 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceSamlConfigRow;
+
+pub async fn select_workspace_saml_config<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceSamlConfigRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceSamlConfigRow,
+    r#"
+      SELECT workspace_id, idp_metadata_xml, email_attribute, enabled, created_at, updated_at
+      FROM af_workspace_saml_config
+      WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn upsert_workspace_saml_config<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  idp_metadata_xml: &str,
+  email_attribute: Option<&str>,
+  enabled: bool,
+) -> Result<AFWorkspaceSamlConfigRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceSamlConfigRow,
+    r#"
+      INSERT INTO af_workspace_saml_config (workspace_id, idp_metadata_xml, email_attribute, enabled)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (workspace_id) DO UPDATE
+      SET idp_metadata_xml = EXCLUDED.idp_metadata_xml,
+          email_attribute = EXCLUDED.email_attribute,
+          enabled = EXCLUDED.enabled,
+          updated_at = CURRENT_TIMESTAMP
+      RETURNING workspace_id, idp_metadata_xml, email_attribute, enabled, created_at, updated_at
+    "#,
+    workspace_id,
+    idp_metadata_xml,
+    email_attribute,
+    enabled
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the workspace had no SAML configuration.
+pub async fn delete_workspace_saml_config<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_saml_config
+      WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Records that the user was created by the identity provider of the workspace.
+pub async fn insert_workspace_saml_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_saml_user (workspace_id, uid)
+      VALUES ($1, $2)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace_id,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn is_workspace_saml_user<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1 FROM af_workspace_saml_user
+        WHERE workspace_id = $1 AND uid = $2
+      ) AS "exists!"
+    "#,
+    workspace_id,
+    uid
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists)
+}
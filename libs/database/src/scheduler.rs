@@ -0,0 +1,153 @@
+use app_error::AppError;
+use sqlx::PgPool;
+
+use crate::pg_row::{AFScheduleRow, AFSchedulerLeaderRow};
+
+/// Creates the schedule of the job, or updates its interval. A job whose interval was shortened
+/// runs no later than one new interval from now.
+pub async fn upsert_schedule(
+  pg_pool: &PgPool,
+  name: &str,
+  interval_secs: i64,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_schedule (name, interval_secs)
+      VALUES ($1, $2)
+      ON CONFLICT (name)
+      DO UPDATE SET
+        interval_secs = EXCLUDED.interval_secs,
+        next_run_at = LEAST(
+          af_schedule.next_run_at,
+          NOW() + EXCLUDED.interval_secs * INTERVAL '1 second'
+        )
+    "#,
+    name,
+    interval_secs,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Acquires or renews the leadership of the scheduler for the node. Returns false if another
+/// node holds a lease that hasn't expired yet.
+pub async fn try_acquire_scheduler_leadership(
+  pg_pool: &PgPool,
+  node_id: &str,
+  lease_secs: i64,
+) -> Result<bool, AppError> {
+  let leader = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_scheduler_leader (id, node_id, lease_expires_at)
+      VALUES (TRUE, $1, NOW() + $2 * INTERVAL '1 second')
+      ON CONFLICT (id)
+      DO UPDATE SET
+        node_id = EXCLUDED.node_id,
+        lease_expires_at = EXCLUDED.lease_expires_at
+      WHERE af_scheduler_leader.node_id = EXCLUDED.node_id
+        OR af_scheduler_leader.lease_expires_at < NOW()
+      RETURNING node_id
+    "#,
+    node_id,
+    lease_secs,
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(leader.is_some())
+}
+
+pub async fn select_scheduler_leader(
+  pg_pool: &PgPool,
+) -> Result<Option<AFSchedulerLeaderRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFSchedulerLeaderRow,
+    r#"
+      SELECT node_id, lease_expires_at
+      FROM af_scheduler_leader
+      WHERE lease_expires_at > NOW()
+    "#,
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Marks the due jobs among the given ones as started by the node, and moves their next run one
+/// interval from now, so that a job is never run twice for the same interval. Returns the names
+/// of the jobs to run.
+pub async fn claim_due_schedules(
+  pg_pool: &PgPool,
+  names: &[String],
+  node_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let names = sqlx::query_scalar!(
+    r#"
+      UPDATE af_schedule
+      SET
+        last_started_at = NOW(),
+        last_run_by = $2,
+        next_run_at = NOW() + interval_secs * INTERVAL '1 second'
+      WHERE name = ANY($1) AND next_run_at <= NOW()
+      RETURNING name
+    "#,
+    names,
+    node_id,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(names)
+}
+
+/// Records the outcome of the run of the job. The error of the last failed run is kept after
+/// the job succeeds again, `consecutive_failures` tells whether the job is still failing.
+pub async fn update_schedule_run(
+  pg_pool: &PgPool,
+  name: &str,
+  error: Option<&str>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_schedule
+      SET
+        last_finished_at = NOW(),
+        run_count = run_count + 1,
+        failure_count = failure_count + CASE WHEN $2::TEXT IS NULL THEN 0 ELSE 1 END,
+        consecutive_failures = CASE WHEN $2::TEXT IS NULL THEN 0 ELSE consecutive_failures + 1 END,
+        last_error = COALESCE($2, last_error),
+        last_failed_at = CASE WHEN $2::TEXT IS NULL THEN last_failed_at ELSE NOW() END
+      WHERE name = $1
+    "#,
+    name,
+    error,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(())
+}
+
+/// Returns the schedules of the jobs, the next to run first.
+pub async fn select_schedules(pg_pool: &PgPool) -> Result<Vec<AFScheduleRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFScheduleRow,
+    r#"
+      SELECT
+        name,
+        interval_secs,
+        next_run_at,
+        last_started_at,
+        last_finished_at,
+        last_run_by,
+        run_count,
+        failure_count,
+        consecutive_failures,
+        last_error,
+        last_failed_at
+      FROM af_schedule
+      ORDER BY next_run_at, name
+    "#,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
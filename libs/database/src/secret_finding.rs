@@ -0,0 +1,68 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFCollabSecretFindingRow;
+
+/// Flags the collab with the kinds of the secrets found in its content. Returns true if the collab
+/// was not flagged yet.
+pub async fn upsert_collab_secret_finding<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  oid: &str,
+  secret_kinds: &[String],
+  detected_by: i64,
+) -> Result<bool, AppError> {
+  let inserted = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_collab_secret_finding (oid, workspace_id, secret_kinds, detected_by)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (oid) DO UPDATE
+      SET secret_kinds = EXCLUDED.secret_kinds,
+          detected_by = EXCLUDED.detected_by,
+          detected_at = CURRENT_TIMESTAMP
+      RETURNING (xmax = 0) AS "inserted!"
+    "#,
+    oid,
+    workspace_id,
+    secret_kinds,
+    detected_by,
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(inserted)
+}
+
+pub async fn delete_collab_secret_finding<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  oid: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_collab_secret_finding WHERE oid = $1
+    "#,
+    oid,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_workspace_secret_findings<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFCollabSecretFindingRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFCollabSecretFindingRow,
+    r#"
+      SELECT oid, workspace_id, secret_kinds, detected_by, detected_at
+      FROM af_collab_secret_finding
+      WHERE workspace_id = $1
+      ORDER BY detected_at DESC
+    "#,
+    workspace_id,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
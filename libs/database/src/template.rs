@@ -1,14 +1,15 @@
 use app_error::AppError;
 use database_entity::dto::{
-  AccountLink, Template, TemplateCategory, TemplateCategoryType, TemplateCreator, TemplateGroup,
-  TemplateMinimal,
+  AccountLink, MarketplaceTemplate, MarketplaceTemplateSort, Template, TemplateCategory,
+  TemplateCategoryType, TemplateCreator, TemplateGroup, TemplateLicense, TemplateMinimal,
 };
 use sqlx::{Executor, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use crate::pg_row::{
-  AFTemplateCategoryMinimalRow, AFTemplateCategoryRow, AFTemplateCategoryTypeColumn,
-  AFTemplateCreatorRow, AFTemplateGroupRow, AFTemplateMinimalRow, AFTemplateRow, AccountLinkColumn,
+  AFMarketplaceTemplateRow, AFTemplateCategoryMinimalRow, AFTemplateCategoryRow,
+  AFTemplateCategoryTypeColumn, AFTemplateCreatorRow, AFTemplateGroupRow, AFTemplateLicenseColumn,
+  AFTemplateMinimalRow, AFTemplateRow, AccountLinkColumn,
 };
 
 pub async fn insert_new_template_category<'a, E: Executor<'a, Database = Postgres>>(
@@ -890,3 +891,172 @@ pub async fn delete_template_by_view_id<'a, E: Executor<'a, Database = Postgres>
   .await?;
   Ok(())
 }
+
+const MARKETPLACE_TEMPLATE_SELECT: &str = r#"
+  SELECT
+    marketplace.view_id,
+    marketplace.created_at,
+    marketplace.updated_at,
+    marketplace.name,
+    marketplace.description,
+    marketplace.license,
+    marketplace.screenshot_urls,
+    category.category_id,
+    category.name AS category_name,
+    category.icon AS category_icon,
+    category.bg_color AS category_bg_color,
+    au.uuid AS creator_uuid,
+    au.name AS creator_name,
+    au.metadata ->> 'icon_url' AS creator_avatar_url,
+    marketplace.install_count
+  FROM af_template_marketplace marketplace
+  JOIN af_user au ON au.uid = marketplace.created_by
+  LEFT JOIN af_template_category category ON category.category_id = marketplace.category_id
+"#;
+
+/// Lists the view in the marketplace, or updates its listing. The install count and the creator of
+/// an existing listing are kept.
+#[allow(clippy::too_many_arguments)]
+pub async fn upsert_marketplace_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: Uuid,
+  workspace_id: Uuid,
+  uid: i64,
+  name: &str,
+  description: &str,
+  license: TemplateLicense,
+  screenshot_urls: &[String],
+  category_id: Option<Uuid>,
+) -> Result<(), AppError> {
+  let license_column: AFTemplateLicenseColumn = license.into();
+  sqlx::query!(
+    r#"
+      INSERT INTO af_template_marketplace (
+        view_id, workspace_id, created_by, name, description, license, screenshot_urls, category_id
+      )
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+      ON CONFLICT (view_id) DO UPDATE SET
+        name = EXCLUDED.name,
+        description = EXCLUDED.description,
+        license = EXCLUDED.license,
+        screenshot_urls = EXCLUDED.screenshot_urls,
+        category_id = EXCLUDED.category_id,
+        updated_at = NOW()
+    "#,
+    view_id,
+    workspace_id,
+    uid,
+    name,
+    description,
+    license_column as AFTemplateLicenseColumn,
+    screenshot_urls,
+    category_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn select_marketplace_templates<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  category_id: Option<Uuid>,
+  name_contains: Option<&str>,
+  sort: MarketplaceTemplateSort,
+  offset: i64,
+  limit: i64,
+) -> Result<Vec<MarketplaceTemplate>, AppError> {
+  let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(MARKETPLACE_TEMPLATE_SELECT);
+  query_builder.push(" WHERE TRUE");
+  if let Some(category_id) = category_id {
+    query_builder.push(" AND marketplace.category_id = ");
+    query_builder.push_bind(category_id);
+  };
+  if let Some(name_contains) = name_contains {
+    query_builder.push(" AND marketplace.name ILIKE CONCAT('%', ");
+    query_builder.push_bind(name_contains);
+    query_builder.push(" , '%')");
+  };
+  match sort {
+    MarketplaceTemplateSort::Popular => query_builder.push(
+      " ORDER BY marketplace.install_count DESC, marketplace.created_at DESC, marketplace.view_id",
+    ),
+    MarketplaceTemplateSort::Newest => {
+      query_builder.push(" ORDER BY marketplace.created_at DESC, marketplace.view_id")
+    },
+  };
+  query_builder.push(" OFFSET ");
+  query_builder.push_bind(offset);
+  query_builder.push(" LIMIT ");
+  query_builder.push_bind(limit);
+  let rows: Vec<AFMarketplaceTemplateRow> = query_builder
+    .build_query_as::<AFMarketplaceTemplateRow>()
+    .fetch_all(executor)
+    .await?;
+  Ok(rows.into_iter().map(|row| row.into()).collect())
+}
+
+pub async fn select_marketplace_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: Uuid,
+) -> Result<Option<MarketplaceTemplate>, AppError> {
+  let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(MARKETPLACE_TEMPLATE_SELECT);
+  query_builder.push(" WHERE marketplace.view_id = ");
+  query_builder.push_bind(view_id);
+  let row: Option<AFMarketplaceTemplateRow> = query_builder
+    .build_query_as::<AFMarketplaceTemplateRow>()
+    .fetch_optional(executor)
+    .await?;
+  Ok(row.map(|row| row.into()))
+}
+
+/// Returns the workspace the view of the listing is published in.
+pub async fn select_marketplace_template_workspace_id<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: Uuid,
+) -> Result<Option<Uuid>, AppError> {
+  let workspace_id = sqlx::query_scalar!(
+    r#"
+      SELECT workspace_id FROM af_template_marketplace WHERE view_id = $1
+    "#,
+    view_id,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(workspace_id)
+}
+
+/// Returns false if the view is not in the marketplace.
+pub async fn delete_marketplace_template<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_template_marketplace WHERE view_id = $1
+    "#,
+    view_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+pub async fn increment_marketplace_template_install_count<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  view_id: Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_template_marketplace
+      SET install_count = install_count + 1
+      WHERE view_id = $1
+    "#,
+    view_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
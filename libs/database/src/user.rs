@@ -132,10 +132,11 @@ pub async fn create_user<'a, E: Executor<'a, Database = Postgres>>(
         RETURNING workspace_id, owner_uid
     ),
     ins_collab_member AS (
-        INSERT INTO af_collab_member (uid, oid, permission_id)
+        INSERT INTO af_collab_member (uid, oid, permission_id, workspace_id)
         SELECT ins_workspace.owner_uid,
                ins_workspace.workspace_id::TEXT,
-               (SELECT permission_id FROM af_role_permissions WHERE role_id = owner_role.id)
+               (SELECT permission_id FROM af_role_permissions WHERE role_id = owner_role.id),
+               ins_workspace.workspace_id
         FROM ins_workspace, owner_role
     )
     SELECT workspace_id FROM ins_workspace;
@@ -214,8 +215,8 @@ pub async fn is_user_exist<'a, E: Executor<'a, Database = Postgres>>(
 }
 
 #[inline]
-pub async fn select_email_from_user_uuid(
-  pool: &PgPool,
+pub async fn select_email_from_user_uuid<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
   user_uuid: &Uuid,
 ) -> Result<String, AppError> {
   let email = sqlx::query_scalar!(
@@ -224,7 +225,7 @@ pub async fn select_email_from_user_uuid(
     "#,
     user_uuid
   )
-  .fetch_one(pool)
+  .fetch_one(executor)
   .await?;
   Ok(email)
 }
@@ -276,3 +277,43 @@ pub async fn select_web_user_from_uid(pool: &PgPool, uid: i64) -> Result<AFWebUs
 
   Ok(row)
 }
+
+/// Replaces the email of the user. Fails if the email is already used by another user.
+pub async fn update_user_email<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  user_uuid: &Uuid,
+  email: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_user SET email = $2 WHERE uuid = $1
+    "#,
+    user_uuid,
+    email
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Readdresses the pending invitations sent to the old email of a user to the new one. Returns the
+/// number of readdressed invitations.
+pub async fn update_pending_invitations_email<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  old_email: &str,
+  new_email: &str,
+) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_workspace_invitation
+      SET invitee_email = $2
+      WHERE LOWER(invitee_email) = LOWER($1)
+        AND status = 0
+    "#,
+    old_email,
+    new_email
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected())
+}
@@ -0,0 +1,132 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::{AFAccessTokenGrantRow, AFUserAccessTokenRow};
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_user_access_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  name: &str,
+  token_hash: &str,
+  read_only: bool,
+  workspace_ids: Option<&[Uuid]>,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<AFUserAccessTokenRow, AppError> {
+  let row = sqlx::query_as!(
+    AFUserAccessTokenRow,
+    r#"
+      INSERT INTO af_user_access_token (uid, name, token_hash, read_only, workspace_ids, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING id, name, read_only, workspace_ids, created_at, expires_at, last_used_at
+    "#,
+    uid,
+    name,
+    token_hash,
+    read_only,
+    workspace_ids,
+    expires_at
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns the access tokens of the user that are neither revoked nor expired, the newest first.
+pub async fn select_user_access_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFUserAccessTokenRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserAccessTokenRow,
+    r#"
+      SELECT id, name, read_only, workspace_ids, created_at, expires_at, last_used_at
+      FROM af_user_access_token
+      WHERE uid = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+      ORDER BY created_at DESC
+    "#,
+    uid
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn count_user_access_tokens<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<i64, AppError> {
+  let count = sqlx::query_scalar!(
+    r#"
+      SELECT COUNT(*) AS "count!"
+      FROM af_user_access_token
+      WHERE uid = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+    "#,
+    uid
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(count)
+}
+
+/// Returns what the token grants, `None` if there is no such token or if it was revoked or
+/// expired.
+pub async fn select_access_token_grant<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token_hash: &str,
+) -> Result<Option<AFAccessTokenGrantRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFAccessTokenGrantRow,
+    r#"
+      SELECT t.id, u.uuid AS user_uuid, t.read_only, t.workspace_ids, t.expires_at
+      FROM af_user_access_token t
+      JOIN af_user u ON u.uid = t.uid
+      WHERE t.token_hash = $1
+        AND t.revoked_at IS NULL
+        AND (t.expires_at IS NULL OR t.expires_at > NOW())
+    "#,
+    token_hash
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn update_access_token_last_used<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  token_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_user_access_token
+      SET last_used_at = NOW()
+      WHERE id = $1
+    "#,
+    token_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns false if the user has no such token, or if the token is already revoked.
+pub async fn revoke_user_access_token<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  token_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_user_access_token
+      SET revoked_at = NOW()
+      WHERE id = $1 AND uid = $2 AND revoked_at IS NULL
+    "#,
+    token_id,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,117 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFBlockedUserRow;
+
+/// Returns false if the user was already blocked.
+pub async fn insert_user_block<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  blocker_uid: i64,
+  blocked_uid: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_user_block (blocker_uid, blocked_uid)
+      VALUES ($1, $2)
+      ON CONFLICT DO NOTHING
+    "#,
+    blocker_uid,
+    blocked_uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns false if the user was not blocked.
+pub async fn delete_user_block<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  blocker_uid: i64,
+  blocked_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_user_block
+      WHERE blocker_uid = $1
+        AND blocked_uid = (SELECT uid FROM af_user WHERE uuid = $2)
+    "#,
+    blocker_uid,
+    blocked_uuid
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+pub async fn select_blocked_users<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  blocker_uid: i64,
+) -> Result<Vec<AFBlockedUserRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFBlockedUserRow,
+    r#"
+      SELECT
+        au.uuid,
+        au.name,
+        au.metadata ->> 'icon_url' AS avatar_url,
+        aub.created_at AS blocked_at
+      FROM af_user_block aub
+      JOIN af_user au ON au.uid = aub.blocked_uid
+      WHERE aub.blocker_uid = $1
+      ORDER BY aub.created_at DESC
+    "#,
+    blocker_uid
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns true if the user who published the view has blocked the user.
+pub async fn select_is_blocked_by_view_publisher<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let is_blocked = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM af_user_block aub
+        JOIN af_published_collab apc ON apc.published_by = aub.blocker_uid
+        JOIN af_user au ON au.uid = aub.blocked_uid
+        WHERE apc.view_id = $1 AND au.uuid = $2
+      ) AS "is_blocked!"
+    "#,
+    view_id,
+    user_uuid
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(is_blocked)
+}
+
+/// Returns true if the author of the comment has blocked the user.
+pub async fn select_is_blocked_by_comment_author<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<bool, AppError> {
+  let is_blocked = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM af_user_block aub
+        JOIN af_published_view_comment avc ON avc.created_by = aub.blocker_uid
+        JOIN af_user au ON au.uid = aub.blocked_uid
+        WHERE avc.comment_id = $1 AND au.uuid = $2
+      ) AS "is_blocked!"
+    "#,
+    comment_id,
+    user_uuid
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(is_blocked)
+}
@@ -0,0 +1,97 @@
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, Postgres};
+
+use crate::pg_row::AFUserDeviceRow;
+
+/// Records that the user connected from the device. The platform and the client version are only
+/// updated when they are given. The revocation of the device is lifted if `reinstate` is true, i.e.
+/// once the user signed in again on the device.
+pub async fn upsert_user_device<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+  platform: Option<&str>,
+  client_version: Option<&str>,
+  reinstate: bool,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_user_device (uid, device_id, platform, client_version)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (uid, device_id) DO UPDATE SET
+        platform = COALESCE(EXCLUDED.platform, af_user_device.platform),
+        client_version = COALESCE(EXCLUDED.client_version, af_user_device.client_version),
+        last_seen_at = NOW(),
+        revoked_at = CASE WHEN $5 THEN NULL ELSE af_user_device.revoked_at END
+    "#,
+    uid,
+    device_id,
+    platform,
+    client_version,
+    reinstate
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Returns the devices of the user that are not revoked, the last seen first.
+pub async fn select_user_devices<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+) -> Result<Vec<AFUserDeviceRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFUserDeviceRow,
+    r#"
+      SELECT device_id, platform, client_version, first_seen_at, last_seen_at, revoked_at
+      FROM af_user_device
+      WHERE uid = $1 AND revoked_at IS NULL
+      ORDER BY last_seen_at DESC
+    "#,
+    uid
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns when the device was revoked, `None` if the device is unknown or not revoked.
+pub async fn select_user_device_revoked_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+  let revoked_at = sqlx::query_scalar!(
+    r#"
+      SELECT revoked_at
+      FROM af_user_device
+      WHERE uid = $1 AND device_id = $2
+    "#,
+    uid,
+    device_id
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(revoked_at.flatten())
+}
+
+/// Returns false if the user has no such device, or if the device is already revoked.
+pub async fn revoke_user_device<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  uid: i64,
+  device_id: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_user_device
+      SET revoked_at = NOW()
+      WHERE uid = $1 AND device_id = $2 AND revoked_at IS NULL
+    "#,
+    uid,
+    device_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,96 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::pg_row::AFViewPermissionRow;
+
+/// Replaces the permissions of the view. Each permission is granted to either a member or a group,
+/// the view is no longer restricted if no permission is given.
+pub async fn replace_view_permissions(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  view_id: &str,
+  uids: &[Option<i64>],
+  group_ids: &[Option<Uuid>],
+  access_levels: &[i32],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_view_permission WHERE workspace_id = $1 AND view_id = $2
+    "#,
+    workspace_id,
+    view_id
+  )
+  .execute(txn.as_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_view_permission (workspace_id, view_id, uid, group_id, access_level)
+      SELECT $1, $2, uid, group_id, access_level
+      FROM UNNEST($3::BIGINT[], $4::UUID[], $5::INT[]) AS t(uid, group_id, access_level)
+    "#,
+    workspace_id,
+    view_id,
+    uids,
+    group_ids,
+    access_levels
+  )
+  .execute(txn.as_mut())
+  .await?;
+  Ok(())
+}
+
+pub async fn select_view_permissions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<Vec<AFViewPermissionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFViewPermissionRow,
+    r#"
+      SELECT view_id, uid, group_id, access_level
+      FROM af_view_permission
+      WHERE workspace_id = $1 AND view_id = $2
+    "#,
+    workspace_id,
+    view_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the permissions of all the restricted views of the workspace.
+pub async fn select_workspace_view_permissions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFViewPermissionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFViewPermissionRow,
+    r#"
+      SELECT view_id, uid, group_id, access_level
+      FROM af_view_permission
+      WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the permissions of all the restricted views, to load them in the access control.
+pub async fn select_all_view_permissions<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<AFViewPermissionRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFViewPermissionRow,
+    r#"
+      SELECT view_id, uid, group_id, access_level
+      FROM af_view_permission
+    "#
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
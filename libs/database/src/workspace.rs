@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
 use database_entity::dto::{
-  AFRole, AFWorkspaceInvitation, AFWorkspaceInvitationStatus, AFWorkspaceSettings, GlobalComment,
-  Reaction,
+  AFRole, AFWorkspaceInvitation, AFWorkspaceInvitationPreview, AFWorkspaceInvitationStatus,
+  AFWorkspaceSettings, CollabTypeUsage, CommentSortOrder, GlobalComment, Reaction,
+  WorkspaceMemberUsage,
 };
 use futures_util::stream::BoxStream;
 use sqlx::{types::uuid, Executor, PgPool, Postgres, Transaction};
@@ -9,10 +10,11 @@ use std::{collections::HashMap, ops::DerefMut};
 use tracing::{event, instrument};
 use uuid::Uuid;
 
+use crate::collab::collab_type_from_partition_key;
 use crate::pg_row::{
   AFGlobalCommentRow, AFImportTask, AFPermissionRow, AFReactionRow, AFUserProfileRow,
-  AFWebUserColumn, AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow, AFWorkspaceMemberRow,
-  AFWorkspaceRow,
+  AFViewGlobalCommentRow, AFWebUserColumn, AFWorkspaceInvitationMinimal, AFWorkspaceMemberPermRow,
+  AFWorkspaceMemberRow, AFWorkspaceRow,
 };
 use crate::user::select_uid_from_email;
 use app_error::AppError;
@@ -65,7 +67,8 @@ pub async fn insert_user_workspace(
       workspace_type,
       new_workspace.deleted_at,
       workspace_name,
-      icon
+      icon,
+      new_workspace.archived_at
     FROM new_workspace
     JOIN public.af_user AS owner_profile ON new_workspace.owner_uid = owner_profile.uid;
     "#,
@@ -201,6 +204,7 @@ pub async fn select_user_role<'a, E: Executor<'a, Database = Postgres>>(
     r#"
      SELECT role_id FROM af_workspace_member
      WHERE workspace_id = $1 AND uid = $2
+       AND (expires_at IS NULL OR expires_at > NOW())
     "#,
     workspace_uuid,
     uid
@@ -300,6 +304,7 @@ pub async fn insert_workspace_invitation(
   inviter_uuid: &Uuid,
   invitee_email: &str,
   invitee_role: AFRole,
+  expires_at: Option<DateTime<Utc>>,
 ) -> Result<(), AppError> {
   let role_id: i32 = invitee_role.into();
   sqlx::query!(
@@ -309,28 +314,121 @@ pub async fn insert_workspace_invitation(
           workspace_id,
           inviter,
           invitee_email,
-          role_id
+          role_id,
+          expires_at
       )
       VALUES (
         $1,
         $2,
         (SELECT uid FROM public.af_user WHERE uuid = $3),
         $4,
-        $5
+        $5,
+        $6
       )
     "#,
     invite_id,
     workspace_id,
     inviter_uuid,
     invitee_email,
-    role_id
+    role_id,
+    expires_at
+  )
+  .execute(txn.deref_mut())
+  .await?;
+
+  Ok(())
+}
+
+/// Extends the expiry of a pending invitation, used when the invitee is invited again.
+pub async fn update_workspace_invitation_expires_at(
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+  invite_id: &Uuid,
+  expires_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE public.af_workspace_invitation
+      SET expires_at = $2, updated_at = CURRENT_TIMESTAMP
+      WHERE id = $1 AND status = 0
+    "#,
+    invite_id,
+    expires_at
+  )
+  .execute(txn.deref_mut())
+  .await?;
+  Ok(())
+}
+
+/// Records the views to grant to the guest when the invitation is accepted.
+pub async fn insert_workspace_invitation_guest_views(
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+  invite_id: &Uuid,
+  view_ids: &[String],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO public.af_workspace_invitation_guest_view (invite_id, view_id)
+      SELECT $1, UNNEST($2::text[])
+      ON CONFLICT DO NOTHING
+    "#,
+    invite_id,
+    view_ids
   )
   .execute(txn.deref_mut())
   .await?;
+  Ok(())
+}
+
+pub async fn select_workspace_invitation_guest_view_ids(
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+  invite_id: &Uuid,
+) -> Result<Vec<String>, AppError> {
+  let view_ids = sqlx::query_scalar!(
+    r#"
+      SELECT view_id
+      FROM public.af_workspace_invitation_guest_view
+      WHERE invite_id = $1
+    "#,
+    invite_id
+  )
+  .fetch_all(txn.deref_mut())
+  .await?;
+  Ok(view_ids)
+}
 
+/// Marks the invitation as expired if it's still pending.
+pub async fn update_workspace_invitation_set_status_expired(
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+  invite_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE public.af_workspace_invitation
+      SET status = 3, updated_at = CURRENT_TIMESTAMP
+      WHERE id = $1 AND status = 0
+    "#,
+    invite_id,
+  )
+  .execute(txn.deref_mut())
+  .await?;
   Ok(())
 }
 
+/// Marks the pending invitations whose expiry has passed as expired, returns the number of
+/// invitations marked.
+pub async fn update_expired_workspace_invitations(pg_pool: &PgPool) -> Result<u64, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE public.af_workspace_invitation
+      SET status = 3, updated_at = CURRENT_TIMESTAMP
+      WHERE status = 0 AND expires_at < CURRENT_TIMESTAMP
+    "#,
+  )
+  .execute(pg_pool)
+  .await?;
+  Ok(res.rows_affected())
+}
+
 pub async fn update_workspace_invitation_set_status_accepted(
   txn: &mut Transaction<'_, sqlx::Postgres>,
   invitee_uuid: &Uuid,
@@ -377,7 +475,8 @@ pub async fn get_invitation_by_id(
         inviter AS inviter_uid,
         (SELECT uid FROM public.af_user WHERE LOWER(email) = LOWER(invitee_email)) AS invitee_uid,
         status,
-        role_id AS role
+        role_id AS role,
+        expires_at
     FROM
     public.af_workspace_invitation
     WHERE id = $1
@@ -409,7 +508,8 @@ pub async fn select_workspace_invitations_for_user(
         i.updated_at,
         u_inviter.metadata->>'icon_url' AS inviter_icon,
         w.icon AS workspace_icon,
-        (SELECT COUNT(*) FROM public.af_workspace_member m WHERE m.workspace_id = i.workspace_id) AS member_count
+        (SELECT COUNT(*) FROM public.af_workspace_member m WHERE m.workspace_id = i.workspace_id) AS member_count,
+        i.expires_at
       FROM
         public.af_workspace_invitation i
         JOIN public.af_workspace w ON i.workspace_id = w.workspace_id
@@ -446,7 +546,8 @@ pub async fn select_workspace_invitation_for_user(
         i.updated_at,
         u_inviter.metadata->>'icon_url' AS inviter_icon,
         w.icon AS workspace_icon,
-        (SELECT COUNT(*) FROM public.af_workspace_member m WHERE m.workspace_id = i.workspace_id) AS member_count
+        (SELECT COUNT(*) FROM public.af_workspace_member m WHERE m.workspace_id = i.workspace_id) AS member_count,
+        i.expires_at
       FROM
         public.af_workspace_invitation i
         JOIN public.af_workspace w ON i.workspace_id = w.workspace_id
@@ -464,6 +565,34 @@ pub async fn select_workspace_invitation_for_user(
   Ok(res)
 }
 
+pub async fn select_workspace_invitation_preview(
+  pg_pool: &PgPool,
+  invite_id: &Uuid,
+) -> Result<Option<AFWorkspaceInvitationPreview>, AppError> {
+  let res = sqlx::query_as!(
+    AFWorkspaceInvitationPreview,
+    r#"
+      SELECT
+        w.workspace_name,
+        w.icon AS workspace_icon,
+        u_inviter.name AS inviter_name,
+        u_inviter.metadata->>'icon_url' AS inviter_icon,
+        i.status,
+        (SELECT COUNT(*) FROM public.af_workspace_member m WHERE m.workspace_id = i.workspace_id) AS member_count
+      FROM
+        public.af_workspace_invitation i
+        JOIN public.af_workspace w ON i.workspace_id = w.workspace_id
+        JOIN public.af_user u_inviter ON i.inviter = u_inviter.uid
+      WHERE
+        i.id = $1;
+    "#,
+    invite_id,
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(res)
+}
+
 #[inline]
 #[instrument(level = "trace", skip(pool, email, role), err)]
 pub async fn upsert_workspace_member(
@@ -558,7 +687,11 @@ pub fn select_workspace_member_perm_stream(
 ) -> BoxStream<'_, sqlx::Result<AFWorkspaceMemberPermRow>> {
   sqlx::query_as!(
     AFWorkspaceMemberPermRow,
-    "SELECT uid, role_id as role, workspace_id FROM af_workspace_member"
+    r#"
+      SELECT uid, role_id as role, workspace_id, expires_at
+      FROM af_workspace_member
+      WHERE expires_at IS NULL OR expires_at > NOW()
+    "#
   )
   .fetch(pg_pool)
 }
@@ -573,10 +706,12 @@ pub async fn select_workspace_member_list(
     AFWorkspaceMemberRow,
     r#"
     SELECT af_user.uid, af_user.name, af_user.email,
-    af_workspace_member.role_id AS role
+    af_workspace_member.role_id AS role,
+    af_workspace_member.expires_at
     FROM public.af_workspace_member
         JOIN public.af_user ON af_workspace_member.uid = af_user.uid
     WHERE af_workspace_member.workspace_id = $1
+    AND (af_workspace_member.expires_at IS NULL OR af_workspace_member.expires_at > NOW())
     ORDER BY af_workspace_member.created_at ASC;
     "#,
     workspace_id
@@ -586,6 +721,64 @@ pub async fn select_workspace_member_list(
   Ok(members)
 }
 
+/// Returns a page of the workspace members sorted by their creation time, and the number of members
+/// matching the search. The search matches the prefix of the name or the email of the members,
+/// ignoring the case.
+pub async fn select_workspace_member_page(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  search: Option<&str>,
+  limit: i64,
+  offset: i64,
+) -> Result<(Vec<AFWorkspaceMemberRow>, i64), AppError> {
+  let pattern = search.map(|search| format!("{}%", escape_like_pattern(search)));
+  let members = sqlx::query_as!(
+    AFWorkspaceMemberRow,
+    r#"
+    SELECT af_user.uid, af_user.name, af_user.email,
+    af_workspace_member.role_id AS role,
+    af_workspace_member.expires_at
+    FROM public.af_workspace_member
+        JOIN public.af_user ON af_workspace_member.uid = af_user.uid
+    WHERE af_workspace_member.workspace_id = $1
+    AND (af_workspace_member.expires_at IS NULL OR af_workspace_member.expires_at > NOW())
+    AND ($2::TEXT IS NULL OR af_user.name ILIKE $2 OR af_user.email ILIKE $2)
+    ORDER BY af_workspace_member.created_at ASC, af_user.uid ASC
+    LIMIT $3 OFFSET $4
+    "#,
+    workspace_id,
+    pattern,
+    limit,
+    offset,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+
+  let total_count = sqlx::query_scalar!(
+    r#"
+    SELECT COUNT(*) AS "count!"
+    FROM public.af_workspace_member
+        JOIN public.af_user ON af_workspace_member.uid = af_user.uid
+    WHERE af_workspace_member.workspace_id = $1
+    AND (af_workspace_member.expires_at IS NULL OR af_workspace_member.expires_at > NOW())
+    AND ($2::TEXT IS NULL OR af_user.name ILIKE $2 OR af_user.email ILIKE $2)
+    "#,
+    workspace_id,
+    pattern,
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok((members, total_count))
+}
+
+/// Escapes the wildcards of a `LIKE` pattern, so that the text is matched literally.
+fn escape_like_pattern(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace('%', "\\%")
+    .replace('_', "\\_")
+}
+
 #[inline]
 pub async fn select_workspace_member<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -595,11 +788,13 @@ pub async fn select_workspace_member<'a, E: Executor<'a, Database = Postgres>>(
   let member = sqlx::query_as!(
     AFWorkspaceMemberRow,
     r#"
-    SELECT af_user.uid, af_user.name, af_user.email, af_workspace_member.role_id AS role
+    SELECT af_user.uid, af_user.name, af_user.email, af_workspace_member.role_id AS role,
+      af_workspace_member.expires_at
     FROM public.af_workspace_member
       JOIN public.af_user ON af_workspace_member.uid = af_user.uid
     WHERE af_workspace_member.workspace_id = $1
     AND af_workspace_member.uid = $2
+    AND (af_workspace_member.expires_at IS NULL OR af_workspace_member.expires_at > NOW())
     "#,
     workspace_id,
     uid,
@@ -668,7 +863,8 @@ pub async fn select_workspace<'a, E: Executor<'a, Database = Postgres>>(
         workspace_type,
         af_workspace.deleted_at,
         workspace_name,
-        icon
+        icon,
+        af_workspace.archived_at
       FROM public.af_workspace
       JOIN public.af_user owner_profile ON af_workspace.owner_uid = owner_profile.uid
       WHERE af_workspace.workspace_id = $1
@@ -681,6 +877,41 @@ pub async fn select_workspace<'a, E: Executor<'a, Database = Postgres>>(
   Ok(workspace)
 }
 
+/// Archives or unarchives the workspace. Returns false if the workspace does not exist.
+#[inline]
+pub async fn update_workspace_archived<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  archived: bool,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      UPDATE af_workspace
+      SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) ELSE NULL END
+      WHERE workspace_id = $1
+    "#,
+    workspace_id,
+    archived
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() > 0)
+}
+
+#[inline]
+pub async fn select_archived_workspace_ids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+) -> Result<Vec<Uuid>, AppError> {
+  let workspace_ids = sqlx::query_scalar!(
+    r#"
+      SELECT workspace_id FROM af_workspace WHERE archived_at IS NOT NULL
+    "#
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(workspace_ids)
+}
+
 #[inline]
 pub async fn select_workspace_database_storage_id<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
@@ -766,7 +997,8 @@ pub async fn select_all_user_workspaces<'a, E: Executor<'a, Database = Postgres>
         w.workspace_type,
         w.deleted_at,
         w.workspace_name,
-        w.icon
+        w.icon,
+        w.archived_at
       FROM af_workspace w
       JOIN af_workspace_member wm ON w.workspace_id = wm.workspace_id
       JOIN public.af_user u ON w.owner_uid = u.uid
@@ -916,6 +1148,63 @@ pub async fn select_workspace_total_collab_bytes(
   }
 }
 
+pub async fn select_workspace_collab_usage_by_type(
+  pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<CollabTypeUsage>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT
+        partition_key,
+        COUNT(*) AS "collab_count!",
+        COALESCE(SUM(len), 0)::BIGINT AS "total_bytes!"
+      FROM af_collab
+      WHERE workspace_id = $1
+      GROUP BY partition_key
+      ORDER BY partition_key
+    "#,
+    workspace_id
+  )
+  .fetch_all(pool)
+  .await?;
+  let usages = rows
+    .into_iter()
+    .map(|row| CollabTypeUsage {
+      collab_type: collab_type_from_partition_key(row.partition_key),
+      collab_count: row.collab_count,
+      total_bytes: row.total_bytes,
+    })
+    .collect();
+  Ok(usages)
+}
+
+/// Attributes each collab to the member who wrote it last.
+pub async fn select_workspace_member_collab_usage(
+  pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceMemberUsage>, AppError> {
+  let usages = sqlx::query_as!(
+    WorkspaceMemberUsage,
+    r#"
+      SELECT
+        au.uid,
+        au.name,
+        au.email,
+        COUNT(*) AS "collab_count!",
+        COALESCE(SUM(ac.len), 0)::BIGINT AS "total_bytes!"
+      FROM af_collab ac
+      JOIN af_user au ON ac.owner_uid = au.uid
+      WHERE ac.workspace_id = $1
+      GROUP BY au.uid, au.name, au.email
+      ORDER BY SUM(ac.len) DESC NULLS LAST
+    "#,
+    workspace_id
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(usages)
+}
+
 #[inline]
 pub async fn select_workspace_name_from_workspace_id(
   pool: &PgPool,
@@ -1072,14 +1361,20 @@ pub async fn select_owner_of_published_collab<'a, E: Executor<'a, Database = Pos
   Ok(res.uuid)
 }
 
-pub async fn select_comments_for_published_view_ordered_by_recency<
-  'a,
-  E: Executor<'a, Database = Postgres>,
->(
+/// Returns a page of the comments on the view, the pinned comments first. The hidden comments are
+/// only returned to the moderators. When `parent` is given, only the comments replying to it are
+/// returned, `Some(None)` being the top level comments.
+#[allow(clippy::too_many_arguments)]
+pub async fn select_comments_for_published_view<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   view_id: &Uuid,
   user_uuid: &Option<Uuid>,
   page_owner_uuid: &Uuid,
+  is_moderator: bool,
+  parent: Option<Option<Uuid>>,
+  sort: CommentSortOrder,
+  offset: i64,
+  limit: Option<i64>,
 ) -> Result<Vec<GlobalComment>, AppError> {
   let user_uuid = user_uuid.unwrap_or(Uuid::nil());
   let is_page_owner = user_uuid == *page_owner_uuid;
@@ -1093,16 +1388,103 @@ pub async fn select_comments_for_published_view_ordered_by_recency<
         avc.content,
         avc.reply_comment_id,
         avc.is_deleted,
+        avc.edited_at,
+        avc.is_hidden,
+        avc.pinned_at IS NOT NULL AS "is_pinned!",
         (au.uuid, au.name, au.metadata ->> 'icon_url') AS "user: AFWebUserColumn",
         (NOT avc.is_deleted AND ($2 OR au.uuid = $3)) AS "can_be_deleted!"
       FROM af_published_view_comment avc
       LEFT OUTER JOIN af_user au ON avc.created_by = au.uid
       WHERE view_id = $1
-      ORDER BY avc.created_at DESC
+        AND (NOT avc.is_hidden OR $4)
+        AND NOT EXISTS (
+          SELECT 1
+          FROM af_user_block aub
+          JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+          WHERE blocker.uuid = $3 AND aub.blocked_uid = avc.created_by
+        )
+        AND (NOT $5 OR avc.reply_comment_id IS NOT DISTINCT FROM $6)
+      ORDER BY
+        avc.pinned_at DESC NULLS LAST,
+        CASE WHEN $7 THEN (
+          SELECT COUNT(*) FROM af_published_view_reaction avr WHERE avr.comment_id = avc.comment_id
+        ) END DESC,
+        CASE WHEN $8 THEN avc.created_at END ASC,
+        CASE WHEN NOT $8 THEN avc.created_at END DESC,
+        avc.comment_id
+      LIMIT $9 OFFSET $10
+    "#,
+    view_id,
+    is_page_owner,
+    user_uuid,
+    is_moderator,
+    parent.is_some(),
+    parent.flatten(),
+    sort == CommentSortOrder::Top,
+    sort == CommentSortOrder::Oldest,
+    limit,
+    offset,
+  )
+  .fetch_all(executor)
+  .await?;
+  let comments = comment_rows.into_iter().map(|row| row.into()).collect();
+  Ok(comments)
+}
+
+/// Returns all the replies to the comments on the view, directly or not, the oldest first. The
+/// replies are filtered in the same way as the comments.
+pub async fn select_comment_replies_for_published_view<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  user_uuid: &Option<Uuid>,
+  page_owner_uuid: &Uuid,
+  is_moderator: bool,
+  comment_ids: &[Uuid],
+) -> Result<Vec<GlobalComment>, AppError> {
+  let user_uuid = user_uuid.unwrap_or(Uuid::nil());
+  let is_page_owner = user_uuid == *page_owner_uuid;
+  let comment_rows = sqlx::query_as!(
+    AFGlobalCommentRow,
+    r#"
+      WITH RECURSIVE thread AS (
+        SELECT comment_id
+        FROM af_published_view_comment
+        WHERE view_id = $1 AND reply_comment_id = ANY($5)
+        UNION
+        SELECT reply.comment_id
+        FROM af_published_view_comment reply
+        JOIN thread ON reply.reply_comment_id = thread.comment_id
+        WHERE reply.view_id = $1
+      )
+      SELECT
+        avc.comment_id,
+        avc.created_at,
+        avc.updated_at AS last_updated_at,
+        avc.content,
+        avc.reply_comment_id,
+        avc.is_deleted,
+        avc.edited_at,
+        avc.is_hidden,
+        avc.pinned_at IS NOT NULL AS "is_pinned!",
+        (au.uuid, au.name, au.metadata ->> 'icon_url') AS "user: AFWebUserColumn",
+        (NOT avc.is_deleted AND ($2 OR au.uuid = $3)) AS "can_be_deleted!"
+      FROM af_published_view_comment avc
+      JOIN thread ON thread.comment_id = avc.comment_id
+      LEFT OUTER JOIN af_user au ON avc.created_by = au.uid
+      WHERE (NOT avc.is_hidden OR $4)
+        AND NOT EXISTS (
+          SELECT 1
+          FROM af_user_block aub
+          JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+          WHERE blocker.uuid = $3 AND aub.blocked_uid = avc.created_by
+        )
+      ORDER BY avc.created_at ASC, avc.comment_id
     "#,
     view_id,
     is_page_owner,
     user_uuid,
+    is_moderator,
+    comment_ids,
   )
   .fetch_all(executor)
   .await?;
@@ -1110,33 +1492,174 @@ pub async fn select_comments_for_published_view_ordered_by_recency<
   Ok(comments)
 }
 
+/// Returns the number of comments that were neither deleted nor hidden on each of the views
+/// published in the workspace, leaving out the comments of the users blocked by the user. The
+/// views without comments are omitted.
+pub async fn select_comment_counts_for_published_views<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  user_uuid: &Uuid,
+) -> Result<Vec<(Uuid, i64)>, AppError> {
+  let rows = sqlx::query!(
+    r#"
+      SELECT avc.view_id, COUNT(*) AS "count!"
+      FROM af_published_view_comment avc
+      JOIN af_published_collab apc ON avc.view_id = apc.view_id
+      WHERE apc.workspace_id = $1
+        AND avc.view_id = ANY($2)
+        AND NOT avc.is_deleted
+        AND NOT avc.is_hidden
+        AND NOT EXISTS (
+          SELECT 1
+          FROM af_user_block aub
+          JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+          WHERE blocker.uuid = $3 AND aub.blocked_uid = avc.created_by
+        )
+      GROUP BY avc.view_id
+    "#,
+    workspace_id,
+    view_ids,
+    user_uuid,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.view_id, row.count))
+      .collect(),
+  )
+}
+
+/// Returns up to `limit` latest comments that were neither deleted nor hidden on each of the views
+/// published in the workspace, the most recent first. The comments of the users blocked by the
+/// user are left out.
+pub async fn select_latest_comments_for_published_views<
+  'a,
+  E: Executor<'a, Database = Postgres>,
+>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_ids: &[Uuid],
+  user_uuid: &Uuid,
+  limit: i64,
+) -> Result<Vec<(Uuid, GlobalComment)>, AppError> {
+  let comment_rows = sqlx::query_as!(
+    AFViewGlobalCommentRow,
+    r#"
+      SELECT
+        latest.view_id,
+        latest.comment_id,
+        latest.created_at,
+        latest.updated_at AS last_updated_at,
+        latest.content,
+        latest.reply_comment_id,
+        latest.is_deleted,
+        latest.edited_at,
+        latest.is_hidden,
+        latest.pinned_at IS NOT NULL AS "is_pinned!",
+        (au.uuid, au.name, au.metadata ->> 'icon_url') AS "user: AFWebUserColumn",
+        (publisher.uuid = $3 OR au.uuid = $3) AS "can_be_deleted!"
+      FROM (
+        SELECT
+          avc.*,
+          apc.published_by,
+          ROW_NUMBER() OVER (PARTITION BY avc.view_id ORDER BY avc.created_at DESC) AS position
+        FROM af_published_view_comment avc
+        JOIN af_published_collab apc ON avc.view_id = apc.view_id
+        WHERE apc.workspace_id = $1
+          AND avc.view_id = ANY($2)
+          AND NOT avc.is_deleted
+          AND NOT avc.is_hidden
+          AND NOT EXISTS (
+            SELECT 1
+            FROM af_user_block aub
+            JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+            WHERE blocker.uuid = $3 AND aub.blocked_uid = avc.created_by
+          )
+      ) latest
+      JOIN af_user publisher ON publisher.uid = latest.published_by
+      LEFT OUTER JOIN af_user au ON latest.created_by = au.uid
+      WHERE latest.position <= $4
+      ORDER BY latest.created_at DESC
+    "#,
+    workspace_id,
+    view_ids,
+    user_uuid,
+    limit,
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(comment_rows.into_iter().map(|row| row.into()).collect())
+}
+
 pub async fn insert_comment_to_published_view<'a, E: Executor<'a, Database = Postgres>>(
   executor: E,
   view_id: &Uuid,
   user_uuid: &Uuid,
   content: &str,
   reply_comment_id: &Option<Uuid>,
-) -> Result<(), AppError> {
-  let res = sqlx::query!(
+) -> Result<Uuid, AppError> {
+  let comment_id = sqlx::query_scalar!(
     r#"
       INSERT INTO af_published_view_comment (view_id, created_by, content, reply_comment_id)
       VALUES ($1, (SELECT uid FROM af_user WHERE uuid = $2), $3, $4)
+      RETURNING comment_id
     "#,
     view_id,
     user_uuid,
     content,
     reply_comment_id.clone(),
   )
-  .execute(executor)
+  .fetch_one(executor)
   .await?;
 
-  if res.rows_affected() != 1 {
-    tracing::error!(
-      "Failed to insert comment to published view, view_id: {}, user_id: {}, content: {}, rows_affected: {}",
-      view_id, user_uuid, content, res.rows_affected()
-    );
-  }
+  Ok(comment_id)
+}
 
+/// Returns the creation time of the comment on the view, if the comment was made by the user and
+/// wasn't deleted.
+pub async fn select_editable_comment_created_at<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<Option<DateTime<Utc>>, AppError> {
+  let created_at = sqlx::query_scalar!(
+    r#"
+      SELECT created_at
+      FROM af_published_view_comment
+      WHERE view_id = $1
+        AND comment_id = $2
+        AND created_by = (SELECT uid FROM af_user WHERE uuid = $3)
+        AND NOT is_deleted
+    "#,
+    view_id,
+    comment_id,
+    user_uuid,
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(created_at)
+}
+
+pub async fn update_comment_content<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  comment_id: &Uuid,
+  content: &str,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_published_view_comment
+      SET content = $2, edited_at = NOW(), updated_at = NOW()
+      WHERE comment_id = $1
+    "#,
+    comment_id,
+    content,
+  )
+  .execute(executor)
+  .await?;
   Ok(())
 }
 
@@ -1172,7 +1695,9 @@ pub async fn select_reactions_for_published_view_ordered_by_reaction_type_creati
 >(
   executor: E,
   view_id: &Uuid,
+  user_uuid: &Option<Uuid>,
 ) -> Result<Vec<Reaction>, AppError> {
+  let user_uuid = user_uuid.unwrap_or(Uuid::nil());
   let reaction_rows = sqlx::query_as!(
     AFReactionRow,
     r#"
@@ -1183,10 +1708,17 @@ pub async fn select_reactions_for_published_view_ordered_by_reaction_type_creati
       FROM af_published_view_reaction avr
       INNER JOIN af_user au ON avr.created_by = au.uid
       WHERE view_id = $1
+        AND NOT EXISTS (
+          SELECT 1
+          FROM af_user_block aub
+          JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+          WHERE blocker.uuid = $2 AND aub.blocked_uid = avr.created_by
+        )
       GROUP BY comment_id, reaction_type
       ORDER BY MIN(avr.created_at)
     "#,
     view_id,
+    user_uuid,
   )
   .fetch_all(executor)
   .await?;
@@ -1201,7 +1733,9 @@ pub async fn select_reactions_for_comment_ordered_by_reaction_type_creation_time
 >(
   executor: E,
   comment_id: &Uuid,
+  user_uuid: &Option<Uuid>,
 ) -> Result<Vec<Reaction>, AppError> {
+  let user_uuid = user_uuid.unwrap_or(Uuid::nil());
   let reaction_rows = sqlx::query_as!(
     AFReactionRow,
     r#"
@@ -1212,10 +1746,17 @@ pub async fn select_reactions_for_comment_ordered_by_reaction_type_creation_time
       FROM af_published_view_reaction avr
       INNER JOIN af_user au ON avr.created_by = au.uid
       WHERE comment_id = $1
+        AND NOT EXISTS (
+          SELECT 1
+          FROM af_user_block aub
+          JOIN af_user blocker ON blocker.uid = aub.blocker_uid
+          WHERE blocker.uuid = $2 AND aub.blocked_uid = avr.created_by
+        )
       GROUP BY comment_id, reaction_type
       ORDER BY MIN(avr.created_at)
     "#,
     comment_id,
+    user_uuid,
   )
   .fetch_all(executor)
   .await?;
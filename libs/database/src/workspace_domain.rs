@@ -0,0 +1,150 @@
+use app_error::AppError;
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspaceDomainRow;
+
+/// Adds the domain to the workspace, unverified. Returns the existing domain if it was already
+/// added.
+pub async fn insert_workspace_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+  verification_token: &str,
+) -> Result<AFWorkspaceDomainRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceDomainRow,
+    r#"
+      INSERT INTO af_workspace_domain (workspace_id, domain, verification_token)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, domain) DO UPDATE SET domain = af_workspace_domain.domain
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+    workspace_id,
+    domain,
+    verification_token
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+pub async fn select_workspace_domains<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceDomainRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceDomainRow,
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_workspace_domain
+      WHERE workspace_id = $1
+      ORDER BY domain
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+pub async fn select_workspace_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<Option<AFWorkspaceDomainRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceDomainRow,
+    r#"
+      SELECT workspace_id, domain, verification_token, verified_at, created_at
+      FROM af_workspace_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+    workspace_id,
+    domain
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns true if another workspace already verified the domain.
+pub async fn is_domain_verified_by_other_workspace<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1 FROM af_workspace_domain
+        WHERE domain = $2 AND workspace_id != $1 AND verified_at IS NOT NULL
+      ) AS "exists!"
+    "#,
+    workspace_id,
+    domain
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists)
+}
+
+pub async fn update_workspace_domain_verified<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<AFWorkspaceDomainRow, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceDomainRow,
+    r#"
+      UPDATE af_workspace_domain
+      SET verified_at = COALESCE(verified_at, NOW())
+      WHERE workspace_id = $1 AND domain = $2
+      RETURNING workspace_id, domain, verification_token, verified_at, created_at
+    "#,
+    workspace_id,
+    domain
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(row)
+}
+
+/// Returns false if the workspace has no such domain.
+pub async fn delete_workspace_domain<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_domain
+      WHERE workspace_id = $1 AND domain = $2
+    "#,
+    workspace_id,
+    domain
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+pub async fn is_workspace_domain_verified<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS (
+        SELECT 1 FROM af_workspace_domain
+        WHERE workspace_id = $1 AND domain = $2 AND verified_at IS NOT NULL
+      ) AS "exists!"
+    "#,
+    workspace_id,
+    domain
+  )
+  .fetch_one(executor)
+  .await?;
+  Ok(exists)
+}
@@ -0,0 +1,230 @@
+use crate::pg_row::AFWorkspaceLifecycleRow;
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+/// Returns the workspaces that are not exempt and have been inactive for `inactive_secs`, along
+/// with the workspaces whose lifecycle has already started, so that the lifecycle can be reset
+/// once they are active again.
+pub async fn select_workspace_lifecycle_candidates(
+  pg_pool: &PgPool,
+  inactive_secs: i64,
+  limit: i64,
+) -> Result<Vec<AFWorkspaceLifecycleRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceLifecycleRow,
+    r#"
+      WITH workspace_activity AS (
+        SELECT
+          af_workspace.workspace_id,
+          GREATEST(
+            COALESCE(af_workspace.created_at, 'epoch'::TIMESTAMPTZ),
+            COALESCE(lifecycle.reset_at, 'epoch'::TIMESTAMPTZ),
+            COALESCE(
+              (
+                SELECT (MAX(activity_date) + 1)::TIMESTAMPTZ
+                FROM af_collab_edit_activity
+                WHERE af_collab_edit_activity.workspace_id = af_workspace.workspace_id
+              ),
+              'epoch'::TIMESTAMPTZ
+            )
+          ) AS last_active_at
+        FROM af_workspace
+        LEFT JOIN af_workspace_lifecycle lifecycle
+          ON lifecycle.workspace_id = af_workspace.workspace_id
+        WHERE NOT COALESCE(lifecycle.exempt, FALSE)
+      )
+      SELECT
+        af_workspace.workspace_id,
+        af_workspace.workspace_name,
+        af_user.name AS "owner_name!",
+        af_user.email AS "owner_email!",
+        (
+          SELECT COUNT(*)
+          FROM af_workspace_member
+          WHERE af_workspace_member.workspace_id = af_workspace.workspace_id
+        ) AS "member_count!",
+        af_workspace.archived_at IS NOT NULL AS "archived!",
+        COALESCE(lifecycle.exempt, FALSE) AS "exempt!",
+        workspace_activity.last_active_at AS "last_active_at!",
+        lifecycle.warned_at AS "warned_at?",
+        lifecycle.archived_at AS "archived_at?",
+        lifecycle.delete_at AS "delete_at?",
+        lifecycle.deletion_notice_days AS "deletion_notice_days?"
+      FROM workspace_activity
+      JOIN af_workspace ON af_workspace.workspace_id = workspace_activity.workspace_id
+      JOIN af_user ON af_user.uid = af_workspace.owner_uid
+      LEFT JOIN af_workspace_lifecycle lifecycle
+        ON lifecycle.workspace_id = af_workspace.workspace_id
+      WHERE workspace_activity.last_active_at < NOW() - $1 * INTERVAL '1 second'
+        OR lifecycle.warned_at IS NOT NULL
+      ORDER BY workspace_activity.last_active_at
+      LIMIT $2
+    "#,
+    inactive_secs as f64,
+    limit,
+  )
+  .fetch_all(pg_pool)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns the lifecycle of the workspace, whether or not it is inactive.
+pub async fn select_workspace_lifecycle(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Option<AFWorkspaceLifecycleRow>, AppError> {
+  let row = sqlx::query_as!(
+    AFWorkspaceLifecycleRow,
+    r#"
+      SELECT
+        af_workspace.workspace_id,
+        af_workspace.workspace_name,
+        af_user.name AS "owner_name!",
+        af_user.email AS "owner_email!",
+        (
+          SELECT COUNT(*)
+          FROM af_workspace_member
+          WHERE af_workspace_member.workspace_id = af_workspace.workspace_id
+        ) AS "member_count!",
+        af_workspace.archived_at IS NOT NULL AS "archived!",
+        COALESCE(lifecycle.exempt, FALSE) AS "exempt!",
+        GREATEST(
+          COALESCE(af_workspace.created_at, 'epoch'::TIMESTAMPTZ),
+          COALESCE(lifecycle.reset_at, 'epoch'::TIMESTAMPTZ),
+          COALESCE(
+            (
+              SELECT (MAX(activity_date) + 1)::TIMESTAMPTZ
+              FROM af_collab_edit_activity
+              WHERE af_collab_edit_activity.workspace_id = af_workspace.workspace_id
+            ),
+            'epoch'::TIMESTAMPTZ
+          )
+        ) AS "last_active_at!",
+        lifecycle.warned_at AS "warned_at?",
+        lifecycle.archived_at AS "archived_at?",
+        lifecycle.delete_at AS "delete_at?",
+        lifecycle.deletion_notice_days AS "deletion_notice_days?"
+      FROM af_workspace
+      JOIN af_user ON af_user.uid = af_workspace.owner_uid
+      LEFT JOIN af_workspace_lifecycle lifecycle
+        ON lifecycle.workspace_id = af_workspace.workspace_id
+      WHERE af_workspace.workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(row)
+}
+
+/// Starts counting the inactivity of the workspace from now, and clears the warning and the
+/// scheduled deletion.
+pub async fn reset_workspace_lifecycle<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_lifecycle (workspace_id, reset_at)
+      VALUES ($1, NOW())
+      ON CONFLICT (workspace_id)
+      DO UPDATE SET
+        reset_at = NOW(),
+        warned_at = NULL,
+        archived_at = NULL,
+        delete_at = NULL,
+        deletion_notice_days = NULL,
+        updated_at = NOW()
+    "#,
+    workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Exempts the workspace from the lifecycle policies, or subjects it to them again. Either way,
+/// the warning and the scheduled deletion are cleared.
+pub async fn upsert_workspace_lifecycle_exempt<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  exempt: bool,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_lifecycle (workspace_id, exempt)
+      VALUES ($1, $2)
+      ON CONFLICT (workspace_id)
+      DO UPDATE SET
+        exempt = $2,
+        warned_at = NULL,
+        delete_at = NULL,
+        deletion_notice_days = NULL,
+        updated_at = NOW()
+    "#,
+    workspace_id,
+    exempt,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_lifecycle_warned<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_lifecycle (workspace_id, warned_at)
+      VALUES ($1, NOW())
+      ON CONFLICT (workspace_id)
+      DO UPDATE SET warned_at = NOW(), updated_at = NOW()
+    "#,
+    workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_workspace_lifecycle_archived<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_lifecycle
+      SET archived_at = NOW(), updated_at = NOW()
+      WHERE workspace_id = $1
+    "#,
+    workspace_id,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
+
+/// Records the scheduled deletion of the workspace and the last deletion notice sent.
+pub async fn update_workspace_lifecycle_deletion<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  delete_at: &DateTime<Utc>,
+  deletion_notice_days: i32,
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      UPDATE af_workspace_lifecycle
+      SET delete_at = $2, deletion_notice_days = $3, updated_at = NOW()
+      WHERE workspace_id = $1
+    "#,
+    workspace_id,
+    delete_at,
+    deletion_notice_days,
+  )
+  .execute(executor)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,124 @@
+use app_error::AppError;
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+use crate::pg_row::AFWorkspacePublisherRow;
+
+pub async fn select_workspace_publishers<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspacePublisherRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspacePublisherRow,
+    r#"
+      SELECT
+        af_user.uid,
+        af_user.name,
+        af_user.email,
+        awp.created_at AS granted_at
+      FROM af_workspace_publisher awp
+      JOIN af_user ON af_user.uid = awp.uid
+      WHERE awp.workspace_id = $1
+      ORDER BY awp.created_at ASC
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Returns false if the member was already granted the publish permission.
+pub async fn insert_workspace_publisher<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+  granted_by: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_publisher (workspace_id, uid, granted_by)
+      VALUES ($1, $2, $3)
+      ON CONFLICT DO NOTHING
+    "#,
+    workspace_id,
+    uid,
+    granted_by
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns false if the member was not granted the publish permission.
+pub async fn delete_workspace_publisher<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let res = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_publisher
+      WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(res.rows_affected() == 1)
+}
+
+/// Returns true if the user is an owner of the workspace, or a member granted the publish
+/// permission.
+pub async fn select_user_can_manage_publishing(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  let exists = sqlx::query_scalar!(
+    r#"
+      SELECT EXISTS(
+        SELECT 1
+        FROM af_workspace_member
+        JOIN af_roles ON af_workspace_member.role_id = af_roles.id
+        WHERE af_workspace_member.workspace_id = $1
+          AND af_workspace_member.uid = (SELECT uid FROM af_user WHERE uuid = $2)
+          AND (af_workspace_member.expires_at IS NULL OR af_workspace_member.expires_at > NOW())
+          AND (
+            af_roles.name = 'Owner'
+            OR EXISTS(
+              SELECT 1
+              FROM af_workspace_publisher
+              WHERE af_workspace_publisher.workspace_id = af_workspace_member.workspace_id
+                AND af_workspace_publisher.uid = af_workspace_member.uid
+            )
+          )
+      ) AS "exists!"
+    "#,
+    workspace_id,
+    user_uuid
+  )
+  .fetch_one(pg_pool)
+  .await?;
+  Ok(exists)
+}
+
+/// Returns true if only the owners and the members granted the publish permission can publish
+/// the views of the workspace.
+pub async fn select_workspace_restrict_publishing(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  let restrict = sqlx::query_scalar!(
+    r#"
+      SELECT COALESCE((settings->>'restrict_publishing')::BOOLEAN, FALSE) AS "restrict!"
+      FROM af_workspace
+      WHERE workspace_id = $1
+    "#,
+    workspace_id
+  )
+  .fetch_optional(pg_pool)
+  .await?;
+  Ok(restrict.unwrap_or(false))
+}
@@ -0,0 +1,189 @@
+use app_error::AppError;
+use futures_util::stream::BoxStream;
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::pg_row::{AFWorkspaceMemberCapabilityRow, AFWorkspaceRoleRow};
+
+/// Creates the role, returns `None` if the workspace already has a role with the same name.
+pub async fn insert_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  name: &str,
+  capabilities: &[String],
+) -> Result<Option<Uuid>, AppError> {
+  let role_id = sqlx::query_scalar!(
+    r#"
+      INSERT INTO af_workspace_custom_role (workspace_id, name, capabilities)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (workspace_id, name) DO NOTHING
+      RETURNING role_id
+    "#,
+    workspace_id,
+    name,
+    capabilities
+  )
+  .fetch_optional(executor)
+  .await?;
+  Ok(role_id)
+}
+
+/// Updates the name and the capabilities of the role, the ones that are `None` are kept. Returns
+/// false if the role is not found in the workspace.
+pub async fn update_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  name: Option<&str>,
+  capabilities: Option<&[String]>,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      UPDATE af_workspace_custom_role
+      SET name = COALESCE($3, name),
+          capabilities = COALESCE($4, capabilities)
+      WHERE workspace_id = $1 AND role_id = $2
+    "#,
+    workspace_id,
+    role_id,
+    name,
+    capabilities
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the roles of the workspace along with their members, by name.
+pub async fn select_workspace_roles<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+) -> Result<Vec<AFWorkspaceRoleRow>, AppError> {
+  let rows = sqlx::query_as!(
+    AFWorkspaceRoleRow,
+    r#"
+      SELECT
+        r.role_id,
+        r.name,
+        r.capabilities,
+        COALESCE(
+          ARRAY_AGG(m.uid ORDER BY m.uid) FILTER (WHERE m.uid IS NOT NULL),
+          '{}'
+        ) AS "member_uids!",
+        r.created_at
+      FROM af_workspace_custom_role r
+      LEFT JOIN af_workspace_member_custom_role m ON m.role_id = r.role_id
+      WHERE r.workspace_id = $1
+      GROUP BY r.role_id
+      ORDER BY r.name
+    "#,
+    workspace_id
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(rows)
+}
+
+/// Deletes the role, which unassigns it from its members. Returns false if the role is not found
+/// in the workspace.
+pub async fn delete_workspace_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_custom_role WHERE workspace_id = $1 AND role_id = $2
+    "#,
+    workspace_id,
+    role_id
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Replaces the members of the role. The members that had another role in the workspace are moved
+/// to this one.
+pub async fn replace_workspace_role_members(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member_custom_role WHERE role_id = $1
+    "#,
+    role_id
+  )
+  .execute(txn.as_mut())
+  .await?;
+  sqlx::query!(
+    r#"
+      INSERT INTO af_workspace_member_custom_role (uid, workspace_id, role_id)
+      SELECT uid, $1, $2 FROM UNNEST($3::BIGINT[]) AS uid
+      ON CONFLICT (uid, workspace_id) DO UPDATE SET role_id = EXCLUDED.role_id
+    "#,
+    workspace_id,
+    role_id,
+    uids
+  )
+  .execute(txn.as_mut())
+  .await?;
+  Ok(())
+}
+
+/// Removes the custom role of the member, if any.
+pub async fn delete_workspace_member_custom_role<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<bool, AppError> {
+  let result = sqlx::query!(
+    r#"
+      DELETE FROM af_workspace_member_custom_role WHERE workspace_id = $1 AND uid = $2
+    "#,
+    workspace_id,
+    uid
+  )
+  .execute(executor)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// Returns the given users that are members of the workspace and can be assigned a custom role,
+/// i.e. that are not guests.
+pub async fn select_role_assignable_member_uids<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  uids: &[i64],
+) -> Result<Vec<i64>, AppError> {
+  let uids = sqlx::query_scalar!(
+    r#"
+      SELECT uid FROM af_workspace_member
+      WHERE workspace_id = $1 AND uid = ANY($2::BIGINT[]) AND role_id IN (1, 2)
+    "#,
+    workspace_id,
+    uids
+  )
+  .fetch_all(executor)
+  .await?;
+  Ok(uids)
+}
+
+/// Returns the capabilities of every member that has a custom role, to load them in the access
+/// control.
+pub fn select_workspace_member_capability_stream(
+  pg_pool: &PgPool,
+) -> BoxStream<'_, sqlx::Result<AFWorkspaceMemberCapabilityRow>> {
+  sqlx::query_as!(
+    AFWorkspaceMemberCapabilityRow,
+    r#"
+      SELECT m.uid, m.workspace_id, r.capabilities
+      FROM af_workspace_member_custom_role m
+      JOIN af_workspace_custom_role r ON r.role_id = m.role_id
+    "#
+  )
+  .fetch(pg_pool)
+}
@@ -0,0 +1,161 @@
+//! Encrypts the exported archives, so the backups stored off-site aren't plaintext.
+//!
+//! A sealed archive starts with [ENVELOPE_MAGIC], followed by the kind of the key, the salt of the
+//! passphrase or the ephemeral public key, and the data encrypted by [encrypt_data]:
+//!
+//! ```text
+//! | magic (6) | kind (1) | salt (16) or ephemeral public key (32) | nonce (12) | ciphertext |
+//! ```
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::Rng;
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+use crate::aes_encrypt::{decrypt_data, encrypt_data};
+
+pub const ENVELOPE_MAGIC: &[u8; 6] = b"AFENC1";
+
+const PASSPHRASE_KIND: u8 = 1;
+const PUBLIC_KEY_KIND: u8 = 2;
+
+const SALT_LENGTH: usize = 16;
+const X25519_KEY_LENGTH: usize = 32;
+
+/// The key used to seal an archive.
+pub enum SealingKey<'a> {
+  Passphrase(&'a str),
+  /// The base64 encoded X25519 public key of the recipient, see [EnvelopeKeyPair].
+  PublicKey(&'a str),
+}
+
+/// The key used to open a sealed archive.
+pub enum OpeningKey<'a> {
+  Passphrase(&'a str),
+  /// The base64 encoded X25519 secret key matching the public key the archive was sealed with.
+  SecretKey(&'a str),
+}
+
+/// A base64 encoded X25519 key pair. The archives sealed with the public key can only be opened
+/// with the secret key.
+pub struct EnvelopeKeyPair {
+  pub secret_key: String,
+  pub public_key: String,
+}
+
+impl EnvelopeKeyPair {
+  pub fn generate() -> Self {
+    let secret: [u8; X25519_KEY_LENGTH] = rand::thread_rng().gen();
+    let public = x25519(secret, X25519_BASEPOINT_BYTES);
+    Self {
+      secret_key: STANDARD.encode(secret),
+      public_key: STANDARD.encode(public),
+    }
+  }
+}
+
+pub fn is_sealed(data: &[u8]) -> bool {
+  data.starts_with(ENVELOPE_MAGIC)
+}
+
+pub fn seal(data: &[u8], key: SealingKey) -> Result<Vec<u8>> {
+  let mut sealed = ENVELOPE_MAGIC.to_vec();
+  let secret = match key {
+    SealingKey::Passphrase(passphrase) => {
+      let salt: [u8; SALT_LENGTH] = rand::thread_rng().gen();
+      sealed.push(PASSPHRASE_KIND);
+      sealed.extend_from_slice(&salt);
+      passphrase_key(passphrase, &salt)?
+    },
+    SealingKey::PublicKey(public_key) => {
+      let public_key = decode_x25519_key(public_key)?;
+      let ephemeral_secret: [u8; X25519_KEY_LENGTH] = rand::thread_rng().gen();
+      sealed.push(PUBLIC_KEY_KIND);
+      sealed.extend_from_slice(&x25519(ephemeral_secret, X25519_BASEPOINT_BYTES));
+      x25519(ephemeral_secret, public_key)
+    },
+  };
+  sealed.extend(encrypt_data(data, secret)?);
+  Ok(sealed)
+}
+
+pub fn open(data: &[u8], key: OpeningKey) -> Result<Vec<u8>> {
+  let data = data
+    .strip_prefix(ENVELOPE_MAGIC.as_slice())
+    .ok_or_else(|| anyhow!("The data is not sealed"))?;
+  let (kind, data) = data
+    .split_first()
+    .ok_or_else(|| anyhow!("The sealed data is truncated"))?;
+  let (secret, encrypted) = match (*kind, key) {
+    (PASSPHRASE_KIND, OpeningKey::Passphrase(passphrase)) => {
+      let (salt, encrypted) = split_at_checked(data, SALT_LENGTH)?;
+      (passphrase_key(passphrase, salt)?, encrypted)
+    },
+    (PUBLIC_KEY_KIND, OpeningKey::SecretKey(secret_key)) => {
+      let (ephemeral_public, encrypted) = split_at_checked(data, X25519_KEY_LENGTH)?;
+      let ephemeral_public: [u8; X25519_KEY_LENGTH] = ephemeral_public.try_into()?;
+      (
+        x25519(decode_x25519_key(secret_key)?, ephemeral_public),
+        encrypted,
+      )
+    },
+    (PASSPHRASE_KIND, _) => return Err(anyhow!("The data is sealed with a passphrase")),
+    (PUBLIC_KEY_KIND, _) => return Err(anyhow!("The data is sealed with a public key")),
+    (kind, _) => return Err(anyhow!("Unknown kind of sealing key: {}", kind)),
+  };
+  decrypt_data(encrypted, secret)
+}
+
+fn passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+  if passphrase.is_empty() {
+    return Err(anyhow!("The passphrase is empty"));
+  }
+  let mut key = [0u8; 32];
+  Argon2::default()
+    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+    .map_err(|err| anyhow!("Failed to derive the key from the passphrase: {}", err))?;
+  Ok(key)
+}
+
+fn decode_x25519_key(key: &str) -> Result<[u8; X25519_KEY_LENGTH]> {
+  STANDARD
+    .decode(key.trim())?
+    .try_into()
+    .map_err(|_| anyhow!("The key must be {} bytes long", X25519_KEY_LENGTH))
+}
+
+fn split_at_checked(data: &[u8], mid: usize) -> Result<(&[u8], &[u8])> {
+  if data.len() < mid {
+    return Err(anyhow!("The sealed data is truncated"));
+  }
+  Ok(data.split_at(mid))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn seal_open_with_passphrase_test() {
+    let data = b"# My page";
+    let sealed = seal(data, SealingKey::Passphrase("correct horse")).unwrap();
+    assert!(is_sealed(&sealed));
+    let opened = open(&sealed, OpeningKey::Passphrase("correct horse")).unwrap();
+    assert_eq!(data, opened.as_slice());
+    assert!(open(&sealed, OpeningKey::Passphrase("wrong horse")).is_err());
+  }
+
+  #[test]
+  fn seal_open_with_key_pair_test() {
+    let data = b"# My page";
+    let key_pair = EnvelopeKeyPair::generate();
+    let sealed = seal(data, SealingKey::PublicKey(&key_pair.public_key)).unwrap();
+    let opened = open(&sealed, OpeningKey::SecretKey(&key_pair.secret_key)).unwrap();
+    assert_eq!(data, opened.as_slice());
+
+    let other_key_pair = EnvelopeKeyPair::generate();
+    assert!(open(&sealed, OpeningKey::SecretKey(&other_key_pair.secret_key)).is_err());
+    assert!(open(&sealed, OpeningKey::Passphrase("passphrase")).is_err());
+  }
+}
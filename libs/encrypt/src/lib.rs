@@ -1,4 +1,5 @@
 pub mod aes_encrypt;
+pub mod envelope;
 mod data;
 mod encryptor;
 
@@ -44,6 +44,17 @@ lazy_static::lazy_static! {
 }
 
 impl GoTrueJWTClaims {
+  /// Whether the session was authenticated with a second factor, i.e. gotrue raised it to the
+  /// `aal2` assurance level once a factor was verified.
+  pub fn has_second_factor(&self) -> bool {
+    self.aal.as_deref() == Some("aal2")
+      || self
+        .amr
+        .iter()
+        .flatten()
+        .any(|amr| matches!(amr.method.as_str(), "totp" | "mfa/totp" | "mfa/phone"))
+  }
+
   pub fn decode(token: &str, secret: &[u8]) -> Result<Self, jsonwebtoken::errors::Error> {
     let token_data = decode::<Self>(token, &DecodingKey::from_secret(secret), &VALIDATION)?;
     Ok(token_data.claims)
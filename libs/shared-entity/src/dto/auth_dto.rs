@@ -3,6 +3,7 @@
 use gotrue_entity::dto::GotrueTokenResponse;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use uuid::Uuid;
 
 #[derive(Deserialize, Serialize)]
 pub struct SignInParams {
@@ -71,3 +72,29 @@ pub struct DeleteUserQuery {
   pub provider_access_token: Option<String>,
   pub provider_refresh_token: Option<String>,
 }
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct ChangeEmailParams {
+  pub email: String,
+}
+
+/// The email of the user, and the new email waiting for the confirmation of the user, if any.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct EmailChangeStatus {
+  pub email: String,
+  pub new_email: Option<String>,
+}
+
+/// Merges the account signed in with the access token into the account of the current user. The
+/// access token proves the user owns both accounts.
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct MergeAccountParams {
+  pub source_access_token: String,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+pub struct MergeAccountSummary {
+  /// The workspaces the merged account was a member of.
+  pub workspace_ids: Vec<Uuid>,
+  pub comment_count: u64,
+}
@@ -59,3 +59,67 @@ pub struct PublishDatabaseData {
   /// Relation view id map
   pub database_relations: HashMap<String, String>,
 }
+
+/// Queries the rows of a published database view. The rows are read from the live database, the
+/// filters and the sorts of the view are applied first, then the ones of the query.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct QueryPublishedDatabaseRows {
+  #[serde(default)]
+  pub filters: Vec<PublishedDatabaseFilter>,
+  /// Replaces the sorts of the view when not empty.
+  #[serde(default)]
+  pub sorts: Vec<PublishedDatabaseSort>,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseFilter {
+  pub field_id: String,
+  pub condition: PublishedDatabaseFilterCondition,
+  /// The value compared to the cell, ignored by `is_empty` and `is_not_empty`.
+  #[serde(default)]
+  pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishedDatabaseFilterCondition {
+  Is,
+  IsNot,
+  Contains,
+  DoesNotContain,
+  IsEmpty,
+  IsNotEmpty,
+  GreaterThan,
+  LessThan,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseSort {
+  pub field_id: String,
+  #[serde(default)]
+  pub descending: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedDatabaseRows {
+  pub view_id: String,
+  pub layout: ViewLayout,
+  /// The settings of the layout of the view, e.g. the date field of a calendar.
+  pub layout_settings: serde_json::Value,
+  /// The grouping of the view, e.g. the columns of a board.
+  pub group_settings: serde_json::Value,
+  /// The number of rows matching the filters.
+  pub total_count: usize,
+  /// The ids of the rows of the page, in the order of the sorts.
+  pub row_ids: Vec<String>,
+  /// The encoded collab data of the rows of the page, using the row_id as the key.
+  pub database_row_collabs: HashMap<String, Vec<u8>>,
+}
+
+/// Enables or disables the automatic republish of a published document when its content changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAutoPublish {
+  pub enabled: bool,
+}
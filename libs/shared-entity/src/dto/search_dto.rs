@@ -57,3 +57,52 @@ impl SearchContentType {
     }
   }
 }
+
+/// Parameters of the full-text search over the documents of a workspace.
+/// In response, a list of [FullTextSearchResultItem] is returned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FullTextSearchRequest {
+  /// Words to search for. Quoted phrases, `or` and `-` for the excluded words are supported.
+  pub q: String,
+  /// Maximum number of results to return. Default: 20.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+}
+
+/// Response array element for the full-text search query.
+/// See: [FullTextSearchRequest].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FullTextSearchResultItem {
+  pub view_id: String,
+  /// Name of the view in the folder of the workspace.
+  pub title: String,
+  /// Fragment of the document text around the matched words.
+  pub snippet: String,
+  /// Match rank of this result, the higher the better. The results are sorted by this value.
+  pub rank: f32,
+}
+
+/// Parameters of the semantic search over the documents of a workspace.
+/// In response, a list of [SemanticSearchResultItem] is returned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SemanticSearchRequest {
+  /// Query statement to search for.
+  pub query: String,
+  /// Maximum number of results to return. Default: 10.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub limit: Option<u32>,
+}
+
+/// Response array element for the semantic search query.
+/// See: [SemanticSearchRequest].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SemanticSearchResultItem {
+  pub view_id: String,
+  /// Name of the view in the folder of the workspace.
+  pub title: String,
+  /// Cosine distance between the query and the view content.
+  /// The lower the better. The results are sorted by this value.
+  pub score: f64,
+  /// First characters of the indexed content of the view.
+  pub preview: Option<String>,
+}
@@ -1,4 +1,9 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use uuid::Uuid;
+
+use super::workspace_dto::WorkspaceMemberInvitation;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum SupportedClientFeatures {
@@ -11,3 +16,256 @@ pub struct ServerInfoResponseItem {
   pub supported_client_features: Vec<SupportedClientFeatures>,
   pub minimum_supported_client_version: Option<String>,
 }
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize_repr, Deserialize_repr)]
+#[repr(i16)]
+pub enum ClientVersionStatus {
+  /// The client is still served, with a warning asking the user to upgrade.
+  Deprecated = 1,
+  /// The requests of the client are rejected with `426 Upgrade Required`.
+  Blocked = 2,
+}
+
+impl ClientVersionStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ClientVersionStatus::Deprecated => "deprecated",
+      ClientVersionStatus::Blocked => "blocked",
+    }
+  }
+}
+
+impl From<i16> for ClientVersionStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      2 => ClientVersionStatus::Blocked,
+      _ => ClientVersionStatus::Deprecated,
+    }
+  }
+}
+
+/// Marks the client versions matching the semver requirement, e.g. `>=0.7.0, <0.7.3`, as
+/// deprecated or blocked.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientVersionPolicy {
+  pub version_req: String,
+  pub status: ClientVersionStatus,
+  /// Shown to the users of the matching versions.
+  pub message: Option<String>,
+  pub upgrade_url: Option<String>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpsertClientVersionPolicyParams {
+  pub version_req: String,
+  pub status: ClientVersionStatus,
+  #[serde(default)]
+  pub message: Option<String>,
+  #[serde(default)]
+  pub upgrade_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeleteClientVersionPolicyParams {
+  pub version_req: String,
+}
+
+/// The state of the inactivity lifecycle of a workspace.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceLifecycle {
+  pub workspace_id: Uuid,
+  /// Exempt workspaces are never warned, archived or deleted for inactivity.
+  pub exempt: bool,
+  pub last_active_at: DateTime<Utc>,
+  pub warned_at: Option<DateTime<Utc>>,
+  /// Set when the workspace was archived for inactivity.
+  pub archived_at: Option<DateTime<Utc>>,
+  pub delete_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateWorkspaceLifecycleParams {
+  pub exempt: bool,
+}
+
+/// Creates an account, along with its workspace, for a user who hasn't signed up yet. Sending the
+/// same request again with the same `idempotency_key` returns the account created the first time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProvisionAccountParams {
+  pub idempotency_key: String,
+  pub email: String,
+  #[serde(default)]
+  pub name: Option<String>,
+  /// Without a password, the user signs in with a magic link or an OAuth provider.
+  #[serde(default)]
+  pub password: Option<String>,
+  #[serde(default)]
+  pub workspace_name: Option<String>,
+  /// The other users invited to the workspace of the account.
+  #[serde(default)]
+  pub invitations: Vec<WorkspaceMemberInvitation>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProvisionedAccount {
+  pub user_uuid: Uuid,
+  pub uid: i64,
+  pub workspace_id: Uuid,
+  pub invited_emails: Vec<String>,
+}
+
+/// A Postgres database the collabs of the workspaces without a residency are stored in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollabShard {
+  pub name: String,
+  /// The number of collabs placed in the shard. The collabs that haven't been written since the
+  /// shards were configured, nor rebalanced, aren't counted.
+  pub collab_count: i64,
+}
+
+/// Moves a batch of collabs to the shard they belong to by consistent hashing. The batch starts
+/// after the `after` object id, so the whole placement is rebalanced by following the returned
+/// cursor.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RebalanceCollabShardsParams {
+  #[serde(default)]
+  pub after: Option<String>,
+  #[serde(default)]
+  pub limit: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollabShardRebalance {
+  /// The collabs of the default database placed by the batch, which hadn't been placed yet.
+  #[serde(default)]
+  pub placed_count: u64,
+  pub moved_count: u32,
+  /// The collabs that couldn't be moved, e.g. because they are already being moved.
+  pub failed_object_ids: Vec<String>,
+  /// The object id to rebalance the next batch from, or `None` once all the collabs were visited.
+  pub next_cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrateCollabParams {
+  pub object_id: String,
+  pub shard: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollabMigration {
+  pub object_id: String,
+  pub shard: String,
+  /// False if the collab was already stored in the shard.
+  pub moved: bool,
+}
+
+/// A job run periodically by the scheduler of the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledJob {
+  pub name: String,
+  pub interval_secs: i64,
+  pub next_run_at: DateTime<Utc>,
+  pub last_started_at: Option<DateTime<Utc>>,
+  pub last_finished_at: Option<DateTime<Utc>>,
+  /// The node of the deployment that ran the job last.
+  pub last_run_by: Option<String>,
+  pub is_running: bool,
+  pub run_count: i64,
+  pub failure_count: i64,
+  /// The number of runs that failed in a row, 0 if the last run succeeded.
+  pub consecutive_failures: i32,
+  /// The error of the last failed run, kept after the job succeeds again.
+  pub last_error: Option<String>,
+  pub last_failed_at: Option<DateTime<Utc>>,
+}
+
+/// Only the leader of the deployment runs the scheduled jobs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SchedulerLeader {
+  pub node_id: String,
+  pub lease_expires_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Schedules {
+  /// The node that served the request.
+  pub node_id: String,
+  /// `None` if no node holds the leadership, e.g. while the leader is being replaced.
+  pub leader: Option<SchedulerLeader>,
+  /// The jobs, the next to run first.
+  pub jobs: Vec<ScheduledJob>,
+}
+
+/// A stored collab or snapshot whose blob doesn't match its checksum.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollabCorruption {
+  pub object_id: String,
+  pub workspace_id: Uuid,
+  /// `None` if the corrupted blob is the one of the collab itself.
+  pub snapshot_id: Option<i64>,
+  /// `read` if the corruption was found when reading the blob, `scrub` if it was found by the
+  /// background scrubber.
+  pub detected_by: String,
+  pub detected_at: DateTime<Utc>,
+  /// The latest snapshot of the collab that still matches its checksum, to restore the collab
+  /// from.
+  pub last_good_snapshot_id: Option<i64>,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Serialize_repr, Deserialize_repr)]
+#[repr(i16)]
+pub enum AnnouncementSeverity {
+  #[default]
+  Info = 0,
+  Warning = 1,
+  Critical = 2,
+}
+
+impl From<i16> for AnnouncementSeverity {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => AnnouncementSeverity::Warning,
+      2 => AnnouncementSeverity::Critical,
+      _ => AnnouncementSeverity::Info,
+    }
+  }
+}
+
+/// A message shown by the clients to all the users of the server, e.g. to warn about a
+/// maintenance window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Announcement {
+  pub announcement_id: Uuid,
+  pub title: String,
+  pub message: String,
+  pub severity: AnnouncementSeverity,
+  /// The announcement is shown from this time, or right away if `None`.
+  pub starts_at: Option<DateTime<Utc>>,
+  /// The announcement is shown until this time, or until it's deleted if `None`.
+  pub ends_at: Option<DateTime<Utc>>,
+  /// Whether the users can dismiss the announcement.
+  pub dismissible: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// Creates an announcement, or replaces all the fields of an existing one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnnouncementParams {
+  pub title: String,
+  pub message: String,
+  #[serde(default)]
+  pub severity: AnnouncementSeverity,
+  #[serde(default)]
+  pub starts_at: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub ends_at: Option<DateTime<Utc>>,
+  #[serde(default = "default_dismissible")]
+  pub dismissible: bool,
+}
+
+fn default_dismissible() -> bool {
+  true
+}
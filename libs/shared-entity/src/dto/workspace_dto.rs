@@ -1,6 +1,8 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use collab_entity::{CollabType, EncodedCollab};
-use database_entity::dto::{AFRole, AFWebUser, AFWorkspaceInvitationStatus, PublishInfo};
+use database_entity::dto::{
+  AFAccessLevel, AFRole, AFWebUser, AFWorkspaceInvitationStatus, AFWorkspaceMember, PublishInfo,
+};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::{collections::HashMap, ops::Deref};
@@ -23,6 +25,72 @@ impl From<Vec<String>> for WorkspaceMembers {
   }
 }
 
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceMembers {
+  /// Maximum number of members to return, defaults to 100 and is capped at 1000.
+  pub limit: Option<u32>,
+  pub offset: Option<u32>,
+  /// Matches the prefix of the name or the email of the members, ignoring the case.
+  pub search: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceMembersPage {
+  pub members: Vec<AFWorkspaceMember>,
+  /// The number of members matching the search, across all the pages.
+  pub total_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OEmbedParams {
+  pub url: String,
+  #[serde(default)]
+  pub max_width: Option<u32>,
+  #[serde(default)]
+  pub max_height: Option<u32>,
+}
+
+/// The embed of a URL resolved with the oEmbed provider of its host. The `html` returned by the
+/// provider is replaced by an iframe of `embed_url`, so that the clients only render iframes of
+/// the trusted providers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OEmbed {
+  pub url: String,
+  /// `video`, `rich`, `photo` or `link`.
+  #[serde(rename = "type")]
+  pub embed_type: String,
+  pub provider_name: Option<String>,
+  pub title: Option<String>,
+  pub author_name: Option<String>,
+  pub thumbnail_url: Option<String>,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  pub embed_url: Option<String>,
+  pub html: Option<String>,
+}
+
+/// A document flagged by the secret scanning of its workspace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabSecretFinding {
+  pub object_id: String,
+  /// The kinds of secrets detected, e.g. `aws_access_key`.
+  pub secret_kinds: Vec<String>,
+  pub detected_by: Option<i64>,
+  pub detected_at: DateTime<Utc>,
+}
+
+/// Whether the workspace can be read by anyone, without signing in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspacePublicAccess {
+  pub enabled: bool,
+  pub enabled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateWorkspacePublicAccess {
+  pub enabled: bool,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct CreateWorkspaceMembers(pub Vec<CreateWorkspaceMember>);
 impl From<Vec<CreateWorkspaceMember>> for CreateWorkspaceMembers {
@@ -44,6 +112,75 @@ pub struct WorkspaceMemberInvitation {
   pub role: AFRole,
 }
 
+/// Invites a guest, who can only read the given views of the workspace. A user who is already a
+/// guest of the workspace is granted the views without a new invitation.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InviteWorkspaceGuest {
+  pub email: String,
+  pub view_ids: Vec<String>,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize_repr, Deserialize_repr)]
+#[repr(i16)]
+pub enum BulkInviteStatus {
+  Pending = 0,
+  Completed = 1,
+  Failed = 2,
+}
+
+impl From<i16> for BulkInviteStatus {
+  fn from(value: i16) -> Self {
+    match value {
+      1 => BulkInviteStatus::Completed,
+      2 => BulkInviteStatus::Failed,
+      _ => BulkInviteStatus::Pending,
+    }
+  }
+}
+
+/// Progress of the invitations of a CSV file uploaded to `POST /api/workspace/{workspace_id}/invite/bulk`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BulkInviteTask {
+  pub task_id: Uuid,
+  pub status: BulkInviteStatus,
+  pub total_count: i32,
+  pub success_count: i32,
+  pub failed_count: i32,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// A row of the CSV file that can't be invited. The row number starts from 1 and counts the
+/// header row.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BulkInviteRowError {
+  pub row: usize,
+  pub email: String,
+  pub error: String,
+}
+
+/// Invitations sent to `POST /api/workspace/{workspace_id}/invite/batch`. Unlike
+/// `POST /api/workspace/{workspace_id}/invite`, an invalid invitation doesn't fail the others.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchInviteWorkspaceMembers {
+  pub invitations: Vec<WorkspaceMemberInvitation>,
+  #[serde(default)]
+  pub message: Option<String>,
+}
+
+/// The outcome of the invitation of an email, `error` is `None` if the invitation was sent.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InvitationResult {
+  pub email: String,
+  pub error: Option<String>,
+}
+
+impl InvitationResult {
+  pub fn is_success(&self) -> bool {
+    self.error.is_none()
+  }
+}
+
 #[derive(Deserialize)]
 pub struct WorkspaceInviteQuery {
   pub status: Option<AFWorkspaceInvitationStatus>,
@@ -54,6 +191,12 @@ pub struct WorkspaceMemberChangeset {
   pub email: String,
   pub role: Option<AFRole>,
   pub name: Option<String>,
+  /// The member loses access to the workspace once the time has passed.
+  #[serde(default)]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Make the access of the member permanent again. Takes precedence over `expires_at`.
+  #[serde(default)]
+  pub clear_expiry: bool,
 }
 
 impl WorkspaceMemberChangeset {
@@ -62,6 +205,8 @@ impl WorkspaceMemberChangeset {
       email,
       role: None,
       name: None,
+      expires_at: None,
+      clear_expiry: false,
     }
   }
   pub fn with_role<T: Into<AFRole>>(mut self, role: T) -> Self {
@@ -72,6 +217,18 @@ impl WorkspaceMemberChangeset {
     self.name = Some(name);
     self
   }
+  pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+    self.expires_at = Some(expires_at);
+    self
+  }
+  pub fn without_expiry(mut self) -> Self {
+    self.clear_expiry = true;
+    self
+  }
+
+  pub fn expiry_changed(&self) -> bool {
+    self.expires_at.is_some() || self.clear_expiry
+  }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -94,6 +251,17 @@ pub struct BlobMetadata {
 #[derive(Serialize, Deserialize)]
 pub struct CreateWorkspaceParam {
   pub workspace_name: Option<String>,
+  /// The region where the data of the workspace is stored. Uses the default region if not set.
+  #[serde(default)]
+  pub residency: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct DuplicateWorkspaceParams {
+  /// The name of the new workspace. Defaults to the name of the duplicated workspace followed by
+  /// `(copy)`.
+  #[serde(default)]
+  pub workspace_name: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -131,6 +299,269 @@ pub struct Page {
 pub struct CreatePageParams {
   pub parent_view_id: String,
   pub layout: ViewLayout,
+  /// The name of the page. Defaults to the first heading of the Markdown content, if any.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  /// The initial content of the document. The document starts with an empty paragraph if missing.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content: Option<PageContent>,
+  /// The page template of the workspace the document is copied from, instead of the `content`.
+  /// The name of the page defaults to the name of the template.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub template_id: Option<Uuid>,
+}
+
+/// The initial content of a document page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum PageContent {
+  Markdown(String),
+  /// The tree of the blocks of the document, rooted at the `page` block. Each block has a `type`,
+  /// its `data` and its `children`. The text of a block is the Quill delta stored as the `delta`
+  /// of its data, e.g.
+  /// `{"type":"page","children":[{"type":"paragraph","data":{"delta":[{"insert":"Hello"}]}}]}`.
+  Blocks(serde_json::Value),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreatePageShareLinkParams {
+  /// The link can't be used anymore after this time. It never expires if missing.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A link sharing a single document page with anyone who has it, without publishing the page. It
+/// grants reading the page, with [SharedPage] or the collab of the page with the
+/// `Authorization: ShareLink <token>` header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageShareLink {
+  pub link_id: Uuid,
+  pub view_id: Uuid,
+  /// The secret of the link, which grants the access to the page.
+  pub token: Uuid,
+  /// The link to the page on AppFlowy Web, if its url is configured on the server.
+  pub url: Option<String>,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateCollabShareTokenParams {
+  /// How long the token grants the access, in seconds. Defaults to a week, and can't exceed 30
+  /// days.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expires_in_secs: Option<i64>,
+}
+
+/// A signed token granting read access to a single collab to anyone who has it, e.g. an external
+/// reviewer without an account. It's sent with the `Authorization: ShareToken <token>` header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabShareToken {
+  pub token_id: Uuid,
+  pub token: String,
+  pub object_id: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// An issued share token, without its secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabShareTokenMeta {
+  pub token_id: Uuid,
+  pub object_id: String,
+  pub issued_by: i64,
+  pub created_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// The share links of the pages and the share tokens of the collabs of a workspace, the expired
+/// share tokens excluded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceShareLinks {
+  pub page_share_links: Vec<PageShareLink>,
+  pub collab_share_tokens: Vec<CollabShareTokenMeta>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevokeShareLinksQuery {
+  /// Only the links and tokens created by this user are revoked. All of them if missing.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_by: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokedShareLinks {
+  pub page_share_links: u64,
+  pub collab_share_tokens: u64,
+}
+
+/// The page shared by a link, as seen by the holder of the link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedPage {
+  pub workspace_id: Uuid,
+  pub view_id: Uuid,
+  pub name: String,
+  /// The doc state of the document, like [PageCollabData::encoded_collab].
+  pub encoded_collab: Vec<u8>,
+}
+
+/// A document of the workspace that new pages can be created from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageTemplate {
+  pub template_id: Uuid,
+  pub name: String,
+  pub description: Option<String>,
+  pub created_by: Option<i64>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+/// The document a page template is made of.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum PageTemplateSource {
+  /// A copy of the document of the view, as it is now.
+  View(String),
+  Content(PageContent),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePageTemplateParams {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  pub source: PageTemplateSource,
+}
+
+/// The fields of the template to update, the missing ones are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdatePageTemplateParams {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub source: Option<PageTemplateSource>,
+}
+
+/// Blocks appended to the end of a document page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendBlockToPageParams {
+  /// The blocks, in the shape of the children of [PageContent::Blocks], e.g.
+  /// `{"type":"todo_list","data":{"checked":false,"delta":[{"insert":"Follow up"}]}}`.
+  pub blocks: Vec<serde_json::Value>,
+}
+
+/// Operations applied together: either all of them are saved, or none is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageOperationsParams {
+  pub operations: Vec<PageOperation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PageOperation {
+  /// Creates a document page, like [CreatePageParams] with a document layout.
+  CreatePage {
+    parent_view_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<PageContent>,
+    /// Also appends a mention of the new page to the document of the parent, which must then be
+    /// a document.
+    #[serde(default)]
+    insert_reference: bool,
+  },
+  /// Appends a paragraph mentioning the page to the document.
+  InsertPageReference {
+    document_view_id: String,
+    page_view_id: String,
+  },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageOperationsResult {
+  /// The pages created by the operations, in the order of the operations.
+  pub pages: Vec<Page>,
+}
+
+/// A tree of document pages created together under the same parent: either all of them are saved,
+/// or none is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCreatePageParams {
+  pub parent_view_id: String,
+  pub pages: Vec<BatchPageParams>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPageParams {
+  /// An id chosen by the client, unique within the batch, to find the view created for the page.
+  pub client_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content: Option<PageContent>,
+  /// The pages created under this page, in order.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub children: Vec<BatchPageParams>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCreatePageResult {
+  /// The id of the view created for each page, by the client id of the page.
+  pub view_ids: HashMap<String, String>,
+}
+
+/// Updates the view of a page in the folder. A missing field leaves the view unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchPageParams {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  /// `Some(None)`, i.e. `null`, removes the icon of the view.
+  #[serde(
+    default,
+    deserialize_with = "database_entity::dto::deserialize_present",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub icon: Option<Option<ViewIcon>>,
+  /// Moves the view under this view.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub parent_view_id: Option<String>,
+  /// When moving the view, places it after this sibling, or first if missing.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub prev_view_id: Option<String>,
+}
+
+/// Moves a view among its siblings. Exactly one of the fields must be set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReorderPageParams {
+  /// Places the view right after this sibling.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub after_view_id: Option<String>,
+  /// Places the view right before this sibling.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub before_view_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportPageFormat {
+  Markdown,
+  Html,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPageParams {
+  pub parent_view_id: String,
+  /// The name of the page. Defaults to the first heading of the content.
+  #[serde(default)]
+  pub name: Option<String>,
+  pub format: ImportPageFormat,
+  /// The content of the page, or the base64 encoded encrypted export if `decryption` is set.
+  pub content: String,
+  #[serde(default)]
+  pub decryption: Option<ExportDecryption>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -189,6 +620,18 @@ pub struct TrashSectionItems {
   pub views: Vec<TrashFolderView>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestFolderView {
+  #[serde(flatten)]
+  pub view: FolderView,
+  pub access_level: AFAccessLevel,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct GuestSectionItems {
+  pub views: Vec<GuestFolderView>,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct FolderView {
   pub view_id: String,
@@ -213,6 +656,19 @@ pub struct FolderViewMinimal {
   pub layout: ViewLayout,
 }
 
+/// The metadata of a single view of the folder, without the nested views.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderViewMetadata {
+  pub view_id: String,
+  pub parent_view_id: String,
+  pub name: String,
+  pub icon: Option<ViewIcon>,
+  pub is_space: bool,
+  pub layout: ViewLayout,
+  /// The ids of the child views that the user can see, in their order in the folder.
+  pub children: Vec<String>,
+}
+
 /// Publish info with actual view info
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PublishInfoView {
@@ -253,12 +709,33 @@ impl Default for ViewLayout {
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct QueryWorkspaceParam {
   pub include_member_count: Option<bool>,
+  /// Archived workspaces are hidden from the list unless this is set.
+  pub include_archived: Option<bool>,
 }
 
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct QueryWorkspaceFolder {
   pub depth: Option<u32>,
   pub root_view_id: Option<String>,
+  /// A [FolderViewFilter] encoded as JSON.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub filter: Option<String>,
+}
+
+/// Keeps the views of the folder matching all the given fields, along with their ancestors so
+/// that the matching views can be located in the tree. The root view is always returned.
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct FolderViewFilter {
+  /// The uid of the user who created the view.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_by: Option<i64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub icon_type: Option<IconType>,
+  /// Matched case-insensitively.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name_contains: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub layout: Option<ViewLayout>,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -272,3 +749,538 @@ pub struct PublishedView {
   pub extra: Option<serde_json::Value>,
   pub children: Vec<PublishedView>,
 }
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPublishedOutline {
+  /// The `cursor` of previously returned changes, or of the time the full outline was fetched at,
+  /// in milliseconds since the epoch. Only the changes made after it are returned.
+  pub since: Option<i64>,
+}
+
+/// A view of the published outline that changed, without its children.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedOutlineChange {
+  pub parent_view_id: String,
+  pub view: PublishedView,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedOutlineChanges {
+  /// The views that were published, republished or edited since the cursor.
+  pub changed_views: Vec<PublishedOutlineChange>,
+  /// The ids of all the views of the outline, to prune the views that were removed from it.
+  pub view_ids: Vec<String>,
+  /// Passed as `since` to get the next changes.
+  pub cursor: i64,
+}
+
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsightsRange {
+  #[default]
+  Last7Days,
+  Last30Days,
+  Last90Days,
+}
+
+impl InsightsRange {
+  pub fn days(&self) -> i64 {
+    match self {
+      InsightsRange::Last7Days => 7,
+      InsightsRange::Last30Days => 30,
+      InsightsRange::Last90Days => 90,
+    }
+  }
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceInsights {
+  pub range: Option<InsightsRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceInsights {
+  pub range: InsightsRange,
+  pub since: DateTime<Utc>,
+  pub member_count: i64,
+  /// Members who edited at least one page during the range.
+  pub active_member_count: i64,
+  /// Members who joined the workspace during the range.
+  pub new_member_count: i64,
+  pub most_edited_views: Vec<ViewEditInsight>,
+  /// Comments made on the published views during the range.
+  pub comment_count: i64,
+  /// Visits of the published views during the range.
+  pub publish_visit_count: i64,
+  pub most_visited_published_views: Vec<PublishedViewVisitInsight>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewEditInsight {
+  pub view_id: String,
+  pub edit_count: i64,
+  pub editor_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewVisitInsight {
+  pub view_id: Uuid,
+  /// `None` if the view has been unpublished since.
+  pub publish_name: Option<String>,
+  pub visit_count: i64,
+}
+
+/// The predefined reports the owner of a workspace can run on its analytics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsReport {
+  /// The edits of each member during the range.
+  ActivityByMember,
+  /// The pages created in each interval of the range, and their running total.
+  PageGrowth,
+  /// The comments made on the published views in each interval of the range.
+  CommentTrend,
+}
+
+impl AnalyticsReport {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnalyticsReport::ActivityByMember => "activity_by_member",
+      AnalyticsReport::PageGrowth => "page_growth",
+      AnalyticsReport::CommentTrend => "comment_trend",
+    }
+  }
+}
+
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsInterval {
+  #[default]
+  Day,
+  Week,
+  Month,
+}
+
+impl AnalyticsInterval {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AnalyticsInterval::Day => "day",
+      AnalyticsInterval::Week => "week",
+      AnalyticsInterval::Month => "month",
+    }
+  }
+}
+
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsFormat {
+  #[default]
+  Json,
+  Csv,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryAnalyticsReport {
+  /// Defaults to 30 days before `until`.
+  pub since: Option<DateTime<Utc>>,
+  /// Defaults to now.
+  pub until: Option<DateTime<Utc>>,
+  /// How the rows of the trend reports are grouped. Defaults to a day.
+  pub interval: Option<AnalyticsInterval>,
+  pub format: Option<AnalyticsFormat>,
+}
+
+/// The result of a report, as a table. The CSV format has the same columns and rows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsReportData {
+  pub report: AnalyticsReport,
+  pub since: DateTime<Utc>,
+  pub until: DateTime<Utc>,
+  pub columns: Vec<String>,
+  pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// The changes recorded in the audit log of a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceAuditAction {
+  WorkspaceUpdated,
+  SettingsUpdated,
+  PublicAccessUpdated,
+  MemberInvited,
+  GuestInvited,
+  MemberJoined,
+  MemberUpdated,
+  MemberRemoved,
+  MemberLeft,
+  CollabDeleted,
+  CollabRestored,
+  CollabPurged,
+  ViewsPublished,
+  ViewsUnpublished,
+  PublishedViewsUpdated,
+  PublishedViewRolledBack,
+  PublishNamespaceUpdated,
+  DefaultPublishViewUpdated,
+  PublisherGranted,
+  PublisherRevoked,
+  ViewPermissionsUpdated,
+  MemberGroupUpdated,
+  RoleUpdated,
+  CollabShareTokenIssued,
+  CollabShareTokenRevoked,
+  ShareLinksRevoked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceAuditEvent {
+  pub event_id: i64,
+  pub actor_uid: i64,
+  /// `None` if the user has been deleted since.
+  pub actor_name: Option<String>,
+  pub actor_email: Option<String>,
+  pub action: WorkspaceAuditAction,
+  /// What the change applies to, e.g. the email of a member or the id of a collab.
+  pub target: Option<String>,
+  pub details: serde_json::Value,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceAuditLog {
+  /// Only the events recorded at or after this time.
+  pub since: Option<DateTime<Utc>>,
+  /// Only the events recorded before this time. Pass the time of the oldest event received to get
+  /// the next page.
+  pub until: Option<DateTime<Utc>>,
+  /// Only the events of this user.
+  pub actor_uid: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+/// What a permission change applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionObjectType {
+  Workspace,
+  Collab,
+}
+
+/// The permission of a user on a workspace or on a collab.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionRole {
+  Workspace(AFRole),
+  Collab(AFAccessLevel),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionAuditEvent {
+  pub event_id: i64,
+  /// `None` if the change was made by the server, e.g. when a grant expired.
+  pub actor_uid: Option<i64>,
+  /// `None` if the user has been deleted since.
+  pub actor_email: Option<String>,
+  /// The user whose permission changed.
+  pub target_uid: i64,
+  pub target_email: Option<String>,
+  pub object_type: PermissionObjectType,
+  /// The id of the workspace or of the collab.
+  pub object_id: String,
+  /// `None` if the user was added by the change.
+  pub old_role: Option<PermissionRole>,
+  /// `None` if the user was removed by the change.
+  pub new_role: Option<PermissionRole>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryPermissionAuditLog {
+  /// Only the changes recorded at or after this time.
+  pub since: Option<DateTime<Utc>>,
+  /// Only the changes recorded before this time. Pass the time of the oldest change received to
+  /// get the next page.
+  pub until: Option<DateTime<Utc>>,
+  /// Only the changes of the permissions of this user.
+  pub target_uid: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+/// The SAML identity provider of a workspace, along with what the identity provider has to be
+/// configured with on its side.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkspaceSamlConfig {
+  pub idp_metadata_xml: String,
+  /// The attribute of the assertions holding the email of the user, the name id if `None`.
+  pub email_attribute: Option<String>,
+  pub enabled: bool,
+  /// The entity id of AppFlowy, which is also the URL of its metadata.
+  pub sp_entity_id: String,
+  /// The assertion consumer service URL, the assertions are posted to.
+  pub acs_url: String,
+  /// The URL the users open to sign in with the identity provider.
+  pub login_url: String,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateWorkspaceSamlConfigParams {
+  pub idp_metadata_xml: String,
+  #[serde(default)]
+  pub email_attribute: Option<String>,
+  #[serde(default = "default_saml_enabled")]
+  pub enabled: bool,
+}
+
+fn default_saml_enabled() -> bool {
+  true
+}
+
+/// An email domain of the workspace. The SAML identity provider of the workspace can only sign in
+/// the users whose email is on one of its verified domains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceDomain {
+  pub domain: String,
+  /// The name of the DNS TXT record proving the workspace owns the domain.
+  pub txt_record_name: String,
+  /// The value the DNS TXT record must have.
+  pub txt_record_value: String,
+  /// `None` until the TXT record is verified.
+  pub verified_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddWorkspaceDomainParams {
+  pub domain: String,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryPublishAccessLog {
+  /// Number of days to include, capped by the retention of the access log.
+  pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishAccessLog {
+  /// Number of days the access log is kept for.
+  pub retention_days: i64,
+  /// The days with at least one visit, most recent first.
+  pub days: Vec<PublishAccessLogDay>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishAccessLogDay {
+  pub date: NaiveDate,
+  pub visit_count: i64,
+  pub unique_visitor_count: i64,
+  pub top_referrers: Vec<PublishReferrerCount>,
+  pub top_pages: Vec<PublishedViewVisitInsight>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishReferrerCount {
+  /// The host of the referring page, or `direct` if the visit has no referrer.
+  pub referrer: String,
+  pub visit_count: i64,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryWorkspaceApiUsage {
+  /// Number of days to include, capped by the retention of the API usage.
+  pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceApiUsage {
+  /// Number of days the API usage is kept for.
+  pub retention_days: i64,
+  pub since: NaiveDate,
+  /// The route families called since `since`, the most called first.
+  pub route_families: Vec<RouteFamilyApiUsage>,
+  /// The days with at least one call, oldest first.
+  pub days: Vec<DailyApiUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteFamilyApiUsage {
+  /// The scope of the API and the first segment of the route after the workspace id, e.g.
+  /// `workspace/collab` or `chat`.
+  pub route_family: String,
+  pub call_count: i64,
+  /// The calls answered with a 4xx status.
+  pub client_error_count: i64,
+  /// The calls answered with a 5xx status.
+  pub server_error_count: i64,
+  /// The share of the calls answered with an error status, between 0 and 1.
+  pub error_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyApiUsage {
+  pub date: NaiveDate,
+  pub call_count: i64,
+  pub client_error_count: i64,
+  pub server_error_count: i64,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct QueryPublishAnalytics {
+  /// Number of days to include, capped by the retention of the analytics.
+  pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishAnalytics {
+  /// Number of days the analytics are kept for.
+  pub retention_days: i64,
+  pub since: NaiveDate,
+  /// The published views viewed since `since`, the most viewed first.
+  pub views: Vec<PublishedViewAnalytics>,
+  /// The days with at least one view, oldest first.
+  pub days: Vec<DailyPublishAnalytics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedViewAnalytics {
+  pub view_id: Uuid,
+  /// `None` if the view is no longer published.
+  pub publish_name: Option<String>,
+  pub view_count: i64,
+  /// The sum of the unique visitors of each day, a visitor can't be followed across days.
+  pub unique_visitor_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyPublishAnalytics {
+  pub date: NaiveDate,
+  pub view_count: i64,
+  pub unique_visitor_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactCollabParams {
+  pub collab_type: CollabType,
+  /// The text spans to redact. Every occurrence is replaced with `[REDACTED]`.
+  #[serde(default)]
+  pub texts: Vec<String>,
+  /// The blocks to remove, only for the document collabs.
+  #[serde(default)]
+  pub block_ids: Vec<String>,
+  /// Why the content is redacted, kept in the audit record.
+  pub reason: String,
+}
+
+/// The audit record of a redaction. The redacted content itself is never stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabRedaction {
+  pub redaction_id: i64,
+  pub workspace_id: Uuid,
+  pub object_id: String,
+  pub redacted_by: Option<i64>,
+  pub reason: String,
+  /// The number of occurrences of the text spans replaced in the current state of the collab.
+  pub span_count: i32,
+  pub block_count: i32,
+  /// The number of snapshots rewritten.
+  pub snapshot_count: i32,
+  /// The number of history states deleted.
+  pub history_count: i32,
+  /// The number of indexed fragments deleted, the collab is indexed again.
+  pub embedding_count: i32,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollabExportFormat {
+  Markdown,
+  /// The text of the blocks, one line per block, without any formatting.
+  Text,
+  Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryCollabExport {
+  pub format: CollabExportFormat,
+}
+
+/// The content of a document page, converted on the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageViewContent {
+  pub view_id: String,
+  pub name: String,
+  pub format: CollabExportFormat,
+  pub content: String,
+}
+
+/// Exports a document, encrypted if `encryption` is set, so it can be stored off-site.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportCollabParams {
+  pub format: CollabExportFormat,
+  #[serde(default)]
+  pub encryption: Option<ExportEncryption>,
+}
+
+/// The key the exported file is encrypted with, using AES-256-GCM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportEncryption {
+  Passphrase {
+    passphrase: String,
+  },
+  /// The base64 encoded X25519 public key of the recipient.
+  PublicKey {
+    public_key: String,
+  },
+}
+
+/// The key an encrypted export is decrypted with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportDecryption {
+  Passphrase {
+    passphrase: String,
+  },
+  /// The base64 encoded X25519 secret key matching the public key of the export.
+  SecretKey {
+    secret_key: String,
+  },
+}
+
+/// A document exported as JSON: the blocks of the page, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedDocument {
+  pub object_id: String,
+  pub blocks: Vec<ExportedBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedBlock {
+  #[serde(rename = "type")]
+  pub ty: String,
+  /// The data of the block, e.g. the level of a heading or the URL of an image.
+  #[serde(default)]
+  pub data: HashMap<String, serde_json::Value>,
+  /// The text of the block, as a list of inserts with their formatting attributes.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub delta: Vec<serde_json::Value>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub children: Vec<ExportedBlock>,
+}
+
+/// The content statistics of a document, computed from its latest state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentStats {
+  pub word_count: u64,
+  /// The number of characters, excluding the whitespaces.
+  pub character_count: u64,
+  /// The number of blocks of each type, e.g. `paragraph` or `image`.
+  pub block_counts: HashMap<String, u64>,
+  /// The number of image and file blocks.
+  pub attachment_count: u64,
+  /// The total size in bytes of the attachments uploaded to this server. The attachments linked
+  /// from other hosts are not counted.
+  pub attachment_size: i64,
+  pub reading_time_secs: u64,
+}
@@ -1,8 +1,9 @@
 use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 
+use crate::dto::server_info_dto::ClientVersionStatus;
 use app_error::AppError;
-pub use app_error::ErrorCode;
+pub use app_error::{ErrorCode, ErrorDetails, FieldError};
 use serde::de::DeserializeOwned;
 use std::fmt::{Debug, Display};
 
@@ -38,6 +39,20 @@ pub struct AppResponse<T> {
 
   #[serde(default)]
   pub message: Cow<'static, str>,
+
+  /// The structured details of the error, if any. See [AppResponseError::details].
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub details: Option<ErrorDetails>,
+
+  /// The rate limit of the user, parsed from the headers of the response. `None` if the response
+  /// is not for an authenticated request.
+  #[serde(skip)]
+  pub rate_limit: Option<RateLimitInfo>,
+
+  /// Set if the version of the client has been deprecated or blocked, parsed from the headers of
+  /// the response.
+  #[serde(skip)]
+  pub client_version_warning: Option<ClientVersionWarning>,
 }
 
 impl<T> AppResponse<T> {
@@ -46,17 +61,22 @@ impl<T> AppResponse<T> {
       data: None,
       code,
       message: message.into(),
+      details: None,
+      rate_limit: None,
+      client_version_warning: None,
     }
   }
 
   static_app_response!(Ok, AppError::Ok);
 
   pub fn split(self) -> (Option<T>, AppResponseError) {
-    if self.is_ok() {
-      (self.data, AppResponseError::new(self.code, self.message))
-    } else {
-      (None, AppResponseError::new(self.code, self.message))
-    }
+    let data = if self.is_ok() { self.data } else { None };
+    let err = AppResponseError {
+      code: self.code,
+      message: self.message,
+      details: self.details,
+    };
+    (data, err)
   }
 
   pub fn into_data(self) -> Result<T, AppResponseError> {
@@ -68,7 +88,7 @@ impl<T> AppResponse<T> {
         Some(data) => Ok(data),
       }
     } else {
-      Err(AppResponseError::new(self.code, self.message))
+      Err(self.split().1)
     }
   }
 
@@ -76,7 +96,7 @@ impl<T> AppResponse<T> {
     if matches!(self.code, ErrorCode::Ok) {
       Ok(())
     } else {
-      Err(AppResponseError::new(self.code, self.message))
+      Err(self.split().1)
     }
   }
 
@@ -123,7 +143,9 @@ where
 {
   fn from(value: T1) -> Self {
     let err: AppResponseError = value.into();
-    AppResponse::new(err.code, err.message)
+    let mut resp = AppResponse::new(err.code, err.message);
+    resp.details = err.details;
+    resp
   }
 }
 
@@ -133,22 +155,123 @@ where
 {
   pub async fn from_response(resp: reqwest::Response) -> Result<Self, anyhow::Error> {
     let status_code = resp.status();
-    if !status_code.is_success() {
+    // The server rejects the blocked client versions, the requests for the expired published
+    // views, and the throttled requests, with a regular error response, so that the client can
+    // tell the user why.
+    if !status_code.is_success()
+      && status_code != reqwest::StatusCode::UPGRADE_REQUIRED
+      && status_code != reqwest::StatusCode::GONE
+      && status_code != reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
       let body = resp.text().await?;
       anyhow::bail!("got error code: {}, body: {}", status_code, body)
     }
 
+    let rate_limit = RateLimitInfo::from_headers(resp.headers());
+    let client_version_warning = ClientVersionWarning::from_headers(resp.headers());
     let bytes = resp.bytes().await?;
-    let resp = serde_json::from_slice(&bytes)?;
+    let mut resp: Self = serde_json::from_slice(&bytes)?;
+    resp.rate_limit = rate_limit;
+    resp.client_version_warning = client_version_warning;
     Ok(resp)
   }
 }
 
+pub const X_RATE_LIMIT_LIMIT: &str = "x-ratelimit-limit";
+pub const X_RATE_LIMIT_REMAINING: &str = "x-ratelimit-remaining";
+pub const X_RATE_LIMIT_RESET: &str = "x-ratelimit-reset";
+
+/// The request quota of a user for the current window, sent by the server with the
+/// `X-RateLimit-*` headers of every authenticated response.
+///
+/// The limit is soft: requests beyond the limit are still served, the quota is a hint for the
+/// clients to throttle the bulk operations.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimitInfo {
+  /// The number of requests allowed in a window.
+  pub limit: u32,
+  /// The number of requests left in the current window.
+  pub remaining: u32,
+  /// The number of seconds until the current window resets.
+  pub reset_secs: u64,
+}
+
+impl RateLimitInfo {
+  pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+    fn parse<N: std::str::FromStr>(headers: &reqwest::header::HeaderMap, name: &str) -> Option<N> {
+      headers.get(name)?.to_str().ok()?.parse().ok()
+    }
+
+    Some(Self {
+      limit: parse(headers, X_RATE_LIMIT_LIMIT)?,
+      remaining: parse(headers, X_RATE_LIMIT_REMAINING)?,
+      reset_secs: parse(headers, X_RATE_LIMIT_RESET)?,
+    })
+  }
+
+  /// Returns true if the quota of the current window has been used up.
+  pub fn is_exhausted(&self) -> bool {
+    self.remaining == 0
+  }
+}
+
+pub const X_CLIENT_VERSION_STATUS: &str = "x-client-version-status";
+pub const X_CLIENT_UPGRADE_URL: &str = "x-client-upgrade-url";
+
+/// Sent by the server when the version of the client has been deprecated or blocked. The
+/// message comes from the `Warning` header, the status and the upgrade url from the
+/// `X-Client-Version-Status` and `X-Client-Upgrade-Url` headers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClientVersionWarning {
+  pub status: ClientVersionStatus,
+  pub message: Option<String>,
+  pub upgrade_url: Option<String>,
+}
+
+impl ClientVersionWarning {
+  pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+    let header = |name: &str| {
+      headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+    };
+    let status = match header(X_CLIENT_VERSION_STATUS)?.as_str() {
+      "blocked" => ClientVersionStatus::Blocked,
+      _ => ClientVersionStatus::Deprecated,
+    };
+    // The warning header looks like `299 - "message"`.
+    let message = header(reqwest::header::WARNING.as_str()).and_then(|warning| {
+      let message = warning.split_once(' ')?.1.split_once(' ')?.1;
+      Some(message.trim_matches('"').to_string())
+    });
+    Some(Self {
+      status,
+      message,
+      upgrade_url: header(X_CLIENT_UPGRADE_URL),
+    })
+  }
+}
+
+/// An error sent by the server.
+///
+/// The `code` is stable and is the one to branch on, the `message` is meant for humans and can
+/// change at any time. Some errors also carry structured `details`:
+/// - [ErrorDetails::Validation] lists the invalid fields of the request, each with a stable
+///   `code` such as `too_long`, `invalid_character` or `invalid_format`, and the values the
+///   check was made with in its `params`.
+/// - [ErrorDetails::Limit] gives the limit, or the quota, exceeded by the request, and the
+///   value that exceeded it when known. It is sent along with the codes such as
+///   [ErrorCode::PayloadTooLarge] or [ErrorCode::StringLengthLimitReached].
+/// - [ErrorDetails::RetryAfter] tells how long to wait before retrying the request. It is sent
+///   along with [ErrorCode::TooManyRequests].
 #[derive(Clone, Debug, Serialize, Deserialize, thiserror::Error)]
 pub struct AppResponseError {
   #[serde(deserialize_with = "default_error_code")]
   pub code: ErrorCode,
   pub message: Cow<'static, str>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub details: Option<ErrorDetails>,
 }
 
 impl AppResponseError {
@@ -156,12 +279,37 @@ impl AppResponseError {
     Self {
       code,
       message: message.into(),
+      details: None,
     }
   }
 
   pub fn is_record_not_found(&self) -> bool {
     matches!(self.code, ErrorCode::RecordNotFound)
   }
+
+  /// Returns the invalid fields of the request, if the error is a validation error.
+  pub fn field_errors(&self) -> &[FieldError] {
+    match &self.details {
+      Some(ErrorDetails::Validation { field_errors }) => field_errors,
+      _ => &[],
+    }
+  }
+
+  /// Returns the exceeded limit and the value that exceeded it, if the error is about a limit.
+  pub fn limit(&self) -> Option<(i64, Option<i64>)> {
+    match &self.details {
+      Some(ErrorDetails::Limit { limit, actual }) => Some((*limit, *actual)),
+      _ => None,
+    }
+  }
+
+  /// Returns how many seconds to wait before retrying, if the request was throttled.
+  pub fn retry_after_secs(&self) -> Option<u64> {
+    match &self.details {
+      Some(ErrorDetails::RetryAfter { retry_after_secs }) => Some(*retry_after_secs),
+      _ => None,
+    }
+  }
 }
 
 impl<T> From<T> for AppResponseError
@@ -173,6 +321,7 @@ where
     Self {
       code: err.code(),
       message: Cow::Owned(err.to_string()),
+      details: err.details(),
     }
   }
 }
@@ -185,12 +334,23 @@ impl Display for AppResponseError {
 
 #[cfg(feature = "cloud")]
 impl actix_web::error::ResponseError for AppResponseError {
+  /// The errors are sent with `200 OK`, except the expired published views, which are gone, and
+  /// the throttled requests, which are sent with `429 Too Many Requests` and a `Retry-After`
+  /// header.
   fn status_code(&self) -> actix_web::http::StatusCode {
-    actix_web::http::StatusCode::OK
+    match self.code {
+      ErrorCode::PublishedViewExpired => actix_web::http::StatusCode::GONE,
+      ErrorCode::TooManyRequests => actix_web::http::StatusCode::TOO_MANY_REQUESTS,
+      _ => actix_web::http::StatusCode::OK,
+    }
   }
 
   fn error_response(&self) -> actix_web::HttpResponse {
-    actix_web::HttpResponse::Ok().json(self)
+    let mut builder = actix_web::HttpResponse::build(self.status_code());
+    if let Some(retry_after_secs) = self.retry_after_secs() {
+      builder.insert_header((actix_web::http::header::RETRY_AFTER, retry_after_secs));
+    }
+    builder.json(self)
   }
 }
 
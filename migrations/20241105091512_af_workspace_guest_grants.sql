@@ -0,0 +1,40 @@
+-- Guests only get access to the views that are explicitly granted to them. Accepting an
+-- invitation as a guest must not grant access to the workspace folder collab.
+CREATE OR REPLACE FUNCTION add_to_af_workspace_member()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.status = 1 THEN
+    -- workspace permission
+    INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+    VALUES (
+      NEW.workspace_id,
+      (SELECT uid FROM af_user WHERE email = NEW.invitee_email),
+      NEW.role_id
+    )
+    ON CONFLICT (workspace_id, uid) DO NOTHING;
+
+    -- collab permission, guests (role_id = 3) are excluded
+    IF NEW.role_id <> 3 THEN
+      INSERT INTO af_collab_member (uid, oid, permission_id)
+      VALUES (
+        (SELECT uid FROM af_user WHERE email = NEW.invitee_email),
+        NEW.workspace_id,
+        (SELECT permission_id
+         FROM public.af_role_permissions
+         WHERE public.af_role_permissions.role_id = NEW.role_id)
+      )
+      ON CONFLICT (uid, oid)
+      DO UPDATE
+        SET permission_id = excluded.permission_id;
+    END IF;
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Revoke the folder collab permission previously granted to existing guests
+DELETE FROM af_collab_member
+USING af_workspace_member
+WHERE af_workspace_member.role_id = 3
+  AND af_collab_member.uid = af_workspace_member.uid
+  AND af_collab_member.oid = af_workspace_member.workspace_id::TEXT;
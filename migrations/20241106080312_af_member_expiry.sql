@@ -0,0 +1,14 @@
+-- Time-boxed access grants. A NULL expires_at means the grant never expires.
+ALTER TABLE af_workspace_member
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS expiry_notified_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_af_workspace_member_expires_at
+    ON af_workspace_member (expires_at)
+    WHERE expires_at IS NOT NULL;
+
+ALTER TABLE af_collab_member
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN IF NOT EXISTS expiry_notified_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_af_collab_member_expires_at
+    ON af_collab_member (expires_at)
+    WHERE expires_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- The region in which the data of the workspace is stored. NULL means the default region.
+-- The residency is chosen when the workspace is created and can not be changed afterwards.
+ALTER TABLE af_workspace ADD COLUMN IF NOT EXISTS residency TEXT;
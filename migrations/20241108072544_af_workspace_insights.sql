@@ -0,0 +1,17 @@
+-- Daily counters used to compute the insights of a workspace.
+CREATE TABLE IF NOT EXISTS af_collab_edit_activity (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    oid TEXT NOT NULL,
+    uid BIGINT NOT NULL,
+    activity_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    edit_count INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (workspace_id, activity_date, oid, uid)
+);
+
+CREATE TABLE IF NOT EXISTS af_published_view_visit (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    visit_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    visit_count INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (workspace_id, visit_date, view_id)
+);
@@ -0,0 +1,10 @@
+-- Custom emoji of a workspace, referenced by shortcode in comments and reactions.
+-- The image of the emoji is stored in the blob storage of the workspace.
+CREATE TABLE IF NOT EXISTS af_workspace_custom_emoji (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    shortcode TEXT NOT NULL,
+    file_id TEXT NOT NULL,
+    created_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, shortcode)
+);
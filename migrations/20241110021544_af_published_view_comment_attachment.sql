@@ -0,0 +1,20 @@
+-- stores the images attached to the comments on a published view. The image itself is stored in
+-- the bucket of the workspace that published the view.
+CREATE TABLE IF NOT EXISTS af_published_view_comment_attachment (
+  attachment_id       UUID NOT NULL,
+  workspace_id        UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id             UUID NOT NULL,
+  -- the attachment is uploaded before the comment is created, so the comment is set once the
+  -- comment referencing the attachment is created
+  comment_id          UUID REFERENCES af_published_view_comment(comment_id) ON DELETE CASCADE,
+  content_type        TEXT NOT NULL,
+  file_size           BIGINT NOT NULL,
+  created_by          BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  created_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY         (attachment_id)
+);
+CREATE INDEX IF NOT EXISTS idx_comment_id_on_af_published_view_comment_attachment
+  ON af_published_view_comment_attachment(comment_id);
+CREATE INDEX IF NOT EXISTS idx_view_id_on_af_published_view_comment_attachment
+  ON af_published_view_comment_attachment(view_id);
@@ -0,0 +1,14 @@
+-- stores the email subscriptions to the new comments on a published view. A subscription only
+-- receives notifications once it has been confirmed through the link sent to the email.
+CREATE TABLE IF NOT EXISTS af_published_view_comment_subscription (
+  view_id             UUID NOT NULL,
+  email               TEXT NOT NULL,
+  -- secret used by the confirm and unsubscribe links sent to the email
+  token               UUID NOT NULL DEFAULT gen_random_uuid(),
+  confirmed_at        TIMESTAMP WITH TIME ZONE,
+  created_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY         (view_id, email)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_token_on_af_published_view_comment_subscription
+  ON af_published_view_comment_subscription(token);
@@ -0,0 +1,18 @@
+-- tracks the progress of inviting the members listed in an uploaded CSV file
+CREATE TABLE IF NOT EXISTS af_workspace_bulk_invite (
+  task_id             UUID NOT NULL,
+  workspace_id        UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  created_by          BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  -- 0 for pending, 1 for completed, 2 for failed
+  status              SMALLINT NOT NULL DEFAULT 0,
+  total_count         INTEGER NOT NULL,
+  success_count       INTEGER NOT NULL DEFAULT 0,
+  -- the rows that can't be invited, as an array of {row, email, error}
+  errors              JSONB NOT NULL DEFAULT '[]'::jsonb,
+  created_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY         (task_id)
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_on_af_workspace_bulk_invite
+  ON af_workspace_bulk_invite(workspace_id);
@@ -0,0 +1,16 @@
+-- Daily aggregates used to build the access log of the published views of a workspace. The
+-- visitors are stored as a salted hash that changes every day, raw IP addresses are never stored.
+CREATE TABLE IF NOT EXISTS af_published_view_visitor (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    visit_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    visitor_hash TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, visit_date, visitor_hash)
+);
+
+CREATE TABLE IF NOT EXISTS af_published_view_referrer (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    visit_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    referrer TEXT NOT NULL,
+    visit_count INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (workspace_id, visit_date, referrer)
+);
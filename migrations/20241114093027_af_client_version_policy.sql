@@ -0,0 +1,11 @@
+-- Client versions marked as deprecated or blocked by the administrator. A version matches a
+-- policy if it satisfies the semver requirement of the policy, e.g. `>=0.7.0, <0.7.3`.
+CREATE TABLE IF NOT EXISTS af_client_version_policy (
+    version_req TEXT PRIMARY KEY,
+    -- 1: deprecated, 2: blocked
+    status SMALLINT NOT NULL,
+    message TEXT,
+    upgrade_url TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- The time at which the workspace was archived. NULL means the workspace is not archived.
+-- An archived workspace is read-only and hidden from the workspace list by default.
+ALTER TABLE af_workspace ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE;
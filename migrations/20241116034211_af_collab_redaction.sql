@@ -0,0 +1,18 @@
+-- audit record of the content redacted from the full history of a collab, e.g. secrets or
+-- personal data pasted into a document. The redacted content itself is never stored.
+CREATE TABLE IF NOT EXISTS af_collab_redaction (
+  redaction_id        BIGSERIAL PRIMARY KEY,
+  workspace_id        UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  oid                 TEXT NOT NULL,
+  redacted_by         BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+  reason              TEXT NOT NULL,
+  -- the number of occurrences of the text spans replaced in the current state of the collab
+  span_count          INTEGER NOT NULL,
+  block_count         INTEGER NOT NULL,
+  snapshot_count      INTEGER NOT NULL,
+  history_count       INTEGER NOT NULL,
+  embedding_count     INTEGER NOT NULL,
+  created_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_oid_on_af_collab_redaction
+  ON af_collab_redaction(workspace_id, oid);
@@ -0,0 +1,12 @@
+-- documents flagged by the secret scanning, see the `secret_scanning` workspace setting. Only the
+-- kinds of the secrets are stored, never the secrets themselves. The flag is removed once a write
+-- of the document no longer contains any secret.
+CREATE TABLE IF NOT EXISTS af_collab_secret_finding (
+  oid                 TEXT PRIMARY KEY,
+  workspace_id        UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  secret_kinds        TEXT[] NOT NULL,
+  detected_by         BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+  detected_at         TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_on_af_collab_secret_finding
+  ON af_collab_secret_finding(workspace_id);
@@ -0,0 +1,4 @@
+-- Hosts of the embeds kept when the views of the namespace are published, in addition to the
+-- hosts trusted by the server.
+ALTER TABLE af_workspace_namespace
+  ADD COLUMN embed_allowlist TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,7 @@
+-- the time after which a pending invitation can no longer be accepted, NULL for the invitations
+-- that never expire. See the `invitation_ttl_days` workspace setting. The expired invitations are
+-- marked with the status 3 by a background job.
+ALTER TABLE af_workspace_invitation ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_expires_at_on_pending_af_workspace_invitation
+  ON af_workspace_invitation(expires_at)
+  WHERE status = 0 AND expires_at IS NOT NULL;
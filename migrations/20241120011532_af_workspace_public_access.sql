@@ -0,0 +1,7 @@
+-- workspaces readable by anyone, without signing in: the folder, except the private spaces and
+-- the trash, and the content of the views. A workspace is public while it has a row.
+CREATE TABLE IF NOT EXISTS af_workspace_public_access (
+  workspace_id        UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  enabled_by          BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+  enabled_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,6 @@
+-- the views granted to a guest once the guest invitation is accepted
+CREATE TABLE IF NOT EXISTS af_workspace_invitation_guest_view (
+  invite_id UUID NOT NULL REFERENCES af_workspace_invitation(id) ON DELETE CASCADE,
+  view_id TEXT NOT NULL,
+  PRIMARY KEY (invite_id, view_id)
+);
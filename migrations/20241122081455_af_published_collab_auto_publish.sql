@@ -0,0 +1,19 @@
+-- published documents that are republished automatically when their content changes
+CREATE TABLE IF NOT EXISTS af_published_collab_auto_publish (
+  workspace_id UUID NOT NULL,
+  view_id UUID NOT NULL,
+  -- the document is republished on behalf of the user who enabled the auto-publish
+  enabled_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  enabled_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  -- the first and the last change that are not republished yet, NULL when the published
+  -- document is up to date
+  pending_since TIMESTAMP WITH TIME ZONE,
+  changed_at TIMESTAMP WITH TIME ZONE,
+  -- the error of the last republish, NULL if it succeeded
+  last_error TEXT,
+  PRIMARY KEY (view_id),
+  FOREIGN KEY (workspace_id, view_id) REFERENCES af_published_collab(workspace_id, view_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_changed_at_on_af_published_collab_auto_publish
+  ON af_published_collab_auto_publish(changed_at)
+  WHERE changed_at IS NOT NULL;
@@ -0,0 +1,18 @@
+-- sites hosted under the publish namespace of a workspace, e.g. `acme/handbook` and `acme/blog`
+CREATE TABLE IF NOT EXISTS af_publish_sub_namespace (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  sub_namespace TEXT NOT NULL,
+  title TEXT NOT NULL DEFAULT '',
+  -- the view served at the root of the sub-namespace
+  default_published_view_id UUID,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (workspace_id, sub_namespace)
+);
+
+-- NULL when the view is served by the publish namespace itself
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS sub_namespace TEXT;
+ALTER TABLE af_published_collab
+  ADD CONSTRAINT af_published_collab_sub_namespace_fkey
+  FOREIGN KEY (workspace_id, sub_namespace)
+  REFERENCES af_publish_sub_namespace(workspace_id, sub_namespace)
+  ON DELETE SET NULL (sub_namespace);
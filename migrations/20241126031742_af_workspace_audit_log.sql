@@ -0,0 +1,30 @@
+-- append-only log of the changes made to a workspace: member changes, collab deletions, publish
+-- actions and settings edits
+CREATE TABLE IF NOT EXISTS af_workspace_audit_log (
+  event_id      BIGSERIAL PRIMARY KEY,
+  workspace_id  UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  -- the user who made the change, kept after the user is deleted
+  actor_uid     BIGINT NOT NULL,
+  action        TEXT NOT NULL,
+  -- what the change applies to, e.g. the email of a member or the id of a collab
+  target        TEXT,
+  details       JSONB NOT NULL DEFAULT '{}',
+  created_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_created_at_on_af_workspace_audit_log
+  ON af_workspace_audit_log(workspace_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_actor_uid_on_af_workspace_audit_log
+  ON af_workspace_audit_log(workspace_id, actor_uid, created_at DESC);
+
+-- the events can't be changed or deleted, except along with their workspace
+CREATE OR REPLACE FUNCTION prevent_change_af_workspace_audit_log_func() RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP = 'DELETE' AND pg_trigger_depth() > 1 THEN
+    RETURN OLD;
+  END IF;
+  RAISE EXCEPTION 'The workspace audit log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+CREATE OR REPLACE TRIGGER trigger_prevent_change_af_workspace_audit_log
+  BEFORE UPDATE OR DELETE ON af_workspace_audit_log
+  FOR EACH ROW EXECUTE FUNCTION prevent_change_af_workspace_audit_log_func();
@@ -0,0 +1,12 @@
+-- plain text of the collabs extracted by the indexer, searched with the postgres full-text search
+CREATE TABLE IF NOT EXISTS af_collab_search_text (
+  oid           TEXT NOT NULL,
+  partition_key INTEGER NOT NULL,
+  content       TEXT NOT NULL,
+  -- the 'simple' configuration doesn't stem the words, so that every language is matched alike
+  search_vector TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
+  indexed_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (oid, partition_key)
+);
+CREATE INDEX IF NOT EXISTS idx_search_vector_on_af_collab_search_text
+  ON af_collab_search_text USING GIN (search_vector);
@@ -0,0 +1,20 @@
+-- when the collab was last written, used to find the collabs nobody touches anymore
+ALTER TABLE af_collab ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP;
+-- set when the payload of the collab is moved to af_collab_archive, the blob is emptied meanwhile
+ALTER TABLE af_collab ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP WITH TIME ZONE DEFAULT NULL;
+CREATE INDEX IF NOT EXISTS idx_updated_at_on_af_collab
+  ON af_collab(updated_at) WHERE archived_at IS NULL AND deleted_at IS NULL;
+
+-- the brotli compressed payloads of the archived collabs, moved back to af_collab on access
+CREATE TABLE IF NOT EXISTS af_collab_archive (
+  oid           TEXT NOT NULL,
+  partition_key INTEGER NOT NULL,
+  workspace_id  UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  blob          BYTEA NOT NULL,
+  -- the length of the payload before compression
+  len           INTEGER NOT NULL,
+  archived_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (oid, partition_key)
+);
+-- the payloads are already compressed
+ALTER TABLE af_collab_archive ALTER COLUMN blob SET STORAGE EXTERNAL;
@@ -0,0 +1,17 @@
+-- State of the inactivity lifecycle of the workspaces. A workspace without a row has never been
+-- warned and is not exempt.
+CREATE TABLE IF NOT EXISTS af_workspace_lifecycle (
+    workspace_id UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    -- Exempt workspaces are never warned, archived or deleted for inactivity.
+    exempt BOOLEAN NOT NULL DEFAULT FALSE,
+    -- The inactivity is counted from this time at the earliest, e.g. when the owner unarchives
+    -- the workspace.
+    reset_at TIMESTAMP WITH TIME ZONE,
+    warned_at TIMESTAMP WITH TIME ZONE,
+    -- Set when the workspace was archived by the lifecycle policy.
+    archived_at TIMESTAMP WITH TIME ZONE,
+    delete_at TIMESTAMP WITH TIME ZONE,
+    -- The number of days before the deletion of the last deletion notice sent.
+    deletion_notice_days INTEGER,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Users blocked by other users. The comments and reactions of a blocked user are hidden from the
+-- blocker, and the blocked user can't comment on, reply to or react to the content of the blocker.
+CREATE TABLE IF NOT EXISTS af_user_block (
+    blocker_uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    blocked_uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (blocker_uid, blocked_uid)
+);
+CREATE INDEX IF NOT EXISTS idx_af_user_block_blocked_uid ON af_user_block (blocked_uid);
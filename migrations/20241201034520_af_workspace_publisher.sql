@@ -0,0 +1,11 @@
+-- Members of the workspaces granted the publish permission. Besides the owners, they can publish
+-- views when the publishing is restricted, manage the views published by the others, the publish
+-- namespace and the default published view. The permission is revoked when the member leaves.
+CREATE TABLE IF NOT EXISTS af_workspace_publisher (
+    workspace_id UUID NOT NULL,
+    uid BIGINT NOT NULL,
+    granted_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, uid),
+    FOREIGN KEY (uid, workspace_id) REFERENCES af_workspace_member(uid, workspace_id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- the deleted collabs stay in the trash until they are restored, or purged once their retention
+-- window ends
+CREATE INDEX IF NOT EXISTS idx_deleted_at_on_af_collab
+  ON af_collab(deleted_at) WHERE deleted_at IS NOT NULL;
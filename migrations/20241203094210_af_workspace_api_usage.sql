@@ -0,0 +1,13 @@
+-- the API calls made on each workspace per day, grouped by route family, e.g. `workspace/collab`
+CREATE TABLE IF NOT EXISTS af_workspace_api_usage (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    usage_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    route_family TEXT NOT NULL,
+    call_count BIGINT NOT NULL DEFAULT 0,
+    -- the calls answered with a 4xx status
+    client_error_count BIGINT NOT NULL DEFAULT 0,
+    -- the calls answered with a 5xx status
+    server_error_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, usage_date, route_family)
+);
+CREATE INDEX IF NOT EXISTS idx_af_workspace_api_usage_date ON af_workspace_api_usage(usage_date);
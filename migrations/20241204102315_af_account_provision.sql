@@ -0,0 +1,9 @@
+-- the accounts provisioned by the administrator of the server, keyed by the idempotency key of
+-- the provisioning request, so that a retried request returns the account created the first time
+CREATE TABLE IF NOT EXISTS af_account_provision (
+    idempotency_key TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    -- set once the account is provisioned, null while the request is in progress
+    result JSONB,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
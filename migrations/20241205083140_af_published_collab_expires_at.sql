@@ -0,0 +1,5 @@
+-- the published view is unpublished once the expiry has passed, NULL if it never expires
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_expires_at_on_af_published_collab
+  ON af_published_collab(expires_at)
+  WHERE expires_at IS NOT NULL;
@@ -0,0 +1,22 @@
+-- the views and the unique visitors of each published view per day. The visits are counted in
+-- memory and written in batches, so the published views are not slowed down.
+CREATE TABLE IF NOT EXISTS af_published_view_daily_stats (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    stat_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    view_count BIGINT NOT NULL DEFAULT 0,
+    unique_visitor_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (workspace_id, stat_date, view_id)
+);
+CREATE INDEX IF NOT EXISTS idx_af_published_view_daily_stats_date ON af_published_view_daily_stats(stat_date);
+
+-- the visitors of each published view per day, stored as the salted hash of the access log, so
+-- each visitor is counted once per day
+CREATE TABLE IF NOT EXISTS af_published_view_daily_visitor (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    stat_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    visitor_hash TEXT NOT NULL,
+    PRIMARY KEY (workspace_id, stat_date, view_id, visitor_hash)
+);
+CREATE INDEX IF NOT EXISTS idx_af_published_view_daily_visitor_date ON af_published_view_daily_visitor(stat_date);
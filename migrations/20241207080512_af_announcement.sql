@@ -0,0 +1,23 @@
+-- Announcements shown by the clients to all the users of the server, e.g. to warn about a
+-- maintenance window. An announcement is shown between its start and its end, NULL meaning
+-- immediately and forever respectively.
+CREATE TABLE IF NOT EXISTS af_announcement (
+    announcement_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title TEXT NOT NULL,
+    message TEXT NOT NULL,
+    -- 0: info, 1: warning, 2: critical
+    severity SMALLINT NOT NULL DEFAULT 0,
+    starts_at TIMESTAMP WITH TIME ZONE,
+    ends_at TIMESTAMP WITH TIME ZONE,
+    dismissible BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- The announcements dismissed by the users, which aren't shown to them anymore.
+CREATE TABLE IF NOT EXISTS af_announcement_dismissal (
+    announcement_id UUID NOT NULL REFERENCES af_announcement(announcement_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    dismissed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (announcement_id, uid)
+);
@@ -0,0 +1,4 @@
+-- overrides of the SEO metadata of the published view, NULL to derive it from the published metadata
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS seo_title TEXT;
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS seo_description TEXT;
+ALTER TABLE af_published_collab ADD COLUMN IF NOT EXISTS seo_image_url TEXT;
@@ -0,0 +1,2 @@
+-- the last time the author edited the content of the comment, NULL if it was never edited
+ALTER TABLE af_published_view_comment ADD COLUMN IF NOT EXISTS edited_at TIMESTAMP WITH TIME ZONE;
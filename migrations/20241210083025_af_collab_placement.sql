@@ -0,0 +1,12 @@
+-- the storage shard of each collab of the workspaces stored in the default region, when the
+-- collabs are spread over several databases. The collabs without a placement are stored in the
+-- default database.
+CREATE TABLE IF NOT EXISTS af_collab_placement (
+  oid           TEXT NOT NULL PRIMARY KEY,
+  workspace_id  UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  shard         TEXT NOT NULL,
+  -- set while the collab is moved to another shard, the writes are rejected meanwhile
+  fenced_at     TIMESTAMP WITH TIME ZONE,
+  updated_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_shard_on_af_collab_placement ON af_collab_placement(shard);
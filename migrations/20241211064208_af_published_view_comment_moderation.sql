@@ -0,0 +1,14 @@
+-- Hidden comments are only shown to the owners of the workspace, pinned comments are listed first
+ALTER TABLE af_published_view_comment
+  ADD COLUMN is_hidden BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN pinned_at TIMESTAMP WITH TIME ZONE;
+
+-- The users who can't comment or react on the views published in the namespace
+CREATE TABLE IF NOT EXISTS af_publish_namespace_comment_blocklist (
+  namespace   TEXT NOT NULL REFERENCES af_workspace_namespace(namespace) ON DELETE CASCADE ON UPDATE CASCADE,
+  blocked_uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  blocked_by  BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+  created_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY (namespace, blocked_uid)
+);
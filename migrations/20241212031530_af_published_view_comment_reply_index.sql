@@ -0,0 +1,4 @@
+-- Used to assemble the threads of the comments on a published view
+CREATE INDEX IF NOT EXISTS idx_reply_comment_id_on_af_published_view_comment
+  ON af_published_view_comment(reply_comment_id)
+  WHERE reply_comment_id IS NOT NULL;
@@ -0,0 +1,21 @@
+-- The version of the published view, incremented each time it is republished or rolled back
+ALTER TABLE af_published_collab
+  ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1,
+  ADD COLUMN IF NOT EXISTS version_published_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  ADD COLUMN IF NOT EXISTS restored_from_version INT;
+UPDATE af_published_collab SET version_published_at = updated_at;
+
+-- The prior versions of the published views, kept so that a view can be rolled back
+CREATE TABLE IF NOT EXISTS af_published_collab_version (
+  workspace_id          UUID NOT NULL,
+  view_id               UUID NOT NULL,
+  version               INT NOT NULL,
+  metadata              JSONB NOT NULL,
+  blob                  BYTEA NOT NULL,
+  published_by          BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+  published_at          TIMESTAMP WITH TIME ZONE NOT NULL,
+  restored_from_version INT,
+  PRIMARY KEY (workspace_id, view_id, version),
+  FOREIGN KEY (workspace_id, view_id)
+    REFERENCES af_published_collab(workspace_id, view_id) ON DELETE CASCADE
+);
@@ -0,0 +1,24 @@
+-- Templates published to the marketplace by the users, from one of their published views. Unlike
+-- the templates of the template center, a listing is removed along with its published view, since
+-- the view is what gets instantiated.
+CREATE TABLE IF NOT EXISTS af_template_marketplace (
+  view_id           UUID NOT NULL,
+  workspace_id      UUID NOT NULL,
+  created_by        BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  created_at        TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at        TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  name              TEXT NOT NULL,
+  description       TEXT NOT NULL,
+  -- see TemplateLicense
+  license           INT NOT NULL,
+  screenshot_urls   TEXT[] NOT NULL DEFAULT '{}',
+  category_id       UUID REFERENCES af_template_category(category_id) ON DELETE SET NULL,
+  install_count     BIGINT NOT NULL DEFAULT 0,
+
+  PRIMARY KEY (view_id),
+  FOREIGN KEY (workspace_id, view_id) REFERENCES af_published_collab(workspace_id, view_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_af_template_marketplace_category_id
+  ON af_template_marketplace(category_id);
+CREATE INDEX IF NOT EXISTS idx_af_template_marketplace_install_count
+  ON af_template_marketplace(install_count DESC, created_at DESC);
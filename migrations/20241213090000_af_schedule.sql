@@ -0,0 +1,23 @@
+-- The periodic jobs of the server, persisted so that their runs survive restarts and can be
+-- inspected by the server admins
+CREATE TABLE IF NOT EXISTS af_schedule (
+  name                 TEXT PRIMARY KEY,
+  interval_secs        BIGINT NOT NULL,
+  next_run_at          TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_started_at      TIMESTAMP WITH TIME ZONE,
+  last_finished_at     TIMESTAMP WITH TIME ZONE,
+  last_run_by          TEXT,
+  run_count            BIGINT NOT NULL DEFAULT 0,
+  failure_count        BIGINT NOT NULL DEFAULT 0,
+  consecutive_failures INT NOT NULL DEFAULT 0,
+  last_error           TEXT,
+  last_failed_at       TIMESTAMP WITH TIME ZONE
+);
+
+-- The node of the deployment that runs the scheduled jobs. The leadership is a lease renewed by
+-- the leader, another node takes over once the lease expires.
+CREATE TABLE IF NOT EXISTS af_scheduler_leader (
+  id               BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+  node_id          TEXT NOT NULL,
+  lease_expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
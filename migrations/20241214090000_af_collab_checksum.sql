@@ -0,0 +1,38 @@
+-- The SHA-256 of the blob, written along with it. NULL for the blobs written before the checksums
+-- were introduced, which can't be verified. The checksum of an archived collab is kept, it matches
+-- the blob once rehydrated.
+ALTER TABLE af_collab ADD COLUMN IF NOT EXISTS checksum BYTEA;
+ALTER TABLE af_collab_snapshot ADD COLUMN IF NOT EXISTS checksum BYTEA;
+
+-- The collabs and snapshots whose blob doesn't match its checksum, found when they are read or by
+-- the background scrubber. A corruption is resolved once the blob matches its checksum again, e.g.
+-- after the collab is restored from a snapshot.
+CREATE TABLE IF NOT EXISTS af_collab_corruption (
+  id                    BIGSERIAL PRIMARY KEY,
+  oid                   TEXT NOT NULL,
+  workspace_id          UUID NOT NULL,
+  -- NULL if the corrupted blob is the one of a snapshot
+  partition_key         INT,
+  -- 0 if the corrupted blob is the one of the collab itself
+  snapshot_id           BIGINT NOT NULL DEFAULT 0,
+  -- 'read' or 'scrub'
+  detected_by           TEXT NOT NULL,
+  detected_at           TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  -- The latest snapshot of the collab whose blob still matches its checksum, to repair from
+  last_good_snapshot_id BIGINT,
+  resolved_at           TIMESTAMP WITH TIME ZONE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_collab_corruption_unresolved
+  ON af_collab_corruption (oid, snapshot_id)
+  WHERE resolved_at IS NULL;
+
+-- Where the scrubber stopped, it resumes from there on its next run. The scrubber starts over once
+-- it reaches the end of the tables.
+CREATE TABLE IF NOT EXISTS af_collab_scrub_cursor (
+  id                   BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+  collab_oid           TEXT NOT NULL DEFAULT '',
+  collab_partition_key INT NOT NULL DEFAULT -1,
+  snapshot_id          BIGINT NOT NULL DEFAULT 0,
+  updated_at           TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
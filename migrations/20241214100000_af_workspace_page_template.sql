@@ -0,0 +1,15 @@
+-- The pages of a workspace that new pages can be created from. The content of a template is the
+-- encoded collab of a document, whose object id is the id of the template.
+CREATE TABLE IF NOT EXISTS af_workspace_page_template (
+    template_id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    description TEXT,
+    encoded_collab_v1 BYTEA NOT NULL,
+    created_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_workspace_page_template_workspace_id
+    ON af_workspace_page_template (workspace_id);
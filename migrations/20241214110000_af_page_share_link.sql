@@ -0,0 +1,17 @@
+-- Links sharing a single page of a workspace with anyone who has the link, without publishing it.
+-- The link grants reading the page. The token is the secret part of the link.
+CREATE TABLE IF NOT EXISTS af_page_share_link (
+    link_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    token UUID NOT NULL DEFAULT gen_random_uuid(),
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    view_id UUID NOT NULL,
+    created_by BIGINT REFERENCES af_user(uid) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- The link can't be used anymore after this time, it never expires if NULL
+    expires_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_page_share_link_token
+    ON af_page_share_link (token);
+CREATE INDEX IF NOT EXISTS idx_af_page_share_link_view_id
+    ON af_page_share_link (workspace_id, view_id);
@@ -0,0 +1,14 @@
+-- The devices each user has connected from, with the realtime websocket or the realtime message
+-- stream. A revoked device can't connect again with the access tokens issued before its revocation,
+-- the user has to sign in again on the device.
+CREATE TABLE IF NOT EXISTS af_user_device (
+  uid            BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  device_id      TEXT NOT NULL,
+  -- The platform reported by the client, e.g. 'macos' or 'android', if any
+  platform       TEXT,
+  client_version TEXT,
+  first_seen_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_seen_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  revoked_at     TIMESTAMP WITH TIME ZONE,
+  PRIMARY KEY (uid, device_id)
+);
@@ -0,0 +1,18 @@
+-- The personal access tokens of the users, for the scripts and the integrations that can't refresh
+-- a gotrue session. Only the SHA-256 of each token is stored, the token is shown once when issued.
+CREATE TABLE IF NOT EXISTS af_user_access_token (
+  id            UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  uid           BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  name          TEXT NOT NULL,
+  token_hash    TEXT NOT NULL UNIQUE,
+  -- A read-only token can only be used for the requests that don't change anything
+  read_only     BOOLEAN NOT NULL DEFAULT TRUE,
+  -- The workspaces the token is limited to, all the workspaces of the user if NULL
+  workspace_ids UUID[],
+  created_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  expires_at    TIMESTAMP WITH TIME ZONE,
+  last_used_at  TIMESTAMP WITH TIME ZONE,
+  revoked_at    TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_user_access_token_uid ON af_user_access_token (uid);
@@ -0,0 +1,30 @@
+-- Named groups of workspace members, which the permissions of the views can be granted to.
+CREATE TABLE IF NOT EXISTS af_workspace_member_group (
+  group_id     UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  name         TEXT NOT NULL,
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (workspace_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS af_workspace_member_group_member (
+  group_id UUID NOT NULL REFERENCES af_workspace_member_group(group_id) ON DELETE CASCADE,
+  uid      BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+  PRIMARY KEY (group_id, uid)
+);
+
+-- The views whose access is restricted to some members or groups. A view without any row is
+-- accessible to every member of its workspace, as granted by their role. The owners of the
+-- workspace always have access to the restricted views.
+CREATE TABLE IF NOT EXISTS af_view_permission (
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  view_id      TEXT NOT NULL,
+  -- Exactly one of the member or the group is set
+  uid          BIGINT REFERENCES af_user(uid) ON DELETE CASCADE,
+  group_id     UUID REFERENCES af_workspace_member_group(group_id) ON DELETE CASCADE,
+  -- The highest access level granted, see AFAccessLevel
+  access_level INT NOT NULL,
+  CHECK ((uid IS NULL) <> (group_id IS NULL))
+);
+CREATE INDEX IF NOT EXISTS idx_af_view_permission_workspace_id ON af_view_permission (workspace_id);
+CREATE INDEX IF NOT EXISTS idx_af_view_permission_view_id ON af_view_permission (view_id);
@@ -0,0 +1,22 @@
+-- Roles defined by the owners of a workspace, granting capabilities to the members they're
+-- assigned to, on top of their owner, member or guest role.
+CREATE TABLE IF NOT EXISTS af_workspace_custom_role (
+  role_id      UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  name         TEXT NOT NULL,
+  -- See WorkspaceCapability, e.g. 'invite' or 'delete_collab'
+  capabilities TEXT[] NOT NULL DEFAULT '{}',
+  created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (workspace_id, name)
+);
+
+-- A member has at most one custom role in a workspace. The assignment is removed along with the
+-- member.
+CREATE TABLE IF NOT EXISTS af_workspace_member_custom_role (
+  uid          BIGINT NOT NULL,
+  workspace_id UUID NOT NULL,
+  role_id      UUID NOT NULL REFERENCES af_workspace_custom_role(role_id) ON DELETE CASCADE,
+  PRIMARY KEY (uid, workspace_id),
+  FOREIGN KEY (uid, workspace_id) REFERENCES af_workspace_member(uid, workspace_id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_af_workspace_member_custom_role_role_id ON af_workspace_member_custom_role (role_id);
@@ -0,0 +1,36 @@
+-- append-only log of the permission changes of a workspace: the members added to, updated in or
+-- removed from the workspace or its collabs
+CREATE TABLE IF NOT EXISTS af_permission_audit_log (
+  event_id      BIGSERIAL PRIMARY KEY,
+  workspace_id  UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  -- the user who made the change, NULL if it was made by the server, e.g. an expired grant
+  actor_uid     BIGINT,
+  -- the user whose permission changed
+  target_uid    BIGINT NOT NULL,
+  -- 'workspace' or 'collab'
+  object_type   TEXT NOT NULL,
+  -- the id of the workspace or of the collab
+  object_id     TEXT NOT NULL,
+  -- the role id for a workspace, the access level for a collab. NULL when the user had no
+  -- permission before the change, or has none after it.
+  old_role      INT,
+  new_role      INT,
+  created_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_created_at_on_af_permission_audit_log
+  ON af_permission_audit_log(workspace_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workspace_id_target_uid_on_af_permission_audit_log
+  ON af_permission_audit_log(workspace_id, target_uid, created_at DESC);
+
+-- the events can't be changed or deleted, except along with their workspace
+CREATE OR REPLACE FUNCTION prevent_change_af_permission_audit_log_func() RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP = 'DELETE' AND pg_trigger_depth() > 1 THEN
+    RETURN OLD;
+  END IF;
+  RAISE EXCEPTION 'The permission audit log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+CREATE OR REPLACE TRIGGER trigger_prevent_change_af_permission_audit_log
+  BEFORE UPDATE OR DELETE ON af_permission_audit_log
+  FOR EACH ROW EXECUTE FUNCTION prevent_change_af_permission_audit_log_func();
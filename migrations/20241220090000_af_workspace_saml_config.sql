@@ -0,0 +1,12 @@
+-- The SAML identity provider the members of a workspace can sign in with. AppFlowy is the service
+-- provider, its metadata and assertion consumer service are served for each workspace.
+CREATE TABLE IF NOT EXISTS af_workspace_saml_config (
+  workspace_id     UUID PRIMARY KEY REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+  -- The metadata XML of the identity provider, with its entity id, sign on URL and certificates
+  idp_metadata_xml TEXT NOT NULL,
+  -- The attribute of the assertions holding the email of the user, the name id if NULL
+  email_attribute  TEXT,
+  enabled          BOOLEAN NOT NULL DEFAULT TRUE,
+  created_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at       TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
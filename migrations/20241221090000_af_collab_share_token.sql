@@ -0,0 +1,17 @@
+-- The share tokens issued for single collabs. A token is only accepted while its row exists, so
+-- deleting the row revokes the token before it expires.
+CREATE TABLE IF NOT EXISTS af_collab_share_token (
+    token_id UUID PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    object_id TEXT NOT NULL,
+    issued_by BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_share_token_workspace_id
+    ON af_collab_share_token (workspace_id, issued_by);
+
+-- The links of a workspace are listed and revoked per creator.
+CREATE INDEX IF NOT EXISTS idx_af_page_share_link_created_by
+    ON af_page_share_link (workspace_id, created_by);
@@ -0,0 +1,23 @@
+-- The email domains of a workspace. A domain is verified once the workspace proves it owns it,
+-- with a DNS TXT record holding the verification token.
+CREATE TABLE IF NOT EXISTS af_workspace_domain (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    domain TEXT NOT NULL,
+    verification_token TEXT NOT NULL,
+    verified_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, domain)
+);
+
+-- A domain can only be verified by a single workspace
+CREATE UNIQUE INDEX IF NOT EXISTS idx_af_workspace_domain_verified
+    ON af_workspace_domain (domain) WHERE verified_at IS NOT NULL;
+
+-- The users created by the SAML identity provider of a workspace, which are the only users it
+-- can sign in.
+CREATE TABLE IF NOT EXISTS af_workspace_saml_user (
+    workspace_id UUID NOT NULL REFERENCES af_workspace(workspace_id) ON DELETE CASCADE,
+    uid BIGINT NOT NULL REFERENCES af_user(uid) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (workspace_id, uid)
+);
@@ -0,0 +1,67 @@
+-- The workspace a collab member is granted access in, so that the grants of a user can be
+-- listed per workspace. The views of a database have no collab of their own, the workspace
+-- can't be found through af_collab.
+ALTER TABLE af_collab_member
+    ADD COLUMN IF NOT EXISTS workspace_id UUID REFERENCES af_workspace(workspace_id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_af_collab_member_uid_workspace_id
+    ON af_collab_member (uid, workspace_id);
+
+-- The workspace members are granted access to the folder collab, whose oid is the workspace id
+UPDATE af_collab_member
+SET workspace_id = af_workspace.workspace_id
+FROM af_workspace
+WHERE af_collab_member.workspace_id IS NULL
+  AND af_collab_member.oid = af_workspace.workspace_id::TEXT;
+
+UPDATE af_collab_member
+SET workspace_id = af_collab.workspace_id
+FROM af_collab
+WHERE af_collab_member.workspace_id IS NULL
+  AND af_collab_member.oid = af_collab.oid;
+
+-- The remaining grants belong to the only workspace of the user, if the user has a single one
+UPDATE af_collab_member
+SET workspace_id = single_workspace.workspace_id
+FROM (
+    SELECT uid, MIN(workspace_id::TEXT)::UUID AS workspace_id
+    FROM af_workspace_member
+    GROUP BY uid
+    HAVING COUNT(*) = 1
+) AS single_workspace
+WHERE af_collab_member.workspace_id IS NULL
+  AND af_collab_member.uid = single_workspace.uid;
+
+CREATE OR REPLACE FUNCTION add_to_af_workspace_member()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.status = 1 THEN
+    -- workspace permission
+    INSERT INTO af_workspace_member (workspace_id, uid, role_id)
+    VALUES (
+      NEW.workspace_id,
+      (SELECT uid FROM af_user WHERE email = NEW.invitee_email),
+      NEW.role_id
+    )
+    ON CONFLICT (workspace_id, uid) DO NOTHING;
+
+    -- collab permission, guests (role_id = 3) are excluded
+    IF NEW.role_id <> 3 THEN
+      INSERT INTO af_collab_member (uid, oid, permission_id, workspace_id)
+      VALUES (
+        (SELECT uid FROM af_user WHERE email = NEW.invitee_email),
+        NEW.workspace_id,
+        (SELECT permission_id
+         FROM public.af_role_permissions
+         WHERE public.af_role_permissions.role_id = NEW.role_id),
+        NEW.workspace_id
+      )
+      ON CONFLICT (uid, oid)
+      DO UPDATE
+        SET permission_id = excluded.permission_id,
+            workspace_id = excluded.workspace_id;
+    END IF;
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -11,12 +11,14 @@ use anyhow::anyhow;
 use async_trait::async_trait;
 use bytes::Bytes;
 use collab_rt_entity::user::RealtimeUser;
-use collab_rt_entity::SystemMessage;
+use collab_rt_entity::{ClientCollabMessage, SystemMessage};
+use dashmap::DashSet;
 use governor::clock::DefaultClock;
 use governor::middleware::NoOpMiddleware;
 use governor::state::{InMemoryState, NotKeyed};
 use governor::{Quota, RateLimiter};
 use semver::Version;
+use std::collections::HashSet;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -44,10 +46,14 @@ pub struct RealtimeClient<S: RealtimeServer> {
   #[allow(dead_code)]
   /// Indicates the version of the client, used for compatibility checks with the server.
   client_version: Version,
+  /// The negotiated version of the realtime message format. The messages of the clients speaking
+  /// an older version are translated when they are received and sent.
+  protocol_version: u32,
   /// To prevent overwhelming the server with too many messages at once, each client has a rate-limiting
   /// mechanism. This limits the number of messages a client can send per second, ensuring the server's
   /// mailbox does not get full from receiving too many messages at the same time.
   binary_rate_limiter: Arc<BinaryRateLimiter>,
+  blocked_workspaces: BlockedWorkspaces,
 }
 
 impl<S> RealtimeClient<S>
@@ -60,6 +66,7 @@ where
     heartbeat_interval: Duration,
     client_timeout: Duration,
     client_version: Version,
+    protocol_version: u32,
     external_source: mpsc::Receiver<RealtimeMessage>,
     rate_limit_times_per_sec: u32,
   ) -> Self {
@@ -72,10 +79,17 @@ where
       client_timeout,
       external_source: Some(external_source),
       client_version,
+      protocol_version,
       binary_rate_limiter: Arc::new(rate_limiter),
+      blocked_workspaces: BlockedWorkspaces::default(),
     }
   }
 
+  pub fn with_blocked_workspaces(mut self, blocked_workspaces: BlockedWorkspaces) -> Self {
+    self.blocked_workspaces = blocked_workspaces;
+    self
+  }
+
   fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
     ctx.run_interval(self.heartbeat_interval, move |act, ctx| {
       if Instant::now().duration_since(act.hb) > act.client_timeout {
@@ -123,10 +137,23 @@ where
     }
     let server = self.server.clone();
     let user = self.user.clone();
+    let protocol_version = self.protocol_version;
+    let blocked_workspaces = self.blocked_workspaces.clone();
 
     let fut = async move {
-      match tokio::task::spawn_blocking(move || RealtimeMessage::decode(&bytes)).await {
+      match tokio::task::spawn_blocking(move || {
+        RealtimeMessage::decode(&bytes)?.upgrade_from(protocol_version)
+      })
+      .await
+      {
         Ok(Ok(decoded_message)) => {
+          let decoded_message = match blocked_workspaces.filter(decoded_message) {
+            Some(decoded_message) => decoded_message,
+            None => {
+              trace!("Drop the messages of the blocked workspaces from {}", user);
+              return Ok(());
+            },
+          };
           let mut client_message = Some(ClientMessage {
             user,
             message: decoded_message,
@@ -272,15 +299,22 @@ where
   type Result = ();
 
   fn handle(&mut self, message: RealtimeMessage, ctx: &mut Self::Context) {
-    match message.encode() {
-      Ok(data) => ctx.binary(Bytes::from(data)),
-      Err(err) => error!("Error encoding message: {}", err),
+    let close_description = match &message {
+      RealtimeMessage::System(SystemMessage::DuplicateConnection) => Some("Duplicate connection"),
+      RealtimeMessage::System(SystemMessage::KickOff) => Some("Kicked off"),
+      _ => None,
+    };
+    for message in message.downgrade_to(self.protocol_version) {
+      match message.encode() {
+        Ok(data) => ctx.binary(Bytes::from(data)),
+        Err(err) => error!("Error encoding message: {}", err),
+      }
     }
 
-    if let RealtimeMessage::System(SystemMessage::DuplicateConnection) = &message {
+    if let Some(description) = close_description {
       let reason = CloseReason {
         code: CloseCode::Normal,
-        description: Some("Duplicate connection".to_string()),
+        description: Some(description.to_string()),
       };
       ctx.close(Some(reason));
     }
@@ -312,6 +346,68 @@ where
   }
 }
 
+/// The workspaces a client can't sync the collabs of, e.g. because its address is outside their
+/// IP allowlist. Only the init sync of a collab tells its workspace, so the other messages of a
+/// collab are dropped until its sync is initialized in a workspace that isn't blocked.
+#[derive(Clone, Default)]
+pub struct BlockedWorkspaces {
+  workspace_ids: Arc<HashSet<String>>,
+  allowed_object_ids: Arc<DashSet<String>>,
+}
+
+impl BlockedWorkspaces {
+  pub fn new(workspace_ids: HashSet<String>) -> Self {
+    Self {
+      workspace_ids: Arc::new(workspace_ids),
+      allowed_object_ids: Default::default(),
+    }
+  }
+
+  pub fn is_blocked(&self, workspace_id: &str) -> bool {
+    self.workspace_ids.contains(workspace_id)
+  }
+
+  /// Removes the messages of the blocked workspaces, returns `None` if none is left.
+  pub fn filter(&self, message: RealtimeMessage) -> Option<RealtimeMessage> {
+    if self.workspace_ids.is_empty() {
+      return Some(message);
+    }
+    match message {
+      RealtimeMessage::RowEditIntent(intent) => {
+        (!self.workspace_ids.contains(&intent.workspace_id))
+          .then_some(RealtimeMessage::RowEditIntent(intent))
+      },
+      RealtimeMessage::Collab(_)
+      | RealtimeMessage::ClientCollabV1(_)
+      | RealtimeMessage::ClientCollabV2(_) => {
+        let mut message_by_object_id = message.transform().ok()?;
+        message_by_object_id.retain(|object_id, messages| {
+          messages.retain(|message| self.is_allowed(object_id, message));
+          !messages.is_empty()
+        });
+        (!message_by_object_id.is_empty())
+          .then_some(RealtimeMessage::ClientCollabV2(message_by_object_id))
+      },
+      message => Some(message),
+    }
+  }
+
+  fn is_allowed(&self, object_id: &str, message: &ClientCollabMessage) -> bool {
+    match message {
+      ClientCollabMessage::ClientInitSync { data } => {
+        if self.workspace_ids.contains(&data.workspace_id) {
+          self.allowed_object_ids.remove(object_id);
+          false
+        } else {
+          self.allowed_object_ids.insert(object_id.to_string());
+          true
+        }
+      },
+      _ => self.allowed_object_ids.contains(object_id),
+    }
+  }
+}
+
 #[derive(Clone)]
 pub struct RealtimeClientWebsocketSinkImpl(pub Recipient<RealtimeMessage>);
 
@@ -334,9 +430,16 @@ fn gen_rate_limiter(
 
 #[cfg(test)]
 mod tests {
+  use std::collections::HashSet;
   use std::time::Duration;
+
+  use collab::core::origin::CollabOrigin;
+  use collab_entity::CollabType;
+  use collab_rt_entity::{ClientCollabMessage, InitSync, RealtimeMessage, UpdateSync};
   use tokio::time::sleep;
 
+  use super::BlockedWorkspaces;
+
   #[tokio::test]
   async fn rate_limit_test() {
     let rate_limiter = super::gen_rate_limiter(10);
@@ -359,4 +462,44 @@ mod tests {
       }
     }
   }
+
+  #[test]
+  fn blocked_workspaces_filter_test() {
+    let blocked = BlockedWorkspaces::new(HashSet::from(["blocked".to_string()]));
+    let init_sync = |object_id: &str, workspace_id: &str| {
+      ClientCollabMessage::new_init_sync(InitSync::new(
+        CollabOrigin::Empty,
+        object_id.to_string(),
+        CollabType::Document,
+        workspace_id.to_string(),
+        1,
+        vec![],
+      ))
+    };
+    let update_sync = |object_id: &str| {
+      ClientCollabMessage::new_update_sync(UpdateSync::new(
+        CollabOrigin::Empty,
+        object_id.to_string(),
+        vec![1],
+        2,
+      ))
+    };
+
+    // the updates of a collab whose sync isn't initialized are dropped
+    let message = RealtimeMessage::ClientCollabV1(vec![update_sync("a"), update_sync("b")]);
+    assert!(blocked.filter(message).is_none());
+
+    let message =
+      RealtimeMessage::ClientCollabV1(vec![init_sync("a", "allowed"), init_sync("b", "blocked")]);
+    let filtered = blocked.filter(message).unwrap().transform().unwrap();
+    assert_eq!(filtered.keys().collect::<Vec<_>>(), vec!["a"]);
+
+    let message = RealtimeMessage::ClientCollabV1(vec![update_sync("a"), update_sync("b")]);
+    let filtered = blocked.filter(message).unwrap().transform().unwrap();
+    assert_eq!(filtered.keys().collect::<Vec<_>>(), vec!["a"]);
+
+    // nothing is filtered when no workspace is blocked
+    let message = RealtimeMessage::ClientCollabV1(vec![update_sync("b")]);
+    assert!(BlockedWorkspaces::default().filter(message).is_some());
+  }
 }
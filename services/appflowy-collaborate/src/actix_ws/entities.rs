@@ -6,6 +6,7 @@ use std::fmt::Debug;
 
 use collab_rt_entity::user::RealtimeUser;
 pub use collab_rt_entity::RealtimeMessage;
+use database_entity::dto::WorkspaceRealtimeStats;
 
 #[derive(Debug, Message, Clone)]
 #[rtype(result = "Result<(), RealtimeError>")]
@@ -43,3 +44,18 @@ pub struct ClientStreamMessage {
   pub device_id: String,
   pub message: RealtimeMessage,
 }
+
+/// Closes the connection of the device of the user, if it's connected to this node.
+#[derive(Debug, Message, Clone)]
+#[rtype(result = "Result<(), RealtimeError>")]
+pub struct KickOffDevice {
+  pub uid: i64,
+  pub device_id: String,
+}
+
+/// Asks for the realtime activity of the workspace on this node.
+#[derive(Message)]
+#[rtype(result = "WorkspaceRealtimeStats")]
+pub struct GetWorkspaceRealtimeStats {
+  pub workspace_id: String,
+}
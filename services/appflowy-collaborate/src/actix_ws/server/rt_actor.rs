@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use actix::{Actor, Context, Handler, ResponseFuture};
+use actix::{Actor, Context, Handler, MessageResult, ResponseFuture};
 use tracing::{error, info, warn};
 
 use crate::error::RealtimeError;
@@ -9,7 +9,10 @@ use collab_rt_entity::user::UserDevice;
 use database::collab::CollabStorage;
 
 use crate::actix_ws::client::rt_client::{RealtimeClientWebsocketSinkImpl, RealtimeServer};
-use crate::actix_ws::entities::{ClientMessage, ClientStreamMessage, Connect, Disconnect};
+use crate::actix_ws::entities::{
+  ClientMessage, ClientStreamMessage, Connect, Disconnect, GetWorkspaceRealtimeStats,
+  KickOffDevice, RealtimeMessage,
+};
 
 #[derive(Clone)]
 pub struct RealtimeServerActor<S>(pub CollaborationServer<S>);
@@ -81,6 +84,16 @@ where
   }
 }
 
+impl<S> Handler<KickOffDevice> for RealtimeServerActor<S>
+where
+  S: CollabStorage + Unpin,
+{
+  type Result = ResponseFuture<anyhow::Result<(), RealtimeError>>;
+  fn handle(&mut self, msg: KickOffDevice, _: &mut Context<Self>) -> Self::Result {
+    self.handle_kick_off_device(UserDevice::new(&msg.device_id, msg.uid))
+  }
+}
+
 impl<S> Handler<ClientMessage> for RealtimeServerActor<S>
 where
   S: CollabStorage + Unpin,
@@ -89,6 +102,10 @@ where
 
   fn handle(&mut self, client_msg: ClientMessage, _ctx: &mut Context<Self>) -> Self::Result {
     let ClientMessage { user, message } = client_msg;
+    if let RealtimeMessage::RowEditIntent(intent) = message {
+      return self.handle_row_edit_intent(user, intent);
+    }
+
     match message.transform() {
       Ok(message_by_object_id) => self.handle_client_message(user, message_by_object_id),
       Err(err) => {
@@ -132,3 +149,14 @@ where
     }
   }
 }
+
+impl<S> Handler<GetWorkspaceRealtimeStats> for RealtimeServerActor<S>
+where
+  S: CollabStorage + Unpin,
+{
+  type Result = MessageResult<GetWorkspaceRealtimeStats>;
+
+  fn handle(&mut self, msg: GetWorkspaceRealtimeStats, _: &mut Context<Self>) -> Self::Result {
+    MessageResult(self.get_workspace_realtime_stats(&msg.workspace_id))
+  }
+}
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use actix::Addr;
-use actix_http::header::{HeaderMap, AUTHORIZATION};
+use actix_http::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
 use actix_web::web::{Data, Json, Payload, PayloadConfig};
 use actix_web::{web, HttpRequest, HttpResponse, Result, Scope};
 use actix_web_actors::ws;
@@ -20,7 +20,10 @@ use tracing::{debug, error, event, instrument, trace};
 use app_error::AppError;
 use authentication::jwt::{authorization_from_token, UserUuid};
 use collab_rt_entity::user::{AFUserChange, RealtimeUser, UserMessage};
-use collab_rt_entity::{HttpRealtimeMessage, RealtimeMessage};
+use collab_rt_entity::{
+  negotiate_realtime_protocol_version, HttpRealtimeMessage, RealtimeMessage,
+  LEGACY_REALTIME_PROTOCOL_VERSION, REALTIME_PROTOCOL_VERSION_HEADER,
+};
 use shared_entity::response::{AppResponse, AppResponseError};
 
 use crate::actix_ws::client::RealtimeClient;
@@ -66,6 +69,7 @@ pub async fn establish_ws_connection_v1(
     client_version,
     device_id,
     connect_at,
+    protocol_version,
   } = match ConnectInfo::parse_from(&request) {
     Ok(info) => info,
     Err(_) => {
@@ -78,6 +82,9 @@ pub async fn establish_ws_connection_v1(
     return Err(AppError::Connect("Client version is too low".to_string()).into());
   }
 
+  let protocol_version = negotiate_realtime_protocol_version(protocol_version)
+    .map_err(|err| AppError::Connect(err.to_string()))?;
+
   start_connect(
     &request,
     payload,
@@ -88,6 +95,7 @@ pub async fn establish_ws_connection_v1(
     device_id,
     client_version,
     connect_at,
+    protocol_version,
   )
   .await
 }
@@ -196,11 +204,12 @@ async fn parser_realtime_msg(
   payload: Bytes,
   req: HttpRequest,
 ) -> Result<RealtimeMessage, AppError> {
-  let HttpRealtimeMessage {
-    device_id: _,
-    payload,
-  } =
+  let http_message =
     HttpRealtimeMessage::decode(payload.as_ref()).map_err(|err| AppError::Internal(err.into()))?;
+  let protocol_version = http_message
+    .negotiate_protocol_version()
+    .map_err(|err| AppError::InvalidRequest(err.to_string()))?;
+  let payload = http_message.payload;
   let payload = match req.headers().get(X_COMPRESSION_TYPE) {
     None => payload,
     Some(_) => match compress_type_from_header_value(req.headers())? {
@@ -217,6 +226,7 @@ async fn parser_realtime_msg(
   };
   let realtime_msg = tokio::task::spawn_blocking(move || {
     RealtimeMessage::decode(&payload)
+      .and_then(|message| message.upgrade_from(protocol_version))
       .map_err(|err| AppError::InvalidRequest(format!("Failed to parse RealtimeMessage: {}", err)))
   })
   .await
@@ -236,6 +246,7 @@ async fn start_connect(
   device_id: String,
   client_app_version: Version,
   connect_at: i64,
+  protocol_version: u32,
 ) -> Result<HttpResponse> {
   let auth = authorization_from_token(access_token.as_str(), jwt_secret)?;
   let user_uuid = UserUuid::from_auth(auth)?;
@@ -263,6 +274,7 @@ async fn start_connect(
         Duration::from_secs(state.config.websocket.heartbeat_interval as u64),
         Duration::from_secs(state.config.websocket.client_timeout as u64),
         client_app_version,
+        protocol_version,
         external_source,
         10,
       );
@@ -274,7 +286,14 @@ async fn start_connect(
         .frame_size(MAX_FRAME_SIZE * 2)
         .start()
       {
-        Ok(response) => Ok(response),
+        Ok(mut response) => {
+          // Tells the client which version of the realtime message format the server speaks.
+          response.headers_mut().insert(
+            HeaderName::from_static(REALTIME_PROTOCOL_VERSION_HEADER),
+            HeaderValue::from(protocol_version),
+          );
+          Ok(response)
+        },
         Err(e) => {
           error!("🔴ws connection error: {:?}", e);
           Err(e)
@@ -321,6 +340,7 @@ struct ConnectInfo {
   client_version: Version,
   device_id: String,
   connect_at: i64,
+  protocol_version: u32,
 }
 
 const CLIENT_VERSION: &str = "client-version";
@@ -374,12 +394,19 @@ impl ConnectInfo {
         .unwrap_or_else(|_| chrono::Utc::now().timestamp()),
       Err(_) => chrono::Utc::now().timestamp(),
     };
+    let protocol_version = match source.extract_param(REALTIME_PROTOCOL_VERSION_HEADER) {
+      Ok(version) => version.parse::<u32>().map_err(|_| {
+        AppError::InvalidRequest(format!("Invalid realtime protocol version:{}", version))
+      })?,
+      Err(_) => LEGACY_REALTIME_PROTOCOL_VERSION,
+    };
 
     Ok(Self {
       access_token,
       client_version,
       device_id,
       connect_at,
+      protocol_version,
     })
   }
 }
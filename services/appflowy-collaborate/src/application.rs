@@ -28,6 +28,7 @@ use crate::command::{CLCommandReceiver, CLCommandSender};
 use crate::config::{Config, DatabaseSetting};
 use crate::indexer::IndexerProvider;
 use crate::pg_listener::PgListeners;
+use crate::row_edit_intent::RowEditIntents;
 use crate::snapshot::SnapshotControl;
 use crate::state::{AppMetrics, AppState, UserCache};
 use crate::CollaborationServer;
@@ -79,6 +80,9 @@ pub async fn run_actix_server(
     config.collab.edit_state_max_count,
     config.collab.edit_state_max_secs,
     state.indexer_provider.clone(),
+    RowEditIntents::new(),
+    (config.collab.group_hibernate_after_secs > 0)
+      .then(|| Duration::from_secs(config.collab.group_hibernate_after_secs)),
   )
   .await
   .unwrap();
@@ -133,7 +137,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   };
   let snapshot_control = SnapshotControl::new(
     redis_conn_manager.clone(),
-    pg_pool.clone(),
+    collab_cache.router().clone(),
     metrics.collab_metrics.clone(),
   )
   .await;
@@ -9,11 +9,12 @@ use collab_rt_entity::ClientCollabMessage;
 use database::collab::cache::CollabCache;
 use itertools::{Either, Itertools};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use sqlx::Transaction;
+use sqlx::{PgPool, Transaction};
 
 use tokio::time::timeout;
 use tracing::warn;
 use tracing::{error, instrument, trace};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::command::{CLCommandSender, CollaborationCommand};
@@ -75,6 +76,13 @@ where
     }
   }
 
+  pub async fn remove_pending_snapshot(&self, object_id: &str) -> Result<(), AppError> {
+    self
+      .snapshot_control
+      .remove_pending_snapshot(object_id)
+      .await
+  }
+
   async fn check_write_workspace_permission(
     &self,
     workspace_id: &str,
@@ -205,9 +213,57 @@ where
     uid: &i64,
     params_list: Vec<CollabParams>,
   ) -> Result<(), AppError> {
-    let mut transaction = self.cache.pg_pool().begin().await?;
-    insert_into_af_collab_bulk_for_user(&mut transaction, uid, workspace_id, &params_list).await?;
-    transaction.commit().await?;
+    let workspace_uuid = Uuid::parse_str(workspace_id)?;
+    let router = self.cache.router();
+    match router.regional_pool(&workspace_uuid).await? {
+      None if router.has_collab_shards() => {
+        // Group the collabs by shard, the placements are recorded in the default database
+        let mut default_transaction = router.default_pool().begin().await?;
+        let mut default_params = vec![];
+        let mut shard_params: HashMap<String, (PgPool, Vec<CollabParams>)> = HashMap::new();
+        for params in &params_list {
+          match router
+            .collab_shard_for_write(&mut default_transaction, &workspace_uuid, &params.object_id)
+            .await?
+          {
+            None => default_params.push(params.clone()),
+            Some((shard, shard_pool)) => {
+              shard_params
+                .entry(shard)
+                .or_insert_with(|| (shard_pool, vec![]))
+                .1
+                .push(params.clone());
+            },
+          }
+        }
+        for (shard_pool, params_list) in shard_params.into_values() {
+          let mut shard_transaction = shard_pool.begin().await?;
+          insert_into_af_collab_bulk_for_user(
+            &mut shard_transaction,
+            uid,
+            workspace_id,
+            &params_list,
+          )
+          .await?;
+          shard_transaction.commit().await?;
+        }
+        insert_into_af_collab_bulk_for_user(
+          &mut default_transaction,
+          uid,
+          workspace_id,
+          &default_params,
+        )
+        .await?;
+        default_transaction.commit().await?;
+      },
+      regional_pool => {
+        let pg_pool = regional_pool.unwrap_or_else(|| router.default_pool().clone());
+        let mut transaction = pg_pool.begin().await?;
+        insert_into_af_collab_bulk_for_user(&mut transaction, uid, workspace_id, &params_list)
+          .await?;
+        transaction.commit().await?;
+      },
+    }
 
     // update the mem cache without blocking the current task
     let cache = self.cache.clone();
@@ -230,6 +286,10 @@ where
     (state.total_attempts, state.success_attempts)
   }
 
+  fn collab_rehydration_state(&self) -> (u64, u64) {
+    self.cache.rehydration_state()
+  }
+
   async fn queue_insert_or_update_collab(
     &self,
     workspace_id: &str,
@@ -116,6 +116,9 @@ pub struct CollabSetting {
   pub group_persistence_interval_secs: u64,
   pub edit_state_max_count: u32,
   pub edit_state_max_secs: i64,
+  /// The collabs without connected client are unloaded from memory after this many seconds. Set
+  /// to 0 to only unload them after the inactivity timeout of their collab type.
+  pub group_hibernate_after_secs: u64,
 }
 
 pub fn get_env_var(key: &str, default: &str) -> String {
@@ -166,6 +169,8 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       .parse()?,
       edit_state_max_count: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_COUNT", "100").parse()?,
       edit_state_max_secs: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_SECS", "60").parse()?,
+      group_hibernate_after_secs: get_env_var("APPFLOWY_COLLAB_GROUP_HIBERNATE_AFTER_SECS", "0")
+        .parse()?,
     },
     redis_uri: get_env_var("APPFLOWY_REDIS_URI", "redis://localhost:6379").into(),
     ai: AISettings {
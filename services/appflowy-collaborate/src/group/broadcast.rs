@@ -32,6 +32,10 @@ use crate::metrics::CollabRealtimeMetrics;
 
 pub trait CollabUpdateStreaming: 'static + Send + Sync {
   fn send_update(&self, update: Vec<u8>) -> Result<(), RealtimeError>;
+
+  /// Sends the awareness state of all the clients, which is kept briefly so the clients that
+  /// connect later get the presence of their peers right away.
+  fn send_awareness_snapshot(&self, awareness_update: Vec<u8>);
 }
 /// A broadcast can be used to propagate updates produced by yrs [yrs::Doc] and [Awareness]
 /// to subscribes. One broadcast can be used to propagate updates for a single document with
@@ -130,6 +134,7 @@ impl CollabBroadcast {
 
       let broadcast_sink = self.broadcast_sender.clone();
       let cloned_oid = self.object_id.clone();
+      let update_streaming = self.update_streaming.clone();
 
       // Observer the awareness's update and broadcast it to all subscribers.
       let awareness_sub = collab
@@ -142,6 +147,9 @@ impl CollabBroadcast {
               trace!("fail to broadcast awareness:{}", err);
             }
           }
+          if let Ok(awareness_snapshot) = awareness.update() {
+            update_streaming.send_awareness_snapshot(awareness_snapshot.encode_v1());
+          }
         });
       (doc_sub, awareness_sub)
     };
@@ -154,6 +154,10 @@ where
         let first_message = messages.first().unwrap();
         self.subscribe_group(user, first_message).await?;
       }
+      self
+        .group_manager
+        .record_client_messages(&object_id, messages.len())
+        .await;
       forward_message_to_group(user, object_id, messages, &self.msg_router_by_user).await;
     } else {
       let first_message = messages.first().unwrap();
@@ -162,6 +166,10 @@ where
       if first_message.is_client_init_sync() {
         self.create_group(user, first_message).await?;
         self.subscribe_group(user, first_message).await?;
+        self
+          .group_manager
+          .record_client_messages(&object_id, messages.len())
+          .await;
         forward_message_to_group(user, object_id, messages, &self.msg_router_by_user).await;
       } else if let Some(entry) = self.msg_router_by_user.get(user) {
         warn!(
@@ -4,6 +4,7 @@ use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use collab::core::awareness::AwarenessUpdate;
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncodedCollab;
 use collab::lock::RwLock;
@@ -11,8 +12,9 @@ use collab::preclude::Collab;
 use collab_entity::CollabType;
 use dashmap::DashMap;
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
-use tracing::{error, event, info, trace};
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+use tracing::{error, event, info, trace, warn};
 use yrs::updates::decoder::Decode;
 use yrs::updates::encoder::Encode;
 use yrs::Update;
@@ -20,6 +22,7 @@ use yrs::Update;
 use collab_rt_entity::user::RealtimeUser;
 use collab_rt_entity::CollabMessage;
 use collab_rt_entity::MessageByObjectId;
+use collab_stream::awareness::AwarenessSnapshotStore;
 use collab_stream::client::CollabRedisStream;
 use collab_stream::error::StreamError;
 use collab_stream::model::{CollabUpdateEvent, StreamBinary};
@@ -32,6 +35,8 @@ use crate::group::persistence::GroupPersistence;
 use crate::indexer::Indexer;
 use crate::metrics::CollabRealtimeMetrics;
 
+const AWARENESS_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// A group used to manage a single [Collab] object
 pub struct CollabGroup {
   pub workspace_id: String,
@@ -44,6 +49,7 @@ pub struct CollabGroup {
   /// A list of subscribers to this group. Each subscriber will receive updates from the
   /// broadcast.
   subscribers: DashMap<RealtimeUser, Subscription>,
+  idle_state: GroupIdleState,
   metrics_calculate: Arc<CollabRealtimeMetrics>,
   destroy_group_tx: mpsc::Sender<Arc<RwLock<Collab>>>,
 }
@@ -79,6 +85,7 @@ impl CollabGroup {
       edit_state_max_secs,
       is_new_collab,
     ));
+    restore_awareness_snapshot(&workspace_id, &object_id, &collab, &collab_redis_stream).await;
     let broadcast = {
       let lock = collab.read().await;
       CollabBroadcast::new(
@@ -113,6 +120,7 @@ impl CollabGroup {
       collab,
       broadcast,
       subscribers: Default::default(),
+      idle_state: GroupIdleState::new(),
       metrics_calculate,
       destroy_group_tx,
     })
@@ -137,13 +145,32 @@ impl CollabGroup {
     if let Some((_, mut old_sub)) = self.subscribers.remove(user) {
       trace!("{} remove subscriber from group: {}", self.object_id, user);
       old_sub.stop().await;
+      if self.subscribers.is_empty() {
+        self.idle_state.set_idle();
+      }
     }
   }
 
+  /// Returns true if the group has had no subscriber for at least the given duration.
+  pub fn is_idle_for(&self, duration: Duration) -> bool {
+    self.subscribers.is_empty() && self.idle_state.is_idle_for(duration)
+  }
+
   pub fn user_count(&self) -> usize {
     self.subscribers.len()
   }
 
+  /// The uids of the subscribers, one per device. The server itself subscribes with the uid 0
+  /// when it edits the collab, it's not listed.
+  pub fn subscriber_uids(&self) -> Vec<i64> {
+    self
+      .subscribers
+      .iter()
+      .map(|entry| entry.key().uid)
+      .filter(|uid| *uid != 0)
+      .collect()
+  }
+
   /// Subscribes a new connection to the broadcast group for collaborative activities.
   ///
   pub async fn subscribe<Sink, Stream>(
@@ -171,6 +198,7 @@ impl CollabGroup {
       tracing::warn!("{}: remove old subscriber: {}", &self.object_id, user);
       old.stop().await;
     }
+    self.idle_state.set_active();
 
     if cfg!(debug_assertions) {
       event!(
@@ -382,8 +410,84 @@ impl EditState {
   }
 }
 
+/// Tracks since when a group has had no subscriber. A new group is idle until its first
+/// subscriber joins.
+struct GroupIdleState {
+  /// When the last subscriber left the group, `None` while the group has subscribers.
+  idle_since: parking_lot::Mutex<Option<Instant>>,
+}
+
+impl GroupIdleState {
+  fn new() -> Self {
+    Self {
+      idle_since: parking_lot::Mutex::new(Some(Instant::now())),
+    }
+  }
+
+  fn set_active(&self) {
+    *self.idle_since.lock() = None;
+  }
+
+  /// Keeps the time the group became idle if it already was.
+  fn set_idle(&self) {
+    self.idle_since.lock().get_or_insert_with(Instant::now);
+  }
+
+  fn is_idle_for(&self, duration: Duration) -> bool {
+    match *self.idle_since.lock() {
+      Some(idle_since) => idle_since.elapsed() >= duration,
+      None => false,
+    }
+  }
+}
+
+/// Applies the last-known awareness state of the collab, if it didn't expire, so the clients
+/// connecting to a new group get the presence of their peers without waiting for them to send it
+/// again. It must be called before the awareness is observed, otherwise the stale state would be
+/// saved as a new snapshot.
+async fn restore_awareness_snapshot(
+  workspace_id: &str,
+  object_id: &str,
+  collab: &RwLock<Collab>,
+  collab_redis_stream: &CollabRedisStream,
+) {
+  let snapshot = match collab_redis_stream
+    .awareness_snapshot_store()
+    .get(workspace_id, object_id)
+    .await
+  {
+    Ok(Some(snapshot)) => snapshot,
+    Ok(None) => return,
+    Err(err) => {
+      warn!(
+        "fail to get the awareness snapshot of {}: {}",
+        object_id, err
+      );
+      return;
+    },
+  };
+  let update = match AwarenessUpdate::decode_v1(&snapshot.update) {
+    Ok(update) => update,
+    Err(err) => {
+      warn!(
+        "fail to decode the awareness snapshot of {}: {}",
+        object_id, err
+      );
+      return;
+    },
+  };
+  let lock = collab.write().await;
+  if let Err(err) = lock.get_awareness().apply_update(update) {
+    warn!(
+      "fail to restore the awareness snapshot of {}: {}",
+      object_id, err
+    );
+  }
+}
+
 struct CollabUpdateStreamingImpl {
   sender: mpsc::UnboundedSender<Vec<u8>>,
+  awareness_sender: watch::Sender<Option<Vec<u8>>>,
   stopped: Arc<AtomicBool>,
 }
 
@@ -405,7 +509,41 @@ impl CollabUpdateStreamingImpl {
       }
       cloned_stopped.store(true, Ordering::SeqCst);
     });
-    Ok(Self { sender, stopped })
+
+    let (awareness_sender, awareness_receiver) = watch::channel(None);
+    tokio::spawn(Self::save_awareness_snapshots(
+      awareness_receiver,
+      collab_redis_stream.awareness_snapshot_store(),
+      workspace_id.to_string(),
+      object_id.to_string(),
+    ));
+    Ok(Self {
+      sender,
+      awareness_sender,
+      stopped,
+    })
+  }
+
+  /// Saves the latest awareness state at most once per [AWARENESS_SNAPSHOT_INTERVAL], the
+  /// awareness changes whenever a user moves their cursor. Stops when the group is dropped.
+  async fn save_awareness_snapshots(
+    mut receiver: watch::Receiver<Option<Vec<u8>>>,
+    store: AwarenessSnapshotStore,
+    workspace_id: String,
+    object_id: String,
+  ) {
+    while receiver.changed().await.is_ok() {
+      let update = receiver.borrow_and_update().clone();
+      if let Some(update) = update {
+        if let Err(err) = store.save(&workspace_id, &object_id, update).await {
+          warn!(
+            "fail to save the awareness snapshot of {}: {}",
+            object_id, err
+          );
+        }
+      }
+      tokio::time::sleep(AWARENESS_SNAPSHOT_INTERVAL).await;
+    }
   }
 
   async fn consume_messages(
@@ -456,11 +594,19 @@ impl CollabUpdateStreaming for CollabUpdateStreamingImpl {
       Ok(())
     }
   }
+
+  fn send_awareness_snapshot(&self, awareness_update: Vec<u8>) {
+    // Only the latest state is kept, the previous one is replaced if it wasn't saved yet.
+    let _ = self.awareness_sender.send(Some(awareness_update));
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use crate::group::group_init::EditState;
+  use std::thread::sleep;
+  use std::time::Duration;
+
+  use crate::group::group_init::{EditState, GroupIdleState};
 
   #[test]
   fn edit_state_test() {
@@ -476,4 +622,29 @@ mod tests {
     edit_state.tick();
     assert!(!edit_state.should_save_to_disk());
   }
+
+  #[test]
+  fn idle_group_hibernates_after_period_test() {
+    let hibernate_after = Duration::from_millis(100);
+    let idle_state = GroupIdleState::new();
+    assert!(!idle_state.is_idle_for(hibernate_after));
+    sleep(hibernate_after);
+    assert!(idle_state.is_idle_for(hibernate_after));
+  }
+
+  #[test]
+  fn active_group_does_not_hibernate_test() {
+    let hibernate_after = Duration::from_millis(100);
+    let idle_state = GroupIdleState::new();
+    idle_state.set_active();
+    sleep(hibernate_after);
+    assert!(!idle_state.is_idle_for(hibernate_after));
+
+    // the idle period starts when the last subscriber leaves
+    idle_state.set_idle();
+    assert!(!idle_state.is_idle_for(hibernate_after));
+    sleep(hibernate_after);
+    idle_state.set_idle();
+    assert!(idle_state.is_idle_for(hibernate_after));
+  }
 }
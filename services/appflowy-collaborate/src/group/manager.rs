@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use collab::core::collab::DataSource;
 use collab::core::origin::CollabOrigin;
@@ -17,12 +17,13 @@ use collab_stream::client::{CollabRedisStream, CONTROL_STREAM_KEY};
 use collab_stream::model::CollabControlEvent;
 use collab_stream::stream_group::StreamGroup;
 use database::collab::{CollabStorage, GetCollabOrigin};
-use database_entity::dto::QueryCollabParams;
+use database_entity::dto::{QueryCollabParams, WorkspaceRealtimeStats};
 
 use crate::client::client_msg_router::ClientMessageRouter;
 use crate::error::{CreateGroupFailedReason, RealtimeError};
 use crate::group::group_init::CollabGroup;
 use crate::group::state::GroupManagementState;
+use crate::group::throughput::MessageThroughput;
 use crate::indexer::IndexerProvider;
 use crate::metrics::CollabRealtimeMetrics;
 
@@ -37,6 +38,7 @@ pub struct GroupManager<S> {
   edit_state_max_count: u32,
   edit_state_max_secs: i64,
   indexer_provider: Arc<IndexerProvider>,
+  message_throughput: MessageThroughput,
 }
 
 impl<S> GroupManager<S>
@@ -53,6 +55,7 @@ where
     edit_state_max_count: u32,
     edit_state_max_secs: i64,
     indexer_provider: Arc<IndexerProvider>,
+    hibernate_after: Option<Duration>,
   ) -> Result<Self, RealtimeError> {
     let collab_stream = Arc::new(collab_stream);
     let control_event_stream = collab_stream
@@ -61,7 +64,11 @@ where
       .map_err(|err| RealtimeError::Internal(err.into()))?;
     let control_event_stream = Arc::new(Mutex::from(control_event_stream));
     Ok(Self {
-      state: GroupManagementState::new(metrics_calculate.clone(), control_event_stream.clone()),
+      state: GroupManagementState::new(
+        metrics_calculate.clone(),
+        control_event_stream.clone(),
+        hibernate_after,
+      ),
       storage,
       access_control,
       metrics_calculate,
@@ -71,6 +78,7 @@ where
       edit_state_max_count,
       edit_state_max_secs,
       indexer_provider,
+      message_throughput: MessageThroughput::default(),
     })
   }
 
@@ -94,6 +102,37 @@ where
     self.state.get_group(object_id).await
   }
 
+  /// Counts the messages received from a client for the collab, in the throughput of its
+  /// workspace.
+  pub async fn record_client_messages(&self, object_id: &str, count: usize) {
+    if let Some(group) = self.state.get_group(object_id).await {
+      self.message_throughput.record(&group.workspace_id, count);
+    }
+  }
+
+  pub fn workspace_realtime_stats(&self, workspace_id: &str) -> WorkspaceRealtimeStats {
+    let (connected_users, active_collabs, active_sessions) =
+      self.state.workspace_activity(workspace_id);
+    WorkspaceRealtimeStats {
+      connected_users,
+      active_collabs,
+      active_sessions,
+      messages_per_sec: self.message_throughput.messages_per_sec(workspace_id),
+    }
+  }
+
+  /// Updates the gauges of the realtime activity of this node.
+  pub fn update_activity_metrics(&self) {
+    self
+      .metrics_calculate
+      .active_collab_sessions
+      .set(self.state.session_count() as i64);
+    self
+      .metrics_calculate
+      .messages_per_sec
+      .set(self.message_throughput.total_messages_per_sec());
+  }
+
   #[instrument(skip(self))]
   async fn remove_group(&self, object_id: &str) {
     self.state.remove_group(object_id).await;
@@ -161,7 +200,14 @@ where
       }
     }
 
+    let start = Instant::now();
     let result = load_collab(user.uid, object_id, params, self.storage.clone()).await;
+    if result.is_ok() {
+      self
+        .metrics_calculate
+        .collab_hydration_time
+        .observe(start.elapsed().as_millis() as f64);
+    }
     let (collab, encode_collab) = {
       let (mut collab, encode_collab) = match result {
         Ok(value) => value,
@@ -6,3 +6,4 @@ mod persistence;
 mod plugin;
 pub(crate) mod protocol;
 mod state;
+pub(crate) mod throughput;
@@ -24,6 +24,9 @@ pub(crate) struct GroupManagementState {
   metrics_calculate: Arc<CollabRealtimeMetrics>,
   /// By default, the number of groups to remove in a single batch is 50.
   remove_batch_size: usize,
+  /// The groups without subscriber are unloaded after this duration, whatever their collab type.
+  /// They are loaded again from the storage on the next subscription.
+  hibernate_after: Option<Duration>,
   control_event_stream: Arc<Mutex<StreamGroup>>,
 }
 
@@ -31,6 +34,7 @@ impl GroupManagementState {
   pub(crate) fn new(
     metrics_calculate: Arc<CollabRealtimeMetrics>,
     control_event_stream: Arc<Mutex<StreamGroup>>,
+    hibernate_after: Option<Duration>,
   ) -> Self {
     let remove_batch_size = get_env_var("APPFLOWY_COLLABORATE_REMOVE_BATCH_SIZE", "50")
       .parse::<usize>()
@@ -40,17 +44,25 @@ impl GroupManagementState {
       editing_by_user: Arc::new(DashMap::new()),
       metrics_calculate,
       remove_batch_size,
+      hibernate_after,
       control_event_stream,
     }
   }
 
   /// Performs a periodic check to remove groups based on the following conditions:
   /// Groups that have been inactive for a specified period of time.
+  /// Groups that have had no subscriber for the hibernation period, when it is set.
   pub async fn get_inactive_group_ids(&self) -> Vec<String> {
     let mut inactive_group_ids = vec![];
     for entry in self.group_by_object_id.iter() {
       let (object_id, group) = (entry.key(), entry.value());
-      if group.is_inactive().await {
+      let hibernate = self
+        .hibernate_after
+        .is_some_and(|hibernate_after| group.is_idle_for(hibernate_after));
+      if hibernate {
+        self.metrics_calculate.hibernate_collab_count.inc();
+      }
+      if hibernate || group.is_inactive().await {
         inactive_group_ids.push(object_id.clone());
         if inactive_group_ids.len() > self.remove_batch_size {
           break;
@@ -204,6 +216,32 @@ impl GroupManagementState {
     }
   }
 
+  /// Returns the number of distinct users, of opened collabs and of subscriptions of the workspace.
+  pub(crate) fn workspace_activity(&self, workspace_id: &str) -> (usize, usize, usize) {
+    let mut uids = HashSet::new();
+    let (mut collab_count, mut session_count) = (0, 0);
+    for entry in self.group_by_object_id.iter() {
+      let group = entry.value();
+      if group.workspace_id != workspace_id {
+        continue;
+      }
+      let subscriber_uids = group.subscriber_uids();
+      collab_count += 1;
+      session_count += subscriber_uids.len();
+      uids.extend(subscriber_uids);
+    }
+    (uids.len(), collab_count, session_count)
+  }
+
+  /// Returns the number of subscriptions to all the opened collabs.
+  pub(crate) fn session_count(&self) -> usize {
+    self
+      .group_by_object_id
+      .iter()
+      .map(|entry| entry.value().subscriber_uids().len())
+      .sum()
+  }
+
   pub async fn contains_user(&self, object_id: &str, user: &RealtimeUser) -> bool {
     match self.group_by_object_id.try_get(object_id) {
       TryResult::Present(entry) => entry.value().contains_user(user),
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+/// The length of the windows the messages are counted in, in seconds.
+const THROUGHPUT_WINDOW_SECS: i64 = 60;
+
+/// Counts the collab messages received from the clients for each workspace, in fixed windows of a
+/// minute. The throughput is the number of messages of the last complete window, so that it
+/// doesn't swing at the start of each window.
+#[derive(Clone, Default)]
+pub(crate) struct MessageThroughput {
+  windows: Arc<DashMap<String, MessageWindow>>,
+}
+
+struct MessageWindow {
+  start: i64,
+  count: u64,
+  previous_count: u64,
+}
+
+impl MessageThroughput {
+  pub(crate) fn record(&self, workspace_id: &str, count: usize) {
+    let window_start = current_window_start();
+    let mut window = self
+      .windows
+      .entry(workspace_id.to_string())
+      .or_insert(MessageWindow {
+        start: window_start,
+        count: 0,
+        previous_count: 0,
+      });
+    if window.start != window_start {
+      window.previous_count = if window.start == window_start - THROUGHPUT_WINDOW_SECS {
+        window.count
+      } else {
+        0
+      };
+      window.start = window_start;
+      window.count = 0;
+    }
+    window.count += count as u64;
+  }
+
+  /// The messages received per second for the workspace over the last complete window.
+  pub(crate) fn messages_per_sec(&self, workspace_id: &str) -> f64 {
+    let window_start = current_window_start();
+    self
+      .windows
+      .get(workspace_id)
+      .map(|window| window.last_complete_count(window_start))
+      .unwrap_or(0) as f64
+      / THROUGHPUT_WINDOW_SECS as f64
+  }
+
+  /// The messages received per second for all the workspaces over the last complete window. The
+  /// workspaces without any message in the last two windows are forgotten.
+  pub(crate) fn total_messages_per_sec(&self) -> f64 {
+    let window_start = current_window_start();
+    self
+      .windows
+      .retain(|_, window| window.start >= window_start - THROUGHPUT_WINDOW_SECS);
+    self
+      .windows
+      .iter()
+      .map(|window| window.last_complete_count(window_start))
+      .sum::<u64>() as f64
+      / THROUGHPUT_WINDOW_SECS as f64
+  }
+}
+
+impl MessageWindow {
+  fn last_complete_count(&self, window_start: i64) -> u64 {
+    if self.start == window_start {
+      self.previous_count
+    } else if self.start == window_start - THROUGHPUT_WINDOW_SECS {
+      self.count
+    } else {
+      0
+    }
+  }
+}
+
+fn current_window_start() -> i64 {
+  let now = chrono::Utc::now().timestamp();
+  now - now % THROUGHPUT_WINDOW_SECS
+}
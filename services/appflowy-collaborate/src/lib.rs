@@ -13,6 +13,7 @@ pub mod indexer;
 pub mod metrics;
 mod permission;
 mod pg_listener;
+pub mod row_edit_intent;
 mod rt_server;
 pub mod snapshot;
 mod state;
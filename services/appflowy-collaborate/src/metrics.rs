@@ -1,6 +1,8 @@
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::time::Duration;
 
+use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::metrics::histogram::Histogram;
 use prometheus_client::registry::Registry;
@@ -25,6 +27,19 @@ pub struct CollabRealtimeMetrics {
   pub(crate) apply_update_time: Histogram,
   /// How big the update is in bytes.
   pub(crate) apply_update_size: Histogram,
+  /// The number of archived collabs moved back to the collab table on access.
+  pub(crate) rehydrate_collab_count: Gauge,
+  /// The total time spent on rehydrating the archived collabs in milliseconds.
+  pub(crate) rehydrate_collab_time: Gauge,
+  /// The number of collabs unloaded from memory after having no subscriber for a while.
+  pub(crate) hibernate_collab_count: Counter,
+  /// How long it takes to load a collab from the storage when its group is created, in
+  /// milliseconds.
+  pub(crate) collab_hydration_time: Histogram,
+  /// The number of subscriptions to the opened collabs, one per device and collab.
+  pub(crate) active_collab_sessions: Gauge,
+  /// The collab messages received from the clients per second, over the last complete minute.
+  pub(crate) messages_per_sec: Gauge<f64, AtomicU64>,
 }
 
 impl CollabRealtimeMetrics {
@@ -39,6 +54,11 @@ impl CollabRealtimeMetrics {
       apply_update_failed_count: Default::default(),
       acquire_collab_lock_count: Default::default(),
       acquire_collab_lock_fail_count: Default::default(),
+      rehydrate_collab_count: Default::default(),
+      rehydrate_collab_time: Default::default(),
+      hibernate_collab_count: Default::default(),
+      active_collab_sessions: Default::default(),
+      messages_per_sec: Default::default(),
 
       // when it comes to histograms we organize them by buckets or specific sizes - since our
       // prometheus client doesn't support Summary type, we use Histogram type instead
@@ -54,6 +74,10 @@ impl CollabRealtimeMetrics {
         ]
         .into_iter(),
       ),
+      // time spent on loading a collab in milliseconds: 5ms, 10ms, 25ms, 50ms, 100ms, 250ms, 500ms, 1s, 5s
+      collab_hydration_time: Histogram::new(
+        [5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 5000.0].into_iter(),
+      ),
     }
   }
 
@@ -77,7 +101,7 @@ impl CollabRealtimeMetrics {
     );
     realtime_registry.register(
       "opening_collab_count",
-      "number of opening collabs",
+      "number of opening collabs, i.e. resident in memory",
       metrics.opening_collab_count.clone(),
     );
     realtime_registry.register(
@@ -116,6 +140,36 @@ impl CollabRealtimeMetrics {
       "size of updates applied to collab in bytes",
       metrics.apply_update_size.clone(),
     );
+    realtime_registry.register(
+      "rehydrate_collab_count",
+      "number of archived collabs rehydrated on access",
+      metrics.rehydrate_collab_count.clone(),
+    );
+    realtime_registry.register(
+      "rehydrate_collab_time",
+      "total time spent on rehydrating archived collabs in milliseconds",
+      metrics.rehydrate_collab_time.clone(),
+    );
+    realtime_registry.register(
+      "hibernate_collab_count",
+      "number of collabs unloaded from memory after having no subscriber for a while",
+      metrics.hibernate_collab_count.clone(),
+    );
+    realtime_registry.register(
+      "collab_hydration_time",
+      "time spent on loading collabs from the storage into memory in milliseconds",
+      metrics.collab_hydration_time.clone(),
+    );
+    realtime_registry.register(
+      "active_collab_sessions",
+      "number of subscriptions to the opened collabs",
+      metrics.active_collab_sessions.clone(),
+    );
+    realtime_registry.register(
+      "messages_per_sec",
+      "collab messages received per second over the last minute",
+      metrics.messages_per_sec.clone(),
+    );
 
     metrics
   }
@@ -138,6 +192,11 @@ where
       metrics
         .total_success_get_encode_collab_from_redis
         .set(success as i64);
+
+      // latency of the rehydration of the archived collabs
+      let (count, millis) = storage.collab_rehydration_state();
+      metrics.rehydrate_collab_count.set(count as i64);
+      metrics.rehydrate_collab_time.set(millis as i64);
     }
   });
 }
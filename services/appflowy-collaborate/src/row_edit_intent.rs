@@ -0,0 +1,90 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use chrono::Utc;
+use collab_rt_entity::user::RealtimeUser;
+use collab_rt_entity::{RowEditIntent, ROW_EDIT_INTENT_TTL_SECS};
+use dashmap::DashMap;
+use database_entity::dto::{DatabasePresence, RowEditIntentInfo};
+
+/// (workspace_id, database_id)
+type DatabaseKey = (String, String);
+
+/// Keeps track of the database rows that the connected users are editing.
+///
+/// Each user device can signal the intent to edit one row of a database at a time. Intents are
+/// removed when the user stops editing the row, when the user disconnects, or when the intent is
+/// not refreshed within [ROW_EDIT_INTENT_TTL_SECS].
+#[derive(Clone, Default)]
+pub struct RowEditIntents {
+  intents_by_database: Arc<DashMap<DatabaseKey, Vec<RowEditIntentInfo>>>,
+}
+
+impl RowEditIntents {
+  pub fn new() -> Self {
+    let this = Self::default();
+    spawn_remove_expired_intents(Arc::downgrade(&this.intents_by_database));
+    this
+  }
+
+  pub fn update(&self, user: &RealtimeUser, intent: &RowEditIntent) {
+    let key = (intent.workspace_id.clone(), intent.database_id.clone());
+    let mut intents = self.intents_by_database.entry(key).or_default();
+    intents.retain(|info| info.uid != user.uid || info.device_id != user.device_id);
+    if intent.is_editing {
+      intents.push(RowEditIntentInfo {
+        row_id: intent.row_id.clone(),
+        uid: user.uid,
+        device_id: user.device_id.clone(),
+        expires_at: Utc::now() + chrono::Duration::seconds(ROW_EDIT_INTENT_TTL_SECS),
+      });
+    }
+  }
+
+  pub fn remove_user(&self, user: &RealtimeUser) {
+    self.intents_by_database.retain(|_, intents| {
+      intents.retain(|info| info.uid != user.uid || info.device_id != user.device_id);
+      !intents.is_empty()
+    });
+  }
+
+  pub fn database_presence(&self, workspace_id: &str, database_id: &str) -> DatabasePresence {
+    let now = Utc::now();
+    let row_edit_intents = self
+      .intents_by_database
+      .get(&(workspace_id.to_string(), database_id.to_string()))
+      .map(|intents| {
+        intents
+          .iter()
+          .filter(|info| info.expires_at > now)
+          .cloned()
+          .collect()
+      })
+      .unwrap_or_default();
+    DatabasePresence {
+      database_id: database_id.to_string(),
+      row_edit_intents,
+    }
+  }
+}
+
+fn spawn_remove_expired_intents(
+  weak_intents_by_database: Weak<DashMap<DatabaseKey, Vec<RowEditIntentInfo>>>,
+) {
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(ROW_EDIT_INTENT_TTL_SECS as u64));
+    loop {
+      interval.tick().await;
+      match weak_intents_by_database.upgrade() {
+        Some(intents_by_database) => {
+          let now = Utc::now();
+          intents_by_database.retain(|_, intents| {
+            intents.retain(|info| info.expires_at > now);
+            !intents.is_empty()
+          });
+        },
+        None => break,
+      }
+    }
+  });
+}
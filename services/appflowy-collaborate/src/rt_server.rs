@@ -12,9 +12,10 @@ use tracing::{error, info, trace};
 
 use access_control::collab::RealtimeAccessControl;
 use collab_rt_entity::user::{RealtimeUser, UserDevice};
-use collab_rt_entity::MessageByObjectId;
+use collab_rt_entity::{MessageByObjectId, RealtimeMessage, RowEditIntent, SystemMessage};
 use collab_stream::client::CollabRedisStream;
 use database::collab::CollabStorage;
+use database_entity::dto::WorkspaceRealtimeStats;
 
 use crate::client::client_msg_router::ClientMessageRouter;
 use crate::command::{spawn_collaboration_command, CLCommandReceiver};
@@ -25,6 +26,7 @@ use crate::group::cmd::{GroupCommand, GroupCommandRunner, GroupCommandSender};
 use crate::group::manager::GroupManager;
 use crate::indexer::IndexerProvider;
 use crate::metrics::spawn_metrics;
+use crate::row_edit_intent::RowEditIntents;
 use crate::rt_server::collaboration_runtime::COLLAB_RUNTIME;
 use crate::state::RedisConnectionManager;
 use crate::{CollabRealtimeMetrics, RealtimeClientWebsocketSink};
@@ -38,6 +40,8 @@ pub struct CollaborationServer<S> {
   #[allow(dead_code)]
   metrics: Arc<CollabRealtimeMetrics>,
   enable_custom_runtime: bool,
+  access_control: Arc<dyn RealtimeAccessControl>,
+  row_edit_intents: RowEditIntents,
 }
 
 impl<S> CollaborationServer<S>
@@ -55,6 +59,8 @@ where
     edit_state_max_count: u32,
     edit_state_max_secs: i64,
     indexer_provider: Arc<IndexerProvider>,
+    row_edit_intents: RowEditIntents,
+    group_hibernate_after: Option<Duration>,
   ) -> Result<Self, RealtimeError> {
     let enable_custom_runtime = get_env_var("APPFLOWY_COLLABORATE_MULTI_THREAD", "false")
       .parse::<bool>()
@@ -78,6 +84,7 @@ where
         edit_state_max_count,
         edit_state_max_secs,
         indexer_provider.clone(),
+        group_hibernate_after,
       )
       .await?,
     );
@@ -86,6 +93,8 @@ where
 
     spawn_period_check_inactive_group(Arc::downgrade(&group_manager), &group_sender_by_object_id);
 
+    spawn_period_update_activity_metrics(Arc::downgrade(&group_manager));
+
     spawn_collaboration_command(
       command_recv,
       &group_sender_by_object_id,
@@ -102,6 +111,8 @@ where
       group_sender_by_object_id,
       metrics,
       enable_custom_runtime,
+      access_control,
+      row_edit_intents,
     })
   }
 
@@ -121,11 +132,13 @@ where
     let group_manager = self.group_manager.clone();
     let connect_state = self.connect_state.clone();
     let metrics_calculate = self.metrics.clone();
+    let row_edit_intents = self.row_edit_intents.clone();
 
     Box::pin(async move {
       if let Some(old_user) = connect_state.handle_user_connect(connected_user, new_client_router) {
         // Remove the old user from all collaboration groups.
         group_manager.remove_user(&old_user).await;
+        row_edit_intents.remove_user(&old_user);
       }
       metrics_calculate
         .connected_users
@@ -148,6 +161,7 @@ where
     let group_manager = self.group_manager.clone();
     let connect_state = self.connect_state.clone();
     let metrics_calculate = self.metrics.clone();
+    let row_edit_intents = self.row_edit_intents.clone();
 
     Box::pin(async move {
       trace!("[realtime]: disconnect => {}", disconnect_user);
@@ -158,8 +172,62 @@ where
           .set(connect_state.number_of_connected_users() as i64);
 
         group_manager.remove_user(&disconnect_user).await;
+        row_edit_intents.remove_user(&disconnect_user);
+      }
+
+      Ok(())
+    })
+  }
+
+  /// Closes the connection of the device, e.g. once the device is revoked. The client is told that
+  /// it was kicked off before its connection is closed.
+  pub fn handle_kick_off_device(
+    &self,
+    user_device: UserDevice,
+  ) -> Pin<Box<dyn Future<Output = Result<(), RealtimeError>>>> {
+    let user = self.get_user_by_device(&user_device);
+    let disconnect = user.map(|user| {
+      if let Some(router) = self.connect_state.client_message_routers.get(&user) {
+        router
+          .sink
+          .do_send(RealtimeMessage::System(SystemMessage::KickOff));
       }
+      self.handle_disconnect(user)
+    });
+    Box::pin(async move {
+      if let Some(disconnect) = disconnect {
+        disconnect.await?;
+      }
+      Ok(())
+    })
+  }
+
+  /// Records that the user starts or stops editing a row of a database. Only the users who can
+  /// edit the database can signal their intent to edit its rows.
+  pub fn handle_row_edit_intent(
+    &self,
+    user: RealtimeUser,
+    intent: RowEditIntent,
+  ) -> Pin<Box<dyn Future<Output = Result<(), RealtimeError>>>> {
+    let access_control = self.access_control.clone();
+    let row_edit_intents = self.row_edit_intents.clone();
 
+    Box::pin(async move {
+      if intent.is_editing {
+        let can_write = access_control
+          .can_write_collab(&intent.workspace_id, &user.uid, &intent.database_id)
+          .await
+          .unwrap_or(false);
+        if !can_write {
+          trace!(
+            "[realtime]: user {} is not allowed to edit database {}",
+            user,
+            intent.database_id
+          );
+          return Ok(());
+        }
+      }
+      row_edit_intents.update(&user, &intent);
       Ok(())
     })
   }
@@ -259,6 +327,11 @@ where
       .get(user_device)
       .map(|entry| entry.value().clone())
   }
+
+  /// Returns the realtime activity of the workspace on this node.
+  pub fn get_workspace_realtime_stats(&self, workspace_id: &str) -> WorkspaceRealtimeStats {
+    self.group_manager.workspace_realtime_stats(workspace_id)
+  }
 }
 
 fn spawn_handle_unindexed_collabs(
@@ -298,6 +371,22 @@ fn spawn_period_check_inactive_group<S>(
   });
 }
 
+fn spawn_period_update_activity_metrics<S>(weak_groups: Weak<GroupManager<S>>)
+where
+  S: CollabStorage,
+{
+  let mut interval = interval(Duration::from_secs(30));
+  tokio::spawn(async move {
+    loop {
+      interval.tick().await;
+      match weak_groups.upgrade() {
+        Some(groups) => groups.update_activity_metrics(),
+        None => break,
+      }
+    }
+  });
+}
+
 /// When the CollaborationServer operates within an actix-web actor, utilizing tokio::spawn for
 /// task execution confines all tasks to the same thread, attributable to the actor's reliance on a
 /// single-threaded Tokio runtime. To circumvent this limitation and enable task execution across
@@ -7,7 +7,6 @@ use async_stream::stream;
 use chrono::{DateTime, Utc};
 use collab::lock::{Mutex, RwLock};
 use futures_util::StreamExt;
-use sqlx::PgPool;
 use tokio::time::interval;
 use tracing::{debug, error, trace, warn};
 use validator::Validate;
@@ -16,8 +15,10 @@ use app_error::AppError;
 use collab_rt_protocol::spawn_blocking_validate_encode_collab;
 use database::collab::{
   create_snapshot_and_maintain_limit, get_all_collab_snapshot_meta, latest_snapshot_time,
-  select_snapshot, AppResult, COLLAB_SNAPSHOT_LIMIT, SNAPSHOT_PER_HOUR,
+  select_snapshot, select_snapshot_retention_policy, verify_snapshot_checksum, AppResult,
+  SNAPSHOT_PER_HOUR,
 };
+use database::residency::PgPoolRouter;
 use database_entity::dto::{AFSnapshotMeta, AFSnapshotMetas, InsertSnapshotParams, SnapshotData};
 
 use crate::metrics::CollabMetrics;
@@ -40,20 +41,20 @@ pub enum SnapshotCommand {
 pub struct SnapshotControl {
   cache: SnapshotCache,
   command_sender: SnapshotCommandSender,
-  pg_pool: PgPool,
+  router: PgPoolRouter,
 }
 
 impl SnapshotControl {
   pub async fn new(
     redis_client: RedisConnectionManager,
-    pg_pool: PgPool,
+    router: PgPoolRouter,
     collab_metrics: Arc<CollabMetrics>,
   ) -> Self {
     let redis_client = Arc::new(Mutex::from(redis_client));
     let (command_sender, rx) = tokio::sync::mpsc::channel(2000);
     let cache = SnapshotCache::new(redis_client);
 
-    let runner = SnapshotCommandRunner::new(pg_pool.clone(), cache.clone(), rx);
+    let runner = SnapshotCommandRunner::new(router.clone(), cache.clone(), rx);
     tokio::spawn(runner.run());
 
     let cloned_sender = command_sender.clone();
@@ -78,7 +79,7 @@ impl SnapshotControl {
     Self {
       cache,
       command_sender,
-      pg_pool,
+      router,
     }
   }
 
@@ -112,14 +113,21 @@ impl SnapshotControl {
     params.validate()?;
 
     debug!("create snapshot for object:{}", params.object_id);
-    match self.pg_pool.try_begin().await {
+    let workspace_id = params.workspace_id.parse()?;
+    let retention =
+      select_snapshot_retention_policy(self.router.default_pool(), &workspace_id).await?;
+    let pg_pool = self
+      .router
+      .pg_pool_for_collab(&workspace_id, &params.object_id)
+      .await?;
+    match pg_pool.try_begin().await {
       Ok(Some(transaction)) => {
         let meta = create_snapshot_and_maintain_limit(
           transaction,
           &params.workspace_id,
           &params.object_id,
           &params.encoded_collab_v1,
-          COLLAB_SNAPSHOT_LIMIT,
+          &retention,
         )
         .await?;
         Ok(meta)
@@ -131,24 +139,40 @@ impl SnapshotControl {
     }
   }
 
-  pub async fn get_collab_snapshot(&self, snapshot_id: &i64) -> AppResult<SnapshotData> {
-    match select_snapshot(&self.pg_pool, snapshot_id).await? {
+  pub async fn get_collab_snapshot(
+    &self,
+    workspace_id: &str,
+    object_id: &str,
+    snapshot_id: &i64,
+  ) -> AppResult<SnapshotData> {
+    let pg_pool = self
+      .router
+      .pg_pool_for_collab(&workspace_id.parse()?, object_id)
+      .await?;
+    match select_snapshot(&pg_pool, snapshot_id).await? {
       None => Err(AppError::RecordNotFound(format!(
         "Can't find the snapshot with id:{}",
         snapshot_id
       ))),
-      Some(row) => Ok(SnapshotData {
-        object_id: row.oid,
-        encoded_collab_v1: row.blob,
-        workspace_id: row.workspace_id.to_string(),
-      }),
+      Some(row) => {
+        verify_snapshot_checksum(&pg_pool, &row).await?;
+        Ok(SnapshotData {
+          object_id: row.oid,
+          encoded_collab_v1: row.blob,
+          workspace_id: row.workspace_id.to_string(),
+        })
+      },
     }
   }
 
   /// Returns list of snapshots for given object_id in descending order of creation time.
   pub async fn get_collab_snapshot_list(&self, oid: &str) -> AppResult<AFSnapshotMetas> {
-    let metas = get_all_collab_snapshot_meta(&self.pg_pool, oid).await?;
-    Ok(metas)
+    let mut metas = vec![];
+    for pg_pool in self.router.all_pools() {
+      metas.extend(get_all_collab_snapshot_meta(pg_pool, oid).await?.0);
+    }
+    metas.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(AFSnapshotMetas(metas))
   }
 
   pub async fn queue_snapshot(&self, params: InsertSnapshotParams) -> Result<(), AppError> {
@@ -172,7 +196,11 @@ impl SnapshotControl {
     let encoded_collab_v1 = self.cache.try_get(&key.0).await.unwrap_or(None);
 
     match encoded_collab_v1 {
-      None => self.get_collab_snapshot(snapshot_id).await,
+      None => {
+        self
+          .get_collab_snapshot(workspace_id, object_id, snapshot_id)
+          .await
+      },
       Some(encoded_collab_v1) => Ok(SnapshotData {
         encoded_collab_v1,
         workspace_id: workspace_id.to_string(),
@@ -181,14 +209,24 @@ impl SnapshotControl {
     }
   }
 
+  /// Drops the snapshot of the object that is queued but not written yet.
+  pub async fn remove_pending_snapshot(&self, object_id: &str) -> Result<(), AppError> {
+    let key = SnapshotKey::from_object_id(object_id);
+    self.cache.remove(&key.0).await
+  }
+
   async fn latest_snapshot_time(&self, oid: &str) -> Result<Option<DateTime<Utc>>, AppError> {
-    let time = latest_snapshot_time(oid, &self.pg_pool).await?;
-    Ok(time)
+    let mut latest_time = None;
+    for pg_pool in self.router.all_pools() {
+      let time = latest_snapshot_time(oid, pg_pool).await?;
+      latest_time = latest_time.max(time);
+    }
+    Ok(latest_time)
   }
 }
 
 struct SnapshotCommandRunner {
-  pg_pool: PgPool,
+  router: PgPoolRouter,
   queue: RwLock<PendingQueue>,
   cache: SnapshotCache,
   recv: Option<SnapshotCommandReceiver>,
@@ -196,10 +234,10 @@ struct SnapshotCommandRunner {
   total_attempts: AtomicU64,
 }
 impl SnapshotCommandRunner {
-  fn new(pg_pool: PgPool, cache: SnapshotCache, recv: SnapshotCommandReceiver) -> Self {
+  fn new(router: PgPoolRouter, cache: SnapshotCache, recv: SnapshotCommandReceiver) -> Self {
     let queue = PendingQueue::new();
     Self {
-      pg_pool,
+      router,
       queue: RwLock::from(queue),
       cache,
       recv: Some(recv),
@@ -282,8 +320,34 @@ impl SnapshotCommandRunner {
       return Ok(());
     }
 
-    // Start a transaction
-    let transaction = match self.pg_pool.try_begin().await {
+    let workspace_id = match next_item.workspace_id.parse() {
+      Ok(workspace_id) => workspace_id,
+      Err(err) => {
+        warn!("Invalid workspace id for snapshot: {}", err);
+        return Ok(());
+      },
+    };
+    let retention =
+      match select_snapshot_retention_policy(self.router.default_pool(), &workspace_id).await {
+        Ok(retention) => retention,
+        Err(err) => {
+          queue.push_item(next_item);
+          return Err(err);
+        },
+      };
+    // Start a transaction against the database the collab is stored in
+    let pg_pool = match self
+      .router
+      .pg_pool_for_collab(&workspace_id, &next_item.object_id)
+      .await
+    {
+      Ok(pg_pool) => pg_pool,
+      Err(err) => {
+        queue.push_item(next_item);
+        return Err(err);
+      },
+    };
+    let transaction = match pg_pool.try_begin().await {
       Ok(Some(tx)) => tx,
       _ => {
         debug!("Failed to start transaction to write snapshot, retrying later");
@@ -298,7 +362,7 @@ impl SnapshotCommandRunner {
       &next_item.workspace_id,
       &next_item.object_id,
       &encoded_collab_v1,
-      COLLAB_SNAPSHOT_LIMIT,
+      &retention,
     )
     .await
     {
@@ -0,0 +1,54 @@
+use actix_web::web::Data;
+use actix_web::{web, Scope};
+use authentication::jwt::Authorization;
+use database::collab::select_unresolved_collab_corruptions;
+use shared_entity::dto::server_info_dto::{CollabCorruption, Schedules};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+
+use crate::api::util::enforce_server_admin;
+use crate::state::AppState;
+
+pub fn admin_scope() -> Scope {
+  web::scope("/api/admin")
+    .service(web::resource("/schedules").route(web::get().to(list_schedules_handler)))
+    .service(
+      web::resource("/collab-corruptions").route(web::get().to(list_collab_corruptions_handler)),
+    )
+}
+
+/// Lists the jobs run by the scheduler, with their upcoming and last runs.
+async fn list_schedules_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Schedules>> {
+  enforce_server_admin(&auth)?;
+  let schedules = state.scheduler.get_schedules().await?;
+  Ok(AppResponse::Ok().with_data(schedules).into())
+}
+
+/// Lists the unresolved corruptions of the stored collabs and snapshots, in every database, the
+/// latest first.
+async fn list_collab_corruptions_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Vec<CollabCorruption>>> {
+  enforce_server_admin(&auth)?;
+  let mut corruptions = vec![];
+  for pg_pool in state.collab_cache.router().all_pools() {
+    corruptions.extend(
+      select_unresolved_collab_corruptions(pg_pool)
+        .await?
+        .into_iter()
+        .map(|row| CollabCorruption {
+          object_id: row.oid,
+          workspace_id: row.workspace_id,
+          snapshot_id: (row.snapshot_id != 0).then_some(row.snapshot_id),
+          detected_by: row.detected_by,
+          detected_at: row.detected_at,
+          last_good_snapshot_id: row.last_good_snapshot_id,
+        }),
+    );
+  }
+  corruptions.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+  Ok(AppResponse::Ok().with_data(corruptions).into())
+}
@@ -0,0 +1,92 @@
+use actix_web::web::{Data, Json};
+use actix_web::{web, Scope};
+use authentication::jwt::{Authorization, UserUuid};
+use shared_entity::dto::server_info_dto::{Announcement, AnnouncementParams};
+use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
+
+use crate::api::util::enforce_server_admin;
+use crate::biz::announcement::{
+  create_announcement, dismiss_announcement, get_active_announcements, list_announcements,
+  remove_announcement, replace_announcement,
+};
+use crate::state::AppState;
+
+pub fn announcement_scope() -> Scope {
+  web::scope("/api/announcements")
+    .service(
+      web::resource("")
+        .route(web::get().to(get_active_announcements_handler))
+        .route(web::post().to(create_announcement_handler)),
+    )
+    .service(web::resource("/all").route(web::get().to(list_announcements_handler)))
+    .service(
+      web::resource("/{announcement_id}")
+        .route(web::put().to(update_announcement_handler))
+        .route(web::delete().to(delete_announcement_handler)),
+    )
+    .service(
+      web::resource("/{announcement_id}/dismiss")
+        .route(web::post().to(dismiss_announcement_handler)),
+    )
+}
+
+async fn get_active_announcements_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Vec<Announcement>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let announcements = get_active_announcements(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(announcements).into())
+}
+
+async fn dismiss_announcement_handler(
+  user_uuid: UserUuid,
+  announcement_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  dismiss_announcement(&state.pg_pool, uid, &announcement_id).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn list_announcements_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Vec<Announcement>>> {
+  enforce_server_admin(&auth)?;
+  let announcements = list_announcements(&state.pg_pool).await?;
+  Ok(AppResponse::Ok().with_data(announcements).into())
+}
+
+async fn create_announcement_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<AnnouncementParams>,
+) -> actix_web::Result<JsonAppResponse<Announcement>> {
+  enforce_server_admin(&auth)?;
+  let announcement = create_announcement(&state.pg_pool, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(announcement).into())
+}
+
+async fn update_announcement_handler(
+  auth: Authorization,
+  announcement_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<AnnouncementParams>,
+) -> actix_web::Result<JsonAppResponse<Announcement>> {
+  enforce_server_admin(&auth)?;
+  let announcement =
+    replace_announcement(&state.pg_pool, &announcement_id, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(announcement).into())
+}
+
+async fn delete_announcement_handler(
+  auth: Authorization,
+  announcement_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<()>> {
+  enforce_server_admin(&auth)?;
+  remove_announcement(&state.pg_pool, &announcement_id).await?;
+  Ok(AppResponse::Ok().into())
+}
@@ -99,7 +99,9 @@ async fn create_upload(
     file_id: req.file_id.clone(),
   };
   let resp = state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .create_upload(key, req)
     .await
     .map_err(AppResponseError::from)?;
@@ -170,7 +172,9 @@ async fn upload_part_handler(
   };
 
   let resp = state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .upload_part(key, data)
     .await
     .map_err(AppResponseError::from)?;
@@ -197,7 +201,9 @@ async fn complete_upload_handler(
     file_id: req.file_id.clone(),
   };
   state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .complete_upload(key, req)
     .await
     .map_err(AppResponseError::from)?;
@@ -261,7 +267,9 @@ async fn put_blob_handler(
   );
 
   state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .put_blob(path, content, content_type)
     .await
     .map_err(AppResponseError::from)?;
@@ -283,7 +291,9 @@ async fn delete_blob_handler(
     .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
     .await?;
   state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .delete_blob(path)
     .await
     .map_err(AppResponseError::from)?;
@@ -315,7 +325,9 @@ async fn delete_blob_v1_handler(
     .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
     .await?;
   state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&workspace_id)
+    .await?
     .delete_blob(path)
     .await
     .map_err(AppResponseError::from)?;
@@ -328,9 +340,12 @@ async fn get_blob_by_object_key(
   key: &impl BlobKey,
   req: HttpRequest,
 ) -> Result<HttpResponse<BoxBody>> {
+  let bucket_storage = state
+    .storage_router
+    .bucket_storage(key.workspace_id())
+    .await?;
   // Get the metadata
-  let result = state
-    .bucket_storage
+  let result = bucket_storage
     .get_blob_metadata(key.workspace_id(), &key.meta_key())
     .await;
 
@@ -355,7 +370,7 @@ async fn get_blob_by_object_key(
     }
   }
 
-  let blob_result = state.bucket_storage.get_blob(key).await;
+  let blob_result = bucket_storage.get_blob(key).await;
   match blob_result {
     Ok(blob) => {
       let response = HttpResponse::Ok()
@@ -397,7 +412,9 @@ async fn get_blob_metadata_handler(
 
   // Get the metadata
   let metadata = state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&path.workspace_id)
+    .await?
     .get_blob_metadata(&path.workspace_id, &path.meta_key())
     .await
     .map(|meta| BlobMetadata {
@@ -421,7 +438,9 @@ async fn get_blob_metadata_v1_handler(
 
   // Get the metadata
   let metadata = state
-    .bucket_storage
+    .storage_router
+    .bucket_storage(&path.workspace_id)
+    .await?
     .get_blob_metadata(&path.workspace_id, &path.meta_key())
     .await
     .map(|meta| BlobMetadata {
@@ -58,6 +58,8 @@ pub struct RequestMetrics {
   requests_latency: Family<PathLabel, CounterWithExemplar<TraceLabel>>,
   requests_result: Family<ResultLabel, CounterWithExemplar<TraceLabel>>,
   openai_token_usage: Family<WorkspaceLabel, Counter>,
+  /// The realtime messages rejected because the device sent more than its share.
+  realtime_throttled_messages: Counter,
 }
 
 #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug, Default)]
@@ -72,6 +74,7 @@ impl RequestMetrics {
       requests_latency: Family::default(),
       requests_result: Family::default(),
       openai_token_usage: Family::default(),
+      realtime_throttled_messages: Counter::default(),
     }
   }
 
@@ -99,9 +102,18 @@ impl RequestMetrics {
       "OpenAI API tokens used for search requests",
       af_metrics.openai_token_usage.clone(),
     );
+    af_registry.register(
+      "realtime_throttled_messages",
+      "realtime messages rejected by the rate limit",
+      af_metrics.realtime_throttled_messages.clone(),
+    );
     af_metrics
   }
 
+  pub fn record_realtime_message_throttled(&self) {
+    self.realtime_throttled_messages.inc();
+  }
+
   pub fn record_search_tokens_used(&self, workspace_id: &Uuid, tokens: u32) {
     self
       .openai_token_usage
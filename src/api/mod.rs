@@ -1,5 +1,7 @@
 pub mod access_request;
+pub mod admin;
 pub mod ai;
+pub mod announcement;
 pub mod chat;
 pub mod data_import;
 pub mod file_storage;
@@ -7,6 +9,7 @@ pub mod history;
 pub mod metrics;
 pub mod search;
 pub mod server_info;
+pub mod sso;
 pub mod template;
 pub mod user;
 pub mod util;
@@ -1,9 +1,42 @@
+use actix_web::web::{Data, Json};
 use actix_web::{web, Scope};
-use shared_entity::dto::server_info_dto::ServerInfoResponseItem;
+use authentication::jwt::Authorization;
+use shared_entity::dto::server_info_dto::{
+  ClientVersionPolicy, CollabMigration, CollabShard, CollabShardRebalance,
+  DeleteClientVersionPolicyParams, MigrateCollabParams, ProvisionAccountParams, ProvisionedAccount,
+  RebalanceCollabShardsParams, ServerInfoResponseItem, UpdateWorkspaceLifecycleParams,
+  UpsertClientVersionPolicyParams, WorkspaceLifecycle,
+};
 use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
+
+use crate::api::util::enforce_server_admin;
+use crate::biz::collab::shard::{list_collab_shards, migrate_collab, rebalance_collab_shards};
+use crate::biz::user::user_provision::provision_account;
+use crate::biz::workspace::lifecycle::{get_workspace_lifecycle, set_workspace_lifecycle_exempt};
+use crate::state::AppState;
 
 pub fn server_info_scope() -> Scope {
-  web::scope("/api/server").service(web::resource("").route(web::get().to(server_info_handler)))
+  web::scope("/api/server")
+    .service(web::resource("").route(web::get().to(server_info_handler)))
+    .service(
+      web::resource("/client-version-policy")
+        .route(web::get().to(list_client_version_policies_handler))
+        .route(web::put().to(upsert_client_version_policy_handler))
+        .route(web::delete().to(delete_client_version_policy_handler)),
+    )
+    .service(
+      web::resource("/workspace-lifecycle/{workspace_id}")
+        .route(web::get().to(get_workspace_lifecycle_handler))
+        .route(web::put().to(update_workspace_lifecycle_handler)),
+    )
+    .service(web::resource("/provision").route(web::post().to(provision_account_handler)))
+    .service(web::resource("/collab-shards").route(web::get().to(list_collab_shards_handler)))
+    .service(
+      web::resource("/collab-shards/rebalance")
+        .route(web::post().to(rebalance_collab_shards_handler)),
+    )
+    .service(web::resource("/collab-shards/migrate").route(web::post().to(migrate_collab_handler)))
 }
 
 async fn server_info_handler() -> actix_web::Result<JsonAppResponse<ServerInfoResponseItem>> {
@@ -16,3 +49,101 @@ async fn server_info_handler() -> actix_web::Result<JsonAppResponse<ServerInfoRe
       .into(),
   )
 }
+
+async fn list_client_version_policies_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Vec<ClientVersionPolicy>>> {
+  enforce_server_admin(&auth)?;
+  let policies = state.client_version_gate.list_policies().await?;
+  Ok(AppResponse::Ok().with_data(policies).into())
+}
+
+async fn upsert_client_version_policy_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<UpsertClientVersionPolicyParams>,
+) -> actix_web::Result<JsonAppResponse<ClientVersionPolicy>> {
+  enforce_server_admin(&auth)?;
+  let policy = state
+    .client_version_gate
+    .upsert_policy(payload.into_inner())
+    .await?;
+  Ok(AppResponse::Ok().with_data(policy).into())
+}
+
+async fn delete_client_version_policy_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<DeleteClientVersionPolicyParams>,
+) -> actix_web::Result<JsonAppResponse<()>> {
+  enforce_server_admin(&auth)?;
+  state
+    .client_version_gate
+    .delete_policy(&payload.version_req)
+    .await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn get_workspace_lifecycle_handler(
+  auth: Authorization,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<WorkspaceLifecycle>> {
+  enforce_server_admin(&auth)?;
+  let lifecycle = get_workspace_lifecycle(&state.pg_pool, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(lifecycle).into())
+}
+
+async fn update_workspace_lifecycle_handler(
+  auth: Authorization,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  payload: Json<UpdateWorkspaceLifecycleParams>,
+) -> actix_web::Result<JsonAppResponse<WorkspaceLifecycle>> {
+  enforce_server_admin(&auth)?;
+  let lifecycle =
+    set_workspace_lifecycle_exempt(&state.pg_pool, &workspace_id, payload.exempt).await?;
+  Ok(AppResponse::Ok().with_data(lifecycle).into())
+}
+
+async fn provision_account_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<ProvisionAccountParams>,
+) -> actix_web::Result<JsonAppResponse<ProvisionedAccount>> {
+  enforce_server_admin(&auth)?;
+  let account = provision_account(&state, payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(account).into())
+}
+
+async fn list_collab_shards_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> actix_web::Result<JsonAppResponse<Vec<CollabShard>>> {
+  enforce_server_admin(&auth)?;
+  let shards = list_collab_shards(state.storage_router.pg_pool_router()).await?;
+  Ok(AppResponse::Ok().with_data(shards).into())
+}
+
+async fn rebalance_collab_shards_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<RebalanceCollabShardsParams>,
+) -> actix_web::Result<JsonAppResponse<CollabShardRebalance>> {
+  enforce_server_admin(&auth)?;
+  let rebalance =
+    rebalance_collab_shards(state.storage_router.pg_pool_router(), payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(rebalance).into())
+}
+
+async fn migrate_collab_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+  payload: Json<MigrateCollabParams>,
+) -> actix_web::Result<JsonAppResponse<CollabMigration>> {
+  enforce_server_admin(&auth)?;
+  let migration =
+    migrate_collab(state.storage_router.pg_pool_router(), payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(migration).into())
+}
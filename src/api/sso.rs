@@ -0,0 +1,84 @@
+use actix_http::header::LOCATION;
+use actix_web::web::{Data, Form, Query};
+use actix_web::{web, HttpResponse, Result, Scope};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::biz::workspace::saml::{
+  get_saml_login_url, get_saml_sp_metadata, sign_in_with_saml_response,
+};
+use crate::state::AppState;
+
+/// The routes the browsers and the SAML identity providers use to sign the members of a workspace
+/// in, see [crate::biz::workspace::saml].
+pub fn sso_scope() -> Scope {
+  web::scope("/api/sso")
+    .service(
+      web::resource("/saml/{workspace_id}/metadata")
+        .route(web::get().to(get_saml_metadata_handler)),
+    )
+    .service(web::resource("/saml/{workspace_id}/login").route(web::get().to(saml_login_handler)))
+    .service(web::resource("/saml/{workspace_id}/acs").route(web::post().to(saml_acs_handler)))
+}
+
+#[derive(Deserialize)]
+struct SamlLoginQuery {
+  redirect_to: Option<String>,
+}
+
+/// The form the identity provider posts the assertion with, using the HTTP-POST binding.
+#[derive(Deserialize)]
+struct SamlAcsForm {
+  #[serde(rename = "SAMLResponse")]
+  saml_response: String,
+  #[serde(rename = "RelayState")]
+  relay_state: Option<String>,
+}
+
+async fn get_saml_metadata_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let metadata = get_saml_sp_metadata(&state.pg_pool, &state.config.saml, &workspace_id).await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/samlmetadata+xml")
+      .body(metadata),
+  )
+}
+
+/// Redirects the browser to the identity provider of the workspace.
+async fn saml_login_handler(
+  workspace_id: web::Path<Uuid>,
+  query: Query<SamlLoginQuery>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let url = get_saml_login_url(&state, &workspace_id, query.redirect_to.as_deref()).await?;
+  Ok(
+    HttpResponse::Found()
+      .append_header((LOCATION, url))
+      .finish(),
+  )
+}
+
+/// Exchanges the assertion posted by the identity provider for an AppFlowy session, the browser
+/// is redirected to the link that opens it.
+async fn saml_acs_handler(
+  workspace_id: web::Path<Uuid>,
+  form: Form<SamlAcsForm>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let form = form.into_inner();
+  let url = sign_in_with_saml_response(
+    &state,
+    &workspace_id,
+    &form.saml_response,
+    form.relay_state.as_deref(),
+  )
+  .await?;
+  Ok(
+    HttpResponse::SeeOther()
+      .append_header((LOCATION, url))
+      .finish(),
+  )
+}
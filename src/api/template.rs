@@ -1,3 +1,4 @@
+use access_control::act::Action;
 use actix_multipart::form::{bytes::Bytes as MPBytes, MultipartForm};
 use actix_web::{
   web::{self, Data, Json},
@@ -6,15 +7,21 @@ use actix_web::{
 use authentication::jwt::UserUuid;
 use database_entity::dto::{
   AvatarImageSource, CreateTemplateCategoryParams, CreateTemplateCreatorParams,
-  CreateTemplateParams, GetTemplateCategoriesQueryParams, GetTemplateCreatorsQueryParams,
-  GetTemplatesQueryParams, Template, TemplateCategories, TemplateCategory, TemplateCreator,
-  TemplateCreators, TemplateHomePage, TemplateHomePageQueryParams, TemplateWithPublishInfo,
-  Templates, UpdateTemplateCategoryParams, UpdateTemplateCreatorParams, UpdateTemplateParams,
+  CreateTemplateParams, GetMarketplaceTemplatesQueryParams, GetTemplateCategoriesQueryParams,
+  GetTemplateCreatorsQueryParams, GetTemplatesQueryParams, InstallMarketplaceTemplateParams,
+  MarketplaceTemplateWithPublishInfo, MarketplaceTemplates, PublishMarketplaceTemplateParams,
+  Template, TemplateCategories, TemplateCategory, TemplateCreator, TemplateCreators,
+  TemplateHomePage, TemplateHomePageQueryParams, TemplateWithPublishInfo, Templates,
+  UpdateTemplateCategoryParams, UpdateTemplateCreatorParams, UpdateTemplateParams,
 };
 use reqwest::StatusCode;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 use uuid::Uuid;
 
+use crate::biz::template::marketplace::{
+  get_marketplace_template, get_marketplace_templates, install_marketplace_template,
+  publish_marketplace_template, unpublish_marketplace_template,
+};
 use crate::{biz::template::ops::*, state::AppState};
 
 pub fn template_scope() -> Scope {
@@ -57,6 +64,24 @@ pub fn template_scope() -> Scope {
     .service(web::resource("/avatar/{avatar_id}").route(web::get().to(get_avatar_handler)))
 }
 
+pub fn template_marketplace_scope() -> Scope {
+  web::scope("/api/templates/marketplace")
+    .service(
+      web::resource("")
+        .route(web::post().to(post_marketplace_template_handler))
+        .route(web::get().to(list_marketplace_templates_handler)),
+    )
+    .service(
+      web::resource("/{view_id}")
+        .route(web::get().to(get_marketplace_template_handler))
+        .route(web::delete().to(delete_marketplace_template_handler)),
+    )
+    .service(
+      web::resource("/{view_id}/install")
+        .route(web::post().to(post_marketplace_template_install_handler)),
+    )
+}
+
 async fn post_template_category_handler(
   _uuid: UserUuid,
   data: Json<CreateTemplateCategoryParams>,
@@ -313,3 +338,63 @@ async fn put_avatar_handler(
     AppResponse::Ok().with_data(AvatarImageSource { file_id }),
   ))
 }
+
+async fn post_marketplace_template_handler(
+  user_uuid: UserUuid,
+  data: Json<PublishMarketplaceTemplateParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<MarketplaceTemplateWithPublishInfo>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let template =
+    publish_marketplace_template(&state.pg_pool, &user_uuid, uid, data.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(template)))
+}
+
+async fn list_marketplace_templates_handler(
+  query: web::Query<GetMarketplaceTemplatesQueryParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<MarketplaceTemplates>> {
+  let templates = get_marketplace_templates(&state.pg_pool, &query).await?;
+  Ok(Json(AppResponse::Ok().with_data(templates)))
+}
+
+async fn get_marketplace_template_handler(
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<MarketplaceTemplateWithPublishInfo>> {
+  let template = get_marketplace_template(&state.pg_pool, view_id.into_inner()).await?;
+  Ok(Json(AppResponse::Ok().with_data(template)))
+}
+
+async fn delete_marketplace_template_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  unpublish_marketplace_template(&state.pg_pool, &user_uuid, view_id.into_inner()).await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn post_marketplace_template_install_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  data: Json<InstallMarketplaceTemplateParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = data.into_inner();
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &params.workspace_id.to_string(), Action::Write)
+    .await?;
+  install_marketplace_template(
+    &state.pg_pool,
+    state.bucket_client.clone(),
+    state.collab_access_control_storage.clone(),
+    uid,
+    view_id.into_inner(),
+    params,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
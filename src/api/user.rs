@@ -1,22 +1,61 @@
+use crate::api::ws::RealtimeServerAddr;
+use crate::biz::user::user_access_token::{
+  create_access_token, get_access_tokens, revoke_access_token,
+};
+use crate::biz::user::user_block::{block_user, get_blocked_users, unblock_user};
 use crate::biz::user::user_delete::delete_user;
+use crate::biz::user::user_device::{get_user_devices, revoke_device};
+use crate::biz::user::user_email::{change_user_email, sync_user_email};
 use crate::biz::user::user_info::{get_profile, get_user_workspace_info, update_user};
+use crate::biz::user::user_merge::merge_user_account;
 use crate::biz::user::user_verify::verify_token;
 use crate::state::AppState;
 use actix_web::web::{Data, Json};
 use actix_web::Result;
 use actix_web::{web, Scope};
+use app_error::AppError;
+use appflowy_collaborate::actix_ws::entities::KickOffDevice;
 use authentication::jwt::{Authorization, UserUuid};
-use database_entity::dto::{AFUserProfile, AFUserWorkspaceInfo};
-use shared_entity::dto::auth_dto::{DeleteUserQuery, SignInTokenResponse, UpdateUserParams};
+use database_entity::dto::{
+  AFCreatedAccessToken, AFUserAccessTokens, AFUserDevices, AFUserProfile, AFUserWorkspaceInfo,
+  BlockUserParams, BlockedUsers, CreateAccessTokenParams,
+};
+use shared_entity::dto::auth_dto::{
+  ChangeEmailParams, DeleteUserQuery, EmailChangeStatus, MergeAccountParams, MergeAccountSummary,
+  SignInTokenResponse, UpdateUserParams,
+};
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
+use uuid::Uuid;
 
 pub fn user_scope() -> Scope {
   web::scope("/api/user")
     .service(web::resource("/verify/{access_token}").route(web::get().to(verify_user_handler)))
     .service(web::resource("/update").route(web::post().to(update_user_handler)))
+    .service(web::resource("/email").route(web::put().to(change_email_handler)))
+    .service(web::resource("/email/sync").route(web::post().to(sync_email_handler)))
+    .service(web::resource("/merge").route(web::post().to(merge_account_handler)))
     .service(web::resource("/profile").route(web::get().to(get_user_profile_handler)))
     .service(web::resource("/workspace").route(web::get().to(get_user_workspace_info_handler)))
+    .service(
+      web::resource("/blocks")
+        .route(web::get().to(get_blocked_users_handler))
+        .route(web::post().to(block_user_handler)),
+    )
+    .service(web::resource("/blocks/{user_uuid}").route(web::delete().to(unblock_user_handler)))
+    .service(web::resource("/devices").route(web::get().to(get_user_devices_handler)))
+    .service(
+      web::resource("/devices/{device_id}").route(web::delete().to(revoke_user_device_handler)),
+    )
+    .service(
+      web::resource("/access-token")
+        .route(web::get().to(get_access_tokens_handler))
+        .route(web::post().to(create_access_token_handler)),
+    )
+    .service(
+      web::resource("/access-token/{token_id}")
+        .route(web::delete().to(revoke_access_token_handler)),
+    )
     .service(web::resource("").route(web::delete().to(delete_user_handler)))
 }
 
@@ -60,10 +99,60 @@ async fn update_user_handler(
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
   let params = payload.into_inner();
-  update_user(&state.pg_pool, auth.uuid()?, params).await?;
+  update_user(
+    &state.pg_pool,
+    &state.gotrue_client,
+    &auth.token,
+    auth.uuid()?,
+    params,
+  )
+  .await?;
   Ok(AppResponse::Ok().into())
 }
 
+#[tracing::instrument(skip(state, auth, payload), err)]
+async fn change_email_handler(
+  auth: Authorization,
+  payload: Json<ChangeEmailParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<EmailChangeStatus>> {
+  let uid = state.user_cache.get_user_uid(&auth.uuid()?).await?;
+  let status = change_user_email(
+    &state.pg_pool,
+    &state.gotrue_client,
+    &auth.token,
+    uid,
+    &payload.email,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(status).into())
+}
+
+#[tracing::instrument(skip(state, auth), err)]
+async fn sync_email_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<EmailChangeStatus>> {
+  let user = state
+    .gotrue_client
+    .user_info(&auth.token)
+    .await
+    .map_err(AppError::from)?;
+  let status = sync_user_email(&state.pg_pool, &user).await?;
+  Ok(AppResponse::Ok().with_data(status).into())
+}
+
+#[tracing::instrument(skip(state, auth, payload), err)]
+async fn merge_account_handler(
+  auth: Authorization,
+  payload: Json<MergeAccountParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<MergeAccountSummary>> {
+  let summary =
+    merge_user_account(state.as_ref(), &auth.token, &payload.source_access_token).await?;
+  Ok(AppResponse::Ok().with_data(summary).into())
+}
+
 #[tracing::instrument(skip(state), err)]
 async fn delete_user_handler(
   auth: Authorization,
@@ -77,7 +166,7 @@ async fn delete_user_handler(
   } = query.into_inner();
   delete_user(
     &state.pg_pool,
-    &state.bucket_storage,
+    &state.storage_router,
     &state.gotrue_client,
     &state.gotrue_admin,
     &state.config.apple_oauth,
@@ -89,3 +178,106 @@ async fn delete_user_handler(
   .await?;
   Ok(AppResponse::Ok().into())
 }
+
+#[tracing::instrument(skip(state), err)]
+async fn get_blocked_users_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<BlockedUsers>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let users = get_blocked_users(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(users).into())
+}
+
+#[tracing::instrument(skip(state, payload), err)]
+async fn block_user_handler(
+  user_uuid: UserUuid,
+  payload: Json<BlockUserParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  block_user(&state.pg_pool, uid, &user_uuid, &payload.user_uuid).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+#[tracing::instrument(skip(state), err)]
+async fn unblock_user_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  unblock_user(&state.pg_pool, uid, &path.into_inner()).await?;
+  Ok(AppResponse::Ok().into())
+}
+
+#[tracing::instrument(skip(state), err)]
+async fn get_user_devices_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AFUserDevices>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let devices = get_user_devices(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(devices).into())
+}
+
+/// Revokes the device and closes its live realtime connection.
+#[tracing::instrument(skip(state, server), err)]
+async fn revoke_user_device_handler(
+  user_uuid: UserUuid,
+  path: web::Path<String>,
+  state: Data<AppState>,
+  server: Data<RealtimeServerAddr>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let device_id = path.into_inner();
+  revoke_device(&state.pg_pool, &state.user_devices, uid, &device_id).await?;
+  server.do_send(KickOffDevice { uid, device_id });
+  Ok(AppResponse::Ok().into())
+}
+
+/// Issues a personal access token. The access tokens can only be managed with a gotrue session,
+/// so that a leaked token can't issue others.
+#[tracing::instrument(skip(state, auth, payload), err)]
+async fn create_access_token_handler(
+  auth: Authorization,
+  payload: Json<CreateAccessTokenParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AFCreatedAccessToken>> {
+  let uid = state.user_cache.get_user_uid(&auth.uuid()?).await?;
+  let access_token = create_access_token(
+    &state.pg_pool,
+    &state.workspace_access_control,
+    uid,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(access_token).into())
+}
+
+#[tracing::instrument(skip(state, auth), err)]
+async fn get_access_tokens_handler(
+  auth: Authorization,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<AFUserAccessTokens>> {
+  let uid = state.user_cache.get_user_uid(&auth.uuid()?).await?;
+  let access_tokens = get_access_tokens(&state.pg_pool, uid).await?;
+  Ok(AppResponse::Ok().with_data(access_tokens).into())
+}
+
+#[tracing::instrument(skip(state, auth), err)]
+async fn revoke_access_token_handler(
+  auth: Authorization,
+  path: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&auth.uuid()?).await?;
+  revoke_access_token(
+    &state.pg_pool,
+    &state.access_tokens,
+    uid,
+    &path.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().into())
+}
@@ -2,6 +2,7 @@ use crate::domain::compression::{CompressionType, X_COMPRESSION_BUFFER_SIZE, X_C
 use actix_http::header::HeaderMap;
 use actix_web::web::Payload;
 use app_error::AppError;
+use authentication::jwt::Authorization;
 
 use actix_web::HttpRequest;
 use appflowy_ai_client::dto::AIModel;
@@ -181,3 +182,26 @@ pub(crate) fn ai_model_from_header(req: &HttpRequest) -> AIModel {
     })
     .unwrap_or(AIModel::GPT4oMini)
 }
+
+/// Returns the page that referred the visitor to a published view. The web app fetches the
+/// published view on behalf of the visitor, so it forwards the referrer of the page with the
+/// `x-publish-referrer` header. Falls back to the `Referer` header of the request.
+#[inline]
+pub(crate) fn publish_referrer_from_header(req: &HttpRequest) -> Option<&str> {
+  let headers = req.headers();
+  headers
+    .get("x-publish-referrer")
+    .or_else(|| headers.get(actix_web::http::header::REFERER))
+    .and_then(|header| header.to_str().ok())
+    .filter(|referrer| !referrer.is_empty())
+}
+
+/// The role of the administrator of the server, see `setup_admin_account`.
+const SERVER_ADMIN_ROLE: &str = "supabase_admin";
+
+pub(crate) fn enforce_server_admin(auth: &Authorization) -> Result<(), AppError> {
+  if auth.claims.role != SERVER_ADMIN_ROLE {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
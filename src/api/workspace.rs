@@ -1,8 +1,11 @@
 use access_control::act::Action;
+use actix_web::http::header::{
+  ContentLength, ContentType, CACHE_CONTROL, CONTENT_DISPOSITION, ETAG, IF_NONE_MATCH,
+};
 use actix_web::web::{Bytes, Payload};
 use actix_web::web::{Data, Json, PayloadConfig};
 use actix_web::{web, Scope};
-use actix_web::{HttpRequest, Result};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Result};
 use anyhow::{anyhow, Context};
 use bytes::BytesMut;
 use collab::entity::EncodedCollab;
@@ -10,7 +13,11 @@ use collab_entity::CollabType;
 use futures_util::future::try_join_all;
 use prost::Message as ProstMessage;
 use rayon::prelude::*;
+use secrecy::ExposeSecret;
+use serde_json::json;
 use sqlx::types::uuid;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 
 use tokio_stream::StreamExt;
@@ -19,42 +26,69 @@ use tracing::{error, event, instrument, trace};
 use uuid::Uuid;
 use validator::Validate;
 
-use app_error::AppError;
-use appflowy_collaborate::actix_ws::entities::ClientStreamMessage;
+use app_error::{AppError, ErrorDetails};
+use appflowy_collaborate::actix_ws::entities::{ClientStreamMessage, GetWorkspaceRealtimeStats};
 use appflowy_collaborate::indexer::IndexerProvider;
 use authentication::jwt::{Authorization, OptionalUserUuid, UserUuid};
+use authentication::session_policy::SessionAssurance;
 use collab_rt_entity::realtime_proto::HttpRealtimeMessage;
+use collab_rt_entity::user::AFFolderChangeKind;
 use collab_rt_entity::RealtimeMessage;
 use collab_rt_protocol::validate_encode_collab;
 use database::collab::{CollabStorage, GetCollabOrigin};
+use database::insights::upsert_published_view_visit;
 use database::user::select_uid_from_email;
 use database_entity::dto::PublishCollabItem;
 use database_entity::dto::PublishInfo;
 use database_entity::dto::*;
+use shared_entity::dto::publish_dto::{
+  PublishedDatabaseRows, QueryPublishedDatabaseRows, UpdateAutoPublish,
+};
+use shared_entity::dto::search_dto::{
+  FullTextSearchRequest, FullTextSearchResultItem, SemanticSearchRequest, SemanticSearchResultItem,
+};
 use shared_entity::dto::workspace_dto::*;
 use shared_entity::response::AppResponseError;
 use shared_entity::response::{AppResponse, JsonAppResponse};
 
 use crate::api::util::PayloadReader;
-use crate::api::util::{compress_type_from_header_value, device_id_from_headers, CollabValidator};
+use crate::api::util::{
+  compress_type_from_header_value, device_id_from_headers, enforce_server_admin,
+  publish_referrer_from_header, CollabValidator,
+};
 use crate::api::ws::RealtimeServerAddr;
 use crate::biz;
 use crate::biz::collab::ops::{
-  get_user_favorite_folder_views, get_user_recent_folder_views, get_user_trash_folder_views,
+  get_guest_granted_views, get_user_favorite_folder_views, get_user_recent_folder_views,
+  get_user_trash_folder_views,
 };
+use crate::biz::search::{full_text_search, semantic_search};
 use crate::biz::user::user_verify::verify_token;
 use crate::biz::workspace;
+use crate::biz::workspace::audit_log::record_audit_event;
+use crate::biz::workspace::folder_change::notify_folder_change;
+use crate::biz::workspace::ip_allowlist::client_ip;
 use crate::biz::workspace::ops::{
   create_comment_on_published_view, create_reaction_on_comment, get_comments_on_published_view,
-  get_reactions_on_published_view, remove_comment_on_published_view, remove_reaction_on_comment,
+  get_comments_on_published_views, get_reactions_on_published_view,
+  remove_comment_on_published_view, remove_reaction_on_comment, update_comment_on_published_view,
 };
 use crate::biz::workspace::page_view::{
-  create_page, get_page_view_collab, update_page_collab_data,
+  append_block_to_page, create_page, duplicate_page, get_collab_sync_state,
+  get_collab_updates_since, get_page_view_collab, get_page_view_content, move_page_to_trash,
+  reorder_page, update_page, update_page_collab_data, validate_page_collab_update,
+};
+use crate::biz::workspace::public_access::{
+  get_public_workspace_collab, get_public_workspace_folder,
 };
 use crate::biz::workspace::publish::get_workspace_default_publish_view_info_meta;
+use crate::biz::workspace::publish_analytics::PublishAnalyticsRecorder;
+use crate::biz::workspace::publish_database::get_published_database_rows;
+use crate::biz::workspace::secret_scan::{get_workspace_secret_findings, scan_document_write};
 use crate::domain::compression::{
   blocking_decompress, decompress, CompressionType, X_COMPRESSION_TYPE,
 };
+use crate::middleware::api_usage_mw::ApiUsageCounter;
 use crate::state::AppState;
 
 pub const WORKSPACE_ID_PATH: &str = "workspace_id";
@@ -80,12 +114,35 @@ pub fn workspace_scope() -> Scope {
     .service(
       web::resource("/{workspace_id}/invite").route(web::post().to(post_workspace_invite_handler)), // invite members to workspace
     )
+    .service(
+      web::resource("/{workspace_id}/invite/batch")
+        .route(web::post().to(post_workspace_batch_invite_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite/bulk")
+        .app_data(
+          PayloadConfig::new(2 * 1024 * 1024), // 2 MB
+        )
+        .route(web::post().to(post_workspace_bulk_invite_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite/bulk/{task_id}")
+        .route(web::get().to(get_workspace_bulk_invite_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/invite/bulk/{task_id}/errors")
+        .route(web::get().to(get_workspace_bulk_invite_errors_handler)),
+    )
     .service(
       web::resource("/invite").route(web::get().to(get_workspace_invite_handler)), // show invites for user
     )
     .service(
       web::resource("/invite/{invite_id}").route(web::get().to(get_workspace_invite_by_id_handler)),
     )
+    .service(
+      web::resource("/invite/{invite_id}/preview")
+        .route(web::get().to(get_workspace_invite_preview_handler)),
+    )
     .service(
       web::resource("/accept-invite/{invite_id}")
         .route(web::post().to(post_accept_workspace_invite_handler)), // accept invitation to workspace
@@ -96,7 +153,63 @@ pub fn workspace_scope() -> Scope {
         .route(web::get().to(get_workspace_settings_handler))
         .route(web::post().to(post_workspace_settings_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/insights")
+        .route(web::get().to(get_workspace_insights_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/analytics/{report}")
+        .route(web::get().to(get_analytics_report_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-access-log")
+        .route(web::get().to(get_publish_access_log_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/analytics")
+        .route(web::get().to(get_publish_analytics_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/api-usage")
+        .route(web::get().to(get_workspace_api_usage_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/audit-log")
+        .route(web::get().to(get_workspace_audit_log_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/audit/permissions")
+        .route(web::get().to(get_permission_audit_log_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/saml")
+        .route(web::get().to(get_workspace_saml_config_handler))
+        .route(web::put().to(put_workspace_saml_config_handler))
+        .route(web::delete().to(delete_workspace_saml_config_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/domain")
+        .route(web::get().to(list_workspace_domains_handler))
+        .route(web::post().to(add_workspace_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/domain/{domain}/verify")
+        .route(web::post().to(verify_workspace_domain_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/domain/{domain}")
+        .route(web::delete().to(delete_workspace_domain_handler)),
+    )
     .service(web::resource("/{workspace_id}/open").route(web::put().to(open_workspace_handler)))
+    .service(
+      web::resource("/{workspace_id}/duplicate").route(web::post().to(duplicate_workspace_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/archive").route(web::put().to(archive_workspace_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/unarchive").route(web::put().to(unarchive_workspace_handler)),
+    )
     .service(web::resource("/{workspace_id}/leave").route(web::post().to(leave_workspace_handler)))
     .service(
       web::resource("/{workspace_id}/member")
@@ -104,6 +217,31 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(update_workspace_member_handler))
         .route(web::delete().to(remove_workspace_member_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/member/paginated")
+        .route(web::get().to(get_workspace_members_paginated_handler)),
+    )
+    .service(web::resource("/{workspace_id}/oembed").route(web::post().to(post_oembed_handler)))
+    .service(
+      web::resource("/{workspace_id}/secret-finding")
+        .route(web::get().to(list_workspace_secret_findings_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/public-access")
+        .route(web::get().to(get_workspace_public_access_handler))
+        .route(web::put().to(put_workspace_public_access_handler)),
+    )
+    .service(
+      web::resource("/public/{workspace_id}/folder")
+        .route(web::get().to(get_public_workspace_folder_handler)),
+    )
+    .service(
+      web::resource("/public/{workspace_id}/collab/{object_id}")
+        .route(web::get().to(get_public_workspace_collab_handler)),
+    )
+    .service(
+      web::resource("/public/share-link/{token}").route(web::get().to(get_shared_page_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/member/user/{user_id}")
         .route(web::get().to(get_workspace_member_handler)),
@@ -126,12 +264,115 @@ pub fn workspace_scope() -> Scope {
       web::resource("/v1/{workspace_id}/collab/{object_id}/web-update")
         .route(web::post().to(post_web_update_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/validate-update")
+        .route(web::post().to(post_validate_update_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/updates/since")
+        .route(web::post().to(post_collab_updates_since_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/sync-state")
+        .route(web::get().to(get_collab_sync_state_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-token")
+        .route(web::post().to(post_collab_share_token_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/share-token/{token_id}")
+        .route(web::delete().to(delete_collab_share_token_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/share-link")
+        .route(web::get().to(list_workspace_share_links_handler))
+        .route(web::delete().to(revoke_workspace_share_links_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/page-view").route(web::post().to(post_page_view_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/page-view/operations")
+        .route(web::post().to(post_page_operations_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/batch")
+        .route(web::post().to(post_page_view_batch_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/import/page").route(web::post().to(import_page_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/page-view/{view_id}")
-        .route(web::get().to(get_page_view_handler)),
+        .route(web::get().to(get_page_view_handler))
+        .route(web::patch().to(patch_page_view_handler))
+        .route(web::delete().to(delete_page_view_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/content")
+        .route(web::get().to(get_page_view_content_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/share-link")
+        .route(web::get().to(list_page_share_links_handler))
+        .route(web::post().to(post_page_share_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/share-link/{link_id}")
+        .route(web::delete().to(delete_page_share_link_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/permission")
+        .route(web::get().to(get_view_permissions_handler))
+        .route(web::put().to(put_view_permissions_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/member-group")
+        .route(web::get().to(list_member_groups_handler))
+        .route(web::post().to(post_member_group_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/member-group/{group_id}")
+        .route(web::put().to(put_member_group_handler))
+        .route(web::delete().to(delete_member_group_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/roles")
+        .route(web::get().to(list_workspace_roles_handler))
+        .route(web::post().to(post_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/roles/{role_id}")
+        .route(web::patch().to(patch_workspace_role_handler))
+        .route(web::delete().to(delete_workspace_role_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/roles/{role_id}/members")
+        .route(web::put().to(put_workspace_role_members_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/duplicate")
+        .route(web::post().to(duplicate_page_view_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/reorder")
+        .route(web::post().to(reorder_page_view_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/page-view/{view_id}/append-block")
+        .route(web::post().to(append_block_to_page_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/template")
+        .route(web::get().to(list_page_templates_handler))
+        .route(web::post().to(post_page_template_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/template/{template_id}")
+        .route(web::get().to(get_page_template_handler))
+        .route(web::patch().to(patch_page_template_handler))
+        .route(web::delete().to(delete_page_template_handler)),
     )
     .service(
       web::resource("/{workspace_id}/batch/collab")
@@ -140,6 +381,10 @@ pub fn workspace_scope() -> Scope {
     .service(
       web::resource("/{workspace_id}/usage").route(web::get().to(get_workspace_usage_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/realtime/stats")
+        .route(web::get().to(get_workspace_realtime_stats_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/{object_id}/snapshot")
         .route(web::get().to(get_collab_snapshot_handler))
@@ -149,6 +394,31 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/{object_id}/snapshot/list")
         .route(web::get().to(get_all_collab_snapshot_list_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/{object_id}/snapshot/{snapshot_id}/restore")
+        .route(web::post().to(restore_collab_snapshot_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/restore")
+        .route(web::post().to(restore_trashed_collab_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/purge")
+        .route(web::delete().to(purge_trashed_collab_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/redact")
+        .route(web::post().to(redact_collab_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/stats")
+        .route(web::get().to(get_document_stats_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/export")
+        .route(web::get().to(export_document_handler))
+        .route(web::post().to(post_export_document_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab/{object_id}/member")
         .route(web::post().to(add_collab_member_handler))
@@ -156,6 +426,8 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(update_collab_member_handler))
         .route(web::delete().to(remove_collab_member_handler)),
     )
+    // The publish namespace may include a sub-namespace, with the slash percent-encoded, e.g.
+    // `acme%2Fhandbook`.
     .service(
       web::resource("/published/{publish_namespace}")
         .route(web::get().to(get_default_published_collab_info_meta_handler)),
@@ -164,10 +436,23 @@ pub fn workspace_scope() -> Scope {
       web::resource("/v1/published/{publish_namespace}/{publish_name}")
         .route(web::get().to(get_v1_published_collab_handler)),
     )
+    .service(
+      web::resource("/v1/published/{publish_namespace}/{publish_name}/meta/seo")
+        .route(web::get().to(get_published_view_seo_handler))
+        .route(web::patch().to(patch_published_view_seo_handler)),
+    )
+    .service(
+      web::resource("/published/{publish_namespace}/sitemap.xml")
+        .route(web::get().to(get_published_sitemap_handler)),
+    )
     .service(
       web::resource("/published/{publish_namespace}/{publish_name}/blob")
         .route(web::get().to(get_published_collab_blob_handler)),
     )
+    .service(
+      web::resource("/published/{publish_namespace}/{publish_name}/database-rows")
+        .route(web::post().to(post_published_database_rows_handler)),
+    )
     .service(
       web::resource("{workspace_id}/published-duplicate")
         .route(web::post().to(post_published_duplicate_handler)),
@@ -184,8 +469,43 @@ pub fn workspace_scope() -> Scope {
       web::resource("/published-info/{view_id}/comment")
         .route(web::get().to(get_published_collab_comment_handler))
         .route(web::post().to(post_published_collab_comment_handler))
+        .route(web::put().to(put_published_collab_comment_handler))
         .route(web::delete().to(delete_published_collab_comment_handler)),
     )
+    .service(
+      web::resource("/published-info/{view_id}/comment/moderation")
+        .route(web::post().to(post_comment_moderation_handler)),
+    )
+    .service(
+      web::resource("/published-info/{view_id}/comment/moderation/blocklist")
+        .route(web::get().to(get_comment_blocklist_handler))
+        .route(web::post().to(post_comment_blocklist_handler))
+        .route(web::delete().to(delete_comment_blocklist_handler)),
+    )
+    .service(
+      web::resource("/published-info/{view_id}/comment/attachment")
+        .route(web::post().to(post_published_collab_comment_attachment_handler)),
+    )
+    .service(
+      web::resource("/published-info/{view_id}/comment/subscription")
+        .route(web::post().to(post_comment_subscription_handler)),
+    )
+    .service(
+      web::resource("/published-info/{view_id}/comment/subscription/confirm")
+        .route(web::post().to(confirm_comment_subscription_handler)),
+    )
+    .service(
+      web::resource("/published-info/{view_id}/comment/subscription/unsubscribe")
+        .route(web::post().to(unsubscribe_comment_subscription_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/published-info/{view_id}/comment/subscriber-count")
+        .route(web::get().to(get_comment_subscriber_count_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/comments/batch")
+        .route(web::post().to(batch_get_comments_handler)),
+    )
     .service(
       web::resource("/published-info/{view_id}/reaction")
         .route(web::get().to(get_published_collab_reaction_handler))
@@ -197,12 +517,50 @@ pub fn workspace_scope() -> Scope {
         .route(web::put().to(put_publish_namespace_handler))
         .route(web::get().to(get_publish_namespace_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/publish-namespace/embed-allowlist")
+        .route(web::put().to(put_publish_embed_allowlist_handler))
+        .route(web::get().to(get_publish_embed_allowlist_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-namespace/sub")
+        .route(web::get().to(list_publish_sub_namespace_handler))
+        .route(web::post().to(post_publish_sub_namespace_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-namespace/sub/{sub_namespace}")
+        .route(web::put().to(put_publish_sub_namespace_handler))
+        .route(web::delete().to(delete_publish_sub_namespace_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish-namespace/sub/{sub_namespace}/view")
+        .route(web::post().to(post_publish_sub_namespace_views_handler))
+        .route(web::delete().to(delete_publish_sub_namespace_views_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publishers")
+        .route(web::get().to(list_workspace_publishers_handler))
+        .route(web::put().to(put_workspace_publisher_handler))
+        .route(web::delete().to(delete_workspace_publisher_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/publish-default")
         .route(web::put().to(put_workspace_default_published_view_handler))
         .route(web::delete().to(delete_workspace_default_published_view_handler))
         .route(web::get().to(get_workspace_published_default_info_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/auto-publish")
+        .route(web::put().to(put_auto_publish_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/versions")
+        .route(web::get().to(list_published_view_versions_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/publish/{view_id}/rollback/{version}")
+        .route(web::post().to(post_published_view_rollback_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/publish")
         .route(web::post().to(post_publish_collabs_handler))
@@ -212,11 +570,27 @@ pub fn workspace_scope() -> Scope {
     .service(
       web::resource("/{workspace_id}/folder").route(web::get().to(get_workspace_folder_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/folder/views/{view_id}")
+        .route(web::get().to(get_workspace_folder_view_handler)),
+    )
     .service(web::resource("/{workspace_id}/recent").route(web::get().to(get_recent_views_handler)))
     .service(
       web::resource("/{workspace_id}/favorite").route(web::get().to(get_favorite_views_handler)),
     )
     .service(web::resource("/{workspace_id}/trash").route(web::get().to(get_trash_views_handler)))
+    .service(web::resource("/{workspace_id}/search").route(web::get().to(full_text_search_handler)))
+    .service(
+      web::resource("/{workspace_id}/search/semantic")
+        .route(web::post().to(semantic_search_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/guest").route(web::post().to(post_workspace_guest_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/guest/{guest_uid}/view")
+        .route(web::get().to(get_guest_views_handler)),
+    )
     .service(
       web::resource("/published-outline/{publish_namespace}")
         .route(web::get().to(get_workspace_publish_outline_handler)),
@@ -225,12 +599,34 @@ pub fn workspace_scope() -> Scope {
       web::resource("/{workspace_id}/collab/{object_id}/member/list")
         .route(web::get().to(get_collab_member_list_handler)),
     )
+    .service(web::resource("/{workspace_id}/emoji").route(web::get().to(list_custom_emoji_handler)))
+    .service(
+      web::resource("/{workspace_id}/emoji/{shortcode}")
+        .route(web::put().to(put_custom_emoji_handler))
+        .route(web::delete().to(delete_custom_emoji_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/database/{database_id}/presence")
+        .route(web::get().to(get_database_presence_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/awareness")
+        .route(web::get().to(get_collab_awareness_handler)),
+    )
+    .service(
+      web::resource("/{workspace_id}/collab/{object_id}/presence")
+        .route(web::get().to(get_collab_presence_handler)),
+    )
     .service(
       web::resource("/{workspace_id}/collab_list")
       .route(web::get().to(batch_get_collab_handler))
       // Web browser can't carry payload when using GET method, so for browser compatibility, we use POST method
       .route(web::post().to(batch_get_collab_handler)),
     )
+    .service(
+      web::resource("/{workspace_id}/collab_list/stream")
+        .route(web::post().to(batch_get_collab_stream_handler)),
+    )
 }
 
 pub fn collab_scope() -> Scope {
@@ -250,25 +646,56 @@ async fn create_workspace_handler(
   state: Data<AppState>,
   create_workspace_param: Json<CreateWorkspaceParam>,
 ) -> Result<Json<AppResponse<AFWorkspace>>> {
-  let workspace_name = create_workspace_param
-    .into_inner()
-    .workspace_name
-    .unwrap_or_else(|| format!("workspace_{}", chrono::Utc::now().timestamp()));
+  let CreateWorkspaceParam {
+    workspace_name,
+    residency,
+  } = create_workspace_param.into_inner();
+  let workspace_name =
+    workspace_name.unwrap_or_else(|| format!("workspace_{}", chrono::Utc::now().timestamp()));
 
   let uid = state.user_cache.get_user_uid(&uuid).await?;
   let new_workspace = workspace::ops::create_workspace_for_user(
     &state.pg_pool,
     state.workspace_access_control.clone(),
     &state.collab_access_control_storage,
+    &state.storage_router,
     &uuid,
     uid,
     &workspace_name,
+    residency.as_deref(),
   )
   .await?;
 
   Ok(AppResponse::Ok().with_data(new_workspace).into())
 }
 
+#[instrument(skip_all, err)]
+async fn duplicate_workspace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<DuplicateWorkspaceParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFWorkspace>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let new_workspace = workspace::duplicate::duplicate_workspace(
+    &state.pg_pool,
+    state.workspace_access_control.clone(),
+    &state.collab_access_control_storage,
+    &state.storage_router,
+    &user_uuid,
+    uid,
+    &workspace_id,
+    payload.into_inner().workspace_name,
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(new_workspace).into())
+}
+
 // Edit existing workspace
 #[instrument(skip_all, err)]
 async fn patch_workspace_handler(
@@ -289,6 +716,18 @@ async fn patch_workspace_handler(
     params.workspace_icon.as_deref(),
   )
   .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &params.workspace_id,
+    uid,
+    WorkspaceAuditAction::WorkspaceUpdated,
+    None,
+    json!({
+      "workspace_name": params.workspace_name,
+      "workspace_icon": params.workspace_icon,
+    }),
+  )
+  .await;
   Ok(AppResponse::Ok().into())
 }
 
@@ -305,7 +744,44 @@ async fn delete_workspace_handler(
   workspace::ops::delete_workspace_for_user(
     state.pg_pool.clone(),
     *workspace_id,
-    state.bucket_storage.clone(),
+    state.storage_router.clone(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn archive_workspace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  update_workspace_archived(user_uuid, workspace_id.into_inner(), state, true).await
+}
+
+async fn unarchive_workspace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  update_workspace_archived(user_uuid, workspace_id.into_inner(), state, false).await
+}
+
+async fn update_workspace_archived(
+  user_uuid: UserUuid,
+  workspace_id: Uuid,
+  state: Data<AppState>,
+  archived: bool,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  workspace::ops::update_workspace_archived(
+    &state.pg_pool,
+    state.realtime_access_control.clone(),
+    &workspace_id,
+    archived,
   )
   .await?;
   Ok(AppResponse::Ok().into())
@@ -318,10 +794,12 @@ async fn list_workspace_handler(
   state: Data<AppState>,
   query: web::Query<QueryWorkspaceParam>,
 ) -> Result<JsonAppResponse<Vec<AFWorkspace>>> {
+  let query = query.into_inner();
   let workspaces = workspace::ops::get_all_user_workspaces(
     &state.pg_pool,
     &uuid,
-    query.into_inner().include_member_count.unwrap_or(false),
+    query.include_member_count.unwrap_or(false),
+    query.include_archived.unwrap_or(false),
   )
   .await?;
   Ok(AppResponse::Ok().with_data(workspaces).into())
@@ -335,12 +813,21 @@ async fn post_workspace_invite_handler(
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  state
-    .workspace_access_control
-    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
-    .await?;
-
   let invited_members = payload.into_inner();
+  let invited_roles = invited_members
+    .iter()
+    .map(|member| (member.email.clone(), member.role.clone()))
+    .collect::<Vec<_>>();
+  workspace::custom_role::enforce_invite(
+    &state.workspace_access_control,
+    &uid,
+    &workspace_id,
+    &invited_roles
+      .iter()
+      .map(|(_, role)| role.clone())
+      .collect::<Vec<_>>(),
+  )
+  .await?;
   workspace::ops::invite_workspace_members(
     &state.mailer,
     &state.gotrue_admin,
@@ -349,26 +836,204 @@ async fn post_workspace_invite_handler(
     &user_uuid,
     &workspace_id,
     invited_members,
+    None,
     state.config.appflowy_web_url.as_deref(),
   )
   .await?;
+  for (email, role) in invited_roles {
+    record_audit_event(
+      &state.pg_pool,
+      &workspace_id,
+      uid,
+      WorkspaceAuditAction::MemberInvited,
+      Some(&email),
+      json!({ "role": role }),
+    )
+    .await;
+  }
   Ok(AppResponse::Ok().into())
 }
 
-async fn get_workspace_invite_handler(
+#[instrument(skip(payload, state), err)]
+async fn post_workspace_guest_handler(
   user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<InviteWorkspaceGuest>,
   state: Data<AppState>,
-  query: web::Query<WorkspaceInviteQuery>,
-) -> Result<JsonAppResponse<Vec<AFWorkspaceInvitation>>> {
-  let query = query.into_inner();
-  let res =
-    workspace::ops::list_workspace_invitations_for_user(&state.pg_pool, &user_uuid, query.status)
-      .await?;
-  Ok(AppResponse::Ok().with_data(res).into())
-}
-
-async fn get_workspace_invite_by_id_handler(
-  user_uuid: UserUuid,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::custom_role::enforce_invite(
+    &state.workspace_access_control,
+    &uid,
+    &workspace_id,
+    &[AFRole::Guest],
+  )
+  .await?;
+  let params = payload.into_inner();
+  let email = params.email.clone();
+  let details = json!({ "view_ids": params.view_ids });
+  workspace::guest::invite_workspace_guest(
+    &state.mailer,
+    &state.gotrue_admin,
+    &state.pg_pool,
+    &state.gotrue_client,
+    &state.collab_access_control_storage,
+    state.collab_access_control.clone(),
+    &user_uuid,
+    &workspace_id,
+    params,
+    state.config.appflowy_web_url.as_deref(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::GuestInvited,
+    Some(&email),
+    details,
+  )
+  .await;
+  Ok(AppResponse::Ok().into())
+}
+
+async fn post_workspace_batch_invite_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<BatchInviteWorkspaceMembers>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<Vec<InvitationResult>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = payload.into_inner();
+  let invited_roles = params
+    .invitations
+    .iter()
+    .map(|invitation| (invitation.email.clone(), invitation.role.clone()))
+    .collect::<HashMap<_, _>>();
+  workspace::custom_role::enforce_invite(
+    &state.workspace_access_control,
+    &uid,
+    &workspace_id,
+    &invited_roles.values().cloned().collect::<Vec<_>>(),
+  )
+  .await?;
+  let results = workspace::bulk_invite::batch_invite_workspace_members(
+    &state.mailer,
+    &state.gotrue_admin,
+    &state.pg_pool,
+    &state.gotrue_client,
+    &user_uuid,
+    &workspace_id,
+    params,
+    state.config.appflowy_web_url.as_deref(),
+  )
+  .await?;
+  for result in results.iter().filter(|result| result.is_success()) {
+    record_audit_event(
+      &state.pg_pool,
+      &workspace_id,
+      uid,
+      WorkspaceAuditAction::MemberInvited,
+      Some(&result.email),
+      json!({ "role": invited_roles.get(&result.email) }),
+    )
+    .await;
+  }
+  Ok(AppResponse::Ok().with_data(results).into())
+}
+
+async fn post_workspace_bulk_invite_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  content: Bytes,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<BulkInviteTask>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::custom_role::enforce_invite(&state.workspace_access_control, &uid, &workspace_id, &[])
+    .await?;
+  let can_invite_owners = state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await
+    .is_ok();
+
+  let task = workspace::bulk_invite::create_bulk_invite(
+    state.mailer.clone(),
+    state.gotrue_admin.clone(),
+    state.pg_pool.clone(),
+    state.gotrue_client.clone(),
+    *user_uuid,
+    uid,
+    workspace_id,
+    &content,
+    can_invite_owners,
+    state.config.appflowy_web_url.clone(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(task).into())
+}
+
+async fn get_workspace_bulk_invite_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<BulkInviteTask>> {
+  let (workspace_id, task_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_capability(&uid, &workspace_id.to_string(), WorkspaceCapability::Invite)
+    .await?;
+
+  let task =
+    workspace::bulk_invite::get_bulk_invite_task(&state.pg_pool, &workspace_id, &task_id).await?;
+  Ok(AppResponse::Ok().with_data(task).into())
+}
+
+async fn get_workspace_bulk_invite_errors_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (workspace_id, task_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_capability(&uid, &workspace_id.to_string(), WorkspaceCapability::Invite)
+    .await?;
+
+  let report =
+    workspace::bulk_invite::get_bulk_invite_error_report(&state.pg_pool, &workspace_id, &task_id)
+      .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/csv")
+      .insert_header((
+        CONTENT_DISPOSITION,
+        format!(
+          "attachment; filename=\"bulk-invite-{}-errors.csv\"",
+          task_id
+        ),
+      ))
+      .body(report),
+  )
+}
+
+async fn get_workspace_invite_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  query: web::Query<WorkspaceInviteQuery>,
+) -> Result<JsonAppResponse<Vec<AFWorkspaceInvitation>>> {
+  let query = query.into_inner();
+  let res =
+    workspace::ops::list_workspace_invitations_for_user(&state.pg_pool, &user_uuid, query.status)
+      .await?;
+  Ok(AppResponse::Ok().with_data(res).into())
+}
+
+async fn get_workspace_invite_by_id_handler(
+  user_uuid: UserUuid,
   state: Data<AppState>,
   invite_id: web::Path<Uuid>,
 ) -> Result<JsonAppResponse<AFWorkspaceInvitation>> {
@@ -379,6 +1044,16 @@ async fn get_workspace_invite_by_id_handler(
   Ok(AppResponse::Ok().with_data(res).into())
 }
 
+/// Doesn't require authentication, the invitee might not have signed up yet.
+async fn get_workspace_invite_preview_handler(
+  state: Data<AppState>,
+  invite_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<AFWorkspaceInvitationPreview>> {
+  let invite_id = invite_id.into_inner();
+  let res = workspace::ops::get_workspace_invitation_preview(&state.pg_pool, &invite_id).await?;
+  Ok(AppResponse::Ok().with_data(res).into())
+}
+
 async fn post_accept_workspace_invite_handler(
   auth: Authorization,
   invite_id: web::Path<Uuid>,
@@ -388,14 +1063,24 @@ async fn post_accept_workspace_invite_handler(
   let user_uuid = auth.uuid()?;
   let user_uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let invite_id = invite_id.into_inner();
-  workspace::ops::accept_workspace_invite(
+  let invitation = workspace::ops::accept_workspace_invite(
     &state.pg_pool,
     state.workspace_access_control.clone(),
+    state.collab_access_control.clone(),
     user_uid,
     &user_uuid,
     &invite_id,
   )
   .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &invitation.workspace_id,
+    user_uid,
+    WorkspaceAuditAction::MemberJoined,
+    None,
+    json!({ "role": invitation.role, "inviter_uid": invitation.inviter_uid }),
+  )
+  .await;
   Ok(AppResponse::Ok().into())
 }
 
@@ -414,500 +1099,2098 @@ async fn get_workspace_settings_handler(
   Ok(AppResponse::Ok().with_data(settings).into())
 }
 
-#[instrument(level = "info", skip_all, err, fields(user_uuid))]
-async fn post_workspace_settings_handler(
+/// Only the owner of the workspace can see the insights of the workspace.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_workspace_insights_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
-  data: Json<AFWorkspaceSettingsChange>,
-) -> Result<JsonAppResponse<AFWorkspaceSettings>> {
-  let data = data.into_inner();
-  trace!("workspace settings: {:?}", data);
+  query: web::Query<QueryWorkspaceInsights>,
+) -> Result<JsonAppResponse<WorkspaceInsights>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
-    .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let settings =
-    workspace::ops::update_workspace_settings(&state.pg_pool, &workspace_id, data).await?;
-  Ok(AppResponse::Ok().with_data(settings).into())
+  let range = query.into_inner().range.unwrap_or_default();
+  let insights =
+    workspace::insights::get_workspace_insights(&state.pg_pool, &workspace_id, range).await?;
+  Ok(AppResponse::Ok().with_data(insights).into())
 }
 
-#[instrument(skip_all, err)]
-async fn get_workspace_members_handler(
+/// Only the owner of the workspace can run the analytics reports. The report is returned as a CSV
+/// file when `format=csv`.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_analytics_report_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
-  workspace_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<Vec<AFWorkspaceMember>>> {
+  path: web::Path<(Uuid, AnalyticsReport)>,
+  query: web::Query<QueryAnalyticsReport>,
+) -> Result<HttpResponse> {
+  let (workspace_id, report) = path.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
-    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let members = workspace::ops::get_workspace_members(&state.pg_pool, &workspace_id)
-    .await?
-    .into_iter()
-    .map(|member| AFWorkspaceMember {
-      name: member.name,
-      email: member.email,
-      role: member.role,
-      avatar_url: None,
-    })
-    .collect();
-
-  Ok(AppResponse::Ok().with_data(members).into())
+  let query = query.into_inner();
+  let data = workspace::analytics_report::run_analytics_report(
+    &state.pg_read_pool,
+    &workspace_id,
+    report,
+    &query,
+  )
+  .await?;
+  match query.format.unwrap_or_default() {
+    AnalyticsFormat::Json => Ok(HttpResponse::Ok().json(AppResponse::Ok().with_data(data))),
+    AnalyticsFormat::Csv => {
+      let csv = workspace::analytics_report::analytics_report_to_csv(&data)?;
+      Ok(
+        HttpResponse::Ok()
+          .content_type("text/csv")
+          .insert_header((
+            CONTENT_DISPOSITION,
+            format!(
+              "attachment; filename=\"{}-{}.csv\"",
+              report.as_str(),
+              data.until.format("%Y-%m-%d")
+            ),
+          ))
+          .body(csv),
+      )
+    },
+  }
 }
 
-#[instrument(skip_all, err)]
-async fn remove_workspace_member_handler(
+/// Only the owner of the workspace can see the access log of the published views.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_publish_access_log_handler(
   user_uuid: UserUuid,
-  payload: Json<WorkspaceMembers>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<()>> {
+  query: web::Query<QueryPublishAccessLog>,
+) -> Result<JsonAppResponse<PublishAccessLog>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-
-  let member_emails = payload
-    .into_inner()
-    .0
-    .into_iter()
-    .map(|member| member.0)
-    .collect::<Vec<String>>();
-  workspace::ops::remove_workspace_members(
-    &state.pg_pool,
-    &workspace_id,
-    &member_emails,
-    state.workspace_access_control.clone(),
-  )
-  .await?;
-
-  Ok(AppResponse::Ok().into())
+  let access_log =
+    workspace::insights::get_publish_access_log(&state.pg_pool, &workspace_id, query.days).await?;
+  Ok(AppResponse::Ok().with_data(access_log).into())
 }
 
-#[instrument(skip_all, err)]
-async fn get_workspace_member_handler(
+/// Only the owner of the workspace can see the analytics of the published views. The views
+/// recorded since the last flush are written first, so that the report is up to date.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_publish_analytics_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
-  path: web::Path<(Uuid, i64)>,
-) -> Result<JsonAppResponse<AFWorkspaceMember>> {
-  let (workspace_id, user_uuid_to_retrieved) = path.into_inner();
+  publish_analytics: Data<Arc<PublishAnalyticsRecorder>>,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryPublishAnalytics>,
+) -> Result<JsonAppResponse<PublishAnalytics>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  // Guest users can not get workspace members
   state
     .workspace_access_control
-    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let member_row =
-    workspace::ops::get_workspace_member(&user_uuid_to_retrieved, &state.pg_pool, &workspace_id)
+  workspace::publish_analytics::flush_publish_analytics(&state.pg_pool, &publish_analytics).await?;
+  let analytics =
+    workspace::publish_analytics::get_publish_analytics(&state.pg_pool, &workspace_id, query.days)
       .await?;
-  let member = AFWorkspaceMember {
-    name: member_row.name,
-    email: member_row.email,
-    role: member_row.role,
-    avatar_url: None,
-  };
-
-  Ok(AppResponse::Ok().with_data(member).into())
+  Ok(AppResponse::Ok().with_data(analytics).into())
 }
 
-#[instrument(level = "debug", skip_all, err)]
-async fn open_workspace_handler(
+/// Only the owner of the workspace can see the API usage of the workspace. The calls counted
+/// since the last flush are written first, so that the report is up to date.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_workspace_api_usage_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
+  api_usage_counter: Data<Arc<ApiUsageCounter>>,
   workspace_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<AFWorkspace>> {
-  let workspace_id = workspace_id.into_inner();
+  query: web::Query<QueryWorkspaceApiUsage>,
+) -> Result<JsonAppResponse<WorkspaceApiUsage>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
-    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let workspace = workspace::ops::open_workspace(&state.pg_pool, &user_uuid, &workspace_id).await?;
-  Ok(AppResponse::Ok().with_data(workspace).into())
+  workspace::api_usage::flush_api_usage(&state.pg_pool, &api_usage_counter).await?;
+  let api_usage =
+    workspace::api_usage::get_workspace_api_usage(&state.pg_pool, &workspace_id, query.days)
+      .await?;
+  Ok(AppResponse::Ok().with_data(api_usage).into())
 }
 
-#[instrument(level = "debug", skip_all, err)]
-async fn leave_workspace_handler(
+/// Only the owner of the workspace can see the audit log of the workspace.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_workspace_audit_log_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<()>> {
-  let workspace_id = workspace_id.into_inner();
-  workspace::ops::leave_workspace(
+  query: web::Query<QueryWorkspaceAuditLog>,
+) -> Result<JsonAppResponse<Vec<WorkspaceAuditEvent>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let events = workspace::audit_log::get_workspace_audit_log(
     &state.pg_pool,
     &workspace_id,
-    &user_uuid,
-    state.workspace_access_control.clone(),
+    query.into_inner(),
   )
   .await?;
-  Ok(AppResponse::Ok().into())
+  Ok(AppResponse::Ok().with_data(events).into())
 }
 
-#[instrument(level = "debug", skip_all, err)]
-async fn update_workspace_member_handler(
+/// Only the owner of the workspace can see the permission changes of the workspace.
+#[instrument(skip_all, err, fields(user_uuid))]
+async fn get_permission_audit_log_handler(
   user_uuid: UserUuid,
-  payload: Json<WorkspaceMemberChangeset>,
   state: Data<AppState>,
   workspace_id: web::Path<Uuid>,
-) -> Result<JsonAppResponse<()>> {
-  let workspace_id = workspace_id.into_inner();
+  query: web::Query<QueryPermissionAuditLog>,
+) -> Result<JsonAppResponse<Vec<PermissionAuditEvent>>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
+  let events = workspace::permission_audit::get_permission_audit_log(
+    &state.pg_pool,
+    &workspace_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(AppResponse::Ok().with_data(events).into())
+}
 
-  let changeset = payload.into_inner();
-
-  if changeset.role.is_some() {
-    let changeset_uid = select_uid_from_email(&state.pg_pool, &changeset.email)
-      .await
-      .map_err(AppResponseError::from)?;
-    workspace::ops::update_workspace_member(
-      &changeset_uid,
-      &state.pg_pool,
-      &workspace_id,
-      &changeset,
-      state.workspace_access_control.clone(),
-    )
+async fn get_workspace_saml_config_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<WorkspaceSamlConfig>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  }
-
-  Ok(AppResponse::Ok().into())
+  let config =
+    workspace::saml::get_workspace_saml_config(&state.pg_pool, &state.config.saml, &workspace_id)
+      .await?;
+  Ok(AppResponse::Ok().with_data(config).into())
 }
 
-#[instrument(skip(state, payload), err)]
-async fn create_collab_handler(
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn put_workspace_saml_config_handler(
   user_uuid: UserUuid,
-  payload: Bytes,
   state: Data<AppState>,
-  req: HttpRequest,
-) -> Result<Json<AppResponse<()>>> {
+  workspace_id: web::Path<Uuid>,
+  params: Json<UpdateWorkspaceSamlConfigParams>,
+) -> Result<JsonAppResponse<WorkspaceSamlConfig>> {
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let params = match req.headers().get(X_COMPRESSION_TYPE) {
-    None => serde_json::from_slice::<CreateCollabParams>(&payload).map_err(|err| {
-      AppError::InvalidRequest(format!(
-        "Failed to parse CreateCollabParams from JSON: {}",
-        err
-      ))
-    })?,
-    Some(_) => match compress_type_from_header_value(req.headers())? {
-      CompressionType::Brotli { buffer_size } => {
-        let decompress_data = blocking_decompress(payload.to_vec(), buffer_size).await?;
-        CreateCollabParams::from_bytes(&decompress_data).map_err(|err| {
-          AppError::InvalidRequest(format!(
-            "Failed to parse CreateCollabParams with brotli decompression data: {}",
-            err
-          ))
-        })?
-      },
-    },
-  };
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let config = workspace::saml::update_workspace_saml_config(
+    &state.pg_pool,
+    &state.config.saml,
+    &workspace_id,
+    params.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::SettingsUpdated,
+    None,
+    json!({ "saml_enabled": config.enabled }),
+  )
+  .await;
+  Ok(AppResponse::Ok().with_data(config).into())
+}
 
-  let (mut params, workspace_id) = params.split();
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn delete_workspace_saml_config_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  workspace::saml::delete_workspace_saml_config(&state.pg_pool, &workspace_id).await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::SettingsUpdated,
+    None,
+    json!({ "saml_enabled": false }),
+  )
+  .await;
+  Ok(AppResponse::Ok().into())
+}
 
-  if params.object_id == workspace_id {
-    // Only the object with [CollabType::Folder] can have the same object_id as workspace_id. But
-    // it should use create workspace API
-    return Err(
-      AppError::InvalidRequest("object_id cannot be the same as workspace_id".to_string()).into(),
-    );
-  }
+async fn list_workspace_domains_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<Vec<WorkspaceDomain>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let domains = workspace::domain::list_workspace_domains(&state.pg_pool, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(domains).into())
+}
 
-  if let Err(err) = params.check_encode_collab().await {
-    return Err(
-      AppError::NoRequiredData(format!(
-        "collab doc state is not correct:{},{}",
-        params.object_id, err
-      ))
-      .into(),
-    );
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn add_workspace_domain_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+  params: Json<AddWorkspaceDomainParams>,
+) -> Result<JsonAppResponse<WorkspaceDomain>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let domain =
+    workspace::domain::add_workspace_domain(&state.pg_pool, &workspace_id, &params.domain).await?;
+  Ok(AppResponse::Ok().with_data(domain).into())
+}
+
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn verify_workspace_domain_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, String)>,
+) -> Result<JsonAppResponse<WorkspaceDomain>> {
+  let (workspace_id, domain) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let domain = workspace::domain::verify_workspace_domain(
+    &state.pg_pool,
+    &state.config.saml.dns_over_https_url,
+    &workspace_id,
+    &domain,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::SettingsUpdated,
+    None,
+    json!({ "verified_domain": domain.domain }),
+  )
+  .await;
+  Ok(AppResponse::Ok().with_data(domain).into())
+}
+
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn delete_workspace_domain_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, String)>,
+) -> Result<JsonAppResponse<()>> {
+  let (workspace_id, domain) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  workspace::domain::delete_workspace_domain(&state.pg_pool, &workspace_id, &domain).await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::SettingsUpdated,
+    None,
+    json!({ "deleted_domain": domain }),
+  )
+  .await;
+  Ok(AppResponse::Ok().into())
+}
+
+#[instrument(level = "info", skip_all, err, fields(user_uuid))]
+async fn post_workspace_settings_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+  data: Json<AFWorkspaceSettingsChange>,
+  req: HttpRequest,
+) -> Result<JsonAppResponse<AFWorkspaceSettings>> {
+  let data = data.into_inner();
+  trace!("workspace settings: {:?}", data);
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
+    .await?;
+  if data.ip_allowlist.is_some() || data.require_2fa.is_some() {
+    state
+      .workspace_access_control
+      .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+      .await?;
   }
+  let details = serde_json::to_value(&data)?;
+  let client_ip = client_ip(&req, state.config.client_ip.trusted_proxy_count);
+  let session = req
+    .extensions()
+    .get::<SessionAssurance>()
+    .copied()
+    .unwrap_or(SessionAssurance {
+      has_second_factor: false,
+    });
+  let settings = workspace::ops::update_workspace_settings(
+    &state.pg_pool,
+    &workspace_id,
+    data,
+    client_ip,
+    session,
+  )
+  .await?;
+  state.ip_allowlists.invalidate(&workspace_id);
+  state.two_factor_policy.invalidate(&workspace_id);
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::SettingsUpdated,
+    None,
+    details,
+  )
+  .await;
+  Ok(AppResponse::Ok().with_data(settings).into())
+}
 
-  if state
-    .indexer_provider
-    .can_index_workspace(&workspace_id)
+#[instrument(skip_all, err)]
+async fn get_workspace_members_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<Vec<AFWorkspaceMember>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let members = workspace::ops::get_workspace_members(&state.pg_pool, &workspace_id)
     .await?
-  {
-    match state
-      .indexer_provider
-      .create_collab_embeddings(&params)
-      .await
-    {
-      Ok(embeddings) => params.embeddings = embeddings,
-      Err(err) => tracing::warn!(
-        "failed to fetch embeddings for document {}: {}",
-        params.object_id,
-        err
-      ),
-    }
-  }
+    .into_iter()
+    .map(|member| AFWorkspaceMember {
+      name: member.name,
+      email: member.email,
+      role: member.role,
+      avatar_url: None,
+      expires_at: member.expires_at,
+    })
+    .collect();
 
-  let mut transaction = state
-    .pg_pool
-    .begin()
-    .await
-    .context("acquire transaction to upsert collab")
-    .map_err(AppError::from)?;
+  Ok(AppResponse::Ok().with_data(members).into())
+}
+
+#[instrument(skip_all, err)]
+async fn get_workspace_members_paginated_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<QueryWorkspaceMembers>,
+) -> Result<JsonAppResponse<WorkspaceMembersPage>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let page =
+    workspace::ops::get_workspace_members_page(&state.pg_pool, &workspace_id, query.into_inner())
+      .await?;
+  Ok(AppResponse::Ok().with_data(page).into())
+}
+
+#[instrument(skip(state, payload), err)]
+async fn post_oembed_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<OEmbedParams>,
+) -> Result<JsonAppResponse<OEmbed>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let embed = state.oembed_resolver.resolve(payload.into_inner()).await?;
+  Ok(AppResponse::Ok().with_data(embed).into())
+}
+
+#[instrument(skip(state), err)]
+async fn list_workspace_secret_findings_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<Vec<CollabSecretFinding>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let findings = get_workspace_secret_findings(&state.pg_pool, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(findings).into())
+}
+
+#[instrument(skip(state), err)]
+async fn get_workspace_public_access_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<WorkspacePublicAccess>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let public_access = state.public_workspace_access.get(&workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(public_access).into())
+}
+
+#[instrument(skip(state, payload), err)]
+async fn put_workspace_public_access_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<UpdateWorkspacePublicAccess>,
+) -> Result<JsonAppResponse<WorkspacePublicAccess>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let public_access = state
+    .public_workspace_access
+    .set(&workspace_id, uid, payload.enabled)
+    .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublicAccessUpdated,
+    None,
+    json!({ "enabled": payload.enabled }),
+  )
+  .await;
+  Ok(AppResponse::Ok().with_data(public_access).into())
+}
+
+async fn get_public_workspace_folder_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  query: web::Query<QueryWorkspaceFolder>,
+) -> Result<Json<AppResponse<FolderView>>> {
+  let folder_view = get_public_workspace_folder(
+    &state.collab_access_control_storage,
+    &state.pg_pool,
+    &state.public_workspace_access,
+    workspace_id.into_inner(),
+    query.depth.unwrap_or(1),
+    query.root_view_id.as_deref(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(folder_view)))
+}
+
+async fn get_public_workspace_collab_handler(
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<CollabTypeParam>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabResponse>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let resp = get_public_workspace_collab(
+    &state.collab_access_control_storage,
+    &state.pg_pool,
+    &state.public_workspace_access,
+    workspace_id,
+    &object_id,
+    query.into_inner().collab_type,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
+/// Reads the page shared by the link, without authentication: the token of the link grants the
+/// access to the page.
+async fn get_shared_page_handler(
+  token: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<SharedPage>>> {
+  let shared_page = workspace::page_share_link::get_shared_page(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    state.collab_access_control.clone(),
+    &token.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(shared_page)))
+}
+
+#[instrument(skip_all, err)]
+async fn remove_workspace_member_handler(
+  user_uuid: UserUuid,
+  payload: Json<WorkspaceMembers>,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let member_emails = payload
+    .into_inner()
+    .0
+    .into_iter()
+    .map(|member| member.0)
+    .collect::<Vec<String>>();
+  for email in &member_emails {
+    let member_uid = select_uid_from_email(&state.pg_pool, email)
+      .await
+      .map_err(AppResponseError::from)?;
+    workspace::custom_role::enforce_manage_member(
+      &state.pg_pool,
+      &state.workspace_access_control,
+      &uid,
+      &workspace_id,
+      &member_uid,
+      None,
+    )
+    .await?;
+  }
+  workspace::ops::remove_workspace_members(
+    &state.pg_pool,
+    &workspace_id,
+    &member_emails,
+    uid,
+    state.workspace_access_control.clone(),
+  )
+  .await?;
+  for email in &member_emails {
+    record_audit_event(
+      &state.pg_pool,
+      &workspace_id,
+      uid,
+      WorkspaceAuditAction::MemberRemoved,
+      Some(email),
+      json!({}),
+    )
+    .await;
+  }
+
+  Ok(AppResponse::Ok().into())
+}
+
+#[instrument(skip_all, err)]
+async fn get_workspace_member_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, i64)>,
+) -> Result<JsonAppResponse<AFWorkspaceMember>> {
+  let (workspace_id, user_uuid_to_retrieved) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  // Guest users can not get workspace members
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let member_row =
+    workspace::ops::get_workspace_member(&user_uuid_to_retrieved, &state.pg_pool, &workspace_id)
+      .await?;
+  let member = AFWorkspaceMember {
+    name: member_row.name,
+    email: member_row.email,
+    role: member_row.role,
+    avatar_url: None,
+    expires_at: member_row.expires_at,
+  };
+
+  Ok(AppResponse::Ok().with_data(member).into())
+}
+
+#[instrument(level = "debug", skip_all, err)]
+async fn open_workspace_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<AFWorkspace>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let workspace = workspace::ops::open_workspace(&state.pg_pool, &user_uuid, &workspace_id).await?;
+  Ok(AppResponse::Ok().with_data(workspace).into())
+}
+
+#[instrument(level = "debug", skip_all, err)]
+async fn leave_workspace_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  workspace::ops::leave_workspace(
+    &state.pg_pool,
+    &workspace_id,
+    &user_uuid,
+    uid,
+    state.workspace_access_control.clone(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::MemberLeft,
+    None,
+    json!({}),
+  )
+  .await;
+  Ok(AppResponse::Ok().into())
+}
+
+#[instrument(level = "debug", skip_all, err)]
+async fn update_workspace_member_handler(
+  user_uuid: UserUuid,
+  payload: Json<WorkspaceMemberChangeset>,
+  state: Data<AppState>,
+  workspace_id: web::Path<Uuid>,
+) -> Result<JsonAppResponse<()>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let changeset = payload.into_inner();
+
+  if changeset.role.is_some() || changeset.expiry_changed() {
+    let changeset_uid = select_uid_from_email(&state.pg_pool, &changeset.email)
+      .await
+      .map_err(AppResponseError::from)?;
+    workspace::custom_role::enforce_manage_member(
+      &state.pg_pool,
+      &state.workspace_access_control,
+      &uid,
+      &workspace_id,
+      &changeset_uid,
+      changeset.role.as_ref(),
+    )
+    .await?;
+    workspace::ops::update_workspace_member(
+      &changeset_uid,
+      &state.pg_pool,
+      &workspace_id,
+      &changeset,
+      uid,
+      state.workspace_access_control.clone(),
+    )
+    .await?;
+    record_audit_event(
+      &state.pg_pool,
+      &workspace_id,
+      uid,
+      WorkspaceAuditAction::MemberUpdated,
+      Some(&changeset.email),
+      json!({
+        "role": changeset.role,
+        "expires_at": changeset.expires_at,
+        "clear_expiry": changeset.clear_expiry,
+      }),
+    )
+    .await;
+  }
+
+  Ok(AppResponse::Ok().into())
+}
+
+#[instrument(skip(state, payload), err)]
+async fn create_collab_handler(
+  user_uuid: UserUuid,
+  payload: Bytes,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let params = match req.headers().get(X_COMPRESSION_TYPE) {
+    None => serde_json::from_slice::<CreateCollabParams>(&payload).map_err(|err| {
+      AppError::InvalidRequest(format!(
+        "Failed to parse CreateCollabParams from JSON: {}",
+        err
+      ))
+    })?,
+    Some(_) => match compress_type_from_header_value(req.headers())? {
+      CompressionType::Brotli { buffer_size } => {
+        let decompress_data = blocking_decompress(payload.to_vec(), buffer_size).await?;
+        CreateCollabParams::from_bytes(&decompress_data).map_err(|err| {
+          AppError::InvalidRequest(format!(
+            "Failed to parse CreateCollabParams with brotli decompression data: {}",
+            err
+          ))
+        })?
+      },
+    },
+  };
+
+  let (mut params, workspace_id) = params.split();
+
+  if params.object_id == workspace_id {
+    // Only the object with [CollabType::Folder] can have the same object_id as workspace_id. But
+    // it should use create workspace API
+    return Err(
+      AppError::InvalidRequest("object_id cannot be the same as workspace_id".to_string()).into(),
+    );
+  }
+
+  if let Err(err) = params.check_encode_collab().await {
+    return Err(
+      AppError::NoRequiredData(format!(
+        "collab doc state is not correct:{},{}",
+        params.object_id, err
+      ))
+      .into(),
+    );
+  }
+
+  scan_document_write(
+    &state.pg_pool,
+    &state.mailer,
+    state.config.appflowy_web_url.as_deref(),
+    &Uuid::parse_str(&workspace_id)?,
+    &user_uuid,
+    uid,
+    &params,
+  )
+  .await?;
+
+  if state
+    .indexer_provider
+    .can_index_workspace(&workspace_id)
+    .await?
+  {
+    match state
+      .indexer_provider
+      .create_collab_embeddings(&params)
+      .await
+    {
+      Ok(embeddings) => params.embeddings = embeddings,
+      Err(err) => tracing::warn!(
+        "failed to fetch embeddings for document {}: {}",
+        params.object_id,
+        err
+      ),
+    }
+  }
+
+  let mut transaction = state
+    .pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to upsert collab")
+    .map_err(AppError::from)?;
+
+  let action = format!("Create new collab: {}", params);
+  state
+    .collab_access_control_storage
+    .insert_new_collab_with_transaction(&workspace_id, &uid, params, &mut transaction, &action)
+    .await?;
+
+  transaction
+    .commit()
+    .await
+    .context("fail to commit the transaction to upsert collab")
+    .map_err(AppError::from)?;
+
+  Ok(Json(AppResponse::Ok()))
+}
+
+#[instrument(skip(state, payload), err)]
+async fn batch_create_collab_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  mut payload: Payload,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner().to_string();
+  let compress_type = compress_type_from_header_value(req.headers())?;
+  event!(tracing::Level::DEBUG, "start decompressing collab list");
+
+  let mut payload_buffer = Vec::new();
+  let mut offset_len_list = Vec::new();
+  let mut current_offset = 0;
+  let start = Instant::now();
+  while let Some(item) = payload.next().await {
+    if let Ok(bytes) = item {
+      payload_buffer.extend_from_slice(&bytes);
+      while current_offset + 4 <= payload_buffer.len() {
+        // The length of the next frame is determined by the first 4 bytes
+        let size = u32::from_be_bytes([
+          payload_buffer[current_offset],
+          payload_buffer[current_offset + 1],
+          payload_buffer[current_offset + 2],
+          payload_buffer[current_offset + 3],
+        ]) as usize;
+
+        // Ensure there is enough data for the frame (4 bytes for size + `size` bytes for data)
+        if current_offset + 4 + size > payload_buffer.len() {
+          break;
+        }
+
+        // Collect the (offset, len) for the current frame (data starts at current_offset + 4)
+        offset_len_list.push((current_offset + 4, size));
+        current_offset += 4 + size;
+      }
+    }
+  }
+  // Perform decompression and processing in a Rayon thread pool
+  let mut collab_params_list = tokio::task::spawn_blocking(move || match compress_type {
+    CompressionType::Brotli { buffer_size } => offset_len_list
+      .into_par_iter()
+      .filter_map(|(offset, len)| {
+        let compressed_data = &payload_buffer[offset..offset + len];
+        match decompress(compressed_data.to_vec(), buffer_size) {
+          Ok(decompressed_data) => {
+            let params = CollabParams::from_bytes(&decompressed_data).ok()?;
+            if params.validate().is_ok() {
+              match validate_encode_collab(
+                &params.object_id,
+                &params.encoded_collab_v1,
+                &params.collab_type,
+              ) {
+                Ok(_) => Some(params),
+                Err(_) => None,
+              }
+            } else {
+              None
+            }
+          },
+          Err(err) => {
+            error!("Failed to decompress data: {:?}", err);
+            None
+          },
+        }
+      })
+      .collect::<Vec<_>>(),
+  })
+  .await
+  .map_err(|_| AppError::InvalidRequest("Failed to decompress data".to_string()))?;
+
+  if collab_params_list.is_empty() {
+    return Err(AppError::InvalidRequest("Empty collab params list".to_string()).into());
+  }
+
+  let total_size = collab_params_list
+    .iter()
+    .fold(0, |acc, x| acc + x.encoded_collab_v1.len());
+  event!(
+    tracing::Level::INFO,
+    "decompressed {} collab objects in {:?}",
+    collab_params_list.len(),
+    start.elapsed()
+  );
+
+  let workspace_uuid = Uuid::parse_str(&workspace_id)?;
+  for params in collab_params_list.iter() {
+    scan_document_write(
+      &state.pg_pool,
+      &state.mailer,
+      state.config.appflowy_web_url.as_deref(),
+      &workspace_uuid,
+      &user_uuid,
+      uid,
+      params,
+    )
+    .await?;
+  }
+
+  if state
+    .indexer_provider
+    .can_index_workspace(&workspace_id)
+    .await?
+  {
+    if let Err(err) = fetch_embeddings(&state.indexer_provider, &mut collab_params_list).await {
+      tracing::warn!(
+        "failed to fetch embeddings for {} new documents: {}",
+        collab_params_list.len(),
+        err
+      );
+    }
+  }
+
+  let start = Instant::now();
+  state
+    .collab_access_control_storage
+    .batch_insert_new_collab(&workspace_id, &uid, collab_params_list)
+    .await?;
+
+  event!(
+    tracing::Level::INFO,
+    "inserted collab objects to disk in {:?}, total size:{}",
+    start.elapsed(),
+    total_size
+  );
+
+  Ok(Json(AppResponse::Ok()))
+}
+
+// Deprecated
+async fn get_collab_handler(
+  user_uuid: UserUuid,
+  payload: Json<QueryCollabParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabResponse>>> {
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let params = payload.into_inner();
+  let object_id = params.object_id.clone();
+  let encode_collab = state
+    .collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::User { uid }, params, true)
+    .await
+    .map_err(AppResponseError::from)?;
+
+  let resp = CollabResponse {
+    encode_collab,
+    object_id,
+  };
+
+  Ok(Json(AppResponse::Ok().with_data(resp)))
+}
+
+async fn v1_get_collab_handler(
+  user_uuid: OptionalUserUuid,
+  path: web::Path<(String, String)>,
+  query: web::Query<CollabTypeParam>,
+  state: Data<AppState>,
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  let (workspace_id, object_id) = path.into_inner();
+  let collab_type = query.into_inner().collab_type;
+  // The holders of a share token, or of the token of a page share link, read the collab without
+  // an account, on behalf of the user who issued the token.
+  let origin = if let Some(share_token) = biz::collab::share_token::share_token_from_request(&req) {
+    biz::collab::share_token::verify_collab_share_token(
+      &state.pg_pool,
+      state.collab_access_control.clone(),
+      &state.config.gotrue.jwt_secret,
+      share_token,
+      &workspace_id,
+      &object_id,
+    )
+    .await
+    .map_err(AppResponseError::from)?;
+    GetCollabOrigin::Server
+  } else if let Some(token) = workspace::page_share_link::share_link_token_from_request(&req) {
+    workspace::page_share_link::verify_page_share_link(
+      &state.pg_pool,
+      &state.collab_access_control_storage,
+      state.collab_access_control.clone(),
+      &token,
+      &workspace_id,
+      &object_id,
+    )
+    .await
+    .map_err(AppResponseError::from)?;
+    GetCollabOrigin::Server
+  } else {
+    let user_uuid = user_uuid
+      .as_uuid()
+      .ok_or(AppError::UserUnAuthorized(
+        "Missing or invalid authorization".to_string(),
+      ))
+      .map_err(AppResponseError::from)?;
+    let uid = state
+      .user_cache
+      .get_user_uid(&user_uuid)
+      .await
+      .map_err(AppResponseError::from)?;
+    GetCollabOrigin::User { uid }
+  };
+
+  let param = QueryCollabParams {
+    workspace_id,
+    inner: QueryCollab {
+      object_id: object_id.clone(),
+      collab_type,
+    },
+  };
+
+  let encode_collab = state
+    .collab_access_control_storage
+    .get_encode_collab(origin, param, true)
+    .await
+    .map_err(AppResponseError::from)?;
+
+  let etag = collab_etag(&encode_collab);
+  let is_not_modified = req
+    .headers()
+    .get(IF_NONE_MATCH)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| {
+      value
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag || tag == "*")
+    })
+    .unwrap_or(false);
+  if is_not_modified {
+    return Ok(
+      HttpResponse::NotModified()
+        .append_header((ETAG, etag))
+        .append_header((CACHE_CONTROL, "private, no-cache"))
+        .finish(),
+    );
+  }
+
+  let resp = CollabResponse {
+    encode_collab,
+    object_id,
+  };
+  Ok(
+    HttpResponse::Ok()
+      .append_header((ETAG, etag))
+      .append_header((CACHE_CONTROL, "private, no-cache"))
+      .json(AppResponse::Ok().with_data(resp)),
+  )
+}
+
+/// The ETag of the state of the collab. The document state is part of it because the deletions
+/// don't advance the state vector.
+fn collab_etag(encode_collab: &EncodedCollab) -> String {
+  let mut context = md5::Context::new();
+  context.consume(&encode_collab.state_vector);
+  context.consume(&encode_collab.doc_state);
+  format!("\"{:x}\"", context.compute())
+}
+
+async fn post_web_update_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateCollabWebParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let collab_type = payload.collab_type.clone();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  update_page_collab_data(
+    state.collab_access_control_storage.clone(),
+    state.metrics.appflowy_web_metrics.clone(),
+    uid,
+    workspace_id,
+    object_id,
+    collab_type,
+    &payload.doc_state,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Returns only the changes the client lacks, for the clients reconnecting after a short offline
+/// period.
+async fn post_collab_updates_since_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<CollabUpdatesSinceParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabUpdatesSince>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let payload = payload.into_inner();
+  let updates = get_collab_updates_since(
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    object_id,
+    payload.collab_type,
+    &payload.state_vector,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(updates)))
+}
+
+/// Returns the state vector and the content hash of the collab, for the clients to detect that
+/// their copy diverged without fetching the whole collab.
+async fn get_collab_sync_state_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  query: web::Query<CollabTypeParam>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabSyncState>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let sync_state = get_collab_sync_state(
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    object_id,
+    query.into_inner().collab_type,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sync_state)))
+}
+
+async fn post_collab_share_token_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<CreateCollabShareTokenParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabShareToken>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let share_token = biz::collab::share_token::create_collab_share_token(
+    &state.pg_pool,
+    state.collab_access_control.clone(),
+    &state.config.gotrue.jwt_secret,
+    uid,
+    &workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::CollabShareTokenIssued,
+    Some(&object_id),
+    json!({ "token_id": share_token.token_id, "expires_at": share_token.expires_at }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(share_token)))
+}
+
+async fn delete_collab_share_token_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id, token_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  biz::collab::share_token::revoke_collab_share_token(
+    &state.pg_pool,
+    &workspace_id,
+    &object_id,
+    &token_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::CollabShareTokenRevoked,
+    Some(&object_id),
+    json!({ "token_id": token_id }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Lists the share links and share tokens of the whole workspace, for its owners to review.
+async fn list_workspace_share_links_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceShareLinks>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let links = workspace::share_links::list_workspace_share_links(
+    &state.pg_pool,
+    state.config.appflowy_web_url.as_deref(),
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(links)))
+}
+
+/// Revokes the share links and share tokens of the workspace at once, e.g. after a leak.
+async fn revoke_workspace_share_links_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<RevokeShareLinksQuery>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<RevokedShareLinks>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let created_by = query.into_inner().created_by;
+  let revoked =
+    workspace::share_links::revoke_workspace_share_links(&state.pg_pool, &workspace_id, created_by)
+      .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::ShareLinksRevoked,
+    None,
+    json!({ "created_by": created_by, "revoked": revoked }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(revoked)))
+}
+
+/// Reports how the update would apply to the collab, without persisting it.
+async fn post_validate_update_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateCollabWebParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabUpdateValidation>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  let payload = payload.into_inner();
+  let validation = validate_page_collab_update(
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    object_id,
+    payload.collab_type,
+    &payload.doc_state,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(validation)))
+}
+
+async fn post_page_view_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<CreatePageParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Page>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_uuid = path.into_inner();
+  let params = payload.into_inner();
+  let parent_view_id = params.parent_view_id.clone();
+  let page = create_page(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    params,
+  )
+  .await?;
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Created,
+    vec![page.view_id.clone()],
+    Some(parent_view_id),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(page)))
+}
+
+/// Applies the operations atomically: either all of them are saved, or none is.
+async fn post_page_operations_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<PageOperationsParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageOperationsResult>>> {
+  let workspace_uuid = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let result = workspace::page_operation::apply_page_operations(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    payload.into_inner(),
+  )
+  .await?;
+  if !result.pages.is_empty() {
+    notify_folder_change(
+      &state.pg_pool,
+      uid,
+      &workspace_uuid,
+      AFFolderChangeKind::Created,
+      result
+        .pages
+        .iter()
+        .map(|page| page.view_id.clone())
+        .collect(),
+      None,
+    )
+    .await;
+  }
+  Ok(Json(AppResponse::Ok().with_data(result)))
+}
+
+/// Creates a tree of document pages in a single transaction, for importers.
+async fn post_page_view_batch_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<BatchCreatePageParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<BatchCreatePageResult>>> {
+  let workspace_uuid = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let result = workspace::page_operation::create_page_batch(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    payload.into_inner(),
+  )
+  .await?;
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Created,
+    result.view_ids.values().cloned().collect(),
+    None,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(result)))
+}
+
+/// Creates a document page from Markdown or HTML content.
+async fn import_page_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<ImportPageParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Page>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_uuid = path.into_inner();
+  let params = payload.into_inner();
+  let parent_view_id = params.parent_view_id.clone();
+  let page = workspace::page_import::import_page(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    params,
+  )
+  .await?;
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Created,
+    vec![page.view_id.clone()],
+    Some(parent_view_id),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(page)))
+}
+
+async fn get_page_view_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageCollab>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Read)
+    .await?;
+
+  let page_collab = get_page_view_collab(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(page_collab)))
+}
+
+/// Returns the document of the page converted to Markdown or plain text, so that the clients don't
+/// need to decode the collab.
+async fn get_page_view_content_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryCollabExport>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageViewContent>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Read)
+    .await?;
+  let content = get_page_view_content(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    &view_id,
+    query.into_inner().format,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(content)))
+}
+
+async fn list_page_share_links_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PageShareLink>>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_uuid.to_string(), AFRole::Member)
+    .await?;
+  let links = workspace::page_share_link::list_page_share_links(
+    &state.pg_pool,
+    state.collab_access_control.clone(),
+    state.config.appflowy_web_url.as_deref(),
+    uid,
+    &workspace_uuid,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(links)))
+}
+
+/// Creates a link sharing the document page with anyone who has it.
+async fn post_page_share_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<CreatePageShareLinkParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageShareLink>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_uuid.to_string(), AFRole::Member)
+    .await?;
+  let link = workspace::page_share_link::create_page_share_link(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    state.collab_access_control.clone(),
+    state.config.appflowy_web_url.as_deref(),
+    uid,
+    workspace_uuid,
+    view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(link)))
+}
+
+async fn delete_page_share_link_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_uuid, view_id, link_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_uuid.to_string(), AFRole::Member)
+    .await?;
+  workspace::page_share_link::revoke_page_share_link(
+    &state.pg_pool,
+    state.collab_access_control.clone(),
+    uid,
+    &workspace_uuid,
+    &view_id,
+    &link_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_view_permissions_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFViewPermissions>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_uuid.to_string(), AFRole::Member)
+    .await?;
+  let permissions =
+    workspace::view_permission::get_view_permissions(&state.pg_pool, &workspace_uuid, &view_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(permissions)))
+}
+
+/// Restricts the view to the given members and groups, or lifts the restriction if no permission
+/// is given. Only the owners of the workspace can change the permissions of the views.
+async fn put_view_permissions_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<UpdateViewPermissionsParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFViewPermissions>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_uuid.to_string(), AFRole::Owner)
+    .await?;
+  let permissions = workspace::view_permission::update_view_permissions(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    state.collab_access_control.clone(),
+    &workspace_uuid,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_uuid,
+    uid,
+    WorkspaceAuditAction::ViewPermissionsUpdated,
+    Some(&view_id),
+    json!({ "permissions": permissions.permissions }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(permissions)))
+}
+
+async fn list_member_groups_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFMemberGroups>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let groups = workspace::view_permission::get_member_groups(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(groups)))
+}
+
+async fn post_member_group_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateMemberGroupParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFMemberGroup>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let group = workspace::view_permission::create_member_group(
+    &state.pg_pool,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::MemberGroupUpdated,
+    Some(&group.group_id.to_string()),
+    json!({ "name": group.name, "member_uids": group.member_uids }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(group)))
+}
+
+async fn put_member_group_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateMemberGroupParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFMemberGroup>>> {
+  let (workspace_id, group_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let group = workspace::view_permission::update_member_group(
+    &state.pg_pool,
+    state.collab_access_control.clone(),
+    &workspace_id,
+    &group_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::MemberGroupUpdated,
+    Some(&group_id.to_string()),
+    json!({ "name": group.name, "member_uids": group.member_uids }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(group)))
+}
+
+async fn delete_member_group_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, group_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  workspace::view_permission::remove_member_group(
+    &state.pg_pool,
+    state.collab_access_control.clone(),
+    &workspace_id,
+    &group_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::MemberGroupUpdated,
+    Some(&group_id.to_string()),
+    json!({ "deleted": true }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn list_workspace_roles_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFWorkspaceRoles>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let roles = workspace::custom_role::get_workspace_roles(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(roles)))
+}
+
+async fn post_workspace_role_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreateWorkspaceRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFWorkspaceRole>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let role = workspace::custom_role::create_workspace_role(
+    &state.pg_pool,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::RoleUpdated,
+    Some(&role.role_id.to_string()),
+    json!({ "name": role.name, "capabilities": role.capabilities }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(role)))
+}
 
-  let action = format!("Create new collab: {}", params);
+async fn patch_workspace_role_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWorkspaceRoleParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFWorkspaceRole>>> {
+  let (workspace_id, role_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
-    .collab_access_control_storage
-    .insert_new_collab_with_transaction(&workspace_id, &uid, params, &mut transaction, &action)
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
+  let role = workspace::custom_role::update_workspace_role(
+    &state.pg_pool,
+    state.workspace_access_control.clone(),
+    &workspace_id,
+    &role_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::RoleUpdated,
+    Some(&role_id.to_string()),
+    json!({ "name": role.name, "capabilities": role.capabilities }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(role)))
+}
 
-  transaction
-    .commit()
-    .await
-    .context("fail to commit the transaction to upsert collab")
-    .map_err(AppError::from)?;
-
-  Ok(Json(AppResponse::Ok()))
+async fn put_workspace_role_members_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdateWorkspaceRoleMembersParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<AFWorkspaceRole>>> {
+  let (workspace_id, role_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let role = workspace::custom_role::update_workspace_role_members(
+    &state.pg_pool,
+    state.workspace_access_control.clone(),
+    &workspace_id,
+    &role_id,
+    payload.into_inner(),
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::RoleUpdated,
+    Some(&role_id.to_string()),
+    json!({ "member_uids": role.member_uids }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(role)))
 }
 
-#[instrument(skip(state, payload), err)]
-async fn batch_create_collab_handler(
+async fn delete_workspace_role_handler(
   user_uuid: UserUuid,
-  workspace_id: web::Path<Uuid>,
-  mut payload: Payload,
+  path: web::Path<(Uuid, Uuid)>,
   state: Data<AppState>,
-  req: HttpRequest,
 ) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, role_id) = path.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
-  let workspace_id = workspace_id.into_inner().to_string();
-  let compress_type = compress_type_from_header_value(req.headers())?;
-  event!(tracing::Level::DEBUG, "start decompressing collab list");
-
-  let mut payload_buffer = Vec::new();
-  let mut offset_len_list = Vec::new();
-  let mut current_offset = 0;
-  let start = Instant::now();
-  while let Some(item) = payload.next().await {
-    if let Ok(bytes) = item {
-      payload_buffer.extend_from_slice(&bytes);
-      while current_offset + 4 <= payload_buffer.len() {
-        // The length of the next frame is determined by the first 4 bytes
-        let size = u32::from_be_bytes([
-          payload_buffer[current_offset],
-          payload_buffer[current_offset + 1],
-          payload_buffer[current_offset + 2],
-          payload_buffer[current_offset + 3],
-        ]) as usize;
-
-        // Ensure there is enough data for the frame (4 bytes for size + `size` bytes for data)
-        if current_offset + 4 + size > payload_buffer.len() {
-          break;
-        }
-
-        // Collect the (offset, len) for the current frame (data starts at current_offset + 4)
-        offset_len_list.push((current_offset + 4, size));
-        current_offset += 4 + size;
-      }
-    }
-  }
-  // Perform decompression and processing in a Rayon thread pool
-  let mut collab_params_list = tokio::task::spawn_blocking(move || match compress_type {
-    CompressionType::Brotli { buffer_size } => offset_len_list
-      .into_par_iter()
-      .filter_map(|(offset, len)| {
-        let compressed_data = &payload_buffer[offset..offset + len];
-        match decompress(compressed_data.to_vec(), buffer_size) {
-          Ok(decompressed_data) => {
-            let params = CollabParams::from_bytes(&decompressed_data).ok()?;
-            if params.validate().is_ok() {
-              match validate_encode_collab(
-                &params.object_id,
-                &params.encoded_collab_v1,
-                &params.collab_type,
-              ) {
-                Ok(_) => Some(params),
-                Err(_) => None,
-              }
-            } else {
-              None
-            }
-          },
-          Err(err) => {
-            error!("Failed to decompress data: {:?}", err);
-            None
-          },
-        }
-      })
-      .collect::<Vec<_>>(),
-  })
-  .await
-  .map_err(|_| AppError::InvalidRequest("Failed to decompress data".to_string()))?;
-
-  if collab_params_list.is_empty() {
-    return Err(AppError::InvalidRequest("Empty collab params list".to_string()).into());
-  }
-
-  let total_size = collab_params_list
-    .iter()
-    .fold(0, |acc, x| acc + x.encoded_collab_v1.len());
-  event!(
-    tracing::Level::INFO,
-    "decompressed {} collab objects in {:?}",
-    collab_params_list.len(),
-    start.elapsed()
-  );
-
-  if state
-    .indexer_provider
-    .can_index_workspace(&workspace_id)
-    .await?
-  {
-    if let Err(err) = fetch_embeddings(&state.indexer_provider, &mut collab_params_list).await {
-      tracing::warn!(
-        "failed to fetch embeddings for {} new documents: {}",
-        collab_params_list.len(),
-        err
-      );
-    }
-  }
-
-  let start = Instant::now();
   state
-    .collab_access_control_storage
-    .batch_insert_new_collab(&workspace_id, &uid, collab_params_list)
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-
-  event!(
-    tracing::Level::INFO,
-    "inserted collab objects to disk in {:?}, total size:{}",
-    start.elapsed(),
-    total_size
-  );
-
+  workspace::custom_role::remove_workspace_role(
+    &state.pg_pool,
+    state.workspace_access_control.clone(),
+    &workspace_id,
+    &role_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::RoleUpdated,
+    Some(&role_id.to_string()),
+    json!({ "deleted": true }),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
-// Deprecated
-async fn get_collab_handler(
+/// Renames, changes the icon of, or moves the view of the page in the folder.
+async fn patch_page_view_handler(
   user_uuid: UserUuid,
-  payload: Json<QueryCollabParams>,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<PatchPageParams>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<CollabResponse>>> {
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
   let params = payload.into_inner();
-  let object_id = params.object_id.clone();
-  let encode_collab = state
-    .collab_access_control_storage
-    .get_encode_collab(GetCollabOrigin::User { uid }, params, true)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  let resp = CollabResponse {
-    encode_collab,
-    object_id,
+  let parent_view_id = params.parent_view_id.clone();
+  update_page(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    &view_id,
+    params,
+  )
+  .await?;
+  let kind = match parent_view_id {
+    Some(_) => AFFolderChangeKind::Moved,
+    None => AFFolderChangeKind::Updated,
   };
-
-  Ok(Json(AppResponse::Ok().with_data(resp)))
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    kind,
+    vec![view_id],
+    parent_view_id,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
 }
 
-async fn v1_get_collab_handler(
+/// Moves the view of the page, along with its descendants, to the trash.
+async fn delete_page_view_handler(
   user_uuid: UserUuid,
-  path: web::Path<(String, String)>,
-  query: web::Query<CollabTypeParam>,
+  path: web::Path<(Uuid, String)>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<CollabResponse>>> {
-  let (workspace_id, object_id) = path.into_inner();
-  let collab_type = query.into_inner().collab_type;
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  let param = QueryCollabParams {
-    workspace_id,
-    inner: QueryCollab {
-      object_id: object_id.clone(),
-      collab_type,
-    },
-  };
-
-  let encode_collab = state
-    .collab_access_control_storage
-    .get_encode_collab(GetCollabOrigin::User { uid }, param, true)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  let resp = CollabResponse {
-    encode_collab,
-    object_id,
-  };
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let trashed_view_ids = move_page_to_trash(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    &view_id,
+  )
+  .await?;
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Trashed,
+    trashed_view_ids,
+    None,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
 
-  Ok(Json(AppResponse::Ok().with_data(resp)))
+/// Moves the view of the page among its siblings.
+async fn reorder_page_view_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<ReorderPageParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  reorder_page(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    &view_id,
+    payload.into_inner(),
+  )
+  .await?;
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Moved,
+    vec![view_id],
+    None,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
 }
 
-async fn post_web_update_handler(
+/// Appends the blocks to the end of the document of the page.
+async fn append_block_to_page_handler(
   user_uuid: UserUuid,
-  path: web::Path<(Uuid, Uuid)>,
-  payload: Json<UpdateCollabWebParams>,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<AppendBlockToPageParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
-  let (workspace_id, object_id) = path.into_inner();
-  let collab_type = payload.collab_type.clone();
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-  update_page_collab_data(
-    state.collab_access_control_storage.clone(),
-    state.metrics.appflowy_web_metrics.clone(),
+  let (workspace_uuid, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  append_block_to_page(
+    &state.collab_access_control_storage,
     uid,
-    workspace_id,
-    object_id,
-    collab_type,
-    &payload.doc_state,
+    workspace_uuid,
+    &view_id,
+    payload.into_inner(),
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn post_page_view_handler(
+async fn list_page_templates_handler(
   user_uuid: UserUuid,
   path: web::Path<Uuid>,
-  payload: Json<CreatePageParams>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<Page>>> {
+) -> Result<Json<AppResponse<Vec<PageTemplate>>>> {
+  let workspace_uuid = path.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Read)
+    .await?;
+  let templates =
+    workspace::page_template::list_page_templates(&state.pg_pool, &workspace_uuid).await?;
+  Ok(Json(AppResponse::Ok().with_data(templates)))
+}
+
+/// Saves a copy of the document as a template of the workspace.
+async fn post_page_template_handler(
+  user_uuid: UserUuid,
+  path: web::Path<Uuid>,
+  payload: Json<CreatePageTemplateParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageTemplate>>> {
   let workspace_uuid = path.into_inner();
-  let page = create_page(
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let template = workspace::page_template::create_page_template(
     &state.pg_pool,
     &state.collab_access_control_storage,
     uid,
     workspace_uuid,
-    &payload.parent_view_id,
-    &payload.layout,
+    payload.into_inner(),
   )
   .await?;
-  Ok(Json(AppResponse::Ok().with_data(page)))
+  Ok(Json(AppResponse::Ok().with_data(template)))
 }
 
-async fn get_page_view_handler(
+async fn get_page_template_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageTemplate>>> {
+  let (workspace_uuid, template_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Read)
+    .await?;
+  let template =
+    workspace::page_template::get_page_template(&state.pg_pool, &workspace_uuid, &template_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(template)))
+}
+
+async fn patch_page_template_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  payload: Json<UpdatePageTemplateParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PageTemplate>>> {
+  let (workspace_uuid, template_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let template = workspace::page_template::patch_page_template(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_uuid,
+    template_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(template)))
+}
+
+async fn delete_page_template_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_uuid, template_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  workspace::page_template::remove_page_template(&state.pg_pool, &workspace_uuid, &template_id)
+    .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+/// Duplicates the page, along with its descendants, right after it.
+async fn duplicate_page_view_handler(
   user_uuid: UserUuid,
   path: web::Path<(Uuid, String)>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<PageCollab>>> {
+) -> Result<Json<AppResponse<Page>>> {
   let (workspace_uuid, view_id) = path.into_inner();
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  let page_collab = get_page_view_collab(
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_uuid.to_string(), Action::Write)
+    .await?;
+  let page = duplicate_page(
     &state.pg_pool,
     &state.collab_access_control_storage,
     uid,
@@ -915,7 +3198,16 @@ async fn get_page_view_handler(
     &view_id,
   )
   .await?;
-  Ok(Json(AppResponse::Ok().with_data(page_collab)))
+  notify_folder_change(
+    &state.pg_pool,
+    uid,
+    &workspace_uuid,
+    AFFolderChangeKind::Created,
+    vec![page.view_id.clone()],
+    None,
+  )
+  .await;
+  Ok(Json(AppResponse::Ok().with_data(page)))
 }
 
 #[instrument(level = "trace", skip_all, err)]
@@ -972,6 +3264,29 @@ async fn create_collab_snapshot_handler(
   Ok(Json(AppResponse::Ok().with_data(meta)))
 }
 
+#[instrument(level = "debug", skip(state, payload), err)]
+async fn restore_collab_snapshot_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  path: web::Path<(Uuid, String, i64)>,
+  payload: Json<CollabType>,
+) -> Result<Json<AppResponse<AFSnapshotMeta>>> {
+  let (workspace_id, object_id, snapshot_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let backup = biz::collab::restore::restore_collab_snapshot(
+    state.collab_access_control.clone(),
+    &state.collab_access_control_storage,
+    state.storage_router.pg_pool_router(),
+    uid,
+    workspace_id,
+    &object_id,
+    snapshot_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(backup)))
+}
+
 #[instrument(level = "trace", skip(path, state), err)]
 async fn get_all_collab_snapshot_list_handler(
   _user_uuid: UserUuid,
@@ -991,6 +3306,7 @@ async fn get_all_collab_snapshot_list_handler(
 async fn batch_get_collab_handler(
   user_uuid: UserUuid,
   state: Data<AppState>,
+  page: web::Query<BatchQueryCollabPage>,
   payload: Json<BatchQueryCollabParams>,
 ) -> Result<Json<AppResponse<BatchQueryCollabResult>>> {
   let uid = state
@@ -998,15 +3314,56 @@ async fn batch_get_collab_handler(
     .get_user_uid(&user_uuid)
     .await
     .map_err(AppResponseError::from)?;
+  let queries = page.apply(payload.into_inner().0);
   let result = BatchQueryCollabResult(
     state
       .collab_access_control_storage
-      .batch_get_collab(&uid, payload.into_inner().0, false)
+      .batch_get_collab(&uid, queries, false)
       .await,
   );
   Ok(Json(AppResponse::Ok().with_data(result)))
 }
 
+/// The queries of a streamed batch query are run in chunks of this size, so that only one chunk
+/// of collabs is held in memory at a time.
+const BATCH_GET_COLLAB_STREAM_CHUNK_SIZE: usize = 50;
+
+/// Same as [batch_get_collab_handler], but each collab is streamed as a length-prefixed frame
+/// as soon as its chunk is fetched, instead of buffering the whole result.
+#[instrument(level = "debug", skip(payload, state), err)]
+async fn batch_get_collab_stream_handler(
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  page: web::Query<BatchQueryCollabPage>,
+  payload: Json<BatchQueryCollabParams>,
+) -> Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let queries = page.apply(payload.into_inner().0);
+  let collab_storage = state.collab_access_control_storage.clone();
+  let frames = async_stream::stream! {
+    for chunk in queries.chunks(BATCH_GET_COLLAB_STREAM_CHUNK_SIZE) {
+      let results = collab_storage
+        .batch_get_collab(&uid, chunk.to_vec(), false)
+        .await;
+      for (object_id, result) in results {
+        let item = BatchQueryCollabItem { object_id, result };
+        match item.to_frame() {
+          Ok(frame) => yield Ok(Bytes::from(frame)),
+          Err(err) => {
+            yield Err(AppError::Internal(err.into()));
+            return;
+          },
+        }
+      }
+    }
+  };
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/octet-stream")
+      .streaming(frames),
+  )
+}
+
 #[instrument(skip(state, payload), err)]
 async fn update_collab_handler(
   user_uuid: UserUuid,
@@ -1018,6 +3375,17 @@ async fn update_collab_handler(
 
   let create_params = CreateCollabParams::from((workspace_id.to_string(), params));
   let (mut params, workspace_id) = create_params.split();
+  scan_document_write(
+    &state.pg_pool,
+    &state.mailer,
+    state.config.appflowy_web_url.as_deref(),
+    &Uuid::parse_str(&workspace_id)?,
+    &user_uuid,
+    uid,
+    &params,
+  )
+  .await?;
+
   if let Some(indexer) = state
     .indexer_provider
     .indexer_for(params.collab_type.clone())
@@ -1055,35 +3423,237 @@ async fn update_collab_handler(
   Ok(AppResponse::Ok().into())
 }
 
-#[instrument(level = "info", skip(state, payload), err)]
-async fn delete_collab_handler(
+#[instrument(level = "info", skip(state, payload), err)]
+async fn delete_collab_handler(
+  user_uuid: UserUuid,
+  payload: Json<DeleteCollabParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let payload = payload.into_inner();
+  payload.validate().map_err(AppError::from)?;
+
+  let uid = state
+    .user_cache
+    .get_user_uid(&user_uuid)
+    .await
+    .map_err(AppResponseError::from)?;
+
+  let workspace_id = Uuid::parse_str(&payload.workspace_id).map_err(AppError::from)?;
+  state
+    .collab_access_control_storage
+    .delete_collab(&payload.workspace_id, &uid, &payload.object_id)
+    .await
+    .map_err(AppResponseError::from)?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::CollabDeleted,
+    Some(&payload.object_id),
+    json!({}),
+  )
+  .await;
+
+  Ok(AppResponse::Ok().into())
+}
+
+#[instrument(skip(state), err)]
+async fn restore_trashed_collab_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_access_level(
+      &workspace_id.to_string(),
+      &uid,
+      &object_id,
+      AFAccessLevel::FullAccess,
+    )
+    .await?;
+  biz::collab::trash::restore_collab(
+    state.storage_router.pg_pool_router(),
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::CollabRestored,
+    Some(&object_id),
+    json!({}),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+#[instrument(skip(state), err)]
+async fn purge_trashed_collab_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, object_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_access_level(
+      &workspace_id.to_string(),
+      &uid,
+      &object_id,
+      AFAccessLevel::FullAccess,
+    )
+    .await?;
+  biz::collab::trash::purge_collab(
+    state.storage_router.pg_pool_router(),
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::CollabPurged,
+    Some(&object_id),
+    json!({}),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+#[instrument(skip(state, payload), err)]
+async fn redact_collab_handler(
+  auth: Authorization,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<RedactCollabParams>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabRedaction>>> {
+  enforce_server_admin(&auth)?;
+  let uid = state.user_cache.get_user_uid(&auth.uuid()?).await?;
+  let (workspace_id, object_id) = path.into_inner();
+  let redaction = biz::collab::redaction::redact_collab(
+    &state.pg_pool,
+    state.storage_router.pg_pool_router(),
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(redaction)))
+}
+
+async fn get_document_stats_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DocumentStats>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let (workspace_id, object_id) = path.into_inner();
+  let stats = biz::collab::stats::get_document_stats(
+    &state.collab_access_control_storage,
+    state.storage_router.pg_pool_router(),
+    &state.redis_connection_manager,
+    uid,
+    workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(stats)))
+}
+
+/// Returns the document as a file in the requested format, Markdown, plain text or JSON.
+async fn export_document_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  query: web::Query<QueryCollabExport>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let (workspace_id, object_id) = path.into_inner();
+  let params = ExportCollabParams {
+    format: query.into_inner().format,
+    encryption: None,
+  };
+  export_document(&state, &user_uuid, workspace_id, &object_id, params).await
+}
+
+/// Same as [export_document_handler], the key of the encrypted exports is sent in the body rather
+/// than the url, which may be logged.
+async fn post_export_document_handler(
   user_uuid: UserUuid,
-  payload: Json<DeleteCollabParams>,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<ExportCollabParams>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<()>>> {
-  let payload = payload.into_inner();
-  payload.validate().map_err(AppError::from)?;
-
-  let uid = state
-    .user_cache
-    .get_user_uid(&user_uuid)
-    .await
-    .map_err(AppResponseError::from)?;
-
-  state
-    .collab_access_control_storage
-    .delete_collab(&payload.workspace_id, &uid, &payload.object_id)
-    .await
-    .map_err(AppResponseError::from)?;
+) -> Result<HttpResponse> {
+  let (workspace_id, object_id) = path.into_inner();
+  export_document(
+    &state,
+    &user_uuid,
+    workspace_id,
+    &object_id,
+    payload.into_inner(),
+  )
+  .await
+}
 
-  Ok(AppResponse::Ok().into())
+async fn export_document(
+  state: &AppState,
+  user_uuid: &Uuid,
+  workspace_id: Uuid,
+  object_id: &str,
+  params: ExportCollabParams,
+) -> Result<HttpResponse> {
+  let uid = state.user_cache.get_user_uid(user_uuid).await?;
+  let content = biz::export::export_document(
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    object_id,
+    params.format,
+  )
+  .await?;
+  let (content_type, extension) = match params.format {
+    CollabExportFormat::Markdown => ("text/markdown; charset=utf-8", "md"),
+    CollabExportFormat::Text => ("text/plain; charset=utf-8", "txt"),
+    CollabExportFormat::Json => ("application/json", "json"),
+  };
+  let (content_type, file_name, content) = match params.encryption {
+    Some(encryption) => (
+      "application/octet-stream",
+      format!("{}.{}.afenc", object_id, extension),
+      biz::export::encrypt_export(content.into_bytes(), encryption).await?,
+    ),
+    None => (
+      content_type,
+      format!("{}.{}", object_id, extension),
+      content.into_bytes(),
+    ),
+  };
+  Ok(
+    HttpResponse::Ok()
+      .content_type(content_type)
+      .insert_header((
+        CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", file_name),
+      ))
+      .body(content),
+  )
 }
 
 #[instrument(level = "debug", skip(state, payload), err)]
 async fn add_collab_member_handler(
+  user_uuid: UserUuid,
   payload: Json<InsertCollabMemberParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let payload = payload.into_inner();
   if !state.collab_cache.is_exist(&payload.object_id).await? {
     return Err(
@@ -1097,6 +3667,7 @@ async fn add_collab_member_handler(
 
   biz::collab::ops::create_collab_member(
     &state.pg_pool,
+    uid,
     &payload,
     state.collab_access_control.clone(),
   )
@@ -1110,6 +3681,7 @@ async fn update_collab_member_handler(
   payload: Json<UpdateCollabMemberParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let payload = payload.into_inner();
 
   if !state.collab_cache.is_exist(&payload.object_id).await? {
@@ -1123,7 +3695,7 @@ async fn update_collab_member_handler(
   }
   biz::collab::ops::upsert_collab_member(
     &state.pg_pool,
-    &user_uuid,
+    uid,
     &payload,
     state.collab_access_control.clone(),
   )
@@ -1142,12 +3714,15 @@ async fn get_collab_member_handler(
 
 #[instrument(skip(state, payload), err)]
 async fn remove_collab_member_handler(
+  user_uuid: UserUuid,
   payload: Json<CollabMemberIdentify>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   let payload = payload.into_inner();
   biz::collab::ops::delete_collab_member(
     &state.pg_pool,
+    uid,
     &payload,
     state.collab_access_control.clone(),
   )
@@ -1162,51 +3737,338 @@ async fn put_workspace_default_published_view_handler(
   payload: Json<UpdateDefaultPublishView>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let new_default_pub_view_id = payload.into_inner().view_id;
+  biz::workspace::publish::set_workspace_default_publish_view(
+    &state.pg_pool,
+    &workspace_id,
+    &new_default_pub_view_id,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::DefaultPublishViewUpdated,
+    None,
+    json!({ "view_id": new_default_pub_view_id }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn delete_workspace_default_published_view_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  biz::workspace::publish::unset_workspace_default_publish_view(&state.pg_pool, &workspace_id)
+    .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::DefaultPublishViewUpdated,
+    None,
+    json!({ "view_id": null }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_workspace_published_default_info_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishInfo>>> {
+  let workspace_id = workspace_id.into_inner();
+  let info =
+    biz::workspace::publish::get_workspace_default_publish_view_info(&state.pg_pool, &workspace_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(info)))
+}
+
+async fn put_publish_namespace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<UpdatePublishNamespace>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let UpdatePublishNamespace {
+    old_namespace,
+    new_namespace,
+  } = payload.into_inner();
+  biz::workspace::publish::set_workspace_namespace(
+    &state.pg_pool,
+    &workspace_id,
+    &old_namespace,
+    &new_namespace,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublishNamespaceUpdated,
+    None,
+    json!({ "old_namespace": old_namespace, "new_namespace": new_namespace }),
+  )
+  .await;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn get_publish_namespace_handler(
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<String>>> {
+  let workspace_id = workspace_id.into_inner();
+  let namespace =
+    biz::workspace::publish::get_workspace_publish_namespace(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(namespace)))
+}
+
+async fn put_publish_embed_allowlist_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<UpdatePublishEmbedAllowlist>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishEmbedAllowlist>>> {
+  let workspace_id = workspace_id.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let new_default_pub_view_id = payload.into_inner().view_id;
-  biz::workspace::publish::set_workspace_default_publish_view(
+  let allowlist = biz::workspace::publish_sanitize::set_publish_embed_allowlist(
     &state.pg_pool,
     &workspace_id,
-    &new_default_pub_view_id,
+    payload.into_inner().hosts,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(allowlist)))
+}
+
+async fn get_publish_embed_allowlist_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishEmbedAllowlist>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let allowlist =
+    biz::workspace::publish_sanitize::get_publish_embed_allowlist(&state.pg_pool, &workspace_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(allowlist)))
+}
+
+async fn list_publish_sub_namespace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishSubNamespace>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let sub_namespaces = biz::workspace::publish_sub_namespace::list_publish_sub_namespaces(
+    &state.pg_pool,
+    &workspace_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sub_namespaces)))
+}
+
+async fn post_publish_sub_namespace_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<CreatePublishSubNamespace>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishSubNamespace>>> {
+  let workspace_id = workspace_id.into_inner();
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let sub_namespace = biz::workspace::publish_sub_namespace::create_publish_sub_namespace(
+    &state.pg_pool,
+    &workspace_id,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sub_namespace)))
+}
+
+async fn put_publish_sub_namespace_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<UpdatePublishSubNamespace>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishSubNamespace>>> {
+  let (workspace_id, sub_namespace) = path.into_inner();
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let sub_namespace = biz::workspace::publish_sub_namespace::update_publish_sub_namespace_settings(
+    &state.pg_pool,
+    &workspace_id,
+    &sub_namespace,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sub_namespace)))
+}
+
+async fn delete_publish_sub_namespace_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, sub_namespace) = path.into_inner();
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  biz::workspace::publish_sub_namespace::remove_publish_sub_namespace(
+    &state.pg_pool,
+    &workspace_id,
+    &sub_namespace,
   )
   .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn delete_workspace_default_published_view_handler(
+async fn post_publish_sub_namespace_views_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<UpdatePublishSubNamespaceViews>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishSubNamespace>>> {
+  let (workspace_id, sub_namespace) = path.into_inner();
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let sub_namespace = biz::workspace::publish_sub_namespace::add_publish_sub_namespace_views(
+    &state.pg_pool,
+    &workspace_id,
+    &sub_namespace,
+    payload.into_inner().view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sub_namespace)))
+}
+
+async fn delete_publish_sub_namespace_views_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  payload: Json<UpdatePublishSubNamespaceViews>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishSubNamespace>>> {
+  let (workspace_id, sub_namespace) = path.into_inner();
+  biz::workspace::publish_permission::enforce_can_manage_publishing(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+  )
+  .await?;
+  let sub_namespace = biz::workspace::publish_sub_namespace::remove_publish_sub_namespace_views(
+    &state.pg_pool,
+    &workspace_id,
+    &sub_namespace,
+    payload.into_inner().view_ids,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(sub_namespace)))
+}
+
+async fn list_workspace_publishers_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspacePublishers>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let publishers =
+    biz::workspace::publish_permission::list_workspace_publishers(&state.pg_pool, &workspace_id)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(publishers)))
+}
+
+async fn put_workspace_publisher_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
+  payload: Json<WorkspacePublisherParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
+  let workspace_id = workspace_id.into_inner();
   let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  biz::workspace::publish::unset_workspace_default_publish_view(&state.pg_pool, &workspace_id)
-    .await?;
+  let email = payload.into_inner().email;
+  biz::workspace::publish_permission::grant_publish_permission(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    &email,
+  )
+  .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublisherGranted,
+    Some(&email),
+    json!({}),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn get_workspace_published_default_info_handler(
-  workspace_id: web::Path<Uuid>,
-  state: Data<AppState>,
-) -> Result<Json<AppResponse<PublishInfo>>> {
-  let workspace_id = workspace_id.into_inner();
-  let info =
-    biz::workspace::publish::get_workspace_default_publish_view_info(&state.pg_pool, &workspace_id)
-      .await?;
-  Ok(Json(AppResponse::Ok().with_data(info)))
-}
-
-async fn put_publish_namespace_handler(
+async fn delete_workspace_publisher_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
-  payload: Json<UpdatePublishNamespace>,
+  payload: Json<WorkspacePublisherParams>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let workspace_id = workspace_id.into_inner();
@@ -1215,30 +4077,25 @@ async fn put_publish_namespace_handler(
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let UpdatePublishNamespace {
-    old_namespace,
-    new_namespace,
-  } = payload.into_inner();
-  biz::workspace::publish::set_workspace_namespace(
+  let email = payload.into_inner().email;
+  biz::workspace::publish_permission::revoke_publish_permission(
     &state.pg_pool,
     &workspace_id,
-    &old_namespace,
-    &new_namespace,
+    &email,
   )
   .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublisherRevoked,
+    Some(&email),
+    json!({}),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
-async fn get_publish_namespace_handler(
-  workspace_id: web::Path<Uuid>,
-  state: Data<AppState>,
-) -> Result<Json<AppResponse<String>>> {
-  let workspace_id = workspace_id.into_inner();
-  let namespace =
-    biz::workspace::publish::get_workspace_publish_namespace(&state.pg_pool, &workspace_id).await?;
-  Ok(Json(AppResponse::Ok().with_data(namespace)))
-}
-
 async fn get_default_published_collab_info_meta_handler(
   publish_namespace: web::Path<String>,
   state: Data<AppState>,
@@ -1251,11 +4108,43 @@ async fn get_default_published_collab_info_meta_handler(
   ))
 }
 
+async fn get_published_sitemap_handler(
+  publish_namespace: web::Path<String>,
+  state: Data<AppState>,
+) -> Result<HttpResponse> {
+  let appflowy_web_url = state
+    .config
+    .appflowy_web_url
+    .as_deref()
+    .ok_or(AppError::Internal(anyhow!(
+      "AppFlowy web url has not been set"
+    )))?;
+  let sitemap = workspace::publish_sitemap::get_published_sitemap(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    appflowy_web_url,
+    &publish_namespace.into_inner(),
+  )
+  .await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/xml")
+      .append_header((CACHE_CONTROL, "public, max-age=3600"))
+      .body(sitemap),
+  )
+}
+
 async fn get_v1_published_collab_handler(
   path_param: web::Path<(String, String)>,
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<serde_json::Value>>> {
   let (workspace_namespace, publish_name) = path_param.into_inner();
+  workspace::publish_expiry::check_published_view_not_expired(
+    &state.pg_pool,
+    &workspace_namespace,
+    &publish_name,
+  )
+  .await?;
   let metadata = state
     .published_collab_store
     .get_collab_metadata(&workspace_namespace, &publish_name)
@@ -1263,18 +4152,125 @@ async fn get_v1_published_collab_handler(
   Ok(Json(AppResponse::Ok().with_data(metadata)))
 }
 
+async fn get_published_view_seo_handler(
+  path_param: web::Path<(String, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedViewSeo>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let appflowy_web_url = state
+    .config
+    .appflowy_web_url
+    .as_deref()
+    .ok_or(AppError::Internal(anyhow!(
+      "AppFlowy web url has not been set"
+    )))?;
+  workspace::publish_expiry::check_published_view_not_expired(
+    &state.pg_pool,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  let seo = workspace::publish_seo::get_published_view_seo(
+    &state.pg_pool,
+    appflowy_web_url,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(seo)))
+}
+
+async fn patch_published_view_seo_handler(
+  path_param: web::Path<(String, String)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  patch: Json<PatchPublishedViewSeo>,
+) -> Result<Json<AppResponse<()>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  workspace::publish_seo::patch_published_view_seo(
+    &state.pg_pool,
+    &user_uuid,
+    &publish_namespace,
+    &publish_name,
+    &patch,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
 async fn get_published_collab_blob_handler(
   path_param: web::Path<(String, String)>,
   state: Data<AppState>,
+  publish_analytics: Data<Arc<PublishAnalyticsRecorder>>,
+  req: HttpRequest,
 ) -> Result<Vec<u8>> {
   let (publish_namespace, publish_name) = path_param.into_inner();
+  workspace::publish_expiry::check_published_view_not_expired(
+    &state.pg_pool,
+    &publish_namespace,
+    &publish_name,
+  )
+  .await?;
   let collab_data = state
     .published_collab_store
     .get_collab_blob_by_publish_namespace(&publish_namespace, &publish_name)
     .await?;
+  if let Err(err) =
+    upsert_published_view_visit(&state.pg_pool, &publish_namespace, &publish_name).await
+  {
+    error!("Failed to record visit of published view: {:?}", err);
+  }
+  // The peer address includes the port, which changes between the connections of a visitor.
+  let ip = req.connection_info().realip_remote_addr().map(|addr| {
+    addr
+      .parse::<std::net::SocketAddr>()
+      .map(|addr| addr.ip().to_string())
+      .unwrap_or_else(|_| addr.to_string())
+  });
+  let user_agent = req
+    .headers()
+    .get(actix_web::http::header::USER_AGENT)
+    .and_then(|value| value.to_str().ok());
+  if let Err(err) = workspace::insights::record_published_view_access(
+    &state.pg_pool,
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    ip.as_deref(),
+    user_agent,
+    publish_referrer_from_header(&req),
+  )
+  .await
+  {
+    error!("Failed to record access of published view: {:?}", err);
+  }
+  publish_analytics.record(
+    state.config.gotrue.jwt_secret.expose_secret(),
+    &publish_namespace,
+    &publish_name,
+    ip.as_deref(),
+    user_agent,
+  );
   Ok(collab_data)
 }
 
+async fn post_published_database_rows_handler(
+  path_param: web::Path<(String, String)>,
+  payload: Json<QueryPublishedDatabaseRows>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<PublishedDatabaseRows>>> {
+  let (publish_namespace, publish_name) = path_param.into_inner();
+  let rows = get_published_database_rows(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    &publish_namespace,
+    &publish_name,
+    payload.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(rows)))
+}
+
 async fn post_published_duplicate_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<String>,
@@ -1329,12 +4325,12 @@ async fn get_published_collab_info_handler(
 async fn get_published_collab_comment_handler(
   view_id: web::Path<Uuid>,
   optional_user_uuid: OptionalUserUuid,
+  query: web::Query<QueryGlobalComments>,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<GlobalComments>> {
   let view_id = view_id.into_inner();
-  let comments =
-    get_comments_on_published_view(&state.pg_pool, &view_id, &optional_user_uuid).await?;
-  let resp = GlobalComments { comments };
+  let resp =
+    get_comments_on_published_view(&state.pg_pool, &view_id, &optional_user_uuid, &query).await?;
   Ok(Json(AppResponse::Ok().with_data(resp)))
 }
 
@@ -1350,6 +4346,45 @@ async fn post_published_collab_comment_handler(
     &view_id,
     &data.reply_comment_id,
     &data.content,
+    &data.attachments,
+    &user_uuid,
+  )
+  .await?;
+
+  if let Some(appflowy_web_url) = state.config.appflowy_web_url.clone() {
+    let pg_pool = state.pg_pool.clone();
+    let mailer = state.mailer.clone();
+    let content = data.into_inner().content;
+    tokio::spawn(async move {
+      if let Err(err) = workspace::comment_subscription::notify_comment_subscribers(
+        &pg_pool,
+        &mailer,
+        &appflowy_web_url,
+        &view_id,
+        &user_uuid,
+        &content,
+      )
+      .await
+      {
+        error!("Failed to notify comment subscribers: {:?}", err);
+      }
+    });
+  }
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn put_published_collab_comment_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<UpdateGlobalCommentParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  update_comment_on_published_view(
+    &state.pg_pool,
+    &view_id,
+    &data.comment_id,
+    &data.content,
     &user_uuid,
   )
   .await?;
@@ -1363,18 +4398,224 @@ async fn delete_published_collab_comment_handler(
   data: Json<DeleteGlobalCommentParams>,
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
-  remove_comment_on_published_view(&state.pg_pool, &view_id, &data.comment_id, &user_uuid).await?;
+  remove_comment_on_published_view(
+    &state.pg_pool,
+    &state.storage_router,
+    &view_id,
+    &data.comment_id,
+    &user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn post_comment_moderation_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<ModerateCommentsParams>,
+) -> Result<JsonAppResponse<CommentModeration>> {
+  let view_id = view_id.into_inner();
+  let moderation = workspace::comment_moderation::moderate_comments(
+    &state.pg_pool,
+    &state.storage_router,
+    &view_id,
+    &user_uuid,
+    data.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(moderation)))
+}
+
+async fn get_comment_blocklist_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<CommentBlocklist>> {
+  let view_id = view_id.into_inner();
+  let blocklist =
+    workspace::comment_moderation::get_comment_blocklist(&state.pg_pool, &view_id, &user_uuid)
+      .await?;
+  Ok(Json(AppResponse::Ok().with_data(blocklist)))
+}
+
+async fn post_comment_blocklist_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CommentBlocklistParams>,
+) -> Result<JsonAppResponse<CommentBlocklist>> {
+  let view_id = view_id.into_inner();
+  let blocklist = workspace::comment_moderation::block_commenter(
+    &state.pg_pool,
+    &view_id,
+    &user_uuid,
+    &data.user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(blocklist)))
+}
+
+async fn delete_comment_blocklist_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CommentBlocklistParams>,
+) -> Result<JsonAppResponse<CommentBlocklist>> {
+  let view_id = view_id.into_inner();
+  let blocklist = workspace::comment_moderation::unblock_commenter(
+    &state.pg_pool,
+    &view_id,
+    &user_uuid,
+    &data.user_uuid,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(blocklist)))
+}
+
+async fn post_published_collab_comment_attachment_handler(
+  user_uuid: UserUuid,
+  view_id: web::Path<Uuid>,
+  content_type: web::Header<ContentType>,
+  content_length: web::Header<ContentLength>,
+  payload: Payload,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<CommentAttachment>> {
+  let view_id = view_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let content_length = content_length.into_inner().into_inner();
+  if content_length > workspace::comment_attachment::MAX_COMMENT_ATTACHMENT_SIZE {
+    return Err(
+      AppError::PayloadTooLarge(format!(
+        "Comment attachment exceeds the maximum size of {} bytes",
+        workspace::comment_attachment::MAX_COMMENT_ATTACHMENT_SIZE
+      ))
+      .with_details(ErrorDetails::limit(
+        workspace::comment_attachment::MAX_COMMENT_ATTACHMENT_SIZE as i64,
+        Some(content_length as i64),
+      ))
+      .into(),
+    );
+  }
+  let mut content = vec![0; content_length];
+  PayloadReader::new(payload).read_exact(&mut content).await?;
+  let attachment = workspace::comment_attachment::upload_comment_attachment(
+    &state.pg_pool,
+    &state.storage_router,
+    &view_id,
+    uid,
+    content,
+    content_type.into_inner().to_string(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(attachment)))
+}
+
+async fn post_comment_subscription_handler(
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<SubscribeCommentParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  let data = data.into_inner();
+  data.validate().map_err(AppError::from)?;
+  let appflowy_web_url = state
+    .config
+    .appflowy_web_url
+    .as_deref()
+    .ok_or(AppError::Internal(anyhow!(
+      "AppFlowy web url has not been set"
+    )))?;
+  workspace::comment_subscription::subscribe_to_comments(
+    &state.pg_pool,
+    state.mailer.clone(),
+    appflowy_web_url,
+    &view_id,
+    &data.email,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn confirm_comment_subscription_handler(
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CommentSubscriptionTokenParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  workspace::comment_subscription::confirm_comment_subscription(
+    &state.pg_pool,
+    &view_id,
+    &data.token,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+async fn unsubscribe_comment_subscription_handler(
+  view_id: web::Path<Uuid>,
+  state: Data<AppState>,
+  data: Json<CommentSubscriptionTokenParams>,
+) -> Result<JsonAppResponse<()>> {
+  let view_id = view_id.into_inner();
+  workspace::comment_subscription::unsubscribe_from_comments(&state.pg_pool, &view_id, &data.token)
+    .await?;
   Ok(Json(AppResponse::Ok()))
 }
 
+async fn get_comment_subscriber_count_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, Uuid)>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<CommentSubscriberCount>> {
+  let (workspace_id, view_id) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let count = workspace::comment_subscription::get_comment_subscriber_count(
+    &state.pg_pool,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(
+    AppResponse::Ok().with_data(CommentSubscriberCount { count }),
+  ))
+}
+
+async fn batch_get_comments_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<BatchCommentsParams>,
+  state: Data<AppState>,
+) -> Result<JsonAppResponse<BatchComments>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let comments =
+    get_comments_on_published_views(&state.pg_pool, &workspace_id, &user_uuid, &payload).await?;
+  Ok(Json(AppResponse::Ok().with_data(comments)))
+}
+
 async fn get_published_collab_reaction_handler(
   view_id: web::Path<Uuid>,
   query: web::Query<GetReactionQueryParams>,
+  optional_user_uuid: OptionalUserUuid,
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<Reactions>> {
   let view_id = view_id.into_inner();
-  let reactions =
-    get_reactions_on_published_view(&state.pg_pool, &view_id, &query.comment_id).await?;
+  let reactions = get_reactions_on_published_view(
+    &state.pg_pool,
+    &view_id,
+    &query.comment_id,
+    &optional_user_uuid,
+  )
+  .await?;
   let resp = Reactions { reactions };
   Ok(Json(AppResponse::Ok().with_data(resp)))
 }
@@ -1386,6 +4627,12 @@ async fn post_published_collab_reaction_handler(
   state: Data<AppState>,
 ) -> Result<JsonAppResponse<()>> {
   let view_id = view_id.into_inner();
+  biz::workspace::custom_emoji::validate_reaction_type(
+    &state.pg_pool,
+    &view_id,
+    &data.reaction_type,
+  )
+  .await?;
   create_reaction_on_comment(
     &state.pg_pool,
     &data.comment_id,
@@ -1412,6 +4659,12 @@ async fn delete_published_collab_reaction_handler(
   Ok(Json(AppResponse::Ok()))
 }
 
+/// The maximum size of the metadata of a published collab, in bytes.
+const MAX_PUBLISH_METADATA_SIZE: u32 = 4 * 1024 * 1024;
+
+/// The maximum size of the data of a published collab, in bytes.
+const MAX_PUBLISH_DATA_SIZE: u32 = 32 * 1024 * 1024;
+
 async fn post_publish_collabs_handler(
   workspace_id: web::Path<Uuid>,
   user_uuid: UserUuid,
@@ -1419,6 +4672,28 @@ async fn post_publish_collabs_handler(
   state: Data<AppState>,
 ) -> Result<Json<AppResponse<()>>> {
   let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Member)
+    .await?;
+  let has_publish_capability = state
+    .workspace_access_control
+    .enforce_capability(
+      &uid,
+      &workspace_id.to_string(),
+      WorkspaceCapability::Publish,
+    )
+    .await
+    .is_ok();
+  if !has_publish_capability {
+    biz::workspace::publish_permission::enforce_can_publish(
+      &state.pg_pool,
+      &user_uuid,
+      &workspace_id,
+    )
+    .await?;
+  }
 
   let mut accumulator = Vec::<PublishCollabItem<serde_json::Value, Vec<u8>>>::new();
   let mut payload_reader: PayloadReader = PayloadReader::new(payload);
@@ -1426,9 +4701,15 @@ async fn post_publish_collabs_handler(
   loop {
     let meta: PublishCollabMetadata<serde_json::Value> = {
       let meta_len = payload_reader.read_u32_little_endian().await?;
-      if meta_len > 4 * 1024 * 1024 {
-        // 4MiB Limit for metadata
-        return Err(AppError::InvalidRequest(String::from("metadata too large")).into());
+      if meta_len > MAX_PUBLISH_METADATA_SIZE {
+        return Err(
+          AppError::InvalidRequest(String::from("metadata too large"))
+            .with_details(ErrorDetails::limit(
+              MAX_PUBLISH_METADATA_SIZE as i64,
+              Some(meta_len as i64),
+            ))
+            .into(),
+        );
       }
       if meta_len == 0 {
         break;
@@ -1441,9 +4722,15 @@ async fn post_publish_collabs_handler(
 
     let data = {
       let data_len = payload_reader.read_u32_little_endian().await?;
-      if data_len > 32 * 1024 * 1024 {
-        // 32MiB Limit for data
-        return Err(AppError::InvalidRequest(String::from("data too large")).into());
+      if data_len > MAX_PUBLISH_DATA_SIZE {
+        return Err(
+          AppError::InvalidRequest(String::from("data too large"))
+            .with_details(ErrorDetails::limit(
+              MAX_PUBLISH_DATA_SIZE as i64,
+              Some(data_len as i64),
+            ))
+            .into(),
+        );
       }
       let mut data_buffer = vec![0; data_len as usize];
       payload_reader.read_exact(&mut data_buffer).await?;
@@ -1458,10 +4745,23 @@ async fn post_publish_collabs_handler(
       AppError::InvalidRequest(String::from("did not receive any data to publish")).into(),
     );
   }
+  let published = accumulator
+    .iter()
+    .map(|item| json!({ "view_id": item.meta.view_id, "publish_name": item.meta.publish_name }))
+    .collect::<Vec<_>>();
   state
     .published_collab_store
     .publish_collabs(accumulator, &workspace_id, &user_uuid)
     .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::ViewsPublished,
+    None,
+    json!({ "views": published }),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -1475,13 +4775,90 @@ async fn patch_published_collabs_handler(
   if patches.is_empty() {
     return Err(AppError::InvalidRequest("No patches provided".to_string()).into());
   }
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .published_collab_store
     .patch_collabs(&workspace_id, &user_uuid, &patches)
     .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublishedViewsUpdated,
+    None,
+    json!({ "patches": patches.into_inner() }),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
+async fn list_published_view_versions_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedViewVersion>>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let versions = biz::workspace::publish::list_published_view_versions(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(versions)))
+}
+
+async fn post_published_view_rollback_handler(
+  path: web::Path<(Uuid, Uuid, i32)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<PublishedViewVersion>>>> {
+  let (workspace_id, view_id, version) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let new_version = state
+    .published_collab_store
+    .rollback_collab(&workspace_id, &view_id, version, &user_uuid)
+    .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::PublishedViewRolledBack,
+    Some(&view_id.to_string()),
+    json!({ "restored_version": version, "new_version": new_version }),
+  )
+  .await;
+  let versions = biz::workspace::publish::list_published_view_versions(
+    &state.pg_pool,
+    &user_uuid,
+    &workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(versions)))
+}
+
+async fn put_auto_publish_handler(
+  path: web::Path<(Uuid, Uuid)>,
+  user_uuid: UserUuid,
+  state: Data<AppState>,
+  payload: Json<UpdateAutoPublish>,
+) -> Result<Json<AppResponse<PublishInfo>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let info = biz::workspace::auto_publish::set_auto_publish(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    &user_uuid,
+    uid,
+    &workspace_id,
+    &view_id,
+    payload.enabled,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(info)))
+}
+
 async fn delete_published_collabs_handler(
   workspace_id: web::Path<Uuid>,
   user_uuid: UserUuid,
@@ -1493,10 +4870,20 @@ async fn delete_published_collabs_handler(
   if view_ids.is_empty() {
     return Err(AppError::InvalidRequest("No view_ids provided".to_string()).into());
   }
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
   state
     .published_collab_store
     .delete_collabs(&workspace_id, &view_ids, &user_uuid)
     .await?;
+  record_audit_event(
+    &state.pg_pool,
+    &workspace_id,
+    uid,
+    WorkspaceAuditAction::ViewsUnpublished,
+    None,
+    json!({ "view_ids": view_ids }),
+  )
+  .await;
   Ok(Json(AppResponse::Ok()))
 }
 
@@ -1510,6 +4897,138 @@ async fn get_collab_member_list_handler(
   Ok(Json(AppResponse::Ok().with_data(AFCollabMembers(members))))
 }
 
+#[instrument(level = "debug", skip_all, err)]
+async fn list_custom_emoji_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<CustomEmoji>>>> {
+  let workspace_id = workspace_id.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let emojis =
+    biz::workspace::custom_emoji::list_custom_emojis(&state.pg_pool, &workspace_id).await?;
+  Ok(Json(AppResponse::Ok().with_data(emojis)))
+}
+
+#[instrument(level = "debug", skip(state, content), err)]
+async fn put_custom_emoji_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  content_type: web::Header<ContentType>,
+  content: Bytes,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CustomEmoji>>> {
+  let (workspace_id, shortcode) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
+    .await?;
+  let bucket_storage = state.storage_router.bucket_storage(&workspace_id).await?;
+  let emoji = biz::workspace::custom_emoji::upload_custom_emoji(
+    &state.pg_pool,
+    bucket_storage,
+    workspace_id,
+    uid,
+    &shortcode,
+    content.to_vec(),
+    content_type.into_inner().to_string(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(emoji)))
+}
+
+#[instrument(level = "debug", skip(state), err)]
+async fn delete_custom_emoji_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<()>>> {
+  let (workspace_id, shortcode) = path_param.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Write)
+    .await?;
+  let bucket_storage = state.storage_router.bucket_storage(&workspace_id).await?;
+  biz::workspace::custom_emoji::remove_custom_emoji(
+    &state.pg_pool,
+    bucket_storage,
+    workspace_id,
+    &shortcode,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok()))
+}
+
+#[instrument(level = "debug", skip_all, err)]
+async fn get_database_presence_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<DatabasePresence>>> {
+  let (workspace_id, database_id) = path_param.into_inner();
+  let workspace_id = workspace_id.to_string();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &database_id, Action::Read)
+    .await?;
+  let presence = state
+    .row_edit_intents
+    .database_presence(&workspace_id, &database_id);
+  Ok(Json(AppResponse::Ok().with_data(presence)))
+}
+
+async fn get_collab_awareness_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabAwareness>>> {
+  let (workspace_id, object_id) = path_param.into_inner();
+  let workspace_id = workspace_id.to_string();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let awareness = biz::collab::awareness::get_collab_awareness(
+    &state.redis_connection_manager,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(awareness)))
+}
+
+/// Returns who is viewing the collab, so that the web dashboards can show it without connecting to
+/// the realtime server.
+async fn get_collab_presence_handler(
+  user_uuid: UserUuid,
+  path_param: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<CollabPresence>>> {
+  let (workspace_id, object_id) = path_param.into_inner();
+  let workspace_id = workspace_id.to_string();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .collab_access_control
+    .enforce_action(&workspace_id, &uid, &object_id, Action::Read)
+    .await?;
+  let presence = biz::collab::awareness::get_collab_presence(
+    &state.pg_pool,
+    &state.redis_connection_manager,
+    &workspace_id,
+    &object_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(presence)))
+}
+
 #[instrument(level = "info", skip_all, err)]
 async fn post_realtime_message_stream_handler(
   user_uuid: UserUuid,
@@ -1525,6 +5044,17 @@ async fn post_realtime_message_stream_handler(
     .get_user_uid(&user_uuid)
     .await
     .map_err(AppResponseError::from)?;
+  if let Err(retry_after_secs) = state.realtime_message_limiter.acquire(uid, &device_id) {
+    state
+      .metrics
+      .request_metrics
+      .record_realtime_message_throttled();
+    return Err(AppError::TooManyRequests { retry_after_secs }.into());
+  }
+  state
+    .user_devices
+    .record_message(&state.pg_pool, uid, &device_id)
+    .await;
 
   let mut bytes = BytesMut::new();
   while let Some(item) = payload.next().await {
@@ -1565,11 +5095,36 @@ async fn get_workspace_usage_handler(
     .workspace_access_control
     .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
     .await?;
-  let res =
-    biz::workspace::ops::get_workspace_document_total_bytes(&state.pg_pool, &workspace_id).await?;
+  let res = biz::workspace::ops::get_workspace_usage(&state.pg_pool, &workspace_id).await?;
   Ok(Json(AppResponse::Ok().with_data(res)))
 }
 
+/// Only the owner of the workspace can see its realtime activity, as seen by the server node that
+/// answers the request.
+async fn get_workspace_realtime_stats_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  server: Data<RealtimeServerAddr>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<WorkspaceRealtimeStats>>> {
+  let workspace_id = workspace_id.into_inner().to_string();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  let stats = server
+    .send(GetWorkspaceRealtimeStats { workspace_id })
+    .await
+    .map_err(|err| {
+      AppError::Internal(anyhow!(
+        "Failed to get the realtime stats from the websocket server, error:{}",
+        err
+      ))
+    })?;
+  Ok(Json(AppResponse::Ok().with_data(stats)))
+}
+
 async fn get_workspace_folder_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -1588,6 +5143,12 @@ async fn get_workspace_folder_handler(
   } else {
     workspace_id.to_string()
   };
+  let filter = query
+    .filter
+    .as_deref()
+    .map(serde_json::from_str::<FolderViewFilter>)
+    .transpose()
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid folder view filter: {}", err)))?;
   let folder_view = biz::collab::ops::get_user_workspace_structure(
     &state.collab_access_control_storage,
     &state.pg_pool,
@@ -1595,11 +5156,34 @@ async fn get_workspace_folder_handler(
     workspace_id,
     depth,
     &root_view_id,
+    filter.as_ref(),
   )
   .await?;
   Ok(Json(AppResponse::Ok().with_data(folder_view)))
 }
 
+async fn get_workspace_folder_view_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, String)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<FolderViewMetadata>>> {
+  let (workspace_id, view_id) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let view = biz::collab::ops::get_user_folder_view_metadata(
+    &state.collab_access_control_storage,
+    &state.pg_pool,
+    uid,
+    workspace_id,
+    &view_id,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(view)))
+}
+
 async fn get_recent_views_handler(
   user_uuid: UserUuid,
   workspace_id: web::Path<Uuid>,
@@ -1659,25 +5243,152 @@ async fn get_trash_views_handler(
     .workspace_access_control
     .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
     .await?;
-  let folder_views =
-    get_user_trash_folder_views(&state.collab_access_control_storage, uid, workspace_id).await?;
+  let folder_views = get_user_trash_folder_views(
+    &state.collab_access_control_storage,
+    &state.pg_pool,
+    state.storage_router.pg_pool_router(),
+    uid,
+    workspace_id,
+  )
+  .await?;
   let section_items = TrashSectionItems {
     views: folder_views,
   };
   Ok(Json(AppResponse::Ok().with_data(section_items)))
 }
 
+async fn full_text_search_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  query: web::Query<FullTextSearchRequest>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<FullTextSearchResultItem>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let results = full_text_search(
+    &state.pg_pool,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    query.into_inner(),
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(results)))
+}
+
+async fn semantic_search_handler(
+  user_uuid: UserUuid,
+  workspace_id: web::Path<Uuid>,
+  payload: Json<SemanticSearchRequest>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<Vec<SemanticSearchResultItem>>>> {
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  let workspace_id = workspace_id.into_inner();
+  state
+    .workspace_access_control
+    .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+    .await?;
+  let results = semantic_search(
+    &state.pg_pool,
+    &state.ai_client,
+    &state.collab_access_control_storage,
+    uid,
+    workspace_id,
+    payload.into_inner(),
+    &state.metrics.request_metrics,
+  )
+  .await?;
+  Ok(Json(AppResponse::Ok().with_data(results)))
+}
+
+async fn get_guest_views_handler(
+  user_uuid: UserUuid,
+  path: web::Path<(Uuid, i64)>,
+  state: Data<AppState>,
+) -> Result<Json<AppResponse<GuestSectionItems>>> {
+  let (workspace_id, guest_uid) = path.into_inner();
+  let uid = state.user_cache.get_user_uid(&user_uuid).await?;
+  state
+    .workspace_access_control
+    .enforce_role(&uid, &workspace_id.to_string(), AFRole::Owner)
+    .await?;
+  let folder_views = get_guest_granted_views(
+    &state.collab_access_control_storage,
+    &state.pg_pool,
+    workspace_id,
+    guest_uid,
+  )
+  .await?;
+  let section_items = GuestSectionItems {
+    views: folder_views,
+  };
+  Ok(Json(AppResponse::Ok().with_data(section_items)))
+}
+
+/// Returns the whole published outline, or only its changes when `since` is set. The whole outline
+/// is returned with an ETag, so that the clients polling it get a 304 while it's unchanged.
 async fn get_workspace_publish_outline_handler(
   publish_namespace: web::Path<String>,
+  query: web::Query<QueryPublishedOutline>,
   state: Data<AppState>,
-) -> Result<Json<AppResponse<PublishedView>>> {
+  req: HttpRequest,
+) -> Result<HttpResponse> {
+  if let Some(since) = query.since {
+    let since = chrono::DateTime::from_timestamp_millis(since)
+      .ok_or_else(|| AppError::InvalidRequest(format!("Invalid since: {}", since)))?;
+    let changes = biz::collab::ops::get_published_view_changes(
+      &state.collab_access_control_storage,
+      publish_namespace.into_inner(),
+      &state.pg_pool,
+      since,
+    )
+    .await?;
+    return Ok(
+      HttpResponse::Ok()
+        .append_header((CACHE_CONTROL, "no-store"))
+        .json(AppResponse::Ok().with_data(changes)),
+    );
+  }
+
   let published_view = biz::collab::ops::get_published_view(
     &state.collab_access_control_storage,
     publish_namespace.into_inner(),
     &state.pg_pool,
   )
   .await?;
-  Ok(Json(AppResponse::Ok().with_data(published_view)))
+  let body = serde_json::to_vec(&AppResponse::Ok().with_data(published_view))
+    .map_err(|err| AppError::Internal(err.into()))?;
+  let etag = format!("\"{:x}\"", md5::compute(&body));
+  let is_not_modified = req
+    .headers()
+    .get(IF_NONE_MATCH)
+    .and_then(|value| value.to_str().ok())
+    .map(|value| {
+      value
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == etag || tag == "*")
+    })
+    .unwrap_or(false);
+  if is_not_modified {
+    return Ok(
+      HttpResponse::NotModified()
+        .append_header((ETAG, etag))
+        .append_header((CACHE_CONTROL, "public, no-cache"))
+        .finish(),
+    );
+  }
+  Ok(
+    HttpResponse::Ok()
+      .append_header((ETAG, etag))
+      .append_header((CACHE_CONTROL, "public, no-cache"))
+      .content_type(ContentType::json())
+      .body(body),
+  )
 }
 
 #[inline]
@@ -1685,11 +5396,12 @@ async fn parser_realtime_msg(
   payload: Bytes,
   req: HttpRequest,
 ) -> Result<RealtimeMessage, AppError> {
-  let HttpRealtimeMessage {
-    device_id: _,
-    payload,
-  } =
+  let http_message =
     HttpRealtimeMessage::decode(payload.as_ref()).map_err(|err| AppError::Internal(err.into()))?;
+  let protocol_version = http_message
+    .negotiate_protocol_version()
+    .map_err(|err| AppError::InvalidRequest(err.to_string()))?;
+  let payload = http_message.payload;
   let payload = match req.headers().get(X_COMPRESSION_TYPE) {
     None => payload,
     Some(_) => match compress_type_from_header_value(req.headers())? {
@@ -1708,9 +5420,11 @@ async fn parser_realtime_msg(
   match message {
     Message::Binary(bytes) => {
       let realtime_msg = tokio::task::spawn_blocking(move || {
-        RealtimeMessage::decode(&bytes).map_err(|err| {
-          AppError::InvalidRequest(format!("Failed to parse RealtimeMessage: {}", err))
-        })
+        RealtimeMessage::decode(&bytes)
+          .and_then(|message| message.upgrade_from(protocol_version))
+          .map_err(|err| {
+            AppError::InvalidRequest(format!("Failed to parse RealtimeMessage: {}", err))
+          })
       })
       .await
       .map_err(AppError::from)??;
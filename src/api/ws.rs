@@ -2,25 +2,32 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 use actix::Addr;
-use actix_http::header::AUTHORIZATION;
+use actix_http::header::{HeaderName, HeaderValue, AUTHORIZATION, USER_AGENT};
 use actix_web::web::{Data, Path, Payload};
 use actix_web::{get, web, HttpRequest, HttpResponse, Result, Scope};
 use actix_web_actors::ws;
 use secrecy::Secret;
 use semver::Version;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 use tracing::{debug, error, instrument, trace};
 
 use app_error::AppError;
-use appflowy_collaborate::actix_ws::client::rt_client::RealtimeClient;
+use appflowy_collaborate::actix_ws::client::rt_client::{BlockedWorkspaces, RealtimeClient};
 use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use authentication::jwt::{authorization_from_token, UserUuid};
 use collab_rt_entity::user::{AFUserChange, RealtimeUser, UserMessage};
-use collab_rt_entity::RealtimeMessage;
+use collab_rt_entity::{
+  negotiate_realtime_protocol_version, RealtimeMessage, LEGACY_REALTIME_PROTOCOL_VERSION,
+  REALTIME_PROTOCOL_VERSION_HEADER,
+};
+use database_entity::dto::AFRole;
+use shared_entity::dto::server_info_dto::ClientVersionStatus;
 use shared_entity::response::AppResponseError;
 
+use crate::biz::workspace::ip_allowlist::{client_ip, select_blocked_workspace_ids};
 use crate::state::AppState;
 
 pub fn ws_scope() -> Scope {
@@ -56,6 +63,8 @@ pub async fn establish_ws_connection(
     device_id,
     client_version,
     connect_at,
+    LEGACY_REALTIME_PROTOCOL_VERSION,
+    None,
   )
   .await
 }
@@ -76,6 +85,8 @@ pub async fn establish_ws_connection_v1(
     client_version,
     device_id,
     connect_at,
+    protocol_version,
+    platform,
   } = match ConnectInfo::parse_from(&request) {
     Ok(info) => info,
     Err(_) => {
@@ -88,6 +99,22 @@ pub async fn establish_ws_connection_v1(
     return Err(AppError::Connect("Client version is too low".to_string()).into());
   }
 
+  let protocol_version = negotiate_realtime_protocol_version(protocol_version)
+    .map_err(|err| AppError::Connect(err.to_string()))?;
+
+  if let Some(policy) = state.client_version_gate.check(&client_version) {
+    if policy.status == ClientVersionStatus::Blocked {
+      return Err(
+        AppError::ClientVersionBlocked(
+          policy
+            .message
+            .unwrap_or_else(|| "This version of the client is no longer supported".to_string()),
+        )
+        .into(),
+      );
+    }
+  }
+
   start_connect(
     &request,
     payload,
@@ -98,6 +125,8 @@ pub async fn establish_ws_connection_v1(
     device_id,
     client_version,
     connect_at,
+    protocol_version,
+    platform,
   )
   .await
 }
@@ -114,8 +143,12 @@ async fn start_connect(
   device_id: String,
   client_app_version: Version,
   connect_at: i64,
+  protocol_version: u32,
+  platform: Option<String>,
 ) -> Result<HttpResponse> {
   let auth = authorization_from_token(access_token.as_str(), jwt_secret)?;
+  let token_issued_at = auth.claims.iat;
+  let has_second_factor = auth.claims.has_second_factor();
   let user_uuid = UserUuid::from_auth(auth)?;
   let result = state.user_cache.get_user_uid(&user_uuid).await;
 
@@ -126,6 +159,46 @@ async fn start_connect(
         uid, device_id, client_app_version
       );
 
+      // The platform is reported by the client, or guessed from its user agent.
+      let platform = platform.or_else(|| {
+        request
+          .headers()
+          .get(USER_AGENT)
+          .and_then(|value| value.to_str().ok())
+          .map(|value| value.to_string())
+      });
+      state
+        .user_devices
+        .record_connect(
+          &state.pg_pool,
+          uid,
+          &device_id,
+          platform.as_deref(),
+          &client_app_version.to_string(),
+          token_issued_at,
+        )
+        .await
+        .map_err(AppResponseError::from)?;
+
+      // The realtime connections aren't about a single workspace, so the workspaces whose IP
+      // allowlist the client is outside of are left out of the connection instead of rejecting it.
+      let client_ip = client_ip(request, state.config.client_ip.trusted_proxy_count);
+      let mut blocked_workspace_ids =
+        select_blocked_workspace_ids(&state.ip_allowlists, &state.pg_pool, &user_uuid, client_ip)
+          .await
+          .map_err(AppResponseError::from)?;
+      // So are the workspaces that require a second factor the session lacks.
+      if !has_second_factor {
+        blocked_workspace_ids.extend(
+          state
+            .two_factor_policy
+            .select_requiring_workspace_ids(&user_uuid)
+            .await
+            .map_err(AppResponseError::from)?,
+        );
+      }
+      let blocked_workspaces = BlockedWorkspaces::new(blocked_workspace_ids);
+
       let session_id = uuid::Uuid::new_v4().to_string();
       let realtime_user = RealtimeUser::new(
         uid,
@@ -141,18 +214,28 @@ async fn start_connect(
         Duration::from_secs(state.config.websocket.heartbeat_interval as u64),
         Duration::from_secs(state.config.websocket.client_timeout as u64),
         client_app_version,
+        protocol_version,
         external_source,
         10,
-      );
+      )
+      .with_blocked_workspaces(blocked_workspaces.clone());
 
       // Receive user change notifications and send them to the client.
-      listen_on_user_change(state, uid, tx);
+      listen_on_user_change(state, uid, tx.clone());
+      listen_on_folder_change(state, uid, blocked_workspaces, tx);
 
       match ws::WsResponseBuilder::new(client, request, payload)
         .frame_size(MAX_FRAME_SIZE * 2)
         .start()
       {
-        Ok(response) => Ok(response),
+        Ok(mut response) => {
+          // Tells the client which version of the realtime message format the server speaks.
+          response.headers_mut().insert(
+            HeaderName::from_static(REALTIME_PROTOCOL_VERSION_HEADER),
+            HeaderValue::from(protocol_version),
+          );
+          Ok(response)
+        },
         Err(e) => {
           error!("🔴ws connection error: {:?}", e);
           Err(e)
@@ -194,16 +277,58 @@ fn listen_on_user_change(state: &Data<AppState>, uid: i64, tx: Sender<RealtimeMe
   });
 }
 
+/// Sends the client the folder changes of the workspaces the user is a member of. The guests are
+/// left out, as the changes may be about views they can't access, and so are the workspaces the
+/// client is blocked from.
+fn listen_on_folder_change(
+  state: &Data<AppState>,
+  uid: i64,
+  blocked_workspaces: BlockedWorkspaces,
+  tx: Sender<RealtimeMessage>,
+) {
+  let mut folder_change_recv = state.pg_listeners.subscribe_folder_change();
+  let workspace_access_control = state.workspace_access_control.clone();
+  actix::spawn(async move {
+    loop {
+      let change = match folder_change_recv.recv().await {
+        Ok(change) => change,
+        Err(RecvError::Lagged(skipped)) => {
+          trace!("Skip {} folder changes for user {}", skipped, uid);
+          continue;
+        },
+        Err(RecvError::Closed) => break,
+      };
+      if blocked_workspaces.is_blocked(&change.workspace_id) {
+        continue;
+      }
+      if workspace_access_control
+        .enforce_role(&uid, &change.workspace_id, AFRole::Member)
+        .await
+        .is_err()
+      {
+        continue;
+      }
+      let msg = UserMessage::FolderChange(change);
+      if tx.send(RealtimeMessage::User(msg)).await.is_err() {
+        break;
+      }
+    }
+  });
+}
+
 struct ConnectInfo {
   access_token: String,
   client_version: Version,
   device_id: String,
   connect_at: i64,
+  protocol_version: u32,
+  platform: Option<String>,
 }
 
 const CLIENT_VERSION: &str = "client-version";
 const DEVICE_ID: &str = "device-id";
 const CONNECT_AT: &str = "connect-at";
+const CLIENT_PLATFORM: &str = "client-platform";
 
 // Trait for parameter extraction
 trait ExtractParameter {
@@ -253,12 +378,22 @@ impl ConnectInfo {
         .unwrap_or_else(|_| chrono::Utc::now().timestamp()),
       Err(_) => chrono::Utc::now().timestamp(),
     };
+    let protocol_version = match source.extract_param(REALTIME_PROTOCOL_VERSION_HEADER) {
+      Ok(version) => version.parse::<u32>().map_err(|_| {
+        AppError::InvalidRequest(format!("Invalid realtime protocol version:{}", version))
+      })?,
+      Err(_) => LEGACY_REALTIME_PROTOCOL_VERSION,
+    };
+
+    let platform = source.extract_param(CLIENT_PLATFORM).ok();
 
     Ok(Self {
       access_token,
       client_version,
       device_id,
       connect_at,
+      protocol_version,
+      platform,
     })
   }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::net::TcpListener;
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,6 +22,8 @@ use actix_web::middleware::NormalizePath;
 use actix_web::{dev::Server, web::Data, App, HttpServer};
 use anyhow::{Context, Error};
 use appflowy_collaborate::collab::access_control::CollabStorageAccessControlImpl;
+use authentication::access_token::AccessTokenVerifier;
+use authentication::session_policy::WorkspaceSessionPolicy;
 use aws_sdk_s3::config::{Credentials, Region, SharedCredentialsProvider};
 use aws_sdk_s3::operation::create_bucket::CreateBucketError;
 use aws_sdk_s3::types::{
@@ -28,6 +31,7 @@ use aws_sdk_s3::types::{
 };
 use collab::lock::Mutex;
 use database::collab::cache::CollabCache;
+use database::residency::PgPoolRouter;
 use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod};
 use openssl::x509::X509;
 use secrecy::{ExposeSecret, Secret};
@@ -40,6 +44,7 @@ use appflowy_collaborate::actix_ws::server::RealtimeServerActor;
 use appflowy_collaborate::collab::storage::CollabStorageImpl;
 use appflowy_collaborate::command::{CLCommandReceiver, CLCommandSender};
 use appflowy_collaborate::indexer::IndexerProvider;
+use appflowy_collaborate::row_edit_intent::RowEditIntents;
 use appflowy_collaborate::snapshot::SnapshotControl;
 use appflowy_collaborate::CollaborationServer;
 use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
@@ -49,7 +54,9 @@ use snowflake::Snowflake;
 use tonic_proto::history::history_client::HistoryClient;
 
 use crate::api::access_request::access_request_scope;
+use crate::api::admin::admin_scope;
 use crate::api::ai::ai_completion_scope;
+use crate::api::announcement::announcement_scope;
 use crate::api::chat::chat_scope;
 use crate::api::data_import::data_import_scope;
 use crate::api::file_storage::file_storage_scope;
@@ -57,19 +64,53 @@ use crate::api::history::history_scope;
 use crate::api::metrics::metrics_scope;
 use crate::api::search::search_scope;
 use crate::api::server_info::server_info_scope;
-use crate::api::template::template_scope;
+use crate::api::sso::sso_scope;
+use crate::api::template::{template_marketplace_scope, template_scope};
 use crate::api::user::user_scope;
 use crate::api::workspace::{collab_scope, workspace_scope};
 use crate::api::ws::ws_scope;
+use crate::biz::client_version::ClientVersionGate;
+use crate::biz::collab::archive::register_collab_archive_job;
+use crate::biz::collab::scrub::register_collab_scrub_job;
+use crate::biz::collab::snapshot_schedule::register_snapshot_schedule_job;
+use crate::biz::collab::trash::register_collab_trash_purge_job;
+use crate::biz::oembed::OEmbedResolver;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::scheduler::Scheduler;
+use crate::biz::user::user_access_token::AccessTokenStore;
+use crate::biz::user::user_device::UserDeviceTracker;
+use crate::biz::workspace::access_expiry::{
+  register_access_expiry_job, spawn_listen_on_access_grant_change,
+};
+use crate::biz::workspace::api_usage::{register_api_usage_cleanup_job, spawn_api_usage_flush_job};
+use crate::biz::workspace::auto_publish::register_auto_publish_job;
+use crate::biz::workspace::insights::register_publish_access_log_cleanup_job;
+use crate::biz::workspace::invitation_expiry::register_invitation_expiry_job;
+use crate::biz::workspace::ip_allowlist::IpAllowlistCache;
+use crate::biz::workspace::lifecycle::register_workspace_lifecycle_job;
+use crate::biz::workspace::public_access::PublicWorkspaceAccess;
 use crate::biz::workspace::publish::{
   PublishedCollabPostgresStore, PublishedCollabS3StoreWithPostgresFallback, PublishedCollabStore,
 };
+use crate::biz::workspace::publish_analytics::{
+  register_publish_analytics_cleanup_job, spawn_publish_analytics_flush_job,
+  PublishAnalyticsRecorder,
+};
+use crate::biz::workspace::publish_expiry::register_publish_expiry_job;
+use crate::biz::workspace::publish_sanitize::PublishSanitizePolicy;
+use crate::biz::workspace::residency::StorageRouter;
+use crate::biz::workspace::two_factor::WorkspaceTwoFactorPolicy;
 use crate::config::config::{
   Config, DatabaseSetting, GoTrueSetting, PublishedCollabStorageBackend, S3Setting,
 };
 use crate::mailer::AFCloudMailer;
+use crate::middleware::api_usage_mw::{ApiUsageCounter, ApiUsageMiddleware};
+use crate::middleware::client_version_mw::ClientVersionMiddleware;
+use crate::middleware::ip_allowlist_mw::IpAllowlistMiddleware;
 use crate::middleware::metrics_mw::MetricsMiddleware;
+use crate::middleware::rate_limit_mw::{
+  RateLimitMiddleware, RealtimeMessageLimiter, RequestRateCounter,
+};
 use crate::middleware::request_id::RequestIdMiddleware;
 use crate::self_signed::create_self_signed_certificate;
 use crate::state::{AppMetrics, AppState, GoTrueAdmin, UserCache};
@@ -137,13 +178,30 @@ pub async fn run_actix_server(
     config.collab.edit_state_max_count,
     config.collab.edit_state_max_secs,
     state.indexer_provider.clone(),
+    state.row_edit_intents.clone(),
+    (config.collab.group_hibernate_after_secs > 0)
+      .then(|| Duration::from_secs(config.collab.group_hibernate_after_secs)),
   )
   .await
   .unwrap();
 
   let realtime_server_actor = Supervisor::start(|_| RealtimeServerActor(realtime_server));
+  let request_rate_counter = Arc::new(RequestRateCounter::new(
+    config.rate_limit.requests_per_window,
+    Duration::from_secs(config.rate_limit.window_secs),
+  ));
+  let api_usage_counter = Arc::new(ApiUsageCounter::default());
+  info!("Setting up API usage flush job...");
+  spawn_api_usage_flush_job(state.pg_pool.clone(), api_usage_counter.clone());
+  let publish_analytics = Arc::new(PublishAnalyticsRecorder::default());
+  info!("Setting up publish analytics flush job...");
+  spawn_publish_analytics_flush_job(state.pg_pool.clone(), publish_analytics.clone());
+  let access_token_verifier: Arc<dyn AccessTokenVerifier> = Arc::new(state.access_tokens.clone());
+  let session_policy: Arc<dyn WorkspaceSessionPolicy> = state.two_factor_policy.clone();
   let mut server = HttpServer::new(move || {
     App::new()
+      // Registered before NormalizePath so that it runs after it, on the normalized path.
+      .wrap(IpAllowlistMiddleware)
       .wrap(NormalizePath::trim())
        // Middleware is registered for each App, scope, or Resource and executed in opposite order as registration
       .wrap(MetricsMiddleware)
@@ -153,6 +211,9 @@ pub async fn run_actix_server(
           .build(),
       )
       .wrap(RequestIdMiddleware)
+      .wrap(ClientVersionMiddleware)
+      .wrap(RateLimitMiddleware)
+      .wrap(ApiUsageMiddleware)
       .service(server_info_scope())
       .service(user_scope())
       .service(workspace_scope())
@@ -165,8 +226,12 @@ pub async fn run_actix_server(
       .service(metrics_scope())
       .service(search_scope())
       .service(template_scope())
+      .service(template_marketplace_scope())
       .service(data_import_scope())
       .service(access_request_scope())
+      .service(announcement_scope())
+      .service(admin_scope())
+      .service(sso_scope())
       .app_data(Data::new(state.metrics.registry.clone()))
       .app_data(Data::new(state.metrics.request_metrics.clone()))
       .app_data(Data::new(state.metrics.realtime_metrics.clone()))
@@ -176,6 +241,11 @@ pub async fn run_actix_server(
       .app_data(Data::new(state.clone()))
       .app_data(Data::new(storage.clone()))
       .app_data(Data::new(state.published_collab_store.clone()))
+      .app_data(Data::new(request_rate_counter.clone()))
+      .app_data(Data::new(api_usage_counter.clone()))
+      .app_data(Data::new(publish_analytics.clone()))
+      .app_data(Data::new(access_token_verifier.clone()))
+      .app_data(Data::new(session_policy.clone()))
   });
 
   server = match pair {
@@ -205,6 +275,13 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   info!("Preparing to run database migrations...");
   let pg_pool = get_connection_pool(&config.db_settings).await?;
   migrate(&pg_pool).await?;
+  let pg_read_pool = match &config.read_replica_db_settings {
+    Some(db_settings) => {
+      info!("Connecting to the read replica...");
+      get_connection_pool(db_settings).await?
+    },
+    None => pg_pool.clone(),
+  };
 
   // Bucket storage
   info!("Setting up S3 bucket...");
@@ -217,6 +294,43 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     pg_pool.clone(),
   ));
 
+  // Data residency
+  let mut regional_pools = HashMap::new();
+  let mut regional_bucket_storages = HashMap::new();
+  for region in &config.residency.regions {
+    info!("Setting up storage for residency: {}", region.name);
+    let regional_pool = get_connection_pool(&region.db_settings).await?;
+    migrate(&regional_pool).await?;
+    let regional_s3_client = AwsS3BucketClientImpl::new(
+      get_aws_s3_client(&region.s3).await?,
+      region.s3.bucket.clone(),
+    );
+    regional_bucket_storages.insert(
+      region.name.clone(),
+      Arc::new(S3BucketStorage::from_bucket_impl(
+        regional_s3_client,
+        regional_pool.clone(),
+      )),
+    );
+    regional_pools.insert(region.name.clone(), regional_pool);
+  }
+
+  // Collab shards
+  let mut collab_shard_pools = HashMap::new();
+  for shard in &config.collab_shard.shards {
+    info!("Setting up storage for collab shard: {}", shard.name);
+    let shard_pool = get_connection_pool(&shard.db_settings).await?;
+    migrate(&shard_pool).await?;
+    collab_shard_pools.insert(shard.name.clone(), shard_pool);
+  }
+  let pg_pool_router = PgPoolRouter::with_regional_pools(pg_pool.clone(), regional_pools)
+    .with_collab_shards(collab_shard_pools);
+  let storage_router = StorageRouter::new(
+    pg_pool_router.clone(),
+    bucket_storage.clone(),
+    regional_bucket_storages,
+  );
+
   // Published Collab Storage
   info!("Setting up Published Collab storage...");
   let published_collab_store: Arc<dyn PublishedCollabStore> =
@@ -226,6 +340,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
         Arc::new(PublishedCollabPostgresStore::new(
           metrics.published_collab_metrics.clone(),
           pg_pool.clone(),
+          PublishSanitizePolicy::from(&config.published_collab),
         ))
       },
       PublishedCollabStorageBackend::S3WithPostgresBackup => {
@@ -234,6 +349,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
           metrics.published_collab_metrics.clone(),
           pg_pool.clone(),
           s3_client.clone(),
+          PublishSanitizePolicy::from(&config.published_collab),
         ))
       },
     };
@@ -282,7 +398,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     } else {
       Arc::new(NoOpsRealtimeCollabAccessControlImpl::new())
     };
-  let collab_cache = CollabCache::new(redis_conn_manager.clone(), pg_pool.clone());
+  let collab_cache = CollabCache::with_router(redis_conn_manager.clone(), pg_pool_router.clone());
 
   let collab_storage_access_control = CollabStorageAccessControlImpl {
     collab_access_control: collab_access_control.clone(),
@@ -291,7 +407,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   };
   let snapshot_control = SnapshotControl::new(
     redis_conn_manager.clone(),
-    pg_pool.clone(),
+    pg_pool_router,
     metrics.collab_metrics.clone(),
   )
   .await;
@@ -316,9 +432,70 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
   let grpc_history_client = Arc::new(Mutex::new(HistoryClient::new(channel)));
   let mailer = get_mailer(config).await?;
 
+  spawn_listen_on_access_grant_change(
+    pg_listeners.subscribe_access_grant_change(),
+    workspace_access_control.clone(),
+    collab_access_control.clone(),
+  );
+
+  info!("Setting up scheduled jobs...");
+  let scheduler = Scheduler::new(pg_pool.clone());
+  register_access_expiry_job(
+    &scheduler,
+    pg_pool.clone(),
+    workspace_access_control.clone(),
+    collab_access_control.clone(),
+    mailer.clone(),
+    config.appflowy_web_url.clone(),
+  );
+  register_publish_access_log_cleanup_job(&scheduler, pg_pool.clone());
+  register_api_usage_cleanup_job(&scheduler, pg_pool.clone());
+  register_publish_analytics_cleanup_job(&scheduler, pg_pool.clone());
+  register_invitation_expiry_job(&scheduler, pg_pool.clone());
+  register_auto_publish_job(
+    &scheduler,
+    pg_pool.clone(),
+    collab_access_control_storage.clone(),
+    published_collab_store.clone(),
+    &config.published_collab,
+  );
+  register_publish_expiry_job(&scheduler, pg_pool.clone(), published_collab_store.clone());
+  register_collab_archive_job(&scheduler, collab_cache.router().clone(), &config.collab);
+  register_collab_trash_purge_job(&scheduler, collab_cache.router().clone(), &config.collab);
+  register_collab_scrub_job(&scheduler, collab_cache.router().clone(), &config.collab);
+  register_snapshot_schedule_job(
+    &scheduler,
+    collab_cache.router().clone(),
+    collab_access_control_storage.clone(),
+  );
+  register_workspace_lifecycle_job(
+    &scheduler,
+    pg_pool.clone(),
+    realtime_access_control.clone(),
+    storage_router.clone(),
+    mailer.clone(),
+    config.appflowy_web_url.clone(),
+    &config.workspace_lifecycle,
+  );
+  scheduler.start();
+
+  info!("Loading client version policies...");
+  let client_version_gate = ClientVersionGate::new(pg_pool.clone()).await?;
+  client_version_gate.spawn_reload_job();
+
+  let oembed_resolver = OEmbedResolver::new(&config.oembed, redis_conn_manager.clone());
+  let public_workspace_access = PublicWorkspaceAccess::new(pg_pool.clone());
+  let realtime_message_limiter = Arc::new(RealtimeMessageLimiter::new(
+    config.realtime_rate_limit.messages_per_sec,
+    config.realtime_rate_limit.burst,
+  ));
+  let access_tokens = AccessTokenStore::new(pg_pool.clone());
+  let two_factor_policy = Arc::new(WorkspaceTwoFactorPolicy::new(pg_pool.clone()));
+
   info!("Application state initialized");
   Ok(AppState {
     pg_pool,
+    pg_read_pool,
     config: Arc::new(config.clone()),
     user_cache,
     id_gen: Arc::new(RwLock::new(Snowflake::new(1))),
@@ -330,6 +507,7 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     workspace_access_control,
     realtime_access_control,
     bucket_storage,
+    storage_router,
     published_collab_store,
     bucket_client: s3_client,
     pg_listeners,
@@ -339,6 +517,16 @@ pub async fn init_state(config: &Config, rt_cmd_tx: CLCommandSender) -> Result<A
     ai_client: appflowy_ai_client,
     grpc_history_client,
     indexer_provider,
+    row_edit_intents: RowEditIntents::new(),
+    client_version_gate,
+    scheduler,
+    oembed_resolver,
+    public_workspace_access,
+    realtime_message_limiter,
+    user_devices: UserDeviceTracker::default(),
+    access_tokens,
+    ip_allowlists: Arc::new(IpAllowlistCache::default()),
+    two_factor_policy,
   })
 }
 
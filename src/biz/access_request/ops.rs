@@ -7,6 +7,7 @@ use crate::{
     folder_view::{to_dto_view_icon, to_dto_view_layout},
     ops::get_latest_collab_folder,
   },
+  biz::workspace::permission_audit::record_workspace_permission_change,
   mailer::{WorkspaceAccessRequestApprovedMailerParam, WorkspaceAccessRequestMailerParam},
 };
 use access_control::workspace::WorkspaceAccessControl;
@@ -19,7 +20,7 @@ use database::{
   },
   collab::GetCollabOrigin,
   pg_row::AFAccessRequestStatusColumn,
-  workspace::upsert_workspace_member_with_txn,
+  workspace::{select_user_role, upsert_workspace_member_with_txn},
 };
 use database_entity::dto::AFRole;
 use shared_entity::dto::access_request_dto::{AccessRequest, AccessRequestView};
@@ -135,7 +136,13 @@ pub async fn approve_or_reject_access_request(
 
   let mut txn = pg_pool.begin().await.context("approving request")?;
   let role = AFRole::Member;
+  let workspace_id = access_request.workspace.workspace_id;
+  let requester_uid = access_request.requester.uid;
+  let mut old_role = None;
   if is_approved {
+    old_role = select_user_role(txn.deref_mut(), &requester_uid, &workspace_id)
+      .await
+      .ok();
     upsert_workspace_member_with_txn(
       &mut txn,
       &access_request.workspace.workspace_id,
@@ -187,5 +194,16 @@ pub async fn approve_or_reject_access_request(
   };
   update_access_request_status(txn.deref_mut(), request_id, status).await?;
   txn.commit().await.context("committing transaction")?;
+  if is_approved {
+    record_workspace_permission_change(
+      pg_pool,
+      &workspace_id,
+      Some(uid),
+      requester_uid,
+      old_role.as_ref(),
+      Some(&role),
+    )
+    .await;
+  }
   Ok(())
 }
@@ -0,0 +1,141 @@
+use app_error::AppError;
+use database::announcement::{
+  delete_announcement, insert_announcement, insert_announcement_dismissal,
+  select_active_announcements_for_user, select_all_announcements, select_announcement,
+  update_announcement,
+};
+use database::pg_row::AFAnnouncementRow;
+use shared_entity::dto::server_info_dto::{Announcement, AnnouncementParams, AnnouncementSeverity};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_ANNOUNCEMENT_TITLE_LENGTH: usize = 200;
+const MAX_ANNOUNCEMENT_MESSAGE_LENGTH: usize = 5000;
+
+/// Returns the announcements to show to the user.
+pub async fn get_active_announcements(
+  pg_pool: &PgPool,
+  uid: i64,
+) -> Result<Vec<Announcement>, AppError> {
+  let announcements = select_active_announcements_for_user(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(announcement_from_row)
+    .collect();
+  Ok(announcements)
+}
+
+/// Hides the announcement from the user. Dismissing an announcement twice does nothing.
+pub async fn dismiss_announcement(
+  pg_pool: &PgPool,
+  uid: i64,
+  announcement_id: &Uuid,
+) -> Result<(), AppError> {
+  let announcement = select_announcement(pg_pool, announcement_id)
+    .await?
+    .ok_or_else(|| announcement_not_found(announcement_id))?;
+  if !announcement.dismissible {
+    return Err(AppError::InvalidRequest(format!(
+      "Announcement {} can't be dismissed",
+      announcement_id
+    )));
+  }
+  insert_announcement_dismissal(pg_pool, announcement_id, uid).await
+}
+
+pub async fn list_announcements(pg_pool: &PgPool) -> Result<Vec<Announcement>, AppError> {
+  let announcements = select_all_announcements(pg_pool)
+    .await?
+    .into_iter()
+    .map(announcement_from_row)
+    .collect();
+  Ok(announcements)
+}
+
+pub async fn create_announcement(
+  pg_pool: &PgPool,
+  params: AnnouncementParams,
+) -> Result<Announcement, AppError> {
+  validate_announcement_params(&params)?;
+  let row = insert_announcement(
+    pg_pool,
+    params.title.trim(),
+    &params.message,
+    params.severity as i16,
+    params.starts_at,
+    params.ends_at,
+    params.dismissible,
+  )
+  .await?;
+  Ok(announcement_from_row(row))
+}
+
+pub async fn replace_announcement(
+  pg_pool: &PgPool,
+  announcement_id: &Uuid,
+  params: AnnouncementParams,
+) -> Result<Announcement, AppError> {
+  validate_announcement_params(&params)?;
+  let row = update_announcement(
+    pg_pool,
+    announcement_id,
+    params.title.trim(),
+    &params.message,
+    params.severity as i16,
+    params.starts_at,
+    params.ends_at,
+    params.dismissible,
+  )
+  .await?
+  .ok_or_else(|| announcement_not_found(announcement_id))?;
+  Ok(announcement_from_row(row))
+}
+
+pub async fn remove_announcement(pg_pool: &PgPool, announcement_id: &Uuid) -> Result<(), AppError> {
+  if !delete_announcement(pg_pool, announcement_id).await? {
+    return Err(announcement_not_found(announcement_id));
+  }
+  Ok(())
+}
+
+fn validate_announcement_params(params: &AnnouncementParams) -> Result<(), AppError> {
+  let title = params.title.trim();
+  if title.is_empty() || title.chars().count() > MAX_ANNOUNCEMENT_TITLE_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The title of the announcement must be between 1 and {} characters",
+      MAX_ANNOUNCEMENT_TITLE_LENGTH
+    )));
+  }
+  if params.message.chars().count() > MAX_ANNOUNCEMENT_MESSAGE_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The message of the announcement exceeds {} characters",
+      MAX_ANNOUNCEMENT_MESSAGE_LENGTH
+    )));
+  }
+  if let (Some(starts_at), Some(ends_at)) = (params.starts_at, params.ends_at) {
+    if ends_at <= starts_at {
+      return Err(AppError::InvalidRequest(
+        "The announcement must end after it starts".to_string(),
+      ));
+    }
+  }
+  Ok(())
+}
+
+fn announcement_not_found(announcement_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("Announcement {} not found", announcement_id))
+}
+
+fn announcement_from_row(row: AFAnnouncementRow) -> Announcement {
+  Announcement {
+    announcement_id: row.announcement_id,
+    title: row.title,
+    message: row.message,
+    severity: AnnouncementSeverity::from(row.severity),
+    starts_at: row.starts_at,
+    ends_at: row.ends_at,
+    dismissible: row.dismissible,
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+  }
+}
@@ -0,0 +1,137 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use app_error::AppError;
+use database::client_version::{
+  delete_client_version_policy, select_client_version_policies, upsert_client_version_policy,
+};
+use database::pg_row::AFClientVersionPolicyRow;
+use semver::{Version, VersionReq};
+use shared_entity::dto::server_info_dto::{
+  ClientVersionPolicy, ClientVersionStatus, UpsertClientVersionPolicyParams,
+};
+use sqlx::PgPool;
+use tracing::error;
+
+/// How often the policies are reloaded from the database, to pick up the changes made through
+/// the other instances of the server.
+const CLIENT_VERSION_POLICY_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps the client version policies in memory, so that the version of the client can be
+/// checked on every request without hitting the database.
+#[derive(Clone)]
+pub struct ClientVersionGate {
+  pg_pool: PgPool,
+  policies: Arc<RwLock<Vec<(VersionReq, ClientVersionPolicy)>>>,
+}
+
+impl ClientVersionGate {
+  pub async fn new(pg_pool: PgPool) -> Result<Self, AppError> {
+    let gate = Self {
+      pg_pool,
+      policies: Default::default(),
+    };
+    gate.reload().await?;
+    Ok(gate)
+  }
+
+  pub fn spawn_reload_job(&self) {
+    let gate = self.clone();
+    tokio::spawn(async move {
+      let mut interval = tokio::time::interval(CLIENT_VERSION_POLICY_RELOAD_INTERVAL);
+      loop {
+        interval.tick().await;
+        if let Err(err) = gate.reload().await {
+          error!("Failed to reload client version policies: {:?}", err);
+        }
+      }
+    });
+  }
+
+  async fn reload(&self) -> Result<(), AppError> {
+    let policies = select_client_version_policies(&self.pg_pool)
+      .await?
+      .into_iter()
+      .filter_map(|row| {
+        let version_req = match VersionReq::parse(&row.version_req) {
+          Ok(version_req) => version_req,
+          Err(err) => {
+            error!(
+              "Invalid client version requirement {}: {}",
+              row.version_req, err
+            );
+            return None;
+          },
+        };
+        Some((version_req, client_version_policy_from_row(row)))
+      })
+      .collect();
+    *self.policies.write().unwrap_or_else(|err| err.into_inner()) = policies;
+    Ok(())
+  }
+
+  /// Returns the policy that applies to the version of the client, or `None` if the version is
+  /// fully supported. A blocking policy takes precedence over a deprecating one.
+  pub fn check(&self, client_version: &Version) -> Option<ClientVersionPolicy> {
+    self
+      .policies
+      .read()
+      .unwrap_or_else(|err| err.into_inner())
+      .iter()
+      .filter(|(version_req, _)| version_req.matches(client_version))
+      .map(|(_, policy)| policy)
+      .max_by_key(|policy| policy.status as i16)
+      .cloned()
+  }
+
+  pub async fn list_policies(&self) -> Result<Vec<ClientVersionPolicy>, AppError> {
+    let policies = select_client_version_policies(&self.pg_pool)
+      .await?
+      .into_iter()
+      .map(client_version_policy_from_row)
+      .collect();
+    Ok(policies)
+  }
+
+  pub async fn upsert_policy(
+    &self,
+    params: UpsertClientVersionPolicyParams,
+  ) -> Result<ClientVersionPolicy, AppError> {
+    VersionReq::parse(&params.version_req).map_err(|err| {
+      AppError::InvalidRequest(format!(
+        "Invalid client version requirement {}: {}",
+        params.version_req, err
+      ))
+    })?;
+    let row = upsert_client_version_policy(
+      &self.pg_pool,
+      &params.version_req,
+      params.status as i16,
+      params.message.as_deref(),
+      params.upgrade_url.as_deref(),
+    )
+    .await?;
+    self.reload().await?;
+    Ok(client_version_policy_from_row(row))
+  }
+
+  pub async fn delete_policy(&self, version_req: &str) -> Result<(), AppError> {
+    if !delete_client_version_policy(&self.pg_pool, version_req).await? {
+      return Err(AppError::RecordNotFound(format!(
+        "Client version policy {} not found",
+        version_req
+      )));
+    }
+    self.reload().await
+  }
+}
+
+fn client_version_policy_from_row(row: AFClientVersionPolicyRow) -> ClientVersionPolicy {
+  ClientVersionPolicy {
+    version_req: row.version_req,
+    status: ClientVersionStatus::from(row.status),
+    message: row.message,
+    upgrade_url: row.upgrade_url,
+    updated_at: row.updated_at,
+  }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use database::collab::{archive_collab, delete_stale_collab_archives, select_collabs_to_archive};
+use database::residency::PgPoolRouter;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::biz::scheduler::Scheduler;
+use crate::config::config::CollabSetting;
+
+/// How often the inactive collabs are archived.
+const COLLAB_ARCHIVE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// The maximum number of collabs archived on each check, per database.
+const COLLAB_ARCHIVE_BATCH_SIZE: i64 = 500;
+
+/// Periodically moves the payloads of the collabs that nobody has written for a while to the
+/// compressed archive table, in every regional database. The archived collabs are rehydrated
+/// transparently when they are read again.
+pub fn register_collab_archive_job(
+  scheduler: &Scheduler,
+  router: PgPoolRouter,
+  setting: &CollabSetting,
+) {
+  if setting.archive_after_days == 0 {
+    info!("Collab archival is disabled");
+    return;
+  }
+  let inactive_secs = (setting.archive_after_days * 24 * 60 * 60) as i64;
+  scheduler.register("collab_archive", COLLAB_ARCHIVE_CHECK_INTERVAL, move || {
+    let router = router.clone();
+    async move {
+      for pg_pool in router.all_pools() {
+        if let Err(err) = archive_inactive_collabs(pg_pool, inactive_secs).await {
+          error!("Failed to archive the inactive collabs: {:?}", err);
+        }
+      }
+      Ok(())
+    }
+  });
+}
+
+async fn archive_inactive_collabs(pg_pool: &PgPool, inactive_secs: i64) -> Result<(), AppError> {
+  let deleted = delete_stale_collab_archives(pg_pool).await?;
+  if deleted > 0 {
+    info!("Deleted {} stale collab archives", deleted);
+  }
+
+  let candidates =
+    select_collabs_to_archive(pg_pool, inactive_secs, COLLAB_ARCHIVE_BATCH_SIZE).await?;
+  let mut archived = 0;
+  for candidate in candidates {
+    match archive_collab(pg_pool, &candidate, inactive_secs).await {
+      Ok(true) => archived += 1,
+      Ok(false) => {},
+      Err(err) => error!("Failed to archive collab {}: {:?}", candidate.oid, err),
+    }
+  }
+  if archived > 0 {
+    info!("Archived {} inactive collabs", archived);
+  }
+  Ok(())
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use chrono::DateTime;
+use collab::core::awareness::AwarenessUpdate;
+use collab_stream::awareness::AwarenessSnapshotStore;
+use database::user::select_web_user_from_uid;
+use database_entity::dto::{
+  AFWebUser, AwarenessClientState, CollabAwareness, CollabPresence, CollabPresenceUser,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use yrs::updates::decoder::Decode;
+
+use crate::state::RedisConnectionManager;
+
+/// Returns the last-known awareness state of the collab saved by the realtime server. The clients
+/// that left the collab are not included.
+pub async fn get_collab_awareness(
+  redis_connection_manager: &RedisConnectionManager,
+  workspace_id: &str,
+  object_id: &str,
+) -> Result<CollabAwareness, AppError> {
+  let snapshot = AwarenessSnapshotStore::new(redis_connection_manager.clone())
+    .get(workspace_id, object_id)
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  let Some(snapshot) = snapshot else {
+    return Ok(CollabAwareness {
+      object_id: object_id.to_string(),
+      ..Default::default()
+    });
+  };
+
+  let update =
+    AwarenessUpdate::decode_v1(&snapshot.update).map_err(|err| AppError::Internal(err.into()))?;
+  let mut states = update
+    .clients
+    .into_iter()
+    .filter_map(|(client_id, entry)| {
+      let state = serde_json::from_str::<serde_json::Value>(&entry.json).ok()?;
+      (!state.is_null()).then_some(AwarenessClientState {
+        client_id,
+        clock: entry.clock,
+        state,
+      })
+    })
+    .collect::<Vec<_>>();
+  states.sort_by_key(|state| state.client_id);
+  Ok(CollabAwareness {
+    object_id: object_id.to_string(),
+    updated_at: DateTime::from_timestamp_millis(snapshot.updated_at),
+    states,
+  })
+}
+
+/// Returns the users viewing the collab, along with their device and cursor, read from the
+/// awareness state saved by the realtime server. The states without a user are skipped.
+pub async fn get_collab_presence(
+  pg_pool: &PgPool,
+  redis_connection_manager: &RedisConnectionManager,
+  workspace_id: &str,
+  object_id: &str,
+) -> Result<CollabPresence, AppError> {
+  let awareness = get_collab_awareness(redis_connection_manager, workspace_id, object_id).await?;
+  let mut web_users: HashMap<i64, Option<AFWebUser>> = HashMap::new();
+  let mut users = vec![];
+  for client in awareness.states {
+    // The user is either at the top of the state, or nested under `user` by the newer clients.
+    let user = client.state.get("user").unwrap_or(&client.state);
+    let Some(uid) = user.get("uid").and_then(Value::as_i64) else {
+      continue;
+    };
+    if !web_users.contains_key(&uid) {
+      let web_user = match select_web_user_from_uid(pg_pool, uid).await {
+        Ok(web_user) => Some(web_user),
+        Err(AppError::RecordNotFound(_)) => None,
+        Err(err) => return Err(err),
+      };
+      web_users.insert(uid, web_user);
+    }
+    let web_user = web_users.get(&uid).cloned().flatten();
+    users.push(CollabPresenceUser {
+      client_id: client.client_id,
+      uid,
+      name: web_user.as_ref().map(|web_user| web_user.name.clone()),
+      avatar_url: web_user.and_then(|web_user| web_user.avatar_url),
+      device_id: user
+        .get("device_id")
+        .and_then(Value::as_str)
+        .map(str::to_string),
+      cursor: client
+        .state
+        .get("selection")
+        .or_else(|| client.state.get("cursor"))
+        .filter(|cursor| !cursor.is_null())
+        .cloned(),
+    });
+  }
+  Ok(CollabPresence {
+    object_id: awareness.object_id,
+    updated_at: awareness.updated_at,
+    users,
+  })
+}
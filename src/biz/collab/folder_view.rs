@@ -1,12 +1,16 @@
 use std::collections::HashSet;
 
 use app_error::AppError;
-use chrono::DateTime;
+use chrono::{DateTime, Utc};
 use collab_folder::{Folder, SectionItem, ViewLayout as CollabFolderViewLayout};
 use shared_entity::dto::workspace_dto::{
-  FavoriteFolderView, FolderView, FolderViewMinimal, RecentFolderView, TrashFolderView, ViewLayout,
+  FavoriteFolderView, FolderView, FolderViewFilter, FolderViewMetadata, FolderViewMinimal,
+  RecentFolderView, TrashFolderView, ViewLayout,
 };
 
+/// Guards against a cycle in the parents of the views.
+const MAX_FOLDER_ANCESTORS: usize = 64;
+
 /// Return all folders belonging to a workspace, excluding private sections which the user does not have access to.
 pub fn collab_folder_to_folder_view(
   root_view_id: &str,
@@ -14,21 +18,30 @@ pub fn collab_folder_to_folder_view(
   max_depth: u32,
   pubished_view_ids: &HashSet<String>,
 ) -> Result<FolderView, AppError> {
-  let mut unviewable = HashSet::new();
-  let mut my_private_view_ids = HashSet::new();
-  for private_section in folder.get_my_private_sections() {
-    my_private_view_ids.insert(private_section.id);
-  }
-  for private_section in folder.get_all_private_sections() {
-    if let Some(private_view) = folder.get_view(&private_section.id) {
-      if view_is_space(&private_view) && !my_private_view_ids.contains(&private_section.id) {
-        unviewable.insert(private_section.id);
-      }
-    }
-  }
-  for trash_view in folder.get_all_trash_sections() {
-    unviewable.insert(trash_view.id);
-  }
+  collab_folder_to_filtered_folder_view(
+    root_view_id,
+    folder,
+    max_depth,
+    pubished_view_ids,
+    &HashSet::new(),
+    None,
+  )
+}
+
+/// Same as [collab_folder_to_folder_view], keeping only the views matching the filter and their
+/// ancestors. The restricted views, which the user isn't granted, are left out with their
+/// descendants.
+pub fn collab_folder_to_filtered_folder_view(
+  root_view_id: &str,
+  folder: &Folder,
+  max_depth: u32,
+  pubished_view_ids: &HashSet<String>,
+  restricted_view_ids: &HashSet<String>,
+  filter: Option<&FolderViewFilter>,
+) -> Result<FolderView, AppError> {
+  let my_private_view_ids = my_private_view_ids(folder);
+  let mut unviewable = unviewable_view_ids(folder, &my_private_view_ids);
+  unviewable.extend(restricted_view_ids.iter().cloned());
 
   to_folder_view(
     "",
@@ -37,6 +50,7 @@ pub fn collab_folder_to_folder_view(
     &unviewable,
     &my_private_view_ids,
     pubished_view_ids,
+    filter,
     false,
     0,
     max_depth,
@@ -47,6 +61,31 @@ pub fn collab_folder_to_folder_view(
   )))
 }
 
+fn my_private_view_ids(folder: &Folder) -> HashSet<String> {
+  folder
+    .get_my_private_sections()
+    .into_iter()
+    .map(|private_section| private_section.id)
+    .collect()
+}
+
+/// The views that are hidden from the user, along with their descendants: the private spaces of
+/// the other members, and the trashed views.
+fn unviewable_view_ids(folder: &Folder, my_private_view_ids: &HashSet<String>) -> HashSet<String> {
+  let mut unviewable = HashSet::new();
+  for private_section in folder.get_all_private_sections() {
+    if let Some(private_view) = folder.get_view(&private_section.id) {
+      if view_is_space(&private_view) && !my_private_view_ids.contains(&private_section.id) {
+        unviewable.insert(private_section.id);
+      }
+    }
+  }
+  for trash_view in folder.get_all_trash_sections() {
+    unviewable.insert(trash_view.id);
+  }
+  unviewable
+}
+
 #[allow(clippy::too_many_arguments)]
 fn to_folder_view(
   parent_view_id: &str,
@@ -55,6 +94,7 @@ fn to_folder_view(
   unviewable: &HashSet<String>,
   private_view_ids: &HashSet<String>,
   published_view_ids: &HashSet<String>,
+  filter: Option<&FolderViewFilter>,
   parent_is_private: bool,
   depth: u32,
   max_depth: u32,
@@ -94,12 +134,16 @@ fn to_folder_view(
         unviewable,
         private_view_ids,
         published_view_ids,
+        filter,
         is_private,
         depth + 1,
         max_depth,
       )
     })
     .collect();
+  if depth > 0 && children.is_empty() && !view_matches_filter(&view, filter) {
+    return None;
+  }
   Some(FolderView {
     view_id: view_id.to_string(),
     name: view.name.clone(),
@@ -118,6 +162,221 @@ fn to_folder_view(
   })
 }
 
+/// Return the folder of a workspace as seen by a guest. Only the views explicitly granted to the guest
+/// are included. Granted views nested below views that are not granted are attached to the closest
+/// visible ancestor instead.
+pub fn collab_folder_to_guest_folder_view(
+  root_view_id: &str,
+  folder: &Folder,
+  max_depth: u32,
+  granted_view_ids: &HashSet<String>,
+  published_view_ids: &HashSet<String>,
+  filter: Option<&FolderViewFilter>,
+) -> Result<FolderView, AppError> {
+  let mut unviewable = HashSet::new();
+  for trash_view in folder.get_all_trash_sections() {
+    unviewable.insert(trash_view.id);
+  }
+  let root_view = folder
+    .get_view(root_view_id)
+    .ok_or(AppError::InvalidFolderView(format!(
+      "There is no valid folder view belonging to the root view id: {}",
+      root_view_id
+    )))?;
+  let private_space_ids = private_space_ids(folder);
+  let is_private = is_in_private_space(folder, &root_view, &private_space_ids);
+  let children = to_guest_folder_view_children(
+    root_view_id,
+    folder,
+    &unviewable,
+    granted_view_ids,
+    &private_space_ids,
+    published_view_ids,
+    filter,
+    is_private,
+    1,
+    max_depth,
+  );
+  Ok(to_guest_folder_view(
+    &root_view,
+    is_private,
+    published_view_ids,
+    children,
+  ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn to_guest_folder_view_children(
+  view_id: &str,
+  folder: &Folder,
+  unviewable: &HashSet<String>,
+  granted_view_ids: &HashSet<String>,
+  private_space_ids: &HashSet<String>,
+  published_view_ids: &HashSet<String>,
+  filter: Option<&FolderViewFilter>,
+  parent_is_private: bool,
+  depth: u32,
+  max_depth: u32,
+) -> Vec<FolderView> {
+  if depth > max_depth {
+    return vec![];
+  }
+  let view = match folder.get_view(view_id) {
+    Some(view) => view,
+    None => return vec![],
+  };
+  let mut children = vec![];
+  for child_view_id in view.children.iter() {
+    if unviewable.contains(&child_view_id.id) {
+      continue;
+    }
+    let child_view = match folder.get_view(&child_view_id.id) {
+      Some(child_view) if child_view.parent_view_id == view_id => child_view,
+      _ => continue,
+    };
+    let is_private = parent_is_private
+      || (view_is_space(&child_view) && private_space_ids.contains(&child_view.id));
+    if granted_view_ids.contains(&child_view.id) {
+      let grand_children = to_guest_folder_view_children(
+        &child_view.id,
+        folder,
+        unviewable,
+        granted_view_ids,
+        private_space_ids,
+        published_view_ids,
+        filter,
+        is_private,
+        depth + 1,
+        max_depth,
+      );
+      if grand_children.is_empty() && !view_matches_filter(&child_view, filter) {
+        continue;
+      }
+      children.push(to_guest_folder_view(
+        &child_view,
+        is_private,
+        published_view_ids,
+        grand_children,
+      ));
+    } else {
+      children.extend(to_guest_folder_view_children(
+        &child_view.id,
+        folder,
+        unviewable,
+        granted_view_ids,
+        private_space_ids,
+        published_view_ids,
+        filter,
+        is_private,
+        depth,
+        max_depth,
+      ));
+    }
+  }
+  children
+}
+
+/// Returns true if there is no filter, or if the view matches all the fields of the filter.
+fn view_matches_filter(view: &collab_folder::View, filter: Option<&FolderViewFilter>) -> bool {
+  let filter = match filter {
+    Some(filter) => filter,
+    None => return true,
+  };
+  if let Some(created_by) = filter.created_by {
+    if view.created_by != Some(created_by) {
+      return false;
+    }
+  }
+  if let Some(icon_type) = &filter.icon_type {
+    let view_icon_type = view
+      .icon
+      .as_ref()
+      .map(|icon| to_dto_view_icon_type(icon.ty.clone()));
+    if view_icon_type.as_ref() != Some(icon_type) {
+      return false;
+    }
+  }
+  if let Some(name_contains) = &filter.name_contains {
+    if !view
+      .name
+      .to_lowercase()
+      .contains(&name_contains.to_lowercase())
+    {
+      return false;
+    }
+  }
+  if let Some(layout) = &filter.layout {
+    if to_dto_view_layout(&view.layout) != *layout {
+      return false;
+    }
+  }
+  true
+}
+
+pub fn to_dto_folder_view_without_children(
+  folder: &Folder,
+  view: &collab_folder::View,
+  private_space_ids: &HashSet<String>,
+  published_view_ids: &HashSet<String>,
+) -> FolderView {
+  let is_private = is_in_private_space(folder, view, private_space_ids);
+  to_guest_folder_view(view, is_private, published_view_ids, vec![])
+}
+
+/// The private spaces of all the users of the workspace.
+pub fn private_space_ids(folder: &Folder) -> HashSet<String> {
+  folder
+    .get_all_private_sections()
+    .into_iter()
+    .map(|private_section| private_section.id)
+    .collect()
+}
+
+/// Returns true if the view is a private space, or is nested in one.
+fn is_in_private_space(
+  folder: &Folder,
+  view: &collab_folder::View,
+  private_space_ids: &HashSet<String>,
+) -> bool {
+  let mut visited = HashSet::new();
+  let mut view_id = view.id.clone();
+  while visited.insert(view_id.clone()) {
+    let view = match folder.get_view(&view_id) {
+      Some(view) => view,
+      None => return false,
+    };
+    if view_is_space(&view) && private_space_ids.contains(&view.id) {
+      return true;
+    }
+    view_id = view.parent_view_id.clone();
+  }
+  false
+}
+
+fn to_guest_folder_view(
+  view: &collab_folder::View,
+  is_private: bool,
+  published_view_ids: &HashSet<String>,
+  children: Vec<FolderView>,
+) -> FolderView {
+  FolderView {
+    view_id: view.id.clone(),
+    name: view.name.clone(),
+    icon: view
+      .icon
+      .as_ref()
+      .map(|icon| to_dto_view_icon(icon.clone())),
+    is_space: view_is_space(view),
+    is_private,
+    is_published: published_view_ids.contains(&view.id),
+    layout: to_dto_view_layout(&view.layout),
+    created_at: DateTime::from_timestamp(view.created_at, 0).unwrap_or_default(),
+    last_edited_time: DateTime::from_timestamp(view.last_edited_time, 0).unwrap_or_default(),
+    extra: view.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
+    children,
+  }
+}
+
 pub fn section_items_to_favorite_folder_view(
   section_items: &[SectionItem],
   folder: &Folder,
@@ -191,28 +450,41 @@ pub fn section_items_to_trash_folder_view(
     .filter_map(|section_item| {
       let view = folder.get_view(&section_item.id);
       view.map(|v| {
-        let folder_view = FolderView {
-          view_id: v.id.clone(),
-          name: v.name.clone(),
-          icon: v.icon.as_ref().map(|icon| to_dto_view_icon(icon.clone())),
-          is_space: false,
-          is_private: false,
-          is_published: false,
-          created_at: DateTime::from_timestamp(v.created_at, 0).unwrap_or_default(),
-          last_edited_time: DateTime::from_timestamp(v.last_edited_time, 0).unwrap_or_default(),
-          layout: to_dto_view_layout(&v.layout),
-          extra: v.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
-          children: vec![],
-        };
-        TrashFolderView {
-          view: folder_view,
-          deleted_at: DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
-        }
+        to_trash_folder_view(
+          &v,
+          DateTime::from_timestamp(section_item.timestamp, 0).unwrap_or_default(),
+        )
       })
     })
     .collect()
 }
 
+pub fn to_trash_folder_view(
+  view: &collab_folder::View,
+  deleted_at: DateTime<Utc>,
+) -> TrashFolderView {
+  let folder_view = FolderView {
+    view_id: view.id.clone(),
+    name: view.name.clone(),
+    icon: view
+      .icon
+      .as_ref()
+      .map(|icon| to_dto_view_icon(icon.clone())),
+    is_space: false,
+    is_private: false,
+    is_published: false,
+    created_at: DateTime::from_timestamp(view.created_at, 0).unwrap_or_default(),
+    last_edited_time: DateTime::from_timestamp(view.last_edited_time, 0).unwrap_or_default(),
+    layout: to_dto_view_layout(&view.layout),
+    extra: view.extra.as_ref().map(|e| parse_extra_field_as_json(e)),
+    children: vec![],
+  };
+  TrashFolderView {
+    view: folder_view,
+    deleted_at,
+  }
+}
+
 pub fn view_is_space(view: &collab_folder::View) -> bool {
   let extra = match view.extra.as_ref() {
     Some(extra) => extra,
@@ -275,3 +547,51 @@ pub fn to_dto_folder_view_miminal(collab_folder_view: &collab_folder::View) -> F
     layout: to_dto_view_layout(&collab_folder_view.layout),
   }
 }
+
+/// Returns the metadata of the view, or `None` if the view, or one of its ancestors, is hidden from
+/// the user. For guests, only the granted views and their granted children are returned.
+pub fn collab_folder_to_folder_view_metadata(
+  view_id: &str,
+  folder: &Folder,
+  guest_view_ids: Option<&HashSet<String>>,
+) -> Option<FolderViewMetadata> {
+  let view = folder.get_view(view_id)?;
+  let unviewable = match guest_view_ids {
+    Some(_) => HashSet::new(),
+    None => unviewable_view_ids(folder, &my_private_view_ids(folder)),
+  };
+  let is_visible = |view_id: &str| match guest_view_ids {
+    Some(guest_view_ids) => guest_view_ids.contains(view_id),
+    None => !unviewable.contains(view_id),
+  };
+  if !is_visible(&view.id) {
+    return None;
+  }
+  let mut parent_view_id = view.parent_view_id.clone();
+  for _ in 0..MAX_FOLDER_ANCESTORS {
+    if unviewable.contains(&parent_view_id) {
+      return None;
+    }
+    match folder.get_view(&parent_view_id) {
+      Some(parent) if !parent.parent_view_id.is_empty() => {
+        parent_view_id = parent.parent_view_id.clone()
+      },
+      _ => break,
+    }
+  }
+
+  Some(FolderViewMetadata {
+    view_id: view.id.clone(),
+    parent_view_id: view.parent_view_id.clone(),
+    name: view.name.clone(),
+    icon: view.icon.clone().map(to_dto_view_icon),
+    is_space: view_is_space(&view),
+    layout: to_dto_view_layout(&view.layout),
+    children: view
+      .children
+      .iter()
+      .map(|child| child.id.clone())
+      .filter(|child_id| is_visible(child_id))
+      .collect(),
+  })
+}
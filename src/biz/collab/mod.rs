@@ -1,3 +1,13 @@
+pub mod archive;
+pub mod awareness;
 pub mod folder_view;
 pub mod ops;
 pub mod publish_outline;
+pub mod redaction;
+pub mod restore;
+pub mod scrub;
+pub mod shard;
+pub mod share_token;
+pub mod snapshot_schedule;
+pub mod stats;
+pub mod trash;
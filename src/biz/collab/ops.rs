@@ -2,49 +2,75 @@ use std::sync::Arc;
 
 use app_error::AppError;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::{DateTime, Utc};
 use collab_entity::CollabType;
 use collab_entity::EncodedCollab;
 use collab_folder::SectionItem;
 use collab_folder::{CollabOrigin, Folder};
-use database::collab::{CollabStorage, GetCollabOrigin};
+use database::collab::{
+  select_collab_member_access_level_for_user, select_trashed_collabs, CollabStorage,
+  GetCollabOrigin,
+};
+use database::member_expiry::update_collab_member_expires_at;
+use database::publish::select_published_view_ids_for_sub_namespace;
 use database::publish::select_published_view_ids_for_workspace;
+use database::publish::select_published_view_update_times_for_sub_namespace;
 use database::publish::select_workspace_id_for_publish_namespace;
+use database::publish::split_publish_namespace;
+use database::residency::PgPoolRouter;
+use database::workspace::select_user_role;
 use database_entity::dto::{QueryCollab, QueryCollabParams};
 use shared_entity::dto::workspace_dto::FavoriteFolderView;
+use shared_entity::dto::workspace_dto::FolderViewMetadata;
+use shared_entity::dto::workspace_dto::GuestFolderView;
 use shared_entity::dto::workspace_dto::RecentFolderView;
 use shared_entity::dto::workspace_dto::TrashFolderView;
 use sqlx::PgPool;
 use std::ops::DerefMut;
 
 use anyhow::Context;
-use shared_entity::dto::workspace_dto::{FolderView, PublishedView};
+use shared_entity::dto::workspace_dto::{
+  FolderView, FolderViewFilter, PublishedOutlineChanges, PublishedView,
+};
 use sqlx::types::Uuid;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use tracing::{event, trace};
 use validator::Validate;
 
 use access_control::collab::CollabAccessControl;
 use database_entity::dto::{
-  AFCollabMember, CollabMemberIdentify, InsertCollabMemberParams, QueryCollabMembers,
-  UpdateCollabMemberParams,
+  AFAccessLevel, AFCollabMember, AFRole, CollabMemberIdentify, InsertCollabMemberParams,
+  QueryCollabMembers, UpdateCollabMemberParams,
 };
 
-use super::folder_view::collab_folder_to_folder_view;
+use super::folder_view::collab_folder_to_filtered_folder_view;
+use super::folder_view::collab_folder_to_folder_view_metadata;
+use super::folder_view::collab_folder_to_guest_folder_view;
+use super::folder_view::private_space_ids;
 use super::folder_view::section_items_to_favorite_folder_view;
 use super::folder_view::section_items_to_recent_folder_view;
 use super::folder_view::section_items_to_trash_folder_view;
+use super::folder_view::to_dto_folder_view_without_children;
+use super::folder_view::to_trash_folder_view;
 use super::publish_outline::collab_folder_to_published_outline;
+use super::publish_outline::published_outline_changes;
+use crate::biz::workspace::access_expiry::{notify_grant_expiry_change, AccessGrant};
+use crate::biz::workspace::permission_audit::record_collab_member_change;
+use crate::biz::workspace::view_permission::get_restricted_view_ids;
 
 /// Create a new collab member
 /// If the collab member already exists, return [AppError::RecordAlreadyExists]
 /// If the collab member does not exist, create a new one
 pub async fn create_collab_member(
   pg_pool: &PgPool,
+  actor_uid: i64,
   params: &InsertCollabMemberParams,
   collab_access_control: Arc<dyn CollabAccessControl>,
 ) -> Result<(), AppError> {
   params.validate()?;
+  check_member_expires_at(&params.expires_at)?;
+  let workspace_id = Uuid::parse_str(&params.workspace_id)?;
 
   let mut transaction = pg_pool
     .begin()
@@ -67,30 +93,61 @@ pub async fn create_collab_member(
   trace!("Inserting collab member: {:?}", params);
   database::collab::insert_collab_member(
     params.uid,
+    &workspace_id,
     &params.object_id,
     &params.access_level,
     &mut transaction,
   )
   .await?;
+  update_collab_member_expires_at(
+    transaction.deref_mut(),
+    &params.object_id,
+    &params.uid,
+    params.expires_at,
+  )
+  .await?;
 
   collab_access_control
     .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
     .await?;
+  collab_access_control
+    .update_access_level_expiry(&params.uid, &params.object_id, params.expires_at)
+    .await?;
 
   transaction
     .commit()
     .await
     .context("fail to commit the transaction to insert collab member")?;
+  notify_grant_expiry_change(
+    pg_pool,
+    params.uid,
+    AccessGrant::Collab(params.object_id.clone()),
+    params.expires_at,
+  )
+  .await;
+  record_collab_member_change(
+    pg_pool,
+    Some(actor_uid),
+    params.uid,
+    &params.object_id,
+    None,
+    Some(params.access_level),
+  )
+  .await;
   Ok(())
 }
 
 pub async fn upsert_collab_member(
   pg_pool: &PgPool,
-  _user_uuid: &Uuid,
+  actor_uid: i64,
   params: &UpdateCollabMemberParams,
   collab_access_control: Arc<dyn CollabAccessControl>,
 ) -> Result<(), AppError> {
   params.validate()?;
+  check_member_expires_at(&params.expires_at)?;
+  let workspace_id = Uuid::parse_str(&params.workspace_id)?;
+  let old_access_level =
+    select_collab_member_access_level(pg_pool, params.uid, &params.object_id).await;
   let mut transaction = pg_pool
     .begin()
     .await
@@ -99,22 +156,58 @@ pub async fn upsert_collab_member(
   collab_access_control
     .update_access_level_policy(&params.uid, &params.object_id, params.access_level)
     .await?;
+  collab_access_control
+    .update_access_level_expiry(&params.uid, &params.object_id, params.expires_at)
+    .await?;
 
   database::collab::insert_collab_member(
     params.uid,
+    &workspace_id,
     &params.object_id,
     &params.access_level,
     &mut transaction,
   )
   .await?;
+  update_collab_member_expires_at(
+    transaction.deref_mut(),
+    &params.object_id,
+    &params.uid,
+    params.expires_at,
+  )
+  .await?;
 
   transaction
     .commit()
     .await
     .context("fail to commit the transaction to upsert collab member")?;
+  notify_grant_expiry_change(
+    pg_pool,
+    params.uid,
+    AccessGrant::Collab(params.object_id.clone()),
+    params.expires_at,
+  )
+  .await;
+  record_collab_member_change(
+    pg_pool,
+    Some(actor_uid),
+    params.uid,
+    &params.object_id,
+    old_access_level,
+    Some(params.access_level),
+  )
+  .await;
   Ok(())
 }
 
+fn check_member_expires_at(expires_at: &Option<DateTime<Utc>>) -> Result<(), AppError> {
+  match expires_at {
+    Some(expires_at) if *expires_at <= Utc::now() => Err(AppError::InvalidRequest(
+      "The expiry time must be in the future".to_string(),
+    )),
+    _ => Ok(()),
+  }
+}
+
 pub async fn get_collab_member(
   pg_pool: &PgPool,
   params: &CollabMemberIdentify,
@@ -127,10 +220,13 @@ pub async fn get_collab_member(
 
 pub async fn delete_collab_member(
   pg_pool: &PgPool,
+  actor_uid: i64,
   params: &CollabMemberIdentify,
   collab_access_control: Arc<dyn CollabAccessControl>,
 ) -> Result<(), AppError> {
   params.validate()?;
+  let old_access_level =
+    select_collab_member_access_level(pg_pool, params.uid, &params.object_id).await;
   let mut transaction = pg_pool
     .begin()
     .await
@@ -151,9 +247,32 @@ pub async fn delete_collab_member(
     .commit()
     .await
     .context("fail to commit the transaction to remove collab member")?;
+  if let Some(old_access_level) = old_access_level {
+    record_collab_member_change(
+      pg_pool,
+      Some(actor_uid),
+      params.uid,
+      &params.object_id,
+      Some(old_access_level),
+      None,
+    )
+    .await;
+  }
   Ok(())
 }
 
+/// The access level of the collab member, `None` if the user isn't a member of the collab.
+async fn select_collab_member_access_level(
+  pg_pool: &PgPool,
+  uid: i64,
+  object_id: &str,
+) -> Option<AFAccessLevel> {
+  database::collab::select_collab_member(&uid, object_id, pg_pool)
+    .await
+    .ok()
+    .map(|member| member.permission.access_level)
+}
+
 pub async fn get_collab_member_list(
   pg_pool: &PgPool,
   params: &QueryCollabMembers,
@@ -169,10 +288,13 @@ pub async fn get_user_favorite_folder_views(
   uid: i64,
   workspace_id: Uuid,
 ) -> Result<Vec<FavoriteFolderView>, AppError> {
-  let folder = get_latest_collab_folder(
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let restricted_view_ids = get_restricted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let folder = get_latest_collab_folder_for_user(
     collab_storage,
-    GetCollabOrigin::User { uid },
+    uid,
     &workspace_id.to_string(),
+    guest_view_ids.is_some(),
   )
   .await?;
   let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
@@ -189,6 +311,8 @@ pub async fn get_user_favorite_folder_views(
     .get_my_favorite_sections()
     .into_iter()
     .filter(|s| !deleted_section_item_ids.contains(&s.id))
+    .filter(|s| is_view_granted(&guest_view_ids, &s.id))
+    .filter(|s| !restricted_view_ids.contains(&s.id))
     .collect();
   Ok(section_items_to_favorite_folder_view(
     &favorite_section_items,
@@ -203,10 +327,13 @@ pub async fn get_user_recent_folder_views(
   uid: i64,
   workspace_id: Uuid,
 ) -> Result<Vec<RecentFolderView>, AppError> {
-  let folder = get_latest_collab_folder(
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let restricted_view_ids = get_restricted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let folder = get_latest_collab_folder_for_user(
     collab_storage,
-    GetCollabOrigin::User { uid },
+    uid,
     &workspace_id.to_string(),
+    guest_view_ids.is_some(),
   )
   .await?;
   let deleted_section_item_ids: Vec<String> = folder
@@ -218,6 +345,8 @@ pub async fn get_user_recent_folder_views(
     .get_my_recent_sections()
     .into_iter()
     .filter(|s| !deleted_section_item_ids.contains(&s.id))
+    .filter(|s| is_view_granted(&guest_view_ids, &s.id))
+    .filter(|s| !restricted_view_ids.contains(&s.id))
     .collect();
   let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
   let publish_view_ids: HashSet<String> = publish_view_ids
@@ -231,19 +360,52 @@ pub async fn get_user_recent_folder_views(
   ))
 }
 
+/// Returns the views in the trash of the user, followed by the views whose collab was deleted
+/// through the server and is still in the server-side trash.
 pub async fn get_user_trash_folder_views(
   collab_storage: &CollabAccessControlStorage,
+  pg_pool: &PgPool,
+  pg_pool_router: &PgPoolRouter,
   uid: i64,
   workspace_id: Uuid,
 ) -> Result<Vec<TrashFolderView>, AppError> {
-  let folder = get_latest_collab_folder(
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let folder = get_latest_collab_folder_for_user(
     collab_storage,
-    GetCollabOrigin::User { uid },
+    uid,
     &workspace_id.to_string(),
+    guest_view_ids.is_some(),
   )
   .await?;
-  let section_items = folder.get_my_trash_sections();
-  Ok(section_items_to_trash_folder_view(&section_items, &folder))
+  let section_items: Vec<SectionItem> = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .filter(|s| is_view_granted(&guest_view_ids, &s.id))
+    .collect();
+  let mut trash_views = section_items_to_trash_folder_view(&section_items, &folder);
+
+  let mut listed_view_ids: HashSet<String> = trash_views
+    .iter()
+    .map(|trash_view| trash_view.view.view_id.clone())
+    .collect();
+  let mut trashed_collabs = vec![];
+  for collab_pg_pool in pg_pool_router
+    .pg_pools_for_workspace_collabs(&workspace_id)
+    .await?
+  {
+    trashed_collabs.extend(select_trashed_collabs(&collab_pg_pool, &workspace_id).await?);
+  }
+  for trashed_collab in trashed_collabs {
+    if !is_view_granted(&guest_view_ids, &trashed_collab.oid)
+      || !listed_view_ids.insert(trashed_collab.oid.clone())
+    {
+      continue;
+    }
+    if let Some(view) = folder.get_view(&trashed_collab.oid) {
+      trash_views.push(to_trash_folder_view(&view, trashed_collab.deleted_at));
+    }
+  }
+  Ok(trash_views)
 }
 
 pub async fn get_user_workspace_structure(
@@ -253,6 +415,7 @@ pub async fn get_user_workspace_structure(
   workspace_id: Uuid,
   depth: u32,
   root_view_id: &str,
+  filter: Option<&FolderViewFilter>,
 ) -> Result<FolderView, AppError> {
   let depth_limit = 10;
   if depth > depth_limit {
@@ -261,9 +424,112 @@ pub async fn get_user_workspace_structure(
       depth, depth_limit
     )));
   }
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let folder = get_latest_collab_folder_for_user(
+    collab_storage,
+    uid,
+    &workspace_id.to_string(),
+    guest_view_ids.is_some(),
+  )
+  .await?;
+  let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
+  let publish_view_ids: HashSet<String> = publish_view_ids
+    .into_iter()
+    .map(|id| id.to_string())
+    .collect();
+  let restricted_view_ids = get_restricted_view_ids(pg_pool, uid, &workspace_id).await?;
+  match guest_view_ids {
+    Some(mut guest_view_ids) => {
+      guest_view_ids.retain(|view_id| !restricted_view_ids.contains(view_id));
+      if root_view_id != workspace_id.to_string() && !guest_view_ids.contains(root_view_id) {
+        return Err(AppError::NotEnoughPermissions);
+      }
+      collab_folder_to_guest_folder_view(
+        root_view_id,
+        &folder,
+        depth,
+        &guest_view_ids,
+        &publish_view_ids,
+        filter,
+      )
+    },
+    None => {
+      if restricted_view_ids.contains(root_view_id) {
+        return Err(AppError::NotEnoughPermissions);
+      }
+      collab_folder_to_filtered_folder_view(
+        root_view_id,
+        &folder,
+        depth,
+        &publish_view_ids,
+        &restricted_view_ids,
+        filter,
+      )
+    },
+  }
+}
+
+/// Returns the metadata of a single view, decoded from the folder collab. Cheaper to transfer than
+/// the folder view for the lookups that only need one view, e.g. breadcrumbs.
+pub async fn get_user_folder_view_metadata(
+  collab_storage: &CollabAccessControlStorage,
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+) -> Result<FolderViewMetadata, AppError> {
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  let folder = get_latest_collab_folder_for_user(
+    collab_storage,
+    uid,
+    &workspace_id.to_string(),
+    guest_view_ids.is_some(),
+  )
+  .await?;
+  collab_folder_to_folder_view_metadata(view_id, &folder, guest_view_ids.as_ref()).ok_or_else(
+    || AppError::MissingView(format!("The view {} is not found in the folder", view_id)),
+  )
+}
+
+/// Returns the ids of the views that are explicitly granted to the user if the user is a guest of
+/// the workspace. Returns `None` for owners and members, who can see the whole folder.
+pub async fn get_guest_granted_view_ids(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<Option<HashSet<String>>, AppError> {
+  let role = select_user_role(pg_pool, &uid, workspace_id).await?;
+  if role != AFRole::Guest {
+    return Ok(None);
+  }
+  let folder_oid = workspace_id.to_string();
+  let granted_view_ids = select_collab_member_access_level_for_user(pg_pool, &uid, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| row.oid)
+    .filter(|oid| oid != &folder_oid)
+    .collect();
+  Ok(Some(granted_view_ids))
+}
+
+/// Lists every view of the workspace that the guest is able to see, along with the access level
+/// that was granted to the guest.
+pub async fn get_guest_granted_views(
+  collab_storage: &CollabAccessControlStorage,
+  pg_pool: &PgPool,
+  workspace_id: Uuid,
+  guest_uid: i64,
+) -> Result<Vec<GuestFolderView>, AppError> {
+  let role = select_user_role(pg_pool, &guest_uid, &workspace_id).await?;
+  if role != AFRole::Guest {
+    return Err(AppError::InvalidRequest(format!(
+      "User {} is not a guest of the workspace {}",
+      guest_uid, workspace_id
+    )));
+  }
   let folder = get_latest_collab_folder(
     collab_storage,
-    GetCollabOrigin::User { uid },
+    GetCollabOrigin::Server,
     &workspace_id.to_string(),
   )
   .await?;
@@ -272,7 +538,47 @@ pub async fn get_user_workspace_structure(
     .into_iter()
     .map(|id| id.to_string())
     .collect();
-  collab_folder_to_folder_view(root_view_id, &folder, depth, &publish_view_ids)
+  let private_space_ids = private_space_ids(&folder);
+  let granted_views =
+    select_collab_member_access_level_for_user(pg_pool, &guest_uid, &workspace_id)
+      .await?
+      .into_iter()
+      .filter_map(|row| {
+        folder.get_view(&row.oid).map(|view| GuestFolderView {
+          view: to_dto_folder_view_without_children(
+            &folder,
+            &view,
+            &private_space_ids,
+            &publish_view_ids,
+          ),
+          access_level: row.access_level,
+        })
+      })
+      .collect();
+  Ok(granted_views)
+}
+
+fn is_view_granted(guest_view_ids: &Option<HashSet<String>>, view_id: &str) -> bool {
+  match guest_view_ids {
+    Some(guest_view_ids) => guest_view_ids.contains(view_id),
+    None => true,
+  }
+}
+
+/// Guests are not allowed to read the folder collab. For them the folder is opened on behalf of the
+/// server, and the caller is responsible for limiting the result to the granted views.
+pub async fn get_latest_collab_folder_for_user(
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: &str,
+  is_guest: bool,
+) -> Result<Folder, AppError> {
+  let collab_origin = if is_guest {
+    GetCollabOrigin::Server
+  } else {
+    GetCollabOrigin::User { uid }
+  };
+  open_latest_collab_folder(collab_storage, collab_origin, uid, workspace_id).await
 }
 
 pub async fn get_latest_collab_folder(
@@ -286,6 +592,15 @@ pub async fn get_latest_collab_folder(
     // Dummy uid to open the collab folder if the request does not originate from user
     0
   };
+  open_latest_collab_folder(collab_storage, collab_origin, folder_uid, workspace_id).await
+}
+
+async fn open_latest_collab_folder(
+  collab_storage: &CollabAccessControlStorage,
+  collab_origin: GetCollabOrigin,
+  folder_uid: i64,
+  workspace_id: &str,
+) -> Result<Folder, AppError> {
   let encoded_collab = get_latest_collab_encoded(
     collab_storage,
     collab_origin,
@@ -332,14 +647,17 @@ pub async fn get_published_view(
   publish_namespace: String,
   pg_pool: &PgPool,
 ) -> Result<PublishedView, AppError> {
-  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, &publish_namespace).await?;
+  let (namespace, sub_namespace) = split_publish_namespace(&publish_namespace);
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, namespace).await?;
   let folder = get_latest_collab_folder(
     collab_storage,
     GetCollabOrigin::Server,
     &workspace_id.to_string(),
   )
   .await?;
-  let publish_view_ids = select_published_view_ids_for_workspace(pg_pool, workspace_id).await?;
+  // Each sub-namespace is a distinct site, which only outlines its own views.
+  let publish_view_ids =
+    select_published_view_ids_for_sub_namespace(pg_pool, workspace_id, sub_namespace).await?;
   let publish_view_ids: HashSet<String> = publish_view_ids
     .into_iter()
     .map(|id| id.to_string())
@@ -348,3 +666,40 @@ pub async fn get_published_view(
     collab_folder_to_published_outline(&workspace_id.to_string(), &folder, &publish_view_ids)?;
   Ok(published_view)
 }
+
+/// Returns the changes of the published outline since the time, which is usually the cursor of
+/// the previous changes. Clients polling the outline get the changed views instead of the whole
+/// tree.
+pub async fn get_published_view_changes(
+  collab_storage: &CollabAccessControlStorage,
+  publish_namespace: String,
+  pg_pool: &PgPool,
+  since: DateTime<Utc>,
+) -> Result<PublishedOutlineChanges, AppError> {
+  // Taken before reading, so that the changes made meanwhile are returned by the next call.
+  let cursor = Utc::now();
+  let (namespace, sub_namespace) = split_publish_namespace(&publish_namespace);
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, namespace).await?;
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  let published_at: HashMap<String, DateTime<Utc>> =
+    select_published_view_update_times_for_sub_namespace(pg_pool, workspace_id, sub_namespace)
+      .await?
+      .into_iter()
+      .map(|(view_id, updated_at)| (view_id.to_string(), updated_at))
+      .collect();
+  let publish_view_ids: HashSet<String> = published_at.keys().cloned().collect();
+  let outline =
+    collab_folder_to_published_outline(&workspace_id.to_string(), &folder, &publish_view_ids)?;
+  let (changed_views, view_ids) =
+    published_outline_changes(&outline, &folder, &published_at, since);
+  Ok(PublishedOutlineChanges {
+    changed_views,
+    view_ids,
+    cursor: cursor.timestamp_millis(),
+  })
+}
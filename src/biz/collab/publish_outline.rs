@@ -1,8 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use app_error::AppError;
+use chrono::{DateTime, Utc};
 use collab_folder::Folder;
-use shared_entity::dto::workspace_dto::PublishedView;
+use shared_entity::dto::workspace_dto::{PublishedOutlineChange, PublishedView};
 
 use super::folder_view::{to_dto_view_icon, to_dto_view_layout};
 
@@ -34,6 +35,50 @@ pub fn collab_folder_to_published_outline(
   )))
 }
 
+/// Returns the views of the outline that were published, republished or edited in the folder
+/// after `since`, without their children, along with the ids of all the views of the outline.
+pub fn published_outline_changes(
+  outline: &PublishedView,
+  folder: &Folder,
+  published_at: &HashMap<String, DateTime<Utc>>,
+  since: DateTime<Utc>,
+) -> (Vec<PublishedOutlineChange>, Vec<String>) {
+  let mut changed_views = vec![];
+  let mut view_ids = vec![];
+  let mut stack: Vec<(&str, &PublishedView)> = outline
+    .children
+    .iter()
+    .rev()
+    .map(|child| (outline.view_id.as_str(), child))
+    .collect();
+  while let Some((parent_view_id, view)) = stack.pop() {
+    view_ids.push(view.view_id.clone());
+    let is_republished = published_at
+      .get(&view.view_id)
+      .is_some_and(|published_at| *published_at > since);
+    let is_edited = folder.get_view(&view.view_id).is_some_and(|folder_view| {
+      DateTime::from_timestamp(folder_view.last_edited_time, 0).unwrap_or_default() > since
+    });
+    if is_republished || is_edited {
+      changed_views.push(PublishedOutlineChange {
+        parent_view_id: parent_view_id.to_string(),
+        view: PublishedView {
+          children: vec![],
+          ..view.clone()
+        },
+      });
+    }
+    stack.extend(
+      view
+        .children
+        .iter()
+        .rev()
+        .map(|child| (view.view_id.as_str(), child)),
+    );
+  }
+  (changed_views, view_ids)
+}
+
 fn to_publish_view(
   parent_view_id: &str,
   view_id: &str,
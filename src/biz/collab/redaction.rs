@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab::core::collab::Collab;
+use collab_document::document::DocumentBody;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{CollabStorage, GetCollabOrigin};
+use database::pg_row::AFCollabRedactionRow;
+use database::redaction::{
+  delete_collab_embeddings, delete_collab_snapshot_history, insert_collab_redaction,
+  select_collab_snapshots, update_collab_snapshot_blob,
+};
+use database::residency::PgPoolRouter;
+use database::workspace::select_workspace;
+use database_entity::dto::{CollabParams, QueryCollab, QueryCollabParams};
+use shared_entity::dto::workspace_dto::{CollabRedaction, RedactCollabParams};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::updates::encoder::Encode;
+use yrs::{
+  Any, Array, ArrayRef, GetString, Map, MapRef, Out, ReadTxn, Text, TextRef, Transact,
+  TransactionMut,
+};
+
+use crate::biz::workspace::ops::{broadcast_update, collab_from_doc_state};
+
+/// Replaces every occurrence of the redacted text spans.
+pub const REDACTED_TEXT_PLACEHOLDER: &str = "[REDACTED]";
+
+/// Redacts text spans and blocks from the full history of a collab in one operation:
+/// - the current state, broadcasted to the connected clients so that their copies are redacted
+///   too,
+/// - the snapshots, rewritten in place,
+/// - the update history kept by the history service, deleted,
+/// - the search index and the embeddings, deleted so that the collab is indexed again.
+///
+/// The redacted state is written on behalf of the owner of the workspace, as the administrator
+/// redacting the content is usually not a member of the workspace.
+#[allow(clippy::too_many_arguments)]
+pub async fn redact_collab(
+  pg_pool: &PgPool,
+  pg_pool_router: &PgPoolRouter,
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: &str,
+  params: RedactCollabParams,
+) -> Result<CollabRedaction, AppError> {
+  let RedactCollabParams {
+    collab_type,
+    texts,
+    block_ids,
+    reason,
+  } = params;
+  let texts = texts
+    .into_iter()
+    .filter(|text| !text.is_empty())
+    .collect::<Vec<_>>();
+  if texts.is_empty() && block_ids.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "Either the texts or the blocks to redact must be provided".to_string(),
+    ));
+  }
+  if !block_ids.is_empty() && collab_type != CollabType::Document {
+    return Err(AppError::InvalidRequest(
+      "Blocks can only be redacted from a document".to_string(),
+    ));
+  }
+  if reason.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The reason of the redaction must be provided".to_string(),
+    ));
+  }
+
+  let redaction = Arc::new(Redaction { texts, block_ids });
+  let owner_uid = select_workspace(pg_pool, &workspace_id).await?.owner_uid;
+  let owner_uid = owner_uid
+    .ok_or_else(|| AppError::Internal(anyhow!("Workspace {} has no owner", workspace_id)))?;
+
+  // Current state
+  let encoded_collab = collab_storage
+    .get_encode_collab(
+      GetCollabOrigin::Server,
+      QueryCollabParams {
+        workspace_id: workspace_id.to_string(),
+        inner: QueryCollab {
+          object_id: object_id.to_string(),
+          collab_type: collab_type.clone(),
+        },
+      },
+      true,
+    )
+    .await?;
+  let redacted =
+    spawn_blocking_redact(object_id, &collab_type, encoded_collab, redaction.clone()).await?;
+  if redacted.count.is_redacted() {
+    let params = CollabParams {
+      object_id: object_id.to_string(),
+      collab_type: collab_type.clone(),
+      encoded_collab_v1: redacted.encoded_collab_v1.into(),
+      embeddings: None,
+    };
+    collab_storage
+      .queue_insert_or_update_collab(&workspace_id.to_string(), &owner_uid, params, true)
+      .await?;
+    broadcast_update(collab_storage, object_id, redacted.update).await?;
+  }
+
+  // Snapshots
+  collab_storage.remove_pending_snapshot(object_id).await?;
+  let snapshot_pg_pool = pg_pool_router
+    .pg_pool_for_collab(&workspace_id, object_id)
+    .await?;
+  let mut snapshot_count = 0;
+  for snapshot in select_collab_snapshots(&snapshot_pg_pool, object_id).await? {
+    let encoded_collab = EncodedCollab::decode_from_bytes(&snapshot.blob)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decode snapshot: {}", err)))?;
+    let redacted_snapshot =
+      spawn_blocking_redact(object_id, &collab_type, encoded_collab, redaction.clone()).await?;
+    if redacted_snapshot.count.is_redacted() {
+      update_collab_snapshot_blob(
+        &snapshot_pg_pool,
+        snapshot.sid,
+        &redacted_snapshot.encoded_collab_v1,
+      )
+      .await?;
+      snapshot_count += 1;
+    }
+  }
+
+  // Update history, search index and embeddings
+  let history_count = delete_collab_snapshot_history(pg_pool, object_id).await?;
+  let embedding_count = delete_collab_embeddings(pg_pool, object_id).await?;
+
+  let row = insert_collab_redaction(
+    pg_pool,
+    &workspace_id,
+    object_id,
+    uid,
+    &reason,
+    redacted.count.spans as i32,
+    redacted.count.blocks as i32,
+    snapshot_count,
+    history_count as i32,
+    embedding_count as i32,
+  )
+  .await?;
+  info!(
+    "Redacted collab {} of workspace {}: redaction id {}",
+    object_id, workspace_id, row.redaction_id
+  );
+  Ok(collab_redaction_from_row(row))
+}
+
+struct Redaction {
+  texts: Vec<String>,
+  block_ids: Vec<String>,
+}
+
+#[derive(Default)]
+struct RedactionCount {
+  spans: usize,
+  blocks: usize,
+}
+
+impl RedactionCount {
+  fn is_redacted(&self) -> bool {
+    self.spans > 0 || self.blocks > 0
+  }
+}
+
+struct RedactedCollab {
+  encoded_collab_v1: Vec<u8>,
+  /// The update that redacts the collab, to be applied by the other replicas.
+  update: Vec<u8>,
+  count: RedactionCount,
+}
+
+async fn spawn_blocking_redact(
+  object_id: &str,
+  collab_type: &CollabType,
+  encoded_collab: EncodedCollab,
+  redaction: Arc<Redaction>,
+) -> Result<RedactedCollab, AppError> {
+  let object_id = object_id.to_string();
+  let collab_type = collab_type.clone();
+  tokio::task::spawn_blocking(move || {
+    // The garbage collection is enabled, the content of the deleted items is dropped from the
+    // encoded state.
+    let collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id)?;
+    redact(collab, &collab_type, &redaction)
+  })
+  .await?
+}
+
+fn redact(
+  mut collab: Collab,
+  collab_type: &CollabType,
+  redaction: &Redaction,
+) -> Result<RedactedCollab, AppError> {
+  let state_vector = collab.transact().state_vector();
+  let mut count = RedactionCount::default();
+
+  if !redaction.block_ids.is_empty() {
+    let body = DocumentBody::from_collab(&collab)
+      .ok_or_else(|| AppError::InvalidRequest("The collab is not a document".to_string()))?;
+    let mut txn = collab.context.transact_mut();
+    for block_id in &redaction.block_ids {
+      match body.delete_block(&mut txn, block_id) {
+        Ok(_) => count.blocks += 1,
+        Err(err) => warn!("Failed to redact block {}: {}", block_id, err),
+      }
+    }
+  }
+
+  if !redaction.texts.is_empty() {
+    let mut txn = collab.context.transact_mut();
+    count.spans += redact_map(&mut txn, &collab.data, &redaction.texts);
+  }
+
+  let update = collab.transact().encode_state_as_update_v1(&state_vector);
+  let encoded_collab_v1 = collab
+    .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
+    .map_err(|err| AppError::Internal(anyhow!("Failed to encode collab: {}", err)))?
+    .encode_to_bytes()?;
+  Ok(RedactedCollab {
+    encoded_collab_v1,
+    update,
+    count,
+  })
+}
+
+fn redact_out(txn: &mut TransactionMut, value: Out, texts: &[String]) -> usize {
+  match value {
+    Out::YText(text) => redact_text(txn, &text, texts),
+    Out::YMap(map) => redact_map(txn, &map, texts),
+    Out::YArray(array) => redact_array(txn, &array, texts),
+    _ => 0,
+  }
+}
+
+fn redact_map(txn: &mut TransactionMut, map: &MapRef, texts: &[String]) -> usize {
+  let entries = map
+    .iter(&*txn)
+    .map(|(key, value)| (key.to_string(), value))
+    .collect::<Vec<_>>();
+  let mut count = 0;
+  for (key, value) in entries {
+    match value {
+      Out::Any(any) => {
+        let (redacted, n) = redact_any(&any, texts);
+        if n > 0 {
+          map.insert(txn, key, redacted);
+          count += n;
+        }
+      },
+      value => count += redact_out(txn, value, texts),
+    }
+  }
+  count
+}
+
+fn redact_array(txn: &mut TransactionMut, array: &ArrayRef, texts: &[String]) -> usize {
+  let items = array.iter(&*txn).collect::<Vec<_>>();
+  let mut count = 0;
+  for (index, item) in items.into_iter().enumerate() {
+    match item {
+      Out::Any(any) => {
+        let (redacted, n) = redact_any(&any, texts);
+        if n > 0 {
+          array.remove(txn, index as u32);
+          array.insert(txn, index as u32, redacted);
+          count += n;
+        }
+      },
+      item => count += redact_out(txn, item, texts),
+    }
+  }
+  count
+}
+
+/// Rewrites the text chunk by chunk, keeping the formatting of the chunks. A span that crosses
+/// chunks with different formatting is redacted by rewriting the whole text without formatting.
+fn redact_text(txn: &mut TransactionMut, text: &TextRef, texts: &[String]) -> usize {
+  if !contains_any(&text.get_string(&*txn), texts) {
+    return 0;
+  }
+
+  let chunks = text.diff(&*txn, YChange::identity);
+  let len = text.len(&*txn);
+  text.remove_range(txn, 0, len);
+  let mut count = 0;
+  for chunk in chunks {
+    let index = text.len(&*txn);
+    match chunk.insert {
+      Out::Any(Any::String(s)) => {
+        let (redacted, n) = redact_str(&s, texts);
+        count += n;
+        match chunk.attributes {
+          Some(attributes) => text.insert_with_attributes(txn, index, &redacted, *attributes),
+          None => text.insert(txn, index, &redacted),
+        }
+      },
+      Out::Any(embed) => match chunk.attributes {
+        Some(attributes) => {
+          text.insert_embed_with_attributes(txn, index, embed, *attributes);
+        },
+        None => {
+          text.insert_embed(txn, index, embed);
+        },
+      },
+      // The collabs don't embed shared types in texts.
+      _ => {},
+    }
+  }
+
+  let content = text.get_string(&*txn);
+  if contains_any(&content, texts) {
+    let (redacted, n) = redact_str(&content, texts);
+    let len = text.len(&*txn);
+    text.remove_range(txn, 0, len);
+    text.insert(txn, 0, &redacted);
+    count += n;
+  }
+  count
+}
+
+fn redact_any(any: &Any, texts: &[String]) -> (Any, usize) {
+  match any {
+    Any::String(s) => {
+      let (redacted, n) = redact_str(s, texts);
+      (Any::String(Arc::from(redacted)), n)
+    },
+    Any::Array(items) => {
+      let mut count = 0;
+      let items = items
+        .iter()
+        .map(|item| {
+          let (redacted, n) = redact_any(item, texts);
+          count += n;
+          redacted
+        })
+        .collect::<Vec<_>>();
+      (Any::Array(Arc::from(items)), count)
+    },
+    Any::Map(map) => {
+      let mut count = 0;
+      let map = map
+        .iter()
+        .map(|(key, value)| {
+          let (redacted, n) = redact_any(value, texts);
+          count += n;
+          (key.clone(), redacted)
+        })
+        .collect::<HashMap<_, _>>();
+      (Any::Map(Arc::new(map)), count)
+    },
+    any => (any.clone(), 0),
+  }
+}
+
+fn redact_str(s: &str, texts: &[String]) -> (String, usize) {
+  let mut redacted = s.to_string();
+  let mut count = 0;
+  for text in texts {
+    count += redacted.matches(text.as_str()).count();
+    redacted = redacted.replace(text.as_str(), REDACTED_TEXT_PLACEHOLDER);
+  }
+  (redacted, count)
+}
+
+fn contains_any(s: &str, texts: &[String]) -> bool {
+  texts.iter().any(|text| s.contains(text.as_str()))
+}
+
+fn collab_redaction_from_row(row: AFCollabRedactionRow) -> CollabRedaction {
+  CollabRedaction {
+    redaction_id: row.redaction_id,
+    workspace_id: row.workspace_id,
+    object_id: row.oid,
+    redacted_by: row.redacted_by,
+    reason: row.reason,
+    span_count: row.span_count,
+    block_count: row.block_count,
+    snapshot_count: row.snapshot_count,
+    history_count: row.history_count,
+    embedding_count: row.embedding_count,
+    created_at: row.created_at,
+  }
+}
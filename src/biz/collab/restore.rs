@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{select_snapshot, verify_snapshot_checksum, CollabStorage, GetCollabOrigin};
+use database::residency::PgPoolRouter;
+use database_entity::dto::{
+  AFSnapshotMeta, CollabParams, InsertSnapshotParams, QueryCollab, QueryCollabParams,
+};
+use tracing::{info, warn};
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::types::Attrs;
+use yrs::{
+  Any, Array, ArrayPrelim, ArrayRef, GetString, Map, MapPrelim, MapRef, Out, ReadTxn, Text,
+  TextPrelim, TextRef, Transact, TransactionMut,
+};
+
+use crate::biz::workspace::ops::{broadcast_update, collab_from_doc_state};
+
+/// Rolls the collab back to the content of the snapshot. The rollback is written as a regular
+/// update on top of the current state, and broadcasted to the connected clients, so that their
+/// copies are rolled back too instead of merging the rolled back changes again.
+///
+/// The current state is saved as a snapshot first, so that the rollback can be undone. Returns
+/// that snapshot. Restoring overwrites the collab, so the user must be allowed to write it.
+pub async fn restore_collab_snapshot(
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  pg_pool_router: &PgPoolRouter,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: &str,
+  snapshot_id: i64,
+  collab_type: CollabType,
+) -> Result<AFSnapshotMeta, AppError> {
+  collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Write)
+    .await?;
+  let snapshot_pg_pool = pg_pool_router
+    .pg_pool_for_collab(&workspace_id, object_id)
+    .await?;
+  let snapshot = select_snapshot(&snapshot_pg_pool, &snapshot_id)
+    .await?
+    .filter(|snapshot| snapshot.oid == object_id && snapshot.workspace_id == workspace_id)
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Can't find the snapshot with id:{} of collab:{}",
+        snapshot_id, object_id
+      ))
+    })?;
+  verify_snapshot_checksum(&snapshot_pg_pool, &snapshot).await?;
+  let snapshot_collab = EncodedCollab::decode_from_bytes(&snapshot.blob)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to decode snapshot: {}", err)))?;
+
+  let current_collab = collab_storage
+    .get_encode_collab(
+      GetCollabOrigin::User { uid },
+      QueryCollabParams {
+        workspace_id: workspace_id.to_string(),
+        inner: QueryCollab {
+          object_id: object_id.to_string(),
+          collab_type: collab_type.clone(),
+        },
+      },
+      true,
+    )
+    .await?;
+  let backup = collab_storage
+    .create_snapshot(InsertSnapshotParams {
+      object_id: object_id.to_string(),
+      encoded_collab_v1: current_collab.encode_to_bytes()?,
+      workspace_id: workspace_id.to_string(),
+      collab_type: collab_type.clone(),
+    })
+    .await?;
+
+  let restored = {
+    let object_id = object_id.to_string();
+    let collab_type = collab_type.clone();
+    tokio::task::spawn_blocking(move || {
+      restore(&object_id, &collab_type, current_collab, snapshot_collab)
+    })
+    .await??
+  };
+  let params = CollabParams {
+    object_id: object_id.to_string(),
+    collab_type,
+    encoded_collab_v1: restored.encoded_collab_v1.into(),
+    embeddings: None,
+  };
+  collab_storage
+    .queue_insert_or_update_collab(&workspace_id.to_string(), &uid, params, true)
+    .await?;
+  broadcast_update(collab_storage, object_id, restored.update).await?;
+  info!(
+    "Restored collab {} of workspace {} from snapshot {}",
+    object_id, workspace_id, snapshot_id
+  );
+  Ok(backup)
+}
+
+struct RestoredCollab {
+  encoded_collab_v1: Vec<u8>,
+  /// The update that rolls the collab back, to be applied by the other replicas.
+  update: Vec<u8>,
+}
+
+fn restore(
+  object_id: &str,
+  collab_type: &CollabType,
+  current_collab: EncodedCollab,
+  snapshot_collab: EncodedCollab,
+) -> Result<RestoredCollab, AppError> {
+  let mut collab = collab_from_doc_state(current_collab.doc_state.to_vec(), object_id)?;
+  let snapshot = collab_from_doc_state(snapshot_collab.doc_state.to_vec(), object_id)?;
+  let state_vector = collab.transact().state_vector();
+  {
+    let src_txn = snapshot.transact();
+    let mut txn = collab.context.transact_mut();
+    restore_map(&src_txn, &snapshot.data, &mut txn, &collab.data);
+  }
+
+  let update = collab.transact().encode_state_as_update_v1(&state_vector);
+  let encoded_collab_v1 = collab
+    .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
+    .map_err(|err| AppError::Internal(anyhow!("Failed to encode collab: {}", err)))?
+    .encode_to_bytes()?;
+  Ok(RestoredCollab {
+    encoded_collab_v1,
+    update,
+  })
+}
+
+/// Makes the content of `dest` equal to the content of `src`. The values that are the same in
+/// both are left untouched, so that the update only carries the rolled back changes.
+fn restore_map<T: ReadTxn>(src_txn: &T, src: &MapRef, txn: &mut TransactionMut, dest: &MapRef) {
+  let removed_keys = dest
+    .keys(&*txn)
+    .filter(|key| src.get(src_txn, key).is_none())
+    .map(|key| key.to_string())
+    .collect::<Vec<_>>();
+  for key in removed_keys {
+    dest.remove(txn, &key);
+  }
+
+  for (key, value) in src.iter(src_txn) {
+    match (value, dest.get(&*txn, key)) {
+      (Out::Any(any), Some(Out::Any(current))) if any == current => {},
+      (Out::Any(any), _) => {
+        dest.insert(txn, key, any);
+      },
+      (Out::YMap(map), Some(Out::YMap(current))) => restore_map(src_txn, &map, txn, &current),
+      (Out::YMap(map), _) => {
+        let new_map = dest.insert(txn, key, MapPrelim::default());
+        restore_map(src_txn, &map, txn, &new_map);
+      },
+      (Out::YArray(array), Some(Out::YArray(current))) => {
+        restore_array(src_txn, &array, txn, &current)
+      },
+      (Out::YArray(array), _) => {
+        let new_array = dest.insert(txn, key, ArrayPrelim::default());
+        restore_array(src_txn, &array, txn, &new_array);
+      },
+      (Out::YText(text), Some(Out::YText(current))) => restore_text(src_txn, &text, txn, &current),
+      (Out::YText(text), _) => {
+        let new_text = dest.insert(txn, key, TextPrelim::new(""));
+        restore_text(src_txn, &text, txn, &new_text);
+      },
+      (value, _) => warn!("Skip restoring unsupported value: {:?}", value),
+    }
+  }
+}
+
+/// The arrays are rewritten as a whole when they differ, their items are not diffed.
+fn restore_array<T: ReadTxn>(
+  src_txn: &T,
+  src: &ArrayRef,
+  txn: &mut TransactionMut,
+  dest: &ArrayRef,
+) {
+  let src_items = src.iter(src_txn).collect::<Vec<_>>();
+  let dest_items = dest.iter(&*txn).collect::<Vec<_>>();
+  let is_same = src_items.len() == dest_items.len()
+    && src_items
+      .iter()
+      .zip(dest_items.iter())
+      .all(|(a, b)| match (a, b) {
+        (Out::Any(a), Out::Any(b)) => a == b,
+        _ => false,
+      });
+  if is_same {
+    return;
+  }
+
+  let len = dest.len(&*txn);
+  dest.remove_range(txn, 0, len);
+  for item in src_items {
+    match item {
+      Out::Any(any) => {
+        dest.push_back(txn, any);
+      },
+      Out::YMap(map) => {
+        let new_map = dest.push_back(txn, MapPrelim::default());
+        restore_map(src_txn, &map, txn, &new_map);
+      },
+      Out::YArray(array) => {
+        let new_array = dest.push_back(txn, ArrayPrelim::default());
+        restore_array(src_txn, &array, txn, &new_array);
+      },
+      Out::YText(text) => {
+        let new_text = dest.push_back(txn, TextPrelim::new(""));
+        restore_text(src_txn, &text, txn, &new_text);
+      },
+      item => warn!("Skip restoring unsupported value: {:?}", item),
+    }
+  }
+}
+
+/// The texts are rewritten as a whole when their content or formatting differ.
+fn restore_text<T: ReadTxn>(src_txn: &T, src: &TextRef, txn: &mut TransactionMut, dest: &TextRef) {
+  let src_chunks = text_chunks(src_txn, src);
+  if src.get_string(src_txn) == dest.get_string(&*txn) && src_chunks == text_chunks(&*txn, dest) {
+    return;
+  }
+
+  let len = dest.len(&*txn);
+  dest.remove_range(txn, 0, len);
+  for (insert, attributes) in src_chunks {
+    let index = dest.len(&*txn);
+    match (insert, attributes) {
+      (Any::String(s), Some(attributes)) => dest.insert_with_attributes(txn, index, &s, attributes),
+      (Any::String(s), None) => dest.insert(txn, index, &s),
+      (embed, Some(attributes)) => {
+        dest.insert_embed_with_attributes(txn, index, embed, attributes);
+      },
+      (embed, None) => {
+        dest.insert_embed(txn, index, embed);
+      },
+    }
+  }
+}
+
+fn text_chunks<T: ReadTxn>(txn: &T, text: &TextRef) -> Vec<(Any, Option<Attrs>)> {
+  text
+    .diff(txn, YChange::identity)
+    .into_iter()
+    .filter_map(|chunk| match chunk.insert {
+      Out::Any(any) => Some((any, chunk.attributes.map(|attributes| *attributes))),
+      // The collabs don't embed shared types in texts.
+      _ => None,
+    })
+    .collect()
+}
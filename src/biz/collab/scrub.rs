@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use database::collab::{
+  resolve_repaired_collab_corruptions, scrub_collab_snapshots, scrub_collabs,
+};
+use database::pg_row::AFCollabCorruptionRow;
+use database::residency::PgPoolRouter;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::biz::scheduler::Scheduler;
+use crate::config::config::CollabSetting;
+
+/// How often the next batch of collabs and snapshots is verified. The batches are kept small and
+/// spaced out, so that the scrubber doesn't compete with the requests for the database.
+const COLLAB_SCRUB_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Periodically verifies the checksums of the stored collabs and snapshots, in every database, to
+/// detect the blobs that were silently corrupted. The corruptions are logged for the operators
+/// along with the last good snapshot of the collab, and listed by the admin API.
+pub fn register_collab_scrub_job(
+  scheduler: &Scheduler,
+  router: PgPoolRouter,
+  setting: &CollabSetting,
+) {
+  if setting.scrub_batch_size <= 0 {
+    info!("Collab scrubbing is disabled");
+    return;
+  }
+  let batch_size = setting.scrub_batch_size;
+  scheduler.register("collab_scrub", COLLAB_SCRUB_INTERVAL, move || {
+    let router = router.clone();
+    async move {
+      for pg_pool in router.all_pools() {
+        if let Err(err) = scrub_collab_storage(pg_pool, batch_size).await {
+          error!("Failed to scrub the collabs: {:?}", err);
+        }
+      }
+      Ok(())
+    }
+  });
+}
+
+async fn scrub_collab_storage(pg_pool: &PgPool, batch_size: i64) -> Result<(), AppError> {
+  let resolved = resolve_repaired_collab_corruptions(pg_pool).await?;
+  if resolved > 0 {
+    info!("Resolved {} collab corruptions", resolved);
+  }
+
+  let mut corruptions = scrub_collabs(pg_pool, batch_size).await?;
+  corruptions.extend(scrub_collab_snapshots(pg_pool, batch_size).await?);
+  if !corruptions.is_empty() {
+    error!(
+      "Found {} corrupted collab blobs: {}",
+      corruptions.len(),
+      corruptions
+        .iter()
+        .map(describe_corruption)
+        .collect::<Vec<_>>()
+        .join(", ")
+    );
+  }
+  Ok(())
+}
+
+fn describe_corruption(corruption: &AFCollabCorruptionRow) -> String {
+  let object = if corruption.snapshot_id == 0 {
+    format!("collab {}", corruption.oid)
+  } else {
+    format!(
+      "snapshot {} of collab {}",
+      corruption.snapshot_id, corruption.oid
+    )
+  };
+  match corruption.last_good_snapshot_id {
+    Some(snapshot_id) => format!(
+      "{} in workspace {} (last good snapshot: {})",
+      object, corruption.workspace_id, snapshot_id
+    ),
+    None => format!(
+      "{} in workspace {} (no good snapshot)",
+      object, corruption.workspace_id
+    ),
+  }
+}
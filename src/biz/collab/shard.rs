@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use database::collab::shard::{
+  insert_collab_placement, insert_default_collab_placements, migrate_collab_to_shard,
+  select_collab_placement, select_collab_placement_counts, select_collab_placements,
+  DEFAULT_COLLAB_SHARD,
+};
+use database::public_access::select_collab_workspace_id;
+use database::residency::PgPoolRouter;
+use shared_entity::dto::server_info_dto::{
+  CollabMigration, CollabShard, CollabShardRebalance, MigrateCollabParams,
+  RebalanceCollabShardsParams,
+};
+use tracing::{info, warn};
+
+const DEFAULT_REBALANCE_LIMIT: u32 = 100;
+const MAX_REBALANCE_LIMIT: u32 = 1000;
+
+pub async fn list_collab_shards(router: &PgPoolRouter) -> Result<Vec<CollabShard>, AppError> {
+  let counts: HashMap<String, i64> = select_collab_placement_counts(router.default_pool())
+    .await?
+    .into_iter()
+    .collect();
+  Ok(
+    router
+      .collab_shard_names()
+      .into_iter()
+      .map(|name| CollabShard {
+        collab_count: counts.get(&name).copied().unwrap_or(0),
+        name,
+      })
+      .collect(),
+  )
+}
+
+/// Moves the collabs of the batch whose placement differs from the consistent hashing, e.g. after
+/// a shard was added. Each batch also places up to `limit` collabs of the default database that
+/// were never placed, so that they are rebalanced too, by the pass they are placed in or the next
+/// one. The shards are balanced once a pass places no collab.
+pub async fn rebalance_collab_shards(
+  router: &PgPoolRouter,
+  params: RebalanceCollabShardsParams,
+) -> Result<CollabShardRebalance, AppError> {
+  let mut rebalance = CollabShardRebalance {
+    placed_count: 0,
+    moved_count: 0,
+    failed_object_ids: vec![],
+    next_cursor: None,
+  };
+  if !router.has_collab_shards() {
+    return Ok(rebalance);
+  }
+
+  let limit = params
+    .limit
+    .unwrap_or(DEFAULT_REBALANCE_LIMIT)
+    .clamp(1, MAX_REBALANCE_LIMIT) as i64;
+  rebalance.placed_count = insert_default_collab_placements(router.default_pool(), limit).await?;
+  if rebalance.placed_count > 0 {
+    info!(
+      "placed {} collabs in the default collab shard",
+      rebalance.placed_count
+    );
+  }
+
+  let placements =
+    select_collab_placements(router.default_pool(), params.after.as_deref(), limit).await?;
+  if placements.len() as i64 == limit {
+    rebalance.next_cursor = placements.last().map(|placement| placement.oid.clone());
+  }
+  for placement in placements {
+    let target_shard = router.collab_shard_for(&placement.oid);
+    if placement.shard == target_shard {
+      continue;
+    }
+    match migrate_collab_to_shard(router, &placement.oid, target_shard).await {
+      Ok(true) => rebalance.moved_count += 1,
+      Ok(false) => {},
+      Err(err) => {
+        warn!(
+          "failed to move collab {} to shard {}: {}",
+          placement.oid, target_shard, err
+        );
+        rebalance.failed_object_ids.push(placement.oid);
+      },
+    }
+  }
+  Ok(rebalance)
+}
+
+/// Moves the collab to the shard, regardless of the consistent hashing. The collab is moved back
+/// by the next rebalancing unless the shard is the one it belongs to.
+pub async fn migrate_collab(
+  router: &PgPoolRouter,
+  params: MigrateCollabParams,
+) -> Result<CollabMigration, AppError> {
+  if router.collab_shard_pool(&params.shard).is_none() {
+    return Err(AppError::InvalidRequest(format!(
+      "The collab shard {} is not configured",
+      params.shard
+    )));
+  }
+  if select_collab_placement(router.default_pool(), &params.object_id)
+    .await?
+    .is_none()
+  {
+    // Only the collabs of the default database may not be placed yet
+    let workspace_id = select_collab_workspace_id(router.default_pool(), &params.object_id)
+      .await?
+      .ok_or_else(|| {
+        AppError::RecordNotFound(format!(
+          "The collab {} is not stored in a collab shard",
+          params.object_id
+        ))
+      })?;
+    if router.regional_pool(&workspace_id).await?.is_some() {
+      return Err(AppError::InvalidRequest(format!(
+        "The collab {} is stored in the region of its workspace",
+        params.object_id
+      )));
+    }
+    insert_collab_placement(
+      router.default_pool(),
+      &params.object_id,
+      &workspace_id,
+      DEFAULT_COLLAB_SHARD,
+    )
+    .await?;
+  }
+
+  let moved = migrate_collab_to_shard(router, &params.object_id, &params.shard).await?;
+  Ok(CollabMigration {
+    object_id: params.object_id,
+    shard: params.shard,
+    moved,
+  })
+}
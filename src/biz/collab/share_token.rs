@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::HttpRequest;
+use app_error::AppError;
+use chrono::{Duration, Utc};
+use database::collab_share_token::{
+  delete_collab_share_token, insert_collab_share_token, select_active_collab_share_token,
+};
+use database::public_access::select_collab_workspace_id;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use secrecy::{ExposeSecret, Secret};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared_entity::dto::workspace_dto::{CollabShareToken, CreateCollabShareTokenParams};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// The scheme of the `Authorization` header carrying a share token.
+pub const SHARE_TOKEN_SCHEME: &str = "ShareToken";
+
+const DEFAULT_SHARE_TOKEN_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+const MAX_SHARE_TOKEN_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+const MIN_SHARE_TOKEN_TTL_SECS: i64 = 60;
+
+/// What a share token grants: reading the collab, on behalf of the user who issued it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollabShareClaims {
+  /// The id of the token, see [database::collab_share_token].
+  pub jti: Uuid,
+  pub workspace_id: Uuid,
+  pub object_id: String,
+  pub issued_by: i64,
+  pub iat: i64,
+  pub exp: i64,
+}
+
+/// Issues a token granting read access to the collab. The tokens are signed with a key derived from
+/// the jwt secret, and only their ids are stored so that they can be listed and revoked.
+pub async fn create_collab_share_token(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  jwt_secret: &Secret<String>,
+  uid: i64,
+  workspace_id: &Uuid,
+  object_id: &str,
+  params: CreateCollabShareTokenParams,
+) -> Result<CollabShareToken, AppError> {
+  let ttl_secs = params
+    .expires_in_secs
+    .unwrap_or(DEFAULT_SHARE_TOKEN_TTL_SECS);
+  if !(MIN_SHARE_TOKEN_TTL_SECS..=MAX_SHARE_TOKEN_TTL_SECS).contains(&ttl_secs) {
+    return Err(AppError::InvalidRequest(format!(
+      "The expiry of a share token must be between {} and {} seconds",
+      MIN_SHARE_TOKEN_TTL_SECS, MAX_SHARE_TOKEN_TTL_SECS
+    )));
+  }
+  check_collab_workspace(pg_pool, workspace_id, object_id).await?;
+  collab_access_control
+    .enforce_action(&workspace_id.to_string(), &uid, object_id, Action::Read)
+    .await?;
+
+  let now = Utc::now();
+  let expires_at = now + Duration::seconds(ttl_secs);
+  let token_id = Uuid::new_v4();
+  let claims = CollabShareClaims {
+    jti: token_id,
+    workspace_id: *workspace_id,
+    object_id: object_id.to_string(),
+    issued_by: uid,
+    iat: now.timestamp(),
+    exp: expires_at.timestamp(),
+  };
+  let token = encode(
+    &Header::new(Algorithm::HS256),
+    &claims,
+    &EncodingKey::from_secret(&share_token_key(jwt_secret)),
+  )
+  .map_err(|err| AppError::Internal(err.into()))?;
+  insert_collab_share_token(pg_pool, &token_id, workspace_id, object_id, uid, expires_at).await?;
+  Ok(CollabShareToken {
+    token_id,
+    token,
+    object_id: object_id.to_string(),
+    expires_at,
+  })
+}
+
+/// Checks that the share token grants reading the collab. The token stops working once it expires
+/// or is revoked, or once the user who issued it can't read the collab anymore.
+pub async fn verify_collab_share_token(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  jwt_secret: &Secret<String>,
+  token: &str,
+  workspace_id: &str,
+  object_id: &str,
+) -> Result<CollabShareClaims, AppError> {
+  let claims = decode::<CollabShareClaims>(
+    token,
+    &DecodingKey::from_secret(&share_token_key(jwt_secret)),
+    &Validation::new(Algorithm::HS256),
+  )
+  .map_err(|err| AppError::UserUnAuthorized(format!("Invalid share token: {}", err)))?
+  .claims;
+  if claims.workspace_id.to_string() != workspace_id || claims.object_id != object_id {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  let is_issued = select_active_collab_share_token(pg_pool, &claims.jti)
+    .await?
+    .is_some_and(|issued| {
+      issued.workspace_id == claims.workspace_id && issued.object_id == claims.object_id
+    });
+  if !is_issued {
+    return Err(AppError::UserUnAuthorized(
+      "The share token has been revoked".to_string(),
+    ));
+  }
+  collab_access_control
+    .enforce_action(workspace_id, &claims.issued_by, object_id, Action::Read)
+    .await?;
+  Ok(claims)
+}
+
+pub async fn revoke_collab_share_token(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  object_id: &str,
+  token_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_collab_share_token(pg_pool, workspace_id, object_id, token_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Share token {} not found",
+      token_id
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the share token of the request, if it's authorized with one.
+pub fn share_token_from_request(req: &HttpRequest) -> Option<&str> {
+  req
+    .headers()
+    .get(AUTHORIZATION)?
+    .to_str()
+    .ok()?
+    .strip_prefix(SHARE_TOKEN_SCHEME)?
+    .strip_prefix(' ')
+    .map(str::trim)
+}
+
+/// The share tokens are signed with their own key, so that they can't be mistaken for the gotrue
+/// JWTs signed with the secret itself.
+fn share_token_key(jwt_secret: &Secret<String>) -> Vec<u8> {
+  let mut hasher = Sha256::new();
+  hasher.update(b"collab-share-token:");
+  hasher.update(jwt_secret.expose_secret().as_bytes());
+  hasher.finalize().to_vec()
+}
+
+async fn check_collab_workspace(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<(), AppError> {
+  match select_collab_workspace_id(pg_pool, object_id).await? {
+    Some(collab_workspace_id) if &collab_workspace_id == workspace_id => Ok(()),
+    _ => Err(AppError::RecordNotFound(format!(
+      "Collab {} not found in workspace {}",
+      object_id, workspace_id
+    ))),
+  }
+}
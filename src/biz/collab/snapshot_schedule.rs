@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::collab::{
+  delete_workspace_snapshots_beyond_retention, select_collabs_to_snapshot,
+  select_workspaces_with_snapshot_retention, CollabStorage, GetCollabOrigin,
+};
+use database::residency::PgPoolRouter;
+use database_entity::dto::{InsertSnapshotParams, SnapshotRetentionPolicy};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::scheduler::Scheduler;
+
+/// How often the snapshot retention policies of the workspaces are applied.
+const SNAPSHOT_SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// The maximum number of collabs snapshotted on each check, per workspace.
+const SNAPSHOT_SCHEDULE_BATCH_SIZE: i64 = 100;
+
+/// Periodically applies the snapshot retention policies of the workspaces: the collabs edited
+/// since their last snapshot are snapshotted at the interval of the policy, and the snapshots
+/// beyond its limits are deleted. The workspaces using the default policy are skipped, their
+/// snapshots are already pruned when new ones are created.
+pub fn register_snapshot_schedule_job(
+  scheduler: &Scheduler,
+  router: PgPoolRouter,
+  collab_storage: Arc<CollabAccessControlStorage>,
+) {
+  scheduler.register(
+    "snapshot_schedule",
+    SNAPSHOT_SCHEDULE_CHECK_INTERVAL,
+    move || {
+      let router = router.clone();
+      let collab_storage = collab_storage.clone();
+      async move {
+        let policies = select_workspaces_with_snapshot_retention(router.default_pool()).await?;
+        for (workspace_id, retention) in policies {
+          if let Err(err) =
+            apply_snapshot_retention(&router, &collab_storage, &workspace_id, &retention).await
+          {
+            error!(
+              "Failed to apply the snapshot retention policy of workspace {}: {:?}",
+              workspace_id, err
+            );
+          }
+        }
+        Ok(())
+      }
+    },
+  );
+}
+
+async fn apply_snapshot_retention(
+  router: &PgPoolRouter,
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  workspace_id: &Uuid,
+  retention: &SnapshotRetentionPolicy,
+) -> Result<(), AppError> {
+  let pg_pools = router.pg_pools_for_workspace_collabs(workspace_id).await?;
+  let mut deleted = 0;
+  for pg_pool in &pg_pools {
+    deleted +=
+      delete_workspace_snapshots_beyond_retention(pg_pool, workspace_id, retention).await?;
+  }
+  if deleted > 0 {
+    info!(
+      "Deleted {} snapshots of workspace {} beyond its retention policy",
+      deleted, workspace_id
+    );
+  }
+
+  if retention.schedule_interval_hours == 0 {
+    return Ok(());
+  }
+  let interval_secs = retention.schedule_interval_hours as i64 * 60 * 60;
+  let mut candidates = vec![];
+  for pg_pool in &pg_pools {
+    candidates.extend(
+      select_collabs_to_snapshot(
+        pg_pool,
+        workspace_id,
+        interval_secs,
+        SNAPSHOT_SCHEDULE_BATCH_SIZE,
+      )
+      .await?,
+    );
+  }
+  let mut created = 0;
+  for candidate in candidates {
+    let result = async {
+      let encoded_collab = get_latest_collab_encoded(
+        collab_storage,
+        GetCollabOrigin::Server,
+        &workspace_id.to_string(),
+        &candidate.oid,
+        candidate.collab_type.clone(),
+      )
+      .await?;
+      collab_storage
+        .create_snapshot(InsertSnapshotParams {
+          object_id: candidate.oid.clone(),
+          encoded_collab_v1: encoded_collab.encode_to_bytes()?,
+          workspace_id: workspace_id.to_string(),
+          collab_type: candidate.collab_type.clone(),
+        })
+        .await
+    }
+    .await;
+    match result {
+      Ok(_) => created += 1,
+      Err(err) => error!(
+        "Failed to create the scheduled snapshot of collab {}: {:?}",
+        candidate.oid, err
+      ),
+    }
+  }
+  if created > 0 {
+    info!(
+      "Created {} scheduled snapshots of workspace {}",
+      created, workspace_id
+    );
+  }
+  Ok(())
+}
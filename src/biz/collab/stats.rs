@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_document::blocks::{Block, DocumentData, TextDelta};
+use collab_document::document::DocumentBody;
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use database::residency::PgPoolRouter;
+use database::resource_usage::select_blob_metadata_by_file_ids;
+use redis::AsyncCommands;
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::DocumentStats;
+use tracing::{trace, warn};
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::ops::collab_from_doc_state;
+use crate::state::RedisConnectionManager;
+
+/// The stats are cached for each state of the document, so the entries of the previous states
+/// only need to outlive the clients polling them.
+const DOCUMENT_STATS_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+const WORDS_PER_MINUTE: u64 = 238;
+
+const ATTACHMENT_BLOCK_TYPES: [&str; 2] = ["image", "file"];
+
+/// Returns the content statistics of the latest state of the document. The stats are cached by
+/// the hash of the document state, so that the document is only decoded when it has changed.
+pub async fn get_document_stats(
+  collab_storage: &CollabAccessControlStorage,
+  pg_pool_router: &PgPoolRouter,
+  redis_connection_manager: &RedisConnectionManager,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: &str,
+) -> Result<DocumentStats, AppError> {
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    object_id,
+    CollabType::Document,
+  )
+  .await?;
+  let cache_key = format!(
+    "af_document_stats:{}:{:x}",
+    object_id,
+    md5::compute(&encoded_collab.doc_state)
+  );
+  if let Some(stats) = get_cached(redis_connection_manager, &cache_key).await {
+    trace!("Document stats cache hit: {}", object_id);
+    return Ok(stats);
+  }
+
+  let doc_state = encoded_collab.doc_state.to_vec();
+  let oid = object_id.to_string();
+  let (mut stats, attachment_urls) =
+    tokio::task::spawn_blocking(move || compute_document_stats(doc_state, &oid)).await??;
+
+  let file_ids = attachment_urls
+    .iter()
+    .filter_map(|url| blob_meta_key(&workspace_id, url))
+    .collect::<Vec<_>>();
+  if !file_ids.is_empty() {
+    let pg_pool = pg_pool_router.pg_pool_for_workspace(&workspace_id).await?;
+    stats.attachment_size = select_blob_metadata_by_file_ids(&pg_pool, &workspace_id, &file_ids)
+      .await?
+      .iter()
+      .map(|metadata| metadata.file_size)
+      .sum();
+  }
+
+  set_cached(redis_connection_manager, &cache_key, &stats).await;
+  Ok(stats)
+}
+
+/// Returns the stats of the document, along with the URLs of its attachments.
+fn compute_document_stats(
+  doc_state: Vec<u8>,
+  object_id: &str,
+) -> Result<(DocumentStats, Vec<String>), AppError> {
+  let collab = collab_from_doc_state(doc_state, object_id)?;
+  let body = DocumentBody::from_collab(&collab)
+    .ok_or_else(|| AppError::InvalidRequest(format!("{} is not a document", object_id)))?;
+  let document_data = body
+    .get_document_data(&collab.transact())
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid document {}: {}", object_id, err)))?;
+
+  let mut stats = DocumentStats::default();
+  let mut attachment_urls = vec![];
+  for (block_id, block) in &document_data.blocks {
+    if block_id == &document_data.page_id {
+      continue;
+    }
+    *stats.block_counts.entry(block.ty.clone()).or_default() += 1;
+    if ATTACHMENT_BLOCK_TYPES.contains(&block.ty.as_str()) {
+      stats.attachment_count += 1;
+      if let Some(url) = block.data.get("url").and_then(Value::as_str) {
+        attachment_urls.push(url.to_string());
+      }
+    }
+    let text = block_text(block, &document_data);
+    stats.word_count += count_words(&text);
+    stats.character_count += text.chars().filter(|c| !c.is_whitespace()).count() as u64;
+  }
+  stats.reading_time_secs = (stats.word_count * 60).div_ceil(WORDS_PER_MINUTE);
+  Ok((stats, attachment_urls))
+}
+
+/// The text of the block, stored either in its data or in the text map of the document.
+fn block_text(block: &Block, document_data: &DocumentData) -> String {
+  let deltas = match block.data.get("delta") {
+    Some(delta) => serde_json::from_value::<Vec<TextDelta>>(delta.clone()).ok(),
+    None => block
+      .external_id
+      .as_ref()
+      .filter(|_| block.external_type.as_deref() == Some("text"))
+      .and_then(|text_id| document_data.meta.text_map.as_ref()?.get(text_id))
+      .and_then(|json| serde_json::from_str::<Vec<TextDelta>>(json).ok()),
+  };
+  let mut text = String::new();
+  for delta in deltas.unwrap_or_default() {
+    if let TextDelta::Inserted(insert, _) = delta {
+      text.push_str(&insert);
+    }
+  }
+  text
+}
+
+/// Counts the words separated by whitespaces. Each CJK character counts as a word, since these
+/// scripts don't separate the words.
+fn count_words(text: &str) -> u64 {
+  let mut count = 0;
+  let mut in_word = false;
+  for c in text.chars() {
+    if is_cjk(c) {
+      count += 1;
+      in_word = false;
+    } else if c.is_whitespace() {
+      in_word = false;
+    } else if !in_word {
+      count += 1;
+      in_word = true;
+    }
+  }
+  count
+}
+
+fn is_cjk(c: char) -> bool {
+  matches!(c as u32,
+    0x3040..=0x30FF // Hiragana and Katakana
+    | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+    | 0x4E00..=0x9FFF // CJK Unified Ideographs
+    | 0xAC00..=0xD7AF // Hangul Syllables
+    | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+  )
+}
+
+/// The key of the blob metadata of an attachment uploaded to the workspace, i.e. whose URL ends
+/// with `/{workspace_id}/v1/blob/{parent_dir}/{file_id}`.
+fn blob_meta_key(workspace_id: &Uuid, url: &str) -> Option<String> {
+  let prefix = format!("/{}/v1/blob/", workspace_id);
+  let path = &url[url.find(&prefix)? + prefix.len()..];
+  let path = path.split(['?', '#']).next()?;
+  let (parent_dir, file_id) = path.split_once('/')?;
+  if parent_dir.is_empty() || file_id.is_empty() || file_id.contains('/') {
+    return None;
+  }
+  Some(format!("{}_{}", parent_dir, file_id))
+}
+
+async fn get_cached(
+  redis_connection_manager: &RedisConnectionManager,
+  cache_key: &str,
+) -> Option<DocumentStats> {
+  let value: Option<String> = match redis_connection_manager.clone().get(cache_key).await {
+    Ok(value) => value,
+    Err(err) => {
+      warn!("Failed to get document stats from cache: {:?}", err);
+      None
+    },
+  };
+  value.and_then(|value| serde_json::from_str(&value).ok())
+}
+
+async fn set_cached(
+  redis_connection_manager: &RedisConnectionManager,
+  cache_key: &str,
+  stats: &DocumentStats,
+) {
+  let value = match serde_json::to_string(stats) {
+    Ok(value) => value,
+    Err(_) => return,
+  };
+  let result: Result<(), _> = redis_connection_manager
+    .clone()
+    .set_ex(cache_key, value, DOCUMENT_STATS_CACHE_TTL_SECS)
+    .await;
+  if let Err(err) = result {
+    warn!("Failed to cache document stats: {:?}", err);
+  }
+}
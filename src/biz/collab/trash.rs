@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use app_error::AppError;
+use database::collab::{
+  purge_expired_trashed_collabs, purge_trashed_collab, restore_trashed_collab,
+};
+use database::residency::PgPoolRouter;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::scheduler::Scheduler;
+use crate::config::config::CollabSetting;
+
+/// How often the collabs at the end of their retention window are purged from the trash.
+const COLLAB_TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of collabs purged on each check, per database.
+const COLLAB_TRASH_PURGE_BATCH_SIZE: i64 = 500;
+
+/// Periodically purges the collabs that have been in the trash for longer than the retention
+/// window, in every regional database.
+pub fn register_collab_trash_purge_job(
+  scheduler: &Scheduler,
+  router: PgPoolRouter,
+  setting: &CollabSetting,
+) {
+  if setting.trash_retention_days == 0 {
+    info!("Collab trash purge is disabled");
+    return;
+  }
+  let retention_secs = (setting.trash_retention_days * 24 * 60 * 60) as i64;
+  scheduler.register(
+    "collab_trash_purge",
+    COLLAB_TRASH_PURGE_INTERVAL,
+    move || {
+      let router = router.clone();
+      async move {
+        for pg_pool in router.all_pools() {
+          match purge_expired_trashed_collabs(
+            pg_pool,
+            retention_secs,
+            COLLAB_TRASH_PURGE_BATCH_SIZE,
+          )
+          .await
+          {
+            Ok(0) => {},
+            Ok(purged) => info!("Purged {} collabs from the trash", purged),
+            Err(err) => error!("Failed to purge the collabs from the trash: {:?}", err),
+          }
+        }
+        Ok(())
+      }
+    },
+  );
+}
+
+pub async fn restore_collab(
+  pg_pool_router: &PgPoolRouter,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<(), AppError> {
+  let pg_pool = pg_pool_router
+    .pg_pool_for_collab(workspace_id, object_id)
+    .await?;
+  if !restore_trashed_collab(&pg_pool, workspace_id, object_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Collab {} is not in the trash",
+      object_id
+    )));
+  }
+  Ok(())
+}
+
+pub async fn purge_collab(
+  pg_pool_router: &PgPoolRouter,
+  workspace_id: &Uuid,
+  object_id: &str,
+) -> Result<(), AppError> {
+  let pg_pool = pg_pool_router
+    .pg_pool_for_collab(workspace_id, object_id)
+    .await?;
+  if !purge_trashed_collab(&pg_pool, workspace_id, object_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Collab {} is not in the trash",
+      object_id
+    )));
+  }
+  Ok(())
+}
@@ -0,0 +1,301 @@
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_document::blocks::{Block, DocumentData};
+use collab_document::document::DocumentBody;
+use collab_entity::CollabType;
+use database::collab::GetCollabOrigin;
+use encrypt::envelope::{open, seal, OpeningKey, SealingKey};
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::{
+  CollabExportFormat, ExportDecryption, ExportEncryption, ExportedBlock, ExportedDocument,
+};
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::ops::collab_from_doc_state;
+
+/// The blocks nested deeper than this are not exported, which also guards against the documents
+/// whose children map contains a cycle.
+const MAX_EXPORT_DEPTH: usize = 64;
+
+/// Exports the latest state of the document in the given format. Returns the exported content.
+pub async fn export_document(
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: &str,
+  format: CollabExportFormat,
+) -> Result<String, AppError> {
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    object_id,
+    CollabType::Document,
+  )
+  .await?;
+
+  let doc_state = encoded_collab.doc_state.to_vec();
+  let oid = object_id.to_string();
+  tokio::task::spawn_blocking(move || -> Result<String, AppError> {
+    let document = exported_document(doc_state, &oid)?;
+    match format {
+      CollabExportFormat::Markdown => Ok(document_to_markdown(&document.blocks)),
+      CollabExportFormat::Text => Ok(document_to_plain_text(&document.blocks)),
+      CollabExportFormat::Json => Ok(serde_json::to_string_pretty(&document)?),
+    }
+  })
+  .await?
+}
+
+/// Encrypts the exported content. The key derivation of the passphrase is deliberately slow, so it
+/// runs on the blocking threads.
+pub async fn encrypt_export(
+  content: Vec<u8>,
+  encryption: ExportEncryption,
+) -> Result<Vec<u8>, AppError> {
+  tokio::task::spawn_blocking(move || {
+    let key = match &encryption {
+      ExportEncryption::Passphrase { passphrase } => SealingKey::Passphrase(passphrase),
+      ExportEncryption::PublicKey { public_key } => SealingKey::PublicKey(public_key),
+    };
+    seal(&content, key)
+      .map_err(|err| AppError::InvalidRequest(format!("Failed to encrypt the export: {}", err)))
+  })
+  .await?
+}
+
+/// Decrypts the content encrypted by [encrypt_export].
+pub async fn decrypt_export(
+  content: Vec<u8>,
+  decryption: ExportDecryption,
+) -> Result<Vec<u8>, AppError> {
+  tokio::task::spawn_blocking(move || {
+    let key = match &decryption {
+      ExportDecryption::Passphrase { passphrase } => OpeningKey::Passphrase(passphrase),
+      ExportDecryption::SecretKey { secret_key } => OpeningKey::SecretKey(secret_key),
+    };
+    open(&content, key)
+      .map_err(|err| AppError::InvalidRequest(format!("Failed to decrypt the export: {}", err)))
+  })
+  .await?
+}
+
+fn exported_document(doc_state: Vec<u8>, object_id: &str) -> Result<ExportedDocument, AppError> {
+  let collab = collab_from_doc_state(doc_state, object_id)?;
+  let body = DocumentBody::from_collab(&collab)
+    .ok_or_else(|| AppError::InvalidRequest(format!("{} is not a document", object_id)))?;
+  let document_data = body
+    .get_document_data(&collab.transact())
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid document {}: {}", object_id, err)))?;
+
+  let blocks = match document_data.blocks.get(&document_data.page_id) {
+    Some(page) => exported_children(page, &document_data, 0),
+    None => vec![],
+  };
+  Ok(ExportedDocument {
+    object_id: object_id.to_string(),
+    blocks,
+  })
+}
+
+fn exported_children(
+  block: &Block,
+  document_data: &DocumentData,
+  depth: usize,
+) -> Vec<ExportedBlock> {
+  if depth >= MAX_EXPORT_DEPTH {
+    return vec![];
+  }
+  document_data
+    .meta
+    .children_map
+    .get(&block.children)
+    .into_iter()
+    .flatten()
+    .filter_map(|child_id| document_data.blocks.get(child_id))
+    .map(|child| exported_block(child, document_data, depth + 1))
+    .collect()
+}
+
+fn exported_block(block: &Block, document_data: &DocumentData, depth: usize) -> ExportedBlock {
+  let mut data = block.data.clone();
+  // The text of the block is stored either in its data or in the text map of the document.
+  let delta = match data.remove("delta") {
+    Some(Value::Array(delta)) => delta,
+    _ => block
+      .external_id
+      .as_ref()
+      .filter(|_| block.external_type.as_deref() == Some("text"))
+      .and_then(|text_id| document_data.meta.text_map.as_ref()?.get(text_id))
+      .and_then(|json| serde_json::from_str::<Vec<Value>>(json).ok())
+      .unwrap_or_default(),
+  };
+  ExportedBlock {
+    ty: block.ty.clone(),
+    data,
+    delta,
+    children: exported_children(block, document_data, depth),
+  }
+}
+
+fn document_to_markdown(blocks: &[ExportedBlock]) -> String {
+  let mut markdown = String::new();
+  write_markdown_blocks(&mut markdown, blocks, 0);
+  markdown
+}
+
+fn write_markdown_blocks(markdown: &mut String, blocks: &[ExportedBlock], depth: usize) {
+  let indent = "  ".repeat(depth);
+  let mut previous: Option<&ExportedBlock> = None;
+  for block in blocks {
+    // The items of a list are kept together, the other blocks are separated by a blank line.
+    let is_same_list = previous.map_or(false, |previous| {
+      previous.ty == block.ty && is_list_block(&block.ty)
+    });
+    if depth == 0 && previous.is_some() && !is_same_list {
+      markdown.push('\n');
+    }
+    for line in block_markdown(block).lines() {
+      markdown.push_str(&indent);
+      markdown.push_str(line);
+      markdown.push('\n');
+    }
+    write_markdown_blocks(markdown, &block.children, depth + 1);
+    previous = Some(block);
+  }
+}
+
+/// Writes the text of each block on its own lines, the children indented under their parent.
+fn document_to_plain_text(blocks: &[ExportedBlock]) -> String {
+  let mut text = String::new();
+  write_plain_text_blocks(&mut text, blocks, 0);
+  text
+}
+
+fn write_plain_text_blocks(text: &mut String, blocks: &[ExportedBlock], depth: usize) {
+  let indent = "  ".repeat(depth);
+  for block in blocks {
+    let block_text = match block.ty.as_str() {
+      "math_equation" => block
+        .data
+        .get("formula")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string(),
+      _ => delta_plain_text(&block.delta),
+    };
+    for line in block_text.lines() {
+      text.push_str(&indent);
+      text.push_str(line);
+      text.push('\n');
+    }
+    write_plain_text_blocks(text, &block.children, depth + 1);
+  }
+}
+
+fn is_list_block(ty: &str) -> bool {
+  matches!(
+    ty,
+    "bulleted_list" | "numbered_list" | "todo_list" | "toggle_list"
+  )
+}
+
+fn block_markdown(block: &ExportedBlock) -> String {
+  let data_str = |key: &str| block.data.get(key).and_then(Value::as_str).unwrap_or("");
+  let text = delta_markdown(&block.delta);
+  match block.ty.as_str() {
+    "heading" => {
+      let level = block
+        .data
+        .get("level")
+        .and_then(Value::as_u64)
+        .unwrap_or(1)
+        .clamp(1, 6);
+      format!("{} {}", "#".repeat(level as usize), text)
+    },
+    "todo_list" => {
+      let checked = block
+        .data
+        .get("checked")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+      format!("- [{}] {}", if checked { "x" } else { " " }, text)
+    },
+    "bulleted_list" | "toggle_list" => format!("- {}", text),
+    "numbered_list" => format!("1. {}", text),
+    "quote" => format!("> {}", text.replace('\n', "\n> ")),
+    "callout" => {
+      let text = format!("{} {}", data_str("icon"), text);
+      format!("> {}", text.trim().replace('\n', "\n> "))
+    },
+    "code" => format!(
+      "```{}\n{}\n```",
+      data_str("language"),
+      delta_plain_text(&block.delta)
+    ),
+    "math_equation" => format!("$$\n{}\n$$", data_str("formula")),
+    "divider" => "---".to_string(),
+    "image" => format!("![]({})", data_str("url")),
+    "file" => {
+      let url = data_str("url");
+      let name = match data_str("name") {
+        "" => url,
+        name => name,
+      };
+      format!("[{}]({})", name, url)
+    },
+    _ => text,
+  }
+}
+
+fn delta_plain_text(delta: &[Value]) -> String {
+  delta
+    .iter()
+    .filter_map(|op| op.get("insert").and_then(Value::as_str))
+    .collect()
+}
+
+/// Converts the inserts of the delta to Markdown, along with their inline formatting.
+fn delta_markdown(delta: &[Value]) -> String {
+  let mut markdown = String::new();
+  for op in delta {
+    let Some(insert) = op.get("insert").and_then(Value::as_str) else {
+      continue;
+    };
+    let attributes = op.get("attributes");
+    let has = |key: &str| {
+      attributes
+        .and_then(|attributes| attributes.get(key))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    };
+    if insert.trim().is_empty() {
+      markdown.push_str(insert);
+      continue;
+    }
+
+    let mut text = insert.to_string();
+    if has("code") {
+      text = format!("`{}`", text);
+    } else {
+      if has("bold") {
+        text = format!("**{}**", text);
+      }
+      if has("italic") {
+        text = format!("_{}_", text);
+      }
+      if has("strikethrough") {
+        text = format!("~~{}~~", text);
+      }
+    }
+    if let Some(href) = attributes
+      .and_then(|attributes| attributes.get("href"))
+      .and_then(Value::as_str)
+    {
+      text = format!("[{}]({})", text, href);
+    }
+    markdown.push_str(&text);
+  }
+  markdown
+}
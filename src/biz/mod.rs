@@ -1,8 +1,13 @@
 pub mod access_request;
+pub mod announcement;
 pub mod chat;
+pub mod client_version;
 pub mod collab;
 pub mod data_import;
+pub mod export;
+pub mod oembed;
 pub mod pg_listener;
+pub mod scheduler;
 pub mod search;
 pub mod template;
 pub mod user;
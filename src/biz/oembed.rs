@@ -0,0 +1,306 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use redis::AsyncCommands;
+use serde::Deserialize;
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::{OEmbed, OEmbedParams};
+use tracing::{trace, warn};
+use url::Url;
+
+use crate::config::config::{OEmbedProvider, OEmbedSetting};
+use crate::state::RedisConnectionManager;
+
+/// The responses of the providers larger than this are rejected.
+const MAX_OEMBED_RESPONSE_SIZE: usize = 256 * 1024;
+
+/// Resolves the embeds of the URLs with the oEmbed providers listed in the configuration, and
+/// caches the embeds in Redis.
+///
+/// The server only sends requests to the configured endpoints, never to the embedded URLs. The
+/// endpoint must resolve to a public address, which the request is pinned to, and the redirects
+/// aren't followed, so that a provider can't be used to reach the internal network.
+#[derive(Clone)]
+pub struct OEmbedResolver {
+  providers: Vec<OEmbedProvider>,
+  cache_ttl_secs: u64,
+  request_timeout: Duration,
+  redis_connection_manager: RedisConnectionManager,
+}
+
+/// The subset of the oEmbed response returned to the clients.
+#[derive(Deserialize)]
+struct ProviderOEmbed {
+  #[serde(rename = "type")]
+  embed_type: String,
+  provider_name: Option<String>,
+  title: Option<String>,
+  author_name: Option<String>,
+  thumbnail_url: Option<String>,
+  // Some providers return the dimensions as strings.
+  width: Option<Value>,
+  height: Option<Value>,
+  html: Option<String>,
+}
+
+impl OEmbedResolver {
+  pub fn new(setting: &OEmbedSetting, redis_connection_manager: RedisConnectionManager) -> Self {
+    Self {
+      providers: setting.providers.clone(),
+      cache_ttl_secs: setting.cache_ttl_secs,
+      request_timeout: Duration::from_secs(setting.request_timeout_secs),
+      redis_connection_manager,
+    }
+  }
+
+  pub async fn resolve(&self, params: OEmbedParams) -> Result<OEmbed, AppError> {
+    let url = Url::parse(params.url.trim())?;
+    if !matches!(url.scheme(), "http" | "https") {
+      return Err(AppError::InvalidRequest(format!(
+        "Only http and https URLs can be embedded: {}",
+        url
+      )));
+    }
+    let host = url
+      .host_str()
+      .ok_or_else(|| AppError::InvalidRequest(format!("URL without host: {}", url)))?
+      .to_lowercase();
+    let provider = self
+      .providers
+      .iter()
+      .find(|provider| is_provider_host(provider, &host))
+      .ok_or_else(|| {
+        AppError::InvalidRequest(format!("No oEmbed provider is allowed for {}", host))
+      })?;
+
+    let cache_key = format!(
+      "af_oembed:{}:{}:{}",
+      params.max_width.unwrap_or_default(),
+      params.max_height.unwrap_or_default(),
+      url
+    );
+    if let Some(embed) = self.get_cached(&cache_key).await {
+      trace!("oEmbed cache hit: {}", url);
+      return Ok(embed);
+    }
+
+    let embed = self.fetch(provider, &url, &params).await?;
+    self.set_cached(&cache_key, &embed).await;
+    Ok(embed)
+  }
+
+  async fn fetch(
+    &self,
+    provider: &OEmbedProvider,
+    url: &Url,
+    params: &OEmbedParams,
+  ) -> Result<OEmbed, AppError> {
+    let mut endpoint = Url::parse(&provider.endpoint)?;
+    {
+      let mut query = endpoint.query_pairs_mut();
+      query.append_pair("url", url.as_str());
+      query.append_pair("format", "json");
+      if let Some(max_width) = params.max_width {
+        query.append_pair("maxwidth", &max_width.to_string());
+      }
+      if let Some(max_height) = params.max_height {
+        query.append_pair("maxheight", &max_height.to_string());
+      }
+    }
+    let endpoint_host = endpoint
+      .host_str()
+      .ok_or_else(|| AppError::Internal(anyhow!("oEmbed endpoint without host: {}", endpoint)))?
+      .to_string();
+    let addr = resolve_public_addr(&endpoint).await?;
+
+    let client = reqwest::Client::builder()
+      .redirect(reqwest::redirect::Policy::none())
+      .timeout(self.request_timeout)
+      .resolve(&endpoint_host, addr)
+      .build()
+      .map_err(|err| AppError::Internal(err.into()))?;
+    let mut resp = client
+      .get(endpoint)
+      .header(reqwest::header::ACCEPT, "application/json")
+      .send()
+      .await
+      .map_err(|err| AppError::Connect(format!("Failed to request oEmbed provider: {}", err)))?;
+    if !resp.status().is_success() {
+      return Err(AppError::InvalidRequest(format!(
+        "The oEmbed provider can't embed {}: {}",
+        url,
+        resp.status()
+      )));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp
+      .chunk()
+      .await
+      .map_err(|err| AppError::Connect(format!("Failed to read oEmbed response: {}", err)))?
+    {
+      if body.len() + chunk.len() > MAX_OEMBED_RESPONSE_SIZE {
+        return Err(AppError::InvalidRequest(
+          "The oEmbed response is too large".to_string(),
+        ));
+      }
+      body.extend_from_slice(&chunk);
+    }
+    let provider_embed: ProviderOEmbed = serde_json::from_slice(&body)?;
+    Ok(to_oembed(provider, url, provider_embed))
+  }
+
+  async fn get_cached(&self, cache_key: &str) -> Option<OEmbed> {
+    let value: Option<String> = match self.redis_connection_manager.clone().get(cache_key).await {
+      Ok(value) => value,
+      Err(err) => {
+        warn!("Failed to get oEmbed from cache: {:?}", err);
+        None
+      },
+    };
+    value.and_then(|value| serde_json::from_str(&value).ok())
+  }
+
+  async fn set_cached(&self, cache_key: &str, embed: &OEmbed) {
+    let value = match serde_json::to_string(embed) {
+      Ok(value) => value,
+      Err(_) => return,
+    };
+    let result: Result<(), _> = self
+      .redis_connection_manager
+      .clone()
+      .set_ex(cache_key, value, self.cache_ttl_secs)
+      .await;
+    if let Err(err) = result {
+      warn!("Failed to cache oEmbed: {:?}", err);
+    }
+  }
+}
+
+/// The subdomains of the hosts of a provider are served by the provider too.
+fn is_provider_host(provider: &OEmbedProvider, host: &str) -> bool {
+  provider.hosts.iter().any(|provider_host| {
+    host == provider_host
+      || host
+        .strip_suffix(provider_host.as_str())
+        .map_or(false, |subdomain| subdomain.ends_with('.'))
+  })
+}
+
+/// Keeps the iframe of the embed only when it's served over https by the provider, and replaces
+/// the html of the provider with a plain iframe.
+fn to_oembed(provider: &OEmbedProvider, url: &Url, embed: ProviderOEmbed) -> OEmbed {
+  let width = embed.width.as_ref().and_then(dimension);
+  let height = embed.height.as_ref().and_then(dimension);
+  let embed_url = embed
+    .html
+    .as_deref()
+    .and_then(iframe_src)
+    .and_then(|src| Url::parse(&src).ok())
+    .filter(|src| {
+      src.scheme() == "https"
+        && src.host_str().map_or(false, |host| {
+          is_provider_host(provider, &host.to_lowercase())
+        })
+    })
+    .map(|src| src.to_string());
+  let html = embed_url.as_ref().map(|embed_url| {
+    format!(
+      r#"<iframe src="{}" width="{}" height="{}" frameborder="0" allowfullscreen></iframe>"#,
+      escape_attribute(embed_url),
+      width.unwrap_or(640),
+      height.unwrap_or(360)
+    )
+  });
+  OEmbed {
+    url: url.to_string(),
+    embed_type: embed.embed_type,
+    provider_name: embed.provider_name,
+    title: embed.title,
+    author_name: embed.author_name,
+    thumbnail_url: embed
+      .thumbnail_url
+      .filter(|thumbnail_url| thumbnail_url.starts_with("https://")),
+    width,
+    height,
+    embed_url,
+    html,
+  }
+}
+
+fn dimension(value: &Value) -> Option<u32> {
+  match value {
+    Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+    Value::String(s) => s.parse().ok(),
+    _ => None,
+  }
+}
+
+fn iframe_src(html: &str) -> Option<String> {
+  let lowercase = html.to_ascii_lowercase();
+  let iframe = lowercase.find("<iframe")?;
+  let src = iframe + lowercase[iframe..].find(" src=")? + " src=".len();
+  let quote = html[src..].chars().next()?;
+  if quote != '"' && quote != '\'' {
+    return None;
+  }
+  let start = src + 1;
+  let end = start + html[start..].find(quote)?;
+  Some(html[start..end].replace("&amp;", "&"))
+}
+
+fn escape_attribute(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+async fn resolve_public_addr(endpoint: &Url) -> Result<SocketAddr, AppError> {
+  let host = endpoint
+    .host_str()
+    .ok_or_else(|| AppError::Internal(anyhow!("oEmbed endpoint without host: {}", endpoint)))?;
+  let port = endpoint.port_or_known_default().unwrap_or(443);
+  let mut addrs = tokio::net::lookup_host((host, port))
+    .await
+    .map_err(|err| AppError::Connect(format!("Failed to resolve {}: {}", host, err)))?;
+  addrs.find(|addr| is_public_ip(addr.ip())).ok_or_else(|| {
+    AppError::InvalidRequest(format!(
+      "The oEmbed endpoint {} doesn't resolve to a public address",
+      host
+    ))
+  })
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => {
+      let octets = ip.octets();
+      !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        // Shared address space, used by the carrier-grade NATs
+        || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+        // Reserved
+        || octets[0] >= 240)
+    },
+    IpAddr::V6(ip) => {
+      let first_segment = ip.segments()[0];
+      !(ip.is_loopback()
+        || ip.is_unspecified()
+        // Unique local addresses
+        || (first_segment & 0xfe00) == 0xfc00
+        // Link-local addresses
+        || (first_segment & 0xffc0) == 0xfe80
+        || ip
+          .to_ipv4_mapped()
+          .map_or(false, |ip| !is_public_ip(IpAddr::V4(ip))))
+    },
+  }
+}
@@ -1,18 +1,36 @@
 use access_control::casbin::notification::WorkspaceMemberNotification;
 use anyhow::Error;
 use appflowy_collaborate::collab::notification::CollabMemberNotification;
+use collab_rt_entity::user::AFFolderChange;
 use database::listener::PostgresDBListener;
 use database::pg_row::AFUserNotification;
 use sqlx::PgPool;
 
+use crate::biz::workspace::access_expiry::AccessGrantChange;
+
+/// The channel the folder changes made through the REST API are sent on.
+pub const FOLDER_CHANGE_CHANNEL: &str = "af_folder_change_channel";
+
+/// The channel the changes of the time-boxed workspace and collab grants are sent on.
+pub const ACCESS_GRANT_CHANGE_CHANNEL: &str = "af_access_grant_change_channel";
+
 pub struct PgListeners {
   user_listener: UserListener,
+  folder_change_listener: FolderChangeListener,
+  access_grant_change_listener: AccessGrantChangeListener,
 }
 
 impl PgListeners {
   pub async fn new(pg_pool: &PgPool) -> Result<Self, Error> {
     let user_listener = UserListener::new(pg_pool, "af_user_channel").await?;
-    Ok(Self { user_listener })
+    let folder_change_listener = FolderChangeListener::new(pg_pool, FOLDER_CHANGE_CHANNEL).await?;
+    let access_grant_change_listener =
+      AccessGrantChangeListener::new(pg_pool, ACCESS_GRANT_CHANGE_CHANNEL).await?;
+    Ok(Self {
+      user_listener,
+      folder_change_listener,
+      access_grant_change_listener,
+    })
   }
 
   pub fn subscribe_user_change(&self, uid: i64) -> tokio::sync::mpsc::Receiver<AFUserNotification> {
@@ -29,8 +47,22 @@ impl PgListeners {
     });
     rx
   }
+
+  /// Subscribes to the folder changes of all the workspaces, the subscriber filters the ones of
+  /// the workspaces it's interested in.
+  pub fn subscribe_folder_change(&self) -> tokio::sync::broadcast::Receiver<AFFolderChange> {
+    self.folder_change_listener.notify.subscribe()
+  }
+
+  pub fn subscribe_access_grant_change(
+    &self,
+  ) -> tokio::sync::broadcast::Receiver<AccessGrantChange> {
+    self.access_grant_change_listener.notify.subscribe()
+  }
 }
 
 pub type CollabMemberListener = PostgresDBListener<CollabMemberNotification>;
 pub type UserListener = PostgresDBListener<AFUserNotification>;
+pub type FolderChangeListener = PostgresDBListener<AFFolderChange>;
+pub type AccessGrantChangeListener = PostgresDBListener<AccessGrantChange>;
 pub type WorkspaceMemberListener = PostgresDBListener<WorkspaceMemberNotification>;
@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use app_error::AppError;
+use database::pg_row::AFScheduleRow;
+use database::scheduler::{
+  claim_due_schedules, select_scheduler_leader, select_schedules, try_acquire_scheduler_leadership,
+  update_schedule_run, upsert_schedule,
+};
+use shared_entity::dto::server_info_dto::{ScheduledJob, SchedulerLeader, Schedules};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// How often the scheduler renews its leadership and looks for the jobs to run.
+const SCHEDULER_TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the leadership lasts without being renewed. Another node takes over the jobs once
+/// the lease of the leader expires, e.g. after it was stopped.
+const SCHEDULER_LEADER_LEASE: Duration = Duration::from_secs(60);
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), AppError>> + Send>>;
+
+#[derive(Clone)]
+struct Job {
+  interval: Duration,
+  run: Arc<dyn Fn() -> JobFuture + Send + Sync>,
+}
+
+/// Runs the periodic jobs of the server. The schedules are persisted, so that restarting the
+/// server doesn't reset them, and only the leader among the nodes of the deployment runs the
+/// jobs, so that each run happens once.
+#[derive(Clone)]
+pub struct Scheduler {
+  pg_pool: PgPool,
+  node_id: String,
+  jobs: Arc<Mutex<HashMap<String, Job>>>,
+  running: Arc<Mutex<HashSet<String>>>,
+}
+
+impl Scheduler {
+  pub fn new(pg_pool: PgPool) -> Self {
+    let instance_id = Uuid::new_v4().to_string();
+    let node_id = match std::env::var("HOSTNAME") {
+      Ok(hostname) if !hostname.is_empty() => format!("{}-{}", hostname, &instance_id[..8]),
+      _ => instance_id,
+    };
+    Self {
+      pg_pool,
+      node_id,
+      jobs: Default::default(),
+      running: Default::default(),
+    }
+  }
+
+  /// Runs the job every `interval`, starting right away if it never ran before. A run is skipped
+  /// while the previous one is still running. The jobs must be registered before the scheduler
+  /// is started.
+  pub fn register<F, Fut>(&self, name: &str, interval: Duration, job: F)
+  where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+  {
+    let job = Job {
+      interval,
+      run: Arc::new(move || Box::pin(job())),
+    };
+    self
+      .jobs
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .insert(name.to_string(), job);
+  }
+
+  pub fn start(&self) {
+    let scheduler = self.clone();
+    tokio::spawn(async move {
+      let mut persisted = false;
+      let mut interval = tokio::time::interval(SCHEDULER_TICK_INTERVAL);
+      loop {
+        interval.tick().await;
+        if !persisted {
+          match scheduler.persist_schedules().await {
+            Ok(()) => persisted = true,
+            Err(err) => {
+              error!("Failed to persist the schedules: {:?}", err);
+              continue;
+            },
+          }
+        }
+        if let Err(err) = scheduler.run_due_jobs().await {
+          error!("Failed to run the scheduled jobs: {:?}", err);
+        }
+      }
+    });
+  }
+
+  async fn persist_schedules(&self) -> Result<(), AppError> {
+    let jobs = self.jobs();
+    for (name, job) in &jobs {
+      upsert_schedule(&self.pg_pool, name, job.interval.as_secs() as i64).await?;
+    }
+    info!("Scheduled {} jobs on node {}", jobs.len(), self.node_id);
+    Ok(())
+  }
+
+  async fn run_due_jobs(&self) -> Result<(), AppError> {
+    if !try_acquire_scheduler_leadership(
+      &self.pg_pool,
+      &self.node_id,
+      SCHEDULER_LEADER_LEASE.as_secs() as i64,
+    )
+    .await?
+    {
+      return Ok(());
+    }
+    let names = {
+      let running = self.running.lock().unwrap_or_else(|err| err.into_inner());
+      self
+        .jobs()
+        .into_keys()
+        .filter(|name| !running.contains(name))
+        .collect::<Vec<_>>()
+    };
+    if names.is_empty() {
+      return Ok(());
+    }
+    for name in claim_due_schedules(&self.pg_pool, &names, &self.node_id).await? {
+      self.spawn_job(name);
+    }
+    Ok(())
+  }
+
+  fn spawn_job(&self, name: String) {
+    let job = match self.jobs().remove(&name) {
+      Some(job) => job,
+      None => return,
+    };
+    self
+      .running
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .insert(name.clone());
+    let scheduler = self.clone();
+    tokio::spawn(async move {
+      // The job runs in its own task, so that a panic is recorded as a failed run
+      let error = match tokio::spawn((job.run)()).await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(err) => Some(format!("The job panicked: {}", err)),
+      };
+      if let Some(error) = &error {
+        error!("Scheduled job {} failed: {}", name, error);
+      }
+      if let Err(err) = update_schedule_run(&scheduler.pg_pool, &name, error.as_deref()).await {
+        error!(
+          "Failed to record the run of scheduled job {}: {:?}",
+          name, err
+        );
+      }
+      scheduler
+        .running
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .remove(&name);
+    });
+  }
+
+  fn jobs(&self) -> HashMap<String, Job> {
+    self
+      .jobs
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .clone()
+  }
+
+  /// Returns the upcoming and the last runs of the jobs, as recorded by all the nodes.
+  pub async fn get_schedules(&self) -> Result<Schedules, AppError> {
+    let leader = select_scheduler_leader(&self.pg_pool)
+      .await?
+      .map(|row| SchedulerLeader {
+        node_id: row.node_id,
+        lease_expires_at: row.lease_expires_at,
+      });
+    let jobs = select_schedules(&self.pg_pool)
+      .await?
+      .into_iter()
+      .map(scheduled_job_from_row)
+      .collect();
+    Ok(Schedules {
+      node_id: self.node_id.clone(),
+      leader,
+      jobs,
+    })
+  }
+}
+
+fn scheduled_job_from_row(row: AFScheduleRow) -> ScheduledJob {
+  let is_running = match (row.last_started_at, row.last_finished_at) {
+    (Some(started_at), Some(finished_at)) => started_at > finished_at,
+    (Some(_), None) => true,
+    (None, _) => false,
+  };
+  ScheduledJob {
+    name: row.name,
+    interval_secs: row.interval_secs,
+    next_run_at: row.next_run_at,
+    last_started_at: row.last_started_at,
+    last_finished_at: row.last_finished_at,
+    last_run_by: row.last_run_by,
+    is_running,
+    run_count: row.run_count,
+    failure_count: row.failure_count,
+    consecutive_failures: row.consecutive_failures,
+    last_error: row.last_error,
+    last_failed_at: row.last_failed_at,
+  }
+}
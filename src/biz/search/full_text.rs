@@ -0,0 +1,65 @@
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::index::search_collab_text;
+use shared_entity::dto::search_dto::{FullTextSearchRequest, FullTextSearchResultItem};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::search::views::SearchableViews;
+
+const DEFAULT_FULL_TEXT_SEARCH_LIMIT: u32 = 20;
+const MAX_FULL_TEXT_SEARCH_LIMIT: u32 = 100;
+const MAX_FULL_TEXT_QUERY_LEN: usize = 256;
+
+/// Searches the text of the documents of the workspace that were extracted by the indexer. Only
+/// the views of the folder that the user can see are returned, the views in the trash are left
+/// out.
+pub async fn full_text_search(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  request: FullTextSearchRequest,
+) -> Result<Vec<FullTextSearchResultItem>, AppError> {
+  let query = request.q.trim();
+  if query.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The search query can't be empty".to_string(),
+    ));
+  }
+  if query.len() > MAX_FULL_TEXT_QUERY_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The search query can have at most {} characters",
+      MAX_FULL_TEXT_QUERY_LEN
+    )));
+  }
+  let limit = request
+    .limit
+    .unwrap_or(DEFAULT_FULL_TEXT_SEARCH_LIMIT)
+    .clamp(1, MAX_FULL_TEXT_SEARCH_LIMIT);
+
+  let views = SearchableViews::load(pg_pool, collab_storage, uid, &workspace_id).await?;
+  let guest_view_ids = views.guest_view_ids();
+  let rows = search_collab_text(
+    pg_pool,
+    &workspace_id,
+    query,
+    guest_view_ids.as_deref(),
+    limit as i64,
+  )
+  .await?;
+  let results = rows
+    .into_iter()
+    .filter_map(|row| {
+      views
+        .view_name(&row.object_id)
+        .map(|title| FullTextSearchResultItem {
+          view_id: row.object_id,
+          title,
+          snippet: row.snippet,
+          rank: row.rank,
+        })
+    })
+    .collect();
+  Ok(results)
+}
@@ -1,3 +1,8 @@
+mod full_text;
 mod ops;
+mod semantic;
+mod views;
 
+pub use self::full_text::*;
 pub use self::ops::*;
+pub use self::semantic::*;
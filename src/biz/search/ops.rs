@@ -22,37 +22,8 @@ pub async fn search_document(
   request: SearchDocumentRequest,
   metrics: &RequestMetrics,
 ) -> Result<Vec<SearchDocumentResponseItem>, AppResponseError> {
-  let embeddings = ai_client
-    .embeddings(EmbeddingRequest {
-      input: EmbeddingInput::String(request.query.clone()),
-      model: EmbeddingsModel::TextEmbedding3Small.to_string(),
-      chunk_size: 500,
-      encoding_format: EmbeddingEncodingFormat::Float,
-      dimensions: 1536,
-    })
-    .await
-    .map_err(|e| AppResponseError::new(ErrorCode::Internal, e.to_string()))?;
-  let total_tokens = embeddings.total_tokens as u32;
-  metrics.record_search_tokens_used(&workspace_id, total_tokens);
-  tracing::info!(
-    "workspace {} OpenAI API search tokens used: {}",
-    workspace_id,
-    total_tokens
-  );
-
-  let embedding = embeddings
-    .data
-    .first()
-    .ok_or_else(|| AppResponseError::new(ErrorCode::Internal, "OpenAI returned no embeddings"))?;
-  let embedding = match &embedding.embedding {
-    EmbeddingOutput::Float(vector) => vector.iter().map(|&v| v as f32).collect(),
-    EmbeddingOutput::Base64(_) => {
-      return Err(AppResponseError::new(
-        ErrorCode::Internal,
-        "OpenAI returned embeddings in unsupported format",
-      ))
-    },
-  };
+  let (embedding, total_tokens) =
+    embed_search_query(ai_client, &workspace_id, &request.query, metrics).await?;
 
   let mut tx = pg_pool
     .begin()
@@ -93,3 +64,44 @@ pub async fn search_document(
       .collect(),
   )
 }
+
+/// Embeds the search query, returns the embedding and the number of tokens used.
+pub(crate) async fn embed_search_query(
+  ai_client: &AppFlowyAIClient,
+  workspace_id: &Uuid,
+  query: &str,
+  metrics: &RequestMetrics,
+) -> Result<(Vec<f32>, u32), AppResponseError> {
+  let embeddings = ai_client
+    .embeddings(EmbeddingRequest {
+      input: EmbeddingInput::String(query.to_string()),
+      model: EmbeddingsModel::TextEmbedding3Small.to_string(),
+      chunk_size: 500,
+      encoding_format: EmbeddingEncodingFormat::Float,
+      dimensions: 1536,
+    })
+    .await
+    .map_err(|e| AppResponseError::new(ErrorCode::Internal, e.to_string()))?;
+  let total_tokens = embeddings.total_tokens as u32;
+  metrics.record_search_tokens_used(workspace_id, total_tokens);
+  tracing::info!(
+    "workspace {} OpenAI API search tokens used: {}",
+    workspace_id,
+    total_tokens
+  );
+
+  let embedding = embeddings
+    .data
+    .first()
+    .ok_or_else(|| AppResponseError::new(ErrorCode::Internal, "OpenAI returned no embeddings"))?;
+  let embedding = match &embedding.embedding {
+    EmbeddingOutput::Float(vector) => vector.iter().map(|&v| v as f32).collect(),
+    EmbeddingOutput::Base64(_) => {
+      return Err(AppResponseError::new(
+        ErrorCode::Internal,
+        "OpenAI returned embeddings in unsupported format",
+      ))
+    },
+  };
+  Ok((embedding, total_tokens))
+}
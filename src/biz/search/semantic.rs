@@ -0,0 +1,71 @@
+use app_error::AppError;
+use appflowy_ai_client::client::AppFlowyAIClient;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::index::{search_documents, SearchDocumentParams};
+use shared_entity::dto::search_dto::{SemanticSearchRequest, SemanticSearchResultItem};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api::metrics::RequestMetrics;
+use crate::biz::search::ops::embed_search_query;
+use crate::biz::search::views::SearchableViews;
+
+const DEFAULT_SEMANTIC_SEARCH_LIMIT: u32 = 10;
+const MAX_SEMANTIC_SEARCH_LIMIT: u32 = 50;
+const SEMANTIC_SEARCH_PREVIEW_SIZE: i32 = 180;
+
+/// Embeds the query and returns the views of the workspace whose indexed content is the nearest
+/// to it. Only the views of the folder that the user can see are returned.
+pub async fn semantic_search(
+  pg_pool: &PgPool,
+  ai_client: &AppFlowyAIClient,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  request: SemanticSearchRequest,
+  metrics: &RequestMetrics,
+) -> Result<Vec<SemanticSearchResultItem>, AppResponseError> {
+  let query = request.query.trim();
+  if query.is_empty() {
+    return Err(AppError::InvalidRequest("The search query can't be empty".to_string()).into());
+  }
+  let limit = request
+    .limit
+    .unwrap_or(DEFAULT_SEMANTIC_SEARCH_LIMIT)
+    .clamp(1, MAX_SEMANTIC_SEARCH_LIMIT);
+
+  let views = SearchableViews::load(pg_pool, collab_storage, uid, &workspace_id).await?;
+  let (embedding, total_tokens) =
+    embed_search_query(ai_client, &workspace_id, query, metrics).await?;
+  let mut tx = pg_pool.begin().await?;
+  let results = search_documents(
+    &mut tx,
+    SearchDocumentParams {
+      user_id: uid,
+      workspace_id,
+      limit: limit as i32,
+      preview: SEMANTIC_SEARCH_PREVIEW_SIZE,
+      embedding,
+    },
+    total_tokens,
+  )
+  .await?;
+  tx.commit().await?;
+
+  Ok(
+    results
+      .into_iter()
+      .filter_map(|item| {
+        views
+          .view_name(&item.object_id)
+          .map(|title| SemanticSearchResultItem {
+            view_id: item.object_id,
+            title,
+            score: item.score,
+            preview: item.content_preview,
+          })
+      })
+      .collect(),
+  )
+}
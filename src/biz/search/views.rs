@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_folder::Folder;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::ops::{get_guest_granted_view_ids, get_latest_collab_folder_for_user};
+
+/// The views of the workspace folder that a user can find with the search: the views in the trash
+/// are left out, and guests only find the views granted to them.
+pub(crate) struct SearchableViews {
+  folder: Folder,
+  guest_view_ids: Option<HashSet<String>>,
+  trash_view_ids: HashSet<String>,
+}
+
+impl SearchableViews {
+  pub async fn load(
+    pg_pool: &PgPool,
+    collab_storage: &CollabAccessControlStorage,
+    uid: i64,
+    workspace_id: &Uuid,
+  ) -> Result<Self, AppError> {
+    let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, workspace_id).await?;
+    let folder = get_latest_collab_folder_for_user(
+      collab_storage,
+      uid,
+      &workspace_id.to_string(),
+      guest_view_ids.is_some(),
+    )
+    .await?;
+    let trash_view_ids = folder
+      .get_my_trash_sections()
+      .into_iter()
+      .map(|section| section.id)
+      .collect();
+    Ok(Self {
+      folder,
+      guest_view_ids,
+      trash_view_ids,
+    })
+  }
+
+  /// The views granted to the user if the user is a guest of the workspace.
+  pub fn guest_view_ids(&self) -> Option<Vec<String>> {
+    self
+      .guest_view_ids
+      .as_ref()
+      .map(|view_ids| view_ids.iter().cloned().collect())
+  }
+
+  /// Returns the name of the view, or `None` if the user can't find the view.
+  pub fn view_name(&self, view_id: &str) -> Option<String> {
+    if self.trash_view_ids.contains(view_id) {
+      return None;
+    }
+    if let Some(guest_view_ids) = &self.guest_view_ids {
+      if !guest_view_ids.contains(view_id) {
+        return None;
+      }
+    }
+    self.folder.get_view(view_id).map(|view| view.name.clone())
+  }
+}
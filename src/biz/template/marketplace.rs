@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::comment_attachment::select_workspace_id_for_published_view;
+use database::file::s3_client_impl::AwsS3BucketClientImpl;
+use database::publish::{select_published_collab_info, select_published_collab_info_for_view_ids};
+use database::template::{
+  delete_marketplace_template, increment_marketplace_template_install_count,
+  select_marketplace_template, select_marketplace_template_workspace_id,
+  select_marketplace_templates, upsert_marketplace_template,
+};
+use database_entity::dto::{
+  GetMarketplaceTemplatesQueryParams, InstallMarketplaceTemplateParams,
+  MarketplaceTemplateWithPublishInfo, MarketplaceTemplates, PublishInfo,
+  PublishMarketplaceTemplateParams,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::biz::workspace::publish::check_workspace_owner_or_publisher;
+use crate::biz::workspace::publish_dup::duplicate_published_collab_to_workspace;
+
+const DEFAULT_MARKETPLACE_PAGE_SIZE: u32 = 20;
+const MAX_MARKETPLACE_PAGE_SIZE: u32 = 100;
+const MAX_TEMPLATE_SCREENSHOTS: usize = 6;
+
+/// Lists the published view in the marketplace, or updates its listing. Only the owners of the
+/// workspace and the publishers of the view can list it.
+pub async fn publish_marketplace_template(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  uid: i64,
+  params: PublishMarketplaceTemplateParams,
+) -> Result<MarketplaceTemplateWithPublishInfo, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The name of the template can't be empty".to_string(),
+    ));
+  }
+  if params.screenshot_urls.len() > MAX_TEMPLATE_SCREENSHOTS {
+    return Err(AppError::InvalidRequest(format!(
+      "A template can have at most {} screenshots",
+      MAX_TEMPLATE_SCREENSHOTS
+    )));
+  }
+  if let Some(url) = params
+    .screenshot_urls
+    .iter()
+    .find(|url| !url.starts_with("https://"))
+  {
+    return Err(AppError::InvalidRequest(format!(
+      "The screenshot {} is not an https URL",
+      url
+    )));
+  }
+  let workspace_id = select_workspace_id_for_published_view(pg_pool, &params.view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} is not published", params.view_id)))?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, &workspace_id, &[params.view_id]).await?;
+  upsert_marketplace_template(
+    pg_pool,
+    params.view_id,
+    workspace_id,
+    uid,
+    name,
+    &params.description,
+    params.license,
+    &params.screenshot_urls,
+    params.category_id,
+  )
+  .await?;
+  info!(
+    "user {} listed view {} in the template marketplace",
+    user_uuid, params.view_id
+  );
+  get_marketplace_template(pg_pool, params.view_id).await
+}
+
+pub async fn get_marketplace_templates(
+  pg_pool: &PgPool,
+  query: &GetMarketplaceTemplatesQueryParams,
+) -> Result<MarketplaceTemplates, AppError> {
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_MARKETPLACE_PAGE_SIZE)
+    .clamp(1, MAX_MARKETPLACE_PAGE_SIZE);
+  let offset = query.offset.unwrap_or(0);
+  let mut templates = select_marketplace_templates(
+    pg_pool,
+    query.category_id,
+    query.name_contains.as_deref(),
+    query.sort,
+    offset as i64,
+    limit as i64 + 1,
+  )
+  .await?;
+  let next_offset = if templates.len() > limit as usize {
+    templates.truncate(limit as usize);
+    Some(offset + limit)
+  } else {
+    None
+  };
+
+  let view_ids: Vec<Uuid> = templates.iter().map(|t| t.view_id).collect();
+  let mut publish_info_map: HashMap<Uuid, PublishInfo> =
+    select_published_collab_info_for_view_ids(pg_pool, &view_ids)
+      .await?
+      .into_iter()
+      .map(|info| (info.view_id, info))
+      .collect();
+  let templates = templates
+    .into_iter()
+    .filter_map(|template| {
+      publish_info_map
+        .remove(&template.view_id)
+        .map(|publish_info| MarketplaceTemplateWithPublishInfo {
+          template,
+          publish_info,
+        })
+    })
+    .collect();
+  Ok(MarketplaceTemplates {
+    templates,
+    next_offset,
+  })
+}
+
+pub async fn get_marketplace_template(
+  pg_pool: &PgPool,
+  view_id: Uuid,
+) -> Result<MarketplaceTemplateWithPublishInfo, AppError> {
+  let template = select_marketplace_template(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("Template {} is not in the marketplace", view_id))
+    })?;
+  let publish_info = select_published_collab_info(pg_pool, &view_id).await?;
+  Ok(MarketplaceTemplateWithPublishInfo {
+    template,
+    publish_info,
+  })
+}
+
+/// Removes the view from the marketplace. The view stays published.
+pub async fn unpublish_marketplace_template(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  view_id: Uuid,
+) -> Result<(), AppError> {
+  let workspace_id = select_marketplace_template_workspace_id(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("Template {} is not in the marketplace", view_id))
+    })?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, &workspace_id, &[view_id]).await?;
+  delete_marketplace_template(pg_pool, view_id).await?;
+  Ok(())
+}
+
+/// Duplicates the published view of the template into the workspace, under the view, and counts
+/// the install.
+pub async fn install_marketplace_template(
+  pg_pool: &PgPool,
+  bucket_client: AwsS3BucketClientImpl,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  uid: i64,
+  view_id: Uuid,
+  params: InstallMarketplaceTemplateParams,
+) -> Result<(), AppError> {
+  if select_marketplace_template_workspace_id(pg_pool, view_id)
+    .await?
+    .is_none()
+  {
+    return Err(AppError::RecordNotFound(format!(
+      "Template {} is not in the marketplace",
+      view_id
+    )));
+  }
+  duplicate_published_collab_to_workspace(
+    pg_pool,
+    bucket_client,
+    collab_storage,
+    uid,
+    view_id.to_string(),
+    params.workspace_id.to_string(),
+    params.dest_view_id,
+  )
+  .await?;
+  increment_marketplace_template_install_count(pg_pool, view_id).await?;
+  Ok(())
+}
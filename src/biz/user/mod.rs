@@ -1,4 +1,10 @@
+pub mod user_access_token;
+pub mod user_block;
 pub mod user_delete;
+pub mod user_device;
+pub mod user_email;
 pub mod user_info;
+pub mod user_merge;
+pub mod user_provision;
 pub mod user_init;
 pub mod user_verify;
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use access_control::act::Action;
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use async_trait::async_trait;
+use authentication::access_token::{
+  generate_access_token, hash_access_token, AccessTokenGrant, AccessTokenVerifier,
+};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use database::user_access_token::{
+  count_user_access_tokens, insert_user_access_token, revoke_user_access_token,
+  select_access_token_grant, select_user_access_tokens, update_access_token_last_used,
+};
+use database_entity::dto::{AFCreatedAccessToken, AFUserAccessTokens, CreateAccessTokenParams};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+/// How long the verified access tokens are cached. A token revoked on another server can still be
+/// used on this one for that long.
+const ACCESS_TOKEN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+const MAX_ACCESS_TOKENS_PER_USER: i64 = 50;
+
+const MAX_ACCESS_TOKEN_NAME_LENGTH: usize = 100;
+
+/// Verifies the personal access tokens against the database. The tokens are cached for a minute,
+/// so the last time a token was used is only written once a minute at most.
+#[derive(Clone)]
+pub struct AccessTokenStore {
+  pg_pool: PgPool,
+  cache: Arc<DashMap<String, CachedAccessToken>>,
+}
+
+struct CachedAccessToken {
+  grant: AccessTokenGrant,
+  expires_at: Option<DateTime<Utc>>,
+  cached_at: Instant,
+}
+
+impl AccessTokenStore {
+  pub fn new(pg_pool: PgPool) -> Self {
+    Self {
+      pg_pool,
+      cache: Arc::new(DashMap::new()),
+    }
+  }
+
+  fn cached_grant(&self, token_hash: &str) -> Option<AccessTokenGrant> {
+    let cached = self.cache.get(token_hash)?;
+    let is_expired = matches!(cached.expires_at, Some(expires_at) if expires_at <= Utc::now());
+    if is_expired || cached.cached_at.elapsed() >= ACCESS_TOKEN_CACHE_TTL {
+      return None;
+    }
+    Some(cached.grant.clone())
+  }
+
+  fn forget(&self, token_id: &Uuid) {
+    self
+      .cache
+      .retain(|_, cached| cached.grant.token_id != *token_id);
+  }
+}
+
+#[async_trait]
+impl AccessTokenVerifier for AccessTokenStore {
+  async fn verify(&self, token: &str) -> Result<AccessTokenGrant, actix_web::Error> {
+    let token_hash = hash_access_token(token);
+    if let Some(grant) = self.cached_grant(&token_hash) {
+      return Ok(grant);
+    }
+    self.cache.remove(&token_hash);
+    let row = select_access_token_grant(&self.pg_pool, &token_hash)
+      .await
+      .map_err(AppResponseError::from)?
+      .ok_or(actix_web::error::ErrorUnauthorized(
+        "The access token is invalid, revoked or expired",
+      ))?;
+    if let Err(err) = update_access_token_last_used(&self.pg_pool, &row.id).await {
+      error!(
+        "Failed to record the use of access token {}: {}",
+        row.id, err
+      );
+    }
+    let grant = AccessTokenGrant {
+      token_id: row.id,
+      user_uuid: row.user_uuid,
+      read_only: row.read_only,
+      workspace_ids: row.workspace_ids,
+    };
+    self.cache.insert(
+      token_hash,
+      CachedAccessToken {
+        grant: grant.clone(),
+        expires_at: row.expires_at,
+        cached_at: Instant::now(),
+      },
+    );
+    Ok(grant)
+  }
+}
+
+/// Issues a personal access token. The token can only be limited to the workspaces the user can
+/// read.
+pub async fn create_access_token(
+  pg_pool: &PgPool,
+  workspace_access_control: &Arc<dyn WorkspaceAccessControl>,
+  uid: i64,
+  params: CreateAccessTokenParams,
+) -> Result<AFCreatedAccessToken, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() || name.chars().count() > MAX_ACCESS_TOKEN_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The name of the access token must be 1 to {} characters long",
+      MAX_ACCESS_TOKEN_NAME_LENGTH
+    )));
+  }
+  if matches!(params.expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The access token must expire in the future".to_string(),
+    ));
+  }
+  if let Some(workspace_ids) = &params.workspace_ids {
+    if workspace_ids.is_empty() {
+      return Err(AppError::InvalidRequest(
+        "The access token must be limited to at least one workspace".to_string(),
+      ));
+    }
+    for workspace_id in workspace_ids {
+      workspace_access_control
+        .enforce_action(&uid, &workspace_id.to_string(), Action::Read)
+        .await?;
+    }
+  }
+  if count_user_access_tokens(pg_pool, uid).await? >= MAX_ACCESS_TOKENS_PER_USER {
+    return Err(AppError::InvalidRequest(format!(
+      "A user can't have more than {} access tokens",
+      MAX_ACCESS_TOKENS_PER_USER
+    )));
+  }
+
+  let token = generate_access_token();
+  let row = insert_user_access_token(
+    pg_pool,
+    uid,
+    name,
+    &hash_access_token(&token),
+    params.read_only,
+    params.workspace_ids.as_deref(),
+    params.expires_at,
+  )
+  .await?;
+  Ok(AFCreatedAccessToken {
+    token,
+    access_token: row.into(),
+  })
+}
+
+pub async fn get_access_tokens(pg_pool: &PgPool, uid: i64) -> Result<AFUserAccessTokens, AppError> {
+  let access_tokens = select_user_access_tokens(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(AFUserAccessTokens { access_tokens })
+}
+
+pub async fn revoke_access_token(
+  pg_pool: &PgPool,
+  store: &AccessTokenStore,
+  uid: i64,
+  token_id: &Uuid,
+) -> Result<(), AppError> {
+  if !revoke_user_access_token(pg_pool, uid, token_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Access token {} not found",
+      token_id
+    )));
+  }
+  store.forget(token_id);
+  Ok(())
+}
@@ -0,0 +1,49 @@
+use app_error::AppError;
+use database::user::select_uid_from_uuid;
+use database::user_block::{delete_user_block, insert_user_block, select_blocked_users};
+use database_entity::dto::BlockedUsers;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn get_blocked_users(pg_pool: &PgPool, uid: i64) -> Result<BlockedUsers, AppError> {
+  let users = select_blocked_users(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(BlockedUsers { users })
+}
+
+/// Blocking a user that is already blocked does nothing.
+pub async fn block_user(
+  pg_pool: &PgPool,
+  uid: i64,
+  user_uuid: &Uuid,
+  blocked_uuid: &Uuid,
+) -> Result<(), AppError> {
+  if user_uuid == blocked_uuid {
+    return Err(AppError::InvalidRequest(
+      "Users can't block themselves".to_string(),
+    ));
+  }
+  let blocked_uid = select_uid_from_uuid(pg_pool, blocked_uuid)
+    .await
+    .map_err(|err| match err {
+      AppError::RecordNotFound(_) => {
+        AppError::RecordNotFound(format!("Can't find the user: {}", blocked_uuid))
+      },
+      err => err,
+    })?;
+  insert_user_block(pg_pool, uid, blocked_uid).await?;
+  Ok(())
+}
+
+pub async fn unblock_user(pg_pool: &PgPool, uid: i64, blocked_uuid: &Uuid) -> Result<(), AppError> {
+  if !delete_user_block(pg_pool, uid, blocked_uuid).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "User {} is not blocked",
+      blocked_uuid
+    )));
+  }
+  Ok(())
+}
@@ -1,10 +1,8 @@
-use std::sync::Arc;
-
+use crate::biz::workspace::residency::StorageRouter;
 use crate::state::GoTrueAdmin;
 use crate::{biz::workspace::ops::delete_workspace_for_user, config::config::AppleOAuthSetting};
 use app_error::ErrorCode;
 use authentication::jwt::Authorization;
-use database::file::s3_client_impl::S3BucketStorage;
 use database::workspace::select_user_owned_workspaces_id;
 use gotrue::params::AdminDeleteUserParams;
 use secrecy::{ExposeSecret, Secret};
@@ -14,7 +12,7 @@ use uuid::Uuid;
 #[allow(clippy::too_many_arguments)]
 pub async fn delete_user(
   pg_pool: &sqlx::PgPool,
-  bucket_storage: &Arc<S3BucketStorage>,
+  storage_router: &StorageRouter,
   gotrue_client: &gotrue::api::Client,
   gotrue_admin: &GoTrueAdmin,
   apple_oauth: &AppleOAuthSetting,
@@ -56,7 +54,7 @@ pub async fn delete_user(
     tasks.push(tokio::spawn(delete_workspace_for_user(
       cloned_pg_pool,
       workspace_id,
-      bucket_storage.clone(),
+      storage_router.clone(),
     )));
   }
   for task in tasks {
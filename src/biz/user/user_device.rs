@@ -0,0 +1,124 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use app_error::AppError;
+use chrono::DateTime;
+use dashmap::DashMap;
+use database::user_device::{
+  revoke_user_device, select_user_device_revoked_at, select_user_devices, upsert_user_device,
+};
+use database_entity::dto::AFUserDevices;
+use sqlx::PgPool;
+use tracing::error;
+
+/// How often the last time a device was seen is written, at most.
+const DEVICE_SEEN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The longest platform name that is recorded, the longer ones are truncated.
+const MAX_PLATFORM_LENGTH: usize = 64;
+
+/// Records the devices the users connect from. The realtime messages are sent often, so the last
+/// time a device sent one is only written once a minute at most.
+#[derive(Clone, Default)]
+pub struct UserDeviceTracker {
+  last_recorded: Arc<DashMap<(i64, String), Instant>>,
+}
+
+impl UserDeviceTracker {
+  /// Records the websocket connection of the device. A revoked device can only connect again with
+  /// an access token issued after its revocation, i.e. once the user signed in again on it.
+  pub async fn record_connect(
+    &self,
+    pg_pool: &PgPool,
+    uid: i64,
+    device_id: &str,
+    platform: Option<&str>,
+    client_version: &str,
+    token_issued_at: Option<i64>,
+  ) -> Result<(), AppError> {
+    if let Some(revoked_at) = select_user_device_revoked_at(pg_pool, uid, device_id).await? {
+      let issued_at = token_issued_at.and_then(|iat| DateTime::from_timestamp(iat, 0));
+      if !matches!(issued_at, Some(issued_at) if issued_at > revoked_at) {
+        return Err(AppError::UserUnAuthorized(
+          "The device was signed out, sign in again to connect".to_string(),
+        ));
+      }
+    }
+    let platform = platform.map(|platform| truncate(platform.trim(), MAX_PLATFORM_LENGTH));
+    upsert_user_device(
+      pg_pool,
+      uid,
+      device_id,
+      platform.filter(|platform| !platform.is_empty()),
+      Some(client_version),
+      true,
+    )
+    .await?;
+    self
+      .last_recorded
+      .insert((uid, device_id.to_string()), Instant::now());
+    Ok(())
+  }
+
+  /// Records that the device sent a realtime message with the message stream. The failures are
+  /// only logged, they shouldn't reject the message.
+  pub async fn record_message(&self, pg_pool: &PgPool, uid: i64, device_id: &str) {
+    if device_id.is_empty() {
+      return;
+    }
+    let key = (uid, device_id.to_string());
+    let now = Instant::now();
+    let is_due = match self.last_recorded.get(&key) {
+      Some(recorded_at) => now.duration_since(*recorded_at) >= DEVICE_SEEN_INTERVAL,
+      None => true,
+    };
+    if !is_due {
+      return;
+    }
+    self.last_recorded.insert(key, now);
+    if let Err(err) = upsert_user_device(pg_pool, uid, device_id, None, None, false).await {
+      error!(
+        "Failed to record the device {} of user {}: {}",
+        device_id, uid, err
+      );
+    }
+  }
+
+  fn forget(&self, uid: i64, device_id: &str) {
+    self.last_recorded.remove(&(uid, device_id.to_string()));
+  }
+}
+
+pub async fn get_user_devices(pg_pool: &PgPool, uid: i64) -> Result<AFUserDevices, AppError> {
+  let devices = select_user_devices(pg_pool, uid)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(AFUserDevices { devices })
+}
+
+/// Revokes the device, it can't connect to the realtime server until the user signs in again on
+/// it. The live connections of the device are closed by the caller.
+pub async fn revoke_device(
+  pg_pool: &PgPool,
+  tracker: &UserDeviceTracker,
+  uid: i64,
+  device_id: &str,
+) -> Result<(), AppError> {
+  if !revoke_user_device(pg_pool, uid, device_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Device {} not found",
+      device_id
+    )));
+  }
+  tracker.forget(uid, device_id);
+  Ok(())
+}
+
+fn truncate(value: &str, max_length: usize) -> &str {
+  match value.char_indices().nth(max_length) {
+    Some((index, _)) => &value[..index],
+    None => value,
+  }
+}
@@ -0,0 +1,82 @@
+use std::ops::DerefMut;
+
+use anyhow::Context;
+use app_error::AppError;
+use database::user::{
+  select_email_from_user_uuid, select_uid_from_email, update_pending_invitations_email,
+  update_user_email,
+};
+use gotrue_entity::dto::{UpdateGotrueUserParams, User};
+use shared_entity::dto::auth_dto::EmailChangeStatus;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+/// Asks GoTrue to change the email of the user. GoTrue sends a confirmation link to the new email,
+/// and the email is only changed once the user confirms it. When the confirmation isn't required,
+/// the email is changed right away.
+pub async fn change_user_email(
+  pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  access_token: &str,
+  uid: i64,
+  email: &str,
+) -> Result<EmailChangeStatus, AppError> {
+  let email = email.trim();
+  if email.is_empty() {
+    return Err(AppError::InvalidRequest("The email is empty".to_string()));
+  }
+  match select_uid_from_email(pg_pool, email).await {
+    Ok(email_uid) if email_uid != uid => {
+      return Err(AppError::RecordAlreadyExists(format!(
+        "The email {} is used by another account, merge the accounts instead",
+        email
+      )));
+    },
+    Ok(_) | Err(AppError::RecordNotFound(_)) => {},
+    Err(err) => return Err(err),
+  }
+
+  let user = gotrue_client
+    .update_user(
+      access_token,
+      &UpdateGotrueUserParams::new().with_opt_email(Some(email)),
+    )
+    .await?;
+  sync_user_email(pg_pool, &user).await
+}
+
+/// Copies the email of the GoTrue user into the user table, once GoTrue confirmed it. The pending
+/// invitations sent to the previous email are readdressed to the new one.
+pub async fn sync_user_email(pg_pool: &PgPool, user: &User) -> Result<EmailChangeStatus, AppError> {
+  let user_uuid = Uuid::parse_str(&user.id)?;
+  let status = EmailChangeStatus {
+    email: user.email.clone(),
+    new_email: user.new_email.clone().filter(|email| !email.is_empty()),
+  };
+  if user.email.is_empty() {
+    return Ok(status);
+  }
+
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to sync the user email")?;
+  let old_email = select_email_from_user_uuid(txn.deref_mut(), &user_uuid).await?;
+  if old_email.eq_ignore_ascii_case(&user.email) {
+    return Ok(status);
+  }
+
+  update_user_email(txn.deref_mut(), &user_uuid, &user.email).await?;
+  let invitation_count =
+    update_pending_invitations_email(txn.deref_mut(), &old_email, &user.email).await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit transaction to sync the user email")?;
+  info!(
+    "user {} changed the email, {} pending invitations readdressed",
+    user_uuid, invitation_count
+  );
+  Ok(status)
+}
@@ -1,3 +1,4 @@
+use crate::biz::user::user_email::sync_user_email;
 use anyhow::Context;
 use app_error::AppError;
 use database::workspace::{select_all_user_workspaces, select_user_profile, select_workspace};
@@ -66,11 +67,23 @@ pub async fn get_user_workspace_info(
   })
 }
 
+/// The email isn't taken from the params, GoTrue is the source of truth for the email, which is
+/// only changed once the user confirmed the new email.
 pub async fn update_user(
   pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  access_token: &str,
   user_uuid: Uuid,
   params: UpdateUserParams,
 ) -> anyhow::Result<(), AppResponseError> {
   let metadata = params.metadata.map(|m| json!(m.into_inner()));
-  Ok(database::user::update_user(pg_pool, &user_uuid, params.name, params.email, metadata).await?)
+  database::user::update_user(pg_pool, &user_uuid, params.name, None, metadata).await?;
+  if params.email.is_some() {
+    let user = gotrue_client
+      .user_info(access_token)
+      .await
+      .map_err(AppError::from)?;
+    sync_user_email(pg_pool, &user).await?;
+  }
+  Ok(())
 }
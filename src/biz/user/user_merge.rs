@@ -0,0 +1,106 @@
+use anyhow::Context;
+use app_error::AppError;
+use database::account_merge::merge_user_accounts;
+use gotrue::params::AdminDeleteUserParams;
+use shared_entity::dto::auth_dto::MergeAccountSummary;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Merges the account signed in with the source access token into the account of the current user.
+/// Both access tokens are checked with GoTrue, so the user must be able to sign in to both accounts.
+///
+/// The workspace memberships, comments and owned content of the source account are moved to the
+/// current account, then the source account is deleted.
+pub async fn merge_user_account(
+  state: &AppState,
+  access_token: &str,
+  source_access_token: &str,
+) -> Result<MergeAccountSummary, AppError> {
+  let target_user = state.gotrue_client.user_info(access_token).await?;
+  let source_user = state
+    .gotrue_client
+    .user_info(source_access_token)
+    .await
+    .map_err(|err| {
+      AppError::UserUnAuthorized(format!(
+        "Can't verify the account to merge: {}",
+        AppError::from(err)
+      ))
+    })?;
+  let target_uuid = Uuid::parse_str(&target_user.id)?;
+  let source_uuid = Uuid::parse_str(&source_user.id)?;
+  if target_uuid == source_uuid {
+    return Err(AppError::InvalidRequest(
+      "Can't merge an account into itself".to_string(),
+    ));
+  }
+  let target_uid = state.user_cache.get_user_uid(&target_uuid).await?;
+  let source_uid = state.user_cache.get_user_uid(&source_uuid).await?;
+
+  let mut txn = state
+    .pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to merge the user accounts")?;
+  let merged = merge_user_accounts(&mut txn, source_uid, target_uid).await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit transaction to merge the user accounts")?;
+
+  for (workspace_id, role) in &merged.workspace_roles {
+    state
+      .workspace_access_control
+      .insert_role(&target_uid, workspace_id, role.clone())
+      .await?;
+    state
+      .workspace_access_control
+      .remove_user_from_workspace(&source_uid, workspace_id)
+      .await?;
+  }
+  for (oid, access_level) in &merged.collab_access_levels {
+    state
+      .collab_access_control
+      .update_access_level_policy(&target_uid, oid, *access_level)
+      .await?;
+    state
+      .collab_access_control
+      .remove_access_level(&source_uid, oid)
+      .await?;
+  }
+
+  // The source account is empty at this point, deleting it can't lose any content.
+  let admin_token = state.gotrue_admin.token().await?;
+  if let Err(err) = state
+    .gotrue_client
+    .admin_delete_user(
+      &admin_token,
+      &source_uuid.to_string(),
+      &AdminDeleteUserParams {
+        should_soft_delete: false,
+      },
+    )
+    .await
+  {
+    warn!("failed to delete the merged user {}: {}", source_uuid, err);
+  }
+  state.user_cache.remove_user(&source_uuid);
+
+  info!(
+    "user {} merged into user {}: {} workspaces, {} comments",
+    source_uuid,
+    target_uuid,
+    merged.workspace_roles.len(),
+    merged.comment_count
+  );
+  Ok(MergeAccountSummary {
+    workspace_ids: merged
+      .workspace_roles
+      .into_iter()
+      .map(|(workspace_id, _)| workspace_id)
+      .collect(),
+    comment_count: merged.comment_count,
+  })
+}
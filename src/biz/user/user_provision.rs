@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::ops::DerefMut;
+
+use anyhow::Context;
+use app_error::{AppError, ErrorDetails};
+use database::account_provision::{
+  delete_account_provision, insert_account_provision, select_account_provision,
+  update_account_provision_result,
+};
+use database::user::{create_user, is_user_exist};
+use database::workspace::{rename_workspace, select_workspace};
+use database_entity::dto::AFRole;
+use gotrue::params::AdminUserParams;
+use shared_entity::dto::server_info_dto::{ProvisionAccountParams, ProvisionedAccount};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+use workspace_template::document::getting_started::GettingStartedTemplate;
+
+use crate::biz::user::user_init::initialize_workspace_for_user;
+use crate::biz::workspace::ops::invite_workspace_members;
+use crate::state::AppState;
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
+
+/// Creates the GoTrue user, the AppFlowy user and its workspace with the getting started content,
+/// then invites the other users to the workspace. The idempotency key is claimed before anything
+/// is created, and released if the provisioning fails, so the request can be retried safely.
+#[instrument(level = "info", skip_all, fields(email = %params.email), err)]
+pub async fn provision_account(
+  state: &AppState,
+  params: ProvisionAccountParams,
+) -> Result<ProvisionedAccount, AppError> {
+  let idempotency_key = params.idempotency_key.trim().to_string();
+  if idempotency_key.is_empty() || idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "The idempotency key must be between 1 and {} characters",
+        MAX_IDEMPOTENCY_KEY_LEN
+      ))
+      .with_details(ErrorDetails::field("idempotency_key", "length")),
+    );
+  }
+  if !validator::validate_email(&params.email) {
+    return Err(
+      AppError::InvalidEmail(format!("Invalid email: {}", params.email))
+        .with_details(ErrorDetails::field("email", "email")),
+    );
+  }
+  if let Some(invitation) = params
+    .invitations
+    .iter()
+    .find(|invitation| !validator::validate_email(&invitation.email))
+  {
+    return Err(
+      AppError::InvalidEmail(format!("Invalid email: {}", invitation.email))
+        .with_details(ErrorDetails::field("invitations", "email")),
+    );
+  }
+
+  if !insert_account_provision(&state.pg_pool, &idempotency_key, &params.email).await? {
+    return provisioned_account_of_key(state, &idempotency_key, &params.email).await;
+  }
+
+  match provision_new_account(state, params).await {
+    Ok(account) => {
+      update_account_provision_result(
+        &state.pg_pool,
+        &idempotency_key,
+        serde_json::to_value(&account)?,
+      )
+      .await?;
+      Ok(account)
+    },
+    Err(err) => {
+      if let Err(release_err) = delete_account_provision(&state.pg_pool, &idempotency_key).await {
+        warn!(
+          "Failed to release the idempotency key {}: {}",
+          idempotency_key, release_err
+        );
+      }
+      Err(err)
+    },
+  }
+}
+
+async fn provisioned_account_of_key(
+  state: &AppState,
+  idempotency_key: &str,
+  email: &str,
+) -> Result<ProvisionedAccount, AppError> {
+  let row = select_account_provision(&state.pg_pool, idempotency_key)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "The provisioning request {} was released, retry it",
+        idempotency_key
+      ))
+    })?;
+  if row.email != email {
+    return Err(AppError::InvalidRequest(format!(
+      "The idempotency key {} was used to provision another account",
+      idempotency_key
+    )));
+  }
+  match row.result {
+    Some(result) => Ok(serde_json::from_value(result)?),
+    None => Err(AppError::RecordAlreadyExists(format!(
+      "The provisioning request {} is in progress",
+      idempotency_key
+    ))),
+  }
+}
+
+async fn provision_new_account(
+  state: &AppState,
+  params: ProvisionAccountParams,
+) -> Result<ProvisionedAccount, AppError> {
+  let admin_token = state.gotrue_admin.token().await?;
+  let name = params.name.unwrap_or_default();
+  let user_uuid = match state
+    .gotrue_client
+    .admin_list_user(&admin_token, Some(&params.email))
+    .await?
+    .users
+    .into_iter()
+    .find(|user| user.email == params.email)
+  {
+    // The user may have been created in GoTrue by a previous request that failed afterwards.
+    Some(user) => Uuid::parse_str(&user.id)?,
+    None => {
+      let user_metadata = BTreeMap::from([("name".to_string(), serde_json::json!(name))]);
+      let user = state
+        .gotrue_client
+        .admin_add_user(
+          &admin_token,
+          &AdminUserParams {
+            email: params.email.clone(),
+            password: params.password,
+            email_confirm: true,
+            user_metadata,
+            ..Default::default()
+          },
+        )
+        .await?;
+      Uuid::parse_str(&user.id)?
+    },
+  };
+
+  let mut txn = state
+    .pg_pool
+    .begin()
+    .await
+    .context("acquire transaction to provision account")?;
+  if is_user_exist(txn.deref_mut(), &user_uuid).await? {
+    return Err(AppError::UserAlreadyRegistered(format!(
+      "{} already has an account",
+      params.email
+    )));
+  }
+  let uid = state.id_gen.write().await.next_id();
+  let workspace_id = create_user(txn.deref_mut(), uid, &user_uuid, &params.email, &name).await?;
+  if let Some(workspace_name) = params
+    .workspace_name
+    .as_deref()
+    .map(str::trim)
+    .filter(|workspace_name| !workspace_name.is_empty())
+  {
+    rename_workspace(&mut txn, &workspace_id, workspace_name).await?;
+  }
+  let workspace_row = select_workspace(txn.deref_mut(), &workspace_id).await?;
+  state
+    .workspace_access_control
+    .insert_role(&uid, &workspace_id, AFRole::Owner)
+    .await?;
+  initialize_workspace_for_user(
+    uid,
+    &user_uuid,
+    &workspace_row,
+    &mut txn,
+    vec![GettingStartedTemplate],
+    &state.collab_access_control_storage,
+  )
+  .await?;
+  txn
+    .commit()
+    .await
+    .context("fail to commit transaction to provision account")?;
+  info!(
+    "provisioned account {} with workspace {}",
+    uid, workspace_id
+  );
+
+  // The account is provisioned at this point, a failure to invite the other users is reported
+  // by leaving their emails out of the result.
+  let mut invited_emails = vec![];
+  if !params.invitations.is_empty() {
+    let emails = params
+      .invitations
+      .iter()
+      .map(|invitation| invitation.email.clone())
+      .collect::<Vec<_>>();
+    match invite_workspace_members(
+      &state.mailer,
+      &state.gotrue_admin,
+      &state.pg_pool,
+      &state.gotrue_client,
+      &user_uuid,
+      &workspace_id,
+      params.invitations,
+      None,
+      state.config.appflowy_web_url.as_deref(),
+    )
+    .await
+    {
+      Ok(()) => invited_emails = emails,
+      Err(err) => warn!(
+        "Failed to invite the members of the provisioned workspace {}: {}",
+        workspace_id, err
+      ),
+    }
+  }
+
+  Ok(ProvisionedAccount {
+    user_uuid,
+    uid,
+    workspace_id,
+    invited_emails,
+  })
+}
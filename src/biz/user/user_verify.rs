@@ -2,7 +2,7 @@ use std::ops::DerefMut;
 
 use anyhow::{Context, Result};
 use sqlx::types::uuid;
-use tracing::{event, instrument, trace};
+use tracing::{event, instrument, trace, warn};
 
 use app_error::AppError;
 use database::user::{create_user, is_user_exist};
@@ -10,6 +10,7 @@ use database::workspace::select_workspace;
 use database_entity::dto::AFRole;
 use workspace_template::document::getting_started::GettingStartedTemplate;
 
+use crate::biz::user::user_email::sync_user_email;
 use crate::biz::user::user_init::initialize_workspace_for_user;
 use crate::state::AppState;
 
@@ -21,19 +22,36 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
   let user = state.gotrue_client.user_info(access_token).await?;
   let user_uuid = uuid::Uuid::parse_str(&user.id)?;
   let name = name_from_user_metadata(&user.user_metadata);
+  let is_new = create_user_if_not_exist(state, &user_uuid, &user.email, &name).await?;
+  if !is_new {
+    trace!("user already exists:{},{}", user.id, user.email);
+    // The user may have confirmed a new email since the last sign in.
+    if let Err(err) = sync_user_email(&state.pg_pool, &user).await {
+      warn!("failed to sync the email of user {}: {}", user_uuid, err);
+    }
+  }
+  Ok(is_new)
+}
 
+/// Creates the AppFlowy user of a GoTrue user, along with its workspace, unless it already exists.
+/// Returns true if the user is created.
+pub async fn create_user_if_not_exist(
+  state: &AppState,
+  user_uuid: &uuid::Uuid,
+  email: &str,
+  name: &str,
+) -> Result<bool, AppError> {
   let mut txn = state
     .pg_pool
     .begin()
     .await
-    .context("acquire transaction to verify token")?;
+    .context("acquire transaction to create user")?;
 
-  let is_new = !is_user_exist(txn.deref_mut(), &user_uuid).await?;
+  let is_new = !is_user_exist(txn.deref_mut(), user_uuid).await?;
   if is_new {
     let new_uid = state.id_gen.write().await.next_id();
     event!(tracing::Level::INFO, "create new user:{}", new_uid);
-    let workspace_id =
-      create_user(txn.deref_mut(), new_uid, &user_uuid, &user.email, &name).await?;
+    let workspace_id = create_user(txn.deref_mut(), new_uid, user_uuid, email, name).await?;
     let workspace_row = select_workspace(txn.deref_mut(), &workspace_id).await?;
 
     // It's essential to cache the user's role because subsequent actions will rely on this cached information.
@@ -45,20 +63,18 @@ pub async fn verify_token(access_token: &str, state: &AppState) -> Result<bool,
     // Create a workspace with the GetStarted template
     initialize_workspace_for_user(
       new_uid,
-      &user_uuid,
+      user_uuid,
       &workspace_row,
       &mut txn,
       vec![GettingStartedTemplate],
       &state.collab_access_control_storage,
     )
     .await?;
-  } else {
-    trace!("user already exists:{},{}", user.id, user.email);
   }
   txn
     .commit()
     .await
-    .context("fail to commit transaction to verify token")?;
+    .context("fail to commit transaction to create user")?;
   Ok(is_new)
 }
 
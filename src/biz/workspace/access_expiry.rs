@@ -0,0 +1,296 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use access_control::collab::CollabAccessControl;
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database::listener::notify_listeners;
+use database::member_expiry::{
+  delete_expired_collab_members, delete_expired_workspace_members, select_expiring_members,
+  update_member_expiry_notified,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::biz::pg_listener::ACCESS_GRANT_CHANGE_CHANNEL;
+use crate::biz::scheduler::Scheduler;
+use crate::biz::workspace::permission_audit::{
+  record_collab_member_change, record_workspace_permission_change,
+};
+use crate::mailer::{AFCloudMailer, WorkspaceAccessExpiringMailerParam};
+
+/// How often the expired grants are removed and the expiring grants are notified.
+const ACCESS_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The grantee is notified once their access expires within this period.
+const ACCESS_EXPIRING_NOTIFY_HOURS: i64 = 24;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AccessGrant {
+  Workspace(Uuid),
+  Collab(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AccessGrantChangeKind {
+  /// The expiry time of the grant changed, `None` once the grant is permanent.
+  ExpiryUpdated(Option<DateTime<Utc>>),
+  /// The grant expired and was removed.
+  Expired,
+}
+
+/// The access control policies are held in memory by every server, the changes of the time-boxed
+/// grants are sent to all of them so that they expire the grants at the same time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AccessGrantChange {
+  pub uid: i64,
+  pub grant: AccessGrant,
+  pub kind: AccessGrantChangeKind,
+}
+
+/// Sends the new expiry time of the grant to the other servers. The change has already been
+/// saved when it's notified, so a failure is logged instead of failing the request.
+pub async fn notify_grant_expiry_change(
+  pg_pool: &PgPool,
+  uid: i64,
+  grant: AccessGrant,
+  expires_at: Option<DateTime<Utc>>,
+) {
+  notify_access_grant_change(
+    pg_pool,
+    AccessGrantChange {
+      uid,
+      grant,
+      kind: AccessGrantChangeKind::ExpiryUpdated(expires_at),
+    },
+  )
+  .await;
+}
+
+async fn notify_access_grant_change(pg_pool: &PgPool, change: AccessGrantChange) {
+  let result = match serde_json::to_string(&change) {
+    Ok(payload) => notify_listeners(pg_pool, ACCESS_GRANT_CHANGE_CHANNEL, &payload).await,
+    Err(err) => Err(err.into()),
+  };
+  if let Err(err) = result {
+    error!(
+      "Failed to notify the access grant change {:?}: {:?}",
+      change, err
+    );
+  }
+}
+
+/// Applies the grant changes made by any server to the access control policies of this server.
+pub fn spawn_listen_on_access_grant_change(
+  mut listener: broadcast::Receiver<AccessGrantChange>,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+) {
+  tokio::spawn(async move {
+    loop {
+      let change = match listener.recv().await {
+        Ok(change) => change,
+        Err(RecvError::Lagged(skipped)) => {
+          warn!("Skip {} access grant changes", skipped);
+          continue;
+        },
+        Err(RecvError::Closed) => break,
+      };
+      let result = match (&change.grant, &change.kind) {
+        (
+          AccessGrant::Workspace(workspace_id),
+          AccessGrantChangeKind::ExpiryUpdated(expires_at),
+        ) => {
+          workspace_access_control
+            .update_role_expiry(&change.uid, workspace_id, *expires_at)
+            .await
+        },
+        (AccessGrant::Workspace(workspace_id), AccessGrantChangeKind::Expired) => {
+          workspace_access_control
+            .remove_user_from_workspace(&change.uid, workspace_id)
+            .await
+        },
+        (AccessGrant::Collab(oid), AccessGrantChangeKind::ExpiryUpdated(expires_at)) => {
+          collab_access_control
+            .update_access_level_expiry(&change.uid, oid, *expires_at)
+            .await
+        },
+        (AccessGrant::Collab(oid), AccessGrantChangeKind::Expired) => {
+          collab_access_control
+            .remove_access_level(&change.uid, oid)
+            .await
+        },
+      };
+      if let Err(err) = result {
+        error!(
+          "Failed to apply the access grant change {:?}: {:?}",
+          change, err
+        );
+      }
+    }
+  });
+}
+
+/// Periodically removes the workspace and collab grants that have expired, and notifies the
+/// grantees whose access is about to expire.
+///
+/// Expired grants are already rejected when the permission is checked, this job makes sure the
+/// expired grants don't linger in the database and in the access control policies of every
+/// server. The scheduler runs it on a single server of the deployment.
+pub fn register_access_expiry_job(
+  scheduler: &Scheduler,
+  pg_pool: PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  mailer: AFCloudMailer,
+  appflowy_web_url: Option<String>,
+) {
+  scheduler.register("access_expiry", ACCESS_EXPIRY_CHECK_INTERVAL, move || {
+    let pg_pool = pg_pool.clone();
+    let workspace_access_control = workspace_access_control.clone();
+    let collab_access_control = collab_access_control.clone();
+    let mailer = mailer.clone();
+    let appflowy_web_url = appflowy_web_url.clone();
+    async move {
+      let removed = remove_expired_grants(
+        &pg_pool,
+        workspace_access_control.as_ref(),
+        collab_access_control.as_ref(),
+      )
+      .await;
+      if let Err(err) = &removed {
+        error!("Failed to remove expired access grants: {:?}", err);
+      }
+      // The expiring grants are notified even if the expired ones couldn't be removed
+      let notified = notify_expiring_grants(&pg_pool, &mailer, appflowy_web_url.as_deref()).await;
+      removed.and(notified)
+    }
+  });
+}
+
+async fn remove_expired_grants(
+  pg_pool: &PgPool,
+  workspace_access_control: &dyn WorkspaceAccessControl,
+  collab_access_control: &dyn CollabAccessControl,
+) -> Result<(), AppError> {
+  let expired_workspace_members = delete_expired_workspace_members(pg_pool).await?;
+  for member in &expired_workspace_members {
+    workspace_access_control
+      .remove_user_from_workspace(&member.uid, &member.workspace_id)
+      .await?;
+    notify_access_grant_change(
+      pg_pool,
+      AccessGrantChange {
+        uid: member.uid,
+        grant: AccessGrant::Workspace(member.workspace_id),
+        kind: AccessGrantChangeKind::Expired,
+      },
+    )
+    .await;
+    record_workspace_permission_change(
+      pg_pool,
+      &member.workspace_id,
+      None,
+      member.uid,
+      Some(&member.role),
+      None,
+    )
+    .await;
+  }
+
+  let expired_collab_members = delete_expired_collab_members(pg_pool).await?;
+  for member in &expired_collab_members {
+    collab_access_control
+      .remove_access_level(&member.uid, &member.oid)
+      .await?;
+    notify_access_grant_change(
+      pg_pool,
+      AccessGrantChange {
+        uid: member.uid,
+        grant: AccessGrant::Collab(member.oid.clone()),
+        kind: AccessGrantChangeKind::Expired,
+      },
+    )
+    .await;
+    record_collab_member_change(
+      pg_pool,
+      None,
+      member.uid,
+      &member.oid,
+      Some(member.access_level),
+      None,
+    )
+    .await;
+  }
+
+  if !expired_workspace_members.is_empty() || !expired_collab_members.is_empty() {
+    info!(
+      "Removed {} expired workspace members and {} expired collab members",
+      expired_workspace_members.len(),
+      expired_collab_members.len()
+    );
+  }
+  Ok(())
+}
+
+async fn notify_expiring_grants(
+  pg_pool: &PgPool,
+  mailer: &AFCloudMailer,
+  appflowy_web_url: Option<&str>,
+) -> Result<(), AppError> {
+  let expires_before = chrono::Utc::now() + chrono::Duration::hours(ACCESS_EXPIRING_NOTIFY_HOURS);
+  let expiring_members = select_expiring_members(pg_pool, expires_before).await?;
+  for member in expiring_members {
+    // Mark the grant as notified before sending the email, so that the grantee won't be notified
+    // repeatedly if the email can't be delivered.
+    update_member_expiry_notified(
+      pg_pool,
+      &member.uid,
+      &member.workspace_id,
+      member.object_id.as_deref(),
+    )
+    .await?;
+
+    let launch_workspace_url = match &member.object_id {
+      None => format!(
+        "{}/app/{}",
+        appflowy_web_url.unwrap_or_default(),
+        member.workspace_id
+      ),
+      Some(object_id) => format!(
+        "{}/app/{}/{}",
+        appflowy_web_url.unwrap_or_default(),
+        member.workspace_id,
+        object_id
+      ),
+    };
+    // use default icon until we have workspace icon
+    let workspace_icon_url =
+      "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
+    let param = WorkspaceAccessExpiringMailerParam {
+      workspace_name: member.workspace_name.unwrap_or_default(),
+      workspace_icon_url,
+      workspace_member_count: member.member_count,
+      expires_at: member.expires_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+      launch_workspace_url,
+    };
+    let mailer = mailer.clone();
+    tokio::spawn(async move {
+      if let Err(err) = mailer
+        .send_workspace_access_expiring_notification(&member.name, &member.email, param)
+        .await
+      {
+        error!(
+          "Failed to send access expiring notification email: {:?}",
+          err
+        );
+      }
+    });
+  }
+  Ok(())
+}
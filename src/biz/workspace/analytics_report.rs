@@ -0,0 +1,133 @@
+use app_error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use database::insights::{select_comment_trend, select_member_activity, select_page_growth};
+use serde_json::{json, Value};
+use shared_entity::dto::workspace_dto::{
+  AnalyticsReport, AnalyticsReportData, QueryAnalyticsReport,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// The range of a report when `since` is missing.
+const DEFAULT_REPORT_DAYS: i64 = 30;
+
+/// The longest range a report can cover.
+const MAX_REPORT_DAYS: i64 = 366;
+
+/// Runs the report on the rows of the workspace only. The pool should be a read replica, since the
+/// reports scan the activity of the whole range.
+pub async fn run_analytics_report(
+  pg_read_pool: &PgPool,
+  workspace_id: &Uuid,
+  report: AnalyticsReport,
+  query: &QueryAnalyticsReport,
+) -> Result<AnalyticsReportData, AppError> {
+  let until = query.until.unwrap_or_else(Utc::now);
+  let since = query
+    .since
+    .unwrap_or_else(|| until - Duration::days(DEFAULT_REPORT_DAYS));
+  check_report_range(since, until)?;
+  let interval = query.interval.unwrap_or_default();
+
+  let (columns, rows): (&[&str], Vec<Vec<Value>>) = match report {
+    AnalyticsReport::ActivityByMember => (
+      &[
+        "uid",
+        "name",
+        "email",
+        "edit_count",
+        "edited_page_count",
+        "last_active_date",
+      ],
+      select_member_activity(pg_read_pool, workspace_id, since, until)
+        .await?
+        .into_iter()
+        .map(|row| {
+          vec![
+            json!(row.uid),
+            json!(row.name),
+            json!(row.email),
+            json!(row.edit_count),
+            json!(row.edited_page_count),
+            json!(row.last_active_date),
+          ]
+        })
+        .collect(),
+    ),
+    AnalyticsReport::PageGrowth => (
+      &["period", "created_count", "total_count"],
+      select_page_growth(pg_read_pool, workspace_id, interval.as_str(), since, until)
+        .await?
+        .into_iter()
+        .map(|row| {
+          vec![
+            json!(row.period),
+            json!(row.created_count),
+            json!(row.total_count),
+          ]
+        })
+        .collect(),
+    ),
+    AnalyticsReport::CommentTrend => (
+      &["period", "comment_count", "commenter_count"],
+      select_comment_trend(pg_read_pool, workspace_id, interval.as_str(), since, until)
+        .await?
+        .into_iter()
+        .map(|row| {
+          vec![
+            json!(row.period),
+            json!(row.comment_count),
+            json!(row.commenter_count),
+          ]
+        })
+        .collect(),
+    ),
+  };
+
+  Ok(AnalyticsReportData {
+    report,
+    since,
+    until,
+    columns: columns.iter().map(|column| column.to_string()).collect(),
+    rows,
+  })
+}
+
+/// Writes the report as a CSV file, with a header row made of the columns.
+pub fn analytics_report_to_csv(data: &AnalyticsReportData) -> Result<Vec<u8>, AppError> {
+  let mut writer = csv::Writer::from_writer(vec![]);
+  writer
+    .write_record(&data.columns)
+    .map_err(|err| AppError::Internal(err.into()))?;
+  for row in &data.rows {
+    writer
+      .write_record(row.iter().map(csv_field))
+      .map_err(|err| AppError::Internal(err.into()))?;
+  }
+  writer
+    .into_inner()
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("Failed to write the report: {}", err)))
+}
+
+fn csv_field(value: &Value) -> String {
+  match value {
+    Value::Null => String::new(),
+    Value::String(value) => value.clone(),
+    value => value.to_string(),
+  }
+}
+
+fn check_report_range(since: DateTime<Utc>, until: DateTime<Utc>) -> Result<(), AppError> {
+  if since >= until {
+    return Err(AppError::InvalidRequest(
+      "The start of the range must be before its end".to_string(),
+    ));
+  }
+  if until - since > Duration::days(MAX_REPORT_DAYS) {
+    return Err(AppError::InvalidRequest(format!(
+      "A report can cover at most {} days",
+      MAX_REPORT_DAYS
+    )));
+  }
+  Ok(())
+}
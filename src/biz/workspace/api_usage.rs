@@ -0,0 +1,140 @@
+use chrono::{Duration, Utc};
+use database::api_usage::{
+  delete_workspace_api_usage_before, select_daily_workspace_api_usage,
+  select_workspace_api_usage_by_route_family, upsert_workspace_api_usage,
+};
+use shared_entity::dto::workspace_dto::{DailyApiUsage, RouteFamilyApiUsage, WorkspaceApiUsage};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::scheduler::Scheduler;
+use crate::middleware::api_usage_mw::ApiUsageCounter;
+
+/// Number of days the API usage of the workspaces is kept for.
+pub const API_USAGE_RETENTION_DAYS: i64 = 90;
+
+/// How often the counted API calls are written to the database.
+const API_USAGE_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How often the API usage older than the retention is removed.
+const API_USAGE_CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Writes the API calls counted since the last flush to the usage of the current day.
+pub async fn flush_api_usage(
+  pg_pool: &PgPool,
+  counter: &ApiUsageCounter,
+) -> Result<(), AppResponseError> {
+  let counts = counter.take();
+  if counts.is_empty() {
+    return Ok(());
+  }
+
+  let mut workspace_ids = Vec::with_capacity(counts.len());
+  let mut route_families = Vec::with_capacity(counts.len());
+  let mut call_counts = Vec::with_capacity(counts.len());
+  let mut client_error_counts = Vec::with_capacity(counts.len());
+  let mut server_error_counts = Vec::with_capacity(counts.len());
+  for (workspace_id, route_family, count) in counts {
+    workspace_ids.push(workspace_id);
+    route_families.push(route_family);
+    call_counts.push(count.call_count);
+    client_error_counts.push(count.client_error_count);
+    server_error_counts.push(count.server_error_count);
+  }
+  upsert_workspace_api_usage(
+    pg_pool,
+    Utc::now().date_naive(),
+    &workspace_ids,
+    &route_families,
+    &call_counts,
+    &client_error_counts,
+    &server_error_counts,
+  )
+  .await?;
+  Ok(())
+}
+
+/// Periodically writes the counted API calls to the database. The calls are counted in memory by
+/// each server, so every server flushes its own counts.
+pub fn spawn_api_usage_flush_job(pg_pool: PgPool, counter: Arc<ApiUsageCounter>) {
+  tokio::spawn(async move {
+    let mut flush_interval = tokio::time::interval(API_USAGE_FLUSH_INTERVAL);
+    loop {
+      flush_interval.tick().await;
+      if let Err(err) = flush_api_usage(&pg_pool, &counter).await {
+        error!("Failed to flush the API usage: {:?}", err);
+      }
+    }
+  });
+}
+
+/// Periodically removes the API usage that is older than the retention.
+pub fn register_api_usage_cleanup_job(scheduler: &Scheduler, pg_pool: PgPool) {
+  scheduler.register("api_usage_cleanup", API_USAGE_CLEANUP_INTERVAL, move || {
+    let pg_pool = pg_pool.clone();
+    async move {
+      let before = Utc::now().date_naive() - Duration::days(API_USAGE_RETENTION_DAYS - 1);
+      let count = delete_workspace_api_usage_before(&pg_pool, before).await?;
+      if count > 0 {
+        info!("Removed {} expired API usage entries", count);
+      }
+      Ok(())
+    }
+  });
+}
+
+/// Returns the API calls made on the workspace and their error rates, by route family and by
+/// day, for the last `days` days within the retention.
+pub async fn get_workspace_api_usage(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  days: Option<i64>,
+) -> Result<WorkspaceApiUsage, AppResponseError> {
+  let days = days
+    .unwrap_or(API_USAGE_RETENTION_DAYS)
+    .clamp(1, API_USAGE_RETENTION_DAYS);
+  let since = Utc::now().date_naive() - Duration::days(days - 1);
+
+  let route_families = select_workspace_api_usage_by_route_family(pg_pool, workspace_id, since)
+    .await?
+    .into_iter()
+    .map(|row| RouteFamilyApiUsage {
+      error_rate: error_rate(
+        row.call_count,
+        row.client_error_count + row.server_error_count,
+      ),
+      route_family: row.route_family,
+      call_count: row.call_count,
+      client_error_count: row.client_error_count,
+      server_error_count: row.server_error_count,
+    })
+    .collect();
+  let days = select_daily_workspace_api_usage(pg_pool, workspace_id, since)
+    .await?
+    .into_iter()
+    .map(|row| DailyApiUsage {
+      date: row.usage_date,
+      call_count: row.call_count,
+      client_error_count: row.client_error_count,
+      server_error_count: row.server_error_count,
+    })
+    .collect();
+
+  Ok(WorkspaceApiUsage {
+    retention_days: API_USAGE_RETENTION_DAYS,
+    since,
+    route_families,
+    days,
+  })
+}
+
+fn error_rate(call_count: i64, error_count: i64) -> f64 {
+  if call_count == 0 {
+    0.0
+  } else {
+    error_count as f64 / call_count as f64
+  }
+}
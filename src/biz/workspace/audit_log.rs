@@ -0,0 +1,84 @@
+use app_error::AppError;
+use database::audit_log::{insert_workspace_audit_event, select_workspace_audit_events};
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::{
+  QueryWorkspaceAuditLog, WorkspaceAuditAction, WorkspaceAuditEvent,
+};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+const DEFAULT_AUDIT_LOG_LIMIT: i64 = 100;
+
+const MAX_AUDIT_LOG_LIMIT: i64 = 1000;
+
+/// Appends an event to the audit log of the workspace. The change has already been made when the
+/// event is recorded, so a failure is logged instead of failing the request.
+pub async fn record_audit_event(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  actor_uid: i64,
+  action: WorkspaceAuditAction,
+  target: Option<&str>,
+  details: Value,
+) {
+  let result = match serde_json::to_value(action) {
+    Ok(Value::String(action)) => {
+      insert_workspace_audit_event(pg_pool, workspace_id, actor_uid, &action, target, &details)
+        .await
+    },
+    Ok(value) => Err(AppError::Internal(anyhow::anyhow!(
+      "Unexpected audit action: {}",
+      value
+    ))),
+    Err(err) => Err(err.into()),
+  };
+  if let Err(err) = result {
+    error!(
+      "Failed to record {:?} in the audit log of workspace {}: {:?}",
+      action, workspace_id, err
+    );
+  }
+}
+
+pub async fn get_workspace_audit_log(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: QueryWorkspaceAuditLog,
+) -> Result<Vec<WorkspaceAuditEvent>, AppError> {
+  if let (Some(since), Some(until)) = (query.since, query.until) {
+    if since >= until {
+      return Err(AppError::InvalidRequest(
+        "The start of the time range must be before its end".to_string(),
+      ));
+    }
+  }
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+    .clamp(1, MAX_AUDIT_LOG_LIMIT);
+  let rows = select_workspace_audit_events(
+    pg_pool,
+    workspace_id,
+    query.since,
+    query.until,
+    query.actor_uid,
+    limit,
+  )
+  .await?;
+  rows
+    .into_iter()
+    .map(|row| {
+      Ok(WorkspaceAuditEvent {
+        event_id: row.event_id,
+        actor_uid: row.actor_uid,
+        actor_name: row.actor_name,
+        actor_email: row.actor_email,
+        action: serde_json::from_value(Value::String(row.action))?,
+        target: row.target,
+        details: row.details,
+        created_at: row.created_at,
+      })
+    })
+    .collect()
+}
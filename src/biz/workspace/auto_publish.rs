@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_entity::CollabType;
+use collab_folder::ViewLayout;
+use database::auto_publish::{
+  delete_auto_publish_view, select_auto_publish_views_to_republish,
+  update_auto_publish_view_republished, upsert_auto_publish_view,
+};
+use database::collab::GetCollabOrigin;
+use database::pg_row::AFAutoPublishViewRow;
+use database::publish::{select_published_collab_info, select_published_metadata_for_view_id};
+use database_entity::dto::{PublishCollabItem, PublishCollabMetadata, PublishInfo};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+use crate::biz::scheduler::Scheduler;
+use crate::biz::workspace::publish::{check_workspace_owner_or_publisher, PublishedCollabStore};
+use crate::config::config::PublishedCollabSetting;
+
+/// How often the changed documents are checked for republish.
+const AUTO_PUBLISH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The maximum number of documents republished on each check.
+const AUTO_PUBLISH_BATCH_SIZE: i64 = 50;
+
+/// Enables or disables the automatic republish of a published document. Only the owner of the
+/// workspace or the publisher of the document can change it.
+pub async fn set_auto_publish(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  enabled: bool,
+) -> Result<PublishInfo, AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  let (published_workspace_id, _) = select_published_metadata_for_view_id(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Published view {}", view_id)))?;
+  if published_workspace_id != *workspace_id {
+    return Err(AppError::RecordNotFound(format!(
+      "Published view {}",
+      view_id
+    )));
+  }
+
+  if enabled {
+    let folder = get_latest_collab_folder(
+      collab_storage,
+      GetCollabOrigin::Server,
+      &workspace_id.to_string(),
+    )
+    .await?;
+    let is_document = folder
+      .get_view(&view_id.to_string())
+      .map_or(false, |view| view.layout == ViewLayout::Document);
+    if !is_document {
+      return Err(AppError::InvalidRequest(
+        "Only the published documents can be republished automatically".to_string(),
+      ));
+    }
+    upsert_auto_publish_view(pg_pool, workspace_id, view_id, uid).await?;
+  } else {
+    delete_auto_publish_view(pg_pool, workspace_id, view_id).await?;
+  }
+  select_published_collab_info(pg_pool, view_id).await
+}
+
+/// Periodically republishes the auto-published documents whose changes have settled down.
+pub fn register_auto_publish_job(
+  scheduler: &Scheduler,
+  pg_pool: PgPool,
+  collab_storage: Arc<CollabAccessControlStorage>,
+  published_collab_store: Arc<dyn PublishedCollabStore>,
+  setting: &PublishedCollabSetting,
+) {
+  let debounce_secs = setting.auto_publish_debounce_secs as i64;
+  let max_delay_secs = setting.auto_publish_max_delay_secs as i64;
+  scheduler.register("auto_publish", AUTO_PUBLISH_CHECK_INTERVAL, move || {
+    let pg_pool = pg_pool.clone();
+    let collab_storage = collab_storage.clone();
+    let published_collab_store = published_collab_store.clone();
+    async move {
+      let rows = select_auto_publish_views_to_republish(
+        &pg_pool,
+        debounce_secs,
+        max_delay_secs,
+        AUTO_PUBLISH_BATCH_SIZE,
+      )
+      .await?;
+      for row in rows {
+        let result = republish_view(
+          &pg_pool,
+          &collab_storage,
+          published_collab_store.as_ref(),
+          &row,
+        )
+        .await;
+        let error = match &result {
+          Ok(_) => {
+            info!("Republished document {} automatically", row.view_id);
+            None
+          },
+          Err(err) => {
+            error!("Failed to republish document {}: {:?}", row.view_id, err);
+            Some(err.to_string())
+          },
+        };
+        if let Err(err) = update_auto_publish_view_republished(
+          &pg_pool,
+          &row.view_id,
+          &row.changed_at,
+          error.as_deref(),
+        )
+        .await
+        {
+          error!(
+            "Failed to record the republish of document {}: {:?}",
+            row.view_id, err
+          );
+        }
+      }
+      Ok(())
+    }
+  });
+}
+
+/// Replaces the published data of the document with its latest content. The metadata and the
+/// publish name are kept.
+async fn republish_view(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  published_collab_store: &dyn PublishedCollabStore,
+  row: &AFAutoPublishViewRow,
+) -> Result<(), AppError> {
+  let info = select_published_collab_info(pg_pool, &row.view_id).await?;
+  let (_, metadata) = select_published_metadata_for_view_id(pg_pool, &row.view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Published view {}", row.view_id)))?;
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &row.workspace_id.to_string(),
+    &row.view_id.to_string(),
+    CollabType::Document,
+  )
+  .await?;
+  let publish_item = PublishCollabItem {
+    meta: PublishCollabMetadata {
+      view_id: row.view_id,
+      publish_name: info.publish_name,
+      metadata,
+    },
+    data: encoded_collab.encode_to_bytes()?,
+  };
+  published_collab_store
+    .publish_collabs(vec![publish_item], &row.workspace_id, &row.publisher_uuid)
+    .await
+}
@@ -0,0 +1,368 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use app_error::{AppError, ErrorDetails};
+use database::bulk_invite::{
+  insert_bulk_invite, select_bulk_invite, update_bulk_invite_progress, update_bulk_invite_status,
+};
+use database::pg_row::AFBulkInviteRow;
+use database_entity::dto::AFRole;
+use futures_util::{stream, StreamExt};
+use shared_entity::dto::workspace_dto::{
+  BatchInviteWorkspaceMembers, BulkInviteRowError, BulkInviteStatus, BulkInviteTask,
+  InvitationResult, WorkspaceMemberInvitation,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::workspace::ops::invite_workspace_members;
+use crate::mailer::AFCloudMailer;
+use crate::state::GoTrueAdmin;
+
+const MAX_BULK_INVITE_ROWS: usize = 1000;
+
+const MAX_INVITE_MESSAGE_LENGTH: usize = 1000;
+
+/// The invitations are sent one by one at this interval, so that inviting a large organization
+/// doesn't exceed the sending rate of the mail server.
+const BULK_INVITE_EMAIL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The number of invitations of a batch processed at the same time, which bounds the concurrent
+/// requests to gotrue and the mail server.
+const BATCH_INVITE_CONCURRENCY: usize = 8;
+
+struct BulkInviteRow {
+  row: usize,
+  invitation: WorkspaceMemberInvitation,
+  message: Option<String>,
+}
+
+/// Invites the members listed in a CSV file with the columns `email`, `role` and an optional
+/// `message`. The rows are validated right away, and the valid rows are invited in the background.
+/// The rows inviting owners are rejected unless the inviter can invite owners.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_bulk_invite(
+  mailer: AFCloudMailer,
+  gotrue_admin: GoTrueAdmin,
+  pg_pool: PgPool,
+  gotrue_client: gotrue::api::Client,
+  inviter: Uuid,
+  inviter_uid: i64,
+  workspace_id: Uuid,
+  csv_content: &[u8],
+  can_invite_owners: bool,
+  appflowy_web_url: Option<String>,
+) -> Result<BulkInviteTask, AppError> {
+  let (mut rows, mut errors) = parse_bulk_invite_csv(csv_content)?;
+  if !can_invite_owners {
+    let (owner_rows, other_rows) = rows
+      .into_iter()
+      .partition::<Vec<_>, _>(|row| row.invitation.role == AFRole::Owner);
+    rows = other_rows;
+    errors.extend(owner_rows.into_iter().map(|row| BulkInviteRowError {
+      row: row.row,
+      email: row.invitation.email,
+      error: "Only the owners can invite owners".to_string(),
+    }));
+    errors.sort_by_key(|error| error.row);
+  }
+  let total_count = (rows.len() + errors.len()) as i32;
+  let task_id = Uuid::new_v4();
+  insert_bulk_invite(
+    &pg_pool,
+    &task_id,
+    &workspace_id,
+    inviter_uid,
+    total_count,
+    serde_json::to_value(&errors)?,
+  )
+  .await?;
+  let task = get_bulk_invite_task(&pg_pool, &workspace_id, &task_id).await?;
+
+  tokio::spawn(async move {
+    let mut status = BulkInviteStatus::Completed;
+    for (index, row) in rows.into_iter().enumerate() {
+      if index > 0 {
+        tokio::time::sleep(BULK_INVITE_EMAIL_INTERVAL).await;
+      }
+      let email = row.invitation.email.clone();
+      let result = invite_workspace_members(
+        &mailer,
+        &gotrue_admin,
+        &pg_pool,
+        &gotrue_client,
+        &inviter,
+        &workspace_id,
+        vec![row.invitation],
+        row.message.as_deref(),
+        appflowy_web_url.as_deref(),
+      )
+      .await;
+      let row_error = match result {
+        Ok(_) => None,
+        Err(err) => serde_json::to_value(BulkInviteRowError {
+          row: row.row,
+          email,
+          error: err.to_string(),
+        })
+        .ok(),
+      };
+      if let Err(err) = update_bulk_invite_progress(&pg_pool, &task_id, row_error).await {
+        error!(
+          "Failed to update progress of bulk invite {}: {:?}",
+          task_id, err
+        );
+        status = BulkInviteStatus::Failed;
+        break;
+      }
+    }
+
+    if let Err(err) = update_bulk_invite_status(&pg_pool, &task_id, status as i16).await {
+      error!(
+        "Failed to update status of bulk invite {}: {:?}",
+        task_id, err
+      );
+    }
+    info!("Bulk invite {} finished with status {:?}", task_id, status);
+  });
+
+  Ok(task)
+}
+
+/// Invites each member of the batch independently, and returns the result of each invitation in
+/// the order of the batch. An invitation that fails, e.g. because of an invalid email, doesn't
+/// prevent the others from being sent.
+#[allow(clippy::too_many_arguments)]
+pub async fn batch_invite_workspace_members(
+  mailer: &AFCloudMailer,
+  gotrue_admin: &GoTrueAdmin,
+  pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  inviter: &Uuid,
+  workspace_id: &Uuid,
+  batch: BatchInviteWorkspaceMembers,
+  appflowy_web_url: Option<&str>,
+) -> Result<Vec<InvitationResult>, AppError> {
+  if batch.invitations.len() > MAX_BULK_INVITE_ROWS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "A batch can have at most {} invitations",
+        MAX_BULK_INVITE_ROWS
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_BULK_INVITE_ROWS as i64,
+        Some(batch.invitations.len() as i64),
+      )),
+    );
+  }
+  if batch
+    .message
+    .as_ref()
+    .map_or(false, |message| message.len() > MAX_INVITE_MESSAGE_LENGTH)
+  {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "Message exceeds {} characters",
+        MAX_INVITE_MESSAGE_LENGTH
+      ))
+      .with_details(
+        ErrorDetails::field("message", "too_long")
+          .with_param("max_length", MAX_INVITE_MESSAGE_LENGTH),
+      ),
+    );
+  }
+
+  let message = batch.message.as_deref();
+  let mut emails = HashSet::new();
+  let results = stream::iter(batch.invitations)
+    .map(|invitation| {
+      let is_duplicate = !emails.insert(invitation.email.to_lowercase());
+      async move {
+        let email = invitation.email.clone();
+        let result = if is_duplicate {
+          Err(AppError::InvalidRequest("Duplicate email".to_string()))
+        } else if !validator::validate_email(&email) {
+          Err(AppError::InvalidRequest("Invalid email".to_string()))
+        } else {
+          invite_workspace_members(
+            mailer,
+            gotrue_admin,
+            pg_pool,
+            gotrue_client,
+            inviter,
+            workspace_id,
+            vec![invitation],
+            message,
+            appflowy_web_url,
+          )
+          .await
+        };
+        InvitationResult {
+          email,
+          error: result.err().map(|err| err.to_string()),
+        }
+      }
+    })
+    .buffered(BATCH_INVITE_CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+  Ok(results)
+}
+
+pub async fn get_bulk_invite_task(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<BulkInviteTask, AppError> {
+  let row = select_bulk_invite_row(pg_pool, workspace_id, task_id).await?;
+  Ok(BulkInviteTask {
+    task_id: row.task_id,
+    status: BulkInviteStatus::from(row.status),
+    total_count: row.total_count,
+    success_count: row.success_count,
+    failed_count: row.failed_count,
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+  })
+}
+
+/// Returns the rows that can't be invited as a CSV file with the columns `row`, `email` and
+/// `error`.
+pub async fn get_bulk_invite_error_report(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<Vec<u8>, AppError> {
+  let row = select_bulk_invite_row(pg_pool, workspace_id, task_id).await?;
+  let errors: Vec<BulkInviteRowError> = serde_json::from_value(row.errors)?;
+  let mut writer = csv::Writer::from_writer(vec![]);
+  writer
+    .write_record(["row", "email", "error"])
+    .map_err(|err| AppError::Internal(err.into()))?;
+  for error in errors {
+    writer
+      .write_record([error.row.to_string(), error.email, error.error])
+      .map_err(|err| AppError::Internal(err.into()))?;
+  }
+  writer
+    .into_inner()
+    .map_err(|err| AppError::Internal(anyhow::anyhow!("Failed to write error report: {}", err)))
+}
+
+async fn select_bulk_invite_row(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  task_id: &Uuid,
+) -> Result<AFBulkInviteRow, AppError> {
+  select_bulk_invite(pg_pool, workspace_id, task_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Bulk invite {} not found", task_id)))
+}
+
+fn parse_bulk_invite_csv(
+  csv_content: &[u8],
+) -> Result<(Vec<BulkInviteRow>, Vec<BulkInviteRowError>), AppError> {
+  let mut reader = csv::ReaderBuilder::new()
+    .has_headers(false)
+    .flexible(true)
+    .trim(csv::Trim::All)
+    .from_reader(csv_content);
+
+  let mut rows = vec![];
+  let mut errors = vec![];
+  let mut emails = HashSet::new();
+  for (index, record) in reader.records().enumerate() {
+    let row = index + 1;
+    let record = match record {
+      Ok(record) => record,
+      Err(err) => {
+        errors.push(BulkInviteRowError {
+          row,
+          email: String::new(),
+          error: format!("Invalid CSV row: {}", err),
+        });
+        continue;
+      },
+    };
+    let email = record.get(0).unwrap_or_default().to_string();
+    if row == 1 && email.eq_ignore_ascii_case("email") {
+      continue;
+    }
+    if record.iter().all(|field| field.is_empty()) {
+      continue;
+    }
+
+    let row_error = |error: String| BulkInviteRowError {
+      row,
+      email: email.clone(),
+      error,
+    };
+    if !validator::validate_email(&email) {
+      errors.push(row_error("Invalid email".to_string()));
+      continue;
+    }
+    let role = match parse_role(record.get(1).unwrap_or_default()) {
+      Some(role) => role,
+      None => {
+        errors.push(row_error(
+          "Invalid role, expected owner, member or guest".to_string(),
+        ));
+        continue;
+      },
+    };
+    let message = record
+      .get(2)
+      .filter(|message| !message.is_empty())
+      .map(|message| message.to_string());
+    if message
+      .as_ref()
+      .map_or(false, |message| message.len() > MAX_INVITE_MESSAGE_LENGTH)
+    {
+      errors.push(row_error(format!(
+        "Message exceeds {} characters",
+        MAX_INVITE_MESSAGE_LENGTH
+      )));
+      continue;
+    }
+    if !emails.insert(email.to_lowercase()) {
+      errors.push(row_error("Duplicate email".to_string()));
+      continue;
+    }
+    rows.push(BulkInviteRow {
+      row,
+      invitation: WorkspaceMemberInvitation { email, role },
+      message,
+    });
+  }
+
+  if rows.len() + errors.len() > MAX_BULK_INVITE_ROWS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "A bulk invite can have at most {} rows",
+        MAX_BULK_INVITE_ROWS
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_BULK_INVITE_ROWS as i64,
+        Some((rows.len() + errors.len()) as i64),
+      )),
+    );
+  }
+  if rows.is_empty() && errors.is_empty() {
+    return Err(
+      AppError::InvalidRequest("The CSV file has no invitation".to_string())
+        .with_details(ErrorDetails::field("file", "empty")),
+    );
+  }
+  Ok((rows, errors))
+}
+
+/// The role can be the name or the id of the role, and defaults to member.
+fn parse_role(role: &str) -> Option<AFRole> {
+  match role.to_lowercase().as_str() {
+    "" | "member" | "2" => Some(AFRole::Member),
+    "owner" | "1" => Some(AFRole::Owner),
+    "guest" | "3" => Some(AFRole::Guest),
+    _ => None,
+  }
+}
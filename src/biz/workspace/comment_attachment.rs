@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use app_error::{AppError, ErrorDetails};
+use database::comment_attachment::{
+  delete_comment_attachments, insert_comment_attachment,
+  select_comment_attachments_for_published_view, select_workspace_id_for_published_view,
+  update_comment_attachments,
+};
+use database::pg_row::AFCommentAttachmentRow;
+use database_entity::dto::{CommentAttachment, GlobalComment};
+use sqlx::{PgPool, Postgres, Transaction};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::api::file_storage::BlobPathV1;
+use crate::biz::workspace::residency::StorageRouter;
+
+/// The files of the comment attachments are stored under this directory of the workspace that
+/// published the view.
+const COMMENT_ATTACHMENT_PARENT_DIR: &str = "comment_attachment";
+
+pub const MAX_COMMENT_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+
+const MAX_COMMENT_ATTACHMENTS_SIZE: i64 = 10 * 1024 * 1024;
+
+const MAX_COMMENT_ATTACHMENTS: usize = 4;
+
+/// Uploads an image that can be attached to a comment on the published view. The attachment is
+/// attached to the comment when the comment is created.
+pub async fn upload_comment_attachment(
+  pg_pool: &PgPool,
+  storage_router: &StorageRouter,
+  view_id: &Uuid,
+  uid: i64,
+  content: Vec<u8>,
+  content_type: String,
+) -> Result<CommentAttachment, AppError> {
+  if !content_type.starts_with("image/") {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "Comment attachment must be an image, got: {}",
+        content_type
+      ))
+      .with_details(ErrorDetails::field("content_type", "not_an_image")),
+    );
+  }
+  if content.len() > MAX_COMMENT_ATTACHMENT_SIZE {
+    return Err(
+      AppError::PayloadTooLarge(format!(
+        "Comment attachment exceeds the maximum size of {} bytes",
+        MAX_COMMENT_ATTACHMENT_SIZE
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_COMMENT_ATTACHMENT_SIZE as i64,
+        Some(content.len() as i64),
+      )),
+    );
+  }
+  let workspace_id = select_workspace_id_for_published_view(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Published view {} not found", view_id)))?;
+
+  let attachment_id = Uuid::new_v4();
+  let file_size = content.len() as i64;
+  let mut txn = pg_pool.begin().await?;
+  insert_comment_attachment(
+    txn.as_mut(),
+    &attachment_id,
+    &workspace_id,
+    view_id,
+    &content_type,
+    file_size,
+    uid,
+  )
+  .await?;
+  storage_router
+    .bucket_storage(&workspace_id)
+    .await?
+    .put_blob(
+      comment_attachment_blob_path(workspace_id, &attachment_id),
+      content,
+      content_type.clone(),
+    )
+    .await?;
+  txn.commit().await?;
+
+  Ok(CommentAttachment {
+    attachment_id,
+    url: comment_attachment_url(&workspace_id, &attachment_id),
+    content_type,
+    file_size,
+  })
+}
+
+/// Attaches the uploaded attachments to a comment that is being created with the given
+/// transaction.
+pub async fn attach_to_comment(
+  txn: &mut Transaction<'_, Postgres>,
+  comment_id: &Uuid,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+  attachment_ids: &[Uuid],
+) -> Result<(), AppError> {
+  if attachment_ids.is_empty() {
+    return Ok(());
+  }
+  if attachment_ids.len() > MAX_COMMENT_ATTACHMENTS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "A comment can have at most {} attachments",
+        MAX_COMMENT_ATTACHMENTS
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_COMMENT_ATTACHMENTS as i64,
+        Some(attachment_ids.len() as i64),
+      )),
+    );
+  }
+
+  let attachments =
+    update_comment_attachments(txn.as_mut(), comment_id, view_id, user_uuid, attachment_ids)
+      .await?;
+  if attachments.len() != attachment_ids.len() {
+    return Err(AppError::InvalidRequest(
+      "Some attachments don't exist or are already attached to another comment".to_string(),
+    ));
+  }
+  let total_size: i64 = attachments.iter().map(|a| a.file_size).sum();
+  if total_size > MAX_COMMENT_ATTACHMENTS_SIZE {
+    return Err(
+      AppError::PayloadTooLarge(format!(
+        "The attachments of a comment exceed the maximum size of {} bytes",
+        MAX_COMMENT_ATTACHMENTS_SIZE
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_COMMENT_ATTACHMENTS_SIZE,
+        Some(total_size),
+      )),
+    );
+  }
+  Ok(())
+}
+
+pub async fn fill_comment_attachments(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  comments: &mut [GlobalComment],
+) -> Result<(), AppError> {
+  let mut attachments_by_comment: HashMap<Uuid, Vec<CommentAttachment>> = HashMap::new();
+  for row in select_comment_attachments_for_published_view(pg_pool, view_id).await? {
+    if let Some(comment_id) = row.comment_id {
+      attachments_by_comment
+        .entry(comment_id)
+        .or_default()
+        .push(comment_attachment_from_row(row));
+    }
+  }
+  for comment in comments.iter_mut() {
+    if let Some(attachments) = attachments_by_comment.remove(&comment.comment_id) {
+      comment.attachments = attachments;
+    }
+  }
+  Ok(())
+}
+
+/// Removes the attachments of a deleted comment, including their files.
+pub async fn remove_comment_attachments(
+  pg_pool: &PgPool,
+  storage_router: &StorageRouter,
+  comment_id: &Uuid,
+) -> Result<(), AppError> {
+  let attachments = delete_comment_attachments(pg_pool, comment_id).await?;
+  for attachment in attachments {
+    let bucket_storage = storage_router
+      .bucket_storage(&attachment.workspace_id)
+      .await?;
+    if let Err(err) = bucket_storage
+      .delete_blob(comment_attachment_blob_path(
+        attachment.workspace_id,
+        &attachment.attachment_id,
+      ))
+      .await
+    {
+      // The attachment is no longer reachable from the comment, so the file is only left behind.
+      error!(
+        "Failed to delete file of comment attachment {}: {:?}",
+        attachment.attachment_id, err
+      );
+    }
+  }
+  Ok(())
+}
+
+fn comment_attachment_from_row(row: AFCommentAttachmentRow) -> CommentAttachment {
+  CommentAttachment {
+    url: comment_attachment_url(&row.workspace_id, &row.attachment_id),
+    attachment_id: row.attachment_id,
+    content_type: row.content_type,
+    file_size: row.file_size,
+  }
+}
+
+fn comment_attachment_blob_path(workspace_id: Uuid, attachment_id: &Uuid) -> BlobPathV1 {
+  BlobPathV1 {
+    workspace_id,
+    parent_dir: COMMENT_ATTACHMENT_PARENT_DIR.to_string(),
+    file_id: attachment_id.to_string(),
+  }
+}
+
+fn comment_attachment_url(workspace_id: &Uuid, attachment_id: &Uuid) -> String {
+  format!(
+    "/api/file_storage/{}/v1/blob/{}/{}",
+    workspace_id, COMMENT_ATTACHMENT_PARENT_DIR, attachment_id
+  )
+}
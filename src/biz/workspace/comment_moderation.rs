@@ -0,0 +1,145 @@
+use app_error::AppError;
+use database::comment_attachment::select_workspace_id_for_published_view;
+use database::comment_moderation::{
+  delete_comment_blocklist_user, insert_comment_blocklist_user, select_comment_blocklist,
+  select_user_is_comment_moderator, update_comments_deleted, update_comments_hidden,
+  update_comments_pinned,
+};
+use database::user::select_uid_from_uuid;
+use database_entity::dto::{
+  CommentBlocklist, CommentModeration, CommentModerationAction, ModerateCommentsParams,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::biz::workspace::comment_attachment::remove_comment_attachments;
+use crate::biz::workspace::publish::get_workspace_publish_namespace;
+use crate::biz::workspace::residency::StorageRouter;
+
+const MAX_MODERATED_COMMENTS: usize = 100;
+
+/// Only the owners of the workspace the view is published in can moderate its comments.
+pub async fn check_comment_moderator(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  if !select_user_is_comment_moderator(pg_pool, view_id, user_uuid).await? {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
+
+pub async fn moderate_comments(
+  pg_pool: &PgPool,
+  storage_router: &StorageRouter,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+  params: ModerateCommentsParams,
+) -> Result<CommentModeration, AppError> {
+  if params.comment_ids.len() > MAX_MODERATED_COMMENTS {
+    return Err(AppError::InvalidRequest(format!(
+      "At most {} comments can be moderated at once",
+      MAX_MODERATED_COMMENTS
+    )));
+  }
+  check_comment_moderator(pg_pool, view_id, user_uuid).await?;
+  let comment_ids = match params.action {
+    CommentModerationAction::Hide => {
+      update_comments_hidden(pg_pool, view_id, &params.comment_ids, true).await?
+    },
+    CommentModerationAction::Unhide => {
+      update_comments_hidden(pg_pool, view_id, &params.comment_ids, false).await?
+    },
+    CommentModerationAction::Pin => {
+      update_comments_pinned(pg_pool, view_id, &params.comment_ids, true).await?
+    },
+    CommentModerationAction::Unpin => {
+      update_comments_pinned(pg_pool, view_id, &params.comment_ids, false).await?
+    },
+    CommentModerationAction::Delete => {
+      let comment_ids = update_comments_deleted(pg_pool, view_id, &params.comment_ids).await?;
+      for comment_id in &comment_ids {
+        remove_comment_attachments(pg_pool, storage_router, comment_id).await?;
+      }
+      comment_ids
+    },
+  };
+  info!(
+    "user {} applied {:?} to {} comments of view {}",
+    user_uuid,
+    params.action,
+    comment_ids.len(),
+    view_id
+  );
+  Ok(CommentModeration { comment_ids })
+}
+
+async fn get_view_workspace_id(pg_pool: &PgPool, view_id: &Uuid) -> Result<Uuid, AppError> {
+  select_workspace_id_for_published_view(pg_pool, view_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} is not published", view_id)))
+}
+
+/// Returns the users blocked from commenting on the views published by the workspace, along with
+/// the namespace in use by the workspace.
+pub async fn get_comment_blocklist(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+) -> Result<CommentBlocklist, AppError> {
+  check_comment_moderator(pg_pool, view_id, user_uuid).await?;
+  let workspace_id = get_view_workspace_id(pg_pool, view_id).await?;
+  let namespace = get_workspace_publish_namespace(pg_pool, &workspace_id).await?;
+  let users = select_comment_blocklist(pg_pool, &workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(CommentBlocklist { namespace, users })
+}
+
+/// Blocks the user from commenting and reacting on the views published in the namespace of the
+/// workspace. The block is kept when the namespace changes. The comments the user already made are
+/// kept, they can be hidden or deleted.
+pub async fn block_commenter(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+  blocked_uuid: &Uuid,
+) -> Result<CommentBlocklist, AppError> {
+  if user_uuid == blocked_uuid {
+    return Err(AppError::InvalidRequest(
+      "Users can't block themselves".to_string(),
+    ));
+  }
+  check_comment_moderator(pg_pool, view_id, user_uuid).await?;
+  if select_user_is_comment_moderator(pg_pool, view_id, blocked_uuid).await? {
+    return Err(AppError::InvalidRequest(
+      "The owners of the workspace can't be blocked".to_string(),
+    ));
+  }
+  let workspace_id = get_view_workspace_id(pg_pool, view_id).await?;
+  let namespace = get_workspace_publish_namespace(pg_pool, &workspace_id).await?;
+  let blocked_uid = select_uid_from_uuid(pg_pool, blocked_uuid).await?;
+  insert_comment_blocklist_user(pg_pool, &namespace, blocked_uid, user_uuid).await?;
+  get_comment_blocklist(pg_pool, view_id, user_uuid).await
+}
+
+pub async fn unblock_commenter(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  user_uuid: &Uuid,
+  blocked_uuid: &Uuid,
+) -> Result<CommentBlocklist, AppError> {
+  check_comment_moderator(pg_pool, view_id, user_uuid).await?;
+  let workspace_id = get_view_workspace_id(pg_pool, view_id).await?;
+  if !delete_comment_blocklist_user(pg_pool, &workspace_id, blocked_uuid).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "User {} is not blocked from commenting",
+      blocked_uuid
+    )));
+  }
+  get_comment_blocklist(pg_pool, view_id, user_uuid).await
+}
@@ -0,0 +1,152 @@
+use app_error::AppError;
+use database::comment_attachment::select_workspace_id_for_published_view;
+use database::comment_subscription::{
+  delete_comment_subscription, select_comment_subscriber_count,
+  select_confirmed_comment_subscriptions, update_comment_subscription_confirmed,
+  upsert_comment_subscription,
+};
+use database::publish::select_published_collab_info_for_view_ids;
+use database::user::select_name_and_email_from_uuid;
+use database_entity::dto::PublishInfo;
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::mailer::{
+  AFCloudMailer, CommentSubscriptionConfirmationMailerParam, NewCommentNotificationMailerParam,
+};
+
+/// Subscribes the email to the new comments on the published view. The subscription only
+/// receives notifications after it is confirmed with the link sent to the email.
+pub async fn subscribe_to_comments(
+  pg_pool: &PgPool,
+  mailer: AFCloudMailer,
+  appflowy_web_url: &str,
+  view_id: &Uuid,
+  email: &str,
+) -> Result<(), AppError> {
+  let publish_info = select_publish_info(pg_pool, view_id).await?;
+  let subscription = upsert_comment_subscription(pg_pool, view_id, email).await?;
+  if subscription.confirmed {
+    return Ok(());
+  }
+
+  let param = CommentSubscriptionConfirmationMailerParam {
+    view_name: publish_info.publish_name.clone(),
+    view_url: published_view_url(appflowy_web_url, &publish_info),
+    confirm_url: format!(
+      "{}/comment-subscription/confirm?view_id={}&token={}",
+      appflowy_web_url, view_id, subscription.token
+    ),
+  };
+  let email = email.to_string();
+  tokio::spawn(async move {
+    if let Err(err) = mailer
+      .send_comment_subscription_confirmation(&email, param)
+      .await
+    {
+      error!(
+        "Failed to send comment subscription confirmation email: {:?}",
+        err
+      );
+    }
+  });
+  Ok(())
+}
+
+pub async fn confirm_comment_subscription(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  token: &Uuid,
+) -> Result<(), AppError> {
+  if !update_comment_subscription_confirmed(pg_pool, view_id, token).await? {
+    return Err(AppError::RecordNotFound(
+      "Comment subscription not found".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+pub async fn unsubscribe_from_comments(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  token: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_comment_subscription(pg_pool, view_id, token).await? {
+    return Err(AppError::RecordNotFound(
+      "Comment subscription not found".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+pub async fn get_comment_subscriber_count(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<i64, AppError> {
+  let published_workspace_id = select_workspace_id_for_published_view(pg_pool, view_id).await?;
+  if published_workspace_id.as_ref() != Some(workspace_id) {
+    return Err(AppError::RecordNotFound(format!(
+      "Published view {} not found in workspace {}",
+      view_id, workspace_id
+    )));
+  }
+  select_comment_subscriber_count(pg_pool, view_id).await
+}
+
+/// Notifies the confirmed subscriptions of the published view about a new comment. The author of
+/// the comment is not notified about their own comment.
+pub async fn notify_comment_subscribers(
+  pg_pool: &PgPool,
+  mailer: &AFCloudMailer,
+  appflowy_web_url: &str,
+  view_id: &Uuid,
+  commenter_uuid: &Uuid,
+  content: &str,
+) -> Result<(), AppError> {
+  let subscriptions = select_confirmed_comment_subscriptions(pg_pool, view_id).await?;
+  if subscriptions.is_empty() {
+    return Ok(());
+  }
+  let publish_info = select_publish_info(pg_pool, view_id).await?;
+  let (commenter_name, commenter_email) =
+    select_name_and_email_from_uuid(pg_pool, commenter_uuid).await?;
+  let view_url = published_view_url(appflowy_web_url, &publish_info);
+  for subscription in subscriptions {
+    if subscription.email.eq_ignore_ascii_case(&commenter_email) {
+      continue;
+    }
+    let param = NewCommentNotificationMailerParam {
+      commenter_name: commenter_name.clone(),
+      comment_content: content.to_string(),
+      view_name: publish_info.publish_name.clone(),
+      view_url: view_url.clone(),
+      unsubscribe_url: format!(
+        "{}/comment-subscription/unsubscribe?view_id={}&token={}",
+        appflowy_web_url, view_id, subscription.token
+      ),
+    };
+    if let Err(err) = mailer
+      .send_new_comment_notification(&subscription.email, param)
+      .await
+    {
+      error!("Failed to send new comment notification email: {:?}", err);
+    }
+  }
+  Ok(())
+}
+
+async fn select_publish_info(pg_pool: &PgPool, view_id: &Uuid) -> Result<PublishInfo, AppError> {
+  select_published_collab_info_for_view_ids(pg_pool, &[*view_id])
+    .await?
+    .pop()
+    .ok_or_else(|| AppError::RecordNotFound(format!("Published view {} not found", view_id)))
+}
+
+fn published_view_url(appflowy_web_url: &str, publish_info: &PublishInfo) -> String {
+  format!(
+    "{}/{}/{}",
+    appflowy_web_url, publish_info.namespace, publish_info.publish_name
+  )
+}
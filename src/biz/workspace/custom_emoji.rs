@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use app_error::{AppError, ErrorDetails};
+use database::custom_emoji::{
+  delete_custom_emoji, insert_custom_emoji, select_custom_emoji_exists_for_published_view,
+  select_custom_emojis,
+};
+use database::file::s3_client_impl::S3BucketStorage;
+use database_entity::dto::{custom_emoji_shortcode, CustomEmoji};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api::file_storage::BlobPathV1;
+
+/// The images of the custom emojis are stored under this directory of the workspace.
+const CUSTOM_EMOJI_PARENT_DIR: &str = "custom_emoji";
+
+const MAX_CUSTOM_EMOJI_SIZE: usize = 256 * 1024;
+
+const MAX_SHORTCODE_LENGTH: usize = 32;
+
+pub async fn upload_custom_emoji(
+  pg_pool: &PgPool,
+  bucket_storage: Arc<S3BucketStorage>,
+  workspace_id: Uuid,
+  uid: i64,
+  shortcode: &str,
+  content: Vec<u8>,
+  content_type: String,
+) -> Result<CustomEmoji, AppError> {
+  validate_shortcode(shortcode)?;
+  if !content_type.starts_with("image/") {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "Custom emoji must be an image, got: {}",
+        content_type
+      ))
+      .with_details(ErrorDetails::field("content_type", "not_an_image")),
+    );
+  }
+  if content.len() > MAX_CUSTOM_EMOJI_SIZE {
+    return Err(
+      AppError::PayloadTooLarge(format!(
+        "Custom emoji exceeds the maximum size of {} bytes",
+        MAX_CUSTOM_EMOJI_SIZE
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_CUSTOM_EMOJI_SIZE as i64,
+        Some(content.len() as i64),
+      )),
+    );
+  }
+
+  // A new file id is generated for every upload, so that an emoji that is deleted and uploaded
+  // again with the same shortcode doesn't serve the cached image of the previous one.
+  let file_id = Uuid::new_v4().to_string();
+  let mut txn = pg_pool.begin().await?;
+  if !insert_custom_emoji(txn.as_mut(), &workspace_id, shortcode, &file_id, uid).await? {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "Custom emoji :{}: already exists",
+      shortcode
+    )));
+  }
+  bucket_storage
+    .put_blob(
+      custom_emoji_blob_path(workspace_id, file_id.clone()),
+      content,
+      content_type,
+    )
+    .await?;
+  txn.commit().await?;
+
+  Ok(CustomEmoji {
+    shortcode: shortcode.to_string(),
+    url: custom_emoji_url(&workspace_id, &file_id),
+    created_by: Some(uid),
+    created_at: chrono::Utc::now(),
+  })
+}
+
+pub async fn list_custom_emojis(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<CustomEmoji>, AppError> {
+  let emojis = select_custom_emojis(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| CustomEmoji {
+      url: custom_emoji_url(workspace_id, &row.file_id),
+      shortcode: row.shortcode,
+      created_by: row.created_by,
+      created_at: row.created_at,
+    })
+    .collect();
+  Ok(emojis)
+}
+
+pub async fn remove_custom_emoji(
+  pg_pool: &PgPool,
+  bucket_storage: Arc<S3BucketStorage>,
+  workspace_id: Uuid,
+  shortcode: &str,
+) -> Result<(), AppError> {
+  let file_id = delete_custom_emoji(pg_pool, &workspace_id, shortcode)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!("Custom emoji :{}: does not exist", shortcode))
+    })?;
+  bucket_storage
+    .delete_blob(custom_emoji_blob_path(workspace_id, file_id))
+    .await?;
+  Ok(())
+}
+
+/// Reactions referencing a custom emoji must reference an emoji of the workspace that published
+/// the view.
+pub async fn validate_reaction_type(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  reaction_type: &str,
+) -> Result<(), AppError> {
+  if let Some(shortcode) = custom_emoji_shortcode(reaction_type) {
+    if !select_custom_emoji_exists_for_published_view(pg_pool, view_id, shortcode).await? {
+      return Err(AppError::InvalidRequest(format!(
+        "Custom emoji :{}: does not exist",
+        shortcode
+      )));
+    }
+  }
+  Ok(())
+}
+
+fn validate_shortcode(shortcode: &str) -> Result<(), AppError> {
+  let is_valid = !shortcode.is_empty()
+    && shortcode.len() <= MAX_SHORTCODE_LENGTH
+    && shortcode
+      .chars()
+      .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+  if !is_valid {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "Invalid shortcode: {}, only lowercase letters, digits, '_' and '-' are allowed, up to {} characters",
+        shortcode, MAX_SHORTCODE_LENGTH
+      ))
+      .with_details(
+        ErrorDetails::field("shortcode", "invalid_format")
+          .with_param("max_length", MAX_SHORTCODE_LENGTH),
+      ),
+    );
+  }
+  Ok(())
+}
+
+fn custom_emoji_blob_path(workspace_id: Uuid, file_id: String) -> BlobPathV1 {
+  BlobPathV1 {
+    workspace_id,
+    parent_dir: CUSTOM_EMOJI_PARENT_DIR.to_string(),
+    file_id,
+  }
+}
+
+fn custom_emoji_url(workspace_id: &Uuid, file_id: &str) -> String {
+  format!(
+    "/api/file_storage/{}/v1/blob/{}/{}",
+    workspace_id, CUSTOM_EMOJI_PARENT_DIR, file_id
+  )
+}
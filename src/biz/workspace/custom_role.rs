@@ -0,0 +1,269 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use access_control::workspace::WorkspaceAccessControl;
+use app_error::AppError;
+use database::workspace::select_user_role;
+use database::workspace_role::{
+  delete_workspace_member_custom_role, delete_workspace_role, insert_workspace_role,
+  replace_workspace_role_members, select_role_assignable_member_uids, select_workspace_roles,
+  update_workspace_role as update_workspace_role_row,
+};
+use database_entity::dto::{
+  AFRole, AFWorkspaceRole, AFWorkspaceRoles, CreateWorkspaceRoleParams,
+  UpdateWorkspaceRoleMembersParams, UpdateWorkspaceRoleParams, WorkspaceCapability,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_ROLE_NAME_LENGTH: usize = 100;
+const MAX_ROLES_PER_WORKSPACE: usize = 50;
+
+pub async fn get_workspace_roles(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<AFWorkspaceRoles, AppError> {
+  let roles = select_workspace_roles(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(AFWorkspaceRoles { roles })
+}
+
+pub async fn create_workspace_role(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateWorkspaceRoleParams,
+) -> Result<AFWorkspaceRole, AppError> {
+  let name = check_role_name(&params.name)?;
+  if select_workspace_roles(pg_pool, workspace_id).await?.len() >= MAX_ROLES_PER_WORKSPACE {
+    return Err(AppError::InvalidRequest(format!(
+      "A workspace can't have more than {} roles",
+      MAX_ROLES_PER_WORKSPACE
+    )));
+  }
+  let capabilities = capability_names(&params.capabilities);
+  let role_id = insert_workspace_role(pg_pool, workspace_id, name, &capabilities)
+    .await?
+    .ok_or(AppError::RecordAlreadyExists(format!(
+      "Role {} already exists",
+      name
+    )))?;
+  find_workspace_role(pg_pool, workspace_id, &role_id).await
+}
+
+/// Renames the role or replaces its capabilities, which apply to its members right away.
+pub async fn update_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  params: UpdateWorkspaceRoleParams,
+) -> Result<AFWorkspaceRole, AppError> {
+  let role = find_workspace_role(pg_pool, workspace_id, role_id).await?;
+  let name = params.name.as_deref().map(check_role_name).transpose()?;
+  if let Some(name) = name.filter(|name| *name != role.name) {
+    let name_taken = select_workspace_roles(pg_pool, workspace_id)
+      .await?
+      .iter()
+      .any(|row| row.name == name);
+    if name_taken {
+      return Err(AppError::RecordAlreadyExists(format!(
+        "Role {} already exists",
+        name
+      )));
+    }
+  }
+  let capabilities = params
+    .capabilities
+    .as_ref()
+    .map(|capabilities| capability_names(capabilities));
+  update_workspace_role_row(
+    pg_pool,
+    workspace_id,
+    role_id,
+    name,
+    capabilities.as_deref(),
+  )
+  .await?;
+
+  let role = find_workspace_role(pg_pool, workspace_id, role_id).await?;
+  if params.capabilities.is_some() {
+    for uid in &role.member_uids {
+      workspace_access_control
+        .update_capabilities(uid, workspace_id, &role.capabilities)
+        .await?;
+    }
+  }
+  Ok(role)
+}
+
+/// Replaces the members of the role. The members that had another role get the capabilities of
+/// this one instead, the members that are removed lose the capabilities of the role.
+pub async fn update_workspace_role_members(
+  pg_pool: &PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+  params: UpdateWorkspaceRoleMembersParams,
+) -> Result<AFWorkspaceRole, AppError> {
+  let previous_role = find_workspace_role(pg_pool, workspace_id, role_id).await?;
+  check_role_members(pg_pool, workspace_id, &params.member_uids).await?;
+
+  let mut txn = pg_pool.begin().await?;
+  replace_workspace_role_members(&mut txn, workspace_id, role_id, &params.member_uids).await?;
+  txn.commit().await?;
+
+  let role = find_workspace_role(pg_pool, workspace_id, role_id).await?;
+  for uid in &previous_role.member_uids {
+    if !role.member_uids.contains(uid) {
+      workspace_access_control
+        .update_capabilities(uid, workspace_id, &[])
+        .await?;
+    }
+  }
+  for uid in &role.member_uids {
+    workspace_access_control
+      .update_capabilities(uid, workspace_id, &role.capabilities)
+      .await?;
+  }
+  Ok(role)
+}
+
+/// Deletes the role, its members lose its capabilities and keep their owner, member or guest role.
+pub async fn remove_workspace_role(
+  pg_pool: &PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<(), AppError> {
+  let role = find_workspace_role(pg_pool, workspace_id, role_id).await?;
+  delete_workspace_role(pg_pool, workspace_id, role_id).await?;
+  for uid in &role.member_uids {
+    workspace_access_control
+      .update_capabilities(uid, workspace_id, &[])
+      .await?;
+  }
+  Ok(())
+}
+
+/// Removes the custom role of a member who became a guest, the guests can't have capabilities.
+pub async fn remove_member_custom_role(
+  pg_pool: &PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  workspace_id: &Uuid,
+  uid: i64,
+) -> Result<(), AppError> {
+  if delete_workspace_member_custom_role(pg_pool, workspace_id, uid).await? {
+    workspace_access_control
+      .update_capabilities(&uid, workspace_id, &[])
+      .await?;
+  }
+  Ok(())
+}
+
+/// Checks that the user can invite to the workspace with the given roles. Inviting owners is
+/// reserved to the owners.
+pub async fn enforce_invite(
+  workspace_access_control: &Arc<dyn WorkspaceAccessControl>,
+  uid: &i64,
+  workspace_id: &Uuid,
+  invited_roles: &[AFRole],
+) -> Result<(), AppError> {
+  let workspace_id = workspace_id.to_string();
+  workspace_access_control
+    .enforce_capability(uid, &workspace_id, WorkspaceCapability::Invite)
+    .await?;
+  if invited_roles.contains(&AFRole::Owner) {
+    workspace_access_control
+      .enforce_role(uid, &workspace_id, AFRole::Owner)
+      .await?;
+  }
+  Ok(())
+}
+
+/// Checks that the user can change or remove the given member of the workspace. The owners, and
+/// making a member an owner, are reserved to the owners.
+pub async fn enforce_manage_member(
+  pg_pool: &PgPool,
+  workspace_access_control: &Arc<dyn WorkspaceAccessControl>,
+  uid: &i64,
+  workspace_id: &Uuid,
+  member_uid: &i64,
+  new_role: Option<&AFRole>,
+) -> Result<(), AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  workspace_access_control
+    .enforce_capability(uid, &workspace_id_str, WorkspaceCapability::ManageMembers)
+    .await?;
+  let is_owner_change = new_role == Some(&AFRole::Owner)
+    || matches!(
+      select_user_role(pg_pool, member_uid, workspace_id).await,
+      Ok(AFRole::Owner)
+    );
+  if is_owner_change {
+    workspace_access_control
+      .enforce_role(uid, &workspace_id_str, AFRole::Owner)
+      .await?;
+  }
+  Ok(())
+}
+
+async fn find_workspace_role(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  role_id: &Uuid,
+) -> Result<AFWorkspaceRole, AppError> {
+  select_workspace_roles(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .find(|row| &row.role_id == role_id)
+    .map(|row| row.into())
+    .ok_or(AppError::RecordNotFound(format!(
+      "Role {} not found",
+      role_id
+    )))
+}
+
+fn check_role_name(name: &str) -> Result<&str, AppError> {
+  let name = name.trim();
+  if name.is_empty() || name.chars().count() > MAX_ROLE_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The name of a role must have between 1 and {} characters",
+      MAX_ROLE_NAME_LENGTH
+    )));
+  }
+  Ok(name)
+}
+
+/// The names of the capabilities as stored, without duplicates.
+fn capability_names(capabilities: &[WorkspaceCapability]) -> Vec<String> {
+  let mut seen = HashSet::new();
+  capabilities
+    .iter()
+    .filter(|capability| seen.insert(**capability))
+    .map(|capability| capability.as_str().to_string())
+    .collect()
+}
+
+async fn check_role_members(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  if uids.is_empty() {
+    return Ok(());
+  }
+  let member_uids: HashSet<i64> = select_role_assignable_member_uids(pg_pool, workspace_id, uids)
+    .await?
+    .into_iter()
+    .collect();
+  match uids.iter().find(|uid| !member_uids.contains(uid)) {
+    Some(uid) => Err(AppError::InvalidRequest(format!(
+      "User {} is not a member of the workspace, or is a guest",
+      uid
+    ))),
+    None => Ok(()),
+  }
+}
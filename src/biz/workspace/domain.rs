@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceDomainRow;
+use database::workspace_domain::{
+  delete_workspace_domain as delete_workspace_domain_row, insert_workspace_domain,
+  is_domain_verified_by_other_workspace, is_workspace_domain_verified, select_workspace_domain,
+  select_workspace_domains, update_workspace_domain_verified,
+};
+use serde::Deserialize;
+use shared_entity::dto::workspace_dto::WorkspaceDomain;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// The TXT record proving the workspace owns a domain is looked up under this subdomain.
+const DOMAIN_CHALLENGE_SUBDOMAIN: &str = "_appflowy-challenge";
+const DOMAIN_VERIFICATION_PREFIX: &str = "appflowy-domain-verification=";
+const DNS_TXT_RECORD_TYPE: u16 = 16;
+const DNS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct DnsJsonResponse {
+  #[serde(rename = "Answer", default)]
+  answer: Vec<DnsJsonAnswer>,
+}
+
+#[derive(Deserialize)]
+struct DnsJsonAnswer {
+  #[serde(rename = "type")]
+  type_: u16,
+  data: String,
+}
+
+/// Adds an unverified email domain to the workspace. The returned TXT record is added to the DNS
+/// of the domain, then [verify_workspace_domain] is called.
+pub async fn add_workspace_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<WorkspaceDomain, AppError> {
+  let domain = normalize_domain(domain)?;
+  let verification_token = Uuid::new_v4().simple().to_string();
+  let row = insert_workspace_domain(pg_pool, workspace_id, &domain, &verification_token).await?;
+  Ok(workspace_domain_from_row(row))
+}
+
+pub async fn list_workspace_domains(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<WorkspaceDomain>, AppError> {
+  let rows = select_workspace_domains(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(workspace_domain_from_row).collect())
+}
+
+/// Verifies the domain once its TXT record holds the token of the workspace. A domain can only be
+/// verified by one workspace.
+pub async fn verify_workspace_domain(
+  pg_pool: &PgPool,
+  dns_over_https_url: &str,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<WorkspaceDomain, AppError> {
+  let domain = normalize_domain(domain)?;
+  let row = select_workspace_domain(pg_pool, workspace_id, &domain)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Workspace {} has no domain {}",
+        workspace_id, domain
+      ))
+    })?;
+  if row.verified_at.is_some() {
+    return Ok(workspace_domain_from_row(row));
+  }
+  if is_domain_verified_by_other_workspace(pg_pool, workspace_id, &domain).await? {
+    return Err(AppError::RecordAlreadyExists(format!(
+      "The domain {} is verified by another workspace",
+      domain
+    )));
+  }
+
+  let record_name = txt_record_name(&domain);
+  let expected_value = txt_record_value(&row.verification_token);
+  let records = resolve_txt_records(dns_over_https_url, &record_name).await?;
+  if !records.iter().any(|record| record == &expected_value) {
+    return Err(AppError::InvalidRequest(format!(
+      "The TXT record {} doesn't have the value {}",
+      record_name, expected_value
+    )));
+  }
+  let row = update_workspace_domain_verified(pg_pool, workspace_id, &domain).await?;
+  Ok(workspace_domain_from_row(row))
+}
+
+pub async fn delete_workspace_domain(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  domain: &str,
+) -> Result<(), AppError> {
+  let domain = normalize_domain(domain)?;
+  if !delete_workspace_domain_row(pg_pool, workspace_id, &domain).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Workspace {} has no domain {}",
+      workspace_id, domain
+    )));
+  }
+  Ok(())
+}
+
+/// Returns true if the domain of the email is verified by the workspace.
+pub async fn is_email_domain_verified(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  email: &str,
+) -> Result<bool, AppError> {
+  let domain = match email
+    .rsplit_once('@')
+    .map(|(_, domain)| normalize_domain(domain))
+  {
+    Some(Ok(domain)) => domain,
+    _ => return Ok(false),
+  };
+  is_workspace_domain_verified(pg_pool, workspace_id, &domain).await
+}
+
+/// Lowercases the domain and checks it's a valid host name, with at least two labels.
+fn normalize_domain(domain: &str) -> Result<String, AppError> {
+  let domain = domain.trim().trim_end_matches('.').to_lowercase();
+  let is_valid_label = |label: &str| {
+    !label.is_empty()
+      && label.len() <= 63
+      && !label.starts_with('-')
+      && !label.ends_with('-')
+      && label
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+  };
+  if domain.len() > 253 || domain.split('.').count() < 2 || !domain.split('.').all(is_valid_label) {
+    return Err(AppError::InvalidRequest(format!(
+      "Invalid domain: {}",
+      domain
+    )));
+  }
+  Ok(domain)
+}
+
+/// Returns the values of the TXT records of the name, resolved with the JSON API of the
+/// DNS-over-HTTPS resolver. The strings a value is split in are joined.
+async fn resolve_txt_records(
+  dns_over_https_url: &str,
+  name: &str,
+) -> Result<Vec<String>, AppError> {
+  let client = reqwest::Client::builder()
+    .timeout(DNS_REQUEST_TIMEOUT)
+    .build()
+    .map_err(|err| AppError::Internal(err.into()))?;
+  let resp = client
+    .get(dns_over_https_url)
+    .query(&[("name", name), ("type", "TXT")])
+    .header(reqwest::header::ACCEPT, "application/dns-json")
+    .send()
+    .await
+    .map_err(|err| AppError::Connect(format!("Failed to resolve {}: {}", name, err)))?;
+  if !resp.status().is_success() {
+    return Err(AppError::Internal(anyhow!(
+      "Failed to resolve {}: {}",
+      name,
+      resp.status()
+    )));
+  }
+  let resp = resp
+    .json::<DnsJsonResponse>()
+    .await
+    .map_err(|err| AppError::Connect(format!("Failed to resolve {}: {}", name, err)))?;
+  Ok(
+    resp
+      .answer
+      .into_iter()
+      .filter(|answer| answer.type_ == DNS_TXT_RECORD_TYPE)
+      .map(|answer| join_txt_strings(&answer.data))
+      .collect(),
+  )
+}
+
+/// A TXT value is made of quoted strings of at most 255 bytes each, `"a" "b"` is the value `ab`.
+fn join_txt_strings(data: &str) -> String {
+  let data = data.trim();
+  if !data.starts_with('"') {
+    return data.to_string();
+  }
+  data
+    .split('"')
+    .skip(1)
+    .step_by(2)
+    .collect::<Vec<_>>()
+    .concat()
+}
+
+fn txt_record_name(domain: &str) -> String {
+  format!("{}.{}", DOMAIN_CHALLENGE_SUBDOMAIN, domain)
+}
+
+fn txt_record_value(verification_token: &str) -> String {
+  format!("{}{}", DOMAIN_VERIFICATION_PREFIX, verification_token)
+}
+
+fn workspace_domain_from_row(row: AFWorkspaceDomainRow) -> WorkspaceDomain {
+  WorkspaceDomain {
+    txt_record_name: txt_record_name(&row.domain),
+    txt_record_value: txt_record_value(&row.verification_token),
+    domain: row.domain,
+    verified_at: row.verified_at,
+    created_at: row.created_at,
+  }
+}
@@ -0,0 +1,458 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use access_control::workspace::WorkspaceAccessControl;
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_database::rows::{meta_id_from_row_id, RowId, RowMetaKey};
+use collab_entity::{CollabType, EncodedCollab};
+use collab_folder::Folder;
+use database::collab::{select_workspace_collab_oids, CollabStorage, GetCollabOrigin};
+use database::workspace::{insert_user_workspace, select_workspace};
+use database_entity::dto::{AFRole, AFWorkspace, CollabParams, QueryCollab, QueryCollabResult};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::{
+  Any, Array, ArrayPrelim, ArrayRef, Map, MapPrelim, MapRef, Out, ReadTxn, Text, TextPrelim,
+  TextRef, TransactionMut,
+};
+
+use crate::biz::collab::ops::get_latest_collab_folder;
+use crate::biz::user::user_init::create_user_awareness;
+use crate::biz::workspace::ops::collab_from_doc_state;
+use crate::biz::workspace::residency::StorageRouter;
+
+/// The number of collabs read and written at once while duplicating a workspace.
+const DUPLICATE_COLLAB_BATCH_SIZE: usize = 50;
+
+/// Length of a hyphenated uuid, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+const UUID_LEN: usize = 36;
+
+/// Deep-copies all the collabs, the folder structure and the blobs of a workspace into a new
+/// workspace owned by the user.
+///
+/// The objects of the new workspace get new ids. The ids of the workspace, the collabs and the
+/// views are replaced wherever they appear in the copied collabs: the keys of the maps, the
+/// values, and the urls embedding them such as the urls of the blobs. The user awareness collabs
+/// are personal, the user gets a new one instead.
+#[allow(clippy::too_many_arguments)]
+pub async fn duplicate_workspace(
+  pg_pool: &PgPool,
+  workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_storage: &Arc<CollabAccessControlStorage>,
+  storage_router: &StorageRouter,
+  user_uuid: &Uuid,
+  uid: i64,
+  workspace_id: &Uuid,
+  workspace_name: Option<String>,
+) -> Result<AFWorkspace, AppError> {
+  let workspace = select_workspace(pg_pool, workspace_id).await?;
+  let workspace_name = workspace_name
+    .filter(|name| !name.trim().is_empty())
+    .unwrap_or_else(|| {
+      format!(
+        "{} (copy)",
+        workspace.workspace_name.as_deref().unwrap_or_default()
+      )
+    });
+  let residency = storage_router
+    .pg_pool_router()
+    .residency(workspace_id)
+    .await?;
+
+  let mut txn = pg_pool.begin().await?;
+  let new_workspace_row = insert_user_workspace(&mut txn, user_uuid, &workspace_name, true).await?;
+  let new_workspace_id = new_workspace_row.workspace_id;
+  storage_router
+    .assign_residency(&mut txn, &new_workspace_id, residency.as_deref())
+    .await?;
+  let result: Result<(), AppError> = async {
+    workspace_access_control
+      .insert_role(&uid, &new_workspace_id, AFRole::Owner)
+      .await?;
+    create_user_awareness(
+      &uid,
+      user_uuid,
+      &new_workspace_id.to_string(),
+      collab_storage,
+      &mut txn,
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(())
+  }
+  .await;
+  if let Err(err) = result {
+    storage_router
+      .unassign_residency(&new_workspace_id, residency.as_deref())
+      .await;
+    return Err(err);
+  }
+
+  let mut collabs = vec![];
+  for collab_pg_pool in storage_router
+    .pg_pool_router()
+    .pg_pools_for_workspace_collabs(workspace_id)
+    .await?
+  {
+    collabs.extend(
+      select_workspace_collab_oids(&collab_pg_pool, workspace_id)
+        .await?
+        .into_iter()
+        .filter(|(_, collab_type)| *collab_type != CollabType::UserAwareness),
+    );
+  }
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  let id_mapping = Arc::new(IdMapping::new(
+    workspace_id,
+    &new_workspace_id,
+    workspace
+      .database_storage_id
+      .as_ref()
+      .zip(new_workspace_row.database_storage_id.as_ref()),
+    &collabs,
+    folder_view_ids(&folder, workspace_id),
+  ));
+
+  let new_workspace_id_str = new_workspace_id.to_string();
+  for chunk in collabs.chunks(DUPLICATE_COLLAB_BATCH_SIZE) {
+    let queries = chunk
+      .iter()
+      .map(|(object_id, collab_type)| QueryCollab {
+        object_id: object_id.clone(),
+        collab_type: collab_type.clone(),
+      })
+      .collect::<Vec<_>>();
+    let mut results = collab_storage.batch_get_collab(&uid, queries, true).await;
+
+    let mut params_list = Vec::with_capacity(chunk.len());
+    for (object_id, collab_type) in chunk {
+      let encoded_collab_v1 = match results.remove(object_id) {
+        Some(QueryCollabResult::Success { encode_collab_v1 }) => encode_collab_v1,
+        Some(QueryCollabResult::Failed { error }) => {
+          warn!("Skip duplicating collab {}: {}", object_id, error);
+          continue;
+        },
+        None => continue,
+      };
+      params_list.push(
+        spawn_blocking_copy_collab(
+          object_id,
+          collab_type,
+          encoded_collab_v1,
+          id_mapping.clone(),
+        )
+        .await?,
+      );
+    }
+    collab_storage
+      .batch_insert_new_collab(&new_workspace_id_str, &uid, params_list)
+      .await?;
+  }
+
+  let blob_count = storage_router
+    .bucket_storage(workspace_id)
+    .await?
+    .copy_workspace_blobs(workspace_id, &new_workspace_id)
+    .await?;
+  info!(
+    "Duplicated workspace {} to {}: {} collabs, {} blobs",
+    workspace_id,
+    new_workspace_id,
+    collabs.len(),
+    blob_count
+  );
+
+  AFWorkspace::try_from(new_workspace_row)
+}
+
+/// Returns the ids of all the views of the folder, walking the view hierarchy from the workspace.
+fn folder_view_ids(folder: &Folder, workspace_id: &Uuid) -> Vec<String> {
+  let mut view_ids = vec![];
+  let mut queue = VecDeque::from([workspace_id.to_string()]);
+  let mut visited = HashSet::new();
+  while let Some(view_id) = queue.pop_front() {
+    if !visited.insert(view_id.clone()) {
+      continue;
+    }
+    if let Some(view) = folder.get_view(&view_id) {
+      queue.extend(view.children.iter().map(|child| child.id.clone()));
+    }
+    view_ids.push(view_id);
+  }
+  view_ids
+}
+
+/// Maps the ids of the objects of the source workspace to the ids of their copies.
+pub(super) struct IdMapping {
+  ids: HashMap<String, String>,
+}
+
+impl IdMapping {
+  fn new(
+    workspace_id: &Uuid,
+    new_workspace_id: &Uuid,
+    database_storage_ids: Option<(&Uuid, &Uuid)>,
+    collabs: &[(String, CollabType)],
+    view_ids: Vec<String>,
+  ) -> Self {
+    let mut id_mapping = Self::for_collabs(collabs, view_ids);
+    id_mapping
+      .ids
+      .insert(workspace_id.to_string(), new_workspace_id.to_string());
+    if let Some((database_storage_id, new_database_storage_id)) = database_storage_ids {
+      id_mapping.ids.insert(
+        database_storage_id.to_string(),
+        new_database_storage_id.to_string(),
+      );
+    }
+    id_mapping
+  }
+
+  /// Gives new ids to the collabs and the views, the other ids are kept. Used to duplicate part
+  /// of a workspace within the same workspace.
+  pub(super) fn for_collabs(collabs: &[(String, CollabType)], view_ids: Vec<String>) -> Self {
+    let mut ids = HashMap::new();
+
+    // The id of the document of a row is derived from the id of the row.
+    let object_ids = collabs
+      .iter()
+      .map(|(object_id, _)| object_id.as_str())
+      .collect::<HashSet<_>>();
+    for (object_id, collab_type) in collabs {
+      if *collab_type != CollabType::DatabaseRow || ids.contains_key(object_id) {
+        continue;
+      }
+      let new_row_id = Uuid::new_v4().to_string();
+      let row_document_id =
+        meta_id_from_row_id(&RowId::from(object_id.clone()), RowMetaKey::DocumentId);
+      if object_ids.contains(row_document_id.as_str()) {
+        let new_row_document_id =
+          meta_id_from_row_id(&RowId::from(new_row_id.clone()), RowMetaKey::DocumentId);
+        ids.insert(row_document_id, new_row_document_id);
+      }
+      ids.insert(object_id.clone(), new_row_id);
+    }
+
+    for id in collabs
+      .iter()
+      .map(|(object_id, _)| object_id.clone())
+      .chain(view_ids)
+    {
+      ids.entry(id).or_insert_with(|| Uuid::new_v4().to_string());
+    }
+    Self { ids }
+  }
+
+  pub(super) fn new_id(&self, id: &str) -> Option<&String> {
+    self.ids.get(id)
+  }
+
+  /// Replaces the ids embedded in the string. Returns `None` if the string has no id to replace.
+  fn remap_str(&self, s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut remapped = String::new();
+    let mut last = 0;
+    let mut i = 0;
+    while i + UUID_LEN <= bytes.len() {
+      if is_hyphenated_uuid(&bytes[i..i + UUID_LEN]) {
+        if let Some(new_id) = self.new_id(&s[i..i + UUID_LEN]) {
+          remapped.push_str(&s[last..i]);
+          remapped.push_str(new_id);
+          i += UUID_LEN;
+          last = i;
+          continue;
+        }
+      }
+      i += 1;
+    }
+    if last == 0 {
+      return None;
+    }
+    remapped.push_str(&s[last..]);
+    Some(remapped)
+  }
+
+  pub(super) fn remap_key(&self, key: &str) -> String {
+    self.remap_str(key).unwrap_or_else(|| key.to_string())
+  }
+
+  fn remap_any(&self, any: Any) -> Any {
+    match any {
+      Any::String(s) => match self.remap_str(&s) {
+        Some(remapped) => Any::String(Arc::from(remapped)),
+        None => Any::String(s),
+      },
+      Any::Array(items) => Any::Array(
+        items
+          .iter()
+          .map(|item| self.remap_any(item.clone()))
+          .collect::<Vec<_>>()
+          .into(),
+      ),
+      Any::Map(map) => Any::Map(Arc::new(
+        map
+          .iter()
+          .map(|(key, value)| (self.remap_key(key), self.remap_any(value.clone())))
+          .collect(),
+      )),
+      any => any,
+    }
+  }
+
+  fn copy_map<T: ReadTxn>(
+    &self,
+    src_txn: &T,
+    src: &MapRef,
+    dest_txn: &mut TransactionMut,
+    dest: &MapRef,
+  ) {
+    for (key, value) in src.iter(src_txn) {
+      let key = self.remap_key(key);
+      match value {
+        Out::Any(any) => {
+          dest.insert(dest_txn, key, self.remap_any(any));
+        },
+        Out::YMap(map) => {
+          let new_map = dest.insert(dest_txn, key, MapPrelim::default());
+          self.copy_map(src_txn, &map, dest_txn, &new_map);
+        },
+        Out::YArray(array) => {
+          let new_array = dest.insert(dest_txn, key, ArrayPrelim::default());
+          self.copy_array(src_txn, &array, dest_txn, &new_array);
+        },
+        Out::YText(text) => {
+          let new_text = dest.insert(dest_txn, key, TextPrelim::new(""));
+          self.copy_text(src_txn, &text, dest_txn, &new_text);
+        },
+        value => warn!("Skip duplicating unsupported value: {:?}", value),
+      }
+    }
+  }
+
+  fn copy_array<T: ReadTxn>(
+    &self,
+    src_txn: &T,
+    src: &ArrayRef,
+    dest_txn: &mut TransactionMut,
+    dest: &ArrayRef,
+  ) {
+    for item in src.iter(src_txn) {
+      match item {
+        Out::Any(any) => {
+          dest.push_back(dest_txn, self.remap_any(any));
+        },
+        Out::YMap(map) => {
+          let new_map = dest.push_back(dest_txn, MapPrelim::default());
+          self.copy_map(src_txn, &map, dest_txn, &new_map);
+        },
+        Out::YArray(array) => {
+          let new_array = dest.push_back(dest_txn, ArrayPrelim::default());
+          self.copy_array(src_txn, &array, dest_txn, &new_array);
+        },
+        Out::YText(text) => {
+          let new_text = dest.push_back(dest_txn, TextPrelim::new(""));
+          self.copy_text(src_txn, &text, dest_txn, &new_text);
+        },
+        item => warn!("Skip duplicating unsupported value: {:?}", item),
+      }
+    }
+  }
+
+  /// Copies the text chunk by chunk, keeping the formatting. The attributes may embed ids, e.g.
+  /// the mentions of the pages.
+  fn copy_text<T: ReadTxn>(
+    &self,
+    src_txn: &T,
+    src: &TextRef,
+    dest_txn: &mut TransactionMut,
+    dest: &TextRef,
+  ) {
+    for chunk in src.diff(src_txn, YChange::identity) {
+      let index = dest.len(dest_txn);
+      let attributes = chunk.attributes.map(|attributes| {
+        attributes
+          .iter()
+          .map(|(key, value)| (key.clone(), self.remap_any(value.clone())))
+          .collect()
+      });
+      match chunk.insert {
+        Out::Any(Any::String(s)) => {
+          let s = self.remap_str(&s).unwrap_or_else(|| s.to_string());
+          match attributes {
+            Some(attributes) => dest.insert_with_attributes(dest_txn, index, &s, attributes),
+            None => dest.insert(dest_txn, index, &s),
+          }
+        },
+        Out::Any(embed) => {
+          let embed = self.remap_any(embed);
+          match attributes {
+            Some(attributes) => {
+              dest.insert_embed_with_attributes(dest_txn, index, embed, attributes);
+            },
+            None => {
+              dest.insert_embed(dest_txn, index, embed);
+            },
+          }
+        },
+        insert => warn!("Skip duplicating unsupported text chunk: {:?}", insert),
+      }
+    }
+  }
+}
+
+fn is_hyphenated_uuid(bytes: &[u8]) -> bool {
+  bytes.iter().enumerate().all(|(i, b)| match i {
+    8 | 13 | 18 | 23 => *b == b'-',
+    _ => b.is_ascii_hexdigit(),
+  })
+}
+
+pub(super) async fn spawn_blocking_copy_collab(
+  object_id: &str,
+  collab_type: &CollabType,
+  encoded_collab_v1: Vec<u8>,
+  id_mapping: Arc<IdMapping>,
+) -> Result<CollabParams, AppError> {
+  let object_id = object_id.to_string();
+  let collab_type = collab_type.clone();
+  tokio::task::spawn_blocking(move || {
+    let new_object_id = id_mapping
+      .new_id(&object_id)
+      .cloned()
+      .ok_or_else(|| AppError::Internal(anyhow!("No new id for collab {}", object_id)))?;
+    let encoded_collab = EncodedCollab::decode_from_bytes(&encoded_collab_v1)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decode collab: {}", err)))?;
+    let collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id)?;
+
+    let mut new_collab =
+      Collab::new_with_origin(CollabOrigin::Server, &new_object_id, vec![], false);
+    {
+      let src_txn = collab.transact();
+      let mut dest_txn = new_collab.context.transact_mut();
+      id_mapping.copy_map(&src_txn, &collab.data, &mut dest_txn, &new_collab.data);
+    }
+
+    let encoded_collab_v1 = new_collab
+      .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode collab: {}", err)))?
+      .encode_to_bytes()?;
+    Ok(CollabParams {
+      object_id: new_object_id,
+      collab_type,
+      encoded_collab_v1: encoded_collab_v1.into(),
+      embeddings: None,
+    })
+  })
+  .await?
+}
@@ -0,0 +1,43 @@
+use collab_rt_entity::user::{AFFolderChange, AFFolderChangeKind};
+use database::listener::notify_listeners;
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::biz::pg_listener::FOLDER_CHANGE_CHANNEL;
+
+/// The most view ids sent in a single notification, to stay below the size limit of the
+/// notification payloads. A larger change is split in several notifications.
+const MAX_VIEW_IDS_PER_NOTIFICATION: usize = 100;
+
+/// Tells the members connected to the workspace that views of its folder changed, so that they
+/// don't have to poll the folder. The change has already been saved when it's notified, so a
+/// failure is logged instead of failing the request.
+pub async fn notify_folder_change(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+  kind: AFFolderChangeKind,
+  view_ids: Vec<String>,
+  parent_view_id: Option<String>,
+) {
+  for view_ids in view_ids.chunks(MAX_VIEW_IDS_PER_NOTIFICATION) {
+    let change = AFFolderChange {
+      workspace_id: workspace_id.to_string(),
+      uid,
+      kind,
+      view_ids: view_ids.to_vec(),
+      parent_view_id: parent_view_id.clone(),
+    };
+    let result = match serde_json::to_string(&change) {
+      Ok(payload) => notify_listeners(pg_pool, FOLDER_CHANGE_CHANNEL, &payload).await,
+      Err(err) => Err(err.into()),
+    };
+    if let Err(err) = result {
+      error!(
+        "Failed to notify the {:?} folder change of workspace {}: {:?}",
+        kind, workspace_id, err
+      );
+    }
+  }
+}
@@ -0,0 +1,187 @@
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use access_control::collab::CollabAccessControl;
+use anyhow::Context;
+use app_error::{AppError, ErrorDetails};
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::collab::{is_collab_member_exists, upsert_collab_member_with_txn, GetCollabOrigin};
+use database::user::select_uid_from_uuid;
+use database::workspace::{
+  insert_workspace_invitation_guest_views, select_workspace_member_list,
+  select_workspace_pending_invitations,
+};
+use database_entity::dto::{AFAccessLevel, AFRole};
+use shared_entity::dto::workspace_dto::{InviteWorkspaceGuest, WorkspaceMemberInvitation};
+use sqlx::{PgPool, Transaction};
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_folder;
+use crate::biz::workspace::ops::invite_workspace_members;
+use crate::biz::workspace::permission_audit::record_collab_permission_change;
+use crate::mailer::AFCloudMailer;
+use crate::state::GoTrueAdmin;
+
+const MAX_GUEST_VIEWS_PER_INVITATION: usize = 100;
+
+/// Invites a guest to read the given views of the workspace. The views are granted when the
+/// invitation is accepted, or right away if the user is already a guest of the workspace.
+#[allow(clippy::too_many_arguments)]
+pub async fn invite_workspace_guest(
+  mailer: &AFCloudMailer,
+  gotrue_admin: &GoTrueAdmin,
+  pg_pool: &PgPool,
+  gotrue_client: &gotrue::api::Client,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  inviter: &Uuid,
+  workspace_id: &Uuid,
+  params: InviteWorkspaceGuest,
+  appflowy_web_url: Option<&str>,
+) -> Result<(), AppError> {
+  let mut view_ids = params.view_ids;
+  view_ids.sort();
+  view_ids.dedup();
+  if view_ids.is_empty() {
+    return Err(
+      AppError::InvalidRequest("At least one view must be granted to the guest".to_string())
+        .with_details(ErrorDetails::field("view_ids", "empty")),
+    );
+  }
+  if view_ids.len() > MAX_GUEST_VIEWS_PER_INVITATION {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "At most {} views can be granted at once",
+        MAX_GUEST_VIEWS_PER_INVITATION
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_GUEST_VIEWS_PER_INVITATION as i64,
+        Some(view_ids.len() as i64),
+      )),
+    );
+  }
+
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  for view_id in &view_ids {
+    if view_id == &workspace_id.to_string() || folder.get_view(view_id).is_none() {
+      return Err(AppError::InvalidRequest(format!(
+        "View {} doesn't belong to the workspace {}",
+        view_id, workspace_id
+      )));
+    }
+  }
+
+  let member = select_workspace_member_list(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .find(|member| member.email == params.email);
+  if let Some(member) = member {
+    if member.role != AFRole::Guest {
+      return Err(AppError::InvalidRequest(format!(
+        "User with email {} is already a member of the workspace",
+        params.email
+      )));
+    }
+    let mut txn = pg_pool
+      .begin()
+      .await
+      .context("Begin transaction to grant guest views")?;
+    let granted_view_ids = grant_guest_views(
+      &mut txn,
+      collab_access_control,
+      workspace_id,
+      member.uid,
+      &view_ids,
+    )
+    .await?;
+    txn.commit().await?;
+    let inviter_uid = select_uid_from_uuid(pg_pool, inviter).await?;
+    record_guest_views_granted(
+      pg_pool,
+      workspace_id,
+      inviter_uid,
+      member.uid,
+      &granted_view_ids,
+    )
+    .await;
+    return Ok(());
+  }
+
+  invite_workspace_members(
+    mailer,
+    gotrue_admin,
+    pg_pool,
+    gotrue_client,
+    inviter,
+    workspace_id,
+    vec![WorkspaceMemberInvitation {
+      email: params.email.clone(),
+      role: AFRole::Guest,
+    }],
+    None,
+    appflowy_web_url,
+  )
+  .await?;
+  let invite_id = select_workspace_pending_invitations(pg_pool, workspace_id)
+    .await?
+    .remove(&params.email)
+    .ok_or_else(|| AppError::RecordNotFound(format!("Invitation for {}", params.email)))?;
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("Begin transaction to insert guest views")?;
+  insert_workspace_invitation_guest_views(&mut txn, &invite_id, &view_ids).await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+/// Grants read-only access to the views, and returns the views that were granted. The views that
+/// the guest can already access keep their access level.
+pub async fn grant_guest_views(
+  txn: &mut Transaction<'_, sqlx::Postgres>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  workspace_id: &Uuid,
+  uid: i64,
+  view_ids: &[String],
+) -> Result<Vec<String>, AppError> {
+  let mut granted_view_ids = vec![];
+  for view_id in view_ids {
+    if is_collab_member_exists(uid, view_id, txn.deref_mut()).await? {
+      continue;
+    }
+    upsert_collab_member_with_txn(uid, workspace_id, view_id, &AFAccessLevel::ReadOnly, txn)
+      .await?;
+    collab_access_control
+      .update_access_level_policy(&uid, view_id, AFAccessLevel::ReadOnly)
+      .await?;
+    granted_view_ids.push(view_id.clone());
+  }
+  Ok(granted_view_ids)
+}
+
+/// Records the views granted to the guest in the permission audit log.
+pub async fn record_guest_views_granted(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  actor_uid: i64,
+  guest_uid: i64,
+  view_ids: &[String],
+) {
+  for view_id in view_ids {
+    record_collab_permission_change(
+      pg_pool,
+      workspace_id,
+      Some(actor_uid),
+      guest_uid,
+      view_id,
+      None,
+      Some(AFAccessLevel::ReadOnly),
+    )
+    .await;
+  }
+}
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use database::insights::{
+  delete_published_view_access_before, select_active_member_count,
+  select_daily_most_visited_published_views, select_daily_published_view_visit_counts,
+  select_daily_published_view_visitor_counts, select_daily_top_published_view_referrers,
+  select_most_edited_views, select_most_visited_published_views, select_new_member_count,
+  select_published_view_comment_count, select_published_view_visit_count,
+  upsert_published_view_access,
+};
+use database::workspace::select_workspace_member_count_from_workspace_id;
+use sha2::{Digest, Sha256};
+use shared_entity::dto::workspace_dto::{
+  InsightsRange, PublishAccessLog, PublishAccessLogDay, PublishReferrerCount,
+  PublishedViewVisitInsight, ViewEditInsight, WorkspaceInsights,
+};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::biz::scheduler::Scheduler;
+
+/// The maximum number of views listed in the rankings of the insights.
+const INSIGHTS_TOP_VIEWS_LIMIT: i64 = 10;
+
+/// Number of days the visitors and the referrers of the published views are kept for.
+pub const PUBLISH_ACCESS_LOG_RETENTION_DAYS: i64 = 30;
+
+/// The maximum number of referrers and pages listed for each day of the access log.
+const PUBLISH_ACCESS_LOG_TOP_LIMIT: i64 = 5;
+
+/// How often the visitors and the referrers older than the retention are removed.
+const PUBLISH_ACCESS_LOG_CLEANUP_INTERVAL: std::time::Duration =
+  std::time::Duration::from_secs(60 * 60);
+
+const DIRECT_REFERRER: &str = "direct";
+
+pub async fn get_workspace_insights(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  range: InsightsRange,
+) -> Result<WorkspaceInsights, AppResponseError> {
+  let since = Utc::now() - Duration::days(range.days());
+  let member_count = select_workspace_member_count_from_workspace_id(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default();
+  let active_member_count = select_active_member_count(pg_pool, workspace_id, since).await?;
+  let new_member_count = select_new_member_count(pg_pool, workspace_id, since).await?;
+  let most_edited_views =
+    select_most_edited_views(pg_pool, workspace_id, since, INSIGHTS_TOP_VIEWS_LIMIT)
+      .await?
+      .into_iter()
+      .map(|row| ViewEditInsight {
+        view_id: row.oid,
+        edit_count: row.edit_count,
+        editor_count: row.editor_count,
+      })
+      .collect();
+  let comment_count = select_published_view_comment_count(pg_pool, workspace_id, since).await?;
+  let publish_visit_count = select_published_view_visit_count(pg_pool, workspace_id, since).await?;
+  let most_visited_published_views =
+    select_most_visited_published_views(pg_pool, workspace_id, since, INSIGHTS_TOP_VIEWS_LIMIT)
+      .await?
+      .into_iter()
+      .map(|row| PublishedViewVisitInsight {
+        view_id: row.view_id,
+        publish_name: row.publish_name,
+        visit_count: row.visit_count,
+      })
+      .collect();
+
+  Ok(WorkspaceInsights {
+    range,
+    since,
+    member_count,
+    active_member_count,
+    new_member_count,
+    most_edited_views,
+    comment_count,
+    publish_visit_count,
+    most_visited_published_views,
+  })
+}
+
+/// Records the visitor and the referrer of a visit of a published view.
+///
+/// The visitor is identified by a hash of its IP address and user agent, salted with a server
+/// secret and the current date. The hash can't be reversed to the IP address, and the same
+/// visitor can't be followed across days. Only the host of the referrer is kept.
+pub async fn record_published_view_access(
+  pg_pool: &PgPool,
+  secret: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+  ip: Option<&str>,
+  user_agent: Option<&str>,
+  referrer: Option<&str>,
+) -> Result<(), AppResponseError> {
+  let visitor_hash = visitor_hash(
+    secret,
+    Utc::now().date_naive(),
+    ip.unwrap_or_default(),
+    user_agent.unwrap_or_default(),
+  );
+  let referrer = referrer_host(referrer);
+  upsert_published_view_access(
+    pg_pool,
+    publish_namespace,
+    publish_name,
+    &visitor_hash,
+    &referrer,
+  )
+  .await?;
+  Ok(())
+}
+
+pub(crate) fn visitor_hash(secret: &str, date: NaiveDate, ip: &str, user_agent: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(secret.as_bytes());
+  hasher.update(date.to_string().as_bytes());
+  hasher.update(ip.as_bytes());
+  hasher.update(user_agent.as_bytes());
+  hex::encode(hasher.finalize())
+}
+
+fn referrer_host(referrer: Option<&str>) -> String {
+  referrer
+    .and_then(|referrer| url::Url::parse(referrer).ok())
+    .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+    .unwrap_or_else(|| DIRECT_REFERRER.to_string())
+}
+
+/// Returns the unique visitors, the visits, the top referrers and the top pages of each day of
+/// the published views of the workspace, for the last `days` days within the retention.
+pub async fn get_publish_access_log(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  days: Option<i64>,
+) -> Result<PublishAccessLog, AppResponseError> {
+  let days = days
+    .unwrap_or(PUBLISH_ACCESS_LOG_RETENTION_DAYS)
+    .clamp(1, PUBLISH_ACCESS_LOG_RETENTION_DAYS);
+  let since = Utc::now().date_naive() - Duration::days(days - 1);
+
+  let mut log_days: BTreeMap<NaiveDate, PublishAccessLogDay> = BTreeMap::new();
+  for (date, visit_count) in
+    select_daily_published_view_visit_counts(pg_pool, workspace_id, since).await?
+  {
+    access_log_day(&mut log_days, date).visit_count = visit_count;
+  }
+  for row in select_daily_published_view_visitor_counts(pg_pool, workspace_id, since).await? {
+    access_log_day(&mut log_days, row.visit_date).unique_visitor_count = row.visitor_count;
+  }
+  for row in select_daily_top_published_view_referrers(
+    pg_pool,
+    workspace_id,
+    since,
+    PUBLISH_ACCESS_LOG_TOP_LIMIT,
+  )
+  .await?
+  {
+    access_log_day(&mut log_days, row.visit_date)
+      .top_referrers
+      .push(PublishReferrerCount {
+        referrer: row.referrer,
+        visit_count: row.visit_count,
+      });
+  }
+  for row in select_daily_most_visited_published_views(
+    pg_pool,
+    workspace_id,
+    since,
+    PUBLISH_ACCESS_LOG_TOP_LIMIT,
+  )
+  .await?
+  {
+    access_log_day(&mut log_days, row.visit_date)
+      .top_pages
+      .push(PublishedViewVisitInsight {
+        view_id: row.view_id,
+        publish_name: row.publish_name,
+        visit_count: row.visit_count,
+      });
+  }
+
+  Ok(PublishAccessLog {
+    retention_days: PUBLISH_ACCESS_LOG_RETENTION_DAYS,
+    days: log_days.into_values().rev().collect(),
+  })
+}
+
+fn access_log_day(
+  log_days: &mut BTreeMap<NaiveDate, PublishAccessLogDay>,
+  date: NaiveDate,
+) -> &mut PublishAccessLogDay {
+  log_days.entry(date).or_insert_with(|| PublishAccessLogDay {
+    date,
+    visit_count: 0,
+    unique_visitor_count: 0,
+    top_referrers: vec![],
+    top_pages: vec![],
+  })
+}
+
+/// Periodically removes the visitors and the referrers of the published views that are older
+/// than the retention of the access log.
+pub fn register_publish_access_log_cleanup_job(scheduler: &Scheduler, pg_pool: PgPool) {
+  scheduler.register(
+    "publish_access_log_cleanup",
+    PUBLISH_ACCESS_LOG_CLEANUP_INTERVAL,
+    move || {
+      let pg_pool = pg_pool.clone();
+      async move {
+        let before =
+          Utc::now().date_naive() - Duration::days(PUBLISH_ACCESS_LOG_RETENTION_DAYS - 1);
+        let count = delete_published_view_access_before(&pg_pool, before).await?;
+        if count > 0 {
+          info!("Removed {} expired publish access log entries", count);
+        }
+        Ok(())
+      }
+    },
+  );
+}
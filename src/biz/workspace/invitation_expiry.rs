@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use database::workspace::update_expired_workspace_invitations;
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::biz::scheduler::Scheduler;
+
+/// How often the pending invitations are checked for expiry.
+const INVITATION_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Periodically marks the pending invitations whose expiry has passed as expired.
+///
+/// Expired invitations are already rejected when they're accepted, this job makes sure the
+/// invitees and the inviters see the invitations as expired.
+pub fn register_invitation_expiry_job(scheduler: &Scheduler, pg_pool: PgPool) {
+  scheduler.register(
+    "invitation_expiry",
+    INVITATION_EXPIRY_CHECK_INTERVAL,
+    move || {
+      let pg_pool = pg_pool.clone();
+      async move {
+        let count = update_expired_workspace_invitations(&pg_pool).await?;
+        if count > 0 {
+          info!("Marked {} workspace invitations as expired", count);
+        }
+        Ok(())
+      }
+    },
+  );
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::HttpRequest;
+use app_error::AppError;
+use dashmap::DashMap;
+use database::workspace::{select_all_user_workspaces, select_workspace_settings};
+use ipnet::IpNet;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const X_FORWARDED_FOR: &str = "X-Forwarded-For";
+
+const MAX_IP_ALLOWLIST_RANGES: usize = 100;
+
+/// How long the allowlist of a workspace is cached. An allowlist changed through another server is
+/// applied once its cached copy expires.
+const IP_ALLOWLIST_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The expired allowlists are removed once the cache holds this many workspaces.
+const MAX_CACHED_IP_ALLOWLISTS: usize = 10_000;
+
+/// The CIDR ranges the requests to a workspace must come from, `None` if every address is allowed.
+#[derive(Debug, Clone, Default)]
+pub struct IpAllowlist(Option<Vec<IpNet>>);
+
+impl IpAllowlist {
+  /// The client whose address is unknown is only allowed when every address is.
+  pub fn allows(&self, ip: Option<IpAddr>) -> bool {
+    match (&self.0, ip) {
+      (None, _) => true,
+      (Some(ranges), Some(ip)) => ranges.iter().any(|range| range.contains(&ip)),
+      (Some(_), None) => false,
+    }
+  }
+}
+
+/// Checks the ranges of an allowlist and returns them normalized, e.g. `10.0.0.1/8` becomes
+/// `10.0.0.0/8`. A single address is a range of one address.
+pub fn normalize_ip_allowlist(ranges: &[String]) -> Result<Vec<String>, AppError> {
+  if ranges.len() > MAX_IP_ALLOWLIST_RANGES {
+    return Err(AppError::InvalidRequest(format!(
+      "An IP allowlist can't have more than {} ranges",
+      MAX_IP_ALLOWLIST_RANGES
+    )));
+  }
+  let mut seen = HashSet::new();
+  ranges
+    .iter()
+    .map(|range| {
+      parse_ip_range(range).ok_or_else(|| {
+        AppError::InvalidRequest(format!("{} is not a valid CIDR range", range.trim()))
+      })
+    })
+    .filter(|range| !matches!(range, Ok(range) if !seen.insert(*range)))
+    .map(|range| range.map(|range| range.to_string()))
+    .collect()
+}
+
+/// Returns the allowlist of the saved ranges, which were checked when they were saved. A range that
+/// can't be parsed anymore is skipped, but the workspace stays restricted.
+pub fn parse_ip_allowlist(ranges: &[String]) -> IpAllowlist {
+  IpAllowlist((!ranges.is_empty()).then(|| {
+    ranges
+      .iter()
+      .filter_map(|range| parse_ip_range(range))
+      .collect()
+  }))
+}
+
+fn parse_ip_range(range: &str) -> Option<IpNet> {
+  let range = range.trim();
+  IpNet::from_str(range)
+    .map(|range| range.trunc())
+    .or_else(|_| IpAddr::from_str(range).map(IpNet::from))
+    .ok()
+}
+
+/// Caches the IP allowlists of the workspaces, which are checked on every request to a workspace.
+#[derive(Default)]
+pub struct IpAllowlistCache {
+  allowlists: DashMap<Uuid, CachedIpAllowlist>,
+}
+
+struct CachedIpAllowlist {
+  allowlist: Arc<IpAllowlist>,
+  cached_at: Instant,
+}
+
+impl IpAllowlistCache {
+  pub async fn get(
+    &self,
+    pg_pool: &PgPool,
+    workspace_id: &Uuid,
+  ) -> Result<Arc<IpAllowlist>, AppError> {
+    if let Some(cached) = self.allowlists.get(workspace_id) {
+      if cached.cached_at.elapsed() < IP_ALLOWLIST_CACHE_TTL {
+        return Ok(cached.allowlist.clone());
+      }
+    }
+
+    let ranges = select_workspace_settings(pg_pool, workspace_id)
+      .await?
+      .map(|settings| settings.ip_allowlist)
+      .unwrap_or_default();
+    let allowlist = Arc::new(parse_ip_allowlist(&ranges));
+    if self.allowlists.len() >= MAX_CACHED_IP_ALLOWLISTS {
+      self
+        .allowlists
+        .retain(|_, cached| cached.cached_at.elapsed() < IP_ALLOWLIST_CACHE_TTL);
+    }
+    self.allowlists.insert(
+      *workspace_id,
+      CachedIpAllowlist {
+        allowlist: allowlist.clone(),
+        cached_at: Instant::now(),
+      },
+    );
+    Ok(allowlist)
+  }
+
+  /// Drops the cached allowlist of the workspace once it changed.
+  pub fn invalidate(&self, workspace_id: &Uuid) {
+    self.allowlists.remove(workspace_id);
+  }
+}
+
+/// Checks that the client is within the IP allowlist of the workspace.
+pub async fn check_ip_allowlist(
+  cache: &IpAllowlistCache,
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  client_ip: Option<IpAddr>,
+) -> Result<(), AppError> {
+  if cache.get(pg_pool, workspace_id).await?.allows(client_ip) {
+    return Ok(());
+  }
+  Err(AppError::IpNotAllowed(match client_ip {
+    Some(ip) => format!(
+      "The address {} is not allowed to access the workspace {}",
+      ip, workspace_id
+    ),
+    None => format!(
+      "The address of the client is unknown, the workspace {} only allows some addresses",
+      workspace_id
+    ),
+  }))
+}
+
+/// Returns the workspaces of the user whose IP allowlist the client is outside of.
+pub async fn select_blocked_workspace_ids(
+  cache: &IpAllowlistCache,
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  client_ip: Option<IpAddr>,
+) -> Result<HashSet<String>, AppError> {
+  let mut blocked_workspace_ids = HashSet::new();
+  for workspace in select_all_user_workspaces(pg_pool, user_uuid).await? {
+    if !cache
+      .get(pg_pool, &workspace.workspace_id)
+      .await?
+      .allows(client_ip)
+    {
+      blocked_workspace_ids.insert(workspace.workspace_id.to_string());
+    }
+  }
+  Ok(blocked_workspace_ids)
+}
+
+/// Returns the address of the client of the request, see [crate::config::config::ClientIpSetting].
+pub fn client_ip(req: &HttpRequest, trusted_proxy_count: usize) -> Option<IpAddr> {
+  if trusted_proxy_count == 0 {
+    return req.peer_addr().map(|addr| addr.ip());
+  }
+  let forwarded_for = req
+    .headers()
+    .get_all(X_FORWARDED_FOR)
+    .filter_map(|value| value.to_str().ok())
+    .flat_map(|value| value.split(','))
+    .map(str::trim)
+    .collect::<Vec<_>>();
+  // The addresses before the one appended by the outermost proxy are set by the client.
+  let client_addr = forwarded_for.get(forwarded_for.len().checked_sub(trusted_proxy_count)?)?;
+  IpAddr::from_str(client_addr)
+    .or_else(|_| SocketAddr::from_str(client_addr).map(|addr| addr.ip()))
+    .ok()
+}
@@ -0,0 +1,314 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use access_control::collab::RealtimeAccessControl;
+use app_error::AppError;
+use chrono::{DateTime, Utc};
+use database::pg_row::AFWorkspaceLifecycleRow;
+use database::workspace_lifecycle::{
+  reset_workspace_lifecycle, select_workspace_lifecycle, select_workspace_lifecycle_candidates,
+  update_workspace_lifecycle_archived, update_workspace_lifecycle_deletion,
+  update_workspace_lifecycle_warned, upsert_workspace_lifecycle_exempt,
+};
+use shared_entity::dto::server_info_dto::WorkspaceLifecycle;
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::scheduler::Scheduler;
+use crate::biz::workspace::ops::{delete_workspace_for_user, update_workspace_archived};
+use crate::biz::workspace::residency::StorageRouter;
+use crate::config::config::WorkspaceLifecycleSetting;
+use crate::mailer::{AFCloudMailer, WorkspaceLifecycleNotificationMailerParam};
+
+/// How often the inactive workspaces are checked.
+const WORKSPACE_LIFECYCLE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of workspaces checked on each run.
+const WORKSPACE_LIFECYCLE_BATCH_SIZE: i64 = 200;
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Periodically applies the lifecycle policies to the inactive workspaces: the owner is warned
+/// first, then the workspace is archived and finally deleted, if the instance is configured to do
+/// so. The owner is notified before each step. Editing the workspace, or unarchiving it, resets
+/// its lifecycle.
+pub fn register_workspace_lifecycle_job(
+  scheduler: &Scheduler,
+  pg_pool: PgPool,
+  realtime_access_control: Arc<dyn RealtimeAccessControl>,
+  storage_router: StorageRouter,
+  mailer: AFCloudMailer,
+  appflowy_web_url: Option<String>,
+  setting: &WorkspaceLifecycleSetting,
+) {
+  if setting.warn_after_days == 0 {
+    return;
+  }
+  let setting = setting.clone();
+  scheduler.register(
+    "workspace_lifecycle",
+    WORKSPACE_LIFECYCLE_CHECK_INTERVAL,
+    move || {
+      let pg_pool = pg_pool.clone();
+      let realtime_access_control = realtime_access_control.clone();
+      let storage_router = storage_router.clone();
+      let mailer = mailer.clone();
+      let appflowy_web_url = appflowy_web_url.clone();
+      let setting = setting.clone();
+      async move {
+        let rows = select_workspace_lifecycle_candidates(
+          &pg_pool,
+          setting.warn_after_days as i64 * SECS_PER_DAY,
+          WORKSPACE_LIFECYCLE_BATCH_SIZE,
+        )
+        .await?;
+        for row in rows {
+          let workspace_id = row.workspace_id;
+          let notifier = LifecycleNotifier {
+            mailer: &mailer,
+            appflowy_web_url: appflowy_web_url.as_deref(),
+            row: &row,
+          };
+          if let Err(err) = apply_lifecycle(
+            &pg_pool,
+            &realtime_access_control,
+            &storage_router,
+            &setting,
+            &notifier,
+            &row,
+          )
+          .await
+          {
+            error!(
+              "Failed to apply the lifecycle of workspace {}: {:?}",
+              workspace_id, err
+            );
+          }
+        }
+        Ok(())
+      }
+    },
+  );
+}
+
+async fn apply_lifecycle(
+  pg_pool: &PgPool,
+  realtime_access_control: &Arc<dyn RealtimeAccessControl>,
+  storage_router: &StorageRouter,
+  setting: &WorkspaceLifecycleSetting,
+  notifier: &LifecycleNotifier<'_>,
+  row: &AFWorkspaceLifecycleRow,
+) -> Result<(), AppResponseError> {
+  let workspace_id = &row.workspace_id;
+  let now = Utc::now();
+  let inactive_days = (now - row.last_active_at).num_days();
+
+  let warned_at = match row.warned_at {
+    Some(warned_at) => warned_at,
+    None => {
+      update_workspace_lifecycle_warned(pg_pool, workspace_id).await?;
+      notifier.notify(
+        format!("has been inactive for {} days", inactive_days),
+        upcoming_steps(setting, row),
+      );
+      return Ok(());
+    },
+  };
+  if row.last_active_at > warned_at {
+    info!(
+      "Workspace {} is active again, resetting its lifecycle",
+      workspace_id
+    );
+    reset_workspace_lifecycle(pg_pool, workspace_id).await?;
+    return Ok(());
+  }
+
+  if setting.archive_after_days > 0
+    && inactive_days >= setting.archive_after_days as i64
+    && row.archived_at.is_none()
+  {
+    if !row.archived {
+      update_workspace_archived(pg_pool, realtime_access_control.clone(), workspace_id, true)
+        .await?;
+    }
+    update_workspace_lifecycle_archived(pg_pool, workspace_id).await?;
+    info!("Archived inactive workspace {}", workspace_id);
+    notifier.notify(
+      format!(
+        "has been archived after {} days of inactivity",
+        inactive_days
+      ),
+      "Unarchive the workspace to keep using it.".to_string(),
+    );
+  }
+
+  if setting.delete_after_days == 0 {
+    return Ok(());
+  }
+  match row.delete_at {
+    None => {
+      let first_notice_days = setting.deletion_notice_days.first().copied().unwrap_or(0) as i64;
+      if inactive_days + first_notice_days < setting.delete_after_days as i64 {
+        return Ok(());
+      }
+      let delete_at = (row.last_active_at
+        + chrono::Duration::days(setting.delete_after_days as i64))
+      .max(now + chrono::Duration::days(first_notice_days));
+      update_workspace_lifecycle_deletion(
+        pg_pool,
+        workspace_id,
+        &delete_at,
+        first_notice_days as i32,
+      )
+      .await?;
+      info!(
+        "Scheduled the deletion of inactive workspace {} at {}",
+        workspace_id, delete_at
+      );
+      if first_notice_days > 0 {
+        notifier.notify_deletion(&delete_at);
+      }
+    },
+    Some(delete_at) if delete_at <= now => {
+      delete_workspace_for_user(pg_pool.clone(), *workspace_id, storage_router.clone()).await?;
+      info!("Deleted inactive workspace {}", workspace_id);
+    },
+    Some(delete_at) => {
+      let last_notice_days = row.deletion_notice_days.unwrap_or(i32::MAX) as i64;
+      let remaining_secs = (delete_at - now).num_seconds();
+      // Only the closest notice is sent if several are due, e.g. after the job was paused.
+      let due_notice_days = setting
+        .deletion_notice_days
+        .iter()
+        .map(|days| *days as i64)
+        .filter(|days| *days < last_notice_days && remaining_secs <= days * SECS_PER_DAY)
+        .min();
+      if let Some(notice_days) = due_notice_days {
+        update_workspace_lifecycle_deletion(pg_pool, workspace_id, &delete_at, notice_days as i32)
+          .await?;
+        notifier.notify_deletion(&delete_at);
+      }
+    },
+  }
+  Ok(())
+}
+
+fn upcoming_steps(setting: &WorkspaceLifecycleSetting, row: &AFWorkspaceLifecycleRow) -> String {
+  let date_after = |days: u64| {
+    (row.last_active_at + chrono::Duration::days(days as i64))
+      .max(Utc::now())
+      .format("%Y-%m-%d")
+      .to_string()
+  };
+  let mut steps = vec![];
+  if setting.archive_after_days > 0 && !row.archived {
+    steps.push(format!(
+      "it will be archived on {}",
+      date_after(setting.archive_after_days)
+    ));
+  }
+  if setting.delete_after_days > 0 {
+    steps.push(format!(
+      "it will be deleted on {} at the earliest",
+      date_after(setting.delete_after_days)
+    ));
+  }
+  if steps.is_empty() {
+    "Edit the workspace to keep it active.".to_string()
+  } else {
+    format!(
+      "Edit the workspace to keep it active, otherwise {}.",
+      steps.join(" and ")
+    )
+  }
+}
+
+struct LifecycleNotifier<'a> {
+  mailer: &'a AFCloudMailer,
+  appflowy_web_url: Option<&'a str>,
+  row: &'a AFWorkspaceLifecycleRow,
+}
+
+impl LifecycleNotifier<'_> {
+  fn notify_deletion(&self, delete_at: &DateTime<Utc>) {
+    self.notify(
+      format!(
+        "will be deleted on {}",
+        delete_at.format("%Y-%m-%d %H:%M UTC")
+      ),
+      "Edit or unarchive the workspace to keep it, the deletion can't be undone.".to_string(),
+    );
+  }
+
+  fn notify(&self, message: String, action: String) {
+    let launch_workspace_url = format!(
+      "{}/app/{}",
+      self.appflowy_web_url.unwrap_or_default(),
+      self.row.workspace_id
+    );
+    // use default icon until we have workspace icon
+    let workspace_icon_url =
+      "https://miro.medium.com/v2/resize:fit:2400/1*mTPfm7CwU31-tLhtLNkyJw.png".to_string();
+    let param = WorkspaceLifecycleNotificationMailerParam {
+      workspace_name: self.row.workspace_name.clone().unwrap_or_default(),
+      workspace_icon_url,
+      workspace_member_count: self.row.member_count,
+      message,
+      action,
+      launch_workspace_url,
+    };
+    let mailer = self.mailer.clone();
+    let owner_name = self.row.owner_name.clone();
+    let owner_email = self.row.owner_email.clone();
+    tokio::spawn(async move {
+      if let Err(err) = mailer
+        .send_workspace_lifecycle_notification(&owner_name, &owner_email, param)
+        .await
+      {
+        error!(
+          "Failed to send workspace lifecycle notification email: {:?}",
+          err
+        );
+      }
+    });
+  }
+}
+
+pub async fn get_workspace_lifecycle(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceLifecycle, AppError> {
+  let row = select_workspace_lifecycle(pg_pool, workspace_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Workspace {} not found", workspace_id)))?;
+  Ok(WorkspaceLifecycle {
+    workspace_id: row.workspace_id,
+    exempt: row.exempt,
+    last_active_at: row.last_active_at,
+    warned_at: row.warned_at,
+    archived_at: row.archived_at,
+    delete_at: row.delete_at,
+  })
+}
+
+/// Exempts the workspace from the lifecycle policies, or subjects it to them again. A scheduled
+/// deletion is canceled either way, the workspace archived for inactivity stays archived.
+pub async fn set_workspace_lifecycle_exempt(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  exempt: bool,
+) -> Result<WorkspaceLifecycle, AppError> {
+  if select_workspace_lifecycle(pg_pool, workspace_id)
+    .await?
+    .is_none()
+  {
+    return Err(AppError::RecordNotFound(format!(
+      "Workspace {} not found",
+      workspace_id
+    )));
+  }
+  upsert_workspace_lifecycle_exempt(pg_pool, workspace_id, exempt).await?;
+  get_workspace_lifecycle(pg_pool, workspace_id).await
+}
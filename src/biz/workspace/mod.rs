@@ -1,4 +1,43 @@
+pub mod access_expiry;
+pub mod analytics_report;
+pub mod api_usage;
+pub mod audit_log;
+pub mod auto_publish;
+pub mod bulk_invite;
+pub mod comment_attachment;
+pub mod comment_moderation;
+pub mod comment_subscription;
+pub mod custom_emoji;
+pub mod custom_role;
+pub mod domain;
+pub mod duplicate;
+pub mod folder_change;
+pub mod guest;
+pub mod insights;
+pub mod invitation_expiry;
+pub mod ip_allowlist;
+pub mod lifecycle;
 pub mod ops;
+pub mod page_import;
+pub mod page_operation;
+pub mod page_share_link;
+pub mod page_template;
 pub mod page_view;
+pub mod permission_audit;
+pub mod public_access;
 pub mod publish;
+pub mod publish_analytics;
+pub mod publish_database;
 pub mod publish_dup;
+pub mod publish_expiry;
+pub mod publish_permission;
+pub mod publish_sanitize;
+pub mod publish_seo;
+pub mod publish_sitemap;
+pub mod publish_sub_namespace;
+pub mod residency;
+pub mod saml;
+pub mod secret_scan;
+pub mod share_links;
+pub mod two_factor;
+pub mod view_permission;
@@ -1,4 +1,6 @@
 use authentication::jwt::OptionalUserUuid;
+use authentication::session_policy::SessionAssurance;
+use chrono::{DateTime, Utc};
 use collab::core::collab::DataSource;
 use collab::preclude::Collab;
 use collab_folder::CollabOrigin;
@@ -6,6 +8,7 @@ use collab_rt_entity::{ClientCollabMessage, UpdateSync};
 use collab_rt_protocol::{Message, SyncMessage};
 use database_entity::dto::AFWorkspaceSettingsChange;
 use std::collections::HashMap;
+use std::net::IpAddr;
 
 use std::ops::DerefMut;
 use std::sync::Arc;
@@ -18,23 +21,36 @@ use tracing::instrument;
 use uuid::Uuid;
 use yrs::updates::encoder::Encode;
 
+use access_control::collab::{CollabAccessControl, RealtimeAccessControl};
 use access_control::workspace::WorkspaceAccessControl;
-use app_error::AppError;
+use app_error::{AppError, ErrorDetails};
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use database::collab::{upsert_collab_member_with_txn, CollabStorage};
-use database::file::s3_client_impl::S3BucketStorage;
-use database::pg_row::AFWorkspaceMemberRow;
+use database::comment_moderation::{
+  select_is_blocked_from_commenting, select_user_is_comment_moderator,
+};
+use database::member_expiry::update_workspace_member_expires_at;
+use database::pg_row::{AFWorkspaceInvitationMinimal, AFWorkspaceMemberRow};
+use database::residency::select_workspace_residency;
+use database::resource_usage::select_workspace_blob_usage;
 
 use database::user::select_uid_from_email;
+use database::user_block::{
+  select_is_blocked_by_comment_author, select_is_blocked_by_view_publisher,
+};
 use database::workspace::*;
+use database::workspace_lifecycle::reset_workspace_lifecycle;
 use database_entity::dto::{
-  AFAccessLevel, AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationStatus,
-  AFWorkspaceSettings, GlobalComment, Reaction, WorkspaceUsage,
+  AFAccessLevel, AFRole, AFWorkspace, AFWorkspaceInvitation, AFWorkspaceInvitationPreview,
+  AFWorkspaceInvitationStatus, AFWorkspaceMember, AFWorkspaceSettings, BatchComments,
+  BatchCommentsParams, GlobalComment, GlobalComments, QueryGlobalComments, Reaction, ViewComments,
+  WorkspaceUsage,
 };
 use gotrue::params::{GenerateLinkParams, GenerateLinkType};
 
 use shared_entity::dto::workspace_dto::{
-  CreateWorkspaceMember, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
+  CreateWorkspaceMember, QueryWorkspaceMembers, WorkspaceMemberChangeset,
+  WorkspaceMemberInvitation, WorkspaceMembersPage,
 };
 use shared_entity::response::AppResponseError;
 use workspace_template::document::getting_started::GettingStartedTemplate;
@@ -43,16 +59,34 @@ use crate::biz::user::user_init::{
   create_user_awareness, create_workspace_collab, create_workspace_database_collab,
   initialize_workspace_for_user,
 };
+use crate::biz::workspace::access_expiry::{notify_grant_expiry_change, AccessGrant};
+use crate::biz::workspace::comment_attachment::{
+  attach_to_comment, fill_comment_attachments, remove_comment_attachments,
+};
+use crate::biz::workspace::custom_role::remove_member_custom_role;
+use crate::biz::workspace::guest::{grant_guest_views, record_guest_views_granted};
+use crate::biz::workspace::ip_allowlist::{normalize_ip_allowlist, parse_ip_allowlist};
+use crate::biz::workspace::permission_audit::record_workspace_permission_change;
+use crate::biz::workspace::residency::StorageRouter;
+use crate::biz::workspace::share_links::revoke_share_links_with_txn;
 use crate::mailer::{AFCloudMailer, WorkspaceInviteMailerParam};
 use crate::state::{GoTrueAdmin, RedisConnectionManager};
 
 const MAX_COMMENT_LENGTH: usize = 5000;
+/// How long after creating a comment its author can edit it.
+const COMMENT_EDIT_WINDOW_MINUTES: i64 = 60;
+const MAX_BATCH_COMMENT_VIEWS: usize = 200;
+const MAX_BATCH_LATEST_COMMENTS: u32 = 20;
+const MAX_INVITATION_TTL_DAYS: u32 = 365;
+const MAX_SNAPSHOT_COUNT: u32 = 100;
+const MAX_SNAPSHOT_SCHEDULE_INTERVAL_HOURS: u32 = 24 * 30;
 
 pub async fn delete_workspace_for_user(
   pg_pool: PgPool,
   workspace_id: Uuid,
-  bucket_storage: Arc<S3BucketStorage>,
+  storage_router: StorageRouter,
 ) -> Result<(), AppResponseError> {
+  let bucket_storage = storage_router.bucket_storage(&workspace_id).await?;
   // remove files from s3
   bucket_storage
     .remove_dir(workspace_id.to_string().as_str())
@@ -126,31 +160,48 @@ pub async fn create_workspace_for_user(
   pg_pool: &PgPool,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
   collab_storage: &Arc<CollabAccessControlStorage>,
+  storage_router: &StorageRouter,
   user_uuid: &Uuid,
   user_uid: i64,
   workspace_name: &str,
+  residency: Option<&str>,
 ) -> Result<AFWorkspace, AppResponseError> {
   let mut txn = pg_pool.begin().await?;
   let new_workspace_row = insert_user_workspace(&mut txn, user_uuid, workspace_name, true).await?;
-
-  workspace_access_control
-    .insert_role(&user_uid, &new_workspace_row.workspace_id, AFRole::Owner)
+  let workspace_id = new_workspace_row.workspace_id;
+  // The residency must be assigned before any collab of the workspace is written, so that the
+  // collabs are stored in the region of the workspace.
+  storage_router
+    .assign_residency(&mut txn, &workspace_id, residency)
     .await?;
 
-  // add create initial collab for user
-  initialize_workspace_for_user(
-    user_uid,
-    user_uuid,
-    &new_workspace_row,
-    &mut txn,
-    vec![GettingStartedTemplate],
-    collab_storage,
-  )
-  .await?;
+  let result: Result<AFWorkspace, AppResponseError> = async {
+    workspace_access_control
+      .insert_role(&user_uid, &workspace_id, AFRole::Owner)
+      .await?;
 
-  let new_workspace = AFWorkspace::try_from(new_workspace_row)?;
-  txn.commit().await?;
-  Ok(new_workspace)
+    // add create initial collab for user
+    initialize_workspace_for_user(
+      user_uid,
+      user_uuid,
+      &new_workspace_row,
+      &mut txn,
+      vec![GettingStartedTemplate],
+      collab_storage,
+    )
+    .await?;
+
+    let new_workspace = AFWorkspace::try_from(new_workspace_row)?;
+    txn.commit().await?;
+    Ok(new_workspace)
+  }
+  .await;
+  if result.is_err() {
+    storage_router
+      .unassign_residency(&workspace_id, residency)
+      .await;
+  }
+  result
 }
 
 pub async fn patch_workspace(
@@ -170,20 +221,208 @@ pub async fn patch_workspace(
   Ok(())
 }
 
+/// Archives or unarchives the workspace. The collabs of an archived workspace are read-only, the
+/// realtime access control rejects their updates.
+pub async fn update_workspace_archived(
+  pg_pool: &PgPool,
+  realtime_access_control: Arc<dyn RealtimeAccessControl>,
+  workspace_id: &Uuid,
+  archived: bool,
+) -> Result<(), AppResponseError> {
+  if !database::workspace::update_workspace_archived(pg_pool, workspace_id, archived).await? {
+    return Err(AppError::RecordNotFound(format!("Workspace {} not found", workspace_id)).into());
+  }
+  realtime_access_control
+    .update_workspace_archived(workspace_id, archived)
+    .await?;
+  if !archived {
+    // The inactivity of an unarchived workspace is counted from scratch.
+    reset_workspace_lifecycle(pg_pool, workspace_id).await?;
+  }
+  Ok(())
+}
+
+const MAX_COMMENT_PAGE_SIZE: u32 = 100;
+
+/// Returns all the comments on the view unless a page size is given. The cursor of the next page
+/// is the number of comments already returned, the comments posted meanwhile may thus show up
+/// again on the next page.
 pub async fn get_comments_on_published_view(
   pg_pool: &PgPool,
   view_id: &Uuid,
   optional_user_uuid: &OptionalUserUuid,
-) -> Result<Vec<GlobalComment>, AppError> {
+  query: &QueryGlobalComments,
+) -> Result<GlobalComments, AppError> {
+  let offset = match query.cursor.as_deref() {
+    Some(cursor) => cursor
+      .parse::<u32>()
+      .map_err(|_| AppError::InvalidRequest(format!("Invalid comment cursor: {}", cursor)))?,
+    None => 0,
+  };
+  let limit = query
+    .limit
+    .map(|limit| limit.clamp(1, MAX_COMMENT_PAGE_SIZE));
+  let parent = match query.reply_to {
+    Some(reply_to) => Some(Some(reply_to)),
+    None if query.threaded => Some(None),
+    None => None,
+  };
+
   let page_owner_uuid = select_owner_of_published_collab(pg_pool, view_id).await?;
-  let comments = select_comments_for_published_view_ordered_by_recency(
+  let user_uuid = optional_user_uuid.as_uuid();
+  let is_moderator = match user_uuid {
+    Some(user_uuid) => select_user_is_comment_moderator(pg_pool, view_id, &user_uuid).await?,
+    None => false,
+  };
+  // One more comment is fetched to know whether there is a next page
+  let mut comments = select_comments_for_published_view(
     pg_pool,
     view_id,
-    &optional_user_uuid.as_uuid(),
+    &user_uuid,
     &page_owner_uuid,
+    is_moderator,
+    parent,
+    query.sort,
+    offset as i64,
+    limit.map(|limit| limit as i64 + 1),
   )
   .await?;
-  Ok(comments)
+  let mut next_cursor = None;
+  if let Some(limit) = limit {
+    if comments.len() > limit as usize {
+      comments.truncate(limit as usize);
+      next_cursor = Some((offset + limit).to_string());
+    }
+  }
+
+  let replies = if query.threaded && !comments.is_empty() {
+    let comment_ids: Vec<Uuid> = comments.iter().map(|comment| comment.comment_id).collect();
+    select_comment_replies_for_published_view(
+      pg_pool,
+      view_id,
+      &user_uuid,
+      &page_owner_uuid,
+      is_moderator,
+      &comment_ids,
+    )
+    .await?
+  } else {
+    vec![]
+  };
+  let page_len = comments.len();
+  comments.extend(replies);
+  fill_comment_attachments(pg_pool, view_id, &mut comments).await?;
+  for comment in comments.iter_mut() {
+    comment.can_be_edited = can_edit_comment(comment, user_uuid);
+  }
+  if query.threaded {
+    let replies = comments.split_off(page_len);
+    comments = nest_comment_replies(comments, replies);
+  }
+  Ok(GlobalComments {
+    comments,
+    next_cursor,
+  })
+}
+
+/// Nests the replies in the comments they reply to. The replies to a comment that isn't returned,
+/// e.g. a hidden one, are left out.
+fn nest_comment_replies(
+  comments: Vec<GlobalComment>,
+  replies: Vec<GlobalComment>,
+) -> Vec<GlobalComment> {
+  fn nest(
+    mut comment: GlobalComment,
+    replies_by_comment: &mut HashMap<Uuid, Vec<GlobalComment>>,
+  ) -> GlobalComment {
+    comment.replies = replies_by_comment
+      .remove(&comment.comment_id)
+      .unwrap_or_default()
+      .into_iter()
+      .map(|reply| nest(reply, replies_by_comment))
+      .collect();
+    comment
+  }
+
+  let mut replies_by_comment: HashMap<Uuid, Vec<GlobalComment>> = HashMap::new();
+  for reply in replies {
+    if let Some(reply_comment_id) = reply.reply_comment_id {
+      replies_by_comment
+        .entry(reply_comment_id)
+        .or_default()
+        .push(reply);
+    }
+  }
+  comments
+    .into_iter()
+    .map(|comment| nest(comment, &mut replies_by_comment))
+    .collect()
+}
+
+/// Returns the comment count and the latest comments of each of the views, for the views published
+/// in the workspace. The other views are returned without comments.
+pub async fn get_comments_on_published_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  params: &BatchCommentsParams,
+) -> Result<BatchComments, AppError> {
+  if params.view_ids.len() > MAX_BATCH_COMMENT_VIEWS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "The comments of at most {} views can be requested at once",
+        MAX_BATCH_COMMENT_VIEWS
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_BATCH_COMMENT_VIEWS as i64,
+        Some(params.view_ids.len() as i64),
+      )),
+    );
+  }
+  if params.latest_count > MAX_BATCH_LATEST_COMMENTS {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "At most {} latest comments can be requested for each view",
+        MAX_BATCH_LATEST_COMMENTS
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_BATCH_LATEST_COMMENTS as i64,
+        Some(params.latest_count as i64),
+      )),
+    );
+  }
+
+  let counts: HashMap<Uuid, i64> =
+    select_comment_counts_for_published_views(pg_pool, workspace_id, &params.view_ids, user_uuid)
+      .await?
+      .into_iter()
+      .collect();
+  let mut latest_comments: HashMap<Uuid, Vec<GlobalComment>> = HashMap::new();
+  if params.latest_count > 0 && !counts.is_empty() {
+    let comments = select_latest_comments_for_published_views(
+      pg_pool,
+      workspace_id,
+      &params.view_ids,
+      user_uuid,
+      params.latest_count as i64,
+    )
+    .await?;
+    for (view_id, mut comment) in comments {
+      comment.can_be_edited = can_edit_comment(&comment, Some(*user_uuid));
+      latest_comments.entry(view_id).or_default().push(comment);
+    }
+  }
+
+  let views = params
+    .view_ids
+    .iter()
+    .map(|view_id| ViewComments {
+      view_id: *view_id,
+      comment_count: counts.get(view_id).copied().unwrap_or(0),
+      latest_comments: latest_comments.remove(view_id).unwrap_or_default(),
+    })
+    .collect();
+  Ok(BatchComments { views })
 }
 
 pub async fn create_comment_on_published_view(
@@ -191,25 +430,88 @@ pub async fn create_comment_on_published_view(
   view_id: &Uuid,
   reply_comment_id: &Option<Uuid>,
   content: &str,
+  attachment_ids: &[Uuid],
   user_uuid: &Uuid,
 ) -> Result<(), AppError> {
   if content.len() > MAX_COMMENT_LENGTH {
-    return Err(AppError::StringLengthLimitReached(
-      "comment content exceed limit".to_string(),
-    ));
+    return Err(
+      AppError::StringLengthLimitReached("comment content exceed limit".to_string()).with_details(
+        ErrorDetails::limit(MAX_COMMENT_LENGTH as i64, Some(content.len() as i64)),
+      ),
+    );
+  }
+  if select_is_blocked_by_view_publisher(pg_pool, view_id, user_uuid).await?
+    || select_is_blocked_from_commenting(pg_pool, view_id, user_uuid).await?
+  {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  if let Some(reply_comment_id) = reply_comment_id {
+    if select_is_blocked_by_comment_author(pg_pool, reply_comment_id, user_uuid).await? {
+      return Err(AppError::NotEnoughPermissions);
+    }
+  }
+  let mut txn = pg_pool.begin().await?;
+  let comment_id =
+    insert_comment_to_published_view(txn.as_mut(), view_id, user_uuid, content, reply_comment_id)
+      .await?;
+  attach_to_comment(&mut txn, &comment_id, view_id, user_uuid, attachment_ids).await?;
+  txn.commit().await?;
+  Ok(())
+}
+
+/// Replaces the content of a comment. Only the author can edit the comment, within
+/// [COMMENT_EDIT_WINDOW_MINUTES] of creating it.
+pub async fn update_comment_on_published_view(
+  pg_pool: &PgPool,
+  view_id: &Uuid,
+  comment_id: &Uuid,
+  content: &str,
+  user_uuid: &Uuid,
+) -> Result<(), AppError> {
+  if content.len() > MAX_COMMENT_LENGTH {
+    return Err(
+      AppError::StringLengthLimitReached("comment content exceed limit".to_string()).with_details(
+        ErrorDetails::limit(MAX_COMMENT_LENGTH as i64, Some(content.len() as i64)),
+      ),
+    );
+  }
+  let created_at = select_editable_comment_created_at(pg_pool, view_id, comment_id, user_uuid)
+    .await?
+    .ok_or_else(|| {
+      AppError::UserUnAuthorized("User is not allowed to edit this comment".to_string())
+    })?;
+  if !is_within_comment_edit_window(created_at) {
+    return Err(AppError::InvalidRequest(format!(
+      "The comment can only be edited within {} minutes of creating it",
+      COMMENT_EDIT_WINDOW_MINUTES
+    )));
   }
-  insert_comment_to_published_view(pg_pool, view_id, user_uuid, content, reply_comment_id).await?;
+  update_comment_content(pg_pool, comment_id, content).await?;
   Ok(())
 }
 
+fn is_within_comment_edit_window(created_at: DateTime<Utc>) -> bool {
+  Utc::now() - created_at < chrono::Duration::minutes(COMMENT_EDIT_WINDOW_MINUTES)
+}
+
+fn can_edit_comment(comment: &GlobalComment, user_uuid: Option<Uuid>) -> bool {
+  let is_author = comment
+    .user
+    .as_ref()
+    .is_some_and(|user| Some(user.uuid) == user_uuid);
+  is_author && !comment.is_deleted && is_within_comment_edit_window(comment.created_at)
+}
+
 pub async fn remove_comment_on_published_view(
   pg_pool: &PgPool,
+  storage_router: &StorageRouter,
   view_id: &Uuid,
   comment_id: &Uuid,
   user_uuid: &Uuid,
 ) -> Result<(), AppError> {
   check_if_user_is_allowed_to_delete_comment(pg_pool, user_uuid, view_id, comment_id).await?;
   update_comment_deletion_status(pg_pool, comment_id).await?;
+  remove_comment_attachments(pg_pool, storage_router, comment_id).await?;
   Ok(())
 }
 
@@ -217,15 +519,21 @@ pub async fn get_reactions_on_published_view(
   pg_pool: &PgPool,
   view_id: &Uuid,
   comment_id: &Option<Uuid>,
+  optional_user_uuid: &OptionalUserUuid,
 ) -> Result<Vec<Reaction>, AppError> {
+  let user_uuid = optional_user_uuid.as_uuid();
   let reaction = match comment_id {
     Some(comment_id) => {
-      select_reactions_for_comment_ordered_by_reaction_type_creation_time(pg_pool, comment_id)
-        .await?
+      select_reactions_for_comment_ordered_by_reaction_type_creation_time(
+        pg_pool, comment_id, &user_uuid,
+      )
+      .await?
     },
     None => {
-      select_reactions_for_published_view_ordered_by_reaction_type_creation_time(pg_pool, view_id)
-        .await?
+      select_reactions_for_published_view_ordered_by_reaction_type_creation_time(
+        pg_pool, view_id, &user_uuid,
+      )
+      .await?
     },
   };
   Ok(reaction)
@@ -238,6 +546,11 @@ pub async fn create_reaction_on_comment(
   reaction_type: &str,
   user_uuid: &Uuid,
 ) -> Result<(), AppError> {
+  if select_is_blocked_by_comment_author(pg_pool, comment_id, user_uuid).await?
+    || select_is_blocked_from_commenting(pg_pool, view_id, user_uuid).await?
+  {
+    return Err(AppError::NotEnoughPermissions);
+  }
   insert_reaction_on_comment(pg_pool, comment_id, view_id, user_uuid, reaction_type).await?;
   Ok(())
 }
@@ -256,10 +569,12 @@ pub async fn get_all_user_workspaces(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
   include_member_count: bool,
+  include_archived: bool,
 ) -> Result<Vec<AFWorkspace>, AppResponseError> {
   let workspaces = select_all_user_workspaces(pg_pool, user_uuid).await?;
   let mut workspaces = workspaces
     .into_iter()
+    .filter(|row| include_archived || row.archived_at.is_none())
     .flat_map(|row| {
       let result = AFWorkspace::try_from(row);
       if let Err(err) = &result {
@@ -309,10 +624,11 @@ pub async fn open_workspace(
 pub async fn accept_workspace_invite(
   pg_pool: &PgPool,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
+  collab_access_control: Arc<dyn CollabAccessControl>,
   user_uid: i64,
   user_uuid: &Uuid,
   invite_id: &Uuid,
-) -> Result<(), AppError> {
+) -> Result<AFWorkspaceInvitationMinimal, AppError> {
   let mut txn = pg_pool.begin().await?;
   let inv = get_invitation_by_id(&mut txn, invite_id).await?;
   if let Some(invitee_uid) = inv.invitee_uid {
@@ -323,15 +639,59 @@ pub async fn accept_workspace_invite(
       )));
     }
   }
+  if inv.status == AFWorkspaceInvitationStatus::Expired
+    || (inv.status == AFWorkspaceInvitationStatus::Pending
+      && inv
+        .expires_at
+        .map_or(false, |expires_at| expires_at <= Utc::now()))
+  {
+    // The invitation may have expired since the last run of the expiry job.
+    update_workspace_invitation_set_status_expired(&mut txn, invite_id).await?;
+    txn.commit().await?;
+    return Err(AppError::InvitationExpired(format!(
+      "The invitation {} has expired, ask the inviter to invite you again",
+      invite_id
+    )));
+  }
   update_workspace_invitation_set_status_accepted(&mut txn, user_uuid, invite_id).await?;
   let invited_uid = inv
     .invitee_uid
     .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Invitee uid is missing for {:?}", inv)))?;
   workspace_access_control
-    .insert_role(&invited_uid, &inv.workspace_id, inv.role)
+    .insert_role(&invited_uid, &inv.workspace_id, inv.role.clone())
     .await?;
+  let mut granted_view_ids = vec![];
+  if inv.role == AFRole::Guest {
+    let view_ids = select_workspace_invitation_guest_view_ids(&mut txn, invite_id).await?;
+    granted_view_ids = grant_guest_views(
+      &mut txn,
+      collab_access_control,
+      &inv.workspace_id,
+      invited_uid,
+      &view_ids,
+    )
+    .await?;
+  }
   txn.commit().await?;
-  Ok(())
+  // The invitation is recorded as a change made by the inviter
+  record_workspace_permission_change(
+    pg_pool,
+    &inv.workspace_id,
+    Some(inv.inviter_uid),
+    invited_uid,
+    None,
+    Some(&inv.role),
+  )
+  .await;
+  record_guest_views_granted(
+    pg_pool,
+    &inv.workspace_id,
+    inv.inviter_uid,
+    invited_uid,
+    &granted_view_ids,
+  )
+  .await;
+  Ok(inv)
 }
 
 #[instrument(level = "debug", skip_all, err)]
@@ -344,6 +704,7 @@ pub async fn invite_workspace_members(
   inviter: &Uuid,
   workspace_id: &Uuid,
   invitations: Vec<WorkspaceMemberInvitation>,
+  message: Option<&str>,
   appflowy_web_url: Option<&str>,
 ) -> Result<(), AppError> {
   let mut txn = pg_pool
@@ -369,6 +730,12 @@ pub async fn invite_workspace_members(
       .collect();
   let pending_invitations =
     database::workspace::select_workspace_pending_invitations(pg_pool, workspace_id).await?;
+  let invitation_ttl_days = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default()
+    .invitation_ttl_days;
+  let expires_at = (invitation_ttl_days > 0)
+    .then(|| Utc::now() + chrono::Duration::days(invitation_ttl_days as i64));
 
   // check if any of the invited users are already members of the workspace
   for invitation in &invitations {
@@ -403,12 +770,15 @@ pub async fn invite_workspace_members(
           inviter,
           invitation.email.as_str(),
           invitation.role,
+          expires_at,
         )
         .await?;
         invite_id
       },
       Some(invite_id) => {
         tracing::warn!("User already invited: {}", invitation.email);
+        // The invitation is sent again, so it can be accepted for another period.
+        update_workspace_invitation_expires_at(&mut txn, invite_id, expires_at).await?;
         *invite_id
       },
     };
@@ -443,6 +813,7 @@ pub async fn invite_workspace_members(
 
     // send email can be slow, so send email in background
     let cloned_mailer = mailer.clone();
+    let message = message.map(|message| message.to_string());
     tokio::spawn(async move {
       if let Err(err) = cloned_mailer
         .send_workspace_invite(
@@ -454,6 +825,7 @@ pub async fn invite_workspace_members(
             workspace_icon_url,
             workspace_member_count,
             accept_url,
+            message,
           },
         )
         .await
@@ -480,6 +852,15 @@ pub async fn list_workspace_invitations_for_user(
   Ok(invis)
 }
 
+pub async fn get_workspace_invitation_preview(
+  pg_pool: &PgPool,
+  invite_id: &Uuid,
+) -> Result<AFWorkspaceInvitationPreview, AppError> {
+  select_workspace_invitation_preview(pg_pool, invite_id)
+    .await?
+    .ok_or_else(|| AppError::RecordNotFound(format!("Invitation {} not found", invite_id)))
+}
+
 pub async fn get_workspace_invitations_for_user(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
@@ -510,16 +891,26 @@ pub async fn add_workspace_members_db_only(
     .context("Begin transaction to insert workspace members")?;
 
   for member in members.into_iter() {
+    // Guests only get access to the views that are explicitly granted to them
     let access_level = match &member.role {
-      AFRole::Owner => AFAccessLevel::FullAccess,
-      AFRole::Member => AFAccessLevel::ReadAndWrite,
-      AFRole::Guest => AFAccessLevel::ReadOnly,
+      AFRole::Owner => Some(AFAccessLevel::FullAccess),
+      AFRole::Member => Some(AFAccessLevel::ReadAndWrite),
+      AFRole::Guest => None,
     };
 
     let uid = select_uid_from_email(txn.deref_mut(), &member.email).await?;
     upsert_workspace_member_with_txn(&mut txn, workspace_id, &member.email, member.role.clone())
       .await?;
-    upsert_collab_member_with_txn(uid, workspace_id.to_string(), &access_level, &mut txn).await?;
+    if let Some(access_level) = access_level {
+      upsert_collab_member_with_txn(
+        uid,
+        workspace_id,
+        workspace_id.to_string(),
+        &access_level,
+        &mut txn,
+      )
+      .await?;
+    }
   }
 
   txn
@@ -534,16 +925,25 @@ pub async fn leave_workspace(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   user_uuid: &Uuid,
+  uid: i64,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
 ) -> Result<(), AppResponseError> {
   let email = database::user::select_email_from_user_uuid(pg_pool, user_uuid).await?;
-  remove_workspace_members(pg_pool, workspace_id, &[email], workspace_access_control).await
+  remove_workspace_members(
+    pg_pool,
+    workspace_id,
+    &[email],
+    uid,
+    workspace_access_control,
+  )
+  .await
 }
 
 pub async fn remove_workspace_members(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   member_emails: &[String],
+  actor_uid: i64,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
 ) -> Result<(), AppResponseError> {
   let mut txn = pg_pool
@@ -551,15 +951,25 @@ pub async fn remove_workspace_members(
     .await
     .context("Begin transaction to delete workspace members")?;
 
+  let mut removed_members = vec![];
   for email in member_emails {
+    let member_uid = select_uid_from_email(txn.deref_mut(), email).await.ok();
+    let old_role = match member_uid {
+      Some(uid) => select_user_role(txn.deref_mut(), &uid, workspace_id)
+        .await
+        .ok(),
+      None => None,
+    };
     delete_workspace_members(&mut txn, workspace_id, email.as_str()).await?;
-    if let Ok(uid) = select_uid_from_email(txn.deref_mut(), email)
-      .await
-      .map_err(AppResponseError::from)
-    {
+    if let Some(uid) = member_uid {
+      // The links shared by the member would keep granting access to the workspace otherwise
+      revoke_share_links_with_txn(&mut txn, workspace_id, Some(uid)).await?;
       workspace_access_control
         .remove_user_from_workspace(&uid, workspace_id)
         .await?;
+      if let Some(old_role) = old_role {
+        removed_members.push((uid, old_role));
+      }
     }
   }
 
@@ -567,6 +977,17 @@ pub async fn remove_workspace_members(
     .commit()
     .await
     .context("Commit transaction to delete workspace members")?;
+  for (uid, old_role) in removed_members {
+    record_workspace_permission_change(
+      pg_pool,
+      workspace_id,
+      Some(actor_uid),
+      uid,
+      Some(&old_role),
+      None,
+    )
+    .await;
+  }
   Ok(())
 }
 
@@ -577,6 +998,48 @@ pub async fn get_workspace_members(
   Ok(select_workspace_member_list(pg_pool, workspace_id).await?)
 }
 
+/// The number of members returned when the page size is not given.
+const DEFAULT_WORKSPACE_MEMBER_PAGE_SIZE: u32 = 100;
+const MAX_WORKSPACE_MEMBER_PAGE_SIZE: u32 = 1000;
+
+pub async fn get_workspace_members_page(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: QueryWorkspaceMembers,
+) -> Result<WorkspaceMembersPage, AppResponseError> {
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_WORKSPACE_MEMBER_PAGE_SIZE)
+    .min(MAX_WORKSPACE_MEMBER_PAGE_SIZE);
+  let search = query
+    .search
+    .as_deref()
+    .map(str::trim)
+    .filter(|search| !search.is_empty());
+  let (rows, total_count) = select_workspace_member_page(
+    pg_pool,
+    workspace_id,
+    search,
+    limit as i64,
+    query.offset.unwrap_or(0) as i64,
+  )
+  .await?;
+  let members = rows
+    .into_iter()
+    .map(|member| AFWorkspaceMember {
+      name: member.name,
+      email: member.email,
+      role: member.role,
+      avatar_url: None,
+      expires_at: member.expires_at,
+    })
+    .collect();
+  Ok(WorkspaceMembersPage {
+    members,
+    total_count,
+  })
+}
+
 pub async fn get_workspace_member(
   uid: &i64,
   pg_pool: &PgPool,
@@ -590,25 +1053,87 @@ pub async fn update_workspace_member(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   changeset: &WorkspaceMemberChangeset,
+  actor_uid: i64,
   workspace_access_control: Arc<dyn WorkspaceAccessControl>,
 ) -> Result<(), AppError> {
   if let Some(role) = &changeset.role {
+    let old_role = select_user_role(pg_pool, uid, workspace_id).await.ok();
     upsert_workspace_member(pg_pool, workspace_id, &changeset.email, role.clone()).await?;
+    record_workspace_permission_change(
+      pg_pool,
+      workspace_id,
+      Some(actor_uid),
+      *uid,
+      old_role.as_ref(),
+      Some(role),
+    )
+    .await;
     workspace_access_control
       .insert_role(uid, workspace_id, role.clone())
       .await?;
+    if *role == AFRole::Guest {
+      remove_member_custom_role(
+        pg_pool,
+        workspace_access_control.clone(),
+        workspace_id,
+        *uid,
+      )
+      .await?;
+    }
+  }
+
+  if changeset.expiry_changed() {
+    let expires_at = if changeset.clear_expiry {
+      None
+    } else {
+      changeset.expires_at
+    };
+    if let Some(expires_at) = expires_at {
+      if expires_at <= Utc::now() {
+        return Err(AppError::InvalidRequest(
+          "The expiry time must be in the future".to_string(),
+        ));
+      }
+      let role = select_user_role(pg_pool, uid, workspace_id).await?;
+      if role == AFRole::Owner {
+        return Err(AppError::InvalidRequest(
+          "The access of the workspace owner can not expire".to_string(),
+        ));
+      }
+    }
+
+    let mut txn = pg_pool.begin().await?;
+    update_workspace_member_expires_at(&mut txn, workspace_id, uid, expires_at).await?;
+    txn.commit().await?;
+    workspace_access_control
+      .update_role_expiry(uid, workspace_id, expires_at)
+      .await?;
+    notify_grant_expiry_change(
+      pg_pool,
+      *uid,
+      AccessGrant::Workspace(*workspace_id),
+      expires_at,
+    )
+    .await;
   }
 
   Ok(())
 }
 
-pub async fn get_workspace_document_total_bytes(
+pub async fn get_workspace_usage(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
 ) -> Result<WorkspaceUsage, AppError> {
   let byte_count = select_workspace_total_collab_bytes(pg_pool, workspace_id).await?;
+  let collab_usages = select_workspace_collab_usage_by_type(pg_pool, workspace_id).await?;
+  let (blob_count, blob_storage_bytes) = select_workspace_blob_usage(pg_pool, workspace_id).await?;
+  let member_usages = select_workspace_member_collab_usage(pg_pool, workspace_id).await?;
   Ok(WorkspaceUsage {
     total_document_size: byte_count,
+    collab_usages,
+    blob_count,
+    blob_storage_bytes,
+    member_usages,
   })
 }
 
@@ -616,14 +1141,19 @@ pub async fn get_workspace_settings(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
 ) -> Result<AFWorkspaceSettings, AppResponseError> {
-  let settings = select_workspace_settings(pg_pool, workspace_id).await?;
-  Ok(settings.unwrap_or_default())
+  let mut settings = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .unwrap_or_default();
+  settings.residency = select_workspace_residency(pg_pool, workspace_id).await?;
+  Ok(settings)
 }
 
 pub async fn update_workspace_settings(
   pg_pool: &PgPool,
   workspace_id: &Uuid,
   change: AFWorkspaceSettingsChange,
+  client_ip: Option<IpAddr>,
+  session: SessionAssurance,
 ) -> Result<AFWorkspaceSettings, AppResponseError> {
   let mut tx = pg_pool.begin().await?;
   let mut setting = select_workspace_settings(tx.deref_mut(), workspace_id)
@@ -637,8 +1167,83 @@ pub async fn update_workspace_settings(
     setting.ai_model = ai_model;
   }
 
+  if let Some(secret_scanning) = change.secret_scanning {
+    setting.secret_scanning = secret_scanning;
+  }
+
+  if let Some(invitation_ttl_days) = change.invitation_ttl_days {
+    if invitation_ttl_days > MAX_INVITATION_TTL_DAYS {
+      return Err(
+        AppError::InvalidRequest(format!(
+          "The invitations can't be valid for more than {} days",
+          MAX_INVITATION_TTL_DAYS
+        ))
+        .into(),
+      );
+    }
+    setting.invitation_ttl_days = invitation_ttl_days;
+  }
+
+  if let Some(restrict_publishing) = change.restrict_publishing {
+    setting.restrict_publishing = restrict_publishing;
+  }
+
+  if let Some(snapshot_retention) = change.snapshot_retention {
+    if snapshot_retention.max_count == 0 || snapshot_retention.max_count > MAX_SNAPSHOT_COUNT {
+      return Err(
+        AppError::InvalidRequest(format!(
+          "Between 1 and {} snapshots can be kept for each collab",
+          MAX_SNAPSHOT_COUNT
+        ))
+        .into(),
+      );
+    }
+    if snapshot_retention.schedule_interval_hours > MAX_SNAPSHOT_SCHEDULE_INTERVAL_HOURS {
+      return Err(
+        AppError::InvalidRequest(format!(
+          "The collabs must be snapshotted at least every {} hours",
+          MAX_SNAPSHOT_SCHEDULE_INTERVAL_HOURS
+        ))
+        .into(),
+      );
+    }
+    setting.snapshot_retention = snapshot_retention;
+  }
+
+  if let Some(ip_allowlist) = change.ip_allowlist {
+    let ip_allowlist = normalize_ip_allowlist(&ip_allowlist)?;
+    // The allowlist applies to the settings too, an allowlist that excludes the client would
+    // lock it out of the workspace.
+    if !ip_allowlist.is_empty() && !parse_ip_allowlist(&ip_allowlist).allows(client_ip) {
+      return Err(
+        AppError::InvalidRequest(
+          "The IP allowlist must include the address the change is made from".to_string(),
+        )
+        .into(),
+      );
+    }
+    setting.ip_allowlist = ip_allowlist;
+  }
+
+  if let Some(require_2fa) = change.require_2fa {
+    // Likewise, a session without a second factor would be locked out once it is required.
+    if require_2fa && !session.has_second_factor {
+      return Err(
+        AppError::InvalidRequest(
+          "A second factor must be used to sign in before requiring it in the workspace"
+            .to_string(),
+        )
+        .into(),
+      );
+    }
+    setting.require_2fa = require_2fa;
+  }
+
+  // The residency is stored along with the workspace, not in the settings.
+  setting.residency = None;
   // Update the workspace settings in the database
   upsert_workspace_settings(&mut tx, workspace_id, &setting).await?;
+  setting.residency = select_workspace_residency(tx.deref_mut(), workspace_id).await?;
   tx.commit().await?;
   Ok(setting)
 }
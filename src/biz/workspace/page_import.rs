@@ -0,0 +1,260 @@
+use app_error::{AppError, ErrorDetails};
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use collab_document::blocks::DocumentData;
+use collab_document::importer::md_importer::MDImporter;
+use scraper::{ElementRef, Html, Node};
+use shared_entity::dto::workspace_dto::{ImportPageFormat, ImportPageParams, Page, PageContent};
+use sqlx::PgPool;
+use uuid::Uuid;
+use workspace_template::document::parser::JsonToDocumentParser;
+
+use super::page_view::create_page_with_document_data;
+use crate::biz::export::decrypt_export;
+
+/// The maximum size of the imported content, in bytes.
+const MAX_IMPORT_PAGE_CONTENT_SIZE: usize = 2 * 1024 * 1024;
+
+const DEFAULT_IMPORTED_PAGE_NAME: &str = "Untitled";
+
+/// Creates a document page from Markdown or HTML content, which may be an encrypted export. The
+/// HTML is converted to Markdown first, and the Markdown is converted to the blocks of the document.
+pub async fn import_page(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  params: ImportPageParams,
+) -> Result<Page, AppError> {
+  check_page_content_size(params.content.len())?;
+
+  let content = match params.decryption {
+    Some(decryption) => {
+      let encrypted = STANDARD.decode(params.content.trim()).map_err(|err| {
+        AppError::InvalidRequest(format!("The encrypted content is not base64: {}", err))
+      })?;
+      let decrypted = decrypt_export(encrypted, decryption).await?;
+      String::from_utf8(decrypted).map_err(|_| {
+        AppError::InvalidRequest("The decrypted content is not valid UTF-8".to_string())
+      })?
+    },
+    None => params.content,
+  };
+
+  let view_id = Uuid::new_v4().to_string();
+  let (name, document_data) = {
+    let view_id = view_id.clone();
+    let ImportPageParams { name, format, .. } = params;
+    tokio::task::spawn_blocking(move || {
+      let markdown = match format {
+        ImportPageFormat::Markdown => content,
+        ImportPageFormat::Html => html_to_markdown(&content),
+      };
+      let name = name
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| first_heading(&markdown))
+        .unwrap_or_else(|| DEFAULT_IMPORTED_PAGE_NAME.to_string());
+      let document_data = markdown_to_document_data(&view_id, markdown)?;
+      Ok::<_, AppError>((name, document_data))
+    })
+    .await??
+  };
+
+  create_page_with_document_data(
+    pg_pool,
+    collab_storage,
+    uid,
+    workspace_id,
+    &params.parent_view_id,
+    &name,
+    view_id,
+    document_data,
+  )
+  .await
+}
+
+pub(crate) fn check_page_content_size(size: usize) -> Result<(), AppError> {
+  if size > MAX_IMPORT_PAGE_CONTENT_SIZE {
+    return Err(
+      AppError::PayloadTooLarge(format!(
+        "The imported content exceeds the maximum size of {} bytes",
+        MAX_IMPORT_PAGE_CONTENT_SIZE
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_IMPORT_PAGE_CONTENT_SIZE as i64,
+        Some(size as i64),
+      )),
+    );
+  }
+  Ok(())
+}
+
+/// Converts the initial content of a page to the data of its document. Also returns the first
+/// heading of the Markdown content, to name the page after it.
+pub(crate) fn page_content_to_document_data(
+  view_id: &str,
+  content: PageContent,
+) -> Result<(Option<String>, DocumentData), AppError> {
+  match content {
+    PageContent::Markdown(markdown) => {
+      check_page_content_size(markdown.len())?;
+      let heading = first_heading(&markdown);
+      Ok((heading, markdown_to_document_data(view_id, markdown)?))
+    },
+    PageContent::Blocks(root) => {
+      check_page_content_size(root.to_string().len())?;
+      if root.get("type").and_then(|ty| ty.as_str()) != Some("page") {
+        return Err(AppError::InvalidRequest(
+          "The root of the blocks must be a page block".to_string(),
+        ));
+      }
+      let document_data = JsonToDocumentParser::json_value_to_document(root)
+        .map_err(|err| AppError::InvalidRequest(format!("Invalid blocks: {}", err)))?;
+      Ok((None, document_data))
+    },
+  }
+}
+
+fn markdown_to_document_data(view_id: &str, markdown: String) -> Result<DocumentData, AppError> {
+  MDImporter::new(None)
+    .import(view_id, markdown)
+    .map_err(|err| AppError::InvalidRequest(format!("Failed to import the page: {}", err)))
+}
+
+fn first_heading(markdown: &str) -> Option<String> {
+  markdown
+    .lines()
+    .map(str::trim)
+    .find(|line| line.starts_with('#'))
+    .map(|line| line.trim_start_matches('#').trim().to_string())
+    .filter(|heading| !heading.is_empty())
+}
+
+/// Converts the HTML to Markdown. Only the elements that have a Markdown equivalent are kept, the
+/// text of the other elements is kept without formatting.
+fn html_to_markdown(html: &str) -> String {
+  let fragment = Html::parse_fragment(html);
+  let markdown = html_children_markdown(fragment.root_element());
+
+  // The blocks are separated by a single blank line.
+  let mut result = String::new();
+  let mut blank_lines = 0;
+  for line in markdown.trim().lines() {
+    let line = line.trim_end();
+    if line.is_empty() {
+      blank_lines += 1;
+      continue;
+    }
+    if !result.is_empty() {
+      result.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+    }
+    result.push_str(line);
+    blank_lines = 0;
+  }
+  result
+}
+
+fn html_children_markdown(element: ElementRef) -> String {
+  element
+    .children()
+    .map(|child| match child.value() {
+      Node::Text(text) => collapse_whitespace(text),
+      Node::Element(_) => ElementRef::wrap(child)
+        .map(html_element_markdown)
+        .unwrap_or_default(),
+      _ => String::new(),
+    })
+    .collect()
+}
+
+fn html_element_markdown(element: ElementRef) -> String {
+  let children = || html_children_markdown(element);
+  let block = |markdown: String| format!("\n\n{}\n\n", markdown.trim());
+  let inline = |marker: &str| {
+    let text = children();
+    match text.trim() {
+      "" => text,
+      trimmed => format!("{}{}{}", marker, trimmed, marker),
+    }
+  };
+  let text = || element.text().collect::<String>();
+  match element.value().name() {
+    name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+      let level = name[1..].parse::<usize>().unwrap_or(1);
+      block(format!("{} {}", "#".repeat(level), children().trim()))
+    },
+    "p" | "div" | "section" | "article" | "header" | "footer" | "main" => block(children()),
+    "br" => "\n".to_string(),
+    "hr" => block("---".to_string()),
+    "strong" | "b" => inline("**"),
+    "em" | "i" => inline("_"),
+    "s" | "del" | "strike" => inline("~~"),
+    "code" => format!("`{}`", text()),
+    "pre" => block(format!("```\n{}\n```", text().trim_end())),
+    "a" => match element.value().attr("href") {
+      Some(href) => format!("[{}]({})", children().trim(), href),
+      None => children(),
+    },
+    "img" => format!(
+      "![{}]({})",
+      element.value().attr("alt").unwrap_or_default(),
+      element.value().attr("src").unwrap_or_default()
+    ),
+    "blockquote" => block(
+      children()
+        .trim()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| format!("> {}", line))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    ),
+    name @ ("ul" | "ol") => {
+      let marker = if name == "ol" { "1. " } else { "- " };
+      let items = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| child.value().name() == "li")
+        .map(|item| list_item_markdown(marker, &html_children_markdown(item)))
+        .collect::<Vec<_>>();
+      block(items.join("\n"))
+    },
+    "script" | "style" | "head" | "title" | "template" => String::new(),
+    _ => children(),
+  }
+}
+
+/// The nested lines of the item, e.g. a nested list, are indented under its marker.
+fn list_item_markdown(marker: &str, content: &str) -> String {
+  let indent = " ".repeat(marker.len());
+  let mut lines = content
+    .trim()
+    .lines()
+    .map(str::trim_end)
+    .filter(|line| !line.trim().is_empty());
+  let mut markdown = format!("{}{}", marker, lines.next().unwrap_or_default().trim());
+  for line in lines {
+    markdown.push('\n');
+    markdown.push_str(&indent);
+    markdown.push_str(line);
+  }
+  markdown
+}
+
+fn collapse_whitespace(text: &str) -> String {
+  let mut collapsed = String::with_capacity(text.len());
+  let mut in_whitespace = false;
+  for c in text.chars() {
+    if c.is_whitespace() {
+      if !in_whitespace {
+        collapsed.push(' ');
+      }
+      in_whitespace = true;
+    } else {
+      collapsed.push(c);
+      in_whitespace = false;
+    }
+  }
+  collapsed
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_document::document::Document;
+use collab_document::document_data::default_document_data;
+use collab_entity::CollabType;
+use collab_folder::hierarchy_builder::NestedChildViewBuilder;
+use collab_folder::Folder;
+use database::collab::{CollabStorage, GetCollabOrigin};
+use database_entity::dto::CollabParams;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  BatchCreatePageParams, BatchCreatePageResult, BatchPageParams, Page, PageContent, PageOperation,
+  PageOperationsParams, PageOperationsResult,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use workspace_template::document::parser::SerdeBlock;
+use yrs::{ReadTxn, StateVector};
+
+use crate::biz::collab::folder_view::view_is_space;
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+
+use super::ops::{broadcast_update, collab_from_doc_state};
+use super::page_import::page_content_to_document_data;
+use super::page_view::{append_blocks, folder_to_encoded_collab};
+
+pub const MAX_PAGE_OPERATIONS: usize = 20;
+pub const MAX_BATCH_PAGES: usize = 1000;
+
+/// A document touched by the operations. The state vector is the one of the stored document, to
+/// broadcast only what the operations changed, and is missing for the documents they create.
+struct TouchedDocument {
+  document: Document,
+  state_vector: Option<StateVector>,
+}
+
+/// Applies the operations on in-memory copies of the folder and of the documents, then saves all
+/// of them in a single transaction, so that a failing operation leaves nothing behind. The
+/// changes are broadcast once saved.
+pub async fn apply_page_operations(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  params: PageOperationsParams,
+) -> Result<PageOperationsResult, AppError> {
+  if params.operations.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "At least one operation is required".to_string(),
+    ));
+  }
+  if params.operations.len() > MAX_PAGE_OPERATIONS {
+    return Err(AppError::InvalidRequest(format!(
+      "At most {} operations can be applied at once",
+      MAX_PAGE_OPERATIONS
+    )));
+  }
+  let workspace_id_str = workspace_id.to_string();
+  let mut folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+  )
+  .await?;
+  let folder_state_vector = folder.collab.transact().state_vector();
+  let mut documents: HashMap<String, TouchedDocument> = HashMap::new();
+  let mut pages = vec![];
+
+  for operation in params.operations {
+    match operation {
+      PageOperation::CreatePage {
+        parent_view_id,
+        name,
+        content,
+        insert_reference,
+      } => {
+        if folder.get_view(&parent_view_id).is_none() {
+          return Err(AppError::RecordNotFound(format!(
+            "View {} not found",
+            parent_view_id
+          )));
+        }
+        let view_id = Uuid::new_v4().to_string();
+        let (heading, document) = create_page_document(&view_id, content).await?;
+        let name = name.filter(|name| !name.trim().is_empty()).or(heading);
+        documents.insert(
+          view_id.clone(),
+          TouchedDocument {
+            document,
+            state_vector: None,
+          },
+        );
+        add_view_to_folder(uid, &parent_view_id, &view_id, name.as_deref(), &mut folder);
+        if insert_reference {
+          let parent = open_document(
+            collab_storage,
+            uid,
+            &workspace_id_str,
+            &folder,
+            &parent_view_id,
+            &mut documents,
+          )
+          .await?;
+          append_page_mention(parent, &view_id)?;
+        }
+        pages.push(Page { view_id });
+      },
+      PageOperation::InsertPageReference {
+        document_view_id,
+        page_view_id,
+      } => {
+        if folder.get_view(&page_view_id).is_none() {
+          return Err(AppError::RecordNotFound(format!(
+            "View {} not found",
+            page_view_id
+          )));
+        }
+        let document = open_document(
+          collab_storage,
+          uid,
+          &workspace_id_str,
+          &folder,
+          &document_view_id,
+          &mut documents,
+        )
+        .await?;
+        append_page_mention(document, &page_view_id)?;
+      },
+    }
+  }
+
+  save_folder_and_documents(
+    pg_pool,
+    collab_storage,
+    uid,
+    &workspace_id_str,
+    &folder,
+    &folder_state_vector,
+    documents,
+    "Apply page operations",
+  )
+  .await?;
+  Ok(PageOperationsResult { pages })
+}
+
+/// Creates the tree of pages under the parent view, and saves all of them in a single
+/// transaction. Returns the id of the view created for each page, by its client id.
+pub async fn create_page_batch(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  params: BatchCreatePageParams,
+) -> Result<BatchCreatePageResult, AppError> {
+  let page_count = count_batch_pages(&params.pages);
+  if page_count == 0 {
+    return Err(AppError::InvalidRequest(
+      "At least one page is required".to_string(),
+    ));
+  }
+  if page_count > MAX_BATCH_PAGES {
+    return Err(AppError::InvalidRequest(format!(
+      "At most {} pages can be created at once",
+      MAX_BATCH_PAGES
+    )));
+  }
+  let workspace_id_str = workspace_id.to_string();
+  let mut folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id_str,
+  )
+  .await?;
+  if folder.get_view(&params.parent_view_id).is_none() {
+    return Err(AppError::RecordNotFound(format!(
+      "View {} not found",
+      params.parent_view_id
+    )));
+  }
+  let folder_state_vector = folder.collab.transact().state_vector();
+  let mut documents: HashMap<String, TouchedDocument> = HashMap::new();
+  let mut view_ids = HashMap::new();
+
+  // Depth first, so that the pages are added under their parent in the order of the request.
+  let mut pending: Vec<(String, BatchPageParams)> = params
+    .pages
+    .into_iter()
+    .rev()
+    .map(|page| (params.parent_view_id.clone(), page))
+    .collect();
+  while let Some((parent_view_id, page)) = pending.pop() {
+    let view_id = Uuid::new_v4().to_string();
+    if view_ids
+      .insert(page.client_id.clone(), view_id.clone())
+      .is_some()
+    {
+      return Err(AppError::InvalidRequest(format!(
+        "The client id {} is used by more than one page",
+        page.client_id
+      )));
+    }
+    let (heading, document) = create_page_document(&view_id, page.content).await?;
+    let name = page.name.filter(|name| !name.trim().is_empty()).or(heading);
+    documents.insert(
+      view_id.clone(),
+      TouchedDocument {
+        document,
+        state_vector: None,
+      },
+    );
+    add_view_to_folder(uid, &parent_view_id, &view_id, name.as_deref(), &mut folder);
+    pending.extend(
+      page
+        .children
+        .into_iter()
+        .rev()
+        .map(|child| (view_id.clone(), child)),
+    );
+  }
+
+  save_folder_and_documents(
+    pg_pool,
+    collab_storage,
+    uid,
+    &workspace_id_str,
+    &folder,
+    &folder_state_vector,
+    documents,
+    "Create page batch",
+  )
+  .await?;
+  Ok(BatchCreatePageResult { view_ids })
+}
+
+fn count_batch_pages(pages: &[BatchPageParams]) -> usize {
+  pages
+    .iter()
+    .map(|page| 1 + count_batch_pages(&page.children))
+    .sum()
+}
+
+/// Saves the folder and the documents in a single transaction, then broadcasts the changes of the
+/// folder and of the documents that already existed.
+#[allow(clippy::too_many_arguments)]
+async fn save_folder_and_documents(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: &str,
+  folder: &Folder,
+  folder_state_vector: &StateVector,
+  documents: HashMap<String, TouchedDocument>,
+  action: &str,
+) -> Result<(), AppError> {
+  let mut updates = vec![];
+  let mut params_list = vec![];
+  for (view_id, touched) in documents {
+    let (collab, _) = touched.document.split();
+    if let Some(state_vector) = touched.state_vector {
+      updates.push((
+        view_id.clone(),
+        collab.transact().encode_state_as_update_v1(&state_vector),
+      ));
+    }
+    let encoded_collab_v1 = collab
+      .encode_collab_v1(|collab| CollabType::Document.validate_require_data(collab))
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode document: {}", err)))?
+      .encode_to_bytes()?;
+    params_list.push(CollabParams {
+      object_id: view_id,
+      encoded_collab_v1: encoded_collab_v1.into(),
+      collab_type: CollabType::Document,
+      embeddings: None,
+    });
+  }
+  let folder_update = folder
+    .collab
+    .transact()
+    .encode_state_as_update_v1(folder_state_vector);
+  params_list.push(CollabParams {
+    object_id: workspace_id.to_string(),
+    encoded_collab_v1: folder_to_encoded_collab(folder)?.into(),
+    collab_type: CollabType::Folder,
+    embeddings: None,
+  });
+  updates.push((workspace_id.to_string(), folder_update));
+
+  let mut transaction = pg_pool.begin().await?;
+  for params in params_list {
+    let action = format!("{}: {}", action, params.object_id);
+    collab_storage
+      .insert_new_collab_with_transaction(workspace_id, &uid, params, &mut transaction, &action)
+      .await?;
+  }
+  transaction.commit().await?;
+  for (object_id, update) in updates {
+    broadcast_update(collab_storage, &object_id, update).await?;
+  }
+  Ok(())
+}
+
+/// Creates the document of a new page, empty or filled with the content. Returns the first heading
+/// of the Markdown content along with the document.
+async fn create_page_document(
+  view_id: &str,
+  content: Option<PageContent>,
+) -> Result<(Option<String>, Document), AppError> {
+  let (heading, document_data) = match content {
+    Some(content) => {
+      let view_id = view_id.to_string();
+      tokio::task::spawn_blocking(move || page_content_to_document_data(&view_id, content))
+        .await??
+    },
+    None => (None, default_document_data(view_id)),
+  };
+  let document = Document::create(view_id, document_data)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to create document: {}", err)))?;
+  Ok((heading, document))
+}
+
+fn add_view_to_folder(
+  uid: i64,
+  parent_view_id: &str,
+  view_id: &str,
+  name: Option<&str>,
+  folder: &mut Folder,
+) {
+  let mut builder =
+    NestedChildViewBuilder::new(uid, parent_view_id.to_string()).with_view_id(view_id);
+  if let Some(name) = name {
+    builder = builder.with_name(name);
+  }
+  let view = builder.build().view;
+  let mut txn = folder.collab.transact_mut();
+  folder.body.views.insert(&mut txn, view, None);
+}
+
+/// Returns the document of the view, reading it from the storage the first time.
+async fn open_document<'a>(
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: &str,
+  folder: &Folder,
+  view_id: &str,
+  documents: &'a mut HashMap<String, TouchedDocument>,
+) -> Result<&'a mut Document, AppError> {
+  if !documents.contains_key(view_id) {
+    let view = folder
+      .get_view(view_id)
+      .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+    if view.layout != collab_folder::ViewLayout::Document || view_is_space(&view) {
+      return Err(AppError::InvalidRequest(format!(
+        "View {} is not a document",
+        view_id
+      )));
+    }
+    let encoded_collab = get_latest_collab_encoded(
+      collab_storage,
+      GetCollabOrigin::User { uid },
+      workspace_id,
+      view_id,
+      CollabType::Document,
+    )
+    .await?;
+    let collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), view_id)?;
+    let state_vector = collab.transact().state_vector();
+    let document = Document::open(collab)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to open document: {}", err)))?;
+    documents.insert(
+      view_id.to_string(),
+      TouchedDocument {
+        document,
+        state_vector: Some(state_vector),
+      },
+    );
+  }
+  documents
+    .get_mut(view_id)
+    .map(|touched| &mut touched.document)
+    .ok_or_else(|| AppError::Internal(anyhow!("Document {} not opened", view_id)))
+}
+
+/// Appends a paragraph made of a mention of the page at the end of the document.
+fn append_page_mention(document: &mut Document, page_view_id: &str) -> Result<(), AppError> {
+  let mention = SerdeBlock {
+    ty: "paragraph".to_string(),
+    data: HashMap::from([(
+      "delta".to_string(),
+      json!([{
+        "insert": "$",
+        "attributes": { "mention": { "type": "page", "page_id": page_view_id } }
+      }]),
+    )]),
+    children: vec![],
+  };
+  append_blocks(document, &[mention])
+}
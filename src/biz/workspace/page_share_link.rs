@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use access_control::act::Action;
+use access_control::collab::CollabAccessControl;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::HttpRequest;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::Utc;
+use collab_entity::CollabType;
+use collab_folder::{Folder, View, ViewLayout};
+use database::collab::GetCollabOrigin;
+use database::page_share_link::{
+  delete_page_share_link, insert_page_share_link, select_active_page_share_link,
+  select_page_share_links,
+};
+use database::pg_row::AFPageShareLinkRow;
+use shared_entity::dto::workspace_dto::{CreatePageShareLinkParams, PageShareLink, SharedPage};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::folder_view::view_is_space;
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+
+/// The scheme of the `Authorization` header carrying the token of a page share link.
+pub const SHARE_LINK_SCHEME: &str = "ShareLink";
+
+/// Creates a link sharing the page. The user must be able to read the page, the link granting no
+/// more than what its creator is granted.
+pub async fn create_page_share_link(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  appflowy_web_url: Option<&str>,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: Uuid,
+  params: CreatePageShareLinkParams,
+) -> Result<PageShareLink, AppError> {
+  if matches!(params.expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+    return Err(AppError::InvalidRequest(
+      "The expiry of the link must be in the future".to_string(),
+    ));
+  }
+  collab_access_control
+    .enforce_action(
+      &workspace_id.to_string(),
+      &uid,
+      &view_id.to_string(),
+      Action::Read,
+    )
+    .await?;
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+  )
+  .await?;
+  shareable_view(&folder, &view_id)?;
+  let row =
+    insert_page_share_link(pg_pool, &workspace_id, &view_id, params.expires_at, uid).await?;
+  Ok(page_share_link_from_row(row, appflowy_web_url))
+}
+
+pub async fn list_page_share_links(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  appflowy_web_url: Option<&str>,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PageShareLink>, AppError> {
+  collab_access_control
+    .enforce_action(
+      &workspace_id.to_string(),
+      &uid,
+      &view_id.to_string(),
+      Action::Read,
+    )
+    .await?;
+  let rows = select_page_share_links(pg_pool, workspace_id, view_id).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| page_share_link_from_row(row, appflowy_web_url))
+      .collect(),
+  )
+}
+
+pub async fn revoke_page_share_link(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  uid: i64,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  link_id: &Uuid,
+) -> Result<(), AppError> {
+  collab_access_control
+    .enforce_action(
+      &workspace_id.to_string(),
+      &uid,
+      &view_id.to_string(),
+      Action::Read,
+    )
+    .await?;
+  if !delete_page_share_link(pg_pool, workspace_id, view_id, link_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Share link {} not found",
+      link_id
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the page shared by the link of the token.
+pub async fn get_shared_page(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  token: &Uuid,
+) -> Result<SharedPage, AppError> {
+  let (link, view) = shared_view(pg_pool, collab_storage, collab_access_control, token).await?;
+  let encoded_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &link.workspace_id.to_string(),
+    &link.view_id.to_string(),
+    CollabType::Document,
+  )
+  .await?;
+  Ok(SharedPage {
+    workspace_id: link.workspace_id,
+    view_id: link.view_id,
+    name: view.name.clone(),
+    encoded_collab: encoded_collab.doc_state.to_vec(),
+  })
+}
+
+/// Checks that the token of a page share link grants reading the collab, which is the document of
+/// the shared page.
+pub async fn verify_page_share_link(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  token: &Uuid,
+  workspace_id: &str,
+  object_id: &str,
+) -> Result<(), AppError> {
+  let (link, _) = shared_view(pg_pool, collab_storage, collab_access_control, token).await?;
+  if link.workspace_id.to_string() != workspace_id || link.view_id.to_string() != object_id {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
+
+/// Returns the token of the page share link the request is authorized with, if any.
+pub fn share_link_token_from_request(req: &HttpRequest) -> Option<Uuid> {
+  let token = req
+    .headers()
+    .get(AUTHORIZATION)?
+    .to_str()
+    .ok()?
+    .strip_prefix(SHARE_LINK_SCHEME)?
+    .strip_prefix(' ')?;
+  Uuid::parse_str(token.trim()).ok()
+}
+
+/// Returns the link of the token and the page it shares. The link can't be used once it has
+/// expired or has been revoked, once the page is deleted or moved to the trash, nor once the user
+/// who created the link can't read the page anymore, e.g. because the page was restricted. All of
+/// these cases are reported as not found, so that the holder of the link can't tell them apart.
+async fn shared_view(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  token: &Uuid,
+) -> Result<(AFPageShareLinkRow, Arc<View>), AppError> {
+  let not_found = || AppError::RecordNotFound("The share link is invalid or expired".to_string());
+  let link = select_active_page_share_link(pg_pool, token)
+    .await?
+    .ok_or_else(not_found)?;
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &link.workspace_id.to_string(),
+  )
+  .await?;
+  let view = shareable_view(&folder, &link.view_id).map_err(|_| not_found())?;
+  let created_by = link.created_by.ok_or_else(not_found)?;
+  collab_access_control
+    .enforce_action(
+      &link.workspace_id.to_string(),
+      &created_by,
+      &link.view_id.to_string(),
+      Action::Read,
+    )
+    .await
+    .map_err(|_| not_found())?;
+  Ok((link, view))
+}
+
+/// Only the document pages that are not in the trash can be shared by a link.
+fn shareable_view(folder: &Folder, view_id: &Uuid) -> Result<Arc<View>, AppError> {
+  let view_id = view_id.to_string();
+  let view = folder
+    .get_view(&view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+  if view.layout != ViewLayout::Document || view_is_space(&view) {
+    return Err(AppError::InvalidRequest(format!(
+      "View {} is not a document",
+      view_id
+    )));
+  }
+  if is_view_in_trash(folder, &view) {
+    return Err(AppError::RecordNotFound(format!(
+      "View {} is in the trash",
+      view_id
+    )));
+  }
+  Ok(view)
+}
+
+/// A view is in the trash if the view or one of its ancestors was moved to the trash.
+fn is_view_in_trash(folder: &Folder, view: &View) -> bool {
+  let trash_view_ids: HashSet<String> = folder
+    .get_all_trash_sections()
+    .into_iter()
+    .map(|section| section.id)
+    .collect();
+  let mut current_view_id = view.id.clone();
+  // The limit guards against a cycle in the parent ids.
+  for _ in 0..64 {
+    if trash_view_ids.contains(&current_view_id) {
+      return true;
+    }
+    match folder.get_view(&current_view_id) {
+      Some(view) if !view.parent_view_id.is_empty() && view.parent_view_id != view.id => {
+        current_view_id = view.parent_view_id.clone();
+      },
+      _ => return false,
+    }
+  }
+  false
+}
+
+pub(crate) fn page_share_link_from_row(
+  row: AFPageShareLinkRow,
+  appflowy_web_url: Option<&str>,
+) -> PageShareLink {
+  PageShareLink {
+    link_id: row.link_id,
+    view_id: row.view_id,
+    token: row.token,
+    url: appflowy_web_url.map(|url| format!("{}/share/{}", url, row.token)),
+    created_by: row.created_by,
+    created_at: row.created_at,
+    expires_at: row.expires_at,
+  }
+}
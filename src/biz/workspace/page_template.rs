@@ -0,0 +1,212 @@
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_document::blocks::DocumentData;
+use collab_document::document::Document;
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::GetCollabOrigin;
+use database::page_template::{
+  delete_page_template, insert_page_template, select_page_template, select_page_template_content,
+  select_page_templates, update_page_template,
+};
+use database::pg_row::AFPageTemplateRow;
+use shared_entity::dto::workspace_dto::{
+  CreatePageTemplateParams, PageTemplate, PageTemplateSource, UpdatePageTemplateParams,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+
+use super::ops::collab_from_doc_state;
+use super::page_import::page_content_to_document_data;
+use super::page_view::prepare_document_collab_param;
+
+const MAX_PAGE_TEMPLATE_NAME_LENGTH: usize = 256;
+
+pub async fn create_page_template(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  params: CreatePageTemplateParams,
+) -> Result<PageTemplate, AppError> {
+  check_page_template_name(&params.name)?;
+  let template_id = Uuid::new_v4();
+  let encoded_collab_v1 = encode_page_template(
+    collab_storage,
+    uid,
+    &workspace_id,
+    &template_id,
+    params.source,
+  )
+  .await?;
+  let row = insert_page_template(
+    pg_pool,
+    &workspace_id,
+    &template_id,
+    params.name.trim(),
+    params.description.as_deref(),
+    &encoded_collab_v1,
+    uid,
+  )
+  .await?;
+  Ok(page_template_from_row(row))
+}
+
+pub async fn list_page_templates(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<PageTemplate>, AppError> {
+  let rows = select_page_templates(pg_pool, workspace_id).await?;
+  Ok(rows.into_iter().map(page_template_from_row).collect())
+}
+
+pub async fn get_page_template(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<PageTemplate, AppError> {
+  select_page_template(pg_pool, workspace_id, template_id)
+    .await?
+    .map(page_template_from_row)
+    .ok_or_else(|| page_template_not_found(template_id))
+}
+
+/// Renames the template, or replaces its document with a new copy of its source.
+pub async fn patch_page_template(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  template_id: Uuid,
+  params: UpdatePageTemplateParams,
+) -> Result<PageTemplate, AppError> {
+  if let Some(name) = &params.name {
+    check_page_template_name(name)?;
+  }
+  let encoded_collab_v1 = match params.source {
+    Some(source) => {
+      Some(encode_page_template(collab_storage, uid, &workspace_id, &template_id, source).await?)
+    },
+    None => None,
+  };
+  update_page_template(
+    pg_pool,
+    &workspace_id,
+    &template_id,
+    params.name.as_deref().map(str::trim),
+    params.description.as_deref(),
+    encoded_collab_v1.as_deref(),
+  )
+  .await?
+  .map(page_template_from_row)
+  .ok_or_else(|| page_template_not_found(&template_id))
+}
+
+pub async fn remove_page_template(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_page_template(pg_pool, workspace_id, template_id).await? {
+    return Err(page_template_not_found(template_id));
+  }
+  Ok(())
+}
+
+/// Returns the name of the template along with the data of its document, to create a page from.
+pub(super) async fn page_template_document_data(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+) -> Result<(String, DocumentData), AppError> {
+  let (name, encoded_collab_v1) = select_page_template_content(pg_pool, workspace_id, template_id)
+    .await?
+    .ok_or_else(|| page_template_not_found(template_id))?;
+  let object_id = template_id.to_string();
+  let document_data = tokio::task::spawn_blocking(move || {
+    let encoded_collab = EncodedCollab::decode_from_bytes(&encoded_collab_v1)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to decode template: {}", err)))?;
+    document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &object_id)
+  })
+  .await??;
+  Ok((name, document_data))
+}
+
+/// Encodes the document of the template, with the id of the template as its object id.
+async fn encode_page_template(
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: &Uuid,
+  template_id: &Uuid,
+  source: PageTemplateSource,
+) -> Result<Vec<u8>, AppError> {
+  let object_id = template_id.to_string();
+  let document_data = match source {
+    PageTemplateSource::View(view_id) => {
+      let encoded_collab = get_latest_collab_encoded(
+        collab_storage,
+        GetCollabOrigin::User { uid },
+        &workspace_id.to_string(),
+        &view_id,
+        CollabType::Document,
+      )
+      .await?;
+      tokio::task::spawn_blocking(move || {
+        document_data_from_doc_state(encoded_collab.doc_state.to_vec(), &view_id)
+      })
+      .await??
+    },
+    PageTemplateSource::Content(content) => {
+      let object_id = object_id.clone();
+      tokio::task::spawn_blocking(move || page_content_to_document_data(&object_id, content))
+        .await??
+        .1
+    },
+  };
+  let params = prepare_document_collab_param(object_id, document_data)?;
+  Ok(params.encoded_collab_v1.to_vec())
+}
+
+fn document_data_from_doc_state(
+  doc_state: Vec<u8>,
+  object_id: &str,
+) -> Result<DocumentData, AppError> {
+  let collab = collab_from_doc_state(doc_state, object_id)?;
+  let document = Document::open(collab)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to open document: {}", err)))?;
+  document
+    .get_document_data()
+    .map_err(|err| AppError::Internal(anyhow!("Failed to read document: {}", err)))
+}
+
+fn check_page_template_name(name: &str) -> Result<(), AppError> {
+  if name.trim().is_empty() {
+    return Err(AppError::InvalidRequest(
+      "The name of the template can't be empty".to_string(),
+    ));
+  }
+  if name.len() > MAX_PAGE_TEMPLATE_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The name of the template can't be longer than {} bytes",
+      MAX_PAGE_TEMPLATE_NAME_LENGTH
+    )));
+  }
+  Ok(())
+}
+
+fn page_template_not_found(template_id: &Uuid) -> AppError {
+  AppError::RecordNotFound(format!("Page template {} not found", template_id))
+}
+
+fn page_template_from_row(row: AFPageTemplateRow) -> PageTemplate {
+  PageTemplate {
+    template_id: row.template_id,
+    name: row.name,
+    description: row.description,
+    created_by: row.created_by,
+    created_at: row.created_at,
+    updated_at: row.updated_at,
+  }
+}
@@ -3,25 +3,35 @@ use app_error::AppError;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use chrono::DateTime;
 use collab::core::collab::Collab;
+use collab_database::database::DatabaseBody;
+use collab_database::rows::{meta_id_from_row_id, RowId, RowMetaKey};
 use collab_database::workspace_database::{NoPersistenceDatabaseCollabService, WorkspaceDatabase};
-use collab_database::{database::DatabaseBody, rows::RowId};
+use collab_document::blocks::{Block, DocumentData};
 use collab_document::document::Document;
 use collab_document::document_data::default_document_data;
 use collab_entity::{CollabType, EncodedCollab};
 use collab_folder::hierarchy_builder::NestedChildViewBuilder;
-use collab_folder::{CollabOrigin, Folder};
+use collab_folder::{CollabOrigin, Folder, RepeatedViewIdentifier, View};
 use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
 use database::publish::select_published_view_ids_for_workspace;
 use database::user::select_web_user_from_uid;
-use database_entity::dto::{CollabParams, QueryCollab, QueryCollabParams, QueryCollabResult};
+use database_entity::dto::{
+  collab_content_hash, CollabParams, CollabSyncState, CollabUpdateValidation, CollabUpdatesSince,
+  QueryCollab, QueryCollabParams, QueryCollabResult,
+};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use shared_entity::dto::workspace_dto::{FolderView, Page, PageCollab, PageCollabData, ViewLayout};
+use shared_entity::dto::workspace_dto::{
+  AppendBlockToPageParams, CollabExportFormat, CreatePageParams, FolderView, Page, PageCollab,
+  PageCollabData, PageViewContent, PatchPageParams, ReorderPageParams, ViewLayout,
+};
 use sqlx::{PgPool, Transaction};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
+use workspace_template::document::parser::SerdeBlock;
 use yrs::updates::decoder::Decode;
-use yrs::Update;
+use yrs::updates::encoder::Encode;
+use yrs::{ReadTxn, StateVector, Update};
 
 use crate::api::metrics::AppFlowyWebMetrics;
 use crate::biz::collab::folder_view::{
@@ -29,43 +39,111 @@ use crate::biz::collab::folder_view::{
 };
 use crate::biz::collab::{
   folder_view::view_is_space,
-  ops::{get_latest_collab_encoded, get_latest_collab_folder},
+  ops::{
+    get_guest_granted_view_ids, get_latest_collab_encoded, get_latest_collab_folder,
+    get_latest_collab_folder_for_user,
+  },
 };
+use crate::biz::export::export_document;
 
+use super::duplicate::{spawn_blocking_copy_collab, IdMapping};
 use super::ops::{broadcast_update, collab_from_doc_state};
+use super::page_import::{check_page_content_size, page_content_to_document_data};
+use super::page_template::page_template_document_data;
+use super::publish_dup::to_folder_view_icon;
 
 struct FolderUpdate {
   pub updated_encoded_collab: Vec<u8>,
   pub encoded_updates: Vec<u8>,
 }
 
+/// Creates a document page, empty or filled with the given content.
 pub async fn create_page(
   pg_pool: &PgPool,
   collab_storage: &CollabAccessControlStorage,
   uid: i64,
   workspace_id: Uuid,
-  parent_view_id: &str,
-  view_layout: &ViewLayout,
+  params: CreatePageParams,
 ) -> Result<Page, AppError> {
-  if *view_layout != ViewLayout::Document {
+  if params.layout != ViewLayout::Document {
     return Err(AppError::InvalidRequest(
       "Only document layout is supported for page creation".to_string(),
     ));
   }
-  create_document_page(pg_pool, collab_storage, uid, workspace_id, parent_view_id).await
+  let object_id = Uuid::new_v4().to_string();
+  let (heading, document_data) = match (params.template_id, params.content) {
+    (Some(_), Some(_)) => {
+      return Err(AppError::InvalidRequest(
+        "A page can't be created from both a template and content".to_string(),
+      ));
+    },
+    (Some(template_id), None) => {
+      let (template_name, document_data) =
+        page_template_document_data(pg_pool, &workspace_id, &template_id).await?;
+      (Some(template_name), document_data)
+    },
+    (None, Some(content)) => {
+      let object_id = object_id.clone();
+      tokio::task::spawn_blocking(move || page_content_to_document_data(&object_id, content))
+        .await??
+    },
+    (None, None) => (None, default_document_data(&object_id)),
+  };
+  let name = params
+    .name
+    .filter(|name| !name.trim().is_empty())
+    .or(heading);
+  let document_collab_params = prepare_document_collab_param(object_id, document_data)?;
+  create_document_page(
+    pg_pool,
+    collab_storage,
+    uid,
+    workspace_id,
+    &params.parent_view_id,
+    name.as_deref(),
+    document_collab_params,
+  )
+  .await
 }
 
-fn prepare_default_document_collab_param() -> Result<CollabParams, AppError> {
-  let object_id = Uuid::new_v4().to_string();
-  let document_data = default_document_data(&object_id);
+/// Creates a document page with the given name and content. The `view_id` must be the id the
+/// document data was created for.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_page_with_document_data(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  parent_view_id: &str,
+  name: &str,
+  view_id: String,
+  document_data: DocumentData,
+) -> Result<Page, AppError> {
+  let document_collab_params = prepare_document_collab_param(view_id, document_data)?;
+  create_document_page(
+    pg_pool,
+    collab_storage,
+    uid,
+    workspace_id,
+    parent_view_id,
+    Some(name),
+    document_collab_params,
+  )
+  .await
+}
+
+pub(super) fn prepare_document_collab_param(
+  object_id: String,
+  document_data: DocumentData,
+) -> Result<CollabParams, AppError> {
   let document = Document::create(&object_id, document_data)
-    .map_err(|err| AppError::Internal(anyhow!("Failed to create default document: {}", err)))?;
+    .map_err(|err| AppError::Internal(anyhow!("Failed to create document: {}", err)))?;
   let encoded_collab_v1 = document
     .encode_collab()
-    .map_err(|err| AppError::Internal(anyhow!("Failed to encode default document: {}", err)))?
+    .map_err(|err| AppError::Internal(anyhow!("Failed to encode document: {}", err)))?
     .encode_to_bytes()?;
   Ok(CollabParams {
-    object_id: object_id.clone(),
+    object_id,
     encoded_collab_v1: encoded_collab_v1.into(),
     collab_type: CollabType::Document,
     embeddings: None,
@@ -76,13 +154,16 @@ async fn add_new_view_to_folder(
   uid: i64,
   parent_view_id: &str,
   view_id: &str,
+  name: Option<&str>,
   folder: &mut Folder,
 ) -> Result<FolderUpdate, AppError> {
   let encoded_update = {
-    let view = NestedChildViewBuilder::new(uid, parent_view_id.to_string())
-      .with_view_id(view_id)
-      .build()
-      .view;
+    let mut builder =
+      NestedChildViewBuilder::new(uid, parent_view_id.to_string()).with_view_id(view_id);
+    if let Some(name) = name {
+      builder = builder.with_name(name);
+    }
+    let view = builder.build().view;
     let mut txn = folder.collab.transact_mut();
     folder.body.views.insert(&mut txn, view, None);
     txn.encode_update_v1()
@@ -93,7 +174,7 @@ async fn add_new_view_to_folder(
   })
 }
 
-fn folder_to_encoded_collab(folder: &Folder) -> Result<Vec<u8>, AppError> {
+pub(super) fn folder_to_encoded_collab(folder: &Folder) -> Result<Vec<u8>, AppError> {
   let collab_type = CollabType::Folder;
   let encoded_folder_collab = folder
     .encode_collab_v1(|collab| collab_type.validate_require_data(collab))
@@ -144,20 +225,22 @@ async fn create_document_page(
   uid: i64,
   workspace_id: Uuid,
   parent_view_id: &str,
+  name: Option<&str>,
+  document_collab_params: CollabParams,
 ) -> Result<Page, AppError> {
-  let default_document_collab_params = prepare_default_document_collab_param()?;
-  let view_id = default_document_collab_params.object_id.clone();
+  let view_id = document_collab_params.object_id.clone();
   let collab_origin = GetCollabOrigin::User { uid };
   let mut folder =
     get_latest_collab_folder(collab_storage, collab_origin, &workspace_id.to_string()).await?;
-  let folder_update = add_new_view_to_folder(uid, parent_view_id, &view_id, &mut folder).await?;
+  let folder_update =
+    add_new_view_to_folder(uid, parent_view_id, &view_id, name, &mut folder).await?;
   let mut transaction = pg_pool.begin().await?;
   let action = format!("Create new collab: {}", view_id);
   collab_storage
     .insert_new_collab_with_transaction(
       &workspace_id.to_string(),
       &uid,
-      default_document_collab_params,
+      document_collab_params,
       &mut transaction,
       &action,
     )
@@ -174,6 +257,644 @@ async fn create_document_page(
   Ok(Page { view_id })
 }
 
+/// Renames, changes the icon of, or moves the view in the folder of the workspace, and broadcasts
+/// the change to the connected clients.
+pub async fn update_page(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+  params: PatchPageParams,
+) -> Result<(), AppError> {
+  if let Some(name) = &params.name {
+    if name.trim().is_empty() {
+      return Err(AppError::InvalidRequest(
+        "The name of the page can't be empty".to_string(),
+      ));
+    }
+  }
+  let collab_origin = GetCollabOrigin::User { uid };
+  let mut folder =
+    get_latest_collab_folder(collab_storage, collab_origin, &workspace_id.to_string()).await?;
+  let view = folder
+    .get_view(view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+  if let Some(parent_view_id) = &params.parent_view_id {
+    check_page_can_move_to(&folder, view_id, parent_view_id)?;
+    if view_is_space(&view) {
+      return Err(AppError::InvalidRequest(
+        "A space can't be moved under another view".to_string(),
+      ));
+    }
+  }
+  let folder_update = update_view_in_folder(view_id, params, &mut folder)?;
+  let mut transaction = pg_pool.begin().await?;
+  insert_and_broadcast_workspace_folder_update(
+    uid,
+    workspace_id,
+    folder_update,
+    collab_storage,
+    &mut transaction,
+  )
+  .await?;
+  transaction.commit().await?;
+  Ok(())
+}
+
+/// Moves the view right after or right before one of its siblings, and broadcasts the change to
+/// the connected clients.
+pub async fn reorder_page(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+  params: ReorderPageParams,
+) -> Result<(), AppError> {
+  let collab_origin = GetCollabOrigin::User { uid };
+  let mut folder =
+    get_latest_collab_folder(collab_storage, collab_origin, &workspace_id.to_string()).await?;
+  let view = folder
+    .get_view(view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+  let parent = folder.get_view(&view.parent_view_id).ok_or_else(|| {
+    AppError::InvalidRequest(format!("View {} has no parent to reorder in", view_id))
+  })?;
+  let sibling_ids: Vec<&str> = parent
+    .children
+    .iter()
+    .map(|child| child.id.as_str())
+    .filter(|id| *id != view_id)
+    .collect();
+  let sibling_index = |sibling_id: &str| {
+    sibling_ids
+      .iter()
+      .position(|id| *id == sibling_id)
+      .ok_or_else(|| {
+        AppError::InvalidRequest(format!(
+          "View {} is not a sibling of view {}",
+          sibling_id, view_id
+        ))
+      })
+  };
+  let prev_view_id = match (&params.after_view_id, &params.before_view_id) {
+    (Some(after_view_id), None) => {
+      sibling_index(after_view_id)?;
+      Some(after_view_id.clone())
+    },
+    (None, Some(before_view_id)) => match sibling_index(before_view_id)? {
+      0 => None,
+      index => Some(sibling_ids[index - 1].to_string()),
+    },
+    _ => {
+      return Err(AppError::InvalidRequest(
+        "Exactly one of after_view_id and before_view_id must be set".to_string(),
+      ))
+    },
+  };
+  let folder_update = update_view_in_folder(
+    view_id,
+    PatchPageParams {
+      parent_view_id: Some(parent.id.clone()),
+      prev_view_id,
+      ..Default::default()
+    },
+    &mut folder,
+  )?;
+  let mut transaction = pg_pool.begin().await?;
+  insert_and_broadcast_workspace_folder_update(
+    uid,
+    workspace_id,
+    folder_update,
+    collab_storage,
+    &mut transaction,
+  )
+  .await?;
+  transaction.commit().await?;
+  Ok(())
+}
+
+/// Moves the view and its descendants to the trash of the user and removes them from their
+/// favorites, like the desktop app does, and broadcasts the change to the connected clients.
+/// Returns the ids of the views moved to the trash.
+pub async fn move_page_to_trash(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let collab_origin = GetCollabOrigin::User { uid };
+  let mut folder =
+    get_latest_collab_folder(collab_storage, collab_origin, &workspace_id.to_string()).await?;
+  let view = folder
+    .get_view(view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+  if view_is_space(&view) {
+    return Err(AppError::InvalidRequest(
+      "A space can't be moved to the trash".to_string(),
+    ));
+  }
+  let (folder_update, trashed_view_ids) = move_view_and_descendants_to_trash(view_id, &mut folder)?;
+  let mut transaction = pg_pool.begin().await?;
+  insert_and_broadcast_workspace_folder_update(
+    uid,
+    workspace_id,
+    folder_update,
+    collab_storage,
+    &mut transaction,
+  )
+  .await?;
+  transaction.commit().await?;
+  Ok(trashed_view_ids)
+}
+
+fn move_view_and_descendants_to_trash(
+  view_id: &str,
+  folder: &mut Folder,
+) -> Result<(FolderUpdate, Vec<String>), AppError> {
+  let trashed_ids: HashSet<String> = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  let mut view_ids = vec![view_id.to_string()];
+  let mut index = 0;
+  while index < view_ids.len() {
+    let children = folder.get_views_belong_to(&view_ids[index]);
+    view_ids.extend(children.iter().map(|child| child.id.clone()));
+    index += 1;
+  }
+  view_ids.retain(|id| !trashed_ids.contains(id));
+
+  let state_vector = folder.collab.transact().state_vector();
+  folder.delete_favorite_view_ids(view_ids.clone());
+  folder.add_trash_view_ids(view_ids.clone());
+  let encoded_update = folder
+    .collab
+    .transact()
+    .encode_state_as_update_v1(&state_vector);
+  let folder_update = FolderUpdate {
+    updated_encoded_collab: folder_to_encoded_collab(folder)?,
+    encoded_updates: encoded_update,
+  };
+  Ok((folder_update, view_ids))
+}
+
+/// Deep-copies the view and its descendants, along with the databases they show and the rows of
+/// these databases, and inserts the copy right after the view. The views in the trash and the AI
+/// chats are left out.
+pub async fn duplicate_page(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+) -> Result<Page, AppError> {
+  let workspace_id_str = workspace_id.to_string();
+  let collab_origin = GetCollabOrigin::User { uid };
+  let mut folder =
+    get_latest_collab_folder(collab_storage, collab_origin, &workspace_id_str).await?;
+  let view = folder
+    .get_view(view_id)
+    .ok_or_else(|| AppError::RecordNotFound(format!("View {} not found", view_id)))?;
+  if view_is_space(&view) {
+    return Err(AppError::InvalidRequest(
+      "A space can't be duplicated".to_string(),
+    ));
+  }
+  if view.layout == collab_folder::ViewLayout::Chat {
+    return Err(AppError::InvalidRequest(
+      "An AI chat can't be duplicated".to_string(),
+    ));
+  }
+  let views = views_to_duplicate(&folder, view);
+
+  let ws_db = if views.iter().any(|view| view_is_database(view)) {
+    Some(open_workspace_database(pg_pool, collab_storage, uid, workspace_id).await?)
+  } else {
+    None
+  };
+  let mut queries = vec![];
+  let mut linked_views_by_database_id: HashMap<String, Vec<String>> = HashMap::new();
+  for view in &views {
+    match &ws_db {
+      Some((_, ws_db_body)) if view_is_database(view) => {
+        let database_meta = ws_db_body
+          .get_database_meta_with_view_id(&view.id)
+          .ok_or_else(|| {
+            AppError::NoRequiredData(format!("Database view {} not found", view.id))
+          })?;
+        if !linked_views_by_database_id.contains_key(&database_meta.database_id) {
+          queries.push(QueryCollab {
+            object_id: database_meta.database_id.clone(),
+            collab_type: CollabType::Database,
+          });
+          linked_views_by_database_id.insert(database_meta.database_id, database_meta.linked_views);
+        }
+      },
+      _ => queries.push(QueryCollab {
+        object_id: view.id.clone(),
+        collab_type: CollabType::Document,
+      }),
+    }
+  }
+  let mut collabs = get_collabs_to_duplicate(collab_storage, uid, queries).await;
+
+  // The document of a row only exists once the row was opened
+  let mut row_queries = vec![];
+  for (object_id, collab_type, encoded_collab_v1) in &collabs {
+    if *collab_type != CollabType::Database {
+      continue;
+    }
+    for row_id in database_row_ids(object_id, encoded_collab_v1)? {
+      row_queries.push(QueryCollab {
+        object_id: meta_id_from_row_id(&row_id, RowMetaKey::DocumentId),
+        collab_type: CollabType::Document,
+      });
+      row_queries.push(QueryCollab {
+        object_id: row_id.to_string(),
+        collab_type: CollabType::DatabaseRow,
+      });
+    }
+  }
+  collabs.extend(get_collabs_to_duplicate(collab_storage, uid, row_queries).await);
+
+  let collab_ids = collabs
+    .iter()
+    .map(|(object_id, collab_type, _)| (object_id.clone(), collab_type.clone()))
+    .collect::<Vec<_>>();
+  let view_ids = views
+    .iter()
+    .map(|view| view.id.clone())
+    .chain(linked_views_by_database_id.values().flatten().cloned())
+    .collect();
+  let id_mapping = Arc::new(IdMapping::for_collabs(&collab_ids, view_ids));
+  let mut params_list = Vec::with_capacity(collabs.len());
+  for (object_id, collab_type, encoded_collab_v1) in collabs {
+    params_list.push(
+      spawn_blocking_copy_collab(
+        &object_id,
+        &collab_type,
+        encoded_collab_v1,
+        id_mapping.clone(),
+      )
+      .await?,
+    );
+  }
+
+  let new_view_id = id_mapping
+    .new_id(view_id)
+    .cloned()
+    .ok_or_else(|| AppError::Internal(anyhow!("No new id for view {}", view_id)))?;
+  let folder_update = add_duplicated_views_to_folder(uid, &views, &id_mapping, &mut folder)?;
+  let mut transaction = pg_pool.begin().await?;
+  for params in params_list {
+    let action = format!("Duplicate collab: {}", params.object_id);
+    collab_storage
+      .insert_new_collab_with_transaction(
+        &workspace_id_str,
+        &uid,
+        params,
+        &mut transaction,
+        &action,
+      )
+      .await?;
+  }
+  if let Some((ws_db_oid, mut ws_db_body)) = ws_db {
+    let view_ids_by_database_id = linked_views_by_database_id
+      .into_iter()
+      .filter_map(|(database_id, linked_views)| {
+        let new_database_id = id_mapping.new_id(&database_id)?.clone();
+        let new_linked_views = linked_views
+          .iter()
+          .filter_map(|view_id| id_mapping.new_id(view_id).cloned())
+          .collect();
+        Some((new_database_id, new_linked_views))
+      })
+      .collect::<HashMap<_, _>>();
+    let ws_db_updates = ws_db_body
+      .batch_add_database(view_ids_by_database_id)
+      .encode_update_v1();
+    let encoded_ws_db = ws_db_body
+      .encode_collab_v1()
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode workspace database: {}", err)))?
+      .encode_to_bytes()?;
+    collab_storage
+      .insert_new_collab_with_transaction(
+        &workspace_id_str,
+        &uid,
+        CollabParams {
+          object_id: ws_db_oid.clone(),
+          encoded_collab_v1: encoded_ws_db.into(),
+          collab_type: CollabType::WorkspaceDatabase,
+          embeddings: None,
+        },
+        &mut transaction,
+        "Update workspace database",
+      )
+      .await?;
+    broadcast_update(collab_storage, &ws_db_oid, ws_db_updates).await?;
+  }
+  insert_and_broadcast_workspace_folder_update(
+    uid,
+    workspace_id,
+    folder_update,
+    collab_storage,
+    &mut transaction,
+  )
+  .await?;
+  transaction.commit().await?;
+  Ok(Page {
+    view_id: new_view_id,
+  })
+}
+
+fn view_is_database(view: &View) -> bool {
+  matches!(
+    view.layout,
+    collab_folder::ViewLayout::Grid
+      | collab_folder::ViewLayout::Board
+      | collab_folder::ViewLayout::Calendar
+  )
+}
+
+/// Returns the view and its descendants, parents before their children.
+fn views_to_duplicate(folder: &Folder, view: Arc<View>) -> Vec<Arc<View>> {
+  let trashed_ids: HashSet<String> = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect();
+  let mut views = vec![view];
+  let mut index = 0;
+  while index < views.len() {
+    let children = folder.get_views_belong_to(&views[index].id);
+    views.extend(children.into_iter().filter(|child| {
+      child.layout != collab_folder::ViewLayout::Chat && !trashed_ids.contains(&child.id)
+    }));
+    index += 1;
+  }
+  views
+}
+
+async fn open_workspace_database(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+) -> Result<(String, WorkspaceDatabase), AppError> {
+  let ws_db_oid = select_workspace_database_oid(pg_pool, &workspace_id).await?;
+  let ws_db = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    &ws_db_oid,
+    CollabType::WorkspaceDatabase,
+  )
+  .await?;
+  let ws_db_collab = collab_from_doc_state(ws_db.doc_state.to_vec(), &ws_db_oid)?;
+  let ws_db_body = WorkspaceDatabase::open(ws_db_collab).map_err(|err| {
+    AppError::Internal(anyhow!("Failed to open workspace database body: {}", err))
+  })?;
+  Ok((ws_db_oid, ws_db_body))
+}
+
+/// Returns the collabs that could be read, along with their type.
+async fn get_collabs_to_duplicate(
+  collab_storage: &CollabAccessControlStorage,
+  uid: i64,
+  queries: Vec<QueryCollab>,
+) -> Vec<(String, CollabType, Vec<u8>)> {
+  let collab_types: HashMap<String, CollabType> = queries
+    .iter()
+    .map(|query| (query.object_id.clone(), query.collab_type.clone()))
+    .collect();
+  collab_storage
+    .batch_get_collab(&uid, queries, true)
+    .await
+    .into_iter()
+    .filter_map(|(object_id, result)| match result {
+      QueryCollabResult::Success { encode_collab_v1 } => {
+        let collab_type = collab_types.get(&object_id)?.clone();
+        Some((object_id, collab_type, encode_collab_v1))
+      },
+      QueryCollabResult::Failed { .. } => None,
+    })
+    .collect()
+}
+
+fn database_row_ids(database_id: &str, encoded_collab_v1: &[u8]) -> Result<Vec<RowId>, AppError> {
+  let encoded_collab = EncodedCollab::decode_from_bytes(encoded_collab_v1)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to decode database: {}", err)))?;
+  let db_collab = Collab::new_with_source(
+    CollabOrigin::Server,
+    database_id,
+    encoded_collab.into(),
+    vec![],
+    false,
+  )
+  .map_err(|err| {
+    AppError::Internal(anyhow!(
+      "Unable to create collab from object id {}: {}",
+      database_id,
+      err
+    ))
+  })?;
+  let db_body = DatabaseBody::from_collab(
+    &db_collab,
+    Arc::new(NoPersistenceDatabaseCollabService),
+    None,
+  )
+  .ok_or_else(|| AppError::RecordNotFound("no database body found".to_string()))?;
+  let txn = db_collab.transact();
+  let inline_view_id = db_body.get_inline_view_id(&txn);
+  let row_ids = db_body
+    .views
+    .get_row_orders(&txn, &inline_view_id)
+    .iter()
+    .map(|row_order| row_order.id.clone())
+    .collect();
+  Ok(row_ids)
+}
+
+/// Inserts the copies of the views, the copy of the first view right after it and the copies of
+/// its descendants under their copied parents.
+fn add_duplicated_views_to_folder(
+  uid: i64,
+  views: &[Arc<View>],
+  id_mapping: &IdMapping,
+  folder: &mut Folder,
+) -> Result<FolderUpdate, AppError> {
+  let root_index = views.first().and_then(|root| {
+    folder.get_view(&root.parent_view_id).and_then(|parent| {
+      parent
+        .children
+        .iter()
+        .position(|child| child.id == root.id)
+        .map(|index| index as u32 + 1)
+    })
+  });
+  let now = chrono::Utc::now().timestamp();
+  let encoded_update = {
+    let mut txn = folder.collab.transact_mut();
+    for (i, view) in views.iter().enumerate() {
+      let new_id = id_mapping
+        .new_id(&view.id)
+        .cloned()
+        .ok_or_else(|| AppError::Internal(anyhow!("No new id for view {}", view.id)))?;
+      let (parent_view_id, name, index) = if i == 0 {
+        (
+          view.parent_view_id.clone(),
+          format!("{} (copy)", view.name),
+          root_index,
+        )
+      } else {
+        (
+          id_mapping.remap_key(&view.parent_view_id),
+          view.name.clone(),
+          None,
+        )
+      };
+      let new_view = View {
+        id: new_id,
+        parent_view_id,
+        name,
+        children: RepeatedViewIdentifier { items: vec![] },
+        created_at: now,
+        is_favorite: false,
+        layout: view.layout.clone(),
+        icon: view.icon.clone(),
+        created_by: Some(uid),
+        last_edited_time: now,
+        last_edited_by: Some(uid),
+        extra: view.extra.as_ref().map(|extra| id_mapping.remap_key(extra)),
+      };
+      folder.body.views.insert(&mut txn, new_view, index);
+    }
+    txn.encode_update_v1()
+  };
+  Ok(FolderUpdate {
+    updated_encoded_collab: folder_to_encoded_collab(folder)?,
+    encoded_updates: encoded_update,
+  })
+}
+
+/// The new parent must exist and must not be the view itself or one of its descendants.
+fn check_page_can_move_to(
+  folder: &Folder,
+  view_id: &str,
+  parent_view_id: &str,
+) -> Result<(), AppError> {
+  let mut ancestor_id = parent_view_id.to_string();
+  loop {
+    if ancestor_id == view_id {
+      return Err(AppError::InvalidRequest(format!(
+        "View {} can't be moved under itself or one of its children",
+        view_id
+      )));
+    }
+    match folder.get_view(&ancestor_id) {
+      Some(ancestor) if !ancestor.parent_view_id.is_empty() => {
+        ancestor_id = ancestor.parent_view_id.clone()
+      },
+      Some(_) => return Ok(()),
+      None if ancestor_id == parent_view_id => {
+        return Err(AppError::RecordNotFound(format!(
+          "View {} not found",
+          parent_view_id
+        )))
+      },
+      None => return Ok(()),
+    }
+  }
+}
+
+fn update_view_in_folder(
+  view_id: &str,
+  params: PatchPageParams,
+  folder: &mut Folder,
+) -> Result<FolderUpdate, AppError> {
+  let encoded_update = {
+    let mut txn = folder.collab.transact_mut();
+    if params.name.is_some() || params.icon.is_some() {
+      folder
+        .body
+        .views
+        .update_view(&mut txn, view_id, |mut update| {
+          if let Some(name) = &params.name {
+            update = update.set_name(name.trim());
+          }
+          if let Some(icon) = params.icon {
+            update = update.set_icon(icon.map(to_folder_view_icon));
+          }
+          update.done()
+        });
+    }
+    if let Some(parent_view_id) = &params.parent_view_id {
+      folder
+        .body
+        .move_nested_view(&mut txn, view_id, parent_view_id, params.prev_view_id);
+    }
+    txn.encode_update_v1()
+  };
+  Ok(FolderUpdate {
+    updated_encoded_collab: folder_to_encoded_collab(folder)?,
+    encoded_updates: encoded_update,
+  })
+}
+
+/// Returns the content of the document of the page, converted to the format.
+pub async fn get_page_view_content(
+  pg_pool: &PgPool,
+  collab_access_control_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+  format: CollabExportFormat,
+) -> Result<PageViewContent, AppError> {
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  if let Some(guest_view_ids) = &guest_view_ids {
+    if !guest_view_ids.contains(view_id) {
+      return Err(AppError::NotEnoughPermissions);
+    }
+  }
+  let folder = get_latest_collab_folder_for_user(
+    collab_access_control_storage,
+    uid,
+    &workspace_id.to_string(),
+    guest_view_ids.is_some(),
+  )
+  .await?;
+  let view = folder
+    .get_view(view_id)
+    .ok_or(AppError::InvalidFolderView(format!(
+      "View {} not found",
+      view_id
+    )))?;
+  if view.layout != collab_folder::ViewLayout::Document || view_is_space(&view) {
+    return Err(AppError::InvalidRequest(format!(
+      "View {} is not a document",
+      view_id
+    )));
+  }
+  let content = export_document(
+    collab_access_control_storage,
+    uid,
+    workspace_id,
+    view_id,
+    format,
+  )
+  .await?;
+  Ok(PageViewContent {
+    view_id: view_id.to_string(),
+    name: view.name.clone(),
+    format,
+    content,
+  })
+}
+
 pub async fn get_page_view_collab(
   pg_pool: &PgPool,
   collab_access_control_storage: &CollabAccessControlStorage,
@@ -181,10 +902,17 @@ pub async fn get_page_view_collab(
   workspace_id: Uuid,
   view_id: &str,
 ) -> Result<PageCollab, AppError> {
-  let folder = get_latest_collab_folder(
+  let guest_view_ids = get_guest_granted_view_ids(pg_pool, uid, &workspace_id).await?;
+  if let Some(guest_view_ids) = &guest_view_ids {
+    if !guest_view_ids.contains(view_id) {
+      return Err(AppError::NotEnoughPermissions);
+    }
+  }
+  let folder = get_latest_collab_folder_for_user(
     collab_access_control_storage,
-    GetCollabOrigin::User { uid },
+    uid,
     &workspace_id.to_string(),
+    guest_view_ids.is_some(),
   )
   .await?;
   let view = folder
@@ -262,19 +990,8 @@ async fn get_page_collab_data_for_database(
   workspace_id: Uuid,
   view_id: &str,
 ) -> Result<PageCollabData, AppError> {
-  let ws_db_oid = select_workspace_database_oid(pg_pool, &workspace_id).await?;
-  let ws_db = get_latest_collab_encoded(
-    collab_access_control_storage,
-    GetCollabOrigin::User { uid },
-    &workspace_id.to_string(),
-    &ws_db_oid,
-    CollabType::WorkspaceDatabase,
-  )
-  .await?;
-  let ws_db_collab = collab_from_doc_state(ws_db.doc_state.to_vec(), &ws_db_oid)?;
-  let ws_db_body = WorkspaceDatabase::open(ws_db_collab).map_err(|err| {
-    AppError::Internal(anyhow!("Failed to open workspace database body: {}", err))
-  })?;
+  let (_, ws_db_body) =
+    open_workspace_database(pg_pool, collab_access_control_storage, uid, workspace_id).await?;
   let db_oid = {
     ws_db_body
       .get_database_meta_with_view_id(view_id)
@@ -435,3 +1152,252 @@ pub async fn update_page_collab_data(
   .await?;
   Ok(())
 }
+
+/// Appends the blocks to the end of the document in a single transaction, saves the document and
+/// broadcasts the change to the connected clients.
+pub async fn append_block_to_page(
+  collab_access_control_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  view_id: &str,
+  params: AppendBlockToPageParams,
+) -> Result<(), AppError> {
+  if params.blocks.is_empty() {
+    return Err(AppError::InvalidRequest(
+      "At least one block is required".to_string(),
+    ));
+  }
+  let blocks = serde_json::Value::Array(params.blocks);
+  check_page_content_size(blocks.to_string().len())?;
+  let blocks = serde_json::from_value::<Vec<SerdeBlock>>(blocks)
+    .map_err(|err| AppError::InvalidRequest(format!("Invalid blocks: {}", err)))?;
+
+  let encoded_collab = get_latest_collab_encoded(
+    collab_access_control_storage,
+    GetCollabOrigin::User { uid },
+    &workspace_id.to_string(),
+    view_id,
+    CollabType::Document,
+  )
+  .await?;
+  let collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), view_id)?;
+  let (encoded_collab_v1, update) = tokio::task::spawn_blocking(move || {
+    let state_vector = collab.transact().state_vector();
+    let mut document = Document::open(collab)
+      .map_err(|err| AppError::Internal(anyhow!("Failed to open document: {}", err)))?;
+    append_blocks(&mut document, &blocks)?;
+    let (collab, _) = document.split();
+    let update = collab.transact().encode_state_as_update_v1(&state_vector);
+    let encoded_collab_v1 = collab
+      .encode_collab_v1(|collab| CollabType::Document.validate_require_data(collab))
+      .map_err(|err| AppError::Internal(anyhow!("Failed to encode document: {}", err)))?
+      .encode_to_bytes()?;
+    Ok::<_, AppError>((encoded_collab_v1, update))
+  })
+  .await??;
+  let params = CollabParams {
+    object_id: view_id.to_string(),
+    collab_type: CollabType::Document,
+    encoded_collab_v1: encoded_collab_v1.into(),
+    embeddings: None,
+  };
+  collab_access_control_storage
+    .queue_insert_or_update_collab(&workspace_id.to_string(), &uid, params, true)
+    .await?;
+  broadcast_update(collab_access_control_storage, view_id, update).await?;
+  Ok(())
+}
+
+/// Appends the blocks, along with their children, to the end of the page block of the document.
+pub(super) fn append_blocks(
+  document: &mut Document,
+  blocks: &[SerdeBlock],
+) -> Result<(), AppError> {
+  let page_id = document
+    .get_page_id()
+    .ok_or_else(|| AppError::Internal(anyhow!("The document has no page block")))?;
+  let mut prev_id = document.get_block_children_ids(&page_id).last().cloned();
+  for block in blocks {
+    prev_id = Some(insert_serde_block(document, &page_id, prev_id, block)?);
+  }
+  Ok(())
+}
+
+/// Inserts the block after `prev_id`, or first if missing, then its children. The `delta` of the
+/// data of the block becomes its text. Returns the id of the block.
+fn insert_serde_block(
+  document: &mut Document,
+  parent_id: &str,
+  prev_id: Option<String>,
+  block: &SerdeBlock,
+) -> Result<String, AppError> {
+  if block.ty.is_empty() || block.ty == "page" {
+    return Err(AppError::InvalidRequest(format!(
+      "Invalid block type: {:?}",
+      block.ty
+    )));
+  }
+  let mut data = block.data.clone();
+  let delta = data.remove("delta");
+  let text_id = delta.as_ref().map(|_| Uuid::new_v4().to_string());
+  let block_id = Uuid::new_v4().to_string();
+  document
+    .insert_block(
+      Block {
+        id: block_id.clone(),
+        ty: block.ty.clone(),
+        parent: parent_id.to_string(),
+        children: Uuid::new_v4().to_string(),
+        external_id: text_id.clone(),
+        external_type: text_id.as_ref().map(|_| "text".to_string()),
+        data,
+      },
+      prev_id,
+    )
+    .map_err(|err| AppError::Internal(anyhow!("Failed to insert block: {}", err)))?;
+  if let (Some(text_id), Some(delta)) = (text_id, delta) {
+    document.apply_text_delta(&text_id, delta.to_string());
+  }
+  let mut child_prev_id = None;
+  for child in &block.children {
+    child_prev_id = Some(insert_serde_block(
+      document,
+      &block_id,
+      child_prev_id,
+      child,
+    )?);
+  }
+  Ok(block_id)
+}
+
+/// Applies the update to a scratch copy of the collab and reports whether it decodes and applies
+/// cleanly, and what it would change. Nothing is persisted nor broadcast.
+pub async fn validate_page_collab_update(
+  collab_access_control_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  collab_type: CollabType,
+  doc_state: &[u8],
+) -> Result<CollabUpdateValidation, AppError> {
+  let param = QueryCollabParams {
+    workspace_id: workspace_id.to_string(),
+    inner: QueryCollab {
+      object_id: object_id.to_string(),
+      collab_type: collab_type.clone(),
+    },
+  };
+  let encode_collab = collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::User { uid }, param, true)
+    .await?;
+  let mut validation = CollabUpdateValidation {
+    decoded: false,
+    applied: false,
+    error: None,
+    has_missing_dependencies: false,
+    is_valid: false,
+    changed_keys: vec![],
+    doc_state_size_before: encode_collab.doc_state.len(),
+    doc_state_size_after: encode_collab.doc_state.len(),
+  };
+  let mut collab = collab_from_doc_state(encode_collab.doc_state.to_vec(), &object_id.to_string())?;
+  let before = collab.to_json_value();
+
+  let update = match Update::decode_v1(doc_state) {
+    Ok(update) => update,
+    Err(err) => {
+      validation.error = Some(format!("Failed to decode update: {}", err));
+      return Ok(validation);
+    },
+  };
+  validation.decoded = true;
+  if let Err(err) = collab.apply_update(update) {
+    validation.error = Some(format!("Failed to apply update: {}", err));
+    return Ok(validation);
+  }
+  validation.applied = true;
+  validation.has_missing_dependencies = collab.transact().store().pending_update().is_some();
+
+  let after = collab.to_json_value();
+  validation.changed_keys = changed_top_level_keys(&before, &after);
+  match collab.encode_collab_v1(|c| collab_type.validate_require_data(c)) {
+    Ok(encoded_collab) => {
+      validation.is_valid = true;
+      validation.doc_state_size_after = encoded_collab.doc_state.len();
+    },
+    Err(err) => validation.error = Some(format!("The updated collab is invalid: {}", err)),
+  }
+  Ok(validation)
+}
+
+/// Returns the changes of the collab that are missing from the state vector of the client.
+pub async fn get_collab_updates_since(
+  collab_access_control_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  collab_type: CollabType,
+  state_vector: &[u8],
+) -> Result<CollabUpdatesSince, AppError> {
+  let state_vector = StateVector::decode_v1(state_vector)
+    .map_err(|err| AppError::InvalidRequest(format!("Failed to decode state vector: {}", err)))?;
+  let param = QueryCollabParams {
+    workspace_id: workspace_id.to_string(),
+    inner: QueryCollab {
+      object_id: object_id.to_string(),
+      collab_type,
+    },
+  };
+  let encode_collab = collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::User { uid }, param, true)
+    .await?;
+  let collab = collab_from_doc_state(encode_collab.doc_state.to_vec(), &object_id.to_string())?;
+  let txn = collab.transact();
+  Ok(CollabUpdatesSince {
+    update: txn.encode_state_as_update_v1(&state_vector),
+    state_vector: txn.state_vector().encode_v1(),
+  })
+}
+
+fn changed_top_level_keys(before: &serde_json::Value, after: &serde_json::Value) -> Vec<String> {
+  let empty = serde_json::Map::new();
+  let before = before.as_object().unwrap_or(&empty);
+  let after = after.as_object().unwrap_or(&empty);
+  let mut keys: Vec<String> = before
+    .keys()
+    .chain(after.keys())
+    .filter(|key| before.get(*key) != after.get(*key))
+    .cloned()
+    .collect::<HashSet<_>>()
+    .into_iter()
+    .collect();
+  keys.sort();
+  keys
+}
+
+/// Returns the state vector and the hash of the content of the collab, for the clients to check
+/// whether their copy diverged from the one on the server before resyncing it.
+pub async fn get_collab_sync_state(
+  collab_access_control_storage: &CollabAccessControlStorage,
+  uid: i64,
+  workspace_id: Uuid,
+  object_id: Uuid,
+  collab_type: CollabType,
+) -> Result<CollabSyncState, AppError> {
+  let param = QueryCollabParams {
+    workspace_id: workspace_id.to_string(),
+    inner: QueryCollab {
+      object_id: object_id.to_string(),
+      collab_type,
+    },
+  };
+  let encode_collab = collab_access_control_storage
+    .get_encode_collab(GetCollabOrigin::User { uid }, param, true)
+    .await?;
+  let collab = collab_from_doc_state(encode_collab.doc_state.to_vec(), &object_id.to_string())?;
+  let state_vector = collab.transact().state_vector().encode_v1();
+  Ok(CollabSyncState {
+    state_vector,
+    content_hash: collab_content_hash(&collab.to_json_value()),
+  })
+}
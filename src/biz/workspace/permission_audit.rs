@@ -0,0 +1,169 @@
+use app_error::AppError;
+use database::permission_audit::{
+  insert_permission_audit_event, select_permission_audit_events, PERMISSION_OBJECT_COLLAB,
+  PERMISSION_OBJECT_WORKSPACE,
+};
+use database::pg_row::AFPermissionAuditEventRow;
+use database::public_access::select_collab_workspace_id;
+use database_entity::dto::{AFAccessLevel, AFRole};
+use shared_entity::dto::workspace_dto::{
+  PermissionAuditEvent, PermissionObjectType, PermissionRole, QueryPermissionAuditLog,
+};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+const DEFAULT_PERMISSION_AUDIT_LOG_LIMIT: i64 = 100;
+
+const MAX_PERMISSION_AUDIT_LOG_LIMIT: i64 = 1000;
+
+/// Records that the role of a member of the workspace changed. `None` as the old role means the
+/// member was added, as the new role that the member was removed. The change has already been made
+/// when it's recorded, so a failure is logged instead of failing the request.
+pub async fn record_workspace_permission_change(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  actor_uid: Option<i64>,
+  target_uid: i64,
+  old_role: Option<&AFRole>,
+  new_role: Option<&AFRole>,
+) {
+  let result = insert_permission_audit_event(
+    pg_pool,
+    workspace_id,
+    actor_uid,
+    target_uid,
+    PERMISSION_OBJECT_WORKSPACE,
+    &workspace_id.to_string(),
+    old_role.map(i32::from),
+    new_role.map(i32::from),
+  )
+  .await;
+  if let Err(err) = result {
+    error!(
+      "Failed to record the role change of user {} in workspace {}: {:?}",
+      target_uid, workspace_id, err
+    );
+  }
+}
+
+/// Records that the access level of a member of the collab changed, like
+/// [record_workspace_permission_change].
+pub async fn record_collab_permission_change(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  actor_uid: Option<i64>,
+  target_uid: i64,
+  object_id: &str,
+  old_access_level: Option<AFAccessLevel>,
+  new_access_level: Option<AFAccessLevel>,
+) {
+  let result = insert_permission_audit_event(
+    pg_pool,
+    workspace_id,
+    actor_uid,
+    target_uid,
+    PERMISSION_OBJECT_COLLAB,
+    object_id,
+    old_access_level.map(i32::from),
+    new_access_level.map(i32::from),
+  )
+  .await;
+  if let Err(err) = result {
+    error!(
+      "Failed to record the access level change of user {} to collab {}: {:?}",
+      target_uid, object_id, err
+    );
+  }
+}
+
+/// Like [record_collab_permission_change], recorded in the workspace the collab belongs to.
+pub async fn record_collab_member_change(
+  pg_pool: &PgPool,
+  actor_uid: Option<i64>,
+  target_uid: i64,
+  object_id: &str,
+  old_access_level: Option<AFAccessLevel>,
+  new_access_level: Option<AFAccessLevel>,
+) {
+  match select_collab_workspace_id(pg_pool, object_id).await {
+    Ok(Some(workspace_id)) => {
+      record_collab_permission_change(
+        pg_pool,
+        &workspace_id,
+        actor_uid,
+        target_uid,
+        object_id,
+        old_access_level,
+        new_access_level,
+      )
+      .await
+    },
+    Ok(None) => {},
+    Err(err) => error!(
+      "Failed to find the workspace of collab {} to record the access level change: {:?}",
+      object_id, err
+    ),
+  }
+}
+
+pub async fn get_permission_audit_log(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  query: QueryPermissionAuditLog,
+) -> Result<Vec<PermissionAuditEvent>, AppError> {
+  if let (Some(since), Some(until)) = (query.since, query.until) {
+    if since >= until {
+      return Err(AppError::InvalidRequest(
+        "The start of the time range must be before its end".to_string(),
+      ));
+    }
+  }
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_PERMISSION_AUDIT_LOG_LIMIT)
+    .clamp(1, MAX_PERMISSION_AUDIT_LOG_LIMIT);
+  let rows = select_permission_audit_events(
+    pg_pool,
+    workspace_id,
+    query.since,
+    query.until,
+    query.target_uid,
+    limit,
+  )
+  .await?;
+  rows.into_iter().map(permission_audit_event).collect()
+}
+
+fn permission_audit_event(
+  row: AFPermissionAuditEventRow,
+) -> Result<PermissionAuditEvent, AppError> {
+  let object_type = match row.object_type.as_str() {
+    PERMISSION_OBJECT_WORKSPACE => PermissionObjectType::Workspace,
+    PERMISSION_OBJECT_COLLAB => PermissionObjectType::Collab,
+    object_type => {
+      return Err(AppError::Internal(anyhow::anyhow!(
+        "Unexpected permission object type: {}",
+        object_type
+      )))
+    },
+  };
+  let role = |value: Option<i32>| {
+    value.map(|value| match object_type {
+      PermissionObjectType::Workspace => PermissionRole::Workspace(AFRole::from(value)),
+      PermissionObjectType::Collab => PermissionRole::Collab(AFAccessLevel::from(value)),
+    })
+  };
+  Ok(PermissionAuditEvent {
+    event_id: row.event_id,
+    actor_uid: row.actor_uid,
+    actor_email: row.actor_email,
+    target_uid: row.target_uid,
+    target_email: row.target_email,
+    object_type,
+    object_id: row.object_id,
+    old_role: role(row.old_role),
+    new_role: role(row.new_role),
+    created_at: row.created_at,
+  })
+}
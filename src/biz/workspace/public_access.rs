@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_entity::CollabType;
+use collab_folder::Folder;
+use dashmap::DashMap;
+use database::collab::GetCollabOrigin;
+use database::public_access::{
+  delete_workspace_public_access, insert_workspace_public_access, select_collab_workspace_id,
+  select_workspace_public_access_enabled_at,
+};
+use database::publish::select_published_view_ids_for_workspace;
+use shared_entity::dto::workspace_dto::{CollabResponse, FolderView, WorkspacePublicAccess};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::folder_view::collab_folder_to_filtered_folder_view;
+use crate::biz::collab::ops::{get_latest_collab_encoded, get_latest_collab_folder};
+use crate::biz::workspace::view_permission::get_all_restricted_view_ids;
+
+/// How long the public access flag of a workspace is cached. A change made through another
+/// instance of the server is picked up once the cached flag expires.
+const PUBLIC_ACCESS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+const MAX_PUBLIC_FOLDER_DEPTH: u32 = 10;
+
+/// The collab types that can be read through the public access, the folder is only exposed
+/// through [get_public_workspace_folder] which hides the private spaces and the trash.
+const PUBLIC_COLLAB_TYPES: &[CollabType] = &[
+  CollabType::Document,
+  CollabType::Database,
+  CollabType::DatabaseRow,
+];
+
+/// Checks whether a workspace is publicly readable, caching the flag in memory since it's checked
+/// on every public read.
+#[derive(Clone)]
+pub struct PublicWorkspaceAccess {
+  pg_pool: PgPool,
+  cache: Arc<DashMap<Uuid, (bool, Instant)>>,
+}
+
+impl PublicWorkspaceAccess {
+  pub fn new(pg_pool: PgPool) -> Self {
+    Self {
+      pg_pool,
+      cache: Default::default(),
+    }
+  }
+
+  pub async fn is_public(&self, workspace_id: &Uuid) -> Result<bool, AppError> {
+    if let Some(entry) = self.cache.get(workspace_id) {
+      let (is_public, cached_at) = *entry;
+      if cached_at.elapsed() < PUBLIC_ACCESS_CACHE_TTL {
+        return Ok(is_public);
+      }
+    }
+    let is_public = select_workspace_public_access_enabled_at(&self.pg_pool, workspace_id)
+      .await?
+      .is_some();
+    self
+      .cache
+      .insert(*workspace_id, (is_public, Instant::now()));
+    Ok(is_public)
+  }
+
+  /// Rejects the reads of the workspaces that are not public. The workspace is reported as not
+  /// found, so that the existence of the private workspaces is not revealed.
+  pub async fn enforce_public(&self, workspace_id: &Uuid) -> Result<(), AppError> {
+    if self.is_public(workspace_id).await? {
+      Ok(())
+    } else {
+      Err(AppError::RecordNotFound(format!(
+        "Workspace {} is not public",
+        workspace_id
+      )))
+    }
+  }
+
+  pub async fn get(&self, workspace_id: &Uuid) -> Result<WorkspacePublicAccess, AppError> {
+    let enabled_at = select_workspace_public_access_enabled_at(&self.pg_pool, workspace_id).await?;
+    Ok(WorkspacePublicAccess {
+      enabled: enabled_at.is_some(),
+      enabled_at,
+    })
+  }
+
+  pub async fn set(
+    &self,
+    workspace_id: &Uuid,
+    uid: i64,
+    enabled: bool,
+  ) -> Result<WorkspacePublicAccess, AppError> {
+    if enabled {
+      insert_workspace_public_access(&self.pg_pool, workspace_id, uid).await?;
+    } else {
+      delete_workspace_public_access(&self.pg_pool, workspace_id).await?;
+    }
+    self.cache.remove(workspace_id);
+    self.get(workspace_id).await
+  }
+}
+
+/// Returns the folder of a public workspace as seen by an anonymous reader, without the private
+/// spaces, the restricted views and the trash.
+pub async fn get_public_workspace_folder(
+  collab_storage: &CollabAccessControlStorage,
+  pg_pool: &PgPool,
+  public_access: &PublicWorkspaceAccess,
+  workspace_id: Uuid,
+  depth: u32,
+  root_view_id: Option<&str>,
+) -> Result<FolderView, AppError> {
+  if depth > MAX_PUBLIC_FOLDER_DEPTH {
+    return Err(AppError::InvalidRequest(format!(
+      "Depth {} is too large (limit: {})",
+      depth, MAX_PUBLIC_FOLDER_DEPTH
+    )));
+  }
+  public_access.enforce_public(&workspace_id).await?;
+  let workspace_id_str = workspace_id.to_string();
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id_str).await?;
+  let root_view_id = root_view_id.unwrap_or(&workspace_id_str);
+  let restricted_view_ids = get_all_restricted_view_ids(pg_pool, &workspace_id).await?;
+  if root_view_id != workspace_id_str
+    && (!is_view_publicly_visible(&folder, root_view_id)
+      || restricted_view_ids.contains(root_view_id))
+  {
+    return Err(AppError::RecordNotFound(format!(
+      "View {} is not public",
+      root_view_id
+    )));
+  }
+  let publish_view_ids: HashSet<String> =
+    select_published_view_ids_for_workspace(pg_pool, workspace_id)
+      .await?
+      .into_iter()
+      .map(|id| id.to_string())
+      .collect();
+  collab_folder_to_filtered_folder_view(
+    root_view_id,
+    &folder,
+    depth,
+    &publish_view_ids,
+    &restricted_view_ids,
+    None,
+  )
+}
+
+/// Returns the content of a collab of a public workspace. The restricted views, and the views in a
+/// private space or in the trash can't be read. The database and row collabs are readable as long as they belong to the
+/// workspace, their ids are only known through the views that reference them.
+pub async fn get_public_workspace_collab(
+  collab_storage: &CollabAccessControlStorage,
+  pg_pool: &PgPool,
+  public_access: &PublicWorkspaceAccess,
+  workspace_id: Uuid,
+  object_id: &str,
+  collab_type: CollabType,
+) -> Result<CollabResponse, AppError> {
+  public_access.enforce_public(&workspace_id).await?;
+  let not_found = || AppError::RecordNotFound(format!("Collab {} is not public", object_id));
+  if !PUBLIC_COLLAB_TYPES.contains(&collab_type) {
+    return Err(not_found());
+  }
+  if select_collab_workspace_id(pg_pool, object_id).await? != Some(workspace_id) {
+    return Err(not_found());
+  }
+  if get_all_restricted_view_ids(pg_pool, &workspace_id)
+    .await?
+    .contains(object_id)
+  {
+    return Err(not_found());
+  }
+
+  let workspace_id = workspace_id.to_string();
+  let folder =
+    get_latest_collab_folder(collab_storage, GetCollabOrigin::Server, &workspace_id).await?;
+  if folder.get_view(object_id).is_some() && !is_view_publicly_visible(&folder, object_id) {
+    return Err(not_found());
+  }
+
+  let encode_collab = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id,
+    object_id,
+    collab_type,
+  )
+  .await?;
+  Ok(CollabResponse {
+    encode_collab,
+    object_id: object_id.to_string(),
+  })
+}
+
+/// A view is hidden from the public readers if the view or one of its ancestors is a private
+/// space or is in the trash.
+fn is_view_publicly_visible(folder: &Folder, view_id: &str) -> bool {
+  let hidden_view_ids: HashSet<String> = folder
+    .get_all_private_sections()
+    .into_iter()
+    .chain(folder.get_all_trash_sections())
+    .map(|section| section.id)
+    .collect();
+  let mut current_view_id = view_id.to_string();
+  // The depth of the folder is bounded, the limit guards against a cycle in the parent ids.
+  for _ in 0..64 {
+    if hidden_view_ids.contains(&current_view_id) {
+      return false;
+    }
+    match folder.get_view(&current_view_id) {
+      Some(view) if !view.parent_view_id.is_empty() && view.parent_view_id != view.id => {
+        current_view_id = view.parent_view_id.clone();
+      },
+      Some(_) => return true,
+      // The workspace itself is the parent of the top level views.
+      None => return current_view_id != view_id,
+    }
+  }
+  false
+}
@@ -13,48 +13,52 @@ use database::{
 use database_entity::dto::PatchPublishedCollab;
 use std::sync::Arc;
 
-use app_error::AppError;
+use app_error::{AppError, ErrorDetails};
 use async_trait::async_trait;
-use database_entity::dto::{PublishCollabItem, PublishInfo};
+use chrono::Utc;
+use database_entity::dto::{PublishCollabItem, PublishInfo, PublishedViewVersion};
 use shared_entity::dto::{
   publish_dto::PublishViewMetaData,
   workspace_dto::{FolderViewMinimal, PublishInfoView},
 };
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool, Postgres};
 use tracing::debug;
 use uuid::Uuid;
 
 use database::{
   file::{s3_client_impl::AwsS3BucketClientImpl, BucketClient, ResponseBlob},
   publish::{
-    delete_published_collabs, insert_or_replace_publish_collabs, select_publish_collab_meta,
-    select_published_collab_blob, select_published_collab_info,
-    select_published_collab_workspace_view_id, select_published_data_for_view_id,
-    select_published_metadata_for_view_id, select_user_is_collab_publisher_for_all_views,
-    select_workspace_publish_namespace_exists, update_non_orginal_workspace_publish_namespace,
+    delete_published_collabs, insert_or_replace_publish_collabs, rollback_published_collab,
+    select_publish_collab_meta, select_published_collab_blob, select_published_collab_info,
+    select_published_collab_versions, select_published_collab_workspace_view_id,
+    select_published_data_for_view_id, select_published_metadata_for_view_id,
+    select_user_is_collab_publisher_for_all_views, select_workspace_publish_namespace_exists,
+    update_non_orginal_workspace_publish_namespace,
   },
-  workspace::select_user_is_workspace_owner,
 };
 
 use crate::{
   api::metrics::PublishedCollabMetrics,
   biz::collab::{folder_view::to_dto_folder_view_miminal, ops::get_latest_collab_folder},
+  biz::workspace::publish_permission::can_manage_publishing,
+  biz::workspace::publish_sanitize::{sanitize_publish_items, PublishSanitizePolicy},
 };
 
-async fn check_workspace_owner_or_publisher(
+pub async fn check_workspace_owner_or_publisher(
   pg_pool: &PgPool,
   user_uuid: &Uuid,
   workspace_id: &Uuid,
   view_id: &[Uuid],
 ) -> Result<(), AppError> {
-  let is_owner = select_user_is_workspace_owner(pg_pool, user_uuid, workspace_id).await?;
-  if !is_owner {
+  let can_manage = can_manage_publishing(pg_pool, user_uuid, workspace_id).await?;
+  if !can_manage {
     let is_publisher =
       select_user_is_collab_publisher_for_all_views(pg_pool, user_uuid, workspace_id, view_id)
         .await?;
     if !is_publisher {
       return Err(AppError::UserUnAuthorized(
-        "User is not the owner of the workspace or the publisher of the document".to_string(),
+        "User can't manage the publishing of the workspace and is not the publisher of the document"
+          .to_string(),
       ));
     }
   }
@@ -300,16 +304,35 @@ pub trait PublishedCollabStore: Sync + Send + 'static {
     user_uuid: &Uuid,
     patches: &[PatchPublishedCollab],
   ) -> Result<(), AppError>;
+
+  /// Serves a prior version of the published view again, as a new version. The source collab of
+  /// the view is left untouched. Returns the new version.
+  async fn rollback_collab(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    version: i32,
+    user_uuid: &Uuid,
+  ) -> Result<i32, AppError>;
 }
 
 pub struct PublishedCollabPostgresStore {
   metrics: Arc<PublishedCollabMetrics>,
   pg_pool: PgPool,
+  sanitize_policy: PublishSanitizePolicy,
 }
 
 impl PublishedCollabPostgresStore {
-  pub fn new(metrics: Arc<PublishedCollabMetrics>, pg_pool: PgPool) -> Self {
-    Self { metrics, pg_pool }
+  pub fn new(
+    metrics: Arc<PublishedCollabMetrics>,
+    pg_pool: PgPool,
+    sanitize_policy: PublishSanitizePolicy,
+  ) -> Self {
+    Self {
+      metrics,
+      pg_pool,
+      sanitize_policy,
+    }
   }
 }
 
@@ -321,6 +344,13 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
     workspace_id: &Uuid,
     user_uuid: &Uuid,
   ) -> Result<(), AppError> {
+    let publish_items = sanitize_publish_items(
+      &self.pg_pool,
+      &self.sanitize_policy,
+      workspace_id,
+      publish_items,
+    )
+    .await?;
     for publish_item in &publish_items {
       check_collab_publish_name(publish_item.meta.publish_name.as_str())?;
       check_view_id_publish_name_conflict(
@@ -420,12 +450,26 @@ impl PublishedCollabStore for PublishedCollabPostgresStore {
   ) -> Result<(), AppError> {
     patch_collabs(&self.pg_pool, workspace_id, user_uuid, patches).await
   }
+
+  async fn rollback_collab(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    version: i32,
+    user_uuid: &Uuid,
+  ) -> Result<i32, AppError> {
+    check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+    let (new_version, _) =
+      rollback_collab(&self.pg_pool, workspace_id, view_id, version, user_uuid).await?;
+    Ok(new_version)
+  }
 }
 
 pub struct PublishedCollabS3StoreWithPostgresFallback {
   metrics: Arc<PublishedCollabMetrics>,
   pg_pool: PgPool,
   bucket_client: AwsS3BucketClientImpl,
+  sanitize_policy: PublishSanitizePolicy,
 }
 
 impl PublishedCollabS3StoreWithPostgresFallback {
@@ -433,11 +477,13 @@ impl PublishedCollabS3StoreWithPostgresFallback {
     metrics: Arc<PublishedCollabMetrics>,
     pg_pool: PgPool,
     bucket_client: AwsS3BucketClientImpl,
+    sanitize_policy: PublishSanitizePolicy,
   ) -> Self {
     Self {
       metrics,
       pg_pool,
       bucket_client,
+      sanitize_policy,
     }
   }
 }
@@ -450,6 +496,13 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
     workspace_id: &Uuid,
     user_uuid: &Uuid,
   ) -> Result<(), AppError> {
+    let publish_items = sanitize_publish_items(
+      &self.pg_pool,
+      &self.sanitize_policy,
+      workspace_id,
+      publish_items,
+    )
+    .await?;
     let publish_items_batch_size = publish_items.len() as i64;
     let mut handles: Vec<tokio::task::JoinHandle<()>> = vec![];
     for publish_item in &publish_items {
@@ -610,6 +663,66 @@ impl PublishedCollabStore for PublishedCollabS3StoreWithPostgresFallback {
   ) -> Result<(), AppError> {
     patch_collabs(&self.pg_pool, workspace_id, user_uuid, patches).await
   }
+
+  async fn rollback_collab(
+    &self,
+    workspace_id: &Uuid,
+    view_id: &Uuid,
+    version: i32,
+    user_uuid: &Uuid,
+  ) -> Result<i32, AppError> {
+    check_workspace_owner_or_publisher(&self.pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+    // The blob is served from S3 first, the rollback is only committed once the blob is written
+    // there.
+    let mut txn = self.pg_pool.begin().await?;
+    let (new_version, blob) =
+      rollback_collab(txn.as_mut(), workspace_id, view_id, version, user_uuid).await?;
+    let object_key = get_collab_s3_key(workspace_id, view_id);
+    self.bucket_client.put_blob(&object_key, &blob).await?;
+    txn.commit().await?;
+    self.metrics.incr_success_write_count(1);
+    Ok(new_version)
+  }
+}
+
+async fn rollback_collab<'a, E: Executor<'a, Database = Postgres>>(
+  executor: E,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+  version: i32,
+  user_uuid: &Uuid,
+) -> Result<(i32, Vec<u8>), AppError> {
+  rollback_published_collab(executor, workspace_id, view_id, version, user_uuid)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "The published view {} has no prior version {}",
+        view_id, version
+      ))
+    })
+}
+
+/// Returns the versions of the published view, the most recent first.
+pub async fn list_published_view_versions(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+  view_id: &Uuid,
+) -> Result<Vec<PublishedViewVersion>, AppError> {
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &[*view_id]).await?;
+  let versions: Vec<PublishedViewVersion> =
+    select_published_collab_versions(pg_pool, workspace_id, view_id)
+      .await?
+      .into_iter()
+      .map(|row| row.into())
+      .collect();
+  if versions.is_empty() {
+    return Err(AppError::RecordNotFound(format!(
+      "The view {} is not published",
+      view_id
+    )));
+  }
+  Ok(versions)
 }
 
 async fn patch_collabs(
@@ -622,11 +735,23 @@ async fn patch_collabs(
     .iter()
     .map(|patch| patch.view_id)
     .collect::<Vec<Uuid>>();
+  let now = Utc::now();
   for patch in patches {
     if let Some(new_publish_name) = patch.publish_name.as_deref() {
       check_collab_publish_name(new_publish_name)?;
       check_publish_name_already_exists(pg_pool, workspace_id, new_publish_name).await?;
     }
+    if let Some(Some(expires_at)) = patch.expires_at {
+      if expires_at <= now {
+        return Err(
+          AppError::InvalidRequest(format!(
+            "The expiry of the published view {} must be in the future",
+            patch.view_id
+          ))
+          .with_details(ErrorDetails::field("expires_at", "future")),
+        );
+      }
+    }
   }
   check_workspace_owner_or_publisher(pg_pool, user_uuid, workspace_id, &view_ids).await?;
 
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use dashmap::DashMap;
+use database::publish::split_publish_namespace;
+use database::publish_analytics::{
+  delete_published_view_daily_stats_before, select_daily_published_view_stats,
+  select_published_view_stats, upsert_published_view_daily_stats,
+};
+use shared_entity::dto::workspace_dto::{
+  DailyPublishAnalytics, PublishAnalytics, PublishedViewAnalytics,
+};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::biz::scheduler::Scheduler;
+use crate::biz::workspace::insights::visitor_hash;
+
+/// Number of days the analytics of the published views are kept for.
+pub const PUBLISH_ANALYTICS_RETENTION_DAYS: i64 = 90;
+
+/// How often the recorded views are written to the database.
+const PUBLISH_ANALYTICS_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often the analytics older than the retention are removed.
+const PUBLISH_ANALYTICS_CLEANUP_INTERVAL: std::time::Duration =
+  std::time::Duration::from_secs(60 * 60);
+
+#[derive(Default, Debug)]
+pub struct PublishedViewVisits {
+  pub view_count: i64,
+  pub visitor_hashes: HashSet<String>,
+}
+
+/// Counts the views of each published view, identified by its publish namespace and publish name,
+/// until they are flushed to the database.
+#[derive(Default)]
+pub struct PublishAnalyticsRecorder {
+  visits: DashMap<(String, String), PublishedViewVisits>,
+}
+
+impl PublishAnalyticsRecorder {
+  /// Records a view of the published view. The visitor is identified by the same salted hash as
+  /// the access log, see [crate::biz::workspace::insights::record_published_view_access].
+  pub fn record(
+    &self,
+    secret: &str,
+    publish_namespace: &str,
+    publish_name: &str,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+  ) {
+    let visitor_hash = visitor_hash(
+      secret,
+      Utc::now().date_naive(),
+      ip.unwrap_or_default(),
+      user_agent.unwrap_or_default(),
+    );
+    let mut visits = self
+      .visits
+      .entry((publish_namespace.to_string(), publish_name.to_string()))
+      .or_default();
+    visits.view_count += 1;
+    visits.visitor_hashes.insert(visitor_hash);
+  }
+
+  /// Removes and returns the views recorded since the last time.
+  pub fn take(&self) -> Vec<(String, String, PublishedViewVisits)> {
+    let keys = self
+      .visits
+      .iter()
+      .map(|entry| entry.key().clone())
+      .collect::<Vec<_>>();
+    keys
+      .into_iter()
+      .filter_map(|key| self.visits.remove(&key))
+      .map(|((publish_namespace, publish_name), visits)| (publish_namespace, publish_name, visits))
+      .collect()
+  }
+}
+
+/// Writes the views recorded since the last flush to the analytics of the current day.
+pub async fn flush_publish_analytics(
+  pg_pool: &PgPool,
+  recorder: &PublishAnalyticsRecorder,
+) -> Result<(), AppResponseError> {
+  let visits = recorder.take();
+  if visits.is_empty() {
+    return Ok(());
+  }
+
+  let mut namespaces = Vec::with_capacity(visits.len());
+  let mut sub_namespaces = Vec::with_capacity(visits.len());
+  let mut publish_names = Vec::with_capacity(visits.len());
+  let mut view_counts = Vec::with_capacity(visits.len());
+  let mut visitor_namespaces = vec![];
+  let mut visitor_sub_namespaces = vec![];
+  let mut visitor_publish_names = vec![];
+  let mut visitor_hashes = vec![];
+  for (publish_namespace, publish_name, visits) in visits {
+    let (namespace, sub_namespace) = split_publish_namespace(&publish_namespace);
+    let sub_namespace = sub_namespace.unwrap_or_default();
+    for visitor_hash in visits.visitor_hashes {
+      visitor_namespaces.push(namespace.to_string());
+      visitor_sub_namespaces.push(sub_namespace.to_string());
+      visitor_publish_names.push(publish_name.clone());
+      visitor_hashes.push(visitor_hash);
+    }
+    namespaces.push(namespace.to_string());
+    sub_namespaces.push(sub_namespace.to_string());
+    publish_names.push(publish_name);
+    view_counts.push(visits.view_count);
+  }
+  upsert_published_view_daily_stats(
+    pg_pool,
+    Utc::now().date_naive(),
+    &namespaces,
+    &sub_namespaces,
+    &publish_names,
+    &view_counts,
+    &visitor_namespaces,
+    &visitor_sub_namespaces,
+    &visitor_publish_names,
+    &visitor_hashes,
+  )
+  .await?;
+  Ok(())
+}
+
+/// Periodically writes the recorded views to the database. The views are recorded in memory by
+/// each server, so every server flushes its own records.
+pub fn spawn_publish_analytics_flush_job(pg_pool: PgPool, recorder: Arc<PublishAnalyticsRecorder>) {
+  tokio::spawn(async move {
+    let mut flush_interval = tokio::time::interval(PUBLISH_ANALYTICS_FLUSH_INTERVAL);
+    loop {
+      flush_interval.tick().await;
+      if let Err(err) = flush_publish_analytics(&pg_pool, &recorder).await {
+        error!("Failed to flush the publish analytics: {:?}", err);
+      }
+    }
+  });
+}
+
+/// Periodically removes the analytics of the published views that are older than the retention.
+pub fn register_publish_analytics_cleanup_job(scheduler: &Scheduler, pg_pool: PgPool) {
+  scheduler.register(
+    "publish_analytics_cleanup",
+    PUBLISH_ANALYTICS_CLEANUP_INTERVAL,
+    move || {
+      let pg_pool = pg_pool.clone();
+      async move {
+        let before = Utc::now().date_naive() - Duration::days(PUBLISH_ANALYTICS_RETENTION_DAYS - 1);
+        let count = delete_published_view_daily_stats_before(&pg_pool, before).await?;
+        if count > 0 {
+          info!("Removed {} expired publish analytics entries", count);
+        }
+        Ok(())
+      }
+    },
+  );
+}
+
+/// Returns the views and the unique visitors of the published views of the workspace, by view
+/// and by day, for the last `days` days within the retention.
+pub async fn get_publish_analytics(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  days: Option<i64>,
+) -> Result<PublishAnalytics, AppResponseError> {
+  let days = days
+    .unwrap_or(PUBLISH_ANALYTICS_RETENTION_DAYS)
+    .clamp(1, PUBLISH_ANALYTICS_RETENTION_DAYS);
+  let since = Utc::now().date_naive() - Duration::days(days - 1);
+
+  let views = select_published_view_stats(pg_pool, workspace_id, since)
+    .await?
+    .into_iter()
+    .map(|row| PublishedViewAnalytics {
+      view_id: row.view_id,
+      publish_name: row.publish_name,
+      view_count: row.view_count,
+      unique_visitor_count: row.unique_visitor_count,
+    })
+    .collect();
+  let days = select_daily_published_view_stats(pg_pool, workspace_id, since)
+    .await?
+    .into_iter()
+    .map(|row| DailyPublishAnalytics {
+      date: row.stat_date,
+      view_count: row.view_count,
+      unique_visitor_count: row.unique_visitor_count,
+    })
+    .collect();
+
+  Ok(PublishAnalytics {
+    retention_days: PUBLISH_ANALYTICS_RETENTION_DAYS,
+    since,
+    views,
+    days,
+  })
+}
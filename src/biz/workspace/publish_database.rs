@@ -0,0 +1,540 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use collab_database::database::DatabaseBody;
+use collab_database::entity::FieldType;
+use collab_database::rows::{DatabaseRowBody, ROW_CELLS};
+use collab_database::template::entity::CELL_DATA;
+use collab_database::workspace_database::{NoPersistenceDatabaseCollabService, WorkspaceDatabase};
+use collab_entity::{CollabType, EncodedCollab};
+use database::collab::{select_workspace_database_oid, CollabStorage, GetCollabOrigin};
+use database::publish::select_published_collab_workspace_view_id;
+use database_entity::dto::{QueryCollab, QueryCollabResult};
+use serde_json::Value;
+use shared_entity::dto::publish_dto::{
+  PublishedDatabaseFilter, PublishedDatabaseFilterCondition, PublishedDatabaseRows,
+  PublishedDatabaseSort, QueryPublishedDatabaseRows,
+};
+use shared_entity::dto::workspace_dto::ViewLayout;
+use sqlx::PgPool;
+use tracing::{debug, error};
+use yrs::{Any, Array, ArrayRef, Map, MapRef, Out};
+
+use crate::biz::collab::ops::get_latest_collab_encoded;
+use crate::biz::workspace::ops::collab_from_doc_state;
+
+const DEFAULT_PUBLISHED_ROWS_PAGE_SIZE: u32 = 50;
+const MAX_PUBLISHED_ROWS_PAGE_SIZE: u32 = 200;
+const MAX_PUBLISHED_ROWS_FILTERS: usize = 20;
+
+/// The value of a checked checkbox cell.
+const CHECKBOX_CHECKED: &str = "Yes";
+
+/// A filter evaluated against the cells of a row, built from the filters of the database view or
+/// from the filters of the query.
+#[derive(Debug)]
+enum RowFilter {
+  And(Vec<RowFilter>),
+  Or(Vec<RowFilter>),
+  Cell {
+    field_id: String,
+    condition: CellCondition,
+    content: String,
+  },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CellCondition {
+  Is,
+  IsNot,
+  Contains,
+  DoesNotContain,
+  StartsWith,
+  EndsWith,
+  IsEmpty,
+  IsNotEmpty,
+  NumberEqual,
+  NumberNotEqual,
+  GreaterThan,
+  LessThan,
+  GreaterThanOrEqual,
+  LessThanOrEqual,
+  OptionIs,
+  OptionIsNot,
+  OptionContains,
+  OptionDoesNotContain,
+  Checked,
+  Unchecked,
+}
+
+struct PublishedRow {
+  row_id: String,
+  doc_state: Vec<u8>,
+  /// The text of the cells, using the field_id as the key.
+  cells: HashMap<String, String>,
+}
+
+/// Returns a page of the rows of a published database view. The rows are read from the database
+/// in the workspace, not from the snapshot taken when the view was published, and are filtered
+/// and sorted by the server according to the view and to the query.
+pub async fn get_published_database_rows(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  publish_namespace: &str,
+  publish_name: &str,
+  query: QueryPublishedDatabaseRows,
+) -> Result<PublishedDatabaseRows, AppError> {
+  if query.filters.len() > MAX_PUBLISHED_ROWS_FILTERS {
+    return Err(AppError::InvalidRequest(format!(
+      "At most {} filters can be applied",
+      MAX_PUBLISHED_ROWS_FILTERS
+    )));
+  }
+  let offset = query.offset.unwrap_or(0) as usize;
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_PUBLISHED_ROWS_PAGE_SIZE)
+    .clamp(1, MAX_PUBLISHED_ROWS_PAGE_SIZE) as usize;
+
+  let key =
+    select_published_collab_workspace_view_id(pg_pool, publish_namespace, publish_name).await?;
+  let workspace_id = key.workspace_id.to_string();
+  let view_id = key.view_id.to_string();
+
+  let ws_db_oid = select_workspace_database_oid(pg_pool, &key.workspace_id).await?;
+  let ws_db = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id,
+    &ws_db_oid,
+    CollabType::WorkspaceDatabase,
+  )
+  .await?;
+  let ws_db_collab = collab_from_doc_state(ws_db.doc_state.to_vec(), &ws_db_oid)?;
+  let ws_db_body = WorkspaceDatabase::open(ws_db_collab).map_err(|err| {
+    AppError::Internal(anyhow!("Failed to open workspace database body: {}", err))
+  })?;
+  let db_oid = ws_db_body
+    .get_database_meta_with_view_id(&view_id)
+    .ok_or_else(|| {
+      AppError::InvalidRequest(format!("The published view {} is not a database", view_id))
+    })?
+    .database_id;
+  let db = get_latest_collab_encoded(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id,
+    &db_oid,
+    CollabType::Database,
+  )
+  .await?;
+  let db_collab = collab_from_doc_state(db.doc_state.to_vec(), &db_oid)?;
+  let db_body = DatabaseBody::from_collab(
+    &db_collab,
+    Arc::new(NoPersistenceDatabaseCollabService),
+    None,
+  )
+  .ok_or_else(|| AppError::RecordNotFound("no database body found".to_string()))?;
+  let row_ids: Vec<String> = {
+    let txn = db_collab.transact();
+    db_body
+      .views
+      .get_row_orders(&txn, &view_id)
+      .iter()
+      .map(|row_order| row_order.id.to_string())
+      .collect()
+  };
+
+  let db_json = db_collab.to_json_value();
+  let view_json = db_json["database"]["views"][&view_id].clone();
+  let field_types: HashMap<String, i64> = db_json["database"]["fields"]
+    .as_object()
+    .map(|fields| {
+      fields
+        .iter()
+        .filter_map(|(field_id, field)| Some((field_id.clone(), field["ty"].as_i64()?)))
+        .collect()
+    })
+    .unwrap_or_default();
+
+  let mut filters = view_json["filters"]
+    .as_array()
+    .map(|filters| filters.iter().filter_map(view_filter).collect::<Vec<_>>())
+    .unwrap_or_default();
+  filters.extend(query.filters.iter().map(query_filter));
+  let sorts = if query.sorts.is_empty() {
+    view_json["sorts"]
+      .as_array()
+      .map(|sorts| sorts.iter().filter_map(view_sort).collect())
+      .unwrap_or_default()
+  } else {
+    query.sorts
+  };
+
+  let queries = row_ids
+    .iter()
+    .map(|row_id| QueryCollab {
+      object_id: row_id.clone(),
+      collab_type: CollabType::DatabaseRow,
+    })
+    .collect();
+  // The rows are read on behalf of the server, the uid is not used.
+  let mut row_results = collab_storage.batch_get_collab(&0, queries, true).await;
+  let (total_count, rows) = tokio::task::spawn_blocking(move || {
+    let mut rows: Vec<PublishedRow> = row_ids
+      .into_iter()
+      .filter_map(|row_id| {
+        let result = row_results.remove(&row_id)?;
+        read_published_row(row_id, result)
+      })
+      .filter(|row| filters.iter().all(|filter| filter.matches(&row.cells)))
+      .collect();
+    if !sorts.is_empty() {
+      // The sort is stable, the rows that compare equal keep the order of the view.
+      rows.sort_by(|a, b| compare_rows(a, b, &sorts, &field_types));
+    }
+    let total_count = rows.len();
+    let page = rows
+      .into_iter()
+      .skip(offset)
+      .take(limit)
+      .collect::<Vec<_>>();
+    (total_count, page)
+  })
+  .await?;
+
+  let mut row_ids = Vec::with_capacity(rows.len());
+  let mut database_row_collabs = HashMap::with_capacity(rows.len());
+  for row in rows {
+    row_ids.push(row.row_id.clone());
+    database_row_collabs.insert(row.row_id, row.doc_state);
+  }
+  Ok(PublishedDatabaseRows {
+    view_id,
+    layout: view_layout(&view_json["layout"]),
+    layout_settings: view_json["layout_settings"].clone(),
+    group_settings: view_json["groups"].clone(),
+    total_count,
+    row_ids,
+    database_row_collabs,
+  })
+}
+
+fn read_published_row(row_id: String, result: QueryCollabResult) -> Option<PublishedRow> {
+  let encode_collab_v1 = match result {
+    QueryCollabResult::Success { encode_collab_v1 } => encode_collab_v1,
+    QueryCollabResult::Failed { error } => {
+      error!("Failed to get database row {}: {}", row_id, error);
+      return None;
+    },
+  };
+  let doc_state = match EncodedCollab::decode_from_bytes(&encode_collab_v1) {
+    Ok(encoded_collab) => encoded_collab.doc_state.to_vec(),
+    Err(err) => {
+      error!("Failed to decode database row {}: {}", row_id, err);
+      return None;
+    },
+  };
+  let mut row_collab = collab_from_doc_state(doc_state.clone(), &row_id).ok()?;
+  let row_body = match DatabaseRowBody::open(row_id.clone().into(), &mut row_collab) {
+    Ok(row_body) => row_body,
+    Err(err) => {
+      error!("Failed to open database row {}: {}", row_id, err);
+      return None;
+    },
+  };
+
+  let mut cells = HashMap::new();
+  {
+    let txn = row_collab.transact();
+    let cell_map = row_body
+      .get_data()
+      .get(&txn, ROW_CELLS)
+      .and_then(|out| out.cast::<MapRef>().ok());
+    if let Some(cell_map) = cell_map {
+      for (field_id, out) in cell_map.iter(&txn) {
+        let Ok(cell) = out.cast::<MapRef>() else {
+          continue;
+        };
+        let text = match cell.get(&txn, CELL_DATA) {
+          Some(Out::Any(any)) => any_to_text(&any),
+          Some(Out::YArray(array)) => array_to_text(&txn, &array),
+          _ => String::new(),
+        };
+        cells.insert(field_id.to_string(), text);
+      }
+    }
+  }
+  Some(PublishedRow {
+    row_id,
+    doc_state,
+    cells,
+  })
+}
+
+fn any_to_text(any: &Any) -> String {
+  match any {
+    Any::String(s) => s.to_string(),
+    Any::BigInt(n) => n.to_string(),
+    Any::Number(n) => n.to_string(),
+    Any::Bool(b) => b.to_string(),
+    Any::Array(items) => items.iter().map(any_to_text).collect::<Vec<_>>().join(","),
+    _ => String::new(),
+  }
+}
+
+fn array_to_text<T: yrs::ReadTxn>(txn: &T, array: &ArrayRef) -> String {
+  array
+    .iter(txn)
+    .filter_map(|item| match item {
+      Out::Any(any) => Some(any_to_text(&any)),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+fn view_layout(value: &Value) -> ViewLayout {
+  // The layouts of the database views: 0 for grid, 1 for board and 2 for calendar.
+  match value.as_i64() {
+    Some(1) => ViewLayout::Board,
+    Some(2) => ViewLayout::Calendar,
+    _ => ViewLayout::Grid,
+  }
+}
+
+/// Converts a filter of the database view, the filters that can't be evaluated by the server are
+/// skipped.
+fn view_filter(value: &Value) -> Option<RowFilter> {
+  // 0 for and, 1 for or, 2 for a filter on a field.
+  match value["filter_type"].as_i64().unwrap_or(2) {
+    0 => Some(RowFilter::And(view_filters(&value["children"]))),
+    1 => Some(RowFilter::Or(view_filters(&value["children"]))),
+    _ => {
+      let field_id = value["field_id"].as_str()?.to_string();
+      let field_type = value["ty"].as_i64()?;
+      let condition = view_filter_condition(field_type, value["condition"].as_i64()?)?;
+      let content = value["content"].as_str().unwrap_or_default().to_string();
+      Some(RowFilter::Cell {
+        field_id,
+        condition,
+        content,
+      })
+    },
+  }
+}
+
+fn view_filters(value: &Value) -> Vec<RowFilter> {
+  value
+    .as_array()
+    .map(|filters| filters.iter().filter_map(view_filter).collect())
+    .unwrap_or_default()
+}
+
+fn view_filter_condition(field_type: i64, condition: i64) -> Option<CellCondition> {
+  let condition = if field_type == FieldType::RichText as i64 || field_type == FieldType::URL as i64
+  {
+    match condition {
+      0 => CellCondition::Is,
+      1 => CellCondition::IsNot,
+      2 => CellCondition::Contains,
+      3 => CellCondition::DoesNotContain,
+      4 => CellCondition::StartsWith,
+      5 => CellCondition::EndsWith,
+      6 => CellCondition::IsEmpty,
+      7 => CellCondition::IsNotEmpty,
+      _ => return None,
+    }
+  } else if field_type == FieldType::Number as i64 {
+    match condition {
+      0 => CellCondition::NumberEqual,
+      1 => CellCondition::NumberNotEqual,
+      2 => CellCondition::GreaterThan,
+      3 => CellCondition::LessThan,
+      4 => CellCondition::GreaterThanOrEqual,
+      5 => CellCondition::LessThanOrEqual,
+      6 => CellCondition::IsEmpty,
+      7 => CellCondition::IsNotEmpty,
+      _ => return None,
+    }
+  } else if field_type == FieldType::SingleSelect as i64
+    || field_type == FieldType::MultiSelect as i64
+  {
+    match condition {
+      0 => CellCondition::OptionIs,
+      1 => CellCondition::OptionIsNot,
+      2 => CellCondition::OptionContains,
+      3 => CellCondition::OptionDoesNotContain,
+      4 => CellCondition::IsEmpty,
+      5 => CellCondition::IsNotEmpty,
+      _ => return None,
+    }
+  } else if field_type == FieldType::Checkbox as i64 {
+    match condition {
+      0 => CellCondition::Checked,
+      1 => CellCondition::Unchecked,
+      _ => return None,
+    }
+  } else {
+    debug!(
+      "Skip the filter on field type {} of a published database",
+      field_type
+    );
+    return None;
+  };
+  Some(condition)
+}
+
+fn query_filter(filter: &PublishedDatabaseFilter) -> RowFilter {
+  let condition = match filter.condition {
+    PublishedDatabaseFilterCondition::Is => CellCondition::Is,
+    PublishedDatabaseFilterCondition::IsNot => CellCondition::IsNot,
+    PublishedDatabaseFilterCondition::Contains => CellCondition::Contains,
+    PublishedDatabaseFilterCondition::DoesNotContain => CellCondition::DoesNotContain,
+    PublishedDatabaseFilterCondition::IsEmpty => CellCondition::IsEmpty,
+    PublishedDatabaseFilterCondition::IsNotEmpty => CellCondition::IsNotEmpty,
+    PublishedDatabaseFilterCondition::GreaterThan => CellCondition::GreaterThan,
+    PublishedDatabaseFilterCondition::LessThan => CellCondition::LessThan,
+  };
+  RowFilter::Cell {
+    field_id: filter.field_id.clone(),
+    condition,
+    content: filter.content.clone(),
+  }
+}
+
+fn view_sort(value: &Value) -> Option<PublishedDatabaseSort> {
+  Some(PublishedDatabaseSort {
+    field_id: value["field_id"].as_str()?.to_string(),
+    // 0 for ascending, 1 for descending.
+    descending: value["condition"].as_i64() == Some(1),
+  })
+}
+
+impl RowFilter {
+  fn matches(&self, cells: &HashMap<String, String>) -> bool {
+    match self {
+      RowFilter::And(filters) => filters.iter().all(|filter| filter.matches(cells)),
+      RowFilter::Or(filters) => {
+        filters.is_empty() || filters.iter().any(|filter| filter.matches(cells))
+      },
+      RowFilter::Cell {
+        field_id,
+        condition,
+        content,
+      } => {
+        let cell = cells.get(field_id).map(|s| s.as_str()).unwrap_or_default();
+        condition.matches(cell, content)
+      },
+    }
+  }
+}
+
+impl CellCondition {
+  fn matches(&self, cell: &str, content: &str) -> bool {
+    let cell_lowercase = cell.to_lowercase();
+    let content_lowercase = content.to_lowercase();
+    match self {
+      CellCondition::Is => cell_lowercase == content_lowercase,
+      CellCondition::IsNot => cell_lowercase != content_lowercase,
+      CellCondition::Contains => cell_lowercase.contains(&content_lowercase),
+      CellCondition::DoesNotContain => !cell_lowercase.contains(&content_lowercase),
+      CellCondition::StartsWith => cell_lowercase.starts_with(&content_lowercase),
+      CellCondition::EndsWith => cell_lowercase.ends_with(&content_lowercase),
+      CellCondition::IsEmpty => cell.trim().is_empty(),
+      CellCondition::IsNotEmpty => !cell.trim().is_empty(),
+      CellCondition::NumberEqual => compare_numbers(cell, content) == Some(Ordering::Equal),
+      CellCondition::NumberNotEqual => compare_numbers(cell, content) != Some(Ordering::Equal),
+      CellCondition::GreaterThan => compare_numbers(cell, content) == Some(Ordering::Greater),
+      CellCondition::LessThan => compare_numbers(cell, content) == Some(Ordering::Less),
+      CellCondition::GreaterThanOrEqual => matches!(
+        compare_numbers(cell, content),
+        Some(Ordering::Greater | Ordering::Equal)
+      ),
+      CellCondition::LessThanOrEqual => matches!(
+        compare_numbers(cell, content),
+        Some(Ordering::Less | Ordering::Equal)
+      ),
+      CellCondition::OptionIs => {
+        let mut cell_options = split_options(cell);
+        let mut content_options = split_options(content);
+        cell_options.sort_unstable();
+        content_options.sort_unstable();
+        cell_options == content_options
+      },
+      CellCondition::OptionIsNot => !CellCondition::OptionIs.matches(cell, content),
+      CellCondition::OptionContains => {
+        let cell_options = split_options(cell);
+        split_options(content)
+          .iter()
+          .any(|option| cell_options.contains(option))
+      },
+      CellCondition::OptionDoesNotContain => !CellCondition::OptionContains.matches(cell, content),
+      CellCondition::Checked => cell == CHECKBOX_CHECKED,
+      CellCondition::Unchecked => cell != CHECKBOX_CHECKED,
+    }
+  }
+}
+
+fn split_options(value: &str) -> Vec<&str> {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|option| !option.is_empty())
+    .collect()
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+  value.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+fn compare_numbers(cell: &str, content: &str) -> Option<Ordering> {
+  parse_number(cell)?.partial_cmp(&parse_number(content)?)
+}
+
+/// Compares the rows by the sorts in order. The empty cells are placed last whatever the
+/// direction of the sort.
+fn compare_rows(
+  a: &PublishedRow,
+  b: &PublishedRow,
+  sorts: &[PublishedDatabaseSort],
+  field_types: &HashMap<String, i64>,
+) -> Ordering {
+  for sort in sorts {
+    let a_cell = a
+      .cells
+      .get(&sort.field_id)
+      .map(|s| s.trim())
+      .unwrap_or_default();
+    let b_cell = b
+      .cells
+      .get(&sort.field_id)
+      .map(|s| s.trim())
+      .unwrap_or_default();
+    let ordering = match (a_cell.is_empty(), b_cell.is_empty()) {
+      (true, true) => Ordering::Equal,
+      (true, false) => return Ordering::Greater,
+      (false, true) => return Ordering::Less,
+      (false, false) => {
+        let is_number = field_types.get(&sort.field_id) == Some(&(FieldType::Number as i64));
+        let ordering = match (is_number, parse_number(a_cell), parse_number(b_cell)) {
+          (true, Some(a_number), Some(b_number)) => a_number.total_cmp(&b_number),
+          _ => a_cell.to_lowercase().cmp(&b_cell.to_lowercase()),
+        };
+        if sort.descending {
+          ordering.reverse()
+        } else {
+          ordering
+        }
+      },
+    };
+    if ordering != Ordering::Equal {
+      return ordering;
+    }
+  }
+  Ordering::Equal
+}
@@ -1171,7 +1171,7 @@ fn add_to_view_info(acc: &mut HashMap<String, PublishViewInfo>, view_infos: &[Pu
   }
 }
 
-fn to_folder_view_icon(icon: workspace_dto::ViewIcon) -> collab_folder::ViewIcon {
+pub(crate) fn to_folder_view_icon(icon: workspace_dto::ViewIcon) -> collab_folder::ViewIcon {
   collab_folder::ViewIcon {
     ty: to_folder_view_icon_type(icon.ty),
     value: icon.value,
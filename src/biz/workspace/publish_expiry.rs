@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use app_error::AppError;
+use chrono::Utc;
+use database::publish::{select_expired_published_collabs, select_published_collab_expires_at};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::biz::scheduler::Scheduler;
+use crate::biz::workspace::publish::PublishedCollabStore;
+
+/// How often the published views are checked for expiry.
+const PUBLISH_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The maximum number of published views unpublished on each check.
+const PUBLISH_EXPIRY_BATCH_SIZE: i64 = 100;
+
+/// Returns [AppError::PublishedViewExpired] if the expiry of the published view has passed. The
+/// view is served until then, and unpublished by the expiry job shortly after.
+pub async fn check_published_view_not_expired(
+  pg_pool: &PgPool,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<(), AppError> {
+  let expires_at =
+    select_published_collab_expires_at(pg_pool, publish_namespace, publish_name).await?;
+  match expires_at {
+    Some(expires_at) if expires_at <= Utc::now() => Err(AppError::PublishedViewExpired(format!(
+      "The published view {}/{} expired at {}",
+      publish_namespace, publish_name, expires_at
+    ))),
+    _ => Ok(()),
+  }
+}
+
+/// Periodically unpublishes the published views whose expiry has passed, on behalf of the users
+/// who published them.
+pub fn register_publish_expiry_job(
+  scheduler: &Scheduler,
+  pg_pool: PgPool,
+  published_collab_store: Arc<dyn PublishedCollabStore>,
+) {
+  scheduler.register("publish_expiry", PUBLISH_EXPIRY_CHECK_INTERVAL, move || {
+    let pg_pool = pg_pool.clone();
+    let published_collab_store = published_collab_store.clone();
+    async move {
+      let rows = select_expired_published_collabs(&pg_pool, PUBLISH_EXPIRY_BATCH_SIZE).await?;
+      for row in rows {
+        match published_collab_store
+          .delete_collabs(&row.workspace_id, &[row.view_id], &row.publisher_uuid)
+          .await
+        {
+          Ok(_) => info!("Unpublished expired view {}", row.view_id),
+          Err(err) => error!(
+            "Failed to unpublish expired view {}: {:?}",
+            row.view_id, err
+          ),
+        }
+      }
+      Ok(())
+    }
+  });
+}
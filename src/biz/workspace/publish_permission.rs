@@ -0,0 +1,94 @@
+use app_error::AppError;
+use database::user::select_uid_from_email;
+use database::workspace::select_workspace_member;
+use database::workspace_publisher::{
+  delete_workspace_publisher, insert_workspace_publisher, select_user_can_manage_publishing,
+  select_workspace_publishers, select_workspace_restrict_publishing,
+};
+use database_entity::dto::{AFRole, WorkspacePublisher, WorkspacePublishers};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Returns true if the user can manage the publishing of the workspace: the owners, and the
+/// members granted the publish permission.
+pub async fn can_manage_publishing(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<bool, AppError> {
+  select_user_can_manage_publishing(pg_pool, user_uuid, workspace_id).await
+}
+
+/// Required to change the publish namespace, the default published view and the sub-namespaces
+/// of the workspace.
+pub async fn enforce_can_manage_publishing(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  if !can_manage_publishing(pg_pool, user_uuid, workspace_id).await? {
+    return Err(AppError::NotEnoughPermissions);
+  }
+  Ok(())
+}
+
+/// Required to publish views. Every member can publish unless the workspace restricts the
+/// publishing, in which case the publish permission is required. The membership itself is
+/// enforced by the caller.
+pub async fn enforce_can_publish(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  if select_workspace_restrict_publishing(pg_pool, workspace_id).await? {
+    enforce_can_manage_publishing(pg_pool, user_uuid, workspace_id).await?;
+  }
+  Ok(())
+}
+
+pub async fn list_workspace_publishers(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<WorkspacePublishers, AppError> {
+  let publishers = select_workspace_publishers(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(WorkspacePublisher::from)
+    .collect();
+  Ok(WorkspacePublishers { publishers })
+}
+
+/// Grants the publish permission to a member of the workspace. The owners already have it, and
+/// the guests can't be granted it.
+pub async fn grant_publish_permission(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  granted_by: i64,
+  email: &str,
+) -> Result<(), AppError> {
+  let uid = select_uid_from_email(pg_pool, email).await?;
+  let member = select_workspace_member(pg_pool, &uid, workspace_id).await?;
+  if member.role != AFRole::Member {
+    return Err(AppError::InvalidRequest(format!(
+      "The publish permission can only be granted to members, {} is {:?}",
+      email, member.role
+    )));
+  }
+  insert_workspace_publisher(pg_pool, workspace_id, uid, granted_by).await?;
+  Ok(())
+}
+
+pub async fn revoke_publish_permission(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  email: &str,
+) -> Result<(), AppError> {
+  let uid = select_uid_from_email(pg_pool, email).await?;
+  if !delete_workspace_publisher(pg_pool, workspace_id, uid).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "{} was not granted the publish permission",
+      email
+    )));
+  }
+  Ok(())
+}
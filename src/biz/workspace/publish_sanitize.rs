@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use collab_document::blocks::Block;
+use collab_document::document::DocumentBody;
+use collab_entity::{CollabType, EncodedCollab};
+use database::publish::{
+  select_workspace_publish_namespace_embed_allowlist,
+  update_workspace_publish_namespace_embed_allowlist,
+};
+use database_entity::dto::{PublishCollabItem, PublishEmbedAllowlist};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, warn};
+use uuid::Uuid;
+use yrs::types::text::YChange;
+use yrs::types::Attrs;
+use yrs::{Any, Array, ArrayRef, Map, MapRef, Out, Text, TextRef, TransactionMut};
+
+use crate::biz::workspace::ops::collab_from_doc_state;
+use crate::biz::workspace::publish::get_workspace_publish_namespace;
+use crate::config::config::PublishedCollabSetting;
+
+/// The block types rendered as an iframe by the published page.
+const EMBED_BLOCK_TYPES: &[&str] = &["embed", "iframe", "video"];
+
+/// The URL schemes that run code when the link is opened or the content is rendered.
+const DANGEROUS_URL_SCHEMES: &[&str] = &["javascript:", "vbscript:", "file:"];
+
+const MAX_EMBED_ALLOWLIST_HOSTS: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct PublishSanitizePolicy {
+  pub enabled: bool,
+  /// Hosts of the embeds trusted for every publish namespace.
+  pub trusted_embed_hosts: Vec<String>,
+}
+
+impl From<&PublishedCollabSetting> for PublishSanitizePolicy {
+  fn from(setting: &PublishedCollabSetting) -> Self {
+    Self {
+      enabled: setting.sanitize_content,
+      trusted_embed_hosts: setting.trusted_embed_hosts.clone(),
+    }
+  }
+}
+
+/// Strips the dangerous content from the published items before they are stored:
+/// - the URLs with a scripting scheme, e.g. `javascript:`, from the metadata, the block data and
+///   the link attributes of the texts,
+/// - the embed blocks whose host is not trusted by the server or by the allowlist of the publish
+///   namespace of the workspace.
+///
+/// Only the documents are sanitized, the data that isn't an encoded collab is stored as is.
+pub async fn sanitize_publish_items(
+  pg_pool: &PgPool,
+  policy: &PublishSanitizePolicy,
+  workspace_id: &Uuid,
+  publish_items: Vec<PublishCollabItem<Value, Vec<u8>>>,
+) -> Result<Vec<PublishCollabItem<Value, Vec<u8>>>, AppError> {
+  if !policy.enabled {
+    return Ok(publish_items);
+  }
+
+  let mut trusted_embed_hosts = policy.trusted_embed_hosts.clone();
+  match get_workspace_publish_namespace(pg_pool, workspace_id).await {
+    Ok(namespace) => trusted_embed_hosts.extend(
+      select_workspace_publish_namespace_embed_allowlist(pg_pool, workspace_id, &namespace).await?,
+    ),
+    Err(AppError::RecordNotFound(_)) => {},
+    Err(err) => return Err(err),
+  }
+
+  tokio::task::spawn_blocking(move || {
+    publish_items
+      .into_iter()
+      .map(|mut item| {
+        let count = sanitize_json(&mut item.meta.metadata);
+        if count > 0 {
+          debug!(
+            "Stripped {} values from the publish metadata of view {}",
+            count, item.meta.view_id
+          );
+        }
+        item.data = sanitize_document_data(&item.meta.view_id, item.data, &trusted_embed_hosts)?;
+        Ok(item)
+      })
+      .collect::<Result<Vec<_>, AppError>>()
+  })
+  .await?
+}
+
+pub async fn get_publish_embed_allowlist(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<PublishEmbedAllowlist, AppError> {
+  let namespace = get_workspace_publish_namespace(pg_pool, workspace_id).await?;
+  let hosts =
+    select_workspace_publish_namespace_embed_allowlist(pg_pool, workspace_id, &namespace).await?;
+  Ok(PublishEmbedAllowlist { namespace, hosts })
+}
+
+/// Replaces the allowlist of the publish namespace in use by the workspace. The hosts apply to
+/// the views published afterward.
+pub async fn set_publish_embed_allowlist(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  hosts: Vec<String>,
+) -> Result<PublishEmbedAllowlist, AppError> {
+  if hosts.len() > MAX_EMBED_ALLOWLIST_HOSTS {
+    return Err(AppError::InvalidRequest(format!(
+      "The allowlist can have at most {} hosts",
+      MAX_EMBED_ALLOWLIST_HOSTS
+    )));
+  }
+  let mut normalized_hosts = Vec::with_capacity(hosts.len());
+  for host in hosts {
+    let host = host.trim().trim_end_matches('.').to_lowercase();
+    if host.is_empty()
+      || !host
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+    {
+      return Err(AppError::InvalidRequest(format!(
+        "Invalid embed host: {}",
+        host
+      )));
+    }
+    if !normalized_hosts.contains(&host) {
+      normalized_hosts.push(host);
+    }
+  }
+
+  let namespace = get_workspace_publish_namespace(pg_pool, workspace_id).await?;
+  update_workspace_publish_namespace_embed_allowlist(
+    pg_pool,
+    workspace_id,
+    &namespace,
+    &normalized_hosts,
+  )
+  .await?;
+  Ok(PublishEmbedAllowlist {
+    namespace,
+    hosts: normalized_hosts,
+  })
+}
+
+fn sanitize_document_data(
+  view_id: &Uuid,
+  data: Vec<u8>,
+  trusted_embed_hosts: &[String],
+) -> Result<Vec<u8>, AppError> {
+  let encoded_collab = match EncodedCollab::decode_from_bytes(&data) {
+    Ok(encoded_collab) => encoded_collab,
+    Err(_) => return Ok(data),
+  };
+  let mut collab = collab_from_doc_state(encoded_collab.doc_state.to_vec(), &view_id.to_string())?;
+  let body = match DocumentBody::from_collab(&collab) {
+    Some(body) => body,
+    None => return Ok(data),
+  };
+
+  let mut count = 0;
+  {
+    let mut txn = collab.context.transact_mut();
+    let document_data = body
+      .get_document_data(&txn)
+      .map_err(|err| AppError::InvalidRequest(format!("Invalid published document: {}", err)))?;
+    for (block_id, block) in document_data.blocks {
+      if is_untrusted_embed(&block, trusted_embed_hosts) {
+        match body.delete_block(&mut txn, &block_id) {
+          Ok(_) => count += 1,
+          Err(err) => warn!("Failed to strip embed block {}: {}", block_id, err),
+        }
+        continue;
+      }
+
+      let mut block_data = Value::Object(block.data.into_iter().collect());
+      let n = sanitize_json(&mut block_data);
+      if n > 0 {
+        let block_data = match block_data {
+          Value::Object(map) => map.into_iter().collect::<HashMap<_, _>>(),
+          _ => HashMap::new(),
+        };
+        body
+          .update_block(&mut txn, &block_id, block_data)
+          .map_err(|err| AppError::Internal(anyhow!("Failed to sanitize block: {}", err)))?;
+        count += n;
+      }
+    }
+    count += sanitize_map(&mut txn, &collab.data);
+  }
+
+  if count == 0 {
+    return Ok(data);
+  }
+  debug!(
+    "Stripped {} values from the published document {}",
+    count, view_id
+  );
+  let encoded_collab_v1 = collab
+    .encode_collab_v1(|collab| CollabType::Document.validate_require_data(collab))
+    .map_err(|err| AppError::Internal(anyhow!("Failed to encode collab: {}", err)))?
+    .encode_to_bytes()?;
+  Ok(encoded_collab_v1)
+}
+
+fn is_untrusted_embed(block: &Block, trusted_embed_hosts: &[String]) -> bool {
+  if !EMBED_BLOCK_TYPES.contains(&block.ty.as_str()) {
+    return false;
+  }
+  match block.data.get("url").and_then(Value::as_str) {
+    None => false,
+    Some(url) if url.is_empty() => false,
+    Some(url) => !is_trusted_url(url, trusted_embed_hosts),
+  }
+}
+
+/// The subdomains of a trusted host are trusted too.
+fn is_trusted_url(url: &str, trusted_embed_hosts: &[String]) -> bool {
+  let url = match url::Url::parse(url) {
+    Ok(url) => url,
+    Err(_) => return false,
+  };
+  if url.scheme() != "https" {
+    return false;
+  }
+  let host = match url.host_str() {
+    Some(host) => host.to_lowercase(),
+    None => return false,
+  };
+  trusted_embed_hosts.iter().any(|trusted| {
+    host == *trusted
+      || host
+        .strip_suffix(trusted.as_str())
+        .map_or(false, |subdomain| subdomain.ends_with('.'))
+  })
+}
+
+fn is_dangerous_url(value: &str) -> bool {
+  // Browsers ignore the whitespaces and the control characters within the scheme.
+  let scheme = value
+    .chars()
+    .filter(|c| !c.is_whitespace() && !c.is_control())
+    .take(16)
+    .collect::<String>()
+    .to_lowercase();
+  if DANGEROUS_URL_SCHEMES
+    .iter()
+    .any(|dangerous| scheme.starts_with(dangerous))
+  {
+    return true;
+  }
+  // Images are the only data URLs rendered by the published page, except SVG that can run
+  // scripts.
+  scheme.starts_with("data:")
+    && (!scheme.starts_with("data:image/") || scheme.starts_with("data:image/svg"))
+}
+
+/// Empties the strings that are dangerous URLs, returns the number of strings emptied.
+fn sanitize_json(value: &mut Value) -> usize {
+  match value {
+    Value::String(s) if is_dangerous_url(s) => {
+      s.clear();
+      1
+    },
+    Value::Array(items) => items.iter_mut().map(sanitize_json).sum(),
+    Value::Object(map) => map.values_mut().map(sanitize_json).sum(),
+    _ => 0,
+  }
+}
+
+fn sanitize_out(txn: &mut TransactionMut, value: Out) -> usize {
+  match value {
+    Out::YText(text) => sanitize_text(txn, &text),
+    Out::YMap(map) => sanitize_map(txn, &map),
+    Out::YArray(array) => sanitize_array(txn, &array),
+    _ => 0,
+  }
+}
+
+fn sanitize_map(txn: &mut TransactionMut, map: &MapRef) -> usize {
+  let entries = map.iter(&*txn).map(|(_, value)| value).collect::<Vec<_>>();
+  entries
+    .into_iter()
+    .map(|value| sanitize_out(txn, value))
+    .sum()
+}
+
+fn sanitize_array(txn: &mut TransactionMut, array: &ArrayRef) -> usize {
+  let items = array.iter(&*txn).collect::<Vec<_>>();
+  items.into_iter().map(|item| sanitize_out(txn, item)).sum()
+}
+
+/// Removes the formatting attributes that are dangerous URLs, e.g. the `href` of a link, by
+/// rewriting the text chunk by chunk.
+fn sanitize_text(txn: &mut TransactionMut, text: &TextRef) -> usize {
+  let chunks = text.diff(&*txn, YChange::identity);
+  let is_dangerous = |attributes: &Option<Box<Attrs>>| {
+    attributes.as_ref().map_or(false, |attributes| {
+      attributes.values().any(|value| match value {
+        Any::String(s) => is_dangerous_url(s),
+        _ => false,
+      })
+    })
+  };
+  if !chunks.iter().any(|chunk| is_dangerous(&chunk.attributes)) {
+    return 0;
+  }
+
+  let len = text.len(&*txn);
+  text.remove_range(txn, 0, len);
+  let mut count = 0;
+  for chunk in chunks {
+    let index = text.len(&*txn);
+    let attributes = chunk.attributes.map(|attributes| {
+      let mut attributes = *attributes;
+      attributes.retain(|_, value| match value {
+        Any::String(s) if is_dangerous_url(s) => {
+          count += 1;
+          false
+        },
+        _ => true,
+      });
+      attributes
+    });
+    match chunk.insert {
+      Out::Any(Any::String(s)) => match attributes {
+        Some(attributes) => text.insert_with_attributes(txn, index, &s, attributes),
+        None => text.insert(txn, index, &s),
+      },
+      Out::Any(embed) => match attributes {
+        Some(attributes) => {
+          text.insert_embed_with_attributes(txn, index, embed, attributes);
+        },
+        None => {
+          text.insert_embed(txn, index, embed);
+        },
+      },
+      // The collabs don't embed shared types in texts.
+      _ => {},
+    }
+  }
+  count
+}
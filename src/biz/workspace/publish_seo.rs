@@ -0,0 +1,114 @@
+use app_error::AppError;
+use database::publish::{select_published_view_seo, update_published_view_seo};
+use database_entity::dto::{PatchPublishedViewSeo, PublishedViewSeo};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::workspace::publish::check_workspace_owner_or_publisher;
+
+const MAX_SEO_TITLE_LENGTH: usize = 256;
+const MAX_SEO_DESCRIPTION_LENGTH: usize = 1024;
+const MAX_SEO_IMAGE_URL_LENGTH: usize = 2048;
+
+/// Returns the SEO metadata of the published view. The overrides set by the publisher take
+/// precedence, otherwise the title is the name of the view and the image is its cover, if the
+/// cover is an image url.
+pub async fn get_published_view_seo(
+  pg_pool: &PgPool,
+  appflowy_web_url: &str,
+  publish_namespace: &str,
+  publish_name: &str,
+) -> Result<PublishedViewSeo, AppError> {
+  let row = select_published_view_seo(pg_pool, publish_namespace, publish_name).await?;
+  let view = &row.metadata["view"];
+  let title = row
+    .seo_title
+    .or_else(|| {
+      view["name"]
+        .as_str()
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| name.to_string())
+    })
+    .unwrap_or_else(|| publish_name.to_string());
+  let image_url = row
+    .seo_image_url
+    .or_else(|| view["extra"].as_str().and_then(cover_image_url));
+  let canonical_url = format!(
+    "{}/{}/{}",
+    appflowy_web_url.trim_end_matches('/'),
+    publish_namespace,
+    publish_name
+  );
+  Ok(PublishedViewSeo {
+    title,
+    description: row.seo_description,
+    image_url,
+    canonical_url,
+  })
+}
+
+/// Overrides the SEO metadata of the published view. Only the workspace owner, the users who can
+/// manage the publishing of the workspace and the publisher of the view can change it.
+pub async fn patch_published_view_seo(
+  pg_pool: &PgPool,
+  user_uuid: &Uuid,
+  publish_namespace: &str,
+  publish_name: &str,
+  patch: &PatchPublishedViewSeo,
+) -> Result<(), AppError> {
+  check_seo_field("title", &patch.title, MAX_SEO_TITLE_LENGTH)?;
+  check_seo_field(
+    "description",
+    &patch.description,
+    MAX_SEO_DESCRIPTION_LENGTH,
+  )?;
+  check_seo_field("image_url", &patch.image_url, MAX_SEO_IMAGE_URL_LENGTH)?;
+  if let Some(Some(image_url)) = &patch.image_url {
+    if !is_http_url(image_url) {
+      return Err(AppError::InvalidRequest(
+        "The image url must be an http or https url".to_string(),
+      ));
+    }
+  }
+
+  let row = select_published_view_seo(pg_pool, publish_namespace, publish_name).await?;
+  check_workspace_owner_or_publisher(pg_pool, user_uuid, &row.workspace_id, &[row.view_id]).await?;
+  update_published_view_seo(pg_pool, &row.workspace_id, &row.view_id, patch).await?;
+  Ok(())
+}
+
+fn check_seo_field(
+  field: &str,
+  value: &Option<Option<String>>,
+  max_length: usize,
+) -> Result<(), AppError> {
+  if let Some(Some(value)) = value {
+    if value.trim().is_empty() {
+      return Err(AppError::InvalidRequest(format!(
+        "The {} is empty, use null to remove the override",
+        field
+      )));
+    }
+    if value.chars().count() > max_length {
+      return Err(AppError::InvalidRequest(format!(
+        "The {} is longer than {} characters",
+        field, max_length
+      )));
+    }
+  }
+  Ok(())
+}
+
+/// The cover of a view is stored in its extra, e.g. `{"cover":{"type":"custom","value":"..."}}`.
+/// Only the covers that are image urls can be used, not the built-in colors and gradients.
+fn cover_image_url(extra: &str) -> Option<String> {
+  let extra: serde_json::Value = serde_json::from_str(extra).ok()?;
+  extra["cover"]["value"]
+    .as_str()
+    .filter(|value| is_http_url(value))
+    .map(|value| value.to_string())
+}
+
+fn is_http_url(url: &str) -> bool {
+  url.starts_with("https://") || url.starts_with("http://")
+}
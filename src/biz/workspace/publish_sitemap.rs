@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use chrono::SecondsFormat;
+use database::collab::GetCollabOrigin;
+use database::pg_row::AFPublishedSitemapEntryRow;
+use database::publish::{
+  select_published_sitemap_entries, select_workspace_id_for_publish_namespace,
+  split_publish_namespace,
+};
+use shared_entity::dto::workspace_dto::PublishedView;
+use sqlx::PgPool;
+
+use crate::biz::collab::ops::get_latest_collab_folder;
+use crate::biz::collab::publish_outline::collab_folder_to_published_outline;
+
+/// The maximum number of URLs of a sitemap, as defined by the sitemap protocol.
+const MAX_SITEMAP_URLS: usize = 50_000;
+
+/// Returns the sitemap of the views published in the namespace, in the order of the publish
+/// outline. The views are linked to their page on AppFlowy Web.
+pub async fn get_published_sitemap(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  appflowy_web_url: &str,
+  publish_namespace: &str,
+) -> Result<String, AppError> {
+  let (namespace, sub_namespace) = split_publish_namespace(publish_namespace);
+  let workspace_id = select_workspace_id_for_publish_namespace(pg_pool, namespace).await?;
+  let entries: HashMap<String, AFPublishedSitemapEntryRow> =
+    select_published_sitemap_entries(pg_pool, workspace_id, sub_namespace)
+      .await?
+      .into_iter()
+      .map(|entry| (entry.view_id.to_string(), entry))
+      .collect();
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  let publish_view_ids: HashSet<String> = entries.keys().cloned().collect();
+  let outline =
+    collab_folder_to_published_outline(&workspace_id.to_string(), &folder, &publish_view_ids)?;
+
+  let base_url = format!(
+    "{}/{}",
+    appflowy_web_url.trim_end_matches('/'),
+    publish_namespace
+  );
+  let mut sitemap = String::from(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+  );
+  let mut url_count = 0;
+  write_sitemap_urls(&mut sitemap, &mut url_count, &outline, &entries, &base_url);
+  sitemap.push_str("</urlset>\n");
+  Ok(sitemap)
+}
+
+fn write_sitemap_urls(
+  sitemap: &mut String,
+  url_count: &mut usize,
+  view: &PublishedView,
+  entries: &HashMap<String, AFPublishedSitemapEntryRow>,
+  base_url: &str,
+) {
+  if *url_count >= MAX_SITEMAP_URLS {
+    return;
+  }
+  if let Some(entry) = entries.get(&view.view_id).filter(|_| view.is_published) {
+    let _ = write!(
+      sitemap,
+      "  <url>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </url>\n",
+      escape_xml(&format!("{}/{}", base_url, entry.publish_name)),
+      entry.updated_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    *url_count += 1;
+  }
+  for child in &view.children {
+    write_sitemap_urls(sitemap, url_count, child, entries, base_url);
+  }
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
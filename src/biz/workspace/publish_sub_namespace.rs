@@ -0,0 +1,197 @@
+use anyhow::Context;
+use app_error::{AppError, ErrorDetails};
+use database::pg_row::AFPublishSubNamespaceRow;
+use database::publish::{
+  delete_publish_sub_namespace, insert_publish_sub_namespace, select_publish_sub_namespace_count,
+  select_publish_sub_namespaces, update_publish_sub_namespace,
+  update_published_collabs_sub_namespace, update_published_collabs_sub_namespace_set_null,
+};
+use database_entity::dto::{
+  CreatePublishSubNamespace, PublishSubNamespace, UpdatePublishSubNamespace,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::workspace::publish::get_workspace_publish_namespace;
+
+const MAX_SUB_NAMESPACE_LENGTH: usize = 64;
+
+const MAX_SUB_NAMESPACES_PER_WORKSPACE: i64 = 50;
+
+pub async fn list_publish_sub_namespaces(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<PublishSubNamespace>, AppError> {
+  let namespace = get_workspace_publish_namespace(pg_pool, workspace_id).await?;
+  let rows = select_publish_sub_namespaces(pg_pool, workspace_id).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| to_publish_sub_namespace(&namespace, row))
+      .collect(),
+  )
+}
+
+/// Creates a sub-namespace under the publish namespace of the workspace. The views are published
+/// in it by moving them with [add_publish_sub_namespace_views].
+pub async fn create_publish_sub_namespace(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreatePublishSubNamespace,
+) -> Result<PublishSubNamespace, AppError> {
+  check_sub_namespace(&params.sub_namespace)?;
+  let sub_namespace_count = select_publish_sub_namespace_count(pg_pool, workspace_id).await?;
+  if sub_namespace_count >= MAX_SUB_NAMESPACES_PER_WORKSPACE {
+    return Err(
+      AppError::InvalidRequest(format!(
+        "A workspace can have at most {} publish sub-namespaces",
+        MAX_SUB_NAMESPACES_PER_WORKSPACE
+      ))
+      .with_details(ErrorDetails::limit(
+        MAX_SUB_NAMESPACES_PER_WORKSPACE,
+        Some(sub_namespace_count),
+      )),
+    );
+  }
+  if !insert_publish_sub_namespace(
+    pg_pool,
+    workspace_id,
+    &params.sub_namespace,
+    params.title.trim(),
+  )
+  .await?
+  {
+    return Err(AppError::PublishNamespaceAlreadyTaken(format!(
+      "publish sub-namespace {} already exists",
+      params.sub_namespace
+    )));
+  }
+  get_publish_sub_namespace(pg_pool, workspace_id, &params.sub_namespace).await
+}
+
+pub async fn update_publish_sub_namespace_settings(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+  params: UpdatePublishSubNamespace,
+) -> Result<PublishSubNamespace, AppError> {
+  let current = get_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await?;
+  if let Some(default_view_id) = &params.default_view_id {
+    if !current.view_ids.contains(default_view_id) {
+      return Err(AppError::InvalidRequest(format!(
+        "View {} is not published in the sub-namespace {}",
+        default_view_id, sub_namespace
+      )));
+    }
+  }
+  update_publish_sub_namespace(
+    pg_pool,
+    workspace_id,
+    sub_namespace,
+    params.title.trim(),
+    params.default_view_id.as_ref(),
+  )
+  .await?;
+  get_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await
+}
+
+/// The views published in the sub-namespace are moved back to the publish namespace of the
+/// workspace.
+pub async fn remove_publish_sub_namespace(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+) -> Result<(), AppError> {
+  if !delete_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Publish sub-namespace {}",
+      sub_namespace
+    )));
+  }
+  Ok(())
+}
+
+/// Moves the published views to the sub-namespace, from the namespace of the workspace or from
+/// another sub-namespace. All the views must be published in the workspace.
+pub async fn add_publish_sub_namespace_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+  view_ids: Vec<Uuid>,
+) -> Result<PublishSubNamespace, AppError> {
+  let view_ids = dedup_view_ids(view_ids);
+  get_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await?;
+  let mut txn = pg_pool
+    .begin()
+    .await
+    .context("Begin transaction to move views to publish sub-namespace")?;
+  let moved =
+    update_published_collabs_sub_namespace(txn.as_mut(), workspace_id, &view_ids, sub_namespace)
+      .await?;
+  if moved != view_ids.len() as u64 {
+    return Err(AppError::InvalidRequest(
+      "Only the views published in the workspace can be moved to a sub-namespace".to_string(),
+    ));
+  }
+  txn.commit().await?;
+  get_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await
+}
+
+/// Moves the views of the sub-namespace back to the publish namespace of the workspace. The views
+/// that aren't published in the sub-namespace are ignored.
+pub async fn remove_publish_sub_namespace_views(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+  view_ids: Vec<Uuid>,
+) -> Result<PublishSubNamespace, AppError> {
+  let view_ids = dedup_view_ids(view_ids);
+  update_published_collabs_sub_namespace_set_null(pg_pool, workspace_id, &view_ids, sub_namespace)
+    .await?;
+  get_publish_sub_namespace(pg_pool, workspace_id, sub_namespace).await
+}
+
+async fn get_publish_sub_namespace(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  sub_namespace: &str,
+) -> Result<PublishSubNamespace, AppError> {
+  list_publish_sub_namespaces(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .find(|item| item.sub_namespace == sub_namespace)
+    .ok_or_else(|| AppError::RecordNotFound(format!("Publish sub-namespace {}", sub_namespace)))
+}
+
+fn to_publish_sub_namespace(namespace: &str, row: AFPublishSubNamespaceRow) -> PublishSubNamespace {
+  PublishSubNamespace {
+    namespace: format!("{}/{}", namespace, row.sub_namespace),
+    sub_namespace: row.sub_namespace,
+    title: row.title,
+    default_view_id: row.default_view_id,
+    view_ids: row.view_ids,
+    created_at: row.created_at,
+  }
+}
+
+fn dedup_view_ids(mut view_ids: Vec<Uuid>) -> Vec<Uuid> {
+  view_ids.sort();
+  view_ids.dedup();
+  view_ids
+}
+
+/// The sub-namespace is a single segment of the URL, like the namespace of the workspace.
+fn check_sub_namespace(sub_namespace: &str) -> Result<(), AppError> {
+  if sub_namespace.is_empty() || sub_namespace.len() > MAX_SUB_NAMESPACE_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The publish sub-namespace must have between 1 and {} characters",
+      MAX_SUB_NAMESPACE_LENGTH
+    )));
+  }
+  for c in sub_namespace.chars() {
+    if !c.is_alphanumeric() && c != '-' {
+      return Err(AppError::CustomNamespaceInvalidCharacter { character: c });
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use app_error::AppError;
+use database::file::s3_client_impl::S3BucketStorage;
+use database::residency::{
+  delete_workspace_in_region, provision_workspace_in_region, update_workspace_residency,
+  PgPoolRouter,
+};
+use sqlx::{Postgres, Transaction};
+use tracing::error;
+use uuid::Uuid;
+
+/// Routes the storage of a workspace, the collab database and the blob bucket, to the region
+/// chosen when the workspace was created.
+#[derive(Clone)]
+pub struct StorageRouter {
+  pg_pool_router: PgPoolRouter,
+  default_bucket_storage: Arc<S3BucketStorage>,
+  regional_bucket_storages: Arc<HashMap<String, Arc<S3BucketStorage>>>,
+}
+
+impl StorageRouter {
+  pub fn new(
+    pg_pool_router: PgPoolRouter,
+    default_bucket_storage: Arc<S3BucketStorage>,
+    regional_bucket_storages: HashMap<String, Arc<S3BucketStorage>>,
+  ) -> Self {
+    Self {
+      pg_pool_router,
+      default_bucket_storage,
+      regional_bucket_storages: Arc::new(regional_bucket_storages),
+    }
+  }
+
+  pub fn pg_pool_router(&self) -> &PgPoolRouter {
+    &self.pg_pool_router
+  }
+
+  pub async fn bucket_storage(
+    &self,
+    workspace_id: &Uuid,
+  ) -> Result<Arc<S3BucketStorage>, AppError> {
+    match self.pg_pool_router.residency(workspace_id).await? {
+      None => Ok(self.default_bucket_storage.clone()),
+      Some(residency) => self
+        .regional_bucket_storages
+        .get(&residency)
+        .cloned()
+        .ok_or_else(|| {
+          AppError::Internal(anyhow::anyhow!(
+            "Bucket for residency:{} of workspace:{} is not configured",
+            residency,
+            workspace_id
+          ))
+        }),
+    }
+  }
+
+  pub fn validate_residency(&self, residency: &str) -> Result<(), AppError> {
+    if self.pg_pool_router.is_supported_residency(residency)
+      && self.regional_bucket_storages.contains_key(residency)
+    {
+      return Ok(());
+    }
+
+    let mut supported_residencies = self.pg_pool_router.supported_residencies();
+    supported_residencies.sort();
+    Err(AppError::InvalidRequest(format!(
+      "Unsupported residency: {}, supported residencies: [{}]",
+      residency,
+      supported_residencies.join(", ")
+    )))
+  }
+
+  /// Sets the residency of a workspace that is being created with the given transaction, and
+  /// prepares the regional database to store the data of the workspace.
+  ///
+  /// The regional database is prepared before the transaction is committed, since the collabs
+  /// written while creating the workspace are stored there. [Self::unassign_residency] must be
+  /// called if the workspace isn't created.
+  pub async fn assign_residency(
+    &self,
+    txn: &mut Transaction<'_, Postgres>,
+    workspace_id: &Uuid,
+    residency: Option<&str>,
+  ) -> Result<(), AppError> {
+    let residency = match residency {
+      None => {
+        self
+          .pg_pool_router
+          .cache_residency(*workspace_id, None)
+          .await;
+        return Ok(());
+      },
+      Some(residency) => residency,
+    };
+
+    self.validate_residency(residency)?;
+    update_workspace_residency(txn.as_mut(), workspace_id, residency).await?;
+    if let Some(regional_pool) = self.pg_pool_router.pg_pool_for_residency(residency) {
+      provision_workspace_in_region(txn, regional_pool, workspace_id).await?;
+    }
+    self
+      .pg_pool_router
+      .cache_residency(*workspace_id, Some(residency.to_string()))
+      .await;
+    Ok(())
+  }
+
+  /// Undoes [Self::assign_residency] once the creation of the workspace failed. The failure is
+  /// logged, the caller returns the error of the creation.
+  pub async fn unassign_residency(&self, workspace_id: &Uuid, residency: Option<&str>) {
+    self
+      .pg_pool_router
+      .remove_cached_residency(workspace_id)
+      .await;
+    let regional_pool = match residency.and_then(|r| self.pg_pool_router.pg_pool_for_residency(r)) {
+      Some(regional_pool) => regional_pool,
+      None => return,
+    };
+    if let Err(err) = delete_workspace_in_region(regional_pool, workspace_id).await {
+      error!(
+        "Failed to delete the workspace:{} from the database of residency:{:?}: {:?}",
+        workspace_id, residency, err
+      );
+    }
+  }
+}
@@ -0,0 +1,455 @@
+use anyhow::anyhow;
+use app_error::AppError;
+use database::pg_row::AFWorkspaceSamlConfigRow;
+use database::saml::{
+  delete_workspace_saml_config as delete_workspace_saml_config_row, insert_workspace_saml_user,
+  is_workspace_saml_user, select_workspace_saml_config, upsert_workspace_saml_config,
+};
+use database::user::select_uid_from_email;
+use database::workspace::{select_user_role, upsert_workspace_member_with_txn};
+use database_entity::dto::AFRole;
+use gotrue::params::{GenerateLinkParams, GenerateLinkType};
+use samael::metadata::{EntityDescriptor, HTTP_REDIRECT_BINDING};
+use samael::schema::Assertion;
+use samael::service_provider::{ServiceProvider, ServiceProviderBuilder};
+use samael::traits::ToXml;
+use shared_entity::dto::workspace_dto::{UpdateWorkspaceSamlConfigParams, WorkspaceSamlConfig};
+use sqlx::PgPool;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::biz::user::user_verify::create_user_if_not_exist;
+use crate::biz::workspace::domain::is_email_domain_verified;
+use crate::biz::workspace::permission_audit::record_workspace_permission_change;
+use crate::config::config::SamlSetting;
+use crate::state::AppState;
+
+const MAX_IDP_METADATA_LEN: usize = 256 * 1024;
+
+/// The assertions are rejected once their id was seen, for as long as an assertion is valid.
+const SAML_ASSERTION_REPLAY_TTL_SECS: u64 = 60 * 60;
+
+/// How long the user has to sign in at the identity provider once the sign in started.
+const SAML_REQUEST_TTL_SECS: u64 = 10 * 60;
+
+/// The URLs AppFlowy is known by as the service provider of a workspace.
+struct ServiceProviderUrls {
+  entity_id: String,
+  acs_url: String,
+  login_url: String,
+}
+
+impl ServiceProviderUrls {
+  fn new(setting: &SamlSetting, workspace_id: &Uuid) -> Self {
+    let base_url = format!("{}/api/sso/saml/{}", setting.base_url, workspace_id);
+    Self {
+      entity_id: format!("{}/metadata", base_url),
+      acs_url: format!("{}/acs", base_url),
+      login_url: format!("{}/login", base_url),
+    }
+  }
+}
+
+pub async fn get_workspace_saml_config(
+  pg_pool: &PgPool,
+  setting: &SamlSetting,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceSamlConfig, AppError> {
+  let row = select_workspace_saml_config(pg_pool, workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Workspace {} has no SAML configuration",
+        workspace_id
+      ))
+    })?;
+  Ok(saml_config_from_row(setting, row))
+}
+
+/// Sets the identity provider of the workspace. Its metadata is checked to have a sign on URL, and
+/// the certificates the assertions are verified with.
+pub async fn update_workspace_saml_config(
+  pg_pool: &PgPool,
+  setting: &SamlSetting,
+  workspace_id: &Uuid,
+  params: UpdateWorkspaceSamlConfigParams,
+) -> Result<WorkspaceSamlConfig, AppError> {
+  let idp_metadata_xml = params.idp_metadata_xml.trim();
+  if idp_metadata_xml.is_empty() || idp_metadata_xml.len() > MAX_IDP_METADATA_LEN {
+    return Err(AppError::InvalidRequest(format!(
+      "The metadata of the identity provider must have between 1 and {} bytes",
+      MAX_IDP_METADATA_LEN
+    )));
+  }
+  let idp_metadata = parse_idp_metadata(idp_metadata_xml)?;
+  let has_signing_certificate = idp_metadata
+    .idp_sso_descriptors
+    .iter()
+    .flatten()
+    .flat_map(|descriptor| descriptor.key_descriptors.iter())
+    .any(|key| key.key_use.as_deref() != Some("encryption"));
+  if !has_signing_certificate {
+    return Err(AppError::InvalidRequest(
+      "The metadata of the identity provider has no signing certificate".to_string(),
+    ));
+  }
+  let sp = service_provider(setting, workspace_id, idp_metadata)?;
+  if sp.sso_binding_location(HTTP_REDIRECT_BINDING).is_none() {
+    return Err(AppError::InvalidRequest(
+      "The metadata of the identity provider has no HTTP-Redirect sign on service".to_string(),
+    ));
+  }
+
+  let email_attribute = params
+    .email_attribute
+    .as_deref()
+    .map(str::trim)
+    .filter(|attribute| !attribute.is_empty());
+  let row = upsert_workspace_saml_config(
+    pg_pool,
+    workspace_id,
+    idp_metadata_xml,
+    email_attribute,
+    params.enabled,
+  )
+  .await?;
+  Ok(saml_config_from_row(setting, row))
+}
+
+pub async fn delete_workspace_saml_config(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_workspace_saml_config_row(pg_pool, workspace_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Workspace {} has no SAML configuration",
+      workspace_id
+    )));
+  }
+  Ok(())
+}
+
+/// Returns the metadata XML of AppFlowy as the service provider of the workspace, which the
+/// identity provider is configured with. It's served while SAML sign in is disabled, so that the
+/// identity provider can be set up before the sign in is enabled.
+pub async fn get_saml_sp_metadata(
+  pg_pool: &PgPool,
+  setting: &SamlSetting,
+  workspace_id: &Uuid,
+) -> Result<String, AppError> {
+  let config = select_workspace_saml_config(pg_pool, workspace_id)
+    .await?
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "Workspace {} has no SAML configuration",
+        workspace_id
+      ))
+    })?;
+  let sp = service_provider(
+    setting,
+    workspace_id,
+    parse_idp_metadata(&config.idp_metadata_xml)?,
+  )?;
+  sp.metadata()
+    .and_then(|metadata| metadata.to_string())
+    .map_err(|err| AppError::Internal(anyhow!("Failed to build the SAML metadata: {}", err)))
+}
+
+/// Returns the URL of the identity provider the user signs in at. The id of the authentication
+/// request is the relay state, which the identity provider sends back along with the assertion:
+/// the assertion must be in response to it, and the user is redirected to the URL stored with it
+/// once signed in. The URL must be the default one, or start with an allowed one.
+pub async fn get_saml_login_url(
+  state: &AppState,
+  workspace_id: &Uuid,
+  redirect_to: Option<&str>,
+) -> Result<String, AppError> {
+  let setting = &state.config.saml;
+  let redirect_to = redirect_to
+    .filter(|redirect_to| !redirect_to.is_empty())
+    .unwrap_or(&setting.default_redirect_to);
+  if !is_allowed_redirect(setting, redirect_to) {
+    return Err(AppError::InvalidRequest(format!(
+      "The sign in can't redirect to {}",
+      redirect_to
+    )));
+  }
+  let sp = enabled_service_provider(&state.pg_pool, setting, workspace_id)
+    .await?
+    .0;
+  let sso_url = sp
+    .sso_binding_location(HTTP_REDIRECT_BINDING)
+    .ok_or_else(|| {
+      AppError::InvalidRequest(
+        "The identity provider has no HTTP-Redirect sign on service".to_string(),
+      )
+    })?;
+  let request = sp
+    .make_authentication_request(&sso_url)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to build the SAML request: {}", err)))?;
+  let _: () = redis::cmd("SET")
+    .arg(saml_request_key(workspace_id, &request.id))
+    .arg(redirect_to)
+    .arg("EX")
+    .arg(SAML_REQUEST_TTL_SECS)
+    .query_async(&mut state.redis_connection_manager.clone())
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  let url = request
+    .redirect(&request.id)
+    .map_err(|err| AppError::Internal(anyhow!("Failed to build the SAML request: {}", err)))?
+    .ok_or_else(|| AppError::Internal(anyhow!("Failed to build the SAML request")))?;
+  Ok(url.to_string())
+}
+
+/// Verifies the assertion posted by the identity provider and signs its user in. Returns the URL
+/// the user is redirected to, which opens an AppFlowy session.
+///
+/// Only the sign ins started by [get_saml_login_url] are accepted. An identity provider can assert
+/// any email, so it can only sign in the emails of the domains its workspace verified. Among them,
+/// the users who don't have an AppFlowy account yet get one and become members of the workspace,
+/// the others can only be signed in if the identity provider created their account and they are
+/// still members of the workspace.
+#[instrument(level = "info", skip(state, saml_response, relay_state), err)]
+pub async fn sign_in_with_saml_response(
+  state: &AppState,
+  workspace_id: &Uuid,
+  saml_response: &str,
+  relay_state: Option<&str>,
+) -> Result<String, AppError> {
+  let (sp, config) =
+    enabled_service_provider(&state.pg_pool, &state.config.saml, workspace_id).await?;
+  let request_id = relay_state
+    .filter(|relay_state| !relay_state.is_empty())
+    .ok_or_else(|| AppError::UserUnAuthorized("The SAML sign in wasn't started".to_string()))?;
+  let redirect_to = take_saml_request(state, workspace_id, request_id).await?;
+  let assertion = sp
+    .parse_base64_response(saml_response, Some(&[request_id]))
+    .map_err(|err| AppError::UserUnAuthorized(format!("Invalid SAML response: {}", err)))?;
+  check_assertion_not_replayed(state, workspace_id, &assertion.id).await?;
+  let email = assertion_email(&assertion, config.email_attribute.as_deref())
+    .ok_or_else(|| AppError::InvalidRequest("The SAML assertion has no email".to_string()))?;
+  if !is_email_domain_verified(&state.pg_pool, workspace_id, &email).await? {
+    return Err(AppError::NotEnoughPermissions);
+  }
+
+  let existing_uid = match select_uid_from_email(&state.pg_pool, &email).await {
+    Ok(uid) => Some(uid),
+    Err(err) if err.is_record_not_found() => None,
+    Err(err) => return Err(err),
+  };
+  if let Some(uid) = existing_uid {
+    if !is_workspace_saml_user(&state.pg_pool, workspace_id, uid).await? {
+      return Err(AppError::NotEnoughPermissions);
+    }
+    match select_user_role(&state.pg_pool, &uid, workspace_id).await {
+      Ok(_) => {},
+      Err(err) if err.is_record_not_found() => return Err(AppError::NotEnoughPermissions),
+      Err(err) => return Err(err),
+    }
+  }
+
+  let admin_token = state.gotrue_admin.token().await?;
+  // The magic link creates the GoTrue user if it doesn't exist, and opens its session.
+  let link = state
+    .gotrue_client
+    .admin_generate_link(
+      &admin_token,
+      &GenerateLinkParams {
+        type_: GenerateLinkType::MagicLink,
+        email: email.clone(),
+        redirect_to,
+        ..Default::default()
+      },
+    )
+    .await?;
+
+  if existing_uid.is_none() {
+    let user_uuid = Uuid::parse_str(&link.id)?;
+    create_user_if_not_exist(state, &user_uuid, &email, "").await?;
+    add_saml_user_to_workspace(state, workspace_id, &email).await?;
+  }
+  Ok(link.action_link)
+}
+
+async fn add_saml_user_to_workspace(
+  state: &AppState,
+  workspace_id: &Uuid,
+  email: &str,
+) -> Result<(), AppError> {
+  let mut txn = state.pg_pool.begin().await?;
+  upsert_workspace_member_with_txn(&mut txn, workspace_id, email, AFRole::Member).await?;
+  txn.commit().await?;
+  let uid = select_uid_from_email(&state.pg_pool, email).await?;
+  insert_workspace_saml_user(&state.pg_pool, workspace_id, uid).await?;
+  state
+    .workspace_access_control
+    .insert_role(&uid, workspace_id, AFRole::Member)
+    .await?;
+  info!(
+    "added user {} to workspace {} on SAML sign in",
+    uid, workspace_id
+  );
+  record_workspace_permission_change(
+    &state.pg_pool,
+    workspace_id,
+    None,
+    uid,
+    None,
+    Some(&AFRole::Member),
+  )
+  .await;
+  Ok(())
+}
+
+/// Returns the email of the assertion, from the attribute of the workspace if any, from the name
+/// id otherwise.
+fn assertion_email(assertion: &Assertion, email_attribute: Option<&str>) -> Option<String> {
+  let email = match email_attribute {
+    Some(email_attribute) => assertion
+      .attribute_statements
+      .iter()
+      .flatten()
+      .flat_map(|statement| statement.attributes.iter())
+      .find(|attribute| {
+        attribute.name.as_deref() == Some(email_attribute)
+          || attribute.friendly_name.as_deref() == Some(email_attribute)
+      })?
+      .values
+      .iter()
+      .find_map(|value| value.value.clone())?,
+    None => assertion.subject.as_ref()?.name_id.as_ref()?.value.clone(),
+  };
+  let email = email.trim().to_lowercase();
+  validator::validate_email(&email).then_some(email)
+}
+
+/// Returns true if the URL is the default redirect, or starts with one of the allowed URLs.
+fn is_allowed_redirect(setting: &SamlSetting, redirect_to: &str) -> bool {
+  redirect_to == setting.default_redirect_to
+    || setting.allowed_redirect_urls.iter().any(|allowed| {
+      match redirect_to.strip_prefix(allowed.as_str()) {
+        Some(rest) => {
+          rest.is_empty() || allowed.ends_with('/') || rest.starts_with(['/', '?', '#'])
+        },
+        None => false,
+      }
+    })
+}
+
+fn saml_request_key(workspace_id: &Uuid, request_id: &str) -> String {
+  format!("af_saml_request:{}:{}", workspace_id, request_id)
+}
+
+/// Returns the URL the sign in started by the request redirects to. The request can only be used
+/// once.
+async fn take_saml_request(
+  state: &AppState,
+  workspace_id: &Uuid,
+  request_id: &str,
+) -> Result<String, AppError> {
+  let redirect_to: Option<String> = redis::cmd("GETDEL")
+    .arg(saml_request_key(workspace_id, request_id))
+    .query_async(&mut state.redis_connection_manager.clone())
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  redirect_to.ok_or_else(|| {
+    AppError::UserUnAuthorized("The SAML sign in expired or was already used".to_string())
+  })
+}
+
+/// The assertions are signed and short-lived, but could still be posted again until they expire.
+async fn check_assertion_not_replayed(
+  state: &AppState,
+  workspace_id: &Uuid,
+  assertion_id: &str,
+) -> Result<(), AppError> {
+  let key = format!("af_saml_assertion:{}:{}", workspace_id, assertion_id);
+  let is_first_use: Option<String> = redis::cmd("SET")
+    .arg(&key)
+    .arg(1)
+    .arg("NX")
+    .arg("EX")
+    .arg(SAML_ASSERTION_REPLAY_TTL_SECS)
+    .query_async(&mut state.redis_connection_manager.clone())
+    .await
+    .map_err(|err| AppError::Internal(err.into()))?;
+  if is_first_use.is_none() {
+    return Err(AppError::UserUnAuthorized(
+      "The SAML assertion was already used".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+async fn enabled_service_provider(
+  pg_pool: &PgPool,
+  setting: &SamlSetting,
+  workspace_id: &Uuid,
+) -> Result<(ServiceProvider, AFWorkspaceSamlConfigRow), AppError> {
+  let config = select_workspace_saml_config(pg_pool, workspace_id)
+    .await?
+    .filter(|config| config.enabled)
+    .ok_or_else(|| {
+      AppError::RecordNotFound(format!(
+        "SAML sign in is not enabled for workspace {}",
+        workspace_id
+      ))
+    })?;
+  let idp_metadata = parse_idp_metadata(&config.idp_metadata_xml)?;
+  let sp = service_provider(setting, workspace_id, idp_metadata)?;
+  Ok((sp, config))
+}
+
+fn service_provider(
+  setting: &SamlSetting,
+  workspace_id: &Uuid,
+  idp_metadata: EntityDescriptor,
+) -> Result<ServiceProvider, AppError> {
+  let urls = ServiceProviderUrls::new(setting, workspace_id);
+  ServiceProviderBuilder::default()
+    .entity_id(urls.entity_id.clone())
+    .metadata_url(urls.entity_id)
+    .acs_url(urls.acs_url)
+    .idp_metadata(idp_metadata)
+    .build()
+    .map_err(|err| {
+      AppError::Internal(anyhow!(
+        "Failed to build the SAML service provider: {}",
+        err
+      ))
+    })
+}
+
+fn parse_idp_metadata(idp_metadata_xml: &str) -> Result<EntityDescriptor, AppError> {
+  let idp_metadata = idp_metadata_xml
+    .parse::<EntityDescriptor>()
+    .map_err(|err| {
+      AppError::InvalidRequest(format!(
+        "Invalid metadata of the identity provider: {}",
+        err
+      ))
+    })?;
+  if idp_metadata.idp_sso_descriptors.is_none() {
+    return Err(AppError::InvalidRequest(
+      "The metadata is not the metadata of an identity provider".to_string(),
+    ));
+  }
+  Ok(idp_metadata)
+}
+
+fn saml_config_from_row(
+  setting: &SamlSetting,
+  row: AFWorkspaceSamlConfigRow,
+) -> WorkspaceSamlConfig {
+  let urls = ServiceProviderUrls::new(setting, &row.workspace_id);
+  WorkspaceSamlConfig {
+    idp_metadata_xml: row.idp_metadata_xml,
+    email_attribute: row.email_attribute,
+    enabled: row.enabled,
+    sp_entity_id: urls.entity_id,
+    acs_url: urls.acs_url,
+    login_url: urls.login_url,
+    updated_at: row.updated_at,
+  }
+}
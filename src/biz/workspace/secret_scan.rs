@@ -0,0 +1,300 @@
+use std::collections::BTreeSet;
+
+use anyhow::anyhow;
+use app_error::AppError;
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::{CollabType, EncodedCollab};
+use database::secret_finding::{
+  delete_collab_secret_finding, select_workspace_secret_findings, upsert_collab_secret_finding,
+};
+use database::user::select_name_from_uuid;
+use database::workspace::{select_workspace, select_workspace_settings};
+use database_entity::dto::{CollabParams, SecretScanningPolicy};
+use serde_json::Value;
+use shared_entity::dto::workspace_dto::CollabSecretFinding;
+use sqlx::PgPool;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::mailer::{AFCloudMailer, SecretDetectedNotificationMailerParam};
+
+/// The kinds of secrets detected in the content of the documents.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum SecretKind {
+  AwsAccessKey,
+  GitHubToken,
+  GoogleApiKey,
+  OpenAIKey,
+  PrivateKey,
+  SlackToken,
+  StripeKey,
+}
+
+impl SecretKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      SecretKind::AwsAccessKey => "aws_access_key",
+      SecretKind::GitHubToken => "github_token",
+      SecretKind::GoogleApiKey => "google_api_key",
+      SecretKind::OpenAIKey => "openai_key",
+      SecretKind::PrivateKey => "private_key",
+      SecretKind::SlackToken => "slack_token",
+      SecretKind::StripeKey => "stripe_key",
+    }
+  }
+}
+
+/// A secret made of a well-known prefix followed by a token.
+struct TokenPattern {
+  kind: SecretKind,
+  prefixes: &'static [&'static str],
+  /// The minimum length of the token following the prefix.
+  min_len: usize,
+  is_token_char: fn(u8) -> bool,
+}
+
+fn is_upper_alphanumeric(c: u8) -> bool {
+  c.is_ascii_uppercase() || c.is_ascii_digit()
+}
+
+fn is_alphanumeric(c: u8) -> bool {
+  c.is_ascii_alphanumeric()
+}
+
+fn is_alphanumeric_or_underscore(c: u8) -> bool {
+  c.is_ascii_alphanumeric() || c == b'_'
+}
+
+fn is_alphanumeric_or_dash(c: u8) -> bool {
+  c.is_ascii_alphanumeric() || c == b'-'
+}
+
+fn is_url_safe_base64(c: u8) -> bool {
+  c.is_ascii_alphanumeric() || c == b'-' || c == b'_'
+}
+
+const TOKEN_PATTERNS: &[TokenPattern] = &[
+  TokenPattern {
+    kind: SecretKind::AwsAccessKey,
+    prefixes: &["AKIA", "ASIA"],
+    min_len: 16,
+    is_token_char: is_upper_alphanumeric,
+  },
+  TokenPattern {
+    kind: SecretKind::GitHubToken,
+    prefixes: &["ghp_", "gho_", "ghu_", "ghs_", "ghr_"],
+    min_len: 36,
+    is_token_char: is_alphanumeric,
+  },
+  TokenPattern {
+    kind: SecretKind::GitHubToken,
+    prefixes: &["github_pat_"],
+    min_len: 22,
+    is_token_char: is_alphanumeric_or_underscore,
+  },
+  TokenPattern {
+    kind: SecretKind::GoogleApiKey,
+    prefixes: &["AIza"],
+    min_len: 35,
+    is_token_char: is_url_safe_base64,
+  },
+  TokenPattern {
+    kind: SecretKind::OpenAIKey,
+    prefixes: &["sk-proj-", "sk-"],
+    min_len: 32,
+    is_token_char: is_url_safe_base64,
+  },
+  TokenPattern {
+    kind: SecretKind::SlackToken,
+    prefixes: &["xoxb-", "xoxp-", "xoxa-", "xoxr-", "xoxs-"],
+    min_len: 10,
+    is_token_char: is_alphanumeric_or_dash,
+  },
+  TokenPattern {
+    kind: SecretKind::StripeKey,
+    prefixes: &["sk_live_", "rk_live_"],
+    min_len: 24,
+    is_token_char: is_alphanumeric,
+  },
+];
+
+/// Returns the kinds of the secrets found in the text.
+pub fn detect_secrets(text: &str) -> BTreeSet<SecretKind> {
+  let mut kinds = BTreeSet::new();
+  let bytes = text.as_bytes();
+  for pattern in TOKEN_PATTERNS {
+    for prefix in pattern.prefixes {
+      for (start, _) in text.match_indices(prefix) {
+        // The prefix must start a word, e.g. `task-` doesn't start an OpenAI key.
+        if start > 0 && is_url_safe_base64(bytes[start - 1]) {
+          continue;
+        }
+        let token_len = bytes[start + prefix.len()..]
+          .iter()
+          .take_while(|c| (pattern.is_token_char)(**c))
+          .count();
+        if token_len >= pattern.min_len {
+          kinds.insert(pattern.kind);
+        }
+      }
+    }
+  }
+
+  if let Some(start) = text.find("-----BEGIN ") {
+    if text[start..].contains("PRIVATE KEY-----") {
+      kinds.insert(SecretKind::PrivateKey);
+    }
+  }
+  kinds
+}
+
+/// Scans the content of a document written through the HTTP API, according to the
+/// `secret_scanning` policy of the workspace. A document with secrets is flagged and the owner of
+/// the workspace is notified, the write is rejected if the policy blocks the secrets. The flag is
+/// removed once the document no longer contains any secret.
+pub async fn scan_document_write(
+  pg_pool: &PgPool,
+  mailer: &AFCloudMailer,
+  appflowy_web_url: Option<&str>,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  uid: i64,
+  params: &CollabParams,
+) -> Result<(), AppError> {
+  if params.collab_type != CollabType::Document {
+    return Ok(());
+  }
+  let policy = select_workspace_settings(pg_pool, workspace_id)
+    .await?
+    .map(|settings| settings.secret_scanning)
+    .unwrap_or_default();
+  if policy == SecretScanningPolicy::Off {
+    return Ok(());
+  }
+
+  let object_id = params.object_id.clone();
+  let encoded_collab_v1 = params.encoded_collab_v1.clone();
+  let kinds = tokio::task::spawn_blocking(move || {
+    let encoded_collab = EncodedCollab::decode_from_bytes(&encoded_collab_v1)
+      .map_err(|err| AppError::InvalidRequest(format!("Failed to decode collab: {}", err)))?;
+    let collab = Collab::new_with_source(
+      CollabOrigin::Server,
+      &object_id,
+      DataSource::DocStateV1(encoded_collab.doc_state.to_vec()),
+      vec![],
+      false,
+    )
+    .map_err(|err| AppError::Internal(anyhow!("Failed to open collab: {}", err)))?;
+    let mut kinds = BTreeSet::new();
+    collect_secrets(&collab.to_json_value(), &mut kinds);
+    Ok::<_, AppError>(kinds)
+  })
+  .await??;
+
+  if kinds.is_empty() {
+    delete_collab_secret_finding(pg_pool, &params.object_id).await?;
+    return Ok(());
+  }
+
+  let secret_kinds = kinds
+    .iter()
+    .map(|kind| kind.as_str().to_string())
+    .collect::<Vec<_>>();
+  warn!(
+    "Secrets detected in document {} of workspace {}: {:?}",
+    params.object_id, workspace_id, secret_kinds
+  );
+  let notify = match policy {
+    SecretScanningPolicy::Block => true,
+    _ => {
+      upsert_collab_secret_finding(pg_pool, workspace_id, &params.object_id, &secret_kinds, uid)
+        .await?
+    },
+  };
+  if notify {
+    if let Err(err) = notify_workspace_owner(
+      pg_pool,
+      mailer,
+      appflowy_web_url,
+      workspace_id,
+      user_uuid,
+      &params.object_id,
+      &secret_kinds,
+      policy,
+    )
+    .await
+    {
+      error!("Failed to notify the secrets detected: {:?}", err);
+    }
+  }
+
+  if policy == SecretScanningPolicy::Block {
+    return Err(AppError::SecretDetected(format!(
+      "The document contains secrets: {}",
+      secret_kinds.join(", ")
+    )));
+  }
+  Ok(())
+}
+
+fn collect_secrets(value: &Value, kinds: &mut BTreeSet<SecretKind>) {
+  match value {
+    Value::String(s) => kinds.extend(detect_secrets(s)),
+    Value::Array(items) => items.iter().for_each(|item| collect_secrets(item, kinds)),
+    Value::Object(map) => map.values().for_each(|value| collect_secrets(value, kinds)),
+    _ => {},
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn notify_workspace_owner(
+  pg_pool: &PgPool,
+  mailer: &AFCloudMailer,
+  appflowy_web_url: Option<&str>,
+  workspace_id: &Uuid,
+  user_uuid: &Uuid,
+  object_id: &str,
+  secret_kinds: &[String],
+  policy: SecretScanningPolicy,
+) -> Result<(), AppError> {
+  let workspace = select_workspace(pg_pool, workspace_id).await?;
+  let (Some(owner_name), Some(owner_email)) = (workspace.owner_name, workspace.owner_email) else {
+    return Ok(());
+  };
+  let action = match policy {
+    SecretScanningPolicy::Block => "The change was rejected.",
+    _ => "The document was saved and flagged.",
+  };
+  let param = SecretDetectedNotificationMailerParam {
+    workspace_name: workspace.workspace_name.unwrap_or_default(),
+    author_name: select_name_from_uuid(pg_pool, user_uuid).await?,
+    secret_kinds: secret_kinds.join(", "),
+    action: action.to_string(),
+    view_url: appflowy_web_url
+      .map(|url| format!("{}/app/{}/{}", url, workspace_id, object_id))
+      .unwrap_or_default(),
+  };
+  mailer
+    .send_secret_detected_notification(&owner_name, &owner_email, param)
+    .await
+    .map_err(AppError::Internal)
+}
+
+pub async fn get_workspace_secret_findings(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<Vec<CollabSecretFinding>, AppError> {
+  let findings = select_workspace_secret_findings(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| CollabSecretFinding {
+      object_id: row.oid,
+      secret_kinds: row.secret_kinds,
+      detected_by: row.detected_by,
+      detected_at: row.detected_at,
+    })
+    .collect();
+  Ok(findings)
+}
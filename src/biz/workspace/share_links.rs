@@ -0,0 +1,74 @@
+use std::ops::DerefMut;
+
+use app_error::AppError;
+use database::collab_share_token::{
+  delete_workspace_collab_share_tokens, select_active_collab_share_tokens,
+};
+use database::page_share_link::{
+  delete_workspace_page_share_links, select_workspace_page_share_links,
+};
+use shared_entity::dto::workspace_dto::{
+  CollabShareTokenMeta, RevokedShareLinks, WorkspaceShareLinks,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::biz::workspace::page_share_link::page_share_link_from_row;
+
+/// Returns the share links of the pages and the share tokens of the collabs of the workspace, so
+/// that the owners can review what has been shared outside of the workspace.
+pub async fn list_workspace_share_links(
+  pg_pool: &PgPool,
+  appflowy_web_url: Option<&str>,
+  workspace_id: &Uuid,
+) -> Result<WorkspaceShareLinks, AppError> {
+  let page_share_links = select_workspace_page_share_links(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| page_share_link_from_row(row, appflowy_web_url))
+    .collect();
+  let collab_share_tokens = select_active_collab_share_tokens(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| CollabShareTokenMeta {
+      token_id: row.token_id,
+      object_id: row.object_id,
+      issued_by: row.issued_by,
+      created_at: row.created_at,
+      expires_at: row.expires_at,
+    })
+    .collect();
+  Ok(WorkspaceShareLinks {
+    page_share_links,
+    collab_share_tokens,
+  })
+}
+
+/// Revokes the share links and share tokens of the workspace, only the ones created by the user if
+/// given.
+pub async fn revoke_workspace_share_links(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  created_by: Option<i64>,
+) -> Result<RevokedShareLinks, AppError> {
+  let mut txn = pg_pool.begin().await?;
+  let revoked = revoke_share_links_with_txn(&mut txn, workspace_id, created_by).await?;
+  txn.commit().await?;
+  Ok(revoked)
+}
+
+/// Like [revoke_workspace_share_links], within the transaction, e.g. removing a member.
+pub async fn revoke_share_links_with_txn(
+  txn: &mut Transaction<'_, Postgres>,
+  workspace_id: &Uuid,
+  created_by: Option<i64>,
+) -> Result<RevokedShareLinks, AppError> {
+  let page_share_links =
+    delete_workspace_page_share_links(txn.deref_mut(), workspace_id, created_by).await?;
+  let collab_share_tokens =
+    delete_workspace_collab_share_tokens(txn.deref_mut(), workspace_id, created_by).await?;
+  Ok(RevokedShareLinks {
+    page_share_links,
+    collab_share_tokens,
+  })
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use app_error::AppError;
+use async_trait::async_trait;
+use authentication::session_policy::{SessionAssurance, WorkspaceSessionPolicy};
+use dashmap::DashMap;
+use database::workspace::{select_all_user_workspaces, select_workspace_settings};
+use shared_entity::response::AppResponseError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// How long the `require_2fa` setting of a workspace is cached. A setting changed through another
+/// server is applied once its cached copy expires.
+const TWO_FACTOR_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The expired settings are removed once the cache holds this many workspaces.
+const MAX_CACHED_TWO_FACTOR_SETTINGS: usize = 10_000;
+
+/// Rejects the sessions without a second factor on the routes of the workspaces that require one.
+/// It is checked by the authentication extractors, see [WorkspaceSessionPolicy].
+pub struct WorkspaceTwoFactorPolicy {
+  pg_pool: PgPool,
+  requirements: DashMap<Uuid, (bool, Instant)>,
+}
+
+impl WorkspaceTwoFactorPolicy {
+  pub fn new(pg_pool: PgPool) -> Self {
+    Self {
+      pg_pool,
+      requirements: DashMap::new(),
+    }
+  }
+
+  pub async fn requires_second_factor(&self, workspace_id: &Uuid) -> Result<bool, AppError> {
+    if let Some(cached) = self.requirements.get(workspace_id) {
+      if cached.1.elapsed() < TWO_FACTOR_CACHE_TTL {
+        return Ok(cached.0);
+      }
+    }
+
+    let require_2fa = select_workspace_settings(&self.pg_pool, workspace_id)
+      .await?
+      .map(|settings| settings.require_2fa)
+      .unwrap_or_default();
+    if self.requirements.len() >= MAX_CACHED_TWO_FACTOR_SETTINGS {
+      self
+        .requirements
+        .retain(|_, cached| cached.1.elapsed() < TWO_FACTOR_CACHE_TTL);
+    }
+    self
+      .requirements
+      .insert(*workspace_id, (require_2fa, Instant::now()));
+    Ok(require_2fa)
+  }
+
+  /// Returns the workspaces of the user that require a second factor.
+  pub async fn select_requiring_workspace_ids(
+    &self,
+    user_uuid: &Uuid,
+  ) -> Result<HashSet<String>, AppError> {
+    let mut workspace_ids = HashSet::new();
+    for workspace in select_all_user_workspaces(&self.pg_pool, user_uuid).await? {
+      if self.requires_second_factor(&workspace.workspace_id).await? {
+        workspace_ids.insert(workspace.workspace_id.to_string());
+      }
+    }
+    Ok(workspace_ids)
+  }
+
+  /// Drops the cached setting of the workspace once it changed.
+  pub fn invalidate(&self, workspace_id: &Uuid) {
+    self.requirements.remove(workspace_id);
+  }
+}
+
+#[async_trait]
+impl WorkspaceSessionPolicy for WorkspaceTwoFactorPolicy {
+  async fn check_session(
+    &self,
+    workspace_id: &Uuid,
+    session: &SessionAssurance,
+  ) -> Result<(), actix_web::Error> {
+    if session.has_second_factor
+      || !self
+        .requires_second_factor(workspace_id)
+        .await
+        .map_err(AppResponseError::from)?
+    {
+      return Ok(());
+    }
+    Err(
+      AppResponseError::from(AppError::SecondFactorRequired(format!(
+        "The workspace {} requires signing in with a second factor",
+        workspace_id
+      )))
+      .into(),
+    )
+  }
+}
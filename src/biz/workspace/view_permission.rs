@@ -0,0 +1,298 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use access_control::collab::CollabAccessControl;
+use app_error::AppError;
+use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
+use database::collab::GetCollabOrigin;
+use database::member_group::{
+  delete_member_group, insert_member_group, replace_member_group_members,
+  select_member_group_exists, select_member_group_ids_for_user, select_member_groups,
+  select_workspace_member_uids,
+};
+use database::view_permission::{
+  replace_view_permissions, select_view_permissions, select_workspace_view_permissions,
+};
+use database::workspace::select_user_role;
+use database_entity::dto::{
+  AFMemberGroup, AFMemberGroups, AFRole, AFViewPermission, AFViewPermissions,
+  CreateMemberGroupParams, UpdateMemberGroupParams, UpdateViewPermissionsParams,
+  ViewPermissionGrantee,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::biz::collab::ops::get_latest_collab_folder;
+
+const MAX_MEMBER_GROUP_NAME_LENGTH: usize = 100;
+const MAX_MEMBER_GROUPS_PER_WORKSPACE: usize = 100;
+const MAX_PERMISSIONS_PER_VIEW: usize = 100;
+
+pub async fn get_view_permissions(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  view_id: &str,
+) -> Result<AFViewPermissions, AppError> {
+  let permissions = select_view_permissions(pg_pool, workspace_id, view_id)
+    .await?
+    .into_iter()
+    .map(AFViewPermission::try_from)
+    .collect::<Result<Vec<_>, _>>()?;
+  Ok(AFViewPermissions {
+    view_id: view_id.to_string(),
+    permissions,
+  })
+}
+
+/// Replaces the permissions of the view. The members must belong to the workspace and the groups
+/// to be groups of the workspace.
+pub async fn update_view_permissions(
+  pg_pool: &PgPool,
+  collab_storage: &CollabAccessControlStorage,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  workspace_id: &Uuid,
+  view_id: &str,
+  params: UpdateViewPermissionsParams,
+) -> Result<AFViewPermissions, AppError> {
+  if params.permissions.len() > MAX_PERMISSIONS_PER_VIEW {
+    return Err(AppError::InvalidRequest(format!(
+      "A view can't have more than {} permissions",
+      MAX_PERMISSIONS_PER_VIEW
+    )));
+  }
+  let folder = get_latest_collab_folder(
+    collab_storage,
+    GetCollabOrigin::Server,
+    &workspace_id.to_string(),
+  )
+  .await?;
+  if folder.get_view(view_id).is_none() {
+    return Err(AppError::MissingView(format!(
+      "The view {} is not found in the folder",
+      view_id
+    )));
+  }
+
+  let mut grantees = HashSet::new();
+  let mut uids = vec![];
+  let mut group_ids = vec![];
+  let mut access_levels = vec![];
+  for permission in &params.permissions {
+    if !grantees.insert(permission.grantee.clone()) {
+      return Err(AppError::InvalidRequest(format!(
+        "The permission of {:?} is given more than once",
+        permission.grantee
+      )));
+    }
+    match &permission.grantee {
+      ViewPermissionGrantee::Member { uid } => {
+        uids.push(Some(*uid));
+        group_ids.push(None);
+      },
+      ViewPermissionGrantee::Group { group_id } => {
+        if !select_member_group_exists(pg_pool, workspace_id, group_id).await? {
+          return Err(AppError::RecordNotFound(format!(
+            "Member group {} not found",
+            group_id
+          )));
+        }
+        uids.push(None);
+        group_ids.push(Some(*group_id));
+      },
+    }
+    access_levels.push(i32::from(permission.access_level));
+  }
+  let member_uids: Vec<i64> = uids.iter().flatten().copied().collect();
+  check_workspace_members(pg_pool, workspace_id, &member_uids).await?;
+
+  let mut txn = pg_pool.begin().await?;
+  replace_view_permissions(
+    &mut txn,
+    workspace_id,
+    view_id,
+    &uids,
+    &group_ids,
+    &access_levels,
+  )
+  .await?;
+  txn.commit().await?;
+  collab_access_control.reload_view_permissions().await?;
+
+  Ok(AFViewPermissions {
+    view_id: view_id.to_string(),
+    permissions: params.permissions,
+  })
+}
+
+/// Returns the ids of the restricted views of the workspace that the user isn't granted. The
+/// owners of the workspace are granted all the views.
+pub async fn get_restricted_view_ids(
+  pg_pool: &PgPool,
+  uid: i64,
+  workspace_id: &Uuid,
+) -> Result<HashSet<String>, AppError> {
+  let permissions = select_workspace_view_permissions(pg_pool, workspace_id).await?;
+  if permissions.is_empty() || select_user_role(pg_pool, &uid, workspace_id).await? == AFRole::Owner
+  {
+    return Ok(HashSet::new());
+  }
+  let group_ids: HashSet<Uuid> = select_member_group_ids_for_user(pg_pool, workspace_id, uid)
+    .await?
+    .into_iter()
+    .collect();
+  let mut restricted_view_ids = HashSet::new();
+  let mut granted_view_ids = HashSet::new();
+  for permission in permissions {
+    let is_granted = match permission.grantee() {
+      Some(ViewPermissionGrantee::Member { uid: grantee_uid }) => grantee_uid == uid,
+      Some(ViewPermissionGrantee::Group { group_id }) => group_ids.contains(&group_id),
+      None => false,
+    };
+    if is_granted {
+      granted_view_ids.insert(permission.view_id.clone());
+    }
+    restricted_view_ids.insert(permission.view_id);
+  }
+  restricted_view_ids.retain(|view_id| !granted_view_ids.contains(view_id));
+  Ok(restricted_view_ids)
+}
+
+/// Returns the ids of all the restricted views of the workspace, none of which is granted to the
+/// anonymous readers.
+pub async fn get_all_restricted_view_ids(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<HashSet<String>, AppError> {
+  let permissions = select_workspace_view_permissions(pg_pool, workspace_id).await?;
+  Ok(
+    permissions
+      .into_iter()
+      .map(|permission| permission.view_id)
+      .collect(),
+  )
+}
+
+pub async fn get_member_groups(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+) -> Result<AFMemberGroups, AppError> {
+  let groups = select_member_groups(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .map(|row| row.into())
+    .collect();
+  Ok(AFMemberGroups { groups })
+}
+
+pub async fn create_member_group(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  params: CreateMemberGroupParams,
+) -> Result<AFMemberGroup, AppError> {
+  let name = params.name.trim();
+  if name.is_empty() || name.chars().count() > MAX_MEMBER_GROUP_NAME_LENGTH {
+    return Err(AppError::InvalidRequest(format!(
+      "The name of a member group must have between 1 and {} characters",
+      MAX_MEMBER_GROUP_NAME_LENGTH
+    )));
+  }
+  if select_member_groups(pg_pool, workspace_id).await?.len() >= MAX_MEMBER_GROUPS_PER_WORKSPACE {
+    return Err(AppError::InvalidRequest(format!(
+      "A workspace can't have more than {} member groups",
+      MAX_MEMBER_GROUPS_PER_WORKSPACE
+    )));
+  }
+  check_workspace_members(pg_pool, workspace_id, &params.member_uids).await?;
+
+  let mut txn = pg_pool.begin().await?;
+  let group_id = insert_member_group(txn.as_mut(), workspace_id, name)
+    .await?
+    .ok_or(AppError::RecordAlreadyExists(format!(
+      "Member group {} already exists",
+      name
+    )))?;
+  replace_member_group_members(&mut txn, &group_id, &params.member_uids).await?;
+  txn.commit().await?;
+
+  find_member_group(pg_pool, workspace_id, &group_id).await
+}
+
+/// Replaces the members of the group, the permissions granted to the group apply to the new
+/// members right away.
+pub async fn update_member_group(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+  params: UpdateMemberGroupParams,
+) -> Result<AFMemberGroup, AppError> {
+  if !select_member_group_exists(pg_pool, workspace_id, group_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Member group {} not found",
+      group_id
+    )));
+  }
+  check_workspace_members(pg_pool, workspace_id, &params.member_uids).await?;
+
+  let mut txn = pg_pool.begin().await?;
+  replace_member_group_members(&mut txn, group_id, &params.member_uids).await?;
+  txn.commit().await?;
+  collab_access_control.reload_view_permissions().await?;
+
+  find_member_group(pg_pool, workspace_id, group_id).await
+}
+
+/// Deletes the group along with the permissions granted to it. The views that were only granted
+/// to the group stay restricted to the owners of the workspace.
+pub async fn remove_member_group(
+  pg_pool: &PgPool,
+  collab_access_control: Arc<dyn CollabAccessControl>,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<(), AppError> {
+  if !delete_member_group(pg_pool, workspace_id, group_id).await? {
+    return Err(AppError::RecordNotFound(format!(
+      "Member group {} not found",
+      group_id
+    )));
+  }
+  collab_access_control.reload_view_permissions().await?;
+  Ok(())
+}
+
+async fn find_member_group(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  group_id: &Uuid,
+) -> Result<AFMemberGroup, AppError> {
+  select_member_groups(pg_pool, workspace_id)
+    .await?
+    .into_iter()
+    .find(|row| &row.group_id == group_id)
+    .map(|row| row.into())
+    .ok_or(AppError::RecordNotFound(format!(
+      "Member group {} not found",
+      group_id
+    )))
+}
+
+async fn check_workspace_members(
+  pg_pool: &PgPool,
+  workspace_id: &Uuid,
+  uids: &[i64],
+) -> Result<(), AppError> {
+  if uids.is_empty() {
+    return Ok(());
+  }
+  let member_uids: HashSet<i64> = select_workspace_member_uids(pg_pool, workspace_id, uids)
+    .await?
+    .into_iter()
+    .collect();
+  match uids.iter().find(|uid| !member_uids.contains(uid)) {
+    Some(uid) => Err(AppError::InvalidRequest(format!(
+      "User {} is not a member of the workspace",
+      uid
+    ))),
+    None => Ok(()),
+  }
+}
@@ -7,6 +7,7 @@ use semver::Version;
 use serde::Deserialize;
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
 
+use database::collab::shard::DEFAULT_COLLAB_SHARD;
 use infra::env_util::{get_env_var, get_env_var_opt};
 use mailer::config::MailerSetting;
 
@@ -15,6 +16,8 @@ pub struct Config {
   pub app_env: Environment,
   pub access_control: AccessControlSetting,
   pub db_settings: DatabaseSetting,
+  /// A read replica of the default database, used by the analytics reports.
+  pub read_replica_db_settings: Option<DatabaseSetting>,
   pub gotrue: GoTrueSetting,
   pub application: ApplicationSetting,
   pub websocket: WebsocketSetting,
@@ -27,6 +30,14 @@ pub struct Config {
   pub mailer: MailerSetting,
   pub apple_oauth: AppleOAuthSetting,
   pub appflowy_web_url: Option<String>,
+  pub residency: ResidencySetting,
+  pub collab_shard: CollabShardSetting,
+  pub rate_limit: RateLimitSetting,
+  pub realtime_rate_limit: RealtimeRateLimitSetting,
+  pub oembed: OEmbedSetting,
+  pub workspace_lifecycle: WorkspaceLifecycleSetting,
+  pub client_ip: ClientIpSetting,
+  pub saml: SamlSetting,
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -135,16 +146,134 @@ impl DatabaseSetting {
   }
 }
 
+/// The regions in which the data of a workspace can be stored. Each region has its own Postgres
+/// database and S3 bucket. Workspaces without a residency are stored in the default database and
+/// bucket.
+#[derive(Clone, Debug, Default)]
+pub struct ResidencySetting {
+  pub regions: Vec<RegionSetting>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RegionSetting {
+  pub name: String,
+  pub db_settings: DatabaseSetting,
+  pub s3: S3Setting,
+}
+
+/// The additional Postgres databases the collabs of the workspaces without a residency are spread
+/// over. The default database is always a shard, named `default`.
+#[derive(Clone, Debug, Default)]
+pub struct CollabShardSetting {
+  pub shards: Vec<CollabShardDbSetting>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CollabShardDbSetting {
+  pub name: String,
+  pub db_settings: DatabaseSetting,
+}
+
 #[derive(Clone, Debug)]
 pub struct GrpcHistorySetting {
   pub addrs: String,
 }
 
+/// The soft rate limit reported to the clients with the `X-RateLimit-*` headers.
+#[derive(Clone, Debug)]
+pub struct RateLimitSetting {
+  pub requests_per_window: u32,
+  pub window_secs: u64,
+}
+
+/// The hard limit of the messages each device of a user can send to the realtime message stream.
+/// The messages are throttled with a token bucket: up to `burst` messages can be sent at once,
+/// and the bucket is refilled with `messages_per_sec` messages every second. A rate of 0 disables
+/// the limit.
+#[derive(Clone, Debug)]
+pub struct RealtimeRateLimitSetting {
+  pub messages_per_sec: u32,
+  pub burst: u32,
+}
+
+/// How the address of the client is found, e.g. to check the IP allowlists of the workspaces.
+/// When the server is behind `trusted_proxy_count` reverse proxies, each of them appending the
+/// address of its peer to the `X-Forwarded-For` header, the address of the client is the one the
+/// outermost proxy appended. With no trusted proxy, the header is ignored as any client can set
+/// it, and the address of the peer is used.
+#[derive(Clone, Debug)]
+pub struct ClientIpSetting {
+  pub trusted_proxy_count: usize,
+}
+
+/// The workspaces can let their members sign in with a SAML identity provider, AppFlowy being the
+/// service provider. `base_url` is the public URL of the server, which the identity providers post
+/// the assertions to. Once signed in, the users are redirected to `default_redirect_to` with their
+/// session, unless the sign in was started with another redirect starting with one of
+/// `allowed_redirect_urls`.
+///
+/// The identity provider of a workspace only signs in the users of the email domains the workspace
+/// verified, with a DNS TXT record resolved by the DNS-over-HTTPS resolver at `dns_over_https_url`.
+#[derive(Clone, Debug)]
+pub struct SamlSetting {
+  pub base_url: String,
+  pub default_redirect_to: String,
+  pub allowed_redirect_urls: Vec<String>,
+  pub dns_over_https_url: String,
+}
+
+/// The oEmbed providers the server resolves the embeds with. The URLs of other hosts can't be
+/// resolved.
+#[derive(Clone, Debug)]
+pub struct OEmbedSetting {
+  pub providers: Vec<OEmbedProvider>,
+  pub cache_ttl_secs: u64,
+  pub request_timeout_secs: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct OEmbedProvider {
+  /// The hosts of the embedded URLs, their subdomains are served by the provider too.
+  pub hosts: Vec<String>,
+  /// The oEmbed endpoint of the provider, must be an https URL.
+  pub endpoint: String,
+}
+
+/// The policies applied to the workspaces that haven't been edited for a while. The periods are
+/// counted from the last edit in the workspace.
+#[derive(Clone, Debug)]
+pub struct WorkspaceLifecycleSetting {
+  /// The owner is warned once the workspace has been inactive for this many days. Set to 0 to
+  /// disable the policies.
+  pub warn_after_days: u64,
+  /// The workspace is archived once it has been inactive for this many days. Set to 0 to never
+  /// archive the inactive workspaces.
+  pub archive_after_days: u64,
+  /// The workspace is deleted once it has been inactive for this many days. Set to 0 to never
+  /// delete the inactive workspaces.
+  pub delete_after_days: u64,
+  /// The owner is notified this many days before the deletion, in descending order. The deletion
+  /// is never scheduled closer than the first notice.
+  pub deletion_notice_days: Vec<u64>,
+}
+
 #[derive(Clone, Debug)]
 pub struct CollabSetting {
   pub group_persistence_interval_secs: u64,
   pub edit_state_max_count: u32,
   pub edit_state_max_secs: i64,
+  /// The collabs without connected client are unloaded from memory after this many seconds. Set
+  /// to 0 to only unload them after the inactivity timeout of their collab type.
+  pub group_hibernate_after_secs: u64,
+  /// The payloads of the collabs that haven't been written for this many days are moved to the
+  /// archive table. Set to 0 to disable the archival.
+  pub archive_after_days: u64,
+  /// The deleted collabs can be restored for this many days, they are purged afterwards. Set to 0
+  /// to keep them until they are purged explicitly.
+  pub trash_retention_days: u64,
+  /// The number of collabs and of snapshots whose checksums are verified by each run of the
+  /// background scrubber. Set to 0 to disable the scrubber.
+  pub scrub_batch_size: i64,
 }
 
 #[derive(Clone, Debug)]
@@ -156,6 +285,14 @@ pub enum PublishedCollabStorageBackend {
 #[derive(Clone, Debug)]
 pub struct PublishedCollabSetting {
   pub storage_backend: PublishedCollabStorageBackend,
+  /// Strips the scripts and the untrusted embeds from the published content.
+  pub sanitize_content: bool,
+  /// Hosts of the embeds trusted for every publish namespace.
+  pub trusted_embed_hosts: Vec<String>,
+  /// An auto-published document is republished once it hasn't changed for this duration.
+  pub auto_publish_debounce_secs: u64,
+  /// An auto-published document that keeps changing is republished at least this often.
+  pub auto_publish_max_delay_secs: u64,
 }
 
 impl TryFrom<&str> for PublishedCollabStorageBackend {
@@ -202,6 +339,7 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
         .parse()
         .context("fail to get APPFLOWY_DATABASE_MAX_CONNECTIONS")?,
     },
+    read_replica_db_settings: get_read_replica_db_settings()?,
     gotrue: GoTrueSetting {
       base_url: get_env_var("APPFLOWY_GOTRUE_BASE_URL", "http://localhost:9999"),
       ext_url: get_env_var("APPFLOWY_GOTRUE_EXT_URL", "http://localhost:9999"),
@@ -251,11 +389,46 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       .parse()?,
       edit_state_max_count: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_COUNT", "100").parse()?,
       edit_state_max_secs: get_env_var("APPFLOWY_COLLAB_EDIT_STATE_MAX_SECS", "60").parse()?,
+      group_hibernate_after_secs: get_env_var("APPFLOWY_COLLAB_GROUP_HIBERNATE_AFTER_SECS", "0")
+        .parse()
+        .context("fail to get APPFLOWY_COLLAB_GROUP_HIBERNATE_AFTER_SECS")?,
+      archive_after_days: get_env_var("APPFLOWY_COLLAB_ARCHIVE_AFTER_DAYS", "0")
+        .parse()
+        .context("fail to get APPFLOWY_COLLAB_ARCHIVE_AFTER_DAYS")?,
+      trash_retention_days: get_env_var("APPFLOWY_COLLAB_TRASH_RETENTION_DAYS", "30")
+        .parse()
+        .context("fail to get APPFLOWY_COLLAB_TRASH_RETENTION_DAYS")?,
+      scrub_batch_size: get_env_var("APPFLOWY_COLLAB_SCRUB_BATCH_SIZE", "100")
+        .parse()
+        .context("fail to get APPFLOWY_COLLAB_SCRUB_BATCH_SIZE")?,
     },
     published_collab: PublishedCollabSetting {
       storage_backend: get_env_var("APPFLOWY_PUBLISHED_COLLAB_STORAGE_BACKEND", "postgres")
         .as_str()
         .try_into()?,
+      sanitize_content: get_env_var("APPFLOWY_PUBLISHED_COLLAB_SANITIZE_CONTENT", "true")
+        .parse()
+        .context("fail to get APPFLOWY_PUBLISHED_COLLAB_SANITIZE_CONTENT")?,
+      trusted_embed_hosts: get_env_var(
+        "APPFLOWY_PUBLISHED_COLLAB_TRUSTED_EMBED_HOSTS",
+        "youtube.com,youtu.be,youtube-nocookie.com,figma.com",
+      )
+      .split(',')
+      .map(|host| host.trim().to_lowercase())
+      .filter(|host| !host.is_empty())
+      .collect(),
+      auto_publish_debounce_secs: get_env_var(
+        "APPFLOWY_PUBLISHED_COLLAB_AUTO_PUBLISH_DEBOUNCE_SECS",
+        "30",
+      )
+      .parse()
+      .context("fail to get APPFLOWY_PUBLISHED_COLLAB_AUTO_PUBLISH_DEBOUNCE_SECS")?,
+      auto_publish_max_delay_secs: get_env_var(
+        "APPFLOWY_PUBLISHED_COLLAB_AUTO_PUBLISH_MAX_DELAY_SECS",
+        "300",
+      )
+      .parse()
+      .context("fail to get APPFLOWY_PUBLISHED_COLLAB_AUTO_PUBLISH_MAX_DELAY_SECS")?,
     },
     mailer: MailerSetting {
       smtp_host: get_env_var("APPFLOWY_MAILER_SMTP_HOST", "smtp.gmail.com"),
@@ -268,10 +441,229 @@ pub fn get_configuration() -> Result<Config, anyhow::Error> {
       client_secret: get_env_var("APPFLOWY_APPLE_OAUTH_CLIENT_SECRET", "").into(),
     },
     appflowy_web_url: get_env_var_opt("APPFLOWY_WEB_URL"),
+    residency: get_residency_setting()?,
+    collab_shard: get_collab_shard_setting()?,
+    rate_limit: RateLimitSetting {
+      requests_per_window: get_env_var("APPFLOWY_RATE_LIMIT_REQUESTS_PER_WINDOW", "600").parse()?,
+      window_secs: get_env_var("APPFLOWY_RATE_LIMIT_WINDOW_SECS", "60").parse()?,
+    },
+    realtime_rate_limit: RealtimeRateLimitSetting {
+      messages_per_sec: get_env_var("APPFLOWY_REALTIME_RATE_LIMIT_MESSAGES_PER_SEC", "50")
+        .parse()
+        .context("fail to get APPFLOWY_REALTIME_RATE_LIMIT_MESSAGES_PER_SEC")?,
+      burst: get_env_var("APPFLOWY_REALTIME_RATE_LIMIT_BURST", "200")
+        .parse()
+        .context("fail to get APPFLOWY_REALTIME_RATE_LIMIT_BURST")?,
+    },
+    oembed: OEmbedSetting {
+      providers: get_oembed_providers(&get_env_var(
+        "APPFLOWY_OEMBED_PROVIDERS",
+        DEFAULT_OEMBED_PROVIDERS,
+      ))?,
+      cache_ttl_secs: get_env_var("APPFLOWY_OEMBED_CACHE_TTL_SECS", "86400").parse()?,
+      request_timeout_secs: get_env_var("APPFLOWY_OEMBED_REQUEST_TIMEOUT_SECS", "5").parse()?,
+    },
+    workspace_lifecycle: WorkspaceLifecycleSetting {
+      warn_after_days: get_env_var("APPFLOWY_WORKSPACE_LIFECYCLE_WARN_AFTER_DAYS", "0")
+        .parse()
+        .context("fail to get APPFLOWY_WORKSPACE_LIFECYCLE_WARN_AFTER_DAYS")?,
+      archive_after_days: get_env_var("APPFLOWY_WORKSPACE_LIFECYCLE_ARCHIVE_AFTER_DAYS", "0")
+        .parse()
+        .context("fail to get APPFLOWY_WORKSPACE_LIFECYCLE_ARCHIVE_AFTER_DAYS")?,
+      delete_after_days: get_env_var("APPFLOWY_WORKSPACE_LIFECYCLE_DELETE_AFTER_DAYS", "0")
+        .parse()
+        .context("fail to get APPFLOWY_WORKSPACE_LIFECYCLE_DELETE_AFTER_DAYS")?,
+      deletion_notice_days: get_deletion_notice_days(&get_env_var(
+        "APPFLOWY_WORKSPACE_LIFECYCLE_DELETION_NOTICE_DAYS",
+        "30,7,1",
+      ))?,
+    },
+    client_ip: ClientIpSetting {
+      trusted_proxy_count: get_env_var("APPFLOWY_TRUSTED_PROXY_COUNT", "0")
+        .parse()
+        .context("fail to get APPFLOWY_TRUSTED_PROXY_COUNT")?,
+    },
+    saml: SamlSetting {
+      base_url: get_env_var("APPFLOWY_BASE_URL", "http://localhost:8000")
+        .trim_end_matches('/')
+        .to_string(),
+      default_redirect_to: get_env_var(
+        "APPFLOWY_SAML_DEFAULT_REDIRECT_TO",
+        "appflowy-flutter://login-callback",
+      ),
+      allowed_redirect_urls: get_env_var("APPFLOWY_SAML_ALLOWED_REDIRECT_URLS", "")
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect(),
+      dns_over_https_url: get_env_var(
+        "APPFLOWY_DNS_OVER_HTTPS_URL",
+        "https://cloudflare-dns.com/dns-query",
+      ),
+    },
   };
   Ok(config)
 }
 
+const DEFAULT_OEMBED_PROVIDERS: &str = "youtube.com|youtu.be=https://www.youtube.com/oembed;\
+  vimeo.com=https://vimeo.com/api/oembed.json;\
+  figma.com=https://www.figma.com/api/oembed;\
+  loom.com=https://www.loom.com/v1/oembed";
+
+/// The providers are separated by semicolons, each one listing its hosts separated by `|` before
+/// its endpoint, e.g. `youtube.com|youtu.be=https://www.youtube.com/oembed`.
+fn get_oembed_providers(value: &str) -> Result<Vec<OEmbedProvider>, anyhow::Error> {
+  let mut providers = vec![];
+  for provider in value
+    .split(';')
+    .map(str::trim)
+    .filter(|provider| !provider.is_empty())
+  {
+    let (hosts, endpoint) = provider
+      .split_once('=')
+      .with_context(|| format!("invalid oEmbed provider: {}", provider))?;
+    let endpoint = endpoint.trim().to_string();
+    if !endpoint.starts_with("https://") {
+      anyhow::bail!("oEmbed endpoint must be an https URL: {}", endpoint);
+    }
+    providers.push(OEmbedProvider {
+      hosts: hosts
+        .split('|')
+        .map(|host| host.trim().to_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect(),
+      endpoint,
+    });
+  }
+  Ok(providers)
+}
+
+/// The notice days are separated by commas, e.g. `30,7,1`.
+fn get_deletion_notice_days(value: &str) -> Result<Vec<u64>, anyhow::Error> {
+  let mut days = vec![];
+  for day in value
+    .split(',')
+    .map(str::trim)
+    .filter(|day| !day.is_empty())
+  {
+    days.push(
+      day
+        .parse()
+        .with_context(|| format!("invalid deletion notice day: {}", day))?,
+    );
+  }
+  days.sort_unstable_by(|a, b| b.cmp(a));
+  days.dedup();
+  Ok(days)
+}
+
+/// The read replica is configured with `APPFLOWY_DATABASE_READ_REPLICA_URL`. The analytics reports
+/// run on the default database if it is not set.
+fn get_read_replica_db_settings() -> Result<Option<DatabaseSetting>, anyhow::Error> {
+  let database_url = match get_env_var_opt("APPFLOWY_DATABASE_READ_REPLICA_URL") {
+    Some(database_url) if !database_url.is_empty() => database_url,
+    _ => return Ok(None),
+  };
+  Ok(Some(DatabaseSetting {
+    pg_conn_opts: PgConnectOptions::from_str(&database_url)?,
+    require_ssl: get_env_var("APPFLOWY_DATABASE_REQUIRE_SSL", "false")
+      .parse()
+      .context("fail to get APPFLOWY_DATABASE_REQUIRE_SSL")?,
+    max_connections: get_env_var("APPFLOWY_DATABASE_READ_REPLICA_MAX_CONNECTIONS", "10")
+      .parse()
+      .context("fail to get APPFLOWY_DATABASE_READ_REPLICA_MAX_CONNECTIONS")?,
+  }))
+}
+
+/// Regions are listed in `APPFLOWY_RESIDENCY_REGIONS`, separated by commas. The storage of each
+/// region is configured with `APPFLOWY_RESIDENCY_<REGION>_DATABASE_URL`,
+/// `APPFLOWY_RESIDENCY_<REGION>_S3_BUCKET` and `APPFLOWY_RESIDENCY_<REGION>_S3_REGION`.
+fn get_residency_setting() -> Result<ResidencySetting, anyhow::Error> {
+  let mut regions = vec![];
+  for name in get_env_var("APPFLOWY_RESIDENCY_REGIONS", "")
+    .split(',')
+    .map(str::trim)
+    .filter(|name| !name.is_empty())
+  {
+    let prefix = format!(
+      "APPFLOWY_RESIDENCY_{}",
+      name.to_uppercase().replace('-', "_")
+    );
+    let database_url = get_env_var_opt(&format!("{}_DATABASE_URL", prefix))
+      .with_context(|| format!("fail to get {}_DATABASE_URL", prefix))?;
+    let db_settings = DatabaseSetting {
+      pg_conn_opts: PgConnectOptions::from_str(&database_url)?,
+      require_ssl: get_env_var("APPFLOWY_DATABASE_REQUIRE_SSL", "false")
+        .parse()
+        .context("fail to get APPFLOWY_DATABASE_REQUIRE_SSL")?,
+      max_connections: get_env_var("APPFLOWY_DATABASE_MAX_CONNECTIONS", "40")
+        .parse()
+        .context("fail to get APPFLOWY_DATABASE_MAX_CONNECTIONS")?,
+    };
+    let s3 = S3Setting {
+      create_bucket: get_env_var("APPFLOWY_S3_CREATE_BUCKET", "true")
+        .parse()
+        .context("fail to get APPFLOWY_S3_CREATE_BUCKET")?,
+      use_minio: get_env_var("APPFLOWY_S3_USE_MINIO", "true")
+        .parse()
+        .context("fail to get APPFLOWY_S3_USE_MINIO")?,
+      minio_url: get_env_var("APPFLOWY_S3_MINIO_URL", "http://localhost:9000"),
+      access_key: get_env_var("APPFLOWY_S3_ACCESS_KEY", "minioadmin"),
+      secret_key: get_env_var("APPFLOWY_S3_SECRET_KEY", "minioadmin").into(),
+      bucket: get_env_var(
+        &format!("{}_S3_BUCKET", prefix),
+        &format!("{}-{}", get_env_var("APPFLOWY_S3_BUCKET", "appflowy"), name),
+      ),
+      region: get_env_var(
+        &format!("{}_S3_REGION", prefix),
+        &get_env_var("APPFLOWY_S3_REGION", ""),
+      ),
+    };
+    regions.push(RegionSetting {
+      name: name.to_string(),
+      db_settings,
+      s3,
+    });
+  }
+  Ok(ResidencySetting { regions })
+}
+
+/// Shards are listed in `APPFLOWY_COLLAB_SHARDS`, separated by commas. The database of each shard
+/// is configured with `APPFLOWY_COLLAB_SHARD_<SHARD>_DATABASE_URL`.
+fn get_collab_shard_setting() -> Result<CollabShardSetting, anyhow::Error> {
+  let mut shards = vec![];
+  for name in get_env_var("APPFLOWY_COLLAB_SHARDS", "")
+    .split(',')
+    .map(str::trim)
+    .filter(|name| !name.is_empty())
+  {
+    if name == DEFAULT_COLLAB_SHARD {
+      anyhow::bail!("The collab shard name {} is reserved", DEFAULT_COLLAB_SHARD);
+    }
+    let prefix = format!(
+      "APPFLOWY_COLLAB_SHARD_{}",
+      name.to_uppercase().replace('-', "_")
+    );
+    let database_url = get_env_var_opt(&format!("{}_DATABASE_URL", prefix))
+      .with_context(|| format!("fail to get {}_DATABASE_URL", prefix))?;
+    let db_settings = DatabaseSetting {
+      pg_conn_opts: PgConnectOptions::from_str(&database_url)?,
+      require_ssl: get_env_var("APPFLOWY_DATABASE_REQUIRE_SSL", "false")
+        .parse()
+        .context("fail to get APPFLOWY_DATABASE_REQUIRE_SSL")?,
+      max_connections: get_env_var("APPFLOWY_DATABASE_MAX_CONNECTIONS", "40")
+        .parse()
+        .context("fail to get APPFLOWY_DATABASE_MAX_CONNECTIONS")?,
+    };
+    shards.push(CollabShardDbSetting {
+      name: name.to_string(),
+      db_settings,
+    });
+  }
+  Ok(CollabShardSetting { shards })
+}
+
 /// The possible runtime environment for our application.
 #[derive(Clone, Debug, Deserialize)]
 pub enum Environment {
@@ -5,6 +5,13 @@ pub const WORKSPACE_INVITE_TEMPLATE_NAME: &str = "workspace_invite";
 pub const WORKSPACE_ACCESS_REQUEST_TEMPLATE_NAME: &str = "workspace_access_request";
 pub const WORKSPACE_ACCESS_REQUEST_APPROVED_NOTIFICATION_TEMPLATE_NAME: &str =
   "workspace_access_request_approved_notification";
+pub const WORKSPACE_ACCESS_EXPIRING_NOTIFICATION_TEMPLATE_NAME: &str =
+  "workspace_access_expiring_notification";
+pub const COMMENT_SUBSCRIPTION_CONFIRMATION_TEMPLATE_NAME: &str =
+  "comment_subscription_confirmation";
+pub const NEW_COMMENT_NOTIFICATION_TEMPLATE_NAME: &str = "new_comment_notification";
+pub const SECRET_DETECTED_NOTIFICATION_TEMPLATE_NAME: &str = "secret_detected_notification";
+pub const WORKSPACE_LIFECYCLE_NOTIFICATION_TEMPLATE_NAME: &str = "workspace_lifecycle_notification";
 
 #[derive(Clone)]
 pub struct AFCloudMailer(Mailer);
@@ -75,6 +82,114 @@ impl AFCloudMailer {
       )
       .await
   }
+
+  pub async fn send_workspace_access_expiring_notification(
+    &self,
+    recipient_name: &str,
+    email: &str,
+    param: WorkspaceAccessExpiringMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "Notification: Your access to {} is expiring soon",
+      param.workspace_name
+    );
+    self
+      .0
+      .send_email_template(
+        Some(recipient_name.to_string()),
+        email,
+        WORKSPACE_ACCESS_EXPIRING_NOTIFICATION_TEMPLATE_NAME,
+        param,
+        &subject,
+      )
+      .await
+  }
+
+  pub async fn send_comment_subscription_confirmation(
+    &self,
+    email: &str,
+    param: CommentSubscriptionConfirmationMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "Action required: Confirm your subscription to the comments on {}",
+      param.view_name
+    );
+    self
+      .0
+      .send_email_template(
+        None,
+        email,
+        COMMENT_SUBSCRIPTION_CONFIRMATION_TEMPLATE_NAME,
+        param,
+        &subject,
+      )
+      .await
+  }
+
+  pub async fn send_new_comment_notification(
+    &self,
+    email: &str,
+    param: NewCommentNotificationMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "Notification: {} commented on {}",
+      param.commenter_name, param.view_name
+    );
+    self
+      .0
+      .send_email_template(
+        None,
+        email,
+        NEW_COMMENT_NOTIFICATION_TEMPLATE_NAME,
+        param,
+        &subject,
+      )
+      .await
+  }
+
+  pub async fn send_secret_detected_notification(
+    &self,
+    recipient_name: &str,
+    email: &str,
+    param: SecretDetectedNotificationMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "Notification: A secret was detected in a document of {}",
+      param.workspace_name
+    );
+    self
+      .0
+      .send_email_template(
+        Some(recipient_name.to_string()),
+        email,
+        SECRET_DETECTED_NOTIFICATION_TEMPLATE_NAME,
+        param,
+        &subject,
+      )
+      .await
+  }
+
+  pub async fn send_workspace_lifecycle_notification(
+    &self,
+    recipient_name: &str,
+    email: &str,
+    param: WorkspaceLifecycleNotificationMailerParam,
+  ) -> Result<(), anyhow::Error> {
+    let subject = format!(
+      "Notification: Your workspace {} {}",
+      param.workspace_name, param.message
+    );
+    self
+      .0
+      .send_email_template(
+        Some(recipient_name.to_string()),
+        email,
+        WORKSPACE_LIFECYCLE_NOTIFICATION_TEMPLATE_NAME,
+        param,
+        &subject,
+      )
+      .await
+  }
 }
 
 async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
@@ -85,6 +200,18 @@ async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
   let access_request_approved_notification_template = include_str!(
     "../assets/mailer_templates/build_production/access_request_approved_notification.html"
   );
+  let access_expiring_notification_template =
+    include_str!("../assets/mailer_templates/build_production/access_expiring_notification.html");
+  let comment_subscription_confirmation_template = include_str!(
+    "../assets/mailer_templates/build_production/comment_subscription_confirmation.html"
+  );
+  let new_comment_notification_template =
+    include_str!("../assets/mailer_templates/build_production/new_comment_notification.html");
+  let secret_detected_notification_template =
+    include_str!("../assets/mailer_templates/build_production/secret_detected_notification.html");
+  let workspace_lifecycle_notification_template = include_str!(
+    "../assets/mailer_templates/build_production/workspace_lifecycle_notification.html"
+  );
   let template_strings = HashMap::from([
     (WORKSPACE_INVITE_TEMPLATE_NAME, workspace_invite_template),
     (
@@ -95,6 +222,26 @@ async fn register_mailer(mailer: &mut Mailer) -> Result<(), anyhow::Error> {
       WORKSPACE_ACCESS_REQUEST_APPROVED_NOTIFICATION_TEMPLATE_NAME,
       access_request_approved_notification_template,
     ),
+    (
+      WORKSPACE_ACCESS_EXPIRING_NOTIFICATION_TEMPLATE_NAME,
+      access_expiring_notification_template,
+    ),
+    (
+      COMMENT_SUBSCRIPTION_CONFIRMATION_TEMPLATE_NAME,
+      comment_subscription_confirmation_template,
+    ),
+    (
+      NEW_COMMENT_NOTIFICATION_TEMPLATE_NAME,
+      new_comment_notification_template,
+    ),
+    (
+      SECRET_DETECTED_NOTIFICATION_TEMPLATE_NAME,
+      secret_detected_notification_template,
+    ),
+    (
+      WORKSPACE_LIFECYCLE_NOTIFICATION_TEMPLATE_NAME,
+      workspace_lifecycle_notification_template,
+    ),
   ]);
 
   for (template_name, template_string) in template_strings {
@@ -115,6 +262,8 @@ pub struct WorkspaceInviteMailerParam {
   pub workspace_icon_url: String,
   pub workspace_member_count: String,
   pub accept_url: String,
+  /// Personal message from the inviter shown in the invitation.
+  pub message: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -134,3 +283,51 @@ pub struct WorkspaceAccessRequestApprovedMailerParam {
   pub workspace_member_count: i64,
   pub launch_workspace_url: String,
 }
+
+#[derive(serde::Serialize)]
+pub struct WorkspaceAccessExpiringMailerParam {
+  pub workspace_name: String,
+  pub workspace_icon_url: String,
+  pub workspace_member_count: i64,
+  pub expires_at: String,
+  pub launch_workspace_url: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct CommentSubscriptionConfirmationMailerParam {
+  pub view_name: String,
+  pub view_url: String,
+  pub confirm_url: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct NewCommentNotificationMailerParam {
+  pub commenter_name: String,
+  pub comment_content: String,
+  pub view_name: String,
+  pub view_url: String,
+  pub unsubscribe_url: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct SecretDetectedNotificationMailerParam {
+  pub workspace_name: String,
+  pub author_name: String,
+  /// The kinds of the detected secrets, separated by commas.
+  pub secret_kinds: String,
+  /// What happened to the write containing the secrets.
+  pub action: String,
+  pub view_url: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct WorkspaceLifecycleNotificationMailerParam {
+  pub workspace_name: String,
+  pub workspace_icon_url: String,
+  pub workspace_member_count: i64,
+  /// What happened to the workspace, e.g. "has been inactive for 180 days".
+  pub message: String,
+  /// What the owner can do to keep the workspace.
+  pub action: String,
+  pub launch_workspace_url: String,
+}
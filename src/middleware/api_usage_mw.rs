@@ -0,0 +1,156 @@
+use actix_http::StatusCode;
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::Error;
+use app_error::ErrorCode;
+use dashmap::DashMap;
+use futures_util::future::LocalBoxFuture;
+use shared_entity::response::AppResponseError;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct ApiUsageCount {
+  pub call_count: i64,
+  pub client_error_count: i64,
+  pub server_error_count: i64,
+}
+
+/// Counts the API calls made on each workspace, by route family, until they are flushed to the
+/// database.
+#[derive(Default)]
+pub struct ApiUsageCounter {
+  counts: DashMap<(Uuid, String), ApiUsageCount>,
+}
+
+impl ApiUsageCounter {
+  pub fn record(&self, workspace_id: Uuid, route_family: String, status: StatusCode) {
+    let mut count = self.counts.entry((workspace_id, route_family)).or_default();
+    count.call_count += 1;
+    if status.is_client_error() {
+      count.client_error_count += 1;
+    } else if status.is_server_error() {
+      count.server_error_count += 1;
+    }
+  }
+
+  /// Removes and returns the calls counted since the last time.
+  pub fn take(&self) -> Vec<(Uuid, String, ApiUsageCount)> {
+    let keys = self
+      .counts
+      .iter()
+      .map(|entry| entry.key().clone())
+      .collect::<Vec<_>>();
+    keys
+      .into_iter()
+      .filter_map(|key| self.counts.remove(&key))
+      .map(|((workspace_id, route_family), count)| (workspace_id, route_family, count))
+      .collect()
+  }
+}
+
+/// Returns the route family of a route pattern: the scope of the API, followed by the first
+/// static segment after the workspace id if any, e.g. `workspace/collab` for
+/// `/api/workspace/v1/{workspace_id}/collab/{object_id}`. Returns `None` if the route is not
+/// specific to a workspace.
+pub fn route_family(pattern: &str) -> Option<String> {
+  let mut segments = pattern.trim_start_matches('/').split('/');
+  if segments.next() != Some("api") {
+    return None;
+  }
+  let scope = segments.next()?;
+  segments
+    .by_ref()
+    .find(|segment| *segment == "{workspace_id}")?;
+  match segments.find(|segment| !segment.starts_with('{')) {
+    Some(segment) => Some(format!("{}/{}", scope, segment)),
+    None => Some(scope.to_string()),
+  }
+}
+
+/// The application errors are sent with a 200 status, so their status is derived from their
+/// error code instead.
+fn response_status<B>(res: &ServiceResponse<B>) -> StatusCode {
+  match res
+    .response()
+    .error()
+    .and_then(|err| err.as_error::<AppResponseError>())
+  {
+    Some(err) if is_server_error(&err.code) => StatusCode::INTERNAL_SERVER_ERROR,
+    Some(_) => StatusCode::BAD_REQUEST,
+    None => res.status(),
+  }
+}
+
+fn is_server_error(code: &ErrorCode) -> bool {
+  matches!(
+    code,
+    ErrorCode::Unhandled
+      | ErrorCode::DBError
+      | ErrorCode::Internal
+      | ErrorCode::IOError
+      | ErrorCode::S3ResponseError
+      | ErrorCode::NetworkError
+      | ErrorCode::AIServiceUnavailable
+  )
+}
+
+/// Records the calls made on the routes of the workspaces in the [ApiUsageCounter].
+pub struct ApiUsageMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for ApiUsageMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = ApiUsageMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(ApiUsageMiddlewareService { service }))
+  }
+}
+
+pub struct ApiUsageMiddlewareService<S> {
+  service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiUsageMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let counter = req.app_data::<Data<Arc<ApiUsageCounter>>>().cloned();
+    let res = self.service.call(req);
+    Box::pin(async move {
+      let res = res.await?;
+      if let Some(counter) = counter {
+        // The path parameters are only known once the request is routed.
+        let request = res.request();
+        let workspace_id = request
+          .match_info()
+          .get("workspace_id")
+          .and_then(|workspace_id| Uuid::parse_str(workspace_id).ok());
+        let route_family = request.match_pattern().as_deref().and_then(route_family);
+        if let (Some(workspace_id), Some(route_family)) = (workspace_id, route_family) {
+          counter.record(workspace_id, route_family, response_status(&res));
+        }
+      }
+      Ok(res)
+    })
+  }
+}
@@ -0,0 +1,112 @@
+use actix_http::header::{HeaderMap, HeaderName, HeaderValue, WARNING};
+use actix_http::StatusCode;
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::body::EitherBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::{Error, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+use semver::Version;
+use shared_entity::dto::server_info_dto::{ClientVersionPolicy, ClientVersionStatus};
+use shared_entity::response::{
+  AppResponse, ErrorCode, X_CLIENT_UPGRADE_URL, X_CLIENT_VERSION_STATUS,
+};
+use std::future::{ready, Ready};
+
+use crate::state::AppState;
+
+/// Rejects the requests of the blocked client versions with `426 Upgrade Required`, and adds a
+/// warning to the responses of the deprecated client versions. The version of the client is
+/// read from the `client-version` header, the requests without it are left untouched.
+pub struct ClientVersionMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for ClientVersionMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Transform = ClientVersionMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(ClientVersionMiddlewareService { service }))
+  }
+}
+
+pub struct ClientVersionMiddlewareService<S> {
+  service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ClientVersionMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let policy = client_version_from_request(&req).and_then(|client_version| {
+      let state = req.app_data::<Data<AppState>>()?;
+      state.client_version_gate.check(&client_version)
+    });
+
+    match policy {
+      Some(policy) if policy.status == ClientVersionStatus::Blocked => {
+        let message = policy
+          .message
+          .clone()
+          .unwrap_or_else(|| "This version of the client is no longer supported".to_string());
+        let mut resp = HttpResponse::build(StatusCode::UPGRADE_REQUIRED).json(
+          AppResponse::<()>::new(ErrorCode::ClientVersionBlocked, message),
+        );
+        insert_client_version_headers(resp.headers_mut(), &policy);
+        let res = req.into_response(resp).map_into_right_body();
+        Box::pin(async move { Ok(res) })
+      },
+      policy => {
+        let res = self.service.call(req);
+        Box::pin(async move {
+          let mut res = res.await?;
+          if let Some(policy) = policy {
+            insert_client_version_headers(res.headers_mut(), &policy);
+          }
+          Ok(res.map_into_left_body())
+        })
+      },
+    }
+  }
+}
+
+fn client_version_from_request(req: &ServiceRequest) -> Option<Version> {
+  let client_version = req.headers().get("client-version")?.to_str().ok()?;
+  Version::parse(client_version).ok()
+}
+
+fn insert_client_version_headers(headers: &mut HeaderMap, policy: &ClientVersionPolicy) {
+  headers.insert(
+    HeaderName::from_static(X_CLIENT_VERSION_STATUS),
+    HeaderValue::from_static(policy.status.as_str()),
+  );
+  if let Some(upgrade_url) = policy
+    .upgrade_url
+    .as_deref()
+    .and_then(|url| HeaderValue::from_str(url).ok())
+  {
+    headers.insert(HeaderName::from_static(X_CLIENT_UPGRADE_URL), upgrade_url);
+  }
+  // Messages that can't be sent in a header, e.g. with non-ASCII characters, are left out.
+  if let Some(warning) = policy.message.as_deref().and_then(|message| {
+    HeaderValue::from_str(&format!("299 - \"{}\"", message.replace('"', "'"))).ok()
+  }) {
+    headers.insert(WARNING, warning);
+  }
+}
@@ -0,0 +1,86 @@
+use actix_router::{Path, ResourceDef};
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+use shared_entity::response::AppResponseError;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use uuid::Uuid;
+
+use crate::biz::workspace::ip_allowlist::{check_ip_allowlist, client_ip};
+use crate::state::AppState;
+
+/// Rejects the requests to the routes of a workspace that come from outside the IP allowlist of
+/// the workspace. The realtime connections are checked when they are established, see
+/// [crate::api::ws].
+pub struct IpAllowlistMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for IpAllowlistMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = IpAllowlistMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(IpAllowlistMiddlewareService {
+      service: Rc::new(service),
+    }))
+  }
+}
+
+pub struct IpAllowlistMiddlewareService<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for IpAllowlistMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let state = req.app_data::<Data<AppState>>().cloned();
+    let workspace_id = workspace_id_from_request(&req);
+    let service = self.service.clone();
+    Box::pin(async move {
+      if let (Some(state), Some(workspace_id)) = (state, workspace_id) {
+        let client_ip = client_ip(req.request(), state.config.client_ip.trusted_proxy_count);
+        check_ip_allowlist(
+          &state.ip_allowlists,
+          &state.pg_pool,
+          &workspace_id,
+          client_ip,
+        )
+        .await
+        .map_err(AppResponseError::from)?;
+      }
+      service.call(req).await
+    })
+  }
+}
+
+/// The path parameters are only extracted once the request is routed, after the middlewares of
+/// the app run, so the workspace id is matched against the pattern of the route instead.
+fn workspace_id_from_request(req: &ServiceRequest) -> Option<Uuid> {
+  let pattern = req.request().match_pattern()?;
+  if !pattern.contains("{workspace_id}") {
+    return None;
+  }
+  let mut path = Path::new(req.path());
+  ResourceDef::new(pattern).capture_match_info(&mut path);
+  Uuid::parse_str(path.get("workspace_id")?).ok()
+}
@@ -1,3 +1,7 @@
+pub mod api_usage_mw;
+pub mod client_version_mw;
 pub mod encrypt_mw;
+pub mod ip_allowlist_mw;
 pub mod metrics_mw;
+pub mod rate_limit_mw;
 pub mod request_id;
@@ -0,0 +1,237 @@
+use actix_http::header::{HeaderName, HeaderValue};
+use actix_service::{forward_ready, Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::web::Data;
+use actix_web::Error;
+use authentication::jwt::authorization_from_token;
+use dashmap::DashMap;
+use futures_util::future::LocalBoxFuture;
+use secrecy::Secret;
+use shared_entity::response::{
+  RateLimitInfo, X_RATE_LIMIT_LIMIT, X_RATE_LIMIT_REMAINING, X_RATE_LIMIT_RESET,
+};
+use std::future::{ready, Ready};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Counts the requests of each user in fixed windows.
+///
+/// The limit is soft: the requests beyond the limit are still served. The quota is only reported
+/// to the clients, so that they can throttle the bulk operations before a hard limit is enforced.
+pub struct RequestRateCounter {
+  limit: u32,
+  window_secs: i64,
+  windows: DashMap<Uuid, RequestWindow>,
+  /// The start of the window in which the windows of the previous periods were last removed.
+  last_purged_window: AtomicI64,
+}
+
+struct RequestWindow {
+  start: i64,
+  count: u32,
+}
+
+impl RequestRateCounter {
+  pub fn new(limit: u32, window: Duration) -> Self {
+    Self {
+      limit,
+      window_secs: window.as_secs().max(1) as i64,
+      windows: DashMap::new(),
+      last_purged_window: AtomicI64::new(0),
+    }
+  }
+
+  /// Counts a request of the user and returns the remaining quota of the current window.
+  pub fn hit(&self, user_uuid: Uuid) -> RateLimitInfo {
+    let now = chrono::Utc::now().timestamp();
+    let window_start = now - now % self.window_secs;
+    if self
+      .last_purged_window
+      .swap(window_start, Ordering::Relaxed)
+      != window_start
+    {
+      self
+        .windows
+        .retain(|_, window| window.start == window_start);
+    }
+
+    let mut window = self.windows.entry(user_uuid).or_insert(RequestWindow {
+      start: window_start,
+      count: 0,
+    });
+    if window.start != window_start {
+      window.start = window_start;
+      window.count = 0;
+    }
+    window.count = window.count.saturating_add(1);
+
+    RateLimitInfo {
+      limit: self.limit,
+      remaining: self.limit.saturating_sub(window.count),
+      reset_secs: (window_start + self.window_secs - now) as u64,
+    }
+  }
+}
+
+/// Throttles the messages sent by each device of a user with a token bucket.
+///
+/// Unlike [RequestRateCounter], the limit is hard: the messages beyond the limit are rejected, and
+/// the client is told how long to wait before sending them again.
+pub struct RealtimeMessageLimiter {
+  /// The number of tokens added to the buckets every second. 0 disables the limit.
+  rate: f64,
+  burst: f64,
+  buckets: DashMap<(i64, String), TokenBucket>,
+  /// When the buckets of the idle devices were last removed, in seconds since the epoch.
+  last_purged_at: AtomicI64,
+}
+
+struct TokenBucket {
+  tokens: f64,
+  updated_at: Instant,
+}
+
+/// How often the buckets of the devices that are idle long enough to refill them are removed.
+const REALTIME_BUCKET_PURGE_INTERVAL_SECS: i64 = 60;
+
+impl RealtimeMessageLimiter {
+  pub fn new(messages_per_sec: u32, burst: u32) -> Self {
+    Self {
+      rate: messages_per_sec as f64,
+      burst: burst.max(1) as f64,
+      buckets: DashMap::new(),
+      last_purged_at: AtomicI64::new(chrono::Utc::now().timestamp()),
+    }
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.rate > 0.0
+  }
+
+  /// Takes a token from the bucket of the device. Returns the number of seconds to wait before a
+  /// token is available if the bucket is empty.
+  pub fn acquire(&self, uid: i64, device_id: &str) -> Result<(), u64> {
+    if !self.is_enabled() {
+      return Ok(());
+    }
+    let now = Instant::now();
+    self.purge_idle_buckets(now);
+
+    let mut bucket = self
+      .buckets
+      .entry((uid, device_id.to_string()))
+      .or_insert(TokenBucket {
+        tokens: self.burst,
+        updated_at: now,
+      });
+    let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+    bucket.updated_at = now;
+    if bucket.tokens >= 1.0 {
+      bucket.tokens -= 1.0;
+      Ok(())
+    } else {
+      Err(((1.0 - bucket.tokens) / self.rate).ceil().max(1.0) as u64)
+    }
+  }
+
+  /// A full bucket is the same as no bucket, so the buckets that were refilled are dropped.
+  fn purge_idle_buckets(&self, now: Instant) {
+    let timestamp = chrono::Utc::now().timestamp();
+    let last_purged_at = self.last_purged_at.load(Ordering::Relaxed);
+    if timestamp - last_purged_at < REALTIME_BUCKET_PURGE_INTERVAL_SECS
+      || self
+        .last_purged_at
+        .compare_exchange(
+          last_purged_at,
+          timestamp,
+          Ordering::Relaxed,
+          Ordering::Relaxed,
+        )
+        .is_err()
+    {
+      return;
+    }
+    self.buckets.retain(|_, bucket| {
+      let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+      bucket.tokens + elapsed * self.rate < self.burst
+    });
+  }
+}
+
+/// Adds the `X-RateLimit-*` headers to the responses of the authenticated requests.
+pub struct RateLimitMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = RateLimitMiddlewareService<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(RateLimitMiddlewareService { service }))
+  }
+}
+
+pub struct RateLimitMiddlewareService<S> {
+  service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+  S::Future: 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let rate_limit = user_uuid_from_request(&req).and_then(|user_uuid| {
+      let counter = req.app_data::<Data<Arc<RequestRateCounter>>>()?;
+      Some(counter.hit(user_uuid))
+    });
+
+    let res = self.service.call(req);
+    Box::pin(async move {
+      let mut res = res.await?;
+      if let Some(rate_limit) = rate_limit {
+        let headers = res.headers_mut();
+        for (name, value) in [
+          (X_RATE_LIMIT_LIMIT, rate_limit.limit as u64),
+          (X_RATE_LIMIT_REMAINING, rate_limit.remaining as u64),
+          (X_RATE_LIMIT_RESET, rate_limit.reset_secs),
+        ] {
+          headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+        }
+      }
+      Ok(res)
+    })
+  }
+}
+
+/// Returns the user of the request, or `None` if the request is not authenticated.
+fn user_uuid_from_request(req: &ServiceRequest) -> Option<Uuid> {
+  let jwt_secret = req.app_data::<Data<Secret<String>>>()?;
+  let token = req
+    .headers()
+    .get("Authorization")?
+    .to_str()
+    .ok()?
+    .strip_prefix("Bearer ")?;
+  authorization_from_token(token, jwt_secret)
+    .ok()?
+    .uuid()
+    .ok()
+}
@@ -17,6 +17,7 @@ use appflowy_ai_client::client::AppFlowyAIClient;
 use appflowy_collaborate::collab::storage::CollabAccessControlStorage;
 use appflowy_collaborate::indexer::IndexerProvider;
 use appflowy_collaborate::metrics::CollabMetrics;
+use appflowy_collaborate::row_edit_intent::RowEditIntents;
 use appflowy_collaborate::CollabRealtimeMetrics;
 use database::file::s3_client_impl::{AwsS3BucketClientImpl, S3BucketStorage};
 use database::user::{select_all_uid_uuid, select_uid_from_uuid};
@@ -26,15 +27,27 @@ use snowflake::Snowflake;
 use tonic_proto::history::history_client::HistoryClient;
 
 use crate::api::metrics::{AppFlowyWebMetrics, PublishedCollabMetrics, RequestMetrics};
+use crate::biz::client_version::ClientVersionGate;
+use crate::biz::oembed::OEmbedResolver;
 use crate::biz::pg_listener::PgListeners;
+use crate::biz::scheduler::Scheduler;
+use crate::biz::user::user_access_token::AccessTokenStore;
+use crate::biz::user::user_device::UserDeviceTracker;
+use crate::biz::workspace::ip_allowlist::IpAllowlistCache;
+use crate::biz::workspace::public_access::PublicWorkspaceAccess;
 use crate::biz::workspace::publish::PublishedCollabStore;
+use crate::biz::workspace::residency::StorageRouter;
+use crate::biz::workspace::two_factor::WorkspaceTwoFactorPolicy;
 use crate::config::config::Config;
 use crate::mailer::AFCloudMailer;
+use crate::middleware::rate_limit_mw::RealtimeMessageLimiter;
 
 pub type RedisConnectionManager = redis::aio::ConnectionManager;
 #[derive(Clone)]
 pub struct AppState {
   pub pg_pool: PgPool,
+  /// The read replica of the default database, or the default database if there is none.
+  pub pg_read_pool: PgPool,
   pub config: Arc<Config>,
   pub user_cache: UserCache,
   pub id_gen: Arc<RwLock<Snowflake>>,
@@ -46,6 +59,7 @@ pub struct AppState {
   pub workspace_access_control: Arc<dyn WorkspaceAccessControl>,
   pub realtime_access_control: Arc<dyn RealtimeAccessControl>,
   pub bucket_storage: Arc<S3BucketStorage>,
+  pub storage_router: StorageRouter,
   pub published_collab_store: Arc<dyn PublishedCollabStore>,
   pub bucket_client: AwsS3BucketClientImpl,
   pub pg_listeners: Arc<PgListeners>,
@@ -55,6 +69,16 @@ pub struct AppState {
   pub ai_client: AppFlowyAIClient,
   pub grpc_history_client: Arc<Mutex<HistoryClient<tonic::transport::Channel>>>,
   pub indexer_provider: Arc<IndexerProvider>,
+  pub row_edit_intents: RowEditIntents,
+  pub client_version_gate: ClientVersionGate,
+  pub scheduler: Scheduler,
+  pub oembed_resolver: OEmbedResolver,
+  pub public_workspace_access: PublicWorkspaceAccess,
+  pub realtime_message_limiter: Arc<RealtimeMessageLimiter>,
+  pub user_devices: UserDeviceTracker,
+  pub access_tokens: AccessTokenStore,
+  pub ip_allowlists: Arc<IpAllowlistCache>,
+  pub two_factor_policy: Arc<WorkspaceTwoFactorPolicy>,
 }
 
 impl AppState {
@@ -113,6 +137,11 @@ impl UserCache {
     self.users.insert(*uuid, AuthenticateUser { uid });
     Ok(uid)
   }
+
+  /// Forgets the user, e.g. after the user was merged into another account.
+  pub fn remove_user(&self, uuid: &Uuid) {
+    self.users.remove(uuid);
+  }
 }
 
 #[derive(Clone)]
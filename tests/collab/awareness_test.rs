@@ -70,6 +70,73 @@ async fn viewing_document_editing_users_test() {
   assert_num_connected_client_within_secs(&owner, &object_id, 2, 30).await;
 }
 
+#[tokio::test]
+async fn get_collab_awareness_snapshot_test() {
+  let mut owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+  owner.wait_object_sync_complete(&object_id).await.unwrap();
+  // the snapshot is saved at most once per second
+  sleep(Duration::from_secs(2)).await;
+
+  let awareness = owner
+    .api_client
+    .get_collab_awareness(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(awareness.object_id, object_id);
+  assert!(awareness.updated_at.is_some());
+  let owner_uid = owner.uid().await;
+  assert!(awareness.states.iter().any(|client| client
+    .state
+    .get("uid")
+    .and_then(|uid| uid.as_i64())
+    == Some(owner_uid)));
+
+  // the users outside of the workspace can't see the presence
+  let stranger = TestClient::new_user().await;
+  let result = stranger
+    .api_client
+    .get_collab_awareness(&workspace_id, &object_id)
+    .await;
+  assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn get_collab_presence_test() {
+  let mut owner = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+  owner.wait_object_sync_complete(&object_id).await.unwrap();
+  // the snapshot is saved at most once per second
+  sleep(Duration::from_secs(2)).await;
+
+  let presence = owner
+    .api_client
+    .get_collab_presence(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(presence.object_id, object_id);
+  let owner_uid = owner.uid().await;
+  let user = presence
+    .users
+    .iter()
+    .find(|user| user.uid == owner_uid)
+    .unwrap();
+  assert!(user.name.is_some());
+
+  let stranger = TestClient::new_user().await;
+  let result = stranger
+    .api_client
+    .get_collab_presence(&workspace_id, &object_id)
+    .await;
+  assert!(result.is_err());
+}
+
 async fn assert_num_connected_client_within_secs(
   client: &TestClient,
   object_id: &str,
@@ -96,3 +163,35 @@ async fn assert_num_connected_client_within_secs(
     }
   }
 }
+
+#[tokio::test]
+async fn get_workspace_realtime_stats_test() {
+  let mut owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+  owner.wait_object_sync_complete(&object_id).await.unwrap();
+
+  let stats = owner
+    .api_client
+    .get_workspace_realtime_stats(&workspace_id)
+    .await
+    .unwrap();
+  assert!(stats.connected_users >= 1);
+  assert!(stats.active_collabs >= 1);
+  assert!(stats.active_sessions >= stats.connected_users);
+  assert!(stats.messages_per_sec >= 0.0);
+
+  // only the owner can see the realtime activity of the workspace
+  let result = member
+    .api_client
+    .get_workspace_realtime_stats(&workspace_id)
+    .await;
+  assert!(result.is_err());
+}
@@ -4,15 +4,19 @@ use collab::entity::EncodedCollab;
 use collab_document::document_data::default_document_collab_data;
 use collab_entity::CollabType;
 use database_entity::dto::{
-  CollabParams, CreateCollabParams, QueryCollab, QueryCollabParams, QueryCollabResult,
+  BatchQueryCollabPage, CollabParams, CreateCollabParams, QueryCollab, QueryCollabParams,
+  QueryCollabResult,
 };
+use futures::TryStreamExt;
 
-use reqwest::Method;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode};
 use serde::Serialize;
 use serde_json::json;
 
 use crate::collab::util::{generate_random_string, test_encode_collab_v1};
 use client_api_test::TestClient;
+use shared_entity::dto::workspace_dto::CollabTypeParam;
 use shared_entity::response::AppResponse;
 use uuid::Uuid;
 
@@ -37,6 +41,64 @@ async fn get_collab_response_compatible_test() {
   assert_eq!(collab_resp.encode_collab, encode_collab);
 }
 
+#[tokio::test]
+async fn get_collab_with_etag_test() {
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let url = format!(
+    "{}/api/workspace/v1/{}/collab/{}",
+    test_client.api_client.base_url, workspace_id, workspace_id
+  );
+  let get_folder = |etag: Option<String>| {
+    let url = url.clone();
+    let api_client = test_client.api_client.clone();
+    async move {
+      let mut builder = api_client
+        .http_client_with_auth(Method::GET, &url)
+        .await
+        .unwrap()
+        .query(&CollabTypeParam {
+          collab_type: CollabType::Folder,
+        });
+      if let Some(etag) = etag {
+        builder = builder.header(IF_NONE_MATCH, etag);
+      }
+      builder.send().await.unwrap()
+    }
+  };
+
+  let resp = get_folder(None).await;
+  assert_eq!(resp.status(), StatusCode::OK);
+  let etag = resp.headers()[ETAG].to_str().unwrap().to_string();
+
+  // The folder didn't change, so it's not sent again
+  let resp = get_folder(Some(etag.clone())).await;
+  assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+  assert_eq!(resp.headers()[ETAG].to_str().unwrap(), etag);
+
+  let resp = get_folder(Some("\"outdated\"".to_string())).await;
+  assert_eq!(resp.status(), StatusCode::OK);
+
+  // The client serves the unchanged collab from its cache
+  let first = test_client
+    .get_collab(
+      workspace_id.clone(),
+      workspace_id.clone(),
+      CollabType::Folder,
+    )
+    .await
+    .unwrap();
+  let second = test_client
+    .get_collab(
+      workspace_id.clone(),
+      workspace_id.clone(),
+      CollabType::Folder,
+    )
+    .await
+    .unwrap();
+  assert_eq!(first.encode_collab, second.encode_collab);
+}
+
 #[tokio::test]
 #[should_panic]
 async fn create_collab_workspace_id_equal_to_object_id_test() {
@@ -123,6 +185,80 @@ async fn batch_insert_collab_success_test() {
   assert_eq!(result.0.values().len(), 5);
 }
 
+#[tokio::test]
+async fn batch_get_collab_stream_test() {
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+
+  // more than a chunk of the streamed queries
+  let params_list = (0..60)
+    .map(|i| {
+      let encoded_collab_v1 =
+        test_encode_collab_v1(&i.to_string(), "title", &generate_random_string(1024));
+      CollabParams {
+        object_id: Uuid::new_v4().to_string(),
+        encoded_collab_v1: encoded_collab_v1.encode_to_bytes().unwrap().into(),
+        collab_type: CollabType::Unknown,
+        embeddings: None,
+      }
+    })
+    .collect::<Vec<_>>();
+  test_client
+    .create_collab_list(&workspace_id, params_list.clone())
+    .await
+    .unwrap();
+  let queries = params_list
+    .iter()
+    .map(|params| QueryCollab::new(&params.object_id, params.collab_type.clone()))
+    .collect::<Vec<_>>();
+
+  let items = test_client
+    .api_client
+    .batch_get_collab_stream(&workspace_id, queries.clone(), None)
+    .await
+    .unwrap()
+    .try_collect::<Vec<_>>()
+    .await
+    .unwrap();
+  assert_eq!(items.len(), 60);
+  for item in items {
+    let params = params_list
+      .iter()
+      .find(|params| params.object_id == item.object_id)
+      .unwrap();
+    assert_eq!(
+      item.result,
+      QueryCollabResult::Success {
+        encode_collab_v1: params.encoded_collab_v1.to_vec()
+      }
+    );
+  }
+
+  let page = BatchQueryCollabPage {
+    offset: Some(50),
+    limit: Some(20),
+  };
+  let items = test_client
+    .api_client
+    .batch_get_collab_stream(&workspace_id, queries, Some(page))
+    .await
+    .unwrap()
+    .try_collect::<Vec<_>>()
+    .await
+    .unwrap();
+  let mut object_ids = items
+    .into_iter()
+    .map(|item| item.object_id)
+    .collect::<Vec<_>>();
+  object_ids.sort();
+  let mut expected = params_list[50..]
+    .iter()
+    .map(|params| params.object_id.clone())
+    .collect::<Vec<_>>();
+  expected.sort();
+  assert_eq!(object_ids, expected);
+}
+
 #[tokio::test]
 async fn create_collab_params_compatibility_serde_test() {
   // This test is to make sure that the CreateCollabParams is compatible with the old InsertCollabParams
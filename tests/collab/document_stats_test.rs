@@ -0,0 +1,46 @@
+use client_api_test::*;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database_entity::dto::CreateCollabParams;
+use uuid::Uuid;
+use workspace_template::document::getting_started::getting_started_document_data;
+
+#[tokio::test]
+async fn get_document_stats_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Document,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let stats = c
+    .get_document_stats(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert!(stats.word_count > 0);
+  assert!(stats.character_count >= stats.word_count);
+  assert!(stats.reading_time_secs > 0);
+  assert!(stats.block_counts.get("paragraph").copied().unwrap_or(0) > 0);
+
+  // The stats of the unchanged document are served from the cache
+  let cached_stats = c
+    .get_document_stats(&workspace_id, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(cached_stats.word_count, stats.word_count);
+  assert_eq!(cached_stats.block_counts, stats.block_counts);
+}
@@ -0,0 +1,200 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use client_api::Client;
+use client_api_test::*;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_document::document::Document;
+use collab_entity::CollabType;
+use database_entity::dto::CreateCollabParams;
+use encrypt::envelope::{is_sealed, EnvelopeKeyPair};
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{
+  CollabExportFormat, ExportCollabParams, ExportDecryption, ExportEncryption, ExportedDocument,
+  ImportPageFormat, ImportPageParams,
+};
+use std::time::Duration;
+use tokio::time::sleep;
+use uuid::Uuid;
+use workspace_template::document::getting_started::getting_started_document_data;
+
+#[tokio::test]
+async fn export_document_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Document,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let markdown = c
+    .export_document(&workspace_id, &object_id, CollabExportFormat::Markdown)
+    .await
+    .unwrap();
+  assert!(markdown.starts_with("## Welcome to AppFlowy\n"));
+  assert!(markdown.contains("\n---\n"));
+  assert!(markdown.contains("- [ ] [Twitter](https://twitter.com/"));
+  // The children of the toggle list are nested under it
+  assert!(markdown.contains("- Download for macOS, Windows, and Linux\n  "));
+
+  let json = c
+    .export_document(&workspace_id, &object_id, CollabExportFormat::Json)
+    .await
+    .unwrap();
+  let document: ExportedDocument = serde_json::from_str(&json).unwrap();
+  assert_eq!(document.object_id, object_id);
+  let heading = &document.blocks[0];
+  assert_eq!(heading.ty, "heading");
+  assert_eq!(heading.data.get("level"), Some(&json!(2)));
+  assert_eq!(
+    heading.delta,
+    vec![json!({ "insert": "Welcome to AppFlowy" })]
+  );
+  let toggle_list = document
+    .blocks
+    .iter()
+    .find(|block| block.ty == "toggle_list")
+    .unwrap();
+  assert_eq!(toggle_list.children.len(), 1);
+}
+
+#[tokio::test]
+async fn export_document_of_other_workspace_test() {
+  let (owner, _) = generate_unique_registered_user_client().await;
+  let (other, _) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&owner).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  owner
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      collab_type: CollabType::Document,
+      workspace_id: workspace_id.clone(),
+      encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+    })
+    .await
+    .unwrap();
+
+  let result = other
+    .export_document(&workspace_id, &object_id, CollabExportFormat::Markdown)
+    .await;
+  assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn export_encrypted_document_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encoded_collab = {
+    let collab = Collab::new_with_origin(CollabOrigin::Empty, &object_id, vec![], false);
+    let document =
+      Document::create_with_data(collab, getting_started_document_data().unwrap()).unwrap();
+    document.encode_collab().unwrap()
+  };
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Document,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encoded_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+  let markdown = c
+    .export_document(&workspace_id, &object_id, CollabExportFormat::Markdown)
+    .await
+    .unwrap();
+
+  // Encrypted with a passphrase
+  let encrypted = c
+    .export_document_with_params(
+      &workspace_id,
+      &object_id,
+      &ExportCollabParams {
+        format: CollabExportFormat::Markdown,
+        encryption: Some(ExportEncryption::Passphrase {
+          passphrase: "correct horse".to_string(),
+        }),
+      },
+    )
+    .await
+    .unwrap();
+  assert!(is_sealed(&encrypted));
+  let decrypted = Client::decrypt_export(
+    &encrypted,
+    &ExportDecryption::Passphrase {
+      passphrase: "correct horse".to_string(),
+    },
+  )
+  .unwrap();
+  assert_eq!(String::from_utf8(decrypted).unwrap(), markdown);
+  assert!(Client::decrypt_export(
+    &encrypted,
+    &ExportDecryption::Passphrase {
+      passphrase: "wrong horse".to_string(),
+    },
+  )
+  .is_err());
+
+  // Encrypted with a public key, then imported with the secret key
+  let key_pair = EnvelopeKeyPair::generate();
+  let encrypted = c
+    .export_document_with_params(
+      &workspace_id,
+      &object_id,
+      &ExportCollabParams {
+        format: CollabExportFormat::Markdown,
+        encryption: Some(ExportEncryption::PublicKey {
+          public_key: key_pair.public_key.clone(),
+        }),
+      },
+    )
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let mut params = ImportPageParams {
+    parent_view_id: general_space.view_id,
+    name: None,
+    format: ImportPageFormat::Markdown,
+    content: STANDARD.encode(&encrypted),
+    decryption: Some(ExportDecryption::Passphrase {
+      passphrase: "correct horse".to_string(),
+    }),
+  };
+  assert!(c.import_page(workspace_uuid, &params).await.is_err());
+  params.decryption = Some(ExportDecryption::SecretKey {
+    secret_key: key_pair.secret_key,
+  });
+  let page = c.import_page(workspace_uuid, &params).await.unwrap();
+  sleep(Duration::from_secs(1)).await;
+  let view = c
+    .get_workspace_folder_view(&workspace_id, &page.view_id)
+    .await
+    .unwrap();
+  assert_eq!(view.name, "Welcome to AppFlowy");
+}
@@ -63,6 +63,7 @@ async fn update_collab_member_permission_test() {
     workspace_id: workspace_id.clone(),
     object_id: object_id.clone(),
     access_level: AFAccessLevel::ReadOnly,
+    expires_at: None,
   })
   .await
   .unwrap();
@@ -106,6 +107,7 @@ async fn add_collab_member_test() {
       workspace_id: workspace_id.clone(),
       object_id: object_id.clone(),
       access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
     })
     .await
     .unwrap();
@@ -152,6 +154,7 @@ async fn add_collab_member_then_remove_test() {
       workspace_id: workspace_id.clone(),
       object_id: object_id.clone(),
       access_level: AFAccessLevel::ReadAndComment,
+      expires_at: None,
     })
     .await
     .unwrap();
@@ -1,10 +1,16 @@
 mod awareness_test;
 mod collab_curd_test;
+mod document_stats_test;
+mod export_test;
 mod member_crud;
 mod missing_update_test;
 mod multi_devices_edit;
 mod permission_test;
+mod redaction_test;
+mod row_edit_intent_test;
+mod share_token_test;
 mod single_device_edit;
+mod snapshot_restore_test;
 mod storage_test;
 pub mod util;
 mod web_edit;
@@ -0,0 +1,98 @@
+use app_error::ErrorCode;
+use client_api_test::*;
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use database_entity::dto::{CreateCollabParams, QueryCollabParams};
+use shared_entity::dto::workspace_dto::RedactCollabParams;
+use uuid::Uuid;
+
+use crate::collab::util::test_encode_collab_v1;
+
+#[tokio::test]
+async fn admin_redact_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "my phone number is 555-0100");
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let admin = admin_user_client().await;
+  let redaction = admin
+    .redact_collab(
+      &workspace_id,
+      &object_id,
+      &RedactCollabParams {
+        collab_type: CollabType::Unknown,
+        texts: vec!["555-0100".to_string()],
+        block_ids: vec![],
+        reason: "personal data removal request".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(redaction.object_id, object_id);
+  assert_eq!(redaction.span_count, 1);
+  assert_eq!(redaction.reason, "personal data removal request");
+
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  let collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &object_id,
+    DataSource::DocStateV1(doc_state.to_vec()),
+    vec![],
+    false,
+  )
+  .unwrap();
+  let json = collab.to_json_value();
+  assert_eq!(json["title"], "my phone number is [REDACTED]");
+}
+
+#[tokio::test]
+async fn non_admin_redact_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+
+  // Even the owner of the workspace can't redact the collab.
+  let error = c
+    .redact_collab(
+      &workspace_id,
+      &object_id,
+      &RedactCollabParams {
+        collab_type: CollabType::Unknown,
+        texts: vec!["hello".to_string()],
+        block_ids: vec![],
+        reason: "personal data removal request".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use client_api_test::TestClient;
+use collab_rt_entity::RowEditIntent;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn row_edit_intent_test() {
+  let client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let database_id = Uuid::new_v4().to_string();
+  let row_id = Uuid::new_v4().to_string();
+
+  client
+    .ws_client
+    .send_row_edit_intent(RowEditIntent {
+      workspace_id: workspace_id.clone(),
+      database_id: database_id.clone(),
+      row_id: row_id.clone(),
+      is_editing: true,
+    })
+    .unwrap();
+  sleep(Duration::from_secs(1)).await;
+
+  let presence = client
+    .api_client
+    .get_database_presence(&workspace_id, &database_id)
+    .await
+    .unwrap();
+  assert_eq!(presence.row_edit_intents.len(), 1);
+  assert_eq!(presence.row_edit_intents[0].row_id, row_id);
+  assert_eq!(presence.row_edit_intents[0].uid, client.uid().await);
+
+  client
+    .ws_client
+    .send_row_edit_intent(RowEditIntent {
+      workspace_id: workspace_id.clone(),
+      database_id: database_id.clone(),
+      row_id,
+      is_editing: false,
+    })
+    .unwrap();
+  sleep(Duration::from_secs(1)).await;
+
+  let presence = client
+    .api_client
+    .get_database_presence(&workspace_id, &database_id)
+    .await
+    .unwrap();
+  assert!(presence.row_edit_intents.is_empty());
+}
@@ -0,0 +1,217 @@
+use app_error::ErrorCode;
+use client_api::entity::QueryCollabParams;
+use client_api_test::{assert_server_collab, localhost_client, TestClient};
+use collab_entity::CollabType;
+use database_entity::dto::AFRole;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::CreateCollabShareTokenParams;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn share_token_grants_read_access_to_a_single_collab_test() {
+  let collab_type = CollabType::Unknown;
+  let mut app_client = TestClient::new_user().await;
+  let workspace_id = app_client.workspace_id().await;
+  let object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  let other_object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  app_client.insert_into(&object_id, "title", "draft").await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    json!({ "title": "draft" }),
+  )
+  .await
+  .unwrap();
+
+  let share_token = app_client
+    .api_client
+    .create_collab_share_token(
+      &workspace_id,
+      &object_id,
+      &CreateCollabShareTokenParams::default(),
+    )
+    .await
+    .unwrap();
+
+  // the reviewer doesn't need an account
+  let reviewer = localhost_client();
+  let collab = reviewer
+    .get_collab_with_share_token(
+      &QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id),
+      &share_token.token,
+    )
+    .await
+    .unwrap();
+  assert_eq!(collab.object_id, object_id);
+
+  // the token only grants access to its collab
+  let err = reviewer
+    .get_collab_with_share_token(
+      &QueryCollabParams::new(&other_object_id, collab_type.clone(), &workspace_id),
+      &share_token.token,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let err = reviewer
+    .get_collab_with_share_token(
+      &QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id),
+      "not-a-share-token",
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  let err = app_client
+    .api_client
+    .create_collab_share_token(
+      &workspace_id,
+      &object_id,
+      &CreateCollabShareTokenParams {
+        expires_in_secs: Some(365 * 24 * 60 * 60),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn revoked_share_token_is_rejected_test() {
+  let collab_type = CollabType::Unknown;
+  let mut app_client = TestClient::new_user().await;
+  let workspace_id = app_client.workspace_id().await;
+  let object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  let share_token = app_client
+    .api_client
+    .create_collab_share_token(
+      &workspace_id,
+      &object_id,
+      &CreateCollabShareTokenParams::default(),
+    )
+    .await
+    .unwrap();
+  let query = QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id);
+  let reviewer = localhost_client();
+  reviewer
+    .get_collab_with_share_token(&query, &share_token.token)
+    .await
+    .unwrap();
+
+  app_client
+    .api_client
+    .revoke_collab_share_token(&workspace_id, &object_id, &share_token.token_id)
+    .await
+    .unwrap();
+  let err = reviewer
+    .get_collab_with_share_token(&query, &share_token.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  let err = app_client
+    .api_client
+    .revoke_collab_share_token(&workspace_id, &object_id, &share_token.token_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn share_tokens_of_removed_member_are_revoked_test() {
+  let collab_type = CollabType::Unknown;
+  let mut owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  let owner_token = owner
+    .api_client
+    .create_collab_share_token(
+      &workspace_id,
+      &object_id,
+      &CreateCollabShareTokenParams::default(),
+    )
+    .await
+    .unwrap();
+  let member_token = member
+    .api_client
+    .create_collab_share_token(
+      &workspace_id,
+      &object_id,
+      &CreateCollabShareTokenParams::default(),
+    )
+    .await
+    .unwrap();
+
+  let links = owner
+    .api_client
+    .list_workspace_share_links(workspace_uuid)
+    .await
+    .unwrap();
+  assert_eq!(links.collab_share_tokens.len(), 2);
+  // only the owners can review the links of the whole workspace
+  let err = member
+    .api_client
+    .list_workspace_share_links(workspace_uuid)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  owner
+    .api_client
+    .remove_workspace_members(&workspace_id, vec![member.email().await])
+    .await
+    .unwrap();
+  let query = QueryCollabParams::new(&object_id, collab_type.clone(), &workspace_id);
+  let reviewer = localhost_client();
+  let err = reviewer
+    .get_collab_with_share_token(&query, &member_token.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  reviewer
+    .get_collab_with_share_token(&query, &owner_token.token)
+    .await
+    .unwrap();
+
+  let links = owner
+    .api_client
+    .list_workspace_share_links(workspace_uuid)
+    .await
+    .unwrap();
+  assert_eq!(links.collab_share_tokens.len(), 1);
+  assert_eq!(links.collab_share_tokens[0].token_id, owner_token.token_id);
+
+  let revoked = owner
+    .api_client
+    .revoke_workspace_share_links(workspace_uuid, None)
+    .await
+    .unwrap();
+  assert_eq!(revoked.collab_share_tokens, 1);
+  let err = reviewer
+    .get_collab_with_share_token(&query, &owner_token.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
@@ -0,0 +1,155 @@
+use client_api_test::*;
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
+use collab_entity::CollabType;
+use database_entity::dto::{AFAccessLevel, CreateCollabParams, QueryCollabParams};
+use uuid::Uuid;
+
+use crate::collab::util::test_encode_collab_v1;
+
+fn collab_json(object_id: &str, doc_state: Vec<u8>) -> serde_json::Value {
+  Collab::new_with_source(
+    CollabOrigin::Empty,
+    object_id,
+    DataSource::DocStateV1(doc_state),
+    vec![],
+    false,
+  )
+  .unwrap()
+  .to_json_value()
+}
+
+#[tokio::test]
+async fn restore_collab_snapshot_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "first version");
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+  })
+  .await
+  .unwrap();
+  let snapshot = c
+    .create_snapshot(&workspace_id, &object_id, CollabType::Unknown)
+    .await
+    .unwrap();
+
+  // Edit the collab on top of the snapshotted state
+  let mut collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &object_id,
+    DataSource::DocStateV1(encode_collab.doc_state.to_vec()),
+    vec![],
+    false,
+  )
+  .unwrap();
+  collab.insert("title", "second version");
+  collab.insert("subtitle", "added later");
+  c.update_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+    encoded_collab_v1: collab
+      .encode_collab_v1(|_| Ok::<(), anyhow::Error>(()))
+      .unwrap()
+      .encode_to_bytes()
+      .unwrap(),
+  })
+  .await
+  .unwrap();
+
+  let backup = c
+    .restore_snapshot(
+      &workspace_id,
+      &object_id,
+      snapshot.snapshot_id,
+      CollabType::Unknown,
+    )
+    .await
+    .unwrap();
+  assert_ne!(backup.snapshot_id, snapshot.snapshot_id);
+
+  let doc_state = c
+    .get_collab(QueryCollabParams::new(
+      &object_id,
+      CollabType::Unknown,
+      &workspace_id,
+    ))
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  let json = collab_json(&object_id, doc_state.to_vec());
+  assert_eq!(json["title"], "first version");
+  assert!(json.get("subtitle").is_none());
+}
+
+#[tokio::test]
+async fn restore_snapshot_of_other_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let mut object_ids = vec![];
+  for _ in 0..2 {
+    let object_id = Uuid::new_v4().to_string();
+    let encode_collab = test_encode_collab_v1(&object_id, "title", "hello");
+    c.create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+    })
+    .await
+    .unwrap();
+    object_ids.push(object_id);
+  }
+  let snapshot = c
+    .create_snapshot(&workspace_id, &object_ids[0], CollabType::Unknown)
+    .await
+    .unwrap();
+
+  let err = c
+    .restore_snapshot(
+      &workspace_id,
+      &object_ids[1],
+      snapshot.snapshot_id,
+      CollabType::Unknown,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, app_error::ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn restore_snapshot_with_readonly_permission_test() {
+  let mut owner = TestClient::new_user().await;
+  let reader = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let object_id = owner
+    .create_and_edit_collab(&workspace_id, CollabType::Unknown)
+    .await;
+  let snapshot = owner
+    .api_client
+    .create_snapshot(&workspace_id, &object_id, CollabType::Unknown)
+    .await
+    .unwrap();
+  owner
+    .add_collab_member(&workspace_id, &object_id, &reader, AFAccessLevel::ReadOnly)
+    .await;
+
+  let err = reader
+    .api_client
+    .restore_snapshot(
+      &workspace_id,
+      &object_id,
+      snapshot.snapshot_id,
+      CollabType::Unknown,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, app_error::ErrorCode::NotEnoughPermissions);
+}
@@ -202,6 +202,58 @@ async fn success_delete_collab_test() {
   assert_eq!(error.code, ErrorCode::RecordNotFound);
 }
 
+#[tokio::test]
+async fn restore_and_purge_deleted_collab_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world")
+    .encode_to_bytes()
+    .unwrap();
+  c.create_collab(CreateCollabParams {
+    object_id: object_id.clone(),
+    encoded_collab_v1: encode_collab.clone(),
+    collab_type: CollabType::Unknown,
+    workspace_id: workspace_id.clone(),
+  })
+  .await
+  .unwrap();
+  let delete_params = DeleteCollabParams {
+    object_id: object_id.clone(),
+    workspace_id: workspace_id.clone(),
+  };
+  let query_params = QueryCollabParams::new(&object_id, CollabType::Unknown, &workspace_id);
+
+  // a deleted collab can be restored from the trash
+  c.delete_collab(delete_params.clone()).await.unwrap();
+  c.restore_collab(&workspace_id, &object_id).await.unwrap();
+  let collab = c.get_collab(query_params.clone()).await.unwrap();
+  assert_eq!(
+    collab.encode_collab.encode_to_bytes().unwrap(),
+    encode_collab
+  );
+
+  // only the collabs in the trash can be restored or purged
+  let error = c
+    .restore_collab(&workspace_id, &object_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+  let error = c.purge_collab(&workspace_id, &object_id).await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+
+  // a purged collab is gone for good
+  c.delete_collab(delete_params).await.unwrap();
+  c.purge_collab(&workspace_id, &object_id).await.unwrap();
+  let error = c
+    .restore_collab(&workspace_id, &object_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+  let error = c.get_collab(query_params).await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::RecordNotFound);
+}
+
 #[tokio::test]
 async fn fail_insert_collab_with_empty_payload_test() {
   let (c, _user) = generate_unique_registered_user_client().await;
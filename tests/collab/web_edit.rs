@@ -1,10 +1,17 @@
-use client_api::entity::{QueryCollab, QueryCollabParams, UpdateCollabWebParams};
+use app_error::ErrorCode;
+use client_api::entity::{
+  CollabUpdatesSinceParams, QueryCollab, QueryCollabParams, UpdateCollabWebParams,
+};
 use client_api_test::{
   assert_client_collab_within_secs, assert_server_collab, generate_unique_registered_user,
   TestClient,
 };
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
 use collab_entity::CollabType;
 use serde_json::json;
+use yrs::updates::encoder::Encode;
 use yrs::{updates::decoder::Decode, Map, ReadTxn, StateVector, Transact};
 
 #[tokio::test]
@@ -100,3 +107,282 @@ async fn web_and_native_app_edit_same_collab_test() {
   )
   .await;
 }
+
+#[tokio::test]
+async fn validate_web_update_without_persisting_test() {
+  let collab_type = CollabType::Unknown;
+  let registered_user = generate_unique_registered_user().await;
+  let mut app_client = TestClient::user_with_new_device(registered_user.clone()).await;
+  let workspace_id = app_client.workspace_id().await;
+  let object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  app_client
+    .insert_into(&object_id, "name", "workspace1")
+    .await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  let expected_json = json!({
+    "name": "workspace1"
+  });
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    expected_json.clone(),
+  )
+  .await
+  .unwrap();
+
+  let collab_doc_state = app_client
+    .api_client
+    .get_collab(QueryCollabParams {
+      workspace_id: workspace_id.clone(),
+      inner: QueryCollab {
+        object_id: object_id.clone(),
+        collab_type: collab_type.clone(),
+      },
+    })
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  let web_doc = yrs::Doc::new();
+  let update = yrs::Update::decode_v1(&collab_doc_state).unwrap();
+  web_doc.transact_mut().apply_update(update).unwrap();
+  let doc_data = web_doc.transact().get_map("data").unwrap();
+  {
+    let mut txn = web_doc.transact_mut();
+    doc_data.insert(&mut txn, "paragraph", "content");
+  }
+  let validation = app_client
+    .api_client
+    .validate_collab_update(
+      &workspace_id,
+      &object_id,
+      &UpdateCollabWebParams {
+        doc_state: web_doc
+          .transact()
+          .encode_state_as_update_v1(&StateVector::default()),
+        collab_type: collab_type.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  assert!(validation.decoded);
+  assert!(validation.applied);
+  assert!(validation.is_valid);
+  assert!(!validation.has_missing_dependencies);
+  assert_eq!(validation.changed_keys, vec!["paragraph".to_string()]);
+  assert!(validation.doc_state_size_after > validation.doc_state_size_before);
+
+  let validation = app_client
+    .api_client
+    .validate_collab_update(
+      &workspace_id,
+      &object_id,
+      &UpdateCollabWebParams {
+        doc_state: vec![255, 255, 255],
+        collab_type: collab_type.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!validation.decoded);
+  assert!(validation.error.is_some());
+
+  // The collab is left untouched
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    expected_json,
+  )
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn get_collab_updates_since_state_vector_test() {
+  let collab_type = CollabType::Unknown;
+  let mut app_client = TestClient::new_user().await;
+  let workspace_id = app_client.workspace_id().await;
+  let object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  app_client.insert_into(&object_id, "title", "first").await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    json!({ "title": "first" }),
+  )
+  .await
+  .unwrap();
+
+  // the web client loads the collab, then goes offline while the app keeps editing
+  let collab_doc_state = app_client
+    .api_client
+    .get_collab(QueryCollabParams {
+      workspace_id: workspace_id.clone(),
+      inner: QueryCollab {
+        object_id: object_id.clone(),
+        collab_type: collab_type.clone(),
+      },
+    })
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  let web_doc = yrs::Doc::new();
+  let update = yrs::Update::decode_v1(&collab_doc_state).unwrap();
+  web_doc.transact_mut().apply_update(update).unwrap();
+  let state_vector = web_doc.transact().state_vector().encode_v1();
+
+  app_client.insert_into(&object_id, "body", "second").await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    json!({ "title": "first", "body": "second" }),
+  )
+  .await
+  .unwrap();
+
+  let updates = app_client
+    .api_client
+    .get_collab_updates_since(
+      &workspace_id,
+      &object_id,
+      &CollabUpdatesSinceParams {
+        state_vector,
+        collab_type: collab_type.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  let update = yrs::Update::decode_v1(&updates.update).unwrap();
+  web_doc.transact_mut().apply_update(update).unwrap();
+  let txn = web_doc.transact();
+  let data = txn.get_map("data").unwrap();
+  assert_eq!(data.get(&txn, "body").unwrap().to_string(&txn), "second");
+  assert_eq!(txn.state_vector().encode_v1(), updates.state_vector);
+  drop(txn);
+
+  let err = app_client
+    .api_client
+    .get_collab_updates_since(
+      &workspace_id,
+      &object_id,
+      &CollabUpdatesSinceParams {
+        state_vector: vec![0xff, 0xff],
+        collab_type,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn get_collab_sync_state_test() {
+  let collab_type = CollabType::Unknown;
+  let mut app_client = TestClient::new_user().await;
+  let workspace_id = app_client.workspace_id().await;
+  let object_id = app_client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+  app_client.insert_into(&object_id, "title", "first").await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    json!({ "title": "first" }),
+  )
+  .await
+  .unwrap();
+
+  // a copy loaded from the server is in sync with it
+  let doc_state = app_client
+    .api_client
+    .get_collab(QueryCollabParams {
+      workspace_id: workspace_id.clone(),
+      inner: QueryCollab {
+        object_id: object_id.clone(),
+        collab_type: collab_type.clone(),
+      },
+    })
+    .await
+    .unwrap()
+    .encode_collab
+    .doc_state;
+  let copy = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &object_id,
+    DataSource::DocStateV1(doc_state.to_vec()),
+    vec![],
+    false,
+  )
+  .unwrap();
+  let sync_state = app_client
+    .api_client
+    .get_collab_sync_state(&workspace_id, &object_id, collab_type.clone())
+    .await
+    .unwrap();
+  assert!(sync_state.content_matches(&copy.to_json_value()));
+  assert_eq!(
+    StateVector::decode_v1(&sync_state.state_vector).unwrap(),
+    copy.transact().state_vector()
+  );
+
+  // the copy diverges once the collab is edited by another client
+  app_client.insert_into(&object_id, "body", "second").await;
+  app_client
+    .wait_object_sync_complete(&object_id)
+    .await
+    .unwrap();
+  assert_server_collab(
+    &workspace_id,
+    &mut app_client.api_client,
+    &object_id,
+    &collab_type,
+    30,
+    json!({ "title": "first", "body": "second" }),
+  )
+  .await
+  .unwrap();
+  let sync_state = app_client
+    .api_client
+    .get_collab_sync_state(&workspace_id, &object_id, collab_type)
+    .await
+    .unwrap();
+  assert!(!sync_state.content_matches(&copy.to_json_value()));
+  assert_ne!(
+    StateVector::decode_v1(&sync_state.state_vector).unwrap(),
+    copy.transact().state_vector()
+  );
+}
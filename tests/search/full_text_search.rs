@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use collab_entity::CollabType;
+use tokio::time::sleep;
+
+use client_api_test::TestClient;
+
+#[ignore]
+#[tokio::test]
+async fn test_full_text_search_getting_started() {
+  let mut test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let folder_view = test_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  test_client
+    .open_collab(
+      &workspace_id,
+      &getting_started.view_id,
+      CollabType::Document,
+    )
+    .await;
+
+  // the document gets indexed after opening if it wasn't indexed before
+  sleep(Duration::from_millis(2000)).await;
+
+  let results = test_client
+    .api_client
+    .full_text_search(&workspace_id, "appflowy", 10)
+    .await
+    .unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0].view_id, getting_started.view_id);
+  assert_eq!(results[0].title, "Getting started");
+  assert!(results[0].snippet.to_lowercase().contains("appflowy"));
+
+  let results = test_client
+    .api_client
+    .full_text_search(&workspace_id, "nonexistentword", 10)
+    .await
+    .unwrap();
+  assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_full_text_search_empty_query() {
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let err = test_client
+    .api_client
+    .full_text_search(&workspace_id, "  ", 10)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
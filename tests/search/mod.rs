@@ -1 +1,3 @@
 mod document_search;
+mod full_text_search;
+mod semantic_search;
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use collab_entity::CollabType;
+use tokio::time::sleep;
+
+use client_api_test::TestClient;
+
+#[ignore]
+#[tokio::test]
+async fn test_semantic_search_getting_started() {
+  let mut test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let folder_view = test_client
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .into_iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  test_client
+    .open_collab(
+      &workspace_id,
+      &getting_started.view_id,
+      CollabType::Document,
+    )
+    .await;
+
+  // the document gets indexed after opening if it wasn't indexed before
+  sleep(Duration::from_millis(2000)).await;
+
+  let results = test_client
+    .api_client
+    .semantic_search(&workspace_id, "Welcome to AppFlowy", 5)
+    .await
+    .unwrap();
+  let item = results
+    .iter()
+    .find(|item| item.view_id == getting_started.view_id)
+    .unwrap();
+  assert_eq!(item.title, "Getting started");
+  assert!(item.preview.is_some());
+}
+
+#[tokio::test]
+async fn test_semantic_search_empty_query() {
+  let test_client = TestClient::new_user().await;
+  let workspace_id = test_client.workspace_id().await;
+  let err = test_client
+    .api_client
+    .semantic_search(&workspace_id, "", 5)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
@@ -0,0 +1,122 @@
+use app_error::ErrorCode;
+use chrono::{Duration, Utc};
+use client_api_test::{admin_user_client, generate_unique_registered_user_client};
+use shared_entity::dto::server_info_dto::{AnnouncementParams, AnnouncementSeverity};
+
+fn announcement_params(title: &str) -> AnnouncementParams {
+  AnnouncementParams {
+    title: title.to_string(),
+    message: "The server will be down for maintenance".to_string(),
+    severity: AnnouncementSeverity::Warning,
+    starts_at: None,
+    ends_at: Some(Utc::now() + Duration::hours(1)),
+    dismissible: true,
+  }
+}
+
+#[tokio::test]
+async fn announcement_crud_and_dismissal() {
+  let admin = admin_user_client().await;
+  let (client, _) = generate_unique_registered_user_client().await;
+
+  let active = admin
+    .create_announcement(&announcement_params("Maintenance"))
+    .await
+    .unwrap();
+  let scheduled = admin
+    .create_announcement(&AnnouncementParams {
+      starts_at: Some(Utc::now() + Duration::minutes(30)),
+      ..announcement_params("Scheduled maintenance")
+    })
+    .await
+    .unwrap();
+  let pinned = admin
+    .create_announcement(&AnnouncementParams {
+      severity: AnnouncementSeverity::Critical,
+      dismissible: false,
+      ..announcement_params("Incident")
+    })
+    .await
+    .unwrap();
+
+  let ids = client
+    .get_announcements()
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|announcement| announcement.announcement_id)
+    .collect::<Vec<_>>();
+  assert!(ids.contains(&active.announcement_id));
+  assert!(ids.contains(&pinned.announcement_id));
+  assert!(!ids.contains(&scheduled.announcement_id));
+
+  client
+    .dismiss_announcement(&active.announcement_id)
+    .await
+    .unwrap();
+  let err = client
+    .dismiss_announcement(&pinned.announcement_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let ids = client
+    .get_announcements()
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|announcement| announcement.announcement_id)
+    .collect::<Vec<_>>();
+  assert!(!ids.contains(&active.announcement_id));
+  assert!(ids.contains(&pinned.announcement_id));
+
+  let updated = admin
+    .update_announcement(
+      &pinned.announcement_id,
+      &AnnouncementParams {
+        severity: AnnouncementSeverity::Info,
+        ..announcement_params("Incident resolved")
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(updated.title, "Incident resolved");
+  assert!(updated.dismissible);
+
+  for announcement in [active, scheduled, pinned] {
+    admin
+      .delete_announcement(&announcement.announcement_id)
+      .await
+      .unwrap();
+  }
+  let err = admin
+    .delete_announcement(&updated.announcement_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn announcement_management_requires_admin() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client
+    .create_announcement(&announcement_params("Maintenance"))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = client.list_all_announcements().await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn announcement_must_end_after_it_starts() {
+  let admin = admin_user_client().await;
+  let err = admin
+    .create_announcement(&AnnouncementParams {
+      starts_at: Some(Utc::now()),
+      ends_at: Some(Utc::now() - Duration::minutes(1)),
+      ..announcement_params("Maintenance")
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
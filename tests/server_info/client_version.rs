@@ -0,0 +1,134 @@
+use app_error::ErrorCode;
+use client_api::{Client, ClientConfiguration};
+use client_api_test::{
+  admin_user_client, generate_unique_registered_user, generate_unique_registered_user_client,
+  LOCALHOST_GOTRUE, LOCALHOST_URL, LOCALHOST_WS,
+};
+use reqwest::Method;
+use shared_entity::dto::server_info_dto::{ClientVersionStatus, UpsertClientVersionPolicyParams};
+use shared_entity::response::AppResponse;
+use uuid::Uuid;
+
+async fn registered_client_with_version(client_version: &str) -> Client {
+  let user = generate_unique_registered_user().await;
+  let client = Client::new(
+    &LOCALHOST_URL,
+    &LOCALHOST_WS,
+    &LOCALHOST_GOTRUE,
+    &Uuid::new_v4().to_string(),
+    ClientConfiguration::default(),
+    client_version,
+  );
+  client
+    .sign_in_password(&user.email, &user.password)
+    .await
+    .unwrap();
+  client
+}
+
+#[tokio::test]
+async fn deprecated_client_version_receives_warning() {
+  let admin = admin_user_client().await;
+  let version_req = "=0.9.91";
+  admin
+    .upsert_client_version_policy(&UpsertClientVersionPolicyParams {
+      version_req: version_req.to_string(),
+      status: ClientVersionStatus::Deprecated,
+      message: Some("Please upgrade to the latest version".to_string()),
+      upgrade_url: Some("https://appflowy.io/download".to_string()),
+    })
+    .await
+    .unwrap();
+
+  let client = registered_client_with_version("0.9.91").await;
+  let url = format!("{}/api/workspace", client.base_url);
+  let resp = client
+    .http_client_with_auth(Method::GET, &url)
+    .await
+    .unwrap()
+    .send()
+    .await
+    .unwrap();
+  let resp = AppResponse::<serde_json::Value>::from_response(resp)
+    .await
+    .unwrap();
+  assert!(resp.is_ok());
+  let warning = resp.client_version_warning.unwrap();
+  assert_eq!(warning.status, ClientVersionStatus::Deprecated);
+  assert_eq!(
+    warning.message.as_deref(),
+    Some("Please upgrade to the latest version")
+  );
+  assert_eq!(
+    warning.upgrade_url.as_deref(),
+    Some("https://appflowy.io/download")
+  );
+
+  admin
+    .delete_client_version_policy(version_req)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn blocked_client_version_is_rejected() {
+  let admin = admin_user_client().await;
+  let version_req = "=0.9.92";
+  let client = registered_client_with_version("0.9.92").await;
+  admin
+    .upsert_client_version_policy(&UpsertClientVersionPolicyParams {
+      version_req: version_req.to_string(),
+      status: ClientVersionStatus::Blocked,
+      message: Some("This version corrupts documents".to_string()),
+      upgrade_url: Some("https://appflowy.io/download".to_string()),
+    })
+    .await
+    .unwrap();
+
+  let error = client.get_workspaces().await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::ClientVersionBlocked);
+  assert_eq!(error.message, "This version corrupts documents");
+
+  // Other versions are still served.
+  let other_client = registered_client_with_version("0.9.93").await;
+  other_client.get_workspaces().await.unwrap();
+
+  admin
+    .delete_client_version_policy(version_req)
+    .await
+    .unwrap();
+  client.get_workspaces().await.unwrap();
+}
+
+#[tokio::test]
+async fn only_admin_can_manage_client_version_policies() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let error = client
+    .upsert_client_version_policy(&UpsertClientVersionPolicyParams {
+      version_req: "=0.9.94".to_string(),
+      status: ClientVersionStatus::Blocked,
+      message: None,
+      upgrade_url: None,
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  let error = client.list_client_version_policies().await.unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn invalid_client_version_requirement() {
+  let admin = admin_user_client().await;
+  let error = admin
+    .upsert_client_version_policy(&UpsertClientVersionPolicyParams {
+      version_req: "not a version".to_string(),
+      status: ClientVersionStatus::Deprecated,
+      message: None,
+      upgrade_url: None,
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
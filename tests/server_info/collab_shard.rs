@@ -0,0 +1,43 @@
+use app_error::ErrorCode;
+use client_api_test::{admin_user_client, generate_unique_registered_user_client};
+use shared_entity::dto::server_info_dto::{MigrateCollabParams, RebalanceCollabShardsParams};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn list_and_rebalance_collab_shards() {
+  let admin = admin_user_client().await;
+  let shards = admin.list_collab_shards().await.unwrap();
+  assert_eq!(shards[0].name, "default");
+
+  // Without additional shards, all the collabs already belong to the default shard.
+  if shards.len() == 1 {
+    let rebalance = admin
+      .rebalance_collab_shards(&RebalanceCollabShardsParams::default())
+      .await
+      .unwrap();
+    assert_eq!(rebalance.moved_count, 0);
+    assert!(rebalance.failed_object_ids.is_empty());
+    assert!(rebalance.next_cursor.is_none());
+  }
+
+  let err = admin
+    .migrate_collab_to_shard(&MigrateCollabParams {
+      object_id: Uuid::new_v4().to_string(),
+      shard: "unknown-shard".to_string(),
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn collab_shards_are_reserved_for_server_admins() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client.list_collab_shards().await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = client
+    .rebalance_collab_shards(&RebalanceCollabShardsParams::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
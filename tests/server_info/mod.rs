@@ -1 +1,8 @@
+mod announcement;
+mod client_version;
+mod collab_shard;
 mod info;
+mod provision;
+mod rate_limit;
+mod retry_policy;
+mod schedule;
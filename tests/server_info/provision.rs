@@ -0,0 +1,75 @@
+use app_error::ErrorCode;
+use client_api_test::{
+  admin_user_client, generate_unique_email, generate_unique_registered_user_client,
+  localhost_client,
+};
+use database_entity::dto::AFRole;
+use shared_entity::dto::server_info_dto::ProvisionAccountParams;
+use shared_entity::dto::workspace_dto::WorkspaceMemberInvitation;
+use uuid::Uuid;
+
+fn provision_params(idempotency_key: &str, email: &str) -> ProvisionAccountParams {
+  ProvisionAccountParams {
+    idempotency_key: idempotency_key.to_string(),
+    email: email.to_string(),
+    name: Some("Provisioned user".to_string()),
+    password: Some("Hello123!".to_string()),
+    workspace_name: Some("Class of 2025".to_string()),
+    invitations: vec![WorkspaceMemberInvitation {
+      email: generate_unique_email(),
+      role: AFRole::Member,
+    }],
+  }
+}
+
+#[tokio::test]
+async fn provision_account_is_idempotent() {
+  let admin = admin_user_client().await;
+  let idempotency_key = Uuid::new_v4().to_string();
+  let email = generate_unique_email();
+  let params = provision_params(&idempotency_key, &email);
+  let invited_email = params.invitations[0].email.clone();
+
+  let account = admin.provision_account(&params).await.unwrap();
+  assert_eq!(account.invited_emails, vec![invited_email]);
+
+  // Retrying the request returns the account created the first time.
+  let retried = admin.provision_account(&params).await.unwrap();
+  assert_eq!(retried.user_uuid, account.user_uuid);
+  assert_eq!(retried.workspace_id, account.workspace_id);
+
+  // The key can't be reused for another account.
+  let err = admin
+    .provision_account(&provision_params(
+      &idempotency_key,
+      &generate_unique_email(),
+    ))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let client = localhost_client();
+  client.sign_in_password(&email, "Hello123!").await.unwrap();
+  let workspaces = client.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+  assert_eq!(workspaces[0].workspace_id, account.workspace_id);
+  assert_eq!(workspaces[0].workspace_name, "Class of 2025");
+  let folder = client
+    .get_workspace_folder(&account.workspace_id.to_string(), Some(1), None)
+    .await
+    .unwrap();
+  assert!(!folder.children.is_empty());
+}
+
+#[tokio::test]
+async fn provision_account_by_non_admin() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client
+    .provision_account(&provision_params(
+      &Uuid::new_v4().to_string(),
+      &generate_unique_email(),
+    ))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,84 @@
+use app_error::ErrorCode;
+use client_api::entity::AFWorkspace;
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use reqwest::{Method, StatusCode};
+use shared_entity::response::{AppResponse, RateLimitInfo};
+
+#[tokio::test]
+async fn authenticated_response_has_rate_limit_headers() {
+  let (c, _) = generate_unique_registered_user_client().await;
+  let url = format!("{}/api/workspace", c.base_url);
+  let mut rate_limits = vec![];
+  for _ in 0..2 {
+    let resp = c
+      .http_client_with_auth(Method::GET, &url)
+      .await
+      .unwrap()
+      .send()
+      .await
+      .unwrap();
+    let resp = AppResponse::<Vec<AFWorkspace>>::from_response(resp)
+      .await
+      .unwrap();
+    rate_limits.push(resp.rate_limit.unwrap());
+  }
+
+  let (first, second) = (rate_limits[0], rate_limits[1]);
+  assert_eq!(first.limit, second.limit);
+  assert!(first.remaining < first.limit);
+  assert!(first.reset_secs > 0);
+  // Both requests fall into the same window, unless the window has been reset in between.
+  if second.reset_secs <= first.reset_secs {
+    assert_eq!(second.remaining, first.remaining.saturating_sub(1));
+  }
+}
+
+#[tokio::test]
+async fn anonymous_response_has_no_rate_limit_headers() {
+  let c = localhost_client();
+  let resp = reqwest::Client::new()
+    .get(format!("{}/api/server", c.base_url))
+    .send()
+    .await
+    .unwrap();
+  assert!(RateLimitInfo::from_headers(resp.headers()).is_none());
+}
+
+#[tokio::test]
+async fn realtime_messages_beyond_the_burst_are_throttled() {
+  let (c, _) = generate_unique_registered_user_client().await;
+  let url = format!("{}/api/realtime/post/stream", c.base_url);
+  // The messages are throttled before they are parsed, so an invalid payload is enough to drain
+  // the bucket of the device.
+  for _ in 0..2000 {
+    let resp = c
+      .http_client_with_auth(Method::POST, &url)
+      .await
+      .unwrap()
+      .body(vec![0u8; 8])
+      .send()
+      .await
+      .unwrap();
+    if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+      continue;
+    }
+    let retry_after: u64 = resp
+      .headers()
+      .get(reqwest::header::RETRY_AFTER)
+      .unwrap()
+      .to_str()
+      .unwrap()
+      .parse()
+      .unwrap();
+    let err = AppResponse::<()>::from_response(resp)
+      .await
+      .unwrap()
+      .into_error()
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::TooManyRequests);
+    assert_eq!(err.retry_after_secs(), Some(retry_after));
+    assert!(retry_after >= 1);
+    return;
+  }
+  panic!("the realtime messages were never throttled");
+}
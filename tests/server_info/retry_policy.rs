@@ -0,0 +1,81 @@
+use std::time::{Duration, Instant};
+
+use client_api::{Client, ClientConfiguration, RetryOn, RetryPolicy};
+use client_api_test::{
+  generate_unique_registered_user, LOCALHOST_GOTRUE, LOCALHOST_URL, LOCALHOST_WS,
+};
+use shared_entity::dto::workspace_dto::{CreatePageParams, ViewLayout};
+use uuid::Uuid;
+
+async fn registered_client_with_retry_policy(retry_policy: RetryPolicy) -> Client {
+  let user = generate_unique_registered_user().await;
+  let client = Client::new(
+    &LOCALHOST_URL,
+    &LOCALHOST_WS,
+    &LOCALHOST_GOTRUE,
+    &Uuid::new_v4().to_string(),
+    ClientConfiguration::default().with_retry_policy(retry_policy),
+    "0.9.0",
+  );
+  client
+    .sign_in_password(&user.email, &user.password)
+    .await
+    .unwrap();
+  client
+}
+
+#[tokio::test]
+async fn create_page_view_with_retry_policy() {
+  let c = registered_client_with_retry_policy(RetryPolicy::default()).await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  c.create_workspace_page_view(
+    workspace_id,
+    &CreatePageParams {
+      parent_view_id: general_space.view_id,
+      layout: ViewLayout::Document,
+      name: None,
+      content: None,
+      template_id: None,
+    },
+  )
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn connection_errors_are_retried_with_backoff() {
+  let mut c = registered_client_with_retry_policy(
+    RetryPolicy::default()
+      .with_max_attempts(3)
+      .with_backoff(Duration::from_millis(200), Duration::from_millis(200))
+      .with_retry_on(vec![RetryOn::ConnectionError]),
+  )
+  .await;
+  // nothing listens on this port, so the connections are refused
+  c.base_url = "http://localhost:1".to_string();
+
+  let started_at = Instant::now();
+  c.create_workspace_page_view(
+    Uuid::new_v4(),
+    &CreatePageParams {
+      parent_view_id: Uuid::new_v4().to_string(),
+      layout: ViewLayout::Document,
+      name: None,
+      content: None,
+      template_id: None,
+    },
+  )
+  .await
+  .unwrap_err();
+  // two retries, each after at least half of the backoff
+  assert!(started_at.elapsed() >= Duration::from_millis(200));
+}
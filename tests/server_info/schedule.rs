@@ -0,0 +1,37 @@
+use app_error::ErrorCode;
+use client_api_test::{admin_user_client, generate_unique_registered_user_client};
+
+#[tokio::test]
+async fn list_schedules() {
+  let admin = admin_user_client().await;
+  let schedules = admin.list_schedules().await.unwrap();
+  assert!(!schedules.node_id.is_empty());
+  let invitation_expiry = schedules
+    .jobs
+    .iter()
+    .find(|job| job.name == "invitation_expiry")
+    .unwrap();
+  assert_eq!(invitation_expiry.interval_secs, 10 * 60);
+  // The next job to run is listed first.
+  assert!(schedules
+    .jobs
+    .windows(2)
+    .all(|jobs| jobs[0].next_run_at <= jobs[1].next_run_at));
+}
+
+#[tokio::test]
+async fn schedules_are_reserved_for_server_admins() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client.list_schedules().await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn collab_corruptions_are_reserved_for_server_admins() {
+  let admin = admin_user_client().await;
+  admin.list_collab_corruptions().await.unwrap();
+
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client.list_collab_corruptions().await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,82 @@
+use crate::sql_test::util::{generate_random_bytes, setup_db, test_create_user};
+use collab_entity::CollabType;
+use database::collab::{
+  archive_collab, delete_stale_collab_archives, insert_into_af_collab, rehydrate_collab,
+  select_blob_from_af_collab, select_collabs_to_archive,
+};
+use database_entity::dto::CollabParams;
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn archive_and_rehydrate_collab_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab_v1 = generate_random_bytes(10240);
+  let params = CollabParams::new(&object_id, CollabType::Document, encoded_collab_v1.clone());
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+
+  // The collab was just written, so it isn't inactive for a day
+  let candidates = select_collabs_to_archive(&pool, 24 * 60 * 60, 100)
+    .await
+    .unwrap();
+  assert!(!candidates.iter().any(|c| c.oid == object_id));
+
+  // A negative duration makes every collab inactive
+  let candidate = select_collabs_to_archive(&pool, -1, 100)
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|c| c.oid == object_id)
+    .unwrap();
+  assert!(archive_collab(&pool, &candidate, -1).await.unwrap());
+  assert!(!archive_collab(&pool, &candidate, -1).await.unwrap());
+  let blob = select_blob_from_af_collab(&pool, &CollabType::Document, &object_id)
+    .await
+    .unwrap();
+  assert!(blob.is_empty());
+
+  let blob = rehydrate_collab(&pool, &object_id, &CollabType::Document)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(blob, encoded_collab_v1);
+  let blob = select_blob_from_af_collab(&pool, &CollabType::Document, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(blob, encoded_collab_v1);
+  assert!(rehydrate_collab(&pool, &object_id, &CollabType::Document)
+    .await
+    .unwrap()
+    .is_none());
+
+  // Writing an archived collab makes its archived payload stale
+  assert!(archive_collab(&pool, &candidate, -1).await.unwrap());
+  let updated_collab_v1 = generate_random_bytes(1024);
+  let params = CollabParams::new(&object_id, CollabType::Document, updated_collab_v1.clone());
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  assert_eq!(delete_stale_collab_archives(&pool).await.unwrap(), 1);
+  assert!(rehydrate_collab(&pool, &object_id, &CollabType::Document)
+    .await
+    .unwrap()
+    .is_none());
+  let blob = select_blob_from_af_collab(&pool, &CollabType::Document, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(blob, updated_collab_v1);
+}
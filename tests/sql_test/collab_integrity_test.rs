@@ -0,0 +1,83 @@
+use crate::sql_test::util::{generate_random_bytes, setup_db, test_create_user};
+use collab_entity::CollabType;
+use database::collab::{
+  collab_checksum, create_snapshot, get_all_collab_snapshot_meta, insert_into_af_collab,
+  is_collab_checksum_valid, resolve_repaired_collab_corruptions, scrub_collabs,
+  select_blob_and_checksum_from_af_collab, select_unresolved_collab_corruptions,
+};
+use database_entity::dto::CollabParams;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[sqlx::test(migrations = false)]
+async fn scrub_corrupted_collab_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab_v1 = generate_random_bytes(1024);
+  let params = CollabParams::new(&object_id, CollabType::Document, encoded_collab_v1.clone());
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  let workspace_id = Uuid::parse_str(&user.workspace_id).unwrap();
+  create_snapshot(&pool, &object_id, &encoded_collab_v1, &workspace_id)
+    .await
+    .unwrap();
+  let snapshot_id = get_all_collab_snapshot_meta(&pool, &object_id)
+    .await
+    .unwrap()
+    .0[0]
+    .snapshot_id;
+
+  // The checksum is written along with the blob
+  let (blob, checksum) =
+    select_blob_and_checksum_from_af_collab(&pool, &CollabType::Document, &object_id)
+      .await
+      .unwrap();
+  assert_eq!(checksum, Some(collab_checksum(&encoded_collab_v1)));
+  assert!(is_collab_checksum_valid(&blob, checksum.as_deref()));
+  assert!(scrub_collabs(&pool, 100).await.unwrap().is_empty());
+
+  // Corrupt the blob behind the back of the storage
+  sqlx::query("UPDATE af_collab SET blob = $2 WHERE oid = $1")
+    .bind(&object_id)
+    .bind(generate_random_bytes(1024))
+    .execute(&pool)
+    .await
+    .unwrap();
+  let corruptions = scrub_collabs(&pool, 100).await.unwrap();
+  assert_eq!(corruptions.len(), 1);
+  assert_eq!(corruptions[0].oid, object_id);
+  assert_eq!(corruptions[0].snapshot_id, 0);
+  assert_eq!(corruptions[0].last_good_snapshot_id, Some(snapshot_id));
+  // The corruption is only reported once
+  assert!(scrub_collabs(&pool, 100).await.unwrap().is_empty());
+  assert_eq!(
+    select_unresolved_collab_corruptions(&pool)
+      .await
+      .unwrap()
+      .len(),
+    1
+  );
+
+  // Writing the collab again repairs it
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+  assert_eq!(resolve_repaired_collab_corruptions(&pool).await.unwrap(), 1);
+  assert!(select_unresolved_collab_corruptions(&pool)
+    .await
+    .unwrap()
+    .is_empty());
+}
@@ -0,0 +1,66 @@
+use crate::sql_test::util::setup_db;
+
+use database::comment_subscription::{
+  delete_comment_subscription, select_comment_subscriber_count,
+  select_confirmed_comment_subscriptions, update_comment_subscription_confirmed,
+  upsert_comment_subscription,
+};
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn comment_subscription_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let email = "subscriber@appflowy.io";
+  let subscription = upsert_comment_subscription(&pool, &view_id, email)
+    .await
+    .unwrap();
+  assert!(!subscription.confirmed);
+
+  // Subscribing again returns the pending subscription
+  let pending = upsert_comment_subscription(&pool, &view_id, email)
+    .await
+    .unwrap();
+  assert_eq!(pending.token, subscription.token);
+  assert_eq!(
+    select_comment_subscriber_count(&pool, &view_id)
+      .await
+      .unwrap(),
+    0
+  );
+
+  assert!(
+    !update_comment_subscription_confirmed(&pool, &view_id, &uuid::Uuid::new_v4())
+      .await
+      .unwrap()
+  );
+  assert!(
+    update_comment_subscription_confirmed(&pool, &view_id, &subscription.token)
+      .await
+      .unwrap()
+  );
+  let subscriptions = select_confirmed_comment_subscriptions(&pool, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(subscriptions.len(), 1);
+  assert_eq!(subscriptions[0].email, email);
+  assert_eq!(
+    select_comment_subscriber_count(&pool, &view_id)
+      .await
+      .unwrap(),
+    1
+  );
+
+  assert!(
+    delete_comment_subscription(&pool, &view_id, &subscription.token)
+      .await
+      .unwrap()
+  );
+  assert_eq!(
+    select_comment_subscriber_count(&pool, &view_id)
+      .await
+      .unwrap(),
+    0
+  );
+}
@@ -1,4 +1,9 @@
 mod chat_test;
+mod collab_archive_test;
+mod collab_integrity_test;
+mod comment_subscription_test;
 mod history_test;
+mod snapshot_retention_test;
 pub(crate) mod util;
+mod workspace_lifecycle_test;
 mod workspace_test;
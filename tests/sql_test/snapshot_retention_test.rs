@@ -0,0 +1,79 @@
+use crate::sql_test::util::{generate_random_bytes, setup_db, test_create_user};
+use collab_entity::CollabType;
+use database::collab::{
+  create_snapshot_and_maintain_limit, delete_workspace_snapshots_beyond_retention,
+  get_all_collab_snapshot_meta, insert_into_af_collab, select_collabs_to_snapshot,
+  select_snapshot_retention_policy,
+};
+use database_entity::dto::{CollabParams, SnapshotRetentionPolicy};
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn snapshot_retention_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+  let retention = select_snapshot_retention_policy(&pool, &user.workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(retention, SnapshotRetentionPolicy::default());
+
+  let object_id = uuid::Uuid::new_v4().to_string();
+  let encoded_collab_v1 = generate_random_bytes(1024);
+  let params = CollabParams::new(&object_id, CollabType::Document, encoded_collab_v1.clone());
+  let mut txn = pool.begin().await.unwrap();
+  insert_into_af_collab(&mut txn, &user.uid, &user.workspace_id, &params)
+    .await
+    .unwrap();
+  txn.commit().await.unwrap();
+
+  // The collab has never been snapshotted
+  let candidates = select_collabs_to_snapshot(&pool, &user.workspace_id, 60 * 60, 100)
+    .await
+    .unwrap();
+  assert!(candidates.iter().any(|c| c.oid == object_id));
+
+  let retention = SnapshotRetentionPolicy {
+    max_count: 3,
+    ..Default::default()
+  };
+  for _ in 0..5 {
+    create_snapshot_and_maintain_limit(
+      pool.begin().await.unwrap(),
+      &user.workspace_id.to_string(),
+      &object_id,
+      &encoded_collab_v1,
+      &retention,
+    )
+    .await
+    .unwrap();
+  }
+  let metas = get_all_collab_snapshot_meta(&pool, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(metas.0.len(), 3);
+
+  // The collab wasn't written since its last snapshot
+  let candidates = select_collabs_to_snapshot(&pool, &user.workspace_id, -1, 100)
+    .await
+    .unwrap();
+  assert!(!candidates.iter().any(|c| c.oid == object_id));
+
+  let retention = SnapshotRetentionPolicy {
+    max_count: 1,
+    ..Default::default()
+  };
+  let deleted = delete_workspace_snapshots_beyond_retention(&pool, &user.workspace_id, &retention)
+    .await
+    .unwrap();
+  assert_eq!(deleted, 2);
+  let metas = get_all_collab_snapshot_meta(&pool, &object_id)
+    .await
+    .unwrap();
+  assert_eq!(metas.0.len(), 1);
+}
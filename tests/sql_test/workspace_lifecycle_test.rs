@@ -0,0 +1,73 @@
+use crate::sql_test::util::{setup_db, test_create_user};
+use database::workspace_lifecycle::{
+  reset_workspace_lifecycle, select_workspace_lifecycle, select_workspace_lifecycle_candidates,
+  update_workspace_lifecycle_warned, upsert_workspace_lifecycle_exempt,
+};
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = false)]
+async fn workspace_lifecycle_sql_test(pool: PgPool) {
+  setup_db(&pool).await.unwrap();
+
+  let user_uuid = uuid::Uuid::new_v4();
+  let name = user_uuid.to_string();
+  let email = format!("{}@appflowy.io", name);
+  let user = test_create_user(&pool, user_uuid, &email, &name)
+    .await
+    .unwrap();
+
+  // The workspace was just created, so it isn't inactive for a day
+  let candidates = select_workspace_lifecycle_candidates(&pool, 24 * 60 * 60, 100)
+    .await
+    .unwrap();
+  assert!(!candidates
+    .iter()
+    .any(|c| c.workspace_id == user.workspace_id));
+
+  // A negative duration makes every workspace inactive
+  let candidate = select_workspace_lifecycle_candidates(&pool, -1, 100)
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|c| c.workspace_id == user.workspace_id)
+    .unwrap();
+  assert_eq!(candidate.owner_email, email);
+  assert!(candidate.warned_at.is_none());
+
+  // A warned workspace stays a candidate, so that its lifecycle can be reset
+  update_workspace_lifecycle_warned(&pool, &user.workspace_id)
+    .await
+    .unwrap();
+  let candidate = select_workspace_lifecycle_candidates(&pool, 24 * 60 * 60, 100)
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|c| c.workspace_id == user.workspace_id)
+    .unwrap();
+  assert!(candidate.warned_at.is_some());
+
+  reset_workspace_lifecycle(&pool, &user.workspace_id)
+    .await
+    .unwrap();
+  let lifecycle = select_workspace_lifecycle(&pool, &user.workspace_id)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(lifecycle.warned_at.is_none());
+
+  // Exempt workspaces are never candidates
+  upsert_workspace_lifecycle_exempt(&pool, &user.workspace_id, true)
+    .await
+    .unwrap();
+  let candidates = select_workspace_lifecycle_candidates(&pool, -1, 100)
+    .await
+    .unwrap();
+  assert!(!candidates
+    .iter()
+    .any(|c| c.workspace_id == user.workspace_id));
+  let lifecycle = select_workspace_lifecycle(&pool, &user.workspace_id)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(lifecycle.exempt);
+}
@@ -0,0 +1,138 @@
+use client_api_test::generate_unique_registered_user_client;
+use database_entity::dto::CreateAccessTokenParams;
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn send_with_access_token(
+  method: Method,
+  url: &str,
+  access_token: &str,
+  body: Option<serde_json::Value>,
+) -> reqwest::Response {
+  let mut request = reqwest::Client::new()
+    .request(method, url)
+    .bearer_auth(access_token);
+  if let Some(body) = body {
+    request = request.json(&body);
+  }
+  request.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn access_token_is_limited_to_its_scopes() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let created = c
+    .create_access_token(&CreateAccessTokenParams {
+      name: "ci".to_string(),
+      read_only: true,
+      workspace_ids: Some(vec![workspace_id]),
+      expires_at: None,
+    })
+    .await
+    .unwrap();
+  assert!(created.token.starts_with("afpat_"));
+
+  let folder_url = format!("{}/api/workspace/{}/folder", c.base_url, workspace_id);
+  let resp = send_with_access_token(Method::GET, &folder_url, &created.token, None).await;
+  assert_eq!(resp.status(), StatusCode::OK);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["code"], 0);
+
+  // the token is read-only
+  let folder = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(1), None)
+    .await
+    .unwrap();
+  let page_url = format!("{}/api/workspace/{}/page-view", c.base_url, workspace_id);
+  let page_params = json!({ "parent_view_id": folder.children[0].view_id, "layout": 0 });
+  let resp = send_with_access_token(
+    Method::POST,
+    &page_url,
+    &created.token,
+    Some(page_params.clone()),
+  )
+  .await;
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+  // the token is limited to the workspace
+  let other_folder_url = format!("{}/api/workspace/{}/folder", c.base_url, Uuid::new_v4());
+  let resp = send_with_access_token(Method::GET, &other_folder_url, &created.token, None).await;
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+  // the tokens can't be managed with a token
+  let tokens_url = format!("{}/api/user/access-token", c.base_url);
+  let resp = send_with_access_token(Method::GET, &tokens_url, &created.token, None).await;
+  assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+  let writer = c
+    .create_access_token(&CreateAccessTokenParams {
+      name: "integration".to_string(),
+      read_only: false,
+      workspace_ids: None,
+      expires_at: None,
+    })
+    .await
+    .unwrap();
+  let resp =
+    send_with_access_token(Method::POST, &page_url, &writer.token, Some(page_params)).await;
+  assert_eq!(resp.status(), StatusCode::OK);
+  let body: serde_json::Value = resp.json().await.unwrap();
+  assert_eq!(body["code"], 0);
+
+  let tokens = c.get_access_tokens().await.unwrap().access_tokens;
+  assert_eq!(tokens.len(), 2);
+  let token = tokens
+    .iter()
+    .find(|token| token.id == created.access_token.id)
+    .unwrap();
+  assert_eq!(token.workspace_ids, Some(vec![workspace_id]));
+  assert!(token.last_used_at.is_some());
+}
+
+#[tokio::test]
+async fn revoked_access_token_is_rejected() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let created = c
+    .create_access_token(&CreateAccessTokenParams {
+      name: "ci".to_string(),
+      read_only: true,
+      workspace_ids: None,
+      expires_at: None,
+    })
+    .await
+    .unwrap();
+  let profile_url = format!("{}/api/user/workspace", c.base_url);
+  let resp = send_with_access_token(Method::GET, &profile_url, &created.token, None).await;
+  assert_eq!(resp.status(), StatusCode::OK);
+
+  c.revoke_access_token(&created.access_token.id)
+    .await
+    .unwrap();
+  let resp = send_with_access_token(Method::GET, &profile_url, &created.token, None).await;
+  assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+  assert!(c
+    .get_access_tokens()
+    .await
+    .unwrap()
+    .access_tokens
+    .is_empty());
+
+  let err = c
+    .revoke_access_token(&created.access_token.id)
+    .await
+    .unwrap_err();
+  assert!(err.is_record_not_found());
+
+  let err = c
+    .create_access_token(&CreateAccessTokenParams {
+      name: "other".to_string(),
+      read_only: true,
+      workspace_ids: Some(vec![Uuid::new_v4()]),
+      expires_at: None,
+    })
+    .await
+    .unwrap_err();
+  assert!(!err.is_record_not_found());
+}
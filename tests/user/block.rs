@@ -0,0 +1,107 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn block_user_on_published_view() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner_client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  owner_client
+    .set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  owner_client
+    .publish_collabs::<serde_json::Value, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "published-view".to_string(),
+          metadata: serde_json::json!({}),
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+  owner_client
+    .create_comment_on_published_view(&view_id, "comment from owner", &None)
+    .await
+    .unwrap();
+  let owner_comment_id = owner_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments[0]
+    .comment_id;
+
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let other_uuid = other_client.get_profile().await.unwrap().uuid;
+  other_client
+    .create_comment_on_published_view(&view_id, "comment from other user", &None)
+    .await
+    .unwrap();
+  other_client
+    .create_reaction_on_comment("👍", &view_id, &owner_comment_id)
+    .await
+    .unwrap();
+
+  let owner_uuid = owner_client.get_profile().await.unwrap().uuid;
+  let err = owner_client.block_user(&owner_uuid).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  owner_client.block_user(&other_uuid).await.unwrap();
+  // Blocking twice does nothing
+  owner_client.block_user(&other_uuid).await.unwrap();
+  let blocked = owner_client.get_blocked_users().await.unwrap();
+  assert_eq!(blocked.users.len(), 1);
+  assert_eq!(blocked.users[0].uuid, other_uuid);
+
+  // The comments and reactions of the blocked user are hidden from the blocker only
+  let comments = owner_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 1);
+  let reactions = owner_client
+    .get_published_view_reactions(&view_id, &None)
+    .await
+    .unwrap()
+    .reactions;
+  assert!(reactions.is_empty());
+  let comments = other_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 2);
+
+  // The blocked user can't interact with the content of the blocker anymore
+  let err = other_client
+    .create_comment_on_published_view(&view_id, "another comment", &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = other_client
+    .create_reaction_on_comment("🎉", &view_id, &owner_comment_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  owner_client.unblock_user(&other_uuid).await.unwrap();
+  let err = owner_client.unblock_user(&other_uuid).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let comments = owner_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 2);
+  other_client
+    .create_comment_on_published_view(&view_id, "another comment", &None)
+    .await
+    .unwrap();
+}
@@ -0,0 +1,32 @@
+use client_api_test::TestClient;
+
+#[tokio::test]
+async fn list_and_revoke_user_devices() {
+  let client = TestClient::new_user().await;
+  let devices = client.api_client.get_user_devices().await.unwrap().devices;
+  let device = devices
+    .iter()
+    .find(|device| device.device_id == client.device_id)
+    .unwrap();
+  assert_eq!(device.platform.as_deref(), Some(std::env::consts::OS));
+  assert!(device.client_version.is_some());
+  assert!(device.last_seen_at >= device.first_seen_at);
+
+  client
+    .api_client
+    .revoke_user_device(&client.device_id)
+    .await
+    .unwrap();
+  let devices = client.api_client.get_user_devices().await.unwrap().devices;
+  assert!(devices
+    .iter()
+    .all(|device| device.device_id != client.device_id));
+
+  // the device is already revoked
+  let err = client
+    .api_client
+    .revoke_user_device(&client.device_id)
+    .await
+    .unwrap_err();
+  assert!(err.is_record_not_found());
+}
@@ -0,0 +1,86 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+
+#[tokio::test]
+async fn merge_account_moves_workspaces_and_comments() {
+  let (target_client, _) = generate_unique_registered_user_client().await;
+  let (source_client, source_user) = generate_unique_registered_user_client().await;
+  let source_workspace_id = source_client.get_workspaces().await.unwrap()[0].workspace_id;
+
+  source_client
+    .set_workspace_publish_namespace(
+      &source_workspace_id.to_string(),
+      uuid::Uuid::new_v4().to_string(),
+    )
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  source_client
+    .publish_collabs::<serde_json::Value, &[u8]>(
+      &source_workspace_id.to_string(),
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "published-view".to_string(),
+          metadata: serde_json::json!({}),
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+  source_client
+    .create_comment_on_published_view(&view_id, "comment from source", &None)
+    .await
+    .unwrap();
+
+  let summary = target_client
+    .merge_account(&source_client.access_token().unwrap())
+    .await
+    .unwrap();
+  assert_eq!(summary.workspace_ids, vec![source_workspace_id]);
+  assert_eq!(summary.comment_count, 1);
+
+  // The workspace, the published view and the comment now belong to the target user.
+  let workspace = target_client
+    .get_workspaces()
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|workspace| workspace.workspace_id == source_workspace_id)
+    .unwrap();
+  let target_uid = target_client.get_profile().await.unwrap().uid;
+  assert_eq!(workspace.owner_uid, target_uid);
+  let comments = target_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 1);
+  assert!(comments[0].can_be_deleted);
+
+  // The source account is deleted.
+  let err = localhost_client()
+    .sign_in_password(&source_user.email, &source_user.password)
+    .await
+    .unwrap_err();
+  assert_ne!(err.code, ErrorCode::Ok);
+}
+
+#[tokio::test]
+async fn merge_account_into_itself() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client
+    .merge_account(&client.access_token().unwrap())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn merge_account_with_invalid_token() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let err = client.merge_account("invalid token").await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
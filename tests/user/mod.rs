@@ -1,4 +1,8 @@
+mod access_token;
+mod block;
 mod delete;
+mod device;
+mod merge;
 mod refresh;
 mod sign_in;
 mod sign_out;
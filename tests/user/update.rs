@@ -1,8 +1,10 @@
 use app_error::ErrorCode;
 use client_api::ws::{WSClient, WSClientConfig};
 use client_api_test::*;
+use database_entity::dto::{AFRole, AFWorkspaceInvitationStatus};
 use serde_json::json;
 use shared_entity::dto::auth_dto::{UpdateUserParams, UserMetaData};
+use shared_entity::dto::workspace_dto::WorkspaceMemberInvitation;
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -185,3 +187,43 @@ async fn user_change_notify_test() {
     },
   }
 }
+
+#[tokio::test]
+async fn change_user_email_keeps_pending_invitations() {
+  let (owner_client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = owner_client.get_workspaces().await.unwrap()[0].workspace_id;
+  let (c, user) = generate_unique_registered_user_client().await;
+  owner_client
+    .invite_workspace_members(
+      &workspace_id.to_string(),
+      vec![WorkspaceMemberInvitation {
+        email: user.email.clone(),
+        role: AFRole::Member,
+      }],
+    )
+    .await
+    .unwrap();
+
+  let new_email = format!("{}@appflowy.io", Uuid::new_v4());
+  let status = c.change_email(&new_email).await.unwrap();
+  assert_eq!(status.email, new_email);
+  let status = c.sync_email().await.unwrap();
+  assert_eq!(status.email, new_email);
+
+  let profile = c.get_profile().await.unwrap();
+  assert_eq!(profile.email.unwrap(), new_email);
+  let invitations = c
+    .list_workspace_invitations(Some(AFWorkspaceInvitationStatus::Pending))
+    .await
+    .unwrap();
+  assert_eq!(invitations.len(), 1);
+  assert_eq!(invitations[0].workspace_id, workspace_id);
+}
+
+#[tokio::test]
+async fn change_user_email_to_email_of_other_user() {
+  let (c, _) = generate_unique_registered_user_client().await;
+  let (_, other_user) = generate_unique_registered_user_client().await;
+  let err = c.change_email(&other_user.email).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+}
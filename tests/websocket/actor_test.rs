@@ -4,7 +4,7 @@ use appflowy_collaborate::actix_ws::client::rt_client::{
 };
 use appflowy_collaborate::actix_ws::entities::{ClientMessage, Connect, Disconnect};
 use collab_rt_entity::user::RealtimeUser;
-use collab_rt_entity::RealtimeMessage;
+use collab_rt_entity::{RealtimeMessage, REALTIME_PROTOCOL_VERSION};
 use semver::Version;
 use std::collections::HashMap;
 use std::time::Duration;
@@ -25,6 +25,7 @@ async fn test_handle_message() {
     Duration::from_secs(6),
     Duration::from_secs(10),
     client_version,
+    REALTIME_PROTOCOL_VERSION,
     external_source,
     10,
   );
@@ -60,6 +61,7 @@ async fn server_mailbox_full_test() {
         Duration::from_secs(6),
         Duration::from_secs(10),
         cloned_client_version,
+        REALTIME_PROTOCOL_VERSION,
         external_source,
         10,
       );
@@ -106,6 +108,7 @@ async fn client_rate_limit_hit_test() {
         Duration::from_secs(6),
         Duration::from_secs(10),
         cloned_client_version,
+        REALTIME_PROTOCOL_VERSION,
         external_source,
         1,
       );
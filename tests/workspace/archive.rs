@@ -0,0 +1,99 @@
+use app_error::ErrorCode;
+use client_api_test::{assert_server_collab, TestClient};
+use collab_entity::CollabType;
+use database_entity::dto::AFRole;
+use serde_json::json;
+use shared_entity::dto::workspace_dto::{CreateWorkspaceParam, QueryWorkspaceParam};
+
+#[tokio::test]
+async fn archive_and_unarchive_workspace_test() {
+  let c = TestClient::new_user_without_ws_conn().await;
+  let workspace = c
+    .api_client
+    .create_workspace(CreateWorkspaceParam {
+      workspace_name: Some("archived workspace".to_string()),
+      residency: None,
+    })
+    .await
+    .unwrap();
+  let workspace_id = workspace.workspace_id.to_string();
+  assert!(!workspace.archived);
+  assert_eq!(c.api_client.get_workspaces().await.unwrap().len(), 2);
+
+  c.api_client.archive_workspace(&workspace_id).await.unwrap();
+  let workspaces = c.api_client.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+  assert!(workspaces
+    .iter()
+    .all(|w| w.workspace_id != workspace.workspace_id));
+
+  let workspaces = c
+    .api_client
+    .get_workspaces_opt(QueryWorkspaceParam {
+      include_archived: Some(true),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(workspaces.len(), 2);
+  let archived_workspace = workspaces
+    .iter()
+    .find(|w| w.workspace_id == workspace.workspace_id)
+    .unwrap();
+  assert!(archived_workspace.archived);
+
+  c.api_client
+    .unarchive_workspace(&workspace_id)
+    .await
+    .unwrap();
+  let workspaces = c.api_client.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 2);
+  assert!(workspaces.iter().all(|w| !w.archived));
+}
+
+#[tokio::test]
+async fn archive_workspace_by_member_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .archive_workspace(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn edit_collab_in_archived_workspace_test() {
+  let collab_type = CollabType::Unknown;
+  let mut client = TestClient::new_user().await;
+  let workspace_id = client.workspace_id().await;
+  let object_id = client
+    .create_and_edit_collab(&workspace_id, collab_type.clone())
+    .await;
+
+  client
+    .api_client
+    .archive_workspace(&workspace_id)
+    .await
+    .unwrap();
+
+  // The server rejects the update, the collab in the server is not updated.
+  client.insert_into(&object_id, "name", "AppFlowy").await;
+  assert_server_collab(
+    &workspace_id,
+    &mut client.api_client,
+    &object_id,
+    &collab_type,
+    5,
+    json!({}),
+  )
+  .await
+  .unwrap();
+}
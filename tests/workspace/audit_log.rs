@@ -0,0 +1,120 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{
+  QueryWorkspaceAuditLog, WorkspaceAuditAction, WorkspaceMemberChangeset,
+};
+
+#[tokio::test]
+async fn get_workspace_audit_log_by_owner() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member_email = member.email().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(member_email.clone()).with_role(AFRole::Guest),
+    )
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .remove_workspace_members(&workspace_id, vec![member_email.clone()])
+    .await
+    .unwrap();
+
+  let events = owner
+    .api_client
+    .get_workspace_audit_log(&workspace_id, &QueryWorkspaceAuditLog::default())
+    .await
+    .unwrap();
+  let actions = events.iter().map(|event| event.action).collect::<Vec<_>>();
+  assert_eq!(
+    actions,
+    vec![
+      WorkspaceAuditAction::MemberRemoved,
+      WorkspaceAuditAction::MemberUpdated,
+      WorkspaceAuditAction::MemberJoined,
+      WorkspaceAuditAction::MemberInvited,
+    ]
+  );
+  assert_eq!(events[0].target.as_deref(), Some(member_email.as_str()));
+  assert_eq!(events[0].actor_uid, owner.uid().await);
+  assert_eq!(events[0].actor_email, Some(owner.email().await));
+
+  // filter by actor
+  let events = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryWorkspaceAuditLog {
+        actor_uid: Some(member.uid().await),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0].action, WorkspaceAuditAction::MemberJoined);
+
+  // filter by time range
+  let latest = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryWorkspaceAuditLog {
+        limit: Some(1),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  let events = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryWorkspaceAuditLog {
+        until: Some(latest[0].created_at),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(events.len(), 3);
+  let events = owner
+    .api_client
+    .get_workspace_audit_log(
+      &workspace_id,
+      &QueryWorkspaceAuditLog {
+        since: Some(latest[0].created_at),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(events.len(), 1);
+}
+
+#[tokio::test]
+async fn get_workspace_audit_log_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_workspace_audit_log(&workspace_id, &QueryWorkspaceAuditLog::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use client_api::entity::{BatchCommentsParams, PublishCollabItem, PublishCollabMetadata};
+use client_api_test::generate_unique_registered_user_client;
+use tokio::time::sleep;
+
+#[tokio::test]
+async fn batch_get_comments_of_published_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  c.set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_ids = [uuid::Uuid::new_v4(), uuid::Uuid::new_v4()];
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    view_ids
+      .iter()
+      .enumerate()
+      .map(|(i, view_id)| PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: *view_id,
+          publish_name: format!("published-view-{}", i),
+          metadata: serde_json::json!({}),
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      })
+      .collect(),
+  )
+  .await
+  .unwrap();
+
+  for i in 0..3 {
+    c.create_comment_on_published_view(&view_ids[0], &format!("comment {}", i), &None)
+      .await
+      .unwrap();
+    // Ensure that the comments are ordered by creation time
+    sleep(Duration::from_millis(1)).await;
+  }
+
+  let unpublished_view_id = uuid::Uuid::new_v4();
+  let comments = c
+    .batch_get_view_comments(
+      &workspace_id,
+      &BatchCommentsParams {
+        view_ids: vec![view_ids[1], view_ids[0], unpublished_view_id],
+        latest_count: 2,
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(comments.views.len(), 3);
+  assert_eq!(comments.views[0].view_id, view_ids[1]);
+  assert_eq!(comments.views[0].comment_count, 0);
+  assert!(comments.views[0].latest_comments.is_empty());
+  assert_eq!(comments.views[1].view_id, view_ids[0]);
+  assert_eq!(comments.views[1].comment_count, 3);
+  let latest = comments.views[1]
+    .latest_comments
+    .iter()
+    .map(|comment| comment.content.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(latest, vec!["comment 2", "comment 1"]);
+  assert!(comments.views[1].latest_comments[0].can_be_deleted);
+  assert_eq!(comments.views[2].comment_count, 0);
+
+  // Only the members of the workspace can get the comments
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .batch_get_view_comments(
+      &workspace_id,
+      &BatchCommentsParams {
+        view_ids: view_ids.to_vec(),
+        latest_count: 1,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
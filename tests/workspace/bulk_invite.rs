@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use app_error::ErrorCode;
+use client_api_test::generate_unique_registered_user_client;
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{
+  BatchInviteWorkspaceMembers, BulkInviteStatus, WorkspaceMemberInvitation,
+};
+
+#[tokio::test]
+async fn bulk_invite_workspace_members() {
+  let (alice_client, alice) = generate_unique_registered_user_client().await;
+  let workspace_id = alice_client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let (bob_client, bob) = generate_unique_registered_user_client().await;
+
+  let csv = format!(
+    "email,role,message\n\
+     {},member,\"Welcome to the team, Bob!\"\n\
+     not-an-email,member,\n\
+     {},admin,\n\
+     {},guest,\n\
+     {},member,\n",
+    bob.email,
+    format!("{}@appflowy.io", uuid::Uuid::new_v4()),
+    bob.email,
+    alice.email,
+  );
+  let task = alice_client
+    .bulk_invite_workspace_members(&workspace_id, csv)
+    .await
+    .unwrap();
+  assert_eq!(task.total_count, 5);
+
+  let mut task = task;
+  for _ in 0..50 {
+    if task.status != BulkInviteStatus::Pending {
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    task = alice_client
+      .get_bulk_invite_task(&workspace_id, &task.task_id)
+      .await
+      .unwrap();
+  }
+  assert_eq!(task.status, BulkInviteStatus::Completed);
+  assert_eq!(task.success_count, 1);
+  // invalid email, invalid role, duplicated email and existing member
+  assert_eq!(task.failed_count, 4);
+
+  let report = alice_client
+    .get_bulk_invite_error_report(&workspace_id, &task.task_id)
+    .await
+    .unwrap();
+  let mut lines = report.lines();
+  assert_eq!(lines.next(), Some("row,email,error"));
+  assert_eq!(lines.count(), 4);
+
+  let invitations = bob_client.list_workspace_invitations(None).await.unwrap();
+  assert_eq!(invitations.len(), 1);
+
+  // Only the owner can bulk invite
+  let err = bob_client
+    .bulk_invite_workspace_members(&workspace_id, "email,role\n")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn batch_invite_workspace_members() {
+  let (alice_client, alice) = generate_unique_registered_user_client().await;
+  let workspace_id = alice_client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let (bob_client, bob) = generate_unique_registered_user_client().await;
+
+  let invitation = |email: &str| WorkspaceMemberInvitation {
+    email: email.to_string(),
+    role: AFRole::Member,
+  };
+  let batch = BatchInviteWorkspaceMembers {
+    invitations: vec![
+      invitation(&bob.email),
+      invitation("not-an-email"),
+      invitation(&bob.email),
+      invitation(&alice.email),
+    ],
+    message: Some("Welcome to the team!".to_string()),
+  };
+  let results = alice_client
+    .batch_invite_workspace_members(&workspace_id, &batch)
+    .await
+    .unwrap();
+  let emails = results
+    .iter()
+    .map(|result| result.email.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    emails,
+    vec![
+      bob.email.as_str(),
+      "not-an-email",
+      bob.email.as_str(),
+      alice.email.as_str()
+    ]
+  );
+  // invalid email, duplicated email and existing member
+  let successes = results
+    .iter()
+    .map(|result| result.is_success())
+    .collect::<Vec<_>>();
+  assert_eq!(successes, vec![true, false, false, false]);
+
+  let invitations = bob_client.list_workspace_invitations(None).await.unwrap();
+  assert_eq!(invitations.len(), 1);
+
+  // Only the owner can batch invite
+  let err = bob_client
+    .batch_invite_workspace_members(&workspace_id, &batch)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
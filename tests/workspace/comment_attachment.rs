@@ -0,0 +1,90 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn comment_with_attachments() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  c.set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "published-view".to_string(),
+        metadata: serde_json::json!({}),
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let err = c
+    .upload_comment_attachment_on_published_view(&view_id, vec![0u8; 64], &mime::TEXT_PLAIN)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let attachment = c
+    .upload_comment_attachment_on_published_view(&view_id, vec![0u8; 64], &mime::IMAGE_PNG)
+    .await
+    .unwrap();
+  assert_eq!(attachment.file_size, 64);
+  c.create_comment_with_attachments_on_published_view(
+    &view_id,
+    "comment with image",
+    &None,
+    &[attachment.attachment_id],
+  )
+  .await
+  .unwrap();
+
+  // An attachment can only be attached to one comment
+  let err = c
+    .create_comment_with_attachments_on_published_view(
+      &view_id,
+      "another comment",
+      &None,
+      &[attachment.attachment_id],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let comments = c
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments.len(), 1);
+  assert_eq!(comments[0].attachments.len(), 1);
+  assert_eq!(comments[0].attachments[0].url, attachment.url);
+  let file_id = attachment.attachment_id.to_string();
+  let (mime, _) = c
+    .get_blob_v1(&workspace_id, "comment_attachment", &file_id)
+    .await
+    .unwrap();
+  assert_eq!(mime, mime::IMAGE_PNG);
+
+  // The attachments are removed along with the comment
+  c.delete_comment_on_published_view(&view_id, &comments[0].comment_id)
+    .await
+    .unwrap();
+  let comments = c
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert!(comments[0].attachments.is_empty());
+  assert!(c
+    .get_blob_v1(&workspace_id, "comment_attachment", &file_id)
+    .await
+    .is_err());
+}
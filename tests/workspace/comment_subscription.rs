@@ -0,0 +1,74 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn subscribe_to_published_view_comments() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  c.set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "published-view".to_string(),
+        metadata: serde_json::json!({}),
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let err = c
+    .subscribe_to_published_view_comments(&view_id, "not an email")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let err = c
+    .subscribe_to_published_view_comments(&uuid::Uuid::new_v4(), "visitor@appflowy.io")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  c.subscribe_to_published_view_comments(&view_id, "visitor@appflowy.io")
+    .await
+    .unwrap();
+
+  // The subscription is not counted until it's confirmed
+  let count = c
+    .get_published_view_comment_subscriber_count(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(count.count, 0);
+
+  let err = c
+    .confirm_published_view_comment_subscription(&view_id, &uuid::Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  let err = c
+    .unsubscribe_from_published_view_comments(&view_id, &uuid::Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // Only the members of the publishing workspace can see the subscriber count
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let other_workspace_id = other_client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let err = other_client
+    .get_published_view_comment_subscriber_count(&other_workspace_id, &view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
@@ -0,0 +1,145 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::generate_unique_registered_user_client;
+
+#[tokio::test]
+async fn custom_emoji_crud() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let image = vec![0u8; 64];
+
+  let emoji = c
+    .put_custom_emoji(
+      &workspace_id,
+      "party_parrot",
+      image.clone(),
+      &mime::IMAGE_PNG,
+    )
+    .await
+    .unwrap();
+  assert_eq!(emoji.shortcode, "party_parrot");
+
+  // The shortcode is unique within the workspace
+  let err = c
+    .put_custom_emoji(
+      &workspace_id,
+      "party_parrot",
+      image.clone(),
+      &mime::IMAGE_PNG,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+
+  let err = c
+    .put_custom_emoji(
+      &workspace_id,
+      "Party Parrot",
+      image.clone(),
+      &mime::IMAGE_PNG,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  assert_eq!(err.field_errors().len(), 1);
+  assert_eq!(err.field_errors()[0].field, "shortcode");
+  assert_eq!(err.field_errors()[0].code, "invalid_format");
+
+  let err = c
+    .put_custom_emoji(&workspace_id, "not_an_image", image, &mime::TEXT_PLAIN)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  assert_eq!(err.field_errors()[0].field, "content_type");
+  assert_eq!(err.field_errors()[0].code, "not_an_image");
+
+  let too_large_image = vec![0u8; 256 * 1024 + 1];
+  let err = c
+    .put_custom_emoji(
+      &workspace_id,
+      "too_large",
+      too_large_image,
+      &mime::IMAGE_PNG,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PayloadTooLarge);
+  assert_eq!(err.limit(), Some((256 * 1024, Some(256 * 1024 + 1))));
+
+  let emojis = c.list_custom_emojis(&workspace_id).await.unwrap();
+  assert_eq!(emojis.len(), 1);
+  assert_eq!(emojis[0].url, emoji.url);
+  let (_, file_id) = emoji.url.rsplit_once('/').unwrap();
+  let blob = c
+    .get_blob_v1(&workspace_id, "custom_emoji", file_id)
+    .await
+    .unwrap();
+  assert_eq!(blob.0, mime::IMAGE_PNG);
+
+  c.delete_custom_emoji(&workspace_id, "party_parrot")
+    .await
+    .unwrap();
+  let emojis = c.list_custom_emojis(&workspace_id).await.unwrap();
+  assert!(emojis.is_empty());
+}
+
+#[tokio::test]
+async fn react_with_custom_emoji() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  c.set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: "published-view".to_string(),
+        metadata: serde_json::json!({}),
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+  c.create_comment_on_published_view(&view_id, "comment", &None)
+    .await
+    .unwrap();
+  let comment_id = c
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments[0]
+    .comment_id;
+
+  let err = c
+    .create_reaction_on_comment(":party_parrot:", &view_id, &comment_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  c.put_custom_emoji(
+    &workspace_id,
+    "party_parrot",
+    vec![0u8; 64],
+    &mime::IMAGE_PNG,
+  )
+  .await
+  .unwrap();
+  c.create_reaction_on_comment(":party_parrot:", &view_id, &comment_id)
+    .await
+    .unwrap();
+  let reactions = c
+    .get_published_view_reactions(&view_id, &None)
+    .await
+    .unwrap()
+    .reactions;
+  assert_eq!(reactions.len(), 1);
+  assert_eq!(reactions[0].reaction_type, ":party_parrot:");
+}
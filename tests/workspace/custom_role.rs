@@ -0,0 +1,172 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::{
+  AFRole, CreateWorkspaceRoleParams, UpdateWorkspaceRoleMembersParams, UpdateWorkspaceRoleParams,
+  WorkspaceCapability,
+};
+use shared_entity::dto::workspace_dto::{WorkspaceMemberChangeset, WorkspaceMemberInvitation};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn custom_role_grants_its_capabilities_to_its_members() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let invitee = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let invite = |role: AFRole| {
+    let invitee = &invitee;
+    let member = &member;
+    let workspace_id = &workspace_id;
+    async move {
+      member
+        .api_client
+        .invite_workspace_members(
+          workspace_id,
+          vec![WorkspaceMemberInvitation {
+            email: invitee.email().await,
+            role,
+          }],
+        )
+        .await
+    }
+  };
+
+  // a member can't invite without the capability
+  let err = invite(AFRole::Member).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let role = owner
+    .api_client
+    .create_workspace_role(
+      &workspace_uuid,
+      &CreateWorkspaceRoleParams {
+        name: "Recruiter".to_string(),
+        capabilities: vec![WorkspaceCapability::Invite, WorkspaceCapability::Invite],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(role.capabilities, vec![WorkspaceCapability::Invite]);
+  let role = owner
+    .api_client
+    .update_workspace_role_members(
+      &workspace_uuid,
+      &role.role_id,
+      &UpdateWorkspaceRoleMembersParams {
+        member_uids: vec![member.uid().await],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(role.member_uids, vec![member.uid().await]);
+
+  // the capability lets the member invite members, but not owners
+  let err = invite(AFRole::Owner).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  invite(AFRole::Member).await.unwrap();
+
+  // the other capabilities are still reserved to the owners
+  let err = member
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(owner.email().await).with_role(AFRole::Member),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // removing the capability from the role applies to its members right away
+  owner
+    .api_client
+    .update_workspace_role(
+      &workspace_uuid,
+      &role.role_id,
+      &UpdateWorkspaceRoleParams {
+        name: None,
+        capabilities: Some(vec![]),
+      },
+    )
+    .await
+    .unwrap();
+  let err = invite(AFRole::Member).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn member_can_not_manage_roles() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let guest = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let params = CreateWorkspaceRoleParams {
+    name: "Publisher".to_string(),
+    capabilities: vec![WorkspaceCapability::Publish],
+  };
+
+  let err = member
+    .api_client
+    .create_workspace_role(&workspace_uuid, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let role = owner
+    .api_client
+    .create_workspace_role(&workspace_uuid, &params)
+    .await
+    .unwrap();
+  let err = owner
+    .api_client
+    .create_workspace_role(&workspace_uuid, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordAlreadyExists);
+
+  // the guests can't be assigned a role
+  let err = owner
+    .api_client
+    .update_workspace_role_members(
+      &workspace_uuid,
+      &role.role_id,
+      &UpdateWorkspaceRoleMembersParams {
+        member_uids: vec![guest.uid().await],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let roles = member
+    .api_client
+    .get_workspace_roles(&workspace_uuid)
+    .await
+    .unwrap();
+  assert_eq!(roles.roles.len(), 1);
+
+  owner
+    .api_client
+    .delete_workspace_role(&workspace_uuid, &role.role_id)
+    .await
+    .unwrap();
+  let roles = owner
+    .api_client
+    .get_workspace_roles(&workspace_uuid)
+    .await
+    .unwrap();
+  assert!(roles.roles.is_empty());
+}
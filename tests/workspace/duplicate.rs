@@ -0,0 +1,62 @@
+use app_error::ErrorCode;
+use client_api_test::{generate_unique_registered_user_client, TestClient};
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::DuplicateWorkspaceParams;
+
+#[tokio::test]
+async fn duplicate_workspace_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+
+  let new_workspace = c
+    .duplicate_workspace(
+      &workspace_id,
+      DuplicateWorkspaceParams {
+        workspace_name: Some("duplicated workspace".to_string()),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(new_workspace.workspace_name, "duplicated workspace");
+  assert_ne!(new_workspace.workspace_id.to_string(), workspace_id);
+  assert_eq!(c.get_workspaces().await.unwrap().len(), 2);
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let new_folder_view = c
+    .get_workspace_folder(&new_workspace.workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  assert_eq!(new_folder_view.children.len(), folder_view.children.len());
+  for (view, new_view) in folder_view
+    .children
+    .iter()
+    .zip(new_folder_view.children.iter())
+  {
+    assert_eq!(view.name, new_view.name);
+    assert_ne!(view.view_id, new_view.view_id);
+    assert_eq!(view.children.len(), new_view.children.len());
+  }
+}
+
+#[tokio::test]
+async fn member_cannot_duplicate_workspace_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .duplicate_workspace(&workspace_id, DuplicateWorkspaceParams::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
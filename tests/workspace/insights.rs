@@ -0,0 +1,329 @@
+use app_error::ErrorCode;
+use client_api::entity::{PublishCollabItem, PublishCollabMetadata};
+use client_api_test::{generate_unique_registered_user_client, localhost_client, TestClient};
+use database_entity::dto::AFRole;
+use shared_entity::dto::workspace_dto::{
+  AnalyticsInterval, AnalyticsReport, InsightsRange, QueryAnalyticsReport,
+};
+
+#[tokio::test]
+async fn get_workspace_insights_by_owner() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let insights = owner
+    .api_client
+    .get_workspace_insights(&workspace_id, Some(InsightsRange::Last30Days))
+    .await
+    .unwrap();
+  assert_eq!(insights.range, InsightsRange::Last30Days);
+  assert_eq!(insights.member_count, 2);
+  assert_eq!(insights.new_member_count, 2);
+  // The pages of the getting started template are created by the owner.
+  assert_eq!(insights.active_member_count, 1);
+  assert!(!insights.most_edited_views.is_empty());
+  assert_eq!(insights.comment_count, 0);
+  assert_eq!(insights.publish_visit_count, 0);
+}
+
+#[tokio::test]
+async fn get_workspace_insights_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_workspace_insights(&workspace_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn get_publish_access_log_by_owner() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let namespace = uuid::Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id, namespace.clone())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "access-log-view";
+  client
+    .publish_collabs::<(), &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: publish_name.to_string(),
+          metadata: (),
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  let guest_client = localhost_client();
+  for _ in 0..2 {
+    guest_client
+      .get_published_collab_blob(&namespace, publish_name)
+      .await
+      .unwrap();
+  }
+
+  let access_log = client
+    .get_publish_access_log(&workspace_id, Some(7))
+    .await
+    .unwrap();
+  assert_eq!(access_log.days.len(), 1);
+  let today = &access_log.days[0];
+  assert_eq!(today.visit_count, 2);
+  // Both visits come from the same address and user agent.
+  assert_eq!(today.unique_visitor_count, 1);
+  assert_eq!(today.top_referrers.len(), 1);
+  assert_eq!(today.top_referrers[0].referrer, "direct");
+  assert_eq!(today.top_referrers[0].visit_count, 2);
+  assert_eq!(today.top_pages.len(), 1);
+  assert_eq!(today.top_pages[0].view_id, view_id);
+  assert_eq!(
+    today.top_pages[0].publish_name.as_deref(),
+    Some(publish_name)
+  );
+}
+
+#[tokio::test]
+async fn get_publish_access_log_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_publish_access_log(&workspace_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn get_publish_analytics_by_owner() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = client.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let namespace = uuid::Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id, namespace.clone())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "analytics-view";
+  client
+    .publish_collabs::<(), &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: publish_name.to_string(),
+          metadata: (),
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  let guest_client = localhost_client();
+  for _ in 0..3 {
+    guest_client
+      .get_published_collab_blob(&namespace, publish_name)
+      .await
+      .unwrap();
+  }
+
+  // The views recorded since the last flush are included.
+  let analytics = client
+    .get_publish_analytics(&workspace_id, Some(7))
+    .await
+    .unwrap();
+  assert_eq!(analytics.views.len(), 1);
+  assert_eq!(analytics.views[0].view_id, view_id);
+  assert_eq!(
+    analytics.views[0].publish_name.as_deref(),
+    Some(publish_name)
+  );
+  assert_eq!(analytics.views[0].view_count, 3);
+  // All the views come from the same address and user agent.
+  assert_eq!(analytics.views[0].unique_visitor_count, 1);
+  assert_eq!(analytics.days.len(), 1);
+  assert_eq!(analytics.days[0].view_count, 3);
+
+  // The visitor is not counted again on the next flush of the same day.
+  guest_client
+    .get_published_collab_blob(&namespace, publish_name)
+    .await
+    .unwrap();
+  let analytics = client
+    .get_publish_analytics(&workspace_id, Some(7))
+    .await
+    .unwrap();
+  assert_eq!(analytics.views[0].view_count, 4);
+  assert_eq!(analytics.views[0].unique_visitor_count, 1);
+}
+
+#[tokio::test]
+async fn get_publish_analytics_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_publish_analytics(&workspace_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn get_workspace_api_usage_by_owner() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  for _ in 0..3 {
+    owner
+      .api_client
+      .get_workspace_insights(&workspace_id, None)
+      .await
+      .unwrap();
+  }
+  member
+    .api_client
+    .get_workspace_insights(&workspace_id, None)
+    .await
+    .unwrap_err();
+
+  let api_usage = owner
+    .api_client
+    .get_workspace_api_usage(&workspace_id, Some(1))
+    .await
+    .unwrap();
+  let insights = api_usage
+    .route_families
+    .iter()
+    .find(|usage| usage.route_family == "workspace/insights")
+    .unwrap();
+  assert_eq!(insights.call_count, 4);
+  assert_eq!(insights.client_error_count, 1);
+  assert_eq!(insights.server_error_count, 0);
+  assert_eq!(insights.error_rate, 0.25);
+  assert_eq!(api_usage.days.len(), 1);
+  assert!(api_usage.days[0].call_count >= 4);
+}
+
+#[tokio::test]
+async fn get_workspace_api_usage_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_workspace_api_usage(&workspace_id, None)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn run_analytics_reports_by_owner() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let activity = owner
+    .api_client
+    .get_workspace_analytics_report(
+      &workspace_id,
+      AnalyticsReport::ActivityByMember,
+      &QueryAnalyticsReport::default(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(activity.columns[0], "uid");
+  assert_eq!(activity.rows.len(), 2);
+  // The pages of the getting started template are created by the owner.
+  assert!(activity.rows[0][3].as_i64().unwrap() > 0);
+  assert_eq!(activity.rows[1][3].as_i64(), Some(0));
+
+  let growth = owner
+    .api_client
+    .get_workspace_analytics_report(
+      &workspace_id,
+      AnalyticsReport::PageGrowth,
+      &QueryAnalyticsReport {
+        interval: Some(AnalyticsInterval::Month),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!growth.rows.is_empty());
+
+  let csv = owner
+    .api_client
+    .get_workspace_analytics_report_csv(
+      &workspace_id,
+      AnalyticsReport::CommentTrend,
+      &QueryAnalyticsReport::default(),
+    )
+    .await
+    .unwrap();
+  assert_eq!(csv.trim(), "period,comment_count,commenter_count");
+
+  let error = member
+    .api_client
+    .get_workspace_analytics_report(
+      &workspace_id,
+      AnalyticsReport::ActivityByMember,
+      &QueryAnalyticsReport::default(),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
@@ -1,6 +1,7 @@
 use app_error::ErrorCode;
-use client_api_test::generate_unique_registered_user_client;
-use database_entity::dto::{AFRole, AFWorkspaceInvitationStatus};
+use chrono::{Duration, Utc};
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use database_entity::dto::{AFRole, AFWorkspaceInvitationStatus, AFWorkspaceSettingsChange};
 use shared_entity::dto::workspace_dto::{QueryWorkspaceParam, WorkspaceMemberInvitation};
 
 #[tokio::test]
@@ -62,6 +63,21 @@ async fn invite_workspace_crud() {
   assert_eq!(invitation.status, AFWorkspaceInvitationStatus::Pending);
   assert_eq!(invitation.member_count.unwrap_or(0), 1);
 
+  // the preview of the invitation is public
+  let preview = localhost_client()
+    .get_workspace_invitation_preview(&invite_id)
+    .await
+    .unwrap();
+  assert_eq!(preview.inviter_name, invitation.inviter_name);
+  assert_eq!(preview.workspace_name, invitation.workspace_name);
+  assert_eq!(preview.status, AFWorkspaceInvitationStatus::Pending);
+  assert_eq!(preview.member_count.unwrap_or(0), 1);
+  let err = localhost_client()
+    .get_workspace_invitation_preview(&uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
   let (charlie_client, _charlie) = generate_unique_registered_user_client().await;
   let err = charlie_client
     .get_workspace_invitation(&invite_id)
@@ -98,6 +114,7 @@ async fn invite_workspace_crud() {
   let member_count = alice_client
     .get_workspaces_opt(QueryWorkspaceParam {
       include_member_count: Some(true),
+      ..Default::default()
     })
     .await
     .unwrap()
@@ -107,3 +124,87 @@ async fn invite_workspace_crud() {
     .unwrap();
   assert_eq!(member_count, 2);
 }
+
+#[tokio::test]
+async fn invitation_expiry_ttl() {
+  let (alice_client, _alice) = generate_unique_registered_user_client().await;
+  let alice_workspace_id = alice_client
+    .get_workspaces()
+    .await
+    .unwrap()
+    .first()
+    .unwrap()
+    .workspace_id
+    .to_string();
+
+  // the invitations expire after 7 days by default
+  let settings = alice_client
+    .get_workspace_settings(&alice_workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(settings.invitation_ttl_days, 7);
+
+  let (bob_client, bob) = generate_unique_registered_user_client().await;
+  alice_client
+    .invite_workspace_members(
+      &alice_workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: bob.email.clone(),
+        role: AFRole::Member,
+      }],
+    )
+    .await
+    .unwrap();
+  let invitation = bob_client
+    .list_workspace_invitations(Some(AFWorkspaceInvitationStatus::Pending))
+    .await
+    .unwrap()
+    .pop()
+    .unwrap();
+  let expires_at = invitation.expires_at.unwrap();
+  assert!(expires_at > Utc::now() + Duration::days(6));
+  assert!(expires_at <= Utc::now() + Duration::days(7));
+
+  // the TTL is capped
+  let err = alice_client
+    .update_workspace_settings(
+      &alice_workspace_id,
+      &AFWorkspaceSettingsChange::new().invitation_ttl_days(1000),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // the invitations sent once the TTL is 0 never expire
+  let settings = alice_client
+    .update_workspace_settings(
+      &alice_workspace_id,
+      &AFWorkspaceSettingsChange::new().invitation_ttl_days(0),
+    )
+    .await
+    .unwrap();
+  assert_eq!(settings.invitation_ttl_days, 0);
+
+  let (charlie_client, charlie) = generate_unique_registered_user_client().await;
+  alice_client
+    .invite_workspace_members(
+      &alice_workspace_id,
+      vec![WorkspaceMemberInvitation {
+        email: charlie.email.clone(),
+        role: AFRole::Member,
+      }],
+    )
+    .await
+    .unwrap();
+  let invitation = charlie_client
+    .list_workspace_invitations(Some(AFWorkspaceInvitationStatus::Pending))
+    .await
+    .unwrap()
+    .pop()
+    .unwrap();
+  assert!(invitation.expires_at.is_none());
+  charlie_client
+    .accept_workspace_invitation(&invitation.invite_id.to_string())
+    .await
+    .unwrap();
+}
@@ -1,8 +1,11 @@
 use app_error::ErrorCode;
+use chrono::{Duration, Utc};
 use client_api::entity::AFWorkspaceInvitationStatus;
 use client_api_test::{api_client_with_email, TestClient};
 use database_entity::dto::{AFAccessLevel, AFRole, QueryCollabMembers};
-use shared_entity::dto::workspace_dto::WorkspaceMemberInvitation;
+use shared_entity::dto::workspace_dto::{
+  QueryWorkspaceMembers, WorkspaceMemberChangeset, WorkspaceMemberInvitation,
+};
 
 #[tokio::test]
 async fn get_workspace_owner_after_sign_up_test() {
@@ -35,6 +38,65 @@ async fn get_workspace_owner_after_sign_up_test() {
   );
 }
 
+#[tokio::test]
+async fn get_workspace_members_paginated_test() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member_1 = TestClient::new_user_without_ws_conn().await;
+  let member_2 = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  for member in [&member_1, &member_2] {
+    owner
+      .invite_and_accepted_workspace_member(&workspace_id, member, AFRole::Member)
+      .await
+      .unwrap();
+  }
+
+  let first_page = owner
+    .api_client
+    .get_workspace_members_paginated(
+      &workspace_id,
+      &QueryWorkspaceMembers {
+        limit: Some(2),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(first_page.total_count, 3);
+  assert_eq!(first_page.members.len(), 2);
+  assert_eq!(first_page.members[0].email, owner.email().await);
+
+  let second_page = owner
+    .api_client
+    .get_workspace_members_paginated(
+      &workspace_id,
+      &QueryWorkspaceMembers {
+        limit: Some(2),
+        offset: Some(2),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(second_page.total_count, 3);
+  assert_eq!(second_page.members.len(), 1);
+
+  let member_2_email = member_2.email().await;
+  let search_page = member_1
+    .api_client
+    .get_workspace_members_paginated(
+      &workspace_id,
+      &QueryWorkspaceMembers {
+        search: Some(member_2_email.to_uppercase()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(search_page.total_count, 1);
+  assert_eq!(search_page.members[0].email, member_2_email);
+}
+
 #[tokio::test]
 async fn workspace_members_through_invite_or_direct_add() {
   let owner = TestClient::new_user_without_ws_conn().await;
@@ -208,6 +270,58 @@ async fn update_workspace_member_role_from_guest_to_member() {
   assert_eq!(members[1].role, AFRole::Member);
 }
 
+#[tokio::test]
+async fn workspace_member_access_expires() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  // the access of the owner can not expire
+  let error = owner
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(owner.email().await)
+        .with_expires_at(Utc::now() + Duration::days(1)),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  owner
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(member.email().await)
+        .with_expires_at(Utc::now() + Duration::seconds(2)),
+    )
+    .await
+    .unwrap();
+  let members = owner.get_workspace_members(&workspace_id).await;
+  assert!(members[0].expires_at.is_none());
+  assert!(members[1].expires_at.is_some());
+  member
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap();
+
+  // the member loses access to the workspace once the access has expired
+  tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+  let error = member
+    .api_client
+    .get_workspace_members(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+  let members = owner.get_workspace_members(&workspace_id).await;
+  assert_eq!(members.len(), 1);
+}
+
 #[tokio::test]
 async fn workspace_add_member() {
   let owner = TestClient::new_user_without_ws_conn().await;
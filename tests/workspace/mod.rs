@@ -1,13 +1,29 @@
 mod access_request;
+mod archive;
+mod audit_log;
+mod batch_comments;
+mod bulk_invite;
+mod comment_attachment;
+mod comment_subscription;
+mod custom_emoji;
+mod custom_role;
 mod default_user_workspace;
+mod duplicate;
 mod edit_workspace;
 mod import_test;
+mod insights;
 mod invitation_crud;
 mod member_crud;
+mod oembed;
 mod page_view;
+mod permission_audit;
+mod public_access;
 mod publish;
 mod published_data;
+mod saml;
+mod secret_scan;
 mod template;
+mod view_permission;
 mod workspace_crud;
 mod workspace_folder;
 mod workspace_settings;
@@ -0,0 +1,53 @@
+use app_error::ErrorCode;
+use client_api_test::{generate_unique_registered_user_client, workspace_id_from_client};
+use shared_entity::dto::workspace_dto::OEmbedParams;
+
+fn oembed_params(url: &str) -> OEmbedParams {
+  OEmbedParams {
+    url: url.to_string(),
+    max_width: None,
+    max_height: None,
+  }
+}
+
+#[tokio::test]
+async fn resolve_oembed_of_untrusted_url_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+
+  for url in [
+    "https://evil.example.com/video/1",
+    // Only the subdomains of the provider hosts are trusted, not the hosts ending with them.
+    "https://notyoutube.com/watch?v=1",
+    "javascript:alert(1)",
+    "http://localhost:8000/api/health",
+  ] {
+    let err = c
+      .resolve_oembed(&workspace_id, &oembed_params(url))
+      .await
+      .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRequest, "{}: {:?}", url, err);
+  }
+
+  let err = c
+    .resolve_oembed(&workspace_id, &oembed_params("not a url"))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidUrl);
+}
+
+#[tokio::test]
+async fn resolve_oembed_by_non_member_test() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = workspace_id_from_client(&c).await;
+  let (other, _other_user) = generate_unique_registered_user_client().await;
+
+  let err = other
+    .resolve_oembed(
+      &workspace_id,
+      &oembed_params("https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
@@ -1,9 +1,22 @@
 use std::time::Duration;
 
+use app_error::ErrorCode;
 use client_api::entity::{QueryCollab, QueryCollabParams};
-use client_api_test::generate_unique_registered_user_client;
+use client_api::ws::{WSClient, WSClientConfig};
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::Collab;
 use collab_entity::CollabType;
-use shared_entity::dto::workspace_dto::{CreatePageParams, ViewLayout};
+use collab_rt_entity::user::{AFFolderChange, AFFolderChangeKind, UserMessage};
+use shared_entity::dto::workspace_dto::{
+  AppendBlockToPageParams, BatchCreatePageParams, BatchPageParams, CollabExportFormat,
+  CreatePageParams, CreatePageShareLinkParams, CreatePageTemplateParams, IconType,
+  ImportPageFormat, ImportPageParams, PageContent, PageOperation, PageOperationsParams,
+  PageTemplateSource, PatchPageParams, ReorderPageParams, UpdatePageTemplateParams, ViewIcon,
+  ViewLayout,
+};
+use tokio::sync::broadcast::Receiver;
 use tokio::time::sleep;
 use uuid::Uuid;
 
@@ -67,6 +80,9 @@ async fn create_new_document_page() {
       &CreatePageParams {
         parent_view_id: general_space.view_id.clone(),
         layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
       },
     )
     .await
@@ -96,3 +112,1189 @@ async fn create_new_document_page() {
   .await
   .unwrap();
 }
+
+#[tokio::test]
+async fn rename_and_move_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let mut pages = vec![];
+  for _ in 0..2 {
+    let page = c
+      .create_workspace_page_view(
+        workspace_id,
+        &CreatePageParams {
+          parent_view_id: general_space.view_id.clone(),
+          layout: ViewLayout::Document,
+          name: None,
+          content: None,
+          template_id: None,
+        },
+      )
+      .await
+      .unwrap();
+    pages.push(Uuid::parse_str(&page.view_id).unwrap());
+  }
+  let (child, parent) = (pages[0], pages[1]);
+
+  let icon = ViewIcon {
+    ty: IconType::Emoji,
+    value: "🚀".to_string(),
+  };
+  c.update_workspace_page_view(
+    workspace_id,
+    child,
+    &PatchPageParams {
+      name: Some("Renamed".to_string()),
+      icon: Some(Some(icon.clone())),
+      parent_view_id: Some(parent.to_string()),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(3), Some(parent.to_string()))
+    .await
+    .unwrap();
+  let moved = folder_view
+    .children
+    .iter()
+    .find(|v| v.view_id == child.to_string())
+    .unwrap();
+  assert_eq!(moved.name, "Renamed");
+  assert_eq!(moved.icon, Some(icon));
+
+  // A missing field leaves the view unchanged, and `null` removes the icon.
+  c.update_workspace_page_view(
+    workspace_id,
+    child,
+    &PatchPageParams {
+      icon: Some(None),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  let view = c
+    .get_workspace_page_view(workspace_id, child)
+    .await
+    .unwrap()
+    .view;
+  assert_eq!(view.name, "Renamed");
+  assert_eq!(view.icon, None);
+
+  let err = c
+    .update_workspace_page_view(
+      workspace_id,
+      parent,
+      &PatchPageParams {
+        parent_view_id: Some(child.to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = c
+    .update_workspace_page_view(
+      workspace_id,
+      parent,
+      &PatchPageParams {
+        name: Some(" ".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn reorder_pages() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let mut page_ids = vec![];
+  for _ in 0..3 {
+    let page = c
+      .create_workspace_page_view(
+        workspace_id,
+        &CreatePageParams {
+          parent_view_id: general_space.view_id.clone(),
+          layout: ViewLayout::Document,
+          name: None,
+          content: None,
+          template_id: None,
+        },
+      )
+      .await
+      .unwrap();
+    page_ids.push(page.view_id);
+  }
+  let get_page_order = || async {
+    let children = c
+      .get_workspace_folder(
+        &workspace_id.to_string(),
+        Some(1),
+        Some(general_space.view_id.clone()),
+      )
+      .await
+      .unwrap()
+      .children;
+    children
+      .into_iter()
+      .map(|v| v.view_id)
+      .filter(|view_id| page_ids.contains(view_id))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(get_page_order().await, page_ids);
+
+  let last_page_id = Uuid::parse_str(&page_ids[2]).unwrap();
+  c.reorder_workspace_page_view(
+    workspace_id,
+    last_page_id,
+    &ReorderPageParams {
+      before_view_id: Some(page_ids[0].clone()),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  assert_eq!(
+    get_page_order().await,
+    vec![
+      page_ids[2].clone(),
+      page_ids[0].clone(),
+      page_ids[1].clone()
+    ]
+  );
+
+  c.reorder_workspace_page_view(
+    workspace_id,
+    last_page_id,
+    &ReorderPageParams {
+      after_view_id: Some(page_ids[1].clone()),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  assert_eq!(get_page_order().await, page_ids);
+
+  let err = c
+    .reorder_workspace_page_view(workspace_id, last_page_id, &ReorderPageParams::default())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = c
+    .reorder_workspace_page_view(
+      workspace_id,
+      last_page_id,
+      &ReorderPageParams {
+        after_view_id: Some(general_space.view_id.clone()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn move_page_to_trash() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let child_page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: page.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+
+  c.move_workspace_page_view_to_trash(workspace_id, Uuid::parse_str(&page.view_id).unwrap())
+    .await
+    .unwrap();
+  let trash = c
+    .get_workspace_trash(&workspace_id.to_string())
+    .await
+    .unwrap();
+  let trashed_ids: Vec<_> = trash.views.iter().map(|v| v.view.view_id.clone()).collect();
+  assert!(trashed_ids.contains(&page.view_id));
+  assert!(trashed_ids.contains(&child_page.view_id));
+
+  // Moving the view to the trash again keeps a single trash entry.
+  c.move_workspace_page_view_to_trash(workspace_id, Uuid::parse_str(&page.view_id).unwrap())
+    .await
+    .unwrap();
+  let trash = c
+    .get_workspace_trash(&workspace_id.to_string())
+    .await
+    .unwrap();
+  assert_eq!(trash.views.len(), trashed_ids.len());
+
+  let err = c
+    .move_workspace_page_view_to_trash(
+      workspace_id,
+      Uuid::parse_str(&general_space.view_id).unwrap(),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = c
+    .move_workspace_page_view_to_trash(workspace_id, Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn notify_folder_change_of_created_and_trashed_pages() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let ws_client = WSClient::new(WSClientConfig::default(), c.clone(), c.clone());
+  let mut user_change_recv = ws_client.subscribe_user_changed();
+  ws_client.connect().await.unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let change = next_folder_change(&mut user_change_recv).await;
+  assert_eq!(change.workspace_id, workspace_id.to_string());
+  assert_eq!(change.kind, AFFolderChangeKind::Created);
+  assert_eq!(change.view_ids, vec![page.view_id.clone()]);
+  assert_eq!(change.parent_view_id, Some(general_space.view_id.clone()));
+
+  c.move_workspace_page_view_to_trash(workspace_id, Uuid::parse_str(&page.view_id).unwrap())
+    .await
+    .unwrap();
+  let change = next_folder_change(&mut user_change_recv).await;
+  assert_eq!(change.kind, AFFolderChangeKind::Trashed);
+  assert_eq!(change.view_ids, vec![page.view_id]);
+}
+
+async fn next_folder_change(user_change_recv: &mut Receiver<UserMessage>) -> AFFolderChange {
+  loop {
+    let message = tokio::time::timeout(Duration::from_secs(5), user_change_recv.recv())
+      .await
+      .unwrap()
+      .unwrap();
+    if let UserMessage::FolderChange(change) = message {
+      return change;
+    }
+  }
+}
+
+#[tokio::test]
+async fn duplicate_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let get_general_space = || async {
+    c.get_workspace_folder(&workspace_id.to_string(), Some(3), None)
+      .await
+      .unwrap()
+      .children
+      .into_iter()
+      .find(|v| v.name == "General")
+      .unwrap()
+  };
+  let general_space = get_general_space().await;
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let child_page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: page.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+
+  let copy = c
+    .duplicate_workspace_page_view(workspace_id, Uuid::parse_str(&page.view_id).unwrap())
+    .await
+    .unwrap();
+  assert_ne!(copy.view_id, page.view_id);
+  let general_space = get_general_space().await;
+  let position = general_space
+    .children
+    .iter()
+    .position(|v| v.view_id == page.view_id)
+    .unwrap();
+  let copied_view = &general_space.children[position + 1];
+  assert_eq!(copied_view.view_id, copy.view_id);
+  assert!(copied_view.name.ends_with("(copy)"));
+  assert_eq!(copied_view.children.len(), 1);
+  assert_ne!(copied_view.children[0].view_id, child_page.view_id);
+  let copied_child_id = Uuid::parse_str(&copied_view.children[0].view_id).unwrap();
+  c.get_workspace_page_view(workspace_id, copied_child_id)
+    .await
+    .unwrap();
+
+  // The rows of a database are copied along with it.
+  let todo = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap();
+  let todo_copy = c
+    .duplicate_workspace_page_view(workspace_id, Uuid::parse_str(&todo.view_id).unwrap())
+    .await
+    .unwrap();
+  let todo_view = c
+    .get_workspace_page_view(workspace_id, Uuid::parse_str(&todo.view_id).unwrap())
+    .await
+    .unwrap();
+  let todo_copy_view = c
+    .get_workspace_page_view(workspace_id, Uuid::parse_str(&todo_copy.view_id).unwrap())
+    .await
+    .unwrap();
+  assert_eq!(
+    todo_copy_view.data.row_data.len(),
+    todo_view.data.row_data.len()
+  );
+  assert!(todo_copy_view
+    .data
+    .row_data
+    .keys()
+    .all(|row_id| !todo_view.data.row_data.contains_key(row_id)));
+
+  let err = c
+    .duplicate_workspace_page_view(
+      workspace_id,
+      Uuid::parse_str(&general_space.view_id).unwrap(),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let err = c
+    .duplicate_workspace_page_view(workspace_id, Uuid::new_v4())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn import_page_from_markdown_and_html() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+
+  let markdown_page = c
+    .import_page_from_markdown(
+      workspace_id,
+      &general_space.view_id,
+      None,
+      "# Imported notes\n\nSome **bold** text.\n\n- first\n- second\n",
+    )
+    .await
+    .unwrap();
+  let html_page = c
+    .import_page(
+      workspace_id,
+      &ImportPageParams {
+        parent_view_id: general_space.view_id.clone(),
+        name: Some("From HTML".to_string()),
+        format: ImportPageFormat::Html,
+        content: "<h2>Agenda</h2><p>Meet <a href=\"https://appflowy.io\">here</a></p><ol><li>One</li><li>Two</li></ol>".to_string(),
+        decryption: None,
+      },
+    )
+    .await
+    .unwrap();
+  sleep(Duration::from_secs(1)).await;
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let name_of = |view_id: &str| {
+    general_space
+      .children
+      .iter()
+      .find(|v| v.view_id == view_id)
+      .map(|v| v.name.clone())
+  };
+  assert_eq!(
+    name_of(&markdown_page.view_id).as_deref(),
+    Some("Imported notes")
+  );
+  assert_eq!(name_of(&html_page.view_id).as_deref(), Some("From HTML"));
+
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &markdown_page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  assert!(markdown.contains("# Imported notes"));
+  assert!(markdown.contains("**bold**"));
+  assert!(markdown.contains("- first\n- second"));
+
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &html_page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  assert!(markdown.contains("## Agenda"));
+  assert!(markdown.contains("[here](https://appflowy.io)"));
+  assert!(markdown.contains("1. One\n1. Two"));
+}
+
+#[tokio::test]
+async fn create_page_with_initial_content() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+
+  let markdown_page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: Some(PageContent::Markdown(
+          "# Meeting notes\n\nSome **bold** text.\n".to_string(),
+        )),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let blocks_page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("From blocks".to_string()),
+        content: Some(PageContent::Blocks(serde_json::json!({
+          "type": "page",
+          "children": [
+            {
+              "type": "heading",
+              "data": { "level": 2, "delta": [{ "insert": "Agenda" }] }
+            },
+            {
+              "type": "paragraph",
+              "data": { "delta": [{ "insert": "Discuss the roadmap" }] }
+            }
+          ]
+        }))),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  sleep(Duration::from_secs(1)).await;
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let name_of = |view_id: &str| {
+    general_space
+      .children
+      .iter()
+      .find(|v| v.view_id == view_id)
+      .map(|v| v.name.clone())
+  };
+  assert_eq!(
+    name_of(&markdown_page.view_id).as_deref(),
+    Some("Meeting notes")
+  );
+  assert_eq!(
+    name_of(&blocks_page.view_id).as_deref(),
+    Some("From blocks")
+  );
+
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &markdown_page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  assert!(markdown.contains("# Meeting notes"));
+  assert!(markdown.contains("**bold**"));
+
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &blocks_page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  assert!(markdown.contains("## Agenda"));
+  assert!(markdown.contains("Discuss the roadmap"));
+
+  // The root of the blocks must be the page
+  let err = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: Some(PageContent::Blocks(serde_json::json!({
+          "type": "paragraph",
+          "data": { "delta": [{ "insert": "Not a page" }] }
+        }))),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn apply_page_operations_atomically() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let parent = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("Parent".to_string()),
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+
+  let result = c
+    .apply_workspace_page_operations(
+      workspace_id,
+      &PageOperationsParams {
+        operations: vec![
+          PageOperation::CreatePage {
+            parent_view_id: parent.view_id.clone(),
+            name: Some("Child".to_string()),
+            content: Some(PageContent::Markdown("Hello".to_string())),
+            insert_reference: true,
+          },
+          PageOperation::CreatePage {
+            parent_view_id: parent.view_id.clone(),
+            name: Some("Sibling".to_string()),
+            content: None,
+            insert_reference: false,
+          },
+        ],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(result.pages.len(), 2);
+  let child_view_id = result.pages[0].view_id.clone();
+  sleep(Duration::from_secs(1)).await;
+
+  let child_names = || async {
+    let folder_view = c
+      .get_workspace_folder(&workspace_id.to_string(), Some(3), None)
+      .await
+      .unwrap();
+    folder_view
+      .children
+      .into_iter()
+      .find(|v| v.name == "General")
+      .unwrap()
+      .children
+      .into_iter()
+      .find(|v| v.view_id == parent.view_id)
+      .unwrap()
+      .children
+      .into_iter()
+      .map(|v| v.name)
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(child_names().await, vec!["Child", "Sibling"]);
+  let parent_collab = c
+    .get_workspace_page_view(workspace_id, Uuid::parse_str(&parent.view_id).unwrap())
+    .await
+    .unwrap();
+  let parent_json = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &parent.view_id,
+    DataSource::DocStateV1(parent_collab.data.encoded_collab),
+    vec![],
+    false,
+  )
+  .unwrap()
+  .to_json_value();
+  assert!(parent_json.to_string().contains(&child_view_id));
+
+  // A failing operation leaves nothing behind
+  let err = c
+    .apply_workspace_page_operations(
+      workspace_id,
+      &PageOperationsParams {
+        operations: vec![
+          PageOperation::CreatePage {
+            parent_view_id: parent.view_id.clone(),
+            name: Some("Orphan".to_string()),
+            content: None,
+            insert_reference: false,
+          },
+          PageOperation::InsertPageReference {
+            document_view_id: Uuid::new_v4().to_string(),
+            page_view_id: child_view_id.clone(),
+          },
+        ],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  assert_eq!(child_names().await, vec!["Child", "Sibling"]);
+}
+
+#[tokio::test]
+async fn batch_create_page_views() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = |client_id: &str, children: Vec<BatchPageParams>| BatchPageParams {
+    client_id: client_id.to_string(),
+    name: None,
+    content: Some(PageContent::Markdown(format!("# {}", client_id))),
+    children,
+  };
+
+  let result = c
+    .batch_create_workspace_page_views(
+      workspace_id,
+      &BatchCreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        pages: vec![
+          page(
+            "Notes",
+            vec![page("Monday", vec![]), page("Tuesday", vec![])],
+          ),
+          page("Archive", vec![]),
+        ],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(result.view_ids.len(), 4);
+  sleep(Duration::from_secs(1)).await;
+
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(3), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let notes = general_space
+    .children
+    .iter()
+    .find(|v| v.view_id == result.view_ids["Notes"])
+    .unwrap();
+  assert_eq!(notes.name, "Notes");
+  assert_eq!(
+    notes
+      .children
+      .iter()
+      .map(|v| v.view_id.clone())
+      .collect::<Vec<_>>(),
+    vec![
+      result.view_ids["Monday"].clone(),
+      result.view_ids["Tuesday"].clone()
+    ]
+  );
+  assert!(general_space
+    .children
+    .iter()
+    .any(|v| v.view_id == result.view_ids["Archive"]));
+
+  // A client id used twice fails the whole batch
+  let err = c
+    .batch_create_workspace_page_views(
+      workspace_id,
+      &BatchCreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        pages: vec![page("Duplicate", vec![page("Duplicate", vec![])])],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn get_page_view_content_as_markdown_and_text() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: Some(PageContent::Markdown(
+          "# Release notes\n\nThe **new** editor".to_string(),
+        )),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = Uuid::parse_str(&page.view_id).unwrap();
+
+  let markdown = c
+    .get_workspace_page_view_content(workspace_id, view_id, CollabExportFormat::Markdown)
+    .await
+    .unwrap();
+  assert_eq!(markdown.name, "Release notes");
+  assert!(markdown.content.contains("# Release notes"));
+  assert!(markdown.content.contains("**new**"));
+
+  let text = c
+    .get_workspace_page_view_content(workspace_id, view_id, CollabExportFormat::Text)
+    .await
+    .unwrap();
+  assert_eq!(text.content, "Release notes\nThe new editor\n");
+
+  let err = c
+    .get_workspace_page_view_content(workspace_id, Uuid::new_v4(), CollabExportFormat::Text)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidFolderView);
+}
+
+#[tokio::test]
+async fn share_page_with_link() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("Shared".to_string()),
+        content: Some(PageContent::Markdown("Hello".to_string())),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = Uuid::parse_str(&page.view_id).unwrap();
+  let link = c
+    .create_workspace_page_share_link(
+      workspace_id,
+      view_id,
+      &CreatePageShareLinkParams {
+        expires_at: Some(chrono::Utc::now() + chrono::Duration::days(1)),
+      },
+    )
+    .await
+    .unwrap();
+
+  // The token grants the access to the page, without signing in
+  let anonymous = localhost_client();
+  let shared_page = anonymous.get_shared_page(link.token).await.unwrap();
+  assert_eq!(shared_page.view_id, view_id);
+  assert_eq!(shared_page.name, "Shared");
+  let shared_json = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &page.view_id,
+    DataSource::DocStateV1(shared_page.encoded_collab),
+    vec![],
+    false,
+  )
+  .unwrap()
+  .to_json_value();
+  assert!(shared_json.to_string().contains("Hello"));
+
+  // The token is accepted by the collab read path, for the document of the page only
+  let query_page = QueryCollabParams::new(&page.view_id, CollabType::Document, workspace_id);
+  anonymous
+    .get_collab_with_share_link(&query_page, &link.token)
+    .await
+    .unwrap();
+  let err = anonymous
+    .get_collab_with_share_link(
+      &QueryCollabParams::new(&general_space.view_id, CollabType::Document, workspace_id),
+      &link.token,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let links = c
+    .list_workspace_page_share_links(workspace_id, view_id)
+    .await
+    .unwrap();
+  assert_eq!(links.len(), 1);
+  c.revoke_workspace_page_share_link(workspace_id, view_id, link.link_id)
+    .await
+    .unwrap();
+  let err = anonymous.get_shared_page(link.token).await.unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let err = anonymous
+    .get_collab_with_share_link(&query_page, &link.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // A link can't expire in the past
+  let err = c
+    .create_workspace_page_share_link(
+      workspace_id,
+      view_id,
+      &CreatePageShareLinkParams {
+        expires_at: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn append_blocks_to_page() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: Some(PageContent::Markdown("# Standup\n".to_string())),
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = Uuid::parse_str(&page.view_id).unwrap();
+
+  c.append_block_to_workspace_page_view(
+    workspace_id,
+    view_id,
+    &AppendBlockToPageParams {
+      blocks: vec![
+        serde_json::json!({
+          "type": "heading",
+          "data": { "level": 2, "delta": [{ "insert": "Action items" }] }
+        }),
+        serde_json::json!({
+          "type": "todo_list",
+          "data": { "checked": false, "delta": [{ "insert": "Send the notes" }] }
+        }),
+        serde_json::json!({
+          "type": "paragraph",
+          "data": { "delta": [{ "insert": "Recorded by the bot" }] }
+        }),
+      ],
+    },
+  )
+  .await
+  .unwrap();
+
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  let standup = markdown.find("# Standup").unwrap();
+  let action_items = markdown.find("## Action items").unwrap();
+  let todo = markdown.find("Send the notes").unwrap();
+  let paragraph = markdown.find("Recorded by the bot").unwrap();
+  assert!(standup < action_items && action_items < todo && todo < paragraph);
+
+  let err = c
+    .append_block_to_workspace_page_view(
+      workspace_id,
+      view_id,
+      &AppendBlockToPageParams { blocks: vec![] },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn create_page_from_template() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0].workspace_id;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+
+  let template = c
+    .create_workspace_page_template(
+      workspace_id,
+      &CreatePageTemplateParams {
+        name: "Weekly review".to_string(),
+        description: Some("What went well, what didn't".to_string()),
+        source: PageTemplateSource::Content(PageContent::Markdown(
+          "## Wins\n\n## Blockers\n".to_string(),
+        )),
+      },
+    )
+    .await
+    .unwrap();
+  let templates = c.list_workspace_page_templates(workspace_id).await.unwrap();
+  assert_eq!(templates.len(), 1);
+  assert_eq!(templates[0].template_id, template.template_id);
+
+  let page = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: Some(template.template_id),
+      },
+    )
+    .await
+    .unwrap();
+  let markdown = c
+    .export_document(
+      &workspace_id.to_string(),
+      &page.view_id,
+      CollabExportFormat::Markdown,
+    )
+    .await
+    .unwrap();
+  assert!(markdown.contains("## Wins"));
+  assert!(markdown.contains("## Blockers"));
+  sleep(Duration::from_secs(1)).await;
+  let folder_view = c
+    .get_workspace_folder(&workspace_id.to_string(), Some(2), None)
+    .await
+    .unwrap();
+  let general_space = &folder_view
+    .children
+    .into_iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let view = general_space
+    .children
+    .iter()
+    .find(|v| v.view_id == page.view_id)
+    .unwrap();
+  assert_eq!(view.name, "Weekly review");
+
+  // The template can be replaced with a copy of a page
+  let template = c
+    .update_workspace_page_template(
+      workspace_id,
+      template.template_id,
+      &UpdatePageTemplateParams {
+        name: Some("Retro".to_string()),
+        source: Some(PageTemplateSource::View(page.view_id.clone())),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(template.name, "Retro");
+  assert_eq!(
+    template.description.as_deref(),
+    Some("What went well, what didn't")
+  );
+
+  c.delete_workspace_page_template(workspace_id, template.template_id)
+    .await
+    .unwrap();
+  let err = c
+    .get_workspace_page_template(workspace_id, template.template_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let err = c
+    .create_workspace_page_view(
+      workspace_id,
+      &CreatePageParams {
+        parent_view_id: general_space.view_id.clone(),
+        layout: ViewLayout::Document,
+        name: None,
+        content: None,
+        template_id: Some(template.template_id),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
@@ -0,0 +1,183 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use collab_entity::CollabType;
+use database_entity::dto::{
+  AFAccessLevel, AFRole, CollabMemberIdentify, CreateCollabParams, InsertCollabMemberParams,
+};
+use shared_entity::dto::workspace_dto::{
+  PermissionObjectType, PermissionRole, QueryPermissionAuditLog, WorkspaceMemberChangeset,
+};
+use uuid::Uuid;
+
+use crate::collab::util::test_encode_collab_v1;
+
+#[tokio::test]
+async fn record_workspace_member_permission_changes() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member_email = member.email().await;
+  let member_uid = member.uid().await;
+  let owner_uid = owner.uid().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_member(
+      &workspace_id,
+      WorkspaceMemberChangeset::new(member_email.clone()).with_role(AFRole::Guest),
+    )
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .remove_workspace_members(&workspace_id, vec![member_email.clone()])
+    .await
+    .unwrap();
+
+  let events = owner
+    .api_client
+    .get_permission_audit_log(&workspace_id, &QueryPermissionAuditLog::default())
+    .await
+    .unwrap();
+  let changes = events
+    .iter()
+    .map(|event| (event.old_role.clone(), event.new_role.clone()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    vec![
+      (Some(PermissionRole::Workspace(AFRole::Guest)), None),
+      (
+        Some(PermissionRole::Workspace(AFRole::Member)),
+        Some(PermissionRole::Workspace(AFRole::Guest))
+      ),
+      (None, Some(PermissionRole::Workspace(AFRole::Member))),
+    ]
+  );
+  for event in &events {
+    assert_eq!(event.actor_uid, Some(owner_uid));
+    assert_eq!(event.target_uid, member_uid);
+    assert_eq!(event.target_email, Some(member_email.clone()));
+    assert_eq!(event.object_type, PermissionObjectType::Workspace);
+    assert_eq!(event.object_id, workspace_id);
+  }
+
+  // filter by the user whose permission changed
+  let events = owner
+    .api_client
+    .get_permission_audit_log(
+      &workspace_id,
+      &QueryPermissionAuditLog {
+        target_uid: Some(owner_uid),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert!(events.is_empty());
+}
+
+#[tokio::test]
+async fn record_collab_member_permission_changes() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let other = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let other_uid = other.uid().await;
+  let object_id = Uuid::new_v4().to_string();
+  let encode_collab = test_encode_collab_v1(&object_id, "title", "hello world");
+  owner
+    .api_client
+    .create_collab(CreateCollabParams {
+      object_id: object_id.clone(),
+      encoded_collab_v1: encode_collab.encode_to_bytes().unwrap(),
+      collab_type: CollabType::Unknown,
+      workspace_id: workspace_id.clone(),
+    })
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .add_collab_member(InsertCollabMemberParams {
+      uid: other_uid,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadOnly,
+      expires_at: None,
+    })
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_collab_member(InsertCollabMemberParams {
+      uid: other_uid,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+      access_level: AFAccessLevel::ReadAndWrite,
+      expires_at: None,
+    })
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .remove_collab_member(CollabMemberIdentify {
+      uid: other_uid,
+      workspace_id: workspace_id.clone(),
+      object_id: object_id.clone(),
+    })
+    .await
+    .unwrap();
+
+  let events = owner
+    .api_client
+    .get_permission_audit_log(
+      &workspace_id,
+      &QueryPermissionAuditLog {
+        target_uid: Some(other_uid),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  let changes = events
+    .iter()
+    .map(|event| (event.old_role.clone(), event.new_role.clone()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    vec![
+      (
+        Some(PermissionRole::Collab(AFAccessLevel::ReadAndWrite)),
+        None
+      ),
+      (
+        Some(PermissionRole::Collab(AFAccessLevel::ReadOnly)),
+        Some(PermissionRole::Collab(AFAccessLevel::ReadAndWrite))
+      ),
+      (None, Some(PermissionRole::Collab(AFAccessLevel::ReadOnly))),
+    ]
+  );
+  assert!(events.iter().all(|event| {
+    event.object_type == PermissionObjectType::Collab && event.object_id == object_id
+  }));
+}
+
+#[tokio::test]
+async fn get_permission_audit_log_by_member() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let error = member
+    .api_client
+    .get_permission_audit_log(&workspace_id, &QueryPermissionAuditLog::default())
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
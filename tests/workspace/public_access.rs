@@ -0,0 +1,81 @@
+use app_error::ErrorCode;
+use client_api_test::{generate_unique_registered_user_client, localhost_client};
+use collab_entity::CollabType;
+
+#[tokio::test]
+async fn public_workspace_read_only_access() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let anonymous = localhost_client();
+
+  // the workspaces are private by default
+  let public_access = c.get_workspace_public_access(&workspace_id).await.unwrap();
+  assert!(!public_access.enabled);
+  let err = anonymous
+    .get_public_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  let public_access = c
+    .set_workspace_public_access(&workspace_id, true)
+    .await
+    .unwrap();
+  assert!(public_access.enabled);
+  assert!(public_access.enabled_at.is_some());
+
+  let folder_view = anonymous
+    .get_public_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let getting_started = general_space
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let resp = anonymous
+    .get_public_workspace_collab(
+      &workspace_id,
+      &getting_started.view_id,
+      CollabType::Document,
+    )
+    .await
+    .unwrap();
+  assert_eq!(resp.object_id, getting_started.view_id);
+  assert!(!resp.encode_collab.doc_state.is_empty());
+
+  // the folder itself is only exposed without the private spaces and the trash
+  let err = anonymous
+    .get_public_workspace_collab(&workspace_id, &workspace_id, CollabType::Folder)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // the public access is read-only and can only be changed by the owner
+  let (other, _other_user) = generate_unique_registered_user_client().await;
+  let err = other
+    .set_workspace_public_access(&workspace_id, false)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  c.set_workspace_public_access(&workspace_id, false)
+    .await
+    .unwrap();
+  let err = anonymous
+    .get_public_workspace_collab(
+      &workspace_id,
+      &getting_started.view_id,
+      CollabType::Document,
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
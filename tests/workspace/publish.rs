@@ -2,8 +2,10 @@ use app_error::ErrorCode;
 use appflowy_cloud::biz::collab::folder_view::collab_folder_to_folder_view;
 use appflowy_cloud::biz::workspace::ops::collab_from_doc_state;
 use client_api::entity::{
-  AFRole, GlobalComment, PatchPublishedCollab, PublishCollabItem, PublishCollabMetadata,
-  PublishInfoMeta,
+  AFRole, AFWorkspaceSettingsChange, CommentModerationAction, CommentSortOrder,
+  CreatePublishSubNamespace, GlobalComment, ModerateCommentsParams, PatchPublishedCollab,
+  PatchPublishedViewSeo, PublishCollabItem, PublishCollabMetadata, PublishInfoMeta,
+  QueryGlobalComments, UpdatePublishSubNamespace,
 };
 use client_api_test::TestClient;
 use client_api_test::{generate_unique_registered_user_client, localhost_client};
@@ -18,7 +20,12 @@ use collab_entity::CollabType;
 use collab_folder::{CollabOrigin, Folder, UserId};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use shared_entity::dto::publish_dto::PublishDatabaseData;
+use shared_entity::dto::publish_dto::{
+  PublishDatabaseData, PublishedDatabaseFilter, PublishedDatabaseFilterCondition,
+  QueryPublishedDatabaseRows,
+};
+use shared_entity::dto::workspace_dto::ViewLayout;
+use shared_entity::response::AppResponseError;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread::sleep;
@@ -358,6 +365,7 @@ async fn test_publish_doc() {
           view_id: view_id_1,
           // publish_name_2 already exists
           publish_name: Some(publish_name_2.to_string()),
+          expires_at: None,
         }],
       )
       .await
@@ -372,6 +380,7 @@ async fn test_publish_doc() {
       &[PatchPublishedCollab {
         view_id: view_id_1,
         publish_name: Some(new_publish_name_1.to_string()),
+        expires_at: None,
       }],
     )
     .await
@@ -399,6 +408,7 @@ async fn test_publish_doc() {
       &[PatchPublishedCollab {
         view_id: view_id_1,
         publish_name: Some(publish_name_1.to_string()),
+        expires_at: None,
       }],
     )
     .await
@@ -683,6 +693,440 @@ async fn test_excessive_comment_length() {
   assert_eq!(resp.unwrap_err().code, ErrorCode::StringLengthLimitReached);
 }
 
+#[tokio::test]
+async fn test_edit_comment() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&client).await;
+  let published_view_namespace = uuid::Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id.to_string(), published_view_namespace)
+    .await
+    .unwrap();
+
+  let publish_name = "published-view";
+  let view_id = uuid::Uuid::new_v4();
+  client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: publish_name.to_string(),
+          metadata: MyCustomMetadata {
+            title: "some_title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  let (commenter_client, _) = generate_unique_registered_user_client().await;
+  commenter_client
+    .create_comment_on_published_view(&view_id, "first version", &None)
+    .await
+    .unwrap();
+  let comment = commenter_client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments
+    .remove(0);
+  assert!(comment.can_be_edited);
+  assert!(comment.edited_at.is_none());
+
+  // The publisher can delete the comment, but only the author can edit it.
+  let comments = client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert!(comments[0].can_be_deleted);
+  assert!(!comments[0].can_be_edited);
+  let err = client
+    .update_comment_on_published_view(&view_id, &comment.comment_id, "not mine")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+  let err = commenter_client
+    .update_comment_on_published_view(&view_id, &comment.comment_id, "a".repeat(5001).as_str())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::StringLengthLimitReached);
+
+  commenter_client
+    .update_comment_on_published_view(&view_id, &comment.comment_id, "second version")
+    .await
+    .unwrap();
+  let comments = localhost_client()
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(comments[0].content, "second version");
+  assert!(comments[0].edited_at.is_some());
+  assert!(!comments[0].can_be_edited);
+
+  // The deleted comments can't be edited.
+  commenter_client
+    .delete_comment_on_published_view(&view_id, &comment.comment_id)
+    .await
+    .unwrap();
+  let err = commenter_client
+    .update_comment_on_published_view(&view_id, &comment.comment_id, "third version")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+}
+
+#[tokio::test]
+async fn test_comment_moderation() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&client).await;
+  let published_view_namespace = uuid::Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id.to_string(), published_view_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "published-view".to_string(),
+          metadata: MyCustomMetadata {
+            title: "some_title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  let (commenter_client, _) = generate_unique_registered_user_client().await;
+  let commenter_uuid = commenter_client.get_profile().await.unwrap().uuid;
+  for content in ["first", "second", "abusive"] {
+    commenter_client
+      .create_comment_on_published_view(&view_id, content, &None)
+      .await
+      .unwrap();
+  }
+  let comments = client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  let comment_id = |content: &str| {
+    comments
+      .iter()
+      .find(|comment| comment.content == content)
+      .unwrap()
+      .comment_id
+  };
+
+  // Only the owners of the workspace can moderate the comments.
+  let err = commenter_client
+    .moderate_comments_on_published_view(
+      &view_id,
+      &ModerateCommentsParams {
+        action: CommentModerationAction::Hide,
+        comment_ids: vec![comment_id("first")],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = commenter_client
+    .get_comment_blocklist(&view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // The pinned comments are listed first, the hidden ones are only listed for the owners.
+  let moderation = client
+    .moderate_comments_on_published_view(
+      &view_id,
+      &ModerateCommentsParams {
+        action: CommentModerationAction::Pin,
+        comment_ids: vec![comment_id("first"), uuid::Uuid::new_v4()],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(moderation.comment_ids, vec![comment_id("first")]);
+  client
+    .moderate_comments_on_published_view(
+      &view_id,
+      &ModerateCommentsParams {
+        action: CommentModerationAction::Hide,
+        comment_ids: vec![comment_id("abusive")],
+      },
+    )
+    .await
+    .unwrap();
+  let public_comments = localhost_client()
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(
+    public_comments
+      .iter()
+      .map(|comment| comment.content.as_str())
+      .collect::<Vec<_>>(),
+    vec!["first", "second"]
+  );
+  assert!(public_comments[0].is_pinned);
+  let owner_comments = client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert_eq!(owner_comments.len(), 3);
+  assert!(
+    owner_comments
+      .iter()
+      .find(|comment| comment.content == "abusive")
+      .unwrap()
+      .is_hidden
+  );
+
+  // Bulk delete
+  let moderation = client
+    .moderate_comments_on_published_view(
+      &view_id,
+      &ModerateCommentsParams {
+        action: CommentModerationAction::Delete,
+        comment_ids: vec![comment_id("second"), comment_id("abusive")],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(moderation.comment_ids.len(), 2);
+  let comments = localhost_client()
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  assert!(comments
+    .iter()
+    .filter(|comment| comment.content != "first")
+    .all(|comment| comment.is_deleted));
+
+  // The blocked users can neither comment nor react.
+  let blocklist = client
+    .block_commenter(&view_id, &commenter_uuid)
+    .await
+    .unwrap();
+  assert_eq!(blocklist.users.len(), 1);
+  assert_eq!(blocklist.users[0].uuid, commenter_uuid);
+  let err = commenter_client
+    .create_comment_on_published_view(&view_id, "again", &None)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = commenter_client
+    .create_reaction_on_comment("👍", &view_id, &comment_id("first"))
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let blocklist = client
+    .unblock_commenter(&view_id, &commenter_uuid)
+    .await
+    .unwrap();
+  assert!(blocklist.users.is_empty());
+  commenter_client
+    .create_comment_on_published_view(&view_id, "again", &None)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_paginated_threaded_comments() {
+  let (client, _) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&client).await;
+  let published_view_namespace = uuid::Uuid::new_v4().to_string();
+  client
+    .set_workspace_publish_namespace(&workspace_id.to_string(), published_view_namespace)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "published-view".to_string(),
+          metadata: MyCustomMetadata {
+            title: "some_title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data_1".as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+
+  let (commenter_client, _) = generate_unique_registered_user_client().await;
+  let comment_id = |comments: &[GlobalComment], content: &str| {
+    comments
+      .iter()
+      .find(|comment| comment.content == content)
+      .unwrap()
+      .comment_id
+  };
+  for content in ["first", "second", "third"] {
+    commenter_client
+      .create_comment_on_published_view(&view_id, content, &None)
+      .await
+      .unwrap();
+  }
+  let comments = client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  let first_comment_id = comment_id(&comments, "first");
+  let second_comment_id = comment_id(&comments, "second");
+  client
+    .create_comment_on_published_view(&view_id, "reply", &Some(first_comment_id))
+    .await
+    .unwrap();
+  let comments = client
+    .get_published_view_comments(&view_id)
+    .await
+    .unwrap()
+    .comments;
+  let reply_comment_id = comment_id(&comments, "reply");
+  commenter_client
+    .create_comment_on_published_view(&view_id, "nested reply", &Some(reply_comment_id))
+    .await
+    .unwrap();
+  for reacting_client in [&client, &commenter_client] {
+    reacting_client
+      .create_reaction_on_comment("🎉", &view_id, &second_comment_id)
+      .await
+      .unwrap();
+  }
+
+  // Without a query, all the comments are returned flat.
+  let comments = client.get_published_view_comments(&view_id).await.unwrap();
+  assert_eq!(comments.comments.len(), 5);
+  assert!(comments.next_cursor.is_none());
+  assert!(comments
+    .comments
+    .iter()
+    .all(|comment| comment.replies.is_empty()));
+
+  // Flat pages, the most recent first.
+  let mut query = QueryGlobalComments {
+    limit: Some(2),
+    ..Default::default()
+  };
+  let mut contents = vec![];
+  loop {
+    let page = client
+      .query_published_view_comments(&view_id, &query)
+      .await
+      .unwrap();
+    assert!(page.comments.len() <= 2);
+    contents.extend(page.comments.into_iter().map(|comment| comment.content));
+    match page.next_cursor {
+      Some(cursor) => query.cursor = Some(cursor),
+      None => break,
+    }
+  }
+  assert_eq!(
+    contents,
+    vec!["nested reply", "reply", "third", "second", "first"]
+  );
+
+  // Threads, the oldest first, with the replies nested.
+  let query = QueryGlobalComments {
+    limit: Some(2),
+    sort: CommentSortOrder::Oldest,
+    threaded: true,
+    ..Default::default()
+  };
+  let page = commenter_client
+    .query_published_view_comments(&view_id, &query)
+    .await
+    .unwrap();
+  assert_eq!(page.comments.len(), 2);
+  assert_eq!(page.comments[0].content, "first");
+  assert_eq!(page.comments[0].replies.len(), 1);
+  assert_eq!(page.comments[0].replies[0].content, "reply");
+  assert_eq!(page.comments[0].replies[0].replies.len(), 1);
+  assert_eq!(
+    page.comments[0].replies[0].replies[0].content,
+    "nested reply"
+  );
+  assert_eq!(page.comments[1].content, "second");
+  let page = commenter_client
+    .query_published_view_comments(
+      &view_id,
+      &QueryGlobalComments {
+        cursor: page.next_cursor,
+        ..query.clone()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.comments.len(), 1);
+  assert_eq!(page.comments[0].content, "third");
+  assert!(page.next_cursor.is_none());
+
+  // The comments with the most reactions first.
+  let page = commenter_client
+    .query_published_view_comments(
+      &view_id,
+      &QueryGlobalComments {
+        sort: CommentSortOrder::Top,
+        threaded: true,
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.comments.len(), 3);
+  assert_eq!(page.comments[0].content, "second");
+
+  // The replies to a comment only.
+  let page = commenter_client
+    .query_published_view_comments(
+      &view_id,
+      &QueryGlobalComments {
+        reply_to: Some(first_comment_id),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.comments.len(), 1);
+  assert_eq!(page.comments[0].content, "reply");
+  assert!(page.comments[0].replies.is_empty());
+
+  let err = commenter_client
+    .query_published_view_comments(
+      &view_id,
+      &QueryGlobalComments {
+        cursor: Some("not a cursor".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
 #[tokio::test]
 async fn test_publish_reactions() {
   let (page_owner_client, _) = generate_unique_registered_user_client().await;
@@ -843,31 +1287,303 @@ async fn test_publish_load_test() {
   c.publish_collabs(&workspace_id, collabs).await.unwrap();
 }
 
-async fn get_first_workspace_string(c: &client_api::Client) -> String {
-  c.get_workspaces()
-    .await
-    .unwrap()
-    .first()
-    .unwrap()
-    .workspace_id
-    .to_string()
-}
-
 #[tokio::test]
-async fn workspace_member_publish_unpublish() {
-  let client_1 = TestClient::new_user_without_ws_conn().await;
-  let workspace_id = client_1.workspace_id().await;
-  let client_2 = TestClient::new_user_without_ws_conn().await;
-  client_1
-    .invite_and_accepted_workspace_member(&workspace_id, &client_2, AFRole::Member)
+async fn test_publish_sanitize_metadata() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
     .await
     .unwrap();
 
-  let view_id = uuid::Uuid::new_v4();
-  // member can publish without owner setting namespace
-  client_2
-    .api_client
-    .publish_collabs::<MyCustomMetadata, &[u8]>(
+  let publish_name = "sanitized";
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: uuid::Uuid::new_v4(),
+        publish_name: publish_name.to_string(),
+        metadata: serde_json::json!({
+          "title": "my_title",
+          "cover": " JavaScript:alert(1)",
+          "icon": { "value": "data:text/html,<script>alert(1)</script>" },
+          "image": "data:image/png;base64,iVBORw0KGgo=",
+        }),
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  let metadata = localhost_client()
+    .get_published_collab::<serde_json::Value>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(metadata["title"], "my_title");
+  assert_eq!(metadata["cover"], "");
+  assert_eq!(metadata["icon"]["value"], "");
+  assert_eq!(metadata["image"], "data:image/png;base64,iVBORw0KGgo=");
+}
+
+#[tokio::test]
+async fn test_publish_versions_rollback() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "versioned";
+  for data in ["version_1", "version_2", "version_3"] {
+    c.publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: publish_name.to_string(),
+          metadata: MyCustomMetadata {
+            title: data.to_string(),
+          },
+        },
+        data: data.as_bytes(),
+      }],
+    )
+    .await
+    .unwrap();
+  }
+
+  let versions = c
+    .list_published_view_versions(&workspace_id, &view_id)
+    .await
+    .unwrap();
+  assert_eq!(
+    versions
+      .iter()
+      .map(|version| version.version)
+      .collect::<Vec<_>>(),
+    vec![3, 2, 1]
+  );
+  assert!(versions[0].is_current);
+  assert!(versions[1..].iter().all(|version| !version.is_current));
+
+  // Rolling back serves the prior version again, as a new version.
+  let versions = c
+    .rollback_published_view(&workspace_id, &view_id, 1)
+    .await
+    .unwrap();
+  assert_eq!(versions.len(), 4);
+  assert_eq!(versions[0].version, 4);
+  assert!(versions[0].is_current);
+  assert_eq!(versions[0].restored_from_version, Some(1));
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(blob, "version_1");
+  let metadata = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(metadata.title, "version_1");
+
+  // Only the prior versions can be restored.
+  let err = c
+    .rollback_published_view(&workspace_id, &view_id, 4)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  // Only the publishers and the owners can see the versions.
+  let (other_client, _) = generate_unique_registered_user_client().await;
+  let err = other_client
+    .list_published_view_versions(&workspace_id, &view_id)
+    .await
+    .unwrap_err();
+  assert_ne!(err.code, ErrorCode::Ok);
+
+  // The versions are removed along with the published view.
+  c.unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+  let err = c
+    .list_published_view_versions(&workspace_id, &view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn test_published_outline_polling() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, namespace.clone())
+    .await
+    .unwrap();
+  let folder = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general_space = folder
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap();
+  let view_ids: Vec<uuid::Uuid> = general_space
+    .children
+    .iter()
+    .take(2)
+    .map(|v| v.view_id.parse().unwrap())
+    .collect();
+  let publish = |view_id: uuid::Uuid| {
+    c.publish_collabs::<MyCustomMetadata, &[u8]>(
+      &workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: view_id.to_string(),
+          metadata: MyCustomMetadata {
+            title: "title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+  };
+  for view_id in &view_ids {
+    publish(*view_id).await.unwrap();
+  }
+
+  let guest_client = localhost_client();
+  let outline = guest_client
+    .get_published_outline(&namespace)
+    .await
+    .unwrap();
+  let space = outline
+    .children
+    .iter()
+    .find(|v| v.view_id == general_space.view_id)
+    .unwrap();
+  assert_eq!(space.children.len(), 2);
+
+  // The unchanged outline is not sent again.
+  let url = format!(
+    "{}/api/workspace/published-outline/{}",
+    guest_client.base_url, namespace
+  );
+  let http_client = reqwest::Client::new();
+  let resp = http_client.get(&url).send().await.unwrap();
+  assert_eq!(resp.status(), reqwest::StatusCode::OK);
+  let etag = resp.headers()[reqwest::header::ETAG]
+    .to_str()
+    .unwrap()
+    .to_string();
+  assert!(resp.headers().contains_key(reqwest::header::CACHE_CONTROL));
+  let resp = http_client
+    .get(&url)
+    .header(reqwest::header::IF_NONE_MATCH, &etag)
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), reqwest::StatusCode::NOT_MODIFIED);
+
+  // Only the changed views are returned since the cursor.
+  let changes = guest_client
+    .get_published_outline_changes(&namespace, 0)
+    .await
+    .unwrap();
+  assert!(changes
+    .changed_views
+    .iter()
+    .any(|change| change.view.view_id == view_ids[0].to_string()
+      && change.parent_view_id == general_space.view_id));
+  let cursor = changes.cursor;
+  sleep(Duration::from_millis(10));
+  let changes = guest_client
+    .get_published_outline_changes(&namespace, cursor)
+    .await
+    .unwrap();
+  assert!(changes.changed_views.is_empty());
+  assert!(changes.view_ids.contains(&view_ids[1].to_string()));
+
+  publish(view_ids[1]).await.unwrap();
+  let changes = guest_client
+    .get_published_outline_changes(&namespace, cursor)
+    .await
+    .unwrap();
+  assert_eq!(changes.changed_views.len(), 1);
+  assert_eq!(
+    changes.changed_views[0].view.view_id,
+    view_ids[1].to_string()
+  );
+  assert!(changes.changed_views[0].view.children.is_empty());
+}
+
+#[tokio::test]
+async fn test_publish_embed_allowlist() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let allowlist = c.get_publish_embed_allowlist(&workspace_id).await.unwrap();
+  assert!(allowlist.hosts.is_empty());
+
+  let allowlist = c
+    .set_publish_embed_allowlist(
+      &workspace_id,
+      vec![
+        "Loom.com".to_string(),
+        "loom.com".to_string(),
+        "miro.com".to_string(),
+      ],
+    )
+    .await
+    .unwrap();
+  assert_eq!(allowlist.hosts, vec!["loom.com", "miro.com"]);
+
+  // The allowlist follows the workspace to its new namespace.
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+  let allowlist = c.get_publish_embed_allowlist(&workspace_id).await.unwrap();
+  assert_eq!(allowlist.namespace, my_namespace);
+  assert_eq!(allowlist.hosts, vec!["loom.com", "miro.com"]);
+
+  let err = c
+    .set_publish_embed_allowlist(&workspace_id, vec!["https://evil.com/".to_string()])
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+async fn get_first_workspace_string(c: &client_api::Client) -> String {
+  c.get_workspaces()
+    .await
+    .unwrap()
+    .first()
+    .unwrap()
+    .workspace_id
+    .to_string()
+}
+
+#[tokio::test]
+async fn workspace_member_publish_unpublish() {
+  let client_1 = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = client_1.workspace_id().await;
+  let client_2 = TestClient::new_user_without_ws_conn().await;
+  client_1
+    .invite_and_accepted_workspace_member(&workspace_id, &client_2, AFRole::Member)
+    .await
+    .unwrap();
+
+  let view_id = uuid::Uuid::new_v4();
+  // member can publish without owner setting namespace
+  client_2
+    .api_client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
       &workspace_id,
       vec![PublishCollabItem {
         meta: PublishCollabMetadata {
@@ -890,6 +1606,133 @@ async fn workspace_member_publish_unpublish() {
     .unwrap();
 }
 
+#[tokio::test]
+async fn workspace_restricted_publishing() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_settings(
+      &workspace_id,
+      &AFWorkspaceSettingsChange::new().restrict_publishing(true),
+    )
+    .await
+    .unwrap();
+
+  // the member can't publish nor change the namespace without the publish permission
+  let err = publish_view(
+    &member,
+    &workspace_id,
+    uuid::Uuid::new_v4(),
+    "publish-name-1",
+  )
+  .await
+  .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // only the owners can grant the permission
+  let err = member
+    .api_client
+    .grant_workspace_publisher(&workspace_id, &member.email().await)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  owner
+    .api_client
+    .grant_workspace_publisher(&workspace_id, &member.email().await)
+    .await
+    .unwrap();
+  let publishers = owner
+    .api_client
+    .get_workspace_publishers(&workspace_id)
+    .await
+    .unwrap()
+    .publishers;
+  assert_eq!(publishers.len(), 1);
+  assert_eq!(publishers[0].email, member.email().await);
+
+  // the publisher can publish, manage the namespace and the views published by the owner
+  let view_id = uuid::Uuid::new_v4();
+  publish_view(&member, &workspace_id, view_id, "publish-name-1")
+    .await
+    .unwrap();
+  member
+    .api_client
+    .set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  member
+    .api_client
+    .set_default_publish_view(&workspace_id, view_id)
+    .await
+    .unwrap();
+  let owner_view_id = uuid::Uuid::new_v4();
+  publish_view(&owner, &workspace_id, owner_view_id, "publish-name-2")
+    .await
+    .unwrap();
+  member
+    .api_client
+    .unpublish_collabs(&workspace_id, &[owner_view_id])
+    .await
+    .unwrap();
+
+  // once revoked, the member can only unpublish the views they published
+  owner
+    .api_client
+    .revoke_workspace_publisher(&workspace_id, &member.email().await)
+    .await
+    .unwrap();
+  let err = publish_view(
+    &member,
+    &workspace_id,
+    uuid::Uuid::new_v4(),
+    "publish-name-3",
+  )
+  .await
+  .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  member
+    .api_client
+    .unpublish_collabs(&workspace_id, &[view_id])
+    .await
+    .unwrap();
+}
+
+async fn publish_view(
+  client: &TestClient,
+  workspace_id: &str,
+  view_id: uuid::Uuid,
+  publish_name: &str,
+) -> Result<(), AppResponseError> {
+  client
+    .api_client
+    .publish_collabs::<MyCustomMetadata, &[u8]>(
+      workspace_id,
+      vec![PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: publish_name.to_string(),
+          metadata: MyCustomMetadata {
+            title: "my_title".to_string(),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      }],
+    )
+    .await
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct MyCustomMetadata {
   title: String,
@@ -1584,6 +2427,337 @@ async fn duplicate_to_workspace_inline_db_doc_with_relation() {
   }
 }
 
+#[tokio::test]
+async fn test_published_database_rows() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, namespace.clone())
+    .await
+    .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let todos = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "To-dos")
+    .unwrap();
+  let publish_name = "todos";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: todos.view_id.parse().unwrap(),
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "To-dos".to_string(),
+        },
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  // the rows are read from the live database, without signing in
+  let guest_client = localhost_client();
+  let rows = guest_client
+    .query_published_database_rows(&namespace, publish_name, &Default::default())
+    .await
+    .unwrap();
+  assert_eq!(rows.view_id, todos.view_id);
+  assert_eq!(rows.total_count, 5);
+  assert_eq!(rows.row_ids.len(), 5);
+  assert_eq!(rows.database_row_collabs.len(), 5);
+
+  let page = guest_client
+    .query_published_database_rows(
+      &namespace,
+      publish_name,
+      &QueryPublishedDatabaseRows {
+        offset: Some(4),
+        limit: Some(2),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(page.total_count, 5);
+  assert_eq!(page.row_ids, rows.row_ids[4..].to_vec());
+
+  // the filters are evaluated by the server
+  let filtered = guest_client
+    .query_published_database_rows(
+      &namespace,
+      publish_name,
+      &QueryPublishedDatabaseRows {
+        filters: vec![PublishedDatabaseFilter {
+          field_id: "unknown_field".to_string(),
+          condition: PublishedDatabaseFilterCondition::IsNotEmpty,
+          content: "".to_string(),
+        }],
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(filtered.total_count, 0);
+  assert!(filtered.row_ids.is_empty());
+}
+
+#[tokio::test]
+async fn test_auto_publish() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  c.set_workspace_publish_namespace(&workspace_id, uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let document = folder_view
+    .children
+    .iter()
+    .flat_map(|space| space.children.iter())
+    .find(|v| v.layout == ViewLayout::Document)
+    .unwrap();
+  let view_id: uuid::Uuid = document.view_id.parse().unwrap();
+  let not_in_folder_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "auto-published".to_string(),
+          metadata: MyCustomMetadata {
+            title: document.name.clone(),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      },
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: not_in_folder_view_id,
+          publish_name: "not-in-folder".to_string(),
+          metadata: MyCustomMetadata {
+            title: "not in folder".to_string(),
+          },
+        },
+        data: "yrs_encoded_data".as_bytes(),
+      },
+    ],
+  )
+  .await
+  .unwrap();
+
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert!(!info.auto_publish);
+  assert!(info.last_published_at.is_some());
+
+  let info = c
+    .set_auto_publish(&workspace_id, &view_id, true)
+    .await
+    .unwrap();
+  assert!(info.auto_publish);
+  assert!(!info.auto_publish_pending);
+  assert!(info.auto_publish_error.is_none());
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert!(info.auto_publish);
+
+  // only the documents of the folder can be republished from their content
+  let err = c
+    .set_auto_publish(&workspace_id, &not_in_folder_view_id, true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // the views that are not published can't be auto-published
+  let err = c
+    .set_auto_publish(&workspace_id, &uuid::Uuid::new_v4(), true)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  let info = c
+    .set_auto_publish(&workspace_id, &view_id, false)
+    .await
+    .unwrap();
+  assert!(!info.auto_publish);
+}
+
+#[tokio::test]
+async fn test_publish_sub_namespace() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+  let handbook_view_id = uuid::Uuid::new_v4();
+  let blog_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: handbook_view_id,
+          publish_name: "handbook-home".to_string(),
+          metadata: MyCustomMetadata {
+            title: "handbook".to_string(),
+          },
+        },
+        data: "handbook_data".as_bytes(),
+      },
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: blog_view_id,
+          publish_name: "blog-post".to_string(),
+          metadata: MyCustomMetadata {
+            title: "blog".to_string(),
+          },
+        },
+        data: "blog_data".as_bytes(),
+      },
+    ],
+  )
+  .await
+  .unwrap();
+
+  let handbook = c
+    .create_publish_sub_namespace(
+      &workspace_id,
+      &CreatePublishSubNamespace {
+        sub_namespace: "handbook".to_string(),
+        title: "Handbook".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  let handbook_namespace = format!("{}/handbook", my_namespace);
+  assert_eq!(handbook.namespace, handbook_namespace);
+  assert!(handbook.view_ids.is_empty());
+
+  let err = c
+    .create_publish_sub_namespace(
+      &workspace_id,
+      &CreatePublishSubNamespace {
+        sub_namespace: "handbook".to_string(),
+        title: "".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishNamespaceAlreadyTaken);
+  let err = c
+    .create_publish_sub_namespace(
+      &workspace_id,
+      &CreatePublishSubNamespace {
+        sub_namespace: "hand/book".to_string(),
+        title: "".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::CustomNamespaceInvalidCharacter);
+
+  let handbook = c
+    .add_publish_sub_namespace_views(&workspace_id, "handbook", vec![handbook_view_id])
+    .await
+    .unwrap();
+  assert_eq!(handbook.view_ids, vec![handbook_view_id]);
+  let info = c
+    .get_published_collab_info(&handbook_view_id)
+    .await
+    .unwrap();
+  assert_eq!(info.namespace, handbook_namespace);
+  assert_eq!(info.sub_namespace.as_deref(), Some("handbook"));
+
+  // each sub-namespace is a distinct site
+  let guest_client = localhost_client();
+  let blob = guest_client
+    .get_published_collab_blob(&handbook_namespace, "handbook-home")
+    .await
+    .unwrap();
+  assert_eq!(blob, "handbook_data".as_bytes());
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, "handbook-home")
+    .await
+    .unwrap_err();
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&handbook_namespace, "blog-post")
+    .await
+    .unwrap_err();
+  let metadata = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, "blog-post")
+    .await
+    .unwrap();
+  assert_eq!(metadata.title, "blog");
+
+  // the default view must be published in the sub-namespace
+  let err = c
+    .update_publish_sub_namespace(
+      &workspace_id,
+      "handbook",
+      &UpdatePublishSubNamespace {
+        title: "Handbook".to_string(),
+        default_view_id: Some(blog_view_id),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let handbook = c
+    .update_publish_sub_namespace(
+      &workspace_id,
+      "handbook",
+      &UpdatePublishSubNamespace {
+        title: "Company handbook".to_string(),
+        default_view_id: Some(handbook_view_id),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(handbook.title, "Company handbook");
+  let default = guest_client
+    .get_default_published_collab::<MyCustomMetadata>(&handbook_namespace)
+    .await
+    .unwrap();
+  assert_eq!(default.info.view_id, handbook_view_id);
+  assert_eq!(default.meta.title, "handbook");
+
+  let sub_namespaces = c.list_publish_sub_namespaces(&workspace_id).await.unwrap();
+  assert_eq!(sub_namespaces.len(), 1);
+  assert_eq!(sub_namespaces[0].default_view_id, Some(handbook_view_id));
+
+  // the views of a deleted sub-namespace are served by the namespace of the workspace
+  c.delete_publish_sub_namespace(&workspace_id, "handbook")
+    .await
+    .unwrap();
+  let info = c
+    .get_published_collab_info(&handbook_view_id)
+    .await
+    .unwrap();
+  assert_eq!(info.namespace, my_namespace);
+  assert!(info.sub_namespace.is_none());
+  guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, "handbook-home")
+    .await
+    .unwrap();
+  assert!(c
+    .list_publish_sub_namespaces(&workspace_id)
+    .await
+    .unwrap()
+    .is_empty());
+}
+
 fn get_database_id_and_row_ids(published_db_blob: &[u8]) -> (String, HashSet<String>) {
   let pub_db_data = serde_json::from_slice::<PublishDatabaseData>(published_db_blob).unwrap();
   let db_collab = collab_from_doc_state(pub_db_data.database_collab, "").unwrap();
@@ -1591,3 +2765,258 @@ fn get_database_id_and_row_ids(published_db_blob: &[u8]) -> (String, HashSet<Str
   let row_ids: HashSet<String> = pub_db_data.database_row_collabs.into_keys().collect();
   (pub_db_id, row_ids)
 }
+
+#[tokio::test]
+async fn test_publish_expiry() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+  let view_id = uuid::Uuid::new_v4();
+  let publish_name = "expiring-page";
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id,
+        publish_name: publish_name.to_string(),
+        metadata: MyCustomMetadata {
+          title: "expiring".to_string(),
+        },
+      },
+      data: "expiring_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  // The expiry must be in the future.
+  let err = c
+    .patch_published_collabs(
+      &workspace_id,
+      &[PatchPublishedCollab {
+        view_id,
+        publish_name: None,
+        expires_at: Some(Some(chrono::Utc::now() - chrono::Duration::minutes(1))),
+      }],
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest, "{:?}", err);
+
+  let expires_at = chrono::Utc::now() + chrono::Duration::seconds(2);
+  c.patch_published_collabs(
+    &workspace_id,
+    &[PatchPublishedCollab {
+      view_id,
+      publish_name: None,
+      expires_at: Some(Some(expires_at)),
+    }],
+  )
+  .await
+  .unwrap();
+  let info = c.get_published_collab_info(&view_id).await.unwrap();
+  assert_eq!(
+    info.expires_at.map(|at| at.timestamp()),
+    Some(expires_at.timestamp())
+  );
+
+  let guest_client = localhost_client();
+  guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap();
+
+  tokio::time::sleep(Duration::from_secs(3)).await;
+  let err = guest_client
+    .get_published_collab::<MyCustomMetadata>(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewExpired, "{:?}", err);
+  let err = guest_client
+    .get_published_collab_blob(&my_namespace, publish_name)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::PublishedViewExpired, "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_published_sitemap() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let getting_started = folder_view
+    .children
+    .iter()
+    .find(|v| v.name == "General")
+    .unwrap()
+    .children
+    .iter()
+    .find(|v| v.name == "Getting started")
+    .unwrap();
+  let view_id = uuid::Uuid::parse_str(&getting_started.view_id).unwrap();
+  // The views that aren't in the folder are not part of the outline, so they're left out.
+  let orphan_view_id = uuid::Uuid::new_v4();
+  c.publish_collabs::<MyCustomMetadata, &[u8]>(
+    &workspace_id,
+    vec![
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id,
+          publish_name: "getting-started".to_string(),
+          metadata: MyCustomMetadata {
+            title: "Getting started".to_string(),
+          },
+        },
+        data: "getting_started_data".as_bytes(),
+      },
+      PublishCollabItem {
+        meta: PublishCollabMetadata {
+          view_id: orphan_view_id,
+          publish_name: "orphan".to_string(),
+          metadata: MyCustomMetadata {
+            title: "orphan".to_string(),
+          },
+        },
+        data: "orphan_data".as_bytes(),
+      },
+    ],
+  )
+  .await
+  .unwrap();
+
+  let sitemap = localhost_client()
+    .get_published_sitemap(&my_namespace)
+    .await
+    .unwrap();
+  assert!(sitemap.starts_with("<?xml"));
+  assert_eq!(sitemap.matches("<url>").count(), 1, "{}", sitemap);
+  assert!(
+    sitemap.contains(&format!("/{}/getting-started</loc>", my_namespace)),
+    "{}",
+    sitemap
+  );
+  assert!(sitemap.contains("<lastmod>"));
+  assert!(!sitemap.contains("orphan"));
+}
+
+#[tokio::test]
+async fn test_published_view_seo() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = get_first_workspace_string(&c).await;
+  let my_namespace = uuid::Uuid::new_v4().to_string();
+  c.set_workspace_publish_namespace(&workspace_id, my_namespace.clone())
+    .await
+    .unwrap();
+
+  let publish_name = "seo";
+  let cover = serde_json::json!({
+    "cover": { "type": "custom", "value": "https://example.com/cover.png" },
+  });
+  c.publish_collabs::<serde_json::Value, &[u8]>(
+    &workspace_id,
+    vec![PublishCollabItem {
+      meta: PublishCollabMetadata {
+        view_id: uuid::Uuid::new_v4(),
+        publish_name: publish_name.to_string(),
+        metadata: serde_json::json!({
+          "view": { "name": "My page", "extra": cover.to_string() },
+        }),
+      },
+      data: "yrs_encoded_data".as_bytes(),
+    }],
+  )
+  .await
+  .unwrap();
+
+  // The SEO metadata is derived from the published metadata.
+  let seo = localhost_client()
+    .get_published_view_seo(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(seo.title, "My page");
+  assert_eq!(seo.description, None);
+  assert_eq!(
+    seo.image_url.as_deref(),
+    Some("https://example.com/cover.png")
+  );
+  assert!(seo
+    .canonical_url
+    .ends_with(&format!("/{}/{}", my_namespace, publish_name)));
+
+  // Other users can't override it.
+  let (c2, _user2) = generate_unique_registered_user_client().await;
+  let err = c2
+    .patch_published_view_seo(
+      &my_namespace,
+      publish_name,
+      &PatchPublishedViewSeo {
+        title: Some(Some("Not mine".to_string())),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  c.patch_published_view_seo(
+    &my_namespace,
+    publish_name,
+    &PatchPublishedViewSeo {
+      title: Some(Some("My SEO title".to_string())),
+      description: Some(Some("My SEO description".to_string())),
+      image_url: None,
+    },
+  )
+  .await
+  .unwrap();
+  let seo = localhost_client()
+    .get_published_view_seo(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(seo.title, "My SEO title");
+  assert_eq!(seo.description.as_deref(), Some("My SEO description"));
+  assert_eq!(
+    seo.image_url.as_deref(),
+    Some("https://example.com/cover.png")
+  );
+
+  // `null` removes the override, and the invalid image urls are rejected.
+  c.patch_published_view_seo(
+    &my_namespace,
+    publish_name,
+    &PatchPublishedViewSeo {
+      title: Some(None),
+      ..Default::default()
+    },
+  )
+  .await
+  .unwrap();
+  let err = c
+    .patch_published_view_seo(
+      &my_namespace,
+      publish_name,
+      &PatchPublishedViewSeo {
+        image_url: Some(Some("javascript:alert(1)".to_string())),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let seo = localhost_client()
+    .get_published_view_seo(&my_namespace, publish_name)
+    .await
+    .unwrap();
+  assert_eq!(seo.title, "My page");
+  assert_eq!(seo.description.as_deref(), Some("My SEO description"));
+}
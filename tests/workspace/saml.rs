@@ -0,0 +1,254 @@
+use app_error::ErrorCode;
+use client_api_test::TestClient;
+use database_entity::dto::AFRole;
+use reqwest::StatusCode;
+use shared_entity::dto::workspace_dto::{
+  AddWorkspaceDomainParams, UpdateWorkspaceSamlConfigParams,
+};
+use shared_entity::response::AppResponse;
+
+const IDP_METADATA_XML: &str = include_str!("asset/saml_idp_metadata.xml");
+
+#[tokio::test]
+async fn owner_configures_saml_identity_provider() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let err = owner
+    .api_client
+    .get_workspace_saml_config(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  let params = UpdateWorkspaceSamlConfigParams {
+    idp_metadata_xml: IDP_METADATA_XML.to_string(),
+    email_attribute: None,
+    enabled: false,
+  };
+  let err = member
+    .api_client
+    .update_workspace_saml_config(&workspace_id, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let err = owner
+    .api_client
+    .update_workspace_saml_config(
+      &workspace_id,
+      &UpdateWorkspaceSamlConfigParams {
+        idp_metadata_xml: "<md:EntityDescriptor".to_string(),
+        email_attribute: None,
+        enabled: true,
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let config = owner
+    .api_client
+    .update_workspace_saml_config(&workspace_id, &params)
+    .await
+    .unwrap();
+  assert!(!config.enabled);
+  assert!(config
+    .acs_url
+    .ends_with(&format!("/api/sso/saml/{}/acs", workspace_id)));
+
+  // the metadata is served while the sign in is disabled, to set the identity provider up
+  let http_client = reqwest::Client::builder()
+    .redirect(reqwest::redirect::Policy::none())
+    .build()
+    .unwrap();
+  let base_url = format!(
+    "{}/api/sso/saml/{}",
+    owner.api_client.base_url, workspace_id
+  );
+  let resp = http_client
+    .get(format!("{}/metadata", base_url))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let metadata = resp.text().await.unwrap();
+  assert!(metadata.contains(&config.acs_url));
+  assert!(metadata.contains(&config.sp_entity_id));
+
+  let resp = http_client
+    .get(format!("{}/login", base_url))
+    .send()
+    .await
+    .unwrap();
+  let err = AppResponse::<()>::from_response(resp)
+    .await
+    .unwrap()
+    .into_error()
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+
+  owner
+    .api_client
+    .update_workspace_saml_config(
+      &workspace_id,
+      &UpdateWorkspaceSamlConfigParams {
+        enabled: true,
+        ..params
+      },
+    )
+    .await
+    .unwrap();
+  let resp = http_client
+    .get(format!("{}/login", base_url))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::FOUND);
+  let location = resp
+    .headers()
+    .get(reqwest::header::LOCATION)
+    .unwrap()
+    .to_str()
+    .unwrap();
+  assert!(location.starts_with("https://idp.example.com/saml/sso?SAMLRequest="));
+
+  // the sign in can only redirect to the allowed URLs
+  let resp = http_client
+    .get(format!("{}/login", base_url))
+    .query(&[("redirect_to", "https://attacker.example.com/callback")])
+    .send()
+    .await
+    .unwrap();
+  let err = AppResponse::<()>::from_response(resp)
+    .await
+    .unwrap()
+    .into_error()
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  // an assertion that isn't in response to a sign in started by AppFlowy is rejected
+  let resp = http_client
+    .post(format!("{}/acs", base_url))
+    .form(&[
+      ("SAMLResponse", "PHNhbWxwOlJlc3BvbnNlLz4="),
+      ("RelayState", "id-unknown"),
+    ])
+    .send()
+    .await
+    .unwrap();
+  let err = AppResponse::<()>::from_response(resp)
+    .await
+    .unwrap()
+    .into_error()
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  // an assertion that isn't signed by the identity provider is rejected
+  let resp = http_client
+    .post(format!("{}/acs", base_url))
+    .form(&[("SAMLResponse", "PHNhbWxwOlJlc3BvbnNlLz4=")])
+    .send()
+    .await
+    .unwrap();
+  let err = AppResponse::<()>::from_response(resp)
+    .await
+    .unwrap()
+    .into_error()
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::UserUnAuthorized);
+
+  owner
+    .api_client
+    .delete_workspace_saml_config(&workspace_id)
+    .await
+    .unwrap();
+  let err = owner
+    .api_client
+    .get_workspace_saml_config(&workspace_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
+
+#[tokio::test]
+async fn owner_adds_and_verifies_workspace_domain() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+
+  let params = AddWorkspaceDomainParams {
+    domain: "Example.COM.".to_string(),
+  };
+  let err = member
+    .api_client
+    .add_workspace_domain(&workspace_id, &params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  let err = owner
+    .api_client
+    .add_workspace_domain(
+      &workspace_id,
+      &AddWorkspaceDomainParams {
+        domain: "not a domain".to_string(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let domain = owner
+    .api_client
+    .add_workspace_domain(&workspace_id, &params)
+    .await
+    .unwrap();
+  assert_eq!(domain.domain, "example.com");
+  assert_eq!(domain.txt_record_name, "_appflowy-challenge.example.com");
+  assert!(domain
+    .txt_record_value
+    .starts_with("appflowy-domain-verification="));
+  assert!(domain.verified_at.is_none());
+
+  // the DNS of the domain doesn't have the TXT record of the workspace
+  owner
+    .api_client
+    .verify_workspace_domain(&workspace_id, "example.com")
+    .await
+    .unwrap_err();
+  let domains = owner
+    .api_client
+    .list_workspace_domains(&workspace_id)
+    .await
+    .unwrap();
+  assert_eq!(domains.len(), 1);
+  assert!(domains[0].verified_at.is_none());
+
+  let err = member
+    .api_client
+    .delete_workspace_domain(&workspace_id, "example.com")
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  owner
+    .api_client
+    .delete_workspace_domain(&workspace_id, "example.com")
+    .await
+    .unwrap();
+  let domains = owner
+    .api_client
+    .list_workspace_domains(&workspace_id)
+    .await
+    .unwrap();
+  assert!(domains.is_empty());
+}
@@ -2,12 +2,16 @@ use std::collections::HashSet;
 
 use app_error::ErrorCode;
 use client_api::entity::{
-  AccountLink, CreateTemplateCategoryParams, CreateTemplateParams, PublishCollabItem,
-  PublishCollabMetadata, TemplateCategoryType, UpdateTemplateCategoryParams, UpdateTemplateParams,
+  AccountLink, CreateTemplateCategoryParams, CreateTemplateParams,
+  GetMarketplaceTemplatesQueryParams, InstallMarketplaceTemplateParams, PublishCollabItem,
+  PublishCollabMetadata, PublishMarketplaceTemplateParams, TemplateCategoryType, TemplateLicense,
+  UpdateTemplateCategoryParams, UpdateTemplateParams,
 };
 use client_api_test::*;
 use uuid::Uuid;
 
+use crate::workspace::published_data;
+
 async fn get_first_workspace_string(c: &client_api::Client) -> String {
   c.get_workspaces()
     .await
@@ -434,3 +438,149 @@ async fn test_template_crud() {
 
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TemplateMetadata {}
+
+#[tokio::test]
+async fn test_template_marketplace() {
+  let creator = TestClient::new_user().await;
+  let workspace_id = creator.workspace_id().await;
+  let doc_2_view_id = Uuid::new_v4();
+  let doc_1_view_id: Uuid = "e8c4f99a-50ea-4758-bca0-afa7df5c2434".parse().unwrap();
+  let grid_1_view_id: Uuid = "8e062f61-d7ae-4f4b-869c-f44c43149399".parse().unwrap();
+  creator
+    .publish_collabs(
+      &workspace_id,
+      vec![
+        (
+          doc_2_view_id,
+          published_data::DOC_2_META,
+          published_data::DOC_2_DOC_STATE_HEX,
+        ),
+        (
+          doc_1_view_id,
+          published_data::DOC_1_META,
+          published_data::DOC_1_DOC_STATE_HEX,
+        ),
+        (
+          grid_1_view_id,
+          published_data::GRID_1_META,
+          published_data::GRID_1_DB_DATA,
+        ),
+      ],
+    )
+    .await;
+
+  let category = creator
+    .api_client
+    .create_template_category(&CreateTemplateCategoryParams {
+      name: Uuid::new_v4().to_string(),
+      icon: "icon".to_string(),
+      bg_color: "bg_color".to_string(),
+      description: "description".to_string(),
+      category_type: TemplateCategoryType::UseCase,
+      priority: 1,
+    })
+    .await
+    .unwrap();
+  let template_name = Uuid::new_v4().to_string();
+  let mut params = PublishMarketplaceTemplateParams {
+    view_id: doc_2_view_id,
+    name: template_name.clone(),
+    description: "A template of the marketplace".to_string(),
+    license: TemplateLicense::Attribution,
+    screenshot_urls: vec!["http://example.com/screenshot.png".to_string()],
+    category_id: Some(category.id),
+  };
+  let err = creator
+    .api_client
+    .publish_marketplace_template(&params)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  params.screenshot_urls = vec!["https://example.com/screenshot.png".to_string()];
+  let template = creator
+    .api_client
+    .publish_marketplace_template(&params)
+    .await
+    .unwrap();
+  assert_eq!(template.template.name, template_name);
+  assert_eq!(template.template.license, TemplateLicense::Attribution);
+  assert_eq!(template.template.category.unwrap().id, category.id);
+  assert_eq!(template.template.install_count, 0);
+  assert_eq!(template.publish_info.view_id, doc_2_view_id);
+
+  // Only the owners of the workspace and the publishers of the view can list it.
+  let other_user = TestClient::new_user().await;
+  let err = other_user
+    .api_client
+    .publish_marketplace_template(&params)
+    .await
+    .unwrap_err();
+  assert_ne!(err.code, ErrorCode::Ok);
+
+  let guest_client = localhost_client();
+  let templates = guest_client
+    .get_marketplace_templates(&GetMarketplaceTemplatesQueryParams {
+      category_id: Some(category.id),
+      name_contains: Some(template_name.clone()),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  assert_eq!(templates.templates.len(), 1);
+  assert_eq!(templates.templates[0].template.view_id, doc_2_view_id);
+  assert_eq!(templates.next_offset, None);
+
+  let dest_workspace_id = other_user.workspace_id().await;
+  let folder = other_user
+    .api_client
+    .get_workspace_folder(&dest_workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  other_user
+    .api_client
+    .install_marketplace_template(
+      doc_2_view_id,
+      &InstallMarketplaceTemplateParams {
+        workspace_id: dest_workspace_id.parse().unwrap(),
+        dest_view_id: folder.view_id.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  let folder = other_user
+    .api_client
+    .get_workspace_folder(&dest_workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  assert!(folder.children.iter().any(|v| v.name == "doc2"));
+  let template = guest_client
+    .get_marketplace_template(doc_2_view_id)
+    .await
+    .unwrap();
+  assert_eq!(template.template.install_count, 1);
+
+  // Users can't install templates into the workspaces they are not a member of.
+  let err = other_user
+    .api_client
+    .install_marketplace_template(
+      doc_2_view_id,
+      &InstallMarketplaceTemplateParams {
+        workspace_id: workspace_id.parse().unwrap(),
+        dest_view_id: workspace_id.clone(),
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_ne!(err.code, ErrorCode::Ok);
+
+  creator
+    .api_client
+    .unpublish_marketplace_template(doc_2_view_id)
+    .await
+    .unwrap();
+  let err = guest_client
+    .get_marketplace_template(doc_2_view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+}
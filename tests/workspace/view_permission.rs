@@ -0,0 +1,398 @@
+use app_error::ErrorCode;
+use client_api::entity::QueryCollabParams;
+use client_api_test::{localhost_client, TestClient};
+use collab_entity::CollabType;
+use database_entity::dto::{
+  AFAccessLevel, AFRole, AFViewPermission, CreateMemberGroupParams, UpdateMemberGroupParams,
+  UpdateViewPermissionsParams, ViewPermissionGrantee,
+};
+use shared_entity::dto::workspace_dto::{
+  CreatePageParams, CreatePageShareLinkParams, FolderView, ViewLayout,
+};
+use uuid::Uuid;
+
+fn contains_view(folder: &FolderView, view_id: &str) -> bool {
+  folder.view_id == view_id
+    || folder
+      .children
+      .iter()
+      .any(|child| contains_view(child, view_id))
+}
+
+#[tokio::test]
+async fn restricted_view_is_only_accessible_to_its_grantees() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let folder = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let page = owner
+    .api_client
+    .create_workspace_page_view(
+      workspace_uuid,
+      &CreatePageParams {
+        parent_view_id: folder.children[0].view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("Salaries".to_string()),
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let query_page = || QueryCollabParams::new(&page.view_id, CollabType::Document, &workspace_id);
+  let member_folder = || async {
+    member
+      .api_client
+      .get_workspace_folder(&workspace_id, Some(5), None)
+      .await
+      .unwrap()
+  };
+  assert!(contains_view(&member_folder().await, &page.view_id));
+
+  // restricting the page to the owner hides it from the member
+  let group = owner
+    .api_client
+    .create_member_group(
+      &workspace_uuid,
+      &CreateMemberGroupParams {
+        name: "Finance".to_string(),
+        member_uids: vec![],
+      },
+    )
+    .await
+    .unwrap();
+  owner
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &page.view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![AFViewPermission {
+          grantee: ViewPermissionGrantee::Group {
+            group_id: group.group_id,
+          },
+          access_level: AFAccessLevel::ReadOnly,
+        }],
+      },
+    )
+    .await
+    .unwrap();
+  assert!(!contains_view(&member_folder().await, &page.view_id));
+  let err = member
+    .api_client
+    .get_collab(query_page())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  assert!(contains_view(
+    &owner
+      .api_client
+      .get_workspace_folder(&workspace_id, Some(5), None)
+      .await
+      .unwrap(),
+    &page.view_id
+  ));
+  owner.api_client.get_collab(query_page()).await.unwrap();
+
+  // the members of the group are granted the page
+  let group = owner
+    .api_client
+    .update_member_group(
+      &workspace_uuid,
+      &group.group_id,
+      &UpdateMemberGroupParams {
+        member_uids: vec![member.uid().await],
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!(group.member_uids, vec![member.uid().await]);
+  assert!(contains_view(&member_folder().await, &page.view_id));
+  member.api_client.get_collab(query_page()).await.unwrap();
+  let permissions = member
+    .api_client
+    .get_workspace_page_view_permissions(workspace_uuid, &page.view_id)
+    .await
+    .unwrap();
+  assert_eq!(permissions.permissions.len(), 1);
+
+  // only the owners can change the permissions
+  let err = member
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &page.view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+
+  // deleting the group leaves the page restricted to the owners
+  owner
+    .api_client
+    .delete_member_group(&workspace_uuid, &group.group_id)
+    .await
+    .unwrap();
+  assert!(!contains_view(&member_folder().await, &page.view_id));
+  assert!(owner
+    .api_client
+    .get_member_groups(&workspace_uuid)
+    .await
+    .unwrap()
+    .groups
+    .is_empty());
+
+  // lifting the restriction
+  owner
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &page.view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![],
+      },
+    )
+    .await
+    .unwrap();
+  assert!(contains_view(&member_folder().await, &page.view_id));
+  member.api_client.get_collab(query_page()).await.unwrap();
+}
+
+#[tokio::test]
+async fn view_permission_requires_workspace_member() {
+  let owner = TestClient::new_user().await;
+  let stranger = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let folder = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let err = owner
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &folder.children[0].view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![AFViewPermission {
+          grantee: ViewPermissionGrantee::Member {
+            uid: stranger.uid().await,
+          },
+          access_level: AFAccessLevel::ReadAndWrite,
+        }],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+
+  let err = owner
+    .api_client
+    .create_member_group(
+      &workspace_uuid,
+      &CreateMemberGroupParams {
+        name: "Outsiders".to_string(),
+        member_uids: vec![stranger.uid().await],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn restricted_view_cannot_be_shared_by_link() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let folder = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let page = owner
+    .api_client
+    .create_workspace_page_view(
+      workspace_uuid,
+      &CreatePageParams {
+        parent_view_id: folder.children[0].view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("Salaries".to_string()),
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = Uuid::parse_str(&page.view_id).unwrap();
+  let link = owner
+    .api_client
+    .create_workspace_page_share_link(
+      workspace_uuid,
+      view_id,
+      &CreatePageShareLinkParams::default(),
+    )
+    .await
+    .unwrap();
+
+  // the page is restricted to the owners
+  owner
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &page.view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![AFViewPermission {
+          grantee: ViewPermissionGrantee::Member {
+            uid: owner.uid().await,
+          },
+          access_level: AFAccessLevel::FullAccess,
+        }],
+      },
+    )
+    .await
+    .unwrap();
+  let err = member
+    .api_client
+    .create_workspace_page_share_link(
+      workspace_uuid,
+      view_id,
+      &CreatePageShareLinkParams::default(),
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .list_workspace_page_share_links(workspace_uuid, view_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+  let err = member
+    .api_client
+    .revoke_workspace_page_share_link(workspace_uuid, view_id, link.link_id)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn restricted_view_is_not_readable_through_share_links() {
+  let owner = TestClient::new_user().await;
+  let member = TestClient::new_user().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let workspace_uuid = Uuid::parse_str(&workspace_id).unwrap();
+  let folder = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(1), None)
+    .await
+    .unwrap();
+  let page = owner
+    .api_client
+    .create_workspace_page_view(
+      workspace_uuid,
+      &CreatePageParams {
+        parent_view_id: folder.children[0].view_id.clone(),
+        layout: ViewLayout::Document,
+        name: Some("Salaries".to_string()),
+        content: None,
+        template_id: None,
+      },
+    )
+    .await
+    .unwrap();
+  let view_id = Uuid::parse_str(&page.view_id).unwrap();
+  let query_page = QueryCollabParams::new(&page.view_id, CollabType::Document, &workspace_id);
+  let member_link = member
+    .api_client
+    .create_workspace_page_share_link(
+      workspace_uuid,
+      view_id,
+      &CreatePageShareLinkParams::default(),
+    )
+    .await
+    .unwrap();
+  let owner_link = owner
+    .api_client
+    .create_workspace_page_share_link(
+      workspace_uuid,
+      view_id,
+      &CreatePageShareLinkParams::default(),
+    )
+    .await
+    .unwrap();
+  let anonymous = localhost_client();
+  anonymous.get_shared_page(member_link.token).await.unwrap();
+
+  // once the page is restricted, the link of the member who lost the access stops working
+  owner
+    .api_client
+    .update_workspace_page_view_permissions(
+      workspace_uuid,
+      &page.view_id,
+      &UpdateViewPermissionsParams {
+        permissions: vec![AFViewPermission {
+          grantee: ViewPermissionGrantee::Member {
+            uid: owner.uid().await,
+          },
+          access_level: AFAccessLevel::FullAccess,
+        }],
+      },
+    )
+    .await
+    .unwrap();
+  let err = anonymous
+    .get_shared_page(member_link.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let err = anonymous
+    .get_collab_with_share_link(&query_page, &member_link.token)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  anonymous.get_shared_page(owner_link.token).await.unwrap();
+  anonymous
+    .get_collab_with_share_link(&query_page, &owner_link.token)
+    .await
+    .unwrap();
+
+  // nor is the page readable through the public access of the workspace
+  owner
+    .api_client
+    .set_workspace_public_access(&workspace_id, true)
+    .await
+    .unwrap();
+  let err = anonymous
+    .get_public_workspace_collab(&workspace_id, &page.view_id, CollabType::Document)
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::RecordNotFound);
+  let public_folder = anonymous
+    .get_public_workspace_folder(&workspace_id, Some(5), None)
+    .await
+    .unwrap();
+  assert!(!contains_view(&public_folder, &page.view_id));
+}
@@ -1,3 +1,4 @@
+use app_error::ErrorCode;
 use client_api_test::generate_unique_registered_user_client;
 use collab_entity::CollabType;
 use database_entity::dto::QueryCollabParams;
@@ -12,6 +13,7 @@ async fn add_and_delete_workspace_for_user() {
   let newly_added_workspace = c
     .create_workspace(CreateWorkspaceParam {
       workspace_name: Some("my_workspace".to_string()),
+      residency: None,
     })
     .await
     .unwrap();
@@ -114,3 +116,50 @@ async fn test_workspace_rename_and_icon_change() {
     assert_eq!(name, "new_name456");
   }
 }
+
+#[tokio::test]
+async fn create_workspace_with_unsupported_residency() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let err = c
+    .create_workspace(CreateWorkspaceParam {
+      workspace_name: Some("my_workspace".to_string()),
+      residency: Some("unknown-region".to_string()),
+    })
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::InvalidRequest);
+  let workspaces = c.get_workspaces().await.unwrap();
+  assert_eq!(workspaces.len(), 1);
+
+  let workspace_id = workspaces.first().unwrap().workspace_id.to_string();
+  let settings = c.get_workspace_settings(&workspace_id).await.unwrap();
+  assert_eq!(settings.residency, None);
+}
+
+#[tokio::test]
+async fn workspace_usage_breakdown() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspaces = c.get_workspaces().await.unwrap();
+  let workspace_id = workspaces.first().unwrap().workspace_id.to_string();
+  let uid = c.get_profile().await.unwrap().uid;
+
+  let usage = c
+    .get_workspace_usage_breakdown(&workspace_id)
+    .await
+    .unwrap();
+  let collab_bytes: i64 = usage.collab_usages.iter().map(|u| u.total_bytes).sum();
+  assert_eq!(collab_bytes, usage.total_document_size);
+  assert!(usage
+    .collab_usages
+    .iter()
+    .any(|u| u.collab_type == CollabType::Document && u.collab_count > 0));
+  assert!(usage
+    .collab_usages
+    .iter()
+    .any(|u| u.collab_type == CollabType::Folder && u.collab_count == 1));
+  assert_eq!(usage.blob_count, 0);
+  assert_eq!(usage.blob_storage_bytes, 0);
+  let member_usage = usage.member_usages.iter().find(|u| u.uid == uid).unwrap();
+  assert!(member_usage.collab_count > 0);
+  assert!(member_usage.total_bytes > 0);
+}
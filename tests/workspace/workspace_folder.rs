@@ -1,7 +1,10 @@
+use app_error::ErrorCode;
 use client_api::entity::{CreateCollabParams, QueryCollabParams};
-use client_api_test::generate_unique_registered_user_client;
+use client_api_test::{generate_unique_registered_user_client, TestClient};
 use collab::core::origin::CollabClient;
 use collab_folder::{CollabOrigin, Folder};
+use database_entity::dto::{AFAccessLevel, AFRole, AFWorkspaceInvitationStatus};
+use shared_entity::dto::workspace_dto::{FolderViewFilter, InviteWorkspaceGuest};
 
 #[tokio::test]
 async fn get_workpace_folder() {
@@ -35,6 +38,91 @@ async fn get_workpace_folder() {
   assert_eq!(folder_view.children.len(), 2);
 }
 
+#[tokio::test]
+async fn get_workspace_folder_with_filter() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general = &folder_view.children[0];
+  let matching_view = &general.children[0];
+
+  let filter = FolderViewFilter {
+    name_contains: Some(matching_view.name.to_uppercase()),
+    ..Default::default()
+  };
+  let filtered_view = c
+    .get_workspace_folder_with_filter(&workspace_id, Some(2), None, &filter)
+    .await
+    .unwrap();
+  assert_eq!(filtered_view.children.len(), 1);
+  assert_eq!(filtered_view.children[0].view_id, general.view_id);
+  assert!(filtered_view.children[0]
+    .children
+    .iter()
+    .all(|child| child.name.contains(&matching_view.name)));
+  assert!(filtered_view.children[0]
+    .children
+    .iter()
+    .any(|child| child.view_id == matching_view.view_id));
+
+  let filter = FolderViewFilter {
+    name_contains: Some(uuid::Uuid::new_v4().to_string()),
+    ..Default::default()
+  };
+  let filtered_view = c
+    .get_workspace_folder_with_filter(&workspace_id, Some(2), None, &filter)
+    .await
+    .unwrap();
+  assert!(filtered_view.children.is_empty());
+}
+
+#[tokio::test]
+async fn get_workspace_folder_view_metadata() {
+  let (c, _user) = generate_unique_registered_user_client().await;
+  let workspace_id = c.get_workspaces().await.unwrap()[0]
+    .workspace_id
+    .to_string();
+  let folder_view = c
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let general = &folder_view.children[0];
+
+  let view = c
+    .get_workspace_folder_view(&workspace_id, &general.view_id)
+    .await
+    .unwrap();
+  assert_eq!(view.name, "General");
+  assert_eq!(view.parent_view_id, workspace_id);
+  assert!(view.is_space);
+  assert_eq!(
+    view.children,
+    general
+      .children
+      .iter()
+      .map(|child| child.view_id.clone())
+      .collect::<Vec<_>>()
+  );
+
+  let child = c
+    .get_workspace_folder_view(&workspace_id, &view.children[0])
+    .await
+    .unwrap();
+  assert_eq!(child.name, general.children[0].name);
+  assert_eq!(child.parent_view_id, general.view_id);
+
+  let err = c
+    .get_workspace_folder_view(&workspace_id, &uuid::Uuid::new_v4().to_string())
+    .await
+    .unwrap_err();
+  assert_eq!(err.code, ErrorCode::MissingView);
+}
+
 #[tokio::test]
 async fn get_section_items() {
   let (c, _user) = generate_unique_registered_user_client().await;
@@ -99,3 +187,209 @@ async fn get_section_items() {
   assert_eq!(recent_section_items.views.len(), 1);
   assert_eq!(recent_section_items.views[0].view.view_id, recent_id);
 }
+
+#[tokio::test]
+async fn guest_only_see_granted_views() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let granted_view_id = folder_view.children[0].children[0].view_id.clone();
+
+  // the guest has not been granted any view yet
+  let guest_folder_view = guest
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  assert!(guest_folder_view.children.is_empty());
+
+  owner
+    .add_collab_member(
+      &workspace_id,
+      &granted_view_id,
+      &guest,
+      AFAccessLevel::ReadOnly,
+    )
+    .await;
+  let guest_folder_view = guest
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  assert_eq!(guest_folder_view.children.len(), 1);
+  assert_eq!(guest_folder_view.children[0].view_id, granted_view_id);
+
+  // the guest is not allowed to read the whole folder
+  let error = guest
+    .api_client
+    .get_collab(QueryCollabParams::new(
+      workspace_id.clone(),
+      collab_entity::CollabType::Folder,
+      workspace_id.clone(),
+    ))
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+
+  // only the owner can list the views granted to the guest
+  let guest_uid = guest.uid().await;
+  let guest_views = owner
+    .api_client
+    .get_workspace_guest_views(&workspace_id, guest_uid)
+    .await
+    .unwrap();
+  assert_eq!(guest_views.views.len(), 1);
+  assert_eq!(guest_views.views[0].view.view_id, granted_view_id);
+  assert_eq!(guest_views.views[0].access_level, AFAccessLevel::ReadOnly);
+  let error = guest
+    .api_client
+    .get_workspace_guest_views(&workspace_id, guest_uid)
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::NotEnoughPermissions);
+}
+
+#[tokio::test]
+async fn invite_guest_with_views() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let folder_view = owner
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let granted_view_id = folder_view.children[0].children[0].view_id.clone();
+
+  // views that don't belong to the workspace can't be granted
+  let error = owner
+    .api_client
+    .invite_workspace_guest(
+      &workspace_id,
+      &InviteWorkspaceGuest {
+        email: guest.email().await,
+        view_ids: vec![uuid::Uuid::new_v4().to_string()],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+
+  owner
+    .api_client
+    .invite_workspace_guest(
+      &workspace_id,
+      &InviteWorkspaceGuest {
+        email: guest.email().await,
+        view_ids: vec![granted_view_id.clone()],
+      },
+    )
+    .await
+    .unwrap();
+  let invitation = guest
+    .api_client
+    .list_workspace_invitations(Some(AFWorkspaceInvitationStatus::Pending))
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|inv| inv.workspace_id.to_string() == workspace_id)
+    .unwrap();
+  guest
+    .api_client
+    .accept_workspace_invitation(&invitation.invite_id.to_string())
+    .await
+    .unwrap();
+
+  // the granted view is the only view visible to the guest
+  let guest_folder_view = guest
+    .api_client
+    .get_workspace_folder(&workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  assert_eq!(guest_folder_view.children.len(), 1);
+  assert_eq!(guest_folder_view.children[0].view_id, granted_view_id);
+  let guest_views = owner
+    .api_client
+    .get_workspace_guest_views(&workspace_id, guest.uid().await)
+    .await
+    .unwrap();
+  assert_eq!(guest_views.views.len(), 1);
+  assert_eq!(guest_views.views[0].access_level, AFAccessLevel::ReadOnly);
+
+  // members can't be invited as guests
+  let member = TestClient::new_user_without_ws_conn().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &member, AFRole::Member)
+    .await
+    .unwrap();
+  let error = owner
+    .api_client
+    .invite_workspace_guest(
+      &workspace_id,
+      &InviteWorkspaceGuest {
+        email: member.email().await,
+        view_ids: vec![granted_view_id],
+      },
+    )
+    .await
+    .unwrap_err();
+  assert_eq!(error.code, ErrorCode::InvalidRequest);
+}
+
+#[tokio::test]
+async fn guest_views_granted_in_another_workspace_are_not_listed() {
+  let owner = TestClient::new_user_without_ws_conn().await;
+  let other_owner = TestClient::new_user_without_ws_conn().await;
+  let guest = TestClient::new_user_without_ws_conn().await;
+  let workspace_id = owner.workspace_id().await;
+  let other_workspace_id = other_owner.workspace_id().await;
+  owner
+    .invite_and_accepted_workspace_member(&workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+  other_owner
+    .invite_and_accepted_workspace_member(&other_workspace_id, &guest, AFRole::Guest)
+    .await
+    .unwrap();
+
+  let other_folder_view = other_owner
+    .api_client
+    .get_workspace_folder(&other_workspace_id, Some(2), None)
+    .await
+    .unwrap();
+  let granted_view_id = other_folder_view.children[0].children[0].view_id.clone();
+  other_owner
+    .add_collab_member(
+      &other_workspace_id,
+      &granted_view_id,
+      &guest,
+      AFAccessLevel::ReadOnly,
+    )
+    .await;
+
+  let guest_uid = guest.uid().await;
+  let guest_views = owner
+    .api_client
+    .get_workspace_guest_views(&workspace_id, guest_uid)
+    .await
+    .unwrap();
+  assert!(guest_views.views.is_empty());
+  let guest_views = other_owner
+    .api_client
+    .get_workspace_guest_views(&other_workspace_id, guest_uid)
+    .await
+    .unwrap();
+  assert_eq!(guest_views.views.len(), 1);
+  assert_eq!(guest_views.views[0].view.view_id, granted_view_id);
+  assert!(!guest_views.views[0].view.is_private);
+}